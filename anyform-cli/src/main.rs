@@ -64,6 +64,10 @@ enum Commands {
         #[arg(long)]
         quiz_only: bool,
 
+        /// Only seed the multi-step onboarding form
+        #[arg(long)]
+        onboarding_only: bool,
+
         /// Clear seeded forms instead of creating them
         #[arg(long)]
         clear: bool,
@@ -226,6 +230,7 @@ async fn main() -> Result<()> {
             contact_only,
             feedback_only,
             quiz_only,
+            onboarding_only,
             clear,
         } => {
             let db = connect(&database_url).await?;
@@ -246,10 +251,14 @@ async fn main() -> Result<()> {
                 println!("Seeding quiz form...");
                 anyform::seed_quiz_form(&db).await?;
                 println!("Done.");
+            } else if onboarding_only {
+                println!("Seeding onboarding form...");
+                anyform::seed_onboarding_form(&db).await?;
+                println!("Done.");
             } else {
                 println!("Seeding all example forms...");
                 anyform::seed_all(&db).await?;
-                println!("Done. Created: contact, feedback, quiz");
+                println!("Done. Created: contact, feedback, quiz, onboarding");
             }
         }
 
@@ -0,0 +1,135 @@
+//! Benchmarks cached condition evaluation in `FormState` against re-walking
+//! the schema on every check (the behavior before `ConditionGraph`).
+//!
+//! Run with `cargo bench -p anyform-client`.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use anyform_client::schema::{ConditionRule, FormJson, StepJson};
+use anyform_client::FormState;
+use serde_json::json;
+use uuid::Uuid;
+
+const STEPS: usize = 10;
+const FIELDS_PER_STEP: usize = 20;
+const ROUNDS: usize = 200;
+
+/// A 200-field form where every field after the first in a step depends on
+/// the step's first field, and every step after the first on the previous
+/// step's first field.
+fn large_form() -> FormJson {
+    let steps: Vec<_> = (0..STEPS)
+        .map(|s| {
+            let fields: Vec<_> = (0..FIELDS_PER_STEP)
+                .map(|f| {
+                    let mut field = json!({
+                        "id": Uuid::new_v4(),
+                        "name": format!("s{s}_f{f}"),
+                        "label": format!("Field {f}"),
+                        "field_type": "text",
+                        "order": f,
+                    });
+                    if f > 0 {
+                        field["condition"] =
+                            json!({ "field": format!("s{s}_f0"), "op": "is_not_empty", "value": null });
+                    }
+                    field
+                })
+                .collect();
+            let mut step = json!({
+                "id": Uuid::new_v4(),
+                "name": format!("Step {s}"),
+                "order": s,
+                "fields": fields,
+            });
+            if s > 0 {
+                step["condition"] =
+                    json!({ "field": format!("s{}_f0", s - 1), "op": "is_not_empty", "value": null });
+            }
+            step
+        })
+        .collect();
+
+    serde_json::from_value(json!({
+        "id": Uuid::new_v4(),
+        "name": "Large",
+        "slug": "large",
+        "steps": steps,
+    }))
+    .unwrap()
+}
+
+/// Field visibility as computed before caching: walk every step and field.
+fn is_field_visible_uncached(
+    steps: &[StepJson],
+    values: &HashMap<String, serde_json::Value>,
+    name: &str,
+) -> bool {
+    for step in steps {
+        if !holds(&step.condition, values) {
+            continue;
+        }
+        for field in &step.fields {
+            if field.name == name {
+                return holds(&field.condition, values);
+            }
+        }
+    }
+    false
+}
+
+fn holds(condition: &Option<ConditionRule>, values: &HashMap<String, serde_json::Value>) -> bool {
+    match condition {
+        None => true,
+        Some(rule) => rule.evaluate(values),
+    }
+}
+
+/// Simulates typing into each field in turn, re-checking every field's
+/// visibility after each keystroke (as the hydrated form does).
+fn run(mut check: impl FnMut(&str, usize) -> usize, names: &[String]) -> Duration {
+    let start = Instant::now();
+    for round in 0..ROUNDS {
+        let name = &names[round % names.len()];
+        black_box(check(name, round));
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let schema = large_form();
+    let names: Vec<String> = schema
+        .steps
+        .iter()
+        .flat_map(|s| s.fields.iter().map(|f| f.name.clone()))
+        .collect();
+
+    let mut values = HashMap::new();
+    let uncached = run(
+        |changed, round| {
+            values.insert(changed.to_string(), json!(format!("v{round}")));
+            names
+                .iter()
+                .filter(|n| is_field_visible_uncached(&schema.steps, &values, n))
+                .count()
+        },
+        &names,
+    );
+
+    let mut state = FormState::from_schema(schema.clone());
+    let cached = run(
+        |changed, round| {
+            state.set_json_value(changed, json!(format!("v{round}")));
+            names.iter().filter(|n| state.is_field_visible(n)).count()
+        },
+        &names,
+    );
+
+    println!(
+        "{} fields, {ROUNDS} changes: uncached {uncached:?}, cached {cached:?} ({:.1}x)",
+        names.len(),
+        uncached.as_secs_f64() / cached.as_secs_f64()
+    );
+}
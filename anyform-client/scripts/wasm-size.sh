@@ -0,0 +1,91 @@
+#!/bin/sh
+set -e
+
+# Measures the size of the anyform-client WASM bundle.
+#
+# Usage:
+#   scripts/wasm-size.sh measure [CRATE_DIR]      # prints "build raw gzip" lines
+#   scripts/wasm-size.sh compare BEFORE AFTER [MAX_GROWTH_BYTES]
+#
+# `measure` builds the default bundle, the minimal one for simple forms
+# (--no-default-features --features dom) and one with the regex crate (--features regex)
+# with size-optimized release settings, runs wasm-bindgen and, if installed,
+# wasm-opt -Oz. Builds a checkout doesn't support (e.g. an older base
+# branch without the feature) are skipped. Needs the wasm32-unknown-unknown target and
+# wasm-bindgen-cli matching the crate's wasm-bindgen version.
+#
+# `compare` prints a Markdown table of two `measure` outputs and fails if a
+# build's gzipped size grew by more than MAX_GROWTH_BYTES (default 2048).
+
+# Size-optimized release settings (also used by the release workflow)
+export CARGO_PROFILE_RELEASE_OPT_LEVEL=z
+export CARGO_PROFILE_RELEASE_LTO=true
+export CARGO_PROFILE_RELEASE_CODEGEN_UNITS=1
+export CARGO_PROFILE_RELEASE_STRIP=true
+
+measure() {
+  CRATE_DIR=$(cd "${1:-$(dirname "$0")/..}" && pwd)
+  OUT_DIR=$(mktemp -d)
+  trap 'rm -rf "$OUT_DIR"' EXIT
+
+  for BUILD in default minimal regex; do
+    case "$BUILD" in
+      default) FLAGS="" ;;
+      minimal) FLAGS="--no-default-features --features dom" ;;
+      regex) FLAGS="--features regex" ;;
+    esac
+
+    # shellcheck disable=SC2086
+    if ! (cd "$CRATE_DIR" && cargo build -q --release --target wasm32-unknown-unknown $FLAGS); then
+      echo "Skipping $BUILD build" >&2
+      continue
+    fi
+    TARGET_DIR=$(cd "$CRATE_DIR" && cargo metadata --format-version 1 --no-deps \
+      | sed -n 's/.*"target_directory":"\([^"]*\)".*/\1/p')
+
+    wasm-bindgen --target web --out-dir "$OUT_DIR/$BUILD" \
+      "$TARGET_DIR/wasm32-unknown-unknown/release/anyform_client.wasm"
+    WASM="$OUT_DIR/$BUILD/anyform_client_bg.wasm"
+    if command -v wasm-opt >/dev/null 2>&1; then
+      wasm-opt -Oz "$WASM" -o "$WASM"
+    fi
+
+    RAW=$(wc -c < "$WASM" | tr -d ' ')
+    GZIP=$(gzip -9c "$WASM" | wc -c | tr -d ' ')
+    echo "$BUILD $RAW $GZIP"
+  done
+}
+
+compare() {
+  BEFORE="$1"
+  AFTER="$2"
+  MAX_GROWTH="${3:-2048}"
+  STATUS=0
+
+  echo "| Build | Before (gzip) | After (gzip) | Change |"
+  echo "|---|---:|---:|---:|"
+  while read -r BUILD RAW GZIP; do
+    OLD_GZIP=$(awk -v b="$BUILD" '$1 == b { print $3 }' "$BEFORE")
+    if [ -z "$OLD_GZIP" ]; then
+      echo "| $BUILD | - | $GZIP ($RAW raw) | new |"
+      continue
+    fi
+    DIFF=$((GZIP - OLD_GZIP))
+    echo "| $BUILD | $OLD_GZIP | $GZIP ($RAW raw) | $DIFF |"
+    if [ "$DIFF" -gt "$MAX_GROWTH" ]; then
+      STATUS=1
+    fi
+  done < "$AFTER"
+
+  if [ "$STATUS" -ne 0 ]; then
+    echo
+    echo "Gzipped size grew by more than $MAX_GROWTH bytes."
+  fi
+  return "$STATUS"
+}
+
+case "$1" in
+  measure) shift; measure "$@" ;;
+  compare) shift; compare "$@" ;;
+  *) echo "Usage: $0 measure [CRATE_DIR] | compare BEFORE AFTER [MAX_GROWTH_BYTES]"; exit 1 ;;
+esac
@@ -1,12 +1,224 @@
 //! HTTP API client for anyform.
 //!
 //! Provides fetch wrappers for communicating with the anyform server.
+//! Every request takes [`FetchOptions`] (default headers, credentials mode,
+//! timeout and retries) and fails with a [`ClientError`] classified as a
+//! network, validation or server error.
 
-use crate::schema::FormJson;
+use crate::schema::{FormJson, StepJson};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{Request, RequestInit, RequestMode, Response};
+use web_sys::{AbortController, Request, RequestCredentials, RequestInit, RequestMode, Response};
+
+/// Longest wait between retries, in milliseconds.
+const MAX_RETRY_DELAY_MS: u32 = 30_000;
+
+/// Options applied to every request.
+///
+/// From JS, pass a plain object to `FormClient.with_options()`:
+///
+/// ```js
+/// const client = FormClient.with_options('https://forms.example.com', {
+///     headers: { Authorization: `Bearer ${token}` },
+///     credentials: 'include',
+///     timeout_ms: 10000,
+///     retries: 2,
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FetchOptions {
+    /// Headers sent with every request, e.g. `Authorization` for private forms.
+    pub headers: BTreeMap<String, String>,
+
+    /// Whether cookies are sent (`omit`, `same-origin` or `include`).
+    /// Defaults to the browser's default (`same-origin`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<Credentials>,
+
+    /// Aborts a request that takes longer than this.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u32>,
+
+    /// How many times a request is retried after a network failure.
+    pub retries: u32,
+
+    /// Wait before the first retry; doubled for each further retry.
+    pub retry_delay_ms: u32,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            headers: BTreeMap::new(),
+            credentials: None,
+            timeout_ms: None,
+            retries: 0,
+            retry_delay_ms: 250,
+        }
+    }
+}
+
+impl FetchOptions {
+    /// Returns how long to wait before retry number `attempt` (0-based).
+    pub fn retry_delay(&self, attempt: u32) -> u32 {
+        self.retry_delay_ms
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_RETRY_DELAY_MS)
+    }
+}
+
+/// Request credentials mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Credentials {
+    Omit,
+    SameOrigin,
+    Include,
+}
+
+impl From<Credentials> for RequestCredentials {
+    fn from(credentials: Credentials) -> Self {
+        match credentials {
+            Credentials::Omit => RequestCredentials::Omit,
+            Credentials::SameOrigin => RequestCredentials::SameOrigin,
+            Credentials::Include => RequestCredentials::Include,
+        }
+    }
+}
+
+/// What kind of failure a [`ClientError`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The request never got a response (offline, CORS, timeout).
+    Network,
+    /// The server rejected the data (HTTP 400 or 422).
+    Validation,
+    /// Any other error response, or a response that couldn't be read.
+    Server,
+}
+
+impl ErrorKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Network => "network",
+            Self::Validation => "validation",
+            Self::Server => "server",
+        }
+    }
+}
+
+/// Error returned by API calls.
+///
+/// Surfaced to JS as an `Error` named `AnyformError` with `kind`, `code`,
+/// `status`, `details` and `user_message` properties. `code` is the server's
+/// machine-readable code (e.g. `FORM_NOT_FOUND`) for error responses, or
+/// `NETWORK_ERROR`, `TIMEOUT` or `PARSE_ERROR` for client-side failures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientError {
+    pub kind: ErrorKind,
+    pub code: String,
+    pub message: String,
+    /// HTTP status, if a response was received.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ClientError {
+    /// Creates a network error.
+    pub fn network(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            kind: ErrorKind::Network,
+            code: code.to_string(),
+            message: message.into(),
+            status: None,
+            details: None,
+        }
+    }
+
+    /// Creates an error from an error response.
+    pub fn from_response(status: u16, error: Option<ApiError>) -> Self {
+        let error = error.unwrap_or_else(|| ApiError {
+            code: "UNKNOWN_ERROR".to_string(),
+            message: "Unknown error".to_string(),
+            details: None,
+        });
+        Self {
+            kind: match status {
+                400 | 422 => ErrorKind::Validation,
+                _ => ErrorKind::Server,
+            },
+            code: error.code,
+            message: error.message,
+            status: Some(status),
+            details: error.details,
+        }
+    }
+
+    /// Returns true if the request may succeed when sent again.
+    pub fn is_retryable(&self) -> bool {
+        self.kind == ErrorKind::Network && matches!(self.code.as_str(), "NETWORK_ERROR" | "TIMEOUT")
+    }
+
+    /// Returns a message to show respondents when the server can't take
+    /// submissions for a while (`READ_ONLY`, `DATABASE_BUSY`), or `None`.
+    pub fn user_message(&self) -> Option<&'static str> {
+        match self.code.as_str() {
+            "READ_ONLY" | "DATABASE_BUSY" => {
+                Some("This form is temporarily unavailable. Please try again in a few minutes.")
+            }
+            _ => None,
+        }
+    }
+
+    fn server(code: &str, message: impl Into<String>, status: u16) -> Self {
+        Self {
+            kind: ErrorKind::Server,
+            code: code.to_string(),
+            message: message.into(),
+            status: Some(status),
+            details: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+impl From<ClientError> for JsValue {
+    fn from(err: ClientError) -> Self {
+        let error = js_sys::Error::new(&err.message);
+        error.set_name("AnyformError");
+
+        let status = err.status.map_or(JsValue::NULL, JsValue::from);
+        let details = err
+            .details
+            .as_ref()
+            .and_then(|d| serde_wasm_bindgen::to_value(d).ok())
+            .unwrap_or(JsValue::NULL);
+        let user_message = err.user_message().map_or(JsValue::NULL, JsValue::from_str);
+        for (key, value) in [
+            ("kind", JsValue::from_str(err.kind.as_str())),
+            ("code", JsValue::from_str(&err.code)),
+            ("status", status),
+            ("details", details),
+            ("user_message", user_message),
+        ] {
+            let _ = js_sys::Reflect::set(&error, &JsValue::from_str(key), &value);
+        }
+
+        error.into()
+    }
+}
 
 /// API response envelope.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +263,20 @@ pub struct SubmissionResponse {
     pub result: Option<ResultInfo>,
 }
 
+/// An in-progress submission, answered when one is started or a step of it
+/// saved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionProgress {
+    pub submission_id: String,
+    pub status: String,
+    /// The step to fill in next.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_step_id: Option<String>,
+    /// Answers saved so far.
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
 /// Quiz result information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResultInfo {
@@ -60,78 +286,322 @@ pub struct ResultInfo {
     pub description: Option<String>,
 }
 
+/// Single-field validation response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldValidationResponse {
+    pub field: String,
+    pub valid: bool,
+    pub errors: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+}
+
 /// Fetches a form schema from the API.
-pub async fn fetch_form(base_url: &str, slug: &str) -> Result<FormJson, String> {
-    let url = format!("{}/api/forms/{}/json", base_url.trim_end_matches('/'), slug);
+pub async fn fetch_form(
+    base_url: &str,
+    slug: &str,
+    options: &FetchOptions,
+) -> Result<FormJson, ClientError> {
+    let url = format!("{}/api/v1/forms/{}/json", base_url.trim_end_matches('/'), slug);
 
-    let response = fetch_json(&url).await?;
-    let api_response: ApiResponse<FormJson> = serde_json::from_str(&response)
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+    let (status, text) = send_with_retries(&url, None, options).await?;
+    parse_response(status, &text)
+}
 
-    if api_response.success {
-        api_response.data.ok_or_else(|| "No data in response".to_string())
-    } else {
-        let error_msg = api_response
-            .error
-            .map(|e| e.message)
-            .unwrap_or_else(|| "Unknown error".to_string());
-        Err(error_msg)
-    }
+/// Fetches a form's skeleton: its steps without their fields, which
+/// [`fetch_step`] loads one step at a time.
+pub async fn fetch_form_skeleton(
+    base_url: &str,
+    slug: &str,
+    options: &FetchOptions,
+) -> Result<FormJson, ClientError> {
+    let url = format!(
+        "{}/api/v1/forms/{}/json?steps=ids",
+        base_url.trim_end_matches('/'),
+        slug
+    );
+
+    let (status, text) = send_with_retries(&url, None, options).await?;
+    parse_response(status, &text)
+}
+
+/// Fetches one step of a form, with its fields.
+pub async fn fetch_step(
+    base_url: &str,
+    slug: &str,
+    step_id: &str,
+    options: &FetchOptions,
+) -> Result<StepJson, ClientError> {
+    let url = format!(
+        "{}/api/v1/forms/{}/json?step={}",
+        base_url.trim_end_matches('/'),
+        slug,
+        step_id
+    );
+
+    let (status, text) = send_with_retries(&url, None, options).await?;
+    parse_response(status, &text)
+}
+
+/// Fetches the steps of a form changed since `since` (its `updated_at`
+/// as last fetched), listing the others in `unchanged`.
+pub async fn fetch_changes(
+    base_url: &str,
+    slug: &str,
+    since: &str,
+    options: &FetchOptions,
+) -> Result<FormJson, ClientError> {
+    let url = format!(
+        "{}/api/v1/forms/{}/json?if_changed_since={}",
+        base_url.trim_end_matches('/'),
+        slug,
+        since
+    );
+
+    let (status, text) = send_with_retries(&url, None, options).await?;
+    parse_response(status, &text)
 }
 
 /// Submits form data to the API.
+///
+/// Retries (if configured) only follow failures where no response arrived.
 pub async fn submit_form(
     base_url: &str,
     slug: &str,
     data: &serde_json::Value,
-) -> Result<SubmissionResponse, ApiError> {
-    let url = format!("{}/api/forms/{}", base_url.trim_end_matches('/'), slug);
+    options: &FetchOptions,
+) -> Result<SubmissionResponse, ClientError> {
+    let url = format!("{}/api/v1/forms/{}", base_url.trim_end_matches('/'), slug);
 
-    let response = post_json(&url, data).await.map_err(|e| ApiError {
-        code: "NETWORK_ERROR".to_string(),
-        message: e,
-        details: None,
-    })?;
+    let (status, text) = send_with_retries(&url, Some(data), options).await?;
+    parse_response(status, &text)
+}
 
-    let api_response: ApiResponse<SubmissionResponse> =
-        serde_json::from_str(&response).map_err(|e| ApiError {
-            code: "PARSE_ERROR".to_string(),
-            message: format!("Failed to parse response: {}", e),
-            details: None,
-        })?;
+/// Starts a submission to be filled in step by step.
+pub async fn start_submission(
+    base_url: &str,
+    slug: &str,
+    options: &FetchOptions,
+) -> Result<SubmissionProgress, ClientError> {
+    let url = format!(
+        "{}/api/v1/forms/{}/submissions/start",
+        base_url.trim_end_matches('/'),
+        slug
+    );
+
+    let body = serde_json::json!({});
+    let (status, text) = send_with_retries(&url, Some(&body), options).await?;
+    parse_response(status, &text)
+}
+
+/// Saves the answers to one step of an in-progress submission, validated
+/// by the server.
+///
+/// `step_id` defaults to the submission's current step.
+pub async fn save_step(
+    base_url: &str,
+    slug: &str,
+    submission_id: &str,
+    step_id: Option<&str>,
+    data: &serde_json::Value,
+    options: &FetchOptions,
+) -> Result<SubmissionProgress, ClientError> {
+    let url = format!(
+        "{}/api/v1/forms/{}/submissions/{}/step",
+        base_url.trim_end_matches('/'),
+        slug,
+        submission_id
+    );
+    let body = serde_json::json!({ "step_id": step_id, "data": data });
+
+    let (status, text) = send_method_with_retries(&url, "PATCH", Some(&body), options).await?;
+    parse_response(status, &text)
+}
+
+/// Validates a single field's value on the server.
+///
+/// `seq` is echoed back in the response, so callers can discard responses
+/// to superseded requests.
+pub async fn validate_field(
+    base_url: &str,
+    slug: &str,
+    field: &str,
+    value: &serde_json::Value,
+    values: &serde_json::Value,
+    seq: u64,
+    options: &FetchOptions,
+) -> Result<FieldValidationResponse, ClientError> {
+    let url = format!(
+        "{}/api/v1/forms/{}/fields/{}/validate",
+        base_url.trim_end_matches('/'),
+        slug,
+        field
+    );
+    let values = if values.is_object() {
+        values.clone()
+    } else {
+        serde_json::json!({})
+    };
+    let body = serde_json::json!({ "value": value, "values": values, "seq": seq });
+
+    let (status, text) = send_with_retries(&url, Some(&body), options).await?;
+    parse_response(status, &text)
+}
+
+/// Parses an API response envelope, returning its data or its error.
+pub(crate) fn parse_response<T: DeserializeOwned>(
+    status: u16,
+    text: &str,
+) -> Result<T, ClientError> {
+    let api_response: ApiResponse<T> = match serde_json::from_str(text) {
+        Ok(response) => response,
+        // Not an anyform envelope, e.g. a proxy's error page
+        Err(_) if !(200..300).contains(&status) => {
+            return Err(ClientError::server(
+                "HTTP_ERROR",
+                format!("HTTP error: {}", status),
+                status,
+            ))
+        }
+        Err(e) => {
+            return Err(ClientError::server(
+                "PARSE_ERROR",
+                format!("Failed to parse response: {}", e),
+                status,
+            ))
+        }
+    };
 
     if api_response.success {
-        api_response.data.ok_or_else(|| ApiError {
-            code: "NO_DATA".to_string(),
-            message: "No data in response".to_string(),
-            details: None,
-        })
+        api_response
+            .data
+            .ok_or_else(|| ClientError::server("NO_DATA", "No data in response", status))
     } else {
-        Err(api_response.error.unwrap_or_else(|| ApiError {
-            code: "UNKNOWN_ERROR".to_string(),
-            message: "Unknown error".to_string(),
-            details: None,
-        }))
+        Err(ClientError::from_response(status, api_response.error))
     }
 }
 
-/// Performs a GET request and returns the response text.
-async fn fetch_json(url: &str) -> Result<String, String> {
-    let window = web_sys::window().ok_or("No window available")?;
+/// Sends a request, retrying network failures with exponential backoff.
+///
+/// Sends a POST with a JSON body if `body` is given, otherwise a GET.
+/// Returns the response status and text.
+async fn send_with_retries(
+    url: &str,
+    body: Option<&serde_json::Value>,
+    options: &FetchOptions,
+) -> Result<(u16, String), ClientError> {
+    let method = if body.is_some() { "POST" } else { "GET" };
+    send_method_with_retries(url, method, body, options).await
+}
 
+/// Sends a request with `method`, retrying network failures as
+/// [`send_with_retries`] does.
+async fn send_method_with_retries(
+    url: &str,
+    method: &str,
+    body: Option<&serde_json::Value>,
+    options: &FetchOptions,
+) -> Result<(u16, String), ClientError> {
+    let mut attempt = 0;
+    loop {
+        match send(url, method, body, options).await {
+            Err(e) if e.is_retryable() && attempt < options.retries => {
+                sleep(options.retry_delay(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Sends a single request.
+async fn send(
+    url: &str,
+    method: &str,
+    body: Option<&serde_json::Value>,
+    options: &FetchOptions,
+) -> Result<(u16, String), ClientError> {
     let opts = RequestInit::new();
-    opts.set_method("GET");
     opts.set_mode(RequestMode::Cors);
+    if let Some(credentials) = options.credentials {
+        opts.set_credentials(credentials.into());
+    }
+    opts.set_method(method);
+    if let Some(data) = body {
+        let body = serde_json::to_string(data).map_err(|e| {
+            ClientError::network("REQUEST_ERROR", format!("Failed to serialize: {}", e))
+        })?;
+        opts.set_body(&JsValue::from_str(&body));
+    }
 
-    let request = Request::new_with_str_and_init(url, &opts)
-        .map_err(|e| format!("Failed to create request: {:?}", e))?;
+    // Aborted by a timer if the request takes too long
+    let controller = match options.timeout_ms {
+        Some(_) => AbortController::new().ok(),
+        None => None,
+    };
+    if let Some(controller) = &controller {
+        opts.set_signal(Some(&controller.signal()));
+    }
+
+    let request = Request::new_with_str_and_init(url, &opts).map_err(|e| {
+        ClientError::network("REQUEST_ERROR", format!("Failed to create request: {:?}", e))
+    })?;
+
+    let headers = request.headers();
+    let mut default_headers = vec![("Accept", "application/json")];
+    if body.is_some() {
+        default_headers.push(("Content-Type", "application/json"));
+    }
+    let custom_headers = options.headers.iter().map(|(k, v)| (k.as_str(), v.as_str()));
+    for (name, value) in default_headers.into_iter().chain(custom_headers) {
+        headers.set(name, value).map_err(|e| {
+            ClientError::network("REQUEST_ERROR", format!("Failed to set header {}: {:?}", name, e))
+        })?;
+    }
+
+    let timeout = match (&controller, options.timeout_ms) {
+        (Some(controller), Some(ms)) => {
+            let controller = controller.clone();
+            let abort = Closure::once_into_js(move || controller.abort());
+            Some(set_timeout(&abort, ms as i32))
+        }
+        _ => None,
+    };
+
+    let result = read_response(&request).await;
+    if let Some(handle) = timeout {
+        clear_timeout(&handle);
+    }
 
-    request
-        .headers()
-        .set("Accept", "application/json")
-        .map_err(|e| format!("Failed to set header: {:?}", e))?;
+    result.map_err(|e| {
+        let timed_out = controller.as_ref().is_some_and(|c| c.signal().aborted());
+        if timed_out {
+            ClientError::network("TIMEOUT", "Request timed out")
+        } else {
+            ClientError::network("NETWORK_ERROR", e)
+        }
+    })
+}
+
+// Globals shared by browsers, workers and Node.js 18+, so requests work
+// without a `window` (e.g. during server-side rendering).
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch)]
+    fn fetch(request: &Request) -> Result<js_sys::Promise, JsValue>;
+
+    /// Returns a number in browsers and a `Timeout` object in Node.js.
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(callback: &JsValue, ms: i32) -> JsValue;
+
+    #[wasm_bindgen(js_name = clearTimeout)]
+    fn clear_timeout(handle: &JsValue);
+}
 
-    let resp_value = JsFuture::from(window.fetch_with_request(&request))
+/// Fetches a request and reads the response text.
+async fn read_response(request: &Request) -> Result<(u16, String), String> {
+    let promise = fetch(request).map_err(|e| format!("Fetch failed: {:?}", e))?;
+    let resp_value = JsFuture::from(promise)
         .await
         .map_err(|e| format!("Fetch failed: {:?}", e))?;
 
@@ -139,52 +609,86 @@ async fn fetch_json(url: &str) -> Result<String, String> {
         .dyn_into()
         .map_err(|_| "Response is not a Response object")?;
 
-    if !resp.ok() {
-        return Err(format!("HTTP error: {}", resp.status()));
-    }
-
     let text = JsFuture::from(resp.text().map_err(|e| format!("Failed to get text: {:?}", e))?)
         .await
         .map_err(|e| format!("Failed to read response: {:?}", e))?;
 
-    text.as_string().ok_or_else(|| "Response is not a string".to_string())
+    let text = text.as_string().ok_or_else(|| "Response is not a string".to_string())?;
+    Ok((resp.status(), text))
 }
 
-/// Performs a POST request with JSON body.
-async fn post_json(url: &str, data: &serde_json::Value) -> Result<String, String> {
-    let window = web_sys::window().ok_or("No window available")?;
+/// Resolves after `ms` milliseconds.
+async fn sleep(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        set_timeout(&resolve, ms as i32);
+    });
+    let _ = JsFuture::from(promise).await;
+}
 
-    let body = serde_json::to_string(data).map_err(|e| format!("Failed to serialize: {}", e))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let opts = RequestInit::new();
-    opts.set_method("POST");
-    opts.set_mode(RequestMode::Cors);
-    opts.set_body(&JsValue::from_str(&body));
+    #[test]
+    fn test_error_kinds_follow_status() {
+        let error = |code: &str| ApiError {
+            code: code.to_string(),
+            message: String::new(),
+            details: None,
+        };
 
-    let request = Request::new_with_str_and_init(url, &opts)
-        .map_err(|e| format!("Failed to create request: {:?}", e))?;
+        let err = ClientError::from_response(400, Some(error("VALIDATION_FAILED")));
+        assert_eq!(err.kind, ErrorKind::Validation);
+        assert_eq!(err.code, "VALIDATION_FAILED");
 
-    request
-        .headers()
-        .set("Content-Type", "application/json")
-        .map_err(|e| format!("Failed to set header: {:?}", e))?;
+        let err = ClientError::from_response(404, Some(error("FORM_NOT_FOUND")));
+        assert_eq!((err.kind, err.status), (ErrorKind::Server, Some(404)));
+        assert!(!err.is_retryable());
 
-    request
-        .headers()
-        .set("Accept", "application/json")
-        .map_err(|e| format!("Failed to set header: {:?}", e))?;
+        assert!(ClientError::network("TIMEOUT", "").is_retryable());
+        assert!(!ClientError::network("REQUEST_ERROR", "").is_retryable());
+    }
 
-    let resp_value = JsFuture::from(window.fetch_with_request(&request))
-        .await
-        .map_err(|e| format!("Fetch failed: {:?}", e))?;
+    #[test]
+    fn test_parse_response_surfaces_server_codes() {
+        let body = r#"{"success":false,"status":401,"error":{"code":"RESPONDENT_REQUIRED","message":"Sign in"}}"#;
+        let err = parse_response::<SubmissionResponse>(401, body).unwrap_err();
+        assert_eq!((err.kind, err.code.as_str()), (ErrorKind::Server, "RESPONDENT_REQUIRED"));
 
-    let resp: Response = resp_value
-        .dyn_into()
-        .map_err(|_| "Response is not a Response object")?;
+        let err = parse_response::<SubmissionResponse>(502, "<html>Bad Gateway</html>")
+            .unwrap_err();
+        assert_eq!((err.code.as_str(), err.status), ("HTTP_ERROR", Some(502)));
 
-    let text = JsFuture::from(resp.text().map_err(|e| format!("Failed to get text: {:?}", e))?)
-        .await
-        .map_err(|e| format!("Failed to read response: {:?}", e))?;
+        let body = r#"{"success":true,"status":201,"data":{"id":"abc"}}"#;
+        assert_eq!(parse_response::<SubmissionResponse>(201, body).unwrap().id, "abc");
+    }
+
+    #[test]
+    fn test_user_message_for_unavailable_server() {
+        let body = r#"{"success":false,"status":503,"error":{"code":"READ_ONLY","message":"Read-only"}}"#;
+        let err = parse_response::<SubmissionResponse>(503, body).unwrap_err();
+        assert_eq!(err.code, "READ_ONLY");
+        assert!(err.user_message().unwrap().contains("temporarily unavailable"));
+        assert!(!err.is_retryable());
 
-    text.as_string().ok_or_else(|| "Response is not a string".to_string())
+        assert_eq!(ClientError::network("TIMEOUT", "").user_message(), None);
+    }
+
+    #[test]
+    fn test_fetch_options_from_js_object() {
+        let options: FetchOptions = serde_json::from_value(serde_json::json!({
+            "headers": { "Authorization": "Bearer t" },
+            "credentials": "same-origin",
+            "retries": 3
+        }))
+        .unwrap();
+
+        assert_eq!(options.credentials, Some(Credentials::SameOrigin));
+        assert_eq!(options.timeout_ms, None);
+        assert_eq!(
+            (options.retry_delay(0), options.retry_delay(2)),
+            (250, 1000)
+        );
+        assert_eq!(options.retry_delay(20), MAX_RETRY_DELAY_MS);
+    }
 }
@@ -0,0 +1,78 @@
+//! Dependency index over step and field conditions.
+//!
+//! Maps each field to the conditions that read it, so FormState can cache
+//! condition results and invalidate only the ones a changed value affects.
+
+use crate::schema::FormJson;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A step or field whose visibility is governed by a condition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConditionTarget {
+    /// Step at the given index.
+    Step(usize),
+    /// Field at the given step and field index.
+    Field(usize, usize),
+}
+
+/// Index of where fields live and which conditions depend on them.
+#[derive(Debug, Clone, Default)]
+pub struct ConditionGraph {
+    steps: HashMap<Uuid, usize>,
+    fields: HashMap<String, Vec<(usize, usize)>>,
+    dependents: HashMap<String, Vec<ConditionTarget>>,
+}
+
+impl ConditionGraph {
+    /// Builds the graph for a form schema.
+    pub fn new(schema: &FormJson) -> Self {
+        let mut graph = Self::default();
+
+        for (step_index, step) in schema.steps.iter().enumerate() {
+            graph.steps.insert(step.id, step_index);
+            if let Some(rule) = &step.condition {
+                graph.add_dependent(&rule.field, ConditionTarget::Step(step_index));
+            }
+
+            for (field_index, field) in step.fields.iter().enumerate() {
+                graph
+                    .fields
+                    .entry(field.name.clone())
+                    .or_default()
+                    .push((step_index, field_index));
+                if let Some(rule) = &field.condition {
+                    graph.add_dependent(
+                        &rule.field,
+                        ConditionTarget::Field(step_index, field_index),
+                    );
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Returns the index of the step with the given ID.
+    pub fn step_index(&self, id: &Uuid) -> Option<usize> {
+        self.steps.get(id).copied()
+    }
+
+    /// Returns every `(step, field)` position of fields with the given name,
+    /// in schema order.
+    pub fn field_positions(&self, name: &str) -> &[(usize, usize)] {
+        self.fields.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the conditions that read the given field.
+    pub fn dependents(&self, field: &str) -> &[ConditionTarget] {
+        self.dependents.get(field).map_or(&[], Vec::as_slice)
+    }
+
+    fn add_dependent(&mut self, field: &str, target: ConditionTarget) {
+        self.dependents
+            .entry(field.to_string())
+            .or_default()
+            .push(target);
+    }
+}
@@ -2,8 +2,11 @@
 //!
 //! FormClient provides a simple interface for fetching forms and managing
 //! form state in the browser.
+//!
+//! Failed calls reject with an `AnyformError` whose `kind` is `network`,
+//! `validation` or `server`, and whose `code` is the server's error code.
 
-use crate::api;
+use crate::api::{self, FetchOptions};
 use crate::form_state::FormState;
 use wasm_bindgen::prelude::*;
 
@@ -11,6 +14,7 @@ use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
 pub struct FormClient {
     base_url: String,
+    options: FetchOptions,
 }
 
 #[wasm_bindgen]
@@ -20,28 +24,167 @@ impl FormClient {
     pub fn new(base_url: &str) -> FormClient {
         FormClient {
             base_url: base_url.trim_end_matches('/').to_string(),
+            options: FetchOptions::default(),
+        }
+    }
+
+    /// Creates a FormClient with request options:
+    /// `{ headers, credentials, timeout_ms, retries, retry_delay_ms }`.
+    ///
+    /// `credentials` is `omit`, `same-origin` or `include`. Network failures
+    /// are retried `retries` times, waiting `retry_delay_ms` (default 250)
+    /// and doubling the wait each time.
+    pub fn with_options(base_url: &str, options: JsValue) -> Result<FormClient, JsValue> {
+        let options: FetchOptions = if options.is_undefined() || options.is_null() {
+            FetchOptions::default()
+        } else {
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|e| JsValue::from_str(&format!("Invalid options: {}", e)))?
+        };
+
+        Ok(FormClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            options,
+        })
+    }
+
+    /// Sets a header sent with every request.
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.options.headers.insert(name.to_string(), value.to_string());
+    }
+
+    /// Removes a header set with `set_header`.
+    pub fn remove_header(&mut self, name: &str) {
+        self.options.headers.remove(name);
+    }
+
+    /// Sends `Authorization: Bearer <token>` with every request, or stops
+    /// sending it if `token` is `null`.
+    pub fn set_auth_token(&mut self, token: Option<String>) {
+        match token {
+            Some(token) => self.set_header("Authorization", &format!("Bearer {}", token)),
+            None => self.remove_header("Authorization"),
         }
     }
 
     /// Fetches a form and returns a FormState for managing it.
     pub async fn fetch_form(&self, slug: &str) -> Result<FormState, JsValue> {
-        let schema = api::fetch_form(&self.base_url, slug)
-            .await
-            .map_err(|e| JsValue::from_str(&e))?;
+        let schema = api::fetch_form(&self.base_url, slug, &self.options).await?;
 
         Ok(FormState::from_schema(schema))
     }
 
+    /// Fetches a form's skeleton, its steps without their fields, and the
+    /// first step's fields, for long forms whose other steps are loaded as
+    /// the respondent gets to them.
+    ///
+    /// After moving to a step, load it if `pending_step()` names one:
+    /// `form.load_step(await client.fetch_step(slug, form.pending_step()))`.
+    pub async fn fetch_form_lazy(&self, slug: &str) -> Result<FormState, JsValue> {
+        let schema = api::fetch_form_skeleton(&self.base_url, slug, &self.options).await?;
+
+        let mut form = FormState::from_schema(schema);
+        self.load_pending_step(&mut form).await?;
+        Ok(form)
+    }
+
+    /// Fetches one step of a form with its fields, for
+    /// `FormState::load_step`.
+    pub async fn fetch_step(&self, slug: &str, step_id: &str) -> Result<JsValue, JsValue> {
+        let step = api::fetch_step(&self.base_url, slug, step_id, &self.options).await?;
+
+        serde_wasm_bindgen::to_value(&step)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize step: {}", e)))
+    }
+
+    /// Fetches the steps of a form changed since `since`, for
+    /// `FormState::load_changes`:
+    /// `form.load_changes(await client.fetch_changes(slug, form.updated_at()))`.
+    pub async fn fetch_changes(&self, slug: &str, since: &str) -> Result<JsValue, JsValue> {
+        let changes = api::fetch_changes(&self.base_url, slug, since, &self.options).await?;
+
+        serde_wasm_bindgen::to_value(&changes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize changes: {}", e)))
+    }
+
     /// Submits form data directly.
     pub async fn submit_form(&self, slug: &str, data: JsValue) -> Result<JsValue, JsValue> {
         let json_data: serde_json::Value = serde_wasm_bindgen::from_value(data)
             .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
 
-        let result = api::submit_form(&self.base_url, slug, &json_data)
-            .await
-            .map_err(|e| {
-                serde_wasm_bindgen::to_value(&e).unwrap_or_else(|_| JsValue::from_str(&e.message))
-            })?;
+        let result = api::submit_form(&self.base_url, slug, &json_data, &self.options).await?;
+
+        serde_wasm_bindgen::to_value(&result)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
+    }
+
+    /// Starts a submission to save step by step, for forms with
+    /// `save_each_step`. Resolves to
+    /// `{ submission_id, status, current_step_id, data }`.
+    pub async fn start_submission(&self, slug: &str) -> Result<JsValue, JsValue> {
+        let progress = api::start_submission(&self.base_url, slug, &self.options).await?;
+
+        serde_wasm_bindgen::to_value(&progress)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize progress: {}", e)))
+    }
+
+    /// Saves the answers to a step of a started submission (the current
+    /// step if `step_id` is `null`).
+    ///
+    /// Submit the form with `_af_submission` set to the submission's ID to
+    /// have it discarded once the form is submitted.
+    pub async fn save_step(
+        &self,
+        slug: &str,
+        submission_id: &str,
+        step_id: Option<String>,
+        data: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let data: serde_json::Value = serde_wasm_bindgen::from_value(data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse data: {}", e)))?;
+
+        let progress = api::save_step(
+            &self.base_url,
+            slug,
+            submission_id,
+            step_id.as_deref(),
+            &data,
+            &self.options,
+        )
+        .await?;
+
+        serde_wasm_bindgen::to_value(&progress)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize progress: {}", e)))
+    }
+
+    /// Validates a single field's value on the server.
+    ///
+    /// `values` holds the rest of the form's values (or `null`). Resolves to
+    /// `{ field, valid, errors, seq }` with `seq` echoed back: pass an
+    /// increasing number per call and apply only the latest result.
+    pub async fn validate_field(
+        &self,
+        slug: &str,
+        field: &str,
+        value: JsValue,
+        values: JsValue,
+        seq: u32,
+    ) -> Result<JsValue, JsValue> {
+        let value: serde_json::Value = serde_wasm_bindgen::from_value(value)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse value: {}", e)))?;
+        let values: serde_json::Value = serde_wasm_bindgen::from_value(values)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse values: {}", e)))?;
+
+        let result = api::validate_field(
+            &self.base_url,
+            slug,
+            field,
+            &value,
+            &values,
+            seq.into(),
+            &self.options,
+        )
+        .await?;
 
         serde_wasm_bindgen::to_value(&result)
             .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
@@ -54,16 +197,38 @@ impl FormClient {
 }
 
 impl FormClient {
+    /// Fetches the fields of a lazily fetched form's current step, if they
+    /// haven't been loaded yet. Returns true if a step was loaded.
+    pub async fn load_pending_step(&self, form: &mut FormState) -> Result<bool, JsValue> {
+        let Some(step_id) = form.pending_step() else {
+            return Ok(false);
+        };
+        let step = api::fetch_step(&self.base_url, &form.slug(), &step_id, &self.options).await?;
+
+        Ok(form.insert_step(step))
+    }
+
+    /// Brings a form up to date with the server, fetching only the steps
+    /// changed since it was fetched, or the whole form if the server never
+    /// said when that was.
+    pub async fn refresh(&self, form: &mut FormState) -> Result<(), JsValue> {
+        let slug = form.slug();
+        let changes = match form.updated_at() {
+            Some(since) => api::fetch_changes(&self.base_url, &slug, &since, &self.options).await?,
+            None => api::fetch_form(&self.base_url, &slug, &self.options).await?,
+        };
+
+        form.apply_changes(changes);
+        Ok(())
+    }
+
     /// Submits a FormState's values.
     pub async fn submit_form_state(&self, form_state: &FormState) -> Result<JsValue, JsValue> {
         let data = serde_json::to_value(form_state.values_map())
             .map_err(|e| JsValue::from_str(&format!("Failed to serialize: {}", e)))?;
 
-        let result = api::submit_form(&self.base_url, &form_state.slug(), &data)
-            .await
-            .map_err(|e| {
-                serde_wasm_bindgen::to_value(&e).unwrap_or_else(|_| JsValue::from_str(&e.message))
-            })?;
+        let result =
+            api::submit_form(&self.base_url, &form_state.slug(), &data, &self.options).await?;
 
         serde_wasm_bindgen::to_value(&result)
             .map_err(|e| JsValue::from_str(&format!("Failed to serialize result: {}", e)))
@@ -2,9 +2,34 @@
 //!
 //! FormState tracks all form values, validation errors, touched fields,
 //! and current step position for multi-step forms.
+//!
+//! Validation can be scheduled rather than immediate: with a validation
+//! delay set, changes to touched fields are only validated once
+//! [`FormState::flush_validation`] runs (after the user pauses typing), and
+//! server checks are tracked with [`FormState::begin_async_validation`] /
+//! [`FormState::finish_async_validation`]. [`FormState::is_validating`]
+//! reports both, so UIs can show a spinner instead of transient errors.
+//!
+//! [`FormState::next_step`] follows the form's jump rules, as the server
+//! does: once a step is done, its shown fields' rules are tried in order,
+//! then the step's own, and the first that applies picks the step to go to
+//! or ends the form. [`FormState::prev_step`] retraces the steps taken.
+//!
+//! Long forms can be fetched as a skeleton, steps without their fields, and
+//! each step filled in with [`FormState::load_step`] once the respondent
+//! gets to it; [`FormState::pending_step`] names the current step while its
+//! fields are missing.
+//!
+//! [`FormState::load_changes`] hot-patches a form in use with the steps
+//! changed since [`FormState::updated_at`], keeping its values and position.
 
+use crate::condition_graph::{ConditionGraph, ConditionTarget};
+use crate::piping;
+#[cfg(feature = "multi-step")]
+use crate::schema::JumpRule;
 use crate::schema::{ConditionRule, FieldJson, FormJson, StepJson};
-use crate::validation::validate_field;
+use crate::validation::{validate_field, validate_rows};
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 use wasm_bindgen::prelude::*;
@@ -18,6 +43,20 @@ pub struct FormState {
     errors: HashMap<String, Vec<String>>,
     touched: HashSet<String>,
     current_step_index: usize,
+    /// Schema indices of the steps taken to reach the current one.
+    #[cfg(feature = "multi-step")]
+    history: Vec<usize>,
+    graph: ConditionGraph,
+    /// Cached condition results, invalidated through `graph` on each change.
+    visibility: RefCell<HashMap<ConditionTarget, bool>>,
+    /// Debounce window for validating touched fields; 0 validates at once.
+    validation_delay_ms: u32,
+    /// Touched fields changed since they were last validated.
+    pending_validation: HashSet<String>,
+    /// Sequence number of each field's outstanding async check.
+    validating: HashMap<String, u32>,
+    validation_seq: u32,
+    on_validation_change: Option<js_sys::Function>,
 }
 
 #[wasm_bindgen]
@@ -28,24 +67,7 @@ impl FormState {
         let schema: FormJson = serde_wasm_bindgen::from_value(schema_js)
             .map_err(|e| JsValue::from_str(&format!("Failed to parse schema: {}", e)))?;
 
-        let mut values = HashMap::new();
-
-        // Initialize with default values
-        for step in &schema.steps {
-            for field in &step.fields {
-                if let Some(default) = &field.default_value {
-                    values.insert(field.name.clone(), default.clone());
-                }
-            }
-        }
-
-        Ok(FormState {
-            schema,
-            values,
-            errors: HashMap::new(),
-            touched: HashSet::new(),
-            current_step_index: 0,
-        })
+        Ok(FormState::from_schema(schema))
     }
 
     // ─────────────────────────────────────────────────────────────────────────
@@ -56,12 +78,7 @@ impl FormState {
     pub fn set_value(&mut self, field: &str, value: JsValue) {
         let json_value: serde_json::Value = serde_wasm_bindgen::from_value(value)
             .unwrap_or(serde_json::Value::Null);
-        self.values.insert(field.to_string(), json_value);
-
-        // Re-validate if field was touched
-        if self.touched.contains(field) {
-            self.validate_field_internal(field);
-        }
+        self.set_json_value(field, json_value);
     }
 
     /// Gets a field value.
@@ -77,6 +94,25 @@ impl FormState {
         serde_wasm_bindgen::to_value(&self.values).unwrap_or(JsValue::NULL)
     }
 
+    /// Fills the `{{field:name}}` placeholders in a label, description or
+    /// help text with the current answers, choices quoted by their labels,
+    /// and `{{field:name|fallback}}` with the fallback while unanswered.
+    pub fn resolve_text(&self, text: &str) -> String {
+        piping::resolve(text, |name| {
+            let Some(value) = self.values.get(name) else {
+                return String::new();
+            };
+            let options = self
+                .schema
+                .steps
+                .iter()
+                .flat_map(|s| &s.fields)
+                .find(|f| f.name == name)
+                .map_or(&[][..], |f| &f.options);
+            piping::answer_text(value, options)
+        })
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Touch tracking
     // ─────────────────────────────────────────────────────────────────────────
@@ -84,8 +120,10 @@ impl FormState {
     /// Marks a field as touched (user has interacted with it).
     pub fn mark_touched(&mut self, field: &str) {
         self.touched.insert(field.to_string());
-        // Validate on touch
+        // Validate on touch, including any deferred validation
+        self.pending_validation.remove(field);
         self.validate_field_internal(field);
+        self.notify_validation_change(field);
     }
 
     /// Checks if a field has been touched.
@@ -103,6 +141,83 @@ impl FormState {
         self.errors.get(field_name).cloned().unwrap_or_default()
     }
 
+    /// Sets how long changes to touched fields wait before being validated.
+    ///
+    /// With a delay, `set_value` defers validation and the caller runs
+    /// `flush_validation` once the delay has passed without further input.
+    /// 0 (the default) validates on every change.
+    pub fn set_validation_delay(&mut self, ms: u32) {
+        self.validation_delay_ms = ms;
+    }
+
+    /// Returns the validation delay in milliseconds.
+    pub fn validation_delay(&self) -> u32 {
+        self.validation_delay_ms
+    }
+
+    /// Validates fields whose validation was deferred, or only `field` if
+    /// given. Returns true if any field was validated.
+    pub fn flush_validation(&mut self, field: Option<String>) -> bool {
+        let fields: Vec<String> = match field {
+            Some(field) => self.pending_validation.take(&field).into_iter().collect(),
+            None => self.pending_validation.drain().collect(),
+        };
+        for field in &fields {
+            self.validate_field_internal(field);
+            self.notify_validation_change(field);
+        }
+        !fields.is_empty()
+    }
+
+    /// Marks `field` as being checked asynchronously (e.g. by the server) and
+    /// returns the check's sequence number, to pass to
+    /// `finish_async_validation`.
+    pub fn begin_async_validation(&mut self, field: &str) -> u32 {
+        self.validation_seq += 1;
+        self.validating
+            .insert(field.to_string(), self.validation_seq);
+        self.notify_validation_change(field);
+        self.validation_seq
+    }
+
+    /// Applies the result of an async check.
+    ///
+    /// Results of superseded checks (the field changed or was checked again
+    /// since) are ignored and false is returned.
+    pub fn finish_async_validation(&mut self, field: &str, seq: u32, errors: Vec<String>) -> bool {
+        if self.validating.get(field) != Some(&seq) {
+            return false;
+        }
+        self.validating.remove(field);
+        if errors.is_empty() {
+            // Clear errors from earlier checks, keeping local ones
+            self.validate_field_internal(field);
+        } else {
+            self.errors.insert(field.to_string(), errors);
+        }
+        self.notify_validation_change(field);
+        true
+    }
+
+    /// Returns true while `field` (or any field, if not given) has deferred
+    /// validation or an async check in flight.
+    pub fn is_validating(&self, field: Option<String>) -> bool {
+        match field {
+            Some(field) => {
+                self.pending_validation.contains(&field) || self.validating.contains_key(&field)
+            }
+            None => !self.pending_validation.is_empty() || !self.validating.is_empty(),
+        }
+    }
+
+    /// Registers a callback run whenever a field's validation state changes.
+    ///
+    /// It is called as `callback(field, validating, errors)`, and must not
+    /// call back into this FormState synchronously.
+    pub fn on_validation_change(&mut self, callback: Option<js_sys::Function>) {
+        self.on_validation_change = callback;
+    }
+
     /// Validates all fields in a step.
     pub fn validate_step(&mut self, step_id: &str) -> JsValue {
         let step_uuid = Uuid::parse_str(step_id).ok();
@@ -116,13 +231,7 @@ impl FormState {
                 }
 
                 let value = self.values.get(&field.name).unwrap_or(&serde_json::Value::Null);
-                let errors = validate_field(field, value);
-                if !errors.is_empty() {
-                    step_errors.insert(field.name.clone(), errors.clone());
-                    self.errors.insert(field.name.clone(), errors);
-                } else {
-                    self.errors.remove(&field.name);
-                }
+                step_errors.extend(record_errors(&mut self.errors, field, value));
             }
         }
 
@@ -131,6 +240,7 @@ impl FormState {
 
     /// Validates all visible fields in the form.
     pub fn validate_all(&mut self) -> JsValue {
+        self.pending_validation.clear();
         for step in &self.schema.steps.clone() {
             // Skip hidden steps
             if !self.is_step_visible_internal(&step.id.to_string()) {
@@ -235,70 +345,44 @@ impl FormState {
         self.current_step_index
     }
 
-    /// Moves to the next visible step. Returns true if successful.
-    pub fn next_step(&mut self) -> bool {
-        let visible_steps = self.get_visible_step_indices();
-        if self.current_step_index + 1 < visible_steps.len() {
-            self.current_step_index += 1;
-            true
-        } else {
-            false
-        }
-    }
-
-    /// Moves to the previous visible step. Returns true if successful.
-    pub fn prev_step(&mut self) -> bool {
-        if self.current_step_index > 0 {
-            self.current_step_index -= 1;
-            true
-        } else {
-            false
-        }
-    }
-
-    /// Navigates to a specific step by ID. Returns true if successful.
-    pub fn go_to_step(&mut self, step_id: &str) -> bool {
-        let uuid = match Uuid::parse_str(step_id) {
-            Ok(u) => u,
-            Err(_) => return false,
-        };
+    // ─────────────────────────────────────────────────────────────────────────
+    // Lazy step loading
+    // ─────────────────────────────────────────────────────────────────────────
 
-        let visible_steps = self.get_visible_step_indices();
-        for (visible_index, &actual_index) in visible_steps.iter().enumerate() {
-            if let Some(step) = self.schema.steps.get(actual_index) {
-                if step.id == uuid {
-                    self.current_step_index = visible_index;
-                    return true;
-                }
-            }
-        }
-        false
+    /// Returns the current step's ID if its fields haven't been loaded yet,
+    /// as in forms fetched with `FormClient::fetch_form_lazy`.
+    pub fn pending_step(&self) -> Option<String> {
+        let step = &self.schema.steps[self.current_schema_step()?];
+        step.lazy.then(|| step.id.to_string())
     }
 
-    /// Returns true if we can advance to the next step.
-    pub fn can_go_next(&self) -> bool {
-        let visible_steps = self.get_visible_step_indices();
-        self.current_step_index + 1 < visible_steps.len()
-    }
-
-    /// Returns true if we can go back to the previous step.
-    pub fn can_go_prev(&self) -> bool {
-        self.current_step_index > 0
+    /// Checks if a step's fields have been loaded.
+    pub fn is_step_loaded(&self, step_id: &str) -> bool {
+        self.schema
+            .steps
+            .iter()
+            .any(|s| s.id.to_string() == step_id && !s.lazy)
     }
 
-    /// Returns progress as [current, total] (1-indexed for display).
-    pub fn progress(&self) -> Vec<u32> {
-        let visible_steps = self.get_visible_step_indices();
-        vec![
-            (self.current_step_index + 1) as u32,
-            visible_steps.len() as u32,
-        ]
+    /// Fills in a step with its fields, as fetched by
+    /// `FormClient::fetch_step`.
+    pub fn load_step(&mut self, step_js: JsValue) -> Result<(), JsValue> {
+        let step: StepJson = serde_wasm_bindgen::from_value(step_js)
+            .map_err(|e| JsValue::from_str(&format!("Invalid step: {}", e)))?;
+        let id = step.id;
+        if self.insert_step(step) {
+            Ok(())
+        } else {
+            Err(JsValue::from_str(&format!("Unknown step: {}", id)))
+        }
     }
 
-    /// Returns true if currently on the last step.
-    pub fn is_last_step(&self) -> bool {
-        let visible_steps = self.get_visible_step_indices();
-        self.current_step_index + 1 >= visible_steps.len()
+    /// Patches the form with the changes fetched by `FormClient::refresh`.
+    pub fn load_changes(&mut self, changes_js: JsValue) -> Result<(), JsValue> {
+        let changes: FormJson = serde_wasm_bindgen::from_value(changes_js)
+            .map_err(|e| JsValue::from_str(&format!("Invalid changes: {}", e)))?;
+        self.apply_changes(changes);
+        Ok(())
     }
 
     // ─────────────────────────────────────────────────────────────────────────
@@ -315,6 +399,11 @@ impl FormState {
         self.schema.name.clone()
     }
 
+    /// Returns when the form last changed, if the server said.
+    pub fn updated_at(&self) -> Option<String> {
+        self.schema.updated_at.clone()
+    }
+
     /// Returns the form schema as JS.
     pub fn schema(&self) -> JsValue {
         serde_wasm_bindgen::to_value(&self.schema).unwrap_or(JsValue::NULL)
@@ -329,7 +418,7 @@ impl FormState {
             .action_url
             .clone()
             .or_else(|| self.schema.settings.action_url.clone())
-            .unwrap_or_else(|| format!("/api/forms/{}", self.schema.slug))
+            .unwrap_or_else(|| format!("/api/v1/forms/{}", self.schema.slug))
     }
 
     /// Returns the HTTP method for form submission.
@@ -344,71 +433,243 @@ impl FormState {
     }
 }
 
-// ─────────────────────────────────────────────────────────────────────────────
-// Internal methods (not exposed to JS)
-// ─────────────────────────────────────────────────────────────────────────────
-
+/// Step navigation, compiled in with the `multi-step` feature.
+#[cfg(feature = "multi-step")]
+#[wasm_bindgen]
 impl FormState {
-    fn validate_field_internal(&mut self, field_name: &str) {
-        // Find the field in the schema
-        for step in &self.schema.steps {
-            for field in &step.fields {
-                if field.name == field_name {
-                    let value = self.values.get(field_name).unwrap_or(&serde_json::Value::Null);
-                    let errors = validate_field(field, value);
-                    if errors.is_empty() {
-                        self.errors.remove(field_name);
-                    } else {
-                        self.errors.insert(field_name.to_string(), errors);
-                    }
-                    return;
-                }
+    /// Moves to the step that follows, visible and picked by the jump
+    /// rules. Returns true if successful, false once the form ends.
+    pub fn next_step(&mut self) -> bool {
+        let Some(current) = self.current_schema_step() else {
+            return false;
+        };
+        let Some(next) = self.following_step(current) else {
+            return false;
+        };
+        let visible_steps = self.get_visible_step_indices();
+        let Some(index) = visible_steps.iter().position(|&i| i == next) else {
+            return false;
+        };
+        self.history.push(current);
+        self.current_step_index = index;
+        true
+    }
+
+    /// Moves back to the step taken before the current one, or to the
+    /// previous visible step. Returns true if successful.
+    pub fn prev_step(&mut self) -> bool {
+        let visible_steps = self.get_visible_step_indices();
+        while let Some(step) = self.history.pop() {
+            if let Some(index) = visible_steps.iter().position(|&i| i == step) {
+                self.current_step_index = index;
+                return true;
             }
         }
+        if self.current_step_index > 0 {
+            self.current_step_index -= 1;
+            true
+        } else {
+            false
+        }
     }
 
-    fn is_step_visible_internal(&self, step_id: &str) -> bool {
+    /// Navigates to a specific step by ID. Returns true if successful.
+    pub fn go_to_step(&mut self, step_id: &str) -> bool {
         let uuid = match Uuid::parse_str(step_id) {
             Ok(u) => u,
             Err(_) => return false,
         };
 
-        for step in &self.schema.steps {
-            if step.id == uuid {
-                return evaluate_condition(&step.condition, &self.values);
+        let visible_steps = self.get_visible_step_indices();
+        for (visible_index, &actual_index) in visible_steps.iter().enumerate() {
+            if let Some(step) = self.schema.steps.get(actual_index) {
+                if step.id == uuid {
+                    // Going back retraces the route; going elsewhere extends it
+                    match self.history.iter().position(|&i| i == actual_index) {
+                        Some(taken) => self.history.truncate(taken),
+                        None => self.history.extend(
+                            self.current_schema_step().filter(|&i| i != actual_index),
+                        ),
+                    }
+                    self.current_step_index = visible_index;
+                    return true;
+                }
             }
         }
         false
     }
 
+    /// Returns true if we can advance to the next step.
+    pub fn can_go_next(&self) -> bool {
+        self.current_schema_step()
+            .and_then(|current| self.following_step(current))
+            .is_some()
+    }
+
+    /// Returns true if we can go back to the previous step.
+    pub fn can_go_prev(&self) -> bool {
+        self.current_step_index > 0
+    }
+
+    /// Returns progress as [current, total] (1-indexed for display).
+    ///
+    /// Steps count along the route: the ones taken so far and the ones the
+    /// jump rules lead to with the current answers. With the form's
+    /// `progress_mode` set to `estimated`, the total instead counts every
+    /// step after the next jump whose condition holds or depends on
+    /// unanswered fields, so it only shrinks as answers come in and the bar
+    /// never moves backwards.
+    pub fn progress(&self) -> Vec<u32> {
+        let current = self.history.len() + 1;
+        use crate::schema::ProgressMode;
+
+        let Some(position) = self.current_schema_step() else {
+            return vec![current as u32, current as u32];
+        };
+        let remaining = match self.schema.settings.progress_mode {
+            ProgressMode::Visible => {
+                std::iter::successors(self.following_step(position), |&i| self.following_step(i))
+                    .count()
+            }
+            ProgressMode::Estimated => match self.jump_start(position) {
+                None => 0,
+                Some(start) => (start..self.schema.steps.len())
+                    .filter(|&i| {
+                        self.condition_holds(ConditionTarget::Step(i))
+                            || self.depends_on_unanswered(&self.schema.steps[i].condition)
+                    })
+                    .count(),
+            },
+        };
+        vec![current as u32, (current + remaining) as u32]
+    }
+
+    /// Returns the completed fraction of the form, from 0.0 to 1.0.
+    pub fn progress_fraction(&self) -> f64 {
+        match self.progress()[..] {
+            [current, total] if total > 0 => f64::from(current) / f64::from(total),
+            _ => 0.0,
+        }
+    }
+
+    /// Returns true if currently on the last step, including steps whose
+    /// jump rules end the form.
+    pub fn is_last_step(&self) -> bool {
+        !self.can_go_next()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Internal methods (not exposed to JS)
+// ─────────────────────────────────────────────────────────────────────────────
+
+impl FormState {
+    fn validate_field_internal(&mut self, field_name: &str) {
+        // Find the field in the schema
+        let Some(&(step, field)) = self.graph.field_positions(field_name).first() else {
+            return;
+        };
+        let field = &self.schema.steps[step].fields[field];
+        let value = self.values.get(field_name).unwrap_or(&serde_json::Value::Null);
+        record_errors(&mut self.errors, field, value);
+    }
+
+    fn is_step_visible_internal(&self, step_id: &str) -> bool {
+        Uuid::parse_str(step_id)
+            .ok()
+            .and_then(|uuid| self.graph.step_index(&uuid))
+            .is_some_and(|index| self.condition_holds(ConditionTarget::Step(index)))
+    }
+
     fn is_field_visible_internal(&self, field_name: &str) -> bool {
-        for step in &self.schema.steps {
+        for &(step_index, field_index) in self.graph.field_positions(field_name) {
             // Skip if step is hidden
-            if !evaluate_condition(&step.condition, &self.values) {
+            if !self.condition_holds(ConditionTarget::Step(step_index)) {
                 continue;
             }
-
-            for field in &step.fields {
-                if field.name == field_name {
-                    return evaluate_condition(&field.condition, &self.values);
-                }
-            }
+            return self.condition_holds(ConditionTarget::Field(step_index, field_index));
         }
         false
     }
 
-    fn get_visible_step_indices(&self) -> Vec<usize> {
-        self.schema
-            .steps
+    /// Evaluates a step or field condition, reusing the cached result until a
+    /// field it reads changes.
+    fn condition_holds(&self, target: ConditionTarget) -> bool {
+        if let Some(&cached) = self.visibility.borrow().get(&target) {
+            return cached;
+        }
+
+        let condition = match target {
+            ConditionTarget::Step(step) => &self.schema.steps[step].condition,
+            ConditionTarget::Field(step, field) => {
+                &self.schema.steps[step].fields[field].condition
+            }
+        };
+        let holds = evaluate_condition(condition, &self.values);
+        self.visibility.borrow_mut().insert(target, holds);
+        holds
+    }
+
+    /// Returns true if the condition reads a field without an answer.
+    #[cfg(feature = "multi-step")]
+    fn depends_on_unanswered(&self, condition: &Option<ConditionRule>) -> bool {
+        condition.as_ref().is_some_and(|rule| {
+            match self.values.get(&rule.field) {
+                None | Some(serde_json::Value::Null) => true,
+                Some(serde_json::Value::String(s)) => s.is_empty(),
+                Some(serde_json::Value::Array(a)) => a.is_empty(),
+                Some(_) => false,
+            }
+        })
+    }
+
+    /// Returns the schema index of the step that follows step `from`, or
+    /// `None` once the form ends.
+    #[cfg(feature = "multi-step")]
+    fn following_step(&self, from: usize) -> Option<usize> {
+        let start = self.jump_start(from)?;
+        (start..self.schema.steps.len()).find(|&i| self.condition_holds(ConditionTarget::Step(i)))
+    }
+
+    /// Returns the schema index from which to look for the step that follows
+    /// step `from`: the target of the first jump rule that applies, or the
+    /// step after it. `None` if a rule ends the form.
+    ///
+    /// Rules naming no later step are skipped.
+    #[cfg(feature = "multi-step")]
+    fn jump_start(&self, from: usize) -> Option<usize> {
+        let step = &self.schema.steps[from];
+        let jump = step
+            .fields
             .iter()
             .enumerate()
-            .filter(|(_, step)| evaluate_condition(&step.condition, &self.values))
-            .map(|(i, _)| i)
+            .filter(|&(i, _)| self.condition_holds(ConditionTarget::Field(from, i)))
+            .flat_map(|(_, field)| &field.next)
+            .chain(&step.next)
+            .find_map(|rule| {
+                if !jump_applies(rule, &self.values) {
+                    return None;
+                }
+                match &rule.to {
+                    None => Some(None),
+                    Some(to) => self.graph.step_index(to).filter(|&i| i > from).map(Some),
+                }
+            });
+        match jump {
+            Some(target) => target,
+            None => Some(from + 1),
+        }
+    }
+
+    fn get_visible_step_indices(&self) -> Vec<usize> {
+        (0..self.schema.steps.len())
+            .filter(|&i| self.condition_holds(ConditionTarget::Step(i)))
             .collect()
     }
 }
 
 /// Evaluates a condition against form values.
+#[cfg(feature = "conditions")]
 fn evaluate_condition(
     condition: &Option<ConditionRule>,
     values: &HashMap<String, serde_json::Value>,
@@ -419,6 +680,51 @@ fn evaluate_condition(
     }
 }
 
+/// Without the `conditions` feature every step and field is visible.
+#[cfg(not(feature = "conditions"))]
+fn evaluate_condition(
+    _condition: &Option<ConditionRule>,
+    _values: &HashMap<String, serde_json::Value>,
+) -> bool {
+    true
+}
+
+/// Returns true if a jump rule applies with the given values.
+#[cfg(all(feature = "multi-step", feature = "conditions"))]
+fn jump_applies(rule: &JumpRule, values: &HashMap<String, serde_json::Value>) -> bool {
+    match &rule.when {
+        None => true,
+        Some(when) => when.evaluate(values),
+    }
+}
+
+/// Without the `conditions` feature only rules without a condition apply.
+#[cfg(all(feature = "multi-step", not(feature = "conditions")))]
+fn jump_applies(rule: &JumpRule, _values: &HashMap<String, serde_json::Value>) -> bool {
+    rule.when.is_none()
+}
+
+/// Validates a field, replacing its errors (and, for a repeatable group, its
+/// entries' errors) in `errors`. Returns the errors found.
+fn record_errors(
+    errors: &mut HashMap<String, Vec<String>>,
+    field: &FieldJson,
+    value: &serde_json::Value,
+) -> Vec<(String, Vec<String>)> {
+    let rows = format!("{}[", field.name);
+    errors.retain(|key, _| *key != field.name && !key.starts_with(&rows));
+
+    let mut found = Vec::new();
+    let field_errors = validate_field(field, value);
+    if !field_errors.is_empty() {
+        found.push((field.name.clone(), field_errors));
+    }
+    found.extend(validate_rows(field, value));
+
+    errors.extend(found.iter().cloned());
+    found
+}
+
 // Re-export for use in form_client
 impl FormState {
     /// Creates a FormState from a Rust FormJson struct.
@@ -435,16 +741,445 @@ impl FormState {
         }
 
         FormState {
+            graph: ConditionGraph::new(&schema),
             schema,
             values,
             errors: HashMap::new(),
             touched: HashSet::new(),
             current_step_index: 0,
+            #[cfg(feature = "multi-step")]
+            history: Vec::new(),
+            visibility: RefCell::new(HashMap::new()),
+            validation_delay_ms: 0,
+            pending_validation: HashSet::new(),
+            validating: HashMap::new(),
+            validation_seq: 0,
+            on_validation_change: None,
         }
     }
 
+    /// Replaces the step with the same ID, e.g. a lazy step with its
+    /// fields. Returns false if the form has no such step.
+    pub fn insert_step(&mut self, step: StepJson) -> bool {
+        let Some(index) = self.schema.steps.iter().position(|s| s.id == step.id) else {
+            return false;
+        };
+        for field in &step.fields {
+            if let Some(default) = &field.default_value {
+                self.values
+                    .entry(field.name.clone())
+                    .or_insert_with(|| default.clone());
+            }
+        }
+        self.schema.steps[index] = step;
+        self.graph = ConditionGraph::new(&self.schema);
+        self.visibility.borrow_mut().clear();
+        true
+    }
+
+    /// Applies a form fetched with `if_changed_since`: steps it lists as
+    /// unchanged are kept, the ones it holds replace or join them, and any
+    /// others are dropped. Without `unchanged`, `changes` is the whole form.
+    ///
+    /// Values are kept, and the respondent stays on the same step if it's
+    /// still there.
+    pub fn apply_changes(&mut self, changes: FormJson) {
+        let current = self.current_schema_step().map(|i| self.schema.steps[i].id);
+        #[cfg(feature = "multi-step")]
+        let history: Vec<Uuid> = self.history.iter().map(|&i| self.schema.steps[i].id).collect();
+
+        let mut steps = match &changes.unchanged {
+            Some(unchanged) => std::mem::take(&mut self.schema.steps)
+                .into_iter()
+                .filter(|s| unchanged.contains(&s.id))
+                .collect(),
+            None => Vec::new(),
+        };
+        for step in changes.steps {
+            for field in &step.fields {
+                if let Some(default) = &field.default_value {
+                    self.values
+                        .entry(field.name.clone())
+                        .or_insert_with(|| default.clone());
+                }
+            }
+            steps.retain(|s| s.id != step.id);
+            steps.push(step);
+        }
+        steps.sort_by_key(|s| s.order);
+
+        self.schema = FormJson {
+            steps,
+            unchanged: None,
+            ..changes
+        };
+        self.graph = ConditionGraph::new(&self.schema);
+        self.visibility.borrow_mut().clear();
+
+        let ids: Vec<Uuid> = self.schema.steps.iter().map(|s| s.id).collect();
+        let index_of = |id: Uuid| ids.iter().position(|&i| i == id);
+        #[cfg(feature = "multi-step")]
+        {
+            self.history = history.into_iter().filter_map(index_of).collect();
+        }
+        let visible = self.get_visible_step_indices();
+        self.current_step_index = current
+            .and_then(index_of)
+            .and_then(|index| visible.iter().position(|&i| i == index))
+            .unwrap_or_else(|| self.current_step_index.min(visible.len().saturating_sub(1)));
+    }
+
+    /// Sets a field value from Rust.
+    ///
+    /// Only the conditions that read `field` are re-evaluated on the next
+    /// visibility check.
+    pub fn set_json_value(&mut self, field: &str, value: serde_json::Value) {
+        self.values.insert(field.to_string(), value);
+
+        let mut visibility = self.visibility.borrow_mut();
+        for target in self.graph.dependents(field) {
+            visibility.remove(target);
+        }
+        drop(visibility);
+
+        // A new value supersedes any async check in flight
+        let was_validating = self.validating.remove(field).is_some();
+
+        // Re-validate if field was touched, now or after the delay
+        if self.touched.contains(field) {
+            if self.validation_delay_ms > 0 {
+                self.pending_validation.insert(field.to_string());
+            } else {
+                self.validate_field_internal(field);
+            }
+            self.notify_validation_change(field);
+        } else if was_validating {
+            self.notify_validation_change(field);
+        }
+    }
+
+    /// Runs the `on_validation_change` callback for a field.
+    fn notify_validation_change(&self, field: &str) {
+        let Some(callback) = &self.on_validation_change else {
+            return;
+        };
+        let errors = serde_wasm_bindgen::to_value(&self.get_errors(field)).unwrap_or(JsValue::NULL);
+        let _ = callback.call3(
+            &JsValue::NULL,
+            &JsValue::from_str(field),
+            &JsValue::from_bool(self.is_validating(Some(field.to_string()))),
+            &errors,
+        );
+    }
+
+    /// Returns the schema index of the current step, or `None` if no step
+    /// is visible.
+    pub fn current_schema_step(&self) -> Option<usize> {
+        self.get_visible_step_indices()
+            .get(self.current_step_index)
+            .copied()
+    }
+
     /// Gets all values as a Rust HashMap (for submission).
     pub fn values_map(&self) -> &HashMap<String, serde_json::Value> {
         &self.values
     }
+
+    /// Returns the answers to the shown fields of the step at schema index
+    /// `index`, e.g. to save the step.
+    pub fn step_values(&self, index: usize) -> serde_json::Map<String, serde_json::Value> {
+        let Some(step) = self.schema.steps.get(index) else {
+            return serde_json::Map::new();
+        };
+        step.fields
+            .iter()
+            .filter(|f| self.is_field_visible_internal(&f.name))
+            .filter_map(|f| Some((f.name.clone(), self.values.get(&f.name)?.clone())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> FormJson {
+        serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "name": "Pets",
+            "slug": "pets",
+            "steps": [
+                {
+                    "id": Uuid::new_v4(),
+                    "name": "About",
+                    "order": 0,
+                    "fields": [
+                        { "id": Uuid::new_v4(), "name": "has_pet", "label": "Pet?", "field_type": "text", "order": 0 },
+                        {
+                            "id": Uuid::new_v4(), "name": "pet_name", "label": "Name", "field_type": "text", "order": 1,
+                            "condition": { "field": "has_pet", "op": "eq", "value": "yes" }
+                        }
+                    ]
+                },
+                {
+                    "id": Uuid::new_v4(),
+                    "name": "Vet",
+                    "order": 1,
+                    "condition": { "field": "pet_name", "op": "is_not_empty", "value": null },
+                    "fields": [
+                        { "id": Uuid::new_v4(), "name": "vet", "label": "Vet", "field_type": "text", "order": 0 }
+                    ]
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "conditions")]
+    fn test_cached_visibility_follows_changes() {
+        let mut state = FormState::from_schema(schema());
+        assert!(!state.is_field_visible("pet_name"));
+        assert!(!state.is_field_visible("vet"));
+
+        state.set_json_value("has_pet", json!("yes"));
+        assert!(state.is_field_visible("pet_name"));
+        assert!(!state.is_field_visible("vet"));
+
+        state.set_json_value("pet_name", json!("Rex"));
+        assert!(state.is_field_visible("vet"));
+
+        state.set_json_value("has_pet", json!("no"));
+        assert!(!state.is_field_visible("pet_name"));
+        // The vet step reads only `pet_name`, which still has a value
+        assert!(state.is_field_visible("vet"));
+    }
+
+    #[test]
+    fn test_resolve_text_quotes_answers() {
+        let mut schema = schema();
+        schema.steps[0].fields[0].options = serde_json::from_value(json!([
+            { "id": Uuid::new_v4(), "label": "Yes, a dog", "value": "yes", "order": 0 }
+        ]))
+        .unwrap();
+        let mut state = FormState::from_schema(schema);
+        let text = "Tell us about {{field:pet_name|your pet}} ({{ field:has_pet }})";
+        assert_eq!(state.resolve_text(text), "Tell us about your pet ()");
+
+        state.set_json_value("has_pet", json!("yes"));
+        state.set_json_value("pet_name", json!("Rex"));
+        assert_eq!(state.resolve_text(text), "Tell us about Rex (Yes, a dog)");
+        assert_eq!(state.resolve_text("{{pet_name}} {{field:"), "{{pet_name}} {{field:");
+    }
+
+    #[test]
+    #[cfg(feature = "conditions")]
+    fn test_step_values_leave_out_hidden_fields() {
+        let mut state = FormState::from_schema(schema());
+        state.set_json_value("has_pet", json!("no"));
+        state.set_json_value("pet_name", json!("Rex"));
+        state.set_json_value("vet", json!("Dr. No"));
+        assert_eq!(serde_json::Value::from(state.step_values(0)), json!({ "has_pet": "no" }));
+
+        state.set_json_value("has_pet", json!("yes"));
+        assert_eq!(
+            serde_json::Value::from(state.step_values(0)),
+            json!({ "has_pet": "yes", "pet_name": "Rex" })
+        );
+        assert!(state.step_values(5).is_empty());
+    }
+
+    #[test]
+    fn test_validation_delay_defers_errors() {
+        let mut schema = schema();
+        schema.steps[0].fields[0].validation.min_length = Some(3);
+        let mut state = FormState::from_schema(schema);
+        state.set_validation_delay(300);
+        state.mark_touched("has_pet");
+
+        state.set_json_value("has_pet", json!("y"));
+        assert!(state.is_validating(Some("has_pet".to_string())));
+        assert!(state.get_errors("has_pet").is_empty());
+
+        state.set_json_value("has_pet", json!("ye"));
+        assert!(state.flush_validation(Some("has_pet".to_string())));
+        assert!(!state.is_validating(None));
+        assert!(!state.get_errors("has_pet").is_empty());
+
+        assert!(!state.flush_validation(None));
+    }
+
+    #[test]
+    fn test_stale_async_results_are_ignored() {
+        let mut state = FormState::from_schema(schema());
+
+        let first = state.begin_async_validation("vet");
+        let second = state.begin_async_validation("vet");
+        assert!(state.is_validating(Some("vet".to_string())));
+
+        assert!(!state.finish_async_validation("vet", first, vec!["Taken".to_string()]));
+        assert!(state.get_errors("vet").is_empty());
+
+        assert!(state.finish_async_validation("vet", second, vec!["Taken".to_string()]));
+        assert_eq!(state.get_errors("vet"), vec!["Taken".to_string()]);
+        assert!(!state.is_validating(None));
+
+        // Changing the value drops the check in flight
+        let third = state.begin_async_validation("vet");
+        state.set_json_value("vet", json!("Dr. No"));
+        assert!(!state.is_validating(None));
+        assert!(!state.finish_async_validation("vet", third, Vec::new()));
+    }
+
+    #[test]
+    #[cfg(all(feature = "multi-step", feature = "conditions"))]
+    fn test_current_schema_step_skips_hidden_steps() {
+        let mut schema = schema();
+        // As rendered by the server: its operator name and no value
+        schema.steps[1].condition =
+            Some(serde_json::from_value(json!({ "field": "pet_name", "op": "not_empty" })).unwrap());
+        schema.steps.push(
+            serde_json::from_value(json!({ "id": Uuid::new_v4(), "name": "Done", "order": 2, "fields": [] }))
+                .unwrap(),
+        );
+        let mut state = FormState::from_schema(schema);
+        assert_eq!(state.current_schema_step(), Some(0));
+
+        assert!(state.next_step());
+        assert_eq!(state.current_schema_step(), Some(2));
+
+        state.set_json_value("pet_name", json!("Rex"));
+        assert!(state.prev_step());
+        assert!(state.next_step());
+        assert_eq!(state.current_schema_step(), Some(1));
+    }
+
+    #[test]
+    #[cfg(all(feature = "multi-step", feature = "conditions"))]
+    fn test_next_step_follows_jump_rules() {
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let schema: FormJson = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "name": "Plans",
+            "slug": "plans",
+            "steps": [
+                {
+                    "id": ids[0], "name": "Plan", "order": 0,
+                    "next": [{ "when": { "field": "plan", "op": "eq", "value": "none" } }],
+                    "fields": [{
+                        "id": Uuid::new_v4(), "name": "plan", "label": "Plan", "field_type": "text", "order": 0,
+                        "next": [{ "when": { "field": "plan", "op": "eq", "value": "business" }, "to": ids[2] }]
+                    }]
+                },
+                { "id": ids[1], "name": "Personal", "order": 1, "fields": [] },
+                { "id": ids[2], "name": "Company", "order": 2, "fields": [] }
+            ]
+        }))
+        .unwrap();
+        let mut state = FormState::from_schema(schema);
+        assert_eq!(state.progress(), vec![1, 3]);
+
+        state.set_json_value("plan", json!("business"));
+        assert_eq!(state.progress(), vec![1, 2]);
+        assert!(state.next_step());
+        assert_eq!(state.current_schema_step(), Some(2));
+        assert_eq!(state.progress(), vec![2, 2]);
+        assert!(state.is_last_step());
+
+        // Going back retraces the jump
+        assert!(state.prev_step());
+        assert_eq!(state.current_schema_step(), Some(0));
+        assert!(!state.can_go_prev());
+
+        state.set_json_value("plan", json!("none"));
+        assert!(state.is_last_step());
+        assert!(!state.next_step());
+        assert_eq!(state.progress(), vec![1, 1]);
+
+        state.set_json_value("plan", json!("personal"));
+        assert!(state.next_step());
+        assert_eq!(state.current_schema_step(), Some(1));
+    }
+
+    #[test]
+    fn test_lazy_steps_are_filled_in() {
+        let full = schema();
+        let mut skeleton = full.clone();
+        for step in &mut skeleton.steps {
+            step.fields.clear();
+            step.lazy = true;
+        }
+        let mut state = FormState::from_schema(skeleton);
+        let first = full.steps[0].id.to_string();
+        assert_eq!(state.pending_step(), Some(first.clone()));
+        assert!(!state.is_step_loaded(&first));
+
+        let mut about = full.steps[0].clone();
+        about.fields[0].default_value = Some(json!("yes"));
+        assert!(state.insert_step(about));
+        assert_eq!(state.pending_step(), None);
+        assert!(state.is_step_loaded(&first));
+        assert_eq!(state.values_map().get("has_pet"), Some(&json!("yes")));
+
+        let mut unknown = full.steps[1].clone();
+        unknown.id = Uuid::new_v4();
+        assert!(!state.insert_step(unknown));
+    }
+
+    #[test]
+    #[cfg(feature = "multi-step")]
+    fn test_apply_changes_patches_steps_in_place() {
+        let schema = schema();
+        let mut state = FormState::from_schema(schema.clone());
+        state.set_json_value("has_pet", json!("yes"));
+        state.set_json_value("pet_name", json!("Rex"));
+        assert!(state.next_step());
+        assert_eq!(state.current_schema_step(), Some(1));
+
+        // The first step is removed and the second gains a field
+        let mut vet = schema.steps[1].clone();
+        vet.fields.push(
+            serde_json::from_value(json!({
+                "id": Uuid::new_v4(), "name": "clinic", "label": "Clinic", "field_type": "text",
+                "order": 1, "default_value": "Main St"
+            }))
+            .unwrap(),
+        );
+        let changes = FormJson {
+            updated_at: Some("2026-01-01T00:00:00Z".to_string()),
+            steps: vec![vet],
+            unchanged: Some(Vec::new()),
+            ..schema.clone()
+        };
+        state.apply_changes(changes);
+
+        assert_eq!(state.updated_at().as_deref(), Some("2026-01-01T00:00:00Z"));
+        assert_eq!(state.schema.steps.len(), 1);
+        assert_eq!(state.schema.steps[0].fields.len(), 2);
+        assert_eq!(state.current_schema_step(), Some(0));
+        assert_eq!(state.values_map().get("pet_name"), Some(&json!("Rex")));
+        assert_eq!(state.values_map().get("clinic"), Some(&json!("Main St")));
+        assert!(!state.can_go_prev());
+
+        // Unchanged steps are kept as they are
+        let changes = FormJson {
+            steps: Vec::new(),
+            unchanged: Some(vec![schema.steps[1].id]),
+            ..schema
+        };
+        state.apply_changes(changes);
+        assert_eq!(state.schema.steps[0].fields.len(), 2);
+    }
+
+    #[test]
+    fn test_unrelated_change_keeps_cached_results() {
+        let mut state = FormState::from_schema(schema());
+        state.set_json_value("has_pet", json!("yes"));
+        assert!(state.is_field_visible("pet_name"));
+        let cached = state.visibility.borrow().len();
+
+        state.set_json_value("vet", json!("Dr. Who"));
+        assert_eq!(state.visibility.borrow().len(), cached);
+    }
 }
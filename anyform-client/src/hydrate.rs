@@ -3,24 +3,23 @@
 //! This module enables automatic hydration of server-rendered forms,
 //! adding client-side interactivity (validation, step navigation, conditions).
 
+use crate::api::{self, FetchOptions};
 use crate::form_state::FormState;
+use crate::piping;
 use crate::schema::{FieldJson, FormJson, StepJson, ValidationRules, ValueType};
 use std::cell::RefCell;
 use std::rc::Rc;
 use uuid::Uuid;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
-use web_sys::{Document, Element, Event, HtmlFormElement, HtmlInputElement};
+use web_sys::{Document, Element, Event, HtmlFormElement, HtmlInputElement, KeyboardEvent};
 
 /// Hydrates all forms on the page with `data-af-form` attribute.
 #[wasm_bindgen]
 pub fn hydrate_all() {
-    let document = match web_sys::window().and_then(|w| w.document()) {
-        Some(d) => d,
-        None => {
-            console_log("No document available");
-            return;
-        }
+    // Nothing to hydrate outside a browser, e.g. during server-side rendering
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return;
     };
 
     let forms = match document.query_selector_all("[data-af-form]") {
@@ -48,12 +47,9 @@ pub fn hydrate_all() {
 /// Hydrates a specific form by slug.
 #[wasm_bindgen]
 pub fn hydrate(slug: &str) {
-    let document = match web_sys::window().and_then(|w| w.document()) {
-        Some(d) => d,
-        None => {
-            console_log("No document available");
-            return;
-        }
+    // Nothing to hydrate outside a browser, e.g. during server-side rendering
+    let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+        return;
     };
 
     let selector = format!("[data-af-form=\"{}\"]", slug);
@@ -79,18 +75,26 @@ fn hydrate_form_element(_document: &Document, form: HtmlFormElement, slug: &str)
     };
 
     // Create form state
-    let state = FormState::from_schema(schema);
+    let mut state = FormState::from_schema(schema);
+    state.set_validation_delay(VALIDATION_DELAY_MS);
     let state = Rc::new(RefCell::new(state));
 
     // Bind input events
-    bind_input_events(&form, state.clone());
+    bind_input_events(&form, state.clone(), slug);
 
-    // Bind navigation events
-    bind_navigation_events(&form, state.clone());
+    // Bind navigation events, saving each step if the form asks to
+    #[cfg(feature = "multi-step")]
+    bind_navigation_events(&form, state.clone(), StepSaver::new(&form, slug));
 
     // Bind form submission
     bind_submit_event(&form, state.clone());
 
+    // Bind help tooltips
+    bind_tooltips(&form);
+
+    // Bind "use my location" buttons
+    bind_locations(&form);
+
     // Initial visibility update
     update_visibility(&form, &state.borrow());
 
@@ -119,7 +123,8 @@ fn parse_form_schema(form: &HtmlFormElement, slug: &str) -> Option<FormJson> {
                         for field_idx in 0..field_elements.length() {
                             if let Some(field_node) = field_elements.get(field_idx) {
                                 if let Ok(field_el) = field_node.dyn_into::<Element>() {
-                                    if let Some(field) = parse_field(&field_el, field_idx as i32) {
+                                    let id = hydrated_id(step_idx, field_idx + 1);
+                                    if let Some(field) = parse_field(&field_el, id, field_idx as i32) {
                                         fields.push(field);
                                     }
                                 }
@@ -128,11 +133,15 @@ fn parse_form_schema(form: &HtmlFormElement, slug: &str) -> Option<FormJson> {
                     }
 
                     steps.push(StepJson {
-                        id: Uuid::new_v4(),
+                        id: hydrated_id(step_idx, 0),
                         name: format!("Step {}", step_idx + 1),
                         description: None,
                         order: step_idx as i32,
                         condition,
+                        next: Vec::new(),
+                        updated_at: None,
+                        etag: None,
+                        lazy: false,
                         fields,
                     });
                 }
@@ -148,7 +157,8 @@ fn parse_form_schema(form: &HtmlFormElement, slug: &str) -> Option<FormJson> {
             for field_idx in 0..field_elements.length() {
                 if let Some(field_node) = field_elements.get(field_idx) {
                     if let Ok(field_el) = field_node.dyn_into::<Element>() {
-                        if let Some(field) = parse_field(&field_el, field_idx as i32) {
+                        let id = hydrated_id(0, field_idx + 1);
+                        if let Some(field) = parse_field(&field_el, id, field_idx as i32) {
                             fields.push(field);
                         }
                     }
@@ -156,31 +166,52 @@ fn parse_form_schema(form: &HtmlFormElement, slug: &str) -> Option<FormJson> {
             }
 
             steps.push(StepJson {
-                id: Uuid::new_v4(),
+                id: hydrated_id(0, 0),
                 name: "Main".to_string(),
                 description: None,
                 order: 0,
                 condition: None,
+                next: Vec::new(),
+                updated_at: None,
+                etag: None,
+                lazy: false,
                 fields,
             });
         }
     }
 
     Some(FormJson {
-        id: Uuid::new_v4(),
+        id: Uuid::nil(),
         name: slug.to_string(),
         slug: slug.to_string(),
         description: None,
         action_url: None,
         action_method: None,
+        closed: false,
         settings: Default::default(),
+        updated_at: None,
         steps,
+        unchanged: None,
     })
 }
 
+/// Returns the ID of a hydrated step (`field` 0) or field (`field` from 1).
+///
+/// The markup carries no IDs; deterministic ones keep a random number
+/// generator out of the bundle.
+fn hydrated_id(step: u32, field: u32) -> Uuid {
+    Uuid::from_u64_pair(u64::from(step) + 1, u64::from(field))
+}
+
 /// Parses a field from an element.
-fn parse_field(field_el: &Element, order: i32) -> Option<FieldJson> {
+fn parse_field(field_el: &Element, id: Uuid, order: i32) -> Option<FieldJson> {
     let field_name = field_el.get_attribute("data-af-field")?;
+    Some(parse_field_named(field_el, field_name, id, order))
+}
+
+/// Parses a field from an element, under the given name.
+fn parse_field_named(field_el: &Element, field_name: String, id: Uuid, order: i32) -> FieldJson {
+    let repeater = field_el.has_attribute("data-af-repeater");
 
     // Parse validation rules
     let validation: ValidationRules = field_el
@@ -193,19 +224,35 @@ fn parse_field(field_el: &Element, order: i32) -> Option<FieldJson> {
         .get_attribute("data-af-condition")
         .and_then(|c| serde_json::from_str(&c).ok());
 
-    // Get label from label element
+    // Get label from label element (a repeatable group's legend)
     let label = field_el
-        .query_selector("label")
+        .query_selector(if repeater { "legend" } else { "label" })
         .ok()
         .flatten()
         .and_then(|l| l.text_content())
         .unwrap_or_else(|| field_name.clone());
 
     // Determine field type from input type
-    let field_type = determine_field_type(field_el);
+    let field_type = if repeater {
+        ValueType::Repeater
+    } else {
+        determine_field_type(field_el)
+    };
 
-    Some(FieldJson {
-        id: Uuid::new_v4(),
+    // A repeatable group's sub-fields, as laid out in its first entry
+    let mut fields = Vec::new();
+    if let (true, Ok(Some(row))) = (repeater, field_el.query_selector(".af-rows > .af-row")) {
+        let (hi, lo) = id.as_u64_pair();
+        for (i, sub_el) in sub_field_elements(&row).into_iter().enumerate() {
+            if let Some(name) = sub_el.get_attribute("data-af-subfield") {
+                let sub_id = Uuid::from_u64_pair(hi, (lo << 16) | (i as u64 + 1));
+                fields.push(parse_field_named(&sub_el, name, sub_id, i as i32));
+            }
+        }
+    }
+
+    FieldJson {
+        id,
         name: field_name,
         label,
         field_type,
@@ -214,14 +261,40 @@ fn parse_field(field_el: &Element, order: i32) -> Option<FieldJson> {
         default_value: None,
         validation,
         condition: field_condition,
+        next: Vec::new(),
         options: vec![],
+        fields,
         order,
-    })
+    }
+}
+
+/// Returns the sub-field elements of a repeatable group's entry.
+fn sub_field_elements(row: &Element) -> Vec<Element> {
+    let Ok(nodes) = row.query_selector_all(".af-subfield") else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.get(i))
+        .filter_map(|node| node.dyn_into::<Element>().ok())
+        .collect()
+}
+
+/// Returns the entries of a repeatable group.
+fn row_elements(field_el: &Element) -> Vec<Element> {
+    let Ok(nodes) = field_el.query_selector_all(".af-rows > .af-row") else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|i| nodes.get(i))
+        .filter_map(|node| node.dyn_into::<Element>().ok())
+        .collect()
 }
 
 /// Determines field type from input element.
 fn determine_field_type(field_el: &Element) -> ValueType {
-    if let Ok(Some(input)) = field_el.query_selector("input") {
+    if field_el.query_selector("[data-af-location]").ok().flatten().is_some() {
+        ValueType::Location
+    } else if let Ok(Some(input)) = field_el.query_selector("input") {
         let input_type = input.get_attribute("type").unwrap_or_default();
         match input_type.as_str() {
             "email" => ValueType::Email,
@@ -231,6 +304,7 @@ fn determine_field_type(field_el: &Element) -> ValueType {
             "date" => ValueType::Date,
             "time" => ValueType::Time,
             "datetime-local" => ValueType::Datetime,
+            "checkbox" if is_checkbox_group(&input) => ValueType::MultiSelect,
             "checkbox" => ValueType::Checkbox,
             "radio" => ValueType::Radio,
             "file" => ValueType::File,
@@ -249,8 +323,23 @@ fn determine_field_type(field_el: &Element) -> ValueType {
     }
 }
 
+/// How long a field waits after the last keystroke before it is validated
+/// (and, for `server_validate` fields, checked by the server).
+const VALIDATION_DELAY_MS: u32 = 300;
+
+/// Debounce state for one field.
+#[derive(Default)]
+struct Debounce {
+    /// Pending timer, if validation is scheduled.
+    timeout: Option<i32>,
+}
+
 /// Binds input change events.
-fn bind_input_events(form: &HtmlFormElement, state: Rc<RefCell<FormState>>) {
+///
+/// Typing updates the value at once but validates only after a pause, so
+/// errors don't flash mid-word; leaving the field (`change`) validates
+/// immediately. Fields show `af-validating` while a check is pending.
+fn bind_input_events(form: &HtmlFormElement, state: Rc<RefCell<FormState>>, slug: &str) {
     let form_element: &Element = form.as_ref();
     let field_elements = match form_element.query_selector_all(".af-field") {
         Ok(els) => els,
@@ -268,46 +357,322 @@ fn bind_input_events(form: &HtmlFormElement, state: Rc<RefCell<FormState>>) {
             continue;
         };
 
-        // Find the actual input element
-        let input = field_el
-            .query_selector("input, textarea, select")
-            .ok()
-            .flatten();
+        // Radio and checkbox groups have one input per option; repeatable
+        // groups gain and lose inputs as entries come and go, so they listen
+        // for their inputs' events on the group instead
+        let targets: Vec<web_sys::EventTarget> = if field_el.has_attribute("data-af-repeater") {
+            bind_repeater_buttons(&field_el);
+            vec![field_el.clone().into()]
+        } else {
+            match field_el.query_selector_all("input, textarea, select") {
+                Ok(inputs) => (0..inputs.length())
+                    .filter_map(|j| inputs.get(j))
+                    .map(Into::into)
+                    .collect(),
+                Err(_) => continue,
+            }
+        };
+        if targets.is_empty() {
+            continue;
+        }
 
-        if let Some(input) = input {
-            let state_clone = state.clone();
-            let field_name_clone = field_name.clone();
-            let form_clone = form.clone();
+        let check = FieldCheck {
+            form: form.clone(),
+            state: state.clone(),
+            slug: slug.to_string(),
+            field_name,
+            // Fields flagged `server_validate` are also checked by the server
+            server_validate: field_el
+                .get_attribute("data-af-validation")
+                .and_then(|v| serde_json::from_str::<ValidationRules>(&v).ok())
+                .is_some_and(|rules| rules.server_validate),
+            debounce: Rc::new(RefCell::new(Debounce::default())),
+        };
+
+        let closure = Closure::wrap(Box::new(move |event: Event| {
+            let value = get_field_value(&field_el);
+            let committed = event.type_() == "change";
+            {
+                let mut state = check.state.borrow_mut();
+                state.set_json_value(&check.field_name, value.clone());
+                if committed {
+                    state.mark_touched(&check.field_name);
+                }
 
-            let closure = Closure::wrap(Box::new(move |event: Event| {
-                if let Some(target) = event.target() {
-                    let value = get_input_value(&target.unchecked_into());
-                    let mut state = state_clone.borrow_mut();
+                // Update visibility after value change
+                update_visibility(&check.form, &state);
 
-                    state.set_value(
-                        &field_name_clone,
-                        serde_wasm_bindgen::to_value(&value).unwrap_or(JsValue::NULL),
-                    );
-                    state.mark_touched(&field_name_clone);
+                // Quote the new answer wherever labels pipe it in
+                update_pipes(&check.form, &check.field_name, &field_el);
+
+                // Update error display
+                update_field_errors(
+                    &check.form,
+                    &check.field_name,
+                    &state.get_errors(&check.field_name),
+                );
+                update_row_errors(&check.form, &check.field_name, &state);
+            }
+            check.schedule(value);
+        }) as Box<dyn FnMut(Event)>);
+
+        for target in &targets {
+            let _ = target.add_event_listener_with_callback("input", closure.as_ref().unchecked_ref());
+            let _ = target.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref());
+        }
+
+        // Prevent closure from being dropped
+        closure.forget();
+    }
+}
+
+/// Scheduled validation of one field.
+#[derive(Clone)]
+struct FieldCheck {
+    form: HtmlFormElement,
+    state: Rc<RefCell<FormState>>,
+    slug: String,
+    field_name: String,
+    server_validate: bool,
+    debounce: Rc<RefCell<Debounce>>,
+}
+
+impl FieldCheck {
+    /// Debounces validation of the field after a change.
+    ///
+    /// Each call supersedes the previous one: a pending timer is cancelled,
+    /// and `FormState` drops results of server checks for older values.
+    fn schedule(&self, value: serde_json::Value) {
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+
+        let mut debounce = self.debounce.borrow_mut();
+        if let Some(handle) = debounce.timeout.take() {
+            window.clear_timeout_with_handle(handle);
+        }
+
+        let (validating, delay) = {
+            let state = self.state.borrow();
+            let pending = state.is_validating(Some(self.field_name.clone()));
+            (
+                pending || (self.server_validate && state.is_touched(&self.field_name)),
+                state.validation_delay(),
+            )
+        };
+        if !validating {
+            update_field_validating(&self.form, &self.field_name, false);
+            return;
+        }
+        update_field_validating(&self.form, &self.field_name, true);
+
+        let check = self.clone();
+        let callback = Closure::once_into_js(move || {
+            check.debounce.borrow_mut().timeout = None;
+            check.run(value);
+        });
+
+        if let Ok(handle) = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+            callback.unchecked_ref(),
+            delay as i32,
+        ) {
+            debounce.timeout = Some(handle);
+        }
+    }
 
-                    // Update visibility after value change
-                    update_visibility(&form_clone, &state);
+    /// Runs deferred local validation, then the server check if the value
+    /// passed locally.
+    fn run(self, value: serde_json::Value) {
+        let (errors, seq) = {
+            let mut state = self.state.borrow_mut();
+            state.flush_validation(Some(self.field_name.clone()));
+            let errors = state.get_errors(&self.field_name);
+
+            // Nothing for the server to check
+            let checkable = !value.is_null() && value.as_str() != Some("");
+            let seq = (self.server_validate && errors.is_empty() && checkable)
+                .then(|| state.begin_async_validation(&self.field_name));
+            (errors, seq)
+        };
+
+        update_field_errors(&self.form, &self.field_name, &errors);
+        update_row_errors(&self.form, &self.field_name, &self.state.borrow());
+        let Some(seq) = seq else {
+            update_field_validating(&self.form, &self.field_name, false);
+            return;
+        };
 
-                    // Update error display
-                    let errors = state.get_errors(&field_name_clone);
-                    update_field_errors(&form_clone, &field_name_clone, &errors);
+        let values = serde_json::to_value(self.state.borrow().values_map()).unwrap_or_default();
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = api::validate_field(
+                "",
+                &self.slug,
+                &self.field_name,
+                &value,
+                &values,
+                seq.into(),
+                &FetchOptions::default(),
+            )
+            .await;
+            let errors = match result {
+                Ok(result) => result.errors,
+                Err(e) => {
+                    console_log(&format!("Server validation failed: {}", e));
+                    Vec::new()
                 }
-            }) as Box<dyn FnMut(Event)>);
+            };
+
+            let mut state = self.state.borrow_mut();
+            if state.finish_async_validation(&self.field_name, seq, errors) {
+                update_field_errors(&self.form, &self.field_name, &state.get_errors(&self.field_name));
+                update_field_validating(&self.form, &self.field_name, false);
+            }
+        });
+    }
+}
 
-            let _ = input.add_event_listener_with_callback("input", closure.as_ref().unchecked_ref());
-            let _ = input.add_event_listener_with_callback("change", closure.as_ref().unchecked_ref());
+/// Gets the value of a field from its inputs.
+///
+/// A radio group gives the checked option (or null), a checkbox group
+/// (`name="x[]"`) the array of checked options, a repeatable group an array
+/// with an object of sub-field values per entry, and a location an object
+/// of its parts (or null, without coordinates).
+fn get_field_value(field_el: &Element) -> serde_json::Value {
+    if let Ok(Some(location)) = field_el.query_selector("[data-af-location]") {
+        return get_location_value(&location);
+    }
+
+    if field_el.has_attribute("data-af-repeater") {
+        let rows = row_elements(field_el).into_iter().map(|row| {
+            let values = sub_field_elements(&row).into_iter().filter_map(|sub_el| {
+                let key = sub_el.get_attribute("data-af-subfield")?;
+                Some((key, get_field_value(&sub_el)))
+            });
+            serde_json::Value::Object(values.collect())
+        });
+        return serde_json::Value::Array(rows.collect());
+    }
 
-            // Prevent closure from being dropped
-            closure.forget();
+    let Ok(nodes) = field_el.query_selector_all("input, textarea, select") else {
+        return serde_json::Value::Null;
+    };
+    let inputs: Vec<Element> = (0..nodes.length())
+        .filter_map(|i| nodes.get(i))
+        .filter_map(|node| node.dyn_into::<Element>().ok())
+        .collect();
+    let Some(first) = inputs.first() else {
+        return serde_json::Value::Null;
+    };
+
+    let checked = || {
+        inputs
+            .iter()
+            .filter_map(|el| el.dyn_ref::<HtmlInputElement>())
+            .filter(|input| input.checked())
+            .map(|input| serde_json::Value::String(input.value()))
+    };
+    match first.get_attribute("type").as_deref() {
+        Some("radio") => checked().next().unwrap_or(serde_json::Value::Null),
+        Some("checkbox") if is_checkbox_group(first) => {
+            serde_json::Value::Array(checked().collect())
         }
+        _ => get_input_value(first),
     }
 }
 
+/// Quotes a field's answer in the `[data-af-pipe]` spans piping it into
+/// labels, descriptions and help text, or their fallbacks while it's
+/// unanswered.
+fn update_pipes(form: &HtmlFormElement, field_name: &str, field_el: &Element) {
+    let form_element: &Element = form.as_ref();
+    let Ok(pipes) = form_element.query_selector_all(&format!("[data-af-pipe=\"{field_name}\"]"))
+    else {
+        return;
+    };
+    if pipes.length() == 0 {
+        return;
+    }
+
+    let answer = get_answer_text(field_el);
+    for i in 0..pipes.length() {
+        let Some(pipe) = pipes.get(i).and_then(|node| node.dyn_into::<Element>().ok()) else {
+            continue;
+        };
+        let text = match answer.trim() {
+            "" => pipe.get_attribute("data-af-fallback").unwrap_or_default(),
+            _ => answer.clone(),
+        };
+        pipe.set_text_content(Some(&text));
+    }
+}
+
+/// Gets a field's answer as piped into labels: choices by the text of
+/// their options, several joined with commas.
+fn get_answer_text(field_el: &Element) -> String {
+    let texts = |selector: &str, of_label: bool| -> Vec<String> {
+        let Ok(nodes) = field_el.query_selector_all(selector) else {
+            return Vec::new();
+        };
+        (0..nodes.length())
+            .filter_map(|i| nodes.get(i))
+            .filter_map(|node| node.dyn_into::<Element>().ok())
+            .filter_map(|el| if of_label { el.closest("label").ok().flatten() } else { Some(el) })
+            .filter_map(|el| el.text_content())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect()
+    };
+
+    if let Ok(Some(select)) = field_el.query_selector("select") {
+        let value = get_input_value(&select);
+        return match value.as_str() {
+            Some("") | None => String::new(),
+            Some(_) => texts("option:checked", false).join(", "),
+        };
+    }
+    let is_choice = field_el
+        .query_selector("input[type=\"radio\"], input[type=\"checkbox\"][name$=\"[]\"]")
+        .ok()
+        .flatten()
+        .is_some();
+    if is_choice {
+        return texts("input:checked", true).join(", ");
+    }
+    piping::answer_text(&get_field_value(field_el), &[])
+}
+
+/// Gets a location's `{lat, lng, accuracy, label}` from its inputs;
+/// coordinates that aren't numbers are kept as typed, for validation to
+/// reject.
+fn get_location_value(location: &Element) -> serde_json::Value {
+    let part = |key: &str| {
+        location
+            .query_selector(&format!("input[name$=\"[{key}]\"]"))
+            .ok()
+            .flatten()
+            .map(|input| get_input_value(&input))
+            .filter(|value| !value.is_null() && value.as_str() != Some(""))
+    };
+    let (lat, lng) = (part("lat"), part("lng"));
+    if lat.is_none() && lng.is_none() {
+        return serde_json::Value::Null;
+    }
+    let mut value = serde_json::json!({ "lat": lat, "lng": lng });
+    for key in ["accuracy", "label"] {
+        if let Some(part) = part(key) {
+            value[key] = part;
+        }
+    }
+    value
+}
+
+/// Returns true if the input is one option of a checkbox group.
+fn is_checkbox_group(input: &Element) -> bool {
+    input
+        .get_attribute("name")
+        .is_some_and(|name| name.ends_with("[]"))
+}
+
 /// Gets the value from an input element.
 fn get_input_value(input: &Element) -> serde_json::Value {
     if let Ok(input_el) = input.clone().dyn_into::<HtmlInputElement>() {
@@ -337,7 +702,12 @@ fn get_input_value(input: &Element) -> serde_json::Value {
 }
 
 /// Binds navigation button events.
-fn bind_navigation_events(form: &HtmlFormElement, state: Rc<RefCell<FormState>>) {
+#[cfg(feature = "multi-step")]
+fn bind_navigation_events(
+    form: &HtmlFormElement,
+    state: Rc<RefCell<FormState>>,
+    saver: Option<StepSaver>,
+) {
     let form_element: &Element = form.as_ref();
 
     // Prev button
@@ -364,8 +734,12 @@ fn bind_navigation_events(form: &HtmlFormElement, state: Rc<RefCell<FormState>>)
 
         let closure = Closure::wrap(Box::new(move |_: Event| {
             let mut state = state_clone.borrow_mut();
+            let done = state.current_schema_step();
 
             if state.next_step() {
+                if let (Some(saver), Some(index)) = (&saver, done) {
+                    saver.save(index, state.step_values(index));
+                }
                 update_step_visibility(&form_clone, &state);
                 update_navigation_buttons(&form_clone, &state);
             }
@@ -379,12 +753,180 @@ fn bind_navigation_events(form: &HtmlFormElement, state: Rc<RefCell<FormState>>)
     update_navigation_buttons(form, &state.borrow());
 }
 
+/// Field naming the in-progress submission the form's steps were saved to;
+/// the server discards it once the form is submitted.
+#[cfg(feature = "multi-step")]
+const DRAFT_FIELD: &str = "_af_submission";
+
+/// Saves each completed step of a form rendered with `data-af-save-steps`
+/// to an in-progress submission, so answers aren't lost if the respondent
+/// leaves before the end.
+///
+/// The submission's ID is kept in session storage, so reloading the page
+/// carries on with it, and sent with the form in a hidden input.
+#[cfg(feature = "multi-step")]
+#[derive(Clone)]
+struct StepSaver {
+    form: HtmlFormElement,
+    slug: String,
+    submission_id: Rc<RefCell<Option<String>>>,
+}
+
+#[cfg(feature = "multi-step")]
+impl StepSaver {
+    /// Returns a saver if the form saves each step.
+    fn new(form: &HtmlFormElement, slug: &str) -> Option<Self> {
+        if !form.has_attribute("data-af-save-steps") {
+            return None;
+        }
+        let saver = Self {
+            form: form.clone(),
+            slug: slug.to_string(),
+            submission_id: Rc::new(RefCell::new(None)),
+        };
+        if let Some(id) =
+            session_storage().and_then(|s| s.get_item(&saver.storage_key()).ok().flatten())
+        {
+            saver.set_submission_id(Some(id));
+        }
+        Some(saver)
+    }
+
+    fn storage_key(&self) -> String {
+        format!("af-draft:{}", self.slug)
+    }
+
+    /// Remembers the submission, or forgets it with `None`.
+    fn set_submission_id(&self, id: Option<String>) {
+        let storage = session_storage();
+        match &id {
+            Some(id) => {
+                if let Some(storage) = &storage {
+                    let _ = storage.set_item(&self.storage_key(), id);
+                }
+                set_hidden_input(&self.form, DRAFT_FIELD, id);
+            }
+            None => {
+                if let Some(storage) = &storage {
+                    let _ = storage.remove_item(&self.storage_key());
+                }
+                set_hidden_input(&self.form, DRAFT_FIELD, "");
+            }
+        }
+        *self.submission_id.borrow_mut() = id;
+    }
+
+    /// Saves the answers to the step at schema index `index` in the
+    /// background, starting the submission first if need be.
+    ///
+    /// A submission that's gone, e.g. completed in another tab, is replaced
+    /// by a new one. Failures are logged; the respondent carries on.
+    fn save(&self, index: usize, data: serde_json::Map<String, serde_json::Value>) {
+        let step_id =
+            step_element(&self.form, index).and_then(|s| s.get_attribute("data-af-step-id"));
+        let data = serde_json::Value::Object(data);
+        let saver = self.clone();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            let mut restarted = false;
+            loop {
+                let existing = saver.submission_id.borrow().clone();
+                let id = match existing {
+                    Some(id) => id,
+                    None => match api::start_submission("", &saver.slug, &FetchOptions::default())
+                        .await
+                    {
+                        Ok(progress) => {
+                            saver.set_submission_id(Some(progress.submission_id.clone()));
+                            progress.submission_id
+                        }
+                        Err(e) => {
+                            console_log(&format!("Starting the submission failed: {}", e));
+                            return;
+                        }
+                    },
+                };
+
+                let result = api::save_step(
+                    "",
+                    &saver.slug,
+                    &id,
+                    step_id.as_deref(),
+                    &data,
+                    &FetchOptions::default(),
+                )
+                .await;
+                match result {
+                    Ok(_) => return,
+                    Err(e) if e.code == "SUBMISSION_NOT_FOUND" && !restarted => {
+                        saver.set_submission_id(None);
+                        restarted = true;
+                    }
+                    Err(e) => {
+                        console_log(&format!("Saving the step failed: {}", e));
+                        return;
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Returns the window's session storage, if it has one.
+#[cfg(feature = "multi-step")]
+fn session_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.session_storage().ok().flatten()
+}
+
+/// Returns the step element at schema index `index`.
+#[cfg(feature = "multi-step")]
+fn step_element(form: &HtmlFormElement, index: usize) -> Option<Element> {
+    let form_element: &Element = form.as_ref();
+    form_element
+        .query_selector_all(".af-step")
+        .ok()?
+        .get(index as u32)?
+        .dyn_into::<Element>()
+        .ok()
+}
+
+/// Sets the value of the form's hidden input `name`, adding it if need be.
+#[cfg(feature = "multi-step")]
+fn set_hidden_input(form: &HtmlFormElement, name: &str, value: &str) {
+    let form_element: &Element = form.as_ref();
+    let selector = format!("input[type=\"hidden\"][name=\"{}\"]", name);
+    let input = match form_element.query_selector(&selector) {
+        Ok(Some(input)) => input,
+        _ => {
+            let Some(input) = web_sys::window()
+                .and_then(|w| w.document())
+                .and_then(|d| d.create_element("input").ok())
+            else {
+                return;
+            };
+            let _ = input.set_attribute("type", "hidden");
+            let _ = input.set_attribute("name", name);
+            let _ = form_element.append_child(&input);
+            input
+        }
+    };
+    if let Ok(input) = input.dyn_into::<HtmlInputElement>() {
+        input.set_value(value);
+    }
+}
+
 /// Binds form submission event.
 fn bind_submit_event(form: &HtmlFormElement, state: Rc<RefCell<FormState>>) {
     let form_clone = form.clone();
     let state_clone = state;
 
     let closure = Closure::wrap(Box::new(move |event: Event| {
+        // The server is turning submissions away (e.g. read-only mode)
+        if form_clone.has_attribute("data-af-unavailable") {
+            event.prevent_default();
+            return;
+        }
+
         let mut state = state_clone.borrow_mut();
 
         // Validate all fields
@@ -428,18 +970,23 @@ fn update_visibility(form: &HtmlFormElement, state: &FormState) {
 
     // Update step visibility
     update_step_visibility(form, state);
+
+    // Conditional steps change the step count
+    #[cfg(feature = "multi-step")]
+    update_navigation_buttons(form, state);
 }
 
 /// Updates step visibility for multi-step forms.
 fn update_step_visibility(form: &HtmlFormElement, state: &FormState) {
     let form_element: &Element = form.as_ref();
-    let current_index = state.current_step_index();
+    // The current step among visible ones, as an index into all steps
+    let current = state.current_schema_step();
 
     if let Ok(step_elements) = form_element.query_selector_all(".af-step") {
         for i in 0..step_elements.length() {
             if let Some(node) = step_elements.get(i) {
                 if let Ok(step_el) = node.dyn_into::<Element>() {
-                    let should_show = i == current_index as u32;
+                    let should_show = current == Some(i as usize);
                     let _ = step_el.set_attribute(
                         "data-af-visible",
                         if should_show { "true" } else { "false" },
@@ -450,7 +997,8 @@ fn update_step_visibility(form: &HtmlFormElement, state: &FormState) {
     }
 }
 
-/// Updates navigation button states.
+/// Updates navigation button states and the progress indicator.
+#[cfg(feature = "multi-step")]
 fn update_navigation_buttons(form: &HtmlFormElement, state: &FormState) {
     let form_element: &Element = form.as_ref();
 
@@ -476,6 +1024,16 @@ fn update_navigation_buttons(form: &HtmlFormElement, state: &FormState) {
             let _ = el.style().set_property("display", display);
         }
     }
+
+    // Update progress indicator
+    let progress = state.progress();
+    if let Ok(Some(bar)) = form_element.query_selector(".af-progress progress") {
+        let _ = bar.set_attribute("value", &progress[0].to_string());
+        let _ = bar.set_attribute("max", &progress[1].to_string());
+    }
+    if let Ok(Some(text)) = form_element.query_selector(".af-progress-text") {
+        text.set_text_content(Some(&format!("Step {} of {}", progress[0], progress[1])));
+    }
 }
 
 /// Updates error display for a field.
@@ -484,18 +1042,280 @@ fn update_field_errors(form: &HtmlFormElement, field_name: &str, errors: &[Strin
     let selector = format!(".af-field[data-af-field=\"{}\"]", field_name);
 
     if let Ok(Some(field_el)) = form_element.query_selector(&selector) {
-        // Toggle error class
-        let class_list = field_el.class_list();
-        if errors.is_empty() {
-            let _ = class_list.remove_1("af-error");
+        show_errors(&field_el, errors);
+    }
+}
+
+/// Updates error display for each entry of a repeatable group; a no-op for
+/// other fields.
+fn update_row_errors(form: &HtmlFormElement, field_name: &str, state: &FormState) {
+    let form_element: &Element = form.as_ref();
+    let selector = format!(".af-repeater[data-af-field=\"{}\"]", field_name);
+    let Ok(Some(field_el)) = form_element.query_selector(&selector) else {
+        return;
+    };
+
+    for (index, row) in row_elements(&field_el).iter().enumerate() {
+        for sub_el in sub_field_elements(row) {
+            if let Some(key) = sub_el.get_attribute("data-af-subfield") {
+                let errors = state.get_errors(&format!("{}[{}][{}]", field_name, index, key));
+                show_errors(&sub_el, &errors);
+            }
+        }
+    }
+}
+
+/// Shows errors on a field or sub-field element, clearing them if empty.
+fn show_errors(field_el: &Element, errors: &[String]) {
+    // Toggle error class
+    let class_list = field_el.class_list();
+    if errors.is_empty() {
+        let _ = class_list.remove_1("af-error");
+    } else {
+        let _ = class_list.add_1("af-error");
+    }
+
+    // Update error message, adding the element for the first error. A
+    // group's own message sits outside its entries.
+    let message = if field_el.has_attribute("data-af-repeater") {
+        field_el.query_selector(
+            ":scope > fieldset > .af-error-message, :scope > .af-error-message",
+        )
+    } else {
+        field_el.query_selector(".af-error-message")
+    };
+    let error_el = match message {
+        Ok(Some(el)) => Some(el),
+        _ if !errors.is_empty() => create_error_message(field_el),
+        _ => None,
+    };
+    if let Some(error_el) = error_el {
+        error_el.set_text_content(Some(&errors.join(", ")));
+    }
+}
+
+/// Adds and removes entries of a repeatable group.
+///
+/// Entries are copies of the group's `<template>`, numbered in place of
+/// `__index__`; removing one renumbers those after it. Either way the group
+/// gets a `change` event, so its value is read again.
+fn bind_repeater_buttons(field_el: &Element) {
+    let group = field_el.clone();
+    let closure = Closure::wrap(Box::new(move |event: Event| {
+        let Some(target) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+            return;
+        };
+        let Some(name) = group.get_attribute("data-af-field") else {
+            return;
+        };
+        let rows = row_elements(&group);
+        let rules: ValidationRules = group
+            .get_attribute("data-af-validation")
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or_default();
+
+        if let Ok(Some(_)) = target.closest(".af-add") {
+            if rules.max_rows.is_some_and(|max| rows.len() >= max) {
+                return;
+            }
+            let template = group.query_selector(".af-row-template").ok().flatten();
+            let container = group.query_selector(".af-rows").ok().flatten();
+            if let (Some(template), Some(container)) = (template, container) {
+                let row = template.inner_html().replace("__index__", &rows.len().to_string());
+                let _ = container.insert_adjacent_html("beforeend", &row);
+            }
+        } else if let Ok(Some(row)) = target.closest(".af-row") {
+            if target.closest(".af-remove").ok().flatten().is_none()
+                || rules.min_rows.unwrap_or(0) >= rows.len()
+            {
+                return;
+            }
+            row.remove();
+            for (index, row) in row_elements(&group).iter().enumerate() {
+                renumber_row(row, &name, index);
+            }
         } else {
-            let _ = class_list.add_1("af-error");
+            return;
         }
 
-        // Update error message
-        if let Ok(Some(error_el)) = field_el.query_selector(".af-error-message") {
-            error_el.set_text_content(Some(&errors.join(", ")));
+        if let Ok(change) = Event::new("change") {
+            let _ = group.dispatch_event(&change);
         }
+    }) as Box<dyn FnMut(Event)>);
+
+    let _ = field_el.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref());
+    closure.forget();
+}
+
+/// Renumbers an entry of the repeatable group `name` and its inputs'
+/// `name`, `id` and `for` attributes.
+fn renumber_row(row: &Element, name: &str, index: usize) {
+    let _ = row.set_attribute("data-af-row", &index.to_string());
+
+    let prefix = format!("{}[", name);
+    let Ok(nodes) = row.query_selector_all("[name], [id], [for]") else {
+        return;
+    };
+    for el in (0..nodes.length()).filter_map(|i| nodes.get(i)?.dyn_into::<Element>().ok()) {
+        for attr in ["name", "id", "for"] {
+            let Some(value) = el.get_attribute(attr) else {
+                continue;
+            };
+            let Some(rest) = value.strip_prefix(&prefix) else {
+                continue;
+            };
+            if let Some(end) = rest.find(']') {
+                let renamed = format!("{}{}{}", prefix, index, &rest[end..]);
+                let _ = el.set_attribute(attr, &renamed);
+            }
+        }
+    }
+}
+
+/// Opens and closes fields' help tooltips with their toggle buttons;
+/// `Escape` closes any open ones.
+fn bind_tooltips(form: &HtmlFormElement) {
+    let form_element: &Element = form.as_ref();
+
+    let root = form_element.clone();
+    let on_click = Closure::wrap(Box::new(move |event: Event| {
+        let Some(target) = event.target().and_then(|t| t.dyn_into::<Element>().ok()) else {
+            return;
+        };
+        if let Ok(Some(toggle)) = target.closest(".af-tooltip-toggle") {
+            let open = toggle.get_attribute("aria-expanded").as_deref() != Some("true");
+            set_tooltip_open(&root, &toggle, open);
+        }
+    }) as Box<dyn FnMut(Event)>);
+    let _ = form_element.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref());
+    on_click.forget();
+
+    let root = form_element.clone();
+    let on_keydown = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        if event.key() != "Escape" {
+            return;
+        }
+        let Ok(open) = root.query_selector_all(".af-tooltip-toggle[aria-expanded=\"true\"]") else {
+            return;
+        };
+        for toggle in (0..open.length()).filter_map(|i| open.get(i)?.dyn_into::<Element>().ok()) {
+            set_tooltip_open(&root, &toggle, false);
+        }
+    }) as Box<dyn FnMut(KeyboardEvent)>);
+    let _ = form_element
+        .add_event_listener_with_callback("keydown", on_keydown.as_ref().unchecked_ref());
+    on_keydown.forget();
+}
+
+/// Shows or hides the tooltip a toggle button controls.
+fn set_tooltip_open(root: &Element, toggle: &Element, open: bool) {
+    let _ = toggle.set_attribute("aria-expanded", if open { "true" } else { "false" });
+    let Some(id) = toggle.get_attribute("aria-controls") else {
+        return;
+    };
+    // Field names in IDs may not be valid `#id` selectors
+    if let Ok(Some(tooltip)) = root.query_selector(&format!("[id=\"{}\"]", id)) {
+        if open {
+            let _ = tooltip.remove_attribute("hidden");
+        } else {
+            let _ = tooltip.set_attribute("hidden", "");
+        }
+    }
+}
+
+/// Shows the "use my location" buttons where the browser can locate the
+/// device, and fills in a location's coordinates and accuracy from the
+/// device when its button is clicked.
+fn bind_locations(form: &HtmlFormElement) {
+    let form_element: &Element = form.as_ref();
+    let Some(geolocation) = web_sys::window().and_then(|w| w.navigator().geolocation().ok()) else {
+        return;
+    };
+    let Ok(buttons) = form_element.query_selector_all("[data-af-locate]") else {
+        return;
+    };
+
+    for button in (0..buttons.length()).filter_map(|i| buttons.get(i)?.dyn_into::<Element>().ok()) {
+        let _ = button.remove_attribute("hidden");
+        let Ok(Some(location)) = button.closest("[data-af-location]") else {
+            continue;
+        };
+
+        let geolocation = geolocation.clone();
+        let on_click = Closure::wrap(Box::new(move |_: Event| {
+            set_location_status(&location, "Locating…");
+
+            let found = location.clone();
+            let on_success = Closure::once_into_js(move |position: JsValue| {
+                let coords = position.unchecked_into::<web_sys::Position>().coords();
+                for (key, value) in [
+                    ("lat", coords.latitude()),
+                    ("lng", coords.longitude()),
+                    ("accuracy", coords.accuracy()),
+                ] {
+                    set_location_part(&found, key, value);
+                }
+                set_location_status(&found, "");
+            });
+            let failed = location.clone();
+            let on_error = Closure::once_into_js(move |error: JsValue| {
+                let error = error.unchecked_into::<web_sys::PositionError>();
+                let message = match error.code() {
+                    web_sys::PositionError::PERMISSION_DENIED => {
+                        "Location access was denied; enter the coordinates instead."
+                    }
+                    _ => "Your location couldn't be found; enter the coordinates instead.",
+                };
+                set_location_status(&failed, message);
+            });
+            let _ = geolocation.get_current_position_with_error_callback(
+                on_success.unchecked_ref(),
+                Some(on_error.unchecked_ref()),
+            );
+        }) as Box<dyn FnMut(Event)>);
+        let _ = button.add_event_listener_with_callback("click", on_click.as_ref().unchecked_ref());
+        on_click.forget();
+    }
+}
+
+/// Sets one part of a location and lets the field's listeners know.
+fn set_location_part(location: &Element, key: &str, value: f64) {
+    let Ok(Some(input)) = location.query_selector(&format!("input[name$=\"[{key}]\"]")) else {
+        return;
+    };
+    let Ok(input) = input.dyn_into::<HtmlInputElement>() else {
+        return;
+    };
+    input.set_value(&value.to_string());
+    if let Ok(event) = Event::new("change") {
+        let _ = input.dispatch_event(&event);
+    }
+}
+
+/// Shows a message in a location's status line.
+fn set_location_status(location: &Element, message: &str) {
+    if let Ok(Some(status)) = location.query_selector(".af-location-status") {
+        status.set_text_content(Some(message));
+    }
+}
+
+/// Appends an empty error message element to a field.
+fn create_error_message(field_el: &Element) -> Option<Element> {
+    let error_el = field_el.owner_document()?.create_element("span").ok()?;
+    error_el.set_class_name("error af-error-message");
+    field_el.append_child(&error_el).ok()?;
+    Some(error_el)
+}
+
+/// Toggles the `af-validating` class while a field's check is pending.
+fn update_field_validating(form: &HtmlFormElement, field_name: &str, validating: bool) {
+    let form_element: &Element = form.as_ref();
+    let selector = format!(".af-field[data-af-field=\"{}\"]", field_name);
+
+    if let Ok(Some(field_el)) = form_element.query_selector(&selector) {
+        let _ = field_el
+            .class_list()
+            .toggle_with_force("af-validating", validating);
     }
 }
 
@@ -510,6 +1330,7 @@ fn show_all_errors(form: &HtmlFormElement, state: &FormState) {
                     if let Some(field_name) = field_el.get_attribute("data-af-field") {
                         let errors = state.get_errors(&field_name);
                         update_field_errors(form, &field_name, &errors);
+                        update_row_errors(form, &field_name, state);
                     }
                 }
             }
@@ -35,6 +35,25 @@
 //! }
 //! ```
 //!
+//! ## Request Options
+//!
+//! ```javascript
+//! const client = FormClient.with_options('https://forms.example.com', {
+//!     headers: { Authorization: `Bearer ${token}` },
+//!     timeout_ms: 10000,
+//!     retries: 2,
+//! });
+//!
+//! try {
+//!     await client.submit_form('contact', values);
+//! } catch (e) {
+//!     // e.kind: 'network' | 'validation' | 'server'; e.code: e.g. 'FORM_CLOSED'
+//!     if (e.kind === 'validation') showErrors(e.details);
+//!     // e.g. 'READ_ONLY' during maintenance: a message fit for respondents
+//!     else if (e.user_message) showNotice(e.user_message);
+//! }
+//! ```
+//!
 //! ## Multi-Step Forms
 //!
 //! ```javascript
@@ -52,17 +71,63 @@
 //! // Validate current step
 //! const errors = form.validate_step(form.current_step().id);
 //! ```
+//!
+//! ## Long Forms
+//!
+//! ```javascript
+//! // The steps without their fields, and the first step's fields
+//! const form = await client.fetch_form_lazy('survey');
+//!
+//! // Load each step's fields as the respondent gets to it
+//! form.next_step();
+//! const pending = form.pending_step();
+//! if (pending) form.load_step(await client.fetch_step('survey', pending));
+//! ```
+
+//! ## Cargo Features
+//!
+//! - `dom` (default): hydration of server-rendered forms (`hydrate`,
+//!   `hydrate_all`)
+//! - `multi-step` (default): step navigation (`next_step`, `progress`, ...)
+//!   and hydrated prev/next buttons
+//! - `conditions` (default): step and field visibility conditions; without
+//!   it every step and field is visible
+//! - `regex`: validate `pattern` rules with the regex crate instead of the
+//!   browser's `RegExp` (adds ~270 KB gzipped)
+//! - `panic-hook`: log panic messages to the browser console
+//!
+//! Build the smallest bundle for simple single-step forms with
+//! `wasm-pack build --release -- --no-default-features --features dom`, and
+//! compare sizes with `scripts/wasm-size.sh`.
+//!
+//! ## Node.js and SSR
+//!
+//! `FormState` and `FormClient` use no DOM APIs, and requests go through the
+//! global `fetch`, so they also run in Node.js 18+ and during server-side
+//! rendering. Build without `dom` for Node.js:
+//!
+//! ```sh
+//! wasm-pack build --target nodejs --release -- \
+//!     --no-default-features --features multi-step,conditions
+//! ```
+//!
+//! `@wordpuppi/anyform-wasm-js` ships this build for `node` imports and
+//! requires, next to the browser build.
 
 pub mod api;
+pub mod condition_graph;
 pub mod form_client;
 pub mod form_state;
+#[cfg(feature = "dom")]
 pub mod hydrate;
+pub mod piping;
 pub mod schema;
 pub mod validation;
 
 // Re-exports for wasm-bindgen
 pub use form_client::FormClient;
 pub use form_state::FormState;
+#[cfg(feature = "dom")]
 pub use hydrate::{hydrate, hydrate_all};
 
 use wasm_bindgen::prelude::*;
@@ -74,8 +139,9 @@ use wasm_bindgen::prelude::*;
 /// after DOMContentLoaded to hydrate server-rendered forms.
 #[wasm_bindgen(start)]
 pub fn init() {
-    // Module initialization - currently a no-op.
     // Hydration happens when user calls hydrate_all() or hydrate(slug).
+    #[cfg(feature = "panic-hook")]
+    console_error_panic_hook::set_once();
 }
 
 /// Returns the version of anyform-client.
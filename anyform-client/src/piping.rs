@@ -0,0 +1,81 @@
+//! Piping answers into labels, descriptions and help text.
+//!
+//! Mirrors the server's `{{field:name}}` and `{{field:name|fallback}}`
+//! placeholders: [`FormState::resolve_text`](crate::FormState::resolve_text)
+//! fills them in for forms fetched as JSON, and hydrated forms update the
+//! `[data-af-pipe]` spans the server renders them as.
+
+use serde_json::Value;
+
+use crate::schema::FieldOptionJson;
+
+/// Replaces the `{{field:name|fallback}}` placeholders in `text` with the
+/// answers `answer` gives for them, or their fallbacks while they're
+/// unanswered. Other text, such as `{{name}}`, is left as it is.
+pub fn resolve(text: &str, answer: impl Fn(&str) -> String) -> String {
+    let mut resolved = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let placeholder = rest[start + 2..]
+            .find("}}")
+            .and_then(|end| parse(&rest[start + 2..start + 2 + end]).map(|p| (p, end)));
+        let Some(((field, fallback), end)) = placeholder else {
+            resolved.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        };
+        resolved.push_str(&rest[..start]);
+        match answer(field) {
+            a if a.trim().is_empty() => resolved.push_str(fallback),
+            a => resolved.push_str(&a),
+        }
+        rest = &rest[start + 2 + end + 2..];
+    }
+    resolved.push_str(rest);
+    resolved
+}
+
+/// Parses what's between the braces of a placeholder into the field name
+/// and fallback.
+fn parse(inner: &str) -> Option<(&str, &str)> {
+    let reference = inner.trim().strip_prefix("field:")?;
+    let (field, fallback) = reference.split_once('|').unwrap_or((reference, ""));
+    let field = field.trim();
+    if field.is_empty() || field.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((field, fallback.trim()))
+}
+
+/// Returns an answer as placeholders quote it: choices by their options'
+/// labels, several joined with commas.
+pub fn answer_text(value: &Value, options: &[FieldOptionJson]) -> String {
+    let label = |v: &str| {
+        options
+            .iter()
+            .find(|o| o.value == v)
+            .map_or_else(|| v.to_string(), |o| o.label.clone())
+    };
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => label(s),
+        Value::Number(n) => n.as_f64().map_or_else(|| n.to_string(), |n| n.to_string()),
+        Value::Bool(b) => b.to_string(),
+        Value::Array(values) => values
+            .iter()
+            .map(|v| answer_text(v, options))
+            .filter(|v| !v.is_empty())
+            .collect::<Vec<_>>()
+            .join(", "),
+        Value::Object(location) => match (location.get("lat"), location.get("lng")) {
+            (Some(lat), Some(lng)) => {
+                let coordinates = format!("{}, {}", answer_text(lat, &[]), answer_text(lng, &[]));
+                match location.get("label").and_then(Value::as_str) {
+                    Some(place) if !place.is_empty() => format!("{coordinates} ({place})"),
+                    _ => coordinates,
+                }
+            }
+            _ => String::new(),
+        },
+    }
+}
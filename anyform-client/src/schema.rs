@@ -20,9 +20,19 @@ pub struct FormJson {
     /// HTTP method for form submission.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub action_method: Option<String>,
+    /// Whether the form has stopped accepting responses.
+    #[serde(default)]
+    pub closed: bool,
     #[serde(default)]
     pub settings: FormSettings,
+    /// When the form last changed, to pass as `since` when fetching changes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
     pub steps: Vec<StepJson>,
+    /// Steps left unchanged since the requested time, when `steps` holds
+    /// only the changed ones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unchanged: Option<Vec<Uuid>>,
 }
 
 /// Step in a multi-step form.
@@ -35,6 +45,19 @@ pub struct StepJson {
     pub order: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<ConditionRule>,
+    /// The step's own jump rules, tried after its fields'.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub next: Vec<JumpRule>,
+    /// When the step's content last changed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Fingerprint of the step's content, changing whenever the step does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// Whether `fields` were left out of the form's skeleton, to be loaded
+    /// with [`FormState::load_step`](crate::FormState::load_step).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub lazy: bool,
     pub fields: Vec<FieldJson>,
 }
 
@@ -55,8 +78,14 @@ pub struct FieldJson {
     pub validation: ValidationRules,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<ConditionRule>,
+    /// Jump rules tried once the field's step is done, while it's shown.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub next: Vec<JumpRule>,
     #[serde(default)]
     pub options: Vec<FieldOptionJson>,
+    /// Sub-fields of a repeatable group, filled in once per entry.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldJson>,
     pub order: i32,
 }
 
@@ -69,6 +98,25 @@ pub struct FieldOptionJson {
     #[serde(default)]
     pub score: Option<i32>,
     pub order: i32,
+    /// Maximum number of submissions that may choose this option.
+    #[serde(default)]
+    pub capacity: Option<i32>,
+    /// Seats left when the schema was fetched.
+    #[serde(default)]
+    pub remaining: Option<i32>,
+    /// Heading the option is listed under.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Picture shown on the option's card, for `image_choice` fields.
+    #[serde(default)]
+    pub image_url: Option<String>,
+}
+
+impl FieldOptionJson {
+    /// Returns true if the option has a capacity and no seats left.
+    pub fn is_sold_out(&self) -> bool {
+        self.remaining == Some(0)
+    }
 }
 
 /// Form settings.
@@ -84,6 +132,9 @@ pub struct FormSettings {
     pub success_redirect: Option<String>,
     #[serde(default)]
     pub show_progress: bool,
+    /// How `FormState::progress()` counts steps.
+    #[serde(default)]
+    pub progress_mode: ProgressMode,
     #[serde(default)]
     pub allow_save_draft: bool,
     /// Custom action URL for form submission.
@@ -92,6 +143,35 @@ pub struct FormSettings {
     /// HTTP method for form submission.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub method: Option<String>,
+    /// Whether responses are anonymous (no IP, user agent or identity stored).
+    #[serde(default)]
+    pub anonymous: bool,
+    /// Notice to show on anonymous forms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anonymous_notice: Option<String>,
+    /// Message to show once the form is closed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_message: Option<String>,
+    /// When the form opens for responses (RFC 3339).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opens_at: Option<String>,
+    /// When the form stops taking responses (RFC 3339).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closes_at: Option<String>,
+    /// Message to show before the form opens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_open_message: Option<String>,
+}
+
+/// How progress through a multi-step form is counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressMode {
+    /// Steps visible with the current answers.
+    #[default]
+    Visible,
+    /// Steps visible so far plus every later step that could still be shown.
+    Estimated,
 }
 
 /// Field value types.
@@ -109,7 +189,10 @@ pub enum ValueType {
     Datetime,
     Select,
     Radio,
+    /// Picture cards, one of which is chosen like a radio button.
+    ImageChoice,
     Checkbox,
+    MultiSelect,
     File,
     Hidden,
     Password,
@@ -117,6 +200,9 @@ pub enum ValueType {
     Range,
     Rating,
     Scale,
+    Repeater,
+    /// Point on the map, as `{lat, lng, accuracy, label}`.
+    Location,
 }
 
 /// Validation rules for a field.
@@ -130,7 +216,7 @@ pub struct ValidationRules {
     pub max_length: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pattern: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "patternMessage")]
     pub pattern_message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min_value: Option<f64>,
@@ -140,6 +226,57 @@ pub struct ValidationRules {
     pub min_selections: Option<usize>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_selections: Option<usize>,
+    /// Fewest entries a repeatable group takes.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "minRows")]
+    pub min_rows: Option<usize>,
+    /// Most entries a repeatable group takes.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "maxRows")]
+    pub max_rows: Option<usize>,
+    /// Custom message for a missing required value; this and the other `*_message`
+    /// templates fill in `{label}` and the rule's `{min}` or `{max}`.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "requiredMessage")]
+    pub required_message: Option<String>,
+    /// Custom message for a value shorter than `min_length`.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "minLengthMessage")]
+    pub min_length_message: Option<String>,
+    /// Custom message for a value longer than `max_length`.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "maxLengthMessage")]
+    pub max_length_message: Option<String>,
+    /// Custom message for a number below `min_value`.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "minMessage")]
+    pub min_message: Option<String>,
+    /// Custom message for a number above `max_value`.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "maxMessage")]
+    pub max_message: Option<String>,
+    /// Custom message for too few choices.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "minSelectionsMessage")]
+    pub min_selections_message: Option<String>,
+    /// Custom message for too many choices.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "maxSelectionsMessage")]
+    pub max_selections_message: Option<String>,
+    /// Custom message for too few entries.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "minRowsMessage")]
+    pub min_rows_message: Option<String>,
+    /// Custom message for too many entries.
+    #[serde(skip_serializing_if = "Option::is_none", alias = "maxRowsMessage")]
+    pub max_rows_message: Option<String>,
+    /// Also check the value with the server's field validator.
+    #[serde(default, alias = "serverValidate")]
+    pub server_validate: bool,
+    /// Named validators the server also runs on the value.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validators: Vec<String>,
+}
+
+/// Where to go once a step is done.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JumpRule {
+    /// When the rule applies; always, if absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<ConditionRule>,
+    /// ID of the later step to go to; absent to end the form.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Uuid>,
 }
 
 /// Condition rule for step/field visibility.
@@ -147,14 +284,19 @@ pub struct ValidationRules {
 pub struct ConditionRule {
     pub field: String,
     pub op: ConditionOp,
+    #[serde(default)]
     pub value: serde_json::Value,
 }
 
 /// Condition operators.
+///
+/// Also accepts the server's names (`neq`, `empty`, `not_empty`), so rules
+/// rendered into `data-af-condition` parse as-is.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ConditionOp {
     Eq,
+    #[serde(alias = "neq")]
     Ne,
     Gt,
     Gte,
@@ -164,10 +306,13 @@ pub enum ConditionOp {
     NotContains,
     StartsWith,
     EndsWith,
+    #[serde(alias = "empty")]
     IsEmpty,
+    #[serde(alias = "not_empty")]
     IsNotEmpty,
 }
 
+#[cfg(feature = "conditions")]
 impl ConditionRule {
     /// Evaluates this condition against the given form values.
     pub fn evaluate(&self, values: &std::collections::HashMap<String, serde_json::Value>) -> bool {
@@ -190,6 +335,7 @@ impl ConditionRule {
     }
 }
 
+#[cfg(feature = "conditions")]
 fn is_empty_value(value: &serde_json::Value) -> bool {
     match value {
         serde_json::Value::Null => true,
@@ -200,6 +346,7 @@ fn is_empty_value(value: &serde_json::Value) -> bool {
     }
 }
 
+#[cfg(feature = "conditions")]
 fn evaluate_comparison(
     op: &ConditionOp,
     field_value: &serde_json::Value,
@@ -219,6 +366,7 @@ fn evaluate_comparison(
     }
 }
 
+#[cfg(feature = "conditions")]
 fn values_equal(a: &serde_json::Value, b: &serde_json::Value) -> bool {
     // Handle type coercion for common cases
     match (a, b) {
@@ -242,6 +390,7 @@ fn values_equal(a: &serde_json::Value, b: &serde_json::Value) -> bool {
     }
 }
 
+#[cfg(feature = "conditions")]
 fn compare_numeric(
     op: &ConditionOp,
     field_value: &serde_json::Value,
@@ -270,6 +419,7 @@ fn compare_numeric(
     }
 }
 
+#[cfg(feature = "conditions")]
 fn string_contains(field_value: &serde_json::Value, condition_value: &serde_json::Value) -> bool {
     match (field_value, condition_value) {
         (serde_json::Value::String(s), serde_json::Value::String(needle)) => s.contains(needle.as_str()),
@@ -278,6 +428,7 @@ fn string_contains(field_value: &serde_json::Value, condition_value: &serde_json
     }
 }
 
+#[cfg(feature = "conditions")]
 fn string_starts_with(field_value: &serde_json::Value, condition_value: &serde_json::Value) -> bool {
     match (field_value, condition_value) {
         (serde_json::Value::String(s), serde_json::Value::String(prefix)) => {
@@ -287,6 +438,7 @@ fn string_starts_with(field_value: &serde_json::Value, condition_value: &serde_j
     }
 }
 
+#[cfg(feature = "conditions")]
 fn string_ends_with(field_value: &serde_json::Value, condition_value: &serde_json::Value) -> bool {
     match (field_value, condition_value) {
         (serde_json::Value::String(s), serde_json::Value::String(suffix)) => {
@@ -3,8 +3,8 @@
 //! This module mirrors the server-side validation logic to provide
 //! instant feedback in the browser without round-trips to the server.
 
+use crate::piping;
 use crate::schema::{FieldJson, ValidationRules, ValueType};
-use regex::Regex;
 
 /// Validates a field value against its rules.
 pub fn validate_field(
@@ -13,10 +13,14 @@ pub fn validate_field(
 ) -> Vec<String> {
     let mut errors = Vec::new();
     let rules = &field.validation;
+    // Answers piped into the label read as their fallbacks
+    let label = &piping::resolve(&field.label, |_| String::new());
 
     // Required validation
     if rules.required && is_empty(value) {
-        errors.push(format!("{} is required", field.label));
+        errors.push(message(&rules.required_message, label, None, || {
+            format!("{} is required", label)
+        }));
         return errors; // Skip other validations if empty and required
     }
 
@@ -42,15 +46,60 @@ pub fn validate_field(
                 errors.push(e);
             }
         }
+        ValueType::Repeater if !value.is_array() => {
+            errors.push(format!("{} must be a list of entries", label));
+        }
+        ValueType::Location if !is_valid_location(value) => {
+            errors.push(format!(
+                "{} must be a location (latitude -90 to 90, longitude -180 to 180)",
+                label
+            ));
+        }
         _ => {}
     }
 
+    // Sold-out options
+    for option in field.options.iter().filter(|o| o.is_sold_out()) {
+        let chosen = match value {
+            serde_json::Value::String(s) => *s == option.value,
+            serde_json::Value::Array(a) => a.iter().any(|v| v.as_str() == Some(&option.value)),
+            _ => false,
+        };
+        if chosen {
+            errors.push(format!("{} is full", option.label));
+        }
+    }
+
     // Rule-based validations
-    validate_rules(rules, value, &field.label, &mut errors);
+    validate_rules(rules, value, label, &mut errors);
 
     errors
 }
 
+/// Validates each entry of a repeatable group against its sub-fields.
+///
+/// Errors are keyed like the entry's inputs, e.g. `members[0][email]`.
+pub fn validate_rows(
+    group: &FieldJson,
+    value: &serde_json::Value,
+) -> Vec<(String, Vec<String>)> {
+    let Some(rows) = value.as_array() else {
+        return Vec::new();
+    };
+
+    let mut errors = Vec::new();
+    for (index, row) in rows.iter().enumerate() {
+        for field in &group.fields {
+            let value = row.get(&field.name).unwrap_or(&serde_json::Value::Null);
+            let field_errors = validate_field(field, value);
+            if !field_errors.is_empty() {
+                errors.push((format!("{}[{}][{}]", group.name, index, field.name), field_errors));
+            }
+        }
+    }
+    errors
+}
+
 /// Checks if a value is considered empty.
 fn is_empty(value: &serde_json::Value) -> bool {
     match value {
@@ -68,11 +117,17 @@ fn validate_email(value: &serde_json::Value) -> Result<(), String> {
         return Ok(());
     };
 
-    // Simple email regex pattern
-    let pattern = r"^[^\s@]+@[^\s@]+\.[^\s@]+$";
-    let re = Regex::new(pattern).unwrap();
-
-    if !re.is_match(email) {
+    // Simple shape check: `local@domain.tld` without whitespace
+    let valid = email.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty()
+            && !email.contains(char::is_whitespace)
+            && !domain.contains('@')
+            && domain
+                .char_indices()
+                .any(|(i, c)| c == '.' && i > 0 && i + 1 < domain.len())
+    });
+
+    if !valid {
         return Err("Invalid email format".to_string());
     }
 
@@ -85,11 +140,11 @@ fn validate_url(value: &serde_json::Value) -> Result<(), String> {
         return Ok(());
     };
 
-    // Simple URL pattern
-    let pattern = r"^https?://[^\s]+$";
-    let re = Regex::new(pattern).unwrap();
+    // Simple URL shape: http(s):// followed by non-whitespace
+    let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"));
+    let valid = rest.is_some_and(|rest| !rest.is_empty() && !rest.contains(char::is_whitespace));
 
-    if !re.is_match(url) {
+    if !valid {
         return Err("Invalid URL format".to_string());
     }
 
@@ -103,16 +158,66 @@ fn validate_tel(value: &serde_json::Value) -> Result<(), String> {
     };
 
     // Allow digits, spaces, dashes, parentheses, and + sign
-    let pattern = r"^[\d\s\-\(\)\+]+$";
-    let re = Regex::new(pattern).unwrap();
+    let valid = !tel.is_empty()
+        && tel
+            .chars()
+            .all(|c| c.is_numeric() || c.is_whitespace() || "-()+".contains(c));
 
-    if !re.is_match(tel) {
+    if !valid {
         return Err("Invalid phone number format".to_string());
     }
 
     Ok(())
 }
 
+/// Returns true if a location answer has coordinates on the globe and a
+/// non-negative accuracy, if any.
+fn is_valid_location(value: &serde_json::Value) -> bool {
+    let coordinate = |key: &str, bound: f64| {
+        value
+            .get(key)
+            .and_then(serde_json::Value::as_f64)
+            .is_some_and(|c| (-bound..=bound).contains(&c))
+    };
+    let accuracy = match value.get("accuracy") {
+        None | Some(serde_json::Value::Null) => true,
+        Some(accuracy) => accuracy.as_f64().is_some_and(|a| a >= 0.0),
+    };
+    coordinate("lat", 90.0) && coordinate("lng", 180.0) && accuracy
+}
+
+/// Returns whether `text` matches a validation `pattern`, or `None` if the
+/// pattern doesn't compile.
+///
+/// In the browser this uses `RegExp`, which keeps the regex engine out of
+/// the bundle, unless the `regex` feature is on; native builds always use the
+/// `regex` crate.
+#[cfg(any(feature = "regex", not(target_arch = "wasm32")))]
+fn pattern_matches(pattern: &str, text: &str) -> Option<bool> {
+    regex::Regex::new(pattern).ok().map(|re| re.is_match(text))
+}
+
+#[cfg(all(not(feature = "regex"), target_arch = "wasm32"))]
+fn pattern_matches(pattern: &str, text: &str) -> Option<bool> {
+    browser::RegExp::new(pattern, "u").ok().map(|re| re.test(text))
+}
+
+#[cfg(all(not(feature = "regex"), target_arch = "wasm32"))]
+mod browser {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        pub type RegExp;
+
+        #[wasm_bindgen(constructor, catch)]
+        pub fn new(pattern: &str, flags: &str) -> Result<RegExp, JsValue>;
+
+        #[wasm_bindgen(method)]
+        pub fn test(this: &RegExp, text: &str) -> bool;
+    }
+}
+
 /// Validates a value against validation rules.
 fn validate_rules(
     rules: &ValidationRules,
@@ -124,7 +229,9 @@ fn validate_rules(
     if let Some(min) = rules.min_length {
         if let serde_json::Value::String(s) = value {
             if s.len() < min {
-                errors.push(format!("{} must be at least {} characters", label, min));
+                errors.push(message(&rules.min_length_message, label, Some(("min", &min)), || {
+                    format!("{} must be at least {} characters", label, min)
+                }));
             }
         }
     }
@@ -133,7 +240,9 @@ fn validate_rules(
     if let Some(max) = rules.max_length {
         if let serde_json::Value::String(s) = value {
             if s.len() > max {
-                errors.push(format!("{} must be at most {} characters", label, max));
+                errors.push(message(&rules.max_length_message, label, Some(("max", &max)), || {
+                    format!("{} must be at most {} characters", label, max)
+                }));
             }
         }
     }
@@ -141,14 +250,10 @@ fn validate_rules(
     // Pattern
     if let Some(pattern) = &rules.pattern {
         if let serde_json::Value::String(s) = value {
-            if let Ok(re) = Regex::new(pattern) {
-                if !re.is_match(s) {
-                    let message = rules
-                        .pattern_message
-                        .clone()
-                        .unwrap_or_else(|| format!("{} does not match the required format", label));
-                    errors.push(message);
-                }
+            if pattern_matches(pattern, s) == Some(false) {
+                errors.push(message(&rules.pattern_message, label, None, || {
+                    format!("{} does not match the required format", label)
+                }));
             }
         }
     }
@@ -157,7 +262,9 @@ fn validate_rules(
     if let Some(min) = rules.min_value {
         if let Some(num) = get_numeric_value(value) {
             if num < min {
-                errors.push(format!("{} must be at least {}", label, min));
+                errors.push(message(&rules.min_message, label, Some(("min", &min)), || {
+                    format!("{} must be at least {}", label, min)
+                }));
             }
         }
     }
@@ -166,7 +273,9 @@ fn validate_rules(
     if let Some(max) = rules.max_value {
         if let Some(num) = get_numeric_value(value) {
             if num > max {
-                errors.push(format!("{} must be at most {}", label, max));
+                errors.push(message(&rules.max_message, label, Some(("max", &max)), || {
+                    format!("{} must be at most {}", label, max)
+                }));
             }
         }
     }
@@ -175,7 +284,9 @@ fn validate_rules(
     if let Some(min) = rules.min_selections {
         if let serde_json::Value::Array(arr) = value {
             if arr.len() < min {
-                errors.push(format!("Select at least {} options", min));
+                errors.push(message(&rules.min_selections_message, label, Some(("min", &min)), || {
+                    format!("Select at least {} options", min)
+                }));
             }
         }
     }
@@ -184,12 +295,54 @@ fn validate_rules(
     if let Some(max) = rules.max_selections {
         if let serde_json::Value::Array(arr) = value {
             if arr.len() > max {
-                errors.push(format!("Select at most {} options", max));
+                errors.push(message(&rules.max_selections_message, label, Some(("max", &max)), || {
+                    format!("Select at most {} options", max)
+                }));
+            }
+        }
+    }
+
+    // Min rows (repeatable groups)
+    if let Some(min) = rules.min_rows {
+        if let serde_json::Value::Array(arr) = value {
+            if arr.len() < min {
+                errors.push(message(&rules.min_rows_message, label, Some(("min", &min)), || {
+                    format!("{} requires at least {} entries", label, min)
+                }));
+            }
+        }
+    }
+
+    // Max rows (repeatable groups)
+    if let Some(max) = rules.max_rows {
+        if let serde_json::Value::Array(arr) = value {
+            if arr.len() > max {
+                errors.push(message(&rules.max_rows_message, label, Some(("max", &max)), || {
+                    format!("{} allows at most {} entries", label, max)
+                }));
             }
         }
     }
 }
 
+/// Returns the field's own message for a rule, with `{label}` and the
+/// rule's `{min}` or `{max}` filled in, or the built-in one.
+fn message(
+    template: &Option<String>,
+    label: &str,
+    arg: Option<(&str, &dyn std::fmt::Display)>,
+    default: impl FnOnce() -> String,
+) -> String {
+    let Some(template) = template else {
+        return default();
+    };
+    let message = template.replace("{label}", label);
+    match arg {
+        Some((name, value)) => message.replace(&format!("{{{name}}}"), &value.to_string()),
+        None => message,
+    }
+}
+
 /// Extracts a numeric value from a JSON value.
 fn get_numeric_value(value: &serde_json::Value) -> Option<f64> {
     match value {
@@ -202,6 +355,7 @@ fn get_numeric_value(value: &serde_json::Value) -> Option<f64> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema::FieldOptionJson;
     use serde_json::json;
     use uuid::Uuid;
 
@@ -216,7 +370,9 @@ mod tests {
             default_value: None,
             validation,
             condition: None,
+            next: Vec::new(),
             options: vec![],
+            fields: vec![],
             order: 0,
         }
     }
@@ -248,6 +404,23 @@ mod tests {
         assert!(validate_field(&field, &json!("test@example.com")).is_empty());
         assert!(!validate_field(&field, &json!("invalid")).is_empty());
         assert!(!validate_field(&field, &json!("test@")).is_empty());
+        assert!(!validate_field(&field, &json!("test@example.")).is_empty());
+        assert!(!validate_field(&field, &json!("te st@example.com")).is_empty());
+        assert!(validate_field(&field, &json!("a@b.c.")).is_empty());
+    }
+
+    #[test]
+    fn test_url_and_tel_validation() {
+        let url = make_field("site", ValueType::Url, ValidationRules::default());
+        assert!(validate_field(&url, &json!("https://example.com/a?b=c")).is_empty());
+        assert!(!validate_field(&url, &json!("https://")).is_empty());
+        assert!(!validate_field(&url, &json!("ftp://example.com")).is_empty());
+        assert!(!validate_field(&url, &json!("http://exa mple.com")).is_empty());
+
+        let tel = make_field("phone", ValueType::Tel, ValidationRules::default());
+        assert!(validate_field(&tel, &json!("+1 (555) 010-9999")).is_empty());
+        assert!(validate_field(&tel, &json!("٠١٢٣")).is_empty());
+        assert!(!validate_field(&tel, &json!("555-CALL")).is_empty());
     }
 
     #[test]
@@ -283,6 +456,31 @@ mod tests {
         assert!(!validate_field(&field, &json!("abcd")).is_empty());
     }
 
+    #[test]
+    fn test_custom_messages() {
+        let field = make_field(
+            "age",
+            ValueType::Number,
+            ValidationRules {
+                required: true,
+                required_message: Some("Tell us your {label}".to_string()),
+                min_value: Some(18.0),
+                min_message: Some("{label} must be {min} or over".to_string()),
+                max_value: Some(120.0),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(validate_field(&field, &json!(null)), vec!["Tell us your age"]);
+        assert_eq!(validate_field(&field, &json!(17)), vec!["age must be 18 or over"]);
+        assert_eq!(validate_field(&field, &json!(121)), vec!["age must be at most 120"]);
+
+        // As rendered into `data-af-validation`
+        let rules: ValidationRules =
+            serde_json::from_str(r#"{"minLengthMessage": "{label}?"}"#).unwrap();
+        assert_eq!(rules.min_length_message.as_deref(), Some("{label}?"));
+    }
+
     #[test]
     fn test_numeric_range_validation() {
         let field = make_field(
@@ -301,4 +499,85 @@ mod tests {
         assert!(validate_field(&field, &json!(120)).is_empty());
         assert!(!validate_field(&field, &json!(121)).is_empty());
     }
+
+    #[test]
+    fn test_sold_out_option_validation() {
+        let mut field = make_field("workshop", ValueType::Select, ValidationRules::default());
+        field.options = vec![
+            FieldOptionJson {
+                id: Uuid::new_v4(),
+                label: "Workshop A".to_string(),
+                value: "a".to_string(),
+                score: None,
+                order: 0,
+                capacity: Some(20),
+                remaining: Some(0),
+                group: None,
+                image_url: None,
+            },
+            FieldOptionJson {
+                id: Uuid::new_v4(),
+                label: "Workshop B".to_string(),
+                value: "b".to_string(),
+                score: None,
+                order: 1,
+                capacity: Some(20),
+                remaining: Some(3),
+                group: None,
+                image_url: None,
+            },
+        ];
+
+        assert_eq!(validate_field(&field, &json!("a")), vec!["Workshop A is full"]);
+        assert!(validate_field(&field, &json!("b")).is_empty());
+    }
+
+    #[test]
+    fn test_repeater_validation() {
+        let mut field = make_field(
+            "members",
+            ValueType::Repeater,
+            ValidationRules {
+                min_rows: Some(1),
+                max_rows: Some(2),
+                ..Default::default()
+            },
+        );
+        field.fields = vec![make_field(
+            "email",
+            ValueType::Email,
+            ValidationRules {
+                required: true,
+                ..Default::default()
+            },
+        )];
+
+        assert_eq!(
+            validate_field(&field, &json!("ann@example.com")),
+            vec!["members must be a list of entries"]
+        );
+        let three = json!([{ "email": "a@example.com" }, {}, {}]);
+        assert_eq!(validate_field(&field, &three), vec!["members allows at most 2 entries"]);
+
+        let rows = json!([{ "email": "a@example.com" }, { "email": "" }]);
+        assert!(validate_field(&field, &rows).is_empty());
+        assert_eq!(
+            validate_rows(&field, &rows),
+            vec![("members[1][email]".to_string(), vec!["email is required".to_string()])]
+        );
+    }
+
+    #[test]
+    fn test_location_validation() {
+        let field = make_field("spot", ValueType::Location, ValidationRules::default());
+        let error = "spot must be a location (latitude -90 to 90, longitude -180 to 180)";
+
+        assert!(validate_field(&field, &json!({ "lat": 51.5, "lng": -0.12 })).is_empty());
+        assert!(validate_field(&field, &json!({ "lat": 0, "lng": 180, "accuracy": 12.5 }))
+            .is_empty());
+        assert_eq!(validate_field(&field, &json!({ "lat": 91, "lng": 0 })), vec![error]);
+        assert_eq!(validate_field(&field, &json!({ "lat": 0, "lng": -181 })), vec![error]);
+        assert_eq!(validate_field(&field, &json!({ "lat": 0 })), vec![error]);
+        assert_eq!(validate_field(&field, &json!("51.5, -0.12")), vec![error]);
+    }
 }
@@ -0,0 +1,405 @@
+//! Browser tests for hydration of server-rendered forms.
+//!
+//! The fixtures are the seeded example forms as rendered by the server, kept
+//! in sync by `anyform/tests/hydration_fixtures_tests.rs`. Run with:
+//!
+//! ```sh
+//! CHROMEDRIVER=/path/to/chromedriver \
+//! CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner \
+//!     cargo test -p anyform-client --target wasm32-unknown-unknown --test hydrate_e2e
+//! ```
+//!
+//! or `wasm-pack test --headless --chrome anyform-client`.
+
+#![cfg(all(target_arch = "wasm32", feature = "dom"))]
+
+use anyform_client::{hydrate, FormClient};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+use web_sys::{Element, Event, EventInit, HtmlButtonElement, HtmlElement, HtmlInputElement};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const CONTACT: &str = include_str!("fixtures/contact.html");
+const ONBOARDING: &str = include_str!("fixtures/onboarding.html");
+
+#[wasm_bindgen(inline_js = "
+const realFetch = globalThis.fetch;
+
+export function stub_fetch(status, body) {
+  const requests = [];
+  globalThis.fetch = async (request) => {
+    requests.push({ url: request.url, method: request.method, body: await request.text() });
+    return new Response(body, { status, headers: { 'Content-Type': 'application/json' } });
+  };
+  return requests;
+}
+
+export function restore_fetch() {
+  globalThis.fetch = realFetch;
+}
+")]
+extern "C" {
+    /// Replaces `fetch` with one answering `status` and `body`, and returns
+    /// the array its requests are recorded in.
+    fn stub_fetch(status: u16, body: &str) -> js_sys::Array;
+
+    fn restore_fetch();
+}
+
+/// Replaces the page with a fixture and hydrates its form.
+fn mount(html: &str, slug: &str) -> Element {
+    let document = web_sys::window().unwrap().document().unwrap();
+    document.body().unwrap().set_inner_html(html);
+    hydrate(slug);
+    document
+        .query_selector(&format!("[data-af-form=\"{slug}\"]"))
+        .unwrap()
+        .expect("fixture has no form")
+}
+
+fn find(form: &Element, selector: &str) -> Element {
+    form.query_selector(selector)
+        .unwrap()
+        .unwrap_or_else(|| panic!("no element matches {selector}"))
+}
+
+fn input(form: &Element, selector: &str) -> HtmlInputElement {
+    find(form, selector).dyn_into().unwrap()
+}
+
+fn dispatch(target: &Element, event: &str) {
+    let init = EventInit::new();
+    init.set_bubbles(true);
+    init.set_cancelable(true);
+    let event = Event::new_with_event_init_dict(event, &init).unwrap();
+    target.dispatch_event(&event).unwrap();
+}
+
+/// Types a value into an input and leaves it.
+fn fill(form: &Element, selector: &str, value: &str) {
+    let el = input(form, selector);
+    el.set_value(value);
+    dispatch(&el, "input");
+    dispatch(&el, "change");
+}
+
+/// Checks or unchecks a radio button or checkbox.
+fn set_checked(form: &Element, selector: &str, checked: bool) {
+    let el = input(form, selector);
+    el.set_checked(checked);
+    dispatch(&el, "input");
+    dispatch(&el, "change");
+}
+
+fn click(form: &Element, selector: &str) {
+    find(form, selector).dyn_into::<HtmlElement>().unwrap().click();
+}
+
+fn is_visible(form: &Element, selector: &str) -> bool {
+    find(form, selector).get_attribute("data-af-visible").as_deref() == Some("true")
+}
+
+/// Returns the index of the only visible step.
+fn current_step(form: &Element) -> usize {
+    let steps = form.query_selector_all(".af-step").unwrap();
+    let visible: Vec<u32> = (0..steps.length())
+        .filter(|&i| {
+            let step: Element = steps.get(i).unwrap().dyn_into().unwrap();
+            step.get_attribute("data-af-visible").as_deref() == Some("true")
+        })
+        .collect();
+    assert_eq!(visible.len(), 1, "expected exactly one visible step");
+    visible[0] as usize
+}
+
+fn progress_text(form: &Element) -> String {
+    find(form, ".af-progress-text").text_content().unwrap_or_default()
+}
+
+fn is_displayed(form: &Element, selector: &str) -> bool {
+    let el: HtmlElement = find(form, selector).dyn_into().unwrap();
+    el.style().get_property_value("display").unwrap() != "none"
+}
+
+fn field_error(form: &Element, field: &str) -> Option<String> {
+    let field = find(form, &format!(".af-field[data-af-field=\"{field}\"]"));
+    if !field.class_list().contains("af-error") {
+        return None;
+    }
+    let message = field.query_selector(".af-error-message").unwrap()?;
+    message.text_content()
+}
+
+// ============================================================================
+// Step navigation
+// ============================================================================
+
+#[wasm_bindgen_test]
+fn test_navigation_skips_hidden_step() {
+    let form = mount(ONBOARDING, "onboarding");
+    assert_eq!(current_step(&form), 0);
+    assert!(find(&form, ".af-prev").dyn_into::<HtmlButtonElement>().unwrap().disabled());
+    assert!(is_displayed(&form, ".af-next"));
+    assert!(!is_displayed(&form, ".af-submit"));
+
+    fill(&form, "#name", "Ada");
+    set_checked(&form, "#role_designer", true);
+    click(&form, ".af-next");
+
+    // "Your Stack" is for developers only
+    assert_eq!(current_step(&form), 2);
+    assert_eq!(progress_text(&form), "Step 2 of 2");
+    assert!(!find(&form, ".af-prev").dyn_into::<HtmlButtonElement>().unwrap().disabled());
+    assert!(!is_displayed(&form, ".af-next"));
+    assert!(is_displayed(&form, ".af-submit"));
+
+    click(&form, ".af-prev");
+    assert_eq!(current_step(&form), 0);
+    assert_eq!(progress_text(&form), "Step 1 of 2");
+}
+
+#[wasm_bindgen_test]
+fn test_navigation_enters_shown_step() {
+    let form = mount(ONBOARDING, "onboarding");
+
+    set_checked(&form, "#role_developer", true);
+    assert_eq!(progress_text(&form), "Step 1 of 3");
+
+    click(&form, ".af-next");
+    assert_eq!(current_step(&form), 1);
+    assert_eq!(progress_text(&form), "Step 2 of 3");
+
+    click(&form, ".af-next");
+    assert_eq!(current_step(&form), 2);
+    assert!(is_displayed(&form, ".af-submit"));
+}
+
+// ============================================================================
+// Conditions
+// ============================================================================
+
+#[wasm_bindgen_test]
+fn test_condition_hides_and_shows_field() {
+    let form = mount(ONBOARDING, "onboarding");
+    let details = ".af-field[data-af-field=\"role_details\"]";
+    assert!(!is_visible(&form, details));
+
+    set_checked(&form, "#role_other", true);
+    assert!(is_visible(&form, details));
+
+    set_checked(&form, "#role_developer", true);
+    assert!(!is_visible(&form, details));
+}
+
+// ============================================================================
+// Validation display
+// ============================================================================
+
+#[wasm_bindgen_test]
+fn test_validation_errors_follow_input() {
+    let form = mount(CONTACT, "contact");
+    assert_eq!(field_error(&form, "name"), None);
+
+    fill(&form, "#name", "J");
+    let error = field_error(&form, "name").expect("name should be too short");
+    assert!(!error.is_empty());
+
+    fill(&form, "#name", "Jane");
+    assert_eq!(field_error(&form, "name"), None);
+}
+
+#[wasm_bindgen_test]
+fn test_invalid_submit_is_blocked() {
+    let form = mount(CONTACT, "contact");
+    fill(&form, "#email", "not-an-email");
+
+    let init = EventInit::new();
+    init.set_cancelable(true);
+    let submit = Event::new_with_event_init_dict("submit", &init).unwrap();
+    form.dispatch_event(&submit).unwrap();
+
+    assert!(submit.default_prevented());
+    assert!(field_error(&form, "name").is_some());
+    assert!(field_error(&form, "email").is_some());
+    assert!(field_error(&form, "message").is_some());
+    // Optional and empty
+    assert_eq!(field_error(&form, "phone"), None);
+}
+
+// ============================================================================
+// Checkbox groups
+// ============================================================================
+
+#[wasm_bindgen_test]
+fn test_checkbox_group_collects_checked_options() {
+    let form = mount(ONBOARDING, "onboarding");
+    set_checked(&form, "#role_developer", true);
+    click(&form, ".af-next");
+
+    set_checked(&form, "#languages_rust", true);
+    set_checked(&form, "#languages_go", true);
+    assert_eq!(field_error(&form, "languages"), None);
+
+    // Required: unchecking the last option empties the array
+    set_checked(&form, "#languages_rust", false);
+    assert_eq!(field_error(&form, "languages"), None);
+    set_checked(&form, "#languages_go", false);
+    assert!(field_error(&form, "languages").is_some());
+}
+
+// ============================================================================
+// Help tooltips
+// ============================================================================
+
+const HELP: &str = r#"<form data-af-form="help">
+  <div class="af-field" data-af-field="cvc">
+    <label for="cvc">CVC</label>
+    <button type="button" class="af-tooltip-toggle" aria-controls="cvc-tooltip" aria-expanded="false">?</button>
+    <span class="af-tooltip" id="cvc-tooltip" role="tooltip" hidden>On the back of your card</span>
+    <input type="text" name="cvc" id="cvc" aria-describedby="cvc-tooltip">
+  </div>
+</form>"#;
+
+#[wasm_bindgen_test]
+fn test_tooltip_toggles() {
+    let form = mount(HELP, "help");
+    let toggle = ".af-tooltip-toggle";
+    assert!(find(&form, "#cvc-tooltip").has_attribute("hidden"));
+
+    click(&form, toggle);
+    assert!(!find(&form, "#cvc-tooltip").has_attribute("hidden"));
+    assert_eq!(find(&form, toggle).get_attribute("aria-expanded").as_deref(), Some("true"));
+
+    click(&form, toggle);
+    assert!(find(&form, "#cvc-tooltip").has_attribute("hidden"));
+    assert_eq!(find(&form, toggle).get_attribute("aria-expanded").as_deref(), Some("false"));
+}
+
+// ============================================================================
+// Locations
+// ============================================================================
+
+const LOCATION: &str = r#"<form data-af-form="meetup">
+  <div class="af-field" data-af-field="spot" data-af-validation='{"required":true}'>
+    <label for="spot">Meeting point</label>
+    <div class="af-location" data-af-location="spot">
+      <button type="button" class="af-location-button" data-af-locate="spot" hidden>Use my location</button>
+      <label class="af-location-part">Latitude <input type="number" name="spot[lat]" id="spot" step="any"></label>
+      <label class="af-location-part">Longitude <input type="number" name="spot[lng]" id="spot-lng" step="any"></label>
+      <input type="hidden" name="spot[accuracy]" id="spot-accuracy">
+      <label class="af-location-part">Place <input type="text" name="spot[label]" id="spot-label"></label>
+      <span class="af-location-status" id="spot-status" role="status"></span>
+    </div>
+  </div>
+</form>"#;
+
+#[wasm_bindgen_test]
+fn test_location_collects_coordinates() {
+    let form = mount(LOCATION, "meetup");
+    // The browser can locate the device
+    assert!(!find(&form, "[data-af-locate]").has_attribute("hidden"));
+
+    fill(&form, "#spot", "91");
+    fill(&form, "#spot-lng", "0");
+    assert!(field_error(&form, "spot").is_some());
+
+    fill(&form, "#spot", "51.5");
+    fill(&form, "#spot-lng", "-0.12");
+    assert_eq!(field_error(&form, "spot"), None);
+
+    fill(&form, "#spot", "");
+    fill(&form, "#spot-lng", "");
+    assert!(field_error(&form, "spot").is_some());
+}
+
+// ============================================================================
+// Piping
+// ============================================================================
+
+const PIPING: &str = r#"<form data-af-form="survey">
+  <div class="af-field" data-af-field="first_name">
+    <label for="first_name">First name</label>
+    <input type="text" name="first_name" id="first_name">
+  </div>
+  <div class="af-field" data-af-field="plan">
+    <label for="plan">Plan</label>
+    <label><input type="radio" name="plan" id="plan_pro" value="pro"> Pro &amp; more</label>
+    <label><input type="radio" name="plan" id="plan_free" value="free"> Free</label>
+  </div>
+  <div class="af-field" data-af-field="rating">
+    <label for="rating">Hi <span class="af-pipe" data-af-pipe="first_name" data-af-fallback="there">there</span>, how do you like <span class="af-pipe" data-af-pipe="plan"></span>?</label>
+    <input type="number" name="rating" id="rating">
+  </div>
+</form>"#;
+
+#[wasm_bindgen_test]
+fn test_pipes_quote_answers() {
+    let form = mount(PIPING, "survey");
+    let label = || find(&form, "label[for=rating]").text_content().unwrap();
+    assert_eq!(label(), "Hi there, how do you like ?");
+
+    fill(&form, "#first_name", "<b>Ada</b>");
+    set_checked(&form, "#plan_pro", true);
+    assert_eq!(label(), "Hi <b>Ada</b>, how do you like Pro & more?");
+    // Quoted as text, never as markup
+    assert!(form.query_selector("label[for=rating] b").unwrap().is_none());
+
+    fill(&form, "#first_name", " ");
+    assert_eq!(label(), "Hi there, how do you like Pro & more?");
+}
+
+// ============================================================================
+// AJAX submit
+// ============================================================================
+
+#[wasm_bindgen_test]
+async fn test_submit_posts_values() {
+    let requests = stub_fetch(
+        201,
+        r#"{"success":true,"status":201,"data":{"id":"sub-1"}}"#,
+    );
+    let client = FormClient::new("https://forms.example.com/");
+    let values = js_sys::JSON::parse(r#"{"name":"Ada","languages":["rust","go"]}"#).unwrap();
+
+    let result = client.submit_form("onboarding", values).await;
+    restore_fetch();
+
+    let result = result.unwrap();
+    let id = js_sys::Reflect::get(&result, &"id".into()).unwrap();
+    assert_eq!(id.as_string().as_deref(), Some("sub-1"));
+
+    assert_eq!(requests.length(), 1);
+    let request = requests.get(0);
+    let get = |key: &str| {
+        js_sys::Reflect::get(&request, &key.into())
+            .unwrap()
+            .as_string()
+            .unwrap()
+    };
+    assert_eq!(get("url"), "https://forms.example.com/api/v1/forms/onboarding");
+    assert_eq!(get("method"), "POST");
+    let body: serde_json::Value = serde_json::from_str(&get("body")).unwrap();
+    assert_eq!(body["languages"], serde_json::json!(["rust", "go"]));
+}
+
+#[wasm_bindgen_test]
+async fn test_submit_rejects_with_validation_error() {
+    stub_fetch(
+        422,
+        r#"{"success":false,"status":422,"error":{"code":"VALIDATION_ERROR","message":"Invalid","details":{"email":["Required"]}}}"#,
+    );
+    let client = FormClient::new("https://forms.example.com");
+    let values = js_sys::JSON::parse(r#"{"name":"Ada"}"#).unwrap();
+
+    let error = client.submit_form("onboarding", values).await;
+    restore_fetch();
+
+    let error = error.unwrap_err();
+    let get = |key: &str| js_sys::Reflect::get(&error, &key.into()).unwrap();
+    assert_eq!(get("name").as_string().as_deref(), Some("AnyformError"));
+    assert_eq!(get("kind").as_string().as_deref(), Some("validation"));
+    assert_eq!(get("code").as_string().as_deref(), Some("VALIDATION_ERROR"));
+    assert_eq!(get("status").as_f64(), Some(422.0));
+}
@@ -60,6 +60,7 @@ export type {
   ValueType,
   ValidationRules,
   FieldOptionJson,
+  JumpRule,
   UiOptions,
   FieldJson,
   StepJson,
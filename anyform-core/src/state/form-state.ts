@@ -7,7 +7,7 @@
  * - Field values
  * - Validation errors
  * - Touched state (user interaction tracking)
- * - Multi-step navigation
+ * - Multi-step navigation, following jump rules
  * - Conditional visibility
  */
 
@@ -17,6 +17,7 @@ import type {
   FieldJson,
   JsonValue,
   ConditionRule,
+  JumpRule,
 } from '../types';
 import { validateField } from '../validation';
 import { evaluateCondition } from '../condition';
@@ -32,6 +33,8 @@ export class FormState {
   private _errors: Map<string, string[]>;
   private _touched: Set<string>;
   private _currentStepIndex: number;
+  /** Schema indices of the steps taken to reach the current one. */
+  private _history: number[];
 
   /**
    * Creates a new FormState from a form schema.
@@ -42,6 +45,7 @@ export class FormState {
     this._errors = new Map();
     this._touched = new Set();
     this._currentStepIndex = 0;
+    this._history = [];
 
     // Initialize with default values
     for (const step of schema.steps) {
@@ -278,21 +282,35 @@ export class FormState {
   }
 
   /**
-   * Moves to the next visible step. Returns true if successful.
+   * Moves to the step that follows, visible and picked by the jump rules.
+   * Returns true if successful, false once the form ends.
    */
   next_step(): boolean {
-    const visibleSteps = this.getVisibleStepIndices();
-    if (this._currentStepIndex + 1 < visibleSteps.length) {
-      this._currentStepIndex += 1;
-      return true;
-    }
-    return false;
+    const current = this.currentSchemaStep();
+    if (current === undefined) return false;
+    const next = this.followingStep(current);
+    if (next === undefined) return false;
+    const index = this.getVisibleStepIndices().indexOf(next);
+    if (index < 0) return false;
+    this._history.push(current);
+    this._currentStepIndex = index;
+    return true;
   }
 
   /**
-   * Moves to the previous visible step. Returns true if successful.
+   * Moves back to the step taken before the current one, or to the previous
+   * visible step. Returns true if successful.
    */
   prev_step(): boolean {
+    const visibleSteps = this.getVisibleStepIndices();
+    let step: number | undefined;
+    while ((step = this._history.pop()) !== undefined) {
+      const index = visibleSteps.indexOf(step);
+      if (index >= 0) {
+        this._currentStepIndex = index;
+        return true;
+      }
+    }
     if (this._currentStepIndex > 0) {
       this._currentStepIndex -= 1;
       return true;
@@ -309,6 +327,14 @@ export class FormState {
       const actualIndex = visibleSteps[visibleIndex];
       const step = this._schema.steps[actualIndex];
       if (step?.id === stepId) {
+        // Going back retraces the route; going elsewhere extends it
+        const taken = this._history.indexOf(actualIndex);
+        const current = this.currentSchemaStep();
+        if (taken >= 0) {
+          this._history.length = taken;
+        } else if (current !== undefined && current !== actualIndex) {
+          this._history.push(current);
+        }
         this._currentStepIndex = visibleIndex;
         return true;
       }
@@ -320,8 +346,8 @@ export class FormState {
    * Returns true if we can advance to the next step.
    */
   can_go_next(): boolean {
-    const visibleSteps = this.getVisibleStepIndices();
-    return this._currentStepIndex + 1 < visibleSteps.length;
+    const current = this.currentSchemaStep();
+    return current !== undefined && this.followingStep(current) !== undefined;
   }
 
   /**
@@ -333,18 +359,51 @@ export class FormState {
 
   /**
    * Returns progress as [current, total] (1-indexed for display).
+   *
+   * Steps count along the route: the ones taken so far and the ones the jump
+   * rules lead to with the current answers. With `settings.progress_mode`
+   * set to `'estimated'`, the total instead counts every step after the next
+   * jump whose condition holds or depends on unanswered fields, so it only
+   * shrinks as answers come in and the bar never moves backwards.
    */
   progress(): [number, number] {
-    const visibleSteps = this.getVisibleStepIndices();
-    return [this._currentStepIndex + 1, visibleSteps.length];
+    const current = this._history.length + 1;
+    const position = this.currentSchemaStep();
+    if (position === undefined) {
+      return [current, current];
+    }
+
+    if (this._schema.settings.progress_mode !== 'estimated') {
+      let remaining = 0;
+      for (
+        let step = this.followingStep(position);
+        step !== undefined;
+        step = this.followingStep(step)
+      ) {
+        remaining += 1;
+      }
+      return [current, current + remaining];
+    }
+
+    const start = this.jumpStart(position);
+    if (start === undefined) {
+      return [current, current];
+    }
+    const remaining = this._schema.steps
+      .slice(start)
+      .filter(
+        (step) =>
+          this.evaluateCondition(step.condition) ||
+          this.dependsOnUnanswered(step.condition)
+      ).length;
+    return [current, current + remaining];
   }
 
   /**
    * Returns true if currently on the last step.
    */
   is_last_step(): boolean {
-    const visibleSteps = this.getVisibleStepIndices();
-    return this._currentStepIndex + 1 >= visibleSteps.length;
+    return !this.can_go_next();
   }
 
   // ─────────────────────────────────────────────────────────────────────────
@@ -438,6 +497,57 @@ export class FormState {
     return false;
   }
 
+  private dependsOnUnanswered(condition: ConditionRule | undefined): boolean {
+    if (!condition) return false;
+    return conditionFields(condition).some((field) => {
+      const value = this._values.get(field);
+      return (
+        value === undefined ||
+        value === null ||
+        value === '' ||
+        (Array.isArray(value) && value.length === 0)
+      );
+    });
+  }
+
+  private currentSchemaStep(): number | undefined {
+    return this.getVisibleStepIndices()[this._currentStepIndex];
+  }
+
+  /**
+   * Returns the schema index of the step that follows step `from`, or
+   * `undefined` once the form ends.
+   */
+  private followingStep(from: number): number | undefined {
+    const start = this.jumpStart(from);
+    if (start === undefined) return undefined;
+    return this.getVisibleStepIndices().find((i) => i >= start);
+  }
+
+  /**
+   * Returns the schema index from which to look for the step that follows
+   * step `from`: the target of the first jump rule that applies, or the step
+   * after it. `undefined` if a rule ends the form.
+   *
+   * Rules naming no later step are skipped.
+   */
+  private jumpStart(from: number): number | undefined {
+    const step = this._schema.steps[from];
+    const rules: JumpRule[] = [
+      ...step.fields
+        .filter((field) => this.evaluateCondition(field.condition))
+        .flatMap((field) => field.next ?? []),
+      ...(step.next ?? []),
+    ];
+    for (const rule of rules) {
+      if (!this.evaluateCondition(rule.when)) continue;
+      if (rule.to === undefined) return undefined;
+      const target = this._schema.steps.findIndex((s) => s.id === rule.to);
+      if (target > from) return target;
+    }
+    return from + 1;
+  }
+
   private getVisibleStepIndices(): number[] {
     return this._schema.steps
       .map((step, i) => ({ step, i }))
@@ -450,3 +560,12 @@ export class FormState {
     return evaluateCondition(condition, this.get_values());
   }
 }
+
+/**
+ * Returns the names of the fields a condition reads.
+ */
+function conditionFields(condition: ConditionRule): string[] {
+  if ('and' in condition) return condition.and.flatMap(conditionFields);
+  if ('or' in condition) return condition.or.flatMap(conditionFields);
+  return [condition.field];
+}
@@ -7,6 +7,7 @@ export type {
   ValueType,
   ValidationRules,
   FieldOptionJson,
+  JumpRule,
   UiOptions,
   FieldJson,
   StepJson,
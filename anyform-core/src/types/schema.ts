@@ -41,7 +41,8 @@ export type ValueType =
   | 'range'
   | 'rating'
   | 'scale'
-  | 'nps';
+  | 'nps'
+  | 'repeater';
 
 /**
  * Validation rules for a field.
@@ -56,6 +57,13 @@ export interface ValidationRules {
   max_value?: number;
   min_selections?: number;
   max_selections?: number;
+  /** Fewest entries a repeatable group takes. */
+  min_rows?: number;
+  /** Most entries a repeatable group takes. */
+  max_rows?: number;
+  server_validate?: boolean;
+  /** Named validators the server also runs on the value. */
+  validators?: string[];
 }
 
 /**
@@ -69,6 +77,16 @@ export interface FieldOptionJson {
   order: number;
 }
 
+/**
+ * Where to go once a step is done.
+ */
+export interface JumpRule {
+  /** When the rule applies; always, if absent. */
+  when?: ConditionRule;
+  /** ID of the later step to go to; absent to end the form. */
+  to?: string;
+}
+
 /**
  * UI options for field rendering.
  */
@@ -96,8 +114,12 @@ export interface FieldJson {
   required?: boolean;
   validation: ValidationRules;
   condition?: ConditionRule;
+  /** Jump rules tried once the field's step is done, while it's shown. */
+  next?: JumpRule[];
   ui_options?: UiOptions;
   options: FieldOptionJson[];
+  /** Sub-fields of a repeatable group, filled in once per entry. */
+  fields?: FieldJson[];
   order: number;
 }
 
@@ -110,6 +132,14 @@ export interface StepJson {
   description?: string;
   order: number;
   condition?: ConditionRule;
+  /** The step's own jump rules, tried after its fields'. */
+  next?: JumpRule[];
+  /** When the step's content last changed. */
+  updated_at?: string;
+  /** Fingerprint of the step's content, changing whenever the step does. */
+  etag?: string;
+  /** Whether `fields` were left out of the form's skeleton (`?steps=ids`). */
+  lazy?: boolean;
   fields: FieldJson[];
 }
 
@@ -122,6 +152,8 @@ export interface FormSettings {
   success_message?: string;
   success_redirect?: string;
   show_progress?: boolean;
+  /** How `progress()` counts steps (see FormState.progress). */
+  progress_mode?: 'visible' | 'estimated';
   allow_save_draft?: boolean;
   action_url?: string;
   method?: string;
@@ -138,5 +170,9 @@ export interface FormJson {
   action_url?: string;
   action_method?: string;
   settings: FormSettings;
+  /** When the form last changed (pass as `?if_changed_since=`). */
+  updated_at?: string;
   steps: StepJson[];
+  /** With `?if_changed_since=`: IDs of the steps left out as unchanged. */
+  unchanged?: string[];
 }
@@ -4,7 +4,7 @@
  * Eliminates the need for a switch statement in user code.
  */
 
-import type { FieldJson, UseAnyFormReturn } from '../types';
+import type { FieldJson, LocationValue, UseAnyFormReturn } from '../types';
 
 export interface AutoFormFieldProps {
   /** Field definition from the form schema */
@@ -108,6 +108,82 @@ export function AutoFormField({
       );
     }
 
+    case 'image_choice': {
+      const props = form.getRadioGroupProps(field.name);
+      return (
+        <fieldset className={className}>
+          <legend>{field.label}</legend>
+          <div className="af-image-choices">
+            {props.options.map((opt) => (
+              <label key={opt.value} className="af-image-card">
+                <input {...props.getOptionProps(opt)} />
+                {opt.image_url && <img src={opt.image_url} alt="" loading="lazy" />}
+                <span className="af-image-caption">{opt.label}</span>
+              </label>
+            ))}
+          </div>
+          {renderErrors()}
+        </fieldset>
+      );
+    }
+
+    case 'location': {
+      const value = form.values[field.name] as Partial<LocationValue> | null | undefined;
+      // Without coordinates the answer is empty
+      const update = (part: Partial<LocationValue>) => {
+        const next = { ...value, ...part };
+        form.setValue(field.name, next.lat === undefined && next.lng === undefined ? null : next);
+      };
+      const coordinate = (raw: string) => (raw === '' ? undefined : Number(raw));
+      const locate = () =>
+        navigator.geolocation?.getCurrentPosition(({ coords }) =>
+          update({ lat: coords.latitude, lng: coords.longitude, accuracy: coords.accuracy })
+        );
+      return (
+        <fieldset className={className}>
+          <legend>{field.label}</legend>
+          <div className="af-location">
+            {typeof navigator !== 'undefined' && navigator.geolocation && (
+              <button type="button" className="af-location-button" onClick={locate}>
+                Use my location
+              </button>
+            )}
+            <label className="af-location-part">
+              Latitude{' '}
+              <input
+                type="number"
+                step="any"
+                min={-90}
+                max={90}
+                value={value?.lat ?? ''}
+                onChange={(e) => update({ lat: coordinate(e.target.value) })}
+              />
+            </label>
+            <label className="af-location-part">
+              Longitude{' '}
+              <input
+                type="number"
+                step="any"
+                min={-180}
+                max={180}
+                value={value?.lng ?? ''}
+                onChange={(e) => update({ lng: coordinate(e.target.value) })}
+              />
+            </label>
+            <label className="af-location-part">
+              Place{' '}
+              <input
+                type="text"
+                value={value?.label ?? ''}
+                onChange={(e) => update({ label: e.target.value || undefined })}
+              />
+            </label>
+          </div>
+          {renderErrors()}
+        </fieldset>
+      );
+    }
+
     case 'checkbox':
       return (
         <div className={className}>
@@ -55,6 +55,7 @@ export type {
   FieldJson,
   FieldOptionJson,
   FieldType,
+  LocationValue,
   FormSettings,
   ValidationRules,
   UiOptions,
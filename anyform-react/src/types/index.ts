@@ -8,6 +8,8 @@ export interface FieldOptionJson {
   label: string;
   value: string;
   order: number;
+  /** Picture shown on the option's card, for `image_choice` fields */
+  image_url?: string;
 }
 
 /** Validation rules for a field */
@@ -26,6 +28,10 @@ export interface ValidationRules {
   allowed_mime_types?: string[];
   min_date?: string;
   max_date?: string;
+  /** Also checked by the server's field validator */
+  server_validate?: boolean;
+  /** Named validators the server also runs on the value */
+  validators?: string[];
   custom?: unknown;
 }
 
@@ -94,6 +100,7 @@ export type FieldType =
   | 'select'
   | 'multi_select'
   | 'radio'
+  | 'image_choice'
   | 'checkbox'
   | 'date'
   | 'datetime'
@@ -106,7 +113,20 @@ export type FieldType =
   | 'rating'
   | 'scale'
   | 'nps'
-  | 'matrix';
+  | 'matrix'
+  | 'location';
+
+/** Answer to a `location` field */
+export interface LocationValue {
+  /** Latitude, from -90 to 90 */
+  lat: number;
+  /** Longitude, from -180 to 180 */
+  lng: number;
+  /** Radius of uncertainty in meters */
+  accuracy?: number;
+  /** Place name */
+  label?: string;
+}
 
 /** Field definition from form schema */
 export interface FieldJson {
@@ -142,11 +162,29 @@ export interface FormSettings {
   notify_emails?: string[];
   show_progress?: boolean;
   allow_partial_save?: boolean;
+  /** Multi-step forms save each completed step as a partial response */
+  save_each_step?: boolean;
   css_class?: string;
   action_url?: string;
   method?: string;
   is_quiz?: boolean;
   show_answers?: boolean;
+  /** Responses are anonymous; show `anonymous_notice` to respondents */
+  anonymous?: boolean;
+  anonymous_notice?: string;
+  /** Submissions require an identified respondent */
+  identified?: boolean;
+  /** Close after this many accepted responses */
+  close_after?: number;
+  closed_message?: string;
+  /** When the form opens for responses (RFC 3339) */
+  opens_at?: string;
+  /** When the form stops taking responses (RFC 3339) */
+  closes_at?: string;
+  /** Shown instead of the form before `opens_at` */
+  not_open_message?: string;
+  /** Fraction of submissions recorded (0-1) */
+  sample_rate?: number;
   custom?: unknown;
 }
 
@@ -158,6 +196,8 @@ export interface FormJson {
   description?: string;
   action_url?: string;
   action_method?: string;
+  /** The form isn't accepting responses; show `settings.closed_message`, or `settings.not_open_message` before `settings.opens_at` */
+  closed?: boolean;
   settings: FormSettings;
   steps: StepJson[];
 }
@@ -83,6 +83,7 @@ export function getInputClass(
     case 'checkbox':
       return classes.checkbox;
     case 'radio':
+    case 'image_choice':
       return classes.radio;
     default:
       return classes.input;
@@ -119,6 +120,7 @@ export function getInputType(fieldType: FieldType): string {
     case 'checkbox':
       return 'checkbox';
     case 'radio':
+    case 'image_choice':
       return 'radio';
     default:
       return 'text';
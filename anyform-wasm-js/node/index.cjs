@@ -0,0 +1,23 @@
+/**
+ * @wordpuppi/anyform-wasm-js - Node.js / SSR entry (CommonJS)
+ *
+ * Loads the Node.js build of anyform-client, which instantiates the WASM
+ * module synchronously, so `init()` is a no-op kept for parity with the
+ * browser entry. The build has no DOM support: `hydrate()` and
+ * `hydrate_all()` do nothing.
+ */
+'use strict';
+
+const wasm = require('../wasm-node/anyform_client.js');
+
+async function init() {}
+
+module.exports = {
+  init,
+  default: init,
+  FormClient: wasm.FormClient,
+  FormState: wasm.FormState,
+  hydrate() {},
+  hydrate_all() {},
+  version: wasm.version,
+};
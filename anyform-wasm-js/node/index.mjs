@@ -0,0 +1,17 @@
+/**
+ * @wordpuppi/anyform-wasm-js - Node.js / SSR entry (ES module)
+ *
+ * See index.cjs: the Node.js build needs no `init()` and has no DOM
+ * support.
+ */
+import wasm from '../wasm-node/anyform_client.js';
+
+export const { FormClient, FormState, version } = wasm;
+
+export async function init() {}
+
+export function hydrate() {}
+
+export function hydrate_all() {}
+
+export default init;
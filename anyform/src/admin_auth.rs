@@ -0,0 +1,318 @@
+//! Authentication for the admin routes.
+//!
+//! Admin routes are open unless an [`AdminAuthorizer`] is registered with
+//! [`AnyFormRouterBuilder::admin_auth`](crate::AnyFormRouterBuilder::admin_auth).
+//! Every `/admin/...` request is then passed to it first, except `OPTIONS`,
+//! which browsers send without credentials and which only lists a route's
+//! methods; public form routes are never checked. Two implementations are included:
+//!
+//! | Authorizer | Credential |
+//! |------------|------------|
+//! | [`BearerTokenAuth`] | `Authorization: Bearer <token>` |
+//! | [`ApiKeyAuth`] | `X-Api-Key: <key>` (or another header) |
+//!
+//! Both accept read-only credentials, which may list and read but get
+//! `403 FORBIDDEN` for anything else. Requests without a valid credential get
+//! `401 UNAUTHORIZED`.
+//!
+//! Implement [`AdminAuthorizer`] to check credentials against your own user
+//! store instead:
+//!
+//! ```rust,ignore
+//! use anyform::admin_auth::AdminAuthorizer;
+//! use anyform::FormError;
+//! use http::request::Parts;
+//!
+//! struct SessionAuth;
+//!
+//! #[async_trait::async_trait]
+//! impl AdminAuthorizer for SessionAuth {
+//!     async fn authorize(&self, request: &Parts) -> Result<(), FormError> {
+//!         let user = session_user(&request.headers).await.ok_or(FormError::Unauthorized)?;
+//!         if user.is_admin { Ok(()) } else { Err(FormError::Forbidden) }
+//!     }
+//! }
+//! ```
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{header, request::Parts, HeaderName, HeaderValue, Method};
+use sha2::{Digest, Sha256};
+
+use crate::error::FormError;
+
+/// Default header read by [`ApiKeyAuth`].
+pub const API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+
+/// Decides whether a request may use the admin routes.
+#[async_trait]
+pub trait AdminAuthorizer: Send + Sync + 'static {
+    /// Returns `Ok(())` to let the request through,
+    /// [`FormError::Unauthorized`] when it carries no valid credential, or
+    /// [`FormError::Forbidden`] when the credential doesn't allow it.
+    async fn authorize(&self, request: &Parts) -> Result<(), FormError>;
+
+    /// `WWW-Authenticate` challenge sent with `401` responses, if any.
+    fn challenge(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl std::fmt::Debug for dyn AdminAuthorizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AdminAuthorizer")
+    }
+}
+
+/// What a credential allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    Full,
+    ReadOnly,
+}
+
+/// A set of accepted credentials, stored as digests.
+#[derive(Clone, Default)]
+struct Credentials(Vec<([u8; 32], Access)>);
+
+impl Credentials {
+    fn add(&mut self, credential: impl AsRef<[u8]>, access: Access) {
+        self.0.push((Sha256::digest(credential.as_ref()).into(), access));
+    }
+
+    /// Returns what `candidate` allows, if it is accepted.
+    ///
+    /// Compares digests, so timing reveals nothing about the credentials.
+    fn access(&self, candidate: &[u8]) -> Option<Access> {
+        let candidate: [u8; 32] = Sha256::digest(candidate).into();
+        self.0
+            .iter()
+            .filter(|(digest, _)| {
+                digest
+                    .iter()
+                    .zip(candidate.iter())
+                    .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+                    == 0
+            })
+            .map(|(_, access)| *access)
+            .max_by_key(|access| *access == Access::Full)
+    }
+
+    /// Checks the credential presented with `request`.
+    fn check(&self, request: &Parts, candidate: Option<&[u8]>) -> Result<(), FormError> {
+        match candidate.and_then(|c| self.access(c)) {
+            None => Err(FormError::Unauthorized),
+            Some(Access::ReadOnly) if !is_read(&request.method) => Err(FormError::Forbidden),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+/// Returns true for methods that never change anything.
+fn is_read(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Accepts `Authorization: Bearer <token>` with any of the configured tokens.
+///
+/// ```rust,ignore
+/// let router = AnyFormRouter::builder()
+///     .database(db)
+///     .enable_admin(true)
+///     .admin_auth(BearerTokenAuth::new(admin_token).read_only_token(dashboard_token))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct BearerTokenAuth {
+    tokens: Credentials,
+}
+
+impl BearerTokenAuth {
+    /// Accepts `token` with full access.
+    #[must_use]
+    pub fn new(token: impl AsRef<[u8]>) -> Self {
+        Self {
+            tokens: Credentials::default(),
+        }
+        .token(token)
+    }
+
+    /// Also accepts `token` with full access.
+    #[must_use]
+    pub fn token(mut self, token: impl AsRef<[u8]>) -> Self {
+        self.tokens.add(token, Access::Full);
+        self
+    }
+
+    /// Also accepts `token` for reads only.
+    #[must_use]
+    pub fn read_only_token(mut self, token: impl AsRef<[u8]>) -> Self {
+        self.tokens.add(token, Access::ReadOnly);
+        self
+    }
+}
+
+#[async_trait]
+impl AdminAuthorizer for BearerTokenAuth {
+    async fn authorize(&self, request: &Parts) -> Result<(), FormError> {
+        let token = request
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                let (scheme, token) = value.split_once(' ')?;
+                scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+            });
+        self.tokens.check(request, token.map(str::as_bytes))
+    }
+
+    fn challenge(&self) -> Option<&'static str> {
+        Some("Bearer realm=\"anyform\"")
+    }
+}
+
+impl std::fmt::Debug for BearerTokenAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("BearerTokenAuth(..)")
+    }
+}
+
+/// Accepts any of the configured keys in a request header
+/// ([`API_KEY_HEADER`] unless set otherwise).
+#[derive(Clone)]
+pub struct ApiKeyAuth {
+    header: HeaderName,
+    keys: Credentials,
+}
+
+impl ApiKeyAuth {
+    /// Accepts `key` with full access.
+    #[must_use]
+    pub fn new(key: impl AsRef<[u8]>) -> Self {
+        Self {
+            header: API_KEY_HEADER,
+            keys: Credentials::default(),
+        }
+        .key(key)
+    }
+
+    /// Also accepts `key` with full access.
+    #[must_use]
+    pub fn key(mut self, key: impl AsRef<[u8]>) -> Self {
+        self.keys.add(key, Access::Full);
+        self
+    }
+
+    /// Also accepts `key` for reads only.
+    #[must_use]
+    pub fn read_only_key(mut self, key: impl AsRef<[u8]>) -> Self {
+        self.keys.add(key, Access::ReadOnly);
+        self
+    }
+
+    /// Reads the key from `header` instead of [`API_KEY_HEADER`].
+    #[must_use]
+    pub fn header(mut self, header: HeaderName) -> Self {
+        self.header = header;
+        self
+    }
+}
+
+#[async_trait]
+impl AdminAuthorizer for ApiKeyAuth {
+    async fn authorize(&self, request: &Parts) -> Result<(), FormError> {
+        let key = request.headers.get(&self.header).map(HeaderValue::as_bytes);
+        self.keys.check(request, key)
+    }
+}
+
+impl std::fmt::Debug for ApiKeyAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKeyAuth")
+            .field("header", &self.header)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Middleware turning away requests the authorizer rejects.
+pub(crate) async fn require_admin(
+    authorizer: Arc<dyn AdminAuthorizer>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if req.method() == Method::OPTIONS {
+        return next.run(req).await;
+    }
+    let (parts, body) = req.into_parts();
+    if let Err(err) = authorizer.authorize(&parts).await {
+        let unauthorized = matches!(err, FormError::Unauthorized);
+        let mut response = err.into_response();
+        if let Some(challenge) = authorizer.challenge().filter(|_| unauthorized) {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static(challenge));
+        }
+        return response;
+    }
+    next.run(Request::from_parts(parts, body)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(method: Method, header: Option<(&str, &str)>) -> Parts {
+        let mut builder = http::Request::builder().method(method).uri("/admin/forms");
+        if let Some((name, value)) = header {
+            builder = builder.header(name, value);
+        }
+        builder.body(()).unwrap().into_parts().0
+    }
+
+    async fn bearer(auth: &BearerTokenAuth, method: Method, value: &str) -> Result<(), FormError> {
+        auth.authorize(&parts(method, Some(("authorization", value)))).await
+    }
+
+    #[tokio::test]
+    async fn test_bearer_tokens() {
+        let auth = BearerTokenAuth::new("s3cret").read_only_token("viewer");
+
+        assert!(bearer(&auth, Method::DELETE, "Bearer s3cret").await.is_ok());
+        assert!(bearer(&auth, Method::GET, "bearer viewer").await.is_ok());
+        assert!(matches!(
+            bearer(&auth, Method::POST, "Bearer viewer").await,
+            Err(FormError::Forbidden)
+        ));
+        assert!(matches!(
+            bearer(&auth, Method::GET, "Basic s3cret").await,
+            Err(FormError::Unauthorized)
+        ));
+        assert!(matches!(
+            auth.authorize(&parts(Method::GET, None)).await,
+            Err(FormError::Unauthorized)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_api_key_header() {
+        let auth = ApiKeyAuth::new("key").header(HeaderName::from_static("x-admin-key"));
+
+        assert!(auth.authorize(&parts(Method::GET, Some(("x-admin-key", "key")))).await.is_ok());
+        assert!(auth.authorize(&parts(Method::GET, Some(("x-api-key", "key")))).await.is_err());
+    }
+
+    #[test]
+    fn test_full_access_wins_over_read_only() {
+        let mut credentials = Credentials::default();
+        credentials.add("shared", Access::ReadOnly);
+        credentials.add("shared", Access::Full);
+        assert_eq!(credentials.access(b"shared"), Some(Access::Full));
+        assert_eq!(credentials.access(b"other"), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! Tagging free-text answers with sentiment and themes.
+//!
+//! Register a [`TextAnalyzer`] with
+//! [`AnyFormRouterBuilder::text_analyzer`](crate::AnyFormRouterBuilder::text_analyzer)
+//! to analyze each submission's free-text answers (`textarea` fields, except
+//! those excluded from analytics by their classification) as it's stored.
+//! The results are kept in the submission's `text_analysis` metadata, by
+//! field name, even for anonymous forms, and
+//! [`Analytics`](crate::services::Analytics) sums them up per field: how
+//! many answers were positive, neutral or negative, and how often each tag
+//! came up.
+//!
+//! [`KeywordAnalyzer`] is a simple built-in analyzer: it scores sentiment
+//! from a word list and tags answers mentioning the keywords of each theme
+//! you give it. Implement the trait yourself to call a language model or an
+//! NLP service.
+//!
+//! ```rust,ignore
+//! use anyform::analysis::KeywordAnalyzer;
+//!
+//! let router = AnyFormRouter::builder()
+//!     .database(db)
+//!     .text_analyzer(
+//!         KeywordAnalyzer::new()
+//!             .tag("pricing", ["price", "expensive", "cost*"])
+//!             .tag("support", ["support", "helpdesk", "agent*"]),
+//!     )
+//!     .build();
+//! ```
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::field;
+use crate::schema::{FieldValue, ValueType};
+
+/// Sentiment at or above which an answer counts as positive, and at or
+/// below the negative of which it counts as negative.
+pub const SENTIMENT_THRESHOLD: f64 = 0.2;
+
+/// What an analyzer found in one free-text answer.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextAnalysis {
+    /// From -1 (negative) to 1 (positive); `None` when the analyzer can't
+    /// tell.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sentiment: Option<f64>,
+    /// Themes the answer mentions, e.g. `pricing`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl TextAnalysis {
+    /// Returns how the sentiment reads, per [`SENTIMENT_THRESHOLD`].
+    #[must_use]
+    pub fn sentiment_label(&self) -> Option<Sentiment> {
+        self.sentiment.map(Sentiment::of)
+    }
+}
+
+/// How a sentiment score reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sentiment {
+    Positive,
+    Neutral,
+    Negative,
+}
+
+impl Sentiment {
+    /// Returns how the score `sentiment`, from -1 to 1, reads.
+    #[must_use]
+    pub fn of(sentiment: f64) -> Self {
+        if sentiment >= SENTIMENT_THRESHOLD {
+            Self::Positive
+        } else if sentiment <= -SENTIMENT_THRESHOLD {
+            Self::Negative
+        } else {
+            Self::Neutral
+        }
+    }
+}
+
+/// Analyzes free-text answers as submissions are stored.
+#[async_trait]
+pub trait TextAnalyzer: Send + Sync + 'static {
+    /// Analyzes the answer `text` to `field`.
+    ///
+    /// Called before the submission is stored, so keep it quick; answers
+    /// it can't analyze (e.g. when a service is down) can come back empty.
+    async fn analyze(&self, field: &field::Model, text: &str) -> TextAnalysis;
+}
+
+impl std::fmt::Debug for dyn TextAnalyzer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TextAnalyzer")
+    }
+}
+
+/// Words that make an answer read positive.
+const POSITIVE_WORDS: &[&str] = &[
+    "amazing", "awesome", "beautiful", "best", "brilliant", "clean", "clear", "comfortable",
+    "easy", "excellent", "fantastic", "fast", "friendly", "fun", "good", "great", "happy",
+    "helpful", "intuitive", "like", "love", "loved", "lovely", "nice", "perfect", "pleasant",
+    "quick", "recommend", "reliable", "simple", "smooth", "thanks", "useful", "wonderful",
+];
+
+/// Words that make an answer read negative.
+const NEGATIVE_WORDS: &[&str] = &[
+    "angry", "annoying", "awful", "bad", "broken", "bug", "buggy", "confusing", "crash",
+    "crashed", "difficult", "disappointed", "disappointing", "expensive", "frustrating", "hard",
+    "hate", "horrible", "poor", "rude", "slow", "terrible", "unclear", "unhappy", "unusable",
+    "useless", "worse", "worst", "wrong",
+];
+
+/// Words that flip the sentiment of the next few words.
+const NEGATIONS: &[&str] = &["not", "no", "never", "nothing", "hardly", "without"];
+
+/// How many words after a negation it flips.
+const NEGATION_REACH: usize = 2;
+
+/// A simple analyzer scoring sentiment from word lists and tagging themes
+/// by keyword.
+///
+/// Sentiment is the balance of positive and negative words, from -1 to 1,
+/// with words shortly after a negation (`not`, `never`, `don't`...)
+/// counting the other way; answers with neither score 0. English word
+/// lists are built in; extend them with [`Self::positive`] and
+/// [`Self::negative`]. Matching ignores case.
+#[derive(Debug, Clone)]
+pub struct KeywordAnalyzer {
+    positive: HashSet<String>,
+    negative: HashSet<String>,
+    tags: Vec<(String, Vec<String>)>,
+}
+
+impl Default for KeywordAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeywordAnalyzer {
+    /// Creates an analyzer with the built-in word lists and no tags.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            positive: POSITIVE_WORDS.iter().map(|w| (*w).to_string()).collect(),
+            negative: NEGATIVE_WORDS.iter().map(|w| (*w).to_string()).collect(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Tags answers mentioning any of `keywords` with `tag`. Keywords are
+    /// words; end one with `*` to match words starting with it (e.g.
+    /// `cost*` for `costs` and `costly`).
+    #[must_use]
+    pub fn tag<I, S>(mut self, tag: impl Into<String>, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let keywords = keywords
+            .into_iter()
+            .map(|k| k.as_ref().trim().to_lowercase())
+            .filter(|k| !k.is_empty())
+            .collect();
+        self.tags.push((tag.into(), keywords));
+        self
+    }
+
+    /// Adds words that make an answer read positive.
+    #[must_use]
+    pub fn positive<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.positive
+            .extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Adds words that make an answer read negative.
+    #[must_use]
+    pub fn negative<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.negative
+            .extend(words.into_iter().map(|w| w.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Analyzes `text`.
+    #[must_use]
+    pub fn analyze_text(&self, text: &str) -> TextAnalysis {
+        let words = words(text);
+
+        let (mut positive, mut negative) = (0u32, 0u32);
+        let mut negated = 0;
+        for word in &words {
+            if is_negation(word) {
+                negated = NEGATION_REACH;
+                continue;
+            }
+            let flipped = negated > 0;
+            negated = negated.saturating_sub(1);
+            let valence = if self.positive.contains(word) {
+                1
+            } else if self.negative.contains(word) {
+                -1
+            } else {
+                continue;
+            };
+            match (valence > 0) != flipped {
+                true => positive += 1,
+                false => negative += 1,
+            }
+        }
+        let sentiment = match positive + negative {
+            0 => 0.0,
+            total => (f64::from(positive) - f64::from(negative)) / f64::from(total),
+        };
+
+        let tags = self
+            .tags
+            .iter()
+            .filter(|(_, keywords)| {
+                keywords.iter().any(|keyword| match keyword.strip_suffix('*') {
+                    Some(prefix) => words.iter().any(|w| w.starts_with(prefix)),
+                    None => words.contains(keyword),
+                })
+            })
+            .map(|(tag, _)| tag.clone())
+            .collect();
+
+        TextAnalysis {
+            sentiment: Some(sentiment),
+            tags,
+        }
+    }
+}
+
+#[async_trait]
+impl TextAnalyzer for KeywordAnalyzer {
+    async fn analyze(&self, _field: &field::Model, text: &str) -> TextAnalysis {
+        self.analyze_text(text)
+    }
+}
+
+/// Returns the lowercased words of `text`, keeping apostrophes within them
+/// (`don't`).
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+        .map(|w| w.trim_matches(|c| c == '\'' || c == '’').to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+fn is_negation(word: &str) -> bool {
+    NEGATIONS.contains(&word) || word.ends_with("n't") || word.ends_with("n’t")
+}
+
+/// Returns true if answers to `field` are analyzed: top-level `textarea`
+/// fields included in analytics.
+pub(crate) fn is_free_text(field: &field::Model) -> bool {
+    field.parent_id.is_none()
+        && field.value_type() == Some(ValueType::Textarea)
+        && field.classification().in_analytics()
+}
+
+/// Analyzes a submission's non-empty free-text answers, by field name.
+pub async fn analyze_answers(
+    analyzer: &dyn TextAnalyzer,
+    fields: &[field::Model],
+    data: &HashMap<String, FieldValue>,
+) -> BTreeMap<String, TextAnalysis> {
+    let mut analyses = BTreeMap::new();
+    for field in fields.iter().filter(|f| is_free_text(f)) {
+        let text = data
+            .get(&field.name)
+            .and_then(FieldValue::as_str)
+            .map(str::trim)
+            .filter(|t| !t.is_empty());
+        if let Some(text) = text {
+            analyses.insert(field.name.clone(), analyzer.analyze(field, text).await);
+        }
+    }
+    analyses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentiment() {
+        let analyzer = KeywordAnalyzer::new();
+        let score = |text: &str| analyzer.analyze_text(text).sentiment.unwrap();
+
+        assert_eq!(score("Great event, lovely venue!"), 1.0);
+        assert_eq!(score("Checkout was SLOW and confusing."), -1.0);
+        assert_eq!(score("Great talks, but the wifi was terrible"), 0.0);
+        assert_eq!(score("It was on Tuesday"), 0.0);
+        assert_eq!(score("The food was not good"), -1.0);
+        assert_eq!(score("I don't hate it"), 1.0);
+        assert_eq!(score("Not very good"), -1.0);
+        assert_eq!(Sentiment::of(score("Not bad, not great")), Sentiment::Neutral);
+    }
+
+    #[test]
+    fn test_tags() {
+        let analyzer = KeywordAnalyzer::new()
+            .tag("pricing", ["price", "cost*"])
+            .tag("support", ["Support"])
+            .negative(["pricey"]);
+        let analysis = analyzer.analyze_text("Costly and pricey, though SUPPORT helped");
+        assert_eq!(analysis.tags, vec!["pricing", "support"]);
+        assert_eq!(analysis.sentiment_label(), Some(Sentiment::Negative));
+
+        assert!(analyzer.analyze_text("Prices were fine").tags.is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+//! Serving the prebuilt WASM hydration client.
+//!
+//! With the `assets` feature enabled, [`AnyFormRouter`](crate::AnyFormRouter)
+//! serves the anyform-client package under `/forms/assets/`:
+//!
+//! - `af-client.js` — bootstrap module loaded by [`HtmlRenderer`](crate::HtmlRenderer);
+//!   initializes the WASM module and hydrates every form on the page
+//! - `anyform_client.{version}.js` — wasm-bindgen JS glue
+//! - `anyform_client_bg.{version}.wasm` — the WASM module
+//!
+//! Versioned files are served with long-lived immutable cache headers; the
+//! bootstrap is revalidated on every load so upgrades take effect immediately.
+//! Rendered pages load the bootstrap with a `?v=` pin and an SRI `integrity`
+//! hash, and the router warns at startup if the bundled client was built for
+//! a different JSON schema version than the server emits.
+
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use axum::{
+    extract::{Path, State},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use http::{header, StatusCode};
+use sha2::{Digest, Sha384};
+
+use crate::render::{is_compatible_client_version, SCHEMA_VERSION};
+
+/// Path under which client assets are mounted.
+pub const ASSETS_PATH: &str = "/forms/assets";
+
+const EMBEDDED_JS: &[u8] = include_bytes!("../../anyform-wasm-js/wasm/anyform_client.js");
+const EMBEDDED_WASM: &[u8] = include_bytes!("../../anyform-wasm-js/wasm/anyform_client_bg.wasm");
+const EMBEDDED_PACKAGE_JSON: &str = include_str!("../../anyform-wasm-js/wasm/package.json");
+
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+const REVALIDATE: &str = "no-cache";
+
+/// Source of the anyform-client package files.
+#[derive(Debug, Clone, Default)]
+pub enum ClientAssets {
+    /// The package bundled into the binary at compile time.
+    #[default]
+    Embedded,
+    /// A wasm-pack output directory read at request time.
+    ///
+    /// Must contain `anyform_client.js`, `anyform_client_bg.wasm` and
+    /// `package.json`.
+    Directory(PathBuf),
+}
+
+impl ClientAssets {
+    /// Uses the package bundled into the binary.
+    #[must_use]
+    pub fn embedded() -> Self {
+        Self::Embedded
+    }
+
+    /// Uses a wasm-pack output directory.
+    #[must_use]
+    pub fn directory(path: impl Into<PathBuf>) -> Self {
+        Self::Directory(path.into())
+    }
+
+    /// Returns the client package version, used in versioned filenames.
+    ///
+    /// Falls back to `"0.0.0"` when the package manifest is missing or
+    /// unreadable.
+    pub fn version(&self) -> String {
+        match self {
+            Self::Embedded => {
+                static VERSION: OnceLock<String> = OnceLock::new();
+                VERSION
+                    .get_or_init(|| package_version(EMBEDDED_PACKAGE_JSON))
+                    .clone()
+            }
+            Self::Directory(dir) => std::fs::read_to_string(dir.join("package.json"))
+                .map(|s| package_version(&s))
+                .unwrap_or_else(|_| package_version("")),
+        }
+    }
+
+    /// Returns the subresource integrity hash (`sha384-...`) of the bootstrap.
+    pub fn integrity(&self) -> String {
+        let digest = Sha384::digest(bootstrap_module(&self.version()).as_bytes());
+        format!("sha384-{}", BASE64.encode(digest))
+    }
+
+    /// Checks the client version against the server's JSON schema version.
+    ///
+    /// Logs a warning and returns `false` when they are incompatible.
+    pub fn check_compatibility(&self) -> bool {
+        let version = self.version();
+        let compatible = is_compatible_client_version(&version);
+        if !compatible {
+            tracing::warn!(
+                client_version = %version,
+                schema_version = SCHEMA_VERSION,
+                "anyform-client bundle does not match the server's JSON schema version; \
+                 hydration may fail. Rebuild the client or serve a matching --assets-dir."
+            );
+        }
+        compatible
+    }
+
+    /// Returns the base URL to pass to `HtmlOptions::wasm_base_url`.
+    #[must_use]
+    pub fn base_url(&self) -> &'static str {
+        ASSETS_PATH
+    }
+
+    async fn read(&self, name: &str) -> Option<Vec<u8>> {
+        match self {
+            Self::Embedded => match name {
+                "anyform_client.js" => Some(EMBEDDED_JS.to_vec()),
+                "anyform_client_bg.wasm" => Some(EMBEDDED_WASM.to_vec()),
+                _ => None,
+            },
+            Self::Directory(dir) => tokio::fs::read(dir.join(name)).await.ok(),
+        }
+    }
+}
+
+/// Builds the asset routes, mounted by the router.
+pub(crate) fn routes<S>(assets: ClientAssets) -> Router<S> {
+    Router::new()
+        .route(&format!("{ASSETS_PATH}/{{file}}"), get(serve_asset))
+        .with_state(Arc::new(assets))
+}
+
+/// Serves a single client asset by filename.
+async fn serve_asset(
+    Path(file): Path<String>,
+    State(assets): State<Arc<ClientAssets>>,
+) -> Response {
+    let version = assets.version();
+
+    if file == "af-client.js" {
+        return (
+            [
+                (header::CONTENT_TYPE, "text/javascript; charset=utf-8"),
+                (header::CACHE_CONTROL, REVALIDATE),
+            ],
+            bootstrap_module(&version),
+        )
+            .into_response();
+    }
+
+    let (source, content_type) = if file == format!("anyform_client.{version}.js") {
+        ("anyform_client.js", "text/javascript; charset=utf-8")
+    } else if file == format!("anyform_client_bg.{version}.wasm") {
+        ("anyform_client_bg.wasm", "application/wasm")
+    } else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match assets.read(source).await {
+        Some(bytes) => (
+            [
+                (header::CONTENT_TYPE, content_type),
+                (header::CACHE_CONTROL, IMMUTABLE),
+            ],
+            bytes,
+        )
+            .into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Returns the bootstrap module that loads the versioned client and hydrates.
+fn bootstrap_module(version: &str) -> String {
+    format!(
+        r#"import init, {{ hydrate_all }} from "./anyform_client.{version}.js";
+await init({{ module_or_path: new URL("./anyform_client_bg.{version}.wasm", import.meta.url) }});
+hydrate_all();
+"#
+    )
+}
+
+/// Extracts `version` from a package.json document.
+fn package_version(package_json: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(package_json)
+        .ok()
+        .and_then(|v| v.get("version")?.as_str().map(String::from))
+        .unwrap_or_else(|| "0.0.0".to_string())
+}
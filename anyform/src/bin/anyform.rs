@@ -2,14 +2,17 @@
 
 use anyhow::Result;
 use anyform::commands;
-use anyform::commands::{FormAction, SubmissionAction};
-use anyform::AnyFormRouter;
-use axum::{routing::get, Router};
+use anyform::commands::{BenchAction, DbAction, FormAction, ProjectionAction, SubmissionAction};
+use anyform::assets::ClientAssets;
+use anyform::database::SqliteConfig;
+use anyform::seed::{self, Seeder};
+use anyform::routing::{ALLOWED_HEADERS, ALLOWED_METHODS};
+use anyform::{AnyFormRouter, BearerTokenAuth, EncryptionKey};
 use clap::{Parser, Subcommand};
-use http::{header, Method};
-use sea_orm::{Database, DatabaseConnection};
+use sea_orm::DatabaseConnection;
 use anyform::MigratorTrait;
 use std::path::PathBuf;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
 use tower_http::cors::{Any, CorsLayer};
@@ -22,6 +25,14 @@ struct Cli {
     #[arg(short, long, env = "DATABASE_URL")]
     database_url: Option<String>,
 
+    /// How long SQLite connections wait for a locked database, in milliseconds
+    #[arg(long, global = true, default_value = "5000")]
+    busy_timeout: u64,
+
+    /// Use SQLite's rollback journal instead of WAL
+    #[arg(long, global = true)]
+    no_wal: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -78,8 +89,30 @@ enum Commands {
         action: SubmissionAction,
     },
 
-    /// Seed example forms into database
+    /// Database maintenance
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+
+    /// Rebuild data derived from submissions
+    Projections {
+        #[command(subcommand)]
+        action: ProjectionAction,
+    },
+
+    /// Load testing and render benchmarks
+    Bench {
+        #[command(subcommand)]
+        action: BenchAction,
+    },
+
+    /// Seed example forms (or your own) into database
     Seed {
+        /// Seed the forms in this directory of JSON files instead of the examples
+        #[arg(long, value_name = "DIR")]
+        from: Option<PathBuf>,
+
         /// Only seed the contact form
         #[arg(long)]
         contact_only: bool,
@@ -92,6 +125,10 @@ enum Commands {
         #[arg(long)]
         quiz_only: bool,
 
+        /// Only seed the multi-step onboarding form
+        #[arg(long)]
+        onboarding_only: bool,
+
         /// Clear seeded forms instead of creating them
         #[arg(long)]
         clear: bool,
@@ -111,9 +148,38 @@ enum Commands {
         #[arg(long)]
         no_admin: bool,
 
+        /// Require `Authorization: Bearer <TOKEN>` on admin routes
+        #[arg(long, env = "ANYFORM_ADMIN_TOKEN", hide_env_values = true)]
+        admin_token: Option<String>,
+
+        /// Encrypt answers to sensitive fields with this key (32 bytes in
+        /// base64, e.g. from `openssl rand -base64 32`)
+        #[arg(long, env = "ANYFORM_ENCRYPTION_KEY", hide_env_values = true)]
+        encryption_key: Option<String>,
+
         /// Enable CORS for specified origin (use '*' for any)
         #[arg(long)]
         cors: Option<String>,
+
+        /// Serve the WASM client from a wasm-pack output directory
+        /// instead of the embedded bundle
+        #[arg(long)]
+        assets_dir: Option<PathBuf>,
+
+        /// Serve forms but reject submissions and other writes with 503
+        /// (for read replicas and maintenance windows); skips migrations
+        #[arg(long)]
+        read_only: bool,
+
+        /// Check the database and configuration, print a report and exit
+        /// without migrating or serving
+        #[arg(long)]
+        check: bool,
+
+        /// Development mode: seed the example forms and serve a gallery of
+        /// them at /forms/examples
+        #[arg(long, conflicts_with = "read_only")]
+        dev: bool,
     },
 }
 
@@ -126,6 +192,9 @@ async fn main() -> Result<()> {
         .database_url
         .or_else(|| std::env::var("DATABASE_URL").ok())
         .unwrap_or_else(|| "sqlite:./anyform.db?mode=rwc".to_string());
+    let sqlite = SqliteConfig::new()
+        .busy_timeout(Duration::from_millis(cli.busy_timeout))
+        .wal(!cli.no_wal);
 
     match cli.command {
         Commands::Init {
@@ -164,7 +233,7 @@ async fn main() -> Result<()> {
             };
 
             println!("Initializing database...");
-            let db = Database::connect(&db_url).await?;
+            let db = sqlite.connect(&db_url).await?;
 
             // Run migrations
             println!("Running migrations...");
@@ -173,8 +242,8 @@ async fn main() -> Result<()> {
             // Seed if requested
             if seed {
                 println!("Seeding example forms...");
-                anyform::seed_all(&db).await?;
-                println!("Created: contact, feedback, quiz");
+                let report = Seeder::examples().run(&db).await?;
+                println!("{report}");
             }
 
             println!("Done! Database initialized at: {}", db_url);
@@ -186,7 +255,7 @@ async fn main() -> Result<()> {
             status,
             rename_tables,
         } => {
-            let db = connect(&database_url).await?;
+            let db = connect(&database_url, sqlite).await?;
 
             if status {
                 println!("Migration status:");
@@ -216,43 +285,62 @@ async fn main() -> Result<()> {
         }
 
         Commands::Form { action } => {
-            let db = connect(&database_url).await?;
+            let db = connect(&database_url, sqlite).await?;
             commands::form::handle(&db, action).await?;
         }
 
         Commands::Submissions { action } => {
-            let db = connect(&database_url).await?;
+            let db = connect(&database_url, sqlite).await?;
             commands::submissions::handle(&db, action).await?;
         }
 
+        Commands::Db { action } => {
+            let db = connect(&database_url, sqlite).await?;
+            commands::db::handle(&db, action).await?;
+        }
+
+        Commands::Projections { action } => {
+            let db = connect(&database_url, sqlite).await?;
+            commands::projections::handle(&db, action).await?;
+        }
+
+        Commands::Bench { action } => {
+            let db = connect(&database_url, sqlite).await?;
+            commands::bench::handle(&db, action).await?;
+        }
+
         Commands::Seed {
+            from,
             contact_only,
             feedback_only,
             quiz_only,
+            onboarding_only,
             clear,
         } => {
-            let db = connect(&database_url).await?;
+            let db = connect(&database_url, sqlite).await?;
 
-            if clear {
-                println!("Clearing seeded forms...");
-                anyform::clear_seeded_forms(&db).await?;
-                println!("Done.");
+            let seeder = if let Some(dir) = from {
+                Seeder::new().dir(dir)
             } else if contact_only {
-                println!("Seeding contact form...");
-                anyform::seed_contact_form(&db).await?;
-                println!("Done.");
+                Seeder::new().form(seed::contact_form())
             } else if feedback_only {
-                println!("Seeding feedback form...");
-                anyform::seed_feedback_form(&db).await?;
-                println!("Done.");
+                Seeder::new().form(seed::feedback_form())
             } else if quiz_only {
-                println!("Seeding quiz form...");
-                anyform::seed_quiz_form(&db).await?;
-                println!("Done.");
+                Seeder::new().form(seed::quiz_form())
+            } else if onboarding_only {
+                Seeder::new().form(seed::onboarding_form())
             } else {
-                println!("Seeding all example forms...");
-                anyform::seed_all(&db).await?;
-                println!("Done. Created: contact, feedback, quiz");
+                Seeder::examples()
+            };
+
+            if clear {
+                println!("Clearing seeded forms...");
+                let deleted = seeder.clear(&db).await?;
+                println!("Done. Deleted {deleted} form(s).");
+            } else {
+                println!("Seeding forms...");
+                let report = seeder.run(&db).await?;
+                println!("{report}");
             }
         }
 
@@ -260,39 +348,77 @@ async fn main() -> Result<()> {
             host,
             port,
             no_admin,
+            admin_token,
+            encryption_key,
             cors,
+            assets_dir,
+            read_only,
+            check,
+            dev,
         } => {
-            let db = connect(&database_url).await?;
-
-            // Run migrations first
-            println!("Running migrations...");
-            anyform::migration::Migrator::up(&db, None).await?;
+            let db = connect(&database_url, sqlite).await?;
 
             // Build anyform router
-            let mut builder = AnyFormRouter::builder().database(db);
+            let mut builder = AnyFormRouter::builder()
+                .database(db.clone())
+                .read_only(read_only)
+                .dev_mode(dev);
             if !no_admin {
                 builder = builder.enable_admin(true);
+                if let Some(token) = admin_token {
+                    builder = builder.admin_auth(BearerTokenAuth::new(token));
+                }
+            }
+            if let Some(key) = encryption_key {
+                builder = builder.encryption_key(EncryptionKey::from_base64(&key)?);
+            }
+            if let Some(dir) = assets_dir {
+                builder = builder.assets(ClientAssets::directory(dir));
             }
-            let anyform_router = builder.build();
 
-            // Health check endpoint
-            let health_router = Router::new().route("/health", get(health_check));
+            if check {
+                let report = builder.preflight().await?;
+                println!("{report}");
+                let errors = report.errors().count();
+                if errors > 0 {
+                    anyhow::bail!("preflight check failed with {errors} error(s)");
+                }
+                return Ok(());
+            }
 
-            // Combine routers
-            let mut app = Router::new().merge(anyform_router).merge(health_router);
+            // Run migrations first, unless the database may be a read replica
+            if read_only {
+                println!("Read-only mode: skipping migrations");
+            } else {
+                println!("Running migrations...");
+                anyform::migration::Migrator::up(&db, None).await?;
+            }
+
+            if dev {
+                println!("Seeding example forms...");
+                let report = Seeder::examples().run(&db).await?;
+                println!("{report}");
+            }
+
+            // Fail fast rather than with 500s on the first requests
+            let report = builder.preflight().await?;
+            for issue in report.warnings() {
+                println!("Warning: {}: {}", issue.code, issue.message);
+            }
+            report.ensure_ok()?;
+
+            // The anyform router serves /health itself
+            let mut app = builder.build();
 
             // Add CORS if specified
             if let Some(origin) = cors {
+                let cors_layer = CorsLayer::new()
+                    .allow_methods(ALLOWED_METHODS)
+                    .allow_headers(ALLOWED_HEADERS);
                 let cors_layer = if origin == "*" {
-                    CorsLayer::new()
-                        .allow_origin(Any)
-                        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-                        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+                    cors_layer.allow_origin(Any)
                 } else {
-                    CorsLayer::new()
-                        .allow_origin(origin.parse::<http::HeaderValue>()?)
-                        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-                        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+                    cors_layer.allow_origin(origin.parse::<http::HeaderValue>()?)
                 };
                 app = app.layer(ServiceBuilder::new().layer(cors_layer));
                 println!("CORS enabled for: {}", if origin == "*" { "any origin" } else { &origin });
@@ -304,6 +430,9 @@ async fn main() -> Result<()> {
             if !no_admin {
                 println!("Admin routes enabled at /api/admin/*");
             }
+            if dev {
+                println!("Example gallery: http://{addr}{}", anyform::gallery::PATH);
+            }
             println!("Press Ctrl+C to stop");
             let listener = TcpListener::bind(&addr).await?;
             axum::serve(listener, app).await?;
@@ -313,13 +442,8 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-/// Health check endpoint handler.
-async fn health_check() -> &'static str {
-    "OK"
-}
-
-async fn connect(url: &str) -> Result<DatabaseConnection> {
+async fn connect(url: &str, sqlite: SqliteConfig) -> Result<DatabaseConnection> {
     println!("Connecting to database...");
-    let db = Database::connect(url).await?;
+    let db = sqlite.connect(url).await?;
     Ok(db)
 }
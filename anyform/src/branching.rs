@@ -0,0 +1,314 @@
+//! Branching between the steps of multi-step forms.
+//!
+//! Conditions only hide steps. Jump rules instead pick where a respondent
+//! goes once they finish a step: "if `plan` is `business`, go to *Company*".
+//! A rule names a later step to go `to`, or ends the form when it names
+//! none, and applies `when` its condition holds (always, without one).
+//!
+//! Rules sit on steps ([`CreateStepInput::jump`](crate::CreateStepInput::jump))
+//! and on fields ([`UiOptions::jump`](crate::UiOptions::jump)). Once a step
+//! is done, its shown fields' rules are tried in order, then the step's own;
+//! the first that applies wins. Without one, the next visible step follows
+//! as before. A jump to a step hidden by its condition lands on the next
+//! visible step after it.
+//!
+//! Steps jumped over aren't validated or scored, and in-progress
+//! submissions move on along the same route. The WASM client's
+//! `FormState::next_step()` follows the same rules.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use anyform::branching::{route, JumpRule, StepBranch};
+//! use anyform::condition::ConditionRule;
+//! use serde_json::json;
+//!
+//! let steps = vec![
+//!     StepBranch::new("Plan").jump(
+//!         JumpRule::to_step("Company").when(ConditionRule::eq("plan", "business")),
+//!     ),
+//!     StepBranch::new("Personal"),
+//!     StepBranch::new("Company"),
+//! ];
+//!
+//! let values = HashMap::from([("plan".to_string(), json!("business"))]);
+//! assert_eq!(route(&steps, &values), vec![0, 2]);
+//! assert_eq!(route(&steps, &HashMap::new()), vec![0, 1, 2]);
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::condition::ConditionRule;
+use crate::entities::{field, step};
+use crate::schema::FieldValue;
+
+/// Where to go once a step is done; see [`crate::branching`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JumpRule {
+    /// When the rule applies; always, if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<ConditionRule>,
+
+    /// Name of the later step to go to; `None` ends the form.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+}
+
+impl JumpRule {
+    /// Goes to the step named `name`.
+    #[must_use]
+    pub fn to_step(name: impl Into<String>) -> Self {
+        Self {
+            when: None,
+            to: Some(name.into()),
+        }
+    }
+
+    /// Ends the form.
+    #[must_use]
+    pub fn end_form() -> Self {
+        Self { when: None, to: None }
+    }
+
+    /// Applies the rule only when `condition` holds.
+    #[must_use]
+    pub fn when(mut self, condition: ConditionRule) -> Self {
+        self.when = Some(condition);
+        self
+    }
+
+    /// Returns whether the rule applies with `values`.
+    #[must_use]
+    pub fn applies(&self, values: &HashMap<String, serde_json::Value>) -> bool {
+        self.when.as_ref().map_or(true, |c| c.evaluate(values))
+    }
+}
+
+/// A step as seen by [`route`]: its name, condition and jump rules.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepBranch {
+    /// The step's name, which rules jump to.
+    pub name: String,
+    /// The step's visibility condition.
+    pub condition: Option<ConditionRule>,
+    /// Jump rules in the order they're tried, fields' first.
+    pub next: Vec<JumpRule>,
+}
+
+impl StepBranch {
+    /// Creates a step without a condition or jump rules.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the step's visibility condition.
+    #[must_use]
+    pub fn condition(mut self, condition: ConditionRule) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Adds a jump rule.
+    #[must_use]
+    pub fn jump(mut self, rule: JumpRule) -> Self {
+        self.next.push(rule);
+        self
+    }
+
+    /// Returns the branches of `steps`, in order, with the jump rules of
+    /// their `fields`.
+    ///
+    /// A field's rules only apply while the field is shown, so its
+    /// condition is added to theirs.
+    #[must_use]
+    pub fn from_models(steps: &[step::Model], fields: &[field::Model]) -> Vec<Self> {
+        steps
+            .iter()
+            .map(|step| {
+                let mut next: Vec<JumpRule> = fields
+                    .iter()
+                    .filter(|f| f.step_id == step.id)
+                    .flat_map(|f| {
+                        let ui = f.ui();
+                        let shown = ui.condition;
+                        ui.next.into_iter().map(move |mut rule| {
+                            rule.when = both(shown.clone(), rule.when);
+                            rule
+                        })
+                    })
+                    .collect();
+                next.extend(step.jump_rules());
+                Self {
+                    name: step.name.clone(),
+                    condition: step.condition_rule(),
+                    next,
+                }
+            })
+            .collect()
+    }
+
+    fn is_visible(&self, values: &HashMap<String, serde_json::Value>) -> bool {
+        self.condition.as_ref().map_or(true, |c| c.evaluate(values))
+    }
+}
+
+/// Returns the index of the step `to` names, looking after step `from`.
+#[must_use]
+pub fn target(steps: &[StepBranch], from: usize, to: &str) -> Option<usize> {
+    steps
+        .iter()
+        .enumerate()
+        .skip(from + 1)
+        .find(|(_, s)| s.name == to)
+        .map(|(i, _)| i)
+}
+
+/// Returns the index of the step that follows step `from` with `values`,
+/// or the first visible step if `from` is `None`; `None` once the form
+/// ends.
+///
+/// Rules naming no later step are skipped.
+#[must_use]
+pub fn next(
+    steps: &[StepBranch],
+    from: Option<usize>,
+    values: &HashMap<String, serde_json::Value>,
+) -> Option<usize> {
+    let start = match from {
+        None => 0,
+        Some(from) => {
+            let jump = steps.get(from)?.next.iter().find_map(|rule| {
+                if !rule.applies(values) {
+                    return None;
+                }
+                match &rule.to {
+                    None => Some(None),
+                    Some(to) => target(steps, from, to).map(Some),
+                }
+            });
+            match jump {
+                Some(None) => return None,
+                Some(Some(to)) => to,
+                None => from + 1,
+            }
+        }
+    };
+    (start..steps.len()).find(|&i| steps[i].is_visible(values))
+}
+
+/// Returns the indices of the steps a respondent with `values` goes
+/// through, in order.
+#[must_use]
+pub fn route(steps: &[StepBranch], values: &HashMap<String, serde_json::Value>) -> Vec<usize> {
+    let mut route = Vec::new();
+    let mut current = next(steps, None, values);
+    while let Some(step) = current {
+        route.push(step);
+        current = next(steps, Some(step), values);
+    }
+    route
+}
+
+/// Returns the IDs of the steps a respondent answering `data` goes
+/// through, in order.
+#[must_use]
+pub fn route_steps(
+    steps: &[step::Model],
+    fields: &[field::Model],
+    data: &HashMap<String, FieldValue>,
+) -> Vec<Uuid> {
+    let values = json_values(data);
+    route(&StepBranch::from_models(steps, fields), &values)
+        .into_iter()
+        .map(|i| steps[i].id)
+        .collect()
+}
+
+/// Returns a condition holding when both `a` and `b` do.
+pub(crate) fn both(a: Option<ConditionRule>, b: Option<ConditionRule>) -> Option<ConditionRule> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(ConditionRule::and(vec![a, b])),
+        (a, b) => a.or(b),
+    }
+}
+
+pub(crate) fn json_values(
+    data: &HashMap<String, FieldValue>,
+) -> HashMap<String, serde_json::Value> {
+    data.iter().map(|(k, v)| (k.clone(), v.into())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn survey() -> Vec<StepBranch> {
+        vec![
+            StepBranch::new("Start")
+                .jump(JumpRule::end_form().when(ConditionRule::eq("consent", "no")))
+                .jump(JumpRule::to_step("Pets").when(ConditionRule::eq("has_pet", "yes"))),
+            StepBranch::new("No pets"),
+            StepBranch::new("Pets").jump(JumpRule::to_step("Start")),
+            StepBranch::new("Dogs").condition(ConditionRule::eq("pet", "dog")),
+            StepBranch::new("Done"),
+        ]
+    }
+
+    fn values(pairs: &[(&str, serde_json::Value)]) -> HashMap<String, serde_json::Value> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+    }
+
+    #[test]
+    fn test_without_a_matching_rule_the_next_visible_step_follows() {
+        assert_eq!(route(&survey(), &values(&[])), vec![0, 1, 2, 4]);
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let steps = survey();
+        let answers = values(&[("has_pet", json!("yes")), ("pet", json!("dog"))]);
+        assert_eq!(route(&steps, &answers), vec![0, 2, 3, 4]);
+
+        let answers = values(&[("has_pet", json!("yes")), ("consent", json!("no"))]);
+        assert_eq!(route(&steps, &answers), vec![0]);
+    }
+
+    #[test]
+    fn test_jumps_to_hidden_steps_land_on_the_next_visible_one() {
+        let mut steps = survey();
+        steps[0].next = vec![JumpRule::to_step("Dogs")];
+        assert_eq!(route(&steps, &values(&[])), vec![0, 4]);
+    }
+
+    #[test]
+    fn test_rules_only_jump_forwards() {
+        // "Pets" jumps back to "Start", which is skipped
+        let steps = survey();
+        assert_eq!(next(&steps, Some(2), &values(&[])), Some(4));
+        assert_eq!(target(&steps, 2, "Start"), None);
+        assert_eq!(target(&steps, 0, "Done"), Some(4));
+    }
+
+    #[test]
+    fn test_rules_serialize_without_empty_parts() {
+        let rule = JumpRule::end_form();
+        assert_eq!(serde_json::to_value(&rule).unwrap(), json!({}));
+
+        let rule: JumpRule = serde_json::from_value(json!({
+            "when": { "field": "plan", "op": "eq", "value": "business" },
+            "to": "Company"
+        }))
+        .unwrap();
+        assert_eq!(
+            rule,
+            JumpRule::to_step("Company").when(ConditionRule::eq("plan", "business"))
+        );
+    }
+}
@@ -0,0 +1,491 @@
+//! Publishing submission and form events to a message bus (Kafka, NATS).
+//!
+//! [`EventPublisher`], a [`FormEvents`] hook registered with
+//! [`AnyFormRouterBuilder::events`](crate::AnyFormRouterBuilder::events),
+//! turns submission and form events into [`BusEvent`]s and publishes them to
+//! one topic (Kafka) or subject (NATS) through a [`MessageBus`], so data
+//! platforms can consume the stream instead of polling the admin API.
+//!
+//! With the `kafka` feature, [`KafkaBus`] publishes to Kafka through
+//! rdkafka; with the `nats` feature, [`NatsBus`] publishes to NATS through
+//! async-nats. Implement the trait yourself to use another client or bus.
+//!
+//! ```rust,ignore
+//! use anyform::bus::{EventPublisher, NatsBus};
+//!
+//! let bus = NatsBus::connect("nats://127.0.0.1:4222").await?;
+//! let router = AnyFormRouter::builder()
+//!     .database(db)
+//!     .events(EventPublisher::new(bus, "anyform.events"))
+//!     .build();
+//! ```
+//!
+//! Events follow the CloudEvents 1.0 JSON format. The `type` names the
+//! event and the version of its `data`, e.g. `anyform.submission.created.v1`;
+//! `data` only changes incompatibly under a new version. Events are
+//! published after the change is committed, at most once: ones the bus
+//! fails to take are logged and dropped.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::{form, submission};
+use crate::events::FormEvents;
+use crate::services::ClosedReason;
+
+/// CloudEvents version events are written in.
+pub const SPEC_VERSION: &str = "1.0";
+
+/// Version of the event `data` layouts, part of each event `type`.
+pub const EVENT_VERSION: u32 = 1;
+
+/// Default `source` of published events.
+pub const DEFAULT_SOURCE: &str = "anyform";
+
+/// Kinds of events published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BusEventType {
+    /// A submission was accepted, or waitlisted (see its `status`).
+    SubmissionCreated,
+    /// A submission changed, e.g. was promoted off the waitlist.
+    SubmissionUpdated,
+    /// A submission was deleted by an admin.
+    SubmissionDeleted,
+    /// A form was created.
+    FormCreated,
+    /// A form's definition was updated.
+    FormUpdated,
+    /// A form was published.
+    FormPublished,
+    /// A form was archived.
+    FormArchived,
+    /// A form started taking submissions.
+    FormOpened,
+    /// A form stopped taking submissions (see the `reason` in its data).
+    FormClosed,
+}
+
+impl BusEventType {
+    /// Returns the event name without the version, e.g.
+    /// `submission.created`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::SubmissionCreated => "submission.created",
+            Self::SubmissionUpdated => "submission.updated",
+            Self::SubmissionDeleted => "submission.deleted",
+            Self::FormCreated => "form.created",
+            Self::FormUpdated => "form.updated",
+            Self::FormPublished => "form.published",
+            Self::FormArchived => "form.archived",
+            Self::FormOpened => "form.opened",
+            Self::FormClosed => "form.closed",
+        }
+    }
+
+    /// Returns the CloudEvents `type`, e.g. `anyform.submission.created.v1`.
+    #[must_use]
+    pub fn cloud_event_type(self) -> String {
+        format!("anyform.{}.v{EVENT_VERSION}", self.name())
+    }
+}
+
+/// An event in CloudEvents 1.0 JSON format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusEvent {
+    pub specversion: String,
+    /// Unique ID; consumers can deduplicate on it.
+    pub id: String,
+    pub source: String,
+    /// Event name and data version (see [`BusEventType::cloud_event_type`]).
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// ID of the submission, or of the form for form events. Used as the
+    /// message key, so one submission's events stay in order.
+    pub subject: String,
+    /// When the event happened, RFC 3339.
+    pub time: String,
+    pub datacontenttype: String,
+    pub data: serde_json::Value,
+}
+
+impl BusEvent {
+    /// Creates a submission event.
+    ///
+    /// `data` holds the form's ID and slug, the submission's ID, status and
+    /// timestamps, and its answers (left out of
+    /// [`SubmissionDeleted`](BusEventType::SubmissionDeleted) events).
+    /// Request metadata is never included.
+    #[must_use]
+    pub fn submission(
+        kind: BusEventType,
+        source: &str,
+        form: &form::Model,
+        submission: &submission::Model,
+    ) -> Self {
+        let mut data = serde_json::json!({
+            "form_id": form.id,
+            "form_slug": form.slug,
+            "submission_id": submission.id,
+            "status": submission.status,
+            "created_at": submission.created_at.to_rfc3339(),
+            "completed_at": submission.completed_at.map(|t| t.to_rfc3339()),
+        });
+        if kind != BusEventType::SubmissionDeleted {
+            data["data"] = submission.data.clone();
+        }
+        Self::new(kind, source, submission.id, data)
+    }
+
+    /// Creates a [`FormPublished`](BusEventType::FormPublished) event.
+    #[must_use]
+    pub fn form_published(source: &str, form: &form::Model) -> Self {
+        Self::form(BusEventType::FormPublished, source, form)
+    }
+
+    /// Creates a form event.
+    ///
+    /// `data` holds the form's ID, slug and name.
+    #[must_use]
+    pub fn form(kind: BusEventType, source: &str, form: &form::Model) -> Self {
+        let data = serde_json::json!({
+            "form_id": form.id,
+            "form_slug": form.slug,
+            "name": form.name,
+        });
+        Self::new(kind, source, form.id, data)
+    }
+
+    /// Creates a [`FormClosed`](BusEventType::FormClosed) event, whose
+    /// `data` also holds why the form closed (e.g. `quota_reached`).
+    #[must_use]
+    pub fn form_closed(source: &str, form: &form::Model, reason: ClosedReason) -> Self {
+        let mut event = Self::form(BusEventType::FormClosed, source, form);
+        event.data["reason"] = serde_json::to_value(reason).unwrap_or_default();
+        event
+    }
+
+    fn new(kind: BusEventType, source: &str, subject: Uuid, data: serde_json::Value) -> Self {
+        Self {
+            specversion: SPEC_VERSION.to_string(),
+            id: Uuid::new_v4().to_string(),
+            source: source.to_string(),
+            event_type: kind.cloud_event_type(),
+            subject: subject.to_string(),
+            time: chrono::Utc::now().to_rfc3339(),
+            datacontenttype: "application/json".to_string(),
+            data,
+        }
+    }
+}
+
+/// Publishes events to a message bus.
+#[async_trait]
+pub trait MessageBus: Send + Sync + 'static {
+    /// Publishes `event` to `topic` (a Kafka topic or NATS subject),
+    /// returning once the bus has taken it.
+    async fn publish(&self, topic: &str, event: &BusEvent) -> Result<(), String>;
+}
+
+impl std::fmt::Debug for dyn MessageBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MessageBus")
+    }
+}
+
+/// Publishes submission and form events to a topic.
+///
+/// Register it with
+/// [`AnyFormRouterBuilder::events`](crate::AnyFormRouterBuilder::events),
+/// or call [`EventPublisher::publish`] from your own hook.
+#[derive(Debug, Clone)]
+pub struct EventPublisher {
+    bus: Arc<dyn MessageBus>,
+    topic: String,
+    source: String,
+}
+
+impl EventPublisher {
+    /// Creates a publisher sending to `topic` through `bus`.
+    #[must_use]
+    pub fn new(bus: impl MessageBus, topic: impl Into<String>) -> Self {
+        Self {
+            bus: Arc::new(bus),
+            topic: topic.into(),
+            source: DEFAULT_SOURCE.to_string(),
+        }
+    }
+
+    /// Sets the `source` of published events, e.g. the server's URL.
+    #[must_use]
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    /// Publishes `event`, returning whether the bus took it.
+    ///
+    /// Failures are logged.
+    pub async fn publish(&self, event: &BusEvent) -> bool {
+        match self.bus.publish(&self.topic, event).await {
+            Ok(()) => true,
+            Err(error) => {
+                tracing::warn!(
+                    topic = %self.topic,
+                    event = %event.event_type,
+                    subject = %event.subject,
+                    %error,
+                    "failed to publish event"
+                );
+                false
+            }
+        }
+    }
+
+    async fn publish_submission(
+        &self,
+        kind: BusEventType,
+        form: &form::Model,
+        submission: &submission::Model,
+    ) {
+        self.publish(&BusEvent::submission(kind, &self.source, form, submission))
+            .await;
+    }
+}
+
+#[async_trait]
+impl FormEvents for EventPublisher {
+    async fn on_submission_created(&self, form: &form::Model, submission: &submission::Model) {
+        self.publish_submission(BusEventType::SubmissionCreated, form, submission)
+            .await;
+    }
+
+    async fn on_submission_waitlisted(&self, form: &form::Model, submission: &submission::Model) {
+        self.publish_submission(BusEventType::SubmissionCreated, form, submission)
+            .await;
+    }
+
+    async fn on_submission_promoted(&self, form: &form::Model, submission: &submission::Model) {
+        self.publish_submission(BusEventType::SubmissionUpdated, form, submission)
+            .await;
+    }
+
+    async fn on_submission_deleted(&self, form: &form::Model, submission: &submission::Model) {
+        self.publish_submission(BusEventType::SubmissionDeleted, form, submission)
+            .await;
+    }
+
+    async fn on_form_created(&self, form: &form::Model) {
+        self.publish(&BusEvent::form(BusEventType::FormCreated, &self.source, form))
+            .await;
+    }
+
+    async fn on_form_updated(&self, form: &form::Model) {
+        self.publish(&BusEvent::form(BusEventType::FormUpdated, &self.source, form))
+            .await;
+    }
+
+    async fn on_form_published(&self, form: &form::Model) {
+        self.publish(&BusEvent::form_published(&self.source, form)).await;
+    }
+
+    async fn on_form_archived(&self, form: &form::Model) {
+        self.publish(&BusEvent::form(BusEventType::FormArchived, &self.source, form))
+            .await;
+    }
+
+    async fn on_form_opened(&self, form: &form::Model) {
+        self.publish(&BusEvent::form(BusEventType::FormOpened, &self.source, form))
+            .await;
+    }
+
+    async fn on_form_closed(&self, form: &form::Model, reason: ClosedReason) {
+        self.publish(&BusEvent::form_closed(&self.source, form, reason)).await;
+    }
+}
+
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaBus;
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use rdkafka::config::ClientConfig;
+    use rdkafka::error::KafkaResult;
+    use rdkafka::producer::{FutureProducer, FutureRecord};
+
+    use super::{BusEvent, MessageBus};
+
+    /// How long an event may wait for delivery before it is given up on.
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Publishes to Kafka with librdkafka, keyed by the event's `subject`.
+    ///
+    /// Each publish waits for the broker's delivery report. The producer
+    /// keeps its own connections to the brokers and reconnects after
+    /// failures. For TLS or SASL, set librdkafka's `security.protocol`,
+    /// `ssl.*` and `sasl.*` properties on a [`ClientConfig`] and use
+    /// [`from_config`](Self::from_config).
+    #[derive(Clone)]
+    pub struct KafkaBus {
+        producer: FutureProducer,
+    }
+
+    impl std::fmt::Debug for KafkaBus {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("KafkaBus").finish_non_exhaustive()
+        }
+    }
+
+    impl KafkaBus {
+        /// Creates a bus publishing to the brokers in `bootstrap_servers`
+        /// (`host:port`, comma-separated), acknowledged by all in-sync
+        /// replicas and with idempotent delivery.
+        pub fn new(bootstrap_servers: &str) -> KafkaResult<Self> {
+            let mut config = ClientConfig::new();
+            config
+                .set("bootstrap.servers", bootstrap_servers)
+                .set("acks", "all")
+                .set("enable.idempotence", "true")
+                .set("message.timeout.ms", TIMEOUT.as_millis().to_string());
+            Self::from_config(&config)
+        }
+
+        /// Creates a bus with a producer made from `config`.
+        pub fn from_config(config: &ClientConfig) -> KafkaResult<Self> {
+            Ok(Self {
+                producer: config.create()?,
+            })
+        }
+    }
+
+    /// Returns whether `topic` is a valid Kafka topic name.
+    fn is_valid_topic(topic: &str) -> bool {
+        !topic.is_empty()
+            && topic.len() <= 249
+            && topic
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+    }
+
+    #[async_trait]
+    impl MessageBus for KafkaBus {
+        async fn publish(&self, topic: &str, event: &BusEvent) -> Result<(), String> {
+            if !is_valid_topic(topic) {
+                return Err(format!("invalid Kafka topic: {topic}"));
+            }
+            let payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+            let record = FutureRecord::to(topic)
+                .key(event.subject.as_str())
+                .payload(payload.as_slice());
+            self.producer
+                .send(record, TIMEOUT)
+                .await
+                .map(|_| ())
+                .map_err(|(error, _)| error.to_string())
+        }
+    }
+}
+
+#[cfg(feature = "nats")]
+pub use nats::NatsBus;
+
+#[cfg(feature = "nats")]
+mod nats {
+    use std::time::Duration;
+
+    use async_nats::jetstream;
+    use async_trait::async_trait;
+
+    use super::{BusEvent, MessageBus};
+
+    /// How long publishing may take before it is given up on.
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Publishes to NATS with the async-nats client.
+    ///
+    /// The client takes care of TLS, authentication and reconnecting; build
+    /// one with [`async_nats::ConnectOptions`] for anything beyond
+    /// [`connect`](Self::connect). Core NATS publishes return once the event
+    /// is written to the connection, as core NATS acknowledges nothing. With
+    /// [`jetstream`](Self::jetstream), events are published to the subject's
+    /// JetStream stream instead and each waits for the stream to acknowledge
+    /// storing it.
+    #[derive(Debug, Clone)]
+    pub struct NatsBus {
+        client: async_nats::Client,
+        jetstream: Option<jetstream::Context>,
+    }
+
+    impl NatsBus {
+        /// Connects to the server at `address` (e.g. `nats://host:4222`).
+        pub async fn connect(
+            address: impl async_nats::ToServerAddrs,
+        ) -> Result<Self, async_nats::ConnectError> {
+            Ok(Self::from_client(async_nats::connect(address).await?))
+        }
+
+        /// Creates a bus publishing through `client`.
+        #[must_use]
+        pub fn from_client(client: async_nats::Client) -> Self {
+            Self {
+                client,
+                jetstream: None,
+            }
+        }
+
+        /// Publishes through JetStream, waiting for each event to be stored.
+        #[must_use]
+        pub fn jetstream(mut self) -> Self {
+            self.jetstream = Some(jetstream::new(self.client.clone()));
+            self
+        }
+    }
+
+    #[async_trait]
+    impl MessageBus for NatsBus {
+        async fn publish(&self, topic: &str, event: &BusEvent) -> Result<(), String> {
+            if topic.is_empty() || topic.contains(char::is_whitespace) {
+                return Err(format!("invalid NATS subject: {topic:?}"));
+            }
+            let payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+
+            let publish = async {
+                if let Some(jetstream) = &self.jetstream {
+                    let ack = jetstream
+                        .publish(topic.to_string(), payload.into())
+                        .await
+                        .map_err(|e| e.to_string())?;
+                    return ack.await.map(|_| ()).map_err(|e| e.to_string());
+                }
+                self.client
+                    .publish(topic.to_string(), payload.into())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                self.client.flush().await.map_err(|e| e.to_string())
+            };
+            tokio::time::timeout(TIMEOUT, publish)
+                .await
+                .unwrap_or_else(|_| Err("timed out publishing to NATS".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_types_are_versioned() {
+        assert_eq!(
+            BusEventType::SubmissionCreated.cloud_event_type(),
+            "anyform.submission.created.v1"
+        );
+        assert_eq!(BusEventType::FormPublished.cloud_event_type(), "anyform.form.published.v1");
+    }
+}
@@ -0,0 +1,306 @@
+//! CAPTCHA verification (hCaptcha, Cloudflare Turnstile, reCAPTCHA).
+//!
+//! Forms with [`FormSettings::captcha`](crate::FormSettings::captcha) set
+//! render the provider's widget, and only accept submissions carrying a
+//! token the provider confirms. Tokens are checked by the router's
+//! [`CaptchaVerifier`], registered with
+//! [`AnyFormRouterBuilder::captcha_verifier`](crate::AnyFormRouterBuilder::captcha_verifier).
+//! With the `captcha` feature, [`ProviderVerifier`] asks the provider's
+//! `siteverify` API; implement the trait yourself to verify some other way.
+//!
+//! Submissions fail with `400 CAPTCHA_FAILED` when the token is missing or
+//! rejected, and when no verifier is registered, so a misconfigured server
+//! never lets unchecked submissions through.
+//!
+//! ```rust,ignore
+//! use anyform::captcha::{Captcha, CaptchaProvider, ProviderVerifier};
+//!
+//! let settings = FormSettings::new().captcha(Captcha::new(CaptchaProvider::Turnstile, site_key));
+//! let router = AnyFormRouter::builder()
+//!     .database(db)
+//!     .captcha_verifier(ProviderVerifier::new().secret(CaptchaProvider::Turnstile, secret_key))
+//!     .build();
+//! ```
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::FieldValue;
+
+/// A CAPTCHA provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CaptchaProvider {
+    /// [hCaptcha](https://www.hcaptcha.com).
+    #[serde(rename = "hcaptcha")]
+    HCaptcha,
+    /// [Cloudflare Turnstile](https://www.cloudflare.com/products/turnstile/).
+    #[serde(rename = "turnstile")]
+    Turnstile,
+    /// [Google reCAPTCHA](https://www.google.com/recaptcha) v2.
+    #[serde(rename = "recaptcha")]
+    ReCaptcha,
+}
+
+impl CaptchaProvider {
+    /// Every provider.
+    pub const ALL: [Self; 3] = [Self::HCaptcha, Self::Turnstile, Self::ReCaptcha];
+
+    /// URL of the provider's widget script.
+    #[must_use]
+    pub fn script_url(self) -> &'static str {
+        match self {
+            Self::HCaptcha => "https://js.hcaptcha.com/1/api.js",
+            Self::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/api.js",
+            Self::ReCaptcha => "https://www.google.com/recaptcha/api.js",
+        }
+    }
+
+    /// Class of the element the widget script renders into.
+    #[must_use]
+    pub fn widget_class(self) -> &'static str {
+        match self {
+            Self::HCaptcha => "h-captcha",
+            Self::Turnstile => "cf-turnstile",
+            Self::ReCaptcha => "g-recaptcha",
+        }
+    }
+
+    /// Name of the input the widget submits its token in.
+    #[must_use]
+    pub fn response_field(self) -> &'static str {
+        match self {
+            Self::HCaptcha => "h-captcha-response",
+            Self::Turnstile => "cf-turnstile-response",
+            Self::ReCaptcha => "g-recaptcha-response",
+        }
+    }
+
+    /// Origins the widget loads scripts, styles and frames from, for the
+    /// page's Content Security Policy.
+    #[must_use]
+    pub fn csp_sources(self) -> &'static str {
+        match self {
+            Self::HCaptcha => "https://hcaptcha.com https://*.hcaptcha.com",
+            Self::Turnstile => "https://challenges.cloudflare.com",
+            Self::ReCaptcha => {
+                "https://www.google.com/recaptcha/ https://www.gstatic.com/recaptcha/ \
+                 https://recaptcha.google.com/recaptcha/"
+            }
+        }
+    }
+
+    /// URL of the provider's server-side token check.
+    #[must_use]
+    pub fn verify_url(self) -> &'static str {
+        match self {
+            Self::HCaptcha => "https://api.hcaptcha.com/siteverify",
+            Self::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+            Self::ReCaptcha => "https://www.google.com/recaptcha/api/siteverify",
+        }
+    }
+}
+
+/// CAPTCHA settings for a form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Captcha {
+    /// Provider whose widget is rendered.
+    pub provider: CaptchaProvider,
+    /// Public site key the widget is rendered with.
+    pub site_key: String,
+}
+
+impl Captcha {
+    /// Creates CAPTCHA settings for a provider and site key.
+    #[must_use]
+    pub fn new(provider: CaptchaProvider, site_key: impl Into<String>) -> Self {
+        Self {
+            provider,
+            site_key: site_key.into(),
+        }
+    }
+
+    /// Removes every provider's token input from submitted data, returning
+    /// this provider's token.
+    pub fn take_token(&self, data: &mut HashMap<String, FieldValue>) -> Option<String> {
+        let token = data.remove(self.provider.response_field());
+        strip(data);
+        token
+            .as_ref()
+            .and_then(FieldValue::as_str)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+    }
+}
+
+/// Removes every provider's token input from submitted data.
+pub fn strip(data: &mut HashMap<String, FieldValue>) {
+    for provider in CaptchaProvider::ALL {
+        data.remove(provider.response_field());
+    }
+}
+
+/// Checks CAPTCHA tokens.
+#[async_trait]
+pub trait CaptchaVerifier: Send + Sync + 'static {
+    /// Returns whether `token`, submitted from a form with `captcha`, proves
+    /// a person filled the form in. `remote_ip` is the submitter's address,
+    /// when known.
+    async fn verify(&self, captcha: &Captcha, token: &str, remote_ip: Option<&str>) -> bool;
+}
+
+impl std::fmt::Debug for dyn CaptchaVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CaptchaVerifier")
+    }
+}
+
+#[cfg(feature = "captcha")]
+pub use provider::ProviderVerifier;
+
+#[cfg(feature = "captcha")]
+mod provider {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use serde::Deserialize;
+
+    use super::{Captcha, CaptchaProvider, CaptchaVerifier};
+
+    /// How long a token check may take before the submission is rejected.
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Verifies tokens with the provider's `siteverify` API.
+    ///
+    /// Tokens from providers without a secret key are rejected, as are
+    /// tokens that can't be checked because the provider is unreachable.
+    #[derive(Clone)]
+    pub struct ProviderVerifier {
+        secrets: HashMap<CaptchaProvider, String>,
+        agent: ureq::Agent,
+    }
+
+    impl Default for ProviderVerifier {
+        fn default() -> Self {
+            let config = ureq::Agent::config_builder()
+                .timeout_global(Some(TIMEOUT))
+                .http_status_as_error(false)
+                .build();
+            Self {
+                secrets: HashMap::new(),
+                agent: config.into(),
+            }
+        }
+    }
+
+    impl std::fmt::Debug for ProviderVerifier {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("ProviderVerifier")
+                .field("providers", &self.secrets.keys().collect::<Vec<_>>())
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl ProviderVerifier {
+        /// Creates a verifier with no secret keys.
+        #[must_use]
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Sets the secret key for a provider's tokens.
+        #[must_use]
+        pub fn secret(mut self, provider: CaptchaProvider, secret: impl Into<String>) -> Self {
+            self.secrets.insert(provider, secret.into());
+            self
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct SiteVerify {
+        success: bool,
+        #[serde(default, rename = "error-codes")]
+        error_codes: Vec<String>,
+    }
+
+    #[async_trait]
+    impl CaptchaVerifier for ProviderVerifier {
+        async fn verify(&self, captcha: &Captcha, token: &str, remote_ip: Option<&str>) -> bool {
+            let provider = captcha.provider;
+            let Some(secret) = self.secrets.get(&provider) else {
+                tracing::warn!(?provider, "no CAPTCHA secret key for provider");
+                return false;
+            };
+
+            let mut params = vec![
+                ("secret", secret.clone()),
+                ("response", token.to_string()),
+            ];
+            if let Some(ip) = remote_ip {
+                params.push(("remoteip", ip.to_string()));
+            }
+            if provider == CaptchaProvider::HCaptcha {
+                params.push(("sitekey", captcha.site_key.clone()));
+            }
+
+            let agent = self.agent.clone();
+            let result = tokio::task::spawn_blocking(move || -> Result<SiteVerify, String> {
+                let mut response = agent
+                    .post(provider.verify_url())
+                    .send_form(params)
+                    .map_err(|e| e.to_string())?;
+                let body = response.body_mut().read_to_string().map_err(|e| e.to_string())?;
+                serde_json::from_str(&body).map_err(|e| e.to_string())
+            })
+            .await;
+
+            match result {
+                Ok(Ok(check)) => {
+                    if !check.success {
+                        tracing::debug!(?provider, errors = ?check.error_codes, "CAPTCHA rejected");
+                    }
+                    check.success
+                }
+                Ok(Err(error)) => {
+                    tracing::warn!(?provider, %error, "CAPTCHA verification failed");
+                    false
+                }
+                Err(error) => {
+                    tracing::warn!(?provider, %error, "CAPTCHA verification task failed");
+                    false
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_token_strips_every_provider() {
+        let captcha = Captcha::new(CaptchaProvider::Turnstile, "site-key");
+        let mut data = HashMap::from([
+            ("name".to_string(), FieldValue::from("Ada")),
+            ("cf-turnstile-response".to_string(), FieldValue::from("tok")),
+            ("g-recaptcha-response".to_string(), FieldValue::from("other")),
+        ]);
+
+        assert_eq!(captcha.take_token(&mut data).as_deref(), Some("tok"));
+        assert_eq!(data.len(), 1);
+        assert_eq!(captcha.take_token(&mut data), None);
+
+        data.insert("cf-turnstile-response".to_string(), FieldValue::from(""));
+        assert_eq!(captcha.take_token(&mut data), None, "empty tokens are missing");
+    }
+
+    #[test]
+    fn test_providers_serialize_by_short_name() {
+        let captcha = Captcha::new(CaptchaProvider::HCaptcha, "key");
+        let json = serde_json::to_value(&captcha).unwrap();
+        assert_eq!(json, serde_json::json!({ "provider": "hcaptcha", "site_key": "key" }));
+    }
+}
@@ -0,0 +1,225 @@
+//! Chaos mode: simulated failures for integration environments.
+//!
+//! With the `chaos` feature and a secret configured through
+//! [`AnyFormRouterBuilder::chaos_secret`](crate::AnyFormRouterBuilder::chaos_secret),
+//! API requests carrying the secret can ask for faults, so client teams can
+//! exercise their retry and error handling against a staging server:
+//!
+//! ```text
+//! X-Anyform-Chaos-Secret: <secret>
+//! X-Anyform-Chaos: db-timeout=2000, slow-render=500
+//! ```
+//!
+//! | Fault | Effect |
+//! |-------|--------|
+//! | `db-timeout[=ms]` | Waits `ms` (default 0), then fails with `500 DATABASE_ERROR` |
+//! | `slow-render=ms` | Delays the response by `ms` |
+//! | `webhook-failure` | Submissions fail with `502 WEBHOOK_FAILED` after validation, and are captured as dead letters |
+//!
+//! Requests without the right secret are served normally. Never enable this
+//! in production.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{request::Parts, HeaderName};
+use sha2::{Digest, Sha256};
+
+use crate::error::FormError;
+
+/// Request header carrying the chaos secret.
+pub const CHAOS_SECRET_HEADER: HeaderName = HeaderName::from_static("x-anyform-chaos-secret");
+
+/// Request header listing the faults to simulate.
+pub const CHAOS_HEADER: HeaderName = HeaderName::from_static("x-anyform-chaos");
+
+/// Longest delay a fault may ask for.
+const MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// A simulated failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaosFault {
+    /// Fail as if the database timed out, after a delay.
+    DbTimeout(Duration),
+    /// Delay the response.
+    SlowRender(Duration),
+    /// Fail submissions as if a delivery hook failed.
+    WebhookFailure,
+}
+
+impl ChaosFault {
+    /// Parses a single directive, e.g. `slow-render=500`.
+    fn parse(directive: &str) -> Result<Self, FormError> {
+        let (name, ms) = match directive.split_once('=') {
+            Some((name, ms)) => (name.trim(), Some(ms.trim())),
+            None => (directive, None),
+        };
+        let delay = ms
+            .map(|ms| {
+                ms.parse::<u64>()
+                    .map(Duration::from_millis)
+                    .map_err(|_| FormError::InvalidData(format!("Invalid chaos delay: {ms}")))
+            })
+            .transpose()?
+            .map(|delay| delay.min(MAX_DELAY));
+
+        match (name, delay) {
+            ("db-timeout", delay) => Ok(Self::DbTimeout(delay.unwrap_or_default())),
+            ("slow-render", Some(delay)) => Ok(Self::SlowRender(delay)),
+            ("webhook-failure", None) => Ok(Self::WebhookFailure),
+            _ => Err(FormError::InvalidData(format!(
+                "Unknown chaos fault: {directive}"
+            ))),
+        }
+    }
+}
+
+/// The faults requested for the current request.
+///
+/// Extracts as empty unless the chaos middleware accepted the request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Chaos(pub Vec<ChaosFault>);
+
+impl Chaos {
+    /// Parses a comma-separated fault list.
+    pub fn parse(value: &str) -> Result<Self, FormError> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+            .map(ChaosFault::parse)
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// Returns true if submissions should fail as if a webhook failed.
+    #[must_use]
+    pub fn webhook_failure(&self) -> bool {
+        self.0.contains(&ChaosFault::WebhookFailure)
+    }
+}
+
+impl<S> FromRequestParts<S> for Chaos
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<Self>().cloned().unwrap_or_default())
+    }
+}
+
+/// Secret that unlocks chaos headers.
+#[derive(Clone)]
+pub struct ChaosSecret(Arc<[u8; 32]>);
+
+impl ChaosSecret {
+    /// Creates a chaos secret.
+    #[must_use]
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self(Arc::new(Sha256::digest(secret.as_ref()).into()))
+    }
+
+    /// Returns true if `candidate` is the secret.
+    ///
+    /// Compares digests, so timing reveals nothing about the secret.
+    #[must_use]
+    pub fn matches(&self, candidate: &[u8]) -> bool {
+        let candidate: [u8; 32] = Sha256::digest(candidate).into();
+        candidate
+            .iter()
+            .zip(self.0.iter())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+    }
+}
+
+impl std::fmt::Debug for ChaosSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ChaosSecret(..)")
+    }
+}
+
+/// Middleware applying the faults requested by authenticated requests.
+pub(crate) async fn inject(secret: ChaosSecret, mut req: Request, next: Next) -> Response {
+    let Some(faults) = req.headers().get(CHAOS_HEADER) else {
+        return next.run(req).await;
+    };
+    let authorized = req
+        .headers()
+        .get(CHAOS_SECRET_HEADER)
+        .is_some_and(|candidate| secret.matches(candidate.as_bytes()));
+    if !authorized {
+        tracing::warn!("ignoring chaos header without a valid secret");
+        return next.run(req).await;
+    }
+
+    let chaos = match faults
+        .to_str()
+        .map_err(|_| FormError::InvalidData("Invalid chaos header".to_string()))
+        .and_then(Chaos::parse)
+    {
+        Ok(chaos) => chaos,
+        Err(err) => return err.into_response(),
+    };
+
+    for fault in &chaos.0 {
+        match *fault {
+            ChaosFault::DbTimeout(delay) => {
+                tokio::time::sleep(delay).await;
+                return FormError::Database("simulated database timeout".to_string())
+                    .into_response();
+            }
+            ChaosFault::SlowRender(delay) => tokio::time::sleep(delay).await,
+            ChaosFault::WebhookFailure => {}
+        }
+    }
+
+    req.extensions_mut().insert(chaos);
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_faults() {
+        let chaos = Chaos::parse("db-timeout, slow-render=250,webhook-failure").unwrap();
+        assert_eq!(
+            chaos.0,
+            vec![
+                ChaosFault::DbTimeout(Duration::ZERO),
+                ChaosFault::SlowRender(Duration::from_millis(250)),
+                ChaosFault::WebhookFailure,
+            ]
+        );
+        assert!(chaos.webhook_failure());
+
+        let chaos = Chaos::parse("db-timeout=999999999").unwrap();
+        assert_eq!(chaos.0, vec![ChaosFault::DbTimeout(MAX_DELAY)]);
+    }
+
+    #[test]
+    fn test_rejects_unknown_faults() {
+        assert!(Chaos::parse("meteor").is_err());
+        assert!(Chaos::parse("slow-render").is_err());
+        assert!(Chaos::parse("slow-render=soon").is_err());
+        assert!(Chaos::parse("webhook-failure=5").is_err());
+    }
+
+    #[test]
+    fn test_secret_matches() {
+        let secret = ChaosSecret::new("s3cret");
+        assert!(secret.matches(b"s3cret"));
+        assert!(!secret.matches(b"s3cre"));
+        assert_eq!(format!("{secret:?}"), "ChaosSecret(..)");
+    }
+}
@@ -0,0 +1,250 @@
+//! Benchmark CLI commands.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use axum::body::Body;
+use http::Request;
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::TokioExecutor;
+use sea_orm::DatabaseConnection;
+
+use crate::entities::form::Entity as FormEntity;
+use crate::loadtest::{
+    self, LoadReport, LoadTestConfig, RenderReport, RouterTarget, SubmissionGenerator,
+    SubmitTarget, Thresholds,
+};
+use crate::{AnyFormRouter, FormBuilder};
+
+use super::BenchAction;
+
+pub async fn handle(db: &DatabaseConnection, action: BenchAction) -> Result<()> {
+    match action {
+        BenchAction::Submit {
+            form,
+            concurrency,
+            duration,
+            url,
+            json,
+            max_p50,
+            max_p95,
+            max_p99,
+            max_error_rate,
+            min_throughput,
+            baseline,
+            tolerance,
+        } => {
+            let thresholds = Thresholds {
+                max_p50_ms: max_p50,
+                max_p95_ms: max_p95,
+                max_p99_ms: max_p99,
+                max_error_rate,
+                min_throughput,
+            };
+            let config = LoadTestConfig { concurrency, duration };
+            submit(db, &form, url, &config, json, &thresholds, baseline, tolerance).await
+        }
+        BenchAction::Render {
+            form,
+            steps,
+            iterations,
+            json,
+        } => render(db, form, steps, iterations, json).await,
+    }
+}
+
+async fn render(
+    db: &DatabaseConnection,
+    slug: Option<String>,
+    steps: usize,
+    iterations: usize,
+    json: bool,
+) -> Result<()> {
+    let form = match slug {
+        Some(slug) => FormEntity::find_by_slug(db, &slug)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Form not found: {}", slug))?,
+        None => {
+            let slug = format!("bench-render-{steps}");
+            match FormEntity::find_by_slug(db, &slug).await? {
+                Some(form) => form,
+                None => FormBuilder::create(db, loadtest::bench_form(&slug, steps)).await?,
+            }
+        }
+    };
+
+    eprintln!("Rendering '{}' {} times...", form.slug, iterations);
+    let report = loadtest::bench_render(db, &form, iterations).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_render_report(&report);
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn submit(
+    db: &DatabaseConnection,
+    slug: &str,
+    url: Option<String>,
+    config: &LoadTestConfig,
+    json: bool,
+    thresholds: &Thresholds,
+    baseline: Option<String>,
+    tolerance: f64,
+) -> Result<()> {
+    let form = FormEntity::find_by_slug(db, slug)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Form not found: {}", slug))?;
+
+    // Read the baseline up front so a bad path fails before the run
+    let baseline: Option<LoadReport> = match baseline {
+        Some(path) => Some(serde_json::from_str(&std::fs::read_to_string(&path)?)?),
+        None => None,
+    };
+
+    let generator = Arc::new(SubmissionGenerator::for_form(db, &form).await?);
+    let target: Arc<dyn SubmitTarget> = match url {
+        Some(url) => Arc::new(HttpTarget::new(&url)),
+        None => {
+            eprintln!("Running in-process; submissions are stored in the database.");
+            Arc::new(RouterTarget::new(AnyFormRouter::new(db.clone())))
+        }
+    };
+
+    eprintln!(
+        "Submitting to '{}' with {} concurrent requests for {:?}...",
+        slug, config.concurrency, config.duration
+    );
+    let report = loadtest::run(target, slug, generator, config).await;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+
+    let mut failures = thresholds.check(&report);
+    if let Some(baseline) = baseline {
+        failures.extend(report.regressions(&baseline, tolerance));
+    }
+    if !failures.is_empty() {
+        for failure in &failures {
+            eprintln!("FAIL: {failure}");
+        }
+        anyhow::bail!("{} threshold(s) failed", failures.len());
+    }
+
+    Ok(())
+}
+
+fn print_report(report: &LoadReport) {
+    println!("Form:        {}", report.form);
+    println!("Concurrency: {}", report.concurrency);
+    println!("Duration:    {:.1}s", report.duration_secs);
+    println!("Requests:    {}", report.requests);
+    println!("Throughput:  {:.1} req/s", report.throughput);
+    println!(
+        "Errors:      {} ({:.2}%)",
+        report.errors,
+        report.error_rate * 100.0
+    );
+    println!();
+    println!("Latency (ms):");
+    println!("  mean  {:>8.2}", report.latency_ms.mean);
+    println!("  p50   {:>8.2}", report.latency_ms.p50);
+    println!("  p95   {:>8.2}", report.latency_ms.p95);
+    println!("  p99   {:>8.2}", report.latency_ms.p99);
+    println!("  max   {:>8.2}", report.latency_ms.max);
+    println!();
+    println!("Statuses:");
+    for (status, count) in &report.statuses {
+        println!("  {status:<6}{count}");
+    }
+}
+
+fn print_render_report(report: &RenderReport) {
+    println!("Form:        {}", report.form);
+    println!("Steps:       {}", report.steps);
+    println!("Fields:      {}", report.fields);
+    println!("Iterations:  {}", report.iterations);
+    println!();
+    println!("Latency (ms):  {:>8}  {:>8}", "html", "json");
+    for (name, html, json) in [
+        ("mean", report.html_ms.mean, report.json_ms.mean),
+        ("p50", report.html_ms.p50, report.json_ms.p50),
+        ("p95", report.html_ms.p95, report.json_ms.p95),
+        ("p99", report.html_ms.p99, report.json_ms.p99),
+        ("max", report.html_ms.max, report.json_ms.max),
+    ] {
+        println!("  {name:<6}       {html:>8.2}  {json:>8.2}");
+    }
+}
+
+/// Parses a duration such as `60s`, `500ms` or `2m` (bare numbers are seconds).
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = s
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((s, ""), |i| s.split_at(i));
+    let n: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration: {s}"))?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(n)),
+        "" | "s" => Ok(Duration::from_secs(n)),
+        "m" => Ok(Duration::from_secs(n * 60)),
+        _ => Err(format!("invalid duration unit '{unit}' (use ms, s or m)")),
+    }
+}
+
+/// Sends submissions to a running server over HTTP/1.1.
+struct HttpTarget {
+    base_url: String,
+    client: Client<HttpConnector, Body>,
+}
+
+impl HttpTarget {
+    fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: Client::builder(TokioExecutor::new()).build_http(),
+        }
+    }
+}
+
+#[async_trait]
+impl SubmitTarget for HttpTarget {
+    async fn submit(&self, slug: &str, body: Vec<u8>) -> Result<u16, String> {
+        let request = Request::post(format!("{}/api/v1/forms/{slug}", self.base_url))
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .map_err(|e| e.to_string())?;
+
+        let response = self.client.request(request).await.map_err(|e| e.to_string())?;
+        let status = response.status().as_u16();
+        axum::body::to_bytes(Body::new(response.into_body()), usize::MAX)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("60s"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("5"), Ok(Duration::from_secs(5)));
+        assert!(parse_duration("5h").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+}
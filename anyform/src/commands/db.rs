@@ -0,0 +1,50 @@
+//! Database maintenance CLI commands.
+
+use anyhow::Result;
+use sea_orm::DatabaseConnection;
+
+use crate::services::{ConsistencyReport, ConsistencyService, IssueKind};
+
+use super::DbAction;
+
+pub async fn handle(db: &DatabaseConnection, action: DbAction) -> Result<()> {
+    match action {
+        DbAction::Check { json } => check(db, json).await,
+    }
+}
+
+async fn check(db: &DatabaseConnection, json: bool) -> Result<()> {
+    let report = ConsistencyService::check(db).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+
+    if !report.is_clean() {
+        anyhow::bail!("{} consistency issue(s) found", report.issues.len());
+    }
+
+    Ok(())
+}
+
+fn print_report(report: &ConsistencyReport) {
+    if report.is_clean() {
+        println!("No issues found.");
+        return;
+    }
+
+    for issue in &report.issues {
+        match issue.kind {
+            IssueKind::Orphan => println!(
+                "{} {}: {} references missing {} row {}",
+                issue.table, issue.id, issue.column, issue.parent_table, issue.parent_id
+            ),
+            IssueKind::ActiveUnderDeletedForm => println!(
+                "{} {}: active, but form {} is deleted",
+                issue.table, issue.id, issue.parent_id
+            ),
+        }
+    }
+}
@@ -1,5 +1,7 @@
 //! Form CLI commands.
 
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
 use sea_orm::DatabaseConnection;
 
@@ -7,24 +9,28 @@ use sea_orm::{ActiveModelTrait, ActiveValue};
 
 use crate::{
     entities::form::{ActiveModel as FormActiveModel, Entity as FormEntity},
+    presets::FieldLibrary,
     render::{HtmlOptions, HtmlRenderer, JsonRenderer},
-    schema::FormSettings,
-    services::{CreateFormInput, FormBuilder},
+    services::{CreateFormInput, FormBuilder, ImportMode, OptionImport},
 };
 
-use super::FormAction;
+use super::{FormAction, OptionAction};
 
 pub async fn handle(db: &DatabaseConnection, action: FormAction) -> Result<()> {
     match action {
         FormAction::List => list(db).await,
         FormAction::Show { slug } => show(db, &slug).await,
-        FormAction::Create { file } => create(db, &file).await,
-        FormAction::Update { slug, file } => update(db, &slug, &file).await,
+        FormAction::Create { file, presets } => create(db, &file, presets.as_deref()).await,
+        FormAction::Update { slug, file, presets } => {
+            update(db, &slug, &file, presets.as_deref()).await
+        }
         FormAction::Delete { slug } => delete(db, &slug).await,
         FormAction::Export { slug, format } => export(db, &slug, &format).await,
         FormAction::Render { slug } => render(db, &slug).await,
-        FormAction::Sync { folder } => sync(db, &folder).await,
+        FormAction::Sync { folder, presets } => sync(db, &folder, presets.as_deref()).await,
+        FormAction::Lint { folder, presets, json } => lint(&folder, presets.as_deref(), json),
         FormAction::SetAction { slug, url, method } => set_action(db, &slug, url, method).await,
+        FormAction::Options { action } => options(db, action).await,
     }
 }
 
@@ -37,8 +43,8 @@ async fn list(db: &DatabaseConnection) -> Result<()> {
     }
 
     println!(
-        "{:<36}  {:<20}  {:<15}  {}",
-        "ID", "NAME", "SLUG", "CREATED"
+        "{:<36}  {:<20}  {:<15}  CREATED",
+        "ID", "NAME", "SLUG"
     );
     println!("{}", "-".repeat(90));
 
@@ -66,10 +72,11 @@ async fn show(db: &DatabaseConnection, slug: &str) -> Result<()> {
     Ok(())
 }
 
-async fn create(db: &DatabaseConnection, file: &str) -> Result<()> {
+async fn create(db: &DatabaseConnection, file: &str, presets: Option<&str>) -> Result<()> {
     let content = std::fs::read_to_string(file)?;
     let input: CreateFormInput = serde_json::from_str(&content)
         .map_err(|e| anyhow::anyhow!("Invalid form JSON: {}", e))?;
+    let input = load_library(presets)?.expand(input)?;
 
     let form = FormBuilder::create(db, input).await?;
 
@@ -81,7 +88,12 @@ async fn create(db: &DatabaseConnection, file: &str) -> Result<()> {
     Ok(())
 }
 
-async fn update(db: &DatabaseConnection, slug: &str, file: &str) -> Result<()> {
+async fn update(
+    db: &DatabaseConnection,
+    slug: &str,
+    file: &str,
+    presets: Option<&str>,
+) -> Result<()> {
     let form = FormEntity::find_by_slug(db, slug)
         .await?
         .ok_or_else(|| anyhow::anyhow!("Form not found: {}", slug))?;
@@ -89,6 +101,7 @@ async fn update(db: &DatabaseConnection, slug: &str, file: &str) -> Result<()> {
     let content = std::fs::read_to_string(file)?;
     let input: CreateFormInput = serde_json::from_str(&content)
         .map_err(|e| anyhow::anyhow!("Invalid form JSON: {}", e))?;
+    let input = load_library(presets)?.expand(input)?;
 
     let updated = FormBuilder::update(db, form.id, input).await?;
 
@@ -141,15 +154,13 @@ async fn render(db: &DatabaseConnection, slug: &str) -> Result<()> {
     Ok(())
 }
 
-async fn sync(db: &DatabaseConnection, folder: &str) -> Result<()> {
-    let pattern = format!("{}/*.json", folder);
+async fn sync(db: &DatabaseConnection, folder: &str, presets: Option<&str>) -> Result<()> {
+    let library = load_library(presets)?;
     let mut created = 0;
     let mut updated = 0;
     let mut errors = 0;
 
-    let entries: Vec<_> = glob::glob(&pattern)
-        .map_err(|e| anyhow::anyhow!("Invalid glob pattern: {}", e))?
-        .collect();
+    let entries = form_files(folder, presets)?;
 
     if entries.is_empty() {
         println!("No JSON files found in: {}", folder);
@@ -183,6 +194,14 @@ async fn sync(db: &DatabaseConnection, folder: &str) -> Result<()> {
                 continue;
             }
         };
+        let input = match library.expand(input) {
+            Ok(i) => i,
+            Err(e) => {
+                eprintln!("Error expanding {}: {}", path.display(), e);
+                errors += 1;
+                continue;
+            }
+        };
 
         let slug = input.slug.clone();
 
@@ -220,6 +239,59 @@ async fn sync(db: &DatabaseConnection, folder: &str) -> Result<()> {
     Ok(())
 }
 
+fn lint(folder: &str, presets: Option<&str>, json: bool) -> Result<()> {
+    let library = load_library(presets)?;
+    let mut forms = Vec::new();
+    for entry in form_files(folder, presets)? {
+        let path = entry?;
+        let content = std::fs::read_to_string(&path)?;
+        let input: CreateFormInput = serde_json::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Error parsing {}: {}", path.display(), e))?;
+        forms.push(input);
+    }
+
+    let findings = library.lint(&forms);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+    } else if findings.is_empty() {
+        println!("No issues found.");
+    } else {
+        for finding in &findings {
+            println!("{finding}");
+        }
+    }
+
+    if !findings.is_empty() {
+        anyhow::bail!("{} field definition issue(s) found", findings.len());
+    }
+
+    Ok(())
+}
+
+/// Reads the field library at `presets`, if given.
+fn load_library(presets: Option<&str>) -> Result<FieldLibrary> {
+    match presets {
+        Some(file) => Ok(FieldLibrary::from_json(&std::fs::read_to_string(file)?)?),
+        None => Ok(FieldLibrary::new()),
+    }
+}
+
+/// Lists the JSON files of `folder`, except the field library at `presets`.
+fn form_files(
+    folder: &str,
+    presets: Option<&str>,
+) -> Result<Vec<Result<PathBuf, glob::GlobError>>> {
+    let pattern = format!("{}/*.json", folder);
+    let presets = presets.and_then(|p| Path::new(p).canonicalize().ok());
+    Ok(glob::glob(&pattern)
+        .map_err(|e| anyhow::anyhow!("Invalid glob pattern: {}", e))?
+        .filter(|entry| match (entry, &presets) {
+            (Ok(path), Some(presets)) => path.canonicalize().ok().as_ref() != Some(presets),
+            _ => true,
+        })
+        .collect())
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
@@ -275,6 +347,9 @@ async fn set_action(
         slug: ActiveValue::Unchanged(form.slug),
         description: ActiveValue::Unchanged(form.description),
         settings: ActiveValue::Set(Some(serde_json::to_value(&settings)?)),
+        response_count: ActiveValue::Unchanged(form.response_count),
+        status: ActiveValue::Unchanged(form.status),
+        lifecycle_state: ActiveValue::Unchanged(form.lifecycle_state),
         created_at: ActiveValue::Unchanged(form.created_at),
         updated_at: ActiveValue::Set(now),
         deleted_at: ActiveValue::Unchanged(form.deleted_at),
@@ -292,3 +367,45 @@ async fn set_action(
 
     Ok(())
 }
+
+async fn options(db: &DatabaseConnection, action: OptionAction) -> Result<()> {
+    let OptionAction::Import {
+        form: slug,
+        field,
+        file,
+        merge,
+        dry_run,
+        json,
+    } = action;
+    let form = FormEntity::find_by_slug(db, &slug)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Form not found: {}", slug))?;
+
+    let content = std::fs::read_to_string(&file)?;
+    let rows = if Path::new(&file).extension().is_some_and(|e| e == "json") {
+        OptionImport::parse_json(&content)?
+    } else {
+        OptionImport::parse_csv(&content)?
+    };
+    let mode = if merge { ImportMode::Merge } else { ImportMode::Replace };
+    let diff = OptionImport::apply(db, &form, &field, rows, mode, dry_run).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+    for (sign, values) in [("+", &diff.added), ("~", &diff.updated), ("-", &diff.removed)] {
+        for value in values {
+            println!("{sign} {value}");
+        }
+    }
+    println!(
+        "{} {} added, {} updated, {} removed, {} unchanged.",
+        if dry_run { "Dry run:" } else { "Imported:" },
+        diff.added.len(),
+        diff.updated.len(),
+        diff.removed.len(),
+        diff.unchanged
+    );
+    Ok(())
+}
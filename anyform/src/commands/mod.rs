@@ -1,8 +1,13 @@
 //! CLI command handlers.
 
+use std::time::Duration;
+
 use clap::Subcommand;
 
+pub mod bench;
+pub mod db;
 pub mod form;
+pub mod projections;
 pub mod submissions;
 
 /// Form subcommand actions.
@@ -22,6 +27,10 @@ pub enum FormAction {
         /// Path to JSON file
         #[arg(short, long)]
         file: String,
+
+        /// JSON file of field presets the form references with `$ref`
+        #[arg(long, value_name = "FILE")]
+        presets: Option<String>,
     },
 
     /// Update a form from JSON file
@@ -32,6 +41,10 @@ pub enum FormAction {
         /// Path to JSON file
         #[arg(short, long)]
         file: String,
+
+        /// JSON file of field presets the form references with `$ref`
+        #[arg(long, value_name = "FILE")]
+        presets: Option<String>,
     },
 
     /// Delete a form
@@ -61,6 +74,26 @@ pub enum FormAction {
         /// Path to folder containing JSON form files
         #[arg(short, long)]
         folder: String,
+
+        /// JSON file of field presets the forms reference with `$ref`
+        #[arg(long, value_name = "FILE")]
+        presets: Option<String>,
+    },
+
+    /// Flag fields copied across the JSON form files of a folder that
+    /// diverge from each other or from a field preset
+    Lint {
+        /// Path to folder containing JSON form files
+        #[arg(short, long)]
+        folder: String,
+
+        /// JSON file of field presets the forms reference with `$ref`
+        #[arg(long, value_name = "FILE")]
+        presets: Option<String>,
+
+        /// Print the findings as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Set form action URL (where the form submits to)
@@ -76,6 +109,44 @@ pub enum FormAction {
         #[arg(long)]
         method: Option<String>,
     },
+
+    /// Manage a field's options
+    Options {
+        #[command(subcommand)]
+        action: OptionAction,
+    },
+}
+
+/// Field option subcommand actions.
+#[derive(Subcommand, Clone)]
+pub enum OptionAction {
+    /// Replace or merge a field's options from a CSV or JSON file
+    Import {
+        /// Form slug
+        #[arg(long)]
+        form: String,
+
+        /// Field name
+        #[arg(long)]
+        field: String,
+
+        /// CSV file with label, value, group and points columns, or a JSON
+        /// array of such objects (`.json`)
+        #[arg(short, long)]
+        file: String,
+
+        /// Keep options missing from the file instead of removing them
+        #[arg(long)]
+        merge: bool,
+
+        /// Only show what would change
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Print the changes as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 /// Submission subcommand actions.
@@ -104,14 +175,145 @@ pub enum SubmissionAction {
         id: String,
     },
 
-    /// Export submissions to CSV
+    /// Export submissions to CSV, JSON or Excel
     Export {
         /// Form slug
         #[arg(long)]
         form: String,
 
-        /// Output format (csv, json)
+        /// Output format (csv, json, xlsx)
         #[arg(short, long, default_value = "csv")]
         format: String,
+
+        /// Include answers to fields classified as sensitive (personal data)
+        #[arg(long)]
+        include_sensitive: bool,
+
+        /// Include partial responses: submissions in progress with at least
+        /// one step saved
+        #[arg(long)]
+        include_partials: bool,
+
+        /// File to write instead of stdout (csv, xlsx)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Key the server encrypts sensitive answers with, to export them
+        /// decrypted
+        #[arg(long, env = "ANYFORM_ENCRYPTION_KEY", hide_env_values = true)]
+        encryption_key: Option<String>,
+    },
+}
+
+/// Database subcommand actions.
+#[derive(Subcommand, Clone)]
+pub enum DbAction {
+    /// Report orphaned rows and active rows of deleted forms
+    Check {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Projection subcommand actions.
+#[derive(Subcommand, Clone)]
+pub enum ProjectionAction {
+    /// List the projections that can be rebuilt
+    List,
+
+    /// Rebuild data derived from a form's submissions by replaying them
+    Rebuild {
+        /// Form slug
+        #[arg(long)]
+        form: String,
+
+        /// Only rebuild this projection (repeatable)
+        #[arg(long, value_name = "NAME")]
+        only: Vec<String>,
+
+        /// Submissions replayed per transaction
+        #[arg(long, default_value_t = crate::services::DEFAULT_CHUNK_SIZE)]
+        chunk_size: u64,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Benchmark subcommand actions.
+#[derive(Subcommand, Clone)]
+pub enum BenchAction {
+    /// Load test the submit endpoint with generated submissions
+    Submit {
+        /// Form slug
+        #[arg(long)]
+        form: String,
+
+        /// Number of concurrent requests
+        #[arg(short, long, default_value = "50")]
+        concurrency: usize,
+
+        /// How long to run (e.g. 60s, 500ms, 2m)
+        #[arg(short, long, default_value = "30s", value_parser = bench::parse_duration)]
+        duration: Duration,
+
+        /// Base URL of a running server; without it the router runs in-process
+        /// against the database
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Fail if median latency exceeds this many milliseconds
+        #[arg(long)]
+        max_p50: Option<f64>,
+
+        /// Fail if 95th percentile latency exceeds this many milliseconds
+        #[arg(long)]
+        max_p95: Option<f64>,
+
+        /// Fail if 99th percentile latency exceeds this many milliseconds
+        #[arg(long)]
+        max_p99: Option<f64>,
+
+        /// Fail if the error rate exceeds this fraction (e.g. 0.01)
+        #[arg(long)]
+        max_error_rate: Option<f64>,
+
+        /// Fail if throughput is below this many requests per second
+        #[arg(long)]
+        min_throughput: Option<f64>,
+
+        /// JSON report of an earlier run to compare against
+        #[arg(long)]
+        baseline: Option<String>,
+
+        /// Allowed regression against the baseline, as a fraction
+        #[arg(long, default_value = "0.2")]
+        tolerance: f64,
+    },
+
+    /// Time HTML and JSON rendering of a form, loads included
+    Render {
+        /// Form slug; without it a synthetic form of `--steps` steps is
+        /// created (or reused)
+        #[arg(long)]
+        form: Option<String>,
+
+        /// Steps in the synthetic form
+        #[arg(long, default_value = "20")]
+        steps: usize,
+
+        /// Renders to time of each kind
+        #[arg(short, long, default_value = "100")]
+        iterations: usize,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
     },
 }
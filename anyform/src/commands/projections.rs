@@ -0,0 +1,81 @@
+//! Projection CLI commands.
+
+use std::io::{IsTerminal, Write};
+
+use anyhow::Result;
+use sea_orm::DatabaseConnection;
+
+use crate::entities::form::Entity as FormEntity;
+use crate::services::{Projection, ProjectionService, BUILTIN_PROJECTIONS};
+
+use super::ProjectionAction;
+
+pub async fn handle(db: &DatabaseConnection, action: ProjectionAction) -> Result<()> {
+    match action {
+        ProjectionAction::List => {
+            for projection in BUILTIN_PROJECTIONS {
+                println!("{}", projection.name());
+            }
+            Ok(())
+        }
+        ProjectionAction::Rebuild {
+            form,
+            only,
+            chunk_size,
+            json,
+        } => rebuild(db, &form, &only, chunk_size, json).await,
+    }
+}
+
+async fn rebuild(
+    db: &DatabaseConnection,
+    form_slug: &str,
+    only: &[String],
+    chunk_size: u64,
+    json: bool,
+) -> Result<()> {
+    let form = FormEntity::find_by_slug(db, form_slug)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Form not found: {}", form_slug))?;
+
+    if let Some(unknown) = only
+        .iter()
+        .find(|name| !BUILTIN_PROJECTIONS.iter().any(|p| p.name() == name.as_str()))
+    {
+        anyhow::bail!("Unknown projection: {unknown}");
+    }
+    let projections: Vec<&dyn Projection> = BUILTIN_PROJECTIONS
+        .iter()
+        .copied()
+        .filter(|p| only.is_empty() || only.iter().any(|name| name == p.name()))
+        .collect();
+
+    // Redraw one progress line on terminals; leave JSON output clean
+    let interactive = !json && std::io::stderr().is_terminal();
+    let report = ProjectionService::rebuild(db, &form, &projections, chunk_size, |progress| {
+        if interactive {
+            eprint!("\rReplayed {}/{} submissions", progress.replayed, progress.total);
+            let _ = std::io::stderr().flush();
+        }
+    })
+    .await?;
+    if interactive {
+        eprintln!();
+    }
+
+    let Some(report) = report else {
+        anyhow::bail!("Another rebuild of '{}' is running", form.slug);
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "Rebuilt {} for '{}' from {} submission(s) in {} chunk(s).",
+            report.projections.join(", "),
+            form.slug,
+            report.replayed,
+            report.chunks
+        );
+    }
+    Ok(())
+}
@@ -1,13 +1,19 @@
 //! Submissions CLI commands.
 
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal};
+
 use anyhow::Result;
 use sea_orm::DatabaseConnection;
 use uuid::Uuid;
 
+use crate::encryption::EncryptionKey;
 use crate::entities::{
     form::Entity as FormEntity,
     submission::Entity as SubmissionEntity,
+    submission_note::Entity as SubmissionNoteEntity,
 };
+use crate::services::{CsvExport, LegalHold, SubmissionSchema, XlsxExport};
 
 use super::SubmissionAction;
 
@@ -16,7 +22,19 @@ pub async fn handle(db: &DatabaseConnection, action: SubmissionAction) -> Result
         SubmissionAction::List { form, limit } => list(db, &form, limit).await,
         SubmissionAction::Show { id } => show(db, &id).await,
         SubmissionAction::Delete { id } => delete(db, &id).await,
-        SubmissionAction::Export { form, format } => export(db, &form, &format).await,
+        SubmissionAction::Export {
+            form,
+            format,
+            include_sensitive,
+            include_partials,
+            output,
+            encryption_key,
+        } => {
+            let key = encryption_key.as_deref().map(EncryptionKey::from_base64).transpose()?;
+            let output = output.as_deref();
+            export(db, &form, &format, include_sensitive, include_partials, output, key.as_ref())
+                .await
+        }
     }
 }
 
@@ -34,10 +52,10 @@ async fn list(db: &DatabaseConnection, form_slug: &str, limit: usize) -> Result<
 
     println!("Submissions for form '{}' ({}):", form.name, form.slug);
     println!(
-        "{:<36}  {:<20}  {}",
-        "ID", "CREATED", "COMPLETED"
+        "{:<36}  {:<20}  {:<10}  COMPLETED",
+        "ID", "CREATED", "STATUS"
     );
-    println!("{}", "-".repeat(80));
+    println!("{}", "-".repeat(92));
 
     for sub in submissions.into_iter().take(limit) {
         let completed = sub
@@ -46,9 +64,10 @@ async fn list(db: &DatabaseConnection, form_slug: &str, limit: usize) -> Result<
             .unwrap_or_else(|| "In progress".to_string());
 
         println!(
-            "{:<36}  {:<20}  {}",
+            "{:<36}  {:<20}  {:<10}  {}",
             sub.id,
             sub.created_at.format("%Y-%m-%d %H:%M"),
+            sub.status,
             completed
         );
     }
@@ -56,21 +75,81 @@ async fn list(db: &DatabaseConnection, form_slug: &str, limit: usize) -> Result<
     Ok(())
 }
 
-async fn export(db: &DatabaseConnection, form_slug: &str, format: &str) -> Result<()> {
+async fn export(
+    db: &DatabaseConnection,
+    form_slug: &str,
+    format: &str,
+    include_sensitive: bool,
+    include_partials: bool,
+    output: Option<&str>,
+    key: Option<&EncryptionKey>,
+) -> Result<()> {
     let form = FormEntity::find_by_slug(db, form_slug)
         .await?
         .ok_or_else(|| anyhow::anyhow!("Form not found: {}", form_slug))?;
 
-    let submissions = SubmissionEntity::find_completed_by_form(db, form.id).await?;
+    // Workbooks are written a page of submissions at a time
+    if format.eq_ignore_ascii_case("xlsx") {
+        let schema = SubmissionSchema::load(db, form.id, &[]).await?;
+        if !include_sensitive && schema.sensitive_count() > 0 {
+            eprintln!(
+                "Left out {} sensitive field(s); pass --include-sensitive to export them.",
+                schema.sensitive_count()
+            );
+        }
+        match output {
+            Some(path) => {
+                let file = BufWriter::new(File::create(path)?);
+                XlsxExport::write_form_with(db, &form, include_sensitive, include_partials, key, file)
+                    .await?;
+            }
+            None if std::io::stdout().is_terminal() => {
+                anyhow::bail!("Refusing to write a workbook to a terminal; pass --output.");
+            }
+            None => {
+                let stdout = BufWriter::new(std::io::stdout());
+                XlsxExport::write_form_with(db, &form, include_sensitive, include_partials, key, stdout)
+                    .await?;
+            }
+        }
+        return Ok(());
+    }
+
+    let submissions: Vec<_> = SubmissionEntity::exported_by_form(form.id, include_partials)
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|s| !s.is_in_progress() || s.is_partial())
+        .collect();
+    let ids: Vec<Uuid> = submissions.iter().map(|s| s.id).collect();
+    let labels = SubmissionNoteEntity::labels_by_submission(db, &ids).await?;
+    let labels_of = |id: &Uuid| labels.get(id).cloned().unwrap_or_default();
+
+    // Superset of columns across every form revision
+    let schema = SubmissionSchema::load(db, form.id, &submissions).await?;
+    if !include_sensitive && schema.sensitive_count() > 0 {
+        eprintln!(
+            "Left out {} sensitive field(s); pass --include-sensitive to export them.",
+            schema.sensitive_count()
+        );
+    }
 
     match format.to_lowercase().as_str() {
         "json" => {
             let json: Vec<_> = submissions
                 .iter()
                 .map(|s| {
+                    let mut data = s.data.clone();
+                    if let Some(key) = key {
+                        key.decrypt_data(&mut data);
+                    }
                     serde_json::json!({
                         "id": s.id.to_string(),
-                        "data": s.data,
+                        "revision_id": s.revision_id.map(|id| id.to_string()),
+                        "status": s.status,
+                        "instance_id": s.instance_id.map(|id| id.to_string()),
+                        "labels": labels_of(&s.id),
+                        "data": schema.export_data(&data, include_sensitive),
                         "completed_at": s.completed_at.map(|d| d.to_rfc3339()),
                         "created_at": s.created_at.to_rfc3339(),
                     })
@@ -80,56 +159,15 @@ async fn export(db: &DatabaseConnection, form_slug: &str, format: &str) -> Resul
             println!("{}", serde_json::to_string_pretty(&json)?);
         }
         "csv" => {
-            // Get all unique field names from submissions
-            let mut field_names: Vec<String> = Vec::new();
-            for sub in &submissions {
-                if let Some(obj) = sub.data.as_object() {
-                    for key in obj.keys() {
-                        if !field_names.contains(key) {
-                            field_names.push(key.clone());
-                        }
-                    }
-                }
-            }
-
-            field_names.sort();
-
-            // Header
-            print!("id,created_at,completed_at");
-            for name in &field_names {
-                print!(",{}", escape_csv(name));
-            }
-            println!();
-
-            // Rows
-            for sub in &submissions {
-                print!(
-                    "{},{},{}",
-                    sub.id,
-                    sub.created_at.format("%Y-%m-%d %H:%M:%S"),
-                    sub.completed_at
-                        .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
-                        .unwrap_or_default()
-                );
-
-                for name in &field_names {
-                    let value = sub
-                        .data
-                        .get(name)
-                        .map(|v| match v {
-                            serde_json::Value::String(s) => s.clone(),
-                            serde_json::Value::Null => String::new(),
-                            other => other.to_string(),
-                        })
-                        .unwrap_or_default();
-
-                    print!(",{}", escape_csv(&value));
-                }
-                println!();
+            let export = CsvExport::new(db, &form, &schema, include_sensitive).await?;
+            let csv = export.render_with(&submissions, &labels, key);
+            match output {
+                Some(path) => std::fs::write(path, csv)?,
+                None => print!("{csv}"),
             }
         }
         _ => {
-            anyhow::bail!("Unsupported format: {}. Use 'json' or 'csv'.", format);
+            anyhow::bail!("Unsupported format: {}. Use 'json', 'csv' or 'xlsx'.", format);
         }
     }
 
@@ -144,15 +182,23 @@ async fn show(db: &DatabaseConnection, id: &str) -> Result<()> {
         .await?
         .ok_or_else(|| anyhow::anyhow!("Submission not found: {}", id))?;
 
+    let notes = SubmissionNoteEntity::find_by_submission(db, sub.id).await?;
+
     let json = serde_json::json!({
         "id": sub.id.to_string(),
         "form_id": sub.form_id.to_string(),
+        "revision_id": sub.revision_id.map(|id| id.to_string()),
+        "status": sub.status,
+        "instance_id": sub.instance_id.map(|id| id.to_string()),
+        "respondent_id": sub.respondent_id,
+        "legal_hold": sub.legal_hold,
         "data": sub.data,
         "metadata": sub.metadata,
         "completed_at": sub.completed_at.map(|d| d.to_rfc3339()),
         "score": sub.score,
         "max_score": sub.max_score,
         "result_key": sub.result_key,
+        "notes": notes,
         "created_at": sub.created_at.to_rfc3339(),
         "updated_at": sub.updated_at.to_rfc3339(),
     });
@@ -166,9 +212,10 @@ async fn delete(db: &DatabaseConnection, id: &str) -> Result<()> {
     let uuid = Uuid::parse_str(id)
         .map_err(|_| anyhow::anyhow!("Invalid submission ID: {}", id))?;
 
-    let _sub = SubmissionEntity::find_active_by_id(db, uuid)
+    let sub = SubmissionEntity::find_active_by_id(db, uuid)
         .await?
         .ok_or_else(|| anyhow::anyhow!("Submission not found: {}", id))?;
+    LegalHold::ensure_deletable(&sub, "delete submission")?;
 
     SubmissionEntity::soft_delete(db, uuid).await?;
 
@@ -176,11 +223,3 @@ async fn delete(db: &DatabaseConnection, id: &str) -> Result<()> {
 
     Ok(())
 }
-
-fn escape_csv(s: &str) -> String {
-    if s.contains(',') || s.contains('"') || s.contains('\n') {
-        format!("\"{}\"", s.replace('"', "\"\""))
-    } else {
-        s.to_string()
-    }
-}
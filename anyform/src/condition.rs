@@ -229,6 +229,33 @@ impl ConditionRule {
         Self::Or { or: rules }
     }
 
+    /// Returns the names of the fields this condition reads.
+    pub fn fields(&self) -> Vec<&str> {
+        match self {
+            ConditionRule::Simple { field, .. } => vec![field.as_str()],
+            ConditionRule::And { and: rules } | ConditionRule::Or { or: rules } => {
+                rules.iter().flat_map(ConditionRule::fields).collect()
+            }
+        }
+    }
+
+    /// Renames the fields this condition reads for which `rename` returns
+    /// a new name.
+    pub fn rename_fields(&mut self, rename: &dyn Fn(&str) -> Option<String>) {
+        match self {
+            ConditionRule::Simple { field, .. } => {
+                if let Some(name) = rename(field) {
+                    *field = name;
+                }
+            }
+            ConditionRule::And { and: rules } | ConditionRule::Or { or: rules } => {
+                for rule in rules {
+                    rule.rename_fields(rename);
+                }
+            }
+        }
+    }
+
     /// Evaluate the condition against form data.
     ///
     /// # Arguments
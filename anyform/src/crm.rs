@@ -0,0 +1,501 @@
+//! CRM push integrations (HubSpot, Salesforce).
+//!
+//! Forms with [`FormSettings::crm`](crate::FormSettings::crm) set turn each
+//! accepted submission into a CRM contact or lead: a [`CrmMapping`] names
+//! the CRM property each answer is written to. Submissions are queued in
+//! the same transaction that stores them, and
+//! [`CrmService::push_due`](crate::services::CrmService::push_due), run on
+//! a schedule, pushes them through a [`CrmClient`], retrying failures with
+//! backoff. Pushes are deduplicated on the respondent's email address, and
+//! the CRM record ID is written back to the submission's metadata under
+//! `crm`.
+//!
+//! With the `crm` feature, [`HubSpotClient`] and [`SalesforceClient`] push
+//! through the providers' REST APIs; implement the trait yourself to push
+//! some other way.
+//!
+//! ```rust,ignore
+//! use anyform::crm::{CrmMapping, CrmProvider, HubSpotClient};
+//!
+//! let settings = FormSettings::new().crm(
+//!     CrmMapping::new(CrmProvider::HubSpot, "email")
+//!         .property("email", "email")
+//!         .property("name", "firstname")
+//!         .property("company", "company")
+//!         .constant("lifecyclestage", "lead"),
+//! );
+//!
+//! // e.g. every minute
+//! CrmService::push_due(&db, &HubSpotClient::new(token), chrono::Utc::now().fixed_offset())
+//!     .await?;
+//! ```
+//!
+//! Anonymous forms are never pushed.
+
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::FieldValue;
+
+/// A CRM a submission can be pushed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrmProvider {
+    /// [HubSpot](https://www.hubspot.com).
+    #[serde(rename = "hubspot")]
+    HubSpot,
+    /// [Salesforce](https://www.salesforce.com).
+    Salesforce,
+}
+
+impl CrmProvider {
+    /// Returns the provider's name as stored in the database.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::HubSpot => "hubspot",
+            Self::Salesforce => "salesforce",
+        }
+    }
+
+    /// Object submissions are pushed to unless the mapping names another.
+    #[must_use]
+    pub fn default_object(self) -> &'static str {
+        match self {
+            Self::HubSpot => "contacts",
+            Self::Salesforce => "Lead",
+        }
+    }
+}
+
+impl std::str::FromStr for CrmProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hubspot" => Ok(Self::HubSpot),
+            "salesforce" => Ok(Self::Salesforce),
+            other => Err(format!("unknown CRM provider: {other}")),
+        }
+    }
+}
+
+/// How a form's submissions map onto CRM records.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use anyform::crm::{CrmMapping, CrmProvider};
+///
+/// let mapping = CrmMapping::new(CrmProvider::Salesforce, "email")
+///     .property("email", "Email")
+///     .property("name", "LastName")
+///     .constant("LeadSource", "Web");
+///
+/// let data = HashMap::from([
+///     ("email".to_string(), " Ada@Example.com ".into()),
+///     ("name".to_string(), "Lovelace".into()),
+/// ]);
+/// assert_eq!(mapping.email(&data).as_deref(), Some("ada@example.com"));
+/// assert_eq!(mapping.properties(&data)["LastName"], "Lovelace");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrmMapping {
+    /// CRM submissions are pushed to.
+    pub provider: CrmProvider,
+
+    /// CRM object records are created in (e.g. `contacts`, `Lead`);
+    /// defaults to [`CrmProvider::default_object`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object: Option<String>,
+
+    /// Field holding the respondent's email address, which identifies
+    /// their CRM record. Submissions without one aren't pushed.
+    pub email_field: String,
+
+    /// CRM property each field's answer is written to, by field name.
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+
+    /// Values written to every record, by CRM property.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub constants: BTreeMap<String, serde_json::Value>,
+}
+
+impl CrmMapping {
+    /// Creates a mapping writing no properties.
+    #[must_use]
+    pub fn new(provider: CrmProvider, email_field: impl Into<String>) -> Self {
+        Self {
+            provider,
+            object: None,
+            email_field: email_field.into(),
+            properties: BTreeMap::new(),
+            constants: BTreeMap::new(),
+        }
+    }
+
+    /// Pushes to `object` instead of the provider's default.
+    #[must_use]
+    pub fn object(mut self, object: impl Into<String>) -> Self {
+        self.object = Some(object.into());
+        self
+    }
+
+    /// Writes the answer to `field` to the CRM property `property`.
+    #[must_use]
+    pub fn property(mut self, field: impl Into<String>, property: impl Into<String>) -> Self {
+        self.properties.insert(field.into(), property.into());
+        self
+    }
+
+    /// Writes `value` to the CRM property `property` on every record.
+    #[must_use]
+    pub fn constant(
+        mut self,
+        property: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.constants.insert(property.into(), value.into());
+        self
+    }
+
+    /// Returns the CRM object records are created in.
+    #[must_use]
+    pub fn object_name(&self) -> &str {
+        self.object
+            .as_deref()
+            .unwrap_or_else(|| self.provider.default_object())
+    }
+
+    /// Returns the respondent's email address, trimmed and lowercased.
+    #[must_use]
+    pub fn email(&self, data: &HashMap<String, FieldValue>) -> Option<String> {
+        let email = data.get(&self.email_field)?.to_string_value();
+        let email = email.trim().to_lowercase();
+        (!email.is_empty()).then_some(email)
+    }
+
+    /// Returns the CRM properties for a submission with `data`.
+    ///
+    /// Unanswered fields are left out; multiple choices are joined with
+    /// `;`, as both providers expect for multi-select properties.
+    #[must_use]
+    pub fn properties(
+        &self,
+        data: &HashMap<String, FieldValue>,
+    ) -> serde_json::Map<String, serde_json::Value> {
+        let mut properties: serde_json::Map<_, _> = self
+            .constants
+            .iter()
+            .map(|(property, value)| (property.clone(), value.clone()))
+            .collect();
+        for (field, property) in &self.properties {
+            let value = match data.get(field) {
+                Some(FieldValue::Array(values)) if !values.is_empty() => values.join(";").into(),
+                Some(value) if !value.is_empty() => serde_json::to_value(value).unwrap_or_default(),
+                _ => continue,
+            };
+            properties.insert(property.clone(), value);
+        }
+        properties
+    }
+}
+
+/// A record to create or update in a CRM.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CrmRecord {
+    /// CRM object the record belongs to (e.g. `contacts`, `Lead`).
+    pub object: String,
+    /// The respondent's email address, trimmed and lowercased.
+    pub email: String,
+    /// Properties to write.
+    pub properties: serde_json::Map<String, serde_json::Value>,
+    /// ID of the record an earlier push for this email created, if any.
+    pub existing_id: Option<String>,
+}
+
+/// Pushes records to a CRM.
+#[async_trait]
+pub trait CrmClient: Send + Sync + 'static {
+    /// The CRM this client pushes to.
+    fn provider(&self) -> CrmProvider;
+
+    /// Creates or updates the record for `record.email`, returning its ID.
+    ///
+    /// Update `record.existing_id` when it is set, and otherwise look for a
+    /// record with the same email before creating one, so respondents who
+    /// submit twice keep one record.
+    async fn upsert(&self, record: &CrmRecord) -> Result<String, String>;
+}
+
+impl std::fmt::Debug for dyn CrmClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CrmClient({})", self.provider().as_str())
+    }
+}
+
+#[cfg(feature = "crm")]
+pub use providers::{HubSpotClient, SalesforceClient};
+
+#[cfg(feature = "crm")]
+mod providers {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+
+    use super::{CrmClient, CrmProvider, CrmRecord};
+
+    /// How long a request to the CRM may take before the push is retried.
+    const TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Salesforce REST API version requests are made against.
+    const SALESFORCE_API: &str = "v60.0";
+
+    fn agent() -> ureq::Agent {
+        ureq::Agent::config_builder()
+            .timeout_global(Some(TIMEOUT))
+            .http_status_as_error(false)
+            .build()
+            .into()
+    }
+
+    /// Sends a request on a blocking thread, returning the JSON response
+    /// body (`null` when empty).
+    async fn send(
+        agent: &ureq::Agent,
+        method: &'static str,
+        url: String,
+        token: &str,
+        body: Option<Value>,
+    ) -> Result<Value, String> {
+        let agent = agent.clone();
+        let authorization = format!("Bearer {token}");
+        tokio::task::spawn_blocking(move || {
+            let request = http::Request::builder()
+                .method(method)
+                .uri(&url)
+                .header("Authorization", authorization)
+                .header("Content-Type", "application/json")
+                .body(body.map(|b| b.to_string()).unwrap_or_default())
+                .map_err(|e| e.to_string())?;
+            let mut response = agent.run(request).map_err(|e| e.to_string())?;
+            let status = response.status();
+            let text = response.body_mut().read_to_string().map_err(|e| e.to_string())?;
+            if !status.is_success() {
+                return Err(format!("{status}: {text}"));
+            }
+            if text.trim().is_empty() {
+                return Ok(Value::Null);
+            }
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    /// Pushes records to HubSpot's CRM API with a private app token.
+    ///
+    /// Records are upserted by email, so HubSpot itself keeps one contact
+    /// per address.
+    #[derive(Clone)]
+    pub struct HubSpotClient {
+        token: String,
+        base_url: String,
+        agent: ureq::Agent,
+    }
+
+    impl std::fmt::Debug for HubSpotClient {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("HubSpotClient")
+                .field("base_url", &self.base_url)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl HubSpotClient {
+        /// Creates a client authenticating with a private app `token`.
+        #[must_use]
+        pub fn new(token: impl Into<String>) -> Self {
+            Self {
+                token: token.into(),
+                base_url: "https://api.hubapi.com".to_string(),
+                agent: agent(),
+            }
+        }
+
+        /// Sends requests to `url` instead of HubSpot's API.
+        #[must_use]
+        pub fn base_url(mut self, url: impl Into<String>) -> Self {
+            self.base_url = url.into().trim_end_matches('/').to_string();
+            self
+        }
+    }
+
+    #[async_trait]
+    impl CrmClient for HubSpotClient {
+        fn provider(&self) -> CrmProvider {
+            CrmProvider::HubSpot
+        }
+
+        async fn upsert(&self, record: &CrmRecord) -> Result<String, String> {
+            let url = format!("{}/crm/v3/objects/{}/batch/upsert", self.base_url, record.object);
+            let body = json!({
+                "inputs": [{
+                    "idProperty": "email",
+                    "id": record.email,
+                    "properties": record.properties,
+                }]
+            });
+            let response = send(&self.agent, "POST", url, &self.token, Some(body)).await?;
+            response["results"][0]["id"]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("no record ID in response: {response}"))
+        }
+    }
+
+    /// Pushes records to Salesforce's REST API with an OAuth access token.
+    ///
+    /// Records without a known ID are matched on their `Email` field before
+    /// a new one is created.
+    #[derive(Clone)]
+    pub struct SalesforceClient {
+        instance_url: String,
+        access_token: String,
+        agent: ureq::Agent,
+    }
+
+    impl std::fmt::Debug for SalesforceClient {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("SalesforceClient")
+                .field("instance_url", &self.instance_url)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl SalesforceClient {
+        /// Creates a client for the org at `instance_url` (e.g.
+        /// `https://example.my.salesforce.com`).
+        #[must_use]
+        pub fn new(instance_url: impl Into<String>, access_token: impl Into<String>) -> Self {
+            Self {
+                instance_url: instance_url.into().trim_end_matches('/').to_string(),
+                access_token: access_token.into(),
+                agent: agent(),
+            }
+        }
+
+        fn sobjects(&self, object: &str) -> String {
+            format!("{}/services/data/{SALESFORCE_API}/sobjects/{object}", self.instance_url)
+        }
+
+        async fn find_by_email(&self, object: &str, email: &str) -> Result<Option<String>, String> {
+            let escaped = email.replace('\\', "\\\\").replace('\'', "\\'");
+            let query = format!("SELECT Id FROM {object} WHERE Email = '{escaped}' LIMIT 1");
+            let url = format!(
+                "{}/services/data/{SALESFORCE_API}/query?q={}",
+                self.instance_url,
+                urlencoding(&query)
+            );
+            let response = send(&self.agent, "GET", url, &self.access_token, None).await?;
+            Ok(response["records"][0]["Id"].as_str().map(str::to_string))
+        }
+    }
+
+    /// Percent-encodes a query string value.
+    fn urlencoding(value: &str) -> String {
+        value
+            .bytes()
+            .map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                    (b as char).to_string()
+                }
+                _ => format!("%{b:02X}"),
+            })
+            .collect()
+    }
+
+    #[async_trait]
+    impl CrmClient for SalesforceClient {
+        fn provider(&self) -> CrmProvider {
+            CrmProvider::Salesforce
+        }
+
+        async fn upsert(&self, record: &CrmRecord) -> Result<String, String> {
+            let object = &record.object;
+            if !object.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("invalid Salesforce object: {object}"));
+            }
+
+            let existing = match &record.existing_id {
+                Some(id) => Some(id.clone()),
+                None => self.find_by_email(object, &record.email).await?,
+            };
+            let body = Value::Object(record.properties.clone());
+            match existing {
+                Some(id) => {
+                    let url = format!("{}/{id}", self.sobjects(object));
+                    send(&self.agent, "PATCH", url, &self.access_token, Some(body)).await?;
+                    Ok(id)
+                }
+                None => {
+                    let url = self.sobjects(object);
+                    let response =
+                        send(&self.agent, "POST", url, &self.access_token, Some(body)).await?;
+                    response["id"]
+                        .as_str()
+                        .map(str::to_string)
+                        .ok_or_else(|| format!("no record ID in response: {response}"))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_properties_follow_the_mapping() {
+        let mapping = CrmMapping::new(CrmProvider::HubSpot, "email")
+            .property("email", "email")
+            .property("interests", "hs_interests")
+            .property("phone", "phone")
+            .constant("lifecyclestage", "lead");
+        let data = HashMap::from([
+            ("email".to_string(), FieldValue::from("ada@example.com")),
+            ("interests".to_string(), FieldValue::Array(vec!["news".into(), "offers".into()])),
+            ("phone".to_string(), FieldValue::from("")),
+            ("unmapped".to_string(), FieldValue::from("x")),
+        ]);
+
+        let properties = mapping.properties(&data);
+        assert_eq!(
+            serde_json::Value::Object(properties),
+            serde_json::json!({
+                "email": "ada@example.com",
+                "hs_interests": "news;offers",
+                "lifecyclestage": "lead"
+            })
+        );
+        assert_eq!(mapping.object_name(), "contacts");
+    }
+
+    #[test]
+    fn test_mappings_round_trip_as_json() {
+        let json = serde_json::json!({
+            "provider": "salesforce",
+            "object": "Contact",
+            "email_field": "work_email",
+            "properties": { "work_email": "Email" }
+        });
+        let mapping: CrmMapping = serde_json::from_value(json).unwrap();
+        assert_eq!(mapping.provider, CrmProvider::Salesforce);
+        assert_eq!(mapping.object_name(), "Contact");
+        assert_eq!(mapping.email(&HashMap::new()), None);
+    }
+}
@@ -0,0 +1,219 @@
+//! Database connection settings and retries for busy databases.
+//!
+//! SQLite allows one writer at a time. [`SqliteConfig`] sets up connections
+//! to wait for the write lock (`busy_timeout`) and to use WAL journaling, so
+//! readers and the writer don't block each other. Writes that still find the
+//! database busy fail with [`FormError::DatabaseBusy`](crate::FormError::DatabaseBusy),
+//! and [`RetryPolicy`] runs them again with backoff.
+//!
+//! ```rust,ignore
+//! use anyform::database::SqliteConfig;
+//! use std::time::Duration;
+//!
+//! let db = SqliteConfig::new()
+//!     .busy_timeout(Duration::from_secs(10))
+//!     .connect("sqlite:./anyform.db?mode=rwc")
+//!     .await?;
+//! ```
+
+use std::time::Duration;
+
+use sea_orm::sqlx::sqlite::{SqliteJournalMode, SqliteSynchronous};
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
+
+use crate::services::random_fraction;
+
+/// Connection settings for SQLite databases.
+///
+/// Applied to each pooled connection as it opens; connections to other
+/// databases ignore them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SqliteConfig {
+    busy_timeout: Duration,
+    wal: bool,
+}
+
+impl Default for SqliteConfig {
+    fn default() -> Self {
+        Self {
+            busy_timeout: Duration::from_secs(5),
+            wal: true,
+        }
+    }
+}
+
+impl SqliteConfig {
+    /// Creates the default settings: a 5 second busy timeout and WAL
+    /// journaling.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how long a connection waits for a lock held by another before
+    /// failing with `database is locked`.
+    #[must_use]
+    pub fn busy_timeout(mut self, timeout: Duration) -> Self {
+        self.busy_timeout = timeout;
+        self
+    }
+
+    /// Enables or disables WAL journaling (with `synchronous = NORMAL`).
+    ///
+    /// The journal mode is stored in the database file, so disabling WAL
+    /// switches an existing database back to rollback journaling.
+    #[must_use]
+    pub fn wal(mut self, enable: bool) -> Self {
+        self.wal = enable;
+        self
+    }
+
+    /// Applies the settings to connect options.
+    pub fn apply(&self, options: &mut ConnectOptions) {
+        let config = *self;
+        options.map_sqlx_sqlite_opts(move |opts| {
+            let opts = opts.busy_timeout(config.busy_timeout);
+            if config.wal {
+                opts.journal_mode(SqliteJournalMode::Wal)
+                    .synchronous(SqliteSynchronous::Normal)
+            } else {
+                opts.journal_mode(SqliteJournalMode::Delete)
+            }
+        });
+    }
+
+    /// Connects to a database with these settings.
+    pub async fn connect(&self, url: &str) -> Result<DatabaseConnection, DbErr> {
+        let mut options = ConnectOptions::new(url);
+        self.apply(&mut options);
+        Database::connect(options).await
+    }
+}
+
+/// Returns true if a database error means the database was locked by
+/// another connection (`SQLITE_BUSY` or `SQLITE_LOCKED`).
+pub(crate) fn is_busy(err: &DbErr) -> bool {
+    let message = err.to_string();
+    message.contains("database is locked") || message.contains("database table is locked")
+}
+
+/// How often and how long to retry writes that found the database busy.
+///
+/// Retries back off exponentially from `initial_backoff` up to
+/// `max_backoff`, with jitter so concurrent writers spread out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates the default policy: up to 5 retries, backing off from 10 ms
+    /// to at most 500 ms.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a policy that never retries.
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self::default().max_retries(0)
+    }
+
+    /// Sets how many times an operation is retried after its first attempt.
+    #[must_use]
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    #[must_use]
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Sets the longest delay between retries.
+    #[must_use]
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Returns the delay before retry number `retry` (from 0): between half
+    /// and all of the exponential backoff.
+    #[must_use]
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+        backoff.mul_f64(0.5 + random_fraction() / 2.0)
+    }
+
+    /// Runs `op`, running it again while it fails with
+    /// [`FormError::DatabaseBusy`](crate::FormError::DatabaseBusy) and
+    /// retries are left.
+    ///
+    /// `op` should start its own transaction, so every attempt starts over.
+    #[cfg(feature = "handlers")]
+    pub async fn run<T, F, Fut>(&self, mut op: F) -> Result<T, crate::FormError>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<T, crate::FormError>>,
+    {
+        let mut retry = 0;
+        loop {
+            match op().await {
+                Err(crate::FormError::DatabaseBusy(message)) if retry < self.max_retries => {
+                    let backoff = self.backoff(retry);
+                    tracing::debug!(retry, ?backoff, %message, "database busy; retrying");
+                    tokio::time::sleep(backoff).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::new()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(300));
+
+        for _ in 0..20 {
+            let first = policy.backoff(0);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let second = policy.backoff(1);
+            assert!(second >= Duration::from_millis(100) && second <= Duration::from_millis(200));
+            let capped = policy.backoff(30);
+            assert!(capped >= Duration::from_millis(150) && capped <= Duration::from_millis(300));
+        }
+    }
+
+    #[test]
+    fn test_is_busy() {
+        let busy = DbErr::Custom("error returned from database: (code: 5) database is locked".into());
+        assert!(is_busy(&busy));
+        assert!(!is_busy(&DbErr::Custom("UNIQUE constraint failed".into())));
+    }
+}
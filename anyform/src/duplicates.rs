@@ -0,0 +1,311 @@
+//! One response per browser, for kiosk and public polls.
+//!
+//! Forms with [`FormSettings::duplicate_guard`](crate::FormSettings::duplicate_guard)
+//! set remember which browsers responded:
+//!
+//! - every browser gets an ID, kept in the [`BROWSER_COOKIE`] cookie and
+//!   signed with the router's [`SpamSecret`], and stored with its
+//!   submissions as `browser_id` metadata;
+//! - a browser that responds gets a token signed for the form and its ID,
+//!   set as a cookie and answered as `duplicate_token`, so clients that keep
+//!   it in `localStorage` instead can send it back in [`RESPONDED_HEADER`].
+//!
+//! Submissions carrying a valid token are rejected with
+//! `409 ALREADY_RESPONDED`, and the form page shows
+//! [`DuplicateGuard::message`] instead of the form. Browsers on the form's
+//! allow list, such as a shared kiosk, are never turned away; admins add
+//! them with `PUT /admin/forms/{id}/duplicate-guard/browsers/{browser_id}`.
+//!
+//! Clearing cookies or switching browsers gets around the guard: it's enough
+//! for an office poll, not for a vote. Use
+//! [`FormSettings::identified`](crate::FormSettings::identified) where it
+//! matters.
+//!
+//! ```
+//! use anyform::duplicates::DuplicateGuard;
+//! use anyform::FormSettings;
+//!
+//! let settings = FormSettings::new().duplicate_guard(
+//!     DuplicateGuard::one_per_browser().message("Thanks, we've got your vote!"),
+//! );
+//! ```
+
+use http::{header, HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::FormError;
+use crate::spam::SpamSecret;
+
+/// Cookie carrying the browser's signed ID.
+pub const BROWSER_COOKIE: &str = "af_browser";
+
+/// Header carrying the browser's signed ID, for clients without cookies.
+pub const BROWSER_HEADER: &str = "x-anyform-browser";
+
+/// Prefix of the cookie marking a form as responded to; the form's ID
+/// follows.
+pub const RESPONDED_COOKIE_PREFIX: &str = "af_responded_";
+
+/// Header carrying a `duplicate_token`, for clients without cookies.
+pub const RESPONDED_HEADER: &str = "x-anyform-responded";
+
+/// Default [`DuplicateGuard::message`].
+pub const DEFAULT_ALREADY_RESPONDED_MESSAGE: &str =
+    "You have already responded to this form. Thank you!";
+
+/// How long the guard's cookies last, in seconds.
+const COOKIE_MAX_AGE: u32 = 365 * 24 * 60 * 60;
+
+/// Which responses a [`DuplicateGuard`] turns away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    /// A second response from the same browser.
+    #[default]
+    OnePerBrowser,
+}
+
+/// Duplicate prevention settings for a form; see [`crate::duplicates`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateGuard {
+    /// Which responses are turned away.
+    #[serde(default)]
+    pub policy: DuplicatePolicy,
+
+    /// Shown to browsers that already responded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// IDs of browsers that may respond any number of times.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_browsers: Vec<String>,
+}
+
+impl DuplicateGuard {
+    /// Accepts one response per browser.
+    #[must_use]
+    pub fn one_per_browser() -> Self {
+        Self::default()
+    }
+
+    /// Sets the message shown to browsers that already responded.
+    #[must_use]
+    pub fn message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Lets a browser respond any number of times.
+    #[must_use]
+    pub fn allow_browser(mut self, browser_id: impl Into<String>) -> Self {
+        self.set_allowed(&browser_id.into(), true);
+        self
+    }
+
+    /// Returns the message shown to browsers that already responded.
+    #[must_use]
+    pub fn message_or_default(&self) -> &str {
+        self.message
+            .as_deref()
+            .unwrap_or(DEFAULT_ALREADY_RESPONDED_MESSAGE)
+    }
+
+    /// Returns whether `browser_id` may respond any number of times.
+    #[must_use]
+    pub fn is_allowed(&self, browser_id: Uuid) -> bool {
+        self.allowed_browsers
+            .iter()
+            .any(|id| Uuid::parse_str(id).is_ok_and(|id| id == browser_id))
+    }
+
+    /// Adds `browser_id` to or removes it from the allow list.
+    pub fn set_allowed(&mut self, browser_id: &str, allowed: bool) {
+        self.allowed_browsers.retain(|id| !id.eq_ignore_ascii_case(browser_id));
+        if allowed {
+            self.allowed_browsers.push(browser_id.to_ascii_lowercase());
+        }
+    }
+
+    /// Rejects a browser that already responded, unless it's on the allow
+    /// list.
+    pub fn check(&self, browser: &Browser) -> Result<(), FormError> {
+        if browser.responded && !self.is_allowed(browser.id) {
+            return Err(FormError::AlreadyResponded);
+        }
+        Ok(())
+    }
+}
+
+/// A browser, as seen by one form's [`DuplicateGuard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Browser {
+    /// The browser's ID.
+    pub id: Uuid,
+    /// Whether the ID was issued by this request.
+    pub issued: bool,
+    /// Whether the browser already responded to the form.
+    pub responded: bool,
+}
+
+impl Browser {
+    /// Identifies the browser making a request to form `form_id` from its
+    /// cookies or headers, issuing it an ID if it has none.
+    #[must_use]
+    pub fn identify(secret: &SpamSecret, form_id: Uuid, headers: &HeaderMap) -> Self {
+        let responded_by = cookie(headers, &responded_cookie(form_id))
+            .into_iter()
+            .chain(header_value(headers, RESPONDED_HEADER))
+            .find_map(|token| open_responded(secret, form_id, token));
+        let known = cookie(headers, BROWSER_COOKIE)
+            .into_iter()
+            .chain(header_value(headers, BROWSER_HEADER))
+            .find_map(|token| open_id(secret, token))
+            .or(responded_by);
+
+        Self {
+            id: known.unwrap_or_else(Uuid::new_v4),
+            issued: known.is_none(),
+            responded: responded_by.is_some(),
+        }
+    }
+
+    /// Returns the `duplicate_token` marking this browser as having
+    /// responded to form `form_id`.
+    #[must_use]
+    pub fn responded_token(&self, secret: &SpamSecret, form_id: Uuid) -> String {
+        let mut payload = [0u8; 32];
+        payload[..16].copy_from_slice(form_id.as_bytes());
+        payload[16..].copy_from_slice(self.id.as_bytes());
+        secret.sign(&payload)
+    }
+
+    /// Returns the `Set-Cookie` values to answer with: the browser's ID when
+    /// newly issued, and the form's responded mark once it `responded`.
+    #[must_use]
+    pub fn cookies(
+        &self,
+        secret: &SpamSecret,
+        form_id: Uuid,
+        responded: bool,
+    ) -> Vec<HeaderValue> {
+        let mut cookies = Vec::new();
+        if self.issued {
+            cookies.push(set_cookie(BROWSER_COOKIE, &secret.sign(self.id.as_bytes())));
+        }
+        if responded {
+            let token = self.responded_token(secret, form_id);
+            cookies.push(set_cookie(&responded_cookie(form_id), &token));
+        }
+        cookies.into_iter().flatten().collect()
+    }
+}
+
+/// Returns the name of the cookie marking form `form_id` as responded to.
+#[must_use]
+pub fn responded_cookie(form_id: Uuid) -> String {
+    format!("{RESPONDED_COOKIE_PREFIX}{}", form_id.simple())
+}
+
+fn open_id(secret: &SpamSecret, token: &str) -> Option<Uuid> {
+    Uuid::from_slice(&secret.open(token)?).ok()
+}
+
+fn open_responded(secret: &SpamSecret, form_id: Uuid, token: &str) -> Option<Uuid> {
+    let payload = secret.open(token)?;
+    if payload.len() != 32 || payload[..16] != form_id.as_bytes()[..] {
+        return None;
+    }
+    Uuid::from_slice(&payload[16..]).ok()
+}
+
+fn set_cookie(name: &str, value: &str) -> Option<HeaderValue> {
+    HeaderValue::from_str(&format!(
+        "{name}={value}; Path=/; Max-Age={COOKIE_MAX_AGE}; HttpOnly; SameSite=Lax"
+    ))
+    .ok()
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Returns the value of cookie `name`.
+fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_cookies(cookies: &[HeaderValue]) -> HeaderMap {
+        let pairs: Vec<&str> = cookies
+            .iter()
+            .map(|c| c.to_str().unwrap().split(';').next().unwrap())
+            .collect();
+        let mut headers = HeaderMap::new();
+        headers.insert(header::COOKIE, HeaderValue::from_str(&pairs.join("; ")).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_browsers_are_remembered_per_form() {
+        let secret = SpamSecret::random();
+        let form_id = Uuid::new_v4();
+
+        let first = Browser::identify(&secret, form_id, &HeaderMap::new());
+        assert!(first.issued && !first.responded);
+
+        let headers = with_cookies(&first.cookies(&secret, form_id, true));
+        let again = Browser::identify(&secret, form_id, &headers);
+        assert_eq!(again.id, first.id);
+        assert!(!again.issued && again.responded);
+
+        let other_form = Browser::identify(&secret, Uuid::new_v4(), &headers);
+        assert_eq!(other_form.id, first.id);
+        assert!(!other_form.responded);
+
+        let forged = Browser::identify(&SpamSecret::random(), form_id, &headers);
+        assert!(forged.issued && !forged.responded);
+    }
+
+    #[test]
+    fn test_tokens_are_accepted_from_headers() {
+        let secret = SpamSecret::random();
+        let form_id = Uuid::new_v4();
+        let browser = Browser::identify(&secret, form_id, &HeaderMap::new());
+
+        let mut headers = HeaderMap::new();
+        let token = browser.responded_token(&secret, form_id);
+        headers.insert(RESPONDED_HEADER, HeaderValue::from_str(&token).unwrap());
+        let again = Browser::identify(&secret, form_id, &headers);
+        assert_eq!(again.id, browser.id);
+        assert!(again.responded);
+    }
+
+    #[test]
+    fn test_allowed_browsers_may_respond_again() {
+        let browser = Browser {
+            id: Uuid::new_v4(),
+            issued: false,
+            responded: true,
+        };
+        let mut guard = DuplicateGuard::one_per_browser();
+        assert!(matches!(guard.check(&browser), Err(FormError::AlreadyResponded)));
+
+        guard.set_allowed(&browser.id.to_string().to_uppercase(), true);
+        assert!(guard.check(&browser).is_ok());
+        assert_eq!(guard.allowed_browsers, vec![browser.id.to_string()]);
+
+        guard.set_allowed(&browser.id.to_string(), false);
+        assert!(guard.allowed_browsers.is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+//! Encryption of sensitive answers at rest.
+//!
+//! Give the router an [`EncryptionKey`] with
+//! [`AnyFormRouterBuilder::encryption_key`](crate::AnyFormRouterBuilder::encryption_key)
+//! and answers to fields classified
+//! [`FieldClassification::Sensitive`](crate::schema::FieldClassification::Sensitive)
+//! are encrypted with AES-256-GCM before they're stored. The admin API, its
+//! exports and PDFs, and event hooks get them decrypted.
+//!
+//! Each encrypted answer is a string, `enc:v1:` followed by the base64url
+//! nonce, ciphertext and tag, bound to the field's name. Answers stored
+//! before the key was set stay readable as they are.
+//!
+//! ```
+//! use anyform::encryption::EncryptionKey;
+//! use serde_json::json;
+//!
+//! let key = EncryptionKey::new([7; 32]);
+//! let stored = key.encrypt("ssn", &json!("078-05-1120"));
+//! assert!(stored.starts_with("enc:v1:"));
+//! assert_eq!(key.decrypt("ssn", &stored), Some(json!("078-05-1120")));
+//! // Bound to the field it answers
+//! assert_eq!(key.decrypt("email", &stored), None);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::entities::{field, submission};
+use crate::error::FormError;
+use crate::schema::{FieldClassification, FieldValue};
+
+/// Prefix of encrypted answers.
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Returns true if `value` is an encrypted answer.
+#[must_use]
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// AES-256-GCM key encrypting answers to sensitive fields.
+#[derive(Clone)]
+pub struct EncryptionKey(Arc<LessSafeKey>);
+
+impl EncryptionKey {
+    /// Creates a key from 32 secret bytes.
+    #[must_use]
+    pub fn new(key: [u8; 32]) -> Self {
+        let key = UnboundKey::new(&AES_256_GCM, &key).expect("AES-256 keys are 32 bytes");
+        Self(Arc::new(LessSafeKey::new(key)))
+    }
+
+    /// Creates a key from 32 bytes in base64 (standard or URL-safe, padded
+    /// or not), e.g. from `openssl rand -base64 32`.
+    ///
+    /// Returns [`FormError::InvalidData`] for anything else.
+    pub fn from_base64(key: &str) -> Result<Self, FormError> {
+        use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE};
+
+        let key = key.trim();
+        [STANDARD, STANDARD_NO_PAD, URL_SAFE, BASE64]
+            .iter()
+            .find_map(|engine| engine.decode(key).ok())
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .map(Self::new)
+            .ok_or_else(|| {
+                FormError::InvalidData("Encryption key must be 32 bytes in base64".to_string())
+            })
+    }
+
+    /// Encrypts the answer to `field`.
+    #[must_use]
+    pub fn encrypt(&self, field: &str, value: &serde_json::Value) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .expect("the system random number generator is available");
+        let mut sealed = serde_json::to_vec(value).unwrap_or_default();
+        self.0
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(field.as_bytes()),
+                &mut sealed,
+            )
+            .expect("answers fit in one AES-GCM message");
+        sealed.splice(0..0, nonce);
+        format!("{ENCRYPTED_PREFIX}{}", BASE64.encode(sealed))
+    }
+
+    /// Decrypts an encrypted answer to `field`.
+    ///
+    /// Returns `None` if it isn't one, or wasn't encrypted with this key for
+    /// `field`.
+    #[must_use]
+    pub fn decrypt(&self, field: &str, encrypted: &str) -> Option<serde_json::Value> {
+        let mut sealed = BASE64.decode(encrypted.strip_prefix(ENCRYPTED_PREFIX)?).ok()?;
+        if sealed.len() < NONCE_LEN {
+            return None;
+        }
+        let nonce = Nonce::try_assume_unique_for_key(&sealed[..NONCE_LEN]).ok()?;
+        let plain = self
+            .0
+            .open_within(nonce, Aad::from(field.as_bytes()), &mut sealed, NONCE_LEN..)
+            .ok()?;
+        serde_json::from_slice(plain).ok()
+    }
+
+    /// Returns `data` with the answers to `fields`' sensitive fields
+    /// encrypted, keyed by field name or ID.
+    ///
+    /// A repeatable group's rows are encrypted as a whole when the group is
+    /// sensitive. Empty answers are left as they are; every other answer is
+    /// encrypted, so `data` must hold the answers as submitted, not as stored.
+    #[must_use]
+    pub fn encrypt_answers(
+        &self,
+        fields: &[field::Model],
+        data: &HashMap<String, FieldValue>,
+    ) -> HashMap<String, FieldValue> {
+        let mut encrypted = data.clone();
+        for field in fields
+            .iter()
+            .filter(|f| f.classification() == FieldClassification::Sensitive)
+        {
+            for key in [field.name.clone(), field.id.to_string()] {
+                let Some(value) = encrypted.get_mut(&key) else {
+                    continue;
+                };
+                if value.is_empty() {
+                    continue;
+                }
+                *value = FieldValue::Text(self.encrypt(&key, &serde_json::Value::from(&*value)));
+            }
+        }
+        encrypted
+    }
+
+    /// Decrypts the encrypted answers in `submission`'s data in place.
+    ///
+    /// Answers encrypted with another key are left encrypted.
+    pub fn decrypt_submission(&self, submission: &mut submission::Model) {
+        self.decrypt_data(&mut submission.data);
+    }
+
+    /// Decrypts the encrypted answers in a JSON object of answers by field
+    /// name or ID, in place, as [`decrypt_submission`](Self::decrypt_submission)
+    /// does.
+    pub fn decrypt_data(&self, data: &mut serde_json::Value) {
+        let Some(data) = data.as_object_mut() else {
+            return;
+        };
+        for (key, value) in data.iter_mut() {
+            let Some(plain) = value.as_str().and_then(|v| self.decrypt(key, v)) else {
+                continue;
+            };
+            *value = plain;
+        }
+    }
+}
+
+/// Returns `submission`'s answers, decrypted with `key` if there is one.
+pub(crate) fn plain_answers(
+    submission: &submission::Model,
+    key: Option<&EncryptionKey>,
+) -> HashMap<String, FieldValue> {
+    let Some(key) = key else {
+        return submission.data_map();
+    };
+    let mut data = submission.data.clone();
+    key.decrypt_data(&mut data);
+    serde_json::from_value(data).unwrap_or_default()
+}
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_round_trip_and_tampering() {
+        let key = EncryptionKey::new([1; 32]);
+        let value = json!({ "lat": 51.5, "lng": -0.12 });
+        let first = key.encrypt("spot", &value);
+        let second = key.encrypt("spot", &value);
+        // Fresh nonce each time
+        assert_ne!(first, second);
+        assert_eq!(key.decrypt("spot", &second), Some(value));
+
+        assert_eq!(EncryptionKey::new([2; 32]).decrypt("spot", &first), None);
+        let mut tampered = first.clone().into_bytes();
+        let last = tampered.len() - 1;
+        tampered[last] = if tampered[last] == b'A' { b'B' } else { b'A' };
+        assert_eq!(key.decrypt("spot", &String::from_utf8(tampered).unwrap()), None);
+        assert_eq!(key.decrypt("spot", "enc:v1:"), None);
+        assert_eq!(key.decrypt("spot", "plain text"), None);
+        assert_eq!(format!("{key:?}"), "EncryptionKey(..)");
+    }
+
+    #[test]
+    fn test_from_base64() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode([9u8; 32]);
+        let key = EncryptionKey::from_base64(&encoded).unwrap();
+        let encrypted = EncryptionKey::new([9; 32]).encrypt("a", &json!(1));
+        assert_eq!(key.decrypt("a", &encrypted), Some(json!(1)));
+
+        assert!(EncryptionKey::from_base64("c2hvcnQ=").is_err());
+        assert!(EncryptionKey::from_base64("not base64!").is_err());
+    }
+}
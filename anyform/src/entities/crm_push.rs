@@ -0,0 +1,222 @@
+//! CRM push entity (submissions queued for, or pushed to, a CRM).
+
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveModelTrait, ActiveValue, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::crm::CrmProvider;
+
+/// Where a CRM push is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrmPushStatus {
+    /// Waiting to be pushed, or retried.
+    Pending,
+    /// Pushed; the CRM record ID is stored.
+    Pushed,
+    /// Given up on after too many failures, or because the submission
+    /// is gone.
+    Failed,
+}
+
+impl CrmPushStatus {
+    /// Returns the status as stored in the database.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Pushed => "pushed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "af_crm_pushes")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub form_id: Uuid,
+
+    pub submission_id: Uuid,
+
+    /// CRM pushed to (see [`CrmProvider`]).
+    pub provider: String,
+
+    /// The respondent's email address, trimmed and lowercased.
+    pub email: String,
+
+    /// `pending`, `pushed` or `failed` (see [`CrmPushStatus`]).
+    pub status: String,
+
+    /// Number of failed attempts.
+    pub attempts: i32,
+
+    /// When the push is next attempted, while pending.
+    pub next_attempt_at: DateTimeWithTimeZone,
+
+    /// The most recent failure.
+    #[sea_orm(column_type = "Text", nullable)]
+    pub last_error: Option<String>,
+
+    /// ID of the CRM record, once pushed.
+    pub record_id: Option<String>,
+
+    pub created_at: DateTimeWithTimeZone,
+
+    pub updated_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::form::Entity",
+        from = "Column::FormId",
+        to = "super::form::Column::Id"
+    )]
+    Form,
+    #[sea_orm(
+        belongs_to = "super::submission::Entity",
+        from = "Column::SubmissionId",
+        to = "super::submission::Column::Id"
+    )]
+    Submission,
+}
+
+impl Related<super::form::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Form.def()
+    }
+}
+
+impl Related<super::submission::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Submission.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Returns the CRM pushed to.
+    #[must_use]
+    pub fn provider(&self) -> Option<CrmProvider> {
+        self.provider.parse().ok()
+    }
+
+    /// Returns true once the record is in the CRM.
+    #[must_use]
+    pub fn is_pushed(&self) -> bool {
+        self.status == CrmPushStatus::Pushed.as_str()
+    }
+}
+
+impl Entity {
+    /// Queues a submission to be pushed.
+    pub async fn enqueue<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        submission_id: Uuid,
+        provider: CrmProvider,
+        email: &str,
+    ) -> Result<Model, DbErr> {
+        let now = chrono::Utc::now().fixed_offset();
+        let model = ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            form_id: ActiveValue::Set(form_id),
+            submission_id: ActiveValue::Set(submission_id),
+            provider: ActiveValue::Set(provider.as_str().to_string()),
+            email: ActiveValue::Set(email.to_string()),
+            status: ActiveValue::Set(CrmPushStatus::Pending.as_str().to_string()),
+            attempts: ActiveValue::Set(0),
+            next_attempt_at: ActiveValue::Set(now),
+            last_error: ActiveValue::Set(None),
+            record_id: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+        };
+        model.insert(db).await
+    }
+
+    /// Find pending pushes to `provider` due at `now`, oldest first.
+    pub async fn find_due(
+        db: &DatabaseConnection,
+        provider: CrmProvider,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<Model>, DbErr> {
+        Self::find()
+            .filter(Column::Provider.eq(provider.as_str()))
+            .filter(Column::Status.eq(CrmPushStatus::Pending.as_str()))
+            .filter(Column::NextAttemptAt.lte(now))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Find the most recent successful push to `provider` for `email`.
+    pub async fn find_pushed_for_email(
+        db: &DatabaseConnection,
+        provider: CrmProvider,
+        email: &str,
+    ) -> Result<Option<Model>, DbErr> {
+        Self::find()
+            .filter(Column::Provider.eq(provider.as_str()))
+            .filter(Column::Email.eq(email))
+            .filter(Column::Status.eq(CrmPushStatus::Pushed.as_str()))
+            .order_by_desc(Column::UpdatedAt)
+            .one(db)
+            .await
+    }
+
+    /// Find the pushes of a submission, oldest first.
+    pub async fn find_by_submission(
+        db: &DatabaseConnection,
+        submission_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Self::find()
+            .filter(Column::SubmissionId.eq(submission_id))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Records a successful push.
+    pub async fn mark_pushed(
+        db: &DatabaseConnection,
+        push: Model,
+        record_id: &str,
+    ) -> Result<Model, DbErr> {
+        let mut active: ActiveModel = push.into();
+        active.status = ActiveValue::Set(CrmPushStatus::Pushed.as_str().to_string());
+        active.record_id = ActiveValue::Set(Some(record_id.to_string()));
+        active.last_error = ActiveValue::Set(None);
+        active.updated_at = ActiveValue::Set(chrono::Utc::now().fixed_offset());
+        active.update(db).await
+    }
+
+    /// Records a failed push, to be retried at `retry_at` or, without one,
+    /// given up on.
+    pub async fn mark_failed(
+        db: &DatabaseConnection,
+        push: Model,
+        error: &str,
+        retry_at: Option<DateTimeWithTimeZone>,
+    ) -> Result<Model, DbErr> {
+        let attempts = push.attempts + 1;
+        let status = if retry_at.is_some() {
+            CrmPushStatus::Pending
+        } else {
+            CrmPushStatus::Failed
+        };
+        let mut active: ActiveModel = push.into();
+        active.status = ActiveValue::Set(status.as_str().to_string());
+        active.attempts = ActiveValue::Set(attempts);
+        active.last_error = ActiveValue::Set(Some(error.to_string()));
+        if let Some(retry_at) = retry_at {
+            active.next_attempt_at = ActiveValue::Set(retry_at);
+        }
+        active.updated_at = ActiveValue::Set(chrono::Utc::now().fixed_offset());
+        active.update(db).await
+    }
+}
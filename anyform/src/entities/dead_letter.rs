@@ -0,0 +1,155 @@
+//! Dead letter entity (validated submissions that failed to persist).
+
+use std::collections::HashMap;
+
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveModelTrait, ActiveValue, PaginatorTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use super::submission::SubmissionMetadata;
+use crate::schema::FieldValue;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "af_dead_letters")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub form_id: Uuid,
+
+    /// The validated submission data.
+    #[sea_orm(column_type = "Json")]
+    pub data: Json,
+
+    /// Submission metadata, as it would have been stored.
+    #[sea_orm(column_type = "Json", nullable)]
+    pub metadata: Option<Json>,
+
+    /// The most recent failure.
+    #[sea_orm(column_type = "Text")]
+    pub error: String,
+
+    /// Number of failed replays.
+    pub attempts: i32,
+
+    /// The submission created by a successful replay.
+    pub submission_id: Option<Uuid>,
+
+    pub created_at: DateTimeWithTimeZone,
+
+    pub replayed_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::form::Entity",
+        from = "Column::FormId",
+        to = "super::form::Column::Id"
+    )]
+    Form,
+}
+
+impl Related<super::form::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Form.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Returns true once a replay has stored the submission.
+    #[must_use]
+    pub fn is_replayed(&self) -> bool {
+        self.replayed_at.is_some()
+    }
+
+    /// Returns the captured submission data.
+    #[must_use]
+    pub fn data(&self) -> HashMap<String, FieldValue> {
+        serde_json::from_value(self.data.clone()).unwrap_or_default()
+    }
+
+    /// Returns the captured submission metadata.
+    #[must_use]
+    pub fn metadata(&self) -> Option<SubmissionMetadata> {
+        self.metadata
+            .clone()
+            .and_then(|m| serde_json::from_value(m).ok())
+    }
+}
+
+impl Entity {
+    /// Find dead letters still awaiting replay, oldest first.
+    ///
+    /// Limited to one form when `form_id` is given.
+    pub async fn find_pending(
+        db: &DatabaseConnection,
+        form_id: Option<Uuid>,
+    ) -> Result<Vec<Model>, DbErr> {
+        let mut query = Self::find().filter(Column::ReplayedAt.is_null());
+        if let Some(form_id) = form_id {
+            query = query.filter(Column::FormId.eq(form_id));
+        }
+        query.order_by_asc(Column::CreatedAt).all(db).await
+    }
+
+    /// Counts dead letters still awaiting replay, across all forms.
+    pub async fn count_pending(db: &DatabaseConnection) -> Result<u64, DbErr> {
+        Self::find()
+            .filter(Column::ReplayedAt.is_null())
+            .count(db)
+            .await
+    }
+
+    /// Records a submission that failed to persist.
+    pub async fn record(
+        db: &DatabaseConnection,
+        form_id: Uuid,
+        data: &HashMap<String, FieldValue>,
+        metadata: Option<&SubmissionMetadata>,
+        error: &str,
+    ) -> Result<Model, DbErr> {
+        let model = ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            form_id: ActiveValue::Set(form_id),
+            data: ActiveValue::Set(serde_json::to_value(data).unwrap_or_default()),
+            metadata: ActiveValue::Set(
+                metadata.map(|m| serde_json::to_value(m).unwrap_or_default()),
+            ),
+            error: ActiveValue::Set(error.to_string()),
+            attempts: ActiveValue::Set(0),
+            submission_id: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(chrono::Utc::now().fixed_offset()),
+            replayed_at: ActiveValue::Set(None),
+        };
+
+        model.insert(db).await
+    }
+
+    /// Marks a dead letter as replayed into `submission_id`.
+    pub async fn mark_replayed(
+        db: &DatabaseConnection,
+        dead_letter: Model,
+        submission_id: Uuid,
+    ) -> Result<Model, DbErr> {
+        let mut active: ActiveModel = dead_letter.into();
+        active.submission_id = ActiveValue::Set(Some(submission_id));
+        active.replayed_at = ActiveValue::Set(Some(chrono::Utc::now().fixed_offset()));
+        active.update(db).await
+    }
+
+    /// Records a failed replay.
+    pub async fn mark_failed(
+        db: &DatabaseConnection,
+        dead_letter: Model,
+        error: &str,
+    ) -> Result<Model, DbErr> {
+        let attempts = dead_letter.attempts + 1;
+        let mut active: ActiveModel = dead_letter.into();
+        active.error = ActiveValue::Set(error.to_string());
+        active.attempts = ActiveValue::Set(attempts);
+        active.update(db).await
+    }
+}
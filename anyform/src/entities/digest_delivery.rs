@@ -0,0 +1,88 @@
+//! Digest delivery entity (notification digests that have been sent).
+
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveValue, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "af_digest_deliveries")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub form_id: Uuid,
+
+    pub period_start: DateTimeWithTimeZone,
+
+    pub period_end: DateTimeWithTimeZone,
+
+    /// Submissions the digest reported.
+    pub submissions: i32,
+
+    pub sent_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::form::Entity",
+        from = "Column::FormId",
+        to = "super::form::Column::Id"
+    )]
+    Form,
+}
+
+impl Related<super::form::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Form.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    /// Find the delivery of a form's digest for the period starting at
+    /// `period_start`.
+    pub async fn find_for_period<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        period_start: DateTimeWithTimeZone,
+    ) -> Result<Option<Model>, DbErr> {
+        Self::find()
+            .filter(Column::FormId.eq(form_id))
+            .filter(Column::PeriodStart.eq(period_start))
+            .one(db)
+            .await
+    }
+
+    /// Find all deliveries of a form's digest, newest first.
+    pub async fn find_by_form<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Self::find()
+            .filter(Column::FormId.eq(form_id))
+            .order_by_desc(Column::PeriodStart)
+            .all(db)
+            .await
+    }
+
+    /// Records that a digest was sent.
+    pub async fn record<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        period_start: DateTimeWithTimeZone,
+        period_end: DateTimeWithTimeZone,
+        submissions: i32,
+    ) -> Result<Model, DbErr> {
+        let model = ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            form_id: ActiveValue::Set(form_id),
+            period_start: ActiveValue::Set(period_start),
+            period_end: ActiveValue::Set(period_end),
+            submissions: ActiveValue::Set(submissions),
+            sent_at: ActiveValue::Set(chrono::Utc::now().fixed_offset()),
+        };
+        model.insert(db).await
+    }
+}
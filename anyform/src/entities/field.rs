@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::condition::ConditionRule;
 
-use crate::schema::{UiOptions, ValidationRules, ValueType};
+use crate::schema::{FieldClassification, UiOptions, ValidationRules, ValueType};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "af_fields")]
@@ -16,6 +16,10 @@ pub struct Model {
 
     pub step_id: Uuid,
 
+    /// The repeatable group this field is a sub-field of; `None` for the
+    /// step's own fields.
+    pub parent_id: Option<Uuid>,
+
     /// Field identifier (snake_case).
     pub name: String,
 
@@ -51,6 +55,12 @@ pub struct Model {
 
     pub weight: Option<f64>,
 
+    /// Scoring category the field's points also count towards.
+    pub category: Option<String>,
+
+    /// Data classification; see [`FieldClassification`].
+    pub classification: String,
+
     pub created_at: DateTimeWithTimeZone,
 }
 
@@ -109,14 +119,26 @@ impl Model {
     #[must_use]
     pub fn requires_options(&self) -> bool {
         self.value_type()
-            .map_or(false, |vt| vt.requires_options())
+            .is_some_and(|vt| vt.requires_options())
+    }
+
+    /// Returns true if this field is a repeatable group of sub-fields.
+    #[must_use]
+    pub fn is_repeater(&self) -> bool {
+        self.value_type() == Some(ValueType::Repeater)
     }
 
     /// Returns true if this field is display-only.
     #[must_use]
     pub fn is_display_only(&self) -> bool {
         self.value_type()
-            .map_or(false, |vt| vt.is_display_only())
+            .is_some_and(|vt| vt.is_display_only())
+    }
+
+    /// Returns the field's data classification.
+    #[must_use]
+    pub fn classification(&self) -> FieldClassification {
+        self.classification.parse().unwrap_or_default()
     }
 
     /// Returns the condition rule for dynamic field visibility.
@@ -128,12 +150,52 @@ impl Model {
 
 impl Entity {
     /// Find all fields for a step, ordered by position.
-    pub async fn find_by_step(
-        db: &DatabaseConnection,
+    ///
+    /// Sub-fields of repeatable groups are left out; see
+    /// [`find_sub_fields`](Self::find_sub_fields).
+    pub async fn find_by_step<C: ConnectionTrait>(
+        db: &C,
         step_id: Uuid,
     ) -> Result<Vec<Model>, DbErr> {
         Self::find()
             .filter(Column::StepId.eq(step_id))
+            .filter(Column::ParentId.is_null())
+            .order_by_asc(Column::Order)
+            .all(db)
+            .await
+    }
+
+    /// Find all fields for several steps in one query, ordered by position
+    /// within each step.
+    ///
+    /// Sub-fields of repeatable groups are left out, as by
+    /// [`find_by_step`](Self::find_by_step).
+    pub async fn find_by_steps<C: ConnectionTrait>(
+        db: &C,
+        step_ids: &[Uuid],
+    ) -> Result<Vec<Model>, DbErr> {
+        if step_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        Self::find()
+            .filter(Column::StepId.is_in(step_ids.iter().copied()))
+            .filter(Column::ParentId.is_null())
+            .order_by_asc(Column::Order)
+            .all(db)
+            .await
+    }
+
+    /// Find the sub-fields of several repeatable groups in one query,
+    /// ordered by position within each group.
+    pub async fn find_sub_fields<C: ConnectionTrait>(
+        db: &C,
+        group_ids: &[Uuid],
+    ) -> Result<Vec<Model>, DbErr> {
+        if group_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        Self::find()
+            .filter(Column::ParentId.is_in(group_ids.iter().copied()))
             .order_by_asc(Column::Order)
             .all(db)
             .await
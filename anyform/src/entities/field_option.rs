@@ -23,6 +23,19 @@ pub struct Model {
     pub is_correct: bool,
 
     pub points: Option<i32>,
+
+    /// Maximum number of submissions that may choose this option.
+    pub capacity: Option<i32>,
+
+    /// Seats taken so far (only tracked for options with a capacity).
+    pub reserved: i32,
+
+    /// Heading the option is listed under, such as a region.
+    #[sea_orm(column_name = "group")]
+    pub group: Option<String>,
+
+    /// Picture shown on the option's card, for image-choice fields.
+    pub image_url: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -43,10 +56,24 @@ impl Related<super::field::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+impl Model {
+    /// Returns the number of seats left, or `None` for unlimited options.
+    #[must_use]
+    pub fn remaining(&self) -> Option<i32> {
+        self.capacity.map(|c| (c - self.reserved).max(0))
+    }
+
+    /// Returns true if the option has a capacity and no seats left.
+    #[must_use]
+    pub fn is_sold_out(&self) -> bool {
+        self.remaining() == Some(0)
+    }
+}
+
 impl Entity {
     /// Find all options for a field, ordered by position.
-    pub async fn find_by_field(
-        db: &DatabaseConnection,
+    pub async fn find_by_field<C: ConnectionTrait>(
+        db: &C,
         field_id: Uuid,
     ) -> Result<Vec<Model>, DbErr> {
         Self::find()
@@ -55,4 +82,20 @@ impl Entity {
             .all(db)
             .await
     }
+
+    /// Find all options for several fields in one query, ordered by position
+    /// within each field.
+    pub async fn find_by_fields<C: ConnectionTrait>(
+        db: &C,
+        field_ids: &[Uuid],
+    ) -> Result<Vec<Model>, DbErr> {
+        if field_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        Self::find()
+            .filter(Column::FieldId.is_in(field_ids.iter().copied()))
+            .order_by_asc(Column::Order)
+            .all(db)
+            .await
+    }
 }
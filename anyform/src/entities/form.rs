@@ -22,6 +22,18 @@ pub struct Model {
     #[sea_orm(column_type = "Json")]
     pub settings: Option<serde_json::Value>,
 
+    /// Cached count of accepted responses, used for `close_after` quotas.
+    pub response_count: i32,
+
+    /// `draft`, `published` or `archived` (see [`FormStatus`]).
+    pub status: String,
+
+    /// `open` or `closed`, as last announced to
+    /// [`FormEvents`](crate::events::FormEvents) (see
+    /// [`LifecycleService`](crate::services::LifecycleService)); `None`
+    /// until first checked.
+    pub lifecycle_state: Option<String>,
+
     pub created_at: DateTimeWithTimeZone,
 
     pub updated_at: DateTimeWithTimeZone,
@@ -59,6 +71,54 @@ impl Related<super::result::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+/// Publication status of a form.
+///
+/// Only published forms are served on public routes; drafts and archived
+/// forms answer `404` there, but stay visible in the admin API and can be
+/// opened through signed preview links (see [`crate::preview`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FormStatus {
+    /// Being prepared; not public yet.
+    Draft,
+    /// Live on public routes.
+    #[default]
+    Published,
+    /// Retired; no longer public, kept with its submissions.
+    Archived,
+}
+
+impl FormStatus {
+    /// Returns the stored string value.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Draft => "draft",
+            Self::Published => "published",
+            Self::Archived => "archived",
+        }
+    }
+}
+
+impl std::str::FromStr for FormStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(Self::Draft),
+            "published" => Ok(Self::Published),
+            "archived" => Ok(Self::Archived),
+            other => Err(format!("unknown form status: {other}")),
+        }
+    }
+}
+
+impl std::fmt::Display for FormStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl Model {
     /// Returns the form settings, parsed from JSON.
     #[must_use]
@@ -69,6 +129,56 @@ impl Model {
             .unwrap_or_default()
     }
 
+    /// Returns the form's publication status.
+    #[must_use]
+    pub fn status(&self) -> FormStatus {
+        self.status.parse().unwrap_or_default()
+    }
+
+    /// Returns true if the form is served on public routes.
+    #[must_use]
+    pub fn is_published(&self) -> bool {
+        self.status() == FormStatus::Published
+    }
+
+    /// Returns true if the form is an unpublished draft.
+    #[must_use]
+    pub fn is_draft(&self) -> bool {
+        self.status() == FormStatus::Draft
+    }
+
+    /// Returns true if the form is archived.
+    #[must_use]
+    pub fn is_archived(&self) -> bool {
+        self.status() == FormStatus::Archived
+    }
+
+    /// Returns true if the form isn't taking responses now; see
+    /// [`Self::is_closed_at`].
+    #[must_use]
+    pub fn is_closed(&self) -> bool {
+        self.is_closed_at(chrono::Utc::now().fixed_offset())
+    }
+
+    /// Returns true if the form isn't taking responses at `at`: it has
+    /// reached its `close_after` quota, or `at` is outside its `opens_at` to
+    /// `closes_at` window.
+    #[must_use]
+    pub fn is_closed_at(&self, at: DateTimeWithTimeZone) -> bool {
+        let settings = self.settings();
+        settings
+            .close_after
+            .is_some_and(|max| i64::from(self.response_count) >= i64::from(max))
+            || settings.opens_after(at)
+            || settings.closed_by(at)
+    }
+
+    /// Returns true if the form is closed now because it hasn't opened yet.
+    #[must_use]
+    pub fn is_upcoming(&self) -> bool {
+        self.settings().opens_after(chrono::Utc::now().fixed_offset())
+    }
+
     /// Returns true if the form is soft-deleted.
     #[must_use]
     pub fn is_deleted(&self) -> bool {
@@ -0,0 +1,118 @@
+//! Form instance entity (periodic runs of a recurring form).
+
+use sea_orm::entity::prelude::*;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ActiveValue, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::InstanceWindow;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "af_form_instances")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub form_id: Uuid,
+
+    /// Zero-based instance number from the form's recurrence rule.
+    pub sequence: i32,
+
+    pub opens_at: DateTimeWithTimeZone,
+
+    pub closes_at: DateTimeWithTimeZone,
+
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::form::Entity",
+        from = "Column::FormId",
+        to = "super::form::Column::Id"
+    )]
+    Form,
+}
+
+impl Related<super::form::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Form.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Returns true if the instance is open at `at`.
+    #[must_use]
+    pub fn is_open_at(&self, at: DateTimeWithTimeZone) -> bool {
+        self.opens_at <= at && at < self.closes_at
+    }
+}
+
+impl Entity {
+    /// Find all instances of a form, oldest first.
+    pub async fn find_by_form<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Self::find()
+            .filter(Column::FormId.eq(form_id))
+            .order_by_asc(Column::Sequence)
+            .all(db)
+            .await
+    }
+
+    /// Find a form's instance by sequence number.
+    pub async fn find_by_sequence<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        sequence: i32,
+    ) -> Result<Option<Model>, DbErr> {
+        Self::find()
+            .filter(Column::FormId.eq(form_id))
+            .filter(Column::Sequence.eq(sequence))
+            .one(db)
+            .await
+    }
+
+    /// Returns the instance for `window`, creating it on first use.
+    ///
+    /// Safe to call concurrently: the `(form_id, sequence)` unique index
+    /// ensures a single row per instance.
+    pub async fn find_or_create<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        window: &InstanceWindow,
+    ) -> Result<Model, DbErr> {
+        let sequence = i32::try_from(window.sequence)
+            .map_err(|_| DbErr::Custom(format!("instance sequence {} out of range", window.sequence)))?;
+
+        if let Some(existing) = Self::find_by_sequence(db, form_id, sequence).await? {
+            return Ok(existing);
+        }
+
+        let model = ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            form_id: ActiveValue::Set(form_id),
+            sequence: ActiveValue::Set(sequence),
+            opens_at: ActiveValue::Set(window.opens_at),
+            closes_at: ActiveValue::Set(window.closes_at),
+            created_at: ActiveValue::Set(chrono::Utc::now().fixed_offset()),
+        };
+
+        Self::insert(model)
+            .on_conflict(
+                OnConflict::columns([Column::FormId, Column::Sequence])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Self::find_by_sequence(db, form_id, sequence)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("form instance {sequence}")))
+    }
+}
@@ -0,0 +1,113 @@
+//! Form revision entity (field snapshots taken on every create/update).
+
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveModelTrait, ActiveValue, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::FieldClassification;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "af_form_revisions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub form_id: Uuid,
+
+    /// Sequential revision number, starting at 1.
+    pub revision: i32,
+
+    /// Field snapshot as JSON: [RevisionField, ...]
+    #[sea_orm(column_type = "Json")]
+    pub fields: serde_json::Value,
+
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::form::Entity",
+        from = "Column::FormId",
+        to = "super::form::Column::Id"
+    )]
+    Form,
+}
+
+impl Related<super::form::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Form.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// A field as it existed in a given revision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionField {
+    /// Field name (the submission data key).
+    pub name: String,
+    /// Field label at the time of the revision.
+    pub label: String,
+    /// Field type at the time of the revision.
+    pub field_type: String,
+    /// Data classification at the time of the revision.
+    #[serde(default, skip_serializing_if = "FieldClassification::is_standard")]
+    pub classification: FieldClassification,
+}
+
+impl Model {
+    /// Returns the snapshotted fields.
+    #[must_use]
+    pub fn fields(&self) -> Vec<RevisionField> {
+        serde_json::from_value(self.fields.clone()).unwrap_or_default()
+    }
+}
+
+impl Entity {
+    /// Find all revisions of a form, oldest first.
+    pub async fn find_by_form<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Self::find()
+            .filter(Column::FormId.eq(form_id))
+            .order_by_asc(Column::Revision)
+            .all(db)
+            .await
+    }
+
+    /// Find the latest revision of a form.
+    pub async fn find_latest<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+    ) -> Result<Option<Model>, DbErr> {
+        Self::find()
+            .filter(Column::FormId.eq(form_id))
+            .order_by_desc(Column::Revision)
+            .one(db)
+            .await
+    }
+
+    /// Records a new revision with the next sequential number.
+    pub async fn record<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        fields: &[RevisionField],
+        created_at: DateTimeWithTimeZone,
+    ) -> Result<Model, DbErr> {
+        let next = Self::find_latest(db, form_id)
+            .await?
+            .map_or(1, |latest| latest.revision + 1);
+
+        let model = ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            form_id: ActiveValue::Set(form_id),
+            revision: ActiveValue::Set(next),
+            fields: ActiveValue::Set(serde_json::to_value(fields).unwrap_or_default()),
+            created_at: ActiveValue::Set(created_at),
+        };
+
+        model.insert(db).await
+    }
+}
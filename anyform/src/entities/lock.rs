@@ -0,0 +1,27 @@
+//! Lock entity (the SQLite fallback for [`LockService`]).
+//!
+//! [`LockService`]: crate::services::LockService
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "af_locks")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub name: String,
+
+    /// Identifies the holder, so that only it releases the lock.
+    pub holder: Uuid,
+
+    pub acquired_at: DateTimeWithTimeZone,
+
+    /// When the lock lapses if its holder never releases it (e.g. because
+    /// it crashed).
+    pub expires_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,13 +1,34 @@
 //! SeaORM entity definitions for anyform.
 
+pub mod crm_push;
+pub mod dead_letter;
+pub mod digest_delivery;
 pub mod field;
 pub mod field_option;
 pub mod form;
+pub mod form_instance;
+pub mod form_revision;
+pub mod lock;
 pub mod result;
+pub mod seed;
 pub mod step;
 pub mod submission;
+pub mod submission_note;
 
 pub mod prelude {
+    pub use super::crm_push::{
+        ActiveModel as CrmPushActiveModel, Column as CrmPushColumn, Entity as CrmPushEntity,
+        Model as CrmPush, Relation as CrmPushRelation,
+    };
+    pub use super::dead_letter::{
+        ActiveModel as DeadLetterActiveModel, Column as DeadLetterColumn,
+        Entity as DeadLetterEntity, Model as DeadLetter, Relation as DeadLetterRelation,
+    };
+    pub use super::digest_delivery::{
+        ActiveModel as DigestDeliveryActiveModel, Column as DigestDeliveryColumn,
+        Entity as DigestDeliveryEntity, Model as DigestDelivery,
+        Relation as DigestDeliveryRelation,
+    };
     pub use super::field::{
         ActiveModel as FieldActiveModel, Column as FieldColumn, Entity as FieldEntity,
         Model as Field, Relation as FieldRelation,
@@ -20,10 +41,26 @@ pub mod prelude {
         ActiveModel as FormActiveModel, Column as FormColumn, Entity as FormEntity, Model as Form,
         Relation as FormRelation,
     };
+    pub use super::form_instance::{
+        ActiveModel as FormInstanceActiveModel, Column as FormInstanceColumn,
+        Entity as FormInstanceEntity, Model as FormInstance, Relation as FormInstanceRelation,
+    };
+    pub use super::form_revision::{
+        ActiveModel as FormRevisionActiveModel, Column as FormRevisionColumn,
+        Entity as FormRevisionEntity, Model as FormRevision, Relation as FormRevisionRelation,
+    };
+    pub use super::lock::{
+        ActiveModel as LockActiveModel, Column as LockColumn, Entity as LockEntity,
+        Model as Lock, Relation as LockRelation,
+    };
     pub use super::result::{
         ActiveModel as ResultActiveModel, Column as ResultColumn, Entity as ResultEntity,
         Model as FormResult, Relation as ResultRelation,
     };
+    pub use super::seed::{
+        ActiveModel as SeedActiveModel, Column as SeedColumn, Entity as SeedEntity,
+        Model as Seed, Relation as SeedRelation,
+    };
     pub use super::step::{
         ActiveModel as StepActiveModel, Column as StepColumn, Entity as StepEntity, Model as Step,
         Relation as StepRelation,
@@ -32,4 +69,9 @@ pub mod prelude {
         ActiveModel as SubmissionActiveModel, Column as SubmissionColumn,
         Entity as SubmissionEntity, Model as Submission, Relation as SubmissionRelation,
     };
+    pub use super::submission_note::{
+        ActiveModel as SubmissionNoteActiveModel, Column as SubmissionNoteColumn,
+        Entity as SubmissionNoteEntity, Model as SubmissionNote,
+        Relation as SubmissionNoteRelation,
+    };
 }
@@ -1,9 +1,14 @@
-//! Result entity (for quiz result buckets).
+//! Result entity (outcomes a completed submission maps to).
+
+use std::collections::HashMap;
 
 use sea_orm::entity::prelude::*;
 use sea_orm::{QueryFilter, QueryOrder};
 use serde::{Deserialize, Serialize};
 
+use crate::condition::ConditionRule;
+use crate::schema::FieldValue;
+
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "af_results")]
 pub struct Model {
@@ -25,8 +30,18 @@ pub struct Model {
     /// Maximum score for this result bucket.
     pub max_score: Option<i32>,
 
+    /// Scoring category that must be among the submission's highest.
+    pub category: Option<String>,
+
+    /// Condition on the answers, a [`ConditionRule`] as JSON.
+    #[sea_orm(column_type = "Json", nullable)]
+    pub condition: Option<Json>,
+
     #[sea_orm(column_name = "order")]
     pub order: i32,
+
+    /// Set when the result's form is soft-deleted.
+    pub deleted_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -55,22 +70,63 @@ impl Model {
         let max_ok = self.max_score.map_or(true, |max| score <= max);
         min_ok && max_ok
     }
+
+    /// Returns the result's condition on the answers, if it has one.
+    #[must_use]
+    pub fn condition(&self) -> Option<ConditionRule> {
+        self.condition
+            .as_ref()
+            .and_then(|c| serde_json::from_value(c.clone()).ok())
+    }
+
+    /// Returns true if a submission with `score` (if scored), highest
+    /// scoring categories `top_categories` and answers `data` gets this
+    /// result.
+    ///
+    /// Results with a score range need a score within it, results with a
+    /// category need it among the top categories, and results with a
+    /// condition need answers satisfying it. Results with none of these
+    /// match any submission.
+    #[must_use]
+    pub fn matches(
+        &self,
+        score: Option<i32>,
+        top_categories: &[&str],
+        data: &HashMap<String, FieldValue>,
+    ) -> bool {
+        let has_range = self.min_score.is_some() || self.max_score.is_some();
+        if has_range && !score.is_some_and(|s| self.matches_score(s)) {
+            return false;
+        }
+        if let Some(category) = &self.category {
+            if !top_categories.contains(&category.as_str()) {
+                return false;
+            }
+        }
+        let Some(condition) = self.condition() else {
+            return true;
+        };
+        let answers: HashMap<String, serde_json::Value> =
+            data.iter().map(|(k, v)| (k.clone(), v.into())).collect();
+        condition.evaluate(&answers)
+    }
 }
 
 impl Entity {
-    /// Find all results for a form, ordered by position.
-    pub async fn find_by_form(
-        db: &DatabaseConnection,
+    /// Find all active results for a form, ordered by position.
+    pub async fn find_by_form<C: ConnectionTrait>(
+        db: &C,
         form_id: Uuid,
     ) -> Result<Vec<Model>, DbErr> {
         Self::find()
             .filter(Column::FormId.eq(form_id))
+            .filter(Column::DeletedAt.is_null())
             .order_by_asc(Column::Order)
             .all(db)
             .await
     }
 
-    /// Find a result by form and key.
+    /// Find an active result by form and key.
     pub async fn find_by_key(
         db: &DatabaseConnection,
         form_id: Uuid,
@@ -79,13 +135,14 @@ impl Entity {
         Self::find()
             .filter(Column::FormId.eq(form_id))
             .filter(Column::Key.eq(key))
+            .filter(Column::DeletedAt.is_null())
             .one(db)
             .await
     }
 
     /// Find the result that matches a given score.
-    pub async fn find_by_score(
-        db: &DatabaseConnection,
+    pub async fn find_by_score<C: ConnectionTrait>(
+        db: &C,
         form_id: Uuid,
         score: i32,
     ) -> Result<Option<Model>, DbErr> {
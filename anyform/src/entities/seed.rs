@@ -0,0 +1,23 @@
+//! Seed entity, recording forms written by [`Seeder`].
+//!
+//! [`Seeder`]: crate::seed::Seeder
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "af_seeds")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub slug: String,
+
+    /// SHA-256 of the seeded form definition, so unchanged seeds are skipped.
+    pub checksum: String,
+
+    pub seeded_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
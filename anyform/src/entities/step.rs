@@ -1,5 +1,6 @@
 //! Step entity.
 
+use crate::branching::JumpRule;
 use crate::condition::ConditionRule;
 use sea_orm::entity::prelude::*;
 use sea_orm::{QueryFilter, QueryOrder};
@@ -24,7 +25,21 @@ pub struct Model {
     #[sea_orm(column_type = "Json")]
     pub condition: Option<serde_json::Value>,
 
+    /// Jump rules as JSON, tried once the step is done.
+    #[sea_orm(column_type = "Json")]
+    pub next: Option<serde_json::Value>,
+
+    /// Slug of a form whose steps' fields this step includes, after its own.
+    pub include_form: Option<String>,
+
+    /// Namespace of the included fields' names; defaults to the included
+    /// form's slug. See [`Model::namespace`].
+    pub include_as: Option<String>,
+
     pub created_at: DateTimeWithTimeZone,
+
+    /// When the step's content last changed.
+    pub updated_at: DateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -63,18 +78,40 @@ impl Model {
     }
 
     /// Returns the condition rule for dynamic step visibility.
+    ///
+    /// Accepts the rule as JSON or as JSON text (as stored by `FormBuilder`).
     #[must_use]
     pub fn condition_rule(&self) -> Option<ConditionRule> {
-        self.condition
+        match self.condition.as_ref()? {
+            serde_json::Value::String(s) => serde_json::from_str(s).ok(),
+            v => serde_json::from_value(v.clone()).ok(),
+        }
+    }
+
+    /// Returns the step's own jump rules, in order.
+    #[must_use]
+    pub fn jump_rules(&self) -> Vec<JumpRule> {
+        self.next
             .as_ref()
             .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Returns the namespace of the fields this step includes, if it
+    /// includes a form: `include_as`, or else the included form's slug with
+    /// dashes turned to underscores. Included fields are named
+    /// `namespace.name`.
+    #[must_use]
+    pub fn namespace(&self) -> Option<String> {
+        let slug = self.include_form.as_deref()?;
+        Some(crate::fragments::namespace(slug, self.include_as.as_deref()))
     }
 }
 
 impl Entity {
     /// Find all steps for a form, ordered by position.
-    pub async fn find_by_form(
-        db: &DatabaseConnection,
+    pub async fn find_by_form<C: ConnectionTrait>(
+        db: &C,
         form_id: Uuid,
     ) -> Result<Vec<Model>, DbErr> {
         Self::find()
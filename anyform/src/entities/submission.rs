@@ -1,11 +1,12 @@
 //! Submission entity.
 
 use sea_orm::entity::prelude::*;
-use sea_orm::{ActiveModelTrait, ActiveValue, QueryFilter, QueryOrder};
+use sea_orm::{ActiveModelTrait, ActiveValue, Condition, PaginatorTrait, QueryFilter, QueryOrder};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::schema::FieldValue;
+use crate::analysis::TextAnalysis;
+use crate::schema::{FieldValue, FormSettings};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "af_submissions")]
@@ -15,6 +16,16 @@ pub struct Model {
 
     pub form_id: Uuid,
 
+    /// Form revision the submission was made against.
+    pub revision_id: Option<Uuid>,
+
+    /// Recurring form instance the submission belongs to.
+    pub instance_id: Option<Uuid>,
+
+    /// ID of the respondent who made the submission, when identified.
+    /// See [`crate::respondent`].
+    pub respondent_id: Option<String>,
+
     /// Submission data as JSON: { "field_id": value }
     #[sea_orm(column_type = "Json")]
     pub data: serde_json::Value,
@@ -35,10 +46,17 @@ pub struct Model {
 
     pub result_key: Option<String>,
 
+    /// Lifecycle status; see [`SubmissionStatus`].
+    pub status: String,
+
     pub created_at: DateTimeWithTimeZone,
 
     pub updated_at: DateTimeWithTimeZone,
 
+    /// Held for legal reasons: may not be deleted until the hold is lifted.
+    /// See [`crate::services::LegalHold`].
+    pub legal_hold: bool,
+
     pub deleted_at: Option<DateTimeWithTimeZone>,
 }
 
@@ -85,6 +103,32 @@ impl Model {
         self.deleted_at.is_some()
     }
 
+    /// Returns the submission's lifecycle status.
+    #[must_use]
+    pub fn status(&self) -> SubmissionStatus {
+        self.status.parse().unwrap_or_default()
+    }
+
+    /// Returns true if the submission was started step by step and not yet
+    /// completed.
+    #[must_use]
+    pub fn is_in_progress(&self) -> bool {
+        self.status() == SubmissionStatus::InProgress
+    }
+
+    /// Returns true if the submission is in progress with at least one
+    /// step saved: a partial response.
+    #[must_use]
+    pub fn is_partial(&self) -> bool {
+        self.is_in_progress() && self.data.as_object().is_some_and(|data| !data.is_empty())
+    }
+
+    /// Returns true if the submission is on the waitlist.
+    #[must_use]
+    pub fn is_waitlisted(&self) -> bool {
+        self.status() == SubmissionStatus::Waitlisted
+    }
+
     /// Returns the submission data as a map of field ID to value.
     #[must_use]
     pub fn data_map(&self) -> HashMap<String, FieldValue> {
@@ -112,6 +156,56 @@ impl Model {
     }
 }
 
+/// Lifecycle status of a submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    /// Accepted submission.
+    #[default]
+    Submitted,
+    /// Accepted onto the waitlist of a full form or option.
+    Waitlisted,
+    /// Made through a preview link; excluded from listings, counts and
+    /// analytics, and discarded in bulk.
+    Preview,
+    /// Started step by step and not yet completed; takes no place until
+    /// completed.
+    InProgress,
+}
+
+impl SubmissionStatus {
+    /// Returns the stored string value.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Submitted => "submitted",
+            Self::Waitlisted => "waitlisted",
+            Self::Preview => "preview",
+            Self::InProgress => "in_progress",
+        }
+    }
+}
+
+impl std::str::FromStr for SubmissionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "submitted" => Ok(Self::Submitted),
+            "waitlisted" => Ok(Self::Waitlisted),
+            "preview" => Ok(Self::Preview),
+            "in_progress" => Ok(Self::InProgress),
+            other => Err(format!("unknown submission status: {other}")),
+        }
+    }
+}
+
+impl std::fmt::Display for SubmissionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Metadata about a submission.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SubmissionMetadata {
@@ -135,13 +229,40 @@ pub struct SubmissionMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
 
+    /// Points earned per scoring category, for quizzes with categories.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub category_scores: BTreeMap<String, i32>,
+
+    /// What the router's text analyzer found in free-text answers, by
+    /// field name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub text_analysis: BTreeMap<String, TextAnalysis>,
+
     /// Additional custom metadata.
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
+impl SubmissionMetadata {
+    /// Returns the metadata that may be stored for a form with `settings`.
+    ///
+    /// Anonymous forms store nothing about the respondent: only the text
+    /// analysis, which describes the answers, is kept.
+    #[must_use]
+    pub fn for_settings(self, settings: &FormSettings) -> Option<Self> {
+        if settings.anonymous {
+            (!self.text_analysis.is_empty()).then(|| Self {
+                text_analysis: self.text_analysis,
+                ..Self::default()
+            })
+        } else {
+            Some(self)
+        }
+    }
+}
+
 impl Entity {
-    /// Find all submissions for a form.
+    /// Find all submissions for a form, excluding previews.
     pub async fn find_by_form(
         db: &DatabaseConnection,
         form_id: Uuid,
@@ -149,28 +270,134 @@ impl Entity {
         Self::find()
             .filter(Column::FormId.eq(form_id))
             .filter(Column::DeletedAt.is_null())
+            .filter(Column::Status.ne(SubmissionStatus::Preview.as_str()))
             .order_by_desc(Column::CreatedAt)
             .all(db)
             .await
     }
 
-    /// Find completed submissions for a form.
+    /// Find completed submissions for a form, excluding previews.
     pub async fn find_completed_by_form(
         db: &DatabaseConnection,
         form_id: Uuid,
     ) -> Result<Vec<Model>, DbErr> {
+        Self::completed_by_form(form_id).all(db).await
+    }
+
+    /// Query for completed submissions to a form, excluding previews, newest
+    /// first; ordered by ID among equal timestamps so it pages stably.
+    pub fn completed_by_form(form_id: Uuid) -> Select<Self> {
         Self::find()
             .filter(Column::FormId.eq(form_id))
             .filter(Column::DeletedAt.is_null())
+            .filter(Column::Status.ne(SubmissionStatus::Preview.as_str()))
             .filter(Column::CompletedAt.is_not_null())
             .order_by_desc(Column::CreatedAt)
+            .order_by_desc(Column::Id)
+    }
+
+    /// Query for the submissions to a form exports list: completed ones,
+    /// and in-progress ones with `include_partials`, ordered as
+    /// [`Self::completed_by_form`].
+    ///
+    /// In-progress submissions are listed whether or not a step was saved;
+    /// filter them with [`Model::is_partial`].
+    pub fn exported_by_form(form_id: Uuid, include_partials: bool) -> Select<Self> {
+        if !include_partials {
+            return Self::completed_by_form(form_id);
+        }
+        Self::find()
+            .filter(Column::FormId.eq(form_id))
+            .filter(Column::DeletedAt.is_null())
+            .filter(
+                Condition::any()
+                    .add(
+                        Condition::all()
+                            .add(Column::Status.ne(SubmissionStatus::Preview.as_str()))
+                            .add(Column::CompletedAt.is_not_null()),
+                    )
+                    .add(Column::Status.eq(SubmissionStatus::InProgress.as_str())),
+            )
+            .order_by_desc(Column::CreatedAt)
+            .order_by_desc(Column::Id)
+    }
+
+    /// Count accepted (active, not waitlisted, preview or in progress)
+    /// submissions for a form.
+    ///
+    /// Recurring forms count per instance.
+    pub async fn count_accepted<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        instance_id: Option<Uuid>,
+    ) -> Result<u64, DbErr> {
+        let mut query = Self::find().filter(Column::FormId.eq(form_id));
+        if let Some(instance_id) = instance_id {
+            query = query.filter(Column::InstanceId.eq(instance_id));
+        }
+
+        query
+            .filter(Column::DeletedAt.is_null())
+            .filter(Column::Status.is_not_in([
+                SubmissionStatus::Waitlisted.as_str(),
+                SubmissionStatus::Preview.as_str(),
+                SubmissionStatus::InProgress.as_str(),
+            ]))
+            .count(db)
+            .await
+    }
+
+    /// Returns true if `respondent_id` already responded to a form: made an
+    /// active submission to it that isn't a preview or still in progress.
+    pub async fn has_responded<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        respondent_id: &str,
+    ) -> Result<bool, DbErr> {
+        let count = Self::find()
+            .filter(Column::FormId.eq(form_id))
+            .filter(Column::RespondentId.eq(respondent_id))
+            .filter(Column::DeletedAt.is_null())
+            .filter(Column::Status.is_not_in([
+                SubmissionStatus::Preview.as_str(),
+                SubmissionStatus::InProgress.as_str(),
+            ]))
+            .count(db)
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// Find a form's preview submissions.
+    pub async fn find_previews(
+        db: &DatabaseConnection,
+        form_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Self::find()
+            .filter(Column::FormId.eq(form_id))
+            .filter(Column::DeletedAt.is_null())
+            .filter(Column::Status.eq(SubmissionStatus::Preview.as_str()))
+            .order_by_desc(Column::CreatedAt)
             .all(db)
             .await
     }
 
+    /// Permanently delete a form's preview submissions, except those under
+    /// legal hold.
+    ///
+    /// Returns the number of submissions discarded.
+    pub async fn delete_previews(db: &DatabaseConnection, form_id: Uuid) -> Result<u64, DbErr> {
+        let result = Self::delete_many()
+            .filter(Column::FormId.eq(form_id))
+            .filter(Column::Status.eq(SubmissionStatus::Preview.as_str()))
+            .filter(Column::LegalHold.eq(false))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
     /// Find a submission by ID (active only).
-    pub async fn find_active_by_id(
-        db: &DatabaseConnection,
+    pub async fn find_active_by_id<C: ConnectionTrait>(
+        db: &C,
         id: Uuid,
     ) -> Result<Option<Model>, DbErr> {
         Self::find_by_id(id)
@@ -180,7 +407,7 @@ impl Entity {
     }
 
     /// Soft-delete a submission.
-    pub async fn soft_delete(db: &DatabaseConnection, id: Uuid) -> Result<(), DbErr> {
+    pub async fn soft_delete<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<(), DbErr> {
         let submission = Self::find_active_by_id(db, id).await?;
 
         if let Some(sub) = submission {
@@ -189,6 +416,9 @@ impl Entity {
             let model = ActiveModel {
                 id: ActiveValue::Unchanged(sub.id),
                 form_id: ActiveValue::Unchanged(sub.form_id),
+                revision_id: ActiveValue::Unchanged(sub.revision_id),
+                instance_id: ActiveValue::Unchanged(sub.instance_id),
+                respondent_id: ActiveValue::Unchanged(sub.respondent_id),
                 data: ActiveValue::Unchanged(sub.data),
                 metadata: ActiveValue::Unchanged(sub.metadata),
                 current_step_id: ActiveValue::Unchanged(sub.current_step_id),
@@ -196,8 +426,10 @@ impl Entity {
                 score: ActiveValue::Unchanged(sub.score),
                 max_score: ActiveValue::Unchanged(sub.max_score),
                 result_key: ActiveValue::Unchanged(sub.result_key),
+                status: ActiveValue::Unchanged(sub.status),
                 created_at: ActiveValue::Unchanged(sub.created_at),
                 updated_at: ActiveValue::Set(now),
+                legal_hold: ActiveValue::Unchanged(sub.legal_hold),
                 deleted_at: ActiveValue::Set(Some(now)),
             };
 
@@ -0,0 +1,138 @@
+//! Submission note entity (admin notes and labels).
+
+use std::collections::HashMap;
+
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveModelTrait, ActiveValue, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "af_submission_notes")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+
+    pub submission_id: Uuid,
+
+    /// Free-text note.
+    pub body: Option<String>,
+
+    /// Labels as JSON: ["urgent", ...]
+    #[sea_orm(column_type = "Json")]
+    pub labels: serde_json::Value,
+
+    /// Who wrote the note, if known.
+    pub author: Option<String>,
+
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::submission::Entity",
+        from = "Column::SubmissionId",
+        to = "super::submission::Column::Id"
+    )]
+    Submission,
+}
+
+impl Related<super::submission::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Submission.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Returns the note's labels.
+    #[must_use]
+    pub fn labels(&self) -> Vec<String> {
+        serde_json::from_value(self.labels.clone()).unwrap_or_default()
+    }
+}
+
+/// Normalizes labels: trimmed, lowercased, non-empty and deduplicated.
+#[must_use]
+pub fn normalize_labels<I, S>(labels: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut out: Vec<String> = Vec::new();
+    for label in labels {
+        let label = label.as_ref().trim().to_lowercase();
+        if !label.is_empty() && !out.contains(&label) {
+            out.push(label);
+        }
+    }
+    out
+}
+
+impl Entity {
+    /// Find all notes on a submission, oldest first.
+    pub async fn find_by_submission(
+        db: &DatabaseConnection,
+        submission_id: Uuid,
+    ) -> Result<Vec<Model>, DbErr> {
+        Self::find()
+            .filter(Column::SubmissionId.eq(submission_id))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Returns the union of labels for each of the given submissions.
+    ///
+    /// Submissions without labels are absent from the map.
+    pub async fn labels_by_submission(
+        db: &DatabaseConnection,
+        submission_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<String>>, DbErr> {
+        let mut labels: HashMap<Uuid, Vec<String>> = HashMap::new();
+        if submission_ids.is_empty() {
+            return Ok(labels);
+        }
+
+        let notes = Self::find()
+            .filter(Column::SubmissionId.is_in(submission_ids.iter().copied()))
+            .order_by_asc(Column::CreatedAt)
+            .all(db)
+            .await?;
+
+        for note in notes {
+            let entry = labels.entry(note.submission_id).or_default();
+            for label in note.labels() {
+                if !entry.contains(&label) {
+                    entry.push(label);
+                }
+            }
+        }
+        labels.retain(|_, v| !v.is_empty());
+
+        Ok(labels)
+    }
+
+    /// Adds a note to a submission.
+    ///
+    /// Labels are normalized with [`normalize_labels`].
+    pub async fn add(
+        db: &DatabaseConnection,
+        submission_id: Uuid,
+        body: Option<String>,
+        labels: &[String],
+        author: Option<String>,
+    ) -> Result<Model, DbErr> {
+        let model = ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            submission_id: ActiveValue::Set(submission_id),
+            body: ActiveValue::Set(body),
+            labels: ActiveValue::Set(serde_json::json!(normalize_labels(labels))),
+            author: ActiveValue::Set(author),
+            created_at: ActiveValue::Set(chrono::Utc::now().fixed_offset()),
+        };
+
+        model.insert(db).await
+    }
+}
@@ -0,0 +1,235 @@
+//! Opting out of the response envelope.
+//!
+//! The public JSON routes wrap their answers in [`ApiResponse`]'s
+//! `{success, status, data, request_id}` envelope. In raw mode they return
+//! the `data` object itself instead, with the request ID (the client's
+//! `X-Request-ID`, when it sent one) in the `X-Request-ID` header, and
+//! errors as `application/problem+json`
+//! ([RFC 9457](https://www.rfc-editor.org/rfc/rfc9457)) [`Problem`]s:
+//!
+//! ```json
+//! {
+//!   "type": "about:blank",
+//!   "title": "Not Found",
+//!   "status": 404,
+//!   "detail": "Form not found: contact",
+//!   "code": "FORM_NOT_FOUND",
+//!   "request_id": "uuid"
+//! }
+//! ```
+//!
+//! The envelope stays the default. Set
+//! [`AnyFormRouterBuilder::response_format`](crate::AnyFormRouterBuilder::response_format)
+//! to [`ResponseFormat::Raw`] to change it, or ask per request with an
+//! `Accept` profile: `Accept: application/json; profile="raw"` (or
+//! `profile="envelope"` for the envelope whatever the default). Responses
+//! that could go either way carry `Vary: Accept`. Admin routes always use
+//! the envelope.
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use serde::{Deserialize, Serialize};
+
+use crate::response::{ApiResponse, Enveloped};
+
+/// Media type of [`Problem`] bodies.
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+/// Response header carrying the request ID in raw mode.
+const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// How the public JSON routes shape their responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResponseFormat {
+    /// Wrapped in [`ApiResponse`]'s envelope.
+    #[default]
+    Envelope,
+    /// The data alone, with errors as [`Problem`]s.
+    Raw,
+}
+
+impl ResponseFormat {
+    /// Reads the format asked for by an `Accept` profile.
+    ///
+    /// Returns `None` when the client expressed no preference.
+    #[must_use]
+    pub fn negotiate(headers: &HeaderMap) -> Option<Self> {
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        accept
+            .split(',')
+            .flat_map(|media_type| media_type.split(';').skip(1))
+            .filter_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case("profile")
+                    .then(|| value.trim().trim_matches('"'))
+            })
+            .flat_map(str::split_whitespace)
+            .find_map(|profile| match profile {
+                "raw" => Some(Self::Raw),
+                "envelope" => Some(Self::Envelope),
+                _ => None,
+            })
+    }
+}
+
+/// An error in raw mode, as `application/problem+json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    /// Always `about:blank`; `code` tells errors apart.
+    #[serde(rename = "type")]
+    pub kind: String,
+
+    /// The status's reason phrase, e.g. `Not Found`.
+    pub title: String,
+
+    /// HTTP status code.
+    pub status: u16,
+
+    /// Human-readable error message.
+    pub detail: String,
+
+    /// Error code, as in the envelope's `error.code`.
+    pub code: String,
+
+    /// Additional error details (e.g. validation errors by field).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+
+    /// Seconds to wait before retrying, also sent as `Retry-After`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
+
+    /// Request ID for tracking/debugging.
+    pub request_id: String,
+}
+
+impl<T: serde::Serialize> From<ApiResponse<T>> for Problem {
+    fn from(response: ApiResponse<T>) -> Self {
+        let status = StatusCode::from_u16(response.status)
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let error = response.error;
+        Self {
+            kind: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail: error.as_ref().map(|e| e.message.clone()).unwrap_or_default(),
+            code: error.as_ref().map(|e| e.code.clone()).unwrap_or_default(),
+            details: error.as_ref().and_then(|e| e.details.clone()),
+            retry_after: error.as_ref().and_then(|e| e.retry_after),
+            request_id: response.request_id,
+        }
+    }
+}
+
+/// Serves enveloped responses as `default`, or as the request's `Accept`
+/// profile asks.
+pub(crate) async fn negotiate_format(
+    default: ResponseFormat,
+    req: Request,
+    next: Next,
+) -> Response {
+    if req.uri().path().starts_with("/admin/") {
+        return next.run(req).await;
+    }
+
+    let format = ResponseFormat::negotiate(req.headers()).unwrap_or(default);
+    let request_id = req
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty())
+        .map(String::from);
+    let mut response = next.run(req).await;
+    if response.extensions().get::<Enveloped>().is_none() {
+        return response;
+    }
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept"));
+    if format == ResponseFormat::Envelope {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    // `HEAD` answers have no body to unwrap
+    let Ok(mut envelope) = serde_json::from_slice::<ApiResponse<serde_json::Value>>(&bytes)
+    else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    // Errors are built without the request, so they carry a fresh ID
+    if let Some(id) = request_id {
+        envelope.request_id = id;
+    }
+
+    if let Ok(id) = HeaderValue::from_str(&envelope.request_id) {
+        parts.headers.insert(REQUEST_ID_HEADER, id);
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    let body = if envelope.success {
+        serde_json::to_vec(&envelope.data.unwrap_or_default())
+    } else {
+        parts.headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(PROBLEM_CONTENT_TYPE),
+        );
+        serde_json::to_vec(&Problem::from(envelope))
+    };
+    Response::from_parts(parts, Body::from(body.unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_negotiate_from_accept_profile() {
+        let raw = accept("application/json; profile=\"raw\"");
+        assert_eq!(ResponseFormat::negotiate(&raw), Some(ResponseFormat::Raw));
+
+        let envelope = accept("text/html, application/vnd.anyform.v1+json;profile=envelope");
+        assert_eq!(ResponseFormat::negotiate(&envelope), Some(ResponseFormat::Envelope));
+
+        let listed = accept("application/json;q=0.9;profile=\"compact raw\"");
+        assert_eq!(ResponseFormat::negotiate(&listed), Some(ResponseFormat::Raw));
+    }
+
+    #[test]
+    fn test_negotiate_without_preference() {
+        assert_eq!(ResponseFormat::negotiate(&HeaderMap::new()), None);
+        assert_eq!(ResponseFormat::negotiate(&accept("application/json")), None);
+        let other = accept("application/json; profile=\"https://example.com/p\"");
+        assert_eq!(ResponseFormat::negotiate(&other), None);
+    }
+
+    #[test]
+    fn test_problems_carry_the_error() {
+        let response = ApiResponse::<()>::not_found("Form not found: contact")
+            .with_request_id("req-1");
+        let problem = Problem::from(response);
+        assert_eq!(problem.kind, "about:blank");
+        assert_eq!(problem.title, "Not Found");
+        assert_eq!(problem.status, 404);
+        assert_eq!(problem.detail, "Form not found: contact");
+        assert_eq!(problem.code, "NOT_FOUND");
+        assert_eq!(problem.request_id, "req-1");
+    }
+}
@@ -25,6 +25,12 @@ pub enum FormError {
     #[error("Database error: {0}")]
     Database(String),
 
+    #[error("Database is busy: {0}")]
+    DatabaseBusy(String),
+
+    #[error("This server is read-only; try again later")]
+    ReadOnly,
+
     #[error("Invalid field type: {0}")]
     InvalidFieldType(String),
 
@@ -37,11 +43,80 @@ pub enum FormError {
     #[error("Invalid form data: {0}")]
     InvalidData(String),
 
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Payload too complex: {0}")]
+    PayloadTooComplex(String),
+
     #[error("Form is deleted")]
     FormDeleted,
 
+    #[error("A form with slug '{0}' already exists")]
+    DuplicateSlug(String),
+
     #[error("Submission not found: {0}")]
     SubmissionNotFound(String),
+
+    #[error("Dead letter not found: {0}")]
+    DeadLetterNotFound(String),
+
+    #[error("Digest recipient not found: {0}")]
+    DigestRecipientNotFound(String),
+
+    #[error("Unsupported API version: {0}")]
+    UnsupportedApiVersion(String),
+
+    #[error("Option '{option}' of field '{field}' is full")]
+    OptionFull { field: String, option: String },
+
+    #[error("Form has reached its submission limit")]
+    SubmissionLimitReached,
+
+    #[error("No instance of this recurring form is open")]
+    InstanceClosed,
+
+    #[error("Form is closed")]
+    FormClosed,
+
+    #[error("Form is not open yet")]
+    FormNotOpen,
+
+    #[error("This form requires an identified respondent")]
+    RespondentRequired,
+
+    #[error("Invalid or expired preview token")]
+    InvalidPreviewToken,
+
+    #[error("Authentication required")]
+    Unauthorized,
+
+    #[error("Not allowed to perform this action")]
+    Forbidden,
+
+    #[error("Under legal hold: {0}")]
+    LegalHold(String),
+
+    #[error("Too many submissions; try again in {retry_after}s")]
+    RateLimited { retry_after: u64 },
+
+    #[error("Submission rejected as spam")]
+    SpamRejected,
+
+    #[error("CAPTCHA verification failed")]
+    CaptchaFailed,
+
+    #[error("This browser has already responded to the form")]
+    AlreadyResponded,
+
+    #[error("Webhook delivery failed: {0}")]
+    WebhookFailed(String),
+
+    #[error("Preflight check failed: {0}")]
+    PreflightFailed(String),
+
+    #[error("Export failed: {0}")]
+    Export(String),
 }
 
 impl FormError {
@@ -52,14 +127,37 @@ impl FormError {
             Self::NotFound(_)
             | Self::StepNotFound(_)
             | Self::FieldNotFound(_)
-            | Self::SubmissionNotFound(_) => StatusCode::NOT_FOUND,
+            | Self::SubmissionNotFound(_)
+            | Self::DeadLetterNotFound(_)
+            | Self::DigestRecipientNotFound(_) => StatusCode::NOT_FOUND,
             Self::ValidationFailed(_)
             | Self::StepValidationFailed(_)
             | Self::InvalidFieldType(_)
-            | Self::InvalidData(_) => StatusCode::BAD_REQUEST,
+            | Self::InvalidData(_)
+            | Self::SpamRejected
+            | Self::CaptchaFailed => StatusCode::BAD_REQUEST,
             Self::FormDeleted => StatusCode::GONE,
-            Self::Database(_) | Self::ConditionError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Database(_)
+            | Self::ConditionError(_)
+            | Self::PreflightFailed(_)
+            | Self::Export(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::DatabaseBusy(_) | Self::ReadOnly => StatusCode::SERVICE_UNAVAILABLE,
             Self::FileUpload(_) => StatusCode::BAD_REQUEST,
+            Self::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::PayloadTooComplex(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::UnsupportedApiVersion(_) => StatusCode::NOT_ACCEPTABLE,
+            Self::RespondentRequired | Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::InvalidPreviewToken | Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::WebhookFailed(_) => StatusCode::BAD_GATEWAY,
+            Self::OptionFull { .. }
+            | Self::DuplicateSlug(_)
+            | Self::SubmissionLimitReached
+            | Self::InstanceClosed
+            | Self::FormClosed
+            | Self::FormNotOpen
+            | Self::AlreadyResponded
+            | Self::LegalHold(_) => StatusCode::CONFLICT,
         }
     }
 
@@ -72,12 +170,37 @@ impl FormError {
             Self::FieldNotFound(_) => "FIELD_NOT_FOUND",
             Self::ValidationFailed(_) | Self::StepValidationFailed(_) => "VALIDATION_FAILED",
             Self::Database(_) => "DATABASE_ERROR",
+            Self::DatabaseBusy(_) => "DATABASE_BUSY",
+            Self::ReadOnly => "READ_ONLY",
             Self::InvalidFieldType(_) => "INVALID_FIELD_TYPE",
             Self::ConditionError(_) => "CONDITION_ERROR",
             Self::FileUpload(_) => "FILE_UPLOAD_ERROR",
             Self::InvalidData(_) => "INVALID_DATA",
+            Self::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+            Self::PayloadTooComplex(_) => "PAYLOAD_TOO_COMPLEX",
             Self::FormDeleted => "FORM_DELETED",
+            Self::DuplicateSlug(_) => "DUPLICATE_SLUG",
             Self::SubmissionNotFound(_) => "SUBMISSION_NOT_FOUND",
+            Self::DeadLetterNotFound(_) => "DEAD_LETTER_NOT_FOUND",
+            Self::DigestRecipientNotFound(_) => "DIGEST_RECIPIENT_NOT_FOUND",
+            Self::UnsupportedApiVersion(_) => "UNSUPPORTED_API_VERSION",
+            Self::OptionFull { .. } => "OPTION_FULL",
+            Self::SubmissionLimitReached => "SUBMISSION_LIMIT_REACHED",
+            Self::InstanceClosed => "INSTANCE_CLOSED",
+            Self::FormClosed => "FORM_CLOSED",
+            Self::FormNotOpen => "FORM_NOT_OPEN",
+            Self::RespondentRequired => "RESPONDENT_REQUIRED",
+            Self::InvalidPreviewToken => "INVALID_PREVIEW_TOKEN",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::Forbidden => "FORBIDDEN",
+            Self::LegalHold(_) => "LEGAL_HOLD",
+            Self::RateLimited { .. } => "RATE_LIMITED",
+            Self::SpamRejected => "SPAM_REJECTED",
+            Self::CaptchaFailed => "CAPTCHA_FAILED",
+            Self::AlreadyResponded => "ALREADY_RESPONDED",
+            Self::WebhookFailed(_) => "WEBHOOK_FAILED",
+            Self::PreflightFailed(_) => "PREFLIGHT_FAILED",
+            Self::Export(_) => "EXPORT_FAILED",
         }
     }
 }
@@ -98,6 +221,12 @@ impl From<FormError> for crate::response::ApiResponse<()> {
             FormError::StepValidationFailed(errors) => {
                 crate::response::ApiResponse::step_validation_failed(errors.clone())
             }
+            FormError::RateLimited { retry_after } => crate::response::ApiResponse::error(
+                err.error_code(),
+                err.to_string(),
+                err.status_code(),
+            )
+            .with_retry_after(*retry_after),
             _ => crate::response::ApiResponse::error(
                 err.error_code(),
                 err.to_string(),
@@ -109,10 +238,19 @@ impl From<FormError> for crate::response::ApiResponse<()> {
 
 impl From<sea_orm::DbErr> for FormError {
     fn from(err: sea_orm::DbErr) -> Self {
+        if crate::database::is_busy(&err) {
+            return Self::DatabaseBusy(err.to_string());
+        }
         Self::Database(err.to_string())
     }
 }
 
+impl From<std::io::Error> for FormError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Export(err.to_string())
+    }
+}
+
 impl From<evalexpr::EvalexprError> for FormError {
     fn from(err: evalexpr::EvalexprError) -> Self {
         Self::ConditionError(err.to_string())
@@ -0,0 +1,88 @@
+//! Hooks for reacting to submission and form events.
+//!
+//! Implement [`FormEvents`] and register it with
+//! [`AnyFormRouterBuilder::events`](crate::AnyFormRouterBuilder::events) to
+//! send notifications, confirmation emails or webhooks from the built-in
+//! handlers. Every method has a no-op default, so implementations only
+//! override the events they care about.
+//!
+//! ```rust,ignore
+//! use anyform::events::FormEvents;
+//! use anyform::{Form, Submission};
+//!
+//! struct Notifier;
+//!
+//! #[async_trait::async_trait]
+//! impl FormEvents for Notifier {
+//!     async fn on_submission_waitlisted(&self, form: &Form, submission: &Submission) {
+//!         for email in form.settings().notify_emails {
+//!             // tell admins someone joined the waitlist
+//!         }
+//!     }
+//! }
+//! ```
+
+use async_trait::async_trait;
+
+use crate::entities::{form, submission};
+use crate::services::ClosedReason;
+
+/// Receives submission and form events from the built-in handlers.
+///
+/// Hooks run after the change has been committed; they cannot reject it.
+/// Handlers await them before responding, so hand slow work (e.g. posting
+/// to Slack or a CRM) to a spawned task or a queue.
+#[async_trait]
+pub trait FormEvents: Send + Sync + 'static {
+    /// A submission was accepted.
+    async fn on_submission_created(&self, _form: &form::Model, _submission: &submission::Model) {}
+
+    /// A submission was accepted onto the waitlist of a full form or option.
+    ///
+    /// Use this to notify admins (e.g. `form.settings().notify_emails`).
+    async fn on_submission_waitlisted(
+        &self,
+        _form: &form::Model,
+        _submission: &submission::Model,
+    ) {
+    }
+
+    /// A waitlisted submission was promoted by an admin.
+    ///
+    /// Use this to send the respondent a confirmation.
+    async fn on_submission_promoted(&self, _form: &form::Model, _submission: &submission::Model) {
+    }
+
+    /// A submission was deleted by an admin.
+    async fn on_submission_deleted(&self, _form: &form::Model, _submission: &submission::Model) {}
+
+    /// A form was created by an admin, through the admin API, GraphQL or a
+    /// sync.
+    async fn on_form_created(&self, _form: &form::Model) {}
+
+    /// A form's definition (its settings, steps or fields) was updated by
+    /// an admin, through the admin API, GraphQL or a sync.
+    async fn on_form_updated(&self, _form: &form::Model) {}
+
+    /// A draft or archived form was published by an admin.
+    async fn on_form_published(&self, _form: &form::Model) {}
+
+    /// A form was archived by an admin.
+    async fn on_form_archived(&self, _form: &form::Model) {}
+
+    /// A form started taking submissions, e.g. it was published, reached
+    /// its `opens_at`, or a deleted submission freed a place under its
+    /// quota. See [`LifecycleService`](crate::services::LifecycleService).
+    async fn on_form_opened(&self, _form: &form::Model) {}
+
+    /// A form stopped taking submissions, for `reason`: e.g. it reached a
+    /// quota or its `closes_at`, or was archived. See
+    /// [`LifecycleService`](crate::services::LifecycleService).
+    async fn on_form_closed(&self, _form: &form::Model, _reason: ClosedReason) {}
+}
+
+impl std::fmt::Debug for dyn FormEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FormEvents")
+    }
+}
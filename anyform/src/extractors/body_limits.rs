@@ -0,0 +1,223 @@
+//! Size and shape limits on submission bodies.
+//!
+//! [`FormSubmission`](super::FormSubmission) checks a body against its
+//! [`BodyLimits`] before parsing it, so a pathological payload is turned away
+//! after one pass over its bytes rather than deserialized. Bodies larger than
+//! [`BodyLimits::max_bytes`], or holding a string longer than
+//! [`BodyLimits::max_string_len`], get `413 PAYLOAD_TOO_LARGE`; JSON nested
+//! deeper than [`BodyLimits::max_depth`], or with an array or key count over
+//! its limit, gets `422 PAYLOAD_TOO_COMPLEX`.
+//!
+//! Limits are set for the whole router with
+//! [`AnyFormRouterBuilder::body_limits`](crate::AnyFormRouterBuilder::body_limits):
+//!
+//! ```
+//! use anyform::BodyLimits;
+//!
+//! // Long-form essays, but no deeper than a repeatable group
+//! let limits = BodyLimits::new().max_string_len(256 * 1024).max_depth(4);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::FormError;
+
+/// Limits on the bodies [`FormSubmission`](super::FormSubmission) accepts.
+///
+/// The depth and array limits only apply to JSON; the byte, key and string
+/// limits apply to every content type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BodyLimits {
+    /// Most bytes in a body. Defaults to 1 MiB.
+    pub max_bytes: usize,
+    /// Deepest nesting of JSON objects and arrays, counting the top-level
+    /// object as 1. Defaults to 8.
+    pub max_depth: usize,
+    /// Most entries in one JSON array. Defaults to 1000.
+    pub max_array_len: usize,
+    /// Most keys in the whole body, counting those of repeatable group
+    /// rows. Defaults to 1000.
+    pub max_keys: usize,
+    /// Most bytes in one key or value. Defaults to 64 KiB.
+    pub max_string_len: usize,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 1024 * 1024,
+            max_depth: 8,
+            max_array_len: 1000,
+            max_keys: 1000,
+            max_string_len: 64 * 1024,
+        }
+    }
+}
+
+impl BodyLimits {
+    /// Creates the default limits.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the most bytes in a body.
+    #[must_use]
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    /// Sets the deepest JSON nesting.
+    #[must_use]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets the most entries in one JSON array.
+    #[must_use]
+    pub fn max_array_len(mut self, len: usize) -> Self {
+        self.max_array_len = len;
+        self
+    }
+
+    /// Sets the most keys in a body.
+    #[must_use]
+    pub fn max_keys(mut self, keys: usize) -> Self {
+        self.max_keys = keys;
+        self
+    }
+
+    /// Sets the most bytes in one key or value.
+    #[must_use]
+    pub fn max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = len;
+        self
+    }
+
+    /// The error for a body over [`max_bytes`](Self::max_bytes).
+    pub(crate) fn too_large(&self) -> FormError {
+        FormError::PayloadTooLarge(format!("body exceeds {} bytes", self.max_bytes))
+    }
+
+    /// Checks that a body of `len` bytes fits.
+    pub(crate) fn check_bytes(&self, len: usize) -> Result<(), FormError> {
+        if len > self.max_bytes {
+            return Err(self.too_large());
+        }
+        Ok(())
+    }
+
+    /// Checks that a key or value of `len` bytes fits.
+    pub(crate) fn check_string(&self, len: usize) -> Result<(), FormError> {
+        if len > self.max_string_len {
+            return Err(FormError::PayloadTooLarge(format!(
+                "a string exceeds {} bytes",
+                self.max_string_len
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that a body holding `keys` keys fits.
+    pub(crate) fn check_keys(&self, keys: usize) -> Result<(), FormError> {
+        if keys > self.max_keys {
+            return Err(FormError::PayloadTooComplex(format!(
+                "more than {} keys",
+                self.max_keys
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks a JSON body against the limits without parsing it.
+    ///
+    /// Scans the bytes once, tracking nesting, array entries, keys and
+    /// string lengths. Malformed JSON is left for the parser to reject.
+    pub(crate) fn check_json(&self, bytes: &[u8]) -> Result<(), FormError> {
+        self.check_bytes(bytes.len())?;
+
+        // Open containers: whether each is an array, and its entries so far
+        let mut open: Vec<(bool, usize)> = Vec::new();
+        let mut keys = 0;
+        // Whether the next token starts an array entry
+        let mut entry_next = false;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let byte = bytes[i];
+            if byte.is_ascii_whitespace() {
+                i += 1;
+                continue;
+            }
+
+            if entry_next && byte != b']' {
+                if let Some((true, entries)) = open.last_mut() {
+                    *entries += 1;
+                    if *entries > self.max_array_len {
+                        return Err(FormError::PayloadTooComplex(format!(
+                            "an array has more than {} entries",
+                            self.max_array_len
+                        )));
+                    }
+                }
+            }
+            entry_next = false;
+
+            match byte {
+                b'{' | b'[' => {
+                    open.push((byte == b'[', 0));
+                    if open.len() > self.max_depth {
+                        return Err(FormError::PayloadTooComplex(format!(
+                            "nested deeper than {}",
+                            self.max_depth
+                        )));
+                    }
+                    entry_next = byte == b'[';
+                }
+                b'}' | b']' => {
+                    open.pop();
+                }
+                b',' => entry_next = matches!(open.last(), Some((true, _))),
+                b':' => {
+                    keys += 1;
+                    self.check_keys(keys)?;
+                }
+                b'"' => {
+                    let start = i + 1;
+                    i = start;
+                    while i < bytes.len() && bytes[i] != b'"' {
+                        i += if bytes[i] == b'\\' { 2 } else { 1 };
+                    }
+                    self.check_string(i.min(bytes.len()) - start)?;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_counts_array_entries_not_commas_in_strings() {
+        let limits = BodyLimits::new().max_array_len(2);
+        assert!(limits
+            .check_json(br#"{"a": ["x, y", "z"], "b": []}"#)
+            .is_ok());
+        assert!(limits.check_json(br#"{"a": [1, [2, 3], 4]}"#).is_err());
+        assert!(limits.check_json(br#"{"a": [[1, 2], [3, 4]]}"#).is_ok());
+    }
+
+    #[test]
+    fn test_scan_skips_escaped_quotes() {
+        let limits = BodyLimits::new().max_keys(1);
+        assert!(limits.check_json(br#"{"a": "say \"b\": c"}"#).is_ok());
+        assert!(limits.check_json(br#"{"a": "\\", "b": 1}"#).is_err());
+    }
+}
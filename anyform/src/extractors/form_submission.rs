@@ -1,11 +1,14 @@
 //! Form submission extractor.
 
 use axum::extract::{FromRequest, Request};
-use axum::http::header::CONTENT_TYPE;
-use std::collections::HashMap;
+use axum::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use futures::StreamExt;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::error::FormError;
-use crate::schema::FieldValue;
+use crate::schema::{FieldValue, GeoLocation};
+
+use super::BodyLimits;
 
 /// Extractor for form submission data.
 ///
@@ -14,6 +17,9 @@ use crate::schema::FieldValue;
 /// - `multipart/form-data`
 /// - `application/json`
 ///
+/// Bodies are checked against the [`BodyLimits`] the router sets (the
+/// defaults otherwise) before they're parsed.
+///
 /// # Example
 ///
 /// ```rust,ignore
@@ -36,6 +42,11 @@ where
     type Rejection = FormError;
 
     async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let limits = req
+            .extensions()
+            .get::<BodyLimits>()
+            .copied()
+            .unwrap_or_default();
         let content_type = req
             .headers()
             .get(CONTENT_TYPE)
@@ -44,9 +55,8 @@ where
 
         if content_type.starts_with("application/json") {
             // Parse as JSON
-            let bytes = axum::body::Bytes::from_request(req, state)
-                .await
-                .map_err(|e| FormError::InvalidData(e.to_string()))?;
+            let bytes = read_body(req, &limits).await?;
+            limits.check_json(&bytes)?;
 
             let data: HashMap<String, FieldValue> = serde_json::from_slice(&bytes)
                 .map_err(|e| FormError::InvalidData(e.to_string()))?;
@@ -58,53 +68,66 @@ where
                 .await
                 .map_err(|e| FormError::InvalidData(e.to_string()))?;
 
-            let data = parse_multipart(multipart).await?;
+            let data = parse_multipart(multipart, &limits).await?;
             Ok(Self(data))
         } else {
             // Default to form-urlencoded
-            let bytes = axum::body::Bytes::from_request(req, state)
-                .await
-                .map_err(|e| FormError::InvalidData(e.to_string()))?;
+            let bytes = read_body(req, &limits).await?;
 
-            let data = parse_urlencoded(&bytes)?;
+            let data = parse_urlencoded(&bytes, &limits)?;
             Ok(Self(data))
         }
     }
 }
 
-/// Parses URL-encoded form data.
-fn parse_urlencoded(bytes: &[u8]) -> Result<HashMap<String, FieldValue>, FormError> {
-    let mut data = HashMap::new();
+/// Reads the request body, up to `limits.max_bytes`.
+async fn read_body(req: Request, limits: &BodyLimits) -> Result<axum::body::Bytes, FormError> {
+    if let Some(len) = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+    {
+        limits.check_bytes(len)?;
+    }
 
-    for (key, value) in form_urlencoded::parse(bytes) {
-        let key = key.into_owned();
-        let value = value.into_owned();
+    // Bodies without a length, or longer than they claim, are cut off
+    let mut body = req.into_body().into_data_stream();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(|e| FormError::InvalidData(e.to_string()))?;
+        if bytes.len() + chunk.len() > limits.max_bytes {
+            return Err(limits.too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes.into())
+}
 
-        // Handle array fields (e.g., "field[]" or "field[0]")
-        if key.ends_with("[]") || key.contains('[') {
-            let base_key = key.split('[').next().unwrap_or(&key).to_string();
+/// Parses URL-encoded form data.
+fn parse_urlencoded(
+    bytes: &[u8],
+    limits: &BodyLimits,
+) -> Result<HashMap<String, FieldValue>, FormError> {
+    let mut values = FormValues::default();
 
-            match data.get_mut(&base_key) {
-                Some(FieldValue::Array(arr)) => {
-                    arr.push(value);
-                }
-                _ => {
-                    data.insert(base_key, FieldValue::Array(vec![value]));
-                }
-            }
-        } else {
-            data.insert(key, FieldValue::from(value));
-        }
+    for (keys, (key, value)) in form_urlencoded::parse(bytes).enumerate() {
+        limits.check_keys(keys + 1)?;
+        limits.check_string(key.len().max(value.len()))?;
+        values.insert(key.into_owned(), value.into_owned());
     }
 
-    Ok(data)
+    Ok(values.finish())
 }
 
 /// Parses multipart form data.
 async fn parse_multipart(
     mut multipart: axum_extra::extract::Multipart,
+    limits: &BodyLimits,
 ) -> Result<HashMap<String, FieldValue>, FormError> {
-    let mut data = HashMap::new();
+    let mut values = FormValues::default();
+    let mut keys = 0;
+    let mut bytes = 0;
 
     while let Some(field) = multipart
         .next_field()
@@ -116,6 +139,9 @@ async fn parse_multipart(
         if name.is_empty() {
             continue;
         }
+        keys += 1;
+        limits.check_keys(keys)?;
+        limits.check_string(name.len())?;
 
         // For now, treat file uploads as text (path/filename)
         // Full file handling will be added in Phase 2
@@ -123,25 +149,117 @@ async fn parse_multipart(
             .text()
             .await
             .map_err(|e| FormError::InvalidData(e.to_string()))?;
+        limits.check_string(value.len())?;
+        bytes += value.len();
+        limits.check_bytes(bytes)?;
+
+        values.insert(name, value);
+    }
 
-        // Handle array fields
-        if name.ends_with("[]") || name.contains('[') {
-            let base_key = name.split('[').next().unwrap_or(&name).to_string();
+    Ok(values.finish())
+}
 
-            match data.get_mut(&base_key) {
-                Some(FieldValue::Array(arr)) => {
-                    arr.push(value);
-                }
-                _ => {
-                    data.insert(base_key, FieldValue::Array(vec![value]));
+/// Parts of a location answer, as `field[lat]`, `field[lng]`...
+const LOCATION_PARTS: [&str; 4] = ["lat", "lng", "accuracy", "label"];
+
+/// Form values collected by name, with repeatable group rows by index.
+#[derive(Default)]
+struct FormValues {
+    data: HashMap<String, FieldValue>,
+    rows: HashMap<String, BTreeMap<usize, BTreeMap<String, FieldValue>>>,
+    locations: HashMap<String, HashMap<String, String>>,
+}
+
+impl FormValues {
+    /// Adds a value.
+    ///
+    /// `field[]` and `field[0]` collect into an array,
+    /// `group[0][field]` (or `group[0][field][]`) into row 0 of a
+    /// repeatable group, and `field[lat]`, `field[lng]`, `field[accuracy]`
+    /// and `field[label]` into a location.
+    fn insert(&mut self, key: String, value: String) {
+        let Some((base, rest)) = key.split_once('[') else {
+            self.data.insert(key, FieldValue::from(value));
+            return;
+        };
+
+        if let Some(part) = rest
+            .strip_suffix(']')
+            .filter(|part| LOCATION_PARTS.contains(part))
+        {
+            self.locations
+                .entry(base.to_string())
+                .or_default()
+                .insert(part.to_string(), value);
+            return;
+        }
+
+        if let Some((index, sub)) = rest.split_once("][") {
+            if let Ok(index) = index.parse::<usize>() {
+                let row = self
+                    .rows
+                    .entry(base.to_string())
+                    .or_default()
+                    .entry(index)
+                    .or_default();
+                if let Some(sub) = sub.strip_suffix("][]") {
+                    match row.get_mut(sub) {
+                        Some(FieldValue::Array(arr)) => arr.push(value),
+                        _ => {
+                            row.insert(sub.to_string(), FieldValue::Array(vec![value]));
+                        }
+                    }
+                } else {
+                    let sub = sub.trim_end_matches(']').to_string();
+                    row.insert(sub, FieldValue::from(value));
                 }
+                return;
             }
-        } else {
-            data.insert(name, FieldValue::from(value));
         }
+
+        match self.data.get_mut(base) {
+            Some(FieldValue::Array(arr)) => arr.push(value),
+            _ => {
+                self.data
+                    .insert(base.to_string(), FieldValue::Array(vec![value]));
+            }
+        }
+    }
+
+    /// Returns the values, leaving out group rows with nothing filled in.
+    fn finish(mut self) -> HashMap<String, FieldValue> {
+        for (group, rows) in self.rows {
+            let rows = rows
+                .into_values()
+                .filter(|row| row.values().any(|v| !v.is_empty()))
+                .collect();
+            self.data.insert(group, FieldValue::Rows(rows));
+        }
+        for (name, parts) in self.locations {
+            self.data.insert(name, location_value(&parts));
+        }
+        self.data
     }
+}
 
-    Ok(data)
+/// Returns the location the parts of a location answer make up: null when
+/// no coordinates were given, and the raw text when they don't parse, so
+/// validation can reject it.
+fn location_value(parts: &HashMap<String, String>) -> FieldValue {
+    let part = |key: &str| parts.get(key).map_or("", |v| v.trim());
+    let (lat, lng) = (part("lat"), part("lng"));
+    if lat.is_empty() && lng.is_empty() {
+        return FieldValue::Null;
+    }
+    match (lat.parse(), lng.parse()) {
+        (Ok(lat), Ok(lng)) => FieldValue::Location(GeoLocation {
+            lat,
+            lng,
+            accuracy: part("accuracy").parse().ok(),
+            label: Some(part("label").to_string()).filter(|l| !l.is_empty()),
+        }),
+        _ => FieldValue::Text(format!("{lat}, {lng}")),
+    }
 }
 
 impl FormSubmission {
@@ -1,9 +1,13 @@
 //! Axum extractors for form handling.
 
+mod body_limits;
 mod form_submission;
 mod request_id;
+mod request_metadata;
 mod validated_submission;
 
+pub use body_limits::BodyLimits;
 pub use form_submission::FormSubmission;
 pub use request_id::RequestId;
+pub use request_metadata::RequestMetadata;
 pub use validated_submission::ValidatedSubmission;
@@ -0,0 +1,112 @@
+//! Submission metadata extractor.
+//!
+//! Collects the submitter's IP address, user agent and referrer.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use http::request::Parts;
+use http::{header, HeaderMap};
+
+use crate::entities::submission::SubmissionMetadata;
+
+/// Extractor for request metadata stored alongside submissions.
+///
+/// The IP address is taken from the first `X-Forwarded-For` entry, then
+/// `X-Real-IP`, then the connection's peer address when the server was
+/// started with `into_make_service_with_connect_info`.
+///
+/// Handlers must not store it for anonymous forms; see
+/// [`SubmissionMetadata::for_settings`].
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use anyform::extractors::RequestMetadata;
+///
+/// async fn handler(RequestMetadata(metadata): RequestMetadata) -> String {
+///     metadata.user_agent.unwrap_or_default()
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequestMetadata(pub SubmissionMetadata);
+
+impl<S> FromRequestParts<S> for RequestMetadata
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let ip = forwarded_ip(&parts.headers).or_else(|| {
+            parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string())
+        });
+
+        Ok(RequestMetadata(SubmissionMetadata {
+            ip,
+            user_agent: header_value(&parts.headers, header::USER_AGENT.as_str()),
+            referrer: header_value(&parts.headers, header::REFERER.as_str()),
+            ..SubmissionMetadata::default()
+        }))
+    }
+}
+
+fn forwarded_ip(headers: &HeaderMap) -> Option<String> {
+    header_value(headers, "x-forwarded-for")
+        .and_then(|v| v.split(',').next().map(|ip| ip.trim().to_string()))
+        .filter(|ip| !ip.is_empty())
+        .or_else(|| header_value(headers, "x-real-ip"))
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    #[tokio::test]
+    async fn test_collects_forwarded_ip_and_headers() {
+        let request = Request::builder()
+            .header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
+            .header("user-agent", "test-agent")
+            .header("referer", "https://example.com/")
+            .body(())
+            .unwrap();
+        let (mut parts, _) = request.into_parts();
+
+        let RequestMetadata(metadata) = RequestMetadata::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(metadata.ip.as_deref(), Some("203.0.113.7"));
+        assert_eq!(metadata.user_agent.as_deref(), Some("test-agent"));
+        assert_eq!(metadata.referrer.as_deref(), Some("https://example.com/"));
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_peer_address() {
+        let request = Request::builder().body(()).unwrap();
+        let (mut parts, _) = request.into_parts();
+        parts
+            .extensions
+            .insert(ConnectInfo(SocketAddr::from(([192, 0, 2, 1], 4000))));
+
+        let RequestMetadata(metadata) = RequestMetadata::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+
+        assert_eq!(metadata.ip.as_deref(), Some("192.0.2.1"));
+        assert!(metadata.user_agent.is_none());
+    }
+}
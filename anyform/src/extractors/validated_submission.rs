@@ -4,8 +4,8 @@ use axum::extract::{FromRequest, FromRequestParts, Path, Request};
 use sea_orm::DatabaseConnection;
 use std::collections::HashMap;
 
-use crate::entities::{field, step};
 use crate::error::{FormError, ValidationErrors};
+use crate::repository::{load_steps_with_fields, StepWithFields};
 use crate::schema::FieldValue;
 use crate::validation::validate_submission;
 
@@ -86,16 +86,15 @@ where
         if form.is_deleted() {
             return Err(FormError::FormDeleted);
         }
-
-        // Load all fields for the form
-        let steps = step::Entity::find_by_form(&db, form.id).await?;
-        let mut all_fields = Vec::new();
-
-        for step in &steps {
-            let fields = field::Entity::find_by_step(&db, step.id).await?;
-            all_fields.extend(fields);
+        if !form.is_published() {
+            return Err(FormError::NotFound(slug));
         }
 
+        // Load all fields for the form, with the sub-fields of its groups
+        let steps = load_steps_with_fields(&db, form.id).await?;
+        let mut all_fields = StepWithFields::all_fields(&steps);
+        all_fields.extend(StepWithFields::all_sub_fields(&steps));
+
         // Validate the submission
         let errors = validate_submission(&all_fields, &data);
 
@@ -0,0 +1,215 @@
+//! Form fragments: steps including the fields of other forms.
+//!
+//! Sections shared by many forms, such as an address or an emergency
+//! contact, can be kept as forms of their own and included by the steps of
+//! other forms with `include_form`:
+//!
+//! ```json
+//! { "name": "Shipping", "include_form": "address-block" }
+//! ```
+//!
+//! The included form's fields follow the step's own fields, named under a
+//! namespace: the included form's slug with dashes turned to underscores
+//! (`address_block.street`), or `include_as` to include a form more than
+//! once (`{ "include_form": "address-block", "include_as": "billing" }`).
+//! Fragments are expanded whenever a form's steps are loaded, for rendering
+//! and for validation alike, so changes to a fragment reach every form that
+//! includes it. Conditions among the fragment's fields are renamed along
+//! with them, and a fragment step's condition carries over to its fields;
+//! the fragment's jump rules do not. Fragments can include fragments, up to
+//! [`MAX_DEPTH`] deep.
+
+use std::collections::HashSet;
+
+use futures::future::BoxFuture;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::condition::ConditionRule;
+use crate::entities::form;
+use crate::error::FormError;
+use crate::repository::{load_own_steps, FieldWithOptions, StepWithFields};
+
+/// Most levels of fragments including fragments.
+pub const MAX_DEPTH: usize = 4;
+
+/// Returns the namespace of the fields included from the form with `slug`.
+#[must_use]
+pub fn namespace(slug: &str, include_as: Option<&str>) -> String {
+    include_as.map_or_else(|| slug.replace('-', "_"), ToString::to_string)
+}
+
+/// Appends the fields of the forms `steps` include to them. `within` holds
+/// the forms being expanded, outermost first, to refuse cycles.
+pub(crate) fn include<'a, C: ConnectionTrait>(
+    db: &'a C,
+    steps: &'a mut [StepWithFields],
+    within: Vec<Uuid>,
+) -> BoxFuture<'a, Result<(), FormError>> {
+    Box::pin(async move {
+        for StepWithFields { step, fields } in steps.iter_mut() {
+            let (Some(slug), Some(namespace)) = (&step.include_form, step.namespace()) else {
+                continue;
+            };
+            let fragment = form::Entity::find()
+                .filter(form::Column::Slug.eq(slug))
+                .filter(form::Column::DeletedAt.is_null())
+                .one(db)
+                .await?
+                .ok_or_else(|| {
+                    FormError::InvalidData(format!(
+                        "Step '{}' includes form '{slug}', which does not exist",
+                        step.name
+                    ))
+                })?;
+            if within.contains(&fragment.id) {
+                return Err(FormError::InvalidData(format!(
+                    "Form '{slug}' includes itself"
+                )));
+            }
+            if within.len() > MAX_DEPTH {
+                return Err(FormError::InvalidData(format!(
+                    "Form '{slug}' is included more than {MAX_DEPTH} levels deep"
+                )));
+            }
+
+            let mut included = load_own_steps(db, fragment.id).await?;
+            let mut chain = within.clone();
+            chain.push(fragment.id);
+            include(db, &mut included, chain).await?;
+
+            let names: HashSet<String> = included
+                .iter()
+                .flat_map(|s| s.fields.iter().map(|f| f.field.name.clone()))
+                .collect();
+            for StepWithFields { step: from, fields: from_fields } in included {
+                let shown_if = from.condition_rule();
+                for field in from_fields {
+                    fields.push(namespaced(field, &namespace, &names, shown_if.clone(), step.id));
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Moves an included field into the step `step_id`, naming it under
+/// `namespace` along with the fields among `names` its condition reads.
+/// The field is shown only if `shown_if`, its step's condition, holds too.
+fn namespaced(
+    mut included: FieldWithOptions,
+    namespace: &str,
+    names: &HashSet<String>,
+    shown_if: Option<ConditionRule>,
+    step_id: Uuid,
+) -> FieldWithOptions {
+    let field = &mut included.field;
+    field.name = format!("{namespace}.{}", field.name);
+    field.step_id = step_id;
+    for sub in &mut included.fields {
+        sub.field.step_id = step_id;
+    }
+
+    let mut ui = field.ui_options.take().unwrap_or_else(|| serde_json::json!({}));
+    if let Some(ui) = ui.as_object_mut() {
+        // Jump rules name the fragment's steps
+        ui.remove("next");
+        let own = ui.remove("condition").and_then(|c| serde_json::from_value(c).ok());
+        let condition = match (shown_if, own) {
+            (Some(step), Some(own)) => Some(ConditionRule::and(vec![step, own])),
+            (step, own) => step.or(own),
+        };
+        if let Some(mut condition) = condition {
+            condition.rename_fields(&|name: &str| {
+                names.contains(name).then(|| format!("{namespace}.{name}"))
+            });
+            ui.insert(
+                "condition".to_string(),
+                serde_json::to_value(condition).unwrap_or_default(),
+            );
+        }
+    }
+    field.ui_options = Some(ui);
+    included
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::field;
+    use chrono::Utc;
+
+    fn field(name: &str, ui_options: Option<serde_json::Value>) -> FieldWithOptions {
+        FieldWithOptions {
+            field: field::Model {
+                id: Uuid::new_v4(),
+                step_id: Uuid::new_v4(),
+                parent_id: None,
+                name: name.to_string(),
+                label: name.to_string(),
+                field_type: "text".to_string(),
+                order: 0,
+                required: false,
+                placeholder: None,
+                help_text: None,
+                default_value: None,
+                validation_rules: None,
+                ui_options,
+                correct_answer: None,
+                points: None,
+                weight: None,
+                category: None,
+                classification: "standard".to_string(),
+                created_at: Utc::now().into(),
+            },
+            options: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_namespace_defaults_to_slug() {
+        assert_eq!(namespace("address-block", None), "address_block");
+        assert_eq!(namespace("address-block", Some("billing")), "billing");
+    }
+
+    #[test]
+    fn test_namespaced_renames_fields_and_conditions() {
+        let names: HashSet<String> = ["country".to_string(), "state".to_string()].into();
+        let step_id = Uuid::new_v4();
+        let state = field(
+            "state",
+            Some(serde_json::json!({
+                "css_class": "half",
+                "condition": { "field": "country", "op": "eq", "value": "US" },
+                "next": [{ "to": "Done" }],
+            })),
+        );
+
+        let state = namespaced(state, "billing", &names, None, step_id);
+        assert_eq!(state.field.name, "billing.state");
+        assert_eq!(state.field.step_id, step_id);
+        assert_eq!(
+            state.field.ui_options,
+            Some(serde_json::json!({
+                "css_class": "half",
+                "condition": { "field": "billing.country", "op": "eq", "value": "US" },
+            }))
+        );
+    }
+
+    #[test]
+    fn test_namespaced_carries_step_condition_over() {
+        let names: HashSet<String> = ["country".to_string()].into();
+        let step = ConditionRule::not_empty("has_address");
+        let country = namespaced(field("country", None), "home", &names, Some(step), Uuid::new_v4());
+        let condition = country.field.condition().unwrap();
+        assert_eq!(condition.fields(), vec!["has_address"]);
+
+        let own = serde_json::json!({ "condition": { "field": "country", "op": "not_empty" } });
+        let step = ConditionRule::not_empty("country");
+        let country = namespaced(field("country", Some(own)), "home", &names, Some(step), Uuid::new_v4());
+        let condition = country.field.condition().unwrap();
+        assert_eq!(condition.fields(), vec!["home.country", "home.country"]);
+    }
+}
@@ -0,0 +1,154 @@
+//! Example form gallery, for evaluating anyform locally.
+//!
+//! With [`AnyFormRouterBuilder::dev_mode`](crate::AnyFormRouterBuilder::dev_mode)
+//! set, the router serves an index of the example forms (see
+//! [`Seeder::examples`]) at [`PATH`], linking each form's HTML page, JSON
+//! schema and `config.js` module, with snippets for embedding it. The page is
+//! marked as a development tool; don't enable it in production.
+//!
+//! The gallery only lists forms: seed them first (`anyform serve --dev` does
+//! so on startup).
+
+use std::fmt::Write;
+
+use axum::{extract::State, response::Html};
+
+use crate::error::FormError;
+use crate::handlers::AnyFormState;
+use crate::render::escape_html;
+use crate::seed::Seeder;
+use crate::services::CreateFormInput;
+use crate::versioning::ApiVersion;
+
+/// Where the gallery is served.
+pub const PATH: &str = "/forms/examples";
+
+const GALLERY_CSS: &str = "body{font-family:system-ui,sans-serif;max-width:56rem;\
+margin:2rem auto;padding:0 1rem;line-height:1.5}\
+.af-dev-banner{background:#fff4ce;border:1px solid #e0b100;padding:.5rem 1rem}\
+.af-example{border-top:1px solid #ddd;padding:1rem 0}\
+pre{background:#f5f5f5;padding:.5rem;overflow-x:auto}";
+
+/// An example form and whether it is in the database.
+struct Example {
+    form: CreateFormInput,
+    seeded: bool,
+}
+
+/// Serves the gallery index.
+pub(crate) async fn index(
+    State(state): State<AnyFormState>,
+) -> Result<Html<String>, FormError> {
+    let mut examples = Vec::new();
+    for form in Seeder::examples().forms()? {
+        let seeded = state
+            .repository
+            .find_form_by_slug(&form.slug)
+            .await?
+            .is_some_and(|f| f.is_published());
+        examples.push(Example { form, seeded });
+    }
+    Ok(Html(render_index(&examples, ApiVersion::LATEST.path_prefix())))
+}
+
+fn render_index(examples: &[Example], prefix: &str) -> String {
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>").unwrap();
+    writeln!(html, "<meta charset=\"utf-8\">").unwrap();
+    writeln!(html, "<title>anyform examples (development mode)</title>").unwrap();
+    writeln!(html, "<style>{GALLERY_CSS}</style>\n</head>\n<body>").unwrap();
+    writeln!(html, "<h1>anyform examples</h1>").unwrap();
+    writeln!(
+        html,
+        "<p class=\"af-dev-banner\" role=\"note\"><strong>Development mode.</strong> \
+        This gallery is served because dev mode is on; turn it off in production.</p>"
+    )
+    .unwrap();
+
+    for example in examples {
+        render_example(&mut html, example, prefix);
+    }
+
+    writeln!(html, "</body>\n</html>").unwrap();
+    html
+}
+
+fn render_example(html: &mut String, example: &Example, prefix: &str) {
+    let form = &example.form;
+    let slug = escape_html(&form.slug);
+    let url = format!("{prefix}/forms/{slug}");
+
+    writeln!(html, "<section class=\"af-example\" id=\"{slug}\">").unwrap();
+    writeln!(html, "  <h2>{}</h2>", escape_html(&form.name)).unwrap();
+    if let Some(description) = &form.description {
+        writeln!(html, "  <p>{}</p>", escape_html(description)).unwrap();
+    }
+    writeln!(
+        html,
+        "  <p>{} step(s). Field types: {}</p>",
+        form.steps.len(),
+        escape_html(&field_types(form).join(", "))
+    )
+    .unwrap();
+
+    if !example.seeded {
+        writeln!(
+            html,
+            "  <p><em>Not seeded.</em> Run <code>anyform seed</code> to add it.</p>\n</section>"
+        )
+        .unwrap();
+        return;
+    }
+
+    writeln!(
+        html,
+        "  <p><a href=\"{url}\">HTML</a> · <a href=\"{url}/json\">JSON</a> · \
+        <a href=\"{url}/config.js\">config.js</a></p>"
+    )
+    .unwrap();
+    let embed = format!(
+        "<iframe src=\"{url}\" title=\"{}\"></iframe>\n\n\
+        <script type=\"module\">\n  \
+        import {{ form, submitUrl }} from \"{url}/config.js\";\n\
+        </script>",
+        escape_html(&form.name)
+    );
+    writeln!(html, "  <pre><code>{}</code></pre>\n</section>", escape_html(&embed)).unwrap();
+}
+
+/// Returns the form's field types, in order of first use.
+fn field_types(form: &CreateFormInput) -> Vec<&str> {
+    let mut types: Vec<&str> = Vec::new();
+    for field in form.steps.iter().flat_map(|s| &s.fields) {
+        if !types.contains(&field.field_type.as_str()) {
+            types.push(&field.field_type);
+        }
+    }
+    types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unseeded_examples_have_no_links() {
+        let examples = [
+            Example {
+                form: crate::seed::contact_form(),
+                seeded: true,
+            },
+            Example {
+                form: crate::seed::quiz_form(),
+                seeded: false,
+            },
+        ];
+        let html = render_index(&examples, "/api/v1");
+
+        assert!(html.contains("<a href=\"/api/v1/forms/contact/json\">JSON</a>"));
+        assert!(html.contains("Field types: text, email, tel, textarea, radio"));
+        assert!(html.contains("Run <code>anyform seed</code>"));
+        assert!(!html.contains("/api/v1/forms/quiz"));
+        assert!(html.contains("&lt;iframe src=&quot;/api/v1/forms/contact&quot;"));
+    }
+}
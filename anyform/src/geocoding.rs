@@ -0,0 +1,82 @@
+//! Naming the places location answers point at.
+//!
+//! Register a [`ReverseGeocoder`] with
+//! [`AnyFormRouterBuilder::reverse_geocoder`](crate::AnyFormRouterBuilder::reverse_geocoder)
+//! to label each submission's `location` answers as it's stored. Answers
+//! the respondent already named are left alone; the others get whatever
+//! place name the geocoder finds for their coordinates, kept in the
+//! answer's `label`.
+//!
+//! ```rust,ignore
+//! use anyform::geocoding::ReverseGeocoder;
+//!
+//! struct Nominatim(reqwest::Client);
+//!
+//! #[async_trait::async_trait]
+//! impl ReverseGeocoder for Nominatim {
+//!     async fn reverse_geocode(&self, lat: f64, lng: f64) -> Option<String> {
+//!         let url = format!(
+//!             "https://nominatim.openstreetmap.org/reverse?format=json&lat={lat}&lon={lng}"
+//!         );
+//!         let place: serde_json::Value = self.0.get(url).send().await.ok()?.json().await.ok()?;
+//!         Some(place["display_name"].as_str()?.to_string())
+//!     }
+//! }
+//!
+//! let router = AnyFormRouter::builder()
+//!     .database(db)
+//!     .reverse_geocoder(Nominatim(reqwest::Client::new()))
+//!     .build();
+//! ```
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+use crate::entities::field;
+use crate::schema::{FieldValue, ValueType};
+
+/// Finds place names for coordinates as submissions are stored.
+#[async_trait]
+pub trait ReverseGeocoder: Send + Sync + 'static {
+    /// Returns the name of the place at `lat`, `lng`.
+    ///
+    /// Called before the submission is stored, so keep it quick; return
+    /// `None` when the place can't be named (e.g. when a service is down)
+    /// and the answer is stored without a label.
+    async fn reverse_geocode(&self, lat: f64, lng: f64) -> Option<String>;
+}
+
+impl std::fmt::Debug for dyn ReverseGeocoder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ReverseGeocoder")
+    }
+}
+
+/// Labels the unnamed answers to `fields`' location fields in `data` with
+/// the place `geocoder` finds for them.
+///
+/// Returns true if any answer was labeled.
+pub async fn label_locations(
+    geocoder: &dyn ReverseGeocoder,
+    fields: &[field::Model],
+    data: &mut HashMap<String, FieldValue>,
+) -> bool {
+    let mut labeled = false;
+    for field in fields
+        .iter()
+        .filter(|f| f.value_type() == Some(ValueType::Location))
+    {
+        let Some(FieldValue::Location(location)) = data.get_mut(&field.name) else {
+            continue;
+        };
+        if location.label.as_deref().is_some_and(|l| !l.trim().is_empty()) {
+            continue;
+        }
+        if let Some(label) = geocoder.reverse_geocode(location.lat, location.lng).await {
+            location.label = Some(label);
+            labeled = true;
+        }
+    }
+    labeled
+}
@@ -0,0 +1,341 @@
+//! GraphQL API over forms and submissions, for GraphQL-first frontends.
+//!
+//! With the `graphql` feature on and
+//! [`AnyFormRouterBuilder::enable_graphql`](crate::AnyFormRouterBuilder::enable_graphql)
+//! set, the router serves two endpoints alongside the REST routes:
+//!
+//! - `/graphql` answers public queries: published forms with their steps,
+//!   fields and options, and the `submit` mutation, which goes through the
+//!   same checks as `POST /forms/{slug}` (rate limits, spam protection,
+//!   CAPTCHA, validation).
+//! - `/admin/graphql`, mounted with the admin routes and behind the same
+//!   [`AdminAuthorizer`](crate::AdminAuthorizer), adds forms in any status,
+//!   submissions, and the `createForm` and `updateForm` mutations.
+//!
+//! Both take `POST` requests with a JSON body of `query`, `variables` and
+//! `operationName`, and `GET` requests with the same as query parameters
+//! for queries. Each serves its schema as SDL at `.../schema.graphql`, and
+//! answers introspection queries.
+//!
+//! Schemas are executed by `async-graphql`, built from the types in
+//! [`schema`] with resolvers that read from the same services as the REST
+//! API. Errors come back in the standard `errors` list with the
+//! [`FormError`] code under `extensions.code`, and `GRAPHQL_PARSE_FAILED`
+//! or `GRAPHQL_VALIDATION_FAILED` for documents that can't run. Documents
+//! are limited in depth and size.
+//!
+//! # Example
+//!
+//! ```graphql
+//! query Contact($slug: String!) {
+//!   form(slug: $slug) {
+//!     name
+//!     steps { name fields { name label fieldType required options { label value } } }
+//!   }
+//! }
+//! ```
+
+mod resolvers;
+mod schema;
+
+use std::sync::Arc;
+
+use async_graphql::extensions::{
+    Extension, ExtensionContext, ExtensionFactory, NextResolve, NextValidation, ResolveInfo,
+};
+use async_graphql::parser::types::{DocumentOperations, ExecutableDocument, OperationType};
+use async_graphql::{
+    ErrorExtensionValues, ErrorExtensions, PathSegment, QueryPathNode, QueryPathSegment, Request,
+    ServerError, ServerResult, ValidationResult,
+};
+use axum::{
+    body::Bytes,
+    extract::{RawQuery, State},
+    response::{IntoResponse, Response},
+    Json,
+};
+use http::{header, HeaderMap, StatusCode};
+
+#[cfg(feature = "chaos")]
+use crate::chaos::Chaos;
+use crate::entities::submission::SubmissionMetadata;
+use crate::error::FormError;
+use crate::extractors::RequestMetadata;
+use crate::handlers::AnyFormState;
+
+const PARSE_FAILED: &str = "GRAPHQL_PARSE_FAILED";
+const VALIDATION_FAILED: &str = "GRAPHQL_VALIDATION_FAILED";
+
+/// Which of the two schemas an endpoint serves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    /// Published forms and submitting to them.
+    Public,
+    /// Everything, for the admin API.
+    Admin,
+}
+
+/// What an operation runs with: the state, and the request that `submit`
+/// mutations are checked and recorded against.
+pub(crate) struct Context {
+    pub state: AnyFormState,
+    pub surface: Surface,
+    pub metadata: SubmissionMetadata,
+    pub headers: HeaderMap,
+    pub webhook_failure: bool,
+}
+
+/// Answers public GraphQL requests sent with `POST`.
+pub async fn public_graphql(
+    State(state): State<AnyFormState>,
+    RequestMetadata(metadata): RequestMetadata,
+    headers: HeaderMap,
+    #[cfg(feature = "chaos")] chaos: Chaos,
+    body: Bytes,
+) -> Response {
+    #[cfg(feature = "chaos")]
+    let webhook_failure = chaos.webhook_failure();
+    #[cfg(not(feature = "chaos"))]
+    let webhook_failure = false;
+
+    let context = Context {
+        state,
+        surface: Surface::Public,
+        metadata,
+        headers,
+        webhook_failure,
+    };
+    post(context, &body).await
+}
+
+/// Answers public GraphQL queries sent with `GET`.
+pub async fn public_graphql_get(
+    State(state): State<AnyFormState>,
+    RequestMetadata(metadata): RequestMetadata,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let context = Context {
+        state,
+        surface: Surface::Public,
+        metadata,
+        headers,
+        webhook_failure: false,
+    };
+    get(context, query.as_deref().unwrap_or_default()).await
+}
+
+/// Answers admin GraphQL requests sent with `POST`.
+#[cfg(feature = "admin")]
+pub async fn admin_graphql(
+    State(state): State<AnyFormState>,
+    RequestMetadata(metadata): RequestMetadata,
+    headers: HeaderMap,
+    #[cfg(feature = "chaos")] chaos: Chaos,
+    body: Bytes,
+) -> Response {
+    #[cfg(feature = "chaos")]
+    let webhook_failure = chaos.webhook_failure();
+    #[cfg(not(feature = "chaos"))]
+    let webhook_failure = false;
+
+    let context = Context {
+        state,
+        surface: Surface::Admin,
+        metadata,
+        headers,
+        webhook_failure,
+    };
+    post(context, &body).await
+}
+
+/// Answers admin GraphQL queries sent with `GET`.
+#[cfg(feature = "admin")]
+pub async fn admin_graphql_get(
+    State(state): State<AnyFormState>,
+    RequestMetadata(metadata): RequestMetadata,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+) -> Response {
+    let context = Context {
+        state,
+        surface: Surface::Admin,
+        metadata,
+        headers,
+        webhook_failure: false,
+    };
+    get(context, query.as_deref().unwrap_or_default()).await
+}
+
+/// Serves the public schema as SDL.
+pub async fn public_schema() -> impl IntoResponse {
+    sdl_response(Surface::Public)
+}
+
+/// Serves the admin schema as SDL.
+#[cfg(feature = "admin")]
+pub async fn admin_schema() -> impl IntoResponse {
+    sdl_response(Surface::Admin)
+}
+
+/// Returns the schema of `surface` as SDL.
+#[must_use]
+pub fn sdl(surface: Surface) -> String {
+    schema::schema(surface).sdl()
+}
+
+fn sdl_response(surface: Surface) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        sdl(surface),
+    )
+}
+
+async fn post(context: Context, body: &[u8]) -> Response {
+    match serde_json::from_slice::<Request>(body) {
+        Ok(request) if request.query.is_empty() => {
+            bad_request(FormError::InvalidData("missing field `query`".into()))
+        }
+        Ok(request) => execute(context, request, true).await,
+        Err(e) => bad_request(FormError::InvalidData(e.to_string())),
+    }
+}
+
+async fn get(context: Context, query: &str) -> Response {
+    match async_graphql::http::parse_query_string(query) {
+        Ok(request) if request.query.is_empty() => {
+            bad_request(FormError::InvalidData("missing query parameter `query`".into()))
+        }
+        Ok(request) => execute(context, request, false).await,
+        Err(e) => bad_request(FormError::InvalidData(e.to_string())),
+    }
+}
+
+/// Runs a request; mutations only when `allow_mutations` is set, as `GET`
+/// requests only query.
+async fn execute(context: Context, mut request: Request, allow_mutations: bool) -> Response {
+    let operation_name = request.operation_name.clone();
+    let is_mutation = match request.parsed_query() {
+        Ok(document) => is_mutation(document, operation_name.as_deref()),
+        Err(error) => return errors(vec![with_code(error, PARSE_FAILED)]),
+    };
+    if is_mutation {
+        if !allow_mutations {
+            let error = ServerError::new("Mutations must be sent with POST", None);
+            return errors(vec![with_code(error, "METHOD_NOT_ALLOWED")]);
+        }
+        if context.state.config.read_only {
+            return errors(vec![graphql_error(FormError::ReadOnly).into_server_error(Default::default())]);
+        }
+    }
+    let schema = schema::schema(context.surface);
+    Json(schema.execute(request.data(context)).await).into_response()
+}
+
+/// Returns whether the operation a request runs is a mutation; unknown
+/// operations are left to validation.
+fn is_mutation(document: &ExecutableDocument, name: Option<&str>) -> bool {
+    let operation = match (&document.operations, name) {
+        (DocumentOperations::Single(operation), _) => Some(operation),
+        (DocumentOperations::Multiple(operations), Some(name)) => operations.get(name),
+        (DocumentOperations::Multiple(_), None) => None,
+    };
+    operation.is_some_and(|op| op.node.ty == OperationType::Mutation)
+}
+
+/// Returns a [`FormError`] as a GraphQL error, with its code, and
+/// `details` and `retryAfter` where the REST API has them.
+pub(crate) fn graphql_error(err: FormError) -> async_graphql::Error {
+    let code = err.error_code();
+    let message = err.to_string();
+    let api_error = crate::response::ApiResponse::<()>::from(err).error;
+    async_graphql::Error::new(message).extend_with(|_, extensions| {
+        extensions.set("code", code);
+        if let Some(api_error) = api_error {
+            if let Some(details) = api_error.details {
+                if let Ok(details) = async_graphql::Value::from_json(details) {
+                    extensions.set("details", details);
+                }
+            }
+            if let Some(retry_after) = api_error.retry_after {
+                extensions.set("retryAfter", retry_after);
+            }
+        }
+    })
+}
+
+fn with_code(mut error: ServerError, code: &str) -> ServerError {
+    error
+        .extensions
+        .get_or_insert_with(ErrorExtensionValues::default)
+        .set("code", code);
+    error
+}
+
+fn path(node: &QueryPathNode<'_>) -> Vec<PathSegment> {
+    let mut path: Vec<_> = std::iter::once(node)
+        .chain(node.parents())
+        .map(|node| match node.segment {
+            QueryPathSegment::Name(name) => PathSegment::Field(name.to_owned()),
+            QueryPathSegment::Index(index) => PathSegment::Index(index),
+        })
+        .collect();
+    path.reverse();
+    path
+}
+
+/// Answers errors that kept a request from running.
+fn errors(errors: Vec<ServerError>) -> Response {
+    Json(async_graphql::Response::from_errors(errors)).into_response()
+}
+
+/// Answers a request that isn't a GraphQL request at all.
+fn bad_request(err: FormError) -> Response {
+    let error = graphql_error(err).into_server_error(Default::default());
+    (
+        StatusCode::BAD_REQUEST,
+        Json(async_graphql::Response::from_errors(vec![error])),
+    )
+        .into_response()
+}
+
+/// Gives validation errors the `GRAPHQL_VALIDATION_FAILED` code, and
+/// resolver errors the path of the field they came from.
+struct ErrorDetails;
+
+impl ExtensionFactory for ErrorDetails {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ErrorDetails)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for ErrorDetails {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        next.run(ctx).await.map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|error| with_code(error, VALIDATION_FAILED))
+                .collect()
+        })
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<async_graphql::Value>> {
+        let node = info.path_node;
+        next.run(ctx, info).await.map_err(|mut error| {
+            if error.path.is_empty() {
+                error.path = path(node);
+            }
+            error
+        })
+    }
+}
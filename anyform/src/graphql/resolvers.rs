@@ -0,0 +1,396 @@
+//! Field resolvers, reading from the database and the services behind the
+//! REST API.
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value as Json};
+use uuid::Uuid;
+
+use super::{Context, Surface};
+use crate::entities::form::FormStatus;
+use crate::entities::submission::SubmissionStatus;
+use crate::entities::{field_option, form, submission};
+use crate::error::FormError;
+use crate::handlers::{accept_submission, decrypted, ResultData, SubmissionCreated};
+use crate::repository::{FieldWithOptions, StepWithFields};
+use crate::schema::FieldValue;
+use crate::services::{CreateFormInput, SubmissionPage, SubmissionQuery};
+
+/// A value a field's selection set runs on.
+pub(crate) enum Node {
+    /// The query or mutation root.
+    Root,
+    Form(form::Model),
+    Step(StepWithFields),
+    Field(FieldWithOptions),
+    FieldOption(field_option::Model),
+    Submission(submission::Model),
+    SubmissionPage(SubmissionPage),
+    Receipt(SubmissionCreated),
+    Result(ResultData),
+}
+
+/// What a resolver returns, before it is shaped to the field's type.
+pub(crate) enum Resolved {
+    Value(Json),
+    Node(Box<Node>),
+    List(Vec<Resolved>),
+}
+
+impl Resolved {
+    fn node(node: Node) -> Self {
+        Self::Node(Box::new(node))
+    }
+
+    fn nodes<T>(items: Vec<T>, node: impl Fn(T) -> Node) -> Self {
+        Self::List(items.into_iter().map(|item| Self::node(node(item))).collect())
+    }
+}
+
+/// Resolves to a scalar value.
+fn value(value: impl Into<Json>) -> Resolved {
+    Resolved::Value(value.into())
+}
+
+/// A field's arguments, with variables substituted.
+pub(crate) struct Args(HashMap<String, Json>);
+
+impl Args {
+    pub fn new(args: HashMap<String, Json>) -> Self {
+        Self(args)
+    }
+
+    fn get(&self, name: &str) -> Option<&Json> {
+        self.0.get(name).filter(|value| !value.is_null())
+    }
+
+    fn invalid(name: &str, expected: &str) -> FormError {
+        FormError::InvalidData(format!("argument \"{name}\" must be {expected}"))
+    }
+
+    fn string(&self, name: &str) -> Result<Option<&str>, FormError> {
+        self.get(name)
+            .map(|value| value.as_str().ok_or_else(|| Self::invalid(name, "a string")))
+            .transpose()
+    }
+
+    fn required_string(&self, name: &str) -> Result<&str, FormError> {
+        self.string(name)?
+            .ok_or_else(|| Self::invalid(name, "given"))
+    }
+
+    fn id(&self, name: &str) -> Result<Option<Uuid>, FormError> {
+        self.string(name)?
+            .map(|id| id.parse().map_err(|_| Self::invalid(name, "a UUID")))
+            .transpose()
+    }
+
+    fn required_id(&self, name: &str) -> Result<Uuid, FormError> {
+        self.id(name)?.ok_or_else(|| Self::invalid(name, "given"))
+    }
+
+    fn page_number(&self, name: &str) -> Result<Option<u32>, FormError> {
+        self.get(name)
+            .map(|value| {
+                value
+                    .as_u64()
+                    .and_then(|n| u32::try_from(n).ok())
+                    .ok_or_else(|| Self::invalid(name, "a non-negative Int"))
+            })
+            .transpose()
+    }
+
+    /// Parses an enum value, as its GraphQL name or stored string.
+    fn status<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, FormError> {
+        self.string(name)?
+            .map(|value| {
+                value
+                    .to_lowercase()
+                    .parse()
+                    .map_err(|_| Self::invalid(name, "a valid enum value"))
+            })
+            .transpose()
+    }
+
+    fn required_json(&self, name: &str) -> Result<Json, FormError> {
+        self.get(name)
+            .cloned()
+            .ok_or_else(|| Self::invalid(name, "given"))
+    }
+}
+
+/// Resolves field `name` of `node`, of type `ty`.
+pub(crate) async fn resolve(
+    context: &Context,
+    node: &Node,
+    ty: &str,
+    name: &str,
+    args: &Args,
+) -> Result<Resolved, FormError> {
+    let repository = context.state.repository.as_ref();
+    Ok(match node {
+        Node::Root if ty == "Mutation" => return mutation(context, name, args).await,
+        Node::Root => return query(context, name, args).await,
+        Node::Form(form) => match name {
+            "id" => value(form.id.to_string()),
+            "slug" => value(form.slug.clone()),
+            "name" => value(form.name.clone()),
+            "description" => value(form.description.clone()),
+            "closed" => value(form.is_closed()),
+            "settings" => value(json_value(&form.settings())?),
+            "steps" => {
+                Resolved::nodes(repository.load_steps_with_fields(form.id).await?, Node::Step)
+            }
+            "status" => value(enum_name(form.status().as_str())),
+            "responseCount" => value(form.response_count),
+            "createdAt" => value(form.created_at.to_rfc3339()),
+            "updatedAt" => value(form.updated_at.to_rfc3339()),
+            "submissions" => submissions(context, form.id, args).await?,
+            _ => value(Json::Null),
+        },
+        Node::Step(StepWithFields { step, fields }) => match name {
+            "id" => value(step.id.to_string()),
+            "name" => value(step.name.clone()),
+            "description" => value(step.description.clone()),
+            "order" => value(step.order),
+            "condition" => value(step.condition_expr()),
+            "fields" => Resolved::nodes(fields.clone(), Node::Field),
+            _ => value(Json::Null),
+        },
+        Node::Field(FieldWithOptions { field, options, .. }) => match name {
+            "id" => value(field.id.to_string()),
+            "name" => value(field.name.clone()),
+            "label" => value(field.label.clone()),
+            "fieldType" => value(field.field_type.clone()),
+            "order" => value(field.order),
+            "required" => value(field.required),
+            "placeholder" => value(field.placeholder.clone()),
+            "helpText" => value(field.help_text.clone()),
+            "defaultValue" => value(field.default_value.clone()),
+            "validation" => value(json_value(&field.validation())?),
+            "uiOptions" => value(json_value(&field.ui())?),
+            "options" => Resolved::nodes(options.clone(), Node::FieldOption),
+            "classification" => value(json_value(&field.classification())?),
+            "correctAnswer" => value(field.correct_answer.clone()),
+            "points" => value(field.points),
+            "weight" => value(field.weight),
+            "category" => value(field.category.clone()),
+            _ => value(Json::Null),
+        },
+        Node::FieldOption(option) => match name {
+            "id" => value(option.id.to_string()),
+            "label" => value(option.label.clone()),
+            "value" => value(option.value.clone()),
+            "order" => value(option.order),
+            "capacity" => value(option.capacity),
+            "remaining" => value(option.remaining()),
+            "isCorrect" => value(option.is_correct),
+            "points" => value(option.points),
+            _ => value(Json::Null),
+        },
+        Node::Submission(sub) => match name {
+            "id" => value(sub.id.to_string()),
+            "formId" => value(sub.form_id.to_string()),
+            "status" => value(enum_name(&sub.status)),
+            "data" => value(sub.data.clone()),
+            "metadata" => value(sub.metadata.clone()),
+            "score" => value(sub.score),
+            "maxScore" => value(sub.max_score),
+            "resultKey" => value(sub.result_key.clone()),
+            "instanceId" => value(sub.instance_id.map(|id| id.to_string())),
+            "respondentId" => value(sub.respondent_id.clone()),
+            "legalHold" => value(sub.legal_hold),
+            "createdAt" => value(sub.created_at.to_rfc3339()),
+            "updatedAt" => value(sub.updated_at.to_rfc3339()),
+            "completedAt" => value(sub.completed_at.map(|at| at.to_rfc3339())),
+            _ => value(Json::Null),
+        },
+        Node::SubmissionPage(page) => match name {
+            "submissions" => Resolved::nodes(page.submissions.clone(), Node::Submission),
+            "page" => value(page.page),
+            "perPage" => value(page.per_page),
+            "total" => value(page.total),
+            _ => value(Json::Null),
+        },
+        Node::Receipt(receipt) => match name {
+            "submissionId" => value(receipt.submission_id.clone()),
+            "status" => value(receipt.status.clone()),
+            "message" => value(receipt.message.clone()),
+            "score" => value(receipt.score),
+            "maxScore" => value(receipt.max_score),
+            "resultKey" => value(receipt.result_key.clone()),
+            "duplicateToken" => value(receipt.duplicate_token.clone()),
+            "result" => match receipt.result.clone() {
+                Some(result) => Resolved::node(Node::Result(result)),
+                None => value(Json::Null),
+            },
+            _ => value(Json::Null),
+        },
+        Node::Result(result) => match name {
+            "key" => value(result.key.clone()),
+            "title" => value(result.title.clone()),
+            "description" => value(result.description.clone()),
+            _ => value(Json::Null),
+        },
+    })
+}
+
+async fn query(context: &Context, name: &str, args: &Args) -> Result<Resolved, FormError> {
+    let repository = context.state.repository.as_ref();
+    let found = |form: Option<form::Model>| match form.filter(|f| !f.is_deleted() && context.state.serves(f)) {
+        Some(form) => Resolved::node(Node::Form(form)),
+        None => value(Json::Null),
+    };
+
+    match (context.surface, name) {
+        (Surface::Public, "form") => {
+            let slug = args.required_string("slug")?;
+            let form = context
+                .state
+                .form_resolver()
+                .resolve(slug, &context.headers, context.state.repository.as_ref())
+                .await?;
+            Ok(found(form.filter(form::Model::is_published)))
+        }
+        (Surface::Admin, "forms") => {
+            let status: Option<FormStatus> = args.status("status")?;
+            let forms = repository
+                .list_forms()
+                .await?
+                .into_iter()
+                .filter(|form| status.map_or(true, |s| form.status() == s))
+                .filter(|form| context.state.serves(form))
+                .collect();
+            Ok(Resolved::nodes(forms, Node::Form))
+        }
+        (Surface::Admin, "form") => match (args.id("id")?, args.string("slug")?) {
+            (Some(id), None) => Ok(found(repository.find_form(id).await?)),
+            (None, Some(slug)) => Ok(found(repository.find_form_by_slug(slug).await?)),
+            _ => Err(FormError::InvalidData(
+                "give exactly one of the arguments \"id\" and \"slug\"".to_string(),
+            )),
+        },
+        (Surface::Admin, "submission") => {
+            let sub = repository.find_submission(args.required_id("id")?).await?;
+            let sub = match sub {
+                Some(sub) if sub.is_deleted() => None,
+                Some(sub) if serves_form(context, sub.form_id).await? => Some(sub),
+                _ => None,
+            };
+            Ok(match sub {
+                Some(sub) => Resolved::node(Node::Submission(decrypted(&context.state, sub))),
+                None => value(Json::Null),
+            })
+        }
+        (Surface::Admin, "submissions") => {
+            let form_id = args.required_id("formId")?;
+            if !serves_form(context, form_id).await? {
+                return Err(FormError::NotFound(form_id.to_string()));
+            }
+            submissions(context, form_id, args).await
+        }
+        _ => Ok(value(Json::Null)),
+    }
+}
+
+async fn mutation(context: &Context, name: &str, args: &Args) -> Result<Resolved, FormError> {
+    let repository = context.state.repository.as_ref();
+    match (context.surface, name) {
+        (_, "submit") => {
+            let data: HashMap<String, FieldValue> =
+                serde_json::from_value(args.required_json("data")?)
+                    .map_err(|e| FormError::InvalidData(e.to_string()))?;
+            let (created, _cookies) = accept_submission(
+                &context.state,
+                args.required_string("slug")?,
+                data,
+                context.metadata.clone(),
+                &context.headers,
+                None,
+                context.webhook_failure,
+            )
+            .await?;
+            Ok(match created.data {
+                Some(receipt) => Resolved::node(Node::Receipt(receipt)),
+                None => value(Json::Null),
+            })
+        }
+        (Surface::Admin, "createForm") => {
+            let input = form_input(context, args)?;
+            context.state.check_slug(&input.slug)?;
+            let form = repository.create_form(input).await?;
+            if let Some(events) = &context.state.config.events {
+                events.on_form_created(&form).await;
+            }
+            Ok(Resolved::node(Node::Form(form)))
+        }
+        (Surface::Admin, "updateForm") => {
+            let id = args.required_id("id")?;
+            if !serves_form(context, id).await? {
+                return Err(FormError::NotFound(id.to_string()));
+            }
+            let input = form_input(context, args)?;
+            context.state.check_slug(&input.slug)?;
+            let form = repository.update_form(id, input).await?;
+            if let Some(events) = &context.state.config.events {
+                events.on_form_updated(&form).await;
+            }
+            Ok(Resolved::node(Node::Form(form)))
+        }
+        _ => Ok(value(Json::Null)),
+    }
+}
+
+/// Returns false for a form outside the router's scope; forms that don't
+/// exist are left to the caller.
+async fn serves_form(context: &Context, form_id: Uuid) -> Result<bool, FormError> {
+    if context.state.config.only_forms.is_none() {
+        return Ok(true);
+    }
+    let form = context.state.repository.find_form(form_id).await?;
+    Ok(form.map_or(true, |form| context.state.serves(&form)))
+}
+
+/// Fetches a page of a form's submissions.
+async fn submissions(
+    context: &Context,
+    form_id: Uuid,
+    args: &Args,
+) -> Result<Resolved, FormError> {
+    let mut query = SubmissionQuery::new(form_id);
+    if let Some(status) = args.status::<SubmissionStatus>("status")? {
+        query = query.status(status);
+    }
+    if let Some(respondent_id) = args.string("respondentId")? {
+        query = query.respondent(respondent_id);
+    }
+    if let Some(page) = args.page_number("page")? {
+        query = query.page(page);
+    }
+    if let Some(per_page) = args.page_number("perPage")? {
+        query = query.per_page(per_page);
+    }
+    let mut page = context.state.repository.list_submissions(&query).await?;
+    page.submissions = std::mem::take(&mut page.submissions)
+        .into_iter()
+        .map(|sub| decrypted(&context.state, sub))
+        .collect();
+    Ok(Resolved::node(Node::SubmissionPage(page)))
+}
+
+/// Reads a form definition from `input`, with its field preset references
+/// expanded.
+fn form_input(context: &Context, args: &Args) -> Result<CreateFormInput, FormError> {
+    let input = serde_json::from_value(args.required_json("input")?)
+        .map_err(|e| FormError::InvalidData(format!("input: {e}")))?;
+    context.state.config.field_library.expand(input)
+}
+
+fn json_value(value: &impl serde::Serialize) -> Result<Json, FormError> {
+    serde_json::to_value(value).map_err(|e| FormError::InvalidData(e.to_string()))
+}
+
+/// Returns a stored status as its GraphQL enum value.
+fn enum_name(status: &str) -> Json {
+    json!(status.to_uppercase())
+}
@@ -0,0 +1,451 @@
+//! The GraphQL schema: its types, built into an `async-graphql` schema per
+//! surface, with every field resolved by [`resolvers::resolve`].
+
+use std::sync::OnceLock;
+
+use async_graphql::dynamic::{
+    Enum, Field, FieldFuture, FieldValue, InputValue, Object, ResolverContext, Scalar, Schema,
+    TypeRef,
+};
+use async_graphql::Value;
+use serde_json::Value as Json;
+
+use super::resolvers::{self, Args, Node, Resolved};
+use super::{graphql_error, Context, Surface, ErrorDetails};
+
+/// Most levels a document may nest selections.
+const MAX_DEPTH: usize = 32;
+
+/// Most fields a document may select, counting each fragment spread anew.
+const MAX_FIELDS: usize = 500;
+
+/// An object type and its fields.
+pub(crate) struct ObjectType {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether the type is only part of the admin schema.
+    pub admin: bool,
+    pub fields: &'static [FieldDef],
+}
+
+/// A field of an object type.
+pub(crate) struct FieldDef {
+    pub name: &'static str,
+    /// The field's type, as written in SDL (e.g. `[Step!]!`).
+    pub ty: &'static str,
+    pub args: &'static [ArgDef],
+    pub description: &'static str,
+    /// Whether the field is only part of the admin schema.
+    pub admin: bool,
+}
+
+/// An argument of a field.
+pub(crate) struct ArgDef {
+    pub name: &'static str,
+    pub ty: &'static str,
+}
+
+const fn field(name: &'static str, ty: &'static str, description: &'static str) -> FieldDef {
+    FieldDef {
+        name,
+        ty,
+        args: &[],
+        description,
+        admin: false,
+    }
+}
+
+const fn admin(name: &'static str, ty: &'static str, description: &'static str) -> FieldDef {
+    FieldDef {
+        admin: true,
+        ..field(name, ty, description)
+    }
+}
+
+const fn arg(name: &'static str, ty: &'static str) -> ArgDef {
+    ArgDef { name, ty }
+}
+
+const SUBMISSION_PAGE_ARGS: &[ArgDef] = &[
+    arg("status", "SubmissionStatus"),
+    arg("respondentId", "String"),
+    arg("page", "Int"),
+    arg("perPage", "Int"),
+];
+
+const PUBLIC_QUERY: ObjectType = ObjectType {
+    name: "Query",
+    description: "",
+    admin: false,
+    fields: &[FieldDef {
+        args: &[arg("slug", "String!")],
+        ..field("form", "Form", "A published form, or null if there is none.")
+    }],
+};
+
+const ADMIN_QUERY: ObjectType = ObjectType {
+    name: "Query",
+    description: "",
+    admin: true,
+    fields: &[
+        FieldDef {
+            args: &[arg("status", "FormStatus")],
+            ..field("forms", "[Form!]!", "Forms that aren't deleted.")
+        },
+        FieldDef {
+            args: &[arg("id", "ID"), arg("slug", "String")],
+            ..field("form", "Form", "A form by ID or slug, in any status.")
+        },
+        FieldDef {
+            args: &[arg("id", "ID!")],
+            ..field("submission", "Submission", "A submission that isn't deleted.")
+        },
+        FieldDef {
+            args: &[
+                arg("formId", "ID!"),
+                arg("status", "SubmissionStatus"),
+                arg("respondentId", "String"),
+                arg("page", "Int"),
+                arg("perPage", "Int"),
+            ],
+            ..field(
+                "submissions",
+                "SubmissionPage!",
+                "A page of a form's submissions, newest first.",
+            )
+        },
+    ],
+};
+
+const SUBMIT: FieldDef = FieldDef {
+    args: &[arg("slug", "String!"), arg("data", "JSON!")],
+    ..field(
+        "submit",
+        "SubmissionReceipt!",
+        "Submits answers to a published form, as `POST /forms/{slug}` does.",
+    )
+};
+
+const PUBLIC_MUTATION: ObjectType = ObjectType {
+    name: "Mutation",
+    description: "",
+    admin: false,
+    fields: &[SUBMIT],
+};
+
+const ADMIN_MUTATION: ObjectType = ObjectType {
+    name: "Mutation",
+    description: "",
+    admin: true,
+    fields: &[
+        SUBMIT,
+        FieldDef {
+            args: &[arg("input", "JSON!")],
+            ..field(
+                "createForm",
+                "Form!",
+                "Creates a form from the body `POST /admin/forms` takes.",
+            )
+        },
+        FieldDef {
+            args: &[arg("id", "ID!"), arg("input", "JSON!")],
+            ..field(
+                "updateForm",
+                "Form!",
+                "Replaces a form with the body `PUT /admin/forms/{id}` takes.",
+            )
+        },
+    ],
+};
+
+/// The object types other than the roots, in SDL order.
+const OBJECT_TYPES: &[ObjectType] = &[
+    ObjectType {
+        name: "Form",
+        description: "A form, with its steps and fields.",
+        admin: false,
+        fields: &[
+            field("id", "ID!", ""),
+            field("slug", "String!", ""),
+            field("name", "String!", ""),
+            field("description", "String", ""),
+            field("closed", "Boolean!", "Whether the form no longer takes submissions."),
+            field("settings", "JSON!", ""),
+            field("steps", "[Step!]!", "The form's steps, in order."),
+            admin("status", "FormStatus!", ""),
+            admin("responseCount", "Int!", ""),
+            admin("createdAt", "DateTime!", ""),
+            admin("updatedAt", "DateTime!", ""),
+            FieldDef {
+                args: SUBMISSION_PAGE_ARGS,
+                ..admin(
+                    "submissions",
+                    "SubmissionPage!",
+                    "A page of the form's submissions, newest first.",
+                )
+            },
+        ],
+    },
+    ObjectType {
+        name: "Step",
+        description: "A step of a form.",
+        admin: false,
+        fields: &[
+            field("id", "ID!", ""),
+            field("name", "String!", ""),
+            field("description", "String", ""),
+            field("order", "Int!", ""),
+            field("condition", "String", "When the step is shown, as an expression."),
+            field("fields", "[Field!]!", "The step's fields, in order."),
+        ],
+    },
+    ObjectType {
+        name: "Field",
+        description: "A field of a form.",
+        admin: false,
+        fields: &[
+            field("id", "ID!", ""),
+            field("name", "String!", ""),
+            field("label", "String!", ""),
+            field("fieldType", "String!", ""),
+            field("order", "Int!", ""),
+            field("required", "Boolean!", ""),
+            field("placeholder", "String", ""),
+            field("helpText", "String", ""),
+            field("defaultValue", "String", ""),
+            field("validation", "JSON!", ""),
+            field("uiOptions", "JSON!", ""),
+            field("options", "[FieldOption!]!", "The field's options, for choice fields."),
+            admin("classification", "String!", ""),
+            admin("correctAnswer", "String", ""),
+            admin("points", "Int", ""),
+            admin("weight", "Float", ""),
+            admin("category", "String", ""),
+        ],
+    },
+    ObjectType {
+        name: "FieldOption",
+        description: "An option of a choice field.",
+        admin: false,
+        fields: &[
+            field("id", "ID!", ""),
+            field("label", "String!", ""),
+            field("value", "String!", ""),
+            field("order", "Int!", ""),
+            field("capacity", "Int", ""),
+            field("remaining", "Int", "Places left, for options with a capacity."),
+            admin("isCorrect", "Boolean!", ""),
+            admin("points", "Int", ""),
+        ],
+    },
+    ObjectType {
+        name: "SubmissionReceipt",
+        description: "The answer to a submission.",
+        admin: false,
+        fields: &[
+            field("submissionId", "ID", "Absent when the submission wasn't recorded."),
+            field("status", "String!", "`submitted`, `waitlisted` or `not_recorded`."),
+            field("message", "String!", ""),
+            field("score", "Int", ""),
+            field("maxScore", "Int", ""),
+            field("resultKey", "String", ""),
+            field("result", "FormResult", ""),
+            field(
+                "duplicateToken",
+                "String",
+                "For forms with a duplicate guard, marks the browser as having responded.",
+            ),
+        ],
+    },
+    ObjectType {
+        name: "FormResult",
+        description: "A form result as shown to the respondent.",
+        admin: false,
+        fields: &[
+            field("key", "String!", ""),
+            field("title", "String!", ""),
+            field("description", "String", ""),
+        ],
+    },
+    ObjectType {
+        name: "Submission",
+        description: "A submission to a form.",
+        admin: true,
+        fields: &[
+            field("id", "ID!", ""),
+            field("formId", "ID!", ""),
+            field("status", "SubmissionStatus!", ""),
+            field("data", "JSON!", "The answers, by field name."),
+            field("metadata", "JSON", ""),
+            field("score", "Int", ""),
+            field("maxScore", "Int", ""),
+            field("resultKey", "String", ""),
+            field("instanceId", "ID", ""),
+            field("respondentId", "String", "The identified respondent's ID."),
+            field("legalHold", "Boolean!", ""),
+            field("createdAt", "DateTime!", ""),
+            field("updatedAt", "DateTime!", ""),
+            field("completedAt", "DateTime", ""),
+        ],
+    },
+    ObjectType {
+        name: "SubmissionPage",
+        description: "A page of submissions.",
+        admin: true,
+        fields: &[
+            field("submissions", "[Submission!]!", ""),
+            field("page", "Int!", "1-indexed page number."),
+            field("perPage", "Int!", ""),
+            field("total", "Int!", "Submissions matching across all pages."),
+        ],
+    },
+];
+
+/// Enum types and their values, with whether they are admin-only.
+const ENUMS: &[(&str, bool, &[&str])] = &[
+    ("FormStatus", true, &["DRAFT", "PUBLISHED", "ARCHIVED"]),
+    (
+        "SubmissionStatus",
+        true,
+        &["SUBMITTED", "WAITLISTED", "PREVIEW", "IN_PROGRESS"],
+    ),
+];
+
+/// Custom scalars and their descriptions.
+const SCALARS: &[(&str, &str)] = &[
+    ("JSON", "Any JSON value."),
+    ("DateTime", "An RFC 3339 timestamp."),
+];
+
+/// Returns the schema of `surface`.
+pub(crate) fn schema(surface: Surface) -> &'static Schema {
+    static PUBLIC: OnceLock<Schema> = OnceLock::new();
+    static ADMIN: OnceLock<Schema> = OnceLock::new();
+    let schema = match surface {
+        Surface::Public => &PUBLIC,
+        Surface::Admin => &ADMIN,
+    };
+    schema.get_or_init(|| build(surface))
+}
+
+fn build(surface: Surface) -> Schema {
+    let (query, mutation) = match surface {
+        Surface::Public => (&PUBLIC_QUERY, &PUBLIC_MUTATION),
+        Surface::Admin => (&ADMIN_QUERY, &ADMIN_MUTATION),
+    };
+    let mut builder = Schema::build(query.name, Some(mutation.name), None)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_FIELDS)
+        .extension(ErrorDetails);
+
+    let objects = OBJECT_TYPES
+        .iter()
+        .filter(|ty| surface == Surface::Admin || !ty.admin);
+    for ty in [query, mutation].into_iter().chain(objects) {
+        builder = builder.register(object(surface, ty));
+    }
+    for (name, admin, values) in ENUMS {
+        if !admin || surface == Surface::Admin {
+            builder = builder.register(Enum::new(*name).items(values.iter().copied()));
+        }
+    }
+    for (name, text) in SCALARS {
+        builder = builder.register(Scalar::new(*name).description(*text));
+    }
+    builder.finish().expect("GraphQL schema is valid")
+}
+
+/// Returns `ty` with the fields that are part of `surface`.
+fn object(surface: Surface, ty: &'static ObjectType) -> Object {
+    let mut object = Object::new(ty.name);
+    if !ty.description.is_empty() {
+        object = object.description(ty.description);
+    }
+    for def in ty.fields.iter().filter(|f| surface == Surface::Admin || !f.admin) {
+        let (parent, name) = (ty.name, def.name);
+        let mut field = Field::new(name, type_ref(def.ty), move |ctx| {
+            FieldFuture::new(resolve(ctx, parent, name))
+        });
+        if !def.description.is_empty() {
+            field = field.description(def.description);
+        }
+        for arg in def.args {
+            field = field.argument(InputValue::new(arg.name, type_ref(arg.ty)));
+        }
+        object = object.field(field);
+    }
+    object
+}
+
+/// Parses a type reference as written in SDL (e.g. `[Step!]!`).
+fn type_ref(ty: &str) -> TypeRef {
+    if let Some(inner) = ty.strip_suffix('!') {
+        TypeRef::NonNull(Box::new(type_ref(inner)))
+    } else if let Some(inner) = ty.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        TypeRef::List(Box::new(type_ref(inner)))
+    } else {
+        TypeRef::named(ty)
+    }
+}
+
+/// Resolves field `name` of type `ty` through [`resolvers::resolve`].
+async fn resolve<'a>(
+    ctx: ResolverContext<'a>,
+    ty: &'static str,
+    name: &'static str,
+) -> async_graphql::Result<Option<FieldValue<'a>>> {
+    let context = ctx.data::<Context>()?;
+    let node = ctx.parent_value.downcast_ref::<Node>().unwrap_or(&Node::Root);
+    let args = ctx
+        .args
+        .iter()
+        .map(|(name, value)| Ok((name.to_string(), value.as_value().clone().into_json()?)))
+        .collect::<async_graphql::Result<_>>()?;
+    let resolved = resolvers::resolve(context, node, ty, name, &Args::new(args))
+        .await
+        .map_err(graphql_error)?;
+    field_value(resolved)
+}
+
+fn field_value<'a>(resolved: Resolved) -> async_graphql::Result<Option<FieldValue<'a>>> {
+    Ok(match resolved {
+        Resolved::Value(Json::Null) => None,
+        Resolved::Value(value) => Some(FieldValue::value(Value::from_json(value)?)),
+        Resolved::Node(node) => Some(FieldValue::owned_any(*node)),
+        Resolved::List(items) => {
+            let items = items
+                .into_iter()
+                .map(|item| Ok(field_value(item)?.unwrap_or(FieldValue::NULL)))
+                .collect::<async_graphql::Result<Vec<_>>>()?;
+            Some(FieldValue::list(items))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_refs() {
+        assert_eq!(type_ref("[Step!]!").to_string(), "[Step!]!");
+        assert_eq!(type_ref("Form").to_string(), "Form");
+    }
+
+    #[test]
+    fn test_public_sdl_leaves_out_admin_fields() {
+        let sdl = schema(Surface::Public).sdl();
+        assert!(sdl.contains("form(slug: String!): Form\n"), "{sdl}");
+        assert!(sdl.contains("submit(slug: String!, data: JSON!): SubmissionReceipt!\n"));
+        assert!(!sdl.contains("createForm"));
+        assert!(!sdl.contains("type Submission {"));
+        assert!(!sdl.contains("correctAnswer"));
+        assert!(!sdl.contains("enum FormStatus"));
+
+        let admin = schema(Surface::Admin).sdl();
+        assert!(admin.contains("form(id: ID, slug: String): Form\n"));
+        assert!(admin.contains("type Submission {"));
+        assert!(admin.contains("enum SubmissionStatus {"));
+        assert!(admin.contains("scalar DateTime"));
+    }
+}
@@ -1,456 +1,3051 @@
 //! Axum route handlers for forms.
 
+mod requests;
 mod responses;
+mod state;
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 
 use axum::{
-    extract::{Path, State},
-    response::{Html, IntoResponse, Redirect},
+    extract::{Path, Query, State},
+    response::{AppendHeaders, Html, IntoResponse, Redirect, Response},
     Json,
 };
-use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use http::{header, HeaderMap, HeaderValue, StatusCode};
 use uuid::Uuid;
 
+use crate::branching::route_steps;
+use crate::captcha::{self, CaptchaProvider};
+use crate::analysis::analyze_answers;
+#[cfg(feature = "chaos")]
+use crate::chaos::Chaos;
+use crate::duplicates::Browser;
+#[cfg(feature = "admin")]
+use crate::duplicates::DuplicateGuard;
+use crate::entities::submission::{SubmissionMetadata, SubmissionStatus};
+#[cfg(feature = "admin")]
+use crate::entities::submission_note;
+#[cfg(feature = "admin")]
+use crate::entities::form::FormStatus;
 use crate::entities::{field, form, step, submission};
-use crate::error::FormError;
-use crate::extractors::{FormSubmission, RequestId};
-use crate::render::{FormJson, HtmlOptions, HtmlRenderer, JsonRenderer};
+use crate::error::{FormError, StepValidationErrors, ValidationErrors};
+use crate::extractors::{FormSubmission, RequestId, RequestMetadata};
+use crate::geocoding::label_locations;
+use crate::i18n::{localize_fields, localize_form, localize_steps};
+use crate::render::{
+    csp, ConfigScript, FormJson, HtmlOptions, HtmlRenderer, JsonRenderer, MULTI_STEP_CSS,
+};
+use crate::repository::{FieldWithOptions, StepWithFields};
 use crate::response::ApiResponse;
 #[cfg(feature = "admin")]
-use crate::services::{CreateFormInput, FormBuilder};
-use crate::validation::validate_submission;
+use crate::preview::{PreviewToken, DEFAULT_PREVIEW_TTL};
+use crate::schema::FieldValue;
+#[cfg(feature = "admin")]
+use crate::schema::NotificationDigest;
+use crate::services::{SubmissionService, DRAFT_FIELD};
+use crate::spam;
+#[cfg(feature = "admin")]
+use crate::services::{
+    hold_refused, parse_bound, CreateFormInput, CsvExport, Digest, FormAnalytics, FormSchedule,
+    InstallationStats, LegalHold, OptionDiff, OptionImport, SubmissionChanges, SubmissionQuery,
+    DEFAULT_UPCOMING,
+};
+#[cfg(all(feature = "admin", feature = "pdf"))]
+use crate::services::{SubmissionPdf, PDF_CONTENT_TYPE};
+#[cfg(all(feature = "admin", feature = "xlsx"))]
+use crate::services::XlsxExport;
+use crate::validation::{
+    is_field_visible, is_step_visible, run_field_validator, run_validators, validate_field_with,
+    validate_rows_with, validate_step_with, validate_submission_with,
+};
+use crate::versioning::ApiVersion;
 
+pub use requests::*;
 pub use responses::*;
+pub use state::{AnyFormState, HandlerConfig};
 
 /// Gets a form by slug and returns its JSON schema.
+///
+/// With `?steps=ids`, returns the form's skeleton (steps without their
+/// fields); with `?step={id}`, only that step with its fields, so clients of
+/// long forms can load each step as the respondent gets to it. With
+/// `?if_changed_since=`, only the steps changed since then, so long-lived
+/// clients can patch the schema they have. With `?locale=`, the form's
+/// translation for that locale. The output shape follows the negotiated
+/// [`ApiVersion`].
 pub async fn get_form_json(
     Path(slug): Path<String>,
-    State(db): State<DatabaseConnection>,
+    State(state): State<AnyFormState>,
+    version: ApiVersion,
+    Query(query): Query<FormJsonQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, FormError> {
+    let form = find_published_form(&state, &slug, &headers).await?;
+    let started = Instant::now();
+    let mut steps = state.repository.load_steps_with_fields(form.id).await?;
+    let locale = query.locale.as_deref();
+    localize_steps(&form, &mut steps, locale);
+    let json = JsonRenderer::render_steps(&localize_form(&form, locale), &steps);
+    observe_render(&state, "json", started);
+    if let Some(id) = query.step {
+        let step = json
+            .into_step(id)
+            .ok_or_else(|| FormError::StepNotFound(id.to_string()))?;
+        return Ok(Json(version.step_json(step)));
+    }
+    let json = match query.steps {
+        StepDetail::Full => json,
+        StepDetail::Ids => json.skeleton(),
+    };
+    let json = match query.if_changed_since {
+        Some(since) => json.changed_since(since),
+        None => json,
+    };
+    Ok(Json(version.form_json(json)))
+}
+
+/// Gets a form's embeddable `config.js`.
+///
+/// Serves an ES module by default, or a JSONP-style script when `?callback=`
+/// is given. Cached by the form's `updated_at`, so pages revalidate cheaply
+/// with `If-None-Match`.
+pub async fn get_form_config_js(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    version: ApiVersion,
+    Query(query): Query<ConfigJsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, FormError> {
+    let form = find_published_form(&state, &slug, &headers).await?;
+
+    let etag = format!(
+        "\"{}-{}\"",
+        form.updated_at.timestamp_millis(),
+        u8::from(form.is_closed())
+    );
+    let cache_headers = [
+        (header::CACHE_CONTROL, "public, max-age=60, must-revalidate".to_string()),
+        (header::ETAG, etag.clone()),
+        (
+            header::LAST_MODIFIED,
+            form.updated_at
+                .with_timezone(&chrono::Utc)
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string(),
+        ),
+    ];
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"));
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    let steps = state.repository.load_steps_with_fields(form.id).await?;
+    let json = version.form_json(JsonRenderer::render_steps(&form, &steps));
+    let script = ConfigScript::new(&json, &slug, version.path_prefix());
+    let body = match query.callback.as_deref() {
+        Some(callback) => script.jsonp(callback)?,
+        None => script.module(),
+    };
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/javascript; charset=utf-8")],
+        cache_headers,
+        body,
+    )
+        .into_response())
+}
+
+/// Gets the currently open instance of a recurring form.
+///
+/// Fails with `409 INSTANCE_CLOSED` between instances and for forms without
+/// a recurrence rule.
+pub async fn get_current_instance(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    headers: HeaderMap,
+) -> Result<ApiResponse<FormInstanceData>, ApiResponse<()>> {
+    let form = find_published_form(&state, &slug, &headers)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    let instance = state
+        .repository
+        .current_instance(&form)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::InstanceClosed))?;
+
+    Ok(ApiResponse::ok(FormInstanceData::from(instance)).with_request_id(request_id))
+}
+
+/// Gets a form by slug and returns its HTML, or with `?print=true` a blank
+/// form to print. With `?locale=`, the form is shown in its translation for
+/// that locale and posts back with it.
+pub async fn get_form_html(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    version: ApiVersion,
+    Query(query): Query<FormHtmlQuery>,
+    headers: HeaderMap,
+) -> Result<Response, FormError> {
+    let form = find_published_form(&state, &slug, &headers).await?;
+    let browser = match check_duplicate(&state, &form, &headers) {
+        Ok(browser) => browser,
+        Err(FormError::AlreadyResponded) => return Ok(already_responded(&form)),
+        Err(e) => return Err(e),
+    };
+    if has_responded(&state, &form, &headers).await? {
+        return Ok(already_responded(&form));
+    }
+
+    let nonce = csp::generate_nonce();
+    let options = form_options(&state, &form, &slug, version, query.locale.as_deref())
+        .csp_nonce(&nonce)
+        .print_mode(query.print);
+    let started = Instant::now();
+    let html = render_html(&state, &form, &options, &HashMap::new(), &ValidationErrors::new())
+        .await?;
+    observe_render(&state, "html", started);
+    let cookies = browser
+        .map(|b| b.cookies(&state.config.spam_secret, form.id, false))
+        .unwrap_or_default();
+    Ok(with_cookies(cookies, with_csp(&nonce, captcha_provider(&form), Html(html))))
+}
+
+/// Submits a form.
+///
+/// Full forms and options reject with `409`, or accept the submission onto
+/// the waitlist when the form has `waitlist` enabled. With `?locale=`,
+/// validation errors are worded in that locale.
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_form(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    RequestMetadata(metadata): RequestMetadata,
+    Query(query): Query<LocaleQuery>,
+    headers: HeaderMap,
+    #[cfg(feature = "chaos")] chaos: Chaos,
+    FormSubmission(data): FormSubmission,
+) -> Result<Response, ApiResponse<()>> {
+    #[cfg(feature = "chaos")]
+    let webhook_failure = chaos.webhook_failure();
+    #[cfg(not(feature = "chaos"))]
+    let webhook_failure = false;
+
+    let locale = query.locale.as_deref();
+    let (created, cookies) = accept_submission(
+        &state,
+        &slug,
+        data,
+        metadata,
+        &headers,
+        locale,
+        webhook_failure,
+    )
+    .await
+    .map_err(ApiResponse::<()>::from)?;
+    Ok(with_cookies(cookies, created.with_request_id(request_id)))
+}
+
+/// Accepts a submission to the published form `slug`, answering as
+/// `POST /forms/{slug}` does, with the cookies to set.
+pub(crate) async fn accept_submission(
+    state: &AnyFormState,
+    slug: &str,
+    mut data: HashMap<String, FieldValue>,
+    metadata: SubmissionMetadata,
+    headers: &HeaderMap,
+    locale: Option<&str>,
+    webhook_failure: bool,
+) -> Result<(ApiResponse<SubmissionCreated>, Vec<HeaderValue>), FormError> {
+    let form = find_open_form(state, slug, headers).await?;
+    let draft = take_draft(&mut data);
+    check_rate_limit(state, &form, &metadata)?;
+    check_spam(state, &form, &mut data, false)?;
+    check_captcha(state, &form, &mut data, &metadata).await?;
+    let browser = check_duplicate(state, &form, headers)?;
+
+    let metadata = collect_metadata(state, &form, metadata, headers).await?;
+    let metadata = with_browser(metadata, browser);
+    let stored = store_submission(state, &form, &data, metadata, locale, webhook_failure).await?;
+    discard_draft(state, &form, draft).await;
+    let mut created = submission_created(state, &localize_form(&form, locale), stored).await;
+    let cookies = mark_responded(state, &form, browser, &mut created);
+    Ok((created, cookies))
+}
+
+/// Submits a form and redirects (for SSR).
+///
+/// With `?locale=`, a form re-rendered with errors, and the success page,
+/// stay in that locale.
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_form_redirect(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    version: ApiVersion,
+    RequestMetadata(metadata): RequestMetadata,
+    Query(query): Query<LocaleQuery>,
+    headers: HeaderMap,
+    #[cfg(feature = "chaos")] chaos: Chaos,
+    FormSubmission(mut data): FormSubmission,
 ) -> Result<impl IntoResponse, FormError> {
-    let form = form::Entity::find_by_slug(&db, &slug)
+    let form = find_published_form(&state, &slug, &headers).await?;
+    let draft = take_draft(&mut data);
+    let locale = query.locale.as_deref();
+    if form.is_closed() {
+        let closed = HtmlRenderer::render_closed(&localize_form(&form, locale));
+        return Ok(Html(closed).into_response());
+    }
+    check_rate_limit(&state, &form, &metadata)?;
+    check_spam(&state, &form, &mut data, true)?;
+    check_captcha(&state, &form, &mut data, &metadata).await?;
+    let browser = match check_duplicate(&state, &form, &headers) {
+        Ok(browser) => browser,
+        Err(FormError::AlreadyResponded) => return Ok(already_responded(&form)),
+        Err(e) => return Err(e),
+    };
+
+    let metadata = collect_metadata(&state, &form, metadata, &headers).await?;
+    let metadata = with_browser(metadata, browser);
+
+    #[cfg(feature = "chaos")]
+    let webhook_failure = chaos.webhook_failure();
+    #[cfg(not(feature = "chaos"))]
+    let webhook_failure = false;
+
+    // Invalid data and full options re-render the form with errors
+    let stored = store_submission(&state, &form, &data, metadata, locale, webhook_failure).await;
+    let stored = match stored {
+        Ok(stored) => stored,
+        Err(FormError::FormClosed | FormError::FormNotOpen) => {
+            let closed = HtmlRenderer::render_closed(&localize_form(&form, locale));
+            return Ok(Html(closed).into_response());
+        }
+        Err(FormError::AlreadyResponded) => return Ok(already_responded(&form)),
+        Err(FormError::ValidationFailed(errors)) => {
+            return render_with_errors(&state, &form, &slug, version, locale, &data, &errors).await;
+        }
+        Err(FormError::OptionFull { field, option }) => {
+            let mut errors = ValidationErrors::new();
+            errors.add(field, format!("{option} is full"));
+            return render_with_errors(&state, &form, &slug, version, locale, &data, &errors).await;
+        }
+        Err(e) => return Err(e),
+    };
+    discard_draft(&state, &form, draft).await;
+
+    let cookies = browser
+        .map(|b| b.cookies(&state.config.spam_secret, form.id, true))
+        .unwrap_or_default();
+
+    // Outside the sample: thank the respondent without recording anything
+    let Some(saved) = stored else {
+        return Ok(with_cookies(cookies, Redirect::to(&success_url(&form, &slug, version, locale))));
+    };
+    notify_created(&state, &form, &saved).await;
+
+    if saved.is_waitlisted() {
+        let mut url = format!("{}/success?status=waitlisted", form_url(version, &slug));
+        if let Some(locale) = locale {
+            url = format!("{url}&locale={}", encode_query(locale));
+        }
+        return Ok(with_cookies(cookies, Redirect::to(&url)));
+    }
+
+    Ok(with_cookies(cookies, Redirect::to(&success_url(&form, &slug, version, locale))))
+}
+
+/// Re-renders a submitted form with its values and errors.
+async fn render_with_errors(
+    state: &AnyFormState,
+    form: &form::Model,
+    key: &str,
+    version: ApiVersion,
+    locale: Option<&str>,
+    data: &HashMap<String, FieldValue>,
+    errors: &ValidationErrors,
+) -> Result<Response, FormError> {
+    let nonce = csp::generate_nonce();
+    let options = form_options(state, form, key, version, locale).csp_nonce(&nonce);
+    let started = Instant::now();
+    let html = render_html(state, form, &options, data, errors).await?;
+    observe_render(state, "html", started);
+    Ok(with_csp(&nonce, captcha_provider(form), Html(html)).into_response())
+}
+
+/// Renders a form's page from its steps as the state's repository loads
+/// them.
+async fn render_html(
+    state: &AnyFormState,
+    form: &form::Model,
+    options: &HtmlOptions,
+    values: &HashMap<String, FieldValue>,
+    errors: &ValidationErrors,
+) -> Result<String, FormError> {
+    let steps = state.repository.load_steps_with_fields(form.id).await?;
+    Ok(HtmlRenderer::render_steps(form, steps, options, values, errors))
+}
+
+/// Validates a single field's value, for inline feedback while typing.
+///
+/// Runs the field's own rules and, for `server_validate` fields, the
+/// registered [`FieldValidator`](crate::FieldValidator), then the named
+/// validators the field lists. Answers `200` with
+/// the outcome either way, and echoes the request's `seq` so clients that
+/// debounce calls can drop stale responses. Nothing is stored. With
+/// `?locale=`, errors are worded in that locale.
+pub async fn validate_form_field(
+    Path((slug, field_name)): Path<(String, String)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Query(query): Query<LocaleQuery>,
+    headers: HeaderMap,
+    Json(input): Json<FieldValidationInput>,
+) -> Result<ApiResponse<FieldValidationResult>, ApiResponse<()>> {
+    let form = find_published_form(&state, &slug, &headers)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    let steps = state
+        .repository
+        .load_steps_with_fields(form.id)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    let FieldWithOptions { field, fields: sub_fields, .. } = steps
+        .into_iter()
+        .flat_map(|s| s.fields)
+        .find(|f| f.field.name == field_name || f.field.id.to_string() == field_name)
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::FieldNotFound(field_name)))?;
+
+    let locale = query.locale.as_deref();
+    let messages = state.config.messages.messages(locale);
+    let mut field = [field];
+    localize_fields(&form, &mut field, locale);
+    let [field] = field;
+
+    let value = input.value.filter(|v| !v.is_empty());
+    let mut errors = validate_field_with(&field, value.as_ref(), messages);
+    if field.is_repeater() {
+        let mut sub_fields: Vec<field::Model> = sub_fields.into_iter().map(|f| f.field).collect();
+        localize_fields(&form, &mut sub_fields, locale);
+        let mut rows: Vec<_> = validate_rows_with(&field, &sub_fields, value.as_ref(), messages)
+            .errors
+            .into_iter()
+            .collect();
+        rows.sort();
+        errors.extend(rows.into_iter().flat_map(|(_, messages)| messages));
+    }
+
+    if let (Some(validator), Some(value)) = (&state.config.field_validator, &value) {
+        if errors.is_empty() && field.validation().server_validate {
+            errors = validator.validate(&form, &field, value, &input.values).await;
+        }
+    }
+    if let Some(value) = value.as_ref().filter(|_| errors.is_empty()) {
+        errors = state.config.validators.validate(&form, &field, value, &input.values).await;
+    }
+
+    Ok(ApiResponse::ok(FieldValidationResult {
+        field: field.name,
+        valid: errors.is_empty(),
+        errors,
+        seq: input.seq,
+    })
+    .with_request_id(request_id))
+}
+
+/// Validates submitted data against the fields' rules and the registered
+/// field and named validators.
+///
+/// Fields on steps hidden by their condition or jumped over, and fields
+/// hidden by their own condition, are skipped. `sub_fields` are checked
+/// against each row of their repeatable group. Messages are worded in
+/// `locale`, naming the fields by their translated labels.
+async fn validate_data(
+    state: &AnyFormState,
+    form: &form::Model,
+    steps: &[step::Model],
+    fields: &[field::Model],
+    sub_fields: &[field::Model],
+    data: &HashMap<String, FieldValue>,
+    locale: Option<&str>,
+) -> ValidationErrors {
+    let route: HashSet<Uuid> = route_steps(steps, fields, data).into_iter().collect();
+    let mut fields: Vec<field::Model> = fields
+        .iter()
+        .filter(|f| route.contains(&f.step_id) && is_field_visible(f, data))
+        .cloned()
+        .collect();
+    let mut sub_fields = sub_fields.to_vec();
+    localize_fields(form, &mut fields, locale);
+    localize_fields(form, &mut sub_fields, locale);
+
+    let messages = state.config.messages.messages(locale);
+    let mut errors =
+        validate_submission_with(&[fields.as_slice(), &sub_fields].concat(), data, messages);
+    if let Some(validator) = &state.config.field_validator {
+        run_field_validator(validator.as_ref(), form, &fields, data, &mut errors).await;
+    }
+    run_validators(&state.config.validators, form, &fields, data, &mut errors).await;
+    errors
+}
+
+/// Answers a stored submission, notifying the event hooks of it.
+///
+/// Submissions outside the form's sample (`None`) are thanked without an ID.
+async fn submission_created(
+    state: &AnyFormState,
+    form: &form::Model,
+    stored: Option<submission::Model>,
+) -> ApiResponse<SubmissionCreated> {
+    let settings = form.settings();
+    let success_message = || {
+        settings
+            .success_message
+            .clone()
+            .unwrap_or_else(|| "Form submitted successfully".to_string())
+    };
+
+    // Outside the sample: thank the respondent without recording anything
+    let Some(saved) = stored else {
+        return ApiResponse::ok(SubmissionCreated {
+            submission_id: None,
+            status: "not_recorded".to_string(),
+            message: success_message(),
+            score: None,
+            max_score: None,
+            result_key: None,
+            result: None,
+            duplicate_token: None,
+        });
+    };
+    notify_created(state, form, &saved).await;
+    let result = result_data(state, form, saved.result_key.as_deref()).await;
+
+    let message = if saved.is_waitlisted() {
+        settings.waitlist_message_or_default().to_string()
+    } else {
+        success_message()
+    };
+
+    ApiResponse::created(SubmissionCreated {
+        submission_id: Some(saved.id.to_string()),
+        status: saved.status,
+        message,
+        score: saved.score,
+        max_score: saved.max_score,
+        result_key: saved.result_key,
+        result,
+        duplicate_token: None,
+    })
+}
+
+/// Looks up the result a submission maps to, for answering the respondent.
+///
+/// A failed lookup leaves the result out rather than failing a submission
+/// that is already stored.
+async fn result_data(
+    state: &AnyFormState,
+    form: &form::Model,
+    key: Option<&str>,
+) -> Option<ResultData> {
+    let key = key?;
+    match state.repository.find_result(form.id, key).await {
+        Ok(result) => result.map(ResultData::from),
+        Err(error) => {
+            tracing::warn!(form = %form.slug, key, %error, "failed to look up result");
+            None
+        }
+    }
+}
+
+/// Starts a submission to be filled in step by step.
+///
+/// Answers the submission's ID, the key for saving its steps and resuming it
+/// later, and the first step to fill in.
+pub async fn start_submission(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    RequestMetadata(metadata): RequestMetadata,
+    headers: HeaderMap,
+) -> Result<ApiResponse<SubmissionProgress>, ApiResponse<()>> {
+    let form = find_open_form(&state, &slug, &headers)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    let browser = check_duplicate(&state, &form, &headers).map_err(ApiResponse::<()>::from)?;
+    let metadata = collect_metadata(&state, &form, metadata, &headers)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    let metadata = with_browser(metadata, browser);
+
+    let started = state
+        .config
+        .retry
+        .run(|| state.repository.start_submission(&form, metadata.clone()))
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::created(SubmissionProgress::from(started)).with_request_id(request_id))
+}
+
+/// Gets an in-progress submission, to resume it.
+pub async fn get_submission_progress(
+    Path((slug, id)): Path<(String, Uuid)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    headers: HeaderMap,
+) -> Result<ApiResponse<SubmissionProgress>, ApiResponse<()>> {
+    let form = find_published_form(&state, &slug, &headers)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    let sub = state
+        .repository
+        .find_submission(id)
+        .await
+        .and_then(|sub| in_progress(&form, id, sub))
+        .map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::ok(SubmissionProgress::from(decrypted(&state, sub))).with_request_id(request_id))
+}
+
+/// Saves one step of an in-progress submission.
+///
+/// Answers to fields outside the step are ignored. The step's fields are
+/// validated against the answers saved so far plus the new ones; errors
+/// answer `VALIDATION_FAILED` grouped under the step.
+/// On success the submission moves on to the next visible step. With
+/// `?locale=`, errors are worded in that locale.
+pub async fn save_submission_step(
+    Path((slug, id)): Path<(String, Uuid)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Query(query): Query<LocaleQuery>,
+    headers: HeaderMap,
+    Json(input): Json<SaveStepInput>,
+) -> Result<ApiResponse<SubmissionProgress>, ApiResponse<()>> {
+    let form = find_open_form(&state, &slug, &headers)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    let saved = state
+        .config
+        .retry
+        .run(|| async {
+            let txn = state.repository.begin().await?;
+            let sub = in_progress(&form, id, txn.find_submission(id).await?)?;
+            let step_id = input
+                .step_id
+                .or(sub.current_step_id)
+                .ok_or_else(|| FormError::InvalidData("No step to save".to_string()))?;
+            let loaded = txn.load_steps_with_fields(form.id).await?;
+            let StepWithFields { step, fields: step_fields } = loaded
+                .iter()
+                .find(|s| s.step.id == step_id)
+                .cloned()
+                .ok_or_else(|| FormError::StepNotFound(step_id.to_string()))?;
+
+            let fields: Vec<field::Model> = step_fields.iter().map(|f| f.field.clone()).collect();
+            // Only the step's own answers are saved; the rest would skip
+            // its validation until completion
+            let step_data: HashMap<String, FieldValue> = input
+                .data
+                .iter()
+                .filter(|(key, _)| {
+                    fields.iter().any(|f| f.name == **key || f.id.to_string() == **key)
+                })
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            let mut answers = decrypted(&state, sub.clone()).data_map();
+            answers.extend(step_data.iter().map(|(k, v)| (k.clone(), v.clone())));
+            let sub_fields: Vec<field::Model> = step_fields
+                .iter()
+                .flat_map(|f| f.fields.iter().map(|sub| sub.field.clone()))
+                .collect();
+            let locale = query.locale.as_deref();
+            let errors =
+                validate_step_data(&state, &form, &step, &fields, &sub_fields, &answers, locale)
+                    .await;
+            if !errors.is_empty() {
+                return Err(FormError::StepValidationFailed(errors));
+            }
+
+            let key = state.config.encryption_key.as_ref();
+            let saved = txn.save_step(sub, step.id, &step_data, key).await?;
+            txn.commit().await?;
+            Ok(saved)
+        })
+        .await;
+    if let Err(e) = &saved {
+        record_validation_failure(&state, &form, e);
+    }
+    let saved = saved.map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::ok(SubmissionProgress::from(decrypted(&state, saved))).with_request_id(request_id))
+}
+
+/// Completes an in-progress submission.
+///
+/// All saved answers are validated as in [`submit_form`], and the submission
+/// then takes its place (or a waitlist spot) and is answered the same way,
+/// in the locale given by `?locale=`.
+pub async fn complete_submission(
+    Path((slug, id)): Path<(String, Uuid)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Query(query): Query<LocaleQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ApiResponse<()>> {
+    let form = find_open_form(&state, &slug, &headers)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    let browser = check_duplicate(&state, &form, &headers).map_err(ApiResponse::<()>::from)?;
+
+    // Geocoded and analyzed up front: retrying the transaction below
+    // shouldn't repeat the calls
+    let enrichment = enrich_saved_answers(&state, &form, id).await;
+
+    let stored = state
+        .config
+        .retry
+        .run(|| async {
+            let txn = state.repository.begin().await?;
+            let sub = in_progress(&form, id, txn.find_submission(id).await?)?;
+            let loaded = txn.load_steps_with_fields(form.id).await?;
+            let steps: Vec<step::Model> = loaded.iter().map(|s| s.step.clone()).collect();
+            let fields = StepWithFields::all_fields(&loaded);
+            let sub_fields = StepWithFields::all_sub_fields(&loaded);
+            let data = decrypted(&state, sub.clone()).data_map();
+            let locale = query.locale.as_deref();
+            let errors =
+                validate_data(&state, &form, &steps, &fields, &sub_fields, &data, locale).await;
+            if !errors.is_empty() {
+                return Err(FormError::ValidationFailed(errors));
+            }
+
+            // Outside the sample: the answers are not kept
+            if !SubmissionService::is_sampled(&form) {
+                txn.discard_submission(sub.id).await?;
+                txn.commit().await?;
+                return Ok(None);
+            }
+
+            let mut sub = sub;
+            // Unless the answers changed since they were enriched
+            if let Some(enrichment) = enrichment.as_ref().filter(|e| e.data == sub.data) {
+                if let Some(stored) = sub.data.as_object_mut() {
+                    stored.extend(
+                        enrichment
+                            .labelled
+                            .iter()
+                            .map(|(k, v)| (k.clone(), serde_json::Value::from(v))),
+                    );
+                }
+                if let Some(metadata) = &enrichment.metadata {
+                    sub.metadata = serde_json::to_value(metadata).ok();
+                }
+            }
+            let key = state.config.encryption_key.as_ref();
+            let completed = txn.complete_submission(&form, &fields, sub, key).await?;
+            txn.commit().await?;
+            Ok(Some(completed))
+        })
+        .await;
+    record_submission(&state, &form, &stored);
+    let stored = stored.map_err(ApiResponse::<()>::from)?;
+
+    let localized = localize_form(&form, query.locale.as_deref());
+    let mut created = submission_created(&state, &localized, stored).await;
+    let cookies = mark_responded(&state, &form, browser, &mut created);
+    Ok(with_cookies(cookies, created.with_request_id(request_id)))
+}
+
+/// What the reverse geocoder and text analyzer add to an in-progress
+/// submission's saved answers.
+struct Enrichment {
+    /// The stored answers it was worked out from.
+    data: serde_json::Value,
+    /// Newly labelled location answers, as stored.
+    labelled: HashMap<String, FieldValue>,
+    /// The submission's metadata with the text analysis.
+    metadata: Option<SubmissionMetadata>,
+}
+
+/// Labels the locations and analyzes the text of in-progress submission
+/// `id`'s saved answers, with the router's reverse geocoder and text
+/// analyzer.
+///
+/// `None` when neither is registered, or the submission or the form's
+/// fields can't be read; completing the submission reports why.
+async fn enrich_saved_answers(
+    state: &AnyFormState,
+    form: &form::Model,
+    id: Uuid,
+) -> Option<Enrichment> {
+    if state.config.reverse_geocoder.is_none() && state.config.text_analyzer.is_none() {
+        return None;
+    }
+    let sub = in_progress(form, id, state.repository.find_submission(id).await.ok()?).ok()?;
+    let loaded = state.repository.load_steps_with_fields(form.id).await.ok()?;
+    let fields = StepWithFields::all_fields(&loaded);
+    let answers = decrypted(state, sub.clone()).data_map();
+
+    // Only the labelled answers change; the rest are stored as they are
+    let mut labelled = geocode_locations(state, &fields, &answers).await.into_owned();
+    labelled.retain(|name, value| match (value, answers.get(name)) {
+        (FieldValue::Location(after), Some(FieldValue::Location(before))) => {
+            after.label != before.label
+        }
+        _ => false,
+    });
+    let labelled = encrypt_answers(state, &fields, &labelled).into_owned();
+
+    let metadata = sub.metadata.and_then(|m| serde_json::from_value(m).ok());
+    let metadata = analyze_text(state, &fields, &answers, metadata).await;
+    Some(Enrichment {
+        data: sub.data,
+        labelled,
+        metadata,
+    })
+}
+
+/// Validates the answers to one step against its fields' rules and the
+/// registered field and named validators.
+///
+/// Hidden steps and fields are skipped. `sub_fields` are checked against
+/// each row of their repeatable group. Messages are worded in `locale`.
+async fn validate_step_data(
+    state: &AnyFormState,
+    form: &form::Model,
+    step: &step::Model,
+    fields: &[field::Model],
+    sub_fields: &[field::Model],
+    data: &HashMap<String, FieldValue>,
+    locale: Option<&str>,
+) -> StepValidationErrors {
+    let mut fields = fields.to_vec();
+    let mut sub_fields = sub_fields.to_vec();
+    localize_fields(form, &mut fields, locale);
+    localize_fields(form, &mut sub_fields, locale);
+
+    let messages = state.config.messages.messages(locale);
+    let mut errors = validate_step_with(
+        step,
+        &[fields.as_slice(), &sub_fields].concat(),
+        data,
+        messages,
+    );
+    if is_step_visible(step, data) {
+        let fields: Vec<field::Model> = fields
+            .iter()
+            .filter(|f| is_field_visible(f, data))
+            .cloned()
+            .collect();
+        if let Some(validator) = &state.config.field_validator {
+            run_field_validator(validator.as_ref(), form, &fields, data, &mut errors).await;
+        }
+        run_validators(&state.config.validators, form, &fields, data, &mut errors).await;
+    }
+
+    let mut step_errors = StepValidationErrors::new();
+    for (field, messages) in errors.errors {
+        for message in messages {
+            step_errors.add(step.id.to_string(), field.clone(), message);
+        }
+    }
+    step_errors
+}
+
+/// Finds the form `key` names, with the router's
+/// [`FormResolver`](crate::FormResolver) or else by slug.
+async fn resolve_form(
+    state: &AnyFormState,
+    key: &str,
+    headers: &HeaderMap,
+) -> Result<form::Model, FormError> {
+    state
+        .form_resolver()
+        .resolve(key, headers, state.repository.as_ref())
         .await?
-        .ok_or_else(|| FormError::NotFound(slug))?;
+        .filter(|form| state.serves(form))
+        .ok_or_else(|| FormError::NotFound(key.to_string()))
+}
+
+/// Finds the form `key` names, if it is published and not deleted.
+async fn find_published_form(
+    state: &AnyFormState,
+    key: &str,
+    headers: &HeaderMap,
+) -> Result<form::Model, FormError> {
+    let form = resolve_form(state, key, headers).await?;
 
     if form.is_deleted() {
         return Err(FormError::FormDeleted);
     }
+    if !form.is_published() {
+        return Err(FormError::NotFound(form.slug));
+    }
+    Ok(form)
+}
+
+/// Finds the form `key` names, if it is published and accepts submissions.
+async fn find_open_form(
+    state: &AnyFormState,
+    key: &str,
+    headers: &HeaderMap,
+) -> Result<form::Model, FormError> {
+    let form = find_published_form(state, key, headers).await?;
+    ensure_open(&form)?;
+    Ok(form)
+}
+
+/// Rejects submissions to a form that hasn't opened yet or has closed.
+fn ensure_open(form: &form::Model) -> Result<(), FormError> {
+    if form.is_upcoming() {
+        return Err(FormError::FormNotOpen);
+    }
+    if form.is_closed() {
+        return Err(FormError::FormClosed);
+    }
+    Ok(())
+}
+
+/// Keeps the submission `id` found, if it is an active, in-progress
+/// submission of `form`.
+fn in_progress(
+    form: &form::Model,
+    id: Uuid,
+    sub: Option<submission::Model>,
+) -> Result<submission::Model, FormError> {
+    sub.filter(|sub| sub.form_id == form.id && !sub.is_deleted() && sub.is_in_progress())
+        .ok_or_else(|| FormError::SubmissionNotFound(id.to_string()))
+}
+
+/// Returns the success page, in `locale`, or custom redirect URL of the
+/// form `key` names.
+fn success_url(form: &form::Model, key: &str, version: ApiVersion, locale: Option<&str>) -> String {
+    form.settings().redirect_url.unwrap_or_else(|| {
+        let url = format!("{}/success", form_url(version, key));
+        match locale {
+            Some(locale) => format!("{url}?locale={}", encode_query(locale)),
+            None => url,
+        }
+    })
+}
+
+/// Percent-encodes a query parameter value.
+fn encode_query(value: &str) -> String {
+    form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+/// Returns the URL of the page of the form `key` names under the router's
+/// API prefix.
+fn form_url(version: ApiVersion, key: &str) -> String {
+    format!("{}/forms/{key}", version.path_prefix())
+}
+
+/// HTML options for the page in `locale` of the form `key` names, posting
+/// back to the router.
+fn form_options(
+    state: &AnyFormState,
+    form: &form::Model,
+    key: &str,
+    version: ApiVersion,
+    locale: Option<&str>,
+) -> HtmlOptions {
+    let mut options = state.config.html_options();
+    if form.settings().action_url.is_none() {
+        let mut action = format!("{}/submit", form_url(version, key));
+        if let Some(locale) = locale {
+            action = format!("{action}?locale={}", encode_query(locale));
+        }
+        options = options.action(action);
+    }
+    match locale {
+        Some(locale) => options.locale(locale),
+        None => options,
+    }
+}
+
+/// Success page after form submission.
+///
+/// `?status=waitlisted` shows the form's waitlist message instead, and
+/// `?locale=` the form's translated messages.
+pub async fn form_success(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    Query(query): Query<SuccessQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, FormError> {
+    let form = resolve_form(&state, &slug, &headers).await?;
+
+    if !form.is_published() {
+        return Err(FormError::NotFound(form.slug));
+    }
+
+    let form = localize_form(&form, query.locale.as_deref());
+    let settings = form.settings();
+    let message = if query.status == Some(SubmissionStatus::Waitlisted) {
+        settings.waitlist_message_or_default().to_string()
+    } else {
+        settings
+            .success_message
+            .unwrap_or_else(|| "Thank you! Your submission has been received.".to_string())
+    };
+
+    Ok(Html(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><title>Success</title></head>
+<body>
+<h1>Success</h1>
+{}</body>
+</html>"#,
+        HtmlRenderer::render_success(&form, &message)
+    )))
+}
+
+/// Renders a form through a signed preview link (`?token=`), drafts included.
+///
+/// The page posts back to [`submit_preview_redirect`], so submissions land in
+/// the discardable preview bucket. The WASM client is not loaded, since it
+/// would submit to the live endpoint.
+pub async fn preview_form(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    version: ApiVersion,
+    Query(query): Query<PreviewQuery>,
+) -> Result<impl IntoResponse, FormError> {
+    let form = find_preview_form(&state, &slug, &query.token).await?;
+
+    let nonce = csp::generate_nonce();
+    let options = preview_options(&state, &form, version, &query.token).csp_nonce(&nonce);
+    let html = render_html(&state, &form, &options, &HashMap::new(), &ValidationErrors::new())
+        .await?;
+    Ok(with_csp(&nonce, None, Html(html)))
+}
+
+/// Submits a form into the preview bucket.
+///
+/// Validates like [`submit_form`], but the submission takes no place within
+/// limits or option capacity, fires no events and is never counted.
+pub async fn submit_preview(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Query(query): Query<PreviewQuery>,
+    FormSubmission(data): FormSubmission,
+) -> Result<ApiResponse<SubmissionCreated>, ApiResponse<()>> {
+    let form = find_preview_form(&state, &slug, &query.token)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    let saved = store_preview(&state, &form, &data)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    let result = result_data(&state, &form, saved.result_key.as_deref()).await;
+
+    Ok(ApiResponse::created(SubmissionCreated {
+        submission_id: Some(saved.id.to_string()),
+        status: saved.status,
+        message: form
+            .settings()
+            .success_message
+            .unwrap_or_else(|| "Form submitted successfully".to_string()),
+        score: saved.score,
+        max_score: saved.max_score,
+        result_key: saved.result_key,
+        result,
+        duplicate_token: None,
+    })
+    .with_request_id(request_id))
+}
+
+/// Submits a form into the preview bucket (for the SSR preview page).
+///
+/// Re-renders the preview with errors when invalid, and shows the success
+/// message in place otherwise.
+pub async fn submit_preview_redirect(
+    Path(slug): Path<String>,
+    State(state): State<AnyFormState>,
+    version: ApiVersion,
+    Query(query): Query<PreviewQuery>,
+    FormSubmission(data): FormSubmission,
+) -> Result<impl IntoResponse, FormError> {
+    let form = find_preview_form(&state, &slug, &query.token).await?;
+
+    match store_preview(&state, &form, &data).await {
+        Ok(_) => {}
+        Err(FormError::ValidationFailed(errors)) => {
+            let nonce = csp::generate_nonce();
+            let options = preview_options(&state, &form, version, &query.token).csp_nonce(&nonce);
+            let html = render_html(&state, &form, &options, &data, &errors).await?;
+            return Ok(with_csp(&nonce, None, Html(html)).into_response());
+        }
+        Err(e) => return Err(e),
+    }
+
+    let restart_url = preview_url(version, &form, &query.token);
+    Ok(Html(HtmlRenderer::render_preview_submitted(&form, &restart_url)).into_response())
+}
+
+/// Loads the form a preview token was issued for.
+async fn find_preview_form(
+    state: &AnyFormState,
+    slug: &str,
+    token: &str,
+) -> Result<form::Model, FormError> {
+    let token = state
+        .config
+        .preview_secret
+        .as_ref()
+        .ok_or(FormError::InvalidPreviewToken)?
+        .verify(token)?;
+
+    let form = state
+        .repository
+        .find_form_by_slug(slug)
+        .await?
+        .filter(|form| state.serves(form))
+        .ok_or_else(|| FormError::NotFound(slug.to_string()))?;
+
+    if form.is_deleted() {
+        return Err(FormError::FormDeleted);
+    }
+    if form.id != token.form_id {
+        return Err(FormError::InvalidPreviewToken);
+    }
+    Ok(form)
+}
+
+/// Returns the preview page URL for a signed token.
+fn preview_url(version: ApiVersion, form: &form::Model, token: &str) -> String {
+    format!(
+        "{}/forms/{}/preview?token={token}",
+        version.path_prefix(),
+        form.slug
+    )
+}
+
+/// Returns the HTML options for preview pages.
+fn preview_options(
+    state: &AnyFormState,
+    form: &form::Model,
+    version: ApiVersion,
+    token: &str,
+) -> HtmlOptions {
+    let mut options = state.config.html_options().preview(true).action(format!(
+        "{}/forms/{}/preview/submit?token={token}",
+        version.path_prefix(),
+        form.slug
+    ));
+    options.wasm_base_url = None;
+    options
+}
+
+/// Serves the built-in multi-step stylesheet.
+///
+/// Lets pages link the CSS (see
+/// [`HtmlOptions::styles_href`](crate::render::HtmlOptions::styles_href))
+/// instead of inlining it.
+pub async fn stylesheet() -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, "text/css; charset=utf-8"),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        MULTI_STEP_CSS,
+    )
+}
+
+/// Attaches a `Content-Security-Policy` header matching the given nonce,
+/// allowing the form's CAPTCHA widget if it has one.
+fn with_csp(
+    nonce: &str,
+    captcha: Option<CaptchaProvider>,
+    response: impl IntoResponse,
+) -> impl IntoResponse {
+    let policy = csp::content_security_policy_with_captcha(nonce, captcha);
+    ([(header::CONTENT_SECURITY_POLICY, policy)], response)
+}
+
+/// Returns the provider of the form's CAPTCHA, if it has one.
+fn captcha_provider(form: &form::Model) -> Option<CaptchaProvider> {
+    form.settings().captcha.map(|c| c.provider)
+}
+
+/// Counts a submission against the form's rate limits; see
+/// [`crate::rate_limit`]. The IP address is only held in memory, so this
+/// applies to anonymous forms too.
+fn check_rate_limit(
+    state: &AnyFormState,
+    form: &form::Model,
+    metadata: &SubmissionMetadata,
+) -> Result<(), FormError> {
+    state
+        .config
+        .rate_limiter
+        .check(form.id, metadata.ip.as_deref(), form.settings().rate_limits)
+}
+
+/// Checks a submission against the form's spam protection, removing the
+/// spam-protection inputs from `data` either way; see [`crate::spam`].
+fn check_spam(
+    state: &AnyFormState,
+    form: &form::Model,
+    data: &mut HashMap<String, FieldValue>,
+    require_stamp: bool,
+) -> Result<(), FormError> {
+    match form.settings().spam_protection {
+        Some(protection) => protection.check(&state.config.spam_secret, data, require_stamp),
+        None => {
+            spam::strip(data);
+            Ok(())
+        }
+    }
+}
+
+/// Checks a submission's CAPTCHA token with the router's verifier, removing
+/// the token inputs from `data` either way; see [`crate::captcha`].
+async fn check_captcha(
+    state: &AnyFormState,
+    form: &form::Model,
+    data: &mut HashMap<String, FieldValue>,
+    metadata: &SubmissionMetadata,
+) -> Result<(), FormError> {
+    let Some(captcha) = form.settings().captcha else {
+        captcha::strip(data);
+        return Ok(());
+    };
+    let token = captcha.take_token(data).ok_or(FormError::CaptchaFailed)?;
+    let Some(verifier) = &state.config.captcha_verifier else {
+        tracing::warn!(form = %form.slug, "form requires a CAPTCHA, but no verifier is registered");
+        return Err(FormError::CaptchaFailed);
+    };
+    if verifier.verify(&captcha, &token, metadata.ip.as_deref()).await {
+        Ok(())
+    } else {
+        Err(FormError::CaptchaFailed)
+    }
+}
+
+/// Identifies the browser behind a request to a form with a duplicate
+/// guard, turning it away if it already responded.
+fn check_duplicate(
+    state: &AnyFormState,
+    form: &form::Model,
+    headers: &HeaderMap,
+) -> Result<Option<Browser>, FormError> {
+    let Some(guard) = form.settings().duplicate_guard else {
+        return Ok(None);
+    };
+    let browser = Browser::identify(&state.config.spam_secret, form.id, headers);
+    guard.check(&browser)?;
+    Ok(Some(browser))
+}
+
+/// Returns true if the respondent behind a request already responded to a
+/// form accepting one response per respondent.
+async fn has_responded(
+    state: &AnyFormState,
+    form: &form::Model,
+    headers: &HeaderMap,
+) -> Result<bool, FormError> {
+    let settings = form.settings();
+    if !settings.one_per_respondent || settings.anonymous {
+        return Ok(false);
+    }
+    let Some(resolver) = &state.config.respondents else {
+        return Ok(false);
+    };
+    match resolver.resolve(headers).await {
+        Some(respondent) => state.repository.has_responded(form.id, &respondent.id).await,
+        None => Ok(false),
+    }
+}
+
+/// Records the browser behind a submission as `browser_id` metadata.
+fn with_browser(
+    metadata: Option<SubmissionMetadata>,
+    browser: Option<Browser>,
+) -> Option<SubmissionMetadata> {
+    let mut metadata = metadata?;
+    if let Some(browser) = browser {
+        metadata
+            .extra
+            .insert("browser_id".into(), browser.id.to_string().into());
+    }
+    Some(metadata)
+}
+
+/// Marks the browser behind an accepted submission as having responded,
+/// returning the cookies to set.
+fn mark_responded(
+    state: &AnyFormState,
+    form: &form::Model,
+    browser: Option<Browser>,
+    created: &mut ApiResponse<SubmissionCreated>,
+) -> Vec<HeaderValue> {
+    let Some(browser) = browser else {
+        return Vec::new();
+    };
+    let secret = &state.config.spam_secret;
+    if let Some(data) = created.data.as_mut() {
+        data.duplicate_token = Some(browser.responded_token(secret, form.id));
+    }
+    browser.cookies(secret, form.id, true)
+}
+
+/// Answers a browser that already responded to a form.
+fn already_responded(form: &form::Model) -> Response {
+    Html(HtmlRenderer::render_already_responded(form)).into_response()
+}
+
+/// Adds `Set-Cookie` headers to a response.
+fn with_cookies(cookies: Vec<HeaderValue>, response: impl IntoResponse) -> Response {
+    let cookies = cookies.into_iter().map(|c| (header::SET_COOKIE, c));
+    (AppendHeaders(cookies), response).into_response()
+}
+
+/// Collects the metadata to store with a submission.
+///
+/// Anonymous forms get none: the respondent resolver is not consulted and
+/// request details are discarded. Identified forms require a respondent.
+async fn collect_metadata(
+    state: &AnyFormState,
+    form: &form::Model,
+    metadata: SubmissionMetadata,
+    headers: &HeaderMap,
+) -> Result<Option<SubmissionMetadata>, FormError> {
+    let settings = form.settings();
+    if settings.anonymous {
+        return Ok(None);
+    }
+
+    let respondent = match &state.config.respondents {
+        Some(resolver) => resolver.resolve(headers).await,
+        None => None,
+    };
+    if settings.requires_respondent() && respondent.is_none() {
+        return Err(FormError::RespondentRequired);
+    }
+
+    let mut metadata = metadata;
+    if let Some(respondent) = respondent {
+        metadata.user_id = Some(respondent.id);
+        if let Some(email) = respondent.email {
+            metadata.extra.insert("email".into(), email.into());
+        }
+    }
+    Ok(metadata.for_settings(&settings))
+}
+
+/// Adds what the router's text analyzer finds in the free-text answers of
+/// `data` to a submission's `metadata`.
+async fn analyze_text(
+    state: &AnyFormState,
+    fields: &[field::Model],
+    data: &HashMap<String, FieldValue>,
+    metadata: Option<SubmissionMetadata>,
+) -> Option<SubmissionMetadata> {
+    let Some(analyzer) = &state.config.text_analyzer else {
+        return metadata;
+    };
+    let analyses = analyze_answers(analyzer.as_ref(), fields, data).await;
+    if analyses.is_empty() {
+        return metadata;
+    }
+    let mut metadata = metadata.unwrap_or_default();
+    metadata.text_analysis = analyses;
+    Some(metadata)
+}
+
+/// Labels the unnamed location answers in `data` with the router's
+/// reverse geocoder, if one is registered.
+async fn geocode_locations<'a>(
+    state: &AnyFormState,
+    fields: &[field::Model],
+    data: &'a HashMap<String, FieldValue>,
+) -> Cow<'a, HashMap<String, FieldValue>> {
+    let Some(geocoder) = &state.config.reverse_geocoder else {
+        return Cow::Borrowed(data);
+    };
+    let mut data = data.clone();
+    label_locations(geocoder.as_ref(), fields, &mut data).await;
+    Cow::Owned(data)
+}
+
+/// Analyzes the text and labels the locations of submitted answers to
+/// `fields`, with the router's text analyzer and reverse geocoder.
+async fn enrich_answers<'a>(
+    state: &AnyFormState,
+    fields: &[field::Model],
+    data: &'a HashMap<String, FieldValue>,
+    metadata: Option<SubmissionMetadata>,
+) -> (Cow<'a, HashMap<String, FieldValue>>, Option<SubmissionMetadata>) {
+    let metadata = analyze_text(state, fields, data, metadata).await;
+    (geocode_locations(state, fields, data).await, metadata)
+}
+
+/// Takes the ID of the in-progress submission a hydrated form saved its
+/// steps to out of `data`; see
+/// [`FormSettings::save_each_step`](crate::schema::FormSettings::save_each_step).
+fn take_draft(data: &mut HashMap<String, FieldValue>) -> Option<Uuid> {
+    data.remove(DRAFT_FIELD)?.as_str()?.parse().ok()
+}
+
+/// Discards the in-progress submission a form submitted in full saved its
+/// steps to, if any. Failures are logged: the submission is stored.
+async fn discard_draft(state: &AnyFormState, form: &form::Model, draft: Option<Uuid>) {
+    let Some(id) = draft else {
+        return;
+    };
+    if let Err(error) = state.repository.discard_draft(form.id, id).await {
+        tracing::warn!(form = %form.slug, submission_id = %id, %error, "failed to discard saved steps");
+    }
+}
+
+/// Encrypts the answers to sensitive fields in `data` with the router's
+/// encryption key, if one is set.
+fn encrypt_answers<'a>(
+    state: &AnyFormState,
+    fields: &[field::Model],
+    data: &'a HashMap<String, FieldValue>,
+) -> Cow<'a, HashMap<String, FieldValue>> {
+    match &state.config.encryption_key {
+        Some(key) => Cow::Owned(key.encrypt_answers(fields, data)),
+        None => Cow::Borrowed(data),
+    }
+}
+
+/// Decrypts the encrypted answers in `sub` with the router's encryption
+/// key, if one is set.
+pub(crate) fn decrypted(state: &AnyFormState, mut sub: submission::Model) -> submission::Model {
+    if let Some(key) = &state.config.encryption_key {
+        key.decrypt_submission(&mut sub);
+    }
+    sub
+}
+
+/// Validates and stores a submission through the state's repository.
+///
+/// The form is read again just before validating, in the same repository
+/// transaction as the insert, so a form closed or changed since the request
+/// arrived refuses it; capacity and quotas are enforced as the submission is
+/// inserted. The whole transaction is retried per the router's
+/// [`RetryPolicy`](crate::database::RetryPolicy) while the database is busy.
+/// Submissions outside the form's sample are validated but not stored
+/// (`None`); `webhook_failure` fails them after validation as if a delivery
+/// hook failed (chaos mode).
+///
+/// Unexpected failures (e.g. a database error, or a database that stays
+/// busy) capture the submission as a dead letter, so it can be replayed from
+/// the admin API instead of being lost. The error is still returned.
+async fn store_submission(
+    state: &AnyFormState,
+    form: &form::Model,
+    data: &HashMap<String, FieldValue>,
+    metadata: Option<SubmissionMetadata>,
+    locale: Option<&str>,
+    webhook_failure: bool,
+) -> Result<Option<submission::Model>, FormError> {
+    let result = insert_checked(state, form, data, metadata.clone(), locale, webhook_failure).await;
+    record_submission(state, form, &result);
+
+    match result {
+        Err(e) if e.status_code().is_server_error() => {
+            capture_dead_letter(state, form, data, metadata.as_ref(), &e).await;
+            Err(e)
+        }
+        result => result,
+    }
+}
+
+/// Checks and inserts a submission for [`store_submission`].
+///
+/// With a reverse geocoder or text analyzer, the answers are enriched once,
+/// before the transaction, so a retry doesn't call them again. So that only
+/// answers the transaction would store are enriched, the form as read with
+/// the request is checked first; the transaction checks it again.
+async fn insert_checked(
+    state: &AnyFormState,
+    form: &form::Model,
+    data: &HashMap<String, FieldValue>,
+    metadata: Option<SubmissionMetadata>,
+    locale: Option<&str>,
+    webhook_failure: bool,
+) -> Result<Option<submission::Model>, FormError> {
+    // Drawn once, so the checks before and in the transaction agree
+    let sampled = SubmissionService::is_sampled(form);
+
+    let (enriched, enriched_metadata) =
+        if state.config.reverse_geocoder.is_some() || state.config.text_analyzer.is_some() {
+            ensure_open(form)?;
+            let loaded = state.repository.load_steps_with_fields(form.id).await?;
+            check_answers(state, form, &loaded, data, locale).await?;
+            if !sampled {
+                return Ok(None);
+            }
+            if webhook_failure {
+                return Err(FormError::WebhookFailed("simulated failure".to_string()));
+            }
+            let fields = StepWithFields::all_fields(&loaded);
+            enrich_answers(state, &fields, data, metadata).await
+        } else {
+            (Cow::Borrowed(data), metadata)
+        };
+
+    state
+        .config
+        .retry
+        .run(|| async {
+            let txn = state.repository.begin().await?;
+            let form = txn
+                .find_form(form.id)
+                .await?
+                .filter(|f| !f.is_deleted())
+                .ok_or(FormError::FormDeleted)?;
+            if !form.is_published() {
+                return Err(FormError::NotFound(form.slug));
+            }
+            ensure_open(&form)?;
+
+            let loaded = txn.load_steps_with_fields(form.id).await?;
+            check_answers(state, &form, &loaded, data, locale).await?;
+
+            if !sampled {
+                return Ok(None);
+            }
+            if webhook_failure {
+                return Err(FormError::WebhookFailed("simulated failure".to_string()));
+            }
+
+            let fields = StepWithFields::all_fields(&loaded);
+            let metadata = enriched_metadata.clone();
+            let key = state.config.encryption_key.as_ref();
+            let saved = txn.insert_submission(&form, &fields, &enriched, metadata, key).await?;
+            txn.commit().await?;
+            Ok(Some(saved))
+        })
+        .await
+}
+
+/// Validates submitted data against the fields of `loaded`; see
+/// [`validate_data`].
+async fn check_answers(
+    state: &AnyFormState,
+    form: &form::Model,
+    loaded: &[StepWithFields],
+    data: &HashMap<String, FieldValue>,
+    locale: Option<&str>,
+) -> Result<(), FormError> {
+    let steps: Vec<step::Model> = loaded.iter().map(|s| s.step.clone()).collect();
+    let fields = StepWithFields::all_fields(loaded);
+    let sub_fields = StepWithFields::all_sub_fields(loaded);
+    let errors = validate_data(state, form, &steps, &fields, &sub_fields, data, locale).await;
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(FormError::ValidationFailed(errors))
+    }
+}
+
+/// Counts an accepted submission, or one rejected by validation, in the
+/// router's metrics.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+fn record_submission<T>(state: &AnyFormState, form: &form::Model, result: &Result<T, FormError>) {
+    match result {
+        Ok(_) => {
+            #[cfg(feature = "metrics")]
+            if let Some(metrics) = &state.config.metrics {
+                metrics.record_submission(&form.slug);
+            }
+        }
+        Err(e) => record_validation_failure(state, form, e),
+    }
+}
+
+/// Counts a submission rejected by validation in the router's metrics;
+/// other errors are not counted.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+fn record_validation_failure(state: &AnyFormState, form: &form::Model, err: &FormError) {
+    #[cfg(feature = "metrics")]
+    if let (Some(metrics), FormError::ValidationFailed(_) | FormError::StepValidationFailed(_)) =
+        (&state.config.metrics, err)
+    {
+        metrics.record_validation_failure(&form.slug);
+    }
+}
+
+/// Records the time since `started` as a render in `format` in the
+/// router's metrics.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+fn observe_render(state: &AnyFormState, format: &'static str, started: Instant) {
+    #[cfg(feature = "metrics")]
+    if let Some(metrics) = &state.config.metrics {
+        metrics.observe_render(format, started.elapsed());
+    }
+}
+
+/// Validates and stores a preview submission in a single transaction,
+/// retried while the database is busy.
+async fn store_preview(
+    state: &AnyFormState,
+    form: &form::Model,
+    data: &HashMap<String, FieldValue>,
+) -> Result<submission::Model, FormError> {
+    state
+        .config
+        .retry
+        .run(|| async {
+            let txn = state.repository.begin().await?;
+            let loaded = txn.load_steps_with_fields(form.id).await?;
+            let steps: Vec<step::Model> = loaded.iter().map(|s| s.step.clone()).collect();
+            let fields = StepWithFields::all_fields(&loaded);
+            let sub_fields = StepWithFields::all_sub_fields(&loaded);
+            let errors = validate_data(state, form, &steps, &fields, &sub_fields, data, None).await;
+            if !errors.is_empty() {
+                return Err(FormError::ValidationFailed(errors));
+            }
+
+            let key = state.config.encryption_key.as_ref();
+            let saved = txn.insert_preview(form, data, key).await?;
+            txn.commit().await?;
+            Ok(saved)
+        })
+        .await
+}
+
+/// Captures a submission that failed to persist as a dead letter.
+async fn capture_dead_letter(
+    state: &AnyFormState,
+    form: &form::Model,
+    data: &HashMap<String, FieldValue>,
+    metadata: Option<&SubmissionMetadata>,
+    err: &FormError,
+) {
+    let error = err.to_string();
+    let fields = match &state.config.encryption_key {
+        Some(_) => match state.repository.load_steps_with_fields(form.id).await {
+            Ok(steps) => StepWithFields::all_fields(&steps),
+            Err(_) => Vec::new(),
+        },
+        None => Vec::new(),
+    };
+    let data = encrypt_answers(state, &fields, data);
+    match state.repository.record_dead_letter(form.id, &data, metadata, &error).await {
+        Ok(letter) => tracing::error!(
+            form_id = %form.id,
+            dead_letter_id = %letter.id,
+            error = %error,
+            "submission failed to persist; captured as a dead letter"
+        ),
+        // Last resort: keep the payload in the logs
+        Err(record_error) => tracing::error!(
+            form_id = %form.id,
+            data = %serde_json::to_value(&*data).unwrap_or_default(),
+            error = %error,
+            record_error = %record_error,
+            "submission failed to persist and could not be captured"
+        ),
+    }
+}
+
+/// Notifies the registered event hooks of a new submission, and of the
+/// form closing if it filled a quota.
+async fn notify_created(state: &AnyFormState, form: &form::Model, sub: &submission::Model) {
+    if let Some(events) = &state.config.events {
+        let sub = &decrypted(state, sub.clone());
+        if sub.is_waitlisted() {
+            events.on_submission_waitlisted(form, sub).await;
+        } else {
+            events.on_submission_created(form, sub).await;
+        }
+    }
+    if has_quota(form) {
+        announce_lifecycle(state, form.id).await;
+    }
+}
+
+/// Returns true if the form has a quota submissions can open or close it
+/// by.
+fn has_quota(form: &form::Model) -> bool {
+    let settings = form.settings();
+    settings.close_after.is_some() || settings.max_submissions.is_some()
+}
+
+/// Tells the registered event hooks if a change opened or closed the form
+/// (see [`LifecycleService::announce`]). Failures are logged.
+async fn announce_lifecycle(state: &AnyFormState, form_id: Uuid) {
+    let Some(events) = &state.config.events else {
+        return;
+    };
+    let now = chrono::Utc::now().fixed_offset();
+    if let Err(error) = state.repository.announce_lifecycle(events.as_ref(), form_id, now).await {
+        tracing::warn!(%form_id, %error, "failed to announce form lifecycle");
+    }
+}
+
+// Admin handlers
+
+/// Lists all forms (admin).
+#[cfg(feature = "admin")]
+pub async fn list_forms(
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<FormList>, ApiResponse<()>> {
+    let forms = state
+        .repository
+        .list_forms()
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    let forms_list: Vec<FormSummary> = forms
+        .into_iter()
+        .filter(|form| state.serves(form))
+        .map(FormSummary::from)
+        .collect();
+
+    let count = forms_list.len();
+    Ok(ApiResponse::ok(FormList {
+        forms: forms_list,
+        count,
+    })
+    .with_request_id(request_id))
+}
+
+/// Gets a form by ID (admin).
+#[cfg(feature = "admin")]
+pub async fn get_form_by_id(
+    Path(id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<FormJson>, ApiResponse<()>> {
+    let form = state
+        .repository
+        .find_form(id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(id.to_string())))?;
+
+    let steps = state
+        .repository
+        .load_steps_with_fields(form.id)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    Ok(ApiResponse::ok(JsonRenderer::render_steps(&form, &steps)).with_request_id(request_id))
+}
+
+/// Lists submissions for a form (admin), one page at a time.
+///
+/// `?label=`, `?status=`, `?instance=`, `?since=`, `?until=` and
+/// `?completed=` restrict the listing to matching submissions, `?sort=`
+/// orders it, and `?page=` / `?per_page=` select the page. Preview
+/// submissions are only listed with `?status=preview`. With the `search`
+/// feature, `?q=` lists the submissions whose text answers contain its
+/// words instead, best match first, each with a highlighted `snippet`.
+#[cfg(feature = "admin")]
+pub async fn list_submissions(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Query(query): Query<SubmissionListQuery>,
+) -> Result<ApiResponse<SubmissionList>, ApiResponse<()>> {
+    #[cfg(feature = "search")]
+    let search = query.q.clone().filter(|q| !q.trim().is_empty());
+    let builder = submission_query(form_id, query).map_err(ApiResponse::<()>::from)?;
+
+    #[cfg(feature = "search")]
+    if let Some(text) = search {
+        let page = state
+            .repository
+            .search_submissions(&builder, &text)
+            .await
+            .map_err(ApiResponse::<()>::from)?;
+        let pagination = page.pagination();
+        let hits = page
+            .hits
+            .into_iter()
+            .map(|hit| (hit.submission, Some(hit.snippet)))
+            .collect();
+        return submission_list(&state, hits, pagination, request_id).await;
+    }
+
+    let page = state
+        .repository
+        .list_submissions(&builder)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    let pagination = page.pagination();
+    let submissions = page.submissions.into_iter().map(|s| (s, None)).collect();
+    submission_list(&state, submissions, pagination, request_id).await
+}
+
+/// Builds a page of a submission listing, with each submission's labels
+/// and search snippet.
+#[cfg(feature = "admin")]
+async fn submission_list(
+    state: &AnyFormState,
+    submissions: Vec<(submission::Model, Option<String>)>,
+    pagination: crate::response::PaginationInfo,
+    request_id: String,
+) -> Result<ApiResponse<SubmissionList>, ApiResponse<()>> {
+    let ids: Vec<Uuid> = submissions.iter().map(|(s, _)| s.id).collect();
+    let mut labels = state
+        .repository
+        .submission_labels(&ids)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    let submissions_list: Vec<SubmissionSummary> = submissions
+        .into_iter()
+        .map(|(s, snippet)| (decrypted(state, s), snippet))
+        .map(|(s, snippet)| SubmissionSummary {
+            labels: labels.remove(&s.id).unwrap_or_default(),
+            id: s.id.to_string(),
+            data: s.data,
+            completed_at: s.completed_at.map(|d| d.to_rfc3339()),
+            score: s.score,
+            status: s.status,
+            instance_id: s.instance_id.map(|id| id.to_string()),
+            respondent_id: s.respondent_id,
+            legal_hold: s.legal_hold,
+            snippet,
+            created_at: s.created_at.to_rfc3339(),
+        })
+        .collect();
+
+    let count = submissions_list.len();
+    Ok(ApiResponse::ok(SubmissionList {
+        submissions: submissions_list,
+        count,
+    })
+    .with_pagination(pagination)
+    .with_request_id(request_id))
+}
+
+/// Lists a form's submissions inserted, updated or soft-deleted since
+/// `?cursor=` (admin), oldest change first, for incremental loads.
+///
+/// Returns up to `?limit=` changes, the `next_cursor` to continue from and
+/// the fields of every form revision; see [`SubmissionChanges`].
+#[cfg(feature = "admin")]
+pub async fn list_submission_changes(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Query(query): Query<SubmissionChangesQuery>,
+) -> Result<ApiResponse<SubmissionChangeList>, ApiResponse<()>> {
+    state
+        .repository
+        .find_form(form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
+
+    let mut changes = SubmissionChanges::new(form_id);
+    if let Some(cursor) = query.cursor {
+        changes = changes.after(cursor.parse().map_err(ApiResponse::<()>::from)?);
+    }
+    if let Some(limit) = query.limit {
+        changes = changes.limit(limit);
+    }
+    let batch = state
+        .repository
+        .submission_changes(&changes, chrono::Utc::now().fixed_offset())
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::ok(SubmissionChangeList {
+        changes: batch
+            .changes
+            .into_iter()
+            .map(|mut change| {
+                change.submission = decrypted(&state, change.submission);
+                SubmissionChangeData::from(change)
+            })
+            .collect(),
+        next_cursor: batch.next_cursor.map(|c| c.to_string()),
+        has_more: batch.has_more,
+        schema: batch.revisions,
+    })
+    .with_request_id(request_id))
+}
+
+/// Exports a form's completed submissions as CSV, or as an Excel workbook
+/// with `?format=xlsx` (`xlsx` feature), laid out by the form's export mapping (admin).
+///
+/// Answers to sensitive fields are left out unless `?include_sensitive=true`.
+/// With `?include_partials=true`, partial responses (in progress, with at
+/// least one step saved) are exported too, with the status `in_progress`.
+#[cfg(feature = "admin")]
+pub async fn export_submissions(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    Query(query): Query<SubmissionExportQuery>,
+) -> Result<Response, FormError> {
+    let repository = state.repository.as_ref();
+    let form = repository
+        .find_form(form_id)
+        .await?
+        .ok_or_else(|| FormError::NotFound(form_id.to_string()))?;
+
+    #[cfg(not(feature = "xlsx"))]
+    if query.format == ExportFormat::Xlsx {
+        return Err(FormError::InvalidData(
+            "Excel exports need the `xlsx` feature".to_string(),
+        ));
+    }
+    let submissions: Vec<submission::Model> = repository
+        .exported_submissions(form.id, query.include_partials)
+        .await?
+        .into_iter()
+        .map(|s| decrypted(&state, s))
+        .collect();
+    let ids: Vec<Uuid> = submissions.iter().map(|s| s.id).collect();
+    let labels = repository.submission_labels(&ids).await?;
+    let schema = repository.submission_schema(form.id, &submissions).await?;
+    let choices = repository.choice_fields(form.id).await?;
+
+    #[cfg(feature = "xlsx")]
+    if query.format == ExportFormat::Xlsx {
+        let export = XlsxExport::with_choices(&form, &schema, &choices, query.include_sensitive);
+        let mut writer = export.writer(Vec::new())?;
+        writer.write(&submissions, &labels)?;
+        let workbook = writer.finish()?;
+        let disposition = format!("attachment; filename=\"{}-submissions.xlsx\"", form.slug);
+        return Ok((
+            [
+                (header::CONTENT_TYPE, XLSX_CONTENT_TYPE.to_string()),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            workbook,
+        )
+            .into_response());
+    }
+
+    let export = CsvExport::with_choices(&form, &schema, &choices, query.include_sensitive);
+
+    let disposition = format!("attachment; filename=\"{}-submissions.csv\"", form.slug);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        export.render(&submissions, &labels),
+    )
+        .into_response())
+}
+
+/// Media type of Excel workbooks.
+#[cfg(all(feature = "admin", feature = "xlsx"))]
+const XLSX_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Builds a [`SubmissionQuery`] from listing query parameters.
+#[cfg(feature = "admin")]
+fn submission_query(
+    form_id: Uuid,
+    query: SubmissionListQuery,
+) -> Result<SubmissionQuery, FormError> {
+    let mut builder = SubmissionQuery::new(form_id);
+    if let Some(status) = query.status {
+        builder = builder.status(status);
+    }
+    if let Some(instance) = query.instance {
+        builder = builder.instance(instance);
+    }
+    if let Some(respondent) = query.respondent {
+        builder = builder.respondent(respondent);
+    }
+    if let Some(label) = query.label {
+        builder = builder.label(label);
+    }
+    if let Some(since) = query.since {
+        builder = builder.since(parse_bound(&since, false)?);
+    }
+    if let Some(until) = query.until {
+        builder = builder.until(parse_bound(&until, true)?);
+    }
+    if let Some(completed) = query.completed {
+        builder = builder.completed(completed);
+    }
+    if let Some(sort) = query.sort {
+        builder = builder.sort(sort.parse()?);
+    }
+    if let Some(page) = query.page {
+        builder = builder.page(page);
+    }
+    if let Some(per_page) = query.per_page {
+        builder = builder.per_page(per_page);
+    }
+    Ok(builder)
+}
+
+/// Creates a new form (admin).
+#[cfg(feature = "admin")]
+pub async fn create_form(
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Json(input): Json<CreateFormInput>,
+) -> Result<ApiResponse<FormCreated>, ApiResponse<()>> {
+    state.check_slug(&input.slug).map_err(ApiResponse::<()>::from)?;
+    let input = state.config.field_library.expand(input).map_err(ApiResponse::<()>::from)?;
+    let form = state
+        .repository
+        .create_form(input)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    if let Some(events) = &state.config.events {
+        events.on_form_created(&form).await;
+    }
+
+    Ok(ApiResponse::created(FormCreated {
+        id: form.id.to_string(),
+        name: form.name,
+        slug: form.slug,
+        description: form.description,
+        created_at: form.created_at.to_rfc3339(),
+    })
+    .with_request_id(request_id))
+}
+
+/// Copies a form with its steps, fields, options and results (admin), as
+/// a draft under a `-copy` slug; see [`FormBuilder::duplicate`].
+#[cfg(feature = "admin")]
+pub async fn duplicate_form(
+    Path(id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<FormCreated>, ApiResponse<()>> {
+    if state.config.only_forms.is_some() {
+        let source = find_active_form(&state, id)
+            .await
+            .map_err(ApiResponse::<()>::from)?
+            .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(id.to_string())))?;
+        let slug = state
+            .repository
+            .copy_slug(&source.slug)
+            .await
+            .map_err(ApiResponse::<()>::from)?;
+        state.check_slug(&slug).map_err(ApiResponse::<()>::from)?;
+    }
+    let form = state
+        .repository
+        .duplicate_form(id)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    if let Some(events) = &state.config.events {
+        events.on_form_created(&form).await;
+    }
+
+    Ok(ApiResponse::created(FormCreated {
+        id: form.id.to_string(),
+        name: form.name,
+        slug: form.slug,
+        description: form.description,
+        created_at: form.created_at.to_rfc3339(),
+    })
+    .with_request_id(request_id))
+}
+
+/// Updates an existing form (admin).
+#[cfg(feature = "admin")]
+pub async fn update_form(
+    Path(id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Json(input): Json<CreateFormInput>,
+) -> Result<ApiResponse<FormUpdated>, ApiResponse<()>> {
+    state.check_slug(&input.slug).map_err(ApiResponse::<()>::from)?;
+    let input = state.config.field_library.expand(input).map_err(ApiResponse::<()>::from)?;
+    let form = state
+        .repository
+        .update_form(id, input)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    if let Some(events) = &state.config.events {
+        events.on_form_updated(&form).await;
+    }
+    announce_lifecycle(&state, form.id).await;
+
+    Ok(ApiResponse::ok(FormUpdated {
+        id: form.id.to_string(),
+        name: form.name,
+        slug: form.slug,
+        description: form.description,
+        updated_at: form.updated_at.to_rfc3339(),
+    })
+    .with_request_id(request_id))
+}
+
+/// Replaces or merges a field's options with options sent as CSV or, with
+/// a JSON content type, as a JSON array, and returns what changed (admin).
+#[cfg(feature = "admin")]
+pub async fn import_field_options(
+    Path((id, field)): Path<(Uuid, String)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Query(query): Query<OptionImportQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<ApiResponse<OptionDiff>, ApiResponse<()>> {
+    let form = find_active_form(&state, id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(id.to_string())))?;
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let rows = if is_json {
+        OptionImport::parse_json(&body)
+    } else {
+        OptionImport::parse_csv(&body)
+    }
+    .map_err(ApiResponse::<()>::from)?;
+
+    let diff = state
+        .repository
+        .import_options(&form, &field, rows, query.mode, query.dry_run)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    if diff.applied {
+        if let Some(events) = &state.config.events {
+            events.on_form_updated(&form).await;
+        }
+    }
+
+    Ok(ApiResponse::ok(diff).with_request_id(request_id))
+}
+
+/// Deletes a form (admin).
+#[cfg(feature = "admin")]
+pub async fn delete_form(
+    Path(id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<Deleted>, ApiResponse<()>> {
+    state
+        .repository
+        .delete_form(id)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::ok(Deleted::form()).with_request_id(request_id))
+}
+
+/// Publishes a form (admin), serving it on public routes.
+#[cfg(feature = "admin")]
+pub async fn publish_form(
+    Path(id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<FormSummary>, ApiResponse<()>> {
+    let was_published = find_active_form(&state, id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .is_some_and(|f| f.is_published());
+    let form = state
+        .repository
+        .set_form_status(id, FormStatus::Published)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    if let Some(events) = state.config.events.as_ref().filter(|_| !was_published) {
+        events.on_form_published(&form).await;
+    }
+    announce_lifecycle(&state, form.id).await;
+
+    Ok(ApiResponse::ok(FormSummary::from(form)).with_request_id(request_id))
+}
+
+/// Takes a form back to draft (admin), hiding it from public routes.
+#[cfg(feature = "admin")]
+pub async fn unpublish_form(
+    Path(id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<FormSummary>, ApiResponse<()>> {
+    let form = state
+        .repository
+        .set_form_status(id, FormStatus::Draft)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    announce_lifecycle(&state, form.id).await;
+
+    Ok(ApiResponse::ok(FormSummary::from(form)).with_request_id(request_id))
+}
+
+/// Archives a form (admin), hiding it from public routes.
+#[cfg(feature = "admin")]
+pub async fn archive_form(
+    Path(id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<FormSummary>, ApiResponse<()>> {
+    let was_archived = find_active_form(&state, id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .is_some_and(|f| f.is_archived());
+    let form = state
+        .repository
+        .set_form_status(id, FormStatus::Archived)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    if let Some(events) = state.config.events.as_ref().filter(|_| !was_archived) {
+        events.on_form_archived(&form).await;
+    }
+    announce_lifecycle(&state, form.id).await;
+
+    Ok(ApiResponse::ok(FormSummary::from(form)).with_request_id(request_id))
+}
+
+/// Gets a specific submission (admin).
+#[cfg(feature = "admin")]
+pub async fn get_submission(
+    Path((form_id, sub_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<SubmissionData>, ApiResponse<()>> {
+    // Verify form exists
+    let _form = state
+        .repository
+        .find_form(form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
+
+    let sub = find_active_submission(&state, sub_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| {
+            ApiResponse::<()>::from(FormError::SubmissionNotFound(sub_id.to_string()))
+        })?;
+
+    // Verify submission belongs to form
+    if sub.form_id != form_id {
+        return Err(FormError::SubmissionNotFound(sub_id.to_string()).into());
+    }
+    let sub = decrypted(&state, sub);
+
+    let notes = state
+        .repository
+        .submission_notes(sub.id)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    let labels = submission_note::normalize_labels(notes.iter().flat_map(|n| n.labels()));
+
+    Ok(ApiResponse::ok(SubmissionData {
+        id: sub.id.to_string(),
+        form_id: sub.form_id.to_string(),
+        data: sub.data,
+        metadata: sub.metadata,
+        completed_at: sub.completed_at.map(|d| d.to_rfc3339()),
+        score: sub.score,
+        max_score: sub.max_score,
+        result_key: sub.result_key,
+        status: sub.status,
+        instance_id: sub.instance_id.map(|id| id.to_string()),
+        respondent_id: sub.respondent_id,
+        legal_hold: sub.legal_hold,
+        labels,
+        notes: notes.into_iter().map(SubmissionNoteData::from).collect(),
+        created_at: sub.created_at.to_rfc3339(),
+        updated_at: sub.updated_at.to_rfc3339(),
+    })
+    .with_request_id(request_id))
+}
+
+/// Renders a submission as a printable PDF (admin, `pdf` feature).
+///
+/// Answers to sensitive fields are left out unless `include_sensitive` is
+/// set.
+#[cfg(all(feature = "admin", not(feature = "pdf")))]
+pub async fn get_submission_pdf(
+    Path(_): Path<(Uuid, Uuid)>,
+    Query(_): Query<SubmissionPdfQuery>,
+) -> Result<Response, FormError> {
+    Err(FormError::InvalidData(
+        "PDFs need the `pdf` feature".to_string(),
+    ))
+}
+
+/// Renders a submission as a printable PDF (admin, `pdf` feature).
+///
+/// Answers to sensitive fields are left out unless `include_sensitive` is
+/// set.
+#[cfg(all(feature = "admin", feature = "pdf"))]
+pub async fn get_submission_pdf(
+    Path((form_id, sub_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AnyFormState>,
+    Query(query): Query<SubmissionPdfQuery>,
+) -> Result<Response, FormError> {
+    let form = state
+        .repository
+        .find_form(form_id)
+        .await?
+        .ok_or_else(|| FormError::NotFound(form_id.to_string()))?;
+    let sub = find_active_submission(&state, sub_id)
+        .await?
+        .filter(|s| s.form_id == form_id)
+        .ok_or_else(|| FormError::SubmissionNotFound(sub_id.to_string()))?;
+    let sub = decrypted(&state, sub);
+
+    let schema = state
+        .repository
+        .submission_schema(form.id, std::slice::from_ref(&sub))
+        .await?;
+    let choices = state.repository.choice_fields(form.id).await?;
+    let pdf = SubmissionPdf::render_with(&form, &schema, &choices, &sub, query.include_sensitive)?;
+    let disposition = format!("inline; filename=\"{}-{}.pdf\"", form.slug, sub.id);
+    Ok((
+        [
+            (header::CONTENT_TYPE, PDF_CONTENT_TYPE.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        pdf,
+    )
+        .into_response())
+}
+
+/// Lists the instances of a recurring form with per-instance analytics
+/// (admin).
+#[cfg(feature = "admin")]
+pub async fn list_form_instances(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<FormInstanceList>, ApiResponse<()>> {
+    let form = state
+        .repository
+        .find_form(form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
+
+    let instances: Vec<FormInstanceStats> = state
+        .repository
+        .instance_stats(&form)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .into_iter()
+        .map(FormInstanceStats::from)
+        .collect();
+
+    let count = instances.len();
+    Ok(ApiResponse::ok(FormInstanceList { instances, count }).with_request_id(request_id))
+}
+
+/// Returns a form's submission analytics, aggregated in the database
+/// (admin).
+#[cfg(feature = "admin")]
+pub async fn get_form_analytics(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<FormAnalytics>, ApiResponse<()>> {
+    let form = state
+        .repository
+        .find_form(form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
+    let analytics = state
+        .repository
+        .form_analytics(&form)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::ok(analytics).with_request_id(request_id))
+}
+
+/// Returns totals across the installation: forms, submissions per day,
+/// the busiest forms and the average completion rate (admin).
+#[cfg(feature = "admin")]
+pub async fn get_stats(
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<InstallationStats>, ApiResponse<()>> {
+    let stats = state
+        .repository
+        .installation_stats()
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::ok(stats).with_request_id(request_id))
+}
+
+/// Returns a form's schedule: whether it takes submissions, how far it is
+/// from its response limits, and its upcoming recurring instances (admin).
+#[cfg(feature = "admin")]
+pub async fn get_form_schedule(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Query(query): Query<ScheduleQuery>,
+) -> Result<ApiResponse<FormSchedule>, ApiResponse<()>> {
+    let form = state
+        .repository
+        .find_form(form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
+    let at = query.at.unwrap_or_else(|| chrono::Utc::now().fixed_offset());
+    let upcoming = query.upcoming.map_or(DEFAULT_UPCOMING, |n| n as usize);
+    let schedule = state
+        .repository
+        .form_schedule(&form, at, upcoming)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::ok(schedule).with_request_id(request_id))
+}
+
+/// Runs a hypothetical set of answers through a form (admin): which steps
+/// and fields would be shown, which validations would fail, and the score
+/// and result it would get. Nothing is stored, and drafts can be simulated.
+#[cfg(feature = "admin")]
+pub async fn simulate_submission(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    FormSubmission(data): FormSubmission,
+) -> Result<ApiResponse<SimulationResult>, ApiResponse<()>> {
+    let form = find_active_form(&state, form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
+    let simulation = simulate(&state, &form, &data)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::ok(simulation).with_request_id(request_id))
+}
+
+/// Works out what a form would do with `data`, as the submit path does.
+#[cfg(feature = "admin")]
+async fn simulate(
+    state: &AnyFormState,
+    form: &form::Model,
+    data: &HashMap<String, FieldValue>,
+) -> Result<SimulationResult, FormError> {
+    let loaded = state.repository.load_steps_with_fields(form.id).await?;
+    let steps: Vec<step::Model> = loaded.iter().map(|s| s.step.clone()).collect();
+    let fields = StepWithFields::all_fields(&loaded);
+    let sub_fields = StepWithFields::all_sub_fields(&loaded);
+
+    let route = route_steps(&steps, &fields, data);
+    let simulated = loaded
+        .iter()
+        .map(|s| {
+            let visible = route.contains(&s.step.id);
+            SimulatedStep {
+                id: s.step.id.to_string(),
+                name: s.step.name.clone(),
+                visible,
+                fields: s
+                    .fields
+                    .iter()
+                    .map(|f| SimulatedField {
+                        id: f.field.id.to_string(),
+                        name: f.field.name.clone(),
+                        visible: visible && is_field_visible(&f.field, data),
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+
+    let errors = validate_data(state, form, &steps, &fields, &sub_fields, data, None).await;
+    let repository = state.repository.as_ref();
+    let score = repository.score(form, &fields, data).await?;
+    let result = match &score {
+        Some(score) => match &score.result_key {
+            Some(key) => repository.find_result(form.id, key).await?,
+            None => None,
+        },
+        None => repository.resolve_result(form.id, None, data).await?,
+    };
+
+    Ok(SimulationResult {
+        valid: errors.is_empty(),
+        route: route.iter().map(Uuid::to_string).collect(),
+        steps: simulated,
+        errors: errors.errors.into_iter().collect(),
+        score: score.as_ref().map(|s| s.score),
+        max_score: score.as_ref().map(|s| s.max_score),
+        categories: score.map(|s| s.categories).unwrap_or_default(),
+        result: result.map(ResultData::from),
+    })
+}
+
+/// Returns the digest of a form's last whole period, as it would be sent
+/// (admin).
+#[cfg(feature = "admin")]
+pub async fn get_form_digest(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<Digest>, ApiResponse<()>> {
+    let form = state
+        .repository
+        .find_form(form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
+    let digest = state
+        .repository
+        .latest_digest(&form, chrono::Utc::now().fixed_offset())
+        .await
+        .map_err(ApiResponse::<()>::from)?;
 
-    let json = JsonRenderer::render(&db, &form).await?;
-    Ok(Json(json))
+    Ok(ApiResponse::ok(digest).with_request_id(request_id))
 }
 
-/// Gets a form by slug and returns its HTML.
-pub async fn get_form_html(
-    Path(slug): Path<String>,
-    State(db): State<DatabaseConnection>,
-) -> Result<impl IntoResponse, FormError> {
-    let form = form::Entity::find_by_slug(&db, &slug)
-        .await?
-        .ok_or_else(|| FormError::NotFound(slug))?;
+/// Subscribes or unsubscribes a recipient of a form's digest (admin).
+#[cfg(feature = "admin")]
+pub async fn set_digest_subscription(
+    Path((form_id, email)): Path<(Uuid, String)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Json(input): Json<DigestSubscriptionInput>,
+) -> Result<ApiResponse<NotificationDigest>, ApiResponse<()>> {
+    let form = state
+        .repository
+        .find_form(form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
+    let digest = state
+        .repository
+        .set_digest_subscription(form, &email, input.subscribed)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
 
-    if form.is_deleted() {
-        return Err(FormError::FormDeleted);
-    }
+    Ok(ApiResponse::ok(digest).with_request_id(request_id))
+}
+
+/// Adds a browser to or removes it from the allow list of a form's
+/// duplicate guard (admin).
+#[cfg(feature = "admin")]
+pub async fn set_browser_allowed(
+    Path((form_id, browser_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+    Json(input): Json<AllowBrowserInput>,
+) -> Result<ApiResponse<DuplicateGuard>, ApiResponse<()>> {
+    let guard = state
+        .repository
+        .set_browser_allowed(form_id, browser_id, input.allowed)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
 
-    let html = HtmlRenderer::render(&db, &form, &HtmlOptions::new()).await?;
-    Ok(Html(html))
+    Ok(ApiResponse::ok(guard).with_request_id(request_id))
 }
 
-/// Submits a form.
-pub async fn submit_form(
-    Path(slug): Path<String>,
-    State(db): State<DatabaseConnection>,
+/// Creates a signed preview link for a form (admin).
+///
+/// The link works for drafts and expires after [`DEFAULT_PREVIEW_TTL`].
+#[cfg(feature = "admin")]
+pub async fn create_preview_link(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    version: ApiVersion,
     RequestId(request_id): RequestId,
-    FormSubmission(data): FormSubmission,
-) -> Result<ApiResponse<SubmissionCreated>, ApiResponse<()>> {
-    let form = form::Entity::find_by_slug(&db, &slug)
+) -> Result<ApiResponse<PreviewLink>, ApiResponse<()>> {
+    let secret = state.config.preview_secret.as_ref().ok_or_else(|| {
+        ApiResponse::<()>::from(FormError::InvalidData(
+            "Preview links require a preview secret".to_string(),
+        ))
+    })?;
+
+    let form = state
+        .repository
+        .find_form(form_id)
         .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?
-        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(slug.clone())))?;
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
 
     if form.is_deleted() {
         return Err(FormError::FormDeleted.into());
     }
 
-    // Load all fields for validation
-    let steps = step::Entity::find_by_form(&db, form.id)
-        .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?;
-    let mut all_fields = Vec::new();
-    for s in &steps {
-        let fields = field::Entity::find_by_step(&db, s.id)
-            .await
-            .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?;
-        all_fields.extend(fields);
-    }
-
-    // Validate
-    let errors = validate_submission(&all_fields, &data);
-    if !errors.is_empty() {
-        return Err(FormError::ValidationFailed(errors).into());
-    }
+    let token = PreviewToken::new(form.id, DEFAULT_PREVIEW_TTL);
+    let signed = secret.sign(&token);
 
-    // Create submission
-    let now = chrono::Utc::now().fixed_offset();
-    let sub = submission::ActiveModel {
-        id: Set(Uuid::new_v4()),
-        form_id: Set(form.id),
-        data: Set(serde_json::to_value(&data).unwrap_or_default()),
-        metadata: Set(None),
-        current_step_id: Set(None),
-        completed_at: Set(Some(now)),
-        score: Set(None),
-        max_score: Set(None),
-        result_key: Set(None),
-        created_at: Set(now),
-        updated_at: Set(now),
-        deleted_at: Set(None),
-    };
+    Ok(ApiResponse::created(PreviewLink {
+        url: preview_url(version, &form, &signed),
+        token: signed,
+        expires_at: token.expires_at.to_rfc3339(),
+    })
+    .with_request_id(request_id))
+}
 
-    let saved = sub
-        .insert(&db)
+/// Permanently deletes a form's preview submissions (admin).
+#[cfg(feature = "admin")]
+pub async fn discard_preview_submissions(
+    Path(form_id): Path<Uuid>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<PreviewsDiscarded>, ApiResponse<()>> {
+    let held: Vec<Uuid> = state
+        .repository
+        .preview_submissions(form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .into_iter()
+        .filter(|s| s.legal_hold)
+        .map(|s| s.id)
+        .collect();
+    let discarded = state
+        .repository
+        .delete_previews(form_id)
         .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?;
+        .map_err(ApiResponse::<()>::from)?;
+    hold_refused("discard previews", form_id, &held);
 
-    Ok(ApiResponse::created(SubmissionCreated {
-        submission_id: saved.id.to_string(),
-        message: form
-            .settings()
-            .success_message
-            .unwrap_or_else(|| "Form submitted successfully".to_string()),
+    Ok(ApiResponse::ok(PreviewsDiscarded {
+        discarded,
+        held: held.iter().map(Uuid::to_string).collect(),
     })
     .with_request_id(request_id))
 }
 
-/// Submits a form and redirects (for SSR).
-pub async fn submit_form_redirect(
-    Path(slug): Path<String>,
-    State(db): State<DatabaseConnection>,
-    FormSubmission(data): FormSubmission,
-) -> Result<impl IntoResponse, FormError> {
-    let form = form::Entity::find_by_slug(&db, &slug)
-        .await?
-        .ok_or_else(|| FormError::NotFound(slug.clone()))?;
+/// Deletes a specific submission (admin).
+#[cfg(feature = "admin")]
+pub async fn delete_submission(
+    Path((form_id, sub_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<Deleted>, ApiResponse<()>> {
+    // Verify form exists
+    let form = state
+        .repository
+        .find_form(form_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
 
-    if form.is_deleted() {
-        return Err(FormError::FormDeleted);
-    }
+    let sub = find_active_submission(&state, sub_id)
+        .await
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| {
+            ApiResponse::<()>::from(FormError::SubmissionNotFound(sub_id.to_string()))
+        })?;
 
-    // Load all fields for validation
-    let steps = step::Entity::find_by_form(&db, form.id).await?;
-    let mut all_fields = Vec::new();
-    for s in &steps {
-        let fields = field::Entity::find_by_step(&db, s.id).await?;
-        all_fields.extend(fields);
-    }
-
-    // Validate
-    let errors = validate_submission(&all_fields, &data);
-    if !errors.is_empty() {
-        // Re-render form with errors
-        let html = HtmlRenderer::render_with_values(
-            &db,
-            &form,
-            &HtmlOptions::new(),
-            &data,
-            &errors,
-        )
-        .await?;
-        return Ok(Html(html).into_response());
+    // Verify submission belongs to form
+    if sub.form_id != form_id {
+        return Err(FormError::SubmissionNotFound(sub_id.to_string()).into());
     }
+    LegalHold::ensure_deletable(&sub, "delete submission").map_err(ApiResponse::<()>::from)?;
 
-    // Create submission
-    let now = chrono::Utc::now().fixed_offset();
-    let submission = submission::ActiveModel {
-        id: Set(Uuid::new_v4()),
-        form_id: Set(form.id),
-        data: Set(serde_json::to_value(&data).unwrap_or_default()),
-        metadata: Set(None),
-        current_step_id: Set(None),
-        completed_at: Set(Some(now)),
-        score: Set(None),
-        max_score: Set(None),
-        result_key: Set(None),
-        created_at: Set(now),
-        updated_at: Set(now),
-        deleted_at: Set(None),
-    };
-
-    submission.insert(&db).await?;
+    // Gives back the seats and quota the submission held
+    state
+        .repository
+        .delete_submission(&sub, state.config.encryption_key.as_ref())
+        .await
+        .map_err(ApiResponse::<()>::from)?;
 
-    // Redirect to success page or custom URL
-    let redirect_url = form
-        .settings()
-        .redirect_url
-        .unwrap_or_else(|| format!("/forms/{}/success", slug));
+    if let Some(events) = &state.config.events {
+        events.on_submission_deleted(&form, &sub).await;
+    }
+    if has_quota(&form) {
+        announce_lifecycle(&state, form_id).await;
+    }
 
-    Ok(Redirect::to(&redirect_url).into_response())
+    Ok(ApiResponse::ok(Deleted::submission()).with_request_id(request_id))
 }
 
-/// Success page after form submission.
-pub async fn form_success(
-    Path(slug): Path<String>,
-    State(db): State<DatabaseConnection>,
-) -> Result<impl IntoResponse, FormError> {
-    let form = form::Entity::find_by_slug(&db, &slug)
-        .await?
-        .ok_or_else(|| FormError::NotFound(slug))?;
-
-    let message = form
-        .settings()
-        .success_message
-        .unwrap_or_else(|| "Thank you! Your submission has been received.".to_string());
-
-    Ok(Html(format!(
-        r#"<!DOCTYPE html>
-<html>
-<head><title>Success</title></head>
-<body>
-<h1>Success</h1>
-<p>{message}</p>
-<p><a href="/forms/{slug}">Submit another response</a></p>
-</body>
-</html>"#,
-        slug = form.slug
-    )))
+/// Places a submission under legal hold (admin).
+#[cfg(feature = "admin")]
+pub async fn place_legal_hold(
+    Path((form_id, sub_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AnyFormState>,
+    RequestId(request_id): RequestId,
+) -> Result<ApiResponse<LegalHoldData>, ApiResponse<()>> {
+    set_legal_hold(&state, form_id, sub_id, true, request_id).await
 }
 
-// Admin handlers
-
-/// Lists all forms (admin).
+/// Lifts the legal hold on a submission (admin).
 #[cfg(feature = "admin")]
-pub async fn list_forms(
-    State(db): State<DatabaseConnection>,
+pub async fn lift_legal_hold(
+    Path((form_id, sub_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AnyFormState>,
     RequestId(request_id): RequestId,
-) -> Result<ApiResponse<FormList>, ApiResponse<()>> {
-    let forms = form::Entity::find_active(&db)
-        .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?;
+) -> Result<ApiResponse<LegalHoldData>, ApiResponse<()>> {
+    set_legal_hold(&state, form_id, sub_id, false, request_id).await
+}
 
-    let forms_list: Vec<FormSummary> = forms
-        .into_iter()
-        .map(|f| FormSummary {
-            id: f.id.to_string(),
-            name: f.name,
-            slug: f.slug,
-            description: f.description,
-            created_at: f.created_at.to_rfc3339(),
-            updated_at: f.updated_at.to_rfc3339(),
-        })
-        .collect();
+#[cfg(feature = "admin")]
+async fn set_legal_hold(
+    state: &AnyFormState,
+    form_id: Uuid,
+    sub_id: Uuid,
+    hold: bool,
+    request_id: String,
+) -> Result<ApiResponse<LegalHoldData>, ApiResponse<()>> {
+    let sub = find_form_submission(state, form_id, sub_id).await?;
+    let sub = state
+        .repository
+        .set_legal_hold(sub, hold)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
 
-    let count = forms_list.len();
-    Ok(ApiResponse::ok(FormList {
-        forms: forms_list,
-        count,
+    Ok(ApiResponse::ok(LegalHoldData {
+        submission_id: sub.id.to_string(),
+        legal_hold: sub.legal_hold,
     })
     .with_request_id(request_id))
 }
 
-/// Gets a form by ID (admin).
+/// Promotes a waitlisted submission (admin).
+///
+/// Fails with `409` while the form or a chosen option is still full.
 #[cfg(feature = "admin")]
-pub async fn get_form_by_id(
-    Path(id): Path<Uuid>,
-    State(db): State<DatabaseConnection>,
+pub async fn promote_submission(
+    Path((form_id, sub_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AnyFormState>,
     RequestId(request_id): RequestId,
-) -> Result<ApiResponse<FormJson>, ApiResponse<()>> {
-    let form = form::Entity::find_by_id(id)
-        .one(&db)
+) -> Result<ApiResponse<SubmissionPromoted>, ApiResponse<()>> {
+    let sub = find_form_submission(&state, form_id, sub_id).await?;
+    let form = state
+        .repository
+        .find_form(form_id)
         .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?
-        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(id.to_string())))?;
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
 
-    let json = JsonRenderer::render(&db, &form)
+    let promoted = state
+        .repository
+        .promote_submission(&form, sub, state.config.encryption_key.as_ref())
         .await
-        .map_err(|e| ApiResponse::<()>::from(e))?;
-    Ok(ApiResponse::ok(json).with_request_id(request_id))
+        .map_err(ApiResponse::<()>::from)?;
+
+    if let Some(events) = &state.config.events {
+        events.on_submission_promoted(&form, &promoted).await;
+    }
+    if has_quota(&form) {
+        announce_lifecycle(&state, form_id).await;
+    }
+
+    Ok(ApiResponse::ok(SubmissionPromoted {
+        submission_id: promoted.id.to_string(),
+        status: promoted.status,
+    })
+    .with_request_id(request_id))
 }
 
-/// Lists submissions for a form (admin).
+/// Lists dead letters awaiting replay (admin).
+///
+/// `pending` counts them across all forms, for monitoring, or across the
+/// forms the router serves if limited.
 #[cfg(feature = "admin")]
-pub async fn list_submissions(
-    Path(form_id): Path<Uuid>,
-    State(db): State<DatabaseConnection>,
+pub async fn list_dead_letters(
+    State(state): State<AnyFormState>,
     RequestId(request_id): RequestId,
-) -> Result<ApiResponse<SubmissionList>, ApiResponse<()>> {
-    let submissions = submission::Entity::find_by_form(&db, form_id)
-        .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?;
+    Query(query): Query<DeadLetterListQuery>,
+) -> Result<ApiResponse<DeadLetterList>, ApiResponse<()>> {
+    let (letters, pending) = if state.config.only_forms.is_some() {
+        let pending = state
+            .repository
+            .pending_dead_letters(None)
+            .await
+            .map_err(ApiResponse::<()>::from)?;
+        let mut served: HashMap<Uuid, bool> = HashMap::new();
+        let mut letters = Vec::new();
+        for letter in pending {
+            let serves = match served.get(&letter.form_id) {
+                Some(serves) => *serves,
+                None => {
+                    let form = state
+                        .repository
+                        .find_form(letter.form_id)
+                        .await
+                        .map_err(ApiResponse::<()>::from)?;
+                    let serves = form.is_some_and(|form| state.serves(&form));
+                    *served.entry(letter.form_id).or_insert(serves)
+                }
+            };
+            if serves {
+                letters.push(letter);
+            }
+        }
+        let pending = letters.len() as u64;
+        let letters = letters
+            .into_iter()
+            .filter(|d| query.form_id.map_or(true, |id| d.form_id == id))
+            .collect();
+        (letters, pending)
+    } else {
+        let letters = state
+            .repository
+            .pending_dead_letters(query.form_id)
+            .await
+            .map_err(ApiResponse::<()>::from)?;
+        let pending = state
+            .repository
+            .count_pending_dead_letters()
+            .await
+            .map_err(ApiResponse::<()>::from)?;
+        (letters, pending)
+    };
 
-    let submissions_list: Vec<SubmissionSummary> = submissions
+    let dead_letters: Vec<DeadLetterData> = letters
         .into_iter()
-        .map(|s| SubmissionSummary {
-            id: s.id.to_string(),
-            data: s.data,
-            completed_at: s.completed_at.map(|d| d.to_rfc3339()),
-            score: s.score,
-            created_at: s.created_at.to_rfc3339(),
+        .map(|mut d| {
+            if let Some(key) = &state.config.encryption_key {
+                key.decrypt_data(&mut d.data);
+            }
+            d
+        })
+        .map(|d| DeadLetterData {
+            id: d.id.to_string(),
+            form_id: d.form_id.to_string(),
+            data: d.data,
+            error: d.error,
+            attempts: d.attempts,
+            created_at: d.created_at.to_rfc3339(),
         })
         .collect();
 
-    let count = submissions_list.len();
-    Ok(ApiResponse::ok(SubmissionList {
-        submissions: submissions_list,
+    let count = dead_letters.len();
+    Ok(ApiResponse::ok(DeadLetterList {
+        dead_letters,
         count,
+        pending,
     })
     .with_request_id(request_id))
 }
 
-/// Creates a new form (admin).
+/// Replays a dead letter, storing its submission (admin).
+///
+/// The captured data is stored as validated at the time; form limits and
+/// option capacity still apply. A failed replay records the error and
+/// keeps the dead letter. Replaying an already replayed dead letter returns
+/// its submission.
 #[cfg(feature = "admin")]
-pub async fn create_form(
-    State(db): State<DatabaseConnection>,
+pub async fn replay_dead_letter(
+    Path(id): Path<Uuid>,
+    State(state): State<AnyFormState>,
     RequestId(request_id): RequestId,
-    Json(input): Json<CreateFormInput>,
-) -> Result<ApiResponse<FormCreated>, ApiResponse<()>> {
-    let form = FormBuilder::create(&db, input)
+) -> Result<ApiResponse<DeadLetterReplayed>, ApiResponse<()>> {
+    let repository = state.repository.as_ref();
+    let letter = repository
+        .find_dead_letter(id)
         .await
-        .map_err(|e| ApiResponse::<()>::from(e))?;
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::DeadLetterNotFound(id.to_string())))?;
 
-    Ok(ApiResponse::created(FormCreated {
-        id: form.id.to_string(),
-        name: form.name,
-        slug: form.slug,
-        description: form.description,
-        created_at: form.created_at.to_rfc3339(),
-    })
-    .with_request_id(request_id))
-}
+    if let Some(submission_id) = letter.submission_id {
+        let sub = repository
+            .find_submission(submission_id)
+            .await
+            .map_err(ApiResponse::<()>::from)?
+            .ok_or_else(|| {
+                ApiResponse::<()>::from(FormError::SubmissionNotFound(submission_id.to_string()))
+            })?;
+        return Ok(ApiResponse::ok(DeadLetterReplayed {
+            dead_letter_id: letter.id.to_string(),
+            submission_id: sub.id.to_string(),
+            status: sub.status,
+        })
+        .with_request_id(request_id));
+    }
 
-/// Updates an existing form (admin).
-#[cfg(feature = "admin")]
-pub async fn update_form(
-    Path(id): Path<Uuid>,
-    State(db): State<DatabaseConnection>,
-    RequestId(request_id): RequestId,
-    Json(input): Json<CreateFormInput>,
-) -> Result<ApiResponse<FormUpdated>, ApiResponse<()>> {
-    let form = FormBuilder::update(&db, id, input)
+    let form = repository
+        .find_form(letter.form_id)
         .await
-        .map_err(|e| ApiResponse::<()>::from(e))?;
+        .map_err(ApiResponse::<()>::from)?
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(letter.form_id.to_string())))?;
 
-    Ok(ApiResponse::ok(FormUpdated {
-        id: form.id.to_string(),
-        name: form.name,
-        slug: form.slug,
-        description: form.description,
-        updated_at: form.updated_at.to_rfc3339(),
+    // Captured answers are encrypted like stored ones; store them as
+    // submitted, to be encrypted again
+    let key = state.config.encryption_key.as_ref();
+    let mut captured = letter.clone();
+    if let Some(key) = key {
+        key.decrypt_data(&mut captured.data);
+    }
+    let (data, metadata) = (captured.data(), letter.metadata());
+    let replayed = async {
+        let txn = repository.begin().await?;
+        let steps = txn.load_steps_with_fields(form.id).await?;
+        let fields = StepWithFields::all_fields(&steps);
+        let saved = txn.insert_submission(&form, &fields, &data, metadata, key).await?;
+        txn.commit().await?;
+        Ok::<_, FormError>(saved)
+    };
+    let saved = match replayed.await {
+        Ok(saved) => saved,
+        Err(e) => {
+            repository
+                .mark_dead_letter_failed(letter, &e.to_string())
+                .await
+                .map_err(ApiResponse::<()>::from)?;
+            return Err(e.into());
+        }
+    };
+    let letter = repository
+        .mark_dead_letter_replayed(letter, saved.id)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+    notify_created(&state, &form, &saved).await;
+
+    Ok(ApiResponse::created(DeadLetterReplayed {
+        dead_letter_id: letter.id.to_string(),
+        submission_id: saved.id.to_string(),
+        status: saved.status,
     })
     .with_request_id(request_id))
 }
 
-/// Deletes a form (admin).
+/// Lists the notes on a submission (admin).
 #[cfg(feature = "admin")]
-pub async fn delete_form(
-    Path(id): Path<Uuid>,
-    State(db): State<DatabaseConnection>,
+pub async fn list_submission_notes(
+    Path((form_id, sub_id)): Path<(Uuid, Uuid)>,
+    State(state): State<AnyFormState>,
     RequestId(request_id): RequestId,
-) -> Result<ApiResponse<Deleted>, ApiResponse<()>> {
-    FormBuilder::soft_delete(&db, id)
+) -> Result<ApiResponse<SubmissionNoteList>, ApiResponse<()>> {
+    let sub = find_form_submission(&state, form_id, sub_id).await?;
+
+    let notes: Vec<SubmissionNoteData> = state
+        .repository
+        .submission_notes(sub.id)
         .await
-        .map_err(|e| ApiResponse::<()>::from(e))?;
+        .map_err(ApiResponse::<()>::from)?
+        .into_iter()
+        .map(SubmissionNoteData::from)
+        .collect();
 
-    Ok(ApiResponse::ok(Deleted::form()).with_request_id(request_id))
+    let count = notes.len();
+    Ok(ApiResponse::ok(SubmissionNoteList { notes, count }).with_request_id(request_id))
 }
 
-/// Gets a specific submission (admin).
+/// Adds a note and/or labels to a submission (admin).
 #[cfg(feature = "admin")]
-pub async fn get_submission(
+pub async fn add_submission_note(
     Path((form_id, sub_id)): Path<(Uuid, Uuid)>,
-    State(db): State<DatabaseConnection>,
+    State(state): State<AnyFormState>,
     RequestId(request_id): RequestId,
-) -> Result<ApiResponse<SubmissionData>, ApiResponse<()>> {
-    // Verify form exists
-    let _form = form::Entity::find_by_id(form_id)
-        .one(&db)
-        .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?
-        .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
-
-    let sub = submission::Entity::find_active_by_id(&db, sub_id)
-        .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?
-        .ok_or_else(|| {
-            ApiResponse::<()>::from(FormError::SubmissionNotFound(sub_id.to_string()))
-        })?;
+    Json(input): Json<AddNoteInput>,
+) -> Result<ApiResponse<SubmissionNoteData>, ApiResponse<()>> {
+    let sub = find_form_submission(&state, form_id, sub_id).await?;
 
-    // Verify submission belongs to form
-    if sub.form_id != form_id {
-        return Err(FormError::SubmissionNotFound(sub_id.to_string()).into());
+    let body = input.body.filter(|b| !b.trim().is_empty());
+    let labels = submission_note::normalize_labels(&input.labels);
+    if body.is_none() && labels.is_empty() {
+        return Err(
+            FormError::InvalidData("a note needs a body or at least one label".into()).into(),
+        );
     }
 
-    Ok(ApiResponse::ok(SubmissionData {
-        id: sub.id.to_string(),
-        form_id: sub.form_id.to_string(),
-        data: sub.data,
-        metadata: sub.metadata,
-        completed_at: sub.completed_at.map(|d| d.to_rfc3339()),
-        score: sub.score,
-        max_score: sub.max_score,
-        result_key: sub.result_key,
-        created_at: sub.created_at.to_rfc3339(),
-        updated_at: sub.updated_at.to_rfc3339(),
-    })
-    .with_request_id(request_id))
+    let note = state
+        .repository
+        .add_submission_note(sub.id, body, &labels, input.author)
+        .await
+        .map_err(ApiResponse::<()>::from)?;
+
+    Ok(ApiResponse::created(SubmissionNoteData::from(note)).with_request_id(request_id))
 }
 
-/// Deletes a specific submission (admin).
+/// Loads an active submission, verifying it belongs to the given form.
 #[cfg(feature = "admin")]
-pub async fn delete_submission(
-    Path((form_id, sub_id)): Path<(Uuid, Uuid)>,
-    State(db): State<DatabaseConnection>,
-    RequestId(request_id): RequestId,
-) -> Result<ApiResponse<Deleted>, ApiResponse<()>> {
-    // Verify form exists
-    let _form = form::Entity::find_by_id(form_id)
-        .one(&db)
+async fn find_form_submission(
+    state: &AnyFormState,
+    form_id: Uuid,
+    sub_id: Uuid,
+) -> Result<submission::Model, ApiResponse<()>> {
+    let _form = state
+        .repository
+        .find_form(form_id)
         .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?
+        .map_err(ApiResponse::<()>::from)?
         .ok_or_else(|| ApiResponse::<()>::from(FormError::NotFound(form_id.to_string())))?;
 
-    let sub = submission::Entity::find_active_by_id(&db, sub_id)
+    find_active_submission(state, sub_id)
         .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?
-        .ok_or_else(|| {
-            ApiResponse::<()>::from(FormError::SubmissionNotFound(sub_id.to_string()))
-        })?;
-
-    // Verify submission belongs to form
-    if sub.form_id != form_id {
-        return Err(FormError::SubmissionNotFound(sub_id.to_string()).into());
-    }
+        .map_err(ApiResponse::<()>::from)?
+        .filter(|sub| sub.form_id == form_id)
+        .ok_or_else(|| ApiResponse::<()>::from(FormError::SubmissionNotFound(sub_id.to_string())))
+}
 
-    submission::Entity::soft_delete(&db, sub_id)
-        .await
-        .map_err(|e| ApiResponse::<()>::from(FormError::from(e)))?;
+/// Finds a form that isn't deleted by ID.
+#[cfg(feature = "admin")]
+async fn find_active_form(
+    state: &AnyFormState,
+    id: Uuid,
+) -> Result<Option<form::Model>, FormError> {
+    Ok(state.repository.find_form(id).await?.filter(|f| !f.is_deleted()))
+}
 
-    Ok(ApiResponse::ok(Deleted::submission()).with_request_id(request_id))
+/// Finds a submission that isn't deleted by ID.
+#[cfg(feature = "admin")]
+async fn find_active_submission(
+    state: &AnyFormState,
+    id: Uuid,
+) -> Result<Option<submission::Model>, FormError> {
+    Ok(state.repository.find_submission(id).await?.filter(|s| !s.is_deleted()))
 }
 
 /// Syncs multiple forms (admin).
 /// Creates new forms or updates existing ones based on slug.
 #[cfg(feature = "admin")]
 pub async fn sync_forms(
-    State(db): State<DatabaseConnection>,
+    State(state): State<AnyFormState>,
     RequestId(request_id): RequestId,
     Json(forms): Json<Vec<CreateFormInput>>,
 ) -> ApiResponse<SyncResult> {
+    let repository = state.repository.as_ref();
+    let events = state.config.events.as_deref();
     let mut created = 0;
     let mut updated = 0;
     let mut errors: Vec<String> = Vec::new();
 
     for input in forms {
         let slug = input.slug.clone();
+        if let Err(e) = state.check_slug(&slug) {
+            errors.push(format!("{}: {}", slug, e));
+            continue;
+        }
+        let input = match state.config.field_library.expand(input) {
+            Ok(input) => input,
+            Err(e) => {
+                errors.push(format!("{}: {}", slug, e));
+                continue;
+            }
+        };
 
-        match FormBuilder::find_by_slug(&db, &slug).await {
+        match repository.find_form_by_slug(&slug).await {
             Ok(Some(existing)) => {
-                match FormBuilder::update(&db, existing.id, input).await {
-                    Ok(_) => updated += 1,
+                match repository.update_form(existing.id, input).await {
+                    Ok(form) => {
+                        updated += 1;
+                        if let Some(events) = events {
+                            events.on_form_updated(&form).await;
+                        }
+                        announce_lifecycle(&state, form.id).await;
+                    }
                     Err(e) => errors.push(format!("{}: {}", slug, e)),
                 }
             }
             Ok(None) => {
-                match FormBuilder::create(&db, input).await {
-                    Ok(_) => created += 1,
+                match repository.create_form(input).await {
+                    Ok(form) => {
+                        created += 1;
+                        if let Some(events) = events {
+                            events.on_form_created(&form).await;
+                        }
+                    }
                     Err(e) => errors.push(format!("{}: {}", slug, e)),
                 }
             }
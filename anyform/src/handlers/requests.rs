@@ -0,0 +1,263 @@
+//! Typed request structs for handler inputs.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::submission::SubmissionStatus;
+use crate::schema::FieldValue;
+use crate::services::ImportMode;
+
+/// Query parameters for the success page.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SuccessQuery {
+    /// Status of the submission that was just made.
+    pub status: Option<SubmissionStatus>,
+
+    /// Locale to show the form in, from its settings' translations.
+    pub locale: Option<String>,
+}
+
+/// Query parameters for a form's HTML.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FormHtmlQuery {
+    /// Render a blank form for printing (see [`HtmlOptions::print_mode`]).
+    ///
+    /// [`HtmlOptions::print_mode`]: crate::render::HtmlOptions::print_mode
+    #[serde(default)]
+    pub print: bool,
+
+    /// Locale to show the form in, from its settings' translations.
+    pub locale: Option<String>,
+}
+
+/// Query parameters for a form's JSON schema.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FormJsonQuery {
+    /// How much of each step to include (default: everything).
+    #[serde(default)]
+    pub steps: StepDetail,
+
+    /// Return only this step, with its fields.
+    pub step: Option<Uuid>,
+
+    /// Return only the steps changed after this time, e.g. the `updated_at`
+    /// of an earlier response (see
+    /// [`FormJson::changed_since`](crate::render::FormJson::changed_since)).
+    pub if_changed_since: Option<DateTime<Utc>>,
+
+    /// Locale to show the form in, from its settings' translations.
+    pub locale: Option<String>,
+}
+
+/// Query parameters for submitting or validating answers.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LocaleQuery {
+    /// Locale to word validation errors in, from the router's message
+    /// catalog and the form's translations.
+    pub locale: Option<String>,
+}
+
+/// How much of each step a form's JSON schema includes.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StepDetail {
+    /// Steps with their fields.
+    #[default]
+    Full,
+    /// The skeleton: steps without their fields (see
+    /// [`FormJson::skeleton`](crate::render::FormJson::skeleton)).
+    Ids,
+}
+
+/// Query parameters for a form's `config.js`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ConfigJsQuery {
+    /// Emit a JSONP-style script calling this function instead of an ES module.
+    pub callback: Option<String>,
+}
+
+/// Query parameters for preview routes.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PreviewQuery {
+    /// Signed preview token (see [`crate::preview`]).
+    pub token: String,
+}
+
+/// Body of a single-field validation request.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FieldValidationInput {
+    /// The value to check; absent or null when the field is empty.
+    #[serde(default)]
+    pub value: Option<FieldValue>,
+
+    /// The form's other values, for checks that depend on them.
+    #[serde(default)]
+    pub values: HashMap<String, FieldValue>,
+
+    /// Client sequence number, echoed back so stale responses can be dropped.
+    #[serde(default)]
+    pub seq: Option<u64>,
+}
+
+/// Request body for saving one step of an in-progress submission.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SaveStepInput {
+    /// The step being saved; defaults to the submission's current step.
+    #[serde(default)]
+    pub step_id: Option<Uuid>,
+
+    /// The step's answers, merged into those saved so far.
+    #[serde(default)]
+    pub data: HashMap<String, FieldValue>,
+}
+
+/// Query parameters for dead letter listings.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeadLetterListQuery {
+    /// Only include dead letters of this form.
+    pub form_id: Option<Uuid>,
+}
+
+/// Query parameters for submission change feeds.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SubmissionChangesQuery {
+    /// `next_cursor` of the previous batch; omit to start from the
+    /// beginning.
+    pub cursor: Option<String>,
+
+    /// Changes per batch (default 500, at most 5000).
+    pub limit: Option<u32>,
+}
+
+/// Query parameters for submission exports.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SubmissionExportQuery {
+    /// Include answers to fields classified as sensitive.
+    #[serde(default)]
+    pub include_sensitive: bool,
+
+    /// Include partial responses: submissions in progress with at least one
+    /// step saved.
+    #[serde(default)]
+    pub include_partials: bool,
+
+    /// File format (default `csv`).
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// Query parameters for submission PDFs.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SubmissionPdfQuery {
+    /// Include answers to fields classified as sensitive.
+    #[serde(default)]
+    pub include_sensitive: bool,
+}
+
+/// File format of submission exports.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// Excel workbook with typed cells and a sheet of form details.
+    Xlsx,
+}
+
+/// Query parameters for submission listings.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SubmissionListQuery {
+    /// Search text answers for these words, listing matches best first
+    /// (`search` feature).
+    #[cfg(feature = "search")]
+    pub q: Option<String>,
+
+    /// Only include submissions carrying this label.
+    pub label: Option<String>,
+
+    /// Only include submissions with this status.
+    pub status: Option<SubmissionStatus>,
+
+    /// Only include submissions made in this recurring form instance.
+    pub instance: Option<Uuid>,
+
+    /// Only include submissions made by this respondent.
+    pub respondent: Option<String>,
+
+    /// Only include submissions created on or after this date
+    /// (`YYYY-MM-DD` or RFC 3339).
+    pub since: Option<String>,
+
+    /// Only include submissions created before this timestamp, or on or
+    /// before this date.
+    pub until: Option<String>,
+
+    /// Only include completed (`true`) or unfinished (`false`) submissions.
+    pub completed: Option<bool>,
+
+    /// Sort order: `created_at`, `completed_at` or `score`, prefixed with
+    /// `-` for descending. Defaults to `-created_at`.
+    pub sort: Option<String>,
+
+    /// 1-indexed page number.
+    pub page: Option<u32>,
+
+    /// Submissions per page (default 50, at most 500).
+    pub per_page: Option<u32>,
+}
+
+/// Query parameters for a form's schedule preview.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ScheduleQuery {
+    /// Compute the schedule at this time instead of now.
+    pub at: Option<DateTime<FixedOffset>>,
+
+    /// Upcoming recurring instances to list (default 5, at most 52).
+    pub upcoming: Option<u32>,
+}
+
+/// Query parameters for importing a field's options.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct OptionImportQuery {
+    /// Replace the field's options (default) or merge into them.
+    #[serde(default)]
+    pub mode: ImportMode,
+
+    /// Only report what the import would change.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Request body for subscribing or unsubscribing a digest recipient.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DigestSubscriptionInput {
+    /// Whether the recipient gets the digest.
+    pub subscribed: bool,
+}
+
+/// Request body for adding a browser to or removing it from a duplicate
+/// guard's allow list.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AllowBrowserInput {
+    /// Whether the browser may respond any number of times.
+    pub allowed: bool,
+}
+
+/// Request body for adding a note to a submission.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AddNoteInput {
+    /// Free-text note.
+    #[serde(default)]
+    pub body: Option<String>,
+
+    /// Labels to attach (e.g. `"urgent"`).
+    #[serde(default)]
+    pub labels: Vec<String>,
+
+    /// Who wrote the note.
+    #[serde(default)]
+    pub author: Option<String>,
+}
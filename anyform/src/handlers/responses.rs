@@ -1,5 +1,7 @@
 //! Typed response structs for handler responses.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 /// Response data for form creation.
@@ -29,10 +31,26 @@ pub struct FormSummary {
     pub name: String,
     pub slug: String,
     pub description: Option<String>,
+    /// `draft`, `published` or `archived`.
+    pub status: String,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl From<crate::entities::form::Model> for FormSummary {
+    fn from(form: crate::entities::form::Model) -> Self {
+        Self {
+            id: form.id.to_string(),
+            name: form.name,
+            slug: form.slug,
+            description: form.description,
+            status: form.status,
+            created_at: form.created_at.to_rfc3339(),
+            updated_at: form.updated_at.to_rfc3339(),
+        }
+    }
+}
+
 /// Response data for form list.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FormList {
@@ -51,8 +69,83 @@ pub struct SyncResult {
 /// Response data for form submission.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SubmissionCreated {
-    pub submission_id: String,
+    /// ID of the stored submission; absent when it was not recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submission_id: Option<String>,
+    /// `submitted`, `waitlisted`, or `not_recorded` for submissions outside
+    /// the form's `sample_rate`.
+    pub status: String,
     pub message: String,
+    /// Points earned, for quiz forms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<i32>,
+    /// Points available, for quiz forms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_score: Option<i32>,
+    /// Key of the result the submission maps to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_key: Option<String>,
+    /// The result the submission maps to, to show the respondent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<ResultData>,
+    /// For forms with a duplicate guard, the token marking the browser as
+    /// having responded, for clients that keep it themselves; see
+    /// [`crate::duplicates`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_token: Option<String>,
+}
+
+/// A form result as shown to the respondent.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ResultData {
+    pub key: String,
+    pub title: String,
+    pub description: Option<String>,
+}
+
+impl From<crate::entities::result::Model> for ResultData {
+    fn from(r: crate::entities::result::Model) -> Self {
+        Self {
+            key: r.key,
+            title: r.title,
+            description: r.description,
+        }
+    }
+}
+
+/// Response data for a submission being filled in step by step.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmissionProgress {
+    /// ID of the submission, the key for resuming it.
+    pub submission_id: String,
+    pub status: String,
+    /// The step to fill in next.
+    pub current_step_id: Option<String>,
+    /// Answers saved so far.
+    pub data: serde_json::Value,
+}
+
+impl From<crate::entities::submission::Model> for SubmissionProgress {
+    fn from(sub: crate::entities::submission::Model) -> Self {
+        Self {
+            submission_id: sub.id.to_string(),
+            status: sub.status,
+            current_step_id: sub.current_step_id.map(|id| id.to_string()),
+            data: sub.data,
+        }
+    }
+}
+
+/// Response data for single-field validation.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldValidationResult {
+    /// Name of the validated field.
+    pub field: String,
+    pub valid: bool,
+    pub errors: Vec<String>,
+    /// The request's `seq`, when one was sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
 }
 
 /// Response data for submission retrieval.
@@ -66,6 +159,12 @@ pub struct SubmissionData {
     pub score: Option<i32>,
     pub max_score: Option<i32>,
     pub result_key: Option<String>,
+    pub status: String,
+    pub instance_id: Option<String>,
+    pub respondent_id: Option<String>,
+    pub legal_hold: bool,
+    pub labels: Vec<String>,
+    pub notes: Vec<SubmissionNoteData>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -77,6 +176,15 @@ pub struct SubmissionSummary {
     pub data: serde_json::Value,
     pub completed_at: Option<String>,
     pub score: Option<i32>,
+    pub status: String,
+    pub instance_id: Option<String>,
+    pub respondent_id: Option<String>,
+    pub legal_hold: bool,
+    pub labels: Vec<String>,
+    /// Text answers around the words searched for, escaped for HTML with
+    /// them wrapped in `<mark>`; only when searching with `?q=`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
     pub created_at: String,
 }
 
@@ -87,6 +195,246 @@ pub struct SubmissionList {
     pub count: usize,
 }
 
+/// A changed submission in a change feed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmissionChangeData {
+    /// `insert`, `update` or `delete`.
+    pub op: crate::services::ChangeOp,
+    pub id: String,
+    pub revision_id: Option<String>,
+    pub instance_id: Option<String>,
+    pub data: serde_json::Value,
+    pub status: String,
+    pub completed_at: Option<String>,
+    pub score: Option<i32>,
+    pub max_score: Option<i32>,
+    pub result_key: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub deleted_at: Option<String>,
+}
+
+impl From<crate::services::SubmissionChange> for SubmissionChangeData {
+    fn from(change: crate::services::SubmissionChange) -> Self {
+        let s = change.submission;
+        Self {
+            op: change.op,
+            id: s.id.to_string(),
+            revision_id: s.revision_id.map(|id| id.to_string()),
+            instance_id: s.instance_id.map(|id| id.to_string()),
+            data: s.data,
+            status: s.status,
+            completed_at: s.completed_at.map(|d| d.to_rfc3339()),
+            score: s.score,
+            max_score: s.max_score,
+            result_key: s.result_key,
+            created_at: s.created_at.to_rfc3339(),
+            updated_at: s.updated_at.to_rfc3339(),
+            deleted_at: s.deleted_at.map(|d| d.to_rfc3339()),
+        }
+    }
+}
+
+/// Response data for a submission change feed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmissionChangeList {
+    pub changes: Vec<SubmissionChangeData>,
+    /// Cursor to fetch the next batch from.
+    pub next_cursor: Option<String>,
+    /// Whether more changes are ready; fetch again from `next_cursor`.
+    pub has_more: bool,
+    /// Fields of every revision of the form, oldest first, for the
+    /// submissions' `revision_id`s.
+    pub schema: Vec<crate::services::RevisionSchema>,
+}
+
+/// A submission that failed to persist.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeadLetterData {
+    pub id: String,
+    pub form_id: String,
+    pub data: serde_json::Value,
+    pub error: String,
+    /// Number of failed replays.
+    pub attempts: i32,
+    pub created_at: String,
+}
+
+/// Response data for dead letter list.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeadLetterList {
+    pub dead_letters: Vec<DeadLetterData>,
+    pub count: usize,
+    /// Dead letters awaiting replay across all forms.
+    pub pending: u64,
+}
+
+/// Response data for a replayed dead letter.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeadLetterReplayed {
+    pub dead_letter_id: String,
+    pub submission_id: String,
+    pub status: String,
+}
+
+/// An instance of a recurring form.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FormInstanceData {
+    pub id: String,
+    pub sequence: i32,
+    pub opens_at: String,
+    pub closes_at: String,
+}
+
+impl From<crate::entities::form_instance::Model> for FormInstanceData {
+    fn from(instance: crate::entities::form_instance::Model) -> Self {
+        Self {
+            id: instance.id.to_string(),
+            sequence: instance.sequence,
+            opens_at: instance.opens_at.to_rfc3339(),
+            closes_at: instance.closes_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Submission analytics for one instance of a recurring form.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FormInstanceStats {
+    #[serde(flatten)]
+    pub instance: FormInstanceData,
+    pub submissions: u64,
+    pub suppressed: bool,
+    pub average_score: Option<f64>,
+    pub field_averages: BTreeMap<String, f64>,
+}
+
+impl From<crate::services::InstanceStats> for FormInstanceStats {
+    fn from(stats: crate::services::InstanceStats) -> Self {
+        Self {
+            instance: stats.instance.into(),
+            submissions: stats.submissions,
+            suppressed: stats.suppressed,
+            average_score: stats.average_score,
+            field_averages: stats.field_averages,
+        }
+    }
+}
+
+/// Response data for the instances of a recurring form, oldest first.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FormInstanceList {
+    pub instances: Vec<FormInstanceStats>,
+    pub count: usize,
+}
+
+/// Response data for a simulated submission: what the form would do with
+/// a set of answers, without storing anything.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulationResult {
+    /// Whether the answers would be accepted.
+    pub valid: bool,
+    /// IDs of the steps the respondent would go through, in order.
+    pub route: Vec<String>,
+    /// Every step, with whether it and its fields would be shown.
+    pub steps: Vec<SimulatedStep>,
+    /// Errors validation would report, keyed by field name.
+    pub errors: BTreeMap<String, Vec<String>>,
+    /// Points earned, for quiz forms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<i32>,
+    /// Points available, for quiz forms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_score: Option<i32>,
+    /// Points earned per scoring category.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, i32>,
+    /// The result the answers map to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<ResultData>,
+}
+
+/// A step of a simulated submission.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulatedStep {
+    pub id: String,
+    pub name: String,
+    /// Whether the step is on the route: shown by its condition and not
+    /// jumped over.
+    pub visible: bool,
+    pub fields: Vec<SimulatedField>,
+}
+
+/// A field of a simulated submission.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SimulatedField {
+    pub id: String,
+    pub name: String,
+    /// Whether the field would be shown: its step is on the route and its
+    /// condition holds.
+    pub visible: bool,
+}
+
+/// Response data for a promoted submission.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmissionPromoted {
+    pub submission_id: String,
+    pub status: String,
+}
+
+/// Response data for a signed preview link.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PreviewLink {
+    pub url: String,
+    pub token: String,
+    pub expires_at: String,
+}
+
+/// Response data for discarded preview submissions.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PreviewsDiscarded {
+    pub discarded: u64,
+    /// Preview submissions kept because they are under legal hold.
+    pub held: Vec<String>,
+}
+
+/// Response data for placing or lifting a legal hold.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LegalHoldData {
+    pub submission_id: String,
+    pub legal_hold: bool,
+}
+
+/// An admin note on a submission.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmissionNoteData {
+    pub id: String,
+    pub submission_id: String,
+    pub body: Option<String>,
+    pub labels: Vec<String>,
+    pub author: Option<String>,
+    pub created_at: String,
+}
+
+impl From<crate::entities::submission_note::Model> for SubmissionNoteData {
+    fn from(note: crate::entities::submission_note::Model) -> Self {
+        Self {
+            id: note.id.to_string(),
+            submission_id: note.submission_id.to_string(),
+            labels: note.labels(),
+            body: note.body,
+            author: note.author,
+            created_at: note.created_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Response data for a submission's notes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SubmissionNoteList {
+    pub notes: Vec<SubmissionNoteData>,
+    pub count: usize,
+}
+
 /// Response for delete operations.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Deleted {
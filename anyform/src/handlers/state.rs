@@ -0,0 +1,167 @@
+//! Shared state for anyform handlers.
+
+use std::sync::Arc;
+
+use axum::extract::FromRef;
+use sea_orm::DatabaseConnection;
+
+use crate::analysis::TextAnalyzer;
+use crate::captcha::CaptchaVerifier;
+use crate::database::RetryPolicy;
+use crate::entities::form;
+#[cfg(any(feature = "admin", feature = "graphql"))]
+use crate::error::FormError;
+use crate::events::FormEvents;
+use crate::geocoding::ReverseGeocoder;
+use crate::i18n::MessageCatalog;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::presets::FieldLibrary;
+use crate::encryption::EncryptionKey;
+use crate::preview::PreviewSecret;
+use crate::rate_limit::RateLimiter;
+use crate::repository::{FormsRepository, SeaOrmRepository};
+use crate::render::HtmlOptions;
+use crate::resolver::{FormResolver, SlugResolver};
+use crate::respondent::RespondentResolver;
+use crate::scope::FormScope;
+use crate::spam::SpamSecret;
+use crate::validation::{FieldValidator, ValidatorRegistry};
+
+/// State shared by all anyform handlers.
+///
+/// Handlers read and write forms and submissions through `repository` and
+/// router-level configuration from `config`.
+#[derive(Clone)]
+pub struct AnyFormState {
+    /// Database connection.
+    pub db: DatabaseConnection,
+    /// Storage for forms and submissions.
+    pub repository: Arc<dyn FormsRepository>,
+    /// Router-level handler configuration.
+    pub config: Arc<HandlerConfig>,
+}
+
+impl AnyFormState {
+    /// Creates state with default configuration.
+    #[must_use]
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self::with_config(db, HandlerConfig::default())
+    }
+
+    /// Creates state with the given configuration.
+    #[must_use]
+    pub fn with_config(db: DatabaseConnection, config: HandlerConfig) -> Self {
+        Self {
+            repository: Arc::new(SeaOrmRepository::new(db.clone())),
+            db,
+            config: Arc::new(config),
+        }
+    }
+
+    /// Replaces the [`SeaOrmRepository`] over `db` with `repository`.
+    #[must_use]
+    pub fn with_repository(mut self, repository: Arc<dyn FormsRepository>) -> Self {
+        self.repository = repository;
+        self
+    }
+
+    /// Returns the resolver for the forms public routes name.
+    pub(crate) fn form_resolver(&self) -> &dyn FormResolver {
+        match &self.config.form_resolver {
+            Some(resolver) => resolver.as_ref(),
+            None => &SlugResolver,
+        }
+    }
+
+    /// Returns true if the router serves `form`; see [`crate::scope`].
+    pub(crate) fn serves(&self, form: &form::Model) -> bool {
+        self.config
+            .only_forms
+            .as_ref()
+            .map_or(true, |scope| scope.contains(&form.slug))
+    }
+
+    /// Rejects writing a form under `slug` the router doesn't serve.
+    #[cfg(any(feature = "admin", feature = "graphql"))]
+    pub(crate) fn check_slug(&self, slug: &str) -> Result<(), FormError> {
+        match &self.config.only_forms {
+            Some(scope) => scope.check_slug(slug),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromRef<AnyFormState> for DatabaseConnection {
+    fn from_ref(state: &AnyFormState) -> Self {
+        state.db.clone()
+    }
+}
+
+/// Router-level configuration consulted by handlers.
+#[derive(Debug, Clone, Default)]
+pub struct HandlerConfig {
+    /// Base URL of the WASM hydration client, when it is being served.
+    pub wasm_base_url: Option<String>,
+    /// Version of the served WASM client, used to pin the loader URL.
+    pub wasm_version: Option<String>,
+    /// Subresource integrity hash of the served WASM loader.
+    pub wasm_integrity: Option<String>,
+    /// Hooks notified of submission and form events.
+    pub events: Option<Arc<dyn FormEvents>>,
+    /// Finds the forms public routes name; by slug when unset.
+    pub form_resolver: Option<Arc<dyn FormResolver>>,
+    /// Identifies respondents for submission metadata.
+    pub respondents: Option<Arc<dyn RespondentResolver>>,
+    /// Tags free-text answers with sentiment and themes.
+    pub text_analyzer: Option<Arc<dyn TextAnalyzer>>,
+    /// Names the places location answers point at.
+    pub reverse_geocoder: Option<Arc<dyn ReverseGeocoder>>,
+    /// Signs and verifies preview links.
+    pub preview_secret: Option<PreviewSecret>,
+    /// Encrypts answers to sensitive fields at rest.
+    pub encryption_key: Option<EncryptionKey>,
+    /// Runs server-side checks for `server_validate` fields.
+    pub field_validator: Option<Arc<dyn FieldValidator>>,
+    /// Async validators fields name in their `validators` rules.
+    pub validators: ValidatorRegistry,
+    /// Validation error messages by locale.
+    pub messages: MessageCatalog,
+    /// Field presets form definitions reference with `$ref`.
+    pub field_library: FieldLibrary,
+    /// Retries submissions that find the database busy.
+    pub retry: RetryPolicy,
+    /// Throttles public submissions.
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Signs the render stamps of forms with spam protection.
+    pub spam_secret: SpamSecret,
+    /// Checks the CAPTCHA tokens of forms with `captcha` set.
+    pub captcha_verifier: Option<Arc<dyn CaptchaVerifier>>,
+    /// Whether the router is in read-only mode, so pages say that
+    /// submissions are unavailable.
+    pub read_only: bool,
+    /// The only forms the router serves, if limited; see [`crate::scope`].
+    pub only_forms: Option<FormScope>,
+    /// Counts submissions and times renders, when metrics are enabled.
+    #[cfg(feature = "metrics")]
+    pub metrics: Option<Metrics>,
+}
+
+impl HandlerConfig {
+    /// Returns the HTML options used for server-rendered pages.
+    pub(crate) fn html_options(&self) -> HtmlOptions {
+        let mut options = HtmlOptions::new();
+        if let Some(url) = &self.wasm_base_url {
+            options = options.wasm_base_url(url);
+        }
+        if let Some(version) = &self.wasm_version {
+            options = options.wasm_version(version);
+        }
+        if let Some(integrity) = &self.wasm_integrity {
+            options = options.wasm_integrity(integrity);
+        }
+        options
+            .spam_secret(self.spam_secret.clone())
+            .unavailable(self.read_only)
+    }
+}
@@ -0,0 +1,627 @@
+//! Translated form content and validation messages.
+//!
+//! A form stores its translations per locale in
+//! [`FormSettings::translations`](crate::FormSettings::translations): the
+//! form's name and description, its messages (submit label, success,
+//! waitlist, closed and anonymity notices), and its steps' and fields' text
+//! by name, with option labels by value. Text a translation leaves out stays
+//! as written.
+//!
+//! ```
+//! use anyform::i18n::{FieldTranslation, FormTranslation, StepTranslation};
+//! use anyform::FormSettings;
+//!
+//! let settings = FormSettings::new().translation(
+//!     "fr",
+//!     FormTranslation::new()
+//!         .name("Contact")
+//!         .submit_label("Envoyer")
+//!         .step("Main", StepTranslation::new().name("Principal"))
+//!         .field("email", FieldTranslation::new().label("Courriel"))
+//!         .field("topic", FieldTranslation::new().option("billing", "Facturation")),
+//! );
+//! ```
+//!
+//! `GET /forms/{slug}?locale=fr` and `GET /forms/{slug}/json?locale=fr`
+//! answer with the French content, and submissions sent with `?locale=fr`
+//! get their validation errors in French. A locale without a translation of
+//! its own falls back to its language's (`fr-CA` to `fr`), then to the form
+//! as written.
+//!
+//! Validation errors come from a [`MessageCatalog`] set with
+//! [`AnyFormRouterBuilder::messages`](crate::AnyFormRouterBuilder::messages),
+//! whose [`Messages`] override the built-in English templates by key:
+//!
+//! ```
+//! use anyform::i18n::{MessageCatalog, Messages};
+//!
+//! let catalog = MessageCatalog::new().locale(
+//!     "fr",
+//!     Messages::new()
+//!         .set("required", "{label} est obligatoire")
+//!         .set("min_length", "{label} doit contenir au moins {min} caractères"),
+//! );
+//! ```
+//!
+//! A field's own templates, such as
+//! [`ValidationRules::min_length_message`](crate::ValidationRules::min_length_message),
+//! take precedence over the catalog's, and are translated with
+//! [`FieldTranslation::message`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::branching::JumpRule;
+use crate::entities::{field, field_option, form};
+use crate::repository::{FieldWithOptions, StepWithFields};
+
+/// The built-in English validation messages, by key.
+///
+/// Templates name their values in braces: `{label}` is the field's label,
+/// `{min}` and `{max}` the rule's bound, and `{message}` a pattern's
+/// message.
+pub const DEFAULT_MESSAGES: &[(&str, &str)] = &[
+    ("required", "{label} is required"),
+    ("email", "{label} must be a valid email address"),
+    ("url", "{label} must be a valid URL"),
+    ("number", "{label} must be a number"),
+    ("rows", "{label} must be a list of entries"),
+    (
+        "location",
+        "{label} must be a location (latitude -90 to 90, longitude -180 to 180)",
+    ),
+    ("phone", "{label} must be a valid phone number"),
+    ("date", "{label} must be a valid date (YYYY-MM-DD)"),
+    ("datetime", "{label} must be a valid date and time"),
+    ("time", "{label} must be a valid time (HH:MM)"),
+    ("min_length", "{label} must be at least {min} characters"),
+    ("max_length", "{label} must be at most {max} characters"),
+    ("pattern", "{label}: {message}"),
+    ("invalid_format", "Invalid format"),
+    ("min", "{label} must be at least {min}"),
+    ("max", "{label} must be at most {max}"),
+    (
+        "min_selections",
+        "{label} requires at least {min} selections",
+    ),
+    ("max_selections", "{label} allows at most {max} selections"),
+    ("min_rows", "{label} requires at least {min} entries"),
+    ("max_rows", "{label} allows at most {max} entries"),
+];
+
+/// Validation message templates for one language, overriding
+/// [`DEFAULT_MESSAGES`] by key.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Messages {
+    templates: HashMap<String, String>,
+}
+
+impl Messages {
+    /// Creates messages that use the built-in English templates.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overrides the template for `key` (e.g. `"required"`).
+    #[must_use]
+    pub fn set(mut self, key: impl Into<String>, template: impl Into<String>) -> Self {
+        self.templates.insert(key.into(), template.into());
+        self
+    }
+
+    /// Returns the template for `key`, or an empty string for a key with
+    /// no built-in template.
+    #[must_use]
+    pub fn template(&self, key: &str) -> &str {
+        self.templates
+            .get(key)
+            .map(String::as_str)
+            .or_else(|| {
+                DEFAULT_MESSAGES
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, t)| *t)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Formats the template for `key`, filling in `{label}` and `args`.
+    ///
+    /// Names the template doesn't know are left as written.
+    #[must_use]
+    pub fn format(&self, key: &str, label: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+        interpolate(self.template(key), label, args)
+    }
+}
+
+/// Fills in `{label}` and the named `args` of a message template, e.g. a
+/// field's [`min_length_message`](crate::ValidationRules::min_length_message).
+///
+/// Names the template doesn't know are left as written.
+#[must_use]
+pub fn interpolate(template: &str, label: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut message = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        message.push_str(&rest[..start]);
+        let name = &rest[start + 1..start + len];
+        if name == "label" {
+            message.push_str(label);
+        } else if let Some((_, value)) = args.iter().find(|(n, _)| *n == name) {
+            write!(message, "{value}").unwrap();
+        } else {
+            message.push_str(&rest[start..=start + len]);
+        }
+        rest = &rest[start + len + 1..];
+    }
+    message.push_str(rest);
+    message
+}
+
+/// Validation messages by locale, falling back to English.
+#[derive(Debug, Clone, Default)]
+pub struct MessageCatalog {
+    locales: BTreeMap<String, Messages>,
+    fallback: Messages,
+}
+
+impl MessageCatalog {
+    /// Creates a catalog with only the built-in English messages.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the messages for `locale` (e.g. `"fr"` or `"pt-BR"`).
+    #[must_use]
+    pub fn locale(mut self, locale: impl Into<String>, messages: Messages) -> Self {
+        self.locales.insert(locale.into(), messages);
+        self
+    }
+
+    /// Sets the messages used when no locale is asked for or matches,
+    /// e.g. to reword the English ones.
+    #[must_use]
+    pub fn fallback(mut self, messages: Messages) -> Self {
+        self.fallback = messages;
+        self
+    }
+
+    /// Returns the messages for `locale`, falling back to its language's
+    /// and then to the fallback messages.
+    #[must_use]
+    pub fn messages(&self, locale: Option<&str>) -> &Messages {
+        locale
+            .and_then(|locale| match_locale(self.locales.keys(), locale))
+            .and_then(|key| self.locales.get(key))
+            .unwrap_or(&self.fallback)
+    }
+}
+
+/// A form's text in one locale; see the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormTranslation {
+    /// The form's name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The form's description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Label for the submit button.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submit_label: Option<String>,
+    /// Message shown after a successful submission.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success_message: Option<String>,
+    /// Message shown to waitlisted respondents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waitlist_message: Option<String>,
+    /// Message shown once the form is closed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_message: Option<String>,
+    /// Message shown before the form opens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_open_message: Option<String>,
+    /// Notice shown on anonymous forms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anonymous_notice: Option<String>,
+    /// Steps' text, by step name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub steps: BTreeMap<String, StepTranslation>,
+    /// Fields' text, by field name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, FieldTranslation>,
+}
+
+impl FormTranslation {
+    /// Creates an empty translation.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the form's name.
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the form's description.
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the submit button label.
+    #[must_use]
+    pub fn submit_label(mut self, label: impl Into<String>) -> Self {
+        self.submit_label = Some(label.into());
+        self
+    }
+
+    /// Sets the success message.
+    #[must_use]
+    pub fn success_message(mut self, message: impl Into<String>) -> Self {
+        self.success_message = Some(message.into());
+        self
+    }
+
+    /// Sets the waitlist message.
+    #[must_use]
+    pub fn waitlist_message(mut self, message: impl Into<String>) -> Self {
+        self.waitlist_message = Some(message.into());
+        self
+    }
+
+    /// Sets the closed message.
+    #[must_use]
+    pub fn closed_message(mut self, message: impl Into<String>) -> Self {
+        self.closed_message = Some(message.into());
+        self
+    }
+
+    /// Sets the message shown before the form opens.
+    #[must_use]
+    pub fn not_open_message(mut self, message: impl Into<String>) -> Self {
+        self.not_open_message = Some(message.into());
+        self
+    }
+
+    /// Sets the anonymity notice.
+    #[must_use]
+    pub fn anonymous_notice(mut self, notice: impl Into<String>) -> Self {
+        self.anonymous_notice = Some(notice.into());
+        self
+    }
+
+    /// Translates the step named `name`.
+    #[must_use]
+    pub fn step(mut self, name: impl Into<String>, step: StepTranslation) -> Self {
+        self.steps.insert(name.into(), step);
+        self
+    }
+
+    /// Translates the field named `name`.
+    #[must_use]
+    pub fn field(mut self, name: impl Into<String>, field: FieldTranslation) -> Self {
+        self.fields.insert(name.into(), field);
+        self
+    }
+
+    /// Applies the translation to `form`, writing its messages into the
+    /// settings along with `locale` as the form's language.
+    #[must_use]
+    pub fn apply_form(&self, form: &form::Model, locale: &str) -> form::Model {
+        let mut form = form.clone();
+        replace(&mut form.name, self.name.as_ref());
+        if self.description.is_some() {
+            form.description.clone_from(&self.description);
+        }
+
+        let mut settings = form.settings();
+        let messages = [
+            (&mut settings.submit_label, &self.submit_label),
+            (&mut settings.success_message, &self.success_message),
+            (&mut settings.waitlist_message, &self.waitlist_message),
+            (&mut settings.closed_message, &self.closed_message),
+            (&mut settings.not_open_message, &self.not_open_message),
+            (&mut settings.anonymous_notice, &self.anonymous_notice),
+        ];
+        for (message, translated) in messages {
+            if translated.is_some() {
+                message.clone_from(translated);
+            }
+        }
+        // The direction follows the new language
+        settings.locale = Some(locale.to_string());
+        settings.direction = None;
+        form.settings = serde_json::to_value(settings).ok();
+        form
+    }
+
+    /// Applies the translation to loaded steps, their fields, options and
+    /// sub-fields.
+    ///
+    /// Jump rules name the steps they go to, so their targets are renamed
+    /// along with the steps.
+    pub fn apply_steps(&self, steps: &mut [StepWithFields]) {
+        for StepWithFields { step, fields } in steps {
+            let mut rules = step.jump_rules();
+            if self.rename_targets(&mut rules) {
+                step.next = serde_json::to_value(rules).ok();
+            }
+            if let Some(translation) = self.steps.get(&step.name) {
+                replace(&mut step.name, translation.name.as_ref());
+                if translation.description.is_some() {
+                    step.description.clone_from(&translation.description);
+                }
+            }
+            self.apply_fields(fields);
+        }
+    }
+
+    fn apply_fields(&self, fields: &mut [FieldWithOptions]) {
+        for FieldWithOptions {
+            field,
+            options,
+            fields,
+        } in fields
+        {
+            let mut ui = field.ui();
+            if self.rename_targets(&mut ui.next) {
+                field.ui_options = serde_json::to_value(ui).ok();
+            }
+            self.apply_field(field, options);
+            self.apply_fields(fields);
+        }
+    }
+
+    /// Applies the translation to a field and its options.
+    pub(crate) fn apply_field(
+        &self,
+        field: &mut field::Model,
+        options: &mut [field_option::Model],
+    ) {
+        let Some(translation) = self.fields.get(&field.name) else {
+            return;
+        };
+        replace(&mut field.label, translation.label.as_ref());
+        if translation.placeholder.is_some() {
+            field.placeholder.clone_from(&translation.placeholder);
+        }
+        if translation.help_text.is_some() {
+            field.help_text.clone_from(&translation.help_text);
+        }
+        if translation.pattern_message.is_some() || !translation.messages.is_empty() {
+            let mut rules = field.validation();
+            if let Some(message) = &translation.pattern_message {
+                rules.pattern_message = Some(message.clone());
+            }
+            for (rule, template) in &translation.messages {
+                rules = rules.message(rule, template);
+            }
+            field.validation_rules = serde_json::to_value(rules).ok();
+        }
+        for option in options {
+            replace(&mut option.label, translation.options.get(&option.value));
+        }
+    }
+
+    /// Renames the steps `rules` go to, returning whether any changed.
+    fn rename_targets(&self, rules: &mut [JumpRule]) -> bool {
+        let mut renamed = false;
+        for to in rules.iter_mut().filter_map(|rule| rule.to.as_mut()) {
+            if let Some(name) = self.steps.get(to.as_str()).and_then(|s| s.name.as_ref()) {
+                to.clone_from(name);
+                renamed = true;
+            }
+        }
+        renamed
+    }
+}
+
+/// A step's text in one locale.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepTranslation {
+    /// The step's name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The step's description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl StepTranslation {
+    /// Creates an empty translation.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the step's name.
+    #[must_use]
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the step's description.
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+/// A field's text in one locale.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldTranslation {
+    /// The field's label.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// The field's placeholder.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+    /// The field's help text.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help_text: Option<String>,
+    /// The message shown when the value doesn't match the field's pattern.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern_message: Option<String>,
+    /// The field's error message templates, by rule (e.g. `"min_length"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub messages: BTreeMap<String, String>,
+    /// Option labels, by option value.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, String>,
+}
+
+impl FieldTranslation {
+    /// Creates an empty translation.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the field's label.
+    #[must_use]
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Sets the field's placeholder.
+    #[must_use]
+    pub fn placeholder(mut self, placeholder: impl Into<String>) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    /// Sets the field's help text.
+    #[must_use]
+    pub fn help_text(mut self, help: impl Into<String>) -> Self {
+        self.help_text = Some(help.into());
+        self
+    }
+
+    /// Sets the pattern mismatch message.
+    #[must_use]
+    pub fn pattern_message(mut self, message: impl Into<String>) -> Self {
+        self.pattern_message = Some(message.into());
+        self
+    }
+
+    /// Sets the error message template for `rule`, as
+    /// [`ValidationRules::message`](crate::ValidationRules::message) does.
+    #[must_use]
+    pub fn message(mut self, rule: impl Into<String>, template: impl Into<String>) -> Self {
+        self.messages.insert(rule.into(), template.into());
+        self
+    }
+
+    /// Sets the label of the option with `value`.
+    #[must_use]
+    pub fn option(mut self, value: impl Into<String>, label: impl Into<String>) -> Self {
+        self.options.insert(value.into(), label.into());
+        self
+    }
+}
+
+/// Returns `form` in `locale`, or as written when it has no translation
+/// for it.
+#[must_use]
+pub fn localize_form(form: &form::Model, locale: Option<&str>) -> form::Model {
+    let settings = form.settings();
+    match locale.and_then(|locale| settings.translation_for(locale)) {
+        Some((locale, translation)) => translation.apply_form(form, locale),
+        None => form.clone(),
+    }
+}
+
+/// Translates `form`'s loaded steps into `locale`, leaving them as written
+/// when the form has no translation for it.
+pub fn localize_steps(form: &form::Model, steps: &mut [StepWithFields], locale: Option<&str>) {
+    let settings = form.settings();
+    if let Some((_, translation)) = locale.and_then(|locale| settings.translation_for(locale)) {
+        translation.apply_steps(steps);
+    }
+}
+
+/// Translates `form`'s fields into `locale`, for validation messages that
+/// name them.
+#[cfg(feature = "handlers")]
+pub(crate) fn localize_fields(
+    form: &form::Model,
+    fields: &mut [field::Model],
+    locale: Option<&str>,
+) {
+    let settings = form.settings();
+    if let Some((_, translation)) = locale.and_then(|locale| settings.translation_for(locale)) {
+        for field in fields {
+            translation.apply_field(field, &mut []);
+        }
+    }
+}
+
+/// Finds the locale among `available` that best matches `requested`: the
+/// same locale ignoring case and `-`/`_`, else its language (`fr-CA` to
+/// `fr`).
+pub fn match_locale<'a>(
+    available: impl IntoIterator<Item = &'a String> + Clone,
+    requested: &str,
+) -> Option<&'a str> {
+    let normalize = |locale: &str| locale.replace('_', "-").to_ascii_lowercase();
+    let requested = normalize(requested);
+    let language = requested.split('-').next().unwrap_or_default().to_string();
+
+    let find = |wanted: &str| {
+        available
+            .clone()
+            .into_iter()
+            .find(|locale| normalize(locale) == wanted)
+            .map(String::as_str)
+    };
+    find(&requested).or_else(|| find(&language))
+}
+
+/// Replaces `text` with `translated`, if there is one.
+fn replace(text: &mut String, translated: Option<&String>) {
+    if let Some(translated) = translated {
+        text.clone_from(translated);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_fills_in_names_once() {
+        let messages = Messages::new().set("min_length", "{label} : au moins {min} ({other}");
+        assert_eq!(
+            messages.format("min_length", "{min}", &[("min", &3)]),
+            "{min} : au moins 3 ({other}"
+        );
+        assert_eq!(
+            Messages::new().format("max", "Age", &[("max", &120)]),
+            "Age must be at most 120"
+        );
+    }
+
+    #[test]
+    fn test_match_locale_prefers_exact_then_language() {
+        let available = ["fr".to_string(), "pt-BR".to_string(), "pt".to_string()];
+        assert_eq!(match_locale(&available, "pt_br"), Some("pt-BR"));
+        assert_eq!(match_locale(&available, "pt-PT"), Some("pt"));
+        assert_eq!(match_locale(&available, "FR-ca"), Some("fr"));
+        assert_eq!(match_locale(&available, "de"), None);
+    }
+}
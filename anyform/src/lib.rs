@@ -9,6 +9,7 @@
 //! - **Multi-step wizards**: Progress tracking with conditional logic
 //! - **Survey & quiz support**: Scoring, results, analytics
 //! - **Multi-database**: SQLite, PostgreSQL, MySQL via SeaORM
+//! - **Built-in hydration**: the `assets` feature serves the WASM client
 //!
 //! ## Quick Start
 //!
@@ -30,17 +31,44 @@
 //! }
 //! ```
 
+pub mod analysis;
+pub mod branching;
+pub mod bus;
+pub mod captcha;
 pub mod condition;
+pub mod crm;
+pub mod database;
+pub mod duplicates;
+pub mod encryption;
 pub mod entities;
 pub mod error;
+pub mod events;
 pub mod extractors;
+pub mod fragments;
+pub mod geocoding;
+pub mod i18n;
 pub mod migration;
+pub mod piping;
+pub mod presets;
+pub mod preview;
+pub mod progress;
+pub mod rate_limit;
+pub mod repository;
+pub mod resolver;
+pub mod respondent;
 pub mod response;
 pub mod schema;
 pub mod seed;
 pub mod services;
+pub mod sms;
+pub mod spam;
 pub mod validation;
 
+#[cfg(feature = "pdf")]
+mod pdf;
+#[cfg(feature = "xlsx")]
+mod xlsx;
+
 #[cfg(feature = "json")]
 pub mod render;
 
@@ -50,6 +78,45 @@ pub mod handlers;
 #[cfg(feature = "router")]
 mod router;
 
+#[cfg(all(feature = "router", feature = "admin"))]
+pub mod admin_auth;
+
+#[cfg(feature = "handlers")]
+pub mod versioning;
+
+#[cfg(feature = "router")]
+pub mod gallery;
+
+#[cfg(feature = "router")]
+pub mod routing;
+
+#[cfg(feature = "router")]
+pub mod envelope;
+
+#[cfg(feature = "router")]
+pub mod preflight;
+
+#[cfg(feature = "router")]
+pub mod read_only;
+
+#[cfg(feature = "handlers")]
+pub mod scope;
+
+#[cfg(feature = "assets")]
+pub mod assets;
+
+#[cfg(feature = "chaos")]
+pub mod chaos;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(feature = "graphql")]
+pub mod graphql;
+
+#[cfg(feature = "loadtest")]
+pub mod loadtest;
+
 #[cfg(feature = "cli")]
 pub mod commands;
 
@@ -58,29 +125,52 @@ pub use migration::{Migrator, MigratorTrait};
 
 // Re-export core types
 pub use entities::{
+    crm_push::{
+        ActiveModel as CrmPushActiveModel, CrmPushStatus, Entity as CrmPushEntity,
+        Model as CrmPush,
+    },
+    dead_letter::{
+        ActiveModel as DeadLetterActiveModel, Entity as DeadLetterEntity, Model as DeadLetter,
+    },
+    digest_delivery::{
+        ActiveModel as DigestDeliveryActiveModel, Entity as DigestDeliveryEntity,
+        Model as DigestDelivery,
+    },
     field::{ActiveModel as FieldActiveModel, Entity as FieldEntity, Model as Field},
     field_option::{
         ActiveModel as FieldOptionActiveModel, Entity as FieldOptionEntity, Model as FieldOption,
     },
-    form::{ActiveModel as FormActiveModel, Entity as FormEntity, Model as Form},
+    form::{ActiveModel as FormActiveModel, Entity as FormEntity, FormStatus, Model as Form},
+    form_instance::{
+        ActiveModel as FormInstanceActiveModel, Entity as FormInstanceEntity,
+        Model as FormInstance,
+    },
+    form_revision::{
+        ActiveModel as FormRevisionActiveModel, Entity as FormRevisionEntity,
+        Model as FormRevision,
+    },
     result::{ActiveModel as ResultActiveModel, Entity as ResultEntity, Model as FormResult},
     step::{ActiveModel as StepActiveModel, Entity as StepEntity, Model as Step},
     submission::{
         ActiveModel as SubmissionActiveModel, Entity as SubmissionEntity, Model as Submission,
+        SubmissionMetadata, SubmissionStatus,
     },
 };
 
 // Re-export schema types
 pub use schema::{
-    FieldValue, FormSettings, ScaleLabels, UiOptions, ValidationRules, ValueType,
+    FieldClassification, FieldValue, FormSettings, Frequency, GeoLocation, HelpMedia, Recurrence,
+    ResultsPrivacy, ScaleLabels, UiOptions, ValidationRules, ValueType,
 };
 
 // Re-export condition types
+pub use branching::JumpRule;
 pub use condition::{ConditionOp, ConditionRule};
 
 // Re-export seeding functions
 pub use seed::{
-    clear_seeded_forms, seed_all, seed_contact_form, seed_feedback_form, seed_quiz_form,
+    clear_seeded_forms, seed_all, seed_contact_form, seed_feedback_form, seed_onboarding_form,
+    seed_quiz_form, SeedProvider, SeedReport, Seeder,
 };
 
 // Re-export error types
@@ -88,22 +178,84 @@ pub use error::{FormError, IntoApiError, StepValidationErrors, ValidationErrors}
 
 // Re-export validation
 pub use validation::{
-    is_field_visible, is_step_visible, validate_field, validate_multi_step_submission,
-    validate_step, validate_submission,
+    is_field_visible, is_step_visible, validate_field, validate_field_with,
+    validate_multi_step_submission, validate_multi_step_submission_with, validate_rows,
+    validate_rows_with, validate_step, validate_step_with, validate_submission,
+    validate_submission_with, FieldValidator, ValidatorRegistry,
 };
 
 // Re-export services
-pub use services::{CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FormBuilder};
+pub use services::{
+    Analytics, ConsistencyReport, ConsistencyService, CreateFieldInput, CreateFormInput,
+    CreateOptionInput, CreateResultInput, CreateStepInput, CrmService, DigestSender, DigestService,
+    ExportColumn, FormAnalytics, FormBuilder, FormTransition, InstanceService, InstanceStats,
+    LifecycleService, LockGuard, LegalHold, LockService, ProjectionService, ResultsEngine,
+    ScheduleService, ScoringEngine, Stats, SubmissionPage, SubmissionQuery, SubmissionSchema,
+    SubmissionService, SubmissionSort,
+};
+#[cfg(feature = "search")]
+pub use services::SearchService;
+
+// Re-export event hooks
+pub use events::FormEvents;
+
+// Re-export storage
+pub use repository::{FormsRepository, FormsTransaction, SeaOrmRepository};
+
+// Re-export form resolution
+pub use resolver::{FormResolver, SlugResolver};
+
+// Re-export respondent identity
+pub use respondent::{Respondent, RespondentResolver};
+
+// Re-export shared field definitions
+pub use presets::{FieldLibrary, LintFinding, LintKind};
+
+// Re-export free-text analysis
+pub use analysis::{KeywordAnalyzer, Sentiment, TextAnalysis, TextAnalyzer};
+
+// Re-export reverse geocoding
+pub use geocoding::ReverseGeocoder;
+
+// Re-export answer encryption
+pub use encryption::EncryptionKey;
+
+// Re-export preview links
+pub use preview::{PreviewSecret, PreviewToken};
+
+// Re-export rate limit types
+pub use rate_limit::{RateLimit, RateLimits};
+
+// Re-export spam protection types
+pub use spam::{SpamProtection, SpamSecret};
+
+// Re-export message bus types
+pub use bus::{BusEvent, BusEventType, EventPublisher, MessageBus};
+
+// Re-export CAPTCHA types
+pub use captcha::{Captcha, CaptchaProvider, CaptchaVerifier};
+
+// Re-export duplicate prevention types
+pub use duplicates::{DuplicateGuard, DuplicatePolicy};
+
+// Re-export translation types
+pub use i18n::{FieldTranslation, FormTranslation, MessageCatalog, Messages, StepTranslation};
+
+// Re-export CRM push types
+pub use crm::{CrmClient, CrmMapping, CrmProvider, CrmRecord};
+
+// Re-export SMS notification types
+pub use sms::{SmsMessage, SmsNotification, SmsNotifier, SmsTransport};
 
 // Re-export extractors
-pub use extractors::{FormSubmission, RequestId, ValidatedSubmission};
+pub use extractors::{BodyLimits, FormSubmission, RequestId, RequestMetadata, ValidatedSubmission};
 
 // Re-export response types
 pub use response::{ApiError, ApiResponse, PaginationInfo};
 
 // Re-export renderers
 #[cfg(feature = "json")]
-pub use render::{FormJson, HtmlOptions, HtmlRenderer, JsonRenderer};
+pub use render::{ConfigScript, FormJson, HtmlOptions, HtmlRenderer, JsonRenderer};
 
 #[cfg(feature = "tera")]
 pub use render::TeraRenderer;
@@ -112,6 +264,18 @@ pub use render::TeraRenderer;
 #[cfg(feature = "router")]
 pub use router::{AnyFormRouter, AnyFormRouterBuilder};
 
+#[cfg(feature = "router")]
+pub use routing::TrailingSlash;
+
+#[cfg(feature = "router")]
+pub use envelope::{Problem, ResponseFormat};
+
+#[cfg(all(feature = "router", feature = "admin"))]
+pub use admin_auth::{AdminAuthorizer, ApiKeyAuth, BearerTokenAuth};
+
+#[cfg(feature = "handlers")]
+pub use handlers::{AnyFormState, HandlerConfig};
+
 // Legacy aliases for backwards compatibility
 #[cfg(feature = "router")]
 #[deprecated(since = "0.4.0", note = "Use AnyFormRouter instead")]
@@ -0,0 +1,683 @@
+//! Load testing of the submit path.
+//!
+//! [`SubmissionGenerator`] builds valid submissions from a form's fields, and
+//! [`run`] posts them from concurrent workers to a [`SubmitTarget`] (the
+//! router in-process, see [`RouterTarget`], or a running server) for a fixed
+//! duration. The resulting [`LoadReport`] holds latency percentiles, throughput
+//! and error rates, and serializes to JSON for CI; [`Thresholds`] and
+//! [`LoadReport::regressions`] turn it into a pass/fail result.
+//!
+//! `anyform bench submit` is the command-line front end:
+//!
+//! ```text
+//! anyform bench submit --form contact --concurrency 200 --duration 60s --json
+//! ```
+//!
+//! [`bench_render`] times the renderers instead, loads included, and
+//! [`bench_form`] builds a synthetic form of any number of steps to time them
+//! on (`anyform bench render --steps 20`).
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::Router;
+use http::Request;
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use tower::ServiceExt;
+
+use crate::condition::ConditionRule;
+use crate::entities::{field, field_option, form, step};
+use crate::error::FormError;
+use crate::render::{HtmlOptions, HtmlRenderer, JsonRenderer};
+use crate::schema::{UiOptions, ValidationRules, ValueType};
+use crate::services::{CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput};
+
+// ============================================================================
+// Submission generation
+// ============================================================================
+
+/// Generates realistic, valid submissions for a form.
+///
+/// Values follow each field's type and validation rules, choices rotate
+/// through the field's options (skipping sold-out ones), optional fields are
+/// left out of every fourth submission, and fields or steps hidden by their
+/// conditions are dropped. Fields with a `pattern` rule are only filled when
+/// required, and then may not match.
+#[derive(Debug, Clone)]
+pub struct SubmissionGenerator {
+    steps: Vec<GeneratorStep>,
+}
+
+#[derive(Debug, Clone)]
+struct GeneratorStep {
+    condition: Option<ConditionRule>,
+    fields: Vec<GeneratorField>,
+}
+
+#[derive(Debug, Clone)]
+struct GeneratorField {
+    name: String,
+    value_type: ValueType,
+    required: bool,
+    rules: ValidationRules,
+    ui: UiOptions,
+    options: Vec<String>,
+    /// Sub-fields of a repeatable group.
+    fields: Vec<GeneratorField>,
+}
+
+impl SubmissionGenerator {
+    /// Loads a form's steps, fields and options.
+    pub async fn for_form(
+        db: &DatabaseConnection,
+        form: &form::Model,
+    ) -> Result<Self, FormError> {
+        let mut steps = Vec::new();
+        for s in step::Entity::find_by_form(db, form.id).await? {
+            let mut fields = Vec::new();
+            for f in field::Entity::find_by_step(db, s.id).await? {
+                let sub_fields = field::Entity::find_sub_fields(db, &[f.id]).await?;
+                let Some(mut field) = GeneratorField::load(db, f).await? else {
+                    continue;
+                };
+                for sub_field in sub_fields {
+                    field.fields.extend(GeneratorField::load(db, sub_field).await?);
+                }
+                fields.push(field);
+            }
+            steps.push(GeneratorStep {
+                condition: s.condition_rule(),
+                fields,
+            });
+        }
+        Ok(Self { steps })
+    }
+
+    /// Generates the `n`th submission; the same `n` gives the same values.
+    pub fn generate(&self, n: u64) -> HashMap<String, serde_json::Value> {
+        let mut values = HashMap::new();
+        for (i, f) in self.steps.iter().flat_map(|s| &s.fields).enumerate() {
+            let seed = mix(n, i as u64);
+            let skip = !f.required && (n % 4 == 3 || f.rules.pattern.is_some());
+            if skip {
+                continue;
+            }
+            if let Some(value) = f.value(n, seed) {
+                values.insert(f.name.clone(), value);
+            }
+        }
+
+        // Drop what the respondent would not have seen
+        let mut hidden = HashSet::new();
+        for s in &self.steps {
+            let step_visible = s.condition.as_ref().map_or(true, |c| c.evaluate(&values));
+            for f in &s.fields {
+                let field_visible =
+                    f.ui.condition.as_ref().map_or(true, |c| c.evaluate(&values));
+                if !step_visible || !field_visible {
+                    hidden.insert(f.name.as_str());
+                }
+            }
+        }
+        values.retain(|name, _| !hidden.contains(name.as_str()));
+        values
+    }
+}
+
+impl GeneratorField {
+    /// Loads a field's options; `None` for fields of unknown types.
+    async fn load(db: &DatabaseConnection, f: field::Model) -> Result<Option<Self>, FormError> {
+        let Some(value_type) = f.value_type() else {
+            return Ok(None);
+        };
+        let options = field_option::Entity::find_by_field(db, f.id)
+            .await?
+            .into_iter()
+            .filter(|o| !o.is_sold_out())
+            .map(|o| o.value)
+            .collect();
+        Ok(Some(Self {
+            rules: f.validation(),
+            ui: f.ui(),
+            name: f.name,
+            value_type,
+            required: f.required,
+            options,
+            fields: Vec::new(),
+        }))
+    }
+
+    fn value(&self, n: u64, seed: u64) -> Option<serde_json::Value> {
+        let json = |s: String| Some(serde_json::Value::String(s));
+        match self.value_type {
+            ValueType::Text | ValueType::Hidden => json(self.fit(format!("Load test {n}"))),
+            ValueType::Textarea => json(self.fit(format!(
+                "Load test submission {n}, generated to exercise the submit path."
+            ))),
+            ValueType::Email => json(format!("loadtest+{n}@example.com")),
+            ValueType::Url => json(format!("https://example.com/loadtest/{n}")),
+            ValueType::Tel => json(format!("+1 555 {:04}", n % 10_000)),
+            ValueType::Date => json(format!("2026-{:02}-{:02}", seed % 12 + 1, seed % 28 + 1)),
+            ValueType::DateTime => json(format!(
+                "2026-{:02}-{:02}T{:02}:30",
+                seed % 12 + 1,
+                seed % 28 + 1,
+                seed % 24
+            )),
+            ValueType::Time => json(format!("{:02}:{:02}", seed % 24, seed % 60)),
+            ValueType::Number => {
+                let min = self.rules.min.unwrap_or(0.0).ceil() as i64;
+                let max = self.rules.max.map_or(min + 100, |m| m.floor() as i64);
+                Some(in_range(seed, min, max).into())
+            }
+            ValueType::Rating => {
+                Some(in_range(seed, 1, i64::from(self.ui.max_rating.unwrap_or(5))).into())
+            }
+            ValueType::Scale => Some(
+                in_range(
+                    seed,
+                    i64::from(self.ui.scale_min.unwrap_or(1)),
+                    i64::from(self.ui.scale_max.unwrap_or(10)),
+                )
+                .into(),
+            ),
+            ValueType::Nps => Some(in_range(seed, 0, 10).into()),
+            ValueType::Select | ValueType::Radio | ValueType::ImageChoice => {
+                let option = self.options.get(seed as usize % self.options.len().max(1))?;
+                json(option.clone())
+            }
+            ValueType::MultiSelect => {
+                if self.options.is_empty() {
+                    return None;
+                }
+                let min = self.rules.min_selections.unwrap_or(1).max(1);
+                let max = self.rules.max_selections.unwrap_or(self.options.len());
+                let count = in_range(seed, min as i64, max.min(self.options.len()) as i64) as usize;
+                let start = seed as usize % self.options.len();
+                let chosen = (0..count)
+                    .map(|k| self.options[(start + k) % self.options.len()].clone().into())
+                    .collect();
+                Some(serde_json::Value::Array(chosen))
+            }
+            ValueType::Checkbox => Some((self.required || seed % 2 == 0).into()),
+            ValueType::Repeater => {
+                let count = self.rules.min_rows.unwrap_or(1).max(1) as u64;
+                let rows = (0..count)
+                    .map(|row| {
+                        let seed = mix(seed, row);
+                        let values = self
+                            .fields
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, f)| f.required || f.rules.pattern.is_none())
+                            .filter_map(|(i, f)| {
+                                Some((f.name.clone(), f.value(n, mix(seed, i as u64))?))
+                            });
+                        serde_json::Value::Object(values.collect())
+                    })
+                    .collect();
+                Some(serde_json::Value::Array(rows))
+            }
+            ValueType::Location => Some(serde_json::json!({
+                "lat": in_range(seed, -90, 90),
+                "lng": in_range(mix(seed, 1), -180, 180),
+            })),
+            // Uploads can't be sent as JSON; the rest take no input
+            ValueType::File
+            | ValueType::Image
+            | ValueType::Heading
+            | ValueType::Paragraph
+            | ValueType::Matrix => None,
+        }
+    }
+
+    /// Pads or truncates text to the field's length rules.
+    fn fit(&self, mut text: String) -> String {
+        if let Some(min) = self.rules.min_length {
+            while text.len() < min {
+                text.push('x');
+            }
+        }
+        if let Some(max) = self.rules.max_length {
+            text.truncate(max);
+        }
+        text
+    }
+}
+
+/// Mixes a submission number and field index into a pseudo-random seed
+/// (SplitMix64), so fields vary independently without a random number
+/// generator.
+fn mix(n: u64, i: u64) -> u64 {
+    let mut z = n.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ i.wrapping_add(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Returns a value from `min..=max` (or `min` if the range is empty).
+fn in_range(seed: u64, min: i64, max: i64) -> i64 {
+    if max <= min {
+        return min;
+    }
+    min + (seed % (max - min + 1) as u64) as i64
+}
+
+// ============================================================================
+// Targets
+// ============================================================================
+
+/// Where submissions are sent.
+#[async_trait]
+pub trait SubmitTarget: Send + Sync {
+    /// Submits a JSON body to a form and returns the response status.
+    ///
+    /// The response body is read to the end. Errors are transport failures.
+    async fn submit(&self, slug: &str, body: Vec<u8>) -> Result<u16, String>;
+}
+
+/// Sends submissions through an in-process router, without a network.
+#[derive(Clone)]
+pub struct RouterTarget {
+    router: Router,
+}
+
+impl RouterTarget {
+    /// Wraps a router, e.g. from [`AnyFormRouter::builder`](crate::AnyFormRouter::builder).
+    pub fn new(router: Router) -> Self {
+        Self { router }
+    }
+}
+
+#[async_trait]
+impl SubmitTarget for RouterTarget {
+    async fn submit(&self, slug: &str, body: Vec<u8>) -> Result<u16, String> {
+        let request = Request::post(format!("/api/v1/forms/{slug}"))
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .map_err(|e| e.to_string())?;
+
+        let response = match self.router.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(never) => match never {},
+        };
+        let status = response.status().as_u16();
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(status)
+    }
+}
+
+// ============================================================================
+// Running
+// ============================================================================
+
+/// How hard and how long to drive a target.
+#[derive(Debug, Clone)]
+pub struct LoadTestConfig {
+    /// Number of concurrent workers, each with one request in flight.
+    pub concurrency: usize,
+    /// How long to keep sending requests.
+    pub duration: Duration,
+}
+
+impl Default for LoadTestConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 50,
+            duration: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Requests recorded by one worker.
+#[derive(Default)]
+struct WorkerStats {
+    latencies_us: Vec<u64>,
+    statuses: BTreeMap<String, u64>,
+}
+
+/// Submits generated submissions to `slug` until the configured duration
+/// has passed, and reports the results.
+pub async fn run(
+    target: Arc<dyn SubmitTarget>,
+    slug: &str,
+    generator: Arc<SubmissionGenerator>,
+    config: &LoadTestConfig,
+) -> LoadReport {
+    let counter = Arc::new(AtomicU64::new(0));
+    let started = Instant::now();
+    let deadline = started + config.duration;
+
+    let workers: Vec<_> = (0..config.concurrency.max(1))
+        .map(|_| {
+            let target = target.clone();
+            let generator = generator.clone();
+            let counter = counter.clone();
+            let slug = slug.to_string();
+            tokio::spawn(async move {
+                let mut stats = WorkerStats::default();
+                while Instant::now() < deadline {
+                    let n = counter.fetch_add(1, Ordering::Relaxed);
+                    let body = serde_json::to_vec(&generator.generate(n)).unwrap_or_default();
+
+                    let sent = Instant::now();
+                    let outcome = target.submit(&slug, body).await;
+                    stats.latencies_us.push(sent.elapsed().as_micros() as u64);
+
+                    let key = match outcome {
+                        Ok(status) => status.to_string(),
+                        Err(_) => "error".to_string(),
+                    };
+                    *stats.statuses.entry(key).or_default() += 1;
+                }
+                stats
+            })
+        })
+        .collect();
+
+    let mut latencies_us = Vec::new();
+    let mut statuses = BTreeMap::new();
+    for worker in workers {
+        // A panicking worker loses its own results only
+        let Ok(stats) = worker.await else {
+            continue;
+        };
+        latencies_us.extend(stats.latencies_us);
+        for (status, count) in stats.statuses {
+            *statuses.entry(status).or_default() += count;
+        }
+    }
+
+    LoadReport::new(slug, config.concurrency, started.elapsed(), latencies_us, statuses)
+}
+
+// ============================================================================
+// Reporting
+// ============================================================================
+
+/// Results of a load test.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadReport {
+    /// Slug of the form submitted to.
+    pub form: String,
+    /// Number of concurrent workers.
+    pub concurrency: usize,
+    /// Wall-clock duration of the run, in seconds.
+    pub duration_secs: f64,
+    /// Requests completed.
+    pub requests: u64,
+    /// Requests that failed: non-2xx responses and transport errors.
+    pub errors: u64,
+    /// `errors / requests`.
+    pub error_rate: f64,
+    /// Requests completed per second.
+    pub throughput: f64,
+    /// Request latency.
+    pub latency_ms: Latency,
+    /// Request count per response status (`"error"` for transport errors).
+    pub statuses: BTreeMap<String, u64>,
+}
+
+/// Latency percentiles in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Latency {
+    pub mean: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Latency {
+    /// Summarizes sorted latencies in microseconds.
+    fn from_sorted_us(latencies_us: &[u64]) -> Self {
+        let ms = |us: u64| us as f64 / 1000.0;
+        if latencies_us.is_empty() {
+            return Self::default();
+        }
+        Self {
+            mean: ms(latencies_us.iter().sum::<u64>()) / latencies_us.len() as f64,
+            p50: ms(percentile(latencies_us, 0.50)),
+            p95: ms(percentile(latencies_us, 0.95)),
+            p99: ms(percentile(latencies_us, 0.99)),
+            max: ms(latencies_us[latencies_us.len() - 1]),
+        }
+    }
+}
+
+impl LoadReport {
+    fn new(
+        slug: &str,
+        concurrency: usize,
+        elapsed: Duration,
+        mut latencies_us: Vec<u64>,
+        statuses: BTreeMap<String, u64>,
+    ) -> Self {
+        latencies_us.sort_unstable();
+        let requests = latencies_us.len() as u64;
+        let errors = statuses
+            .iter()
+            .filter(|(status, _)| !status.starts_with('2'))
+            .map(|(_, count)| count)
+            .sum();
+        let latency = Latency::from_sorted_us(&latencies_us);
+        let secs = elapsed.as_secs_f64();
+
+        Self {
+            form: slug.to_string(),
+            concurrency,
+            duration_secs: secs,
+            requests,
+            errors,
+            error_rate: if requests == 0 { 0.0 } else { errors as f64 / requests as f64 },
+            throughput: if secs > 0.0 { requests as f64 / secs } else { 0.0 },
+            latency_ms: latency,
+            statuses,
+        }
+    }
+
+    /// Compares this run with a baseline and describes each metric that got
+    /// worse by more than `tolerance` (e.g. `0.2` for 20%). The error rate may
+    /// rise by `tolerance` percentage points, so a clean baseline still allows
+    /// the odd failure.
+    pub fn regressions(&self, baseline: &LoadReport, tolerance: f64) -> Vec<String> {
+        let mut regressions = Vec::new();
+        for (name, now, before) in [
+            ("p50", self.latency_ms.p50, baseline.latency_ms.p50),
+            ("p95", self.latency_ms.p95, baseline.latency_ms.p95),
+            ("p99", self.latency_ms.p99, baseline.latency_ms.p99),
+        ] {
+            if now > before * (1.0 + tolerance) {
+                regressions.push(format!("{name} latency {now:.2} ms exceeds baseline {before:.2} ms"));
+            }
+        }
+        if self.throughput < baseline.throughput * (1.0 - tolerance) {
+            regressions.push(format!(
+                "throughput {:.1} req/s is below baseline {:.1} req/s",
+                self.throughput, baseline.throughput
+            ));
+        }
+        if self.error_rate > baseline.error_rate + tolerance / 100.0 {
+            regressions.push(format!(
+                "error rate {:.2}% exceeds baseline {:.2}%",
+                self.error_rate * 100.0,
+                baseline.error_rate * 100.0
+            ));
+        }
+        regressions
+    }
+}
+
+/// Nearest-rank percentile of sorted values.
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Absolute limits a load test must stay within.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Thresholds {
+    /// Highest acceptable median latency, in milliseconds.
+    pub max_p50_ms: Option<f64>,
+    /// Highest acceptable 95th percentile latency, in milliseconds.
+    pub max_p95_ms: Option<f64>,
+    /// Highest acceptable 99th percentile latency, in milliseconds.
+    pub max_p99_ms: Option<f64>,
+    /// Highest acceptable error rate, from 0.0 to 1.0.
+    pub max_error_rate: Option<f64>,
+    /// Lowest acceptable throughput, in requests per second.
+    pub min_throughput: Option<f64>,
+}
+
+impl Thresholds {
+    /// Describes each limit the report breaks; empty if it passes.
+    pub fn check(&self, report: &LoadReport) -> Vec<String> {
+        let mut failures = Vec::new();
+        for (name, limit, value) in [
+            ("p50", self.max_p50_ms, report.latency_ms.p50),
+            ("p95", self.max_p95_ms, report.latency_ms.p95),
+            ("p99", self.max_p99_ms, report.latency_ms.p99),
+        ] {
+            if let Some(limit) = limit.filter(|&limit| value > limit) {
+                failures.push(format!("{name} latency {value:.2} ms exceeds {limit:.2} ms"));
+            }
+        }
+        if let Some(limit) = self.max_error_rate.filter(|&limit| report.error_rate > limit) {
+            failures.push(format!(
+                "error rate {:.2}% exceeds {:.2}%",
+                report.error_rate * 100.0,
+                limit * 100.0
+            ));
+        }
+        if let Some(limit) = self.min_throughput.filter(|&limit| report.throughput < limit) {
+            failures.push(format!(
+                "throughput {:.1} req/s is below {limit:.1} req/s",
+                report.throughput
+            ));
+        }
+        if report.requests == 0 {
+            failures.push("no requests completed".to_string());
+        }
+        failures
+    }
+}
+
+// ============================================================================
+// Render benchmarks
+// ============================================================================
+
+/// Results of a render benchmark.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RenderReport {
+    /// Slug of the form rendered.
+    pub form: String,
+    /// Steps in the form.
+    pub steps: usize,
+    /// Fields in the form, sub-fields included.
+    pub fields: usize,
+    /// Renders timed of each kind.
+    pub iterations: usize,
+    /// Latency of [`HtmlRenderer::render`].
+    pub html_ms: Latency,
+    /// Latency of [`JsonRenderer::render`].
+    pub json_ms: Latency,
+}
+
+/// Renders `form` to HTML and to JSON `iterations` times each, one at a
+/// time, and reports the latency of each, loading the form's steps, fields
+/// and options included.
+pub async fn bench_render(
+    db: &DatabaseConnection,
+    form: &form::Model,
+    iterations: usize,
+) -> Result<RenderReport, FormError> {
+    let options = HtmlOptions::new();
+    let mut html_us = Vec::with_capacity(iterations);
+    let mut json_us = Vec::with_capacity(iterations);
+    let mut json = JsonRenderer::render(db, form).await?;
+    for _ in 0..iterations {
+        let started = Instant::now();
+        HtmlRenderer::render(db, form, &options).await?;
+        html_us.push(started.elapsed().as_micros() as u64);
+
+        let started = Instant::now();
+        json = JsonRenderer::render(db, form).await?;
+        json_us.push(started.elapsed().as_micros() as u64);
+    }
+    html_us.sort_unstable();
+    json_us.sort_unstable();
+
+    let fields = json
+        .steps
+        .iter()
+        .flat_map(|s| &s.fields)
+        .map(|f| 1 + f.fields.len())
+        .sum();
+    Ok(RenderReport {
+        form: form.slug.clone(),
+        steps: json.steps.len(),
+        fields,
+        iterations,
+        html_ms: Latency::from_sorted_us(&html_us),
+        json_ms: Latency::from_sorted_us(&json_us),
+    })
+}
+
+/// A synthetic form of `steps` steps to benchmark rendering on.
+///
+/// Each step has a text and an email field, a select and a radio group with
+/// four options each, and a multi-select with six.
+pub fn bench_form(slug: &str, steps: usize) -> CreateFormInput {
+    let options = |n: usize| {
+        (1..=n)
+            .map(|i| CreateOptionInput::new(format!("Option {i}"), format!("option_{i}")))
+            .collect::<Vec<_>>()
+    };
+    (1..=steps).fold(
+        CreateFormInput::new(format!("Benchmark ({steps} steps)"), slug),
+        |input, n| {
+            input.step(
+                CreateStepInput::new(format!("Step {n}"))
+                    .order(n as i32)
+                    .fields(vec![
+                        CreateFieldInput::new(format!("name_{n}"), "Name", "text").required(),
+                        CreateFieldInput::new(format!("email_{n}"), "Email", "email"),
+                        CreateFieldInput::new(format!("country_{n}"), "Country", "select")
+                            .options(options(4)),
+                        CreateFieldInput::new(format!("plan_{n}"), "Plan", "radio")
+                            .options(options(4)),
+                        CreateFieldInput::new(format!("topics_{n}"), "Topics", "multi_select")
+                            .options(options(6)),
+                    ]),
+            )
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let values: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&values, 0.50), 50);
+        assert_eq!(percentile(&values, 0.95), 95);
+        assert_eq!(percentile(&values, 0.99), 99);
+        assert_eq!(percentile(&[7], 0.99), 7);
+    }
+
+    #[test]
+    fn test_in_range_is_inclusive() {
+        assert!((0..1000).map(|s| in_range(s, 1, 5)).all(|v| (1..=5).contains(&v)));
+        assert!((0..1000).any(|s| in_range(s, 1, 5) == 5));
+        assert_eq!(in_range(42, 3, 3), 3);
+    }
+}
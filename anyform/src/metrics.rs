@@ -0,0 +1,226 @@
+//! Prometheus metrics.
+//!
+//! With the `metrics` feature and a handle given to
+//! [`AnyFormRouterBuilder::metrics`](crate::AnyFormRouterBuilder::metrics),
+//! the router counts submissions and validation failures, times page
+//! renders and database queries, and serves the lot in the Prometheus text
+//! format at [`PATH`]:
+//!
+//! | Series | Type | Labels |
+//! |--------|------|--------|
+//! | `anyform_submissions_total` | counter | `form` |
+//! | `anyform_validation_failures_total` | counter | `form` |
+//! | `anyform_render_duration_seconds` | histogram | `format` (`html` or `json`) |
+//! | `anyform_db_query_duration_seconds` | histogram | `outcome` (`ok` or `error`) |
+//!
+//! [`Metrics`] is cheap to clone; keep a clone to serve the metrics from
+//! another listener with [`Metrics::render`] instead.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use axum::response::{IntoResponse, Response};
+use http::header;
+use sea_orm::DatabaseConnection;
+
+/// Where the metrics are served.
+pub const PATH: &str = "/metrics";
+
+/// Content type of the Prometheus text format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Upper bounds of the histogram buckets, in seconds.
+pub const BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+];
+
+/// Handle on the router's metrics.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    inner: Arc<Mutex<Registry>>,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    submissions: BTreeMap<String, u64>,
+    validation_failures: BTreeMap<String, u64>,
+    renders: BTreeMap<&'static str, Histogram>,
+    queries: BTreeMap<&'static str, Histogram>,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        for (bound, bucket) in BUCKETS.iter().zip(&mut self.buckets) {
+            if seconds <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    /// Writes the histogram's series, labeled `label="value"`.
+    fn write(&self, out: &mut String, name: &str, label: &str, value: &str) {
+        for (bound, bucket) in BUCKETS.iter().zip(&self.buckets) {
+            let _ = writeln!(out, "{name}_bucket{{{label}=\"{value}\",le=\"{bound}\"}} {bucket}");
+        }
+        let count = self.count;
+        let _ = writeln!(out, "{name}_bucket{{{label}=\"{value}\",le=\"+Inf\"}} {count}");
+        let _ = writeln!(out, "{name}_sum{{{label}=\"{value}\"}} {}", self.sum);
+        let _ = writeln!(out, "{name}_count{{{label}=\"{value}\"}} {count}");
+    }
+}
+
+impl Metrics {
+    /// Creates an empty set of metrics.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn registry(&self) -> MutexGuard<'_, Registry> {
+        // Counters stay usable if a panic poisoned the lock
+        self.inner.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Counts a submission accepted to the form `slug`.
+    pub fn record_submission(&self, slug: &str) {
+        *self.registry().submissions.entry(slug.to_string()).or_default() += 1;
+    }
+
+    /// Counts a submission to the form `slug` rejected by validation.
+    pub fn record_validation_failure(&self, slug: &str) {
+        *self.registry().validation_failures.entry(slug.to_string()).or_default() += 1;
+    }
+
+    /// Records how long rendering a form in `format` (`html` or `json`) took.
+    pub fn observe_render(&self, format: &'static str, elapsed: Duration) {
+        self.registry().renders.entry(format).or_default().observe(elapsed);
+    }
+
+    /// Records how long a database query took, and whether it failed.
+    pub fn observe_query(&self, elapsed: Duration, failed: bool) {
+        let outcome = if failed { "error" } else { "ok" };
+        self.registry().queries.entry(outcome).or_default().observe(elapsed);
+    }
+
+    /// Times the queries run through `db`, and through the transactions it
+    /// begins. Only connections made after this see it: call it before
+    /// cloning `db`.
+    pub fn instrument(&self, db: &mut DatabaseConnection) {
+        let metrics = self.clone();
+        db.set_metric_callback(move |info| metrics.observe_query(info.elapsed, info.failed));
+    }
+
+    /// Returns the metrics in the Prometheus text format.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let registry = self.registry();
+        let mut out = String::new();
+
+        let counters = [
+            (
+                "anyform_submissions_total",
+                "Submissions accepted, by form.",
+                &registry.submissions,
+            ),
+            (
+                "anyform_validation_failures_total",
+                "Submissions rejected by validation, by form.",
+                &registry.validation_failures,
+            ),
+        ];
+        for (name, help, values) in counters {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} counter");
+            for (form, count) in values {
+                let _ = writeln!(out, "{name}{{form=\"{}\"}} {count}", escape_label(form));
+            }
+        }
+
+        let histograms = [
+            (
+                "anyform_render_duration_seconds",
+                "Time spent rendering forms.",
+                "format",
+                &registry.renders,
+            ),
+            (
+                "anyform_db_query_duration_seconds",
+                "Time spent in database queries.",
+                "outcome",
+                &registry.queries,
+            ),
+        ];
+        for (name, help, label, values) in histograms {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} histogram");
+            for (value, histogram) in values {
+                histogram.write(&mut out, name, label, value);
+            }
+        }
+        out
+    }
+}
+
+/// Serves the metrics.
+pub(crate) fn export(metrics: &Metrics) -> Response {
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], metrics.render()).into_response()
+}
+
+/// Escapes a label value for the text format.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters_are_labeled_by_form() {
+        let metrics = Metrics::new();
+        metrics.record_submission("contact");
+        metrics.record_submission("contact");
+        metrics.record_validation_failure("say \"hi\"");
+
+        let text = metrics.render();
+        assert!(text.contains("# TYPE anyform_submissions_total counter\n"));
+        assert!(text.contains("anyform_submissions_total{form=\"contact\"} 2\n"));
+        assert!(text.contains("anyform_validation_failures_total{form=\"say \\\"hi\\\"\"} 1\n"));
+    }
+
+    #[test]
+    fn test_histograms_are_cumulative() {
+        let metrics = Metrics::new();
+        metrics.observe_render("html", Duration::from_millis(3));
+        metrics.observe_render("html", Duration::from_millis(30));
+        metrics.observe_query(Duration::from_secs(10), true);
+
+        let text = metrics.render();
+        let name = "anyform_render_duration_seconds";
+        assert!(text.contains(&format!("{name}_bucket{{format=\"html\",le=\"0.001\"}} 0\n")));
+        assert!(text.contains(&format!("{name}_bucket{{format=\"html\",le=\"0.005\"}} 1\n")));
+        assert!(text.contains(&format!("{name}_bucket{{format=\"html\",le=\"0.05\"}} 2\n")));
+        assert!(text.contains(&format!("{name}_bucket{{format=\"html\",le=\"+Inf\"}} 2\n")));
+        assert!(text.contains(&format!("{name}_count{{format=\"html\"}} 2\n")));
+
+        let name = "anyform_db_query_duration_seconds";
+        assert!(text.contains(&format!("{name}_bucket{{outcome=\"error\",le=\"5\"}} 0\n")));
+        assert!(text.contains(&format!("{name}_bucket{{outcome=\"error\",le=\"+Inf\"}} 1\n")));
+        assert!(text.contains(&format!("{name}_sum{{outcome=\"error\"}} 10\n")));
+    }
+}
@@ -0,0 +1,106 @@
+//! Form revisions and submission revision stamping.
+//!
+//! Every create/update of a form records a snapshot of its fields, and
+//! submissions reference the revision they were made against, so exports can
+//! resolve columns and labels across form changes.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AfFormRevisions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AfFormRevisions::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AfFormRevisions::FormId).uuid().not_null())
+                    .col(ColumnDef::new(AfFormRevisions::Revision).integer().not_null())
+                    .col(ColumnDef::new(AfFormRevisions::Fields).json().not_null())
+                    .col(
+                        ColumnDef::new(AfFormRevisions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_af_form_revisions_form")
+                            .from(AfFormRevisions::Table, AfFormRevisions::FormId)
+                            .to(AfForms::Table, AfForms::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_form_revisions_form_revision")
+                    .table(AfFormRevisions::Table)
+                    .col(AfFormRevisions::FormId)
+                    .col(AfFormRevisions::Revision)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSubmissions::Table)
+                    .add_column(ColumnDef::new(AfSubmissions::RevisionId).uuid())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSubmissions::Table)
+                    .drop_column(AfSubmissions::RevisionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AfFormRevisions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfForms {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+pub enum AfSubmissions {
+    Table,
+    RevisionId,
+}
+
+#[derive(DeriveIden)]
+pub enum AfFormRevisions {
+    Table,
+    Id,
+    FormId,
+    Revision,
+    Fields,
+    CreatedAt,
+}
@@ -0,0 +1,80 @@
+//! Admin notes and labels on submissions.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AfSubmissionNotes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AfSubmissionNotes::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AfSubmissionNotes::SubmissionId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AfSubmissionNotes::Body).text())
+                    .col(ColumnDef::new(AfSubmissionNotes::Labels).json().not_null())
+                    .col(ColumnDef::new(AfSubmissionNotes::Author).string_len(255))
+                    .col(
+                        ColumnDef::new(AfSubmissionNotes::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_af_submission_notes_submission")
+                            .from(AfSubmissionNotes::Table, AfSubmissionNotes::SubmissionId)
+                            .to(AfSubmissions::Table, AfSubmissions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_submission_notes_submission")
+                    .table(AfSubmissionNotes::Table)
+                    .col(AfSubmissionNotes::SubmissionId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AfSubmissionNotes::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfSubmissions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+pub enum AfSubmissionNotes {
+    Table,
+    Id,
+    SubmissionId,
+    Body,
+    Labels,
+    Author,
+    CreatedAt,
+}
@@ -0,0 +1,64 @@
+//! Capacity-limited field options.
+//!
+//! `capacity` caps how many submissions may choose an option; `reserved`
+//! counts the seats taken so far and is incremented atomically at submit time.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFieldOptions::Table)
+                    .add_column(ColumnDef::new(AfFieldOptions::Capacity).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFieldOptions::Table)
+                    .add_column(
+                        ColumnDef::new(AfFieldOptions::Reserved)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFieldOptions::Table)
+                    .drop_column(AfFieldOptions::Reserved)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFieldOptions::Table)
+                    .drop_column(AfFieldOptions::Capacity)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfFieldOptions {
+    Table,
+    Capacity,
+    Reserved,
+}
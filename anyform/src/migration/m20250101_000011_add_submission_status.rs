@@ -0,0 +1,42 @@
+//! Submission status (`submitted` or `waitlisted`).
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSubmissions::Table)
+                    .add_column(
+                        ColumnDef::new(AfSubmissions::Status)
+                            .string_len(32)
+                            .not_null()
+                            .default("submitted"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSubmissions::Table)
+                    .drop_column(AfSubmissions::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfSubmissions {
+    Table,
+    Status,
+}
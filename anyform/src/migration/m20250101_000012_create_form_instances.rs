@@ -0,0 +1,115 @@
+//! Recurring form instances and submission instance stamping.
+//!
+//! Forms with a recurrence rule open periodic instances (e.g. a weekly pulse
+//! survey); each submission references the instance it was made in.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AfFormInstances::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AfFormInstances::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AfFormInstances::FormId).uuid().not_null())
+                    .col(ColumnDef::new(AfFormInstances::Sequence).integer().not_null())
+                    .col(
+                        ColumnDef::new(AfFormInstances::OpensAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AfFormInstances::ClosesAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AfFormInstances::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_af_form_instances_form")
+                            .from(AfFormInstances::Table, AfFormInstances::FormId)
+                            .to(AfForms::Table, AfForms::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_form_instances_form_sequence")
+                    .table(AfFormInstances::Table)
+                    .col(AfFormInstances::FormId)
+                    .col(AfFormInstances::Sequence)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSubmissions::Table)
+                    .add_column(ColumnDef::new(AfSubmissions::InstanceId).uuid())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSubmissions::Table)
+                    .drop_column(AfSubmissions::InstanceId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AfFormInstances::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfForms {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+pub enum AfSubmissions {
+    Table,
+    InstanceId,
+}
+
+#[derive(DeriveIden)]
+pub enum AfFormInstances {
+    Table,
+    Id,
+    FormId,
+    Sequence,
+    OpensAt,
+    ClosesAt,
+    CreatedAt,
+}
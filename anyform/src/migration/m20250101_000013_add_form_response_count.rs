@@ -0,0 +1,60 @@
+//! Cached count of accepted responses per form.
+//!
+//! Lets the submit path enforce `close_after` quotas with a conditional
+//! `UPDATE` instead of counting submissions on every request.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfForms::Table)
+                    .add_column(
+                        ColumnDef::new(AfForms::ResponseCount)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Backfill from existing accepted submissions
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE af_forms SET response_count = (\
+                    SELECT COUNT(*) FROM af_submissions \
+                    WHERE af_submissions.form_id = af_forms.id \
+                    AND af_submissions.deleted_at IS NULL \
+                    AND af_submissions.completed_at IS NOT NULL \
+                    AND af_submissions.status <> 'waitlisted')",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfForms::Table)
+                    .drop_column(AfForms::ResponseCount)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfForms {
+    Table,
+    ResponseCount,
+}
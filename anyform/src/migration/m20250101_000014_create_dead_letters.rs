@@ -0,0 +1,87 @@
+//! Dead letters: validated submissions that failed to persist.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AfDeadLetters::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AfDeadLetters::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AfDeadLetters::FormId).uuid().not_null())
+                    .col(ColumnDef::new(AfDeadLetters::Data).json().not_null())
+                    .col(ColumnDef::new(AfDeadLetters::Metadata).json())
+                    .col(ColumnDef::new(AfDeadLetters::Error).text().not_null())
+                    .col(
+                        ColumnDef::new(AfDeadLetters::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(AfDeadLetters::SubmissionId).uuid())
+                    .col(
+                        ColumnDef::new(AfDeadLetters::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(AfDeadLetters::ReplayedAt).timestamp_with_time_zone())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_af_dead_letters_form")
+                            .from(AfDeadLetters::Table, AfDeadLetters::FormId)
+                            .to(AfForms::Table, AfForms::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_dead_letters_form")
+                    .table(AfDeadLetters::Table)
+                    .col(AfDeadLetters::FormId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AfDeadLetters::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfForms {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+pub enum AfDeadLetters {
+    Table,
+    Id,
+    FormId,
+    Data,
+    Metadata,
+    Error,
+    Attempts,
+    SubmissionId,
+    CreatedAt,
+    ReplayedAt,
+}
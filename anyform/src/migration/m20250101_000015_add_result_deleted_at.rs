@@ -0,0 +1,54 @@
+//! Soft deletes for results, and children of soft-deleted forms.
+//!
+//! Soft-deleting a form now soft-deletes its submissions and results with
+//! the same timestamp. This backfills that for forms deleted before.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfResults::Table)
+                    .add_column(ColumnDef::new(AfResults::DeletedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        for table in ["af_submissions", "af_results"] {
+            db.execute_unprepared(&format!(
+                "UPDATE {table} SET deleted_at = (\
+                    SELECT af_forms.deleted_at FROM af_forms \
+                    WHERE af_forms.id = {table}.form_id) \
+                WHERE deleted_at IS NULL \
+                AND form_id IN (SELECT id FROM af_forms WHERE deleted_at IS NOT NULL)"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfResults::Table)
+                    .drop_column(AfResults::DeletedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfResults {
+    Table,
+    DeletedAt,
+}
@@ -0,0 +1,155 @@
+//! Makes form slugs unique among active forms only.
+//!
+//! `af_forms.slug` was unique across all rows, so a soft-deleted form kept
+//! its slug forever, while `FormBuilder` only checked active forms before
+//! inserting. The column constraint is replaced by a unique index over
+//! active slugs: a partial index on SQLite and PostgreSQL, and an index on a
+//! generated column that is `NULL` for deleted forms on MySQL.
+
+use sea_orm::sqlx;
+use sea_orm::{ConnectionTrait, DatabaseBackend};
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::SchemaManagerConnection;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DatabaseBackend::Sqlite => {
+                rebuild_sqlite_forms(manager, "").await?;
+                db.execute_unprepared(
+                    "CREATE UNIQUE INDEX idx_af_forms_active_slug ON af_forms (slug) \
+                    WHERE deleted_at IS NULL",
+                )
+                .await?;
+            }
+            DatabaseBackend::Postgres => {
+                // Created before the table was renamed from asf_forms
+                db.execute_unprepared(
+                    "ALTER TABLE af_forms DROP CONSTRAINT IF EXISTS asf_forms_slug_key",
+                )
+                .await?;
+                db.execute_unprepared(
+                    "ALTER TABLE af_forms DROP CONSTRAINT IF EXISTS af_forms_slug_key",
+                )
+                .await?;
+                db.execute_unprepared(
+                    "CREATE UNIQUE INDEX idx_af_forms_active_slug ON af_forms (slug) \
+                    WHERE deleted_at IS NULL",
+                )
+                .await?;
+            }
+            DatabaseBackend::MySql => {
+                db.execute_unprepared("ALTER TABLE af_forms DROP INDEX slug")
+                    .await?;
+                db.execute_unprepared(
+                    "ALTER TABLE af_forms \
+                    ADD COLUMN active_slug VARCHAR(255) \
+                    GENERATED ALWAYS AS (IF(deleted_at IS NULL, slug, NULL)) VIRTUAL, \
+                    ADD UNIQUE INDEX idx_af_forms_active_slug (active_slug)",
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        match manager.get_database_backend() {
+            DatabaseBackend::Sqlite => {
+                rebuild_sqlite_forms(manager, " UNIQUE").await?;
+            }
+            DatabaseBackend::Postgres => {
+                db.execute_unprepared("DROP INDEX IF EXISTS idx_af_forms_active_slug")
+                    .await?;
+                db.execute_unprepared(
+                    "ALTER TABLE af_forms ADD CONSTRAINT asf_forms_slug_key UNIQUE (slug)",
+                )
+                .await?;
+            }
+            DatabaseBackend::MySql => {
+                db.execute_unprepared(
+                    "ALTER TABLE af_forms DROP INDEX idx_af_forms_active_slug, \
+                    DROP COLUMN active_slug, ADD UNIQUE INDEX slug (slug)",
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Recreates `af_forms` on SQLite, which can't drop a column constraint,
+/// with `slug_constraint` appended to the slug column.
+///
+/// Foreign keys must be off while the table is swapped, or dropping it would
+/// delete every child row. The pragma is per connection and ignored within a
+/// transaction, so one pooled connection is held throughout.
+async fn rebuild_sqlite_forms(
+    manager: &SchemaManager<'_>,
+    slug_constraint: &str,
+) -> Result<(), DbErr> {
+    let SchemaManagerConnection::Connection(db) = manager.get_connection() else {
+        return Err(DbErr::Migration(
+            "af_forms can't be rebuilt within a transaction".to_string(),
+        ));
+    };
+    let mut conn = db
+        .get_sqlite_connection_pool()
+        .acquire()
+        .await
+        .map_err(|e| DbErr::Migration(e.to_string()))?;
+
+    let create = format!(
+        "CREATE TABLE af_forms_new ( \
+        \"id\" uuid_text NOT NULL PRIMARY KEY, \
+        \"name\" varchar(255) NOT NULL, \
+        \"slug\" varchar(255) NOT NULL{slug_constraint}, \
+        \"description\" text, \
+        \"settings\" json_text, \
+        \"created_at\" timestamp_with_timezone_text NOT NULL DEFAULT CURRENT_TIMESTAMP, \
+        \"updated_at\" timestamp_with_timezone_text NOT NULL DEFAULT CURRENT_TIMESTAMP, \
+        \"deleted_at\" timestamp_with_timezone_text, \
+        \"response_count\" integer NOT NULL DEFAULT 0)"
+    );
+    let statements = [
+        "BEGIN",
+        create.as_str(),
+        "INSERT INTO af_forms_new (id, name, slug, description, settings, created_at, \
+        updated_at, deleted_at, response_count) \
+        SELECT id, name, slug, description, settings, created_at, updated_at, deleted_at, \
+        response_count FROM af_forms",
+        "DROP TABLE af_forms",
+        "ALTER TABLE af_forms_new RENAME TO af_forms",
+        "CREATE INDEX idx_af_forms_slug ON af_forms (slug)",
+        "COMMIT",
+    ];
+
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| DbErr::Migration(e.to_string()))?;
+    let mut result = Ok(());
+    for sql in statements {
+        if let Err(e) = sqlx::query(sql).execute(&mut *conn).await {
+            let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+            result = Err(DbErr::Migration(e.to_string()));
+            break;
+        }
+    }
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| DbErr::Migration(e.to_string()))?;
+
+    result
+}
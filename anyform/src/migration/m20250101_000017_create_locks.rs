@@ -0,0 +1,55 @@
+//! Lock table, the SQLite fallback for `LockService`.
+//!
+//! PostgreSQL and MySQL use advisory locks instead; the table is created on
+//! every backend so the schema is the same everywhere.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AfLocks::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AfLocks::Name)
+                            .string_len(255)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AfLocks::Holder).uuid().not_null())
+                    .col(
+                        ColumnDef::new(AfLocks::AcquiredAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AfLocks::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AfLocks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfLocks {
+    Table,
+    Name,
+    Holder,
+    AcquiredAt,
+    ExpiresAt,
+}
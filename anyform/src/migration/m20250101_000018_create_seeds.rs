@@ -0,0 +1,46 @@
+//! Seed table, recording the checksum of each form written by `Seeder`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AfSeeds::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AfSeeds::Slug)
+                            .string_len(255)
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AfSeeds::Checksum).string_len(64).not_null())
+                    .col(
+                        ColumnDef::new(AfSeeds::SeededAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AfSeeds::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfSeeds {
+    Table,
+    Slug,
+    Checksum,
+    SeededAt,
+}
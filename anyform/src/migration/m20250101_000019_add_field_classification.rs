@@ -0,0 +1,42 @@
+//! Field data classification (`standard`, `no_analytics` or `sensitive`).
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFields::Table)
+                    .add_column(
+                        ColumnDef::new(AfFields::Classification)
+                            .string_len(32)
+                            .not_null()
+                            .default("standard"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFields::Table)
+                    .drop_column(AfFields::Classification)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfFields {
+    Table,
+    Classification,
+}
@@ -0,0 +1,94 @@
+//! Notification digest deliveries.
+//!
+//! One row per form and digest period that has been sent, so a digest job
+//! that runs twice (or on several servers) sends each digest once.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AfDigestDeliveries::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AfDigestDeliveries::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AfDigestDeliveries::FormId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(AfDigestDeliveries::PeriodStart)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AfDigestDeliveries::PeriodEnd)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AfDigestDeliveries::Submissions)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(AfDigestDeliveries::SentAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_af_digest_deliveries_form")
+                            .from(AfDigestDeliveries::Table, AfDigestDeliveries::FormId)
+                            .to(AfForms::Table, AfForms::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_digest_deliveries_form_period")
+                    .table(AfDigestDeliveries::Table)
+                    .col(AfDigestDeliveries::FormId)
+                    .col(AfDigestDeliveries::PeriodStart)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AfDigestDeliveries::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfForms {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+pub enum AfDigestDeliveries {
+    Table,
+    Id,
+    FormId,
+    PeriodStart,
+    PeriodEnd,
+    Submissions,
+    SentAt,
+}
@@ -0,0 +1,130 @@
+//! CRM push queue.
+//!
+//! One row per submission queued for a CRM, pending until it is pushed or
+//! given up on, then keeping the CRM record ID.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AfCrmPushes::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(AfCrmPushes::Id).uuid().not_null().primary_key())
+                    .col(ColumnDef::new(AfCrmPushes::FormId).uuid().not_null())
+                    .col(ColumnDef::new(AfCrmPushes::SubmissionId).uuid().not_null())
+                    .col(ColumnDef::new(AfCrmPushes::Provider).string_len(32).not_null())
+                    .col(ColumnDef::new(AfCrmPushes::Email).string_len(320).not_null())
+                    .col(
+                        ColumnDef::new(AfCrmPushes::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(
+                        ColumnDef::new(AfCrmPushes::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(AfCrmPushes::NextAttemptAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AfCrmPushes::LastError).text())
+                    .col(ColumnDef::new(AfCrmPushes::RecordId).string_len(255))
+                    .col(
+                        ColumnDef::new(AfCrmPushes::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(AfCrmPushes::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_af_crm_pushes_form")
+                            .from(AfCrmPushes::Table, AfCrmPushes::FormId)
+                            .to(AfForms::Table, AfForms::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_af_crm_pushes_submission")
+                            .from(AfCrmPushes::Table, AfCrmPushes::SubmissionId)
+                            .to(AfSubmissions::Table, AfSubmissions::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_crm_pushes_due")
+                    .table(AfCrmPushes::Table)
+                    .col(AfCrmPushes::Status)
+                    .col(AfCrmPushes::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_crm_pushes_email")
+                    .table(AfCrmPushes::Table)
+                    .col(AfCrmPushes::Provider)
+                    .col(AfCrmPushes::Email)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AfCrmPushes::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfForms {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+pub enum AfSubmissions {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+pub enum AfCrmPushes {
+    Table,
+    Id,
+    FormId,
+    SubmissionId,
+    Provider,
+    Email,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    LastError,
+    RecordId,
+    CreatedAt,
+    UpdatedAt,
+}
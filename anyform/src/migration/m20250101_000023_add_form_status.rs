@@ -0,0 +1,87 @@
+//! Form publication status (`draft`, `published` or `archived`).
+//!
+//! Replaces the `draft` flag in form settings: forms flagged as drafts
+//! become `draft`, and the flag is removed. Rolling back flags every form
+//! that isn't published as a draft again.
+
+use sea_orm::{ConnectionTrait, DatabaseBackend};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfForms::Table)
+                    .add_column(
+                        ColumnDef::new(AfForms::Status)
+                            .string_len(16)
+                            .not_null()
+                            .default("published"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let backfill = match manager.get_database_backend() {
+            DatabaseBackend::Sqlite => {
+                "UPDATE af_forms SET status = 'draft', \
+                 settings = json_remove(settings, '$.draft') \
+                 WHERE json_extract(settings, '$.draft') = 1"
+            }
+            DatabaseBackend::Postgres => {
+                "UPDATE af_forms SET status = 'draft', \
+                 settings = (settings::jsonb - 'draft')::json \
+                 WHERE settings->>'draft' = 'true'"
+            }
+            DatabaseBackend::MySql => {
+                "UPDATE af_forms SET status = 'draft', \
+                 settings = JSON_REMOVE(settings, '$.draft') \
+                 WHERE JSON_EXTRACT(settings, '$.draft') = true"
+            }
+        };
+        manager.get_connection().execute_unprepared(backfill).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let restore = match manager.get_database_backend() {
+            DatabaseBackend::Sqlite => {
+                "UPDATE af_forms \
+                 SET settings = json_set(COALESCE(settings, '{}'), '$.draft', json('true')) \
+                 WHERE status <> 'published'"
+            }
+            DatabaseBackend::Postgres => {
+                "UPDATE af_forms \
+                 SET settings = (COALESCE(settings::jsonb, '{}') || '{\"draft\": true}')::json \
+                 WHERE status <> 'published'"
+            }
+            DatabaseBackend::MySql => {
+                "UPDATE af_forms \
+                 SET settings = JSON_SET(COALESCE(settings, JSON_OBJECT()), '$.draft', true) \
+                 WHERE status <> 'published'"
+            }
+        };
+        manager.get_connection().execute_unprepared(restore).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfForms::Table)
+                    .drop_column(AfForms::Status)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfForms {
+    Table,
+    Status,
+}
@@ -0,0 +1,44 @@
+//! Index for submission change feeds.
+//!
+//! Change feeds page through a form's submissions by `(updated_at, id)`.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_submissions_changes")
+                    .table(AfSubmissions::Table)
+                    .col(AfSubmissions::FormId)
+                    .col(AfSubmissions::UpdatedAt)
+                    .col(AfSubmissions::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_af_submissions_changes")
+                    .table(AfSubmissions::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfSubmissions {
+    Table,
+    Id,
+    FormId,
+    UpdatedAt,
+}
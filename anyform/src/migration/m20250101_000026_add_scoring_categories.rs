@@ -0,0 +1,64 @@
+//! Scoring categories for fields and results.
+//!
+//! Quiz fields can be tagged with a category, scored separately, and
+//! results can be picked by a submission's highest category.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFields::Table)
+                    .add_column(ColumnDef::new(AfFields::Category).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfResults::Table)
+                    .add_column(ColumnDef::new(AfResults::Category).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfResults::Table)
+                    .drop_column(AfResults::Category)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFields::Table)
+                    .drop_column(AfFields::Category)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfFields {
+    Table,
+    Category,
+}
+
+#[derive(DeriveIden)]
+pub enum AfResults {
+    Table,
+    Category,
+}
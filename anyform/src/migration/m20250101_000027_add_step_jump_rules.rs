@@ -0,0 +1,40 @@
+//! Jump rules for steps.
+//!
+//! Steps can now send respondents to a later step, or end the form, based
+//! on their answers.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSteps::Table)
+                    .add_column(ColumnDef::new(AfSteps::Next).json())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSteps::Table)
+                    .drop_column(AfSteps::Next)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfSteps {
+    Table,
+    Next,
+}
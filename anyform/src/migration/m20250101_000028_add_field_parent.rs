@@ -0,0 +1,59 @@
+//! Repeatable field groups.
+//!
+//! Sub-fields of a `repeater` field point at it through `parent_id`; they
+//! share its step but aren't fields of the step themselves.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFields::Table)
+                    .add_column(ColumnDef::new(AfFields::ParentId).uuid())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_fields_parent_id")
+                    .table(AfFields::Table)
+                    .col(AfFields::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_af_fields_parent_id")
+                    .table(AfFields::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFields::Table)
+                    .drop_column(AfFields::ParentId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfFields {
+    Table,
+    ParentId,
+}
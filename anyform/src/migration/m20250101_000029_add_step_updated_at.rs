@@ -0,0 +1,49 @@
+//! Step change times.
+//!
+//! Steps record when their content last changed, so clients can fetch only
+//! the steps changed since they last loaded a form. Existing steps count as
+//! changed when they were created.
+
+use sea_orm::ConnectionTrait;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSteps::Table)
+                    .add_column(ColumnDef::new(AfSteps::UpdatedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("UPDATE af_steps SET updated_at = created_at")
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSteps::Table)
+                    .drop_column(AfSteps::UpdatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfSteps {
+    Table,
+    UpdatedAt,
+}
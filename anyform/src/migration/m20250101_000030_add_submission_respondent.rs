@@ -0,0 +1,83 @@
+//! Respondent IDs on submissions.
+//!
+//! Submissions store the ID of the respondent the router's
+//! `RespondentResolver` identified, indexed per form so admins can list a
+//! respondent's submissions and forms can accept one per respondent.
+//! Existing submissions are backfilled from their `user_id` metadata.
+
+use sea_orm::{ConnectionTrait, DatabaseBackend};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSubmissions::Table)
+                    .add_column(ColumnDef::new(AfSubmissions::RespondentId).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_af_submissions_respondent")
+                    .table(AfSubmissions::Table)
+                    .col(AfSubmissions::FormId)
+                    .col(AfSubmissions::RespondentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        let backfill = match manager.get_database_backend() {
+            DatabaseBackend::Sqlite => {
+                "UPDATE af_submissions SET respondent_id = json_extract(metadata, '$.user_id') \
+                 WHERE metadata IS NOT NULL"
+            }
+            DatabaseBackend::Postgres => {
+                "UPDATE af_submissions SET respondent_id = metadata->>'user_id' \
+                 WHERE metadata IS NOT NULL"
+            }
+            DatabaseBackend::MySql => {
+                "UPDATE af_submissions \
+                 SET respondent_id = JSON_UNQUOTE(JSON_EXTRACT(metadata, '$.user_id')) \
+                 WHERE JSON_EXTRACT(metadata, '$.user_id') IS NOT NULL"
+            }
+        };
+        manager.get_connection().execute_unprepared(backfill).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_af_submissions_respondent")
+                    .table(AfSubmissions::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSubmissions::Table)
+                    .drop_column(AfSubmissions::RespondentId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfSubmissions {
+    Table,
+    FormId,
+    RespondentId,
+}
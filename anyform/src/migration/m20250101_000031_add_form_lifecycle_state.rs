@@ -0,0 +1,41 @@
+//! Announced form lifecycle state.
+//!
+//! Forms record whether they were last announced to `FormEvents` as open or
+//! closed, so `LifecycleService` only announces changes. Existing forms
+//! start unannounced; the first check records their state without an event.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfForms::Table)
+                    .add_column(ColumnDef::new(AfForms::LifecycleState).string_len(16))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfForms::Table)
+                    .drop_column(AfForms::LifecycleState)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfForms {
+    Table,
+    LifecycleState,
+}
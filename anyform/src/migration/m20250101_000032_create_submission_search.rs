@@ -0,0 +1,62 @@
+//! Full-text index of submissions' text answers.
+//!
+//! `af_submission_search` holds a row per indexed submission, with its
+//! text answers joined into `content`, for the `search` feature's
+//! `SearchService`. Each backend indexes it with its own full-text search:
+//! a generated `tsvector` with a GIN index on PostgreSQL, an FTS5 virtual
+//! table on SQLite and a `FULLTEXT` index on MySQL. The table is created on
+//! every backend, with or without the feature, so the schema is the same
+//! everywhere.
+
+use sea_orm::{ConnectionTrait, DatabaseBackend};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let statements: &[&str] = match manager.get_database_backend() {
+            DatabaseBackend::Postgres => &[
+                "CREATE TABLE af_submission_search (\
+                 submission_id UUID NOT NULL PRIMARY KEY, \
+                 form_id UUID NOT NULL, \
+                 content TEXT NOT NULL, \
+                 document TSVECTOR GENERATED ALWAYS AS (to_tsvector('simple', content)) STORED)",
+                "CREATE INDEX idx_af_submission_search_form ON af_submission_search (form_id)",
+                "CREATE INDEX idx_af_submission_search_document \
+                 ON af_submission_search USING GIN (document)",
+            ],
+            DatabaseBackend::Sqlite => &[
+                "CREATE VIRTUAL TABLE af_submission_search \
+                 USING fts5(submission_id UNINDEXED, form_id UNINDEXED, content)",
+            ],
+            DatabaseBackend::MySql => &[
+                "CREATE TABLE af_submission_search (\
+                 submission_id BINARY(16) NOT NULL PRIMARY KEY, \
+                 form_id BINARY(16) NOT NULL, \
+                 content TEXT NOT NULL, \
+                 INDEX idx_af_submission_search_form (form_id), \
+                 FULLTEXT INDEX idx_af_submission_search_content (content)\
+                 ) ENGINE = InnoDB",
+            ],
+        };
+        let db = manager.get_connection();
+        for statement in statements {
+            db.execute_unprepared(statement).await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AfSubmissionSearch::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfSubmissionSearch {
+    Table,
+}
@@ -0,0 +1,59 @@
+//! Form fragments included by steps.
+//!
+//! A step can include the steps of another form, such as an address block,
+//! under a namespace for the included fields' names.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSteps::Table)
+                    .add_column(ColumnDef::new(AfSteps::IncludeForm).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSteps::Table)
+                    .add_column(ColumnDef::new(AfSteps::IncludeAs).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSteps::Table)
+                    .drop_column(AfSteps::IncludeAs)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfSteps::Table)
+                    .drop_column(AfSteps::IncludeForm)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfSteps {
+    Table,
+    IncludeForm,
+    IncludeAs,
+}
@@ -0,0 +1,39 @@
+//! Option groups.
+//!
+//! Options can be listed under a heading, such as a region for countries.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFieldOptions::Table)
+                    .add_column(ColumnDef::new(AfFieldOptions::Group).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFieldOptions::Table)
+                    .drop_column(AfFieldOptions::Group)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfFieldOptions {
+    Table,
+    Group,
+}
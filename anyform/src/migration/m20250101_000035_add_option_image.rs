@@ -0,0 +1,39 @@
+//! Option images.
+//!
+//! Options of image-choice fields are shown as cards with a picture.
+
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFieldOptions::Table)
+                    .add_column(ColumnDef::new(AfFieldOptions::ImageUrl).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(AfFieldOptions::Table)
+                    .drop_column(AfFieldOptions::ImageUrl)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AfFieldOptions {
+    Table,
+    ImageUrl,
+}
@@ -7,6 +7,34 @@ mod m20250101_000004_create_field_options;
 mod m20250101_000005_create_submissions;
 mod m20250101_000006_create_results;
 mod m20250101_000007_rename_tables_af;
+mod m20250101_000008_create_form_revisions;
+mod m20250101_000009_create_submission_notes;
+mod m20250101_000010_add_option_capacity;
+mod m20250101_000011_add_submission_status;
+mod m20250101_000012_create_form_instances;
+mod m20250101_000013_add_form_response_count;
+mod m20250101_000014_create_dead_letters;
+mod m20250101_000015_add_result_deleted_at;
+mod m20250101_000016_unique_active_slug;
+mod m20250101_000017_create_locks;
+mod m20250101_000018_create_seeds;
+mod m20250101_000019_add_field_classification;
+mod m20250101_000020_add_submission_legal_hold;
+mod m20250101_000021_create_digest_deliveries;
+mod m20250101_000022_create_crm_pushes;
+mod m20250101_000023_add_form_status;
+mod m20250101_000024_add_submission_changes_index;
+mod m20250101_000025_add_result_condition;
+mod m20250101_000026_add_scoring_categories;
+mod m20250101_000027_add_step_jump_rules;
+mod m20250101_000028_add_field_parent;
+mod m20250101_000029_add_step_updated_at;
+mod m20250101_000030_add_submission_respondent;
+mod m20250101_000031_add_form_lifecycle_state;
+mod m20250101_000032_create_submission_search;
+mod m20250101_000033_add_step_include_form;
+mod m20250101_000034_add_option_group;
+mod m20250101_000035_add_option_image;
 
 pub struct Migrator;
 
@@ -21,6 +49,34 @@ impl MigratorTrait for Migrator {
             Box::new(m20250101_000005_create_submissions::Migration),
             Box::new(m20250101_000006_create_results::Migration),
             Box::new(m20250101_000007_rename_tables_af::Migration),
+            Box::new(m20250101_000008_create_form_revisions::Migration),
+            Box::new(m20250101_000009_create_submission_notes::Migration),
+            Box::new(m20250101_000010_add_option_capacity::Migration),
+            Box::new(m20250101_000011_add_submission_status::Migration),
+            Box::new(m20250101_000012_create_form_instances::Migration),
+            Box::new(m20250101_000013_add_form_response_count::Migration),
+            Box::new(m20250101_000014_create_dead_letters::Migration),
+            Box::new(m20250101_000015_add_result_deleted_at::Migration),
+            Box::new(m20250101_000016_unique_active_slug::Migration),
+            Box::new(m20250101_000017_create_locks::Migration),
+            Box::new(m20250101_000018_create_seeds::Migration),
+            Box::new(m20250101_000019_add_field_classification::Migration),
+            Box::new(m20250101_000020_add_submission_legal_hold::Migration),
+            Box::new(m20250101_000021_create_digest_deliveries::Migration),
+            Box::new(m20250101_000022_create_crm_pushes::Migration),
+            Box::new(m20250101_000023_add_form_status::Migration),
+            Box::new(m20250101_000024_add_submission_changes_index::Migration),
+            Box::new(m20250101_000025_add_result_condition::Migration),
+            Box::new(m20250101_000026_add_scoring_categories::Migration),
+            Box::new(m20250101_000027_add_step_jump_rules::Migration),
+            Box::new(m20250101_000028_add_field_parent::Migration),
+            Box::new(m20250101_000029_add_step_updated_at::Migration),
+            Box::new(m20250101_000030_add_submission_respondent::Migration),
+            Box::new(m20250101_000031_add_form_lifecycle_state::Migration),
+            Box::new(m20250101_000032_create_submission_search::Migration),
+            Box::new(m20250101_000033_add_step_include_form::Migration),
+            Box::new(m20250101_000034_add_option_group::Migration),
+            Box::new(m20250101_000035_add_option_image::Migration),
         ]
     }
 }
@@ -0,0 +1,377 @@
+//! Printable PDF documents, written with `printpdf`.
+//!
+//! Text is set in DejaVu Sans, which is embedded in every document, so
+//! answers in Latin, Greek and Cyrillic scripts print as written. Text isn't
+//! shaped, so right-to-left and joining scripts print letter by letter.
+//! Characters the font has no glyph for, like CJK ideographs and emoji,
+//! print as `�` rather than silently dropping out. The fonts are embedded
+//! whole, about 1.5 MB a document. Pages are A4.
+
+use std::sync::OnceLock;
+
+use printpdf::{Color, IndirectFontRef, Mm, PdfDocument, PdfLayerReference, Pt, Rect};
+use ttf_parser::Face;
+
+use crate::error::FormError;
+
+/// Page width, in points.
+pub(crate) const PAGE_WIDTH: f32 = 595.28;
+
+/// Page height, in points.
+pub(crate) const PAGE_HEIGHT: f32 = 841.89;
+
+/// Printed in place of characters the fonts have no glyph for.
+pub(crate) const REPLACEMENT: char = '\u{fffd}';
+
+const REGULAR: &[u8] = include_bytes!("../assets/fonts/DejaVuSans.ttf");
+const BOLD: &[u8] = include_bytes!("../assets/fonts/DejaVuSans-Bold.ttf");
+
+/// A font of the embedded family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn bytes(self) -> &'static [u8] {
+        match self {
+            Self::Regular => REGULAR,
+            Self::Bold => BOLD,
+        }
+    }
+
+    fn face(self) -> &'static Face<'static> {
+        static REGULAR_FACE: OnceLock<Face<'static>> = OnceLock::new();
+        static BOLD_FACE: OnceLock<Face<'static>> = OnceLock::new();
+        let face = match self {
+            Self::Regular => &REGULAR_FACE,
+            Self::Bold => &BOLD_FACE,
+        };
+        face.get_or_init(|| Face::parse(self.bytes(), 0).expect("embedded font is valid"))
+    }
+
+    /// Returns `text` as it prints: tabs become spaces, and characters
+    /// without a glyph become [`REPLACEMENT`].
+    pub(crate) fn printable(self, text: &str) -> String {
+        let face = self.face();
+        text.chars()
+            .map(|c| match c {
+                '\t' => ' ',
+                c if face.glyph_index(c).is_some() => c,
+                _ => REPLACEMENT,
+            })
+            .collect()
+    }
+
+    /// Returns the width of `text` set at `size`, in points.
+    pub(crate) fn width(self, text: &str, size: f32) -> f32 {
+        let face = self.face();
+        let units: u32 = self
+            .printable(text)
+            .chars()
+            .filter_map(|c| face.glyph_hor_advance(face.glyph_index(c)?))
+            .map(u32::from)
+            .sum();
+        units as f32 * size / f32::from(face.units_per_em())
+    }
+
+    /// Breaks `text` into lines no wider than `max_width` at `size`,
+    /// between words where possible. Line breaks in `text` are kept.
+    pub(crate) fn wrap(self, text: &str, size: f32, max_width: f32) -> Vec<String> {
+        let mut lines = Vec::new();
+        for paragraph in text.lines() {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                let candidate = if line.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{line} {word}")
+                };
+                if self.width(&candidate, size) <= max_width {
+                    line = candidate;
+                    continue;
+                }
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                // Words wider than a line are broken anywhere
+                for c in word.chars() {
+                    line.push(c);
+                    if self.width(&line, size) > max_width && line.chars().count() > 1 {
+                        line.pop();
+                        lines.push(std::mem::replace(&mut line, c.to_string()));
+                    }
+                }
+            }
+            lines.push(line);
+        }
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines
+    }
+}
+
+/// An RGB color, each component from 0 to 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Rgb(pub f32, pub f32, pub f32);
+
+impl Rgb {
+    pub(crate) const BLACK: Self = Self(0.0, 0.0, 0.0);
+    pub(crate) const WHITE: Self = Self(1.0, 1.0, 1.0);
+
+    /// Creates a color from 8-bit components.
+    #[must_use]
+    pub(crate) fn from_bytes(r: u8, g: u8, b: u8) -> Self {
+        Self(f32::from(r) / 255.0, f32::from(g) / 255.0, f32::from(b) / 255.0)
+    }
+
+    /// Parses a CSS hex (`#0a7`, `#00aa77`, with or without alpha) or
+    /// `rgb()`/`rgba()` color. Other CSS colors, like names and `hsl()`,
+    /// aren't understood.
+    #[must_use]
+    pub(crate) fn parse_css(color: &str) -> Option<Self> {
+        let color = color.trim().to_ascii_lowercase();
+        if let Some(hex) = color.strip_prefix('#') {
+            let digits: Vec<u8> = hex
+                .chars()
+                .map(|c| c.to_digit(16).map(|d| d as u8))
+                .collect::<Option<_>>()?;
+            return match digits.len() {
+                3 | 4 => Some(Self::from_bytes(digits[0] * 17, digits[1] * 17, digits[2] * 17)),
+                6 | 8 => Some(Self::from_bytes(
+                    digits[0] * 16 + digits[1],
+                    digits[2] * 16 + digits[3],
+                    digits[4] * 16 + digits[5],
+                )),
+                _ => None,
+            };
+        }
+
+        let args = color
+            .strip_prefix("rgba(")
+            .or_else(|| color.strip_prefix("rgb("))?
+            .strip_suffix(')')?;
+        let components: Vec<u8> = args
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .take(3)
+            .map(|s| s.parse::<f32>().ok().map(|v| v.clamp(0.0, 255.0) as u8))
+            .collect::<Option<_>>()?;
+        match components[..] {
+            [r, g, b] => Some(Self::from_bytes(r, g, b)),
+            _ => None,
+        }
+    }
+}
+
+/// Something drawn on a page.
+#[derive(Debug)]
+enum Op {
+    Text {
+        x: f32,
+        y: f32,
+        font: Font,
+        size: f32,
+        color: Rgb,
+        text: String,
+    },
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        color: Rgb,
+    },
+}
+
+/// A page being drawn.
+#[derive(Debug, Default)]
+pub(crate) struct Page {
+    ops: Vec<Op>,
+}
+
+impl Page {
+    /// Writes `text` on one line with its baseline starting at `(x, y)`,
+    /// measured from the bottom left of the page.
+    pub(crate) fn text(&mut self, x: f32, y: f32, font: Font, size: f32, color: Rgb, text: &str) {
+        self.ops.push(Op::Text {
+            x,
+            y,
+            font,
+            size,
+            color,
+            text: font.printable(text),
+        });
+    }
+
+    /// Fills a rectangle whose bottom left corner is at `(x, y)`.
+    pub(crate) fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Rgb) {
+        self.ops.push(Op::Rect {
+            x,
+            y,
+            width,
+            height,
+            color,
+        });
+    }
+
+    fn draw(&self, layer: &PdfLayerReference, regular: &IndirectFontRef, bold: &IndirectFontRef) {
+        for op in &self.ops {
+            match op {
+                Op::Text {
+                    x,
+                    y,
+                    font,
+                    size,
+                    color,
+                    text,
+                } => {
+                    let font = match font {
+                        Font::Regular => regular,
+                        Font::Bold => bold,
+                    };
+                    layer.set_fill_color(fill(*color));
+                    layer.use_text(text.as_str(), *size, mm(*x), mm(*y), font);
+                }
+                Op::Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                    color,
+                } => {
+                    layer.set_fill_color(fill(*color));
+                    layer.add_rect(Rect::new(mm(*x), mm(*y), mm(x + width), mm(y + height)));
+                }
+            }
+        }
+    }
+}
+
+/// A document being drawn, a page at a time.
+#[derive(Debug)]
+pub(crate) struct Document {
+    title: String,
+    pages: Vec<Page>,
+}
+
+impl Document {
+    pub(crate) fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            pages: Vec::new(),
+        }
+    }
+
+    /// Starts a new page and returns it.
+    pub(crate) fn add_page(&mut self) -> &mut Page {
+        self.pages.push(Page::default());
+        self.pages.last_mut().expect("page was just added")
+    }
+
+    /// Returns the pages drawn so far.
+    pub(crate) fn pages_mut(&mut self) -> &mut [Page] {
+        &mut self.pages
+    }
+
+    /// Returns the page being drawn, starting one if there is none.
+    pub(crate) fn page(&mut self) -> &mut Page {
+        if self.pages.is_empty() {
+            self.add_page();
+        }
+        self.pages.last_mut().expect("there is a page")
+    }
+
+    /// Returns the complete document.
+    pub(crate) fn finish(mut self) -> Result<Vec<u8>, FormError> {
+        if self.pages.is_empty() {
+            self.add_page();
+        }
+
+        let (doc, first_page, first_layer) =
+            PdfDocument::new(&self.title, mm(PAGE_WIDTH), mm(PAGE_HEIGHT), "Content");
+        let doc = doc.with_producer("anyform");
+        let regular = doc.add_external_font(REGULAR).map_err(pdf_error)?;
+        let bold = doc.add_external_font(BOLD).map_err(pdf_error)?;
+
+        for (i, page) in self.pages.iter().enumerate() {
+            let (page_index, layer_index) = if i == 0 {
+                (first_page, first_layer)
+            } else {
+                doc.add_page(mm(PAGE_WIDTH), mm(PAGE_HEIGHT), "Content")
+            };
+            let layer = doc.get_page(page_index).get_layer(layer_index);
+            page.draw(&layer, &regular, &bold);
+        }
+        doc.save_to_bytes().map_err(pdf_error)
+    }
+}
+
+fn mm(points: f32) -> Mm {
+    Mm::from(Pt(points))
+}
+
+fn fill(color: Rgb) -> Color {
+    Color::Rgb(printpdf::Rgb::new(color.0, color.1, color.2, None))
+}
+
+fn pdf_error(err: printpdf::Error) -> FormError {
+    FormError::Export(format!("PDF: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_widths() {
+        assert!((Font::Regular.width("Hello", 10.0) - 25.35).abs() < 0.01);
+        assert!(Font::Bold.width("Hello", 10.0) > Font::Regular.width("Hello", 10.0));
+        assert!(Font::Regular.width("Привет", 10.0) > 0.0);
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let lines = Font::Regular.wrap("the quick brown fox jumps over the lazy dog", 10.0, 60.0);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| Font::Regular.width(l, 10.0) <= 60.0));
+        assert_eq!(lines.join(" "), "the quick brown fox jumps over the lazy dog");
+
+        // Paragraphs stay apart, and overlong words are broken
+        assert_eq!(Font::Regular.wrap("one\ntwo", 10.0, 100.0), vec!["one", "two"]);
+        let lines = Font::Regular.wrap(&"x".repeat(40), 10.0, 50.0);
+        assert!(lines.len() > 1 && lines.iter().all(|l| Font::Regular.width(l, 10.0) <= 50.0));
+    }
+
+    #[test]
+    fn test_css_colors() {
+        assert_eq!(Rgb::parse_css("#fff"), Some(Rgb::WHITE));
+        assert_eq!(Rgb::parse_css("#000000cc"), Some(Rgb::BLACK));
+        assert_eq!(Rgb::parse_css("rgb(255, 0, 0)"), Some(Rgb(1.0, 0.0, 0.0)));
+        assert_eq!(Rgb::parse_css("rgba(0 0 255 / 50%)"), Some(Rgb(0.0, 0.0, 1.0)));
+        assert_eq!(Rgb::parse_css("rebeccapurple"), None);
+        assert_eq!(Rgb::parse_css("#12"), None);
+    }
+
+    #[test]
+    fn test_printable_text() {
+        assert_eq!(Font::Regular.printable("Café – 5€ Ωμέγα Привет"), "Café – 5€ Ωμέγα Привет");
+        assert_eq!(Font::Bold.printable("a\tb 東京 ✓"), "a b \u{fffd}\u{fffd} ✓");
+        assert!(Font::Regular.width("\u{fffd}", 10.0) > 0.0);
+    }
+
+    #[test]
+    fn test_document_structure() {
+        let mut doc = Document::new("Report");
+        doc.page().text(50.0, 800.0, Font::Bold, 12.0, Rgb::BLACK, "First");
+        doc.add_page().rect(0.0, 0.0, 10.0, 10.0, Rgb::WHITE);
+        let pdf = doc.finish().unwrap();
+
+        let doc = printpdf::lopdf::Document::load_mem(&pdf).unwrap();
+        assert_eq!(doc.get_pages().len(), 2);
+        let fonts = doc.get_page_fonts(doc.page_iter().next().unwrap());
+        assert!(fonts.values().all(|f| f.get(b"Subtype").unwrap().as_name_str().unwrap() == "Type0"));
+    }
+}
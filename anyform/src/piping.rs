@@ -0,0 +1,236 @@
+//! Piping earlier answers into labels, descriptions and help text.
+//!
+//! A field's label and help text, and a step's description, can quote the
+//! answer to another field with `{{field:name}}`:
+//!
+//! ```text
+//! Hi {{field:first_name}}, how satisfied are you?
+//! ```
+//!
+//! `{{field:name|fallback}}` gives the text shown while the field isn't
+//! answered (nothing by default). Choices are quoted by their options'
+//! labels, several joined with commas. Text that isn't a placeholder, such
+//! as `{{name}}` or an unclosed `{{`, is left as it is.
+//!
+//! Server-rendered forms quote the answers they're rendered with, so a form
+//! re-rendered with its errors quotes what was submitted; each quote is a
+//! `<span data-af-pipe="name">` the WASM client keeps up to date as the
+//! respondent answers. Answers are escaped like any other text.
+//!
+//! ```
+//! use anyform::piping::{resolve, Answers};
+//!
+//! let mut answers = Answers::default();
+//! answers.insert("first_name", "Ada");
+//!
+//! let label = "Hi {{field:first_name}}, how satisfied are you?";
+//! assert_eq!(resolve(label, &answers), "Hi Ada, how satisfied are you?");
+//!
+//! let label = "Thanks{{field:nickname|}}! Anything else, {{ field:nickname | friend }}?";
+//! assert_eq!(resolve(label, &answers), "Thanks! Anything else, friend?");
+//! ```
+
+use std::collections::HashMap;
+
+use crate::repository::{FieldWithOptions, StepWithFields};
+use crate::schema::FieldValue;
+
+/// A `{{field:name|fallback}}` placeholder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placeholder<'a> {
+    /// The name of the field whose answer is quoted.
+    pub field: &'a str,
+    /// The text quoted while the field isn't answered.
+    pub fallback: &'a str,
+}
+
+/// A piece of text with placeholders: literal text or a placeholder.
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder(Placeholder<'a>),
+}
+
+/// Splits `text` into literal text and placeholders.
+fn parts(text: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    let mut literal = 0;
+    while let Some(start) = rest[literal..].find("{{").map(|i| literal + i) {
+        let placeholder = rest[start + 2..]
+            .find("}}")
+            .and_then(|end| parse(&rest[start + 2..start + 2 + end]).map(|p| (p, end)));
+        match placeholder {
+            Some((placeholder, end)) => {
+                if start > 0 {
+                    parts.push(Part::Text(&rest[..start]));
+                }
+                parts.push(Part::Placeholder(placeholder));
+                rest = &rest[start + 2 + end + 2..];
+                literal = 0;
+            }
+            None => literal = start + 2,
+        }
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Text(rest));
+    }
+    parts
+}
+
+/// Parses what's between the braces of a placeholder.
+fn parse(inner: &str) -> Option<Placeholder<'_>> {
+    let reference = inner.trim().strip_prefix("field:")?;
+    let (field, fallback) = reference.split_once('|').unwrap_or((reference, ""));
+    let field = field.trim();
+    if field.is_empty() || field.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(Placeholder { field, fallback: fallback.trim() })
+}
+
+/// Returns the placeholders in `text`, in order.
+#[must_use]
+pub fn placeholders(text: &str) -> Vec<Placeholder<'_>> {
+    parts(text)
+        .into_iter()
+        .filter_map(|part| match part {
+            Part::Placeholder(p) => Some(p),
+            Part::Text(_) => None,
+        })
+        .collect()
+}
+
+/// Returns true if `text` has placeholders.
+#[must_use]
+pub fn has_placeholders(text: &str) -> bool {
+    !placeholders(text).is_empty()
+}
+
+/// Replaces the placeholders in `text` with the answers they quote, or
+/// their fallbacks.
+#[must_use]
+pub fn resolve(text: &str, answers: &Answers) -> String {
+    parts(text)
+        .into_iter()
+        .map(|part| match part {
+            Part::Text(text) => text,
+            Part::Placeholder(p) => answers.get(p.field).unwrap_or(p.fallback),
+        })
+        .collect()
+}
+
+/// Escapes `text` for HTML, quoting answers in `<span data-af-pipe>`s for
+/// the WASM client to update.
+#[cfg(feature = "json")]
+pub(crate) fn render_html(text: &str, answers: &Answers) -> String {
+    use crate::render::escape_html;
+
+    parts(text)
+        .into_iter()
+        .map(|part| match part {
+            Part::Text(text) => escape_html(text),
+            Part::Placeholder(p) => {
+                let fallback = match p.fallback {
+                    "" => String::new(),
+                    fallback => format!(" data-af-fallback=\"{}\"", escape_html(fallback)),
+                };
+                format!(
+                    "<span class=\"af-pipe\" data-af-pipe=\"{}\"{fallback}>{}</span>",
+                    escape_html(p.field),
+                    escape_html(answers.get(p.field).unwrap_or(p.fallback))
+                )
+            }
+        })
+        .collect()
+}
+
+/// The answers placeholders quote, as text, by field name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers(HashMap<String, String>);
+
+impl Answers {
+    /// Returns the answers to the fields of `steps` in `values`, keyed by
+    /// field ID or name, with choices quoted by their options' labels.
+    #[must_use]
+    pub fn new(steps: &[StepWithFields], values: &HashMap<String, FieldValue>) -> Self {
+        let mut answers = Self::default();
+        for FieldWithOptions { field, options, .. } in steps.iter().flat_map(|s| &s.fields) {
+            let Some(value) = values
+                .get(&field.id.to_string())
+                .or_else(|| values.get(&field.name))
+            else {
+                continue;
+            };
+            let label = |v: &str| {
+                options
+                    .iter()
+                    .find(|o| o.value == v)
+                    .map_or_else(|| v.to_string(), |o| o.label.clone())
+            };
+            let text = match value {
+                FieldValue::Text(v) if !options.is_empty() => label(v),
+                FieldValue::Array(vs) if !options.is_empty() => {
+                    vs.iter().map(|v| label(v)).collect::<Vec<_>>().join(", ")
+                }
+                value => value.to_string_value(),
+            };
+            answers.insert(&field.name, text);
+        }
+        answers
+    }
+
+    /// Sets the answer to `field`; blank answers are left out.
+    pub fn insert(&mut self, field: impl Into<String>, answer: impl Into<String>) {
+        let answer = answer.into();
+        if answer.trim().is_empty() {
+            return;
+        }
+        self.0.insert(field.into(), answer);
+    }
+
+    /// Returns the answer to `field`, if it's answered.
+    #[must_use]
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.0.get(field).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        let found = placeholders("{{field:a}} and {{ field: b | none }}, {{b}} {{field:}} {{field:c");
+        assert_eq!(
+            found,
+            vec![
+                Placeholder { field: "a", fallback: "" },
+                Placeholder { field: "b", fallback: "none" },
+            ]
+        );
+        assert!(!has_placeholders("{{ not one }}"));
+    }
+
+    #[test]
+    fn test_resolve_keeps_other_text() {
+        let mut answers = Answers::default();
+        answers.insert("name", "Ada");
+        answers.insert("blank", "  ");
+        assert_eq!(resolve("{{x}} {{field:name}}{{", &answers), "{{x}} Ada{{");
+        assert_eq!(resolve("{{field:blank|-}}", &answers), "-");
+        assert_eq!(resolve("{{ {{field:name}} }}", &answers), "{{ Ada }}");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_render_html_escapes() {
+        let mut answers = Answers::default();
+        answers.insert("name", "<b>Ada</b>");
+        assert_eq!(
+            render_html("Hi & {{field:name}}, {{field:pet|\"pal\"}}", &answers),
+            "Hi &amp; <span class=\"af-pipe\" data-af-pipe=\"name\">&lt;b&gt;Ada&lt;/b&gt;</span>, \
+             <span class=\"af-pipe\" data-af-pipe=\"pet\" data-af-fallback=\"&quot;pal&quot;\">&quot;pal&quot;</span>"
+        );
+    }
+}
@@ -0,0 +1,464 @@
+//! Startup self-check.
+//!
+//! [`AnyFormRouter::preflight`](crate::AnyFormRouter::preflight) (or
+//! [`AnyFormRouterBuilder::preflight`](crate::AnyFormRouterBuilder::preflight),
+//! which also checks the builder's configuration) inspects the database and
+//! configuration before the server takes traffic, so problems surface at
+//! startup rather than as `500`s on the first request:
+//!
+//! | Code | Severity | Problem |
+//! |------|----------|---------|
+//! | `MIGRATIONS_PENDING` | error | Migrations of this version haven't been applied |
+//! | `UNKNOWN_MIGRATIONS` | warning | The database has migrations this version doesn't know (it was migrated by a newer one) |
+//! | `RESPONDENT_RESOLVER_MISSING` | error | Forms require identified respondents, but no resolver is registered |
+//! | `CAPTCHA_VERIFIER_MISSING` | error | Forms require a CAPTCHA, but no verifier is registered |
+//! | `FIELD_VALIDATOR_MISSING` | warning | Fields ask for server-side validation, but no validator is registered |
+//! | `EVENTS_HOOK_MISSING` | warning | Forms have `notify_emails` or SMS notifications, but no events hook sends them |
+//! | `DEAD_LETTERS_PENDING` | warning | Failed submissions are waiting to be replayed |
+//! | `CLIENT_VERSION_MISMATCH` | warning | The served WASM client doesn't match the server's schema version |
+//! | `ADMIN_UNPROTECTED` | warning | Admin routes are served without an [`AdminAuthorizer`](crate::admin_auth::AdminAuthorizer) |
+//!
+//! The checks only read, so they are safe against a read replica.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, EntityTrait, Statement};
+use serde::Serialize;
+
+use crate::entities::{dead_letter, field, form, step};
+use crate::error::FormError;
+use crate::migration::{Migrator, MigratorTrait};
+
+/// How serious a [`PreflightIssue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Something will misbehave, but requests are served.
+    Warning,
+    /// Requests will fail; the server shouldn't start.
+    Error,
+}
+
+/// A problem found by a preflight check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PreflightIssue {
+    pub severity: Severity,
+    /// Machine-readable code, e.g. `MIGRATIONS_PENDING`.
+    pub code: &'static str,
+    /// What is wrong and how to fix it.
+    pub message: String,
+}
+
+/// Migrations applied to the database, compared to this version's.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationSummary {
+    pub applied: usize,
+    /// Names of migrations not applied yet.
+    pub pending: Vec<String>,
+    /// Names of applied migrations this version doesn't know.
+    pub unknown: Vec<String>,
+}
+
+/// Forms in the database, by state.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct FormCounts {
+    /// Live forms accepting submissions.
+    pub published: u64,
+    pub drafts: u64,
+    pub archived: u64,
+    /// Forms that reached their `close_after` quota.
+    pub closed: u64,
+    pub deleted: u64,
+}
+
+/// The result of a preflight check.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    pub migrations: MigrationSummary,
+    /// Form counts, or `None` while migrations are pending.
+    pub forms: Option<FormCounts>,
+    /// Cargo features anyform was built with.
+    pub features: Vec<&'static str>,
+    pub read_only: bool,
+    pub issues: Vec<PreflightIssue>,
+}
+
+impl PreflightReport {
+    /// Returns true if no check failed with an error. Warnings are allowed.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Returns the issues with [`Severity::Error`].
+    pub fn errors(&self) -> impl Iterator<Item = &PreflightIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    /// Returns the issues with [`Severity::Warning`].
+    pub fn warnings(&self) -> impl Iterator<Item = &PreflightIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning)
+    }
+
+    /// Fails with [`FormError::PreflightFailed`] listing the errors, if any.
+    pub fn ensure_ok(&self) -> Result<(), FormError> {
+        if self.is_ok() {
+            return Ok(());
+        }
+        let errors: Vec<String> = self
+            .errors()
+            .map(|i| format!("{}: {}", i.code, i.message))
+            .collect();
+        Err(FormError::PreflightFailed(errors.join("; ")))
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let migrations = &self.migrations;
+        write!(
+            f,
+            "Migrations: {} applied, {} pending",
+            migrations.applied,
+            migrations.pending.len()
+        )?;
+        if !migrations.unknown.is_empty() {
+            write!(f, ", {} unknown", migrations.unknown.len())?;
+        }
+        writeln!(f)?;
+
+        match &self.forms {
+            Some(forms) => writeln!(
+                f,
+                "Forms: {} published, {} drafts, {} archived, {} closed, {} deleted",
+                forms.published, forms.drafts, forms.archived, forms.closed, forms.deleted
+            )?,
+            None => writeln!(f, "Forms: not counted until migrations are applied")?,
+        }
+        writeln!(f, "Features: {}", self.features.join(", "))?;
+        writeln!(f, "Read-only: {}", if self.read_only { "yes" } else { "no" })?;
+
+        if self.issues.is_empty() {
+            return write!(f, "No issues found.");
+        }
+        write!(f, "Issues:")?;
+        for issue in &self.issues {
+            let severity = match issue.severity {
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            };
+            write!(f, "\n  {severity:<7} {}: {}", issue.code, issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// What the router was configured with, for the checks that depend on it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Configured {
+    pub respondent_resolver: bool,
+    pub captcha_verifier: bool,
+    pub field_validator: bool,
+    /// Names of the registered async validators.
+    pub validators: Vec<String>,
+    pub events: bool,
+    pub read_only: bool,
+    /// Whether admin routes are served without an authorizer.
+    pub admin_unprotected: bool,
+    /// Whether the served WASM client matches the schema version, if one is
+    /// served.
+    pub client_compatible: Option<bool>,
+}
+
+/// Runs every check against `db`.
+pub(crate) async fn run(
+    db: &DatabaseConnection,
+    configured: &Configured,
+) -> Result<PreflightReport, FormError> {
+    let mut report = PreflightReport {
+        migrations: migration_summary(db).await?,
+        forms: None,
+        features: features(),
+        read_only: configured.read_only,
+        issues: Vec::new(),
+    };
+
+    let migrations = &report.migrations;
+    if !migrations.pending.is_empty() {
+        report.issues.push(PreflightIssue {
+            severity: Severity::Error,
+            code: "MIGRATIONS_PENDING",
+            message: format!(
+                "{} migration(s) not applied ({}); run `anyform migrate --up` or \
+                 `Migrator::up(&db, None)`",
+                migrations.pending.len(),
+                abbreviate(&migrations.pending)
+            ),
+        });
+    }
+    if !migrations.unknown.is_empty() {
+        report.issues.push(PreflightIssue {
+            severity: Severity::Warning,
+            code: "UNKNOWN_MIGRATIONS",
+            message: format!(
+                "the database has migrations this version doesn't know ({}); \
+                 it was probably migrated by a newer anyform",
+                migrations.unknown.join(", ")
+            ),
+        });
+    }
+    if configured.client_compatible == Some(false) {
+        report.issues.push(PreflightIssue {
+            severity: Severity::Warning,
+            code: "CLIENT_VERSION_MISMATCH",
+            message: "the served WASM client doesn't match the server's schema version, so \
+                      hydration may fail; rebuild the client or serve a matching assets directory"
+                .to_string(),
+        });
+    }
+
+    if configured.admin_unprotected {
+        report.issues.push(PreflightIssue {
+            severity: Severity::Warning,
+            code: "ADMIN_UNPROTECTED",
+            message: "admin routes are enabled without authentication, so anyone who can \
+                      reach the server can edit forms and read submissions; call `admin_auth`"
+                .to_string(),
+        });
+    }
+
+    // The remaining checks read tables that pending migrations may change
+    if !report.migrations.pending.is_empty() {
+        return Ok(report);
+    }
+
+    let forms = form::Entity::find().all(db).await?;
+    let mut counts = FormCounts::default();
+    for form in &forms {
+        let count = if form.is_deleted() {
+            &mut counts.deleted
+        } else if form.is_draft() {
+            &mut counts.drafts
+        } else if form.is_archived() {
+            &mut counts.archived
+        } else if form.is_closed() {
+            &mut counts.closed
+        } else {
+            &mut counts.published
+        };
+        *count += 1;
+    }
+    report.forms = Some(counts);
+
+    let live: Vec<&form::Model> = forms.iter().filter(|f| !f.is_deleted()).collect();
+    let slugs = |pick: &dyn Fn(&form::Model) -> bool| -> Vec<&str> {
+        live.iter().filter(|f| pick(f)).map(|f| f.slug.as_str()).collect()
+    };
+
+    let identified = slugs(&|f| f.settings().requires_respondent() && !f.settings().anonymous);
+    if !identified.is_empty() && !configured.respondent_resolver {
+        report.issues.push(PreflightIssue {
+            severity: Severity::Error,
+            code: "RESPONDENT_RESOLVER_MISSING",
+            message: format!(
+                "forms {} require identified respondents, but no resolver is registered, \
+                 so every submission fails with 401; call `respondent_resolver`",
+                identified.join(", ")
+            ),
+        });
+    }
+
+    let captcha = slugs(&|f| f.settings().captcha.is_some());
+    if !captcha.is_empty() && !configured.captcha_verifier {
+        report.issues.push(PreflightIssue {
+            severity: Severity::Error,
+            code: "CAPTCHA_VERIFIER_MISSING",
+            message: format!(
+                "forms {} require a CAPTCHA, but no verifier is registered, so every \
+                 submission fails with 400; call `captcha_verifier`",
+                captcha.join(", ")
+            ),
+        });
+    }
+
+    let notifying = slugs(&|f| {
+        let settings = f.settings();
+        !settings.notify_emails.is_empty() || settings.sms.is_some()
+    });
+    if !notifying.is_empty() && !configured.events {
+        report.issues.push(PreflightIssue {
+            severity: Severity::Warning,
+            code: "EVENTS_HOOK_MISSING",
+            message: format!(
+                "forms {} have notify_emails or SMS notifications, but no events hook is \
+                 registered to send them; call `events`",
+                notifying.join(", ")
+            ),
+        });
+    }
+
+    if !configured.field_validator {
+        let validated = server_validated_forms(db, &live).await?;
+        if !validated.is_empty() {
+            report.issues.push(PreflightIssue {
+                severity: Severity::Warning,
+                code: "FIELD_VALIDATOR_MISSING",
+                message: format!(
+                    "forms {} have server_validate fields, but no field validator is \
+                     registered, so only their own rules are checked; call `field_validator`",
+                    validated.join(", ")
+                ),
+            });
+        }
+    }
+
+    let unregistered = unregistered_validators(db, &live, &configured.validators).await?;
+    if !unregistered.is_empty() {
+        report.issues.push(PreflightIssue {
+            severity: Severity::Warning,
+            code: "VALIDATOR_NOT_REGISTERED",
+            message: format!(
+                "fields use validators {} that aren't registered, so they're skipped; \
+                 call `validator`",
+                unregistered.join(", ")
+            ),
+        });
+    }
+
+    let dead_letters = dead_letter::Entity::count_pending(db).await?;
+    if dead_letters > 0 {
+        report.issues.push(PreflightIssue {
+            severity: Severity::Warning,
+            code: "DEAD_LETTERS_PENDING",
+            message: format!(
+                "{dead_letters} failed submission(s) are waiting; replay them from \
+                 /api/admin/dead-letters"
+            ),
+        });
+    }
+
+    Ok(report)
+}
+
+/// Compares the migrations recorded in the database with this version's,
+/// without creating the migrations table as `Migrator` would.
+async fn migration_summary(db: &DatabaseConnection) -> Result<MigrationSummary, FormError> {
+    let known: Vec<String> = Migrator::migrations()
+        .iter()
+        .map(|m| m.name().to_string())
+        .collect();
+
+    let rows = db
+        .query_all(Statement::from_string(
+            db.get_database_backend(),
+            "SELECT version FROM seaql_migrations",
+        ))
+        .await;
+    // A fresh database has no migrations table yet
+    let applied: HashSet<String> = match rows {
+        Ok(rows) => rows
+            .iter()
+            .map(|row| row.try_get::<String>("", "version"))
+            .collect::<Result<_, _>>()?,
+        Err(_) => HashSet::new(),
+    };
+
+    let mut unknown: Vec<String> = applied
+        .iter()
+        .filter(|name| !known.contains(name))
+        .cloned()
+        .collect();
+    unknown.sort();
+
+    Ok(MigrationSummary {
+        applied: applied.len() - unknown.len(),
+        pending: known.into_iter().filter(|name| !applied.contains(name)).collect(),
+        unknown,
+    })
+}
+
+/// Returns the slugs of `forms` with a field that asks for server-side
+/// validation.
+async fn server_validated_forms<'a>(
+    db: &DatabaseConnection,
+    forms: &[&'a form::Model],
+) -> Result<Vec<&'a str>, FormError> {
+    let steps: HashSet<_> = field::Entity::find()
+        .all(db)
+        .await?
+        .iter()
+        .filter(|f| f.validation().server_validate)
+        .map(|f| f.step_id)
+        .collect();
+    if steps.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let form_ids: HashSet<_> = step::Entity::find()
+        .all(db)
+        .await?
+        .iter()
+        .filter(|s| steps.contains(&s.id))
+        .map(|s| s.form_id)
+        .collect();
+    Ok(forms
+        .iter()
+        .filter(|f| form_ids.contains(&f.id))
+        .map(|f| f.slug.as_str())
+        .collect())
+}
+
+/// Returns the validator names the fields of `forms` use that aren't in
+/// `registered`, sorted.
+async fn unregistered_validators(
+    db: &DatabaseConnection,
+    forms: &[&form::Model],
+    registered: &[String],
+) -> Result<Vec<String>, FormError> {
+    let form_ids: HashSet<_> = forms.iter().map(|f| f.id).collect();
+    let steps: HashSet<_> = step::Entity::find()
+        .all(db)
+        .await?
+        .iter()
+        .filter(|s| form_ids.contains(&s.form_id))
+        .map(|s| s.id)
+        .collect();
+
+    let names: BTreeSet<String> = field::Entity::find()
+        .all(db)
+        .await?
+        .iter()
+        .filter(|f| steps.contains(&f.step_id))
+        .flat_map(|f| f.validation().validators)
+        .filter(|name| !registered.contains(name))
+        .collect();
+    Ok(names.into_iter().collect())
+}
+
+/// Lists the first few `names`, and how many more there are.
+fn abbreviate(names: &[String]) -> String {
+    const SHOWN: usize = 3;
+    if names.len() <= SHOWN {
+        return names.join(", ");
+    }
+    format!("{}, and {} more", names[..SHOWN].join(", "), names.len() - SHOWN)
+}
+
+/// Returns the Cargo features anyform was built with.
+fn features() -> Vec<&'static str> {
+    [
+        ("json", cfg!(feature = "json")),
+        ("tera", cfg!(feature = "tera")),
+        ("handlers", cfg!(feature = "handlers")),
+        ("router", cfg!(feature = "router")),
+        ("admin", cfg!(feature = "admin")),
+        ("assets", cfg!(feature = "assets")),
+        ("chaos", cfg!(feature = "chaos")),
+        ("loadtest", cfg!(feature = "loadtest")),
+        ("cli", cfg!(feature = "cli")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
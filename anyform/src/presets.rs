@@ -0,0 +1,362 @@
+//! Shared field definitions.
+//!
+//! A [`FieldLibrary`] holds named field presets, such as a standard email
+//! field or an EU consent block, defined once and referenced from any
+//! number of forms with `$ref` in place of a field:
+//!
+//! ```json
+//! { "name": "Contact", "slug": "contact", "steps": [{ "name": "Main", "fields": [
+//!     { "name": "message", "label": "Message", "field_type": "textarea" },
+//!     { "$ref": "eu_consent" }
+//! ] }] }
+//! ```
+//!
+//! References are replaced by the preset's fields as forms are created,
+//! updated or synced: through the admin API and GraphQL with the library
+//! set with [`AnyFormRouterBuilder::field_library`](crate::AnyFormRouterBuilder::field_library),
+//! and by `anyform form create|update|sync --presets <FILE>`. Changing a
+//! preset and syncing again updates every form referencing it. Presets
+//! can't reference other presets.
+//!
+//! ```
+//! use anyform::presets::FieldLibrary;
+//! use anyform::{CreateFieldInput, CreateFormInput, CreateStepInput};
+//!
+//! let library = FieldLibrary::new().preset(
+//!     "email",
+//!     vec![CreateFieldInput::new("email", "Email", "email").required()],
+//! );
+//! let input = CreateFormInput::new("Contact", "contact")
+//!     .step(CreateStepInput::new("Main").field(CreateFieldInput::preset("email")));
+//! let input = library.expand(input).unwrap();
+//! assert_eq!(input.steps[0].fields[0].name, "email");
+//! ```
+//!
+//! [`FieldLibrary::lint`] flags the copies that drift apart: forms
+//! defining a preset's field inline instead of referencing it, and fields
+//! of the same name defined differently across forms (`anyform form lint`).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::FormError;
+use crate::services::{CreateFieldInput, CreateFormInput};
+
+/// Named field presets forms reference with `$ref`.
+///
+/// Serialized as an object of preset names to their fields.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldLibrary {
+    presets: BTreeMap<String, Vec<CreateFieldInput>>,
+}
+
+impl FieldLibrary {
+    /// Creates an empty library.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the preset `name`, expanding to `fields`.
+    #[must_use]
+    pub fn preset(mut self, name: impl Into<String>, fields: Vec<CreateFieldInput>) -> Self {
+        self.presets.insert(name.into(), fields);
+        self
+    }
+
+    /// Parses a library from JSON: an object of preset names to arrays of
+    /// fields.
+    ///
+    /// Fails with [`FormError::InvalidData`] for invalid JSON or presets
+    /// referencing other presets.
+    pub fn from_json(json: &str) -> Result<Self, FormError> {
+        let library: Self = serde_json::from_str(json)
+            .map_err(|e| FormError::InvalidData(format!("Invalid field library: {e}")))?;
+        for (name, fields) in &library.presets {
+            if fields.iter().any(|f| f.preset.is_some()) {
+                return Err(FormError::InvalidData(format!(
+                    "Field preset '{name}' references another preset"
+                )));
+            }
+        }
+        Ok(library)
+    }
+
+    /// Returns the fields of the preset `name`.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&[CreateFieldInput]> {
+        self.presets.get(name).map(Vec::as_slice)
+    }
+
+    /// Returns true if the library has no presets.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+
+    /// Replaces the `$ref` fields of `input`, including repeater
+    /// sub-fields, with the fields of the presets they name.
+    ///
+    /// Fails with [`FormError::InvalidData`] for unknown presets.
+    pub fn expand(&self, mut input: CreateFormInput) -> Result<CreateFormInput, FormError> {
+        for step in &mut input.steps {
+            step.fields = self.expand_fields(std::mem::take(&mut step.fields))?;
+        }
+        Ok(input)
+    }
+
+    fn expand_fields(
+        &self,
+        fields: Vec<CreateFieldInput>,
+    ) -> Result<Vec<CreateFieldInput>, FormError> {
+        let mut expanded = Vec::with_capacity(fields.len());
+        for mut field in fields {
+            match field.preset.take() {
+                Some(name) => {
+                    let preset = self.get(&name).ok_or_else(|| {
+                        FormError::InvalidData(format!("Unknown field preset '{name}'"))
+                    })?;
+                    expanded.extend_from_slice(preset);
+                }
+                None => {
+                    field.fields = self.expand_fields(std::mem::take(&mut field.fields))?;
+                    expanded.push(field);
+                }
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Flags fields defined in several places that may drift apart, across
+    /// the top-level fields of `forms` (before expansion).
+    ///
+    /// Fields defined as a preset defines them are flagged as copies to
+    /// replace by a reference; fields differing from the preset defining
+    /// their name, or from fields of the same name in other forms, are
+    /// flagged with what differs. Display order doesn't count.
+    #[must_use]
+    pub fn lint(&self, forms: &[CreateFormInput]) -> Vec<LintFinding> {
+        let mut by_preset = BTreeMap::new();
+        for (preset, fields) in &self.presets {
+            for field in fields {
+                by_preset.entry(field.name.as_str()).or_insert((preset, field));
+            }
+        }
+        let mut inline: BTreeMap<&str, Vec<(&str, &CreateFieldInput)>> = BTreeMap::new();
+        for form in forms {
+            let fields = form.steps.iter().flat_map(|s| &s.fields);
+            for field in fields.filter(|f| f.preset.is_none()) {
+                inline
+                    .entry(field.name.as_str())
+                    .or_default()
+                    .push((form.slug.as_str(), field));
+            }
+        }
+
+        let mut findings = Vec::new();
+        for (name, copies) in inline {
+            if let Some((preset, definition)) = by_preset.get(name) {
+                let mut identical = Vec::new();
+                for (slug, field) in copies {
+                    let differences = differences(definition, field);
+                    if differences.is_empty() {
+                        identical.push(slug.to_string());
+                    } else {
+                        findings.push(LintFinding {
+                            kind: LintKind::DivergesFromPreset,
+                            field: name.to_string(),
+                            preset: Some((*preset).clone()),
+                            forms: vec![slug.to_string()],
+                            differences,
+                        });
+                    }
+                }
+                if !identical.is_empty() {
+                    findings.push(LintFinding {
+                        kind: LintKind::CopiesPreset,
+                        field: name.to_string(),
+                        preset: Some((*preset).clone()),
+                        forms: identical,
+                        differences: Vec::new(),
+                    });
+                }
+                continue;
+            }
+
+            let (_, first) = copies[0];
+            let differences: BTreeSet<String> = copies[1..]
+                .iter()
+                .flat_map(|(_, field)| differences(first, field))
+                .collect();
+            if !differences.is_empty() {
+                let mut forms: Vec<String> = copies.iter().map(|(s, _)| s.to_string()).collect();
+                forms.dedup();
+                findings.push(LintFinding {
+                    kind: LintKind::Divergent,
+                    field: name.to_string(),
+                    preset: None,
+                    forms,
+                    differences: differences.into_iter().collect(),
+                });
+            }
+        }
+        findings
+    }
+}
+
+/// A field definition [`FieldLibrary::lint`] flagged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintFinding {
+    pub kind: LintKind,
+    /// The field's name.
+    pub field: String,
+    /// The preset defining a field of that name, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+    /// Slugs of the forms defining the field.
+    pub forms: Vec<String>,
+    /// Properties that differ (`label`, `help_text`, `options`...).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub differences: Vec<String>,
+}
+
+/// Why a field definition was flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintKind {
+    /// Forms define a preset's field inline, exactly as the preset does;
+    /// they should reference the preset instead.
+    CopiesPreset,
+    /// A form defines a preset's field inline, differently.
+    DivergesFromPreset,
+    /// Forms define a field of the same name differently, without a
+    /// preset.
+    Divergent,
+}
+
+impl std::fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let forms = self.forms.join(", ");
+        let preset = self.preset.as_deref().unwrap_or_default();
+        let differences = self.differences.join(", ");
+        match self.kind {
+            LintKind::CopiesPreset => write!(
+                f,
+                "'{}' in {forms} copies preset '{preset}'; reference it with $ref",
+                self.field
+            ),
+            LintKind::DivergesFromPreset => write!(
+                f,
+                "'{}' in {forms} differs from preset '{preset}' ({differences})",
+                self.field
+            ),
+            LintKind::Divergent => {
+                write!(f, "'{}' differs across {forms} ({differences})", self.field)
+            }
+        }
+    }
+}
+
+/// Returns the properties, other than display order, that differ between
+/// two field definitions.
+fn differences(a: &CreateFieldInput, b: &CreateFieldInput) -> Vec<String> {
+    let properties = |field: &CreateFieldInput| match serde_json::to_value(field) {
+        Ok(serde_json::Value::Object(mut map)) => {
+            map.remove("order");
+            map
+        }
+        _ => serde_json::Map::new(),
+    };
+    let (a, b) = (properties(a), properties(b));
+    let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    keys.into_iter()
+        .filter(|key| a.get(*key) != b.get(*key))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::CreateStepInput;
+
+    fn consent(help_text: &str) -> CreateFieldInput {
+        CreateFieldInput::new("consent", "I agree", "checkbox")
+            .required()
+            .help_text(help_text)
+    }
+
+    fn form(slug: &str, fields: Vec<CreateFieldInput>) -> CreateFormInput {
+        CreateFormInput::new(slug, slug).step(CreateStepInput::new("Main").fields(fields))
+    }
+
+    #[test]
+    fn test_expand_replaces_references() {
+        let library = FieldLibrary::new().preset(
+            "contact",
+            vec![
+                CreateFieldInput::new("name", "Name", "text"),
+                CreateFieldInput::new("email", "Email", "email"),
+            ],
+        );
+        let input = form(
+            "signup",
+            vec![
+                CreateFieldInput::preset("contact"),
+                CreateFieldInput::new("guests", "Guests", "repeater")
+                    .field(CreateFieldInput::preset("contact")),
+            ],
+        );
+        let expanded = library.expand(input).unwrap();
+        let names: Vec<&str> = expanded.steps[0].fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["name", "email", "guests"]);
+        assert_eq!(expanded.steps[0].fields[2].fields.len(), 2);
+
+        let unknown = library.expand(form("x", vec![CreateFieldInput::preset("nope")]));
+        assert!(matches!(unknown, Err(FormError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_from_json() {
+        let library = FieldLibrary::from_json(
+            r#"{"email": [{"name": "email", "label": "Email", "field_type": "email"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(library.get("email").unwrap()[0].field_type, "email");
+
+        let nested = FieldLibrary::from_json(r#"{"a": [{"$ref": "b"}]}"#);
+        assert!(matches!(nested, Err(FormError::InvalidData(_))));
+    }
+
+    #[test]
+    fn test_lint_flags_copies_and_divergence() {
+        let library = FieldLibrary::new().preset("eu_consent", vec![consent("We keep it 1 year")]);
+        let forms = [
+            form("a", vec![CreateFieldInput::preset("eu_consent"), consent("We keep it 1 year")]),
+            form("b", vec![consent("We keep it 2 years").order(3)]),
+            form("c", vec![CreateFieldInput::new("phone", "Phone", "tel")]),
+            form("d", vec![CreateFieldInput::new("phone", "Mobile", "tel").order(2)]),
+            form("e", vec![CreateFieldInput::new("phone", "Phone", "tel")]),
+        ];
+
+        let findings = library.lint(&forms);
+        let shown: Vec<String> = findings.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            shown,
+            vec![
+                "'consent' in b differs from preset 'eu_consent' (help_text)",
+                "'consent' in a copies preset 'eu_consent'; reference it with $ref",
+                "'phone' differs across c, d, e (label)",
+            ]
+        );
+
+        // Same definitions in other positions aren't flagged
+        let forms = [
+            form("c", vec![CreateFieldInput::new("phone", "Phone", "tel")]),
+            form("d", vec![CreateFieldInput::new("phone", "Phone", "tel").order(2)]),
+        ];
+        assert!(library.lint(&forms).is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+//! Signed preview links for unpublished forms.
+//!
+//! Configure a secret with
+//! [`AnyFormRouterBuilder::preview_secret`](crate::AnyFormRouterBuilder::preview_secret)
+//! and mint links from the admin API (`POST /api/admin/forms/{id}/preview`).
+//! A [`PreviewToken`] lets its holder render one form, including draft
+//! and archived forms (see [`FormStatus`](crate::FormStatus)), and submit into a discardable
+//! preview bucket until the token expires.
+//!
+//! Tokens are `<payload>.<signature>`, both base64url: the payload holds the
+//! form ID and expiry, the signature is an HMAC-SHA256 over it.
+
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine};
+use chrono::{DateTime, Duration, SubsecRound, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::error::FormError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How long preview links stay valid by default.
+pub const DEFAULT_PREVIEW_TTL: Duration = Duration::hours(24);
+
+/// Grants preview access to a single form until it expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewToken {
+    /// The form that may be previewed.
+    pub form_id: Uuid,
+    /// When the token stops being accepted.
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PreviewToken {
+    /// Creates a token for `form_id`, valid for `ttl` from now.
+    ///
+    /// Expiry is kept to whole seconds, as signed.
+    #[must_use]
+    pub fn new(form_id: Uuid, ttl: Duration) -> Self {
+        Self {
+            form_id,
+            expires_at: (Utc::now() + ttl).trunc_subsecs(0),
+        }
+    }
+
+    /// Returns true if the token has expired at `at`.
+    #[must_use]
+    pub fn is_expired_at(&self, at: DateTime<Utc>) -> bool {
+        at >= self.expires_at
+    }
+
+    fn payload(&self) -> [u8; 24] {
+        let mut payload = [0u8; 24];
+        payload[..16].copy_from_slice(self.form_id.as_bytes());
+        payload[16..].copy_from_slice(&self.expires_at.timestamp().to_be_bytes());
+        payload
+    }
+
+    fn from_payload(payload: &[u8]) -> Option<Self> {
+        let form_id = Uuid::from_slice(payload.get(..16)?).ok()?;
+        let expires_at = i64::from_be_bytes(payload.get(16..24)?.try_into().ok()?);
+        Some(Self {
+            form_id,
+            expires_at: DateTime::from_timestamp(expires_at, 0)?,
+        })
+    }
+}
+
+/// Secret key that signs and verifies [`PreviewToken`]s.
+#[derive(Clone)]
+pub struct PreviewSecret(Arc<[u8]>);
+
+impl PreviewSecret {
+    /// Creates a signing key from secret bytes.
+    #[must_use]
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self(Arc::from(secret.as_ref()))
+    }
+
+    /// Signs a token for use in a preview URL.
+    #[must_use]
+    pub fn sign(&self, token: &PreviewToken) -> String {
+        let payload = token.payload();
+        let signature = self.mac(&payload).finalize().into_bytes();
+        format!("{}.{}", BASE64.encode(payload), BASE64.encode(signature))
+    }
+
+    /// Verifies a signed token and checks that it has not expired.
+    ///
+    /// Returns [`FormError::InvalidPreviewToken`] for malformed, forged or
+    /// expired tokens.
+    pub fn verify(&self, token: &str) -> Result<PreviewToken, FormError> {
+        self.verify_at(token, Utc::now())
+    }
+
+    /// Like [`Self::verify`], checking expiry at `at`.
+    pub fn verify_at(&self, token: &str, at: DateTime<Utc>) -> Result<PreviewToken, FormError> {
+        let (payload, signature) = token
+            .split_once('.')
+            .ok_or(FormError::InvalidPreviewToken)?;
+        let payload = BASE64
+            .decode(payload)
+            .map_err(|_| FormError::InvalidPreviewToken)?;
+        let signature = BASE64
+            .decode(signature)
+            .map_err(|_| FormError::InvalidPreviewToken)?;
+
+        self.mac(&payload)
+            .verify_slice(&signature)
+            .map_err(|_| FormError::InvalidPreviewToken)?;
+
+        let token = PreviewToken::from_payload(&payload).ok_or(FormError::InvalidPreviewToken)?;
+        if token.is_expired_at(at) {
+            return Err(FormError::InvalidPreviewToken);
+        }
+        Ok(token)
+    }
+
+    fn mac(&self, payload: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac
+    }
+}
+
+impl std::fmt::Debug for PreviewSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PreviewSecret(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let secret = PreviewSecret::new("s3cret");
+        let token = PreviewToken::new(Uuid::new_v4(), Duration::hours(1));
+
+        assert_eq!(secret.verify(&secret.sign(&token)).unwrap(), token);
+        assert_eq!(format!("{secret:?}"), "PreviewSecret(..)");
+    }
+
+    #[test]
+    fn test_rejects_other_secret_and_tampering() {
+        let secret = PreviewSecret::new("s3cret");
+        let signed = secret.sign(&PreviewToken::new(Uuid::new_v4(), Duration::hours(1)));
+
+        assert!(PreviewSecret::new("other").verify(&signed).is_err());
+
+        let (_, signature) = signed.split_once('.').unwrap();
+        let forged = PreviewToken::new(Uuid::new_v4(), Duration::hours(1));
+        let forged = format!("{}.{signature}", BASE64.encode(forged.payload()));
+        assert!(secret.verify(&forged).is_err());
+
+        assert!(secret.verify("garbage").is_err());
+    }
+
+    #[test]
+    fn test_rejects_expired() {
+        let secret = PreviewSecret::new("s3cret");
+        let token = PreviewToken::new(Uuid::new_v4(), Duration::hours(1));
+        let signed = secret.sign(&token);
+
+        assert!(secret.verify_at(&signed, token.expires_at).is_err());
+    }
+}
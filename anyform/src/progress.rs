@@ -0,0 +1,150 @@
+//! Progress through multi-step forms.
+//!
+//! Counting only the steps visible right now makes the total jump as
+//! answers change. [`ProgressMode::Estimated`] instead counts every later
+//! step that could still be shown: one whose condition holds now or depends
+//! on a field not yet answered. Answers only rule steps out, so the total
+//! shrinks as the respondent advances and the bar never moves backwards.
+//!
+//! The WASM client's `FormState::progress()` follows the same rules.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use anyform::condition::ConditionRule;
+//! use anyform::progress::{Progress, ProgressMode};
+//! use serde_json::json;
+//!
+//! let steps = vec![
+//!     None,
+//!     Some(ConditionRule::eq("has_pet", "yes")),
+//!     None,
+//! ];
+//!
+//! // Before `has_pet` is answered, the pet step may still come
+//! let mut values = HashMap::new();
+//! let p = Progress::compute(ProgressMode::Estimated, &steps, 0, &values);
+//! assert_eq!((p.current, p.total), (1, 3));
+//!
+//! // Answering "no" rules it out
+//! values.insert("has_pet".to_string(), json!("no"));
+//! let p = Progress::compute(ProgressMode::Estimated, &steps, 0, &values);
+//! assert_eq!((p.current, p.total), (1, 2));
+//! ```
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::condition::ConditionRule;
+
+/// How the progress total is counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressMode {
+    /// Steps visible with the current answers.
+    #[default]
+    Visible,
+    /// Steps visible so far plus every later step that could still be shown.
+    Estimated,
+}
+
+/// Position within a multi-step form, 1-indexed for display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Progress {
+    pub current: u32,
+    pub total: u32,
+}
+
+impl Progress {
+    /// Computes progress at step `current` (an index into `steps`, which
+    /// holds each step's condition in order).
+    #[must_use]
+    pub fn compute(
+        mode: ProgressMode,
+        steps: &[Option<ConditionRule>],
+        current: usize,
+        values: &HashMap<String, serde_json::Value>,
+    ) -> Self {
+        let visible =
+            |rule: &Option<ConditionRule>| rule.as_ref().map_or(true, |r| r.evaluate(values));
+
+        let passed = steps.iter().take(current).filter(|r| visible(r)).count();
+        let remaining = steps.iter().skip(current + 1).filter(|r| match mode {
+            ProgressMode::Visible => visible(r),
+            ProgressMode::Estimated => visible(r) || depends_on_unanswered(r, values),
+        });
+
+        Self {
+            current: (passed + 1) as u32,
+            total: (passed + 1 + remaining.count()) as u32,
+        }
+    }
+
+    /// Returns the completed fraction, from 0.0 to 1.0.
+    #[must_use]
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            f64::from(self.current) / f64::from(self.total)
+        }
+    }
+}
+
+/// Returns true if the condition references a field without an answer.
+fn depends_on_unanswered(
+    rule: &Option<ConditionRule>,
+    values: &HashMap<String, serde_json::Value>,
+) -> bool {
+    rule.as_ref().is_some_and(|r| {
+        r.fields().into_iter().any(|field| match values.get(field) {
+            None | Some(serde_json::Value::Null) => true,
+            Some(serde_json::Value::String(s)) => s.is_empty(),
+            Some(serde_json::Value::Array(a)) => a.is_empty(),
+            Some(_) => false,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn branching_steps() -> Vec<Option<ConditionRule>> {
+        vec![
+            None,
+            Some(ConditionRule::eq("plan", "business")),
+            Some(ConditionRule::eq("plan", "personal")),
+            None,
+        ]
+    }
+
+    #[test]
+    fn test_visible_mode_counts_current_answers() {
+        let steps = branching_steps();
+        let values = HashMap::new();
+
+        let p = Progress::compute(ProgressMode::Visible, &steps, 0, &values);
+        assert_eq!((p.current, p.total), (1, 2));
+    }
+
+    #[test]
+    fn test_estimated_mode_never_moves_backwards() {
+        let steps = branching_steps();
+        let mut values = HashMap::new();
+
+        let start = Progress::compute(ProgressMode::Estimated, &steps, 0, &values);
+        assert_eq!((start.current, start.total), (1, 4));
+
+        values.insert("plan".to_string(), json!("business"));
+        let answered = Progress::compute(ProgressMode::Estimated, &steps, 0, &values);
+        assert_eq!((answered.current, answered.total), (1, 3));
+
+        let next = Progress::compute(ProgressMode::Estimated, &steps, 1, &values);
+        assert_eq!((next.current, next.total), (2, 3));
+
+        assert!(start.fraction() <= answered.fraction());
+        assert!(answered.fraction() <= next.fraction());
+    }
+}
@@ -0,0 +1,311 @@
+//! Rate limiting for public submissions.
+//!
+//! Submissions to `POST /forms/{slug}` (and the server-rendered
+//! `/forms/{slug}/submit`) are throttled by token buckets: one per client IP
+//! and form, and one per form shared by every client. Each bucket holds up
+//! to [`RateLimit::burst`] submissions and refills evenly over
+//! [`RateLimit::period_secs`]. A submission finding either bucket empty is
+//! turned away with `429 RATE_LIMITED` and a `Retry-After` header.
+//!
+//! Limits are set for the whole router with
+//! [`AnyFormRouterBuilder::rate_limits`](crate::AnyFormRouterBuilder::rate_limits)
+//! and replaced for a single form by
+//! [`FormSettings::rate_limits`](crate::FormSettings::rate_limits):
+//!
+//! ```
+//! use anyform::{FormSettings, RateLimit, RateLimits};
+//!
+//! // At most 5 submissions a minute from one client, 1000 an hour overall
+//! let limits = RateLimits::new()
+//!     .per_ip(RateLimit::per_minute(5))
+//!     .per_form(RateLimit::per_hour(1000));
+//!
+//! // A signup form that may see bursts from one office network
+//! let settings = FormSettings::new()
+//!     .rate_limits(RateLimits::new().per_ip(RateLimit::per_minute(50)));
+//! ```
+//!
+//! Buckets live in memory, so each server process counts on its own and
+//! counts start over on restart. Clients are told apart by the IP address
+//! [`RequestMetadata`](crate::RequestMetadata) reports; per-IP limits aren't
+//! applied when it is unknown.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::FormError;
+
+/// Number of buckets kept before idle ones are dropped.
+const PRUNE_AT: usize = 10_000;
+
+/// A token bucket: up to `burst` submissions at once, refilled at `burst`
+/// per `period_secs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Submissions allowed at once.
+    pub burst: u32,
+    /// Seconds over which a full bucket refills.
+    pub period_secs: u64,
+}
+
+impl RateLimit {
+    /// Allows `burst` submissions per `period`.
+    #[must_use]
+    pub fn new(burst: u32, period: Duration) -> Self {
+        Self {
+            burst,
+            period_secs: period.as_secs(),
+        }
+    }
+
+    /// Allows `burst` submissions per minute.
+    #[must_use]
+    pub fn per_minute(burst: u32) -> Self {
+        Self::new(burst, Duration::from_secs(60))
+    }
+
+    /// Allows `burst` submissions per hour.
+    #[must_use]
+    pub fn per_hour(burst: u32) -> Self {
+        Self::new(burst, Duration::from_secs(3600))
+    }
+
+    /// Tokens regained per second.
+    fn refill_rate(self) -> f64 {
+        f64::from(self.burst) / self.period_secs.max(1) as f64
+    }
+}
+
+/// The rate limits applied to a form's submissions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimits {
+    /// Limit for each client IP.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_ip: Option<RateLimit>,
+    /// Limit shared by all clients.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_form: Option<RateLimit>,
+}
+
+impl RateLimits {
+    /// Creates limits that allow everything.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits each client IP.
+    #[must_use]
+    pub fn per_ip(mut self, limit: RateLimit) -> Self {
+        self.per_ip = Some(limit);
+        self
+    }
+
+    /// Limits all clients together.
+    #[must_use]
+    pub fn per_form(mut self, limit: RateLimit) -> Self {
+        self.per_form = Some(limit);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: f64::from(limit.burst),
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.limit.refill_rate()).min(f64::from(self.limit.burst));
+        self.updated = now;
+    }
+
+    /// Seconds until a token is available, or `None` if one is now.
+    fn wait(&self) -> Option<u64> {
+        if self.tokens >= 1.0 {
+            return None;
+        }
+        let rate = self.limit.refill_rate();
+        if rate <= 0.0 {
+            return Some(self.limit.period_secs.max(1));
+        }
+        Some((((1.0 - self.tokens) / rate).ceil() as u64).max(1))
+    }
+
+    fn is_full(&self) -> bool {
+        self.tokens >= f64::from(self.limit.burst)
+    }
+}
+
+/// Which clients a bucket counts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Scope {
+    Form,
+    Ip(String),
+}
+
+/// Tracks submissions against the configured [`RateLimits`].
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    defaults: RateLimits,
+    buckets: Mutex<HashMap<(Uuid, Scope), Bucket>>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter applying `defaults` to forms without their own
+    /// limits.
+    #[must_use]
+    pub fn new(defaults: RateLimits) -> Self {
+        Self {
+            defaults,
+            buckets: Mutex::default(),
+        }
+    }
+
+    /// Counts a submission to `form_id` from `ip`, failing with
+    /// [`FormError::RateLimited`] if a limit is reached. `overrides` (the
+    /// form's own limits) replace the defaults.
+    pub fn check(
+        &self,
+        form_id: Uuid,
+        ip: Option<&str>,
+        overrides: Option<RateLimits>,
+    ) -> Result<(), FormError> {
+        self.check_at(Instant::now(), form_id, ip, overrides)
+    }
+
+    fn check_at(
+        &self,
+        now: Instant,
+        form_id: Uuid,
+        ip: Option<&str>,
+        overrides: Option<RateLimits>,
+    ) -> Result<(), FormError> {
+        let limits = overrides.unwrap_or(self.defaults);
+        let scoped = [
+            limits.per_form.map(|limit| (Scope::Form, limit)),
+            limits
+                .per_ip
+                .zip(ip)
+                .map(|(limit, ip)| (Scope::Ip(ip.to_string()), limit)),
+        ];
+        if scoped.iter().all(Option::is_none) {
+            return Ok(());
+        }
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= PRUNE_AT {
+            buckets.retain(|_, bucket| {
+                bucket.refill(now);
+                !bucket.is_full()
+            });
+        }
+
+        // Only take a token once every bucket has one to give
+        let mut wait = None;
+        for (scope, limit) in scoped.iter().flatten() {
+            let bucket = buckets
+                .entry((form_id, scope.clone()))
+                .or_insert_with(|| Bucket::new(*limit, now));
+            if bucket.limit != *limit {
+                *bucket = Bucket::new(*limit, now);
+            }
+            bucket.refill(now);
+            wait = wait.max(bucket.wait());
+        }
+        if let Some(retry_after) = wait {
+            return Err(FormError::RateLimited { retry_after });
+        }
+        for (scope, _) in scoped.iter().flatten() {
+            if let Some(bucket) = buckets.get_mut(&(form_id, scope.clone())) {
+                bucket.tokens -= 1.0;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry_after(result: Result<(), FormError>) -> Option<u64> {
+        match result {
+            Err(FormError::RateLimited { retry_after }) => Some(retry_after),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_per_ip_bucket_refills() {
+        let limiter = RateLimiter::new(RateLimits::new().per_ip(RateLimit::per_minute(2)));
+        let form = Uuid::new_v4();
+        let start = Instant::now();
+
+        assert!(limiter.check_at(start, form, Some("192.0.2.1"), None).is_ok());
+        assert!(limiter.check_at(start, form, Some("192.0.2.1"), None).is_ok());
+        let result = limiter.check_at(start, form, Some("192.0.2.1"), None);
+        assert_eq!(retry_after(result), Some(30));
+
+        // Other clients and forms have their own buckets; unknown clients
+        // aren't limited per IP
+        assert!(limiter.check_at(start, form, Some("192.0.2.2"), None).is_ok());
+        assert!(limiter.check_at(start, Uuid::new_v4(), Some("192.0.2.1"), None).is_ok());
+        for _ in 0..5 {
+            assert!(limiter.check_at(start, form, None, None).is_ok());
+        }
+
+        let later = start + Duration::from_secs(30);
+        assert!(limiter.check_at(later, form, Some("192.0.2.1"), None).is_ok());
+        assert!(limiter.check_at(later, form, Some("192.0.2.1"), None).is_err());
+    }
+
+    #[test]
+    fn test_rejected_submissions_take_no_token() {
+        let limits = RateLimits::new()
+            .per_ip(RateLimit::per_minute(1))
+            .per_form(RateLimit::per_minute(2));
+        let limiter = RateLimiter::new(limits);
+        let form = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert!(limiter.check_at(now, form, Some("192.0.2.1"), None).is_ok());
+        // Refused by the IP bucket, so the form bucket keeps its token
+        assert!(limiter.check_at(now, form, Some("192.0.2.1"), None).is_err());
+        assert!(limiter.check_at(now, form, Some("192.0.2.2"), None).is_ok());
+        assert!(limiter.check_at(now, form, Some("192.0.2.3"), None).is_err());
+    }
+
+    #[test]
+    fn test_form_limits_replace_defaults() {
+        let limiter = RateLimiter::new(RateLimits::new().per_ip(RateLimit::per_minute(1)));
+        let form = Uuid::new_v4();
+        let now = Instant::now();
+        let unlimited = Some(RateLimits::new());
+
+        for _ in 0..3 {
+            assert!(limiter.check_at(now, form, Some("192.0.2.1"), unlimited).is_ok());
+        }
+
+        let strict = Some(RateLimits::new().per_form(RateLimit::per_hour(1)));
+        assert!(limiter.check_at(now, form, Some("192.0.2.1"), strict).is_ok());
+        let result = limiter.check_at(now, form, Some("192.0.2.2"), strict);
+        assert_eq!(retry_after(result), Some(3600));
+    }
+}
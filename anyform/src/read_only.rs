@@ -0,0 +1,90 @@
+//! Read-only mode, for read replicas and maintenance windows.
+//!
+//! With [`AnyFormRouterBuilder::read_only`](crate::AnyFormRouterBuilder::read_only)
+//! set, the router keeps serving forms (HTML, JSON schemas, success pages
+//! and single-field validation) but turns away every request that would
+//! write with `503 READ_ONLY` and a `Retry-After` header. Server-rendered
+//! form posts get an HTML notice instead of the JSON envelope, and form
+//! pages say up front that submissions are unavailable.
+
+use std::time::Duration;
+
+use axum::{
+    extract::Request,
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
+};
+use http::{header, HeaderValue, Method};
+
+use crate::error::FormError;
+use crate::render::HtmlRenderer;
+
+/// How long clients are told to wait before retrying a write, unless
+/// configured otherwise.
+pub const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// Rejects requests that would write, passing reads through.
+pub(crate) async fn reject_writes(retry_after: Duration, req: Request, next: Next) -> Response {
+    let path = req.uri().path();
+    if !is_write(req.method(), path) {
+        return next.run(req).await;
+    }
+
+    let error = FormError::ReadOnly;
+    let mut response = match ssr_form_slug(path) {
+        Some(slug) => (
+            error.status_code(),
+            Html(HtmlRenderer::render_unavailable(slug, error.error_code())),
+        )
+            .into_response(),
+        None => error.into_response(),
+    };
+    response.headers_mut().insert(
+        header::RETRY_AFTER,
+        HeaderValue::from(retry_after.as_secs().max(1)),
+    );
+    response
+}
+
+/// Returns true for requests that would write. Validating a single field
+/// is a `POST`, but stores nothing; GraphQL queries are `POST`s too, so the
+/// GraphQL executor turns mutations away itself.
+fn is_write(method: &Method, path: &str) -> bool {
+    let safe = matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS);
+    let validation = path.starts_with("/forms/") && path.ends_with("/validate");
+    let graphql = path.ends_with("/graphql");
+    !(safe || validation || graphql)
+}
+
+/// Returns the form slug of a server-rendered form post
+/// (`/forms/{slug}/submit` or `/forms/{slug}/preview/submit`).
+fn ssr_form_slug(path: &str) -> Option<&str> {
+    let rest = path.strip_prefix("/forms/")?;
+    let rest = rest
+        .strip_suffix("/preview/submit")
+        .or_else(|| rest.strip_suffix("/submit"))?;
+    (!rest.is_empty() && !rest.contains('/')).then_some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writes_are_recognised() {
+        assert!(!is_write(&Method::GET, "/forms/contact"));
+        assert!(!is_write(&Method::POST, "/forms/contact/fields/email/validate"));
+        assert!(!is_write(&Method::POST, "/graphql"));
+        assert!(is_write(&Method::POST, "/forms/contact"));
+        assert!(is_write(&Method::PATCH, "/forms/contact/submissions/1/step"));
+        assert!(is_write(&Method::DELETE, "/admin/forms/1"));
+    }
+
+    #[test]
+    fn test_ssr_form_slug() {
+        assert_eq!(ssr_form_slug("/forms/contact/submit"), Some("contact"));
+        assert_eq!(ssr_form_slug("/forms/contact/preview/submit"), Some("contact"));
+        assert_eq!(ssr_form_slug("/forms/contact"), None);
+        assert_eq!(ssr_form_slug("/forms/contact/submissions/1/complete"), None);
+    }
+}
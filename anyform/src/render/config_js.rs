@@ -0,0 +1,125 @@
+//! Embeddable JavaScript config for static sites.
+//!
+//! Wraps a form's JSON schema and API base in a small script, so pages can
+//! load it with a plain `<script>` tag (no CORS preflight, no bundled WASM
+//! client) and render a custom UI.
+//!
+//! The API base is resolved in the browser relative to the script's own URL,
+//! so it is correct behind proxies and on any host:
+//!
+//! ```html
+//! <script type="module">
+//!   import form, { apiBase, submitUrl } from "https://forms.example.com/api/v1/forms/contact/config.js";
+//! </script>
+//!
+//! <!-- or JSONP-style, for classic scripts -->
+//! <script src="https://forms.example.com/api/v1/forms/contact/config.js?callback=renderForm"></script>
+//! ```
+
+use crate::error::FormError;
+
+/// Renders the `config.js` script for a form.
+pub struct ConfigScript<'a> {
+    form: &'a serde_json::Value,
+    slug: &'a str,
+    api_prefix: &'a str,
+}
+
+impl<'a> ConfigScript<'a> {
+    /// Creates a config script for `form`, served under `api_prefix` (e.g. `/api/v1`).
+    #[must_use]
+    pub fn new(form: &'a serde_json::Value, slug: &'a str, api_prefix: &'a str) -> Self {
+        Self {
+            form,
+            slug,
+            api_prefix,
+        }
+    }
+
+    /// Renders an ES module exporting `form` (also the default export),
+    /// `apiBase` and `submitUrl`.
+    #[must_use]
+    pub fn module(&self) -> String {
+        format!(
+            "export const form = {form};\n\
+             export const apiBase = new URL({prefix}, import.meta.url).href;\n\
+             export const submitUrl = apiBase + {path};\n\
+             export default form;\n",
+            form = self.form,
+            prefix = js_string(self.api_prefix),
+            path = js_string(&self.submit_path()),
+        )
+    }
+
+    /// Renders a JSONP-style script calling `callback({ form, apiBase, submitUrl })`.
+    ///
+    /// Returns [`FormError::InvalidData`] unless `callback` is a plain
+    /// (optionally dotted) JavaScript identifier.
+    pub fn jsonp(&self, callback: &str) -> Result<String, FormError> {
+        if !is_callback_name(callback) {
+            return Err(FormError::InvalidData(format!(
+                "Invalid callback name: {callback}"
+            )));
+        }
+
+        Ok(format!(
+            "(function (apiBase) {{\n  \
+             {callback}({{ form: {form}, apiBase: apiBase, submitUrl: apiBase + {path} }});\n\
+             }})(new URL({prefix}, document.currentScript ? document.currentScript.src : location.href).href);\n",
+            form = self.form,
+            prefix = js_string(self.api_prefix),
+            path = js_string(&self.submit_path()),
+        ))
+    }
+
+    fn submit_path(&self) -> String {
+        format!("/forms/{}", self.slug)
+    }
+}
+
+/// Quotes `s` as a JavaScript string literal.
+fn js_string(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+/// Returns true for identifiers like `render` or `window.anyform.load`.
+fn is_callback_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 128
+        && name.split('.').all(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_exports() {
+        let form = serde_json::json!({ "slug": "contact" });
+        let js = ConfigScript::new(&form, "contact", "/api/v1").module();
+
+        assert!(js.contains("export const form = {\"slug\":\"contact\"};"));
+        assert!(js.contains("new URL(\"/api/v1\", import.meta.url)"));
+        assert!(js.contains("apiBase + \"/forms/contact\""));
+        assert!(js.contains("export default form;"));
+    }
+
+    #[test]
+    fn test_jsonp_callback() {
+        let form = serde_json::json!({});
+        let script = ConfigScript::new(&form, "contact", "/api/v1");
+
+        let js = script.jsonp("window.af.load").unwrap();
+        assert!(js.contains("window.af.load({ form: {}"));
+
+        assert!(script.jsonp("alert(1)").is_err());
+        assert!(script.jsonp("a..b").is_err());
+        assert!(script.jsonp("1abc").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Content Security Policy support for rendered HTML.
+//!
+//! The HTML renderer emits a small amount of inline markup (the multi-step
+//! `<style>` block, the WASM loader `<script>`, and a `style` attribute on the
+//! final submit button). Under a strict policy these need either a per-request
+//! nonce or a hash; this module provides both.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use sha2::{Digest, Sha256};
+
+use crate::captcha::CaptchaProvider;
+
+/// Inline `style` attribute values emitted by [`HtmlRenderer`](super::HtmlRenderer).
+///
+/// Attribute styles cannot carry a nonce, so they are allowed by hash instead.
+pub const INLINE_STYLE_ATTRIBUTES: &[&str] = &["display:none"];
+
+/// Generates a fresh, base64-encoded nonce suitable for a single response.
+#[must_use]
+pub fn generate_nonce() -> String {
+    BASE64.encode(uuid::Uuid::new_v4().as_bytes())
+}
+
+/// Returns the CSP hash source (`'sha256-...'`) for the given inline content.
+#[must_use]
+pub fn hash_source(content: &str) -> String {
+    format!("'sha256-{}'", BASE64.encode(Sha256::digest(content.as_bytes())))
+}
+
+/// Builds a `Content-Security-Policy` header value matching rendered form HTML.
+///
+/// Scripts and `<style>` blocks are allowed by `nonce`; the inline style
+/// attributes listed in [`INLINE_STYLE_ATTRIBUTES`] are allowed by hash.
+/// `'wasm-unsafe-eval'` is included so the hydration client can instantiate
+/// its WebAssembly module.
+#[must_use]
+pub fn content_security_policy(nonce: &str) -> String {
+    content_security_policy_with_captcha(nonce, None)
+}
+
+/// Like [`content_security_policy`], also allowing the scripts, styles,
+/// frames and requests of a CAPTCHA provider's widget.
+#[must_use]
+pub fn content_security_policy_with_captcha(
+    nonce: &str,
+    captcha: Option<CaptchaProvider>,
+) -> String {
+    let attribute_hashes: Vec<String> = INLINE_STYLE_ATTRIBUTES
+        .iter()
+        .map(|style| hash_source(style))
+        .collect();
+    let (sources, widget) = match captcha {
+        Some(provider) => (
+            format!(" {}", provider.csp_sources()),
+            format!(
+                "frame-src {0}; connect-src 'self' {0}; ",
+                provider.csp_sources()
+            ),
+        ),
+        None => (String::new(), String::new()),
+    };
+
+    format!(
+        "default-src 'self'; \
+         script-src 'self' 'nonce-{nonce}' 'wasm-unsafe-eval'{sources}; \
+         style-src 'self' 'nonce-{nonce}'{sources}; \
+         style-src-attr 'unsafe-hashes' {}; \
+         {widget}\
+         object-src 'none'; \
+         base-uri 'self'",
+        attribute_hashes.join(" ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_nonce_is_unique() {
+        let a = generate_nonce();
+        let b = generate_nonce();
+        assert_ne!(a, b);
+        assert_eq!(a.len(), 24);
+    }
+
+    #[test]
+    fn test_hash_source_known_value() {
+        // echo -n 'display:none' | openssl dgst -sha256 -binary | base64
+        assert_eq!(
+            hash_source("display:none"),
+            "'sha256-aqNNdDLnnrDOnTNdkJpYlAxKVJtLt9CtFLklmInuUAE='"
+        );
+    }
+
+    #[test]
+    fn test_policy_contains_nonce_and_hashes() {
+        let policy = content_security_policy("abc123");
+        assert!(policy.contains("script-src 'self' 'nonce-abc123'"));
+        assert!(policy.contains("style-src 'self' 'nonce-abc123'"));
+        assert!(policy.contains(&hash_source("display:none")));
+    }
+
+    #[test]
+    fn test_policy_allows_captcha_widget() {
+        let policy = content_security_policy_with_captcha("abc", Some(CaptchaProvider::Turnstile));
+        let origin = CaptchaProvider::Turnstile.csp_sources();
+        assert!(policy.contains(&format!("'wasm-unsafe-eval' {origin};")));
+        assert!(policy.contains(&format!("frame-src {origin};")));
+        assert!(!content_security_policy("abc").contains("frame-src"));
+    }
+}
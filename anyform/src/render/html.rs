@@ -1,13 +1,26 @@
 //! HTML rendering for forms with multi-step and WASM hydration support.
 
 use crate::condition::ConditionRule;
+use regex::Regex;
 use sea_orm::DatabaseConnection;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
 
-use crate::entities::{field, field_option, form, step};
+use crate::captcha::Captcha;
+use crate::entities::{field, field_option, form};
 use crate::error::{FormError, ValidationErrors};
-use crate::schema::{FieldValue, ValidationRules, ValueType};
+use crate::i18n::{localize_form, localize_steps};
+use crate::piping::{self, Answers};
+use crate::progress::Progress;
+use crate::repository::{load_steps_with_fields, FieldWithOptions, StepWithFields};
+use crate::schema::{
+    check_custom_css, check_custom_js, Branding, FieldValue, FormSettings, HelpMedia,
+    ValidationRules, ValueType,
+};
+use crate::spam::{SpamProtection, SpamSecret, HONEYPOT_FIELD, RENDERED_AT_FIELD};
+
+/// Stands in for the row number in a repeatable group's row template.
+pub const ROW_INDEX: &str = "__index__";
 
 /// Options for HTML rendering.
 #[derive(Debug, Clone, Default)]
@@ -38,10 +51,28 @@ pub struct HtmlOptions {
     pub method: Option<String>,
     /// Base URL for WASM client files. None = no WASM loader.
     pub wasm_base_url: Option<String>,
+    /// Client version appended to the loader URL as `?v=` for cache pinning.
+    pub wasm_version: Option<String>,
+    /// Subresource integrity hash for the loader (e.g. `sha384-...`).
+    pub wasm_integrity: Option<String>,
     /// Whether to force multi-step mode (auto-detected if None).
     pub multi_step: Option<bool>,
     /// Whether to include inline CSS for multi-step forms.
     pub include_styles: bool,
+    /// URL of an external stylesheet to link instead of inlining CSS.
+    pub styles_href: Option<String>,
+    /// CSP nonce applied to every inline `<style>` and `<script>` tag.
+    pub csp_nonce: Option<String>,
+    /// Whether to mark the form as a preview whose submissions are not recorded.
+    pub preview: bool,
+    /// Whether submissions are temporarily unavailable (e.g. read-only mode).
+    pub unavailable: bool,
+    /// Key signing the render stamp of forms with spam protection.
+    pub spam_secret: Option<SpamSecret>,
+    /// Whether to render a blank form for printing instead of a web form.
+    pub print_mode: bool,
+    /// Locale to render the form's text in; see [`crate::i18n`].
+    pub locale: Option<String>,
 }
 
 impl HtmlOptions {
@@ -86,6 +117,20 @@ impl HtmlOptions {
         self
     }
 
+    /// Pins the WASM loader URL to a client version (`af-client.js?v=...`).
+    #[must_use]
+    pub fn wasm_version(mut self, version: impl Into<String>) -> Self {
+        self.wasm_version = Some(version.into());
+        self
+    }
+
+    /// Sets the subresource integrity hash emitted on the WASM loader tag.
+    #[must_use]
+    pub fn wasm_integrity(mut self, integrity: impl Into<String>) -> Self {
+        self.wasm_integrity = Some(integrity.into());
+        self
+    }
+
     /// Forces multi-step mode on or off.
     ///
     /// By default, multi-step mode is auto-detected based on the number of steps.
@@ -101,19 +146,117 @@ impl HtmlOptions {
         self.include_styles = include;
         self
     }
+
+    /// Links the multi-step CSS from the given URL instead of inlining it.
+    ///
+    /// Useful under a Content Security Policy that disallows inline styles.
+    /// The built-in stylesheet is available as [`MULTI_STEP_CSS`].
+    #[must_use]
+    pub fn styles_href(mut self, href: impl Into<String>) -> Self {
+        self.styles_href = Some(href.into());
+        self
+    }
+
+    /// Sets the CSP nonce applied to inline `<style>` and `<script>` tags.
+    ///
+    /// Pair with [`content_security_policy`](super::csp::content_security_policy)
+    /// to build the matching response header.
+    #[must_use]
+    pub fn csp_nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.csp_nonce = Some(nonce.into());
+        self
+    }
+
+    /// Marks the form as a preview, rendering a notice that submissions are
+    /// not recorded.
+    #[must_use]
+    pub fn preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    /// Marks submissions as temporarily unavailable, rendering a notice and
+    /// disabling the submit button.
+    #[must_use]
+    pub fn unavailable(mut self, unavailable: bool) -> Self {
+        self.unavailable = unavailable;
+        self
+    }
+
+    /// Signs the render stamp of forms with spam protection with `secret`;
+    /// see [`crate::spam`]. Without one, only the honeypot is rendered.
+    #[must_use]
+    pub fn spam_secret(mut self, secret: SpamSecret) -> Self {
+        self.spam_secret = Some(secret);
+        self
+    }
+
+    /// Renders a blank form to print and fill in on paper: all steps
+    /// expanded, choices as empty boxes and lines to write answers on,
+    /// without buttons or scripts, so the same definition can collect
+    /// answers on paper to key in later.
+    ///
+    /// The inline styles are [`PRINT_CSS`]; a stylesheet linked with
+    /// [`Self::styles_href`] should include them.
+    #[must_use]
+    pub fn print_mode(mut self, print: bool) -> Self {
+        self.print_mode = print;
+        self
+    }
+
+    /// Renders the form's text in `locale` when it has a translation for
+    /// it; see [`crate::i18n`].
+    #[must_use]
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Returns the ` nonce="..."` attribute, or an empty string.
+    fn nonce_attr(&self) -> String {
+        self.csp_nonce
+            .as_ref()
+            .map(|n| format!(" nonce=\"{}\"", escape_html(n)))
+            .unwrap_or_default()
+    }
 }
 
-/// Inline CSS for multi-step forms.
-const MULTI_STEP_CSS: &str = r#"<style>
-.af-step:not([data-af-visible="true"]) { display: none; }
+/// Built-in CSS for multi-step forms and image-choice cards.
+pub const MULTI_STEP_CSS: &str = r#".af-step:not([data-af-visible="true"]) { display: none; }
 .af-field:not([data-af-visible="true"]) { display: none; }
 .af-field.af-error input,
 .af-field.af-error select,
 .af-field.af-error textarea { border-color: var(--af-error, #ef4444); }
 .af-field .af-error-message { color: var(--af-error, #ef4444); font-size: 0.875rem; }
-</style>
+.af-navigation { display: flex; gap: 0.5rem; }
+.af-navigation .af-prev { margin-inline-end: auto; }
+.af-progress { display: flex; align-items: center; gap: 0.5rem; }
+.af-image-choices { display: grid; grid-template-columns: repeat(auto-fill, minmax(10rem, 1fr)); gap: 0.75rem; }
+.af-image-card { display: flex; flex-direction: column; gap: 0.5rem; padding: 0.5rem; border: 2px solid #d1d5db; border-radius: 0.5rem; cursor: pointer; }
+.af-image-card:has(input:checked) { border-color: var(--af-primary, #2563eb); }
+.af-image-card:has(input:focus-visible) { outline: 2px solid var(--af-primary, #2563eb); outline-offset: 2px; }
+.af-image-card img { width: 100%; aspect-ratio: 4 / 3; object-fit: cover; border-radius: 0.25rem; }
+"#;
+
+/// Built-in CSS for forms rendered with [`HtmlOptions::print_mode`].
+pub const PRINT_CSS: &str = r#".af-print { max-width: 48rem; margin: 0 auto; color: #000; }
+.af-print .af-field { margin: 0 0 1.25rem; break-inside: avoid; }
+.af-print .af-label { font-weight: bold; margin: 0 0 0.5rem; }
+.af-print .af-choice { display: flex; align-items: center; gap: 0.5rem; margin: 0.25rem 0; }
+.af-print .af-box { width: 0.9rem; height: 0.9rem; border: 1px solid #000; flex: none; }
+.af-print .af-box--round { border-radius: 50%; }
+.af-print .af-line { display: block; height: 1.75rem; border-bottom: 1px solid #000; }
+.af-print .help { display: block; font-size: 0.875rem; margin-top: 0.25rem; }
+@page { margin: 15mm; }
 "#;
 
+/// Notice shown on preview pages.
+const PREVIEW_NOTICE: &str = "Preview: submissions are not recorded.";
+
+/// Notice shown while submissions are unavailable.
+const UNAVAILABLE_NOTICE: &str =
+    "This form is temporarily unavailable. Please try again in a few minutes.";
+
 /// Renders forms to HTML.
 pub struct HtmlRenderer;
 
@@ -128,6 +271,100 @@ impl HtmlRenderer {
             .await
     }
 
+    /// Renders the closed message shown instead of a closed form, or its
+    /// not-open message before it opens.
+    #[must_use]
+    pub fn render_closed(form: &form::Model) -> String {
+        let settings = form.settings();
+        let (state, message) = if form.is_upcoming() {
+            ("not-open", settings.not_open_message_or_default())
+        } else {
+            ("closed", settings.closed_message_or_default())
+        };
+        format!(
+            "<div class=\"af-form af-{state}\" data-af-form=\"{}\"{} data-af-{state}>\n  <p>{}</p>\n</div>\n",
+            escape_html(&form.slug),
+            dir_attrs(&settings),
+            escape_html(message)
+        )
+    }
+
+    /// Renders the message shown instead of a form to a browser that already
+    /// responded to it; see [`crate::duplicates`].
+    #[must_use]
+    pub fn render_already_responded(form: &form::Model) -> String {
+        let settings = form.settings();
+        let guard = settings.duplicate_guard.clone().unwrap_or_default();
+        format!(
+            "<div class=\"af-form af-responded\" data-af-form=\"{}\"{} data-af-responded>\n  <p>{}</p>\n</div>\n",
+            escape_html(&form.slug),
+            dir_attrs(&settings),
+            escape_html(guard.message_or_default())
+        )
+    }
+
+    /// Renders the notice shown instead of accepting a submission that the
+    /// server can't take right now, tagged with the error's `code`.
+    #[must_use]
+    pub fn render_unavailable(slug: &str, code: &str) -> String {
+        format!(
+            "<div class=\"af-form af-unavailable\" data-af-form=\"{}\" data-af-error=\"{}\">\n  <p>{UNAVAILABLE_NOTICE}</p>\n  <p><a href=\"/forms/{}\">Back to the form</a></p>\n</div>\n",
+            escape_html(slug),
+            escape_html(code),
+            escape_html(slug)
+        )
+    }
+
+    /// Renders the confirmation shown after a preview submission.
+    #[must_use]
+    pub fn render_preview_submitted(form: &form::Model, restart_url: &str) -> String {
+        let settings = form.settings();
+        let message = settings
+            .success_message
+            .as_deref()
+            .unwrap_or("Thank you! Your submission has been received.");
+        format!(
+            "<div class=\"af-form af-preview\" data-af-form=\"{}\"{} data-af-preview>\n  <p>{}</p>\n  <p class=\"af-preview-notice\">{PREVIEW_NOTICE}</p>\n  <p><a href=\"{}\">Start over</a></p>\n</div>\n",
+            escape_html(&form.slug),
+            dir_attrs(&settings),
+            escape_html(message),
+            escape_html(restart_url)
+        )
+    }
+
+    /// Renders the message shown after a submission, with the form's branding.
+    #[must_use]
+    pub fn render_success(form: &form::Model, message: &str) -> String {
+        let settings = form.settings();
+        let branding = checked_branding(form, &settings);
+        let mut html = String::new();
+        if let Some(css) = branding.and_then(Branding::css_variables) {
+            writeln!(html, "<style>{css}</style>").unwrap();
+        }
+        writeln!(
+            html,
+            "<div class=\"af-form af-success\" data-af-form=\"{}\"{} data-af-success>",
+            escape_html(&form.slug),
+            dir_attrs(&settings)
+        )
+        .unwrap();
+        if let Some(branding) = branding {
+            render_logo(&mut html, branding);
+        }
+        writeln!(html, "  <p>{}</p>", escape_html(message)).unwrap();
+        writeln!(
+            html,
+            "  <p><a href=\"/forms/{}\">Submit another response</a></p>",
+            escape_html(&form.slug)
+        )
+        .unwrap();
+        if let Some(branding) = branding {
+            render_footer(&mut html, branding);
+        }
+        writeln!(html, "</div>").unwrap();
+        html
+    }
+
     /// Renders a form to HTML with pre-filled values and errors.
     pub async fn render_with_values(
         db: &DatabaseConnection,
@@ -136,34 +373,64 @@ impl HtmlRenderer {
         values: &HashMap<String, FieldValue>,
         errors: &ValidationErrors,
     ) -> Result<String, FormError> {
+        let steps = load_steps_with_fields(db, form.id).await?;
+        Ok(Self::render_steps(form, steps, options, values, errors))
+    }
+
+    /// Renders a form to HTML with pre-filled values and errors, from its
+    /// steps, fields and options as loaded by a
+    /// [`FormsRepository`](crate::repository::FormsRepository).
+    #[must_use]
+    pub fn render_steps(
+        form: &form::Model,
+        mut steps: Vec<StepWithFields>,
+        options: &HtmlOptions,
+        values: &HashMap<String, FieldValue>,
+        errors: &ValidationErrors,
+    ) -> String {
+        let form = &localize_form(form, options.locale.as_deref());
+        if form.is_closed() {
+            return Self::render_closed(form);
+        }
+        localize_steps(form, &mut steps, options.locale.as_deref());
+        if options.print_mode {
+            return Self::render_print(form, &steps, options);
+        }
+
         let settings = form.settings();
+        let branding = checked_branding(form, &settings);
         let mut html = String::new();
 
-        // Load all steps and fields
-        let steps = step::Entity::find_by_form(db, form.id).await?;
-        let mut needs_multipart = false;
-        let mut all_fields: Vec<(&step::Model, Vec<field::Model>)> = Vec::new();
-
-        for step in &steps {
-            let fields = field::Entity::find_by_step(db, step.id).await?;
-            for field in &fields {
-                if let Some(vt) = field.value_type() {
-                    if vt.is_file_type() {
-                        needs_multipart = true;
-                    }
-                }
-            }
-            all_fields.push((step, fields));
-        }
+        let needs_multipart = steps
+            .iter()
+            .flat_map(|s| &s.fields)
+            .any(|f| f.field.value_type().is_some_and(|vt| vt.is_file_type()));
 
         // Determine if this is a multi-step form
-        let is_multi_step = options.multi_step.unwrap_or(all_fields.len() > 1);
-
-        // Include CSS for multi-step forms
-        if is_multi_step && options.include_styles {
-            html.push_str(MULTI_STEP_CSS);
+        let is_multi_step = options.multi_step.unwrap_or(steps.len() > 1);
+
+        // Include CSS for multi-step forms, and for image cards
+        let has_image_choice = steps
+            .iter()
+            .flat_map(|s| &s.fields)
+            .any(|f| f.field.value_type() == Some(ValueType::ImageChoice));
+        if (is_multi_step || has_image_choice) && options.include_styles {
+            if let Some(href) = &options.styles_href {
+                writeln!(
+                    html,
+                    "<link rel=\"stylesheet\" href=\"{}\">",
+                    escape_html(href)
+                )
+                .unwrap();
+            } else {
+                writeln!(html, "<style{}>", options.nonce_attr()).unwrap();
+                html.push_str(MULTI_STEP_CSS);
+                writeln!(html, "</style>").unwrap();
+            }
         }
 
+        render_form_css(&mut html, form, &settings, branding, options);
+
         // Form opening tag
         let default_action = format!("/forms/{}", form.slug);
         let action = options
@@ -195,10 +462,16 @@ impl HtmlRenderer {
             form_class.push_str(custom_class);
         }
 
+        let unavailable = if options.unavailable { " data-af-unavailable" } else { "" };
+        // The WASM client saves each step through the JSON API
+        let save_steps =
+            is_multi_step && settings.save_each_step && !options.preview && !options.unavailable;
+        let save_steps_attr = if save_steps { " data-af-save-steps" } else { "" };
         writeln!(
             html,
-            "<form method=\"{method}\" action=\"{action}\"{enctype} class=\"{form_class}\" data-af-form=\"{}\">",
-            form.slug
+            "<form method=\"{method}\" action=\"{action}\"{enctype} class=\"{form_class}\" data-af-form=\"{}\"{}{unavailable}{save_steps_attr}>",
+            form.slug,
+            dir_attrs(&settings)
         )
         .unwrap();
 
@@ -213,25 +486,75 @@ impl HtmlRenderer {
             }
         }
 
-        // Render steps and fields
-        for (step_index, (step, fields)) in all_fields.iter().enumerate() {
+        if let Some(protection) = settings.spam_protection.filter(|_| !options.preview) {
+            Self::render_spam_protection(&mut html, &protection, options);
+        }
+
+        if let Some(branding) = branding {
+            render_logo(&mut html, branding);
+        }
+
+        if settings.anonymous {
+            writeln!(
+                html,
+                "  <p class=\"af-anonymous-notice\" data-af-anonymous>{}</p>",
+                escape_html(settings.anonymous_notice_or_default())
+            )
+            .unwrap();
+        }
+
+        if options.preview {
+            writeln!(
+                html,
+                "  <p class=\"af-preview-notice\" data-af-preview>{PREVIEW_NOTICE}</p>"
+            )
+            .unwrap();
+        }
+
+        if options.unavailable {
+            writeln!(
+                html,
+                "  <p class=\"af-unavailable-notice\" role=\"status\">{UNAVAILABLE_NOTICE}</p>"
+            )
+            .unwrap();
+        }
+
+        if is_multi_step && settings.show_progress {
+            let conditions: Vec<_> = steps.iter().map(|s| s.step.condition_rule()).collect();
+            let json_values: HashMap<String, serde_json::Value> =
+                values.iter().map(|(k, v)| (k.clone(), v.into())).collect();
+            let progress = Progress::compute(
+                settings.progress_mode.unwrap_or_default(),
+                &conditions,
+                0,
+                &json_values,
+            );
+            Self::render_progress(&mut html, progress);
+        }
+
+        // Render steps and fields, quoting the answers labels pipe in
+        let answers = Answers::new(&steps, values);
+        for (step_index, step) in steps.iter().enumerate() {
             Self::render_step(
                 &mut html,
                 step,
-                fields,
                 step_index,
                 is_multi_step,
+                save_steps,
                 values,
+                &answers,
                 errors,
                 options,
-                db,
-            )
-            .await?;
+            );
+        }
+
+        if let Some(captcha) = settings.captcha.as_ref().filter(|_| !options.preview) {
+            Self::render_captcha(&mut html, captcha, options);
         }
 
         // Navigation buttons (multi-step) or submit button (single-step)
         if is_multi_step {
-            Self::render_navigation(&mut html, &settings.submit_label_or_default(), options);
+            Self::render_navigation(&mut html, settings.submit_label_or_default(), options);
         } else {
             let button_class = options
                 .button_class
@@ -239,97 +562,549 @@ impl HtmlRenderer {
                 .map(|c| format!(" class=\"{c}\""))
                 .unwrap_or_default();
 
-            writeln!(
-                html,
-                "  <button type=\"submit\"{button_class}>{}</button>",
-                escape_html(&settings.submit_label_or_default())
-            )
-            .unwrap();
+            writeln!(
+                html,
+                "  <button type=\"submit\"{button_class}{}>{}</button>",
+                disabled_attr(options),
+                escape_html(settings.submit_label_or_default())
+            )
+            .unwrap();
+        }
+
+        // WASM loader script
+        if let Some(wasm_url) = &options.wasm_base_url {
+            let url = wasm_url.trim_end_matches('/');
+            let version = options
+                .wasm_version
+                .as_ref()
+                .map(|v| format!("?v={}", escape_html(v)))
+                .unwrap_or_default();
+            let integrity = options
+                .wasm_integrity
+                .as_ref()
+                .map(|i| format!(" integrity=\"{}\" crossorigin=\"anonymous\"", escape_html(i)))
+                .unwrap_or_default();
+            writeln!(
+                html,
+                "  <script type=\"module\" src=\"{url}/af-client.js{version}\"{integrity}{}></script>",
+                options.nonce_attr()
+            )
+            .unwrap();
+        }
+
+        if let Some(branding) = branding {
+            render_footer(&mut html, branding);
+        }
+
+        writeln!(html, "</form>").unwrap();
+
+        // Per-form JS, scoped to a function so it cannot leak globals
+        if let Some(js) = settings.custom_js.as_deref().filter(|js| !js.is_empty()) {
+            match check_custom_js(js) {
+                Ok(()) => {
+                    writeln!(html, "<script{} data-af-custom>", options.nonce_attr()).unwrap();
+                    writeln!(html, "(function () {{\n{js}\n}})();").unwrap();
+                    writeln!(html, "</script>").unwrap();
+                }
+                Err(e) => tracing::warn!(form = %form.slug, error = %e, "skipping custom JS"),
+            }
+        }
+
+        html
+    }
+
+    /// Renders a blank form for printing; see [`HtmlOptions::print_mode`].
+    fn render_print(form: &form::Model, steps: &[StepWithFields], options: &HtmlOptions) -> String {
+        let settings = form.settings();
+        let branding = checked_branding(form, &settings);
+        let mut html = String::new();
+
+        if options.include_styles {
+            if let Some(href) = &options.styles_href {
+                writeln!(html, "<link rel=\"stylesheet\" href=\"{}\">", escape_html(href))
+                    .unwrap();
+            } else {
+                writeln!(html, "<style{}>", options.nonce_attr()).unwrap();
+                html.push_str(PRINT_CSS);
+                writeln!(html, "</style>").unwrap();
+            }
+        }
+        render_form_css(&mut html, form, &settings, branding, options);
+
+        let mut form_class = String::from("af-form af-print");
+        if let Some(custom_class) = options
+            .form_class
+            .as_deref()
+            .or(settings.css_class.as_deref())
+        {
+            form_class.push(' ');
+            form_class.push_str(custom_class);
+        }
+        writeln!(
+            html,
+            "<div class=\"{form_class}\" data-af-form=\"{}\"{} data-af-print>",
+            escape_html(&form.slug),
+            dir_attrs(&settings)
+        )
+        .unwrap();
+
+        if let Some(branding) = branding {
+            render_logo(&mut html, branding);
+        }
+        writeln!(html, "  <h1>{}</h1>", escape_html(&form.name)).unwrap();
+        if let Some(description) = &form.description {
+            writeln!(html, "  <p>{}</p>", escape_html(description)).unwrap();
+        }
+        if settings.anonymous {
+            writeln!(
+                html,
+                "  <p class=\"af-anonymous-notice\" data-af-anonymous>{}</p>",
+                escape_html(settings.anonymous_notice_or_default())
+            )
+            .unwrap();
+        }
+
+        // Every step, one after the other; marked visible so the multi-step
+        // stylesheet doesn't hide them either
+        for (step_index, StepWithFields { step, fields }) in steps.iter().enumerate() {
+            writeln!(
+                html,
+                "  <section class=\"af-step\" data-af-step=\"{step_index}\" data-af-visible=\"true\">"
+            )
+            .unwrap();
+            if steps.len() > 1 {
+                writeln!(html, "    <h2>{}</h2>", escape_html(&step.name)).unwrap();
+            }
+            if let Some(desc) = &step.description {
+                writeln!(html, "    <p>{}</p>", piping::render_html(desc, &Answers::default()))
+                    .unwrap();
+            }
+            for field in fields {
+                Self::render_print_field(&mut html, &field.field, &field.options, options);
+            }
+            writeln!(html, "  </section>").unwrap();
+        }
+
+        if let Some(branding) = branding {
+            render_footer(&mut html, branding);
+        }
+        writeln!(html, "</div>").unwrap();
+        html
+    }
+
+    /// Renders a field of a printed form: its label, then boxes to tick or
+    /// lines to write on.
+    fn render_print_field(
+        html: &mut String,
+        field: &field::Model,
+        options: &[field_option::Model],
+        html_options: &HtmlOptions,
+    ) {
+        let ui = field.ui();
+        let value_type = field.value_type();
+        match value_type {
+            Some(ValueType::Heading) => {
+                let level = ui.heading_level.unwrap_or(2);
+                let label = piping::render_html(&field.label, &Answers::default());
+                writeln!(html, "    <h{level}>{label}</h{level}>").unwrap();
+                return;
+            }
+            Some(ValueType::Paragraph) => {
+                if let Some(text) = &field.help_text {
+                    let text = piping::render_html(text, &Answers::default());
+                    writeln!(html, "    <p>{text}</p>").unwrap();
+                }
+                return;
+            }
+            Some(ValueType::Hidden) => return,
+            _ => {}
+        }
+
+        let field_class = html_options.field_class.as_deref().unwrap_or("field");
+        writeln!(
+            html,
+            "    <div class=\"af-field {field_class}\" data-af-field=\"{}\" data-af-visible=\"true\">",
+            field.name
+        )
+        .unwrap();
+
+        let label_class = html_options
+            .label_class
+            .as_ref()
+            .map(|c| format!(" {c}"))
+            .unwrap_or_default();
+        let required_indicator = if field.required && html_options.show_required_indicator {
+            " <span class=\"required\">*</span>"
+        } else {
+            ""
+        };
+        // Printed blank, so placeholders show their fallbacks
+        let label = piping::render_html(&field.label, &Answers::default());
+
+        match value_type {
+            Some(ValueType::Checkbox) => {
+                writeln!(
+                    html,
+                    "      <div class=\"af-choice af-label{label_class}\"><span class=\"af-box\"></span> {label}{required_indicator}</div>"
+                )
+                .unwrap();
+            }
+            Some(
+                vt @ (ValueType::Select
+                | ValueType::Radio
+                | ValueType::ImageChoice
+                | ValueType::MultiSelect),
+            ) => {
+                writeln!(
+                    html,
+                    "      <p class=\"af-label{label_class}\">{label}{required_indicator}</p>"
+                )
+                .unwrap();
+                let shape = if vt == ValueType::MultiSelect { "" } else { " af-box--round" };
+                for opt in options {
+                    writeln!(
+                        html,
+                        "      <div class=\"af-choice\"><span class=\"af-box{shape}\"></span> {}</div>",
+                        escape_html(&option_label(opt))
+                    )
+                    .unwrap();
+                }
+            }
+            _ => {
+                writeln!(
+                    html,
+                    "      <p class=\"af-label{label_class}\">{label}{required_indicator}</p>"
+                )
+                .unwrap();
+                let lines = match value_type {
+                    Some(ValueType::Textarea) => ui.rows.unwrap_or(4),
+                    _ => 1,
+                };
+                for _ in 0..lines {
+                    writeln!(html, "      <span class=\"af-line\"></span>").unwrap();
+                }
+            }
+        }
+
+        if let Some(help) = &field.help_text {
+            let help_class = html_options.help_class.as_deref().unwrap_or("help");
+            writeln!(
+                html,
+                "      <small class=\"{help_class}\">{}</small>",
+                piping::render_html(help, &Answers::default())
+            )
+            .unwrap();
+        }
+        writeln!(html, "    </div>").unwrap();
+    }
+
+    /// Renders a single step container with its fields.
+    #[allow(clippy::too_many_arguments)]
+    fn render_step(
+        html: &mut String,
+        StepWithFields { step, fields }: &StepWithFields,
+        step_index: usize,
+        is_multi_step: bool,
+        save_steps: bool,
+        values: &HashMap<String, FieldValue>,
+        answers: &Answers,
+        errors: &ValidationErrors,
+        options: &HtmlOptions,
+    ) {
+        if is_multi_step {
+            // Multi-step: use div with data attributes
+            let visible = if step_index == 0 { "true" } else { "false" };
+            // Stored as JSON text or an object; render the parsed rule so
+            // the client always gets an object
+            let condition_attr = step
+                .condition_rule()
+                .map(|c| format!(" data-af-condition='{}'", render_condition_json(&c)))
+                .unwrap_or_default();
+            // Steps are saved by ID
+            let id_attr = if save_steps {
+                format!(" data-af-step-id=\"{}\"", step.id)
+            } else {
+                String::new()
+            };
+
+            writeln!(
+                html,
+                "  <div class=\"af-step\" data-af-step=\"{step_index}\"{id_attr} data-af-visible=\"{visible}\"{condition_attr}>"
+            )
+            .unwrap();
+
+            writeln!(html, "    <h2>{}</h2>", escape_html(&step.name)).unwrap();
+
+            if let Some(desc) = &step.description {
+                writeln!(html, "    <p>{}</p>", piping::render_html(desc, answers)).unwrap();
+            }
+        } else if fields.len() > 1 || step.description.is_some() {
+            // Single-step with multiple fields: use fieldset
+            writeln!(html, "  <fieldset>").unwrap();
+            writeln!(html, "    <legend>{}</legend>", escape_html(&step.name)).unwrap();
+
+            if let Some(desc) = &step.description {
+                writeln!(html, "    <p>{}</p>", piping::render_html(desc, answers)).unwrap();
+            }
+        }
+
+        // Render fields
+        for FieldWithOptions {
+            field,
+            options: field_options,
+            fields: sub_fields,
+        } in fields
+        {
+            let value = values
+                .get(&field.id.to_string())
+                .or_else(|| values.get(&field.name));
+            if field.is_repeater() {
+                Self::render_repeater(
+                    html,
+                    field,
+                    sub_fields,
+                    value,
+                    answers,
+                    errors,
+                    options,
+                    is_multi_step,
+                );
+                continue;
+            }
+            let field_errors = errors.get(&field.name);
+
+            Self::render_field(
+                html,
+                field,
+                field_options,
+                value,
+                answers,
+                field_errors,
+                options,
+                is_multi_step,
+            );
+        }
+
+        if is_multi_step {
+            writeln!(html, "  </div>").unwrap();
+        } else if fields.len() > 1 || step.description.is_some() {
+            writeln!(html, "  </fieldset>").unwrap();
+        }
+    }
+
+    /// Renders a repeatable group: a row of its sub-fields per answer, a
+    /// template the client copies for new rows, and add/remove buttons.
+    ///
+    /// Sub-field inputs are named `{group}[{row}][{sub-field}]`. Without the
+    /// client the rendered rows are all there is, so there's at least one;
+    /// rows left blank aren't submitted.
+    #[allow(clippy::too_many_arguments)]
+    fn render_repeater(
+        html: &mut String,
+        field: &field::Model,
+        sub_fields: &[FieldWithOptions],
+        value: Option<&FieldValue>,
+        answers: &Answers,
+        errors: &ValidationErrors,
+        html_options: &HtmlOptions,
+        is_multi_step: bool,
+    ) {
+        let ui = field.ui();
+        let validation = field.validation();
+        let rows = value.and_then(FieldValue::as_rows).unwrap_or_default();
+        let count = rows.len().max(validation.min_rows.unwrap_or(0)).max(1);
+        let field_errors = errors.get(&field.name);
+
+        let mut field_class = String::from("af-field af-repeater ");
+        field_class.push_str(html_options.field_class.as_deref().unwrap_or("field"));
+        if let Some(width) = &ui.width {
+            write!(field_class, " field--{width}").unwrap();
+        }
+        if field_errors.is_some() {
+            field_class.push_str(" field--error af-error");
+        }
+
+        let mut data_attrs = format!(" data-af-field=\"{}\" data-af-repeater", field.name);
+        if is_multi_step {
+            data_attrs.push_str(" data-af-visible=\"true\"");
+        }
+        if let Some(condition) = &ui.condition {
+            write!(data_attrs, " data-af-condition='{}'", render_condition_json(condition))
+                .unwrap();
+        }
+        if field.required || !validation.is_empty() {
+            write!(
+                data_attrs,
+                " data-af-validation='{}'",
+                render_validation_json(field.required, &validation)
+            )
+            .unwrap();
+        }
+
+        let label_class = html_options
+            .label_class
+            .as_ref()
+            .map(|c| format!(" class=\"{c}\""))
+            .unwrap_or_default();
+        let required_indicator = if field.required && html_options.show_required_indicator {
+            " <span class=\"required\">*</span>"
+        } else {
+            ""
+        };
+        let button_class = html_options
+            .button_class
+            .as_ref()
+            .map(|c| format!(" {c}"))
+            .unwrap_or_default();
+
+        writeln!(html, "    <div class=\"{field_class}\"{data_attrs}>").unwrap();
+        writeln!(html, "      <fieldset>").unwrap();
+        writeln!(
+            html,
+            "      <legend{label_class}>{}{required_indicator}</legend>",
+            piping::render_html(&field.label, answers)
+        )
+        .unwrap();
+
+        writeln!(html, "      <div class=\"af-rows\">").unwrap();
+        for index in 0..count {
+            let row = rows.get(index);
+            let index = index.to_string();
+            Self::render_row(html, field, sub_fields, &index, row, answers, errors, html_options);
+        }
+        writeln!(html, "      </div>").unwrap();
+
+        writeln!(html, "      <template class=\"af-row-template\">").unwrap();
+        let blank = ValidationErrors::new();
+        Self::render_row(html, field, sub_fields, ROW_INDEX, None, answers, &blank, html_options);
+        writeln!(html, "      </template>").unwrap();
+
+        writeln!(
+            html,
+            "      <button type=\"button\" class=\"af-add{button_class}\">{}</button>",
+            escape_html(ui.add_label.as_deref().unwrap_or("Add"))
+        )
+        .unwrap();
+
+        if let Some(help) = &field.help_text {
+            let help_class = html_options.help_class.as_deref().unwrap_or("help");
+            let help = piping::render_html(help, answers);
+            writeln!(html, "      <small class=\"{help_class}\">{help}</small>").unwrap();
         }
-
-        // WASM loader script
-        if let Some(wasm_url) = &options.wasm_base_url {
-            let url = wasm_url.trim_end_matches('/');
-            writeln!(
-                html,
-                "  <script type=\"module\" src=\"{url}/af-client.js\"></script>"
-            )
-            .unwrap();
+        if let Some(help) = &ui.help {
+            render_help_media(html, field, help);
+        }
+        if let Some(errs) = field_errors {
+            let error_class = html_options
+                .error_class
+                .as_deref()
+                .unwrap_or("error af-error-message");
+            for err in errs {
+                writeln!(html, "      <span class=\"{error_class}\">{}</span>", escape_html(err))
+                    .unwrap();
+            }
         }
 
-        writeln!(html, "</form>").unwrap();
-
-        Ok(html)
+        writeln!(html, "      </fieldset>").unwrap();
+        writeln!(html, "    </div>").unwrap();
     }
 
-    /// Renders a single step container with its fields.
-    async fn render_step(
+    /// Renders one row of a repeatable group, numbered `index`.
+    #[allow(clippy::too_many_arguments)]
+    fn render_row(
         html: &mut String,
-        step: &step::Model,
-        fields: &[field::Model],
-        step_index: usize,
-        is_multi_step: bool,
-        values: &HashMap<String, FieldValue>,
+        group: &field::Model,
+        sub_fields: &[FieldWithOptions],
+        index: &str,
+        row: Option<&BTreeMap<String, FieldValue>>,
+        answers: &Answers,
         errors: &ValidationErrors,
-        options: &HtmlOptions,
-        db: &DatabaseConnection,
-    ) -> Result<(), FormError> {
-        if is_multi_step {
-            // Multi-step: use div with data attributes
-            let visible = if step_index == 0 { "true" } else { "false" };
-            let condition_attr = step
-                .condition
-                .as_ref()
-                .map(|c| format!(" data-af-condition='{}'", escape_json_attr(c)))
-                .unwrap_or_default();
+        html_options: &HtmlOptions,
+    ) {
+        writeln!(html, "      <div class=\"af-row\" data-af-row=\"{index}\">").unwrap();
+        for FieldWithOptions { field, options, .. } in sub_fields {
+            let mut input = field.clone();
+            input.name = format!("{}[{index}][{}]", group.name, field.name);
+            let value = row.and_then(|row| row.get(&field.name));
+            let field_errors = errors.get(&input.name);
+            Self::render_field(html, &input, options, value, answers, field_errors, html_options, false);
+        }
+        let button_class = html_options
+            .button_class
+            .as_ref()
+            .map(|c| format!(" {c}"))
+            .unwrap_or_default();
+        writeln!(
+            html,
+            "      <button type=\"button\" class=\"af-remove{button_class}\">{}</button>",
+            escape_html(group.ui().remove_label.as_deref().unwrap_or("Remove"))
+        )
+        .unwrap();
+        writeln!(html, "      </div>").unwrap();
+    }
 
+    /// Renders the honeypot and signed render stamp; see [`crate::spam`].
+    fn render_spam_protection(
+        html: &mut String,
+        protection: &SpamProtection,
+        options: &HtmlOptions,
+    ) {
+        if protection.honeypot {
             writeln!(
                 html,
-                "  <div class=\"af-step\" data-af-step=\"{step_index}\" data-af-visible=\"{visible}\"{condition_attr}>"
+                "  <div class=\"af-hp\" hidden aria-hidden=\"true\"><label>Leave this field empty \
+                 <input type=\"text\" name=\"{HONEYPOT_FIELD}\" tabindex=\"-1\" \
+                 autocomplete=\"off\"></label></div>"
             )
             .unwrap();
-
-            writeln!(html, "    <h2>{}</h2>", escape_html(&step.name)).unwrap();
-
-            if let Some(desc) = &step.description {
-                writeln!(html, "    <p>{}</p>", escape_html(desc)).unwrap();
-            }
-        } else if fields.len() > 1 || step.description.is_some() {
-            // Single-step with multiple fields: use fieldset
-            writeln!(html, "  <fieldset>").unwrap();
-            writeln!(html, "    <legend>{}</legend>", escape_html(&step.name)).unwrap();
-
-            if let Some(desc) = &step.description {
-                writeln!(html, "    <p>{}</p>", escape_html(desc)).unwrap();
-            }
         }
-
-        // Render fields
-        for field in fields {
-            let field_options = if field.requires_options() {
-                field_option::Entity::find_by_field(db, field.id).await?
-            } else {
-                Vec::new()
-            };
-
-            let value = values
-                .get(&field.id.to_string())
-                .or_else(|| values.get(&field.name));
-            let field_errors = errors.get(&field.name);
-
-            Self::render_field(html, field, &field_options, value, field_errors, options, is_multi_step);
+        let secret = options.spam_secret.as_ref();
+        if let Some(secret) = secret.filter(|_| protection.min_fill_secs > 0) {
+            writeln!(
+                html,
+                "  <input type=\"hidden\" name=\"{RENDERED_AT_FIELD}\" value=\"{}\">",
+                secret.stamp(chrono::Utc::now())
+            )
+            .unwrap();
         }
+    }
 
-        if is_multi_step {
-            writeln!(html, "  </div>").unwrap();
-        } else if fields.len() > 1 || step.description.is_some() {
-            writeln!(html, "  </fieldset>").unwrap();
-        }
+    /// Renders the CAPTCHA widget and its script; see [`crate::captcha`].
+    fn render_captcha(html: &mut String, captcha: &Captcha, options: &HtmlOptions) {
+        let provider = captcha.provider;
+        writeln!(
+            html,
+            "  <div class=\"af-captcha {}\" data-sitekey=\"{}\"></div>",
+            provider.widget_class(),
+            escape_html(&captcha.site_key)
+        )
+        .unwrap();
+        writeln!(
+            html,
+            "  <script src=\"{}\" async defer{}></script>",
+            provider.script_url(),
+            options.nonce_attr()
+        )
+        .unwrap();
+    }
 
-        Ok(())
+    /// Renders the progress indicator for multi-step forms.
+    fn render_progress(html: &mut String, progress: Progress) {
+        let Progress { current, total } = progress;
+        writeln!(html, "  <div class=\"af-progress\" data-af-progress>").unwrap();
+        writeln!(html, "    <progress value=\"{current}\" max=\"{total}\"></progress>").unwrap();
+        writeln!(
+            html,
+            "    <span class=\"af-progress-text\">Step {current} of {total}</span>"
+        )
+        .unwrap();
+        writeln!(html, "  </div>").unwrap();
     }
 
     /// Renders navigation buttons for multi-step forms.
+    ///
+    /// The buttons keep their reading order (Back, Next, Submit), so under
+    /// `dir="rtl"` the built-in CSS lays them out mirrored.
     fn render_navigation(html: &mut String, submit_label: &str, options: &HtmlOptions) {
         let button_class = options
             .button_class
@@ -350,7 +1125,8 @@ impl HtmlRenderer {
         .unwrap();
         writeln!(
             html,
-            "    <button type=\"submit\" class=\"af-submit{button_class}\" style=\"display:none\">{}</button>",
+            "    <button type=\"submit\" class=\"af-submit{button_class}\" style=\"display:none\"{}>{}</button>",
+            disabled_attr(options),
             escape_html(submit_label)
         )
         .unwrap();
@@ -358,11 +1134,13 @@ impl HtmlRenderer {
     }
 
     /// Renders a single field to HTML.
+    #[allow(clippy::too_many_arguments)]
     fn render_field(
         html: &mut String,
         field: &field::Model,
         options: &[field_option::Model],
         value: Option<&FieldValue>,
+        answers: &Answers,
         errors: Option<&Vec<String>>,
         html_options: &HtmlOptions,
         is_multi_step: bool,
@@ -373,19 +1151,29 @@ impl HtmlRenderer {
         // Skip display-only fields that don't render as inputs
         if let Some(ValueType::Heading) = value_type {
             let level = ui.heading_level.unwrap_or(2);
-            writeln!(html, "    <h{level}>{}</h{level}>", escape_html(&field.label)).unwrap();
+            let label = piping::render_html(&field.label, answers);
+            writeln!(html, "    <h{level}>{label}</h{level}>").unwrap();
             return;
         }
 
         if let Some(ValueType::Paragraph) = value_type {
             if let Some(text) = &field.help_text {
-                writeln!(html, "    <p>{}</p>", escape_html(text)).unwrap();
+                writeln!(html, "    <p>{}</p>", piping::render_html(text, answers)).unwrap();
             }
             return;
         }
 
+        // they're keyed by their own name, the last part of the input's name
+        // they're keyed by their own name, the last part of the input's
+        let (mut field_class, data_attr, key) = match field.parent_id {
+            Some(_) => {
+                let key = field.name.rsplit('[').next().unwrap_or_default();
+                (String::from("af-subfield"), "data-af-subfield", key.trim_end_matches(']'))
+            }
+            None => (String::from("af-field"), "data-af-field", field.name.as_str()),
+        };
+
         // Build field container class
-        let mut field_class = String::from("af-field");
         if let Some(custom_class) = &html_options.field_class {
             field_class.push(' ');
             field_class.push_str(custom_class);
@@ -402,7 +1190,7 @@ impl HtmlRenderer {
         }
 
         // Build data attributes
-        let mut data_attrs = format!(" data-af-field=\"{}\"", field.name);
+        let mut data_attrs = format!(" {data_attr}=\"{key}\"");
 
         // Always visible initially (WASM will hide based on conditions)
         if is_multi_step {
@@ -449,9 +1237,12 @@ impl HtmlRenderer {
             html,
             "      <label for=\"{}\"{label_class}>{}{required_indicator}</label>",
             field.name,
-            escape_html(&field.label)
+            piping::render_html(&field.label, answers)
         )
         .unwrap();
+        if let Some(help) = &ui.help {
+            render_tooltip(html, field, help, answers);
+        }
 
         // Input element
         let input_class = html_options
@@ -462,8 +1253,14 @@ impl HtmlRenderer {
             .unwrap_or_default();
 
         let required = if field.required { " required" } else { "" };
+        let constraints = validation_attrs(&validation, value_type);
         let disabled = if ui.disabled { " disabled" } else { "" };
         let readonly = if ui.readonly { " readonly" } else { "" };
+        let described = ui
+            .help
+            .as_ref()
+            .map(|help| format!(" aria-describedby=\"{}\"", help_ids(field, help)))
+            .unwrap_or_default();
 
         let placeholder = field
             .placeholder
@@ -478,7 +1275,7 @@ impl HtmlRenderer {
                 let rows = ui.rows.unwrap_or(4);
                 writeln!(
                     html,
-                    "      <textarea name=\"{}\" id=\"{}\" rows=\"{rows}\"{input_class}{required}{disabled}{readonly}{placeholder}>{}</textarea>",
+                    "      <textarea name=\"{}\" id=\"{}\" rows=\"{rows}\"{input_class}{described}{required}{constraints}{disabled}{readonly}{placeholder}>{}</textarea>",
                     field.name,
                     field.name,
                     escape_html(&current_value)
@@ -488,7 +1285,7 @@ impl HtmlRenderer {
             Some(ValueType::Select) => {
                 writeln!(
                     html,
-                    "      <select name=\"{}\" id=\"{}\"{input_class}{required}{disabled}>",
+                    "      <select name=\"{}\" id=\"{}\"{input_class}{described}{required}{disabled}>",
                     field.name, field.name
                 )
                 .unwrap();
@@ -497,7 +1294,19 @@ impl HtmlRenderer {
                     writeln!(html, "        <option value=\"\">-- Select --</option>").unwrap();
                 }
 
+                // Consecutive options of a group share an optgroup
+                let mut group: Option<&str> = None;
                 for opt in options {
+                    if opt.group.as_deref() != group {
+                        if group.is_some() {
+                            writeln!(html, "        </optgroup>").unwrap();
+                        }
+                        group = opt.group.as_deref();
+                        if let Some(label) = group {
+                            writeln!(html, "        <optgroup label=\"{}\">", escape_html(label))
+                                .unwrap();
+                        }
+                    }
                     let selected = if current_value == opt.value {
                         " selected"
                     } else {
@@ -505,12 +1314,16 @@ impl HtmlRenderer {
                     };
                     writeln!(
                         html,
-                        "        <option value=\"{}\"{selected}>{}</option>",
+                        "        <option value=\"{}\"{selected}{}>{}</option>",
                         escape_html(&opt.value),
-                        escape_html(&opt.label)
+                        sold_out_attr(opt),
+                        escape_html(&option_label(opt))
                     )
                     .unwrap();
                 }
+                if group.is_some() {
+                    writeln!(html, "        </optgroup>").unwrap();
+                }
 
                 writeln!(html, "      </select>").unwrap();
             }
@@ -524,11 +1337,71 @@ impl HtmlRenderer {
                     let opt_id = format!("{}_{}", field.name, opt.value);
                     writeln!(
                         html,
-                        "      <label><input type=\"radio\" name=\"{}\" id=\"{}\" value=\"{}\"{checked}{required}{disabled}> {}</label>",
+                        "      <label><input type=\"radio\" name=\"{}\" id=\"{}\" value=\"{}\"{checked}{described}{required}{disabled}{}> {}</label>",
+                        field.name,
+                        opt_id,
+                        escape_html(&opt.value),
+                        sold_out_attr(opt),
+                        escape_html(&option_label(opt))
+                    )
+                    .unwrap();
+                }
+            }
+            Some(ValueType::ImageChoice) => {
+                // Cards around radio buttons, captioned by the option label
+                writeln!(html, "      <div class=\"af-image-choices\">").unwrap();
+                for opt in options {
+                    let checked = if current_value == opt.value {
+                        " checked"
+                    } else {
+                        ""
+                    };
+                    let opt_id = format!("{}_{}", field.name, opt.value);
+                    writeln!(html, "        <label class=\"af-image-card\">").unwrap();
+                    writeln!(
+                        html,
+                        "          <input type=\"radio\" name=\"{}\" id=\"{}\" value=\"{}\"{checked}{described}{required}{disabled}{}>",
+                        field.name,
+                        opt_id,
+                        escape_html(&opt.value),
+                        sold_out_attr(opt)
+                    )
+                    .unwrap();
+                    if let Some(url) = &opt.image_url {
+                        writeln!(
+                            html,
+                            "          <img src=\"{}\" alt=\"\" loading=\"lazy\">",
+                            escape_html(url)
+                        )
+                        .unwrap();
+                    }
+                    writeln!(
+                        html,
+                        "          <span class=\"af-image-caption\">{}</span>",
+                        escape_html(&option_label(opt))
+                    )
+                    .unwrap();
+                    writeln!(html, "        </label>").unwrap();
+                }
+                writeln!(html, "      </div>").unwrap();
+            }
+            Some(ValueType::MultiSelect) => {
+                let selected = value.and_then(FieldValue::as_array).unwrap_or_default();
+                for opt in options {
+                    let checked = if selected.contains(&opt.value) {
+                        " checked"
+                    } else {
+                        ""
+                    };
+                    let opt_id = format!("{}_{}", field.name, opt.value);
+                    writeln!(
+                        html,
+                        "      <label><input type=\"checkbox\" name=\"{}[]\" id=\"{}\" value=\"{}\"{checked}{described}{disabled}{}> {}</label>",
                         field.name,
                         opt_id,
                         escape_html(&opt.value),
-                        escape_html(&opt.label)
+                        sold_out_attr(opt),
+                        escape_html(&option_label(opt))
                     )
                     .unwrap();
                 }
@@ -540,11 +1413,53 @@ impl HtmlRenderer {
                 let checked_attr = if checked { " checked" } else { "" };
                 writeln!(
                     html,
-                    "      <input type=\"checkbox\" name=\"{}\" id=\"{}\" value=\"1\"{input_class}{checked_attr}{disabled}>",
+                    "      <input type=\"checkbox\" name=\"{}\" id=\"{}\" value=\"1\"{input_class}{described}{checked_attr}{disabled}>",
                     field.name, field.name
                 )
                 .unwrap();
             }
+            Some(ValueType::Location) => {
+                // Coordinates typed in by hand, or filled in by the WASM
+                // client's button from the device's location
+                let (lat, lng, accuracy, place) = location_parts(value);
+                let name = &field.name;
+                writeln!(html, "      <div class=\"af-location\" data-af-location=\"{name}\">").unwrap();
+                writeln!(
+                    html,
+                    "        <button type=\"button\" class=\"af-location-button\" data-af-locate=\"{name}\" hidden{disabled}>Use my location</button>"
+                )
+                .unwrap();
+                writeln!(
+                    html,
+                    "        <label class=\"af-location-part\">Latitude <input type=\"number\" name=\"{name}[lat]\" id=\"{name}\" value=\"{}\" step=\"any\" min=\"-90\" max=\"90\"{input_class}{described}{required}{disabled}{readonly}></label>",
+                    escape_html(&lat)
+                )
+                .unwrap();
+                writeln!(
+                    html,
+                    "        <label class=\"af-location-part\">Longitude <input type=\"number\" name=\"{name}[lng]\" id=\"{name}-lng\" value=\"{}\" step=\"any\" min=\"-180\" max=\"180\"{input_class}{required}{disabled}{readonly}></label>",
+                    escape_html(&lng)
+                )
+                .unwrap();
+                writeln!(
+                    html,
+                    "        <input type=\"hidden\" name=\"{name}[accuracy]\" id=\"{name}-accuracy\" value=\"{}\">",
+                    escape_html(&accuracy)
+                )
+                .unwrap();
+                writeln!(
+                    html,
+                    "        <label class=\"af-location-part\">Place <input type=\"text\" name=\"{name}[label]\" id=\"{name}-label\" value=\"{}\"{input_class}{disabled}{readonly}{placeholder}></label>",
+                    escape_html(&place)
+                )
+                .unwrap();
+                writeln!(
+                    html,
+                    "        <span class=\"af-location-status\" id=\"{name}-status\" role=\"status\"></span>"
+                )
+                .unwrap();
+                writeln!(html, "      </div>").unwrap();
+            }
             Some(ValueType::Hidden) => {
                 writeln!(
                     html,
@@ -559,7 +1474,7 @@ impl HtmlRenderer {
                 let input_type = vt.html_input_type();
                 writeln!(
                     html,
-                    "      <input type=\"{input_type}\" name=\"{}\" id=\"{}\" value=\"{}\"{input_class}{required}{disabled}{readonly}{placeholder}>",
+                    "      <input type=\"{input_type}\" name=\"{}\" id=\"{}\" value=\"{}\"{input_class}{described}{required}{constraints}{disabled}{readonly}{placeholder}>",
                     field.name,
                     field.name,
                     escape_html(&current_value)
@@ -570,7 +1485,7 @@ impl HtmlRenderer {
                 // Default to text input
                 writeln!(
                     html,
-                    "      <input type=\"text\" name=\"{}\" id=\"{}\" value=\"{}\"{input_class}{required}{disabled}{readonly}{placeholder}>",
+                    "      <input type=\"text\" name=\"{}\" id=\"{}\" value=\"{}\"{input_class}{described}{required}{constraints}{disabled}{readonly}{placeholder}>",
                     field.name,
                     field.name,
                     escape_html(&current_value)
@@ -587,7 +1502,16 @@ impl HtmlRenderer {
                 .map(|c| format!(" class=\"{c}\""))
                 .unwrap_or_else(|| " class=\"help\"".to_string());
 
-            writeln!(html, "      <small{help_class}>{}</small>", escape_html(help)).unwrap();
+            // Help media describe the input along with the help text
+            let id = match &ui.help {
+                Some(_) => format!(" id=\"{}-help\"", field.name),
+                None => String::new(),
+            };
+            let help = piping::render_html(help, answers);
+            writeln!(html, "      <small{help_class}{id}>{help}</small>").unwrap();
+        }
+        if let Some(help) = &ui.help {
+            render_help_media(html, field, help);
         }
 
         // Error messages
@@ -607,9 +1531,104 @@ impl HtmlRenderer {
     }
 }
 
+/// Returns a location answer's latitude, longitude, accuracy and place name
+/// as input values; coordinates that didn't parse are given back as typed.
+fn location_parts(value: Option<&FieldValue>) -> (String, String, String, String) {
+    match value {
+        Some(FieldValue::Location(location)) => (
+            location.lat.to_string(),
+            location.lng.to_string(),
+            location.accuracy.map(|a| a.to_string()).unwrap_or_default(),
+            location.label.clone().unwrap_or_default(),
+        ),
+        Some(FieldValue::Text(raw)) => {
+            let (lat, lng) = raw.split_once(", ").unwrap_or((raw, ""));
+            (lat.to_string(), lng.to_string(), String::new(), String::new())
+        }
+        _ => Default::default(),
+    }
+}
+
+/// Returns the IDs of the elements describing a field with help media, for
+/// its input's `aria-describedby`.
+fn help_ids(field: &field::Model, help: &HelpMedia) -> String {
+    let mut ids = Vec::new();
+    if field.help_text.is_some() {
+        ids.push(format!("{}-help", field.name));
+    }
+    if help.tooltip.is_some() {
+        ids.push(format!("{}-tooltip", field.name));
+    }
+    if help.has_media() {
+        ids.push(format!("{}-media", field.name));
+    }
+    ids.join(" ")
+}
+
+/// Renders a field's tooltip, hidden behind a toggle button the WASM client
+/// opens; the button's `title` shows it without the client.
+fn render_tooltip(html: &mut String, field: &field::Model, help: &HelpMedia, answers: &Answers) {
+    let Some(tooltip) = &help.tooltip else {
+        return;
+    };
+    let tooltip = escape_html(tooltip);
+    writeln!(
+        html,
+        "      <button type=\"button\" class=\"af-tooltip-toggle\" aria-controls=\"{}-tooltip\" aria-expanded=\"false\" aria-label=\"Help: {}\" title=\"{tooltip}\">?</button>",
+        field.name,
+        escape_html(&piping::resolve(&field.label, answers))
+    )
+    .unwrap();
+    writeln!(
+        html,
+        "      <span class=\"af-tooltip\" id=\"{}-tooltip\" role=\"tooltip\" hidden>{tooltip}</span>",
+        field.name
+    )
+    .unwrap();
+}
+
+/// Renders a field's help image, as a figure, and help links.
+fn render_help_media(html: &mut String, field: &field::Model, help: &HelpMedia) {
+    if !help.has_media() {
+        return;
+    }
+    writeln!(html, "      <div class=\"af-help-media\" id=\"{}-media\">", field.name).unwrap();
+    if let Some(image) = &help.image {
+        writeln!(html, "        <figure class=\"af-help-image\">").unwrap();
+        writeln!(
+            html,
+            "          <img src=\"{}\" alt=\"{}\" loading=\"lazy\">",
+            escape_html(&image.url),
+            escape_html(&image.alt)
+        )
+        .unwrap();
+        if let Some(caption) = &image.caption {
+            writeln!(html, "          <figcaption>{}</figcaption>", escape_html(caption)).unwrap();
+        }
+        writeln!(html, "        </figure>").unwrap();
+    }
+    if !help.links.is_empty() {
+        writeln!(html, "        <ul class=\"af-help-links\">").unwrap();
+        for link in &help.links {
+            // New tab, so following a link keeps the form's answers
+            writeln!(
+                html,
+                "          <li><a href=\"{}\" target=\"_blank\" rel=\"noopener noreferrer\">{}</a></li>",
+                escape_html(&link.url),
+                escape_html(&link.label)
+            )
+            .unwrap();
+        }
+        writeln!(html, "        </ul>").unwrap();
+    }
+    writeln!(html, "      </div>").unwrap();
+}
+
 /// Renders a ConditionRule to JSON for data attribute.
 fn render_condition_json(condition: &ConditionRule) -> String {
-    serde_json::to_string(condition).unwrap_or_else(|_| "{}".to_string())
+    serde_json::to_value(condition)
+        .map(|v| escape_json_attr(&v))
+        .unwrap_or_else(|_| "{}".to_string())
 }
 
 /// Renders validation rules to JSON for data attribute.
@@ -636,15 +1655,214 @@ fn render_validation_json(required: bool, rules: &ValidationRules) -> String {
         obj.insert("max".to_string(), serde_json::json!(max));
     }
 
+    if let Some(step) = rules.step {
+        obj.insert("step".to_string(), serde_json::json!(step));
+    }
+
     if let Some(pattern) = &rules.pattern {
         obj.insert("pattern".to_string(), serde_json::json!(pattern));
     }
 
-    serde_json::to_string(&obj).unwrap_or_else(|_| "{}".to_string())
+    if let Some(min) = rules.min_rows {
+        obj.insert("minRows".to_string(), serde_json::json!(min));
+    }
+
+    if let Some(max) = rules.max_rows {
+        obj.insert("maxRows".to_string(), serde_json::json!(max));
+    }
+
+    if rules.is_server_checked() {
+        obj.insert("serverValidate".to_string(), serde_json::Value::Bool(true));
+    }
+
+    // Custom messages, e.g. `minLengthMessage`
+    for (rule, template) in rules.messages() {
+        let mut key = String::new();
+        for (i, word) in rule.split('_').enumerate() {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) if i > 0 => key.extend(first.to_uppercase().chain(chars)),
+                _ => key.push_str(word),
+            }
+        }
+        key.push_str("Message");
+        obj.insert(key, serde_json::json!(template));
+    }
+
+    escape_json_attr(&serde_json::Value::Object(obj))
+}
+
+/// Renders validation rules as HTML5 constraint attributes for an input of
+/// `value_type`, e.g. ` minlength="3" pattern="[0-9]+"`.
+///
+/// These mirror [`render_validation_json`], so browsers check the same rules
+/// natively: lengths and patterns on text-like inputs and textareas, bounds
+/// and steps on number inputs. `required` is left to the caller.
+pub(crate) fn validation_attrs(rules: &ValidationRules, value_type: Option<ValueType>) -> String {
+    let (text, number) = match value_type {
+        None
+        | Some(
+            ValueType::Text
+            | ValueType::Email
+            | ValueType::Url
+            | ValueType::Tel
+            | ValueType::Textarea,
+        ) => (true, false),
+        Some(vt) => (false, vt.html_input_type() == "number"),
+    };
+
+    let mut attrs = String::new();
+    if text {
+        if let Some(min) = rules.min_length {
+            write!(attrs, " minlength=\"{min}\"").unwrap();
+        }
+        if let Some(max) = rules.max_length {
+            write!(attrs, " maxlength=\"{max}\"").unwrap();
+        }
+        // Textareas take no pattern
+        let pattern = rules.pattern.as_deref().and_then(html_pattern);
+        if let Some(pattern) = pattern.filter(|_| value_type != Some(ValueType::Textarea)) {
+            write!(attrs, " pattern=\"{}\"", escape_html(&pattern)).unwrap();
+        }
+    }
+    if number {
+        if let Some(min) = rules.min {
+            write!(attrs, " min=\"{min}\"").unwrap();
+        }
+        if let Some(max) = rules.max {
+            write!(attrs, " max=\"{max}\"").unwrap();
+        }
+        if let Some(step) = rules.step {
+            write!(attrs, " step=\"{step}\"").unwrap();
+        }
+    }
+    attrs
+}
+
+/// Converts a validation regex to an HTML `pattern`, or `None` if it uses
+/// syntax browsers don't share.
+///
+/// Browsers match a pattern against the whole value, while validation finds
+/// it anywhere in the value, so an unanchored pattern is wrapped in `.*`.
+fn html_pattern(pattern: &str) -> Option<String> {
+    // Inline flags, named groups and `\A`/`\z` are Rust-only
+    if pattern.contains("(?P<")
+        || pattern.contains("\\A")
+        || pattern.contains("\\z")
+        || Regex::new(r"\(\?[a-zA-Z-]+[:)]").unwrap().is_match(pattern)
+    {
+        return None;
+    }
+    let anchored = pattern.starts_with('^') && pattern.ends_with('$') && !pattern.ends_with("\\$");
+    Some(if anchored {
+        pattern.to_string()
+    } else {
+        format!(".*(?:{pattern}).*")
+    })
+}
+
+/// Returns the ` dir="..." lang="..."` attributes for a form, or an empty
+/// string when neither a direction nor a locale is set.
+fn dir_attrs(settings: &FormSettings) -> String {
+    let mut attrs = String::new();
+    if settings.direction.is_some() || settings.locale.is_some() {
+        write!(attrs, " dir=\"{}\"", settings.direction_or_default().as_str()).unwrap();
+    }
+    if let Some(locale) = &settings.locale {
+        write!(attrs, " lang=\"{}\"", escape_html(locale)).unwrap();
+    }
+    attrs
+}
+
+/// Returns ` disabled` for submit buttons while submissions are unavailable.
+fn disabled_attr(options: &HtmlOptions) -> &'static str {
+    if options.unavailable {
+        " disabled"
+    } else {
+        ""
+    }
+}
+
+/// Renders the branding colors and the form's custom CSS.
+fn render_form_css(
+    html: &mut String,
+    form: &form::Model,
+    settings: &FormSettings,
+    branding: Option<&Branding>,
+    options: &HtmlOptions,
+) {
+    // Branding colors as CSS variables, before custom CSS so it can use them
+    if let Some(css) = branding.and_then(Branding::css_variables) {
+        writeln!(html, "<style{} data-af-branding>{css}</style>", options.nonce_attr()).unwrap();
+    }
+
+    // Per-form CSS, screened again in case it was stored without checks
+    if let Some(css) = settings.custom_css.as_deref().filter(|css| !css.is_empty()) {
+        match check_custom_css(css) {
+            Ok(()) => {
+                writeln!(html, "<style{} data-af-custom>", options.nonce_attr()).unwrap();
+                writeln!(html, "{css}").unwrap();
+                writeln!(html, "</style>").unwrap();
+            }
+            Err(e) => tracing::warn!(form = %form.slug, error = %e, "skipping custom CSS"),
+        }
+    }
+}
+
+/// Returns the form's branding, screened again in case it was stored without
+/// checks.
+fn checked_branding<'a>(form: &form::Model, settings: &'a FormSettings) -> Option<&'a Branding> {
+    let branding = settings.branding.as_ref()?;
+    match branding.check() {
+        Ok(()) => Some(branding),
+        Err(e) => {
+            tracing::warn!(form = %form.slug, error = %e, "skipping branding");
+            None
+        }
+    }
+}
+
+/// Renders the branding logo, if set.
+fn render_logo(html: &mut String, branding: &Branding) {
+    if let Some(url) = &branding.logo_url {
+        writeln!(
+            html,
+            "  <div class=\"af-logo\"><img src=\"{}\" alt=\"{}\"></div>",
+            escape_html(url),
+            escape_html(branding.logo_alt.as_deref().unwrap_or(""))
+        )
+        .unwrap();
+    }
+}
+
+/// Renders the branding footer text and links, if set.
+fn render_footer(html: &mut String, branding: &Branding) {
+    if !branding.has_footer() {
+        return;
+    }
+    writeln!(html, "  <footer class=\"af-footer\">").unwrap();
+    if let Some(text) = &branding.footer_text {
+        writeln!(html, "    <p>{}</p>", escape_html(text)).unwrap();
+    }
+    if !branding.footer_links.is_empty() {
+        let links: Vec<String> = branding
+            .footer_links
+            .iter()
+            .map(|link| {
+                format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(&link.url),
+                    escape_html(&link.label)
+                )
+            })
+            .collect();
+        writeln!(html, "    <nav>{}</nav>", links.join(" ")).unwrap();
+    }
+    writeln!(html, "  </footer>").unwrap();
 }
 
 /// Escapes HTML special characters.
-fn escape_html(s: &str) -> String {
+pub(crate) fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
@@ -652,6 +1870,24 @@ fn escape_html(s: &str) -> String {
         .replace('\'', "&#39;")
 }
 
+/// Returns ` disabled data-af-sold-out` for options without seats left.
+fn sold_out_attr(opt: &field_option::Model) -> &'static str {
+    if opt.is_sold_out() {
+        " disabled data-af-sold-out"
+    } else {
+        ""
+    }
+}
+
+/// Returns the display label, marking options without seats left.
+fn option_label(opt: &field_option::Model) -> String {
+    if opt.is_sold_out() {
+        format!("{} (sold out)", opt.label)
+    } else {
+        opt.label.clone()
+    }
+}
+
 /// Escapes a JSON value for use in an HTML attribute.
 fn escape_json_attr(value: &serde_json::Value) -> String {
     serde_json::to_string(value)
@@ -1,12 +1,32 @@
 //! JSON rendering for forms.
 
+use chrono::{DateTime, Utc};
 use sea_orm::DatabaseConnection;
 use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
 
-use crate::entities::{field, field_option, form, step};
+use crate::branching::{both, JumpRule};
+use crate::condition::ConditionRule;
+use crate::entities::form;
 use crate::error::FormError;
+use crate::repository::{load_steps_with_fields, FieldWithOptions, StepWithFields};
 use crate::schema::{FormSettings, UiOptions, ValidationRules};
 
+/// Version of the JSON contract produced by [`JsonRenderer`].
+///
+/// Bumped whenever the [`FormJson`] shape changes. Clients built for the same
+/// `major.minor` can consume it.
+pub const SCHEMA_VERSION: &str = "0.5";
+
+/// Returns whether a client version (e.g. `"0.5.1"`) understands [`SCHEMA_VERSION`].
+#[must_use]
+pub fn is_compatible_client_version(client_version: &str) -> bool {
+    let mut client = client_version.split('.');
+    let mut schema = SCHEMA_VERSION.split('.');
+    client.next() == schema.next() && client.next() == schema.next()
+}
+
 /// Renders forms to JSON format for SPAs and headless usage.
 pub struct JsonRenderer;
 
@@ -16,65 +36,111 @@ impl JsonRenderer {
         db: &DatabaseConnection,
         form: &form::Model,
     ) -> Result<FormJson, FormError> {
-        let steps = step::Entity::find_by_form(db, form.id).await?;
+        let steps = load_steps_with_fields(db, form.id).await?;
+        Ok(Self::render_steps(form, &steps))
+    }
 
+    /// Renders a form to JSON from its steps, fields and options as loaded by
+    /// a [`FormsRepository`](crate::repository::FormsRepository).
+    #[must_use]
+    pub fn render_steps(form: &form::Model, steps: &[StepWithFields]) -> FormJson {
         let mut step_jsons = Vec::new();
-        for step in &steps {
-            let fields = field::Entity::find_by_step(db, step.id).await?;
-
-            let mut field_jsons = Vec::new();
-            for field in &fields {
-                let options = if field.requires_options() {
-                    field_option::Entity::find_by_field(db, field.id).await?
-                } else {
-                    Vec::new()
-                };
-
-                field_jsons.push(FieldJson {
-                    id: field.id.to_string(),
-                    name: field.name.clone(),
-                    label: field.label.clone(),
-                    field_type: field.field_type.clone(),
-                    order: field.order,
-                    required: field.required,
-                    placeholder: field.placeholder.clone(),
-                    help_text: field.help_text.clone(),
-                    default_value: field.default_value.clone(),
-                    validation: field.validation(),
-                    ui_options: field.ui(),
-                    options: options
-                        .into_iter()
-                        .map(|o| FieldOptionJson {
-                            id: o.id.to_string(),
-                            label: o.label,
-                            value: o.value,
-                            order: o.order,
-                        })
-                        .collect(),
-                });
-            }
+        for (index, StepWithFields { step, fields }) in steps.iter().enumerate() {
+            // Jump targets are named; clients get the step's ID
+            let jumps = |rules: Vec<JumpRule>| -> Vec<JumpRuleJson> {
+                rules
+                    .into_iter()
+                    .filter_map(|rule| {
+                        let to = match &rule.to {
+                            None => None,
+                            Some(to) => Some(
+                                steps[index + 1..]
+                                    .iter()
+                                    .find(|s| &s.step.name == to)?
+                                    .step
+                                    .id
+                                    .to_string(),
+                            ),
+                        };
+                        Some(JumpRuleJson { when: rule.when, to })
+                    })
+                    .collect()
+            };
 
-            step_jsons.push(StepJson {
+            let field_jsons = fields
+                .iter()
+                .map(|field| {
+                    let mut json = Self::field_json(field);
+                    json.next = jumps(field.field.ui().next);
+                    json
+                })
+                .collect();
+
+            let mut step_json = StepJson {
                 id: step.id.to_string(),
                 name: step.name.clone(),
                 description: step.description.clone(),
                 order: step.order,
                 condition: step.condition_expr(),
+                next: jumps(step.jump_rules()),
+                updated_at: step.updated_at.with_timezone(&Utc),
+                etag: String::new(),
+                lazy: false,
                 fields: field_jsons,
-            });
+            };
+            step_json.etag = content_etag(&step_json);
+            step_jsons.push(step_json);
         }
 
         let settings = form.settings();
-        Ok(FormJson {
+        FormJson {
             id: form.id.to_string(),
             name: form.name.clone(),
             slug: form.slug.clone(),
             description: form.description.clone(),
             action_url: settings.action_url.clone(),
             action_method: settings.method.clone(),
+            closed: form.is_closed(),
             settings,
+            updated_at: form.updated_at.with_timezone(&Utc),
             steps: step_jsons,
-        })
+            unchanged: Vec::new(),
+        }
+    }
+
+    /// Renders a field with its options and sub-fields, leaving out its
+    /// jump rules.
+    fn field_json(FieldWithOptions { field, options, fields }: &FieldWithOptions) -> FieldJson {
+        let mut ui_options = field.ui();
+        ui_options.next.clear();
+        FieldJson {
+            id: field.id.to_string(),
+            name: field.name.clone(),
+            label: field.label.clone(),
+            field_type: field.field_type.clone(),
+            order: field.order,
+            required: field.required,
+            placeholder: field.placeholder.clone(),
+            help_text: field.help_text.clone(),
+            default_value: field.default_value.clone(),
+            validation: field.validation(),
+            ui_options,
+            next: Vec::new(),
+            options: options
+                .iter()
+                .map(|o| FieldOptionJson {
+                    id: o.id.to_string(),
+                    remaining: o.remaining(),
+                    capacity: o.capacity,
+                    label: o.label.clone(),
+                    value: o.value.clone(),
+                    order: o.order,
+                    group: o.group.clone(),
+                    image_url: o.image_url.clone(),
+                })
+                .collect(),
+            fields: fields.iter().map(Self::field_json).collect(),
+        }
     }
 
     /// Renders a form to a JSON string.
@@ -96,6 +162,36 @@ impl JsonRenderer {
     }
 }
 
+/// Fingerprints a step's content: its JSON without its own ID, the IDs
+/// generated for its fields and options (which change whenever the form is
+/// saved), seats left, or its change time.
+fn content_etag(step: &StepJson) -> String {
+    fn strip(fields: Option<&mut serde_json::Value>) {
+        for field in fields.and_then(|f| f.as_array_mut()).into_iter().flatten() {
+            let Some(field) = field.as_object_mut() else {
+                continue;
+            };
+            field.remove("id");
+            let options = field.get_mut("options").and_then(|o| o.as_array_mut());
+            for option in options.into_iter().flatten().filter_map(|o| o.as_object_mut()) {
+                option.remove("id");
+                option.remove("remaining");
+            }
+            strip(field.get_mut("fields"));
+        }
+    }
+
+    let mut value = serde_json::to_value(step).unwrap_or_default();
+    if let Some(step) = value.as_object_mut() {
+        step.remove("id");
+        step.remove("updated_at");
+        step.remove("etag");
+        strip(step.get_mut("fields"));
+    }
+    let digest = format!("{:x}", Sha256::digest(value.to_string().as_bytes()));
+    digest[..16].to_string()
+}
+
 /// JSON representation of a form.
 #[derive(Debug, Clone, Serialize)]
 pub struct FormJson {
@@ -110,8 +206,69 @@ pub struct FormJson {
     /// HTTP method for form submission (convenience field from settings).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub action_method: Option<String>,
+    /// Whether the form has stopped accepting responses; show
+    /// `settings.closed_message` instead of the form, or
+    /// `settings.not_open_message` before `settings.opens_at`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub closed: bool,
     pub settings: FormSettings,
+    /// When the form was last changed.
+    pub updated_at: DateTime<Utc>,
     pub steps: Vec<StepJson>,
+    /// IDs of the steps left out of `steps` by [`FormJson::changed_since`],
+    /// unchanged since then.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unchanged: Vec<String>,
+}
+
+impl FormJson {
+    /// Keeps only the steps changed after `since`, listing the others'
+    /// IDs in `unchanged`, for clients patching a schema they loaded then.
+    ///
+    /// Steps missing from both were removed; the form's own fields (name,
+    /// settings, ...) are always included.
+    #[must_use]
+    pub fn changed_since(mut self, since: DateTime<Utc>) -> Self {
+        let (changed, unchanged) = std::mem::take(&mut self.steps)
+            .into_iter()
+            .partition(|step| step.updated_at > since);
+        self.steps = changed;
+        self.unchanged = unchanged.into_iter().map(|step: StepJson| step.id).collect();
+        self
+    }
+
+    /// Returns the form's skeleton: its steps without their fields, marked
+    /// `lazy`, for clients that fetch each step's fields as they get to it.
+    ///
+    /// Fields' jump rules move to their step, ahead of its own and limited
+    /// to when the field is shown, so clients can still route around steps
+    /// they haven't loaded.
+    #[must_use]
+    pub fn skeleton(mut self) -> Self {
+        for step in &mut self.steps {
+            let mut next: Vec<JumpRuleJson> = std::mem::take(&mut step.fields)
+                .into_iter()
+                .flat_map(|field| {
+                    let shown = field.ui_options.condition;
+                    field.next.into_iter().map(move |mut rule| {
+                        rule.when = both(shown.clone(), rule.when);
+                        rule
+                    })
+                })
+                .collect();
+            next.append(&mut step.next);
+            step.next = next;
+            step.lazy = true;
+        }
+        self
+    }
+
+    /// Returns the step with ID `id`, with its fields.
+    #[must_use]
+    pub fn into_step(self, id: Uuid) -> Option<StepJson> {
+        let id = id.to_string();
+        self.steps.into_iter().find(|step| step.id == id)
+    }
 }
 
 /// JSON representation of a form step.
@@ -124,6 +281,17 @@ pub struct StepJson {
     pub order: i32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<String>,
+    /// The step's own jump rules, tried after its fields'.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub next: Vec<JumpRuleJson>,
+    /// When the step's content last changed.
+    pub updated_at: DateTime<Utc>,
+    /// Fingerprint of the step's content, changing whenever the step does.
+    pub etag: String,
+    /// Whether `fields` were left out of a [`FormJson::skeleton`]; fetch
+    /// them with `?step={id}`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub lazy: bool,
     pub fields: Vec<FieldJson>,
 }
 
@@ -146,8 +314,25 @@ pub struct FieldJson {
     pub validation: ValidationRules,
     #[serde(skip_serializing_if = "is_default_ui")]
     pub ui_options: UiOptions,
+    /// Jump rules tried once the field's step is done, while it's shown.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub next: Vec<JumpRuleJson>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub options: Vec<FieldOptionJson>,
+    /// Sub-fields of a repeatable group, answered once per row.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldJson>,
+}
+
+/// JSON representation of a jump rule; see [`crate::branching`].
+#[derive(Debug, Clone, Serialize)]
+pub struct JumpRuleJson {
+    /// When the rule applies; always, if absent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<ConditionRule>,
+    /// ID of the step to go to; absent to end the form.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
 }
 
 /// JSON representation of a field option.
@@ -157,6 +342,18 @@ pub struct FieldOptionJson {
     pub label: String,
     pub value: String,
     pub order: i32,
+    /// Maximum number of submissions that may choose this option.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+    /// Seats left at render time; `0` means sold out.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<i32>,
+    /// Heading the option is listed under.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Picture shown on the option's card, for `image_choice` fields.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
 }
 
 fn is_default_ui(ui: &UiOptions) -> bool {
@@ -165,6 +362,9 @@ fn is_default_ui(ui: &UiOptions) -> bool {
         && ui.label_class.is_none()
         && ui.width.is_none()
         && ui.rows.is_none()
+        && ui.add_label.is_none()
+        && ui.remove_label.is_none()
+        && ui.help.is_none()
         && !ui.autofocus
         && !ui.disabled
         && !ui.readonly
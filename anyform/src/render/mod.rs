@@ -1,13 +1,17 @@
 //! Form rendering to different output formats.
 
+mod config_js;
+pub mod csp;
 mod json;
 mod html;
 
 #[cfg(feature = "tera")]
 mod tera_render;
 
-pub use html::{HtmlOptions, HtmlRenderer};
-pub use json::{FormJson, JsonRenderer};
+pub use config_js::ConfigScript;
+pub(crate) use html::escape_html;
+pub use html::{HtmlOptions, HtmlRenderer, MULTI_STEP_CSS, PRINT_CSS, ROW_INDEX};
+pub use json::{is_compatible_client_version, FormJson, JsonRenderer, StepJson, SCHEMA_VERSION};
 
 #[cfg(feature = "tera")]
 pub use tera_render::TeraRenderer;
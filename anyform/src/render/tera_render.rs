@@ -1,14 +1,54 @@
 //! Tera template context builder for forms.
+//!
+//! [`TeraRenderer::context`] gives a template the `form`, its `values` and
+//! `errors`. [`TeraRenderer::register_functions`] adds functions for custom
+//! layouts, which work on that context without querying the database:
+//!
+//! - `field(form=form, name="email")`: the field named `email`, searched
+//!   through every step and repeatable group
+//! - `options_of(form=form, name="country")`: the options of that field
+//! - `is_visible(field=field, values=values)`: whether a field's condition
+//!   holds for the values; also takes `step=step`
+//! - `t(key="next", locale=locale)`: the text for `key` from the router's
+//!   [`MessageCatalog`], filling in `label=` and any other arguments, or the
+//!   key itself when the catalog has no text for it
+//!
+//! ```
+//! use anyform::i18n::{MessageCatalog, Messages};
+//! use anyform::TeraRenderer;
+//!
+//! let mut tera = tera::Tera::default();
+//! TeraRenderer::register_functions(
+//!     &mut tera,
+//!     MessageCatalog::new().locale("fr", Messages::new().set("next", "Suivant")),
+//! );
+//! tera.add_raw_template(
+//!     "country.html",
+//!     r#"{% for o in options_of(form=form, name="country") %}{{ o.label }} {% endfor %}"#,
+//! )
+//! .unwrap();
+//! ```
+//!
+//! The example templates in `tests/fixtures/tera` are rendered by the tests
+//! next to their output.
 
 use sea_orm::DatabaseConnection;
 use serde::Serialize;
+use serde_json::Value;
 use std::collections::HashMap;
-use tera::Context;
+use std::fmt;
+use tera::{Context, Tera};
 
-use crate::entities::{field, field_option, form, step};
+use crate::condition::ConditionRule;
+use crate::entities::form;
 use crate::error::{FormError, ValidationErrors};
+use crate::i18n::{interpolate, localize_form, localize_steps, MessageCatalog};
+use crate::piping::{self, Answers};
+use crate::repository::{load_steps_with_fields, FieldWithOptions, StepWithFields};
 use crate::schema::{FieldValue, FormSettings, UiOptions, ValidationRules};
 
+use super::html::validation_attrs;
+
 /// Builds Tera template contexts for forms.
 pub struct TeraRenderer;
 
@@ -28,73 +68,75 @@ impl TeraRenderer {
         values: &HashMap<String, FieldValue>,
         errors: &ValidationErrors,
     ) -> Result<Context, FormError> {
-        let form_data = Self::build_form_data(db, form).await?;
+        Self::context_with_locale(db, form, values, errors, None).await
+    }
+
+    /// Builds a Tera context like [`context_with_values`](Self::context_with_values),
+    /// with the form translated into `locale`, which is also given to the
+    /// template as `locale`.
+    pub async fn context_with_locale(
+        db: &DatabaseConnection,
+        form: &form::Model,
+        values: &HashMap<String, FieldValue>,
+        errors: &ValidationErrors,
+        locale: Option<&str>,
+    ) -> Result<Context, FormError> {
+        let form_data = Self::build_form_data(db, form, values, locale).await?;
 
         let mut ctx = Context::new();
         ctx.insert("form", &form_data);
         ctx.insert("values", &values);
         ctx.insert("errors", &errors.errors);
+        ctx.insert("locale", &locale);
 
         Ok(ctx)
     }
 
-    /// Builds the form data structure for templates.
+    /// Registers the `field`, `options_of`, `is_visible` and `t` template
+    /// functions on `tera`, with `t` taking its text from `catalog`.
+    pub fn register_functions(tera: &mut Tera, catalog: MessageCatalog) {
+        tera.register_function("field", |args: &HashMap<String, Value>| {
+            find_field(args).cloned()
+        });
+        tera.register_function("options_of", |args: &HashMap<String, Value>| {
+            Ok(find_field(args)?["options"].clone())
+        });
+        tera.register_function("is_visible", is_visible);
+        tera.register_function("t", move |args: &HashMap<String, Value>| {
+            translate(&catalog, args)
+        });
+    }
+
+    /// Builds the form data structure for templates, with the answers in
+    /// `values` piped into labels, descriptions and help text.
     async fn build_form_data(
         db: &DatabaseConnection,
         form: &form::Model,
+        values: &HashMap<String, FieldValue>,
+        locale: Option<&str>,
     ) -> Result<FormData, FormError> {
-        let steps = step::Entity::find_by_form(db, form.id).await?;
+        let mut steps = load_steps_with_fields(db, form.id).await?;
+        localize_steps(form, &mut steps, locale);
+        let form = &localize_form(form, locale);
+        let answers = Answers::new(&steps, values);
 
         let mut step_data = Vec::new();
         let mut needs_multipart = false;
 
-        for step in &steps {
-            let fields = field::Entity::find_by_step(db, step.id).await?;
-
+        for StepWithFields { step, fields } in steps {
             let mut field_data = Vec::new();
-            for f in &fields {
-                let options = if f.requires_options() {
-                    field_option::Entity::find_by_field(db, f.id)
-                        .await?
-                        .into_iter()
-                        .map(|o| OptionData {
-                            id: o.id.to_string(),
-                            label: o.label,
-                            value: o.value,
-                            order: o.order,
-                            is_correct: o.is_correct,
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
-                };
-
-                if let Some(vt) = f.value_type() {
-                    if vt.is_file_type() {
-                        needs_multipart = true;
-                    }
-                }
-
-                field_data.push(FieldData {
-                    id: f.id.to_string(),
-                    name: f.name.clone(),
-                    label: f.label.clone(),
-                    field_type: f.field_type.clone(),
-                    order: f.order,
-                    required: f.required,
-                    placeholder: f.placeholder.clone(),
-                    help_text: f.help_text.clone(),
-                    default_value: f.default_value.clone(),
-                    validation: f.validation(),
-                    ui_options: f.ui(),
-                    options,
-                });
+            for field in fields {
+                needs_multipart |= field
+                    .field
+                    .value_type()
+                    .is_some_and(|vt| vt.is_file_type());
+                field_data.push(Self::field_data(field, &answers));
             }
 
             step_data.push(StepData {
                 id: step.id.to_string(),
                 name: step.name.clone(),
-                description: step.description.clone(),
+                description: step.description.as_deref().map(|d| piping::resolve(d, &answers)),
                 order: step.order,
                 condition: step.condition_expr(),
                 fields: field_data,
@@ -111,6 +153,48 @@ impl TeraRenderer {
             needs_multipart,
         })
     }
+
+    /// Builds the template data of a field with its options and sub-fields.
+    fn field_data(
+        FieldWithOptions { field: f, options, fields }: FieldWithOptions,
+        answers: &Answers,
+    ) -> FieldData {
+        let options = options
+            .into_iter()
+            .map(|o| OptionData {
+                id: o.id.to_string(),
+                sold_out: o.is_sold_out(),
+                remaining: o.remaining(),
+                label: o.label,
+                value: o.value,
+                order: o.order,
+                is_correct: o.is_correct,
+            })
+            .collect();
+
+        let validation = f.validation();
+        let mut html_attrs = validation_attrs(&validation, f.value_type());
+        if f.required {
+            html_attrs.insert_str(0, " required");
+        }
+
+        FieldData {
+            id: f.id.to_string(),
+            name: f.name.clone(),
+            label: piping::resolve(&f.label, answers),
+            field_type: f.field_type.clone(),
+            order: f.order,
+            required: f.required,
+            placeholder: f.placeholder.clone(),
+            help_text: f.help_text.as_deref().map(|h| piping::resolve(h, answers)),
+            default_value: f.default_value.clone(),
+            validation,
+            html_attrs,
+            ui_options: f.ui(),
+            options,
+            fields: fields.into_iter().map(|sub| Self::field_data(sub, answers)).collect(),
+        }
+    }
 }
 
 /// Form data for Tera templates.
@@ -149,8 +233,13 @@ pub struct FieldData {
     pub help_text: Option<String>,
     pub default_value: Option<String>,
     pub validation: ValidationRules,
+    /// The validation rules as HTML5 attributes for the field's input, e.g.
+    /// ` required minlength="3"`; write them with `{{ field.html_attrs | safe }}`.
+    pub html_attrs: String,
     pub ui_options: UiOptions,
     pub options: Vec<OptionData>,
+    /// Sub-fields of a repeatable group, answered once per row.
+    pub fields: Vec<FieldData>,
 }
 
 /// Option data for Tera templates.
@@ -161,4 +250,97 @@ pub struct OptionData {
     pub value: String,
     pub order: i32,
     pub is_correct: bool,
+    /// Seats left on a capacity-limited option.
+    pub remaining: Option<i32>,
+    /// Whether a capacity-limited option has no seats left.
+    pub sold_out: bool,
+}
+
+/// Returns the string argument `name` of a template function.
+fn str_arg<'a>(args: &'a HashMap<String, Value>, name: &str) -> tera::Result<&'a str> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| tera::Error::msg(format!("missing string argument `{name}`")))
+}
+
+/// Finds the field named by the `name` argument in the `form` argument.
+fn find_field(args: &HashMap<String, Value>) -> tera::Result<&Value> {
+    fn search<'a>(fields: &'a Value, name: &str) -> Option<&'a Value> {
+        fields.as_array()?.iter().find_map(|field| {
+            if field["name"] == name {
+                Some(field)
+            } else {
+                search(&field["fields"], name)
+            }
+        })
+    }
+
+    let form = args
+        .get("form")
+        .ok_or_else(|| tera::Error::msg("missing argument `form`"))?;
+    let name = str_arg(args, "name")?;
+    form["steps"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find_map(|step| search(&step["fields"], name))
+        .ok_or_else(|| tera::Error::msg(format!("no field named `{name}`")))
+}
+
+/// The `is_visible` template function.
+fn is_visible(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let condition = match (args.get("field"), args.get("step")) {
+        (Some(field), _) => field["ui_options"]["condition"].clone(),
+        // Steps keep their condition as JSON text
+        (None, Some(step)) => match &step["condition"] {
+            Value::String(text) => serde_json::from_str(text).unwrap_or(Value::Null),
+            other => other.clone(),
+        },
+        (None, None) => return Err(tera::Error::msg("missing argument `field` or `step`")),
+    };
+    if condition.is_null() {
+        return Ok(Value::Bool(true));
+    }
+
+    let rule: ConditionRule = serde_json::from_value(condition)
+        .map_err(|e| tera::Error::msg(format!("invalid condition: {e}")))?;
+    let values = match args.get("values") {
+        Some(Value::Object(values)) => values.clone().into_iter().collect(),
+        _ => HashMap::new(),
+    };
+    Ok(Value::Bool(rule.evaluate(&values)))
+}
+
+/// The `t` template function.
+fn translate(catalog: &MessageCatalog, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    /// A template argument, written without quotes.
+    struct Arg<'a>(&'a Value);
+
+    impl fmt::Display for Arg<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self.0 {
+                Value::String(s) => f.write_str(s),
+                other => write!(f, "{other}"),
+            }
+        }
+    }
+
+    let key = str_arg(args, "key")?;
+    let locale = args.get("locale").and_then(Value::as_str);
+    let label = args.get("label").and_then(Value::as_str).unwrap_or_default();
+    let template = match catalog.messages(locale).template(key) {
+        "" => key,
+        template => template,
+    };
+
+    let named: Vec<(&str, Arg)> = args
+        .iter()
+        .filter(|(name, _)| !matches!(name.as_str(), "key" | "locale" | "label"))
+        .map(|(name, value)| (name.as_str(), Arg(value)))
+        .collect();
+    let named: Vec<(&str, &dyn fmt::Display)> = named
+        .iter()
+        .map(|(name, value)| (*name, value as &dyn fmt::Display))
+        .collect();
+    Ok(Value::String(interpolate(template, label, &named)))
 }
@@ -0,0 +1,965 @@
+//! Storage behind the router's routes.
+//!
+//! Handlers read and write forms and submissions through a
+//! [`FormsRepository`] rather than querying entities themselves, and store
+//! submissions in a [`FormsTransaction`] so the form they are validated
+//! against can't change before they are saved. The router uses
+//! [`SeaOrmRepository`] over its database connection unless another is
+//! registered with
+//! [`AnyFormRouterBuilder::repository`](crate::AnyFormRouterBuilder::repository),
+//! so a repository can cache form definitions, serve them from elsewhere, or
+//! stand in for the database in tests.
+//!
+//! ```rust,ignore
+//! use anyform::repository::{FormsRepository, SeaOrmRepository, StepWithFields};
+//!
+//! struct Cached {
+//!     inner: SeaOrmRepository,
+//!     steps: Mutex<HashMap<Uuid, Vec<StepWithFields>>>,
+//! }
+//!
+//! #[async_trait::async_trait]
+//! impl FormsRepository for Cached {
+//!     async fn load_steps_with_fields(&self, id: Uuid) -> Result<Vec<StepWithFields>, FormError> {
+//!         if let Some(steps) = self.steps.lock().unwrap().get(&id) {
+//!             return Ok(steps.clone());
+//!         }
+//!         let steps = self.inner.load_steps_with_fields(id).await?;
+//!         self.steps.lock().unwrap().insert(id, steps.clone());
+//!         Ok(steps)
+//!     }
+//!     // ...the other methods delegate to `inner`
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset};
+use futures::{StreamExt, TryStreamExt};
+use sea_orm::{
+    ConnectionTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::duplicates::DuplicateGuard;
+use crate::encryption::EncryptionKey;
+use crate::entities::form::FormStatus;
+use crate::entities::submission::{SubmissionMetadata, SubmissionStatus};
+use crate::entities::{
+    dead_letter, field, field_option, form, form_instance, result, step, submission,
+    submission_note,
+};
+use crate::error::FormError;
+use crate::events::FormEvents;
+use crate::fragments;
+use crate::schema::{FieldValue, NotificationDigest};
+#[cfg(feature = "search")]
+use crate::services::{SearchPage, SearchService};
+use crate::services::{
+    choice_fields, form_fields, release_response, release_seats, Analytics, ChangeBatch,
+    CreateFormInput, Digest, DigestService, FormAnalytics, FormBuilder, FormSchedule,
+    FormTransition, ImportMode, InstallationStats, InstanceService, InstanceStats, LegalHold,
+    LifecycleService, OptionDiff, OptionImport, OptionRow, ResultsEngine, ScheduleService, Score,
+    ScoringEngine, Stats, SubmissionChanges, SubmissionPage, SubmissionQuery, SubmissionSchema,
+    SubmissionService,
+};
+
+/// A step with its fields, in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepWithFields {
+    pub step: step::Model,
+    pub fields: Vec<FieldWithOptions>,
+}
+
+/// A field with its options, in order; fields that take no options have
+/// none. Repeatable groups also have their sub-fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldWithOptions {
+    pub field: field::Model,
+    pub options: Vec<field_option::Model>,
+    pub fields: Vec<FieldWithOptions>,
+}
+
+impl StepWithFields {
+    /// Returns the fields of every step, in order.
+    #[must_use]
+    pub fn all_fields(steps: &[Self]) -> Vec<field::Model> {
+        steps
+            .iter()
+            .flat_map(|s| s.fields.iter().map(|f| f.field.clone()))
+            .collect()
+    }
+
+    /// Returns the sub-fields of every repeatable group, in order.
+    #[must_use]
+    pub fn all_sub_fields(steps: &[Self]) -> Vec<field::Model> {
+        steps
+            .iter()
+            .flat_map(|s| &s.fields)
+            .flat_map(|f| f.fields.iter().map(|sub| sub.field.clone()))
+            .collect()
+    }
+}
+
+/// Reads and writes forms and their submissions for the router's handlers.
+///
+/// Writes that must happen together, such as validating a submission
+/// against the form and storing it, go through a [`FormsTransaction`]
+/// started with [`begin`](Self::begin).
+#[async_trait]
+pub trait FormsRepository: Send + Sync + 'static {
+    /// Finds a form that isn't deleted by its slug.
+    async fn find_form_by_slug(&self, slug: &str) -> Result<Option<form::Model>, FormError>;
+
+    /// Finds a form by ID, deleted or not.
+    async fn find_form(&self, id: Uuid) -> Result<Option<form::Model>, FormError>;
+
+    /// Loads a form's steps with their fields and options.
+    async fn load_steps_with_fields(&self, form_id: Uuid)
+        -> Result<Vec<StepWithFields>, FormError>;
+
+    /// Starts a unit of work; see [`FormsTransaction`].
+    async fn begin(&self) -> Result<Box<dyn FormsTransaction>, FormError>;
+
+    /// Lists the forms that aren't deleted, newest first.
+    async fn list_forms(&self) -> Result<Vec<form::Model>, FormError>;
+
+    /// Creates a form; see [`FormBuilder::create`].
+    async fn create_form(&self, input: CreateFormInput) -> Result<form::Model, FormError>;
+
+    /// Replaces a form's definition; see [`FormBuilder::update`].
+    async fn update_form(&self, id: Uuid, input: CreateFormInput)
+        -> Result<form::Model, FormError>;
+
+    /// Copies a form as a draft; see [`FormBuilder::duplicate`].
+    async fn duplicate_form(&self, id: Uuid) -> Result<form::Model, FormError>;
+
+    /// Returns the free `-copy` slug a copy of the form `slug` would get.
+    async fn copy_slug(&self, slug: &str) -> Result<String, FormError>;
+
+    /// Publishes, unpublishes or archives a form that isn't deleted.
+    async fn set_form_status(&self, id: Uuid, status: FormStatus)
+        -> Result<form::Model, FormError>;
+
+    /// Soft-deletes a form; see [`FormBuilder::soft_delete`].
+    async fn delete_form(&self, id: Uuid) -> Result<(), FormError>;
+
+    /// Allows or stops allowing a browser past a form's duplicate guard.
+    async fn set_browser_allowed(
+        &self,
+        form_id: Uuid,
+        browser_id: Uuid,
+        allowed: bool,
+    ) -> Result<DuplicateGuard, FormError>;
+
+    /// Subscribes or unsubscribes a recipient of a form's digest.
+    async fn set_digest_subscription(
+        &self,
+        form: form::Model,
+        email: &str,
+        subscribed: bool,
+    ) -> Result<NotificationDigest, FormError>;
+
+    /// Replaces or merges a field's options; see [`OptionImport::apply`].
+    async fn import_options(
+        &self,
+        form: &form::Model,
+        field_name: &str,
+        rows: Vec<OptionRow>,
+        mode: ImportMode,
+        dry_run: bool,
+    ) -> Result<OptionDiff, FormError>;
+
+    /// Finds one of a form's results that isn't deleted by its key.
+    async fn find_result(&self, form_id: Uuid, key: &str)
+        -> Result<Option<result::Model>, FormError>;
+
+    /// Returns the instance of a recurring form open now, creating it if
+    /// needed.
+    async fn current_instance(
+        &self,
+        form: &form::Model,
+    ) -> Result<Option<form_instance::Model>, FormError>;
+
+    /// Announces a change in whether a form takes submissions at `at`; see
+    /// [`LifecycleService::announce`].
+    async fn announce_lifecycle(
+        &self,
+        events: &dyn FormEvents,
+        form_id: Uuid,
+        at: DateTime<FixedOffset>,
+    ) -> Result<Option<FormTransition>, FormError>;
+
+    /// Finds a submission by ID, deleted or not.
+    async fn find_submission(&self, id: Uuid) -> Result<Option<submission::Model>, FormError>;
+
+    /// Returns true if `respondent_id` already responded to a form.
+    async fn has_responded(&self, form_id: Uuid, respondent_id: &str) -> Result<bool, FormError>;
+
+    /// Starts a submission to be filled in step by step.
+    async fn start_submission(
+        &self,
+        form: &form::Model,
+        metadata: Option<SubmissionMetadata>,
+    ) -> Result<submission::Model, FormError>;
+
+    /// Deletes an in-progress submission of a form, returning true if there
+    /// was one.
+    async fn discard_draft(&self, form_id: Uuid, id: Uuid) -> Result<bool, FormError>;
+
+    /// Promotes a waitlisted submission; see [`SubmissionService::promote`].
+    async fn promote_submission(
+        &self,
+        form: &form::Model,
+        sub: submission::Model,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError>;
+
+    /// Soft-deletes a submission, giving back the seats and quota it held.
+    /// Its answers are decrypted with `key` to find the seats.
+    async fn delete_submission(
+        &self,
+        sub: &submission::Model,
+        key: Option<&EncryptionKey>,
+    ) -> Result<(), FormError>;
+
+    /// Places or lifts the legal hold on a submission.
+    async fn set_legal_hold(
+        &self,
+        sub: submission::Model,
+        hold: bool,
+    ) -> Result<submission::Model, FormError>;
+
+    /// Fetches a page of a submission listing.
+    async fn list_submissions(&self, query: &SubmissionQuery)
+        -> Result<SubmissionPage, FormError>;
+
+    /// Fetches a page of the submissions matching `query` whose text answers
+    /// contain the words of `text`; see [`SearchService::search`].
+    ///
+    /// Repositories that can't search fail with [`FormError::InvalidData`].
+    #[cfg(feature = "search")]
+    async fn search_submissions(
+        &self,
+        query: &SubmissionQuery,
+        text: &str,
+    ) -> Result<SearchPage, FormError> {
+        let _ = (query, text);
+        Err(FormError::InvalidData("Search isn't supported".to_string()))
+    }
+
+    /// Fetches a batch of a form's submission changes made by `now`; see
+    /// [`SubmissionChanges::fetch`].
+    async fn submission_changes(
+        &self,
+        changes: &SubmissionChanges,
+        now: DateTime<FixedOffset>,
+    ) -> Result<ChangeBatch, FormError>;
+
+    /// Lists the submissions an export of a form holds, newest first: the
+    /// completed ones and, with `include_partials`, partial responses.
+    async fn exported_submissions(
+        &self,
+        form_id: Uuid,
+        include_partials: bool,
+    ) -> Result<Vec<submission::Model>, FormError>;
+
+    /// Builds the export schema for a form and a set of its submissions.
+    async fn submission_schema(
+        &self,
+        form_id: Uuid,
+        subs: &[submission::Model],
+    ) -> Result<SubmissionSchema, FormError>;
+
+    /// Loads a form's choice fields, those that take options and
+    /// checkboxes, with their options, for labelling answers in exports.
+    async fn choice_fields(&self, form_id: Uuid) -> Result<Vec<FieldWithOptions>, FormError>;
+
+    /// Lists a form's preview submissions.
+    async fn preview_submissions(&self, form_id: Uuid)
+        -> Result<Vec<submission::Model>, FormError>;
+
+    /// Deletes a form's preview submissions that aren't under legal hold,
+    /// returning how many.
+    async fn delete_previews(&self, form_id: Uuid) -> Result<u64, FormError>;
+
+    /// Returns the labels of each of the given submissions that has any.
+    async fn submission_labels(&self, ids: &[Uuid])
+        -> Result<HashMap<Uuid, Vec<String>>, FormError>;
+
+    /// Lists the notes on a submission, oldest first.
+    async fn submission_notes(&self, id: Uuid)
+        -> Result<Vec<submission_note::Model>, FormError>;
+
+    /// Adds a note to a submission.
+    async fn add_submission_note(
+        &self,
+        id: Uuid,
+        body: Option<String>,
+        labels: &[String],
+        author: Option<String>,
+    ) -> Result<submission_note::Model, FormError>;
+
+    /// Records a submission that failed to persist as a dead letter.
+    async fn record_dead_letter(
+        &self,
+        form_id: Uuid,
+        data: &HashMap<String, FieldValue>,
+        metadata: Option<&SubmissionMetadata>,
+        error: &str,
+    ) -> Result<dead_letter::Model, FormError>;
+
+    /// Finds a dead letter by ID.
+    async fn find_dead_letter(&self, id: Uuid) -> Result<Option<dead_letter::Model>, FormError>;
+
+    /// Lists the dead letters awaiting replay, oldest first, of one form
+    /// when `form_id` is given.
+    async fn pending_dead_letters(
+        &self,
+        form_id: Option<Uuid>,
+    ) -> Result<Vec<dead_letter::Model>, FormError>;
+
+    /// Counts the dead letters awaiting replay, across all forms.
+    async fn count_pending_dead_letters(&self) -> Result<u64, FormError>;
+
+    /// Records a failed replay of a dead letter.
+    async fn mark_dead_letter_failed(
+        &self,
+        letter: dead_letter::Model,
+        error: &str,
+    ) -> Result<dead_letter::Model, FormError>;
+
+    /// Marks a dead letter as replayed into `submission_id`.
+    async fn mark_dead_letter_replayed(
+        &self,
+        letter: dead_letter::Model,
+        submission_id: Uuid,
+    ) -> Result<dead_letter::Model, FormError>;
+
+    /// Aggregates a form's submission analytics.
+    async fn form_analytics(&self, form: &form::Model) -> Result<FormAnalytics, FormError>;
+
+    /// Compares submissions across a recurring form's instances.
+    async fn instance_stats(&self, form: &form::Model) -> Result<Vec<InstanceStats>, FormError>;
+
+    /// Totals submissions across the installation.
+    async fn installation_stats(&self) -> Result<InstallationStats, FormError>;
+
+    /// Previews a form's schedule at `at`, with up to `upcoming` instances.
+    async fn form_schedule(
+        &self,
+        form: &form::Model,
+        at: DateTime<FixedOffset>,
+        upcoming: usize,
+    ) -> Result<FormSchedule, FormError>;
+
+    /// Builds the digest of a form's last whole period before `now`.
+    async fn latest_digest(
+        &self,
+        form: &form::Model,
+        now: DateTime<FixedOffset>,
+    ) -> Result<Digest, FormError>;
+
+    /// Scores answers to a quiz; see [`ScoringEngine::score`].
+    async fn score(
+        &self,
+        form: &form::Model,
+        fields: &[field::Model],
+        data: &HashMap<String, FieldValue>,
+    ) -> Result<Option<Score>, FormError>;
+
+    /// Finds the result answers map to; see [`ResultsEngine::resolve`].
+    async fn resolve_result(
+        &self,
+        form_id: Uuid,
+        score: Option<&Score>,
+        data: &HashMap<String, FieldValue>,
+    ) -> Result<Option<result::Model>, FormError>;
+}
+
+impl std::fmt::Debug for dyn FormsRepository {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FormsRepository")
+    }
+}
+
+/// A unit of work over a [`FormsRepository`]: what it reads sees its own
+/// writes, and its writes are kept only once [committed](Self::commit).
+/// Dropped without committing, it is rolled back.
+#[async_trait]
+pub trait FormsTransaction: Send + Sync {
+    /// Finds a form by ID, deleted or not.
+    async fn find_form(&self, id: Uuid) -> Result<Option<form::Model>, FormError>;
+
+    /// Loads a form's steps with their fields and options.
+    async fn load_steps_with_fields(&self, form_id: Uuid)
+        -> Result<Vec<StepWithFields>, FormError>;
+
+    /// Finds a submission by ID, deleted or not.
+    async fn find_submission(&self, id: Uuid) -> Result<Option<submission::Model>, FormError>;
+
+    /// Stores a validated, completed submission to `form`.
+    ///
+    /// Implementations must enforce option capacity and response quotas,
+    /// and score the submission, as [`SubmissionService::create`] does.
+    /// `data` holds the answers as submitted; with a `key`, the answers to
+    /// sensitive fields must be encrypted as they are stored.
+    async fn insert_submission(
+        &self,
+        form: &form::Model,
+        fields: &[field::Model],
+        data: &HashMap<String, FieldValue>,
+        metadata: Option<SubmissionMetadata>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError>;
+
+    /// Stores a validated submission made through a preview link; see
+    /// [`SubmissionService::create_preview`].
+    async fn insert_preview(
+        &self,
+        form: &form::Model,
+        data: &HashMap<String, FieldValue>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError>;
+
+    /// Saves the validated answers to one step of an in-progress
+    /// submission; see [`SubmissionService::save_step`].
+    async fn save_step(
+        &self,
+        sub: submission::Model,
+        step_id: Uuid,
+        data: &HashMap<String, FieldValue>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError>;
+
+    /// Completes a validated in-progress submission; see
+    /// [`SubmissionService::complete`].
+    async fn complete_submission(
+        &self,
+        form: &form::Model,
+        fields: &[field::Model],
+        sub: submission::Model,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError>;
+
+    /// Deletes a submission for good, as for answers outside a form's
+    /// sample.
+    async fn discard_submission(&self, id: Uuid) -> Result<(), FormError>;
+
+    /// Keeps the unit's writes.
+    async fn commit(self: Box<Self>) -> Result<(), FormError>;
+}
+
+/// The [`FormsRepository`] over a SeaORM connection.
+#[derive(Debug, Clone)]
+pub struct SeaOrmRepository {
+    db: DatabaseConnection,
+}
+
+impl SeaOrmRepository {
+    /// Creates a repository over `db`.
+    #[must_use]
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl FormsRepository for SeaOrmRepository {
+    async fn find_form_by_slug(&self, slug: &str) -> Result<Option<form::Model>, FormError> {
+        Ok(form::Entity::find_by_slug(&self.db, slug).await?)
+    }
+
+    async fn find_form(&self, id: Uuid) -> Result<Option<form::Model>, FormError> {
+        Ok(form::Entity::find_by_id(id).one(&self.db).await?)
+    }
+
+    async fn load_steps_with_fields(
+        &self,
+        form_id: Uuid,
+    ) -> Result<Vec<StepWithFields>, FormError> {
+        load_steps_with_fields(&self.db, form_id).await
+    }
+
+    async fn begin(&self) -> Result<Box<dyn FormsTransaction>, FormError> {
+        Ok(Box::new(SeaOrmTransaction { txn: self.db.begin().await? }))
+    }
+
+    async fn list_forms(&self) -> Result<Vec<form::Model>, FormError> {
+        FormBuilder::list(&self.db).await
+    }
+
+    async fn create_form(&self, input: CreateFormInput) -> Result<form::Model, FormError> {
+        FormBuilder::create(&self.db, input).await
+    }
+
+    async fn update_form(
+        &self,
+        id: Uuid,
+        input: CreateFormInput,
+    ) -> Result<form::Model, FormError> {
+        FormBuilder::update(&self.db, id, input).await
+    }
+
+    async fn duplicate_form(&self, id: Uuid) -> Result<form::Model, FormError> {
+        FormBuilder::duplicate(&self.db, id).await
+    }
+
+    async fn copy_slug(&self, slug: &str) -> Result<String, FormError> {
+        FormBuilder::copy_slug(&self.db, slug).await
+    }
+
+    async fn set_form_status(
+        &self,
+        id: Uuid,
+        status: FormStatus,
+    ) -> Result<form::Model, FormError> {
+        FormBuilder::set_status(&self.db, id, status).await
+    }
+
+    async fn delete_form(&self, id: Uuid) -> Result<(), FormError> {
+        FormBuilder::soft_delete(&self.db, id).await
+    }
+
+    async fn set_browser_allowed(
+        &self,
+        form_id: Uuid,
+        browser_id: Uuid,
+        allowed: bool,
+    ) -> Result<DuplicateGuard, FormError> {
+        FormBuilder::set_browser_allowed(&self.db, form_id, browser_id, allowed).await
+    }
+
+    async fn set_digest_subscription(
+        &self,
+        form: form::Model,
+        email: &str,
+        subscribed: bool,
+    ) -> Result<NotificationDigest, FormError> {
+        DigestService::set_subscribed(&self.db, form, email, subscribed).await
+    }
+
+    async fn import_options(
+        &self,
+        form: &form::Model,
+        field_name: &str,
+        rows: Vec<OptionRow>,
+        mode: ImportMode,
+        dry_run: bool,
+    ) -> Result<OptionDiff, FormError> {
+        OptionImport::apply(&self.db, form, field_name, rows, mode, dry_run).await
+    }
+
+    async fn find_result(
+        &self,
+        form_id: Uuid,
+        key: &str,
+    ) -> Result<Option<result::Model>, FormError> {
+        Ok(result::Entity::find_by_key(&self.db, form_id, key).await?)
+    }
+
+    async fn current_instance(
+        &self,
+        form: &form::Model,
+    ) -> Result<Option<form_instance::Model>, FormError> {
+        InstanceService::current(&self.db, form).await
+    }
+
+    async fn announce_lifecycle(
+        &self,
+        events: &dyn FormEvents,
+        form_id: Uuid,
+        at: DateTime<FixedOffset>,
+    ) -> Result<Option<FormTransition>, FormError> {
+        LifecycleService::announce(&self.db, events, form_id, at).await
+    }
+
+    async fn find_submission(&self, id: Uuid) -> Result<Option<submission::Model>, FormError> {
+        Ok(submission::Entity::find_by_id(id).one(&self.db).await?)
+    }
+
+    async fn has_responded(&self, form_id: Uuid, respondent_id: &str) -> Result<bool, FormError> {
+        Ok(submission::Entity::has_responded(&self.db, form_id, respondent_id).await?)
+    }
+
+    async fn start_submission(
+        &self,
+        form: &form::Model,
+        metadata: Option<SubmissionMetadata>,
+    ) -> Result<submission::Model, FormError> {
+        SubmissionService::start(&self.db, form, metadata).await
+    }
+
+    async fn discard_draft(&self, form_id: Uuid, id: Uuid) -> Result<bool, FormError> {
+        SubmissionService::discard_draft(&self.db, form_id, id).await
+    }
+
+    async fn promote_submission(
+        &self,
+        form: &form::Model,
+        sub: submission::Model,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError> {
+        SubmissionService::promote(&self.db, form, sub, key).await
+    }
+
+    async fn delete_submission(
+        &self,
+        sub: &submission::Model,
+        key: Option<&EncryptionKey>,
+    ) -> Result<(), FormError> {
+        let txn = self.db.begin().await?;
+        submission::Entity::soft_delete(&txn, sub.id).await?;
+        // Waitlisted and preview submissions hold neither seats nor quota
+        if sub.status() == SubmissionStatus::Submitted {
+            let fields = form_fields(&txn, sub.form_id).await?;
+            let mut data = sub.data.clone();
+            if let Some(key) = key {
+                key.decrypt_data(&mut data);
+            }
+            release_seats(&txn, &fields, &data).await?;
+            release_response(&txn, sub.form_id).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+
+    async fn set_legal_hold(
+        &self,
+        sub: submission::Model,
+        hold: bool,
+    ) -> Result<submission::Model, FormError> {
+        LegalHold::set(&self.db, sub, hold).await
+    }
+
+    async fn list_submissions(
+        &self,
+        query: &SubmissionQuery,
+    ) -> Result<SubmissionPage, FormError> {
+        query.fetch(&self.db).await
+    }
+
+    #[cfg(feature = "search")]
+    async fn search_submissions(
+        &self,
+        query: &SubmissionQuery,
+        text: &str,
+    ) -> Result<SearchPage, FormError> {
+        SearchService::search(&self.db, query, text).await
+    }
+
+    async fn submission_changes(
+        &self,
+        changes: &SubmissionChanges,
+        now: DateTime<FixedOffset>,
+    ) -> Result<ChangeBatch, FormError> {
+        changes.fetch(&self.db, now).await
+    }
+
+    async fn exported_submissions(
+        &self,
+        form_id: Uuid,
+        include_partials: bool,
+    ) -> Result<Vec<submission::Model>, FormError> {
+        Ok(submission::Entity::exported_by_form(form_id, include_partials)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .filter(|s| !s.is_in_progress() || s.is_partial())
+            .collect())
+    }
+
+    async fn submission_schema(
+        &self,
+        form_id: Uuid,
+        subs: &[submission::Model],
+    ) -> Result<SubmissionSchema, FormError> {
+        SubmissionSchema::load(&self.db, form_id, subs).await
+    }
+
+    async fn choice_fields(&self, form_id: Uuid) -> Result<Vec<FieldWithOptions>, FormError> {
+        choice_fields(&self.db, form_id).await
+    }
+
+    async fn preview_submissions(
+        &self,
+        form_id: Uuid,
+    ) -> Result<Vec<submission::Model>, FormError> {
+        Ok(submission::Entity::find_previews(&self.db, form_id).await?)
+    }
+
+    async fn delete_previews(&self, form_id: Uuid) -> Result<u64, FormError> {
+        Ok(submission::Entity::delete_previews(&self.db, form_id).await?)
+    }
+
+    async fn submission_labels(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<String>>, FormError> {
+        Ok(submission_note::Entity::labels_by_submission(&self.db, ids).await?)
+    }
+
+    async fn submission_notes(
+        &self,
+        id: Uuid,
+    ) -> Result<Vec<submission_note::Model>, FormError> {
+        Ok(submission_note::Entity::find_by_submission(&self.db, id).await?)
+    }
+
+    async fn add_submission_note(
+        &self,
+        id: Uuid,
+        body: Option<String>,
+        labels: &[String],
+        author: Option<String>,
+    ) -> Result<submission_note::Model, FormError> {
+        Ok(submission_note::Entity::add(&self.db, id, body, labels, author).await?)
+    }
+
+    async fn record_dead_letter(
+        &self,
+        form_id: Uuid,
+        data: &HashMap<String, FieldValue>,
+        metadata: Option<&SubmissionMetadata>,
+        error: &str,
+    ) -> Result<dead_letter::Model, FormError> {
+        Ok(dead_letter::Entity::record(&self.db, form_id, data, metadata, error).await?)
+    }
+
+    async fn find_dead_letter(&self, id: Uuid) -> Result<Option<dead_letter::Model>, FormError> {
+        Ok(dead_letter::Entity::find_by_id(id).one(&self.db).await?)
+    }
+
+    async fn pending_dead_letters(
+        &self,
+        form_id: Option<Uuid>,
+    ) -> Result<Vec<dead_letter::Model>, FormError> {
+        Ok(dead_letter::Entity::find_pending(&self.db, form_id).await?)
+    }
+
+    async fn count_pending_dead_letters(&self) -> Result<u64, FormError> {
+        Ok(dead_letter::Entity::count_pending(&self.db).await?)
+    }
+
+    async fn mark_dead_letter_failed(
+        &self,
+        letter: dead_letter::Model,
+        error: &str,
+    ) -> Result<dead_letter::Model, FormError> {
+        Ok(dead_letter::Entity::mark_failed(&self.db, letter, error).await?)
+    }
+
+    async fn mark_dead_letter_replayed(
+        &self,
+        letter: dead_letter::Model,
+        submission_id: Uuid,
+    ) -> Result<dead_letter::Model, FormError> {
+        Ok(dead_letter::Entity::mark_replayed(&self.db, letter, submission_id).await?)
+    }
+
+    async fn form_analytics(&self, form: &form::Model) -> Result<FormAnalytics, FormError> {
+        Analytics::form(&self.db, form).await
+    }
+
+    async fn instance_stats(&self, form: &form::Model) -> Result<Vec<InstanceStats>, FormError> {
+        InstanceService::stats(&self.db, form).await
+    }
+
+    async fn installation_stats(&self) -> Result<InstallationStats, FormError> {
+        Stats::installation(&self.db).await
+    }
+
+    async fn form_schedule(
+        &self,
+        form: &form::Model,
+        at: DateTime<FixedOffset>,
+        upcoming: usize,
+    ) -> Result<FormSchedule, FormError> {
+        ScheduleService::preview(&self.db, form, at, upcoming).await
+    }
+
+    async fn latest_digest(
+        &self,
+        form: &form::Model,
+        now: DateTime<FixedOffset>,
+    ) -> Result<Digest, FormError> {
+        DigestService::latest(&self.db, form, now).await
+    }
+
+    async fn score(
+        &self,
+        form: &form::Model,
+        fields: &[field::Model],
+        data: &HashMap<String, FieldValue>,
+    ) -> Result<Option<Score>, FormError> {
+        ScoringEngine::score(&self.db, form, fields, data).await
+    }
+
+    async fn resolve_result(
+        &self,
+        form_id: Uuid,
+        score: Option<&Score>,
+        data: &HashMap<String, FieldValue>,
+    ) -> Result<Option<result::Model>, FormError> {
+        ResultsEngine::resolve(&self.db, form_id, score, data).await
+    }
+}
+
+/// The [`FormsTransaction`] of a [`SeaOrmRepository`], over a database
+/// transaction.
+#[derive(Debug)]
+pub struct SeaOrmTransaction {
+    txn: DatabaseTransaction,
+}
+
+#[async_trait]
+impl FormsTransaction for SeaOrmTransaction {
+    async fn find_form(&self, id: Uuid) -> Result<Option<form::Model>, FormError> {
+        Ok(form::Entity::find_by_id(id).one(&self.txn).await?)
+    }
+
+    async fn load_steps_with_fields(
+        &self,
+        form_id: Uuid,
+    ) -> Result<Vec<StepWithFields>, FormError> {
+        load_steps_with_fields(&self.txn, form_id).await
+    }
+
+    async fn find_submission(&self, id: Uuid) -> Result<Option<submission::Model>, FormError> {
+        Ok(submission::Entity::find_by_id(id).one(&self.txn).await?)
+    }
+
+    async fn insert_submission(
+        &self,
+        form: &form::Model,
+        fields: &[field::Model],
+        data: &HashMap<String, FieldValue>,
+        metadata: Option<SubmissionMetadata>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError> {
+        SubmissionService::create(&self.txn, form, fields, data, metadata, key).await
+    }
+
+    async fn insert_preview(
+        &self,
+        form: &form::Model,
+        data: &HashMap<String, FieldValue>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError> {
+        SubmissionService::create_preview(&self.txn, form, data, key).await
+    }
+
+    async fn save_step(
+        &self,
+        sub: submission::Model,
+        step_id: Uuid,
+        data: &HashMap<String, FieldValue>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError> {
+        SubmissionService::save_step(&self.txn, sub, step_id, data, key).await
+    }
+
+    async fn complete_submission(
+        &self,
+        form: &form::Model,
+        fields: &[field::Model],
+        sub: submission::Model,
+        key: Option<&EncryptionKey>,
+    ) -> Result<submission::Model, FormError> {
+        SubmissionService::complete(&self.txn, form, fields, sub, key).await
+    }
+
+    async fn discard_submission(&self, id: Uuid) -> Result<(), FormError> {
+        submission::Entity::delete_by_id(id).exec(&self.txn).await?;
+        Ok(())
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), FormError> {
+        Ok(self.txn.commit().await?)
+    }
+}
+
+/// Most fields whose options one query loads, well under the bound
+/// parameter limits of the supported databases.
+const OPTION_BATCH: usize = 500;
+
+/// Most option queries one load has in flight at once.
+const MAX_OPTION_QUERIES: usize = 4;
+
+/// Loads a form's steps with their fields and options from `db`, with the
+/// fields of the [fragments](crate::fragments) they include.
+pub(crate) async fn load_steps_with_fields<C: ConnectionTrait>(
+    db: &C,
+    form_id: Uuid,
+) -> Result<Vec<StepWithFields>, FormError> {
+    let mut steps = load_own_steps(db, form_id).await?;
+    if steps.iter().any(|s| s.step.include_form.is_some()) {
+        fragments::include(db, &mut steps, vec![form_id]).await?;
+    }
+    Ok(steps)
+}
+
+/// Loads a form's steps with their own fields and options from `db`.
+///
+/// Takes one query each for the steps and their fields, then loads the
+/// fields' options in batches of [`OPTION_BATCH`] fields, up to
+/// [`MAX_OPTION_QUERIES`] at a time, and groups them in memory. Forms with
+/// repeatable groups take one more query, for the groups' sub-fields.
+pub(crate) async fn load_own_steps<C: ConnectionTrait>(
+    db: &C,
+    form_id: Uuid,
+) -> Result<Vec<StepWithFields>, FormError> {
+    let steps = step::Entity::find_by_form(db, form_id).await?;
+    let step_ids: Vec<Uuid> = steps.iter().map(|s| s.id).collect();
+    let fields = field::Entity::find_by_steps(db, &step_ids).await?;
+
+    let groups: Vec<Uuid> = fields.iter().filter(|f| f.is_repeater()).map(|f| f.id).collect();
+    let sub_fields = field::Entity::find_sub_fields(db, &groups).await?;
+
+    let with_options: Vec<Uuid> = fields
+        .iter()
+        .chain(&sub_fields)
+        .filter(|f| f.requires_options())
+        .map(|f| f.id)
+        .collect();
+    let queries: Vec<_> = with_options
+        .chunks(OPTION_BATCH)
+        .map(|ids| field_option::Entity::find_by_fields(db, ids))
+        .collect();
+    let batches: Vec<Vec<field_option::Model>> = futures::stream::iter(queries)
+        .buffered(MAX_OPTION_QUERIES)
+        .try_collect()
+        .await?;
+    let mut options: HashMap<Uuid, Vec<field_option::Model>> = HashMap::new();
+    for option in batches.into_iter().flatten() {
+        options.entry(option.field_id).or_default().push(option);
+    }
+
+    let mut by_group: HashMap<Uuid, Vec<FieldWithOptions>> = HashMap::new();
+    for field in sub_fields {
+        let options = options.remove(&field.id).unwrap_or_default();
+        by_group
+            .entry(field.parent_id.unwrap_or_default())
+            .or_default()
+            .push(FieldWithOptions { field, options, fields: Vec::new() });
+    }
+
+    let mut by_step: HashMap<Uuid, Vec<FieldWithOptions>> = HashMap::new();
+    for field in fields {
+        let options = options.remove(&field.id).unwrap_or_default();
+        let fields = by_group.remove(&field.id).unwrap_or_default();
+        by_step
+            .entry(field.step_id)
+            .or_default()
+            .push(FieldWithOptions { field, options, fields });
+    }
+
+    Ok(steps
+        .into_iter()
+        .map(|step| StepWithFields {
+            fields: by_step.remove(&step.id).unwrap_or_default(),
+            step,
+        })
+        .collect())
+}
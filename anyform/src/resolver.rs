@@ -0,0 +1,84 @@
+//! Finding the form a public route names.
+//!
+//! Public routes name their form with the `{slug}` path segment, which by
+//! default is the form's slug. Register a [`FormResolver`] with
+//! [`AnyFormRouterBuilder::form_resolver`](crate::AnyFormRouterBuilder::form_resolver)
+//! to look forms up another way, e.g. by an external ID, or by the request's
+//! host and a campaign code. Every public route resolves its form this way,
+//! and the links they make (form actions, success redirects and `config.js`)
+//! reuse the key the request named. Preview links, issued by the admin API,
+//! keep using slugs.
+//!
+//! ```rust,ignore
+//! use anyform::entities::form;
+//! use anyform::repository::FormsRepository;
+//! use anyform::resolver::FormResolver;
+//! use anyform::FormError;
+//! use http::HeaderMap;
+//!
+//! /// Resolves `/forms/{campaign}` within the tenant the host names.
+//! struct Campaigns(CampaignStore);
+//!
+//! #[async_trait::async_trait]
+//! impl FormResolver for Campaigns {
+//!     async fn resolve(
+//!         &self,
+//!         key: &str,
+//!         headers: &HeaderMap,
+//!         repository: &dyn FormsRepository,
+//!     ) -> Result<Option<form::Model>, FormError> {
+//!         let Some(host) = headers.get("host").and_then(|h| h.to_str().ok()) else {
+//!             return Ok(None);
+//!         };
+//!         match self.0.form_id(host, key).await {
+//!             Some(id) => repository.find_form(id).await,
+//!             None => Ok(None),
+//!         }
+//!     }
+//! }
+//! ```
+
+use async_trait::async_trait;
+use http::HeaderMap;
+
+use crate::entities::form;
+use crate::error::FormError;
+use crate::repository::FormsRepository;
+
+/// Finds the form a public route's key names.
+#[async_trait]
+pub trait FormResolver: Send + Sync + 'static {
+    /// Returns the form `key` names in a request with `headers`, or `None`
+    /// when it names none.
+    ///
+    /// Deleted, unpublished and closed forms may be returned; the routes
+    /// turn them away as they would any other.
+    async fn resolve(
+        &self,
+        key: &str,
+        headers: &HeaderMap,
+        repository: &dyn FormsRepository,
+    ) -> Result<Option<form::Model>, FormError>;
+}
+
+impl std::fmt::Debug for dyn FormResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FormResolver")
+    }
+}
+
+/// The default [`FormResolver`], taking the key as the form's slug.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SlugResolver;
+
+#[async_trait]
+impl FormResolver for SlugResolver {
+    async fn resolve(
+        &self,
+        key: &str,
+        _headers: &HeaderMap,
+        repository: &dyn FormsRepository,
+    ) -> Result<Option<form::Model>, FormError> {
+        repository.find_form_by_slug(key).await
+    }
+}
@@ -0,0 +1,71 @@
+//! Resolving who is submitting a form.
+//!
+//! Register a [`RespondentResolver`] with
+//! [`AnyFormRouterBuilder::respondent_resolver`](crate::AnyFormRouterBuilder::respondent_resolver)
+//! to stamp submissions with the respondent's identity (e.g. from a session
+//! cookie or bearer token), stored as their `respondent_id`. Forms with
+//! [`FormSettings::identified`](crate::FormSettings::identified) reject
+//! submissions the resolver cannot identify, forms with
+//! [`FormSettings::one_per_respondent`](crate::FormSettings::one_per_respondent)
+//! also reject a respondent's second submission, and
+//! [`FormSettings::anonymous`](crate::FormSettings::anonymous) forms never
+//! consult it.
+//!
+//! ```rust,ignore
+//! use anyform::respondent::{Respondent, RespondentResolver};
+//! use http::HeaderMap;
+//!
+//! struct BearerToken;
+//!
+//! #[async_trait::async_trait]
+//! impl RespondentResolver for BearerToken {
+//!     async fn resolve(&self, headers: &HeaderMap) -> Option<Respondent> {
+//!         let token = headers.get("authorization")?.to_str().ok()?;
+//!         lookup_user(token).await.map(|user| Respondent::new(user.id).email(user.email))
+//!     }
+//! }
+//! ```
+
+use async_trait::async_trait;
+use http::HeaderMap;
+
+/// An identified respondent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Respondent {
+    /// Stable user ID, stored as the submission's `respondent_id` and
+    /// `user_id` metadata.
+    pub id: String,
+    /// Email address, stored as `email` metadata when present.
+    pub email: Option<String>,
+}
+
+impl Respondent {
+    /// Creates a respondent with the given user ID.
+    #[must_use]
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            email: None,
+        }
+    }
+
+    /// Sets the respondent's email address.
+    #[must_use]
+    pub fn email(mut self, email: impl Into<String>) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+}
+
+/// Identifies the respondent of a submission request.
+#[async_trait]
+pub trait RespondentResolver: Send + Sync + 'static {
+    /// Returns the respondent making the request, if known.
+    async fn resolve(&self, headers: &HeaderMap) -> Option<Respondent>;
+}
+
+impl std::fmt::Debug for dyn RespondentResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RespondentResolver")
+    }
+}
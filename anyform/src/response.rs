@@ -4,7 +4,7 @@
 
 use axum::response::{IntoResponse, Response};
 use axum::Json;
-use http::StatusCode;
+use http::{header, HeaderValue, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{self, Display};
@@ -69,6 +69,10 @@ pub struct ApiError {
     /// Additional error details (e.g., validation errors by field).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<serde_json::Value>,
+
+    /// Seconds to wait before retrying, also sent as `Retry-After`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_after: Option<u64>,
 }
 
 /// Pagination information for list responses.
@@ -168,6 +172,7 @@ impl ApiResponse<()> {
                 code: code.into(),
                 message: message.into(),
                 details: None,
+                retry_after: None,
             }),
             pagination: None,
             request_id: Uuid::new_v4().to_string(),
@@ -190,6 +195,7 @@ impl ApiResponse<()> {
                 code: code.into(),
                 message: message.into(),
                 details: Some(details),
+                retry_after: None,
             }),
             pagination: None,
             request_id: Uuid::new_v4().to_string(),
@@ -197,6 +203,15 @@ impl ApiResponse<()> {
         }
     }
 
+    /// Tells the client to wait `seconds` before retrying.
+    #[must_use]
+    pub fn with_retry_after(mut self, seconds: u64) -> Self {
+        if let Some(error) = &mut self.error {
+            error.retry_after = Some(seconds);
+        }
+        self
+    }
+
     /// Creates a 404 Not Found error response.
     pub fn not_found(message: impl Into<String>) -> Self {
         Self::error("NOT_FOUND", message, StatusCode::NOT_FOUND)
@@ -245,10 +260,23 @@ impl ApiResponse<()> {
     }
 }
 
+/// Marks responses rendered from an [`ApiResponse`], so raw mode knows
+/// which bodies to unwrap; see [`crate::envelope`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Enveloped;
+
 impl<T: Serialize> IntoResponse for ApiResponse<T> {
     fn into_response(self) -> Response {
         let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
-        (status, Json(&self)).into_response()
+        let retry_after = self.error.as_ref().and_then(|e| e.retry_after);
+        let mut response = (status, Json(&self)).into_response();
+        if let Some(seconds) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response.extensions_mut().insert(Enveloped);
+        response
     }
 }
 
@@ -268,7 +296,7 @@ impl PaginationInfo {
         let total_pages = if total_items == 0 {
             1
         } else {
-            (total_items + per_page - 1) / per_page
+            total_items.div_ceil(per_page)
         };
 
         Self {
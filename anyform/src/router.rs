@@ -1,12 +1,53 @@
 //! AnyFormRouter for easy Axum integration.
 
 use axum::{
-    routing::{delete, get, post, put},
-    Router,
+    extract::{OriginalUri, Request, State},
+    middleware,
+    routing::{delete, get, patch, post, put},
+    Extension, Router,
 };
 use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::Layer;
 
-use crate::handlers;
+#[cfg(feature = "admin")]
+use crate::admin_auth::{self, AdminAuthorizer};
+#[cfg(feature = "assets")]
+use crate::assets::{self, ClientAssets};
+#[cfg(feature = "assets")]
+use crate::render::is_compatible_client_version;
+#[cfg(feature = "chaos")]
+use crate::chaos::{self, ChaosSecret};
+#[cfg(feature = "graphql")]
+use crate::graphql;
+#[cfg(feature = "metrics")]
+use crate::metrics::{self, Metrics};
+use crate::analysis::TextAnalyzer;
+use crate::captcha::CaptchaVerifier;
+use crate::database::RetryPolicy;
+use crate::envelope::{self, ResponseFormat};
+use crate::error::FormError;
+use crate::events::FormEvents;
+use crate::extractors::BodyLimits;
+use crate::i18n::MessageCatalog;
+use crate::gallery;
+use crate::geocoding::ReverseGeocoder;
+use crate::preflight::{self, Configured, PreflightReport};
+use crate::presets::FieldLibrary;
+use crate::encryption::EncryptionKey;
+use crate::preview::PreviewSecret;
+use crate::rate_limit::{RateLimiter, RateLimits};
+use crate::read_only::{self, DEFAULT_RETRY_AFTER};
+use crate::repository::FormsRepository;
+use crate::resolver::FormResolver;
+use crate::respondent::RespondentResolver;
+use crate::routing::{self, TrailingSlash};
+use crate::scope::FormScope;
+use crate::spam::SpamSecret;
+use crate::handlers::{self, AnyFormState, HandlerConfig};
+use crate::validation::{FieldValidator, ValidatorRegistry};
+use crate::versioning::{self, ApiVersion, Deprecation};
 
 /// A pre-configured router for form routes.
 ///
@@ -20,17 +61,24 @@ use crate::handlers;
 ///     .merge(AnyFormRouter::new(db.clone()));
 ///
 /// // Routes available:
-/// // GET  /api/forms/{slug}         - Render form HTML
-/// // GET  /api/forms/{slug}/json    - Get form schema JSON
-/// // POST /api/forms/{slug}         - Submit form (JSON response)
-/// // POST /api/forms/{slug}/submit  - Submit form (redirect)
-/// // GET  /api/forms/{slug}/success - Success page
+/// // GET  /api/v1/forms/{slug}         - Render form HTML
+/// // GET  /api/v1/forms/{slug}/json    - Get form schema JSON
+/// // POST /api/v1/forms/{slug}         - Submit form (JSON response)
+/// // POST /api/v1/forms/{slug}/submit  - Submit form (redirect)
+/// // GET  /api/v1/forms/{slug}/success - Success page
+/// // GET  /forms/assets/anyform.css    - Built-in form stylesheet
+/// // GET  /forms/assets/{file}         - WASM client bundle (`assets` feature)
 /// ```
+///
+/// Every `/api/v1/...` route is also mounted unversioned under `/api/...`
+/// with deprecation headers; see [`crate::versioning`]. `GET` routes answer
+/// `HEAD` too, and every API route answers `OPTIONS`; see
+/// [`crate::routing`].
 pub struct AnyFormRouter;
 
 impl AnyFormRouter {
     /// Creates a new forms router with default routes.
-    #[must_use]
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(db: DatabaseConnection) -> Router {
         Self::builder().database(db).build()
     }
@@ -40,6 +88,15 @@ impl AnyFormRouter {
     pub fn builder() -> AnyFormRouterBuilder {
         AnyFormRouterBuilder::default()
     }
+
+    /// Checks the database before serving it with the default router; see
+    /// [`crate::preflight`].
+    ///
+    /// Use [`AnyFormRouterBuilder::preflight`] to also check a customized
+    /// router's configuration.
+    pub async fn preflight(db: &DatabaseConnection) -> Result<PreflightReport, FormError> {
+        preflight::run(db, &Configured::default()).await
+    }
 }
 
 /// Builder for customizing the AnyFormRouter.
@@ -52,6 +109,41 @@ pub struct AnyFormRouterBuilder {
     enable_success: bool,
     #[cfg(feature = "admin")]
     enable_admin: bool,
+    #[cfg(feature = "admin")]
+    admin_auth: Option<Arc<dyn AdminAuthorizer>>,
+    #[cfg(feature = "assets")]
+    assets: Option<ClientAssets>,
+    unversioned_routes: Option<bool>,
+    unversioned_sunset: Option<String>,
+    events: Option<Arc<dyn FormEvents>>,
+    respondents: Option<Arc<dyn RespondentResolver>>,
+    text_analyzer: Option<Arc<dyn TextAnalyzer>>,
+    reverse_geocoder: Option<Arc<dyn ReverseGeocoder>>,
+    form_resolver: Option<Arc<dyn FormResolver>>,
+    preview_secret: Option<PreviewSecret>,
+    encryption_key: Option<EncryptionKey>,
+    field_validator: Option<Arc<dyn FieldValidator>>,
+    validators: ValidatorRegistry,
+    messages: MessageCatalog,
+    field_library: FieldLibrary,
+    retry: RetryPolicy,
+    rate_limits: RateLimits,
+    body_limits: BodyLimits,
+    spam_secret: Option<SpamSecret>,
+    captcha_verifier: Option<Arc<dyn CaptchaVerifier>>,
+    repository: Option<Arc<dyn FormsRepository>>,
+    read_only: bool,
+    read_only_retry_after: Option<Duration>,
+    only_forms: Option<FormScope>,
+    dev_mode: bool,
+    trailing_slash: TrailingSlash,
+    response_format: ResponseFormat,
+    #[cfg(feature = "chaos")]
+    chaos_secret: Option<ChaosSecret>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
+    #[cfg(feature = "graphql")]
+    enable_graphql: bool,
 }
 
 impl AnyFormRouterBuilder {
@@ -98,12 +190,345 @@ impl AnyFormRouterBuilder {
         self
     }
 
+    /// Requires admin requests to pass `authorizer`, e.g. a
+    /// [`BearerTokenAuth`](crate::admin_auth::BearerTokenAuth); see
+    /// [`crate::admin_auth`]. Without one, admin routes are open to anyone
+    /// who can reach them.
+    #[cfg(feature = "admin")]
+    #[must_use]
+    pub fn admin_auth(mut self, authorizer: impl AdminAuthorizer) -> Self {
+        self.admin_auth = Some(Arc::new(authorizer));
+        self
+    }
+
+    /// Sets where the WASM client bundle is served from (default: embedded).
+    ///
+    /// Client assets are served alongside the HTML routes, and server-rendered
+    /// pages load the hydration client from them.
+    #[cfg(feature = "assets")]
+    #[must_use]
+    pub fn assets(mut self, assets: ClientAssets) -> Self {
+        self.assets = Some(assets);
+        self
+    }
+
+    /// Mounts the deprecated unversioned `/api/...` routes (default: true).
+    #[must_use]
+    pub fn unversioned_routes(mut self, enable: bool) -> Self {
+        self.unversioned_routes = Some(enable);
+        self
+    }
+
+    /// Announces a removal date for the unversioned routes.
+    ///
+    /// Sent as the `Sunset` header; use an HTTP-date such as
+    /// `"Wed, 01 Jul 2026 00:00:00 GMT"`.
+    #[must_use]
+    pub fn unversioned_sunset(mut self, date: impl Into<String>) -> Self {
+        self.unversioned_sunset = Some(date.into());
+        self
+    }
+
+    /// Registers hooks for submission and form events (e.g. waitlist
+    /// notifications, or forms opening and closing).
+    #[must_use]
+    pub fn events(mut self, events: impl FormEvents) -> Self {
+        self.events = Some(Arc::new(events));
+        self
+    }
+
+    /// Registers the resolver that identifies respondents.
+    ///
+    /// Required for forms with `identified` or `one_per_respondent` set;
+    /// never consulted for `anonymous` forms.
+    #[must_use]
+    pub fn respondent_resolver(mut self, resolver: impl RespondentResolver) -> Self {
+        self.respondents = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Registers the analyzer that tags free-text answers with sentiment
+    /// and themes as submissions are stored; see [`crate::analysis`].
+    #[must_use]
+    pub fn text_analyzer(mut self, analyzer: impl TextAnalyzer) -> Self {
+        self.text_analyzer = Some(Arc::new(analyzer));
+        self
+    }
+
+    /// Registers the geocoder that names the places `location` answers
+    /// point at as submissions are stored; see [`crate::geocoding`].
+    #[must_use]
+    pub fn reverse_geocoder(mut self, geocoder: impl ReverseGeocoder) -> Self {
+        self.reverse_geocoder = Some(Arc::new(geocoder));
+        self
+    }
+
+    /// Registers the resolver that finds the form a public route's
+    /// `{slug}` segment names, in place of looking it up by slug; see
+    /// [`crate::resolver`].
+    #[must_use]
+    pub fn form_resolver(mut self, resolver: impl FormResolver) -> Self {
+        self.form_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Enables signed preview links, signed with `secret`.
+    ///
+    /// Mounts `GET`/`POST /forms/{slug}/preview` (and `/preview/submit` for
+    /// the SSR page), plus `POST`/`DELETE /admin/forms/{id}/preview` to mint
+    /// links and discard preview submissions. Use a long random secret and
+    /// keep it stable across restarts, or outstanding links stop working.
+    #[must_use]
+    pub fn preview_secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.preview_secret = Some(PreviewSecret::new(secret));
+        self
+    }
+
+    /// Encrypts answers to sensitive fields at rest with `key`; see
+    /// [`crate::encryption`].
+    ///
+    /// Fields are sensitive when their `classification` is `"sensitive"`.
+    /// The admin API, exports, PDFs and event hooks get their answers
+    /// decrypted. Keep the key stable across restarts: answers encrypted
+    /// with a lost key can't be read.
+    #[must_use]
+    pub fn encryption_key(mut self, key: EncryptionKey) -> Self {
+        self.encryption_key = Some(key);
+        self
+    }
+
+    /// Registers server-side checks for fields with `server_validate` set
+    /// (e.g. uniqueness or coupon validity).
+    ///
+    /// Consulted by `POST /forms/{slug}/fields/{field}/validate` and on
+    /// submit.
+    #[must_use]
+    pub fn field_validator(mut self, validator: impl FieldValidator) -> Self {
+        self.field_validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// Registers an async validator under `name`, run on fields that list
+    /// it in their `validators` rules (e.g. `"unique_email"`).
+    #[must_use]
+    pub fn validator(mut self, name: impl Into<String>, validator: impl FieldValidator) -> Self {
+        self.validators = self.validators.register(name, validator);
+        self
+    }
+
+    /// Replaces the registered async validators; see [`ValidatorRegistry`].
+    #[must_use]
+    pub fn validators(mut self, registry: ValidatorRegistry) -> Self {
+        self.validators = registry;
+        self
+    }
+
+    /// Sets the validation error messages by locale, chosen by a
+    /// submission's `?locale=`; see [`MessageCatalog`]. Defaults to the
+    /// built-in English messages.
+    #[must_use]
+    pub fn messages(mut self, catalog: MessageCatalog) -> Self {
+        self.messages = catalog;
+        self
+    }
+
+    /// Sets the field presets forms created, updated or synced through the
+    /// admin API and GraphQL reference with `$ref`; see
+    /// [`crate::presets`].
+    #[must_use]
+    pub fn field_library(mut self, library: FieldLibrary) -> Self {
+        self.field_library = library;
+        self
+    }
+
+    /// Sets how submissions that find the database busy (e.g. SQLite's
+    /// `database is locked`) are retried. Defaults to [`RetryPolicy::new`];
+    /// a submission that stays busy fails with `503 DATABASE_BUSY`.
+    #[must_use]
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Sets the submission rate limits (default: none); see
+    /// [`crate::rate_limit`]. Forms with
+    /// [`FormSettings::rate_limits`](crate::FormSettings::rate_limits) set
+    /// use their own instead.
+    #[must_use]
+    pub fn rate_limits(mut self, limits: RateLimits) -> Self {
+        self.rate_limits = limits;
+        self
+    }
+
+    /// Sets the size and shape limits on submission bodies (default:
+    /// [`BodyLimits::new`]); see [`BodyLimits`]. Bodies over them get
+    /// `413 PAYLOAD_TOO_LARGE` or `422 PAYLOAD_TOO_COMPLEX`.
+    #[must_use]
+    pub fn body_limits(mut self, limits: BodyLimits) -> Self {
+        self.body_limits = limits;
+        self
+    }
+
+    /// Sets the key signing render stamps for spam protection (default: a
+    /// random key per router); see [`crate::spam`]. Set the same secret on
+    /// every server sharing the traffic.
+    #[must_use]
+    pub fn spam_secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.spam_secret = Some(SpamSecret::new(secret));
+        self
+    }
+
+    /// Registers the verifier that checks CAPTCHA tokens; see
+    /// [`crate::captcha`].
+    ///
+    /// Required for forms with `captcha` set: without one, their
+    /// submissions fail with `400 CAPTCHA_FAILED`.
+    #[must_use]
+    pub fn captcha_verifier(mut self, verifier: impl CaptchaVerifier) -> Self {
+        self.captcha_verifier = Some(Arc::new(verifier));
+        self
+    }
+
+    /// Registers the storage the public form routes read forms and store
+    /// submissions through; see [`crate::repository`].
+    ///
+    /// Defaults to a [`SeaOrmRepository`](crate::SeaOrmRepository) over the
+    /// router's database.
+    #[must_use]
+    pub fn repository(mut self, repository: impl FormsRepository) -> Self {
+        self.repository = Some(Arc::new(repository));
+        self
+    }
+
+    /// Serves the GraphQL API at `/graphql`, and at `/admin/graphql` with
+    /// the admin routes (default: false); see [`crate::graphql`].
+    #[cfg(feature = "graphql")]
+    #[must_use]
+    pub fn enable_graphql(mut self, enable: bool) -> Self {
+        self.enable_graphql = enable;
+        self
+    }
+
+    /// Puts the router in read-only mode (default: false), e.g. on a read
+    /// replica or during maintenance.
+    ///
+    /// Forms are still served, but requests that would write fail with
+    /// `503 READ_ONLY` and a `Retry-After` header; see [`crate::read_only`].
+    #[must_use]
+    pub fn read_only(mut self, enable: bool) -> Self {
+        self.read_only = enable;
+        self
+    }
+
+    /// Sets the `Retry-After` sent while read-only (default:
+    /// [`DEFAULT_RETRY_AFTER`]).
+    #[must_use]
+    pub fn read_only_retry_after(mut self, retry_after: Duration) -> Self {
+        self.read_only_retry_after = Some(retry_after);
+        self
+    }
+
+    /// Serves only the forms with these slugs, on the public and admin
+    /// routes alike; other forms answer `404`. For apps embedding the
+    /// router for a few forms of a shared database; see [`crate::scope`].
+    #[must_use]
+    pub fn only_forms<I, S>(mut self, slugs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.only_forms = Some(FormScope::new(slugs));
+        self
+    }
+
+    /// Enables dev mode, serving a gallery of the example forms at
+    /// `/forms/examples`; see [`crate::gallery`]. For local evaluation only.
+    #[must_use]
+    pub fn dev_mode(mut self, enable: bool) -> Self {
+        self.dev_mode = enable;
+        self
+    }
+
+    /// Sets how API paths with a trailing slash are handled; by default
+    /// they're served as if the slash weren't there. See
+    /// [`crate::routing`].
+    #[must_use]
+    pub fn trailing_slash(mut self, mode: TrailingSlash) -> Self {
+        self.trailing_slash = mode;
+        self
+    }
+
+    /// Sets how the public JSON routes shape their responses (default:
+    /// [`ResponseFormat::Envelope`]); clients can ask for either with an
+    /// `Accept` profile. See [`crate::envelope`].
+    #[must_use]
+    pub fn response_format(mut self, format: ResponseFormat) -> Self {
+        self.response_format = format;
+        self
+    }
+
+    /// Enables chaos mode: API requests carrying `secret` in the
+    /// `X-Anyform-Chaos-Secret` header may simulate failures. See
+    /// [`crate::chaos`]; for integration environments only.
+    #[cfg(feature = "chaos")]
+    #[must_use]
+    pub fn chaos_secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.chaos_secret = Some(ChaosSecret::new(secret));
+        self
+    }
+
+    /// Records Prometheus metrics into `metrics` and serves them at
+    /// `/metrics`. See [`crate::metrics`].
+    #[cfg(feature = "metrics")]
+    #[must_use]
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Checks the database and this builder's configuration; see
+    /// [`crate::preflight`].
+    ///
+    /// Call before [`build`](Self::build), and refuse to start if
+    /// [`PreflightReport::ensure_ok`] fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no database connection was provided.
+    pub async fn preflight(&self) -> Result<PreflightReport, FormError> {
+        let db = self
+            .db
+            .as_ref()
+            .expect("Database connection is required. Call .database(db) before .preflight()");
+
+        #[allow(unused_mut)]
+        let mut configured = Configured {
+            respondent_resolver: self.respondents.is_some(),
+            captcha_verifier: self.captcha_verifier.is_some(),
+            field_validator: self.field_validator.is_some(),
+            validators: self.validators.names().into_iter().map(String::from).collect(),
+            events: self.events.is_some(),
+            read_only: self.read_only,
+            ..Configured::default()
+        };
+        #[cfg(feature = "admin")]
+        {
+            configured.admin_unprotected = self.enable_admin && self.admin_auth.is_none();
+        }
+        #[cfg(feature = "assets")]
+        if self.enable_html || !self.any_explicitly_set() {
+            let version = self.assets.clone().unwrap_or_default().version();
+            configured.client_compatible = Some(is_compatible_client_version(&version));
+        }
+
+        preflight::run(db, &configured).await
+    }
+
     /// Builds the router.
     ///
     /// # Panics
     ///
     /// Panics if no database connection was provided.
-    #[must_use]
     pub fn build(self) -> Router {
         // Check explicitly set flags before consuming db
         let any_set = self.any_explicitly_set();
@@ -112,55 +537,315 @@ impl AnyFormRouterBuilder {
         let enable_submit = self.enable_submit || !any_set;
         let enable_success = self.enable_success || !any_set;
 
-        let db = self
+        #[allow(unused_mut)]
+        let mut db = self
             .db
             .expect("Database connection is required. Call .database(db) before .build()");
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.instrument(&mut db);
+        }
+
+        #[allow(unused_mut)]
+        let mut config = HandlerConfig {
+            events: self.events,
+            respondents: self.respondents,
+            text_analyzer: self.text_analyzer,
+            reverse_geocoder: self.reverse_geocoder,
+            form_resolver: self.form_resolver,
+            preview_secret: self.preview_secret.clone(),
+            encryption_key: self.encryption_key,
+            field_validator: self.field_validator,
+            validators: self.validators,
+            messages: self.messages,
+            field_library: self.field_library,
+            retry: self.retry,
+            rate_limiter: Arc::new(RateLimiter::new(self.rate_limits)),
+            spam_secret: self.spam_secret.unwrap_or_default(),
+            captcha_verifier: self.captcha_verifier,
+            read_only: self.read_only,
+            only_forms: self.only_forms.clone(),
+            ..HandlerConfig::default()
+        };
 
         // Start with health check route (always enabled)
         let mut router = Router::new().route("/health", get(handlers::health_check));
 
+        #[cfg(feature = "assets")]
+        if enable_html {
+            let client_assets = self.assets.unwrap_or_default();
+            client_assets.check_compatibility();
+            config.wasm_base_url = Some(client_assets.base_url().to_string());
+            config.wasm_version = Some(client_assets.version());
+            config.wasm_integrity = Some(client_assets.integrity());
+            router = router.merge(assets::routes(client_assets));
+        }
+
         if enable_html {
-            router = router.route("/api/forms/{slug}", get(handlers::get_form_html));
+            router = router.route("/forms/assets/anyform.css", get(handlers::stylesheet));
+        }
+
+        if self.dev_mode {
+            router = router.route(gallery::PATH, get(gallery::index));
+        }
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = self.metrics {
+            config.metrics = Some(metrics.clone());
+            router = router.route(metrics::PATH, get(move || async move { metrics::export(&metrics) }));
+        }
+
+        let mut state = AnyFormState::with_config(db, config);
+        if let Some(repository) = self.repository {
+            state = state.with_repository(repository);
+        }
+
+        // API routes, relative to the version prefix
+        let mut api = Router::new();
+        #[cfg(feature = "admin")]
+        let mut admin = Router::new();
+
+        if enable_html {
+            api = api.route("/forms/{slug}", get(handlers::get_form_html));
         }
 
         if enable_json {
-            router = router.route("/api/forms/{slug}/json", get(handlers::get_form_json));
+            api = api
+                .route("/forms/{slug}/json", get(handlers::get_form_json))
+                .route("/forms/{slug}/config.js", get(handlers::get_form_config_js))
+                .route("/forms/{slug}/instance", get(handlers::get_current_instance));
         }
 
         if enable_submit {
-            router = router
-                .route("/api/forms/{slug}", post(handlers::submit_form))
-                .route("/api/forms/{slug}/submit", post(handlers::submit_form_redirect));
+            api = api
+                .route("/forms/{slug}", post(handlers::submit_form))
+                .route("/forms/{slug}/submit", post(handlers::submit_form_redirect))
+                .route(
+                    "/forms/{slug}/fields/{field}/validate",
+                    post(handlers::validate_form_field),
+                )
+                .route("/forms/{slug}/submissions/start", post(handlers::start_submission))
+                .route("/forms/{slug}/submissions/{id}", get(handlers::get_submission_progress))
+                .route(
+                    "/forms/{slug}/submissions/{id}/step",
+                    patch(handlers::save_submission_step),
+                )
+                .route(
+                    "/forms/{slug}/submissions/{id}/complete",
+                    post(handlers::complete_submission),
+                );
         }
 
         if enable_success {
-            router = router.route("/api/forms/{slug}/success", get(handlers::form_success));
+            api = api.route("/forms/{slug}/success", get(handlers::form_success));
+        }
+
+        if self.preview_secret.is_some() {
+            api = api
+                .route(
+                    "/forms/{slug}/preview",
+                    get(handlers::preview_form).post(handlers::submit_preview),
+                )
+                .route(
+                    "/forms/{slug}/preview/submit",
+                    post(handlers::submit_preview_redirect),
+                );
+
+            #[cfg(feature = "admin")]
+            if self.enable_admin {
+                admin = admin.route(
+                    "/admin/forms/{id}/preview",
+                    post(handlers::create_preview_link)
+                        .delete(handlers::discard_preview_submissions),
+                );
+            }
         }
 
         #[cfg(feature = "admin")]
         if self.enable_admin {
-            router = router
-                .route("/api/admin/forms", get(handlers::list_forms))
-                .route("/api/admin/forms", post(handlers::create_form))
-                .route("/api/admin/forms/sync", post(handlers::sync_forms))
-                .route("/api/admin/forms/{id}", get(handlers::get_form_by_id))
-                .route("/api/admin/forms/{id}", put(handlers::update_form))
-                .route("/api/admin/forms/{id}", delete(handlers::delete_form))
-                .route(
-                    "/api/admin/forms/{id}/submissions",
+            // Installation-wide totals would cover every form
+            if self.only_forms.is_none() {
+                admin = admin.route("/admin/stats", get(handlers::get_stats));
+            }
+            admin = admin
+                .route("/admin/forms", get(handlers::list_forms))
+                .route("/admin/forms", post(handlers::create_form))
+                .route("/admin/forms/sync", post(handlers::sync_forms))
+                .route("/admin/forms/{id}", get(handlers::get_form_by_id))
+                .route("/admin/forms/{id}", put(handlers::update_form))
+                .route("/admin/forms/{id}", delete(handlers::delete_form))
+                .route("/admin/forms/{id}/publish", post(handlers::publish_form))
+                .route("/admin/forms/{id}/unpublish", post(handlers::unpublish_form))
+                .route("/admin/forms/{id}/archive", post(handlers::archive_form))
+                .route("/admin/forms/{id}/duplicate", post(handlers::duplicate_form))
+                .route(
+                    "/admin/forms/{id}/fields/{field}/options/import",
+                    post(handlers::import_field_options),
+                )
+                .route(
+                    "/admin/forms/{id}/submissions",
                     get(handlers::list_submissions),
                 )
                 .route(
-                    "/api/admin/forms/{form_id}/submissions/{sub_id}",
+                    "/admin/forms/{id}/submissions/changes",
+                    get(handlers::list_submission_changes),
+                )
+                .route(
+                    "/admin/forms/{id}/submissions/export",
+                    get(handlers::export_submissions),
+                )
+                .route(
+                    "/admin/forms/{id}/instances",
+                    get(handlers::list_form_instances),
+                )
+                .route(
+                    "/admin/forms/{id}/analytics",
+                    get(handlers::get_form_analytics),
+                )
+                .route(
+                    "/admin/forms/{id}/simulate",
+                    post(handlers::simulate_submission),
+                )
+                .route(
+                    "/admin/forms/{id}/schedule",
+                    get(handlers::get_form_schedule),
+                )
+                .route("/admin/forms/{id}/digest", get(handlers::get_form_digest))
+                .route(
+                    "/admin/forms/{id}/digest/recipients/{email}",
+                    put(handlers::set_digest_subscription),
+                )
+                .route(
+                    "/admin/forms/{id}/duplicate-guard/browsers/{browser_id}",
+                    put(handlers::set_browser_allowed),
+                )
+                .route(
+                    "/admin/forms/{form_id}/submissions/{sub_id}",
                     get(handlers::get_submission),
                 )
                 .route(
-                    "/api/admin/forms/{form_id}/submissions/{sub_id}",
+                    "/admin/forms/{form_id}/submissions/{sub_id}",
                     delete(handlers::delete_submission),
+                )
+                .route(
+                    "/admin/forms/{form_id}/submissions/{sub_id}/pdf",
+                    get(handlers::get_submission_pdf),
+                )
+                .route(
+                    "/admin/forms/{form_id}/submissions/{sub_id}/legal-hold",
+                    post(handlers::place_legal_hold).delete(handlers::lift_legal_hold),
+                )
+                .route(
+                    "/admin/forms/{form_id}/submissions/{sub_id}/promote",
+                    post(handlers::promote_submission),
+                )
+                .route(
+                    "/admin/forms/{form_id}/submissions/{sub_id}/notes",
+                    get(handlers::list_submission_notes).post(handlers::add_submission_note),
+                )
+                .route("/admin/dead-letters", get(handlers::list_dead_letters))
+                .route(
+                    "/admin/dead-letters/{id}/replay",
+                    post(handlers::replay_dead_letter),
                 );
         }
 
-        router.with_state(db)
+        #[cfg(feature = "graphql")]
+        if self.enable_graphql {
+            api = api
+                .route(
+                    "/graphql",
+                    get(graphql::public_graphql_get).post(graphql::public_graphql),
+                )
+                .route("/graphql/schema.graphql", get(graphql::public_schema));
+
+            #[cfg(feature = "admin")]
+            if self.enable_admin {
+                admin = admin
+                    .route(
+                        "/admin/graphql",
+                        get(graphql::admin_graphql_get).post(graphql::admin_graphql),
+                    )
+                    .route("/admin/graphql/schema.graphql", get(graphql::admin_schema));
+            }
+        }
+
+        #[cfg(feature = "admin")]
+        {
+            if self.only_forms.is_some() {
+                admin = admin.route_layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    crate::scope::restrict_admin,
+                ));
+            }
+            if let Some(authorizer) = self.admin_auth {
+                admin = admin.route_layer(middleware::from_fn(move |req, next| {
+                    admin_auth::require_admin(authorizer.clone(), req, next)
+                }));
+            }
+            api = api.merge(admin);
+        }
+
+        if self.read_only {
+            let retry_after = self.read_only_retry_after.unwrap_or(DEFAULT_RETRY_AFTER);
+            api = api.layer(middleware::from_fn(move |req, next| {
+                read_only::reject_writes(retry_after, req, next)
+            }));
+        }
+
+        #[cfg(feature = "chaos")]
+        if let Some(secret) = self.chaos_secret {
+            api = api.layer(middleware::from_fn(move |req, next| {
+                chaos::inject(secret.clone(), req, next)
+            }));
+        }
+
+        api = api.layer(Extension(self.body_limits));
+
+        let routes = api.clone();
+        let mode = self.trailing_slash;
+        api = api.fallback(
+            move |State(state): State<AnyFormState>, uri: OriginalUri, req: Request| {
+                routing::trailing_slash(mode, routes.clone().with_state(state), uri, req)
+            },
+        );
+
+        // Nested as services so `OPTIONS` answers see the `Allow` header,
+        // which axum only sets on a router's final response
+        let answer_options = middleware::from_fn(routing::answer_options);
+        let format = self.response_format;
+        let negotiate_format = middleware::from_fn(move |req, next| {
+            envelope::negotiate_format(format, req, next)
+        });
+        for &version in ApiVersion::ALL {
+            let versioned = api
+                .clone()
+                .layer(middleware::from_fn(move |req, next| {
+                    versioning::pin_version(version, req, next)
+                }))
+                .layer(negotiate_format.clone());
+            router = router.nest_service(
+                version.path_prefix(),
+                answer_options.layer(versioned.with_state(state.clone())),
+            );
+        }
+
+        if self.unversioned_routes.unwrap_or(true) {
+            let deprecation = Deprecation {
+                sunset: self.unversioned_sunset,
+            };
+            let unversioned = api
+                .layer(middleware::from_fn(move |req, next| {
+                    versioning::negotiate_unversioned(deprecation.clone(), req, next)
+                }))
+                .layer(negotiate_format);
+            router = router.nest_service(
+                "/api",
+                answer_options.layer(unversioned.with_state(state.clone())),
+            );
+        }
+
+        router.with_state(state)
     }
 
     fn any_explicitly_set(&self) -> bool {
@@ -0,0 +1,174 @@
+//! Trailing slashes, `HEAD` and `OPTIONS` on the API routes.
+//!
+//! Every `GET` route of [`AnyFormRouter`](crate::AnyFormRouter) also answers
+//! `HEAD`, with the same status and headers but no body. `OPTIONS` on any
+//! API route is answered with `204 No Content`, listing the methods the
+//! route accepts in `Allow` and `Access-Control-Allow-Methods` and the
+//! request headers the API reads in `Access-Control-Allow-Headers`.
+//!
+//! Which origins may call the API is up to the embedder: a CORS layer such
+//! as `tower_http::cors::CorsLayer` answers preflights before they reach the
+//! router, and should allow [`ALLOWED_METHODS`] and [`ALLOWED_HEADERS`] so
+//! every route works cross-origin (`anyform serve --cors` does).
+//!
+//! API paths with a trailing slash, such as `/api/forms/contact/`, are
+//! handled as configured with
+//! [`AnyFormRouterBuilder::trailing_slash`](crate::AnyFormRouterBuilder::trailing_slash).
+
+use std::convert::Infallible;
+
+use axum::{
+    body::Body,
+    extract::{OriginalUri, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Router,
+};
+use http::{header, HeaderName, HeaderValue, Method, StatusCode, Uri};
+use tower::Service;
+
+/// Methods used by the API routes.
+pub const ALLOWED_METHODS: [Method; 7] = [
+    Method::GET,
+    Method::HEAD,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+    Method::OPTIONS,
+];
+
+/// Request headers read by the API routes.
+pub const ALLOWED_HEADERS: [HeaderName; 9] = [
+    header::ACCEPT,
+    header::AUTHORIZATION,
+    header::CONTENT_TYPE,
+    header::IF_NONE_MATCH,
+    HeaderName::from_static("anyform-version"),
+    HeaderName::from_static("x-api-key"),
+    HeaderName::from_static("x-request-id"),
+    HeaderName::from_static("x-anyform-browser"),
+    HeaderName::from_static("x-anyform-responded"),
+];
+
+/// How long clients may cache an `OPTIONS` answer, in seconds.
+const OPTIONS_MAX_AGE: u32 = 24 * 60 * 60;
+
+/// How API paths with a trailing slash are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrailingSlash {
+    /// Served as if the slash weren't there.
+    #[default]
+    Ignore,
+    /// Redirected to the path without the slash with `308 Permanent
+    /// Redirect`, which keeps the method and body.
+    Redirect,
+    /// Not found, like any other unknown path.
+    Strict,
+}
+
+/// Answers `OPTIONS` on routes that don't handle it themselves.
+pub(crate) async fn answer_options(req: Request, next: Next) -> Response {
+    if req.method() != Method::OPTIONS {
+        return next.run(req).await;
+    }
+
+    // The route answers other methods with `405` and the ones it accepts;
+    // keep its other headers, such as the API version
+    let response = next.run(req).await;
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+        return response;
+    }
+    let (mut parts, _) = response.into_parts();
+    let mut methods: Vec<&str> = parts
+        .headers
+        .get(header::ALLOW)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|m| !m.is_empty())
+        .collect();
+    methods.push(Method::OPTIONS.as_str());
+    let methods = HeaderValue::from_str(&methods.join(", "))
+        .unwrap_or(HeaderValue::from_static("OPTIONS"));
+    let headers = ALLOWED_HEADERS
+        .iter()
+        .map(HeaderName::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    parts.status = StatusCode::NO_CONTENT;
+    parts.headers.remove(header::CONTENT_TYPE);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(header::ALLOW, methods.clone());
+    parts.headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, methods);
+    parts.headers.insert(
+        header::ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_str(&headers).unwrap(),
+    );
+    parts
+        .headers
+        .insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from(OPTIONS_MAX_AGE));
+    Response::from_parts(parts, Body::empty())
+}
+
+/// Handles a request for an unknown API path, serving or redirecting it
+/// when it only differs from a route by a trailing slash.
+///
+/// `api` is the API router, without this fallback.
+pub(crate) async fn trailing_slash(
+    mode: TrailingSlash,
+    mut api: Router,
+    OriginalUri(original): OriginalUri,
+    mut req: Request,
+) -> Response {
+    let path = req.uri().path();
+    let trimmed = path.trim_end_matches('/');
+    if mode == TrailingSlash::Strict || trimmed.len() == path.len() || trimmed.is_empty() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    match mode {
+        TrailingSlash::Redirect => {
+            let location = with_path(&original, original.path().trim_end_matches('/'));
+            match HeaderValue::from_str(&location) {
+                Ok(location) => (
+                    StatusCode::PERMANENT_REDIRECT,
+                    [(header::LOCATION, location)],
+                )
+                    .into_response(),
+                Err(_) => StatusCode::NOT_FOUND.into_response(),
+            }
+        }
+        _ => {
+            let Ok(uri) = with_path(req.uri(), trimmed).parse::<Uri>() else {
+                return StatusCode::NOT_FOUND.into_response();
+            };
+            *req.uri_mut() = uri;
+            let response: Result<Response, Infallible> = api.call(req).await;
+            response.unwrap_or_else(|never| match never {})
+        }
+    }
+}
+
+/// Returns `uri`'s path and query, with `path` for its path.
+fn with_path(uri: &Uri, path: &str) -> String {
+    match uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_keep_their_query() {
+        let uri: Uri = "/api/forms/contact/?lang=fr".parse().unwrap();
+        assert_eq!(with_path(&uri, "/api/forms/contact"), "/api/forms/contact?lang=fr");
+        let uri: Uri = "/api/forms/contact/".parse().unwrap();
+        assert_eq!(with_path(&uri, "/api/forms/contact"), "/api/forms/contact");
+    }
+}
@@ -0,0 +1,239 @@
+//! White-label branding for forms.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::FormError;
+
+/// Branding applied wherever a form is shown: the rendered form, its success
+/// page, and any template given the form's settings (e.g. emails).
+///
+/// Colors are emitted as the `--af-primary` and `--af-secondary` CSS
+/// variables on `.af-form`, so themes can use `var(--af-primary)`.
+///
+/// ```
+/// use anyform::schema::Branding;
+///
+/// let branding = Branding::new()
+///     .logo_url("https://cdn.example.com/acme.svg")
+///     .primary_color("#0a7")
+///     .footer_text("Acme Inc.")
+///     .footer_link("Privacy", "/privacy");
+///
+/// assert!(branding.check().is_ok());
+/// assert_eq!(
+///     branding.css_variables().as_deref(),
+///     Some(".af-form { --af-primary: #0a7; }")
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Branding {
+    /// Logo shown above the form.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_url: Option<String>,
+
+    /// Alt text for the logo.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_alt: Option<String>,
+
+    /// Primary color, e.g. `#0a7` or `rgb(0, 170, 119)`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub primary_color: Option<String>,
+
+    /// Secondary color.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secondary_color: Option<String>,
+
+    /// Text shown in the footer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer_text: Option<String>,
+
+    /// Links shown in the footer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub footer_links: Vec<FooterLink>,
+}
+
+/// A link in the branded footer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FooterLink {
+    pub label: String,
+    pub url: String,
+}
+
+impl Branding {
+    /// Creates empty branding.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the logo URL.
+    #[must_use]
+    pub fn logo_url(mut self, url: impl Into<String>) -> Self {
+        self.logo_url = Some(url.into());
+        self
+    }
+
+    /// Sets the logo alt text.
+    #[must_use]
+    pub fn logo_alt(mut self, alt: impl Into<String>) -> Self {
+        self.logo_alt = Some(alt.into());
+        self
+    }
+
+    /// Sets the primary color.
+    #[must_use]
+    pub fn primary_color(mut self, color: impl Into<String>) -> Self {
+        self.primary_color = Some(color.into());
+        self
+    }
+
+    /// Sets the secondary color.
+    #[must_use]
+    pub fn secondary_color(mut self, color: impl Into<String>) -> Self {
+        self.secondary_color = Some(color.into());
+        self
+    }
+
+    /// Sets the footer text.
+    #[must_use]
+    pub fn footer_text(mut self, text: impl Into<String>) -> Self {
+        self.footer_text = Some(text.into());
+        self
+    }
+
+    /// Adds a footer link.
+    #[must_use]
+    pub fn footer_link(mut self, label: impl Into<String>, url: impl Into<String>) -> Self {
+        self.footer_links.push(FooterLink {
+            label: label.into(),
+            url: url.into(),
+        });
+        self
+    }
+
+    /// Returns true if the branding adds a footer.
+    #[must_use]
+    pub fn has_footer(&self) -> bool {
+        self.footer_text.is_some() || !self.footer_links.is_empty()
+    }
+
+    /// Checks that colors are plain CSS colors and URLs are `http(s)`,
+    /// `mailto:` or site-relative.
+    ///
+    /// Returns [`FormError::InvalidData`] otherwise.
+    pub fn check(&self) -> Result<(), FormError> {
+        for (name, color) in [
+            ("primary_color", &self.primary_color),
+            ("secondary_color", &self.secondary_color),
+        ] {
+            if let Some(color) = color {
+                if !is_css_color(color) {
+                    return Err(FormError::InvalidData(format!(
+                        "branding.{name} must be a CSS color, got '{color}'"
+                    )));
+                }
+            }
+        }
+
+        let urls = self
+            .logo_url
+            .iter()
+            .map(|url| ("logo_url", url))
+            .chain(self.footer_links.iter().map(|link| ("footer_links", &link.url)));
+        for (name, url) in urls {
+            if !is_safe_url(url) {
+                return Err(FormError::InvalidData(format!(
+                    "branding.{name} must be an http(s), mailto or relative URL, got '{url}'"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a CSS rule setting the color variables on `.af-form`, if any
+    /// valid color is set.
+    ///
+    /// Invalid colors are skipped, so the result is always safe to inline.
+    #[must_use]
+    pub fn css_variables(&self) -> Option<String> {
+        let vars: Vec<String> = [
+            ("--af-primary", &self.primary_color),
+            ("--af-secondary", &self.secondary_color),
+        ]
+        .into_iter()
+        .filter_map(|(var, color)| {
+            color
+                .as_deref()
+                .filter(|c| is_css_color(c))
+                .map(|c| format!("{var}: {c};"))
+        })
+        .collect();
+
+        (!vars.is_empty()).then(|| format!(".af-form {{ {} }}", vars.join(" ")))
+    }
+}
+
+/// Returns true for hex colors, `rgb()`/`rgba()`/`hsl()`/`hsla()` with plain
+/// arguments, and named colors.
+fn is_css_color(color: &str) -> bool {
+    let color = color.trim();
+    if let Some(hex) = color.strip_prefix('#') {
+        return matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    }
+
+    let lower = color.to_ascii_lowercase();
+    for func in ["rgba(", "rgb(", "hsla(", "hsl("] {
+        if let Some(args) = lower.strip_prefix(func).and_then(|a| a.strip_suffix(')')) {
+            return !args.is_empty()
+                && args
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || " ,.%/+-".contains(c));
+        }
+    }
+
+    !color.is_empty() && color.len() <= 32 && color.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// Returns true for `http(s)://`, `mailto:` and site-relative URLs.
+pub(crate) fn is_safe_url(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    lower.starts_with("https://")
+        || lower.starts_with("http://")
+        || lower.starts_with("mailto:")
+        || (lower.starts_with('/') && !lower.starts_with("//") && !lower.starts_with("/\\"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css_colors() {
+        for color in ["#0a7", "#00aa77cc", "rgb(0, 170, 119)", "hsl(160deg 100% 33%)", "teal"] {
+            assert!(is_css_color(color), "{color}");
+        }
+        for color in ["#0a", "red; } body { color: red", "url(x)", "rgb(0,0,0);x", "var(--x)", ""] {
+            assert!(!is_css_color(color), "{color}");
+        }
+    }
+
+    #[test]
+    fn test_check_rejects_unsafe_urls() {
+        assert!(Branding::new().logo_url("/logo.png").check().is_ok());
+        assert!(Branding::new().footer_link("Mail", "mailto:hi@example.com").check().is_ok());
+
+        assert!(Branding::new().logo_url("javascript:alert(1)").check().is_err());
+        assert!(Branding::new().footer_link("X", "//evil.example.com").check().is_err());
+        assert!(Branding::new().primary_color("red;}").check().is_err());
+    }
+
+    #[test]
+    fn test_css_variables_skip_invalid_colors() {
+        let branding = Branding::new().primary_color("red;}").secondary_color("#fff");
+        assert_eq!(
+            branding.css_variables().as_deref(),
+            Some(".af-form { --af-secondary: #fff; }")
+        );
+        assert_eq!(Branding::new().css_variables(), None);
+    }
+}
@@ -0,0 +1,65 @@
+//! Data classification of form fields.
+
+use serde::{Deserialize, Serialize};
+
+/// How a field's answers may be used once submitted.
+///
+/// Classes are ordered from least to most restricted.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldClassification {
+    /// Aggregated in analytics and exported.
+    #[default]
+    Standard,
+    /// Exported, but never aggregated (e.g. free-text comments).
+    NoAnalytics,
+    /// Personal data: never aggregated, and left out of exports unless
+    /// sensitive data is asked for explicitly.
+    Sensitive,
+}
+
+impl FieldClassification {
+    /// Returns the stored string value.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::NoAnalytics => "no_analytics",
+            Self::Sensitive => "sensitive",
+        }
+    }
+
+    /// Returns true if answers may be aggregated in analytics.
+    #[must_use]
+    pub fn in_analytics(self) -> bool {
+        self == Self::Standard
+    }
+
+    /// Returns true if answers are exported without asking for sensitive
+    /// data.
+    #[must_use]
+    pub fn in_default_export(self) -> bool {
+        self != Self::Sensitive
+    }
+
+    /// Returns true for [`Self::Standard`], for skipping it when serializing.
+    #[must_use]
+    pub fn is_standard(&self) -> bool {
+        *self == Self::Standard
+    }
+}
+
+impl std::str::FromStr for FieldClassification {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "standard" => Ok(Self::Standard),
+            "no_analytics" => Ok(Self::NoAnalytics),
+            "sensitive" => Ok(Self::Sensitive),
+            other => Err(format!("unknown field classification: {other}")),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Notification digests: periodic summary emails instead of one per
+//! submission.
+
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How often a digest is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestFrequency {
+    /// Covers the previous day, midnight to midnight UTC.
+    Daily,
+    /// Covers the previous week, Monday to Monday UTC.
+    Weekly,
+}
+
+impl DigestFrequency {
+    /// Returns the last whole period ending at or before `now`, as
+    /// `(start, end)`.
+    ///
+    /// ```
+    /// use anyform::schema::DigestFrequency;
+    ///
+    /// // A Thursday afternoon
+    /// let now = "2025-01-16T15:30:00+00:00".parse().unwrap();
+    ///
+    /// let (start, end) = DigestFrequency::Daily.period_before(now);
+    /// assert_eq!(start.to_rfc3339(), "2025-01-15T00:00:00+00:00");
+    /// assert_eq!(end.to_rfc3339(), "2025-01-16T00:00:00+00:00");
+    ///
+    /// let (start, end) = DigestFrequency::Weekly.period_before(now);
+    /// assert_eq!(start.to_rfc3339(), "2025-01-06T00:00:00+00:00");
+    /// assert_eq!(end.to_rfc3339(), "2025-01-13T00:00:00+00:00");
+    /// ```
+    #[must_use]
+    pub fn period_before(
+        self,
+        now: DateTime<FixedOffset>,
+    ) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+        let today = now.with_timezone(&Utc).date_naive();
+        let (end, length) = match self {
+            Self::Daily => (today, Duration::days(1)),
+            Self::Weekly => {
+                let since_monday = i64::from(today.weekday().num_days_from_monday());
+                (today - Duration::days(since_monday), Duration::weeks(1))
+            }
+        };
+        let end = end.and_time(NaiveTime::MIN).and_utc().fixed_offset();
+        (end - length, end)
+    }
+}
+
+/// Someone who receives a form's digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestRecipient {
+    /// Email address the digest is sent to.
+    pub email: String,
+
+    /// Whether the recipient still wants the digest; unsubscribed
+    /// recipients stay listed so they can opt back in.
+    #[serde(default = "default_subscribed")]
+    pub subscribed: bool,
+}
+
+fn default_subscribed() -> bool {
+    true
+}
+
+/// Digest settings for a form; see
+/// [`DigestService`](crate::services::DigestService).
+///
+/// ```
+/// use anyform::schema::{FormSettings, NotificationDigest};
+///
+/// let settings = FormSettings::new().digest(
+///     NotificationDigest::weekly()
+///         .recipient("owner@example.com")
+///         .recipient("team@example.com"),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationDigest {
+    /// How often the digest is sent.
+    pub frequency: DigestFrequency,
+
+    /// Who the digest is sent to.
+    #[serde(default)]
+    pub recipients: Vec<DigestRecipient>,
+}
+
+impl NotificationDigest {
+    /// Creates a digest with no recipients.
+    #[must_use]
+    pub fn new(frequency: DigestFrequency) -> Self {
+        Self {
+            frequency,
+            recipients: Vec::new(),
+        }
+    }
+
+    /// Creates a daily digest with no recipients.
+    #[must_use]
+    pub fn daily() -> Self {
+        Self::new(DigestFrequency::Daily)
+    }
+
+    /// Creates a weekly digest with no recipients.
+    #[must_use]
+    pub fn weekly() -> Self {
+        Self::new(DigestFrequency::Weekly)
+    }
+
+    /// Adds a subscribed recipient.
+    #[must_use]
+    pub fn recipient(mut self, email: impl Into<String>) -> Self {
+        self.recipients.push(DigestRecipient {
+            email: email.into(),
+            subscribed: true,
+        });
+        self
+    }
+
+    /// Returns the email addresses of subscribed recipients.
+    #[must_use]
+    pub fn subscribed_recipients(&self) -> Vec<String> {
+        self.recipients
+            .iter()
+            .filter(|r| r.subscribed)
+            .map(|r| r.email.clone())
+            .collect()
+    }
+
+    /// Subscribes or unsubscribes a recipient, matching the email address
+    /// case-insensitively. Returns false if no such recipient is listed.
+    pub fn set_subscribed(&mut self, email: &str, subscribed: bool) -> bool {
+        match self
+            .recipients
+            .iter_mut()
+            .find(|r| r.email.eq_ignore_ascii_case(email))
+        {
+            Some(recipient) => {
+                recipient.subscribed = subscribed;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periods_end_at_midnight_before_now() {
+        let monday = "2025-01-13T00:00:00+00:00".parse().unwrap();
+        let (start, end) = DigestFrequency::Weekly.period_before(monday);
+        assert_eq!(start.to_rfc3339(), "2025-01-06T00:00:00+00:00");
+        assert_eq!(end, monday);
+
+        // Offsets are normalized to UTC first
+        let late = "2025-01-16T23:30:00-05:00".parse().unwrap();
+        let (start, _) = DigestFrequency::Daily.period_before(late);
+        assert_eq!(start.to_rfc3339(), "2025-01-16T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_unsubscribed_recipients_are_kept_but_skipped() {
+        let mut digest = NotificationDigest::daily()
+            .recipient("a@example.com")
+            .recipient("b@example.com");
+
+        assert!(digest.set_subscribed("A@example.com", false));
+        assert!(!digest.set_subscribed("c@example.com", false));
+        assert_eq!(digest.subscribed_recipients(), vec!["b@example.com"]);
+        assert_eq!(digest.recipients.len(), 2);
+
+        let json = serde_json::json!({
+            "frequency": "weekly",
+            "recipients": [{ "email": "x@y.z" }]
+        });
+        let parsed: NotificationDigest = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.subscribed_recipients(), vec!["x@y.z"]);
+    }
+}
@@ -0,0 +1,54 @@
+//! Text direction for rendered forms.
+
+use serde::{Deserialize, Serialize};
+
+/// Languages written right-to-left, by ISO 639 code.
+const RTL_LANGUAGES: &[&str] = &[
+    "ar", "arc", "ckb", "dv", "fa", "he", "iw", "ks", "ku", "ps", "sd", "syr", "ug", "ur", "yi",
+];
+
+/// Text direction of a form.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Left-to-right.
+    #[default]
+    Ltr,
+    /// Right-to-left (e.g. Arabic, Hebrew).
+    Rtl,
+}
+
+impl Direction {
+    /// Returns the direction a locale is written in, e.g. `Rtl` for `"ar-EG"`
+    /// or `"he"`.
+    ///
+    /// ```
+    /// use anyform::schema::Direction;
+    ///
+    /// assert_eq!(Direction::from_locale("ar-EG"), Direction::Rtl);
+    /// assert_eq!(Direction::from_locale("he_IL"), Direction::Rtl);
+    /// assert_eq!(Direction::from_locale("en-US"), Direction::Ltr);
+    /// ```
+    #[must_use]
+    pub fn from_locale(locale: &str) -> Self {
+        let lang = locale
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if RTL_LANGUAGES.contains(&lang.as_str()) {
+            Self::Rtl
+        } else {
+            Self::Ltr
+        }
+    }
+
+    /// Returns the value of the HTML `dir` attribute.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ltr => "ltr",
+            Self::Rtl => "rtl",
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Column mapping for CSV exports.
+
+use chrono::format::{Item, StrftimeItems};
+use serde::{Deserialize, Serialize};
+
+use crate::error::FormError;
+
+/// How a form's submissions are laid out in CSV exports, so exports drop
+/// into an existing spreadsheet as they are.
+///
+/// Without `columns`, exports hold the submission attributes followed by
+/// every field, as without a mapping.
+///
+/// ```
+/// use anyform::schema::{ExportColumnMapping, ExportMapping, SubmissionAttribute};
+///
+/// let mapping = ExportMapping::new()
+///     .column(ExportColumnMapping::attribute(SubmissionAttribute::CreatedAt).header("Date"))
+///     .column(ExportColumnMapping::field("email").header("E-mail"))
+///     .column(ExportColumnMapping::field("plan"))
+///     .date_format("%d/%m/%Y")
+///     .option_labels(true);
+/// assert!(mapping.check().is_ok());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportMapping {
+    /// Columns in order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<ExportColumnMapping>,
+
+    /// Fields left out, e.g. of exports without `columns`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+
+    /// `strftime` format for timestamps and date answers, e.g. `%d/%m/%Y`;
+    /// timestamps default to `%Y-%m-%d %H:%M:%S`, and date answers are
+    /// written as submitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+
+    /// Writes select, radio and checkbox answers as option labels rather
+    /// than stored values.
+    #[serde(default)]
+    pub option_labels: bool,
+}
+
+/// One column of an [`ExportMapping`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportColumnMapping {
+    /// What the column holds: `{"field": "email"}` or
+    /// `{"attribute": "created_at"}`.
+    #[serde(flatten)]
+    pub source: ExportSource,
+
+    /// Header, instead of the field's label or the attribute's name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+}
+
+/// What an export column holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportSource {
+    /// The answer to the field of this name.
+    Field(String),
+    /// A submission attribute.
+    Attribute(SubmissionAttribute),
+}
+
+/// Submission attributes exports can hold besides answers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubmissionAttribute {
+    Id,
+    CreatedAt,
+    CompletedAt,
+    Status,
+    InstanceId,
+    /// Labels, joined with `;`.
+    Labels,
+}
+
+impl SubmissionAttribute {
+    /// Every attribute, in the order exports list them by default.
+    pub const ALL: [Self; 6] = [
+        Self::Id,
+        Self::CreatedAt,
+        Self::CompletedAt,
+        Self::Status,
+        Self::InstanceId,
+        Self::Labels,
+    ];
+
+    /// Returns the attribute's name, also its default header.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::CreatedAt => "created_at",
+            Self::CompletedAt => "completed_at",
+            Self::Status => "status",
+            Self::InstanceId => "instance_id",
+            Self::Labels => "labels",
+        }
+    }
+}
+
+impl ExportColumnMapping {
+    /// A column holding the answer to field `name`.
+    #[must_use]
+    pub fn field(name: impl Into<String>) -> Self {
+        Self {
+            source: ExportSource::Field(name.into()),
+            header: None,
+        }
+    }
+
+    /// A column holding a submission attribute.
+    #[must_use]
+    pub fn attribute(attribute: SubmissionAttribute) -> Self {
+        Self {
+            source: ExportSource::Attribute(attribute),
+            header: None,
+        }
+    }
+
+    /// Sets the column header.
+    #[must_use]
+    pub fn header(mut self, header: impl Into<String>) -> Self {
+        self.header = Some(header.into());
+        self
+    }
+}
+
+impl ExportMapping {
+    /// Creates a mapping that changes nothing.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a column.
+    #[must_use]
+    pub fn column(mut self, column: ExportColumnMapping) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    /// Leaves a field out.
+    #[must_use]
+    pub fn exclude(mut self, name: impl Into<String>) -> Self {
+        self.exclude.push(name.into());
+        self
+    }
+
+    /// Sets the `strftime` format for timestamps and date answers.
+    #[must_use]
+    pub fn date_format(mut self, format: impl Into<String>) -> Self {
+        self.date_format = Some(format.into());
+        self
+    }
+
+    /// Writes choice answers as option labels.
+    #[must_use]
+    pub fn option_labels(mut self, labels: bool) -> Self {
+        self.option_labels = labels;
+        self
+    }
+
+    /// Checks that columns name a field and the date format is valid.
+    ///
+    /// Returns [`FormError::InvalidData`] otherwise.
+    pub fn check(&self) -> Result<(), FormError> {
+        for column in &self.columns {
+            if matches!(&column.source, ExportSource::Field(name) if name.trim().is_empty()) {
+                return Err(FormError::InvalidData(
+                    "export.columns must name a field".to_string(),
+                ));
+            }
+        }
+        if let Some(format) = &self.date_format {
+            if format.is_empty() || StrftimeItems::new(format).any(|i| i == Item::Error) {
+                return Err(FormError::InvalidData(format!(
+                    "export.date_format must be a strftime format, got '{format}'"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns_from_json() {
+        let mapping: ExportMapping = serde_json::from_value(serde_json::json!({
+            "columns": [
+                { "attribute": "created_at", "header": "Date" },
+                { "field": "email" }
+            ],
+            "option_labels": true
+        }))
+        .unwrap();
+        assert_eq!(
+            mapping.columns,
+            vec![
+                ExportColumnMapping::attribute(SubmissionAttribute::CreatedAt).header("Date"),
+                ExportColumnMapping::field("email"),
+            ]
+        );
+        assert!(mapping.option_labels);
+    }
+
+    #[test]
+    fn test_invalid_date_format() {
+        assert!(ExportMapping::new().date_format("%Q").check().is_err());
+        assert!(ExportMapping::new().column(ExportColumnMapping::field(" ")).check().is_err());
+    }
+}
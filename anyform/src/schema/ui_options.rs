@@ -1,6 +1,9 @@
 //! UI options for form fields.
 
+use crate::branching::JumpRule;
 use crate::condition::ConditionRule;
+use crate::error::FormError;
+use crate::schema::is_safe_url;
 use serde::{Deserialize, Serialize};
 
 /// UI/display options for a form field.
@@ -80,6 +83,18 @@ pub struct UiOptions {
     #[serde(default)]
     pub show_char_count: bool,
 
+    /// Help beyond `help_text`: an image, a tooltip and links.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub help: Option<HelpMedia>,
+
+    /// Label of the button adding a row to a repeatable group.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub add_label: Option<String>,
+
+    /// Label of the button removing a row from a repeatable group.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remove_label: Option<String>,
+
     /// Custom HTML attributes as key-value pairs.
     #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
     pub custom_attributes: std::collections::HashMap<String, String>,
@@ -89,6 +104,11 @@ pub struct UiOptions {
     /// When present, the field is only shown if the condition evaluates to true.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<ConditionRule>,
+
+    /// Jump rules tried once the field's step is done, while the field is
+    /// shown; see [`crate::branching`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub next: Vec<JumpRule>,
 }
 
 /// Labels for scale endpoints.
@@ -102,6 +122,137 @@ pub struct ScaleLabels {
     pub mid_label: Option<String>,
 }
 
+/// Help shown with a field beyond its `help_text`.
+///
+/// Rendered after the field's help text, and referenced from its input with
+/// `aria-describedby`. The tooltip sits behind a toggle button next to the
+/// label, which the WASM client opens and closes.
+///
+/// ```
+/// use anyform::schema::HelpMedia;
+///
+/// let help = HelpMedia::new()
+///     .image("/img/cvc.png", "The three digits on the back of the card")
+///     .tooltip("Usually on the back of your card")
+///     .link("Where is my CVC?", "https://example.com/cvc");
+///
+/// assert!(help.check("cvc").is_ok());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HelpMedia {
+    /// Illustration, e.g. where to find a number on a card.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<HelpImage>,
+
+    /// Short hint shown on demand.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+
+    /// Links to further reading, opened in a new tab.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<HelpLink>,
+}
+
+/// An image illustrating a field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HelpImage {
+    pub url: String,
+    /// Text alternative for the image; required.
+    pub alt: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+}
+
+/// A link to further help on a field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HelpLink {
+    pub label: String,
+    pub url: String,
+}
+
+impl HelpMedia {
+    /// Creates empty help media.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the image and its alt text.
+    #[must_use]
+    pub fn image(mut self, url: impl Into<String>, alt: impl Into<String>) -> Self {
+        self.image = Some(HelpImage {
+            url: url.into(),
+            alt: alt.into(),
+            caption: None,
+        });
+        self
+    }
+
+    /// Sets the image's caption; call after [`image`](Self::image).
+    #[must_use]
+    pub fn caption(mut self, caption: impl Into<String>) -> Self {
+        if let Some(image) = &mut self.image {
+            image.caption = Some(caption.into());
+        }
+        self
+    }
+
+    /// Sets the tooltip.
+    #[must_use]
+    pub fn tooltip(mut self, text: impl Into<String>) -> Self {
+        self.tooltip = Some(text.into());
+        self
+    }
+
+    /// Adds a link.
+    #[must_use]
+    pub fn link(mut self, label: impl Into<String>, url: impl Into<String>) -> Self {
+        self.links.push(HelpLink {
+            label: label.into(),
+            url: url.into(),
+        });
+        self
+    }
+
+    /// Returns true if there is an image or links to show below the field.
+    #[must_use]
+    pub fn has_media(&self) -> bool {
+        self.image.is_some() || !self.links.is_empty()
+    }
+
+    /// Checks that the image has alt text, links have labels, and URLs are
+    /// `http(s)`, `mailto:` or site-relative.
+    ///
+    /// Returns [`FormError::InvalidData`] naming the field otherwise.
+    pub fn check(&self, field: &str) -> Result<(), FormError> {
+        if let Some(image) = &self.image {
+            if image.alt.trim().is_empty() {
+                return Err(FormError::InvalidData(format!(
+                    "The help image of '{field}' needs alt text"
+                )));
+            }
+        }
+        if self.links.iter().any(|link| link.label.trim().is_empty()) {
+            return Err(FormError::InvalidData(format!(
+                "The help links of '{field}' need labels"
+            )));
+        }
+        let urls = self
+            .image
+            .iter()
+            .map(|image| &image.url)
+            .chain(self.links.iter().map(|link| &link.url));
+        for url in urls {
+            if !is_safe_url(url) {
+                return Err(FormError::InvalidData(format!(
+                    "The help of '{field}' must link http(s), mailto or relative URLs, got '{url}'"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl UiOptions {
     /// Creates new default UI options.
     #[must_use]
@@ -151,6 +302,21 @@ impl UiOptions {
         self
     }
 
+    /// Sets help media.
+    #[must_use]
+    pub fn help(mut self, help: HelpMedia) -> Self {
+        self.help = Some(help);
+        self
+    }
+
+    /// Sets the labels of a repeatable group's add and remove buttons.
+    #[must_use]
+    pub fn row_labels(mut self, add: impl Into<String>, remove: impl Into<String>) -> Self {
+        self.add_label = Some(add.into());
+        self.remove_label = Some(remove.into());
+        self
+    }
+
     /// Adds a custom HTML attribute.
     #[must_use]
     pub fn attr(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
@@ -164,4 +330,11 @@ impl UiOptions {
         self.condition = Some(condition);
         self
     }
+
+    /// Adds a jump rule.
+    #[must_use]
+    pub fn jump(mut self, rule: JumpRule) -> Self {
+        self.next.push(rule);
+        self
+    }
 }
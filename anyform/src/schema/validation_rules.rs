@@ -2,6 +2,20 @@
 
 use serde::{Deserialize, Serialize};
 
+/// The rules that take a custom error message.
+const MESSAGE_RULES: &[&str] = &[
+    "required",
+    "min_length",
+    "max_length",
+    "min",
+    "max",
+    "pattern",
+    "min_selections",
+    "max_selections",
+    "min_rows",
+    "max_rows",
+];
+
 /// Validation rules for a form field.
 ///
 /// These rules are stored as JSON in the database and applied
@@ -36,6 +50,45 @@ pub struct ValidationRules {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pattern_message: Option<String>,
 
+    /// Custom error message for a missing required value.
+    ///
+    /// This and the other `*_message` templates replace the built-in message
+    /// for their rule, filling in `{label}` and the rule's `{min}` or `{max}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_message: Option<String>,
+
+    /// Custom error message for a value shorter than `min_length`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length_message: Option<String>,
+
+    /// Custom error message for a value longer than `max_length`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length_message: Option<String>,
+
+    /// Custom error message for a number below `min`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_message: Option<String>,
+
+    /// Custom error message for a number above `max`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_message: Option<String>,
+
+    /// Custom error message for fewer than `min_selections` choices.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_selections_message: Option<String>,
+
+    /// Custom error message for more than `max_selections` choices.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_selections_message: Option<String>,
+
+    /// Custom error message for fewer than `min_rows` rows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_rows_message: Option<String>,
+
+    /// Custom error message for more than `max_rows` rows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rows_message: Option<String>,
+
     /// Minimum number of selections (for multi-select).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min_selections: Option<usize>,
@@ -44,6 +97,14 @@ pub struct ValidationRules {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_selections: Option<usize>,
 
+    /// Minimum number of rows (for repeatable groups).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_rows: Option<usize>,
+
+    /// Maximum number of rows (for repeatable groups).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_rows: Option<usize>,
+
     /// Allowed file extensions (for file uploads).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allowed_extensions: Option<Vec<String>>,
@@ -67,6 +128,20 @@ pub struct ValidationRules {
     /// Custom validation rules as JSON.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub custom: Option<serde_json::Value>,
+
+    /// Whether the field has server-side checks (e.g. uniqueness).
+    ///
+    /// Clients validate it as the user types through
+    /// `POST /forms/{slug}/fields/{field}/validate`, and registered
+    /// [`FieldValidator`](crate::validation::FieldValidator) hooks run for it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub server_validate: bool,
+
+    /// Names of async validators to run on the field, as registered in a
+    /// [`ValidatorRegistry`](crate::validation::ValidatorRegistry) (e.g.
+    /// `"unique_email"`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub validators: Vec<String>,
 }
 
 impl ValidationRules {
@@ -104,6 +179,13 @@ impl ValidationRules {
         self
     }
 
+    /// Sets the step between allowed numeric values.
+    #[must_use]
+    pub fn step(mut self, step: f64) -> Self {
+        self.step = Some(step);
+        self
+    }
+
     /// Sets the regex pattern.
     #[must_use]
     pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
@@ -118,6 +200,95 @@ impl ValidationRules {
         self
     }
 
+    /// Sets the error message template for `rule` (e.g. `"min_length"`),
+    /// such as `"{label} needs {min} letters or more"`.
+    ///
+    /// Rules are named like their fields, plus `"required"`; unknown rules
+    /// are ignored.
+    #[must_use]
+    pub fn message(mut self, rule: &str, template: impl Into<String>) -> Self {
+        if let Some(slot) = self.message_slot(rule) {
+            *slot = Some(template.into());
+        }
+        self
+    }
+
+    /// Returns the custom error message template for `rule`, if set.
+    #[must_use]
+    pub fn message_for(&self, rule: &str) -> Option<&str> {
+        match rule {
+            "required" => self.required_message.as_deref(),
+            "min_length" => self.min_length_message.as_deref(),
+            "max_length" => self.max_length_message.as_deref(),
+            "min" => self.min_message.as_deref(),
+            "max" => self.max_message.as_deref(),
+            "pattern" => self.pattern_message.as_deref(),
+            "min_selections" => self.min_selections_message.as_deref(),
+            "max_selections" => self.max_selections_message.as_deref(),
+            "min_rows" => self.min_rows_message.as_deref(),
+            "max_rows" => self.max_rows_message.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the custom error messages that are set, by rule.
+    pub fn messages(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        MESSAGE_RULES
+            .iter()
+            .filter_map(|rule| Some((*rule, self.message_for(rule)?)))
+    }
+
+    fn message_slot(&mut self, rule: &str) -> Option<&mut Option<String>> {
+        Some(match rule {
+            "required" => &mut self.required_message,
+            "min_length" => &mut self.min_length_message,
+            "max_length" => &mut self.max_length_message,
+            "min" => &mut self.min_message,
+            "max" => &mut self.max_message,
+            "pattern" => &mut self.pattern_message,
+            "min_selections" => &mut self.min_selections_message,
+            "max_selections" => &mut self.max_selections_message,
+            "min_rows" => &mut self.min_rows_message,
+            "max_rows" => &mut self.max_rows_message,
+            _ => return None,
+        })
+    }
+
+    /// Sets the minimum number of rows of a repeatable group.
+    #[must_use]
+    pub fn min_rows(mut self, rows: usize) -> Self {
+        self.min_rows = Some(rows);
+        self
+    }
+
+    /// Sets the maximum number of rows of a repeatable group.
+    #[must_use]
+    pub fn max_rows(mut self, rows: usize) -> Self {
+        self.max_rows = Some(rows);
+        self
+    }
+
+    /// Sets whether the field has server-side checks.
+    #[must_use]
+    pub fn server_validate(mut self, enabled: bool) -> Self {
+        self.server_validate = enabled;
+        self
+    }
+
+    /// Adds a registered async validator to run on the field.
+    #[must_use]
+    pub fn validator(mut self, name: impl Into<String>) -> Self {
+        self.validators.push(name.into());
+        self
+    }
+
+    /// Returns true if the server checks the field beyond these rules,
+    /// with the field validator or named validators.
+    #[must_use]
+    pub fn is_server_checked(&self) -> bool {
+        self.server_validate || !self.validators.is_empty()
+    }
+
     /// Returns true if any validation rules are set.
     #[must_use]
     pub fn is_empty(&self) -> bool {
@@ -129,11 +300,15 @@ impl ValidationRules {
             && self.pattern.is_none()
             && self.min_selections.is_none()
             && self.max_selections.is_none()
+            && self.min_rows.is_none()
+            && self.max_rows.is_none()
             && self.allowed_extensions.is_none()
             && self.max_file_size.is_none()
             && self.allowed_mime_types.is_none()
             && self.min_date.is_none()
             && self.max_date.is_none()
             && self.custom.is_none()
+            && !self.server_validate
+            && self.validators.is_empty()
     }
 }
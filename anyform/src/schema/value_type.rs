@@ -27,6 +27,8 @@ pub enum ValueType {
     MultiSelect,
     /// Radio button group.
     Radio,
+    /// Grid of picture cards, one of which is chosen (radio semantics).
+    ImageChoice,
     /// Single checkbox.
     Checkbox,
 
@@ -61,6 +63,14 @@ pub enum ValueType {
     Nps,
     /// Matrix/grid of questions.
     Matrix,
+
+    // Composite
+    /// Repeatable group of sub-fields, answered once per row (e.g. team
+    /// members).
+    Repeater,
+    /// Point on the map (latitude, longitude, accuracy and an optional
+    /// place name), filled in by hand or from the device's location.
+    Location,
 }
 
 impl ValueType {
@@ -78,9 +88,9 @@ impl ValueType {
             Self::Time => "time",
             Self::File | Self::Image => "file",
             Self::Checkbox => "checkbox",
-            Self::Radio => "radio",
+            Self::Radio | Self::ImageChoice => "radio",
             // These don't map directly to input types
-            Self::Textarea | Self::Select | Self::MultiSelect | Self::Heading | Self::Paragraph | Self::Matrix => "",
+            Self::Textarea | Self::Select | Self::MultiSelect | Self::Heading | Self::Paragraph | Self::Matrix | Self::Repeater | Self::Location => "",
         }
     }
 
@@ -89,7 +99,7 @@ impl ValueType {
     pub fn requires_options(&self) -> bool {
         matches!(
             self,
-            Self::Select | Self::MultiSelect | Self::Radio | Self::Matrix
+            Self::Select | Self::MultiSelect | Self::Radio | Self::ImageChoice | Self::Matrix
         )
     }
 
@@ -105,6 +115,12 @@ impl ValueType {
         matches!(self, Self::File | Self::Image)
     }
 
+    /// Returns true if this field type collects a number.
+    #[must_use]
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Self::Number | Self::Rating | Self::Scale | Self::Nps)
+    }
+
     /// Returns true if this field type accepts multiple values.
     #[must_use]
     pub fn is_multi_value(&self) -> bool {
@@ -124,6 +140,7 @@ impl std::fmt::Display for ValueType {
             Self::Select => "select",
             Self::MultiSelect => "multi_select",
             Self::Radio => "radio",
+            Self::ImageChoice => "image_choice",
             Self::Checkbox => "checkbox",
             Self::Date => "date",
             Self::DateTime => "datetime",
@@ -137,6 +154,8 @@ impl std::fmt::Display for ValueType {
             Self::Scale => "scale",
             Self::Nps => "nps",
             Self::Matrix => "matrix",
+            Self::Repeater => "repeater",
+            Self::Location => "location",
         };
         write!(f, "{s}")
     }
@@ -156,6 +175,7 @@ impl std::str::FromStr for ValueType {
             "select" | "dropdown" => Ok(Self::Select),
             "multi_select" | "multiselect" => Ok(Self::MultiSelect),
             "radio" => Ok(Self::Radio),
+            "image_choice" | "picture_choice" => Ok(Self::ImageChoice),
             "checkbox" => Ok(Self::Checkbox),
             "date" => Ok(Self::Date),
             "datetime" | "date_time" => Ok(Self::DateTime),
@@ -169,6 +189,8 @@ impl std::str::FromStr for ValueType {
             "scale" | "slider" => Ok(Self::Scale),
             "nps" => Ok(Self::Nps),
             "matrix" | "grid" => Ok(Self::Matrix),
+            "repeater" | "group" | "repeatable" => Ok(Self::Repeater),
+            "location" | "geolocation" | "geo" => Ok(Self::Location),
             _ => Err(format!("Unknown field type: {s}")),
         }
     }
@@ -0,0 +1,161 @@
+//! Serving only some of a database's forms.
+//!
+//! Apps embedding the router for one or two forms of a shared database can
+//! name them with
+//! [`AnyFormRouterBuilder::only_forms`](crate::AnyFormRouterBuilder::only_forms).
+//! The public and admin routes then serve only forms with those slugs:
+//! other forms answer `404 NOT_FOUND` as if they didn't exist, admin
+//! listings (forms, dead letters, GraphQL) leave them out, and creating a
+//! form, or renaming one, to a slug outside the list is rejected.
+//! Installation-wide statistics (`/admin/stats`) aren't served.
+//!
+//! ```rust,ignore
+//! let router = AnyFormRouter::builder()
+//!     .database(db)
+//!     .enable_admin(true)
+//!     .only_forms(["contact", "newsletter"])
+//!     .build();
+//! ```
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+#[cfg(all(feature = "router", feature = "admin"))]
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+#[cfg(all(feature = "router", feature = "admin"))]
+use uuid::Uuid;
+
+#[cfg(all(feature = "router", feature = "admin"))]
+use crate::entities::form;
+use crate::error::FormError;
+#[cfg(all(feature = "router", feature = "admin"))]
+use crate::handlers::AnyFormState;
+
+/// The slugs of the forms a router serves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormScope(Arc<HashSet<String>>);
+
+impl FormScope {
+    /// Creates a scope of the forms with `slugs`.
+    pub fn new<I, S>(slugs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self(Arc::new(slugs.into_iter().map(Into::into).collect()))
+    }
+
+    /// Returns true if the form with `slug` is served.
+    #[must_use]
+    pub fn contains(&self, slug: &str) -> bool {
+        self.0.contains(slug)
+    }
+
+    /// Rejects writing a form under `slug` unless it is served.
+    pub fn check_slug(&self, slug: &str) -> Result<(), FormError> {
+        if self.contains(slug) {
+            return Ok(());
+        }
+        Err(FormError::InvalidData(format!(
+            "Form '{slug}' is not one of the forms this router serves"
+        )))
+    }
+}
+
+/// What an admin route acts on, by its path.
+#[cfg(all(feature = "router", feature = "admin"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    /// `/admin/forms/{id}/...`
+    Form(Uuid),
+    /// `/admin/dead-letters/{id}/...`
+    DeadLetter(Uuid),
+}
+
+/// Returns the form or dead letter an admin route's path names, if any.
+#[cfg(all(feature = "router", feature = "admin"))]
+fn target(path: &str) -> Option<Target> {
+    let id = |rest: &str| rest.split('/').next().and_then(|id| Uuid::parse_str(id).ok());
+    if let Some(rest) = path.strip_prefix("/admin/forms/") {
+        return id(rest).map(Target::Form);
+    }
+    if let Some(rest) = path.strip_prefix("/admin/dead-letters/") {
+        return id(rest).map(Target::DeadLetter);
+    }
+    None
+}
+
+/// Answers admin requests for a form outside the router's scope, or one
+/// of its dead letters, as if it didn't exist.
+#[cfg(all(feature = "router", feature = "admin"))]
+pub(crate) async fn restrict_admin(
+    State(state): State<AnyFormState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(scope) = &state.config.only_forms else {
+        return next.run(req).await;
+    };
+    let served = |form: Option<form::Model>| form.map_or(true, |f| scope.contains(&f.slug));
+
+    let rejected = match target(req.uri().path()) {
+        Some(Target::Form(id)) => match state.repository.find_form(id).await {
+            Ok(form) => (!served(form)).then(|| FormError::NotFound(id.to_string())),
+            Err(e) => Some(e),
+        },
+        Some(Target::DeadLetter(id)) => {
+            match state.repository.find_dead_letter(id).await {
+                Ok(Some(letter)) => {
+                    match state.repository.find_form(letter.form_id).await {
+                        Ok(form) => {
+                            (!served(form)).then(|| FormError::DeadLetterNotFound(id.to_string()))
+                        }
+                        Err(e) => Some(e),
+                    }
+                }
+                Ok(None) => None,
+                Err(e) => Some(e),
+            }
+        }
+        None => None,
+    };
+
+    match rejected {
+        Some(error) => error.into_response(),
+        None => next.run(req).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "router", feature = "admin"))]
+    fn test_admin_targets() {
+        let id = Uuid::new_v4();
+        assert_eq!(target(&format!("/admin/forms/{id}")), Some(Target::Form(id)));
+        assert_eq!(
+            target(&format!("/admin/forms/{id}/submissions/{}", Uuid::new_v4())),
+            Some(Target::Form(id))
+        );
+        assert_eq!(
+            target(&format!("/admin/dead-letters/{id}/replay")),
+            Some(Target::DeadLetter(id))
+        );
+        assert_eq!(target("/admin/forms/sync"), None);
+        assert_eq!(target("/admin/forms"), None);
+        assert_eq!(target("/forms/contact"), None);
+    }
+
+    #[test]
+    fn test_check_slug() {
+        let scope = FormScope::new(["contact"]);
+        assert!(scope.check_slug("contact").is_ok());
+        assert!(matches!(scope.check_slug("other"), Err(FormError::InvalidData(_))));
+    }
+}
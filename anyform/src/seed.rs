@@ -1,29 +1,378 @@
-//! Database seeding for example forms.
+//! Database seeding.
+//!
+//! A [`Seeder`] collects form definitions from any number of
+//! [`SeedProvider`]s (forms built in code, a directory of JSON files, or
+//! JSON embedded in the binary) and writes them to the database. Each
+//! seeded form's checksum is recorded in `af_seeds`, so running a seeder
+//! again only touches forms whose definition changed, and forms that were
+//! created some other way are left alone.
+//!
+//! The example forms (contact, feedback, quiz, onboarding and a field
+//! showcase) are available through [`Seeder::examples`].
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use anyform::seed::{EmbeddedJson, Seeder};
+//!
+//! let report = Seeder::new()
+//!     .provider(EmbeddedJson::new(&[
+//!         ("signup.json", include_str!("../seeds/signup.json")),
+//!     ]))
+//!     .dir("./seeds/local")
+//!     .run(&db)
+//!     .await?;
+//! println!("{report}");
+//! ```
 
-use sea_orm::DatabaseConnection;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
+use sea_orm::{sea_query::OnConflict, DatabaseConnection, EntityTrait, Set};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::condition::ConditionRule;
+use crate::entities::seed::{self, Entity as SeedEntity};
 use crate::error::FormError;
 use crate::schema::{FormSettings, UiOptions, ValidationRules};
 use crate::services::{
     CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FormBuilder,
 };
 
+/// A source of form definitions for a [`Seeder`].
+pub trait SeedProvider: Send + Sync {
+    /// Returns the forms to seed.
+    fn forms(&self) -> Result<Vec<CreateFormInput>, FormError>;
+}
+
+impl SeedProvider for CreateFormInput {
+    fn forms(&self) -> Result<Vec<CreateFormInput>, FormError> {
+        Ok(vec![self.clone()])
+    }
+}
+
+impl SeedProvider for Vec<CreateFormInput> {
+    fn forms(&self) -> Result<Vec<CreateFormInput>, FormError> {
+        Ok(self.clone())
+    }
+}
+
+/// Seeds every `*.json` file in a directory, in file name order.
+///
+/// Each file holds one form definition (as accepted by
+/// `anyform form create`) or an array of them.
+#[derive(Debug, Clone)]
+pub struct JsonDir {
+    path: PathBuf,
+}
+
+impl JsonDir {
+    /// Reads forms from `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl SeedProvider for JsonDir {
+    fn forms(&self) -> Result<Vec<CreateFormInput>, FormError> {
+        let read_error = |e: std::io::Error| {
+            FormError::InvalidData(format!("Cannot read {}: {e}", self.path.display()))
+        };
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.path)
+            .map_err(read_error)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()
+            .map_err(read_error)?;
+        paths.retain(|p| p.extension().is_some_and(|ext| ext == "json"));
+        paths.sort();
+
+        let mut forms = Vec::new();
+        for path in paths {
+            let content = std::fs::read_to_string(&path).map_err(|e| {
+                FormError::InvalidData(format!("Cannot read {}: {e}", path.display()))
+            })?;
+            forms.extend(parse_seed_file(&path, &content)?);
+        }
+        Ok(forms)
+    }
+}
+
+/// Seeds JSON files compiled into the binary, e.g. with `include_str!`.
+///
+/// Takes `(file name, contents)` pairs; the names are only used in error
+/// messages.
+#[derive(Debug, Clone, Copy)]
+pub struct EmbeddedJson {
+    files: &'static [(&'static str, &'static str)],
+}
+
+impl EmbeddedJson {
+    /// Reads forms from the given files.
+    pub const fn new(files: &'static [(&'static str, &'static str)]) -> Self {
+        Self { files }
+    }
+}
+
+impl SeedProvider for EmbeddedJson {
+    fn forms(&self) -> Result<Vec<CreateFormInput>, FormError> {
+        let mut forms = Vec::new();
+        for (name, content) in self.files {
+            forms.extend(parse_seed_file(Path::new(name), content)?);
+        }
+        Ok(forms)
+    }
+}
+
+/// A seed file holds one form or several.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SeedFile {
+    One(Box<CreateFormInput>),
+    Many(Vec<CreateFormInput>),
+}
+
+fn parse_seed_file(path: &Path, content: &str) -> Result<Vec<CreateFormInput>, FormError> {
+    match serde_json::from_str(content) {
+        Ok(SeedFile::One(form)) => Ok(vec![*form]),
+        Ok(SeedFile::Many(forms)) => Ok(forms),
+        Err(e) => Err(FormError::InvalidData(format!(
+            "Invalid form JSON in {}: {e}",
+            path.display()
+        ))),
+    }
+}
+
+/// What a [`Seeder`] run did, by form slug.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    /// Forms that did not exist and were created.
+    pub created: Vec<String>,
+    /// Previously seeded forms whose definition changed.
+    pub updated: Vec<String>,
+    /// Previously seeded forms whose definition is unchanged.
+    pub unchanged: Vec<String>,
+    /// Forms that already existed but were not created by a seeder; they
+    /// are never overwritten.
+    pub skipped: Vec<String>,
+}
+
+impl fmt::Display for SeedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let groups = [
+            ("Created", &self.created),
+            ("Updated", &self.updated),
+            ("Unchanged", &self.unchanged),
+            ("Skipped (not seeded)", &self.skipped),
+        ];
+        let mut first = true;
+        for (label, slugs) in groups {
+            if slugs.is_empty() {
+                continue;
+            }
+            if !first {
+                writeln!(f)?;
+            }
+            write!(f, "{label}: {}", slugs.join(", "))?;
+            first = false;
+        }
+        if first {
+            write!(f, "Nothing to seed.")?;
+        }
+        Ok(())
+    }
+}
+
+/// A registry of seed providers.
+#[derive(Default)]
+pub struct Seeder {
+    providers: Vec<Box<dyn SeedProvider>>,
+}
+
+impl Seeder {
+    /// Creates an empty seeder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a seeder for the example forms.
+    #[must_use]
+    pub fn examples() -> Self {
+        Self::new()
+            .form(contact_form())
+            .form(feedback_form())
+            .form(quiz_form())
+            .form(onboarding_form())
+            .form(showcase_form())
+    }
+
+    /// Adds a provider.
+    #[must_use]
+    pub fn provider(mut self, provider: impl SeedProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// Adds a single form.
+    #[must_use]
+    pub fn form(self, form: CreateFormInput) -> Self {
+        self.provider(form)
+    }
+
+    /// Adds every `*.json` file in a directory (see [`JsonDir`]).
+    #[must_use]
+    pub fn dir(self, path: impl Into<PathBuf>) -> Self {
+        self.provider(JsonDir::new(path))
+    }
+
+    /// Collects the forms of every provider, in registration order.
+    ///
+    /// Fails if a provider fails or two forms share a slug.
+    pub fn forms(&self) -> Result<Vec<CreateFormInput>, FormError> {
+        let mut forms = Vec::new();
+        let mut slugs = HashSet::new();
+        for provider in &self.providers {
+            for form in provider.forms()? {
+                if !slugs.insert(form.slug.clone()) {
+                    return Err(FormError::InvalidData(format!(
+                        "Form '{}' is seeded more than once",
+                        form.slug
+                    )));
+                }
+                forms.push(form);
+            }
+        }
+        Ok(forms)
+    }
+
+    /// Writes the forms to the database.
+    ///
+    /// Forms that don't exist are created. Forms this or an earlier seeder
+    /// wrote are updated if their definition changed and skipped otherwise;
+    /// forms created some other way are never touched.
+    pub async fn run(&self, db: &DatabaseConnection) -> Result<SeedReport, FormError> {
+        let mut report = SeedReport::default();
+        for form in self.forms()? {
+            let slug = form.slug.clone();
+            let checksum = checksum(&form)?;
+            let record = SeedEntity::find_by_id(slug.clone()).one(db).await?;
+
+            match (FormBuilder::find_by_slug(db, &slug).await?, record) {
+                (None, _) => {
+                    FormBuilder::create(db, form).await?;
+                    report.created.push(slug.clone());
+                }
+                (Some(_), Some(record)) if record.checksum == checksum => {
+                    report.unchanged.push(slug);
+                    continue;
+                }
+                (Some(existing), Some(_)) => {
+                    FormBuilder::update(db, existing.id, form).await?;
+                    report.updated.push(slug.clone());
+                }
+                (Some(_), None) => {
+                    report.skipped.push(slug);
+                    continue;
+                }
+            }
+            record_seed(db, slug, checksum).await?;
+        }
+        Ok(report)
+    }
+
+    /// Permanently deletes the seeder's forms, with their submissions, and
+    /// forgets that they were seeded.
+    ///
+    /// Returns the number of forms deleted.
+    pub async fn clear(&self, db: &DatabaseConnection) -> Result<usize, FormError> {
+        let mut deleted = 0;
+        for form in self.forms()? {
+            if let Some(existing) = FormBuilder::find_by_slug(db, &form.slug).await? {
+                FormBuilder::hard_delete(db, existing.id).await?;
+                deleted += 1;
+            }
+            SeedEntity::delete_by_id(form.slug).exec(db).await?;
+        }
+        Ok(deleted)
+    }
+}
+
+/// Returns the SHA-256 of a form definition, with object keys sorted so
+/// that it doesn't depend on map ordering.
+fn checksum(form: &CreateFormInput) -> Result<String, FormError> {
+    fn sorted(value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut entries: Vec<_> = map.into_iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                entries.into_iter().map(|(k, v)| (k, sorted(v))).collect()
+            }
+            serde_json::Value::Array(items) => items.into_iter().map(sorted).collect(),
+            other => other,
+        }
+    }
+
+    let value = serde_json::to_value(form).map_err(|e| FormError::InvalidData(e.to_string()))?;
+    let digest = Sha256::digest(sorted(value).to_string().as_bytes());
+    Ok(format!("{digest:x}"))
+}
+
+async fn record_seed(
+    db: &DatabaseConnection,
+    slug: String,
+    checksum: String,
+) -> Result<(), FormError> {
+    let record = seed::ActiveModel {
+        slug: Set(slug),
+        checksum: Set(checksum),
+        seeded_at: Set(chrono::Utc::now().fixed_offset()),
+    };
+    SeedEntity::insert(record)
+        .on_conflict(
+            OnConflict::column(seed::Column::Slug)
+                .update_columns([seed::Column::Checksum, seed::Column::SeededAt])
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
 /// Seeds all example forms into the database.
 pub async fn seed_all(db: &DatabaseConnection) -> Result<(), FormError> {
-    seed_contact_form(db).await?;
-    seed_feedback_form(db).await?;
-    seed_quiz_form(db).await?;
+    Seeder::examples().run(db).await?;
     Ok(())
 }
 
 /// Seeds only the contact form.
 pub async fn seed_contact_form(db: &DatabaseConnection) -> Result<(), FormError> {
-    // Check if already exists
-    if FormBuilder::find_by_slug(db, "contact").await?.is_some() {
-        return Ok(());
-    }
+    Seeder::new().form(contact_form()).run(db).await?;
+    Ok(())
+}
 
-    let form = CreateFormInput::new("Contact Form", "contact")
+/// Seeds only the feedback survey form.
+pub async fn seed_feedback_form(db: &DatabaseConnection) -> Result<(), FormError> {
+    Seeder::new().form(feedback_form()).run(db).await?;
+    Ok(())
+}
+
+/// Seeds only the quiz example form.
+pub async fn seed_quiz_form(db: &DatabaseConnection) -> Result<(), FormError> {
+    Seeder::new().form(quiz_form()).run(db).await?;
+    Ok(())
+}
+
+/// Seeds only the multi-step onboarding form.
+pub async fn seed_onboarding_form(db: &DatabaseConnection) -> Result<(), FormError> {
+    Seeder::new().form(onboarding_form()).run(db).await?;
+    Ok(())
+}
+
+/// The example contact form.
+pub fn contact_form() -> CreateFormInput {
+    CreateFormInput::new("Contact Form", "contact")
         .description("Get in touch with us")
         .settings(
             FormSettings::new()
@@ -56,20 +405,12 @@ pub async fn seed_contact_form(db: &DatabaseConnection) -> Result<(), FormError>
                     ])
                     .default_value("email"),
             ]),
-        );
-
-    FormBuilder::create(db, form).await?;
-    Ok(())
+        )
 }
 
-/// Seeds only the feedback survey form.
-pub async fn seed_feedback_form(db: &DatabaseConnection) -> Result<(), FormError> {
-    // Check if already exists
-    if FormBuilder::find_by_slug(db, "feedback").await?.is_some() {
-        return Ok(());
-    }
-
-    let form = CreateFormInput::new("Feedback Survey", "feedback")
+/// The example feedback survey.
+pub fn feedback_form() -> CreateFormInput {
+    CreateFormInput::new("Feedback Survey", "feedback")
         .description("Help us improve by sharing your feedback")
         .settings(
             FormSettings::new()
@@ -110,20 +451,12 @@ pub async fn seed_feedback_form(db: &DatabaseConnection) -> Result<(), FormError
                     .placeholder("Share your suggestions...")
                     .ui(UiOptions::new().rows(4)),
             ]),
-        );
-
-    FormBuilder::create(db, form).await?;
-    Ok(())
+        )
 }
 
-/// Seeds only the quiz example form.
-pub async fn seed_quiz_form(db: &DatabaseConnection) -> Result<(), FormError> {
-    // Check if already exists
-    if FormBuilder::find_by_slug(db, "quiz").await?.is_some() {
-        return Ok(());
-    }
-
-    let form = CreateFormInput::new("Knowledge Quiz", "quiz")
+/// The example quiz.
+pub fn quiz_form() -> CreateFormInput {
+    CreateFormInput::new("Knowledge Quiz", "quiz")
         .description("Test your knowledge!")
         .settings(
             FormSettings::new()
@@ -165,19 +498,151 @@ pub async fn seed_quiz_form(db: &DatabaseConnection) -> Result<(), FormError> {
                         CreateOptionInput::new("22", "22"),
                     ]),
             ]),
-        );
+        )
+}
 
-    FormBuilder::create(db, form).await?;
-    Ok(())
+/// The example multi-step onboarding form.
+///
+/// Exercises step navigation, a conditional field, a conditional step and a
+/// checkbox group.
+pub fn onboarding_form() -> CreateFormInput {
+    CreateFormInput::new("Onboarding", "onboarding")
+        .description("Tell us a little about yourself")
+        .settings(
+            FormSettings::new()
+                .success_message("Welcome aboard!")
+                .submit_label("Finish")
+                .show_progress(true),
+        )
+        .step(
+            CreateStepInput::new("About You").fields(vec![
+                CreateFieldInput::new("name", "Your Name", "text")
+                    .required()
+                    .validation(ValidationRules::new().min_length(2).max_length(100)),
+                CreateFieldInput::new("role", "Your Role", "radio")
+                    .required()
+                    .options(vec![
+                        CreateOptionInput::new("Developer", "developer"),
+                        CreateOptionInput::new("Designer", "designer"),
+                        CreateOptionInput::new("Something else", "other"),
+                    ]),
+                CreateFieldInput::new("role_details", "Describe your role", "text")
+                    .ui(UiOptions::new().condition(ConditionRule::eq("role", "other"))),
+            ]),
+        )
+        .step(
+            CreateStepInput::new("Your Stack")
+                .condition(r#"{"field": "role", "op": "eq", "value": "developer"}"#)
+                .fields(vec![
+                    CreateFieldInput::new("languages", "Languages you use", "multi_select")
+                        .required()
+                        .options(vec![
+                            CreateOptionInput::new("Rust", "rust"),
+                            CreateOptionInput::new("TypeScript", "typescript"),
+                            CreateOptionInput::new("Python", "python"),
+                            CreateOptionInput::new("Go", "go"),
+                        ])
+                        .help_text("Select all that apply"),
+                ]),
+        )
+        .step(
+            CreateStepInput::new("Stay in Touch").fields(vec![
+                CreateFieldInput::new("email", "Email Address", "email")
+                    .required()
+                    .placeholder("you@example.com"),
+                CreateFieldInput::new("newsletter", "Send me product updates", "checkbox"),
+            ]),
+        )
+}
+
+/// The example field showcase.
+///
+/// One field of every type the built-in renderers support (file uploads
+/// and matrices need a custom client), with validation, a conditional
+/// field and several steps.
+pub fn showcase_form() -> CreateFormInput {
+    CreateFormInput::new("Field Showcase", "showcase")
+        .description("One of every field type")
+        .settings(
+            FormSettings::new()
+                .success_message("Thanks for trying anyform!")
+                .submit_label("Submit")
+                .show_progress(true),
+        )
+        .step(
+            CreateStepInput::new("Text").fields(vec![
+                CreateFieldInput::new("text_heading", "Text and numbers", "heading"),
+                CreateFieldInput::new("text_intro", "Intro", "paragraph")
+                    .help_text("Inputs for free text, contact details and numbers."),
+                CreateFieldInput::new("username", "Username", "text")
+                    .required()
+                    .placeholder("ada_l")
+                    .validation(
+                        ValidationRules::new()
+                            .min_length(3)
+                            .pattern("^[a-z0-9_]+$")
+                            .pattern_message("Lowercase letters, digits and underscores only"),
+                    ),
+                CreateFieldInput::new("email", "Email", "email").required(),
+                CreateFieldInput::new("website", "Website", "url")
+                    .placeholder("https://example.com"),
+                CreateFieldInput::new("phone", "Phone", "tel"),
+                CreateFieldInput::new("age", "Age", "number")
+                    .validation(ValidationRules::new().min(13.0).max(120.0)),
+                CreateFieldInput::new("bio", "Bio", "textarea")
+                    .validation(ValidationRules::new().max_length(500))
+                    .ui(UiOptions::new().rows(3)),
+            ]),
+        )
+        .step(
+            CreateStepInput::new("Choices").fields(vec![
+                CreateFieldInput::new("plan", "Plan", "select")
+                    .required()
+                    .options(vec![
+                        CreateOptionInput::new("Free", "free"),
+                        CreateOptionInput::new("Pro", "pro"),
+                        CreateOptionInput::new("Team", "team"),
+                    ]),
+                CreateFieldInput::new("interests", "Interests", "multi_select").options(vec![
+                    CreateOptionInput::new("Surveys", "surveys"),
+                    CreateOptionInput::new("Quizzes", "quizzes"),
+                    CreateOptionInput::new("Event signups", "events"),
+                ]),
+                CreateFieldInput::new("source", "How did you hear about us?", "radio").options(
+                    vec![
+                        CreateOptionInput::new("Search", "search"),
+                        CreateOptionInput::new("A friend", "friend"),
+                        CreateOptionInput::new("Somewhere else", "other"),
+                    ],
+                ),
+                CreateFieldInput::new("source_other", "Where?", "text")
+                    .ui(UiOptions::new().condition(ConditionRule::eq("source", "other"))),
+                CreateFieldInput::new("terms", "I accept the terms", "checkbox").required(),
+            ]),
+        )
+        .step(
+            CreateStepInput::new("Dates and Ratings").fields(vec![
+                CreateFieldInput::new("start_date", "Start date", "date"),
+                CreateFieldInput::new("meeting", "Meeting", "datetime"),
+                CreateFieldInput::new("call_time", "Best time to call", "time"),
+                CreateFieldInput::new("stars", "Rating", "rating").ui(UiOptions {
+                    max_rating: Some(5),
+                    ..Default::default()
+                }),
+                CreateFieldInput::new("effort", "How easy was this?", "scale").ui(UiOptions {
+                    scale_min: Some(1),
+                    scale_max: Some(7),
+                    ..Default::default()
+                }),
+                CreateFieldInput::new("recommend", "How likely are you to recommend us?", "nps"),
+                CreateFieldInput::new("campaign", "Campaign", "hidden").default_value("gallery"),
+            ]),
+        )
 }
 
 /// Clears all seeded example forms.
 pub async fn clear_seeded_forms(db: &DatabaseConnection) -> Result<(), FormError> {
-    for slug in ["contact", "feedback", "quiz"] {
-        if let Some(form) = FormBuilder::find_by_slug(db, slug).await? {
-            FormBuilder::hard_delete(db, form.id).await?;
-        }
-    }
+    Seeder::examples().clear(db).await?;
     Ok(())
 }
 
@@ -198,4 +663,22 @@ mod tests {
         assert_eq!(form.steps.len(), 1);
         assert_eq!(form.steps[0].fields.len(), 1);
     }
+
+    #[test]
+    fn test_checksum_tracks_definition() {
+        let a = checksum(&contact_form()).unwrap();
+        assert_eq!(a, checksum(&contact_form()).unwrap());
+        assert_ne!(a, checksum(&contact_form().description("Changed")).unwrap());
+    }
+
+    #[test]
+    fn test_seed_file_holds_one_form_or_many() {
+        let one = r#"{"name": "A", "slug": "a"}"#;
+        let many = r#"[{"name": "A", "slug": "a"}, {"name": "B", "slug": "b"}]"#;
+        assert_eq!(parse_seed_file(Path::new("one.json"), one).unwrap().len(), 1);
+        assert_eq!(parse_seed_file(Path::new("many.json"), many).unwrap().len(), 2);
+
+        let err = parse_seed_file(Path::new("bad.json"), "{}").unwrap_err();
+        assert!(err.to_string().contains("bad.json"));
+    }
 }
@@ -0,0 +1,552 @@
+//! Submission analytics computed in the database.
+//!
+//! [`Analytics`] summarizes a form's submissions with SQL aggregates over
+//! the JSON `data` column, so the cost of a report doesn't grow with the
+//! rows loaded into memory. Answers are read per backend (`json_extract`
+//! on SQLite, `->>` on PostgreSQL, `JSON_EXTRACT` on MySQL); numeric
+//! answers stored as text, as HTML forms submit them, are cast in SQL.
+//! Free-text answers are summed up from the sentiment and tags a
+//! [`TextAnalyzer`](crate::analysis::TextAnalyzer) stored in each
+//! submission's `text_analysis` metadata.
+
+use std::collections::HashMap;
+
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult, Statement};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::analysis::SENTIMENT_THRESHOLD;
+use crate::entities::{
+    field::Model as Field, field_option::Entity as FieldOptionEntity, form::Model as Form,
+};
+use crate::error::FormError;
+use crate::schema::ValueType;
+use crate::services::capacity::form_fields;
+use crate::services::OptionCount;
+
+/// Computes submission analytics.
+pub struct Analytics;
+
+/// Submission analytics for a form.
+///
+/// Previews and deleted submissions are never counted. Scores and answers
+/// are aggregated over accepted, completed submissions; waitlisted ones
+/// are left out.
+#[derive(Debug, Clone, Serialize)]
+pub struct FormAnalytics {
+    pub form_id: Uuid,
+    /// Submissions started, including incomplete ones.
+    pub submissions: u64,
+    /// Submissions completed, including waitlisted ones.
+    pub completed: u64,
+    /// Submissions started step by step and not completed yet.
+    pub in_progress: u64,
+    /// Submissions in progress with at least one step saved: responses
+    /// abandoned (or not yet finished) part way, e.g. by forms that
+    /// [save each step](crate::schema::FormSettings::save_each_step).
+    pub partial: u64,
+    pub waitlisted: u64,
+    /// Accepted, completed submissions: the respondents answers are
+    /// aggregated over.
+    pub respondents: u64,
+    /// Share of started submissions that were completed, from 0 to 1.
+    pub completion_rate: Option<f64>,
+    /// Whether the averages and field aggregates are withheld because too
+    /// few respondents took part.
+    pub suppressed: bool,
+    /// Mean quiz score, when submissions are scored.
+    pub average_score: Option<f64>,
+    /// Aggregates per select, radio and numeric field, and per analyzed
+    /// free-text field, in form order, except fields excluded from
+    /// analytics by their classification.
+    pub fields: Vec<FieldAnalytics>,
+}
+
+/// Aggregated answers to one field.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldAnalytics {
+    pub name: String,
+    pub label: String,
+    pub field_type: String,
+    /// Respondents who answered the field.
+    pub responses: u64,
+    /// How often each option was chosen (select and radio fields), in
+    /// option order, followed by answers matching no current option.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<OptionCount>>,
+    /// Range and mean of the answers (number, rating, scale and NPS
+    /// fields), when there are any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub numbers: Option<NumberSummary>,
+    /// Net Promoter Score breakdown (NPS fields).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nps: Option<NpsBreakdown>,
+    /// Sentiment and themes of the analyzed answers (textarea fields).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<TextSummary>,
+}
+
+/// Range and mean of a numeric field's answers.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct NumberSummary {
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+}
+
+/// Sentiment and themes of a free-text field's analyzed answers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TextSummary {
+    /// Answers reading positive, per
+    /// [`SENTIMENT_THRESHOLD`](crate::analysis::SENTIMENT_THRESHOLD).
+    pub positive: u64,
+    pub neutral: u64,
+    pub negative: u64,
+    /// Mean sentiment, from -1 to 1; `None` without scored answers.
+    pub average_sentiment: Option<f64>,
+    /// How many answers each tag was given, most frequent first.
+    pub tags: Vec<TagCount>,
+}
+
+/// How many answers a tag was given.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: u64,
+}
+
+/// Net Promoter Score breakdown of answers from 0 to 10.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct NpsBreakdown {
+    /// Answers from 0 to 6.
+    pub detractors: u64,
+    /// Answers of 7 or 8.
+    pub passives: u64,
+    /// Answers of 9 or 10.
+    pub promoters: u64,
+    /// Percentage of promoters minus percentage of detractors, from -100
+    /// to 100; `None` without answers.
+    pub score: Option<f64>,
+}
+
+impl NpsBreakdown {
+    fn new(detractors: u64, passives: u64, promoters: u64) -> Self {
+        let total = detractors + passives + promoters;
+        let score = (total > 0)
+            .then(|| (promoters as f64 - detractors as f64) * 100.0 / total as f64);
+        Self {
+            detractors,
+            passives,
+            promoters,
+            score,
+        }
+    }
+}
+
+/// Numbers as answers are validated: optionally signed decimals with an
+/// optional exponent.
+const NUMBER_PATTERN: &str = "^[+-]?([0-9]+[.]?[0-9]*|[.][0-9]+)([eE][+-]?[0-9]+)?$";
+
+/// Submissions whose answers are aggregated, for `$2` the form ID.
+const RESPONDENTS: &str = "form_id = $2 AND deleted_at IS NULL \
+    AND status = 'submitted' AND completed_at IS NOT NULL";
+
+impl Analytics {
+    /// Computes a form's submission analytics.
+    ///
+    /// Forms with [`results_privacy`](crate::schema::FormSettings::results_privacy)
+    /// get averages and field aggregates only with enough respondents, and
+    /// counts reported accordingly.
+    pub async fn form(db: &DatabaseConnection, form: &Form) -> Result<FormAnalytics, FormError> {
+        let backend = db.get_database_backend();
+        let sql = format!(
+            "SELECT COUNT(*) AS submissions, \
+             COUNT(completed_at) AS completed, \
+             COUNT(CASE WHEN status = 'in_progress' THEN 1 END) AS in_progress, \
+             COUNT(CASE WHEN status = 'in_progress' AND {answered} THEN 1 END) AS partial, \
+             COUNT(CASE WHEN status = 'waitlisted' THEN 1 END) AS waitlisted, \
+             COUNT(CASE WHEN status = 'submitted' AND completed_at IS NOT NULL \
+                 THEN 1 END) AS respondents, \
+             AVG(CASE WHEN status = 'submitted' AND completed_at IS NOT NULL \
+                 THEN CAST(score AS {double}) END) AS average_score \
+             FROM af_submissions \
+             WHERE form_id = $1 AND deleted_at IS NULL AND status <> 'preview'",
+            double = double(backend),
+            answered = answered(backend),
+        );
+        let row = query_one(db, &sql, vec![form.id.into()]).await?;
+        let count = |column: &str| -> Result<u64, FormError> {
+            Ok(u64::try_from(row.try_get::<i64>("", column)?).unwrap_or_default())
+        };
+        let (submissions, completed) = (count("submissions")?, count("completed")?);
+        let respondents = count("respondents")?;
+
+        let mut analytics = FormAnalytics {
+            form_id: form.id,
+            submissions,
+            completed,
+            in_progress: count("in_progress")?,
+            partial: count("partial")?,
+            waitlisted: count("waitlisted")?,
+            respondents,
+            completion_rate: (submissions > 0).then(|| completed as f64 / submissions as f64),
+            suppressed: false,
+            average_score: row.try_get("", "average_score")?,
+            fields: Vec::new(),
+        };
+        if let Some(privacy) = form.settings().results_privacy {
+            for count in [
+                &mut analytics.submissions,
+                &mut analytics.completed,
+                &mut analytics.in_progress,
+                &mut analytics.partial,
+                &mut analytics.waitlisted,
+                &mut analytics.respondents,
+            ] {
+                *count = privacy.report_count(*count);
+            }
+            if privacy.is_suppressed(respondents) {
+                analytics.suppressed = true;
+                analytics.average_score = None;
+                return Ok(analytics);
+            }
+        }
+
+        for field in form_fields(db, form.id).await? {
+            if !field.classification().in_analytics() {
+                continue;
+            }
+            let summary = match field.value_type() {
+                Some(ValueType::Select | ValueType::Radio | ValueType::ImageChoice) => {
+                    options(db, form.id, &field).await?
+                }
+                Some(t) if t.is_numeric() => numbers(db, form.id, &field, t).await?,
+                Some(ValueType::Textarea) => match text(db, form.id, &field).await? {
+                    Some(summary) => summary,
+                    None => continue,
+                },
+                _ => continue,
+            };
+            analytics.fields.push(summary);
+        }
+
+        Ok(analytics)
+    }
+}
+
+/// Counts the answers to a select or radio field per option.
+async fn options(
+    db: &DatabaseConnection,
+    form_id: Uuid,
+    field: &Field,
+) -> Result<FieldAnalytics, FormError> {
+    let backend = db.get_database_backend();
+    let sql = format!(
+        "SELECT answer, COUNT(*) AS count FROM ({answers}) answers \
+         WHERE answer IS NOT NULL AND answer <> '' GROUP BY answer",
+        answers = answers(backend),
+    );
+    let rows = query_all(db, &sql, vec![path(backend, &field.name).into(), form_id.into()]).await?;
+    let mut counts = HashMap::new();
+    for row in rows {
+        let count: i64 = row.try_get("", "count")?;
+        counts.insert(row.try_get::<String>("", "answer")?, u64::try_from(count).unwrap_or(0));
+    }
+    let responses = counts.values().sum();
+
+    let mut options: Vec<OptionCount> = FieldOptionEntity::find_by_field(db, field.id)
+        .await?
+        .into_iter()
+        .map(|o| OptionCount {
+            count: counts.remove(&o.value).unwrap_or(0),
+            value: o.value,
+            label: o.label,
+        })
+        .collect();
+    let mut unknown: Vec<OptionCount> = counts
+        .into_iter()
+        .map(|(value, count)| OptionCount {
+            label: value.clone(),
+            value,
+            count,
+        })
+        .collect();
+    unknown.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    options.extend(unknown);
+
+    Ok(FieldAnalytics {
+        name: field.name.clone(),
+        label: field.label.clone(),
+        field_type: field.field_type.clone(),
+        responses,
+        options: Some(options),
+        numbers: None,
+        nps: None,
+        text: None,
+    })
+}
+
+/// Summarizes the answers to a numeric field.
+async fn numbers(
+    db: &DatabaseConnection,
+    form_id: Uuid,
+    field: &Field,
+    value_type: ValueType,
+) -> Result<FieldAnalytics, FormError> {
+    let backend = db.get_database_backend();
+    let number = match backend {
+        DatabaseBackend::Sqlite => "CASE WHEN answer GLOB '*[0-9]*' \
+            AND answer NOT GLOB '*[^0-9.eE+-]*' THEN CAST(answer AS REAL) END"
+            .to_string(),
+        DatabaseBackend::Postgres => format!(
+            "CASE WHEN answer ~ '{NUMBER_PATTERN}' THEN CAST(answer AS DOUBLE PRECISION) END"
+        ),
+        DatabaseBackend::MySql => {
+            format!("CASE WHEN answer REGEXP '{NUMBER_PATTERN}' THEN CAST(answer AS DOUBLE) END")
+        }
+    };
+    let sql = format!(
+        "SELECT COUNT(n) AS responses, MIN(n) AS min, AVG(n) AS avg, MAX(n) AS max, \
+         COUNT(CASE WHEN n >= 0 AND n < 7 THEN 1 END) AS detractors, \
+         COUNT(CASE WHEN n >= 7 AND n < 9 THEN 1 END) AS passives, \
+         COUNT(CASE WHEN n >= 9 AND n <= 10 THEN 1 END) AS promoters \
+         FROM (SELECT {number} AS n FROM ({answers}) answers) numbers",
+        answers = answers(backend),
+    );
+    let row = query_one(db, &sql, vec![path(backend, &field.name).into(), form_id.into()]).await?;
+    let count = |column: &str| -> Result<u64, FormError> {
+        Ok(u64::try_from(row.try_get::<i64>("", column)?).unwrap_or_default())
+    };
+
+    let numbers = match (
+        row.try_get::<Option<f64>>("", "min")?,
+        row.try_get::<Option<f64>>("", "avg")?,
+        row.try_get::<Option<f64>>("", "max")?,
+    ) {
+        (Some(min), Some(avg), Some(max)) => Some(NumberSummary { min, avg, max }),
+        _ => None,
+    };
+    let nps = if value_type == ValueType::Nps {
+        Some(NpsBreakdown::new(count("detractors")?, count("passives")?, count("promoters")?))
+    } else {
+        None
+    };
+
+    Ok(FieldAnalytics {
+        name: field.name.clone(),
+        label: field.label.clone(),
+        field_type: field.field_type.clone(),
+        responses: count("responses")?,
+        options: None,
+        numbers,
+        nps,
+        text: None,
+    })
+}
+
+/// Sums up the analyzed answers to a free-text field; `None` when none
+/// were analyzed.
+async fn text(
+    db: &DatabaseConnection,
+    form_id: Uuid,
+    field: &Field,
+) -> Result<Option<FieldAnalytics>, FormError> {
+    let backend = db.get_database_backend();
+    let (analysis, sentiment) = match backend {
+        DatabaseBackend::Sqlite => (
+            "json_extract(metadata, $1)",
+            "CAST(json_extract(metadata, $1 || '.sentiment') AS REAL)",
+        ),
+        DatabaseBackend::Postgres => (
+            "metadata->'text_analysis'->$1",
+            "CAST(metadata->'text_analysis'->$1->>'sentiment' AS DOUBLE PRECISION)",
+        ),
+        DatabaseBackend::MySql => (
+            "JSON_EXTRACT(metadata, $1)",
+            "CAST(JSON_EXTRACT(metadata, CONCAT($1, '.sentiment')) AS DOUBLE)",
+        ),
+    };
+    let sql = format!(
+        "SELECT COUNT(*) AS responses, COUNT(s) AS scored, AVG(s) AS average, \
+         COUNT(CASE WHEN s >= {SENTIMENT_THRESHOLD} THEN 1 END) AS positive, \
+         COUNT(CASE WHEN s <= -{SENTIMENT_THRESHOLD} THEN 1 END) AS negative \
+         FROM (SELECT {sentiment} AS s FROM af_submissions \
+         WHERE {RESPONDENTS} AND {analysis} IS NOT NULL) analyzed",
+    );
+    let path = analysis_path(backend, &field.name);
+    let row = query_one(db, &sql, vec![path.clone().into(), form_id.into()]).await?;
+    let count = |column: &str| -> Result<u64, FormError> {
+        Ok(u64::try_from(row.try_get::<i64>("", column)?).unwrap_or_default())
+    };
+    let responses = count("responses")?;
+    if responses == 0 {
+        return Ok(None);
+    }
+    let (positive, negative) = (count("positive")?, count("negative")?);
+
+    let sql = match backend {
+        DatabaseBackend::Sqlite => format!(
+            "SELECT tags.value AS tag, COUNT(*) AS count \
+             FROM af_submissions, json_each(metadata, $1 || '.tags') tags \
+             WHERE {RESPONDENTS} GROUP BY tags.value"
+        ),
+        DatabaseBackend::Postgres => format!(
+            "SELECT tags.tag, COUNT(*) AS count FROM af_submissions, \
+             json_array_elements_text(metadata->'text_analysis'->$1->'tags') tags(tag) \
+             WHERE {RESPONDENTS} GROUP BY tags.tag"
+        ),
+        DatabaseBackend::MySql => format!(
+            "SELECT tags.tag, COUNT(*) AS count FROM af_submissions, \
+             JSON_TABLE(JSON_EXTRACT(metadata, CONCAT($1, '.tags')), '$[*]' \
+             COLUMNS (tag VARCHAR(255) PATH '$')) tags \
+             WHERE {RESPONDENTS} GROUP BY tags.tag"
+        ),
+    };
+    let mut tags = Vec::new();
+    for row in query_all(db, &sql, vec![path.into(), form_id.into()]).await? {
+        let count: i64 = row.try_get("", "count")?;
+        tags.push(TagCount {
+            tag: row.try_get("", "tag")?,
+            count: u64::try_from(count).unwrap_or(0),
+        });
+    }
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+
+    Ok(Some(FieldAnalytics {
+        name: field.name.clone(),
+        label: field.label.clone(),
+        field_type: field.field_type.clone(),
+        responses,
+        options: None,
+        numbers: None,
+        nps: None,
+        text: Some(TextSummary {
+            positive,
+            neutral: count("scored")?.saturating_sub(positive + negative),
+            negative,
+            average_sentiment: row.try_get("", "average")?,
+            tags,
+        }),
+    }))
+}
+
+/// Selects respondents' answers to the field at path `$1` as text, in an
+/// `answer` column.
+fn answers(backend: DatabaseBackend) -> String {
+    let answer = match backend {
+        DatabaseBackend::Sqlite => "CAST(json_extract(data, $1) AS TEXT)",
+        DatabaseBackend::Postgres => "data->>$1",
+        // JSON_UNQUOTE turns JSON nulls into 'null'
+        DatabaseBackend::MySql => {
+            "CASE WHEN JSON_TYPE(JSON_EXTRACT(data, $1)) <> 'NULL' \
+             THEN JSON_UNQUOTE(JSON_EXTRACT(data, $1)) END"
+        }
+    };
+    format!("SELECT {answer} AS answer FROM af_submissions WHERE {RESPONDENTS}")
+}
+
+/// Returns how `backend` addresses the field `name` in `data`.
+fn path(backend: DatabaseBackend, name: &str) -> String {
+    match backend {
+        DatabaseBackend::Postgres => name.to_string(),
+        DatabaseBackend::Sqlite | DatabaseBackend::MySql => format!("$.\"{name}\""),
+    }
+}
+
+/// Returns how `backend` addresses the text analysis of the field `name`
+/// in `metadata`.
+fn analysis_path(backend: DatabaseBackend, name: &str) -> String {
+    match backend {
+        DatabaseBackend::Postgres => name.to_string(),
+        DatabaseBackend::Sqlite | DatabaseBackend::MySql => {
+            format!("$.text_analysis.\"{name}\"")
+        }
+    }
+}
+
+/// Returns `backend`'s name for double-precision floats.
+fn double(backend: DatabaseBackend) -> &'static str {
+    match backend {
+        DatabaseBackend::Sqlite => "REAL",
+        DatabaseBackend::Postgres => "DOUBLE PRECISION",
+        DatabaseBackend::MySql => "DOUBLE",
+    }
+}
+
+/// Condition on a submission having answers: `data` isn't an empty object.
+fn answered(backend: DatabaseBackend) -> &'static str {
+    match backend {
+        DatabaseBackend::Sqlite => "data <> '{}'",
+        DatabaseBackend::Postgres => "CAST(data AS TEXT) <> '{}'",
+        DatabaseBackend::MySql => "JSON_LENGTH(data) > 0",
+    }
+}
+
+/// Builds a statement from SQL with PostgreSQL-style `$N` placeholders,
+/// which backends with `?` placeholders get in order of appearance.
+pub(crate) fn statement(backend: DatabaseBackend, sql: &str, values: Vec<sea_orm::Value>) -> Statement {
+    if backend == DatabaseBackend::Postgres {
+        return Statement::from_sql_and_values(backend, sql, values);
+    }
+
+    let mut converted = String::with_capacity(sql.len());
+    let mut bound = Vec::new();
+    let mut rest = sql;
+    while let Some(at) = rest.find('$') {
+        converted.push_str(&rest[..at]);
+        let digits = rest[at + 1..].bytes().take_while(u8::is_ascii_digit).count();
+        match rest[at + 1..at + 1 + digits].parse::<usize>() {
+            Ok(n) if (1..=values.len()).contains(&n) => {
+                converted.push('?');
+                bound.push(values[n - 1].clone());
+            }
+            _ => converted.push_str(&rest[at..=at + digits]),
+        }
+        rest = &rest[at + 1 + digits..];
+    }
+    converted.push_str(rest);
+    Statement::from_sql_and_values(backend, converted, bound)
+}
+
+async fn query_one(
+    db: &DatabaseConnection,
+    sql: &str,
+    values: Vec<sea_orm::Value>,
+) -> Result<QueryResult, FormError> {
+    db.query_one(statement(db.get_database_backend(), sql, values))
+        .await?
+        .ok_or_else(|| FormError::Database("aggregate query returned no row".into()))
+}
+
+async fn query_all(
+    db: &DatabaseConnection,
+    sql: &str,
+    values: Vec<sea_orm::Value>,
+) -> Result<Vec<QueryResult>, FormError> {
+    Ok(db.query_all(statement(db.get_database_backend(), sql, values)).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders_follow_the_backend() {
+        let sql = "SELECT $1, $2, $1 WHERE x ~ '^a$'";
+        let values = vec![1.into(), 2.into()];
+
+        let sqlite = statement(DatabaseBackend::Sqlite, sql, values.clone());
+        assert_eq!(sqlite.sql, "SELECT ?, ?, ? WHERE x ~ '^a$'");
+        assert_eq!(sqlite.values.unwrap().0, vec![1.into(), 2.into(), 1.into()]);
+
+        let postgres = statement(DatabaseBackend::Postgres, sql, values);
+        assert_eq!(postgres.sql, sql);
+    }
+
+    #[test]
+    fn test_nps_score() {
+        let nps = NpsBreakdown::new(1, 1, 2);
+        assert_eq!(nps.score, Some(25.0));
+        assert_eq!(NpsBreakdown::new(0, 0, 0).score, None);
+    }
+}
@@ -0,0 +1,243 @@
+//! Seat counting for capacity-limited options.
+//!
+//! Options with a `capacity` (e.g. "Workshop A — 20 seats") track the seats
+//! taken in their `reserved` column. Seats are taken with a single conditional
+//! `UPDATE ... WHERE reserved < capacity`, so concurrent submissions can never
+//! oversell an option; run [`reserve_seats`] in the same transaction as the
+//! submission insert so a failed insert gives the seats back.
+
+use std::collections::HashMap;
+
+use sea_orm::sea_query::{Condition, Expr};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::entities::{
+    field::{self, Entity as FieldEntity},
+    field_option::{self, Column as FieldOptionColumn, Entity as FieldOptionEntity},
+    step::Entity as StepEntity,
+};
+use crate::error::FormError;
+use crate::repository::{load_steps_with_fields, StepWithFields};
+use crate::schema::FieldValue;
+
+/// Takes one seat on every capacity-limited option chosen in `data`.
+///
+/// Returns [`FormError::OptionFull`] for the first option without seats left;
+/// seats taken before the failure are only given back if the caller rolls
+/// back the surrounding transaction.
+pub async fn reserve_seats<C: ConnectionTrait>(
+    db: &C,
+    fields: &[field::Model],
+    data: &HashMap<String, FieldValue>,
+) -> Result<(), FormError> {
+    for (field, option) in chosen_options(db, fields, |name| {
+        data.get(name).map(selected_values).unwrap_or_default()
+    })
+    .await?
+    {
+        let result = FieldOptionEntity::update_many()
+            .col_expr(
+                FieldOptionColumn::Reserved,
+                Expr::col(FieldOptionColumn::Reserved).add(1),
+            )
+            .filter(FieldOptionColumn::Id.eq(option.id))
+            .filter(
+                Condition::any()
+                    .add(FieldOptionColumn::Capacity.is_null())
+                    .add(
+                        Expr::col(FieldOptionColumn::Reserved)
+                            .lt(Expr::col(FieldOptionColumn::Capacity)),
+                    ),
+            )
+            .exec(db)
+            .await?;
+
+        if result.rows_affected == 0 {
+            return Err(FormError::OptionFull {
+                field: field.name.clone(),
+                option: option.label,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Gives back the seats held by a stored submission's data.
+pub async fn release_seats<C: ConnectionTrait>(
+    db: &C,
+    fields: &[field::Model],
+    data: &serde_json::Value,
+) -> Result<(), FormError> {
+    for (_, option) in chosen_options(db, fields, |name| {
+        data.get(name).map(json_values).unwrap_or_default()
+    })
+    .await?
+    {
+        FieldOptionEntity::update_many()
+            .col_expr(
+                FieldOptionColumn::Reserved,
+                Expr::col(FieldOptionColumn::Reserved).sub(1),
+            )
+            .filter(FieldOptionColumn::Id.eq(option.id))
+            .filter(FieldOptionColumn::Reserved.gt(0))
+            .exec(db)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Adds the seats held by stored submissions' data, without checking
+/// capacities; used to rebuild the `reserved` counts.
+pub(crate) async fn add_seats<C: ConnectionTrait>(
+    db: &C,
+    fields: &[field::Model],
+    data: &[&serde_json::Value],
+) -> Result<(), FormError> {
+    if fields.is_empty() || data.is_empty() {
+        return Ok(());
+    }
+
+    let options = FieldOptionEntity::find()
+        .filter(FieldOptionColumn::FieldId.is_in(fields.iter().map(|f| f.id)))
+        .filter(FieldOptionColumn::Capacity.is_not_null())
+        .all(db)
+        .await?;
+
+    let mut seats: HashMap<Uuid, i32> = HashMap::new();
+    for data in data {
+        for field in fields {
+            let values = data.get(&field.name).map(json_values).unwrap_or_default();
+            for option in options.iter().filter(|o| o.field_id == field.id) {
+                if values.contains(&option.value) {
+                    *seats.entry(option.id).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    for (option_id, taken) in seats {
+        FieldOptionEntity::update_many()
+            .col_expr(
+                FieldOptionColumn::Reserved,
+                Expr::col(FieldOptionColumn::Reserved).add(taken),
+            )
+            .filter(FieldOptionColumn::Id.eq(option_id))
+            .exec(db)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Gives back every seat taken on the options of `fields`.
+pub(crate) async fn clear_seats<C: ConnectionTrait>(
+    db: &C,
+    fields: &[field::Model],
+) -> Result<(), FormError> {
+    FieldOptionEntity::update_many()
+        .col_expr(FieldOptionColumn::Reserved, Expr::value(0))
+        .filter(FieldOptionColumn::FieldId.is_in(fields.iter().map(|f| f.id)))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Loads every field of a form, in step order, in two queries; forms
+/// including [fragments](crate::fragments) take more.
+///
+/// Sub-fields of repeatable groups are left out; see [`sub_fields`].
+pub(crate) async fn form_fields<C: ConnectionTrait>(
+    db: &C,
+    form_id: Uuid,
+) -> Result<Vec<field::Model>, FormError> {
+    let steps = StepEntity::find_by_form(db, form_id).await?;
+    if steps.iter().any(|s| s.include_form.is_some()) {
+        let steps = load_steps_with_fields(db, form_id).await?;
+        return Ok(StepWithFields::all_fields(&steps));
+    }
+    let step_ids: Vec<Uuid> = steps.iter().map(|s| s.id).collect();
+    let mut by_step: HashMap<Uuid, Vec<field::Model>> = HashMap::new();
+    for field in FieldEntity::find_by_steps(db, &step_ids).await? {
+        by_step.entry(field.step_id).or_default().push(field);
+    }
+    Ok(step_ids
+        .iter()
+        .flat_map(|id| by_step.remove(id).unwrap_or_default())
+        .collect())
+}
+
+/// Returns the seats taken per `(field name, option value)` for a form.
+///
+/// Used to carry counts over when a form update re-creates its options.
+pub(crate) async fn reserved_by_option<C: ConnectionTrait>(
+    db: &C,
+    form_id: Uuid,
+) -> Result<HashMap<(String, String), i32>, FormError> {
+    let fields = form_fields(db, form_id).await?;
+    let names: HashMap<Uuid, &str> = fields.iter().map(|f| (f.id, f.name.as_str())).collect();
+
+    let options = FieldOptionEntity::find()
+        .filter(FieldOptionColumn::FieldId.is_in(names.keys().copied()))
+        .filter(FieldOptionColumn::Reserved.gt(0))
+        .all(db)
+        .await?;
+
+    Ok(options
+        .into_iter()
+        .filter_map(|o| {
+            let name = names.get(&o.field_id)?;
+            Some(((name.to_string(), o.value), o.reserved))
+        })
+        .collect())
+}
+
+/// Finds the capacity-limited options chosen for each field.
+async fn chosen_options<'a, C, F>(
+    db: &C,
+    fields: &'a [field::Model],
+    values_for: F,
+) -> Result<Vec<(&'a field::Model, field_option::Model)>, FormError>
+where
+    C: ConnectionTrait,
+    F: Fn(&str) -> Vec<String>,
+{
+    if fields.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let options = FieldOptionEntity::find()
+        .filter(FieldOptionColumn::FieldId.is_in(fields.iter().map(|f| f.id)))
+        .filter(FieldOptionColumn::Capacity.is_not_null())
+        .all(db)
+        .await?;
+
+    let mut chosen = Vec::new();
+    for field in fields {
+        let values = values_for(&field.name);
+        for option in options.iter().filter(|o| o.field_id == field.id) {
+            if values.contains(&option.value) {
+                chosen.push((field, option.clone()));
+            }
+        }
+    }
+    Ok(chosen)
+}
+
+pub(crate) fn selected_values(value: &FieldValue) -> Vec<String> {
+    match value {
+        FieldValue::Array(values) => values.clone(),
+        FieldValue::Null => Vec::new(),
+        other => vec![other.to_string_value()],
+    }
+}
+
+fn json_values(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::Array(values) => values.iter().flat_map(json_values).collect(),
+        serde_json::Value::String(s) => vec![s.clone()],
+        serde_json::Value::Null => Vec::new(),
+        other => vec![other.to_string()],
+    }
+}
@@ -0,0 +1,128 @@
+//! Database consistency checks.
+//!
+//! Foreign keys keep child rows from outliving their parents, but only when
+//! they are enforced: SQLite databases written with `foreign_keys` off, or
+//! edited by hand, can still end up with orphans. Soft-deleting a form
+//! soft-deletes its submissions and results too, so an active child of a
+//! deleted form is also reported.
+
+use sea_orm::{ConnectionTrait, Statement};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::error::FormError;
+
+/// Child tables, the column referencing the parent, and the parent table.
+const REFERENCES: &[(&str, &str, &str)] = &[
+    ("af_steps", "form_id", "af_forms"),
+    ("af_fields", "step_id", "af_steps"),
+    ("af_field_options", "field_id", "af_fields"),
+    ("af_submissions", "form_id", "af_forms"),
+    ("af_submissions", "revision_id", "af_form_revisions"),
+    ("af_submissions", "instance_id", "af_form_instances"),
+    ("af_submission_notes", "submission_id", "af_submissions"),
+    ("af_results", "form_id", "af_forms"),
+    ("af_form_revisions", "form_id", "af_forms"),
+    ("af_form_instances", "form_id", "af_forms"),
+    ("af_dead_letters", "form_id", "af_forms"),
+];
+
+/// Tables whose rows are soft-deleted along with their form.
+const SOFT_DELETED_WITH_FORM: &[&str] = &["af_submissions", "af_results"];
+
+/// What is wrong with a row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// The row references a parent that doesn't exist.
+    Orphan,
+    /// The row is active but its form is soft-deleted.
+    ActiveUnderDeletedForm,
+}
+
+/// A row found by [`ConsistencyService::check`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConsistencyIssue {
+    pub kind: IssueKind,
+    pub table: &'static str,
+    pub id: Uuid,
+    /// Column referencing the parent.
+    pub column: &'static str,
+    pub parent_table: &'static str,
+    pub parent_id: Uuid,
+}
+
+/// The result of a consistency check.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConsistencyReport {
+    pub issues: Vec<ConsistencyIssue>,
+}
+
+impl ConsistencyReport {
+    /// Returns true if no issues were found.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Service for checking the database for orphaned or inconsistent rows.
+pub struct ConsistencyService;
+
+impl ConsistencyService {
+    /// Finds rows whose parent is missing and active submissions and results
+    /// of soft-deleted forms.
+    pub async fn check<C: ConnectionTrait>(db: &C) -> Result<ConsistencyReport, FormError> {
+        let mut issues = Vec::new();
+
+        for &(table, column, parent_table) in REFERENCES {
+            let sql = format!(
+                "SELECT c.id, c.{column} AS parent_id FROM {table} c \
+                LEFT JOIN {parent_table} p ON p.id = c.{column} \
+                WHERE c.{column} IS NOT NULL AND p.id IS NULL"
+            );
+            for (id, parent_id) in Self::query(db, sql).await? {
+                issues.push(ConsistencyIssue {
+                    kind: IssueKind::Orphan,
+                    table,
+                    id,
+                    column,
+                    parent_table,
+                    parent_id,
+                });
+            }
+        }
+
+        for &table in SOFT_DELETED_WITH_FORM {
+            let sql = format!(
+                "SELECT c.id, c.form_id AS parent_id FROM {table} c \
+                JOIN af_forms p ON p.id = c.form_id \
+                WHERE c.deleted_at IS NULL AND p.deleted_at IS NOT NULL"
+            );
+            for (id, parent_id) in Self::query(db, sql).await? {
+                issues.push(ConsistencyIssue {
+                    kind: IssueKind::ActiveUnderDeletedForm,
+                    table,
+                    id,
+                    column: "form_id",
+                    parent_table: "af_forms",
+                    parent_id,
+                });
+            }
+        }
+
+        Ok(ConsistencyReport { issues })
+    }
+
+    async fn query<C: ConnectionTrait>(
+        db: &C,
+        sql: String,
+    ) -> Result<Vec<(Uuid, Uuid)>, FormError> {
+        let rows = db
+            .query_all(Statement::from_string(db.get_database_backend(), sql))
+            .await?;
+        rows.iter()
+            .map(|row| Ok((row.try_get("", "id")?, row.try_get("", "parent_id")?)))
+            .collect()
+    }
+}
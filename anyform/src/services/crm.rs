@@ -0,0 +1,200 @@
+//! Pushing submissions to CRMs.
+//!
+//! Accepted submissions to forms with
+//! [`FormSettings::crm`](crate::schema::FormSettings::crm) set are queued in
+//! the `af_crm_pushes` table as they are stored. Run
+//! [`CrmService::push_due`] on a schedule (e.g. every minute, from cron or
+//! a timer task) with a [`CrmClient`] to push them; see [`crate::crm`].
+//! When sensitive answers are encrypted, run
+//! [`CrmService::push_due_with`] and the key instead.
+
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset};
+use sea_orm::{ActiveModelTrait, ActiveValue, ConnectionTrait, DatabaseConnection, EntityTrait};
+
+use crate::crm::{CrmClient, CrmRecord};
+use crate::encryption::{plain_answers, EncryptionKey};
+use crate::entities::{
+    crm_push::{Entity as CrmPushEntity, Model as CrmPush},
+    form::{Entity as FormEntity, Model as Form},
+    submission::{self, Entity as SubmissionEntity, Model as Submission},
+};
+use crate::error::FormError;
+use crate::services::LockService;
+
+/// Name of the lock [`CrmService::push_due`] runs under, suffixed with the
+/// provider.
+pub const CRM_LOCK: &str = "anyform.crm";
+
+/// Failed attempts after which a push is given up on.
+pub const MAX_CRM_ATTEMPTS: i32 = 8;
+
+/// Longest wait between attempts.
+const MAX_BACKOFF_MINUTES: i64 = 6 * 60;
+
+/// Service for queueing and pushing submissions to CRMs.
+pub struct CrmService;
+
+impl CrmService {
+    /// Queues an accepted submission for the form's CRM.
+    ///
+    /// Returns `None`, queueing nothing, when the form has no CRM mapping,
+    /// is anonymous, or the submission is waitlisted or has no email
+    /// address in the mapping's `email_field`. The email address is read
+    /// from the answers decrypted with `key`, if their sensitive answers are
+    /// encrypted.
+    pub async fn enqueue<C: ConnectionTrait>(
+        db: &C,
+        form: &Form,
+        submission: &Submission,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Option<CrmPush>, FormError> {
+        let settings = form.settings();
+        let Some(mapping) = settings.crm.filter(|_| !settings.anonymous) else {
+            return Ok(None);
+        };
+        if submission.is_waitlisted() {
+            return Ok(None);
+        }
+        let Some(email) = mapping.email(&plain_answers(submission, key)) else {
+            tracing::debug!(form = %form.slug, submission = %submission.id, "no email for CRM");
+            return Ok(None);
+        };
+
+        let push =
+            CrmPushEntity::enqueue(db, form.id, submission.id, mapping.provider, &email).await?;
+        Ok(Some(push))
+    }
+
+    /// Pushes every queued submission for `client`'s CRM that is due at
+    /// `now`, returning the pushes attempted.
+    ///
+    /// Each respondent keeps one CRM record: a push updates the record an
+    /// earlier push for the same email created. The record ID is stored
+    /// with the push and in the submission's metadata under `crm`. Failed
+    /// pushes are retried with exponential backoff, up to
+    /// [`MAX_CRM_ATTEMPTS`] times. Runs under the [`CRM_LOCK`] lock, so
+    /// only one server pushes to a CRM at a time; runs that find the lock
+    /// held push nothing.
+    pub async fn push_due(
+        db: &DatabaseConnection,
+        client: &dyn CrmClient,
+        now: DateTime<FixedOffset>,
+    ) -> Result<Vec<CrmPush>, FormError> {
+        Self::push_due_with(db, client, now, None).await
+    }
+
+    /// Pushes due submissions as [`push_due`](Self::push_due) does, with
+    /// their answers decrypted with `key`.
+    pub async fn push_due_with(
+        db: &DatabaseConnection,
+        client: &dyn CrmClient,
+        now: DateTime<FixedOffset>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Vec<CrmPush>, FormError> {
+        let lock = format!("{CRM_LOCK}.{}", client.provider().as_str());
+        let ttl = Duration::from_secs(15 * 60);
+        LockService::run_exclusive(db, &lock, ttl, || {
+            Self::push_due_locked(db, client, now, key)
+        })
+        .await
+        .map(Option::unwrap_or_default)
+    }
+
+    async fn push_due_locked(
+        db: &DatabaseConnection,
+        client: &dyn CrmClient,
+        now: DateTime<FixedOffset>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Vec<CrmPush>, FormError> {
+        let provider = client.provider();
+        let mut attempted = Vec::new();
+        for push in CrmPushEntity::find_due(db, provider, now).await? {
+            let form = FormEntity::find_by_id(push.form_id).one(db).await?;
+            let submission = SubmissionEntity::find_by_id(push.submission_id)
+                .one(db)
+                .await?
+                .filter(|s| s.deleted_at.is_none());
+            let mapping = form
+                .and_then(|f| f.settings().crm)
+                .filter(|m| m.provider == provider);
+            let (Some(mapping), Some(submission)) = (mapping, submission) else {
+                let error = "submission deleted or form no longer pushes to this CRM";
+                attempted.push(CrmPushEntity::mark_failed(db, push, error, None).await?);
+                continue;
+            };
+
+            let existing_id = CrmPushEntity::find_pushed_for_email(db, provider, &push.email)
+                .await?
+                .and_then(|p| p.record_id);
+            let record = CrmRecord {
+                object: mapping.object_name().to_string(),
+                email: push.email.clone(),
+                properties: mapping.properties(&plain_answers(&submission, key)),
+                existing_id,
+            };
+
+            match client.upsert(&record).await {
+                Ok(record_id) => {
+                    let push = CrmPushEntity::mark_pushed(db, push, &record_id).await?;
+                    Self::record_on_submission(db, submission, &push).await?;
+                    attempted.push(push);
+                }
+                Err(error) => {
+                    let retry_at = (push.attempts + 1 < MAX_CRM_ATTEMPTS)
+                        .then(|| now + backoff(push.attempts));
+                    tracing::warn!(
+                        submission = %push.submission_id,
+                        attempts = push.attempts + 1,
+                        %error,
+                        "failed to push submission to CRM"
+                    );
+                    attempted.push(CrmPushEntity::mark_failed(db, push, &error, retry_at).await?);
+                }
+            }
+        }
+        Ok(attempted)
+    }
+
+    /// Stores the CRM record ID in the submission's metadata under `crm`.
+    async fn record_on_submission(
+        db: &DatabaseConnection,
+        submission: Submission,
+        push: &CrmPush,
+    ) -> Result<Submission, FormError> {
+        let mut metadata = submission
+            .metadata
+            .clone()
+            .filter(serde_json::Value::is_object)
+            .unwrap_or_else(|| serde_json::json!({}));
+        metadata["crm"] = serde_json::json!({
+            "provider": push.provider,
+            "record_id": push.record_id,
+        });
+
+        let mut model: submission::ActiveModel = submission.into();
+        model.metadata = ActiveValue::Set(Some(metadata));
+        model.updated_at = ActiveValue::Set(chrono::Utc::now().fixed_offset());
+        Ok(model.update(db).await?)
+    }
+}
+
+/// Wait before retrying a push that has failed `attempts` times before:
+/// one minute, doubling up to [`MAX_BACKOFF_MINUTES`].
+fn backoff(attempts: i32) -> chrono::Duration {
+    let minutes = 1_i64 << attempts.clamp(0, 16);
+    chrono::Duration::minutes(minutes.min(MAX_BACKOFF_MINUTES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(0), chrono::Duration::minutes(1));
+        assert_eq!(backoff(3), chrono::Duration::minutes(8));
+        assert_eq!(backoff(20), chrono::Duration::minutes(MAX_BACKOFF_MINUTES));
+    }
+}
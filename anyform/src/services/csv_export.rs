@@ -0,0 +1,329 @@
+//! CSV exports of submissions, laid out by a form's export mapping.
+//!
+//! [`CsvExport`] picks, orders and names the columns of an export from the
+//! form's [`ExportMapping`](crate::schema::ExportMapping), and formats
+//! timestamps, date answers and choice answers as the mapping asks.
+//! Location answers take two columns, latitude and longitude. Both the
+//! admin export route and `anyform submissions export` use it.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+use uuid::Uuid;
+
+use crate::encryption::EncryptionKey;
+use crate::entities::{
+    field_option::Entity as FieldOptionEntity, form::Model as Form, submission::Model as Submission,
+};
+use crate::error::FormError;
+use crate::repository::FieldWithOptions;
+use crate::schema::{ExportSource, FieldClassification, SubmissionAttribute, ValueType};
+use crate::services::capacity::form_fields;
+use crate::services::SubmissionSchema;
+
+/// Loads a form's choice fields, those that take options and checkboxes,
+/// with their options.
+pub(crate) async fn choice_fields<C: ConnectionTrait>(
+    db: &C,
+    form_id: Uuid,
+) -> Result<Vec<FieldWithOptions>, FormError> {
+    let mut choices = Vec::new();
+    for field in form_fields(db, form_id).await? {
+        if field.requires_options() || field.value_type() == Some(ValueType::Checkbox) {
+            let options = FieldOptionEntity::find_by_field(db, field.id).await?;
+            choices.push(FieldWithOptions {
+                field,
+                options,
+                fields: Vec::new(),
+            });
+        }
+    }
+    Ok(choices)
+}
+
+/// Timestamp format of exports whose mapping sets none.
+pub const DEFAULT_EXPORT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// A laid-out CSV export of a form's submissions.
+#[derive(Debug, Clone)]
+pub struct CsvExport {
+    columns: Vec<CsvColumn>,
+    date_format: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct CsvColumn {
+    pub(super) header: String,
+    pub(super) content: Content,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Content {
+    Attribute(SubmissionAttribute),
+    Field {
+        name: String,
+        value_type: Option<ValueType>,
+        /// Option labels by value, when answers are written as labels.
+        labels: Option<HashMap<String, String>>,
+    },
+    /// One coordinate (`lat` or `lng`) of a location answer.
+    Coordinate { name: String, axis: &'static str },
+}
+
+impl CsvExport {
+    /// Lays out an export of `schema`'s columns by the form's export
+    /// mapping.
+    ///
+    /// Fields classified as sensitive in any revision are left out, even
+    /// when the mapping lists them, unless `include_sensitive` is set.
+    /// Mapped fields the form never had are exported as empty columns.
+    pub async fn new(
+        db: &DatabaseConnection,
+        form: &Form,
+        schema: &SubmissionSchema,
+        include_sensitive: bool,
+    ) -> Result<Self, FormError> {
+        let option_labels = form.settings().export.is_some_and(|m| m.option_labels);
+        let choices = if option_labels {
+            choice_fields(db, form.id).await?
+        } else {
+            Vec::new()
+        };
+        Ok(Self::with_choices(form, schema, &choices, include_sensitive))
+    }
+
+    /// Lays out an export as [`new`](Self::new) does, with the form's
+    /// choice fields as loaded by
+    /// [`FormsRepository::choice_fields`](crate::FormsRepository::choice_fields).
+    #[must_use]
+    pub fn with_choices(
+        form: &Form,
+        schema: &SubmissionSchema,
+        choices: &[FieldWithOptions],
+        include_sensitive: bool,
+    ) -> Self {
+        let mapping = form.settings().export.unwrap_or_default();
+
+        let sources: Vec<(ExportSource, Option<String>)> = if mapping.columns.is_empty() {
+            SubmissionAttribute::ALL
+                .into_iter()
+                .map(ExportSource::Attribute)
+                .chain(schema.columns().iter().map(|c| ExportSource::Field(c.name.clone())))
+                .map(|source| (source, None))
+                .collect()
+        } else {
+            mapping.columns.into_iter().map(|c| (c.source, c.header)).collect()
+        };
+
+        let mut options: HashMap<String, HashMap<String, String>> = HashMap::new();
+        if mapping.option_labels {
+            for choice in choices {
+                let labels = choice
+                    .options
+                    .iter()
+                    .map(|o| (o.value.clone(), o.label.clone()))
+                    .collect();
+                options.insert(choice.field.name.clone(), labels);
+            }
+        }
+
+        let mut columns = Vec::new();
+        for (source, header) in sources {
+            let column = match source {
+                ExportSource::Attribute(attribute) => CsvColumn {
+                    header: header.unwrap_or_else(|| attribute.as_str().to_string()),
+                    content: Content::Attribute(attribute),
+                },
+                ExportSource::Field(name) => {
+                    let known = schema.columns().iter().find(|c| c.name == name);
+                    let classification =
+                        known.map_or(FieldClassification::Standard, |c| c.classification);
+                    if mapping.exclude.contains(&name)
+                        || !(include_sensitive || classification.in_default_export())
+                    {
+                        continue;
+                    }
+                    let header = header
+                        .or_else(|| known.map(|c| c.label.clone()))
+                        .unwrap_or_else(|| name.clone());
+                    let value_type = known
+                        .and_then(|c| c.field_type.as_deref())
+                        .and_then(|t| t.parse().ok());
+                    // Locations take a column per coordinate
+                    if value_type == Some(ValueType::Location) {
+                        for axis in ["lat", "lng"] {
+                            columns.push(CsvColumn {
+                                header: format!("{header} ({axis})"),
+                                content: Content::Coordinate {
+                                    name: name.clone(),
+                                    axis,
+                                },
+                            });
+                        }
+                        continue;
+                    }
+                    CsvColumn {
+                        header,
+                        content: Content::Field {
+                            value_type,
+                            labels: options.remove(&name),
+                            name,
+                        },
+                    }
+                }
+            };
+            columns.push(column);
+        }
+
+        Self {
+            columns,
+            date_format: mapping.date_format,
+        }
+    }
+
+    /// Returns the column headers, in order.
+    #[must_use]
+    pub fn headers(&self) -> Vec<&str> {
+        self.columns.iter().map(|c| c.header.as_str()).collect()
+    }
+
+    /// Returns the laid-out columns, for other export formats.
+    #[cfg(feature = "xlsx")]
+    pub(super) fn columns(&self) -> &[CsvColumn] {
+        &self.columns
+    }
+
+    /// Writes the header line and a line per submission, with the labels
+    /// of each submission from `labels`.
+    #[must_use]
+    pub fn render(
+        &self,
+        submissions: &[Submission],
+        labels: &HashMap<Uuid, Vec<String>>,
+    ) -> String {
+        self.render_with(submissions, labels, None)
+    }
+
+    /// Writes the export as [`render`](Self::render) does, decrypting
+    /// answers encrypted with `key`.
+    #[must_use]
+    pub fn render_with(
+        &self,
+        submissions: &[Submission],
+        labels: &HashMap<Uuid, Vec<String>>,
+        key: Option<&EncryptionKey>,
+    ) -> String {
+        let mut csv = line(self.columns.iter().map(|c| c.header.clone()));
+        for sub in submissions {
+            let sub = match key {
+                Some(key) => {
+                    let mut sub = sub.clone();
+                    key.decrypt_submission(&mut sub);
+                    Cow::Owned(sub)
+                }
+                None => Cow::Borrowed(sub),
+            };
+            let no_labels = Vec::new();
+            let sub_labels = labels.get(&sub.id).unwrap_or(&no_labels);
+            csv.push_str(&line(self.columns.iter().map(|c| self.cell(c, &sub, sub_labels))));
+        }
+        csv
+    }
+
+    fn cell(&self, column: &CsvColumn, sub: &Submission, labels: &[String]) -> String {
+        match &column.content {
+            Content::Attribute(attribute) => match attribute {
+                SubmissionAttribute::Id => sub.id.to_string(),
+                SubmissionAttribute::CreatedAt => self.timestamp(&sub.created_at),
+                SubmissionAttribute::CompletedAt => {
+                    sub.completed_at.map(|at| self.timestamp(&at)).unwrap_or_default()
+                }
+                SubmissionAttribute::Status => sub.status.clone(),
+                SubmissionAttribute::InstanceId => {
+                    sub.instance_id.map(|id| id.to_string()).unwrap_or_default()
+                }
+                SubmissionAttribute::Labels => labels.join(";"),
+            },
+            Content::Field {
+                name,
+                value_type,
+                labels,
+            } => {
+                let label = |value: &str| match labels {
+                    Some(labels) => labels.get(value).cloned().unwrap_or_else(|| value.to_string()),
+                    None => value.to_string(),
+                };
+                match sub.data.get(name) {
+                    None | Some(serde_json::Value::Null) => String::new(),
+                    Some(serde_json::Value::String(s)) => match value_type {
+                        Some(ValueType::Date | ValueType::DateTime) => self.date_answer(s),
+                        _ => label(s),
+                    },
+                    Some(serde_json::Value::Array(values)) if labels.is_some() => values
+                        .iter()
+                        .map(|v| v.as_str().map_or_else(|| v.to_string(), label))
+                        .collect::<Vec<_>>()
+                        .join(";"),
+                    Some(other) => other.to_string(),
+                }
+            }
+            Content::Coordinate { name, axis } => coordinate(sub, name, axis)
+                .map(|c| c.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn timestamp(&self, at: &chrono::DateTime<chrono::FixedOffset>) -> String {
+        let format = self.date_format.as_deref().unwrap_or(DEFAULT_EXPORT_DATE_FORMAT);
+        let mut formatted = String::new();
+        match write!(formatted, "{}", at.format(format)) {
+            Ok(()) => formatted,
+            Err(_) => at.format(DEFAULT_EXPORT_DATE_FORMAT).to_string(),
+        }
+    }
+
+    /// Reformats a date or date-time answer, leaving answers that don't
+    /// parse, or that the format can't express, as submitted.
+    fn date_answer(&self, answer: &str) -> String {
+        let Some(format) = &self.date_format else {
+            return answer.to_string();
+        };
+        let mut formatted = String::new();
+        let written = if let Ok(date) = NaiveDate::parse_from_str(answer, "%Y-%m-%d") {
+            write!(formatted, "{}", date.format(format))
+        } else if let Ok(at) = NaiveDateTime::parse_from_str(answer, "%Y-%m-%dT%H:%M")
+            .or_else(|_| NaiveDateTime::parse_from_str(answer, "%Y-%m-%dT%H:%M:%S"))
+        {
+            write!(formatted, "{}", at.format(format))
+        } else {
+            return answer.to_string();
+        };
+        match written {
+            Ok(()) => formatted,
+            Err(_) => answer.to_string(),
+        }
+    }
+}
+
+/// Returns the `axis` coordinate of a submission's answer to the location
+/// field `name`.
+pub(super) fn coordinate(sub: &Submission, name: &str, axis: &str) -> Option<f64> {
+    sub.data.get(name)?.get(axis)?.as_f64()
+}
+
+fn line(cells: impl Iterator<Item = String>) -> String {
+    let mut line = cells.map(|c| escape_csv(&c)).collect::<Vec<_>>().join(",");
+    line.push('\n');
+    line
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
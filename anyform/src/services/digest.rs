@@ -0,0 +1,313 @@
+//! Notification digests.
+//!
+//! Forms with [`FormSettings::digest`](crate::schema::FormSettings::digest)
+//! set get one summary per day or week instead of an email per submission.
+//! The crate doesn't send email: run [`DigestService::send_due`] on a
+//! schedule (e.g. hourly, from cron or a timer task) with a
+//! [`DigestSender`] that does. Each digest is sent once per period, however
+//! often the job runs and on however many servers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset};
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::{
+    digest_delivery::Entity as DigestDeliveryEntity,
+    field_option::Entity as FieldOptionEntity,
+    form::{ActiveModel as FormActiveModel, Entity as FormEntity, Model as Form},
+    submission::Entity as SubmissionEntity,
+};
+use crate::error::FormError;
+use crate::schema::{DigestFrequency, FieldValue, NotificationDigest, ValueType};
+use crate::services::capacity::form_fields;
+use crate::services::LockService;
+
+/// Name of the lock [`DigestService::send_due`] runs under.
+pub const DIGEST_LOCK: &str = "anyform.digests";
+
+/// Most recent submissions shown in a digest.
+const HIGHLIGHTS: usize = 3;
+
+/// Most popular options shown per field in a digest.
+const TOP_OPTIONS: usize = 3;
+
+/// Longest answer shown in a highlight, in characters.
+const MAX_ANSWER_CHARS: usize = 140;
+
+/// Service for building and sending notification digests.
+pub struct DigestService;
+
+/// A summary of one period's submissions to a form.
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+    pub form_id: Uuid,
+    pub form_name: String,
+    pub form_slug: String,
+    pub frequency: DigestFrequency,
+    pub period_start: DateTime<FixedOffset>,
+    pub period_end: DateTime<FixedOffset>,
+    /// Subscribed recipients to send the digest to.
+    pub recipients: Vec<String>,
+    /// Accepted submissions in the period, as reported under the form's
+    /// [`ResultsPrivacy`](crate::schema::ResultsPrivacy).
+    pub submissions: u64,
+    /// Whether highlights and option counts are withheld because too few
+    /// respondents took part.
+    pub suppressed: bool,
+    /// The period's most recent submissions.
+    pub highlights: Vec<DigestHighlight>,
+    /// The most chosen options per choice field, most chosen first.
+    pub top_options: BTreeMap<String, Vec<OptionCount>>,
+}
+
+/// A submission shown in a digest.
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestHighlight {
+    pub submission_id: Uuid,
+    pub submitted_at: DateTime<FixedOffset>,
+    /// Answers by field name, shortened, without sensitive fields.
+    pub answers: BTreeMap<String, String>,
+}
+
+/// How often an option was chosen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OptionCount {
+    pub value: String,
+    pub label: String,
+    pub count: u64,
+}
+
+/// Delivers digests, e.g. by email.
+#[async_trait]
+pub trait DigestSender: Send + Sync {
+    /// Sends `digest` to its recipients.
+    async fn send(&self, digest: &Digest) -> Result<(), String>;
+}
+
+impl DigestService {
+    /// Builds the digest of a form's submissions between `start` and `end`.
+    ///
+    /// Forms without a digest get one with no recipients.
+    pub async fn build(
+        db: &DatabaseConnection,
+        form: &Form,
+        start: DateTime<FixedOffset>,
+        end: DateTime<FixedOffset>,
+    ) -> Result<Digest, FormError> {
+        let settings = form.settings();
+        let digest = settings.digest.unwrap_or_else(NotificationDigest::daily);
+
+        let subs: Vec<_> = SubmissionEntity::find_completed_by_form(db, form.id)
+            .await?
+            .into_iter()
+            .filter(|s| !s.is_waitlisted())
+            .filter(|s| s.completed_at.is_some_and(|at| start <= at && at < end))
+            .collect();
+        let respondents = subs.len() as u64;
+
+        let mut summary = Digest {
+            form_id: form.id,
+            form_name: form.name.clone(),
+            form_slug: form.slug.clone(),
+            frequency: digest.frequency,
+            period_start: start,
+            period_end: end,
+            recipients: digest.subscribed_recipients(),
+            submissions: respondents,
+            suppressed: false,
+            highlights: Vec::new(),
+            top_options: BTreeMap::new(),
+        };
+        if let Some(privacy) = settings.results_privacy {
+            summary.submissions = privacy.report_count(respondents);
+            if privacy.is_suppressed(respondents) {
+                summary.suppressed = true;
+                return Ok(summary);
+            }
+        }
+
+        let fields = form_fields(db, form.id).await?;
+        let shown: Vec<&str> = fields
+            .iter()
+            .filter(|f| f.classification().in_default_export() && !f.is_display_only())
+            .map(|f| f.name.as_str())
+            .collect();
+        summary.highlights = subs
+            .iter()
+            .take(HIGHLIGHTS)
+            .map(|sub| DigestHighlight {
+                submission_id: sub.id,
+                submitted_at: sub.completed_at.unwrap_or(sub.created_at),
+                answers: shown
+                    .iter()
+                    .filter_map(|name| {
+                        let value = sub.get_field(name).filter(|v| !v.is_empty())?;
+                        Some((name.to_string(), shorten(&value.to_string_value())))
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let is_choice = |t: ValueType| {
+            matches!(
+                t,
+                ValueType::Select
+                    | ValueType::MultiSelect
+                    | ValueType::Radio
+                    | ValueType::ImageChoice
+                    | ValueType::Checkbox
+            )
+        };
+        for field in fields
+            .iter()
+            .filter(|f| f.value_type().is_some_and(is_choice) && f.classification().in_analytics())
+        {
+            let mut counts: HashMap<String, u64> = HashMap::new();
+            for sub in &subs {
+                match sub.get_field(&field.name) {
+                    Some(FieldValue::Array(values)) => {
+                        for value in values {
+                            *counts.entry(value).or_default() += 1;
+                        }
+                    }
+                    Some(value) if !value.is_empty() => {
+                        *counts.entry(value.to_string_value()).or_default() += 1;
+                    }
+                    _ => {}
+                }
+            }
+            if counts.is_empty() {
+                continue;
+            }
+
+            let labels: HashMap<String, String> = FieldOptionEntity::find_by_field(db, field.id)
+                .await?
+                .into_iter()
+                .map(|o| (o.value, o.label))
+                .collect();
+            let mut top: Vec<OptionCount> = counts
+                .into_iter()
+                .map(|(value, count)| OptionCount {
+                    label: labels.get(&value).cloned().unwrap_or_else(|| value.clone()),
+                    value,
+                    count,
+                })
+                .collect();
+            top.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+            top.truncate(TOP_OPTIONS);
+            summary.top_options.insert(field.name.clone(), top);
+        }
+
+        Ok(summary)
+    }
+
+    /// Builds the digest of a form's last whole period before `now`.
+    pub async fn latest(
+        db: &DatabaseConnection,
+        form: &Form,
+        now: DateTime<FixedOffset>,
+    ) -> Result<Digest, FormError> {
+        let frequency = form
+            .settings()
+            .digest
+            .map_or(DigestFrequency::Daily, |d| d.frequency);
+        let (start, end) = frequency.period_before(now);
+        Self::build(db, form, start, end).await
+    }
+
+    /// Sends every digest due at `now` that hasn't been sent yet, returning
+    /// the digests sent.
+    ///
+    /// Each form's digest covers its last whole period before `now`. Periods
+    /// without submissions, and forms without subscribed recipients, are
+    /// skipped; digests the sender fails to deliver are retried on the next
+    /// run. Runs under the [`DIGEST_LOCK`] lock, so only one server sends at
+    /// a time; runs that find the lock held send nothing.
+    pub async fn send_due(
+        db: &DatabaseConnection,
+        sender: &dyn DigestSender,
+        now: DateTime<FixedOffset>,
+    ) -> Result<Vec<Digest>, FormError> {
+        let ttl = Duration::from_secs(15 * 60);
+        LockService::run_exclusive(db, DIGEST_LOCK, ttl, || Self::send_due_locked(db, sender, now))
+            .await
+            .map(Option::unwrap_or_default)
+    }
+
+    async fn send_due_locked(
+        db: &DatabaseConnection,
+        sender: &dyn DigestSender,
+        now: DateTime<FixedOffset>,
+    ) -> Result<Vec<Digest>, FormError> {
+        let mut sent = Vec::new();
+        for form in FormEntity::find_active(db).await? {
+            let Some(settings) = form.settings().digest else {
+                continue;
+            };
+            if settings.subscribed_recipients().is_empty() {
+                continue;
+            }
+            let (start, end) = settings.frequency.period_before(now);
+            if DigestDeliveryEntity::find_for_period(db, form.id, start)
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+
+            let digest = Self::build(db, &form, start, end).await?;
+            if digest.submissions == 0 {
+                continue;
+            }
+            if let Err(error) = sender.send(&digest).await {
+                tracing::warn!(form = %form.slug, %error, "failed to send digest");
+                continue;
+            }
+
+            let count = i32::try_from(digest.submissions).unwrap_or(i32::MAX);
+            DigestDeliveryEntity::record(db, form.id, start, end, count).await?;
+            sent.push(digest);
+        }
+        Ok(sent)
+    }
+
+    /// Subscribes or unsubscribes a recipient of a form's digest, returning
+    /// the updated digest settings.
+    pub async fn set_subscribed(
+        db: &DatabaseConnection,
+        form: Form,
+        email: &str,
+        subscribed: bool,
+    ) -> Result<NotificationDigest, FormError> {
+        let mut settings = form.settings();
+        let found = settings
+            .digest
+            .as_mut()
+            .is_some_and(|d| d.set_subscribed(email, subscribed));
+        let digest = settings
+            .digest
+            .clone()
+            .filter(|_| found)
+            .ok_or_else(|| FormError::DigestRecipientNotFound(email.to_string()))?;
+
+        let mut model: FormActiveModel = form.into();
+        let settings = serde_json::to_value(&settings).unwrap_or_default();
+        model.settings = ActiveValue::Set(Some(settings));
+        model.updated_at = ActiveValue::Set(chrono::Utc::now().fixed_offset());
+        model.update(db).await?;
+        Ok(digest)
+    }
+}
+
+/// Shortens an answer to [`MAX_ANSWER_CHARS`] characters.
+fn shorten(value: &str) -> String {
+    match value.char_indices().nth(MAX_ANSWER_CHARS) {
+        Some((end, _)) => format!("{}…", &value[..end]),
+        None => value.to_string(),
+    }
+}
@@ -1,8 +1,13 @@
 //! Form builder service for creating, updating, and deleting forms.
 
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use sea_orm::prelude::{DateTimeWithTimeZone, Expr};
+#[cfg(feature = "json")]
+use sea_orm::sea_query::CaseStatement;
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
-    TransactionTrait,
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, DatabaseTransaction, DbErr,
+    EntityTrait, IntoActiveModel, Iterable, QueryFilter, SqlErr, TransactionTrait, TryIntoModel,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -10,11 +15,35 @@ use uuid::Uuid;
 use crate::entities::{
     field::{ActiveModel as FieldActiveModel, Entity as FieldEntity},
     field_option::{ActiveModel as FieldOptionActiveModel, Entity as FieldOptionEntity},
-    form::{ActiveModel as FormActiveModel, Column as FormColumn, Entity as FormEntity, Model as Form},
-    step::{ActiveModel as StepActiveModel, Entity as StepEntity},
+    form::{
+        ActiveModel as FormActiveModel, Column as FormColumn, Entity as FormEntity, FormStatus,
+        Model as Form,
+    },
+    form_revision::{Entity as FormRevisionEntity, RevisionField},
+    result::{ActiveModel as ResultActiveModel, Column as ResultColumn, Entity as ResultEntity},
+    step::{ActiveModel as StepActiveModel, Entity as StepEntity, Model as Step},
+    submission::{Column as SubmissionColumn, Entity as SubmissionEntity},
 };
+use crate::branching::JumpRule;
+use crate::condition::ConditionRule;
+use crate::duplicates::DuplicateGuard;
 use crate::error::FormError;
-use crate::schema::{FormSettings, UiOptions, ValidationRules};
+use crate::fragments;
+#[cfg(feature = "json")]
+use crate::entities::step::Column as StepColumn;
+#[cfg(feature = "json")]
+use crate::render::JsonRenderer;
+#[cfg(feature = "json")]
+use crate::repository::StepWithFields;
+use crate::repository::{load_own_steps, load_steps_with_fields};
+use crate::services::capacity;
+use crate::services::lifecycle::current_state;
+use crate::services::quota::lock_form;
+use crate::services::LegalHold;
+use crate::schema::{
+    check_custom_css, check_custom_js, is_safe_url, FieldClassification, FormSettings,
+    UiOptions, ValidationRules, ValueType,
+};
 
 /// Input for creating a new form.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +65,12 @@ pub struct CreateFormInput {
     /// Steps in the form (at least one required).
     #[serde(default)]
     pub steps: Vec<CreateStepInput>,
+
+    /// Results a completed submission maps to, in the order they're tried.
+    ///
+    /// On update, `None` keeps the form's results as they are.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub results: Option<Vec<CreateResultInput>>,
 }
 
 impl CreateFormInput {
@@ -48,6 +83,7 @@ impl CreateFormInput {
             description: None,
             settings: FormSettings::default(),
             steps: Vec::new(),
+            results: None,
         }
     }
 
@@ -78,6 +114,20 @@ impl CreateFormInput {
         self.steps = steps;
         self
     }
+
+    /// Adds a result to the form.
+    #[must_use]
+    pub fn result(mut self, result: CreateResultInput) -> Self {
+        self.results.get_or_insert_with(Vec::new).push(result);
+        self
+    }
+
+    /// Sets the form's results.
+    #[must_use]
+    pub fn results(mut self, results: Vec<CreateResultInput>) -> Self {
+        self.results = Some(results);
+        self
+    }
 }
 
 /// Input for creating a form step.
@@ -98,9 +148,23 @@ pub struct CreateStepInput {
     #[serde(default)]
     pub condition: Option<String>,
 
+    /// Jump rules tried once the step is done; see [`crate::branching`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub next: Vec<JumpRule>,
+
     /// Fields in this step.
     #[serde(default)]
     pub fields: Vec<CreateFieldInput>,
+
+    /// Slug of a form whose fields the step includes after its own; see
+    /// [`crate::fragments`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_form: Option<String>,
+
+    /// Namespace of the included fields' names, for including a form more
+    /// than once; defaults to its slug.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub include_as: Option<String>,
 }
 
 impl CreateStepInput {
@@ -112,7 +176,10 @@ impl CreateStepInput {
             description: None,
             order: 0,
             condition: None,
+            next: Vec::new(),
             fields: Vec::new(),
+            include_form: None,
+            include_as: None,
         }
     }
 
@@ -137,6 +204,13 @@ impl CreateStepInput {
         self
     }
 
+    /// Adds a jump rule.
+    #[must_use]
+    pub fn jump(mut self, rule: JumpRule) -> Self {
+        self.next.push(rule);
+        self
+    }
+
     /// Adds a field to the step.
     #[must_use]
     pub fn field(mut self, field: CreateFieldInput) -> Self {
@@ -150,18 +224,40 @@ impl CreateStepInput {
         self.fields = fields;
         self
     }
+
+    /// Includes the fields of the form with `slug`.
+    #[must_use]
+    pub fn include_form(mut self, slug: impl Into<String>) -> Self {
+        self.include_form = Some(slug.into());
+        self
+    }
+
+    /// Sets the namespace of the included fields' names.
+    #[must_use]
+    pub fn include_as(mut self, namespace: impl Into<String>) -> Self {
+        self.include_as = Some(namespace.into());
+        self
+    }
 }
 
 /// Input for creating a form field.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateFieldInput {
+    /// Name of a [`FieldLibrary`](crate::presets::FieldLibrary) preset
+    /// whose fields stand in for this one, which then needs nothing else.
+    #[serde(rename = "$ref", default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+
     /// Field identifier (snake_case).
+    #[serde(default)]
     pub name: String,
 
     /// Display label.
+    #[serde(default)]
     pub label: String,
 
     /// Field type (text, email, select, etc.).
+    #[serde(default)]
     pub field_type: String,
 
     /// Display order within step.
@@ -196,6 +292,10 @@ pub struct CreateFieldInput {
     #[serde(default)]
     pub options: Vec<CreateOptionInput>,
 
+    /// Sub-fields of a `repeater` field, answered once per row.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<CreateFieldInput>,
+
     // Quiz fields
     /// Correct answer for quiz questions.
     #[serde(default)]
@@ -208,6 +308,14 @@ pub struct CreateFieldInput {
     /// Weight for weighted scoring.
     #[serde(default)]
     pub weight: Option<f64>,
+
+    /// Scoring category the field's points also count towards.
+    #[serde(default)]
+    pub category: Option<String>,
+
+    /// Data classification, controlling analytics and exports.
+    #[serde(default, skip_serializing_if = "FieldClassification::is_standard")]
+    pub classification: FieldClassification,
 }
 
 impl CreateFieldInput {
@@ -219,6 +327,7 @@ impl CreateFieldInput {
         field_type: impl Into<String>,
     ) -> Self {
         Self {
+            preset: None,
             name: name.into(),
             label: label.into(),
             field_type: field_type.into(),
@@ -230,9 +339,22 @@ impl CreateFieldInput {
             validation_rules: ValidationRules::default(),
             ui_options: UiOptions::default(),
             options: Vec::new(),
+            fields: Vec::new(),
             correct_answer: None,
             points: None,
             weight: None,
+            category: None,
+            classification: FieldClassification::Standard,
+        }
+    }
+
+    /// Creates a reference to the field library preset `name`, replaced by
+    /// its fields when the library expands the form.
+    #[must_use]
+    pub fn preset(name: impl Into<String>) -> Self {
+        Self {
+            preset: Some(name.into()),
+            ..Self::new("", "", "")
         }
     }
 
@@ -299,6 +421,20 @@ impl CreateFieldInput {
         self
     }
 
+    /// Adds a sub-field to a `repeater` field.
+    #[must_use]
+    pub fn field(mut self, field: CreateFieldInput) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Adds multiple sub-fields to a `repeater` field.
+    #[must_use]
+    pub fn fields(mut self, fields: Vec<CreateFieldInput>) -> Self {
+        self.fields = fields;
+        self
+    }
+
     /// Sets correct answer for quiz.
     #[must_use]
     pub fn correct_answer(mut self, answer: impl Into<String>) -> Self {
@@ -312,6 +448,29 @@ impl CreateFieldInput {
         self.points = Some(points);
         self
     }
+
+    /// Sets the weight the field's points are multiplied by for quiz.
+    #[must_use]
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Sets the scoring category, e.g. a personality trait the field
+    /// measures.
+    #[must_use]
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    /// Sets the data classification, e.g. [`FieldClassification::Sensitive`]
+    /// for personal data.
+    #[must_use]
+    pub fn classification(mut self, classification: FieldClassification) -> Self {
+        self.classification = classification;
+        self
+    }
 }
 
 /// Input for creating a field option.
@@ -334,6 +493,20 @@ pub struct CreateOptionInput {
     /// Points for this option (for quizzes).
     #[serde(default)]
     pub points: Option<i32>,
+
+    /// Maximum number of submissions that may choose this option.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+
+    /// Heading the option is listed under; consecutive options of a group
+    /// are listed together.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// Picture shown on the option's card, for `image_choice` fields; the
+    /// label is its caption.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
 }
 
 impl CreateOptionInput {
@@ -346,6 +519,9 @@ impl CreateOptionInput {
             order: 0,
             is_correct: false,
             points: None,
+            capacity: None,
+            group: None,
+            image_url: None,
         }
     }
 
@@ -369,6 +545,122 @@ impl CreateOptionInput {
         self.points = Some(points);
         self
     }
+
+    /// Limits how many submissions may choose this option.
+    #[must_use]
+    pub fn capacity(mut self, capacity: i32) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Lists this option under a group heading.
+    #[must_use]
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// Sets the picture shown on the option's card.
+    #[must_use]
+    pub fn image(mut self, url: impl Into<String>) -> Self {
+        self.image_url = Some(url.into());
+        self
+    }
+}
+
+/// Input for creating a form result.
+///
+/// A completed submission gets the first result it matches: one with a
+/// score range needs a score within it, one with a category needs it to
+/// be the submission's highest scoring category, and one with a condition
+/// needs answers satisfying it. A result with none of these is a catch-all.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateResultInput {
+    /// Result identifier (e.g., "introvert", "expert").
+    pub key: String,
+
+    /// Title shown to the respondent.
+    pub title: String,
+
+    /// Optional description shown to the respondent.
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Minimum score (inclusive).
+    #[serde(default)]
+    pub min_score: Option<i32>,
+
+    /// Maximum score (inclusive).
+    #[serde(default)]
+    pub max_score: Option<i32>,
+
+    /// Scoring category that must be the submission's highest.
+    #[serde(default)]
+    pub category: Option<String>,
+
+    /// Condition on the answers.
+    #[serde(default)]
+    pub condition: Option<ConditionRule>,
+}
+
+impl CreateResultInput {
+    /// Creates a new result input.
+    #[must_use]
+    pub fn new(key: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            title: title.into(),
+            description: None,
+            min_score: None,
+            max_score: None,
+            category: None,
+            condition: None,
+        }
+    }
+
+    /// Sets the description.
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the score range, both ends inclusive.
+    #[must_use]
+    pub fn score_range(mut self, min: i32, max: i32) -> Self {
+        self.min_score = Some(min);
+        self.max_score = Some(max);
+        self
+    }
+
+    /// Sets the minimum score.
+    #[must_use]
+    pub fn min_score(mut self, min: i32) -> Self {
+        self.min_score = Some(min);
+        self
+    }
+
+    /// Sets the maximum score.
+    #[must_use]
+    pub fn max_score(mut self, max: i32) -> Self {
+        self.max_score = Some(max);
+        self
+    }
+
+    /// Picks the result for submissions whose highest scoring category is
+    /// `category`.
+    #[must_use]
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    /// Sets the condition on the answers.
+    #[must_use]
+    pub fn condition(mut self, condition: ConditionRule) -> Self {
+        self.condition = Some(condition);
+        self
+    }
 }
 
 /// Service for creating, updating, and deleting forms.
@@ -392,12 +684,235 @@ impl CreateOptionInput {
 pub struct FormBuilder;
 
 impl FormBuilder {
+    /// Rejects contradictory settings (including a `closes_at` before
+    /// `opens_at`), unsafe snippets or branding, and invalid export mappings.
+    fn check_settings(settings: &FormSettings) -> Result<(), FormError> {
+        if settings.anonymous && settings.requires_respondent() {
+            return Err(FormError::InvalidData(
+                "A form cannot be both anonymous and identified".to_string(),
+            ));
+        }
+        if let (Some(opens), Some(closes)) = (settings.opens_at, settings.closes_at) {
+            if opens >= closes {
+                return Err(FormError::InvalidData(format!(
+                    "closes_at ({closes}) must be after opens_at ({opens})"
+                )));
+            }
+        }
+        if let Some(rate) = settings.sample_rate {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(FormError::InvalidData(format!(
+                    "sample_rate must be between 0 and 1, got {rate}"
+                )));
+            }
+        }
+        if let Some(css) = &settings.custom_css {
+            check_custom_css(css)?;
+        }
+        if let Some(js) = &settings.custom_js {
+            check_custom_js(js)?;
+        }
+        if let Some(branding) = &settings.branding {
+            branding.check()?;
+        }
+        if let Some(export) = &settings.export {
+            export.check()?;
+        }
+        Ok(())
+    }
+
+    /// Rejects results without a key or title, with a repeated key, or
+    /// with an empty score range.
+    fn check_results(results: &[CreateResultInput]) -> Result<(), FormError> {
+        let mut keys = std::collections::HashSet::new();
+        for result in results {
+            if result.key.trim().is_empty() || result.title.trim().is_empty() {
+                return Err(FormError::InvalidData(
+                    "Results need a key and a title".to_string(),
+                ));
+            }
+            if !keys.insert(result.key.as_str()) {
+                return Err(FormError::InvalidData(format!(
+                    "Duplicate result key '{}'",
+                    result.key
+                )));
+            }
+            if let (Some(min), Some(max)) = (result.min_score, result.max_score) {
+                if min > max {
+                    return Err(FormError::InvalidData(format!(
+                        "Result '{}' has min_score {min} above max_score {max}",
+                        result.key
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects jump rules to a step that doesn't come later in the form.
+    fn check_jumps(steps: &[CreateStepInput]) -> Result<(), FormError> {
+        // Steps are ordered as `insert_steps` stores them
+        let orders: Vec<(i32, &str)> = steps
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (if s.order == 0 { i as i32 } else { s.order }, s.name.as_str()))
+            .collect();
+
+        for (step, &(order, name)) in steps.iter().zip(&orders) {
+            let rules = step
+                .fields
+                .iter()
+                .flat_map(|f| f.ui_options.next.iter().map(move |r| (f.name.as_str(), r)))
+                .chain(step.next.iter().map(|r| (name, r)));
+            for (owner, rule) in rules {
+                let Some(to) = &rule.to else {
+                    continue;
+                };
+                if !orders.iter().any(|&(o, n)| n == to && o > order) {
+                    return Err(FormError::InvalidData(format!(
+                        "'{owner}' jumps to '{to}', which is not a later step"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects steps including their own form, namespaces without an
+    /// included form or with a dot, and namespaces used twice.
+    fn check_includes(slug: &str, steps: &[CreateStepInput]) -> Result<(), FormError> {
+        let mut namespaces = HashSet::new();
+        for step in steps {
+            let Some(form) = &step.include_form else {
+                if step.include_as.is_some() {
+                    return Err(FormError::InvalidData(format!(
+                        "Step '{}' sets include_as without include_form",
+                        step.name
+                    )));
+                }
+                continue;
+            };
+            if form == slug {
+                return Err(FormError::InvalidData(format!(
+                    "Step '{}' includes its own form",
+                    step.name
+                )));
+            }
+            let namespace = fragments::namespace(form, step.include_as.as_deref());
+            if namespace.is_empty() || namespace.contains('.') {
+                return Err(FormError::InvalidData(format!(
+                    "Step '{}' has an invalid include namespace '{namespace}'",
+                    step.name
+                )));
+            }
+            if !namespaces.insert(namespace.clone()) {
+                return Err(FormError::InvalidData(format!(
+                    "Namespace '{namespace}' is included twice; set include_as"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects references to field presets, which a
+    /// [`FieldLibrary`](crate::presets::FieldLibrary) must expand first,
+    /// fields without a name or type, unsafe or inaccessible help media, and
+    /// image-choice options without a safe image.
+    fn check_fields(steps: &[CreateStepInput]) -> Result<(), FormError> {
+        let fields = steps
+            .iter()
+            .flat_map(|s| &s.fields)
+            .flat_map(|f| std::iter::once(f).chain(&f.fields));
+        for field in fields {
+            if let Some(preset) = &field.preset {
+                return Err(FormError::InvalidData(format!(
+                    "Field preset '{preset}' was not expanded; is a field library configured?"
+                )));
+            }
+            if field.name.trim().is_empty() || field.field_type.trim().is_empty() {
+                return Err(FormError::InvalidData(
+                    "Fields need a name and a field_type".to_string(),
+                ));
+            }
+            if let Some(help) = &field.ui_options.help {
+                help.check(&field.name)?;
+            }
+            let image_choice = field.field_type.parse::<ValueType>() == Ok(ValueType::ImageChoice);
+            for option in &field.options {
+                match &option.image_url {
+                    None if image_choice => {
+                        return Err(FormError::InvalidData(format!(
+                            "Option '{}' of '{}' needs an image_url",
+                            option.value, field.name
+                        )));
+                    }
+                    Some(url) if !is_safe_url(url) => {
+                        return Err(FormError::InvalidData(format!(
+                            "Option '{}' of '{}' must have an http(s) or relative image_url, got '{url}'",
+                            option.value, field.name
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rejects repeatable groups without sub-fields, sub-fields on other
+    /// field types, and groups within groups or with location sub-fields.
+    fn check_groups(steps: &[CreateStepInput]) -> Result<(), FormError> {
+        let is_repeater = |field: &CreateFieldInput| {
+            field.field_type.parse::<ValueType>() == Ok(ValueType::Repeater)
+        };
+        let is_location = |field: &CreateFieldInput| {
+            field.field_type.parse::<ValueType>() == Ok(ValueType::Location)
+        };
+        for field in steps.iter().flat_map(|s| &s.fields) {
+            if is_repeater(field) && field.fields.is_empty() {
+                return Err(FormError::InvalidData(format!(
+                    "Repeatable group '{}' has no sub-fields",
+                    field.name
+                )));
+            }
+            if !is_repeater(field) && !field.fields.is_empty() {
+                return Err(FormError::InvalidData(format!(
+                    "'{}' has sub-fields but is not a repeater",
+                    field.name
+                )));
+            }
+            if field.fields.iter().any(|sub| is_repeater(sub) || !sub.fields.is_empty()) {
+                return Err(FormError::InvalidData(format!(
+                    "Repeatable group '{}' cannot contain another group",
+                    field.name
+                )));
+            }
+            if let Some(sub) = field.fields.iter().find(|sub| is_location(sub)) {
+                return Err(FormError::InvalidData(format!(
+                    "Repeatable group '{}' cannot contain location field '{}'",
+                    field.name, sub.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Creates a new form with all nested steps, fields, and options.
     ///
     /// This operation is transactional - if any part fails, the entire
-    /// operation is rolled back.
+    /// operation is rolled back. Fails with [`FormError::DuplicateSlug`] if
+    /// an active form already uses the slug.
     pub async fn create(db: &DatabaseConnection, input: CreateFormInput) -> Result<Form, FormError> {
-        // Check for slug uniqueness
+        Self::check_settings(&input.settings)?;
+        Self::check_fields(&input.steps)?;
+        Self::check_includes(&input.slug, &input.steps)?;
+        Self::check_jumps(&input.steps)?;
+        Self::check_groups(&input.steps)?;
+        let results = input.results.unwrap_or_default();
+        Self::check_results(&results)?;
+
+        // Check for slug uniqueness; the unique index on active slugs still
+        // catches a concurrent create
         let existing = FormEntity::find()
             .filter(FormColumn::Slug.eq(&input.slug))
             .filter(FormColumn::DeletedAt.is_null())
@@ -405,10 +920,7 @@ impl FormBuilder {
             .await?;
 
         if existing.is_some() {
-            return Err(FormError::InvalidData(format!(
-                "Form with slug '{}' already exists",
-                input.slug
-            )));
+            return Err(FormError::DuplicateSlug(input.slug));
         }
 
         // Start transaction
@@ -417,110 +929,164 @@ impl FormBuilder {
         let now = chrono::Utc::now().fixed_offset();
         let form_id = Uuid::new_v4();
 
+        // The draft flag only picks the initial status
+        let mut settings = input.settings;
+        let status = if std::mem::take(&mut settings.draft) {
+            FormStatus::Draft
+        } else {
+            FormStatus::Published
+        };
+
         // Create form
-        let form = FormActiveModel {
+        let mut form = FormActiveModel {
             id: ActiveValue::Set(form_id),
             name: ActiveValue::Set(input.name),
-            slug: ActiveValue::Set(input.slug),
+            slug: ActiveValue::Set(input.slug.clone()),
             description: ActiveValue::Set(input.description),
-            settings: ActiveValue::Set(Some(serde_json::to_value(&input.settings).unwrap_or_default())),
+            settings: ActiveValue::Set(Some(serde_json::to_value(&settings).unwrap_or_default())),
+            response_count: ActiveValue::Set(0),
+            status: ActiveValue::Set(status.as_str().to_string()),
+            lifecycle_state: ActiveValue::Set(None),
             created_at: ActiveValue::Set(now),
             updated_at: ActiveValue::Set(now),
             deleted_at: ActiveValue::Set(None),
         };
 
-        let form = form.insert(&txn).await?;
+        // New forms count as announced in the state they start in
+        let model = form.clone().try_into_model()?;
+        let (lifecycle_state, _) = current_state(&txn, &model, now).await?;
+        form.lifecycle_state = ActiveValue::Set(Some(lifecycle_state.to_string()));
 
-        // Create steps (use default step if none provided)
-        let steps = if input.steps.is_empty() {
-            vec![CreateStepInput::new("Main")]
-        } else {
-            input.steps
-        };
+        let form = form
+            .insert(&txn)
+            .await
+            .map_err(|e| slug_conflict(e, &input.slug))?;
 
-        for (step_idx, step_input) in steps.into_iter().enumerate() {
-            let step_id = Uuid::new_v4();
-            let step_order = if step_input.order == 0 {
-                step_idx as i32
-            } else {
-                step_input.order
-            };
-
-            let step = StepActiveModel {
-                id: ActiveValue::Set(step_id),
-                form_id: ActiveValue::Set(form_id),
-                name: ActiveValue::Set(step_input.name),
-                description: ActiveValue::Set(step_input.description),
-                order: ActiveValue::Set(step_order),
-                condition: ActiveValue::Set(
-                    step_input.condition.map(|c| serde_json::Value::String(c)),
-                ),
-                created_at: ActiveValue::Set(now),
-            };
-
-            step.insert(&txn).await?;
-
-            // Create fields
-            for (field_idx, field_input) in step_input.fields.into_iter().enumerate() {
-                let field_id = Uuid::new_v4();
-                let field_order = if field_input.order == 0 {
-                    field_idx as i32
-                } else {
-                    field_input.order
-                };
-
-                let validation_json = if field_input.validation_rules.is_empty() {
-                    None
-                } else {
-                    Some(serde_json::to_value(&field_input.validation_rules).unwrap_or_default())
-                };
+        let revision_fields =
+            Self::insert_steps(&txn, form_id, input.steps, now, &HashMap::new(), &[]).await?;
+        Self::insert_results(&txn, form_id, results).await?;
 
-                let ui_json = serde_json::to_value(&field_input.ui_options).ok();
-                let ui_json = ui_json.filter(|v| v != &serde_json::json!({}));
+        FormRevisionEntity::record(&txn, form_id, &revision_fields, now).await?;
 
-                let field = FieldActiveModel {
-                    id: ActiveValue::Set(field_id),
-                    step_id: ActiveValue::Set(step_id),
-                    name: ActiveValue::Set(field_input.name),
-                    label: ActiveValue::Set(field_input.label),
-                    field_type: ActiveValue::Set(field_input.field_type),
-                    order: ActiveValue::Set(field_order),
-                    required: ActiveValue::Set(field_input.required),
-                    placeholder: ActiveValue::Set(field_input.placeholder),
-                    help_text: ActiveValue::Set(field_input.help_text),
-                    default_value: ActiveValue::Set(field_input.default_value),
-                    validation_rules: ActiveValue::Set(validation_json),
-                    ui_options: ActiveValue::Set(ui_json),
-                    correct_answer: ActiveValue::Set(field_input.correct_answer),
-                    points: ActiveValue::Set(field_input.points),
-                    weight: ActiveValue::Set(field_input.weight),
-                    created_at: ActiveValue::Set(now),
-                };
+        txn.commit().await?;
 
-                field.insert(&txn).await?;
+        Ok(form)
+    }
 
-                // Create options
-                for (opt_idx, opt_input) in field_input.options.into_iter().enumerate() {
-                    let opt_order = if opt_input.order == 0 {
-                        opt_idx as i32
-                    } else {
-                        opt_input.order
-                    };
+    /// Returns the first free slug of `{slug}-copy`, `{slug}-copy-2`, ...
+    pub(crate) async fn copy_slug(db: &DatabaseConnection, slug: &str) -> Result<String, FormError> {
+        let prefix = format!("{slug}-copy");
+        let taken: HashSet<String> = FormEntity::find()
+            .filter(FormColumn::Slug.starts_with(&prefix))
+            .filter(FormColumn::DeletedAt.is_null())
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|f| f.slug)
+            .collect();
+        Ok(std::iter::once(prefix.clone())
+            .chain((2..).map(|n| format!("{prefix}-{n}")))
+            .find(|slug| !taken.contains(slug))
+            .expect("some suffix is free"))
+    }
 
-                    let option = FieldOptionActiveModel {
-                        id: ActiveValue::Set(Uuid::new_v4()),
-                        field_id: ActiveValue::Set(field_id),
-                        label: ActiveValue::Set(opt_input.label),
-                        value: ActiveValue::Set(opt_input.value),
-                        order: ActiveValue::Set(opt_order),
-                        is_correct: ActiveValue::Set(opt_input.is_correct),
-                        points: ActiveValue::Set(opt_input.points),
-                    };
+    /// Copies a form with its steps, fields, options and results, under the
+    /// first free slug of `{slug}-copy`, `{slug}-copy-2`, ... and its name
+    /// followed by "(copy)".
+    ///
+    /// Settings, conditions, jump rules and scoring are copied as they are,
+    /// and included fragments stay included. The copy starts as a draft
+    /// without submissions or seats taken.
+    pub async fn duplicate(db: &DatabaseConnection, form_id: Uuid) -> Result<Form, FormError> {
+        let source = Self::find_by_id(db, form_id)
+            .await?
+            .ok_or_else(|| FormError::NotFound(form_id.to_string()))?;
+        let steps = load_own_steps(db, form_id).await?;
+        let results = ResultEntity::find_by_form(db, form_id).await?;
+        let slug = Self::copy_slug(db, &source.slug).await?;
 
-                    option.insert(&txn).await?;
+        let txn = db.begin().await?;
+        let now = chrono::Utc::now().fixed_offset();
+        let copy_id = Uuid::new_v4();
+
+        let mut form = FormActiveModel {
+            id: ActiveValue::Set(copy_id),
+            name: ActiveValue::Set(format!("{} (copy)", source.name)),
+            slug: ActiveValue::Set(slug.clone()),
+            description: ActiveValue::Set(source.description),
+            settings: ActiveValue::Set(source.settings),
+            response_count: ActiveValue::Set(0),
+            status: ActiveValue::Set(FormStatus::Draft.as_str().to_string()),
+            lifecycle_state: ActiveValue::Set(None),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            deleted_at: ActiveValue::Set(None),
+        };
+        let model = form.clone().try_into_model()?;
+        let (lifecycle_state, _) = current_state(&txn, &model, now).await?;
+        form.lifecycle_state = ActiveValue::Set(Some(lifecycle_state.to_string()));
+        let form = form.insert(&txn).await.map_err(|e| slug_conflict(e, &slug))?;
+
+        let mut step_models = Vec::with_capacity(steps.len());
+        let mut field_models = Vec::new();
+        let mut option_models = Vec::new();
+        let mut revision_fields = Vec::new();
+        for loaded in steps {
+            let step_id = Uuid::new_v4();
+            let mut step = loaded.step.into_active_model().reset_all();
+            step.id = ActiveValue::Set(step_id);
+            step.form_id = ActiveValue::Set(copy_id);
+            step.created_at = ActiveValue::Set(now);
+            step.updated_at = ActiveValue::Set(now);
+            step_models.push(step);
+
+            for mut field in loaded.fields {
+                revision_fields.push(RevisionField {
+                    classification: field.field.classification(),
+                    name: field.field.name.clone(),
+                    label: field.field.label.clone(),
+                    field_type: field.field.field_type.clone(),
+                });
+                // A repeatable group's sub-fields follow it
+                let field_id = Uuid::new_v4();
+                let sub_fields = std::mem::take(&mut field.fields);
+                let copies = std::iter::once((None, field_id, field)).chain(
+                    sub_fields
+                        .into_iter()
+                        .map(|sub| (Some(field_id), Uuid::new_v4(), sub)),
+                );
+                for (parent_id, id, copied) in copies {
+                    let mut model = copied.field.into_active_model().reset_all();
+                    model.id = ActiveValue::Set(id);
+                    model.step_id = ActiveValue::Set(step_id);
+                    model.parent_id = ActiveValue::Set(parent_id);
+                    model.created_at = ActiveValue::Set(now);
+                    field_models.push(model);
+
+                    for option in copied.options {
+                        let mut option = option.into_active_model().reset_all();
+                        option.id = ActiveValue::Set(Uuid::new_v4());
+                        option.field_id = ActiveValue::Set(id);
+                        option.reserved = ActiveValue::Set(0);
+                        option_models.push(option);
+                    }
                 }
             }
         }
+        let result_models = results.into_iter().map(|result| {
+            let mut result = result.into_active_model().reset_all();
+            result.id = ActiveValue::Set(Uuid::new_v4());
+            result.form_id = ActiveValue::Set(copy_id);
+            result
+        });
+
+        // Parents go first so the foreign keys resolve
+        insert_batched(&txn, step_models).await?;
+        insert_batched(&txn, field_models).await?;
+        insert_batched(&txn, option_models).await?;
+        insert_batched(&txn, result_models.collect()).await?;
+
+        FormRevisionEntity::record(&txn, copy_id, &revision_fields, now).await?;
 
         txn.commit().await?;
 
@@ -536,6 +1102,15 @@ impl FormBuilder {
         form_id: Uuid,
         input: CreateFormInput,
     ) -> Result<Form, FormError> {
+        Self::check_settings(&input.settings)?;
+        Self::check_fields(&input.steps)?;
+        Self::check_includes(&input.slug, &input.steps)?;
+        Self::check_jumps(&input.steps)?;
+        Self::check_groups(&input.steps)?;
+        if let Some(results) = &input.results {
+            Self::check_results(results)?;
+        }
+
         // Find existing form
         let existing = FormEntity::find_by_id(form_id)
             .filter(FormColumn::DeletedAt.is_null())
@@ -552,15 +1127,23 @@ impl FormBuilder {
                 .await?;
 
             if slug_exists.is_some() {
-                return Err(FormError::InvalidData(format!(
-                    "Form with slug '{}' already exists",
-                    input.slug
-                )));
+                return Err(FormError::DuplicateSlug(input.slug));
             }
         }
 
         let txn = db.begin().await?;
 
+        // Counted with the form's row locked, as accepting a submission locks
+        // it too, so seats taken meanwhile aren't lost
+        lock_form(&txn, form_id).await?;
+        let reserved = capacity::reserved_by_option(&txn, form_id).await?;
+
+        // Steps are re-created below; what they were tells which changed
+        let previous = load_steps_with_fields(&txn, form_id).await?;
+        #[cfg(feature = "json")]
+        let etags = Self::step_etags(&existing, &previous);
+        let previous: Vec<Step> = previous.into_iter().map(|s| s.step).collect();
+
         // Delete existing steps (cascades to fields and options via FK)
         StepEntity::delete_many()
             .filter(crate::entities::step::Column::FormId.eq(form_id))
@@ -569,50 +1152,174 @@ impl FormBuilder {
 
         let now = chrono::Utc::now().fixed_offset();
 
+        // Setting the draft flag takes the form back to draft; otherwise the
+        // status is left to publish/unpublish/archive
+        let mut settings = input.settings;
+        let status = if std::mem::take(&mut settings.draft) {
+            ActiveValue::Set(FormStatus::Draft.as_str().to_string())
+        } else {
+            ActiveValue::Unchanged(existing.status)
+        };
+
         // Update form
         let form = FormActiveModel {
             id: ActiveValue::Unchanged(form_id),
             name: ActiveValue::Set(input.name),
-            slug: ActiveValue::Set(input.slug),
+            slug: ActiveValue::Set(input.slug.clone()),
             description: ActiveValue::Set(input.description),
-            settings: ActiveValue::Set(Some(serde_json::to_value(&input.settings).unwrap_or_default())),
+            settings: ActiveValue::Set(Some(serde_json::to_value(&settings).unwrap_or_default())),
+            response_count: ActiveValue::Unchanged(existing.response_count),
+            status,
+            lifecycle_state: ActiveValue::Unchanged(existing.lifecycle_state),
             created_at: ActiveValue::Unchanged(existing.created_at),
             updated_at: ActiveValue::Set(now),
             deleted_at: ActiveValue::Unchanged(existing.deleted_at),
         };
 
-        let form = form.update(&txn).await?;
+        let form = form
+            .update(&txn)
+            .await
+            .map_err(|e| slug_conflict(e, &input.slug))?;
+
+        // Seats already taken carry over to the re-created options
+        let revision_fields =
+            Self::insert_steps(&txn, form_id, input.steps, now, &reserved, &previous).await?;
+        #[cfg(feature = "json")]
+        Self::keep_unchanged_steps(&txn, &form, &etags).await?;
+
+        if let Some(results) = input.results {
+            ResultEntity::delete_many()
+                .filter(ResultColumn::FormId.eq(form_id))
+                .filter(ResultColumn::DeletedAt.is_null())
+                .exec(&txn)
+                .await?;
+            Self::insert_results(&txn, form_id, results).await?;
+        }
+
+        FormRevisionEntity::record(&txn, form_id, &revision_fields, now).await?;
+
+        txn.commit().await?;
+
+        Ok(form)
+    }
+
+    /// Returns the etags and change times of a form's steps, by step ID.
+    #[cfg(feature = "json")]
+    fn step_etags(
+        form: &Form,
+        steps: &[StepWithFields],
+    ) -> HashMap<Uuid, (String, DateTimeWithTimeZone)> {
+        JsonRenderer::render_steps(form, steps)
+            .steps
+            .into_iter()
+            .zip(steps)
+            .map(|(json, loaded)| (loaded.step.id, (json.etag, loaded.step.updated_at)))
+            .collect()
+    }
 
-        // Create steps (same logic as create)
-        let steps = if input.steps.is_empty() {
+    /// Restores the change time of re-created steps whose content (as
+    /// fingerprinted by [`StepJson::etag`](crate::render::StepJson::etag))
+    /// is unchanged. `etags` holds the steps' etags and change times from
+    /// before the update, by ID.
+    ///
+    /// The fingerprint is part of the JSON rendering, so without the `json`
+    /// feature every re-created step takes the update's time.
+    #[cfg(feature = "json")]
+    async fn keep_unchanged_steps(
+        txn: &DatabaseTransaction,
+        form: &Form,
+        etags: &HashMap<Uuid, (String, DateTimeWithTimeZone)>,
+    ) -> Result<(), FormError> {
+        let steps = load_steps_with_fields(txn, form.id).await?;
+        let mut kept = Vec::new();
+        let mut times = CaseStatement::new();
+        for step in JsonRenderer::render_steps(form, &steps).steps {
+            let Ok(id) = Uuid::parse_str(&step.id) else {
+                continue;
+            };
+            if let Some((_, updated_at)) = etags.get(&id).filter(|(etag, _)| *etag == step.etag) {
+                times = times.case(StepColumn::Id.eq(id), Expr::value(*updated_at));
+                kept.push(id);
+            }
+        }
+        if !kept.is_empty() {
+            // One statement for all kept steps
+            StepEntity::update_many()
+                .col_expr(StepColumn::UpdatedAt, times.into())
+                .filter(StepColumn::Id.is_in(kept))
+                .exec(txn)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Inserts the steps of a form with their fields and options.
+    ///
+    /// Rows are inserted in one batch per table rather than one statement
+    /// per row. `reserved` holds seats already taken, keyed by field name and
+    /// option value. Steps named like one of the `previous` steps keep its
+    /// ID and creation time. Returns the field snapshot for the form's
+    /// revision.
+    async fn insert_steps(
+        txn: &DatabaseTransaction,
+        form_id: Uuid,
+        steps: Vec<CreateStepInput>,
+        now: DateTimeWithTimeZone,
+        reserved: &HashMap<(String, String), i32>,
+        previous: &[Step],
+    ) -> Result<Vec<RevisionField>, FormError> {
+        // Use a default step if none provided
+        let steps = if steps.is_empty() {
             vec![CreateStepInput::new("Main")]
         } else {
-            input.steps
+            steps
         };
 
+        let mut step_models = Vec::with_capacity(steps.len());
+        let mut field_models = Vec::new();
+        let mut option_models = Vec::new();
+        let mut revision_fields = Vec::new();
+
+        let mut previous_by_name: HashMap<&str, &Step> = HashMap::new();
+        for step in previous {
+            previous_by_name.entry(step.name.as_str()).or_insert(step);
+        }
+
         for (step_idx, step_input) in steps.into_iter().enumerate() {
-            let step_id = Uuid::new_v4();
+            let kept = previous_by_name.remove(step_input.name.as_str());
+            let step_id = kept.map_or_else(Uuid::new_v4, |s| s.id);
             let step_order = if step_input.order == 0 {
                 step_idx as i32
             } else {
                 step_input.order
             };
+            let next = (!step_input.next.is_empty())
+                .then(|| serde_json::to_value(&step_input.next).unwrap_or_default());
 
-            let step = StepActiveModel {
+            step_models.push(StepActiveModel {
                 id: ActiveValue::Set(step_id),
                 form_id: ActiveValue::Set(form_id),
                 name: ActiveValue::Set(step_input.name),
                 description: ActiveValue::Set(step_input.description),
                 order: ActiveValue::Set(step_order),
                 condition: ActiveValue::Set(
-                    step_input.condition.map(|c| serde_json::Value::String(c)),
+                    step_input.condition.map(serde_json::Value::String),
                 ),
-                created_at: ActiveValue::Set(now),
-            };
-
-            step.insert(&txn).await?;
-
-            for (field_idx, field_input) in step_input.fields.into_iter().enumerate() {
+                next: ActiveValue::Set(next),
+                include_form: ActiveValue::Set(step_input.include_form),
+                include_as: ActiveValue::Set(step_input.include_as),
+                created_at: ActiveValue::Set(kept.map_or(now, |s| s.created_at)),
+                updated_at: ActiveValue::Set(now),
+            });
+
+            // Sub-fields of repeatable groups are queued after the step's fields
+            let mut queue: VecDeque<(Option<Uuid>, usize, CreateFieldInput)> = step_input
+                .fields
+                .into_iter()
+                .enumerate()
+                .map(|(idx, field)| (None, idx, field))
+                .collect();
+            while let Some((parent_id, field_idx, mut field_input)) = queue.pop_front() {
                 let field_id = Uuid::new_v4();
                 let field_order = if field_input.order == 0 {
                     field_idx as i32
@@ -629,26 +1336,21 @@ impl FormBuilder {
                 let ui_json = serde_json::to_value(&field_input.ui_options).ok();
                 let ui_json = ui_json.filter(|v| v != &serde_json::json!({}));
 
-                let field = FieldActiveModel {
-                    id: ActiveValue::Set(field_id),
-                    step_id: ActiveValue::Set(step_id),
-                    name: ActiveValue::Set(field_input.name),
-                    label: ActiveValue::Set(field_input.label),
-                    field_type: ActiveValue::Set(field_input.field_type),
-                    order: ActiveValue::Set(field_order),
-                    required: ActiveValue::Set(field_input.required),
-                    placeholder: ActiveValue::Set(field_input.placeholder),
-                    help_text: ActiveValue::Set(field_input.help_text),
-                    default_value: ActiveValue::Set(field_input.default_value),
-                    validation_rules: ActiveValue::Set(validation_json),
-                    ui_options: ActiveValue::Set(ui_json),
-                    correct_answer: ActiveValue::Set(field_input.correct_answer),
-                    points: ActiveValue::Set(field_input.points),
-                    weight: ActiveValue::Set(field_input.weight),
-                    created_at: ActiveValue::Set(now),
-                };
-
-                field.insert(&txn).await?;
+                if parent_id.is_none() {
+                    revision_fields.push(RevisionField {
+                        name: field_input.name.clone(),
+                        label: field_input.label.clone(),
+                        field_type: field_input.field_type.clone(),
+                        classification: field_input.classification,
+                    });
+                }
+                let sub_fields = std::mem::take(&mut field_input.fields);
+                queue.extend(
+                    sub_fields
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, field)| (Some(field_id), idx, field)),
+                );
 
                 for (opt_idx, opt_input) in field_input.options.into_iter().enumerate() {
                     let opt_order = if opt_input.order == 0 {
@@ -657,7 +1359,13 @@ impl FormBuilder {
                         opt_input.order
                     };
 
-                    let option = FieldOptionActiveModel {
+                    let reserved = reserved
+                        .get(&(field_input.name.clone(), opt_input.value.clone()))
+                        .filter(|_| parent_id.is_none())
+                        .copied()
+                        .unwrap_or(0);
+
+                    option_models.push(FieldOptionActiveModel {
                         id: ActiveValue::Set(Uuid::new_v4()),
                         field_id: ActiveValue::Set(field_id),
                         label: ActiveValue::Set(opt_input.label),
@@ -665,22 +1373,81 @@ impl FormBuilder {
                         order: ActiveValue::Set(opt_order),
                         is_correct: ActiveValue::Set(opt_input.is_correct),
                         points: ActiveValue::Set(opt_input.points),
-                    };
-
-                    option.insert(&txn).await?;
+                        capacity: ActiveValue::Set(opt_input.capacity),
+                        reserved: ActiveValue::Set(reserved),
+                        group: ActiveValue::Set(opt_input.group),
+                        image_url: ActiveValue::Set(opt_input.image_url),
+                    });
                 }
+
+                field_models.push(FieldActiveModel {
+                    id: ActiveValue::Set(field_id),
+                    step_id: ActiveValue::Set(step_id),
+                    parent_id: ActiveValue::Set(parent_id),
+                    name: ActiveValue::Set(field_input.name),
+                    label: ActiveValue::Set(field_input.label),
+                    field_type: ActiveValue::Set(field_input.field_type),
+                    order: ActiveValue::Set(field_order),
+                    required: ActiveValue::Set(field_input.required),
+                    placeholder: ActiveValue::Set(field_input.placeholder),
+                    help_text: ActiveValue::Set(field_input.help_text),
+                    default_value: ActiveValue::Set(field_input.default_value),
+                    validation_rules: ActiveValue::Set(validation_json),
+                    ui_options: ActiveValue::Set(ui_json),
+                    correct_answer: ActiveValue::Set(field_input.correct_answer),
+                    points: ActiveValue::Set(field_input.points),
+                    weight: ActiveValue::Set(field_input.weight),
+                    category: ActiveValue::Set(field_input.category),
+                    classification: ActiveValue::Set(
+                        field_input.classification.as_str().to_string(),
+                    ),
+                    created_at: ActiveValue::Set(now),
+                });
             }
         }
 
-        txn.commit().await?;
+        // Parents go first so the foreign keys resolve
+        insert_batched(txn, step_models).await?;
+        insert_batched(txn, field_models).await?;
+        insert_batched(txn, option_models).await?;
 
-        Ok(form)
+        Ok(revision_fields)
+    }
+
+    /// Inserts the results of a form, ordered as listed.
+    async fn insert_results(
+        txn: &DatabaseTransaction,
+        form_id: Uuid,
+        results: Vec<CreateResultInput>,
+    ) -> Result<(), FormError> {
+        let models = results
+            .into_iter()
+            .enumerate()
+            .map(|(idx, result)| ResultActiveModel {
+                id: ActiveValue::Set(Uuid::new_v4()),
+                form_id: ActiveValue::Set(form_id),
+                key: ActiveValue::Set(result.key),
+                title: ActiveValue::Set(result.title),
+                description: ActiveValue::Set(result.description),
+                min_score: ActiveValue::Set(result.min_score),
+                max_score: ActiveValue::Set(result.max_score),
+                category: ActiveValue::Set(result.category),
+                condition: ActiveValue::Set(
+                    result.condition.and_then(|c| serde_json::to_value(c).ok()),
+                ),
+                order: ActiveValue::Set(idx as i32),
+                deleted_at: ActiveValue::Set(None),
+            })
+            .collect();
+        insert_batched(txn, models).await?;
+        Ok(())
     }
 
     /// Soft-deletes a form by setting deleted_at.
     ///
     /// The form and its data remain in the database but won't appear
-    /// in normal queries.
+    /// in normal queries. Its active submissions and results are
+    /// soft-deleted along with it, with the same timestamp.
     pub async fn soft_delete(db: &DatabaseConnection, form_id: Uuid) -> Result<(), FormError> {
         let form = FormEntity::find_by_id(form_id)
             .filter(FormColumn::DeletedAt.is_null())
@@ -688,6 +1455,7 @@ impl FormBuilder {
             .await?
             .ok_or_else(|| FormError::NotFound(form_id.to_string()))?;
 
+        let txn = db.begin().await?;
         let now = chrono::Utc::now().fixed_offset();
 
         let form = FormActiveModel {
@@ -696,28 +1464,54 @@ impl FormBuilder {
             slug: ActiveValue::Unchanged(form.slug),
             description: ActiveValue::Unchanged(form.description),
             settings: ActiveValue::Unchanged(form.settings),
+            response_count: ActiveValue::Unchanged(form.response_count),
+            status: ActiveValue::Unchanged(form.status),
+            lifecycle_state: ActiveValue::Unchanged(form.lifecycle_state),
             created_at: ActiveValue::Unchanged(form.created_at),
             updated_at: ActiveValue::Set(now),
             deleted_at: ActiveValue::Set(Some(now)),
         };
 
-        form.update(db).await?;
+        form.update(&txn).await?;
+
+        SubmissionEntity::update_many()
+            .col_expr(SubmissionColumn::DeletedAt, Expr::value(now))
+            .col_expr(SubmissionColumn::UpdatedAt, Expr::value(now))
+            .filter(SubmissionColumn::FormId.eq(form_id))
+            .filter(SubmissionColumn::DeletedAt.is_null())
+            .exec(&txn)
+            .await?;
+
+        ResultEntity::update_many()
+            .col_expr(ResultColumn::DeletedAt, Expr::value(now))
+            .filter(ResultColumn::FormId.eq(form_id))
+            .filter(ResultColumn::DeletedAt.is_null())
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
 
         Ok(())
     }
 
     /// Restores a soft-deleted form.
+    ///
+    /// Only the submissions and results deleted along with the form are
+    /// restored; ones deleted on their own before stay deleted. Fails with
+    /// [`FormError::DuplicateSlug`] if an active form has taken its slug.
     pub async fn restore(db: &DatabaseConnection, form_id: Uuid) -> Result<Form, FormError> {
         let form = FormEntity::find_by_id(form_id)
             .one(db)
             .await?
             .ok_or_else(|| FormError::NotFound(form_id.to_string()))?;
 
-        if form.deleted_at.is_none() {
+        let Some(deleted_at) = form.deleted_at else {
             return Ok(form);
-        }
+        };
 
+        let txn = db.begin().await?;
         let now = chrono::Utc::now().fixed_offset();
+        let slug = form.slug.clone();
 
         let form = FormActiveModel {
             id: ActiveValue::Unchanged(form.id),
@@ -725,16 +1519,105 @@ impl FormBuilder {
             slug: ActiveValue::Unchanged(form.slug),
             description: ActiveValue::Unchanged(form.description),
             settings: ActiveValue::Unchanged(form.settings),
+            response_count: ActiveValue::Unchanged(form.response_count),
+            status: ActiveValue::Unchanged(form.status),
+            lifecycle_state: ActiveValue::Unchanged(form.lifecycle_state),
             created_at: ActiveValue::Unchanged(form.created_at),
             updated_at: ActiveValue::Set(now),
             deleted_at: ActiveValue::Set(None),
         };
 
-        let form = form.update(db).await?;
+        // Another form may have taken the slug since
+        let form = form.update(&txn).await.map_err(|e| slug_conflict(e, &slug))?;
+
+        let not_deleted: Option<DateTimeWithTimeZone> = None;
+        SubmissionEntity::update_many()
+            .col_expr(SubmissionColumn::DeletedAt, Expr::value(not_deleted))
+            .col_expr(SubmissionColumn::UpdatedAt, Expr::value(now))
+            .filter(SubmissionColumn::FormId.eq(form_id))
+            .filter(SubmissionColumn::DeletedAt.eq(deleted_at))
+            .exec(&txn)
+            .await?;
+
+        ResultEntity::update_many()
+            .col_expr(ResultColumn::DeletedAt, Expr::value(not_deleted))
+            .filter(ResultColumn::FormId.eq(form_id))
+            .filter(ResultColumn::DeletedAt.eq(deleted_at))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await?;
 
         Ok(form)
     }
 
+    /// Publishes a form, serving it on public routes.
+    pub async fn publish(db: &DatabaseConnection, form_id: Uuid) -> Result<Form, FormError> {
+        Self::set_status(db, form_id, FormStatus::Published).await
+    }
+
+    /// Takes a form back to draft, hiding it from public routes.
+    pub async fn unpublish(db: &DatabaseConnection, form_id: Uuid) -> Result<Form, FormError> {
+        Self::set_status(db, form_id, FormStatus::Draft).await
+    }
+
+    /// Archives a form, hiding it from public routes. Its submissions are
+    /// kept; publish it again to bring it back.
+    pub async fn archive(db: &DatabaseConnection, form_id: Uuid) -> Result<Form, FormError> {
+        Self::set_status(db, form_id, FormStatus::Archived).await
+    }
+
+    /// Sets a form's status, unless it is deleted.
+    pub(crate) async fn set_status(
+        db: &DatabaseConnection,
+        form_id: Uuid,
+        status: FormStatus,
+    ) -> Result<Form, FormError> {
+        let form = FormEntity::find_by_id(form_id)
+            .filter(FormColumn::DeletedAt.is_null())
+            .one(db)
+            .await?
+            .ok_or_else(|| FormError::NotFound(form_id.to_string()))?;
+
+        if form.status() == status {
+            return Ok(form);
+        }
+
+        let mut model = form.into_active_model();
+        model.status = ActiveValue::Set(status.as_str().to_string());
+        model.updated_at = ActiveValue::Set(chrono::Utc::now().fixed_offset());
+        Ok(model.update(db).await?)
+    }
+
+    /// Adds a browser to or removes it from the allow list of a form's
+    /// duplicate guard, returning the updated guard.
+    ///
+    /// Fails with [`FormError::InvalidData`] for forms without a guard.
+    pub async fn set_browser_allowed(
+        db: &DatabaseConnection,
+        form_id: Uuid,
+        browser_id: Uuid,
+        allowed: bool,
+    ) -> Result<DuplicateGuard, FormError> {
+        let form = Self::find_by_id(db, form_id)
+            .await?
+            .ok_or_else(|| FormError::NotFound(form_id.to_string()))?;
+        let mut settings = form.settings();
+        let guard = settings
+            .duplicate_guard
+            .as_mut()
+            .ok_or_else(|| FormError::InvalidData("Form has no duplicate guard".to_string()))?;
+        guard.set_allowed(&browser_id.to_string(), allowed);
+        let guard = guard.clone();
+
+        let settings = serde_json::to_value(&settings).unwrap_or_default();
+        let mut model = form.into_active_model();
+        model.settings = ActiveValue::Set(Some(settings));
+        model.updated_at = ActiveValue::Set(chrono::Utc::now().fixed_offset());
+        model.update(db).await?;
+        Ok(guard)
+    }
+
     /// Finds a form by slug (active forms only).
     pub async fn find_by_slug(db: &DatabaseConnection, slug: &str) -> Result<Option<Form>, FormError> {
         let form = FormEntity::find_by_slug(db, slug).await?;
@@ -759,8 +1642,12 @@ impl FormBuilder {
     /// Permanently deletes a form and all related data.
     ///
     /// **Warning**: This is irreversible. Use `soft_delete` for safe deletion.
+    ///
+    /// Fails with [`FormError::LegalHold`] while any of the form's
+    /// submissions are under legal hold.
     pub async fn hard_delete(db: &DatabaseConnection, form_id: Uuid) -> Result<(), FormError> {
         let txn = db.begin().await?;
+        LegalHold::ensure_form_deletable(&txn, form_id, "hard delete form").await?;
 
         // Delete options for all fields in all steps
         let steps = StepEntity::find()
@@ -793,6 +1680,8 @@ impl FormBuilder {
             .await?;
 
         // Delete submissions
+        #[cfg(feature = "search")]
+        crate::services::SearchService::clear(&txn, form_id).await?;
         crate::entities::submission::Entity::delete_many()
             .filter(crate::entities::submission::Column::FormId.eq(form_id))
             .exec(&txn)
@@ -813,6 +1702,41 @@ impl FormBuilder {
     }
 }
 
+/// Most bind parameters a single statement may use (SQLite's limit, the
+/// lowest of the supported databases).
+const MAX_BIND_PARAMS: usize = 32_766;
+
+/// Inserts rows with multi-row `INSERT` statements, as few as the bind
+/// parameter limit allows.
+pub(crate) async fn insert_batched<A>(txn: &DatabaseTransaction, mut models: Vec<A>) -> Result<(), FormError>
+where
+    A: ActiveModelTrait + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+{
+    let columns = <A::Entity as EntityTrait>::Column::iter().count();
+    let batch_size = (MAX_BIND_PARAMS / columns).max(1);
+
+    while !models.is_empty() {
+        let rest = models.split_off(batch_size.min(models.len()));
+        let batch = std::mem::replace(&mut models, rest);
+        <A::Entity as EntityTrait>::insert_many(batch)
+            .exec_without_returning(txn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Maps a unique index violation on writing a form to
+/// [`FormError::DuplicateSlug`]; the slug is the only unique column besides
+/// the random ID.
+fn slug_conflict(err: DbErr, slug: &str) -> FormError {
+    match err.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => FormError::DuplicateSlug(slug.to_string()),
+        _ => err.into(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
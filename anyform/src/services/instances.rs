@@ -0,0 +1,142 @@
+//! Recurring form instances.
+//!
+//! Forms with [`FormSettings::recurrence`](crate::schema::FormSettings::recurrence)
+//! open a new instance per period. Instances are created lazily the first time
+//! they are needed, so no background scheduler is required.
+
+use std::collections::{BTreeMap, HashMap};
+
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::{
+    form::Model as Form,
+    form_instance::{Entity as FormInstanceEntity, Model as FormInstance},
+    submission::Entity as SubmissionEntity,
+};
+use crate::error::FormError;
+use crate::services::capacity::form_fields;
+
+/// Service for resolving and comparing recurring form instances.
+pub struct InstanceService;
+
+/// Submission analytics for one instance of a recurring form.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceStats {
+    pub instance: FormInstance,
+    /// Accepted submissions in the instance, as reported under the form's
+    /// [`ResultsPrivacy`](crate::schema::ResultsPrivacy).
+    pub submissions: u64,
+    /// Whether the averages are withheld because too few respondents took
+    /// part.
+    pub suppressed: bool,
+    /// Mean quiz score, when submissions are scored.
+    pub average_score: Option<f64>,
+    /// Mean answer per numeric field (number, rating, scale, NPS), except
+    /// fields excluded from analytics by their classification.
+    pub field_averages: BTreeMap<String, f64>,
+}
+
+impl InstanceService {
+    /// Returns the instance open now, creating it if needed.
+    ///
+    /// Returns `None` for non-recurring forms and between instances.
+    pub async fn current<C: ConnectionTrait>(
+        db: &C,
+        form: &Form,
+    ) -> Result<Option<FormInstance>, FormError> {
+        Self::current_at(db, form, chrono::Utc::now().fixed_offset()).await
+    }
+
+    /// Returns the instance open at `at`, creating it if needed.
+    pub async fn current_at<C: ConnectionTrait>(
+        db: &C,
+        form: &Form,
+        at: chrono::DateTime<chrono::FixedOffset>,
+    ) -> Result<Option<FormInstance>, FormError> {
+        let Some(window) = form.settings().recurrence.and_then(|r| r.window_at(at)) else {
+            return Ok(None);
+        };
+
+        Ok(Some(
+            FormInstanceEntity::find_or_create(db, form.id, &window).await?,
+        ))
+    }
+
+    /// Compares submissions across a form's instances, oldest first.
+    ///
+    /// Forms with [`results_privacy`](crate::schema::FormSettings::results_privacy)
+    /// set get averages only for instances with enough respondents, and
+    /// counts reported accordingly.
+    pub async fn stats(
+        db: &DatabaseConnection,
+        form: &Form,
+    ) -> Result<Vec<InstanceStats>, FormError> {
+        let privacy = form.settings().results_privacy;
+        let instances = FormInstanceEntity::find_by_form(db, form.id).await?;
+        let numeric_fields: Vec<String> = form_fields(db, form.id)
+            .await?
+            .into_iter()
+            .filter(|f| f.value_type().is_some_and(|t| t.is_numeric()))
+            .filter(|f| f.classification().in_analytics())
+            .map(|f| f.name)
+            .collect();
+
+        let mut by_instance: HashMap<Uuid, Vec<_>> = HashMap::new();
+        for sub in SubmissionEntity::find_completed_by_form(db, form.id).await? {
+            if sub.is_waitlisted() {
+                continue;
+            }
+            if let Some(instance_id) = sub.instance_id {
+                by_instance.entry(instance_id).or_default().push(sub);
+            }
+        }
+
+        Ok(instances
+            .into_iter()
+            .map(|instance| {
+                let subs = by_instance.remove(&instance.id).unwrap_or_default();
+                let respondents = subs.len() as u64;
+                if let Some(privacy) = privacy.filter(|p| p.is_suppressed(respondents)) {
+                    return InstanceStats {
+                        submissions: privacy.report_count(respondents),
+                        suppressed: true,
+                        average_score: None,
+                        field_averages: BTreeMap::new(),
+                        instance,
+                    };
+                }
+
+                let scores: Vec<f64> = subs.iter().filter_map(|s| s.score).map(f64::from).collect();
+
+                let mut field_averages = BTreeMap::new();
+                for name in &numeric_fields {
+                    let values: Vec<f64> = subs
+                        .iter()
+                        .filter_map(|s| s.get_field(name).and_then(|v| v.as_number()))
+                        .collect();
+                    if let Some(avg) = mean(&values) {
+                        field_averages.insert(name.clone(), avg);
+                    }
+                }
+
+                InstanceStats {
+                    submissions: privacy.map_or(respondents, |p| p.report_count(respondents)),
+                    suppressed: false,
+                    average_score: mean(&scores),
+                    field_averages,
+                    instance,
+                }
+            })
+            .collect())
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
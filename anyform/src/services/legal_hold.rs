@@ -0,0 +1,106 @@
+//! Legal holds on submissions.
+//!
+//! A submission under legal hold can't be deleted until the hold is lifted:
+//! deleting it fails with `409 LEGAL_HOLD`, bulk deletes skip it and report
+//! it, and a form with held submissions can't be
+//! [hard-deleted](crate::FormBuilder::hard_delete). Placing and lifting holds,
+//! and every refused deletion, is logged under the [`AUDIT_TARGET`] tracing
+//! target.
+
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QuerySelect,
+};
+use uuid::Uuid;
+
+use crate::entities::submission::{self, Column, Entity as SubmissionEntity, Model as Submission};
+use crate::error::FormError;
+
+/// Tracing target of the audit log.
+pub const AUDIT_TARGET: &str = "anyform::audit";
+
+/// Service for placing, lifting and enforcing legal holds.
+pub struct LegalHold;
+
+impl LegalHold {
+    /// Places or lifts the hold on a submission, returning it updated.
+    pub async fn set(
+        db: &DatabaseConnection,
+        submission: Submission,
+        hold: bool,
+    ) -> Result<Submission, FormError> {
+        if submission.legal_hold == hold {
+            return Ok(submission);
+        }
+
+        let id = submission.id;
+        let form_id = submission.form_id;
+        let mut model: submission::ActiveModel = submission.into();
+        model.legal_hold = ActiveValue::Set(hold);
+        model.updated_at = ActiveValue::Set(chrono::Utc::now().fixed_offset());
+        let updated = model.update(db).await?;
+
+        let action = if hold { "placed" } else { "lifted" };
+        tracing::info!(
+            target: AUDIT_TARGET,
+            submission_id = %id,
+            form_id = %form_id,
+            "legal hold {action}"
+        );
+        Ok(updated)
+    }
+
+    /// Fails with [`FormError::LegalHold`] if the submission is held,
+    /// logging the refused `action`.
+    pub fn ensure_deletable(submission: &Submission, action: &str) -> Result<(), FormError> {
+        if !submission.legal_hold {
+            return Ok(());
+        }
+        refused(action, submission.form_id, &[submission.id]);
+        Err(FormError::LegalHold(format!("submission {}", submission.id)))
+    }
+
+    /// Returns the IDs of a form's held submissions, deleted or not.
+    pub async fn held<C: ConnectionTrait>(db: &C, form_id: Uuid) -> Result<Vec<Uuid>, FormError> {
+        let ids = SubmissionEntity::find()
+            .select_only()
+            .column(Column::Id)
+            .filter(Column::FormId.eq(form_id))
+            .filter(Column::LegalHold.eq(true))
+            .into_tuple()
+            .all(db)
+            .await?;
+        Ok(ids)
+    }
+
+    /// Fails with [`FormError::LegalHold`] if any of the form's submissions
+    /// are held, logging the refused `action`.
+    pub async fn ensure_form_deletable<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        action: &str,
+    ) -> Result<(), FormError> {
+        let held = Self::held(db, form_id).await?;
+        if held.is_empty() {
+            return Ok(());
+        }
+        refused(action, form_id, &held);
+        Err(FormError::LegalHold(format!(
+            "{} submission(s) of form {form_id}",
+            held.len()
+        )))
+    }
+}
+
+/// Logs deletions skipped or refused because of a legal hold.
+pub(crate) fn refused(action: &str, form_id: Uuid, held: &[Uuid]) {
+    for id in held {
+        tracing::warn!(
+            target: AUDIT_TARGET,
+            submission_id = %id,
+            form_id = %form_id,
+            action,
+            "deletion refused: submission is under legal hold"
+        );
+    }
+}
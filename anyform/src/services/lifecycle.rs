@@ -0,0 +1,136 @@
+//! Announcing when forms open and close.
+//!
+//! A form opens and closes as it's published or archived, reaches a quota,
+//! or passes its `opens_at` and `closes_at` (see
+//! [`ScheduleService`](crate::services::ScheduleService)).
+//! [`LifecycleService::announce`] compares a form's state with the one last
+//! announced, stored in `af_forms.lifecycle_state`, and tells
+//! [`FormEvents`] when it changed. The built-in handlers announce after
+//! status changes and submissions; schedules pass without a request, so
+//! also run [`LifecycleService::announce_due`] on a schedule (e.g. every
+//! minute, from cron or a timer task). Each change is announced once,
+//! however many servers check.
+
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::entities::form::{Column as FormColumn, Entity as FormEntity, Model as Form};
+use crate::error::FormError;
+use crate::events::FormEvents;
+use crate::services::{ClosedReason, LockService, ScheduleService, ScheduleState};
+
+/// Name of the lock [`LifecycleService::announce_due`] runs under.
+pub const LIFECYCLE_LOCK: &str = "anyform.lifecycle";
+
+/// A change in whether a form takes submissions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormTransition {
+    /// The form started taking submissions (or waitlisting them).
+    Opened,
+    /// The form stopped taking submissions.
+    Closed(ClosedReason),
+}
+
+/// Service for announcing forms opening and closing.
+pub struct LifecycleService;
+
+impl LifecycleService {
+    /// Announces a change in whether the form `form_id` takes submissions
+    /// at `at`, returning the change announced.
+    ///
+    /// Forms never checked before have their state recorded without an
+    /// announcement. Deleted forms are never announced.
+    pub async fn announce(
+        db: &DatabaseConnection,
+        events: &dyn FormEvents,
+        form_id: Uuid,
+        at: DateTime<FixedOffset>,
+    ) -> Result<Option<FormTransition>, FormError> {
+        let form = FormEntity::find_by_id(form_id)
+            .filter(FormColumn::DeletedAt.is_null())
+            .one(db)
+            .await?;
+        match form {
+            Some(form) => Self::announce_form(db, events, &form, at).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Announces every form whose state changed by `now`, returning the
+    /// forms and their changes.
+    ///
+    /// Runs under the [`LIFECYCLE_LOCK`] lock, so only one server checks at
+    /// a time; runs that find the lock held announce nothing.
+    pub async fn announce_due(
+        db: &DatabaseConnection,
+        events: &dyn FormEvents,
+        now: DateTime<FixedOffset>,
+    ) -> Result<Vec<(Form, FormTransition)>, FormError> {
+        let ttl = Duration::from_secs(15 * 60);
+        LockService::run_exclusive(db, LIFECYCLE_LOCK, ttl, || async {
+            let mut announced = Vec::new();
+            for form in FormEntity::find_active(db).await? {
+                if let Some(transition) = Self::announce_form(db, events, &form, now).await? {
+                    announced.push((form, transition));
+                }
+            }
+            Ok(announced)
+        })
+        .await
+        .map(Option::unwrap_or_default)
+    }
+
+    async fn announce_form(
+        db: &DatabaseConnection,
+        events: &dyn FormEvents,
+        form: &Form,
+        at: DateTime<FixedOffset>,
+    ) -> Result<Option<FormTransition>, FormError> {
+        let (state, reason) = current_state(db, form, at).await?;
+        if form.lifecycle_state.as_deref() == Some(state) {
+            return Ok(None);
+        }
+
+        // Only the check that moves the stored state on announces, so
+        // concurrent checks announce a change once
+        let mut update = FormEntity::update_many()
+            .col_expr(FormColumn::LifecycleState, Expr::value(state))
+            .filter(FormColumn::Id.eq(form.id));
+        update = match &form.lifecycle_state {
+            Some(previous) => update.filter(FormColumn::LifecycleState.eq(previous.as_str())),
+            None => update.filter(FormColumn::LifecycleState.is_null()),
+        };
+        if update.exec(db).await?.rows_affected == 0 || form.lifecycle_state.is_none() {
+            return Ok(None);
+        }
+
+        let transition = match reason {
+            Some(reason) => FormTransition::Closed(reason),
+            None => FormTransition::Opened,
+        };
+        match transition {
+            FormTransition::Opened => events.on_form_opened(form).await,
+            FormTransition::Closed(reason) => events.on_form_closed(form, reason).await,
+        }
+        Ok(Some(transition))
+    }
+}
+
+/// Returns the lifecycle state (`open` or `closed`) a form is in at `at`,
+/// and why it's closed.
+pub(crate) async fn current_state<C: ConnectionTrait>(
+    db: &C,
+    form: &Form,
+    at: DateTime<FixedOffset>,
+) -> Result<(&'static str, Option<ClosedReason>), FormError> {
+    let schedule = ScheduleService::preview(db, form, at, 0).await?;
+    let state = match schedule.state {
+        ScheduleState::Open | ScheduleState::Waitlisting => "open",
+        ScheduleState::Closed => "closed",
+    };
+    Ok((state, schedule.reason))
+}
@@ -0,0 +1,260 @@
+//! Distributed locks for jobs that must not run on several replicas at once.
+//!
+//! PostgreSQL and MySQL use the database's session-level advisory locks
+//! (`pg_try_advisory_lock`, `GET_LOCK`), taken on a connection the
+//! [`LockGuard`] takes out of the pool and keeps, outside any transaction,
+//! until it is released, so a crashed holder's lock goes away with its
+//! connection. SQLite has none: a row in `af_locks` stands in,
+//! and lapses after a TTL in case its holder never releases it.
+//!
+//! Background jobs shipped with the crate run through
+//! [`LockService::run_exclusive`].
+
+use std::future::Future;
+use std::time::Duration;
+
+use sea_orm::sea_query::OnConflict;
+use sea_orm::sqlx::pool::PoolConnection;
+use sea_orm::sqlx::{self, MySql, Postgres};
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    QueryFilter, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::lock::{self, Column as LockColumn, Entity as LockEntity};
+use crate::error::FormError;
+
+/// Service for taking named locks shared by every process using the database.
+pub struct LockService;
+
+impl LockService {
+    /// Takes the lock `name`, or returns `None` if someone else holds it.
+    ///
+    /// `ttl` bounds how long the SQLite lock table keeps a lock whose holder
+    /// never releases it; pick one well above the job's running time. Other
+    /// backends release the lock when its connection closes.
+    pub async fn try_acquire(
+        db: &DatabaseConnection,
+        name: &str,
+        ttl: Duration,
+    ) -> Result<Option<LockGuard>, FormError> {
+        let held = match db.get_database_backend() {
+            DatabaseBackend::Postgres => {
+                let mut conn = db
+                    .get_postgres_connection_pool()
+                    .acquire()
+                    .await
+                    .map_err(sqlx_error)?;
+                let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+                    .bind(lock_key(name))
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(sqlx_error)?;
+                locked.then_some(Held::Postgres(conn))
+            }
+            DatabaseBackend::MySql => {
+                let mut conn = db
+                    .get_mysql_connection_pool()
+                    .acquire()
+                    .await
+                    .map_err(sqlx_error)?;
+                let locked: Option<i64> = sqlx::query_scalar("SELECT GET_LOCK(?, 0)")
+                    .bind(mysql_name(name))
+                    .fetch_one(&mut *conn)
+                    .await
+                    .map_err(sqlx_error)?;
+                (locked == Some(1)).then_some(Held::MySql(conn))
+            }
+            DatabaseBackend::Sqlite => {
+                let holder = Uuid::new_v4();
+                Self::insert_row(db, name, holder, ttl)
+                    .await?
+                    .then(|| Held::Row {
+                        db: db.clone(),
+                        holder,
+                    })
+            }
+        };
+
+        Ok(held.map(|held| LockGuard {
+            name: name.to_string(),
+            held: Some(held),
+        }))
+    }
+
+    /// Runs `job` while holding the lock `name`, or returns `None` without
+    /// running it if someone else holds the lock.
+    ///
+    /// The lock is released when the job finishes, whether it succeeded or
+    /// not; if the job panics, dropping the guard frees it.
+    pub async fn run_exclusive<T, F, Fut>(
+        db: &DatabaseConnection,
+        name: &str,
+        ttl: Duration,
+        job: F,
+    ) -> Result<Option<T>, FormError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, FormError>>,
+    {
+        let Some(lock) = Self::try_acquire(db, name, ttl).await? else {
+            tracing::debug!(lock = name, "lock held elsewhere; skipping job");
+            return Ok(None);
+        };
+
+        let result = job().await;
+        lock.release().await?;
+        result.map(Some)
+    }
+
+    /// Inserts the lock row, first clearing an expired one.
+    ///
+    /// Returns false if the lock is held.
+    async fn insert_row(
+        db: &DatabaseConnection,
+        name: &str,
+        holder: Uuid,
+        ttl: Duration,
+    ) -> Result<bool, FormError> {
+        let now = chrono::Utc::now().fixed_offset();
+        let ttl = chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::MAX);
+        let expires_at = now.checked_add_signed(ttl).unwrap_or(now);
+
+        let txn = db.begin().await?;
+        LockEntity::delete_many()
+            .filter(LockColumn::Name.eq(name))
+            .filter(LockColumn::ExpiresAt.lte(now))
+            .exec(&txn)
+            .await?;
+
+        let row = lock::ActiveModel {
+            name: ActiveValue::Set(name.to_string()),
+            holder: ActiveValue::Set(holder),
+            acquired_at: ActiveValue::Set(now),
+            expires_at: ActiveValue::Set(expires_at),
+        };
+        let inserted = LockEntity::insert(row)
+            .on_conflict(OnConflict::column(LockColumn::Name).do_nothing().to_owned())
+            .exec_without_returning(&txn)
+            .await?;
+        txn.commit().await?;
+
+        Ok(inserted == 1)
+    }
+}
+
+/// A held lock; see [`LockService::try_acquire`].
+///
+/// Call [`release`](Self::release) when done. Dropping the guard instead
+/// closes a PostgreSQL or MySQL lock's connection rather than returning it
+/// to the pool, which frees the lock; a SQLite lock stays until its TTL
+/// lapses.
+#[must_use = "the lock should be released when done"]
+pub struct LockGuard {
+    name: String,
+    /// `None` once released.
+    held: Option<Held>,
+}
+
+enum Held {
+    /// A session advisory lock on a connection kept out of the pool.
+    Postgres(PoolConnection<Postgres>),
+    /// A `GET_LOCK` lock on a connection kept out of the pool.
+    MySql(PoolConnection<MySql>),
+    /// A row in `af_locks`.
+    Row { db: DatabaseConnection, holder: Uuid },
+}
+
+impl LockGuard {
+    /// Returns the lock's name.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Releases the lock.
+    ///
+    /// A SQLite lock that lapsed and was taken over is left to its new
+    /// holder.
+    pub async fn release(mut self) -> Result<(), FormError> {
+        let Some(held) = self.held.take() else {
+            return Ok(());
+        };
+        match held {
+            Held::Postgres(mut conn) => {
+                let unlocked = sqlx::query("SELECT pg_advisory_unlock($1)")
+                    .bind(lock_key(&self.name))
+                    .execute(&mut *conn)
+                    .await;
+                if unlocked.is_err() {
+                    // Never hand a connection that may still hold the lock back
+                    conn.close_on_drop();
+                }
+                unlocked.map_err(sqlx_error)?;
+            }
+            Held::MySql(mut conn) => {
+                let unlocked = sqlx::query("SELECT RELEASE_LOCK(?)")
+                    .bind(mysql_name(&self.name))
+                    .execute(&mut *conn)
+                    .await;
+                if unlocked.is_err() {
+                    conn.close_on_drop();
+                }
+                unlocked.map_err(sqlx_error)?;
+            }
+            Held::Row { db, holder } => {
+                LockEntity::delete_many()
+                    .filter(LockColumn::Name.eq(self.name.as_str()))
+                    .filter(LockColumn::Holder.eq(holder))
+                    .exec(&db)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        // Not released: closing the connection ends its session, and with it
+        // the lock, instead of returning it to the pool still held
+        match &mut self.held {
+            Some(Held::Postgres(conn)) => conn.close_on_drop(),
+            Some(Held::MySql(conn)) => conn.close_on_drop(),
+            Some(Held::Row { .. }) | None => {}
+        }
+    }
+}
+
+fn sqlx_error(err: sqlx::Error) -> FormError {
+    FormError::Database(err.to_string())
+}
+
+/// Returns a stable 64-bit key for a lock name (FNV-1a), as PostgreSQL
+/// advisory locks take integers.
+fn lock_key(name: &str) -> i64 {
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    i64::from_ne_bytes(hash.to_ne_bytes())
+}
+
+/// Returns the MySQL lock name for `name`, within `GET_LOCK`'s 64 characters.
+fn mysql_name(name: &str) -> String {
+    format!("anyform:{:016x}", lock_key(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_key_is_stable() {
+        let offset_basis = i64::from_ne_bytes(0xcbf2_9ce4_8422_2325_u64.to_ne_bytes());
+        assert_eq!(lock_key(""), offset_basis);
+        assert_eq!(lock_key("retention"), lock_key("retention"));
+        assert_ne!(lock_key("retention"), lock_key("archival"));
+        assert_eq!(mysql_name("retention").len(), 24);
+    }
+}
@@ -3,8 +3,83 @@
 //! This module provides high-level services for form management,
 //! including creation, updates, and deletion with full transaction support.
 
+mod analytics;
+mod capacity;
+mod consistency;
+mod crm;
+mod csv_export;
+mod digest;
 mod form_builder;
+mod instances;
+mod legal_hold;
+mod lifecycle;
+mod locks;
+mod option_import;
+mod projections;
+mod quota;
+mod results;
+mod schedule;
+mod scoring;
+#[cfg(feature = "search")]
+mod search;
+mod stats;
+mod submission_changes;
+#[cfg(feature = "pdf")]
+mod submission_pdf;
+mod submission_query;
+mod submission_schema;
+mod submissions;
+#[cfg(feature = "xlsx")]
+mod xlsx_export;
 
+pub use analytics::{
+    Analytics, FieldAnalytics, FormAnalytics, NpsBreakdown, NumberSummary, TagCount, TextSummary,
+};
+pub(crate) use capacity::form_fields;
+pub(crate) use capacity::{add_seats, clear_seats};
+pub use capacity::{release_seats, reserve_seats};
+pub use consistency::{ConsistencyIssue, ConsistencyReport, ConsistencyService, IssueKind};
+pub use crm::{CrmService, CRM_LOCK, MAX_CRM_ATTEMPTS};
+pub(crate) use csv_export::choice_fields;
+pub use csv_export::{CsvExport, DEFAULT_EXPORT_DATE_FORMAT};
+pub use digest::{Digest, DigestHighlight, DigestSender, DigestService, OptionCount, DIGEST_LOCK};
 pub use form_builder::{
-    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FormBuilder,
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateResultInput, CreateStepInput,
+    FormBuilder,
+};
+pub(crate) use quota::release_response;
+pub(crate) use quota::random_fraction;
+pub use instances::{InstanceService, InstanceStats};
+pub use legal_hold::{LegalHold, AUDIT_TARGET};
+pub use lifecycle::{FormTransition, LifecycleService, LIFECYCLE_LOCK};
+#[cfg(feature = "admin")]
+pub(crate) use legal_hold::refused as hold_refused;
+pub use locks::{LockGuard, LockService};
+pub use option_import::{ImportMode, OptionDiff, OptionImport, OptionRow};
+pub use projections::{
+    OptionSeats, Projection, ProjectionService, RebuildProgress, RebuildReport, ResponseCount,
+    BUILTIN_PROJECTIONS, DEFAULT_CHUNK_SIZE, PROJECTIONS_LOCK,
+};
+pub use results::ResultsEngine;
+pub use schedule::{
+    ClosedReason, FormSchedule, QuotaKind, QuotaProjection, ScheduleService, ScheduleState,
+    DEFAULT_UPCOMING, MAX_UPCOMING,
+};
+pub use scoring::{Score, ScoringEngine};
+#[cfg(feature = "search")]
+pub use search::{SearchHit, SearchIndex, SearchPage, SearchService, MAX_SEARCH_MATCHES};
+pub use stats::{DayCount, FormVolume, InstallationStats, Stats, STATS_DAYS, TOP_FORMS};
+pub use submission_changes::{
+    ChangeBatch, ChangeCursor, ChangeOp, RevisionSchema, SubmissionChange, SubmissionChanges,
+    CHANGES_SETTLE, DEFAULT_CHANGES_LIMIT, MAX_CHANGES_LIMIT,
+};
+#[cfg(feature = "pdf")]
+pub use submission_pdf::{SubmissionPdf, PDF_CONTENT_TYPE};
+pub use submission_query::{
+    parse_bound, SortField, SubmissionPage, SubmissionQuery, SubmissionSort, DEFAULT_PER_PAGE,
+    MAX_PER_PAGE,
 };
+pub use submission_schema::{ExportColumn, SubmissionSchema};
+pub use submissions::{SubmissionService, DRAFT_FIELD};
+#[cfg(feature = "xlsx")]
+pub use xlsx_export::{XlsxExport, XlsxWriter, XLSX_PAGE_SIZE};
@@ -0,0 +1,407 @@
+//! Bulk imports of a field's options.
+//!
+//! Long option lists, such as the countries of a select, are easier kept in
+//! a spreadsheet than in form JSON. [`OptionImport`] reads options from CSV
+//! (with a header row naming the `label`, `value`, `group` and `points`
+//! columns, in any order) or from a JSON array of the same objects, checks
+//! them for duplicates, and replaces or merges them into a field's options.
+//! Options keep their ID, capacity and seats taken when their value is
+//! imported again.
+
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::{
+    field::{self, Entity as FieldEntity},
+    field_option::{self, Column as FieldOptionColumn, Entity as FieldOptionEntity},
+    form::Model as Form,
+    step::{Column as StepColumn, Entity as StepEntity},
+};
+use crate::error::FormError;
+use crate::services::form_builder::insert_batched;
+
+/// Imports options into a field.
+pub struct OptionImport;
+
+/// An option to import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OptionRow {
+    pub label: String,
+    pub value: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub points: Option<i32>,
+}
+
+/// How imported options combine with a field's options.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// The imported options, in their order, become the field's options.
+    #[default]
+    Replace,
+    /// Options with an imported value are updated and the others are kept;
+    /// new options follow them.
+    Merge,
+}
+
+/// What an import changed, or would change on a dry run, by option value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OptionDiff {
+    pub added: Vec<String>,
+    /// Options whose label, group or points changed.
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    pub unchanged: usize,
+    /// False on a dry run.
+    pub applied: bool,
+}
+
+impl OptionImport {
+    /// Reads options from CSV with a header row.
+    ///
+    /// A missing `label` column takes the values as labels, and a missing
+    /// `value` column the labels as values. Blank lines are skipped.
+    pub fn parse_csv(text: &str) -> Result<Vec<OptionRow>, FormError> {
+        let mut records = csv_records(text.trim_start_matches('\u{feff}'))?.into_iter();
+        let Some(header) = records.next() else {
+            return Ok(Vec::new());
+        };
+        let mut columns: HashMap<String, usize> = HashMap::new();
+        for (i, name) in header.iter().enumerate() {
+            let name = name.trim().to_lowercase();
+            if !["label", "value", "group", "points"].contains(&name.as_str()) {
+                return Err(FormError::InvalidData(format!(
+                    "Unknown column '{name}'; expected label, value, group and points"
+                )));
+            }
+            columns.insert(name, i);
+        }
+        let (label, value) = match (columns.get("label"), columns.get("value")) {
+            (None, None) => {
+                return Err(FormError::InvalidData(
+                    "Options need a label or a value column".to_string(),
+                ))
+            }
+            (label, value) => (label.or(value).copied(), value.or(label).copied()),
+        };
+
+        let mut rows = Vec::new();
+        for (line, record) in records.enumerate() {
+            if record.iter().all(|cell| cell.trim().is_empty()) {
+                continue;
+            }
+            let cell = |column: Option<usize>| {
+                column
+                    .and_then(|i| record.get(i))
+                    .map(|cell| cell.trim().to_string())
+                    .filter(|cell| !cell.is_empty())
+            };
+            let points = match cell(columns.get("points").copied()) {
+                Some(points) => Some(points.parse().map_err(|_| {
+                    FormError::InvalidData(format!(
+                        "Row {}: points '{points}' is not a whole number",
+                        line + 2
+                    ))
+                })?),
+                None => None,
+            };
+            rows.push(OptionRow {
+                label: cell(label).unwrap_or_default(),
+                value: cell(value).unwrap_or_default(),
+                group: cell(columns.get("group").copied()),
+                points,
+            });
+        }
+        Ok(rows)
+    }
+
+    /// Reads options from a JSON array of objects.
+    pub fn parse_json(text: &str) -> Result<Vec<OptionRow>, FormError> {
+        serde_json::from_str(text)
+            .map_err(|e| FormError::InvalidData(format!("Invalid options JSON: {e}")))
+    }
+
+    /// Rejects options without a label or value, values imported twice, and
+    /// labels used twice in one group.
+    pub fn check(rows: &[OptionRow]) -> Result<(), FormError> {
+        let mut values = HashSet::new();
+        let mut labels = HashSet::new();
+        let mut duplicates = Vec::new();
+        for (i, row) in rows.iter().enumerate() {
+            if row.label.trim().is_empty() || row.value.trim().is_empty() {
+                return Err(FormError::InvalidData(format!(
+                    "Option {} needs a label and a value",
+                    i + 1
+                )));
+            }
+            if !values.insert(row.value.as_str()) {
+                duplicates.push(format!("value '{}'", row.value));
+            }
+            if !labels.insert((row.group.as_deref(), row.label.as_str())) {
+                duplicates.push(format!("label '{}'", row.label));
+            }
+        }
+        if !duplicates.is_empty() {
+            return Err(FormError::InvalidData(format!(
+                "Duplicate options: {}",
+                duplicates.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Imports `rows` into the options of the form's field `field_name`,
+    /// in one transaction, and returns what changed. A dry run only
+    /// returns what would change.
+    ///
+    /// Only the form's own fields take imports, not those it includes from
+    /// [fragments](crate::fragments).
+    pub async fn apply(
+        db: &DatabaseConnection,
+        form: &Form,
+        field_name: &str,
+        rows: Vec<OptionRow>,
+        mode: ImportMode,
+        dry_run: bool,
+    ) -> Result<OptionDiff, FormError> {
+        Self::check(&rows)?;
+        let field = find_field(db, form, field_name).await?;
+        let existing = FieldOptionEntity::find_by_field(db, field.id).await?;
+
+        let mut diff = OptionDiff::default();
+        let mut by_value: HashMap<String, field_option::Model> =
+            existing.iter().map(|o| (o.value.clone(), o.clone())).collect();
+        let mut options: Vec<(Option<field_option::Model>, OptionRow)> = Vec::new();
+        if mode == ImportMode::Merge {
+            // Kept options stay first, updated in place
+            let mut imported: HashMap<String, OptionRow> =
+                rows.iter().map(|r| (r.value.clone(), r.clone())).collect();
+            for option in &existing {
+                let row = imported.remove(&option.value).unwrap_or_else(|| row_of(option));
+                by_value.remove(&option.value);
+                options.push((Some(option.clone()), row));
+            }
+            options.extend(
+                rows.into_iter()
+                    .filter(|r| imported.contains_key(&r.value))
+                    .map(|r| (None, r)),
+            );
+        } else {
+            for row in rows {
+                options.push((by_value.remove(&row.value), row));
+            }
+        }
+        let removed: Vec<field_option::Model> = existing
+            .iter()
+            .filter(|o| by_value.contains_key(&o.value))
+            .cloned()
+            .collect();
+        diff.removed = removed.iter().map(|o| o.value.clone()).collect();
+
+        let mut updates = Vec::new();
+        let mut inserts = Vec::new();
+        for (order, (option, row)) in options.into_iter().enumerate() {
+            let order = order as i32;
+            match option {
+                Some(option) => {
+                    let changed = row != row_of(&option);
+                    if changed {
+                        diff.updated.push(row.value.clone());
+                    } else {
+                        diff.unchanged += 1;
+                    }
+                    if changed || option.order != order {
+                        let mut model: field_option::ActiveModel = option.into();
+                        model.label = ActiveValue::Set(row.label);
+                        model.group = ActiveValue::Set(row.group);
+                        model.points = ActiveValue::Set(row.points);
+                        model.order = ActiveValue::Set(order);
+                        updates.push(model);
+                    }
+                }
+                None => {
+                    diff.added.push(row.value.clone());
+                    inserts.push(field_option::ActiveModel {
+                        id: ActiveValue::Set(Uuid::new_v4()),
+                        field_id: ActiveValue::Set(field.id),
+                        label: ActiveValue::Set(row.label),
+                        value: ActiveValue::Set(row.value),
+                        order: ActiveValue::Set(order),
+                        is_correct: ActiveValue::Set(false),
+                        points: ActiveValue::Set(row.points),
+                        capacity: ActiveValue::Set(None),
+                        reserved: ActiveValue::Set(0),
+                        group: ActiveValue::Set(row.group),
+                        image_url: ActiveValue::Set(None),
+                    });
+                }
+            }
+        }
+        if dry_run || (updates.is_empty() && inserts.is_empty() && removed.is_empty()) {
+            return Ok(diff);
+        }
+
+        let txn = db.begin().await?;
+        if !removed.is_empty() {
+            FieldOptionEntity::delete_many()
+                .filter(FieldOptionColumn::Id.is_in(removed.iter().map(|o| o.id)))
+                .exec(&txn)
+                .await?;
+        }
+        for model in updates {
+            model.update(&txn).await?;
+        }
+        insert_batched(&txn, inserts).await?;
+        // Clients refetch steps whose content changed
+        StepEntity::update_many()
+            .col_expr(
+                StepColumn::UpdatedAt,
+                sea_orm::sea_query::Expr::value(chrono::Utc::now().fixed_offset()),
+            )
+            .filter(StepColumn::Id.eq(field.step_id))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+
+        diff.applied = true;
+        Ok(diff)
+    }
+}
+
+/// Finds the form's own field named `name` that takes options.
+async fn find_field(
+    db: &DatabaseConnection,
+    form: &Form,
+    name: &str,
+) -> Result<field::Model, FormError> {
+    let steps: Vec<Uuid> = StepEntity::find_by_form(db, form.id)
+        .await?
+        .iter()
+        .map(|s| s.id)
+        .collect();
+    let field = FieldEntity::find_by_steps(db, &steps)
+        .await?
+        .into_iter()
+        .find(|f| f.name == name)
+        .ok_or_else(|| FormError::FieldNotFound(name.to_string()))?;
+    if !field.requires_options() {
+        return Err(FormError::InvalidData(format!(
+            "Field '{name}' is a {} field, which takes no options",
+            field.field_type
+        )));
+    }
+    Ok(field)
+}
+
+/// Returns the imported form of an option.
+fn row_of(option: &field_option::Model) -> OptionRow {
+    OptionRow {
+        label: option.label.clone(),
+        value: option.value.clone(),
+        group: option.group.clone(),
+        points: option.points,
+    }
+}
+
+/// Splits CSV into records of cells. Quoted cells may hold commas, line
+/// breaks and doubled quotes.
+fn csv_records(text: &str) -> Result<Vec<Vec<String>>, FormError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if cell.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut cell)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut cell));
+                records.push(std::mem::take(&mut record));
+            }
+            c => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err(FormError::InvalidData("Unterminated quote in CSV".to_string()));
+    }
+    if !cell.is_empty() || !record.is_empty() {
+        record.push(cell);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(label: &str, value: &str) -> OptionRow {
+        OptionRow {
+            label: label.to_string(),
+            value: value.to_string(),
+            group: None,
+            points: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "\u{feff}Value,Label,group,points\r\n\
+                   fr,France,Europe,\r\n\
+                   \r\n\
+                   us,\"United States, The\",Americas,2\r\n\
+                   ci,\"C\u{f4}te d'Ivoire \"\"CI\"\"\",Africa,";
+        let rows = OptionImport::parse_csv(csv).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], OptionRow { group: Some("Europe".into()), ..row("France", "fr") });
+        assert_eq!(rows[1].label, "United States, The");
+        assert_eq!(rows[1].points, Some(2));
+        assert_eq!(rows[2].label, "C\u{f4}te d'Ivoire \"CI\"");
+    }
+
+    #[test]
+    fn test_parse_csv_with_one_column() {
+        let rows = OptionImport::parse_csv("label\nRed\nGreen\n").unwrap();
+        assert_eq!(rows, vec![row("Red", "Red"), row("Green", "Green")]);
+    }
+
+    #[test]
+    fn test_parse_csv_rejects_bad_input() {
+        assert!(OptionImport::parse_csv("name,value\nRed,red").is_err());
+        assert!(OptionImport::parse_csv("points\n1").is_err());
+        let error = OptionImport::parse_csv("label,points\nRed,many").unwrap_err();
+        assert!(error.to_string().contains("Row 2"));
+        assert!(OptionImport::parse_csv("label\n\"Red").is_err());
+    }
+
+    #[test]
+    fn test_check_finds_duplicates() {
+        let rows = vec![row("Red", "red"), row("Rouge", "red"), row("Red", "crimson")];
+        let error = OptionImport::check(&rows).unwrap_err().to_string();
+        assert!(error.contains("value 'red'"));
+        assert!(error.contains("label 'Red'"));
+
+        // The same label may appear in different groups
+        let rows = vec![
+            OptionRow { group: Some("A".into()), ..row("Other", "a-other") },
+            OptionRow { group: Some("B".into()), ..row("Other", "b-other") },
+        ];
+        assert!(OptionImport::check(&rows).is_ok());
+        assert!(OptionImport::check(&[row(" ", "x")]).is_err());
+    }
+}
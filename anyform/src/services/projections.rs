@@ -0,0 +1,272 @@
+//! Rebuilding data derived from submissions.
+//!
+//! Some tables keep what a form's submissions add up to, so the submit path
+//! needn't count them: forms' `response_count` (for `close_after` quotas)
+//! and options' `reserved` seats. A [`Projection`] derives such data, and
+//! [`ProjectionService::rebuild`] reconstructs it after a bug, a hand edit
+//! or a schema change: it clears what the projections derived for a form,
+//! then replays the form's submissions oldest first, a chunk per
+//! transaction. Replays are deterministic, so rebuilding twice gives the
+//! same result.
+//!
+//! Submissions made while a rebuild runs may be counted twice or not at
+//! all; rebuild while the form is unpublished, or run it again once quiet.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use sea_orm::sea_query::{Condition, Expr};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::{
+    form::{Column as FormColumn, Entity as FormEntity, Model as Form},
+    submission::{
+        Column as SubmissionColumn, Entity as SubmissionEntity, Model as Submission,
+        SubmissionStatus,
+    },
+};
+use crate::error::FormError;
+use crate::services::{add_seats, clear_seats, form_fields, LockService};
+
+/// Name of the lock [`ProjectionService::rebuild`] runs under, suffixed
+/// with the form's ID.
+pub const PROJECTIONS_LOCK: &str = "anyform.projections";
+
+/// Submissions replayed per transaction by default.
+pub const DEFAULT_CHUNK_SIZE: u64 = 500;
+
+/// The projections anyform maintains.
+pub const BUILTIN_PROJECTIONS: &[&dyn Projection] = &[
+    &ResponseCount,
+    &OptionSeats,
+    #[cfg(feature = "search")]
+    &crate::services::SearchIndex,
+];
+
+/// Data derived from a form's submissions.
+///
+/// Implement it for your own read models (e.g. daily aggregates) to rebuild
+/// them with [`ProjectionService::rebuild`].
+#[async_trait]
+pub trait Projection: Send + Sync {
+    /// Name to select the projection by, e.g. `response_count`.
+    fn name(&self) -> &str;
+
+    /// Clears what the projection derived from the form's submissions.
+    async fn reset(&self, db: &DatabaseTransaction, form: &Form) -> Result<(), FormError>;
+
+    /// Derives data from a chunk of the form's submissions, oldest first.
+    ///
+    /// Every submission that isn't deleted is replayed, whatever its
+    /// status; skip the ones the projection doesn't count.
+    async fn apply(
+        &self,
+        db: &DatabaseTransaction,
+        form: &Form,
+        submissions: &[Submission],
+    ) -> Result<(), FormError>;
+}
+
+impl std::fmt::Debug for dyn Projection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Projection({})", self.name())
+    }
+}
+
+/// Forms' `response_count`: their accepted submissions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResponseCount;
+
+#[async_trait]
+impl Projection for ResponseCount {
+    fn name(&self) -> &str {
+        "response_count"
+    }
+
+    async fn reset(&self, db: &DatabaseTransaction, form: &Form) -> Result<(), FormError> {
+        FormEntity::update_many()
+            .col_expr(FormColumn::ResponseCount, Expr::value(0))
+            .filter(FormColumn::Id.eq(form.id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    async fn apply(
+        &self,
+        db: &DatabaseTransaction,
+        form: &Form,
+        submissions: &[Submission],
+    ) -> Result<(), FormError> {
+        let accepted = submissions.iter().filter(|s| is_accepted(s)).count();
+        let accepted = i32::try_from(accepted).unwrap_or(i32::MAX);
+        if accepted > 0 {
+            FormEntity::update_many()
+                .col_expr(
+                    FormColumn::ResponseCount,
+                    Expr::col(FormColumn::ResponseCount).add(accepted),
+                )
+                .filter(FormColumn::Id.eq(form.id))
+                .exec(db)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Options' `reserved` seats: the options accepted submissions chose.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptionSeats;
+
+#[async_trait]
+impl Projection for OptionSeats {
+    fn name(&self) -> &str {
+        "option_seats"
+    }
+
+    async fn reset(&self, db: &DatabaseTransaction, form: &Form) -> Result<(), FormError> {
+        let fields = form_fields(db, form.id).await?;
+        clear_seats(db, &fields).await
+    }
+
+    async fn apply(
+        &self,
+        db: &DatabaseTransaction,
+        form: &Form,
+        submissions: &[Submission],
+    ) -> Result<(), FormError> {
+        let fields = form_fields(db, form.id).await?;
+        let data: Vec<_> = submissions
+            .iter()
+            .filter(|s| is_accepted(s))
+            .map(|s| &s.data)
+            .collect();
+        add_seats(db, &fields, &data).await
+    }
+}
+
+/// Returns true if the submission took a place: seats and quotas are only
+/// held by accepted submissions.
+fn is_accepted(submission: &Submission) -> bool {
+    submission.status() == SubmissionStatus::Submitted
+}
+
+/// How far a rebuild has got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RebuildProgress {
+    /// Submissions replayed so far.
+    pub replayed: u64,
+    /// Submissions to replay.
+    pub total: u64,
+}
+
+/// The outcome of a rebuild.
+#[derive(Debug, Clone, Serialize)]
+pub struct RebuildReport {
+    pub form_id: Uuid,
+    /// Names of the projections rebuilt.
+    pub projections: Vec<String>,
+    /// Submissions replayed.
+    pub replayed: u64,
+    /// Transactions the replay was committed in, after the reset.
+    pub chunks: u64,
+}
+
+/// Service for rebuilding projections from submissions.
+pub struct ProjectionService;
+
+impl ProjectionService {
+    /// Rebuilds `projections` for a form, replaying its submissions
+    /// `chunk_size` at a time and calling `progress` after each chunk.
+    ///
+    /// Runs under the form's [`PROJECTIONS_LOCK`] lock, so one rebuild of a
+    /// form runs at a time; returns `None` without rebuilding if another
+    /// holds it.
+    pub async fn rebuild(
+        db: &DatabaseConnection,
+        form: &Form,
+        projections: &[&dyn Projection],
+        chunk_size: u64,
+        progress: impl FnMut(RebuildProgress) + Send,
+    ) -> Result<Option<RebuildReport>, FormError> {
+        let lock = format!("{PROJECTIONS_LOCK}.{}", form.id);
+        let ttl = Duration::from_secs(60 * 60);
+        LockService::run_exclusive(db, &lock, ttl, || {
+            Self::rebuild_locked(db, form, projections, chunk_size.max(1), progress)
+        })
+        .await
+    }
+
+    async fn rebuild_locked(
+        db: &DatabaseConnection,
+        form: &Form,
+        projections: &[&dyn Projection],
+        chunk_size: u64,
+        mut progress: impl FnMut(RebuildProgress) + Send,
+    ) -> Result<RebuildReport, FormError> {
+        let active = || {
+            SubmissionEntity::find()
+                .filter(SubmissionColumn::FormId.eq(form.id))
+                .filter(SubmissionColumn::DeletedAt.is_null())
+        };
+        let total = active().count(db).await?;
+
+        let txn = db.begin().await?;
+        for projection in projections {
+            projection.reset(&txn, form).await?;
+        }
+        txn.commit().await?;
+
+        let mut report = RebuildReport {
+            form_id: form.id,
+            projections: projections.iter().map(|p| p.name().to_string()).collect(),
+            replayed: 0,
+            chunks: 0,
+        };
+        let mut after: Option<Submission> = None;
+        loop {
+            let mut query = active();
+            if let Some(last) = &after {
+                query = query.filter(
+                    Condition::any()
+                        .add(SubmissionColumn::CreatedAt.gt(last.created_at))
+                        .add(
+                            Condition::all()
+                                .add(SubmissionColumn::CreatedAt.eq(last.created_at))
+                                .add(SubmissionColumn::Id.gt(last.id)),
+                        ),
+                );
+            }
+            let chunk = query
+                .order_by_asc(SubmissionColumn::CreatedAt)
+                .order_by_asc(SubmissionColumn::Id)
+                .limit(chunk_size)
+                .all(db)
+                .await?;
+            if chunk.is_empty() {
+                break;
+            }
+
+            let txn = db.begin().await?;
+            for projection in projections {
+                projection.apply(&txn, form, &chunk).await?;
+            }
+            txn.commit().await?;
+
+            report.replayed += chunk.len() as u64;
+            report.chunks += 1;
+            progress(RebuildProgress {
+                replayed: report.replayed,
+                total: total.max(report.replayed),
+            });
+            after = chunk.into_iter().last();
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,108 @@
+//! Response quotas and sampling.
+//!
+//! Each form caches its accepted response count in `response_count`. The
+//! count is taken with a single conditional `UPDATE ... WHERE response_count
+//! < close_after`, so the submit path never counts submissions and
+//! concurrent submissions cannot exceed the quota; run [`record_response`] in
+//! the same transaction as the submission insert.
+
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::entities::form::{Column as FormColumn, Entity as FormEntity};
+use crate::error::FormError;
+use crate::schema::FormSettings;
+
+/// Counts one accepted response towards the form's quota.
+///
+/// Returns [`FormError::FormClosed`] once `close_after` responses have been
+/// accepted.
+pub(crate) async fn record_response<C: ConnectionTrait>(
+    db: &C,
+    form_id: Uuid,
+    close_after: Option<u32>,
+) -> Result<(), FormError> {
+    let mut update = FormEntity::update_many()
+        .col_expr(
+            FormColumn::ResponseCount,
+            Expr::col(FormColumn::ResponseCount).add(1),
+        )
+        .filter(FormColumn::Id.eq(form_id));
+    if let Some(max) = close_after {
+        update = update.filter(FormColumn::ResponseCount.lt(i64::from(max)));
+    }
+
+    if update.exec(db).await?.rows_affected == 0 {
+        return Err(FormError::FormClosed);
+    }
+    Ok(())
+}
+
+/// Locks the form's row until the end of the transaction `db` is in, by
+/// writing its `response_count` back unchanged.
+///
+/// Submissions to the same form then take their places one at a time, so a
+/// count of accepted submissions made after locking (e.g. against
+/// `max_submissions`) can't be overtaken by a concurrent one.
+pub(crate) async fn lock_form<C: ConnectionTrait>(db: &C, form_id: Uuid) -> Result<(), FormError> {
+    FormEntity::update_many()
+        .col_expr(FormColumn::ResponseCount, Expr::col(FormColumn::ResponseCount).into())
+        .filter(FormColumn::Id.eq(form_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Gives back a response counted by [`record_response`].
+pub(crate) async fn release_response<C: ConnectionTrait>(
+    db: &C,
+    form_id: Uuid,
+) -> Result<(), FormError> {
+    FormEntity::update_many()
+        .col_expr(
+            FormColumn::ResponseCount,
+            Expr::col(FormColumn::ResponseCount).sub(1),
+        )
+        .filter(FormColumn::Id.eq(form_id))
+        .filter(FormColumn::ResponseCount.gt(0))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Decides whether a submission falls within the form's `sample_rate`.
+pub(crate) fn is_sampled(settings: &FormSettings) -> bool {
+    match settings.sample_rate {
+        Some(rate) => random_fraction() < rate,
+        None => true,
+    }
+}
+
+/// Returns a uniformly distributed value in `[0, 1)`.
+pub(crate) fn random_fraction() -> f64 {
+    // The low 53 bits of a v4 UUID are random (version and variant bits sit
+    // higher) and fill an f64 mantissa exactly
+    let (_, bits) = Uuid::new_v4().as_u64_pair();
+    (bits & ((1u64 << 53) - 1)) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_rate_bounds() {
+        assert!(is_sampled(&FormSettings::new()));
+        assert!(is_sampled(&FormSettings::new().sample_rate(1.0)));
+        assert!(!is_sampled(&FormSettings::new().sample_rate(0.0)));
+    }
+
+    #[test]
+    fn test_random_fraction_in_range() {
+        for _ in 0..1000 {
+            let x = random_fraction();
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Mapping completed submissions to results.
+//!
+//! A form's results (personality types, skill levels, recommended plans...)
+//! each carry a score range, a scoring category, a condition on the answers,
+//! or a combination. When a
+//! submission is completed, the first result in order that it matches is
+//! picked, and its key stored as the submission's `result_key`; see
+//! [`FormResult::matches`](crate::entities::result::Model::matches).
+//! Scores only come from quiz forms, so results of other forms are picked
+//! by condition alone. A result with a category is picked when that
+//! category is among the submission's highest scoring ones, e.g. the
+//! personality type a respondent's answers lean towards most.
+
+use std::collections::HashMap;
+
+use sea_orm::ConnectionTrait;
+use uuid::Uuid;
+
+use crate::entities::result::{Entity as ResultEntity, Model as FormResult};
+use crate::error::FormError;
+use crate::schema::FieldValue;
+use crate::services::Score;
+
+/// Service for picking a submission's result.
+pub struct ResultsEngine;
+
+impl ResultsEngine {
+    /// Picks the result for a submission to a form with `score` (if scored)
+    /// and answers `data`.
+    pub async fn resolve<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        score: Option<&Score>,
+        data: &HashMap<String, FieldValue>,
+    ) -> Result<Option<FormResult>, FormError> {
+        let results = ResultEntity::find_by_form(db, form_id).await?;
+        Ok(Self::select(&results, score, data).cloned())
+    }
+
+    /// Picks the first of `results` the submission matches.
+    #[must_use]
+    pub fn select<'a>(
+        results: &'a [FormResult],
+        score: Option<&Score>,
+        data: &HashMap<String, FieldValue>,
+    ) -> Option<&'a FormResult> {
+        let top = score.map(Score::top_categories).unwrap_or_default();
+        results
+            .iter()
+            .find(|r| r.matches(score.map(|s| s.score), &top, data))
+    }
+}
@@ -0,0 +1,209 @@
+//! Form schedule previews.
+//!
+//! Whether a form takes submissions depends on its publish status, its
+//! `opens_at` to `closes_at` window, its response quotas and, for recurring
+//! forms, which instance is open.
+//! [`ScheduleService::preview`] works these out the way the submit path
+//! does, so admin UIs can show "opens in 3 days" or "closes after 500
+//! responses" without repeating the rules.
+
+use chrono::{DateTime, FixedOffset};
+use sea_orm::ConnectionTrait;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::{
+    form::{FormStatus, Model as Form},
+    form_instance::Entity as FormInstanceEntity,
+    submission::Entity as SubmissionEntity,
+};
+use crate::error::FormError;
+use crate::schema::InstanceWindow;
+
+/// Upcoming instances listed by default.
+pub const DEFAULT_UPCOMING: usize = 5;
+
+/// Most upcoming instances listed.
+pub const MAX_UPCOMING: usize = 52;
+
+/// Service for previewing when a form takes submissions.
+pub struct ScheduleService;
+
+/// Whether a form takes submissions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleState {
+    /// Submissions are accepted.
+    Open,
+    /// Submissions are taken but waitlisted.
+    Waitlisting,
+    /// Submissions are turned away.
+    Closed,
+}
+
+/// Why a form doesn't accept submissions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClosedReason {
+    /// The form is an unpublished draft.
+    Draft,
+    /// The form is archived.
+    Archived,
+    /// The form's `opens_at` is still to come.
+    NotYetOpen,
+    /// The form's `closes_at` has passed.
+    PastCloseDate,
+    /// `close_after` responses have been accepted.
+    QuotaReached,
+    /// `max_submissions` submissions have been accepted.
+    LimitReached,
+    /// A recurring form is between instances, or before its first.
+    BetweenInstances,
+    /// A recurring form's last instance has closed.
+    Ended,
+}
+
+/// A response limit on a form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaKind {
+    /// [`FormSettings::close_after`](crate::schema::FormSettings::close_after),
+    /// counted over the form's lifetime.
+    CloseAfter,
+    /// [`FormSettings::max_submissions`](crate::schema::FormSettings::max_submissions),
+    /// counted per instance for recurring forms.
+    MaxSubmissions,
+}
+
+/// How far a form is from one of its response limits.
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaProjection {
+    pub kind: QuotaKind,
+    pub limit: u64,
+    /// Responses counted towards the limit so far.
+    pub accepted: u64,
+    /// Responses left before the limit is reached.
+    pub remaining: u64,
+    /// What the form does once the limit is reached.
+    pub then: ScheduleState,
+}
+
+/// A form's computed schedule.
+#[derive(Debug, Clone, Serialize)]
+pub struct FormSchedule {
+    pub form_id: Uuid,
+    /// When the schedule was computed for.
+    pub at: DateTime<FixedOffset>,
+    pub state: ScheduleState,
+    /// Why the form is closed, when it is.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<ClosedReason>,
+    /// The form's response limits.
+    pub quotas: Vec<QuotaProjection>,
+    /// The recurring instance open at `at`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<InstanceWindow>,
+    /// Recurring instances opening after `at`, soonest first.
+    pub upcoming: Vec<InstanceWindow>,
+}
+
+impl ScheduleService {
+    /// Computes a form's schedule at `at`, listing up to `upcoming`
+    /// instances to come.
+    ///
+    /// Nothing is written: instances that haven't been needed yet count no
+    /// submissions.
+    pub async fn preview<C: ConnectionTrait>(
+        db: &C,
+        form: &Form,
+        at: DateTime<FixedOffset>,
+        upcoming: usize,
+    ) -> Result<FormSchedule, FormError> {
+        let settings = form.settings();
+        let instance = settings.recurrence.as_ref().and_then(|r| r.window_at(at));
+        let upcoming = settings
+            .recurrence
+            .as_ref()
+            .map(|r| r.upcoming(at, upcoming.min(MAX_UPCOMING)))
+            .unwrap_or_default();
+
+        let mut quotas = Vec::new();
+        if let Some(max) = settings.max_submissions {
+            let accepted = match (&settings.recurrence, instance) {
+                (Some(_), None) => 0,
+                (Some(_), Some(window)) => {
+                    let sequence = i32::try_from(window.sequence).unwrap_or(i32::MAX);
+                    match FormInstanceEntity::find_by_sequence(db, form.id, sequence).await? {
+                        Some(row) => {
+                            SubmissionEntity::count_accepted(db, form.id, Some(row.id)).await?
+                        }
+                        None => 0,
+                    }
+                }
+                (None, _) => SubmissionEntity::count_accepted(db, form.id, None).await?,
+            };
+            quotas.push(QuotaProjection {
+                kind: QuotaKind::MaxSubmissions,
+                limit: u64::from(max),
+                accepted,
+                remaining: u64::from(max).saturating_sub(accepted),
+                then: if settings.waitlist {
+                    ScheduleState::Waitlisting
+                } else {
+                    ScheduleState::Closed
+                },
+            });
+        }
+        if let Some(max) = settings.close_after {
+            let accepted = u64::try_from(form.response_count).unwrap_or(0);
+            quotas.push(QuotaProjection {
+                kind: QuotaKind::CloseAfter,
+                limit: u64::from(max),
+                accepted,
+                remaining: u64::from(max).saturating_sub(accepted),
+                then: ScheduleState::Closed,
+            });
+        }
+
+        let reached = |kind| quotas.iter().any(|q| q.kind == kind && q.remaining == 0);
+        let reason = match form.status() {
+            FormStatus::Draft => Some(ClosedReason::Draft),
+            FormStatus::Archived => Some(ClosedReason::Archived),
+            FormStatus::Published if settings.opens_after(at) => Some(ClosedReason::NotYetOpen),
+            FormStatus::Published if settings.closed_by(at) => Some(ClosedReason::PastCloseDate),
+            FormStatus::Published if settings.recurrence.is_some() && instance.is_none() => {
+                if upcoming.is_empty() {
+                    Some(ClosedReason::Ended)
+                } else {
+                    Some(ClosedReason::BetweenInstances)
+                }
+            }
+            FormStatus::Published if reached(QuotaKind::MaxSubmissions) && !settings.waitlist => {
+                Some(ClosedReason::LimitReached)
+            }
+            // Waitlisted submissions don't count towards `close_after`
+            FormStatus::Published
+                if reached(QuotaKind::CloseAfter)
+                    && !(reached(QuotaKind::MaxSubmissions) && settings.waitlist) =>
+            {
+                Some(ClosedReason::QuotaReached)
+            }
+            FormStatus::Published => None,
+        };
+        let state = match reason {
+            Some(_) => ScheduleState::Closed,
+            None if reached(QuotaKind::MaxSubmissions) => ScheduleState::Waitlisting,
+            None => ScheduleState::Open,
+        };
+
+        Ok(FormSchedule {
+            form_id: form.id,
+            at,
+            state,
+            reason,
+            quotas,
+            instance,
+            upcoming,
+        })
+    }
+}
@@ -0,0 +1,193 @@
+//! Scoring quiz submissions.
+//!
+//! Submissions to quiz forms ([`FormSettings::is_quiz`]) are scored as they
+//! are stored. A field counts towards the score when:
+//!
+//! - its options carry `points`: the points of the chosen options are added
+//!   up, and the field is worth its best option (or, for multi-value fields,
+//!   all options with positive points);
+//! - otherwise, it has options marked correct or a `correct_answer`: the
+//!   field's `points` (1 if unset) are earned when the answer is exactly
+//!   right; for options, when the chosen options are exactly the correct
+//!   ones, and for free text, compared trimmed and case-insensitively.
+//!
+//! A field's `weight` multiplies both what it earned and what it is worth.
+//! Fields tagged with a `category` also add what they earned to that
+//! category's score, so a personality quiz can tell which trait a
+//! respondent scored highest on. Fields hidden by a step or field condition,
+//! or on steps [jumped over](crate::branching), don't count. The score then
+//! picks the form's matching result with [`ResultsEngine`].
+//!
+//! [`ResultsEngine`]: crate::services::ResultsEngine
+//!
+//! [`FormSettings::is_quiz`]: crate::schema::FormSettings::is_quiz
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::branching::route_steps;
+use crate::entities::{
+    field,
+    field_option::{self, Column as FieldOptionColumn, Entity as FieldOptionEntity},
+    form::Model as Form,
+    step::Entity as StepEntity,
+};
+use crate::error::FormError;
+use crate::schema::FieldValue;
+use crate::services::capacity::selected_values;
+use crate::services::ResultsEngine;
+use crate::validation::is_field_visible;
+
+/// A scored submission.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Score {
+    /// Points earned.
+    pub score: i32,
+    /// Points available.
+    pub max_score: i32,
+    /// Points earned per scoring category, for fields with a category.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub categories: BTreeMap<String, i32>,
+    /// Key of the result bucket the score falls into, if any.
+    pub result_key: Option<String>,
+}
+
+impl Score {
+    /// Returns the categories with the most points, more than one on a tie.
+    #[must_use]
+    pub fn top_categories(&self) -> Vec<&str> {
+        let Some(top) = self.categories.values().max() else {
+            return Vec::new();
+        };
+        self.categories
+            .iter()
+            .filter(|(_, points)| *points == top)
+            .map(|(category, _)| category.as_str())
+            .collect()
+    }
+}
+
+/// Service for scoring quiz submissions.
+pub struct ScoringEngine;
+
+impl ScoringEngine {
+    /// Scores a submission to `form`.
+    ///
+    /// Returns `None` for forms that aren't quizzes and quizzes without any
+    /// scored fields.
+    pub async fn score<C: ConnectionTrait>(
+        db: &C,
+        form: &Form,
+        fields: &[field::Model],
+        data: &HashMap<String, FieldValue>,
+    ) -> Result<Option<Score>, FormError> {
+        if !form.settings().is_quiz || fields.is_empty() {
+            return Ok(None);
+        }
+
+        let steps = StepEntity::find_by_form(db, form.id).await?;
+        let route: HashSet<Uuid> = route_steps(&steps, fields, data).into_iter().collect();
+        let fields: Vec<field::Model> = fields
+            .iter()
+            .filter(|f| route.contains(&f.step_id) && is_field_visible(f, data))
+            .cloned()
+            .collect();
+
+        let options = FieldOptionEntity::find()
+            .filter(FieldOptionColumn::FieldId.is_in(fields.iter().map(|f| f.id)))
+            .all(db)
+            .await?;
+
+        let Some(mut score) = Self::score_answers(&fields, &options, data) else {
+            return Ok(None);
+        };
+        score.result_key = ResultsEngine::resolve(db, form.id, Some(&score), data)
+            .await?
+            .map(|r| r.key);
+        Ok(Some(score))
+    }
+
+    /// Scores answers against the fields' correct answers and option
+    /// points, without resolving a result bucket.
+    ///
+    /// Returns `None` if none of the fields is scored.
+    #[must_use]
+    pub fn score_answers(
+        fields: &[field::Model],
+        options: &[field_option::Model],
+        data: &HashMap<String, FieldValue>,
+    ) -> Option<Score> {
+        let mut total = None;
+        let mut categories: BTreeMap<String, f64> = BTreeMap::new();
+        for field in fields {
+            let field_options: Vec<&field_option::Model> =
+                options.iter().filter(|o| o.field_id == field.id).collect();
+            let chosen = data.get(&field.name).map(selected_values).unwrap_or_default();
+
+            let Some((earned, worth)) = field_points(field, &field_options, &chosen) else {
+                continue;
+            };
+            let weight = field.weight.unwrap_or(1.0);
+            let (score, max_score) = total.unwrap_or((0.0, 0.0));
+            total = Some((score + earned * weight, max_score + worth * weight));
+            if let Some(category) = &field.category {
+                *categories.entry(category.clone()).or_default() += earned * weight;
+            }
+        }
+
+        total.map(|(score, max_score)| Score {
+            score: score.round() as i32,
+            max_score: max_score.round() as i32,
+            categories: categories
+                .into_iter()
+                .map(|(category, points)| (category, points.round() as i32))
+                .collect(),
+            result_key: None,
+        })
+    }
+}
+
+/// Returns the points a field earned and is worth, or `None` if it isn't
+/// scored.
+fn field_points(
+    field: &field::Model,
+    options: &[&field_option::Model],
+    chosen: &[String],
+) -> Option<(f64, f64)> {
+    let multi = field.value_type().is_some_and(|t| t.is_multi_value());
+
+    if options.iter().any(|o| o.points.is_some()) {
+        let points = |o: &&field_option::Model| f64::from(o.points.unwrap_or(0));
+        let earned = options
+            .iter()
+            .filter(|o| chosen.contains(&o.value))
+            .map(points)
+            .sum();
+        let worth = if multi {
+            options.iter().map(points).filter(|p| *p > 0.0).sum()
+        } else {
+            options.iter().map(points).fold(0.0, f64::max)
+        };
+        return Some((earned, worth));
+    }
+
+    let worth = f64::from(field.points.unwrap_or(1));
+    let correct: BTreeSet<&str> = options
+        .iter()
+        .filter(|o| o.is_correct)
+        .map(|o| o.value.as_str())
+        .collect();
+
+    let right = if !correct.is_empty() {
+        chosen.iter().map(String::as_str).collect::<BTreeSet<_>>() == correct
+    } else if let Some(answer) = &field.correct_answer {
+        matches!(chosen, [value] if value.trim().to_lowercase() == answer.trim().to_lowercase())
+    } else {
+        return None;
+    };
+
+    Some((if right { worth } else { 0.0 }, worth))
+}
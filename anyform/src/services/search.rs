@@ -0,0 +1,445 @@
+//! Full-text search over submissions' text answers.
+//!
+//! Accepted and waitlisted submissions have their `text` and `textarea`
+//! answers indexed in `af_submission_search` as they're stored, using the
+//! database's own full-text search: a `tsvector` on PostgreSQL, FTS5 on
+//! SQLite and a `FULLTEXT` index on MySQL. Answers to sensitive fields are
+//! left out. [`SearchService::search`] ranks a listing's submissions by how
+//! well they match, best first, each with a snippet of its answers around
+//! the words searched for.
+//!
+//! Submissions stored before the `search` feature was enabled aren't
+//! indexed; rebuild the [`SearchIndex`] projection (see
+//! [`ProjectionService`](crate::services::ProjectionService)) to index them.
+
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, DatabaseTransaction,
+    EntityTrait, QueryFilter, QuerySelect,
+};
+use uuid::Uuid;
+
+use crate::entities::{
+    field,
+    form::Model as Form,
+    submission::{Column, Entity as SubmissionEntity, Model as Submission, SubmissionStatus},
+};
+use crate::error::FormError;
+use crate::response::PaginationInfo;
+use crate::schema::{FieldClassification, ValueType};
+use crate::services::analytics::statement;
+use crate::services::{form_fields, Projection, SubmissionQuery};
+
+/// The most matches a search ranks; weaker ones are left out.
+pub const MAX_SEARCH_MATCHES: u64 = 10_000;
+
+/// The most words a search looks for; later ones are ignored.
+const MAX_TERMS: usize = 16;
+
+/// Characters of context shown before the first match in a snippet.
+const SNIPPET_CONTEXT: usize = 60;
+
+/// Approximate length of a snippet, in characters.
+const SNIPPET_LENGTH: usize = 200;
+
+/// A submission matching a search.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub submission: Submission,
+    /// How well the submission matches; higher is better. Only comparable
+    /// within one search.
+    pub relevance: f64,
+    /// HTML-escaped text answers around the first match, with the words
+    /// searched for wrapped in `<mark>`.
+    pub snippet: String,
+}
+
+/// One page of search results.
+#[derive(Debug, Clone)]
+pub struct SearchPage {
+    /// The matches on this page, best first.
+    pub hits: Vec<SearchHit>,
+    /// 1-indexed page number.
+    pub page: u32,
+    pub per_page: u32,
+    /// Matches across all pages.
+    pub total: u64,
+}
+
+impl SearchPage {
+    /// Returns pagination info for the API response.
+    #[must_use]
+    pub fn pagination(&self) -> PaginationInfo {
+        let total = u32::try_from(self.total).unwrap_or(u32::MAX);
+        PaginationInfo::new(self.page, self.per_page, total)
+    }
+}
+
+/// Service for indexing and searching submissions' text answers.
+pub struct SearchService;
+
+impl SearchService {
+    /// Searches the submissions `query` lists for `text`, returning the
+    /// requested page of matches, best first.
+    ///
+    /// Submissions match when their text answers contain every word of
+    /// `text` (case-insensitively); the query's sort order is ignored.
+    /// Only the best [`MAX_SEARCH_MATCHES`] are ranked.
+    pub async fn search(
+        db: &DatabaseConnection,
+        query: &SubmissionQuery,
+        text: &str,
+    ) -> Result<SearchPage, FormError> {
+        let mut page = SearchPage {
+            hits: Vec::new(),
+            page: query.page,
+            per_page: query.per_page,
+            total: 0,
+        };
+        let terms = search_terms(text);
+        if terms.is_empty() {
+            return Ok(page);
+        }
+        let matches = Self::matches(db, query.form_id, &terms).await?;
+        if matches.is_empty() {
+            return Ok(page);
+        }
+        let Some(select) = query.filtered(db).await? else {
+            return Ok(page);
+        };
+
+        // Keep the matches the query's filters list, in ranked order
+        let ids: Vec<Uuid> = matches.iter().map(|(id, _)| *id).collect();
+        let listed: HashSet<Uuid> = select
+            .select_only()
+            .column(Column::Id)
+            .filter(Column::Id.is_in(ids))
+            .into_tuple::<Uuid>()
+            .all(db)
+            .await?
+            .into_iter()
+            .collect();
+        let matches: Vec<(Uuid, f64)> = matches
+            .into_iter()
+            .filter(|(id, _)| listed.contains(id))
+            .collect();
+        page.total = matches.len() as u64;
+
+        let offset = (page.page as usize - 1) * page.per_page as usize;
+        let matches: Vec<(Uuid, f64)> = matches
+            .into_iter()
+            .skip(offset)
+            .take(page.per_page as usize)
+            .collect();
+        let ids: Vec<Uuid> = matches.iter().map(|(id, _)| *id).collect();
+        let mut submissions: HashMap<Uuid, Submission> = SubmissionEntity::find()
+            .filter(Column::Id.is_in(ids.clone()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|s| (s.id, s))
+            .collect();
+        let mut contents = Self::contents(db, &ids).await?;
+
+        page.hits = matches
+            .into_iter()
+            .filter_map(|(id, relevance)| {
+                let submission = submissions.remove(&id)?;
+                let content = contents.remove(&id).unwrap_or_default();
+                Some(SearchHit {
+                    submission,
+                    relevance,
+                    snippet: snippet(&content, &terms),
+                })
+            })
+            .collect();
+        Ok(page)
+    }
+
+    /// Indexes a submission's text answers to `fields`.
+    ///
+    /// Submissions are indexed once, when accepted or waitlisted; previews
+    /// and in-progress submissions aren't. [`SubmissionService`] indexes
+    /// the submissions it stores, so only call this for ones written
+    /// otherwise.
+    ///
+    /// [`SubmissionService`]: crate::services::SubmissionService
+    pub async fn index<C: ConnectionTrait>(
+        db: &C,
+        fields: &[field::Model],
+        submission: &Submission,
+    ) -> Result<(), FormError> {
+        if matches!(
+            submission.status(),
+            SubmissionStatus::Preview | SubmissionStatus::InProgress
+        ) {
+            return Ok(());
+        }
+        let content = index_content(fields, &submission.data);
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        let sql = "INSERT INTO af_submission_search (submission_id, form_id, content) \
+                   VALUES ($1, $2, $3)";
+        let values = vec![submission.id.into(), submission.form_id.into(), content.into()];
+        db.execute(statement(db.get_database_backend(), sql, values))
+            .await?;
+        Ok(())
+    }
+
+    /// Removes a form's submissions from the index.
+    pub async fn clear<C: ConnectionTrait>(db: &C, form_id: Uuid) -> Result<(), FormError> {
+        let sql = "DELETE FROM af_submission_search WHERE form_id = $1";
+        db.execute(statement(db.get_database_backend(), sql, vec![form_id.into()]))
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the IDs and relevance of a form's indexed submissions
+    /// containing every term, best first.
+    async fn matches(
+        db: &DatabaseConnection,
+        form_id: Uuid,
+        terms: &[String],
+    ) -> Result<Vec<(Uuid, f64)>, FormError> {
+        let backend = db.get_database_backend();
+        let (sql, search) = match backend {
+            DatabaseBackend::Postgres => (
+                "SELECT submission_id, ts_rank(document, query)::float8 AS relevance \
+                 FROM af_submission_search, plainto_tsquery('simple', $1) AS query \
+                 WHERE form_id = $2 AND document @@ query \
+                 ORDER BY relevance DESC, submission_id LIMIT $3",
+                terms.join(" "),
+            ),
+            DatabaseBackend::Sqlite => (
+                "SELECT submission_id, -bm25(af_submission_search) AS relevance \
+                 FROM af_submission_search \
+                 WHERE af_submission_search MATCH $1 AND form_id = $2 \
+                 ORDER BY relevance DESC, submission_id LIMIT $3",
+                terms
+                    .iter()
+                    .map(|t| format!("\"{t}\""))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            DatabaseBackend::MySql => (
+                "SELECT submission_id, MATCH (content) AGAINST ($1 IN BOOLEAN MODE) AS relevance \
+                 FROM af_submission_search \
+                 WHERE form_id = $2 AND MATCH (content) AGAINST ($1 IN BOOLEAN MODE) \
+                 ORDER BY relevance DESC, submission_id LIMIT $3",
+                terms
+                    .iter()
+                    .map(|t| format!("+{t}"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+        };
+        let limit = i64::try_from(MAX_SEARCH_MATCHES).unwrap_or(i64::MAX);
+        let values = vec![search.into(), form_id.into(), limit.into()];
+        let rows = db.query_all(statement(backend, sql, values)).await?;
+        rows.iter()
+            .map(|row| {
+                let id: Uuid = row.try_get("", "submission_id")?;
+                let relevance: f64 = row.try_get("", "relevance")?;
+                Ok((id, relevance))
+            })
+            .collect()
+    }
+
+    /// Returns the indexed text of submissions, by ID.
+    async fn contents(
+        db: &DatabaseConnection,
+        ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, String>, FormError> {
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let placeholders: Vec<String> = (1..=ids.len()).map(|n| format!("${n}")).collect();
+        let sql = format!(
+            "SELECT submission_id, content FROM af_submission_search \
+             WHERE submission_id IN ({})",
+            placeholders.join(", ")
+        );
+        let values = ids.iter().map(|id| (*id).into()).collect();
+        let rows = db
+            .query_all(statement(db.get_database_backend(), &sql, values))
+            .await?;
+        rows.iter()
+            .map(|row| {
+                let id: Uuid = row.try_get("", "submission_id")?;
+                let content: String = row.try_get("", "content")?;
+                Ok((id, content))
+            })
+            .collect()
+    }
+}
+
+/// The search index: submissions' text answers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchIndex;
+
+#[async_trait]
+impl Projection for SearchIndex {
+    fn name(&self) -> &str {
+        "search_index"
+    }
+
+    async fn reset(&self, db: &DatabaseTransaction, form: &Form) -> Result<(), FormError> {
+        SearchService::clear(db, form.id).await
+    }
+
+    async fn apply(
+        &self,
+        db: &DatabaseTransaction,
+        form: &Form,
+        submissions: &[Submission],
+    ) -> Result<(), FormError> {
+        let fields = form_fields(db, form.id).await?;
+        for submission in submissions {
+            SearchService::index(db, &fields, submission).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Joins a submission's non-empty text answers to `fields`, leaving out
+/// sensitive fields and sub-fields of repeatable groups.
+fn index_content(fields: &[field::Model], data: &serde_json::Value) -> String {
+    fields
+        .iter()
+        .filter(|f| f.parent_id.is_none())
+        .filter(|f| f.classification() != FieldClassification::Sensitive)
+        .filter(|f| matches!(f.value_type(), Some(ValueType::Text | ValueType::Textarea)))
+        .filter_map(|f| data.get(&f.name)?.as_str())
+        .map(str::trim)
+        .filter(|answer| !answer.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns the distinct lowercased words of a search.
+fn search_terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for (segment, is_word) in segments(text) {
+        let term = segment.to_lowercase();
+        if is_word && !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms.truncate(MAX_TERMS);
+    terms
+}
+
+/// Splits text into runs of letters and digits (words) and the runs
+/// between them, flagging the words.
+fn segments(text: &str) -> Vec<(&str, bool)> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut in_word = None;
+    for (at, c) in text.char_indices() {
+        let is_word = c.is_alphanumeric();
+        if in_word != Some(is_word) {
+            if let Some(was_word) = in_word {
+                segments.push((&text[start..at], was_word));
+            }
+            start = at;
+            in_word = Some(is_word);
+        }
+    }
+    if let Some(was_word) = in_word {
+        segments.push((&text[start..], was_word));
+    }
+    segments
+}
+
+/// Cuts a snippet of `content` around its first match of `terms`, escaped
+/// for HTML with matches wrapped in `<mark>`.
+fn snippet(content: &str, terms: &[String]) -> String {
+    let segments = segments(content);
+    let is_match = |(text, is_word): &(&str, bool)| *is_word && terms.contains(&text.to_lowercase());
+    let first = segments.iter().position(is_match).unwrap_or(0);
+
+    let mut start = first;
+    let mut before = 0;
+    while start > 0 {
+        let length = segments[start - 1].0.chars().count();
+        if before + length > SNIPPET_CONTEXT {
+            break;
+        }
+        before += length;
+        start -= 1;
+    }
+    if start < first && !segments[start].1 {
+        start += 1;
+    }
+
+    let mut html = String::new();
+    if start > 0 {
+        html.push('…');
+    }
+    let mut length = 0;
+    let mut end = start;
+    while end < segments.len() && (length < SNIPPET_LENGTH || end <= first) {
+        let segment = &segments[end];
+        if is_match(segment) {
+            html.push_str("<mark>");
+            escape_into(&mut html, segment.0);
+            html.push_str("</mark>");
+        } else {
+            escape_into(&mut html, segment.0);
+        }
+        length += segment.0.chars().count();
+        end += 1;
+    }
+    if end < segments.len() {
+        html.push('…');
+    }
+    html
+}
+
+/// Appends text escaped for HTML, with line breaks between answers as
+/// spaces.
+fn escape_into(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            '\n' => html.push(' '),
+            c => html.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_terms() {
+        assert_eq!(search_terms("Slow  CHECKOUT, slow!"), vec!["slow", "checkout"]);
+        assert_eq!(search_terms("\"a\" OR b*"), vec!["a", "or", "b"]);
+        assert!(search_terms(" -- ").is_empty());
+    }
+
+    #[test]
+    fn test_snippet_marks_matches() {
+        let terms = search_terms("checkout");
+        assert_eq!(
+            snippet("The <checkout> was slow.\nCheckout again?", &terms),
+            "The &lt;<mark>checkout</mark>&gt; was slow. <mark>Checkout</mark> again?"
+        );
+
+        let long = format!("{} the checkout {}", "word ".repeat(40), "word ".repeat(60));
+        let cut = snippet(&long, &terms);
+        assert!(cut.starts_with("…word"));
+        assert!(cut.contains("<mark>checkout</mark>"));
+        assert!(cut.ends_with('…'));
+        assert!(cut.chars().count() < long.chars().count());
+    }
+}
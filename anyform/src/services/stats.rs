@@ -0,0 +1,163 @@
+//! Installation-wide statistics computed in the database.
+//!
+//! [`Stats`] totals the forms and submissions of the whole installation
+//! with grouped SQL queries: submissions per day (UTC) over the last
+//! [`STATS_DAYS`] days, and per form, from which the busiest forms and the
+//! average completion rate are taken.
+
+use std::collections::HashMap;
+
+use chrono::{Days, NaiveDate, Utc};
+use sea_orm::{ConnectionTrait, DatabaseBackend, DatabaseConnection, QueryResult};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::error::FormError;
+use crate::services::analytics::statement;
+
+/// Days [`InstallationStats::submissions_per_day`] covers, today included.
+pub const STATS_DAYS: u64 = 30;
+
+/// Forms [`InstallationStats::top_forms`] lists.
+pub const TOP_FORMS: usize = 10;
+
+/// Submissions counted: those to forms not deleted, except previews and
+/// deleted submissions.
+const COUNTED: &str = "af_submissions s JOIN af_forms f ON f.id = s.form_id \
+    WHERE f.deleted_at IS NULL AND s.deleted_at IS NULL AND s.status <> 'preview'";
+
+/// Computes installation-wide statistics.
+pub struct Stats;
+
+/// Totals across the installation.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallationStats {
+    /// Forms not deleted, drafts and archived ones included.
+    pub forms: u64,
+    pub published_forms: u64,
+    /// Submissions started, including incomplete and waitlisted ones.
+    pub submissions: u64,
+    /// Mean of the completion rates of forms with submissions, from 0 to 1.
+    pub average_completion_rate: Option<f64>,
+    /// Submissions started per day over the last [`STATS_DAYS`] days,
+    /// oldest first, days without any included.
+    pub submissions_per_day: Vec<DayCount>,
+    /// The [`TOP_FORMS`] forms with the most submissions, most first.
+    pub top_forms: Vec<FormVolume>,
+}
+
+/// Submissions started on one day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DayCount {
+    pub date: NaiveDate,
+    pub submissions: u64,
+}
+
+/// A form's submission volume.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FormVolume {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    /// Submissions started, including incomplete and waitlisted ones.
+    pub submissions: u64,
+    /// Share of started submissions that were completed, from 0 to 1.
+    pub completion_rate: f64,
+}
+
+impl Stats {
+    /// Computes the installation's statistics as of now.
+    pub async fn installation(db: &DatabaseConnection) -> Result<InstallationStats, FormError> {
+        let backend = db.get_database_backend();
+        let row = query_one(
+            db,
+            "SELECT COUNT(*) AS forms, \
+             COUNT(CASE WHEN status = 'published' THEN 1 END) AS published \
+             FROM af_forms WHERE deleted_at IS NULL",
+            vec![],
+        )
+        .await?;
+        let (forms, published_forms) = (count(&row, "forms")?, count(&row, "published")?);
+
+        let sql = format!(
+            "SELECT f.id AS id, f.name AS name, f.slug AS slug, COUNT(*) AS submissions, \
+             COUNT(s.completed_at) AS completed FROM {COUNTED} \
+             GROUP BY f.id, f.name, f.slug ORDER BY submissions DESC, f.name"
+        );
+        let mut volumes = Vec::new();
+        for row in query_all(db, &sql, vec![]).await? {
+            let submissions = count(&row, "submissions")?;
+            volumes.push(FormVolume {
+                id: row.try_get("", "id")?,
+                name: row.try_get("", "name")?,
+                slug: row.try_get("", "slug")?,
+                submissions,
+                completion_rate: count(&row, "completed")? as f64 / submissions as f64,
+            });
+        }
+        let submissions = volumes.iter().map(|v| v.submissions).sum();
+        let average_completion_rate = (!volumes.is_empty()).then(|| {
+            volumes.iter().map(|v| v.completion_rate).sum::<f64>() / volumes.len() as f64
+        });
+        volumes.truncate(TOP_FORMS);
+
+        let today = Utc::now().date_naive();
+        let first = today - Days::new(STATS_DAYS - 1);
+        let day = match backend {
+            DatabaseBackend::Sqlite => "date(s.created_at)",
+            DatabaseBackend::Postgres => "to_char(s.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD')",
+            DatabaseBackend::MySql => "DATE_FORMAT(s.created_at, '%Y-%m-%d')",
+        };
+        let sql = format!(
+            "SELECT day, COUNT(*) AS submissions FROM \
+             (SELECT {day} AS day FROM {COUNTED}) days \
+             WHERE day >= $1 GROUP BY day"
+        );
+        let mut per_day = HashMap::new();
+        for row in query_all(db, &sql, vec![first.format("%Y-%m-%d").to_string().into()]).await? {
+            let day: String = row.try_get("", "day")?;
+            if let Ok(date) = NaiveDate::parse_from_str(&day, "%Y-%m-%d") {
+                per_day.insert(date, count(&row, "submissions")?);
+            }
+        }
+        let submissions_per_day = first
+            .iter_days()
+            .take_while(|date| *date <= today)
+            .map(|date| DayCount {
+                date,
+                submissions: per_day.get(&date).copied().unwrap_or(0),
+            })
+            .collect();
+
+        Ok(InstallationStats {
+            forms,
+            published_forms,
+            submissions,
+            average_completion_rate,
+            submissions_per_day,
+            top_forms: volumes,
+        })
+    }
+}
+
+fn count(row: &QueryResult, column: &str) -> Result<u64, FormError> {
+    Ok(u64::try_from(row.try_get::<i64>("", column)?).unwrap_or_default())
+}
+
+async fn query_one(
+    db: &DatabaseConnection,
+    sql: &str,
+    values: Vec<sea_orm::Value>,
+) -> Result<QueryResult, FormError> {
+    db.query_one(statement(db.get_database_backend(), sql, values))
+        .await?
+        .ok_or_else(|| FormError::Database("aggregate query returned no row".into()))
+}
+
+async fn query_all(
+    db: &DatabaseConnection,
+    sql: &str,
+    values: Vec<sea_orm::Value>,
+) -> Result<Vec<QueryResult>, FormError> {
+    Ok(db.query_all(statement(db.get_database_backend(), sql, values)).await?)
+}
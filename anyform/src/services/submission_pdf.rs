@@ -0,0 +1,245 @@
+//! Printable PDFs of single submissions.
+//!
+//! [`SubmissionPdf`] renders one response as question and answer pairs, for
+//! attaching to tickets and compliance records. Questions are labelled as in
+//! the revision the submission was made against, chosen options by their
+//! labels, and the form's [`Branding`] colors the header while its footer
+//! text runs along the bottom of every page. The logo isn't included, as
+//! branding only links to it.
+
+use std::collections::HashMap;
+
+use sea_orm::DatabaseConnection;
+
+use crate::entities::{form::Model as Form, submission::Model as Submission};
+use crate::error::FormError;
+use crate::pdf::{Document, Font, Rgb, PAGE_HEIGHT, PAGE_WIDTH};
+use crate::repository::FieldWithOptions;
+use crate::schema::Branding;
+use crate::services::csv_export::choice_fields;
+use crate::services::SubmissionSchema;
+
+/// Media type of PDF documents.
+pub const PDF_CONTENT_TYPE: &str = "application/pdf";
+
+const MARGIN: f32 = 56.0;
+const HEADER_HEIGHT: f32 = 84.0;
+const FOOTER_Y: f32 = 32.0;
+const LABEL_SIZE: f32 = 9.0;
+const ANSWER_SIZE: f32 = 11.0;
+const LINE_SPACING: f32 = 1.4;
+
+/// Header color of forms without a branded primary color.
+const DEFAULT_PRIMARY: Rgb = Rgb(0.18, 0.22, 0.28);
+const MUTED: Rgb = Rgb(0.4, 0.4, 0.4);
+
+/// Service for rendering submissions as PDF documents.
+pub struct SubmissionPdf;
+
+impl SubmissionPdf {
+    /// Renders a submission to `form` as a PDF document.
+    ///
+    /// Answers to sensitive fields are left out, with a note of how many,
+    /// unless `include_sensitive` is set.
+    pub async fn render(
+        db: &DatabaseConnection,
+        form: &Form,
+        submission: &Submission,
+        include_sensitive: bool,
+    ) -> Result<Vec<u8>, FormError> {
+        let schema = SubmissionSchema::load(db, form.id, std::slice::from_ref(submission)).await?;
+        let choices = choice_fields(db, form.id).await?;
+        Self::render_with(form, &schema, &choices, submission, include_sensitive)
+    }
+
+    /// Renders a submission as [`render`](Self::render) does, with its
+    /// schema and the form's choice fields already loaded.
+    pub fn render_with(
+        form: &Form,
+        schema: &SubmissionSchema,
+        choices: &[FieldWithOptions],
+        submission: &Submission,
+        include_sensitive: bool,
+    ) -> Result<Vec<u8>, FormError> {
+        let options = option_labels(choices);
+
+        let mut answers = Vec::new();
+        let mut withheld = 0;
+        for column in schema.columns() {
+            let Some(value) = submission.data.get(&column.name).filter(|v| !v.is_null()) else {
+                continue;
+            };
+            if !include_sensitive && !column.classification.in_default_export() {
+                withheld += 1;
+                continue;
+            }
+            let label = schema
+                .label_for(submission.revision_id, &column.name)
+                .unwrap_or(&column.label);
+            answers.push((label.to_string(), answer_text(value, options.get(&column.name))));
+        }
+
+        let settings = form.settings();
+        let branding = settings.branding.as_ref().filter(|b| b.check().is_ok());
+        let mut layout = Layout::new(form, branding);
+
+        let mut details = vec![
+            format!("Submission {}", submission.id),
+            format!("Status: {}", submission.status),
+        ];
+        let submitted_at = submission.completed_at.unwrap_or(submission.created_at);
+        details.push(format!("Submitted: {}", submitted_at.format("%Y-%m-%d %H:%M:%S %:z")));
+        if let (Some(score), Some(max)) = (submission.score, submission.max_score) {
+            details.push(format!("Score: {score} / {max}"));
+        }
+        if let Some(result) = &submission.result_key {
+            details.push(format!("Result: {result}"));
+        }
+        layout.paragraph(&details.join("\n"), Font::Regular, LABEL_SIZE, MUTED);
+        layout.gap(ANSWER_SIZE);
+
+        for (label, answer) in &answers {
+            layout.answer(label, answer);
+        }
+        if answers.is_empty() {
+            layout.paragraph("No answers.", Font::Regular, ANSWER_SIZE, MUTED);
+        }
+        if withheld > 0 {
+            let note = format!("{withheld} sensitive answer(s) left out.");
+            layout.paragraph(&note, Font::Regular, LABEL_SIZE, MUTED);
+        }
+
+        layout.finish(branding)
+    }
+}
+
+/// Lays out a submission top to bottom, starting pages as they fill up.
+struct Layout {
+    doc: Document,
+    primary: Rgb,
+    title: String,
+    /// Baseline of the next line.
+    y: f32,
+}
+
+impl Layout {
+    fn new(form: &Form, branding: Option<&Branding>) -> Self {
+        let primary = branding
+            .and_then(|b| b.primary_color.as_deref())
+            .and_then(Rgb::parse_css)
+            .unwrap_or(DEFAULT_PRIMARY);
+        let mut layout = Self {
+            doc: Document::new(format!("{} submission", form.name)),
+            primary,
+            title: form.name.clone(),
+            y: 0.0,
+        };
+        layout.start_page();
+        layout
+    }
+
+    /// Starts a page under a header band with the form's name.
+    fn start_page(&mut self) {
+        let page = self.doc.add_page();
+        page.rect(0.0, PAGE_HEIGHT - HEADER_HEIGHT, PAGE_WIDTH, HEADER_HEIGHT, self.primary);
+        let title = Font::Bold.wrap(&self.title, 18.0, PAGE_WIDTH - 2.0 * MARGIN);
+        page.text(MARGIN, PAGE_HEIGHT - 50.0, Font::Bold, 18.0, Rgb::WHITE, &title[0]);
+        self.y = PAGE_HEIGHT - HEADER_HEIGHT - 32.0;
+    }
+
+    /// Makes room for `height` more points, on a new page if needed.
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < FOOTER_Y + 24.0 {
+            self.start_page();
+        }
+    }
+
+    fn gap(&mut self, height: f32) {
+        self.y -= height;
+    }
+
+    /// Writes wrapped text, a line at a time.
+    fn paragraph(&mut self, text: &str, font: Font, size: f32, color: Rgb) {
+        for line in font.wrap(text, size, PAGE_WIDTH - 2.0 * MARGIN) {
+            self.reserve(size * LINE_SPACING);
+            self.doc.page().text(MARGIN, self.y, font, size, color, &line);
+            self.y -= size * LINE_SPACING;
+        }
+    }
+
+    /// Writes a question's label and its answer, keeping the label with at
+    /// least the first line of the answer.
+    fn answer(&mut self, label: &str, answer: &str) {
+        self.reserve((LABEL_SIZE + ANSWER_SIZE) * LINE_SPACING);
+        self.paragraph(label, Font::Bold, LABEL_SIZE, self.primary);
+        self.paragraph(answer, Font::Regular, ANSWER_SIZE, Rgb::BLACK);
+        self.gap(ANSWER_SIZE * 0.8);
+    }
+
+    /// Adds the footer and page numbers to every page and returns the
+    /// document.
+    fn finish(mut self, branding: Option<&Branding>) -> Result<Vec<u8>, FormError> {
+        let footer = branding.map(footer_text).unwrap_or_default();
+        let pages = self.doc.pages_mut();
+        let count = pages.len();
+        for (i, page) in pages.iter_mut().enumerate() {
+            let number = format!("Page {} of {count}", i + 1);
+            let number_width = Font::Regular.width(&number, 8.0);
+            let x = PAGE_WIDTH - MARGIN - number_width;
+            page.text(x, FOOTER_Y, Font::Regular, 8.0, MUTED, &number);
+
+            let room = PAGE_WIDTH - 2.0 * MARGIN - number_width - 12.0;
+            if let Some(line) = Font::Regular.wrap(&footer, 8.0, room).first() {
+                page.text(MARGIN, FOOTER_Y, Font::Regular, 8.0, MUTED, line);
+            }
+        }
+        self.doc.finish()
+    }
+}
+
+/// Returns the branding's footer text followed by its links.
+fn footer_text(branding: &Branding) -> String {
+    let mut parts: Vec<String> = branding.footer_text.iter().cloned().collect();
+    parts.extend(
+        branding
+            .footer_links
+            .iter()
+            .map(|link| format!("{}: {}", link.label, link.url)),
+    );
+    parts.join(" · ")
+}
+
+/// Returns the labels of the form's options by field name, then value.
+fn option_labels(choices: &[FieldWithOptions]) -> HashMap<String, HashMap<String, String>> {
+    choices
+        .iter()
+        .map(|choice| {
+            let labels = choice
+                .options
+                .iter()
+                .map(|o| (o.value.clone(), o.label.clone()))
+                .collect();
+            (choice.field.name.clone(), labels)
+        })
+        .collect()
+}
+
+/// Returns an answer as text, with chosen options by their labels.
+fn answer_text(value: &serde_json::Value, options: Option<&HashMap<String, String>>) -> String {
+    let label = |value: &str| {
+        options
+            .and_then(|o| o.get(value))
+            .map_or_else(|| value.to_string(), Clone::clone)
+    };
+    match value {
+        serde_json::Value::Bool(true) => "Yes".to_string(),
+        serde_json::Value::Bool(false) => "No".to_string(),
+        serde_json::Value::String(s) => label(s),
+        serde_json::Value::Array(values) => values
+            .iter()
+            .map(|v| v.as_str().map_or_else(|| v.to_string(), label))
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,393 @@
+//! Paginated, filtered submission listings.
+//!
+//! [`SubmissionQuery`] does its filtering, sorting and paging in the
+//! database, so listing a form with a large number of submissions only ever
+//! loads one page of them.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, Order, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, QueryTrait, Select,
+};
+use uuid::Uuid;
+
+use crate::entities::{
+    submission::{Column, Entity as SubmissionEntity, Model as Submission, SubmissionStatus},
+    submission_note::{self, Entity as SubmissionNoteEntity},
+};
+use crate::error::FormError;
+use crate::response::PaginationInfo;
+
+/// Submissions per page, unless asked for otherwise.
+pub const DEFAULT_PER_PAGE: u32 = 50;
+
+/// The most submissions a single page can hold.
+pub const MAX_PER_PAGE: u32 = 500;
+
+/// Column a submission listing is ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortField {
+    /// When the submission was created.
+    #[default]
+    CreatedAt,
+    /// When the submission was completed; in-progress submissions have none.
+    CompletedAt,
+    /// Quiz score; unscored submissions have none.
+    Score,
+}
+
+/// Order of a submission listing, written `field` for ascending or
+/// `-field` for descending (e.g. `-score`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubmissionSort {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+impl Default for SubmissionSort {
+    /// Newest first.
+    fn default() -> Self {
+        Self {
+            field: SortField::CreatedAt,
+            descending: true,
+        }
+    }
+}
+
+impl FromStr for SubmissionSort {
+    type Err = FormError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (descending, name) = match s.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, s),
+        };
+        let field = match name {
+            "created_at" => SortField::CreatedAt,
+            "completed_at" => SortField::CompletedAt,
+            "score" => SortField::Score,
+            _ => {
+                return Err(FormError::InvalidData(format!(
+                    "Cannot sort submissions by '{s}' (expected created_at, completed_at \
+                    or score, optionally prefixed with '-')"
+                )))
+            }
+        };
+        Ok(Self { field, descending })
+    }
+}
+
+/// One page of a submission listing.
+#[derive(Debug, Clone)]
+pub struct SubmissionPage {
+    /// The submissions on this page.
+    pub submissions: Vec<Submission>,
+    /// 1-indexed page number.
+    pub page: u32,
+    pub per_page: u32,
+    /// Submissions matching the query across all pages.
+    pub total: u64,
+}
+
+impl SubmissionPage {
+    /// Returns pagination info for the API response.
+    #[must_use]
+    pub fn pagination(&self) -> PaginationInfo {
+        let total = u32::try_from(self.total).unwrap_or(u32::MAX);
+        PaginationInfo::new(self.page, self.per_page, total)
+    }
+}
+
+/// Builder for a paginated listing of one form's submissions.
+///
+/// Previews are only listed when asked for by status, and deleted
+/// submissions never are.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let page = SubmissionQuery::new(form.id)
+///     .completed(true)
+///     .sort("-score".parse()?)
+///     .page(2)
+///     .fetch(&db)
+///     .await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct SubmissionQuery {
+    pub(crate) form_id: Uuid,
+    status: Option<SubmissionStatus>,
+    instance: Option<Uuid>,
+    respondent: Option<String>,
+    label: Option<String>,
+    since: Option<DateTime<FixedOffset>>,
+    until: Option<DateTime<FixedOffset>>,
+    completed: Option<bool>,
+    sort: SubmissionSort,
+    pub(crate) page: u32,
+    pub(crate) per_page: u32,
+}
+
+impl SubmissionQuery {
+    /// Lists every submission of a form, newest first, one default-sized
+    /// page at a time.
+    #[must_use]
+    pub fn new(form_id: Uuid) -> Self {
+        Self {
+            form_id,
+            status: None,
+            instance: None,
+            respondent: None,
+            label: None,
+            since: None,
+            until: None,
+            completed: None,
+            sort: SubmissionSort::default(),
+            page: 1,
+            per_page: DEFAULT_PER_PAGE,
+        }
+    }
+
+    /// Only include submissions with this status.
+    #[must_use]
+    pub fn status(mut self, status: SubmissionStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Only include submissions made in this recurring form instance.
+    #[must_use]
+    pub fn instance(mut self, instance_id: Uuid) -> Self {
+        self.instance = Some(instance_id);
+        self
+    }
+
+    /// Only include submissions made by this respondent (their
+    /// `respondent_id`).
+    #[must_use]
+    pub fn respondent(mut self, respondent_id: impl Into<String>) -> Self {
+        self.respondent = Some(respondent_id.into());
+        self
+    }
+
+    /// Only include submissions carrying this label (matched as normalized
+    /// by [`submission_note::normalize_labels`]).
+    #[must_use]
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Only include submissions created at or after `since`.
+    #[must_use]
+    pub fn since(mut self, since: DateTime<FixedOffset>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Only include submissions created before `until`.
+    #[must_use]
+    pub fn until(mut self, until: DateTime<FixedOffset>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Only include completed (`true`) or unfinished (`false`) submissions.
+    #[must_use]
+    pub fn completed(mut self, completed: bool) -> Self {
+        self.completed = Some(completed);
+        self
+    }
+
+    /// Sets the order. Ties are broken by ID so pages never overlap.
+    #[must_use]
+    pub fn sort(mut self, sort: SubmissionSort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Sets the 1-indexed page to fetch; `0` is treated as `1`.
+    #[must_use]
+    pub fn page(mut self, page: u32) -> Self {
+        self.page = page.max(1);
+        self
+    }
+
+    /// Sets the page size, clamped to `1..=`[`MAX_PER_PAGE`].
+    #[must_use]
+    pub fn per_page(mut self, per_page: u32) -> Self {
+        self.per_page = per_page.clamp(1, MAX_PER_PAGE);
+        self
+    }
+
+    /// Fetches the requested page and the total number of matches.
+    pub async fn fetch(&self, db: &DatabaseConnection) -> Result<SubmissionPage, FormError> {
+        let Some(select) = self.filtered(db).await? else {
+            return Ok(self.empty_page());
+        };
+
+        let total = select.clone().count(db).await?;
+        let order = if self.sort.descending {
+            Order::Desc
+        } else {
+            Order::Asc
+        };
+        let column = match self.sort.field {
+            SortField::CreatedAt => Column::CreatedAt,
+            SortField::CompletedAt => Column::CompletedAt,
+            SortField::Score => Column::Score,
+        };
+        let submissions = select
+            .order_by(column, order.clone())
+            .order_by(Column::Id, order)
+            .offset(u64::from(self.page - 1) * u64::from(self.per_page))
+            .limit(u64::from(self.per_page))
+            .all(db)
+            .await?;
+
+        Ok(SubmissionPage {
+            submissions,
+            page: self.page,
+            per_page: self.per_page,
+            total,
+        })
+    }
+
+    /// Builds the query for every filter, or returns `None` when no
+    /// submission carries the label filtered by.
+    pub(crate) async fn filtered(
+        &self,
+        db: &DatabaseConnection,
+    ) -> Result<Option<Select<SubmissionEntity>>, FormError> {
+        let mut select = self.select();
+        if let Some(label) = &self.label {
+            let Some(ids) = self.labelled(db, label).await? else {
+                return Ok(None);
+            };
+            select = select.filter(Column::Id.is_in(ids));
+        }
+        Ok(Some(select))
+    }
+
+    /// Builds the query for every filter except the label.
+    fn select(&self) -> Select<SubmissionEntity> {
+        let mut select = SubmissionEntity::find()
+            .filter(Column::FormId.eq(self.form_id))
+            .filter(Column::DeletedAt.is_null());
+
+        select = match self.status {
+            Some(status) => select.filter(Column::Status.eq(status.as_str())),
+            None => select.filter(Column::Status.ne(SubmissionStatus::Preview.as_str())),
+        };
+        if let Some(instance_id) = self.instance {
+            select = select.filter(Column::InstanceId.eq(instance_id));
+        }
+        if let Some(respondent_id) = &self.respondent {
+            select = select.filter(Column::RespondentId.eq(respondent_id.as_str()));
+        }
+        if let Some(since) = self.since {
+            select = select.filter(Column::CreatedAt.gte(since));
+        }
+        if let Some(until) = self.until {
+            select = select.filter(Column::CreatedAt.lt(until));
+        }
+        match self.completed {
+            Some(true) => select = select.filter(Column::CompletedAt.is_not_null()),
+            Some(false) => select = select.filter(Column::CompletedAt.is_null()),
+            None => {}
+        }
+        select
+    }
+
+    /// Returns the IDs of this form's submissions carrying `label`, or
+    /// `None` when there are none.
+    ///
+    /// Labels are stored as a JSON array on each note, which databases
+    /// can't query portably, so notes are matched here instead.
+    async fn labelled(
+        &self,
+        db: &DatabaseConnection,
+        label: &str,
+    ) -> Result<Option<Vec<Uuid>>, FormError> {
+        let Some(label) = submission_note::normalize_labels([label.to_string()]).pop() else {
+            return Ok(None);
+        };
+        let form_submissions = SubmissionEntity::find()
+            .select_only()
+            .column(Column::Id)
+            .filter(Column::FormId.eq(self.form_id))
+            .into_query();
+        let notes = SubmissionNoteEntity::find()
+            .filter(submission_note::Column::SubmissionId.in_subquery(form_submissions))
+            .all(db)
+            .await?;
+
+        let ids: HashSet<Uuid> = notes
+            .into_iter()
+            .filter(|note| note.labels().contains(&label))
+            .map(|note| note.submission_id)
+            .collect();
+        Ok((!ids.is_empty()).then(|| ids.into_iter().collect()))
+    }
+
+    fn empty_page(&self) -> SubmissionPage {
+        SubmissionPage {
+            submissions: Vec::new(),
+            page: self.page,
+            per_page: self.per_page,
+            total: 0,
+        }
+    }
+}
+
+/// Parses a listing bound: an RFC 3339 timestamp, or a `YYYY-MM-DD` date
+/// meaning midnight UTC at its start (`end_of_day = false`) or end.
+pub fn parse_bound(value: &str, end_of_day: bool) -> Result<DateTime<FixedOffset>, FormError> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at);
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        FormError::InvalidData(format!(
+            "Invalid date '{value}' (expected YYYY-MM-DD or an RFC 3339 timestamp)"
+        ))
+    })?;
+    let date = if end_of_day {
+        date.succ_opt().unwrap_or(date)
+    } else {
+        date
+    };
+    Ok(date.and_time(chrono::NaiveTime::MIN).and_utc().fixed_offset())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_parsing() {
+        let sort: SubmissionSort = "-score".parse().unwrap();
+        assert_eq!(sort.field, SortField::Score);
+        assert!(sort.descending);
+
+        let sort: SubmissionSort = "completed_at".parse().unwrap();
+        assert_eq!(sort.field, SortField::CompletedAt);
+        assert!(!sort.descending);
+
+        assert!("name".parse::<SubmissionSort>().is_err());
+    }
+
+    #[test]
+    fn test_date_bounds_cover_whole_days() {
+        let since = parse_bound("2025-03-01", false).unwrap();
+        let until = parse_bound("2025-03-01", true).unwrap();
+        assert_eq!(since.to_rfc3339(), "2025-03-01T00:00:00+00:00");
+        assert_eq!(until.to_rfc3339(), "2025-03-02T00:00:00+00:00");
+
+        let at = parse_bound("2025-03-01T12:30:00+02:00", true).unwrap();
+        assert_eq!(at.to_rfc3339(), "2025-03-01T12:30:00+02:00");
+        assert!(parse_bound("yesterday", false).is_err());
+    }
+}
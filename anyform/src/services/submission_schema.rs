@@ -0,0 +1,208 @@
+//! Export schema resolution across form revisions.
+//!
+//! Submissions are stamped with the form revision they were made against.
+//! When a form is edited, fields may be renamed, relabelled or removed, so a
+//! single set of current fields no longer describes every stored payload.
+//! [`SubmissionSchema`] builds the superset of columns across all revisions
+//! and resolves labels per revision.
+//!
+//! It also decides what an export may contain: fields classified as
+//! [`FieldClassification::Sensitive`] in any revision are left out unless
+//! sensitive data is asked for.
+
+use std::collections::{HashMap, HashSet};
+
+use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::{
+    form_revision::{Entity as FormRevisionEntity, RevisionField},
+    submission::Model as Submission,
+};
+use crate::error::FormError;
+use crate::services::capacity::form_fields;
+use crate::schema::FieldClassification;
+
+/// A column in a submissions export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportColumn {
+    /// Submission data key.
+    pub name: String,
+    /// Label from the newest revision that contains the field.
+    pub label: String,
+    /// Field type from the newest revision that contains the field, if known.
+    pub field_type: Option<String>,
+    /// Most restrictive classification the field had in any revision.
+    #[serde(default)]
+    pub classification: FieldClassification,
+}
+
+/// The superset of export columns for a form's submissions.
+#[derive(Debug, Clone, Default)]
+pub struct SubmissionSchema {
+    columns: Vec<ExportColumn>,
+    revisions: HashMap<Uuid, Vec<RevisionField>>,
+}
+
+impl SubmissionSchema {
+    /// Builds the export schema for a form and a set of its submissions.
+    ///
+    /// Columns are ordered by first appearance, oldest revision first. Forms
+    /// without recorded revisions fall back to their current fields. Data keys
+    /// not described by any revision are appended, labelled by their name.
+    pub async fn load(
+        db: &DatabaseConnection,
+        form_id: Uuid,
+        submissions: &[Submission],
+    ) -> Result<Self, FormError> {
+        let revisions = FormRevisionEntity::find_by_form(db, form_id).await?;
+
+        let snapshots: Vec<(Option<Uuid>, Vec<RevisionField>)> = if revisions.is_empty() {
+            vec![(None, current_fields(db, form_id).await?)]
+        } else {
+            revisions
+                .iter()
+                .map(|r| (Some(r.id), r.fields()))
+                .collect()
+        };
+
+        let mut schema = Self::default();
+        let mut index: HashMap<String, usize> = HashMap::new();
+
+        for (revision_id, fields) in snapshots {
+            for field in &fields {
+                let mut column = ExportColumn {
+                    name: field.name.clone(),
+                    label: field.label.clone(),
+                    field_type: Some(field.field_type.clone()),
+                    classification: field.classification,
+                };
+                match index.get(&field.name) {
+                    // Later revisions win for labels and types; answers
+                    // collected as sensitive stay sensitive
+                    Some(&i) => {
+                        column.classification =
+                            column.classification.max(schema.columns[i].classification);
+                        schema.columns[i] = column;
+                    }
+                    None => {
+                        index.insert(field.name.clone(), schema.columns.len());
+                        schema.columns.push(column);
+                    }
+                }
+            }
+            if let Some(id) = revision_id {
+                schema.revisions.insert(id, fields);
+            }
+        }
+
+        schema.extend(submissions);
+
+        Ok(schema)
+    }
+
+    /// Appends columns for the data keys of `submissions` that no revision
+    /// describes, labelled by their name.
+    ///
+    /// Lets a schema loaded without submissions take them in page by page.
+    pub fn extend(&mut self, submissions: &[Submission]) {
+        let mut known: HashSet<String> = self.columns.iter().map(|c| c.name.clone()).collect();
+        for submission in submissions {
+            if let Some(obj) = submission.data.as_object() {
+                let mut unknown: Vec<&String> =
+                    obj.keys().filter(|k| !known.contains(*k)).collect();
+                unknown.sort();
+                for key in unknown {
+                    known.insert(key.clone());
+                    self.columns.push(ExportColumn {
+                        name: key.clone(),
+                        label: key.clone(),
+                        field_type: None,
+                        classification: FieldClassification::Standard,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Returns the export columns.
+    #[must_use]
+    pub fn columns(&self) -> &[ExportColumn] {
+        &self.columns
+    }
+
+    /// Returns the columns to export, leaving out sensitive ones unless
+    /// `include_sensitive` is set.
+    #[must_use]
+    pub fn export_columns(&self, include_sensitive: bool) -> Vec<&ExportColumn> {
+        self.columns
+            .iter()
+            .filter(|c| include_sensitive || c.classification.in_default_export())
+            .collect()
+    }
+
+    /// Returns the number of columns left out of exports by default.
+    #[must_use]
+    pub fn sensitive_count(&self) -> usize {
+        self.columns
+            .iter()
+            .filter(|c| !c.classification.in_default_export())
+            .count()
+    }
+
+    /// Returns submission data as it may be exported: without the answers
+    /// to sensitive fields, unless `include_sensitive` is set.
+    #[must_use]
+    pub fn export_data(
+        &self,
+        data: &serde_json::Value,
+        include_sensitive: bool,
+    ) -> serde_json::Value {
+        let mut data = data.clone();
+        if let (false, Some(obj)) = (include_sensitive, data.as_object_mut()) {
+            for column in &self.columns {
+                if !column.classification.in_default_export() {
+                    obj.remove(&column.name);
+                }
+            }
+        }
+        data
+    }
+
+    /// Returns the label a field had in the given revision.
+    ///
+    /// Falls back to the column label when the revision is unknown or does
+    /// not contain the field.
+    #[must_use]
+    pub fn label_for(&self, revision_id: Option<Uuid>, name: &str) -> Option<&str> {
+        revision_id
+            .and_then(|id| self.revisions.get(&id))
+            .and_then(|fields| fields.iter().find(|f| f.name == name))
+            .map(|f| f.label.as_str())
+            .or_else(|| {
+                self.columns
+                    .iter()
+                    .find(|c| c.name == name)
+                    .map(|c| c.label.as_str())
+            })
+    }
+}
+
+/// Returns a form's current fields as a revision snapshot, for forms
+/// without recorded revisions.
+pub(crate) async fn current_fields(
+    db: &DatabaseConnection,
+    form_id: Uuid,
+) -> Result<Vec<RevisionField>, FormError> {
+    Ok(form_fields(db, form_id)
+        .await?
+        .into_iter()
+        .map(|field| RevisionField {
+            classification: field.classification(),
+            name: field.name,
+            label: field.label,
+            field_type: field.field_type,
+        })
+        .collect())
+}
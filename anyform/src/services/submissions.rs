@@ -0,0 +1,601 @@
+//! Storing validated submissions.
+
+use std::collections::HashMap;
+
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    DatabaseTransaction, EntityTrait, QueryFilter, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::branching::{self, json_values, StepBranch};
+use crate::encryption::{plain_answers, EncryptionKey};
+use crate::entities::{
+    field,
+    form::Model as Form,
+    form_revision::Entity as FormRevisionEntity,
+    step::{self, Entity as StepEntity},
+    submission::{
+        self, Entity as SubmissionEntity, Model as Submission, SubmissionMetadata,
+        SubmissionStatus,
+    },
+};
+use crate::error::FormError;
+use crate::schema::{FieldValue, FormSettings};
+use crate::services::capacity::{form_fields, reserve_seats};
+use crate::services::quota::{is_sampled, lock_form, record_response};
+#[cfg(feature = "search")]
+use crate::services::SearchService;
+use crate::services::{CrmService, InstanceService, ResultsEngine, Score, ScoringEngine};
+
+/// Form input naming the in-progress submission a hydrated form saved its
+/// steps to; see [`FormSettings::save_each_step`].
+pub const DRAFT_FIELD: &str = "_af_submission";
+
+/// Service for storing submissions and managing the waitlist.
+///
+/// Submissions are stamped with the current form revision (and, for recurring
+/// forms, the open instance), and take a place within `max_submissions` and
+/// on any capacity-limited options they choose.
+/// When the form or an option is full, the submission is either rejected or,
+/// with [`FormSettings::waitlist`], stored as
+/// [`SubmissionStatus::Waitlisted`] without taking a place.
+///
+/// Multi-step forms can also be filled in over several requests: a
+/// submission is [started](Self::start), its steps
+/// [saved](Self::save_step) one at a time, and then
+/// [completed](Self::complete).
+pub struct SubmissionService;
+
+impl SubmissionService {
+    /// Stores a validated submission.
+    ///
+    /// Submissions to quiz forms are scored with [`ScoringEngine`], every
+    /// submission gets its result from [`ResultsEngine`], accepted ones
+    /// are queued for the form's CRM (see [`CrmService`]), and, with the
+    /// `search` feature, text answers are indexed for search. `metadata` is
+    /// dropped for anonymous forms, whatever the caller passes; category
+    /// scores are added to it either way.
+    /// Pass a transaction as `db` to store the submission together with
+    /// earlier reads (e.g. the form lookup and validation); places are then
+    /// taken within a savepoint.
+    ///
+    /// Returns [`FormError::SubmissionLimitReached`] or
+    /// [`FormError::OptionFull`] when full and the form has no waitlist,
+    /// [`FormError::FormClosed`] once `close_after` responses were accepted
+    /// or after `closes_at`, [`FormError::FormNotOpen`] before `opens_at`,
+    /// [`FormError::InstanceClosed`] when a recurring form has no open
+    /// instance, and [`FormError::AlreadyResponded`] when the metadata's
+    /// `user_id` already responded to a form with
+    /// [`FormSettings::one_per_respondent`]. That ID is stored as the
+    /// submission's `respondent_id`.
+    ///
+    /// `data` holds the answers as submitted. With a `key`, the answers to
+    /// sensitive fields are encrypted as they are stored (see
+    /// [`crate::encryption`]); everything else works on the plain answers.
+    pub async fn create<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        form: &Form,
+        fields: &[field::Model],
+        data: &HashMap<String, FieldValue>,
+        metadata: Option<SubmissionMetadata>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        let settings = form.settings();
+        let metadata = metadata.and_then(|m| m.for_settings(&settings));
+        let respondent_id = metadata.as_ref().and_then(|m| m.user_id.clone());
+        let metadata = metadata.map(|m| serde_json::to_value(m).unwrap_or_default());
+        let (txn, accepted) =
+            Self::accept(db, form, fields, data, respondent_id.as_deref()).await?;
+
+        let now = chrono::Utc::now().fixed_offset();
+        let submission = submission::ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            form_id: ActiveValue::Set(form.id),
+            revision_id: ActiveValue::Set(accepted.revision_id),
+            instance_id: ActiveValue::Set(accepted.instance_id),
+            respondent_id: ActiveValue::Set(respondent_id),
+            data: ActiveValue::Set(stored_answers(fields, data, key)),
+            metadata: ActiveValue::Set(with_category_scores(metadata, accepted.score.as_ref())),
+            current_step_id: ActiveValue::Set(None),
+            completed_at: ActiveValue::Set(Some(now)),
+            score: ActiveValue::Set(accepted.score.as_ref().map(|s| s.score)),
+            max_score: ActiveValue::Set(accepted.score.as_ref().map(|s| s.max_score)),
+            result_key: ActiveValue::Set(accepted.result_key),
+            status: ActiveValue::Set(accepted.status.as_str().to_string()),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            legal_hold: ActiveValue::Set(false),
+            deleted_at: ActiveValue::Set(None),
+        };
+
+        let saved = submission.insert(&txn).await?;
+        CrmService::enqueue(&txn, form, &saved, key).await?;
+        #[cfg(feature = "search")]
+        SearchService::index(&txn, fields, &saved).await?;
+        txn.commit().await?;
+
+        Ok(saved)
+    }
+
+    /// Starts a submission to be filled in step by step.
+    ///
+    /// The submission is stored as [`SubmissionStatus::InProgress`] on the
+    /// form's first visible step, and takes no place until
+    /// [`Self::complete`]. Its ID is the key for resuming it. Like
+    /// [`Self::create`], it stores the metadata's `user_id` as the
+    /// `respondent_id`, and turns away a respondent who already responded.
+    pub async fn start<C: ConnectionTrait>(
+        db: &C,
+        form: &Form,
+        metadata: Option<SubmissionMetadata>,
+    ) -> Result<Submission, FormError> {
+        let settings = form.settings();
+        let metadata = metadata.and_then(|m| m.for_settings(&settings));
+        let respondent_id = metadata.as_ref().and_then(|m| m.user_id.clone());
+        Self::check_respondent(db, form, &settings, respondent_id.as_deref()).await?;
+        let metadata = metadata.map(|m| serde_json::to_value(m).unwrap_or_default());
+        let steps = StepEntity::find_by_form(db, form.id).await?;
+        let current_step_id = next_step(&steps, &[], None, &HashMap::new());
+
+        let now = chrono::Utc::now().fixed_offset();
+        let submission = submission::ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            form_id: ActiveValue::Set(form.id),
+            revision_id: ActiveValue::Set(None),
+            instance_id: ActiveValue::Set(None),
+            respondent_id: ActiveValue::Set(respondent_id),
+            data: ActiveValue::Set(serde_json::json!({})),
+            metadata: ActiveValue::Set(metadata),
+            current_step_id: ActiveValue::Set(current_step_id),
+            completed_at: ActiveValue::Set(None),
+            score: ActiveValue::Set(None),
+            max_score: ActiveValue::Set(None),
+            result_key: ActiveValue::Set(None),
+            status: ActiveValue::Set(SubmissionStatus::InProgress.as_str().to_string()),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            legal_hold: ActiveValue::Set(false),
+            deleted_at: ActiveValue::Set(None),
+        };
+
+        Ok(submission.insert(db).await?)
+    }
+
+    /// Saves the answers to one step of an in-progress submission.
+    ///
+    /// `data` is merged into the answers saved so far, and the submission
+    /// moves on to the step that follows with them, visible and not
+    /// [jumped over](crate::branching); after the last step it stays there.
+    /// Validate the step (e.g. with
+    /// [`validate_step`](crate::validation::validate_step)) first.
+    ///
+    /// As with [`Self::create`], `data` is the step's answers as submitted,
+    /// encrypted with `key` as they are stored; the answers saved so far
+    /// are decrypted with it to find the next step.
+    pub async fn save_step<C: ConnectionTrait>(
+        db: &C,
+        submission: Submission,
+        step_id: Uuid,
+        data: &HashMap<String, FieldValue>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        if !submission.is_in_progress() {
+            return Err(FormError::InvalidData(format!(
+                "Submission {} is not in progress",
+                submission.id
+            )));
+        }
+
+        let steps = StepEntity::find_by_form(db, submission.form_id).await?;
+        let step_ids: Vec<Uuid> = steps.iter().map(|s| s.id).collect();
+        let fields = field::Entity::find_by_steps(db, &step_ids).await?;
+        let mut answers = plain_answers(&submission, key);
+        answers.extend(data.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let current_step_id =
+            next_step(&steps, &fields, Some(step_id), &answers).or(Some(step_id));
+
+        // Only the step's answers are encrypted; the rest already are
+        let mut stored = submission.data.clone();
+        if let (Some(stored), serde_json::Value::Object(step)) =
+            (stored.as_object_mut(), stored_answers(&fields, data, key))
+        {
+            stored.extend(step);
+        }
+
+        let model = submission::ActiveModel {
+            id: ActiveValue::Unchanged(submission.id),
+            form_id: ActiveValue::Unchanged(submission.form_id),
+            revision_id: ActiveValue::Unchanged(submission.revision_id),
+            instance_id: ActiveValue::Unchanged(submission.instance_id),
+            respondent_id: ActiveValue::Unchanged(submission.respondent_id),
+            data: ActiveValue::Set(stored),
+            metadata: ActiveValue::Unchanged(submission.metadata),
+            current_step_id: ActiveValue::Set(current_step_id),
+            completed_at: ActiveValue::Unchanged(submission.completed_at),
+            score: ActiveValue::Unchanged(submission.score),
+            max_score: ActiveValue::Unchanged(submission.max_score),
+            result_key: ActiveValue::Unchanged(submission.result_key),
+            status: ActiveValue::Unchanged(submission.status),
+            created_at: ActiveValue::Unchanged(submission.created_at),
+            updated_at: ActiveValue::Set(chrono::Utc::now().fixed_offset()),
+            legal_hold: ActiveValue::Unchanged(submission.legal_hold),
+            deleted_at: ActiveValue::Unchanged(submission.deleted_at),
+        };
+
+        Ok(model.update(db).await?)
+    }
+
+    /// Discards the in-progress submission `id` to a form, once its answers
+    /// were submitted in full as another submission (see
+    /// [`FormSettings::save_each_step`]).
+    ///
+    /// Returns true if there was one. Submissions that aren't in progress,
+    /// or are on legal hold, are left alone.
+    pub async fn discard_draft<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        id: Uuid,
+    ) -> Result<bool, FormError> {
+        let deleted = SubmissionEntity::delete_many()
+            .filter(submission::Column::Id.eq(id))
+            .filter(submission::Column::FormId.eq(form_id))
+            .filter(submission::Column::Status.eq(SubmissionStatus::InProgress.as_str()))
+            .filter(submission::Column::LegalHold.eq(false))
+            .exec(db)
+            .await?;
+        Ok(deleted.rows_affected > 0)
+    }
+
+    /// Completes an in-progress submission with its saved answers.
+    ///
+    /// The submission is stamped, scored and takes a place as if it had been
+    /// stored with [`Self::create`] now, and fails the same way when the form
+    /// is full. Validate the answers first. Answers encrypted with `key`
+    /// are decrypted to score them, and stored as they are.
+    pub async fn complete<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        form: &Form,
+        fields: &[field::Model],
+        submission: Submission,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        if !submission.is_in_progress() {
+            return Err(FormError::InvalidData(format!(
+                "Submission {} is not in progress",
+                submission.id
+            )));
+        }
+
+        let data = plain_answers(&submission, key);
+        let respondent_id = submission.respondent_id.as_deref();
+        let (txn, accepted) = Self::accept(db, form, fields, &data, respondent_id).await?;
+
+        let now = chrono::Utc::now().fixed_offset();
+        let model = submission::ActiveModel {
+            id: ActiveValue::Unchanged(submission.id),
+            form_id: ActiveValue::Unchanged(submission.form_id),
+            revision_id: ActiveValue::Set(accepted.revision_id),
+            instance_id: ActiveValue::Set(accepted.instance_id),
+            respondent_id: ActiveValue::Unchanged(submission.respondent_id.clone()),
+            data: ActiveValue::Set(submission.data),
+            metadata: ActiveValue::Set(with_category_scores(
+                submission.metadata,
+                accepted.score.as_ref(),
+            )),
+            current_step_id: ActiveValue::Set(None),
+            completed_at: ActiveValue::Set(Some(now)),
+            score: ActiveValue::Set(accepted.score.as_ref().map(|s| s.score)),
+            max_score: ActiveValue::Set(accepted.score.as_ref().map(|s| s.max_score)),
+            result_key: ActiveValue::Set(accepted.result_key),
+            status: ActiveValue::Set(accepted.status.as_str().to_string()),
+            created_at: ActiveValue::Unchanged(submission.created_at),
+            updated_at: ActiveValue::Set(now),
+            legal_hold: ActiveValue::Unchanged(submission.legal_hold),
+            deleted_at: ActiveValue::Unchanged(submission.deleted_at),
+        };
+
+        let completed = model.update(&txn).await?;
+        CrmService::enqueue(&txn, form, &completed, key).await?;
+        #[cfg(feature = "search")]
+        SearchService::index(&txn, fields, &completed).await?;
+        txn.commit().await?;
+
+        Ok(completed)
+    }
+
+    /// Stores a submission made through a preview link.
+    ///
+    /// Preview submissions are scored like any other, but take no place
+    /// within limits, quotas or option capacity, are not tied to a recurring
+    /// instance and store no metadata besides category scores; they are only kept until discarded
+    /// with [`SubmissionEntity::delete_previews`]. Sensitive answers are
+    /// encrypted with `key` as in [`Self::create`].
+    pub async fn create_preview<C: ConnectionTrait>(
+        db: &C,
+        form: &Form,
+        data: &HashMap<String, FieldValue>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        let revision_id = FormRevisionEntity::find_latest(db, form.id)
+            .await?
+            .map(|r| r.id);
+        let fields = if form.settings().is_quiz || key.is_some() {
+            form_fields(db, form.id).await?
+        } else {
+            Vec::new()
+        };
+        let score = ScoringEngine::score(db, form, &fields, data).await?;
+        let result_key = result_key(db, form, score.as_ref(), data).await?;
+
+        let now = chrono::Utc::now().fixed_offset();
+        let submission = submission::ActiveModel {
+            id: ActiveValue::Set(Uuid::new_v4()),
+            form_id: ActiveValue::Set(form.id),
+            revision_id: ActiveValue::Set(revision_id),
+            instance_id: ActiveValue::Set(None),
+            respondent_id: ActiveValue::Set(None),
+            data: ActiveValue::Set(stored_answers(&fields, data, key)),
+            metadata: ActiveValue::Set(with_category_scores(None, score.as_ref())),
+            current_step_id: ActiveValue::Set(None),
+            completed_at: ActiveValue::Set(Some(now)),
+            score: ActiveValue::Set(score.as_ref().map(|s| s.score)),
+            max_score: ActiveValue::Set(score.as_ref().map(|s| s.max_score)),
+            result_key: ActiveValue::Set(result_key),
+            status: ActiveValue::Set(SubmissionStatus::Preview.as_str().to_string()),
+            created_at: ActiveValue::Set(now),
+            updated_at: ActiveValue::Set(now),
+            legal_hold: ActiveValue::Set(false),
+            deleted_at: ActiveValue::Set(None),
+        };
+
+        Ok(submission.insert(db).await?)
+    }
+
+    /// Promotes a waitlisted submission to an accepted one.
+    ///
+    /// The submission must still fit within `max_submissions` and the
+    /// capacity of its chosen options; free a place first (e.g. by deleting
+    /// a cancelled submission) when the form is still full. Answers
+    /// encrypted with `key` are decrypted to take their option seats.
+    pub async fn promote(
+        db: &DatabaseConnection,
+        form: &Form,
+        submission: Submission,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        if !submission.is_waitlisted() {
+            return Err(FormError::InvalidData(format!(
+                "Submission {} is not waitlisted",
+                submission.id
+            )));
+        }
+
+        let fields = form_fields(db, form.id).await?;
+        let txn = db.begin().await?;
+        Self::take_place(
+            &txn,
+            form.id,
+            submission.instance_id,
+            &form.settings(),
+            &fields,
+            &plain_answers(&submission, key),
+        )
+        .await?;
+
+        let model = submission::ActiveModel {
+            id: ActiveValue::Unchanged(submission.id),
+            form_id: ActiveValue::Unchanged(submission.form_id),
+            revision_id: ActiveValue::Unchanged(submission.revision_id),
+            instance_id: ActiveValue::Unchanged(submission.instance_id),
+            respondent_id: ActiveValue::Unchanged(submission.respondent_id),
+            data: ActiveValue::Unchanged(submission.data),
+            metadata: ActiveValue::Unchanged(submission.metadata),
+            current_step_id: ActiveValue::Unchanged(submission.current_step_id),
+            completed_at: ActiveValue::Unchanged(submission.completed_at),
+            score: ActiveValue::Unchanged(submission.score),
+            max_score: ActiveValue::Unchanged(submission.max_score),
+            result_key: ActiveValue::Unchanged(submission.result_key),
+            status: ActiveValue::Set(SubmissionStatus::Submitted.as_str().to_string()),
+            created_at: ActiveValue::Unchanged(submission.created_at),
+            updated_at: ActiveValue::Set(chrono::Utc::now().fixed_offset()),
+            legal_hold: ActiveValue::Unchanged(submission.legal_hold),
+            deleted_at: ActiveValue::Unchanged(submission.deleted_at),
+        };
+
+        let promoted = model.update(&txn).await?;
+        CrmService::enqueue(&txn, form, &promoted, key).await?;
+        txn.commit().await?;
+
+        Ok(promoted)
+    }
+
+    /// Returns true if a submission to `form` should be recorded, per its
+    /// `sample_rate`.
+    ///
+    /// Handlers thank respondents outside the sample without calling
+    /// [`Self::create`].
+    #[must_use]
+    pub fn is_sampled(form: &Form) -> bool {
+        is_sampled(&form.settings())
+    }
+
+    /// Stamps a submission about to be accepted with the current revision,
+    /// instance, score and result, and takes its place, after checking the
+    /// respondent hasn't already responded to a form accepting one response
+    /// per respondent.
+    ///
+    /// Returns the transaction the place was taken in, for the caller to
+    /// write the submission in and commit.
+    async fn accept<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        form: &Form,
+        fields: &[field::Model],
+        data: &HashMap<String, FieldValue>,
+        respondent_id: Option<&str>,
+    ) -> Result<(DatabaseTransaction, Accepted), FormError> {
+        let settings = form.settings();
+        let now = chrono::Utc::now().fixed_offset();
+        if settings.opens_after(now) {
+            return Err(FormError::FormNotOpen);
+        }
+        if settings.closed_by(now) {
+            return Err(FormError::FormClosed);
+        }
+        let revision_id = FormRevisionEntity::find_latest(db, form.id)
+            .await?
+            .map(|r| r.id);
+        let instance_id = if settings.recurrence.is_some() {
+            let instance = InstanceService::current(db, form)
+                .await?
+                .ok_or(FormError::InstanceClosed)?;
+            Some(instance.id)
+        } else {
+            None
+        };
+        let score = ScoringEngine::score(db, form, fields, data).await?;
+        let result_key = result_key(db, form, score.as_ref(), data).await?;
+
+        let mut txn = db.begin().await?;
+        Self::check_respondent(&txn, form, &settings, respondent_id).await?;
+        let status = match Self::take_place(&txn, form.id, instance_id, &settings, fields, data)
+            .await
+        {
+            Ok(()) => SubmissionStatus::Submitted,
+            Err(FormError::SubmissionLimitReached | FormError::OptionFull { .. })
+                if settings.waitlist =>
+            {
+                // Give back any seats taken before the form or option filled up
+                txn.rollback().await?;
+                txn = db.begin().await?;
+                SubmissionStatus::Waitlisted
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok((
+            txn,
+            Accepted {
+                revision_id,
+                instance_id,
+                score,
+                result_key,
+                status,
+            },
+        ))
+    }
+
+    /// Rejects a respondent who already responded to a form with
+    /// [`FormSettings::one_per_respondent`]. Submissions without a
+    /// respondent aren't checked; handlers require one for such forms.
+    async fn check_respondent<C: ConnectionTrait>(
+        db: &C,
+        form: &Form,
+        settings: &FormSettings,
+        respondent_id: Option<&str>,
+    ) -> Result<(), FormError> {
+        let Some(respondent_id) = respondent_id.filter(|_| settings.one_per_respondent) else {
+            return Ok(());
+        };
+        if SubmissionEntity::has_responded(db, form.id, respondent_id).await? {
+            return Err(FormError::AlreadyResponded);
+        }
+        Ok(())
+    }
+
+    /// Takes a place within the form's (or instance's) limit, its
+    /// `close_after` quota and on chosen options.
+    async fn take_place<C: ConnectionTrait>(
+        db: &C,
+        form_id: Uuid,
+        instance_id: Option<Uuid>,
+        settings: &FormSettings,
+        fields: &[field::Model],
+        data: &HashMap<String, FieldValue>,
+    ) -> Result<(), FormError> {
+        if let Some(max) = settings.max_submissions {
+            // Counted under the form's lock, so concurrent submissions can't
+            // both take the last place
+            lock_form(db, form_id).await?;
+            if SubmissionEntity::count_accepted(db, form_id, instance_id).await? >= u64::from(max) {
+                return Err(FormError::SubmissionLimitReached);
+            }
+        }
+
+        reserve_seats(db, fields, data).await?;
+        record_response(db, form_id, settings.close_after).await
+    }
+}
+
+/// What [`SubmissionService::accept`] stamps a submission with.
+struct Accepted {
+    revision_id: Option<Uuid>,
+    instance_id: Option<Uuid>,
+    score: Option<Score>,
+    result_key: Option<String>,
+    status: SubmissionStatus,
+}
+
+/// Returns the key of the result a submission gets: the quiz result its
+/// score picked or, for unscored submissions, the result its answers match.
+async fn result_key<C: ConnectionTrait>(
+    db: &C,
+    form: &Form,
+    score: Option<&Score>,
+    data: &HashMap<String, FieldValue>,
+) -> Result<Option<String>, FormError> {
+    match score {
+        Some(score) => Ok(score.result_key.clone()),
+        None => Ok(ResultsEngine::resolve(db, form.id, None, data).await?.map(|r| r.key)),
+    }
+}
+
+/// Adds the per-category points of `score` to a submission's stored
+/// `metadata`.
+///
+/// Category scores describe the answers rather than the respondent, so
+/// they're stored even where other metadata isn't (anonymous forms and
+/// previews).
+fn with_category_scores(
+    metadata: Option<serde_json::Value>,
+    score: Option<&Score>,
+) -> Option<serde_json::Value> {
+    let Some(score) = score.filter(|s| !s.categories.is_empty()) else {
+        return metadata;
+    };
+    let mut stored: SubmissionMetadata = metadata
+        .and_then(|m| serde_json::from_value(m).ok())
+        .unwrap_or_default();
+    stored.category_scores = score.categories.clone();
+    serde_json::to_value(stored).ok()
+}
+
+/// Returns answers as they are stored: with the answers to sensitive
+/// `fields` encrypted, given a `key`.
+fn stored_answers(
+    fields: &[field::Model],
+    data: &HashMap<String, FieldValue>,
+    key: Option<&EncryptionKey>,
+) -> serde_json::Value {
+    let data = match key {
+        Some(key) => serde_json::to_value(key.encrypt_answers(fields, data)),
+        None => serde_json::to_value(data),
+    };
+    data.unwrap_or_default()
+}
+
+/// Returns the step that follows `after` (or the first step, if `None`)
+/// with `data`, following the jump rules of `steps` and their `fields`.
+fn next_step(
+    steps: &[step::Model],
+    fields: &[field::Model],
+    after: Option<Uuid>,
+    data: &HashMap<String, FieldValue>,
+) -> Option<Uuid> {
+    let from = match after {
+        Some(id) => Some(steps.iter().position(|s| s.id == id)?),
+        None => None,
+    };
+    let branches = StepBranch::from_models(steps, fields);
+    branching::next(&branches, from, &json_values(data)).map(|i| steps[i].id)
+}
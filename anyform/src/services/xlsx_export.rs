@@ -0,0 +1,335 @@
+//! Excel (`.xlsx`) exports of submissions.
+//!
+//! [`XlsxExport`] lays out its columns like [`CsvExport`], from the form's
+//! export mapping, but writes typed cells: numbers as numbers, dates and
+//! timestamps as dates, and yes/no answers as booleans. A second sheet holds
+//! the form's details and how often each option was chosen. Rows are
+//! written as they come, so [`XlsxExport::write_form`] exports a form of any
+//! size a page of submissions at a time.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Write;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use sea_orm::{DatabaseConnection, PaginatorTrait};
+use uuid::Uuid;
+
+use crate::entities::{
+    form::Model as Form, submission::Entity as SubmissionEntity,
+    submission::Model as Submission, submission_note::Entity as SubmissionNoteEntity,
+};
+use crate::encryption::EncryptionKey;
+use crate::error::FormError;
+use crate::repository::FieldWithOptions;
+use crate::schema::{SubmissionAttribute, ValueType};
+use crate::services::csv_export::{choice_fields, coordinate, Content, CsvColumn};
+use crate::services::{CsvExport, SubmissionSchema};
+use crate::xlsx::{Cell, Workbook};
+
+/// Submissions [`XlsxExport::write_form`] loads at a time.
+pub const XLSX_PAGE_SIZE: u64 = 500;
+
+/// A laid-out Excel export of a form's submissions.
+#[derive(Debug, Clone)]
+pub struct XlsxExport {
+    layout: CsvExport,
+    form: Form,
+    /// Choice fields in the export, with their options as `(value, label)`.
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Clone)]
+struct Choice {
+    name: String,
+    label: String,
+    options: Vec<(String, String)>,
+}
+
+impl XlsxExport {
+    /// Lays out an export of `schema`'s columns by the form's export
+    /// mapping, leaving out sensitive fields as [`CsvExport::new`] does.
+    ///
+    /// Dates are written as date cells, so the mapping's `date_format`
+    /// doesn't apply; `option_labels` does.
+    pub async fn new(
+        db: &DatabaseConnection,
+        form: &Form,
+        schema: &SubmissionSchema,
+        include_sensitive: bool,
+    ) -> Result<Self, FormError> {
+        let choices = choice_fields(db, form.id).await?;
+        Ok(Self::with_choices(form, schema, &choices, include_sensitive))
+    }
+
+    /// Lays out an export as [`new`](Self::new) does, with the form's
+    /// choice fields as loaded by
+    /// [`FormsRepository::choice_fields`](crate::FormsRepository::choice_fields).
+    #[must_use]
+    pub fn with_choices(
+        form: &Form,
+        schema: &SubmissionSchema,
+        choices: &[FieldWithOptions],
+        include_sensitive: bool,
+    ) -> Self {
+        let layout = CsvExport::with_choices(form, schema, choices, include_sensitive);
+
+        let choices = choices
+            .iter()
+            .filter(|choice| {
+                layout.columns().iter().any(|c| {
+                    matches!(&c.content, Content::Field { name, .. } if *name == choice.field.name)
+                })
+            })
+            .filter(|choice| !choice.options.is_empty())
+            .map(|choice| Choice {
+                name: choice.field.name.clone(),
+                label: choice.field.label.clone(),
+                options: choice
+                    .options
+                    .iter()
+                    .map(|o| (o.value.clone(), o.label.clone()))
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            layout,
+            form: form.clone(),
+            choices,
+        }
+    }
+
+    /// Exports a form's completed submissions to `out`, newest first,
+    /// loading [`XLSX_PAGE_SIZE`] submissions at a time.
+    ///
+    /// Returns `out` once the workbook is complete.
+    pub async fn write_form<W: Write>(
+        db: &DatabaseConnection,
+        form: &Form,
+        include_sensitive: bool,
+        out: W,
+    ) -> Result<W, FormError> {
+        Self::write_form_with(db, form, include_sensitive, false, None, out).await
+    }
+
+    /// Exports a form's submissions to `out` as
+    /// [`write_form`](Self::write_form) does, with
+    /// [partial responses](Submission::is_partial) when `include_partials`
+    /// is set, decrypting answers encrypted with `key`.
+    pub async fn write_form_with<W: Write>(
+        db: &DatabaseConnection,
+        form: &Form,
+        include_sensitive: bool,
+        include_partials: bool,
+        key: Option<&EncryptionKey>,
+        out: W,
+    ) -> Result<W, FormError> {
+        // The columns must be known before the first row, so a first pass
+        // collects answers no revision describes
+        let mut schema = SubmissionSchema::load(db, form.id, &[]).await?;
+        let mut pages = SubmissionEntity::exported_by_form(form.id, include_partials)
+            .paginate(db, XLSX_PAGE_SIZE);
+        while let Some(mut page) = pages.fetch_and_next().await? {
+            page.retain(|s| !s.is_in_progress() || s.is_partial());
+            schema.extend(&page);
+        }
+
+        let export = Self::new(db, form, &schema, include_sensitive).await?;
+        let mut writer = export.writer(out)?;
+        let mut pages = SubmissionEntity::exported_by_form(form.id, include_partials)
+            .paginate(db, XLSX_PAGE_SIZE);
+        while let Some(mut page) = pages.fetch_and_next().await? {
+            page.retain(|s| !s.is_in_progress() || s.is_partial());
+            if let Some(key) = key {
+                page.iter_mut().for_each(|s| key.decrypt_submission(s));
+            }
+            let ids: Vec<Uuid> = page.iter().map(|s| s.id).collect();
+            let labels = SubmissionNoteEntity::labels_by_submission(db, &ids).await?;
+            writer.write(&page, &labels)?;
+        }
+        writer.finish()
+    }
+
+    /// Returns the column headers, in order.
+    #[must_use]
+    pub fn headers(&self) -> Vec<&str> {
+        self.layout.headers()
+    }
+
+    /// Starts the workbook in `out`, writing the header row.
+    pub fn writer<W: Write>(&self, out: W) -> Result<XlsxWriter<'_, W>, FormError> {
+        let mut book = Workbook::new(out);
+        book.add_sheet("Submissions")?;
+        book.header(&self.headers())?;
+        Ok(XlsxWriter {
+            export: self,
+            book,
+            submissions: 0,
+            counts: vec![HashMap::new(); self.choices.len()],
+        })
+    }
+}
+
+/// An [`XlsxExport`] being written, a batch of submissions at a time.
+pub struct XlsxWriter<'a, W: Write> {
+    export: &'a XlsxExport,
+    book: Workbook<W>,
+    submissions: u64,
+    /// Times each answer was given, by value, for each of the choices.
+    counts: Vec<HashMap<String, u64>>,
+}
+
+impl<W: Write> XlsxWriter<'_, W> {
+    /// Writes a row per submission, with the labels of each submission
+    /// from `labels`.
+    pub fn write(
+        &mut self,
+        submissions: &[Submission],
+        labels: &HashMap<Uuid, Vec<String>>,
+    ) -> Result<(), FormError> {
+        let no_labels = Vec::new();
+        for sub in submissions {
+            let sub_labels = labels.get(&sub.id).unwrap_or(&no_labels);
+            let cells: Vec<Cell<'_>> = self
+                .export
+                .layout
+                .columns()
+                .iter()
+                .map(|c| cell(c, sub, sub_labels))
+                .collect();
+            self.book.row(&cells)?;
+            self.submissions += 1;
+
+            for (choice, counts) in self.export.choices.iter().zip(&mut self.counts) {
+                match sub.data.get(&choice.name) {
+                    Some(serde_json::Value::String(value)) => {
+                        *counts.entry(value.clone()).or_default() += 1;
+                    }
+                    Some(serde_json::Value::Array(values)) => {
+                        for value in values.iter().filter_map(|v| v.as_str()) {
+                            *counts.entry(value.to_string()).or_default() += 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the form sheet and returns `out` with the complete workbook.
+    pub fn finish(mut self) -> Result<W, FormError> {
+        let form = &self.export.form;
+        let book = &mut self.book;
+        book.add_sheet("Form")?;
+        book.row(&[Cell::from("Form"), Cell::from(form.name.as_str())])?;
+        book.row(&[Cell::from("Slug"), Cell::from(form.slug.as_str())])?;
+        book.row(&[Cell::from("Form ID"), Cell::from(form.id.to_string())])?;
+        book.row(&[Cell::from("Status"), Cell::from(form.status.as_str())])?;
+        book.row(&[Cell::from("Submissions"), Cell::Number(self.submissions as f64)])?;
+        book.row(&[
+            Cell::from("Exported at"),
+            Cell::DateTime(chrono::Utc::now().naive_utc()),
+        ])?;
+
+        if !self.export.choices.is_empty() {
+            book.row(&[])?;
+            book.header(&["Field", "Option", "Value", "Count"])?;
+        }
+        let all_counts = std::mem::take(&mut self.counts);
+        for (choice, mut counts) in self.export.choices.iter().zip(all_counts) {
+            let mut rows: Vec<(&str, &str, u64)> = choice
+                .options
+                .iter()
+                .map(|(value, label)| {
+                    (label.as_str(), value.as_str(), counts.remove(value).unwrap_or(0))
+                })
+                .collect();
+            // Answers no longer among the options, e.g. of removed options
+            let mut other: Vec<(String, u64)> = counts.into_iter().collect();
+            other.sort();
+            rows.extend(other.iter().map(|(value, n)| (value.as_str(), value.as_str(), *n)));
+
+            for (label, value, count) in rows {
+                book.row(&[
+                    Cell::from(choice.label.as_str()),
+                    Cell::from(label),
+                    Cell::from(value),
+                    Cell::Number(count as f64),
+                ])?;
+            }
+        }
+
+        Ok(self.book.finish()?)
+    }
+}
+
+/// Returns the typed cell of `column` for a submission.
+fn cell<'a>(column: &'a CsvColumn, sub: &'a Submission, labels: &[String]) -> Cell<'a> {
+    match &column.content {
+        Content::Attribute(attribute) => match attribute {
+            SubmissionAttribute::Id => Cell::from(sub.id.to_string()),
+            SubmissionAttribute::CreatedAt => Cell::DateTime(sub.created_at.naive_utc()),
+            SubmissionAttribute::CompletedAt => {
+                sub.completed_at.map_or(Cell::Empty, |at| Cell::DateTime(at.naive_utc()))
+            }
+            SubmissionAttribute::Status => Cell::from(sub.status.as_str()),
+            SubmissionAttribute::InstanceId => {
+                sub.instance_id.map_or(Cell::Empty, |id| Cell::from(id.to_string()))
+            }
+            SubmissionAttribute::Labels => Cell::from(labels.join(";")),
+        },
+        Content::Field {
+            name,
+            value_type,
+            labels,
+        } => {
+            let label = |value: &'a str| -> Cow<'a, str> {
+                match labels.as_ref().and_then(|l| l.get(value)) {
+                    Some(label) => Cow::Borrowed(label.as_str()),
+                    None => Cow::Borrowed(value),
+                }
+            };
+            match sub.data.get(name) {
+                None | Some(serde_json::Value::Null) => Cell::Empty,
+                Some(serde_json::Value::Bool(b)) => Cell::Bool(*b),
+                Some(serde_json::Value::Number(n)) => {
+                    n.as_f64().map_or_else(|| Cell::from(n.to_string()), Cell::Number)
+                }
+                Some(serde_json::Value::String(s)) => typed(s, *value_type)
+                    .unwrap_or_else(|| Cell::Text(label(s))),
+                Some(serde_json::Value::Array(values)) => Cell::from(
+                    values
+                        .iter()
+                        .map(|v| v.as_str().map_or_else(|| v.to_string(), |s| label(s).into()))
+                        .collect::<Vec<_>>()
+                        .join(";"),
+                ),
+                Some(other) => Cell::from(other.to_string()),
+            }
+        }
+        Content::Coordinate { name, axis } => {
+            coordinate(sub, name, axis).map_or(Cell::Empty, Cell::Number)
+        }
+    }
+}
+
+/// Parses a text answer by its field's type: numbers, dates, date-times and
+/// yes/no checkboxes. Answers that don't parse stay text.
+fn typed(answer: &str, value_type: Option<ValueType>) -> Option<Cell<'static>> {
+    match value_type? {
+        t if t.is_numeric() => answer.trim().parse().ok().map(Cell::Number),
+        ValueType::Date => NaiveDate::parse_from_str(answer, "%Y-%m-%d").ok().map(Cell::Date),
+        ValueType::DateTime => NaiveDateTime::parse_from_str(answer, "%Y-%m-%dT%H:%M")
+            .or_else(|_| NaiveDateTime::parse_from_str(answer, "%Y-%m-%dT%H:%M:%S"))
+            .ok()
+            .map(Cell::DateTime),
+        ValueType::Checkbox => match answer {
+            "true" | "on" => Some(Cell::Bool(true)),
+            "false" | "off" => Some(Cell::Bool(false)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,382 @@
+//! SMS notifications (Twilio and compatible APIs).
+//!
+//! Forms with [`FormSettings::sms`](crate::FormSettings::sms) set text their
+//! recipients when a submission is accepted, e.g. "New demo request from
+//! {{name}}". Each form picks its own sender number, recipients and
+//! message, and may only text for submissions matching a
+//! [`ConditionRule`] and at most so often.
+//!
+//! Texts are sent by [`SmsNotifier`], a [`FormEvents`] hook registered with
+//! [`AnyFormRouterBuilder::events`](crate::AnyFormRouterBuilder::events),
+//! through an [`SmsTransport`]. With the `sms` feature, [`TwilioTransport`]
+//! sends through Twilio's Messages API, or any API compatible with it;
+//! implement the trait yourself to send some other way.
+//!
+//! ```rust,ignore
+//! use anyform::sms::{SmsNotification, SmsNotifier, TwilioTransport};
+//!
+//! let settings = FormSettings::new().sms(
+//!     SmsNotification::new("+15005550006", "New demo request from {{name}}")
+//!         .recipient("+15551234567")
+//!         .condition(ConditionRule::eq("plan", "enterprise"))
+//!         .rate_limit(RateLimit::per_hour(20)),
+//! );
+//! let router = AnyFormRouter::builder()
+//!     .database(db)
+//!     .events(SmsNotifier::new(TwilioTransport::new(account_sid, auth_token)))
+//!     .build();
+//! ```
+//!
+//! Submissions over a form's rate limit are accepted as usual but not
+//! texted about. Like [`crate::rate_limit`], counts are kept in memory by
+//! each server process.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::condition::ConditionRule;
+use crate::entities::{form, submission};
+use crate::events::FormEvents;
+use crate::rate_limit::{RateLimit, RateLimiter, RateLimits};
+use crate::schema::FieldValue;
+
+/// Longest message sent, in characters; longer messages are shortened.
+pub const MAX_MESSAGE_CHARS: usize = 1600;
+
+/// SMS notification settings for a form.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SmsNotification {
+    /// Number (or messaging service ID) texts are sent from.
+    pub from: String,
+
+    /// Numbers texts are sent to.
+    #[serde(default)]
+    pub to: Vec<String>,
+
+    /// Message text; `{{field}}` is replaced with the submitted answer.
+    pub template: String,
+
+    /// Only text about submissions matching this condition.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<ConditionRule>,
+
+    /// Most submissions texted about, counted across all recipients.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl SmsNotification {
+    /// Creates settings texting no one.
+    #[must_use]
+    pub fn new(from: impl Into<String>, template: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: Vec::new(),
+            template: template.into(),
+            condition: None,
+            rate_limit: None,
+        }
+    }
+
+    /// Adds a recipient.
+    #[must_use]
+    pub fn recipient(mut self, number: impl Into<String>) -> Self {
+        self.to.push(number.into());
+        self
+    }
+
+    /// Only texts about submissions matching `condition`.
+    #[must_use]
+    pub fn condition(mut self, condition: ConditionRule) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Caps how often the form texts.
+    #[must_use]
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Returns whether a submission with `data` should be texted about.
+    #[must_use]
+    pub fn matches(&self, data: &HashMap<String, FieldValue>) -> bool {
+        let Some(condition) = &self.condition else {
+            return true;
+        };
+        let values = data
+            .iter()
+            .map(|(name, value)| (name.clone(), serde_json::to_value(value).unwrap_or_default()))
+            .collect();
+        condition.evaluate(&values)
+    }
+
+    /// Fills in the template with `data`, shortened to
+    /// [`MAX_MESSAGE_CHARS`].
+    ///
+    /// Placeholders for unanswered fields are left empty.
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use anyform::sms::SmsNotification;
+    ///
+    /// let sms = SmsNotification::new("+15005550006", "New demo request from {{ name }}");
+    /// let data = HashMap::from([("name".to_string(), "Ada".into())]);
+    /// assert_eq!(sms.render(&data), "New demo request from Ada");
+    /// ```
+    #[must_use]
+    pub fn render(&self, data: &HashMap<String, FieldValue>) -> String {
+        let mut message = String::new();
+        let mut rest = self.template.as_str();
+        while let Some(open) = rest.find("{{") {
+            let Some(close) = rest[open..].find("}}") else {
+                break;
+            };
+            message.push_str(&rest[..open]);
+            let name = rest[open + 2..open + close].trim();
+            if let Some(value) = data.get(name) {
+                message.push_str(&value.to_string_value());
+            }
+            rest = &rest[open + close + 2..];
+        }
+        message.push_str(rest);
+
+        match message.char_indices().nth(MAX_MESSAGE_CHARS) {
+            Some((end, _)) => message[..end].to_string(),
+            None => message,
+        }
+    }
+}
+
+/// A text to send.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SmsMessage {
+    pub from: String,
+    pub to: String,
+    pub body: String,
+}
+
+/// Sends texts, e.g. through an SMS provider's API.
+#[async_trait]
+pub trait SmsTransport: Send + Sync + 'static {
+    /// Sends `message`.
+    async fn send(&self, message: &SmsMessage) -> Result<(), String>;
+}
+
+impl std::fmt::Debug for dyn SmsTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SmsTransport")
+    }
+}
+
+/// Texts the recipients in each form's [`SmsNotification`] when a
+/// submission is accepted.
+///
+/// Register it with
+/// [`AnyFormRouterBuilder::events`](crate::AnyFormRouterBuilder::events),
+/// or call [`SmsNotifier::notify`] from your own hook. Waitlisted
+/// submissions aren't texted about.
+#[derive(Debug)]
+pub struct SmsNotifier {
+    transport: Arc<dyn SmsTransport>,
+    limiter: RateLimiter,
+}
+
+impl SmsNotifier {
+    /// Creates a notifier sending through `transport`.
+    #[must_use]
+    pub fn new(transport: impl SmsTransport) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            limiter: RateLimiter::default(),
+        }
+    }
+
+    /// Texts the form's recipients about `submission`, returning the texts
+    /// sent.
+    ///
+    /// Nothing is sent for forms without SMS settings, submissions not
+    /// matching the condition, or once the form's rate limit is reached.
+    /// Texts the transport fails to send are logged and dropped.
+    pub async fn notify(
+        &self,
+        form: &form::Model,
+        submission: &submission::Model,
+    ) -> Vec<SmsMessage> {
+        let Some(sms) = form.settings().sms else {
+            return Vec::new();
+        };
+        let data = submission.data_map();
+        if sms.to.is_empty() || !sms.matches(&data) {
+            return Vec::new();
+        }
+        let limits = sms.rate_limit.map(|limit| RateLimits::new().per_form(limit));
+        if limits.is_some() && self.limiter.check(form.id, None, limits).is_err() {
+            tracing::debug!(form = %form.slug, "SMS rate limit reached");
+            return Vec::new();
+        }
+
+        let body = sms.render(&data);
+        let mut sent = Vec::new();
+        for to in sms.to {
+            let message = SmsMessage {
+                from: sms.from.clone(),
+                to,
+                body: body.clone(),
+            };
+            match self.transport.send(&message).await {
+                Ok(()) => sent.push(message),
+                Err(error) => {
+                    tracing::warn!(
+                        form = %form.slug,
+                        to = %message.to,
+                        %error,
+                        "failed to send SMS"
+                    );
+                }
+            }
+        }
+        sent
+    }
+}
+
+#[async_trait]
+impl FormEvents for SmsNotifier {
+    async fn on_submission_created(&self, form: &form::Model, submission: &submission::Model) {
+        self.notify(form, submission).await;
+    }
+}
+
+#[cfg(feature = "sms")]
+pub use twilio::TwilioTransport;
+
+#[cfg(feature = "sms")]
+mod twilio {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use base64::Engine;
+
+    use super::{SmsMessage, SmsTransport};
+
+    /// How long sending a text may take before it is given up on.
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Sends texts through Twilio's Messages API.
+    ///
+    /// Other providers offering the same API can be used by pointing
+    /// [`TwilioTransport::base_url`] at them.
+    #[derive(Clone)]
+    pub struct TwilioTransport {
+        account_sid: String,
+        auth_token: String,
+        base_url: String,
+        agent: ureq::Agent,
+    }
+
+    impl std::fmt::Debug for TwilioTransport {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("TwilioTransport")
+                .field("account_sid", &self.account_sid)
+                .field("base_url", &self.base_url)
+                .finish_non_exhaustive()
+        }
+    }
+
+    impl TwilioTransport {
+        /// Creates a transport for a Twilio account.
+        #[must_use]
+        pub fn new(account_sid: impl Into<String>, auth_token: impl Into<String>) -> Self {
+            let config = ureq::Agent::config_builder()
+                .timeout_global(Some(TIMEOUT))
+                .http_status_as_error(false)
+                .build();
+            Self {
+                account_sid: account_sid.into(),
+                auth_token: auth_token.into(),
+                base_url: "https://api.twilio.com".to_string(),
+                agent: config.into(),
+            }
+        }
+
+        /// Sends through a Twilio-compatible API at `url` instead.
+        #[must_use]
+        pub fn base_url(mut self, url: impl Into<String>) -> Self {
+            self.base_url = url.into().trim_end_matches('/').to_string();
+            self
+        }
+
+        fn messages_url(&self) -> String {
+            format!("{}/2010-04-01/Accounts/{}/Messages.json", self.base_url, self.account_sid)
+        }
+    }
+
+    #[async_trait]
+    impl SmsTransport for TwilioTransport {
+        async fn send(&self, message: &SmsMessage) -> Result<(), String> {
+            let credentials = format!("{}:{}", self.account_sid, self.auth_token);
+            let authorization = format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            );
+            let params = [
+                ("From", message.from.clone()),
+                ("To", message.to.clone()),
+                ("Body", message.body.clone()),
+            ];
+            let url = self.messages_url();
+            let agent = self.agent.clone();
+
+            tokio::task::spawn_blocking(move || {
+                let mut response = agent
+                    .post(&url)
+                    .header("Authorization", &authorization)
+                    .send_form(params)
+                    .map_err(|e| e.to_string())?;
+                if response.status().is_success() {
+                    return Ok(());
+                }
+                let body = response.body_mut().read_to_string().unwrap_or_default();
+                Err(format!("{}: {body}", response.status()))
+            })
+            .await
+            .map_err(|e| e.to_string())?
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_fills_placeholders() {
+        let sms = SmsNotification::new("+1", "{{name}} ({{ email }}) wants {{plan}}{{missing}}");
+        let data = HashMap::from([
+            ("name".to_string(), FieldValue::from("Ada")),
+            ("email".to_string(), FieldValue::from("ada@example.com")),
+            ("plan".to_string(), FieldValue::from("enterprise")),
+        ]);
+        assert_eq!(sms.render(&data), "Ada (ada@example.com) wants enterprise");
+
+        let unclosed = SmsNotification::new("+1", "Hi {{name");
+        assert_eq!(unclosed.render(&data), "Hi {{name");
+
+        let long = SmsNotification::new("+1", "x".repeat(MAX_MESSAGE_CHARS + 10));
+        assert_eq!(long.render(&data).chars().count(), MAX_MESSAGE_CHARS);
+    }
+
+    #[test]
+    fn test_condition_filters_submissions() {
+        let sms = SmsNotification::new("+1", "hi").condition(ConditionRule::eq("plan", "pro"));
+        let pro = HashMap::from([("plan".to_string(), FieldValue::from("pro"))]);
+        let free = HashMap::from([("plan".to_string(), FieldValue::from("free"))]);
+        assert!(sms.matches(&pro));
+        assert!(!sms.matches(&free));
+        assert!(SmsNotification::new("+1", "hi").matches(&free));
+    }
+}
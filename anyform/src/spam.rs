@@ -0,0 +1,281 @@
+//! Honeypot and time-trap spam protection.
+//!
+//! Forms with [`FormSettings::spam_protection`](crate::FormSettings::spam_protection)
+//! set are rendered with two extra hidden inputs:
+//!
+//! - a honeypot ([`HONEYPOT_FIELD`]) that people never see but form-filling
+//!   bots tend to fill in, and
+//! - a render stamp ([`RENDERED_AT_FIELD`]): the time the form was
+//!   rendered, signed with the router's [`SpamSecret`].
+//!
+//! Submissions with the honeypot filled in, or sent sooner than
+//! [`SpamProtection::min_fill_secs`] after the form was rendered, are
+//! rejected with `400 SPAM_REJECTED`. Server-rendered form posts must carry
+//! a valid stamp; JSON API submissions are checked when they include one,
+//! so API clients that render forms themselves keep working.
+//!
+//! The router signs stamps with a random key unless one is set with
+//! [`AnyFormRouterBuilder::spam_secret`](crate::AnyFormRouterBuilder::spam_secret).
+//! Set one when several servers share the traffic, or forms rendered by one
+//! server are rejected by the others.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::error::FormError;
+use crate::schema::FieldValue;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Name of the honeypot input.
+pub const HONEYPOT_FIELD: &str = "_af_website";
+
+/// Name of the input carrying the signed render stamp.
+pub const RENDERED_AT_FIELD: &str = "_af_rendered_at";
+
+/// Default [`SpamProtection::min_fill_secs`].
+pub const DEFAULT_MIN_FILL_SECS: u32 = 3;
+
+/// Spam protection settings for a form.
+///
+/// ```
+/// use anyform::{FormSettings, SpamProtection};
+///
+/// // Honeypot, and at least 5 seconds between rendering and submitting
+/// let settings = FormSettings::new().spam_protection(SpamProtection::new().min_fill_secs(5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpamProtection {
+    /// Whether to render and check the honeypot.
+    #[serde(default = "default_honeypot")]
+    pub honeypot: bool,
+
+    /// Fewest seconds between rendering the form and submitting it; `0`
+    /// turns the time trap off.
+    #[serde(default = "default_min_fill_secs")]
+    pub min_fill_secs: u32,
+}
+
+fn default_honeypot() -> bool {
+    true
+}
+
+fn default_min_fill_secs() -> u32 {
+    DEFAULT_MIN_FILL_SECS
+}
+
+impl Default for SpamProtection {
+    fn default() -> Self {
+        Self {
+            honeypot: true,
+            min_fill_secs: DEFAULT_MIN_FILL_SECS,
+        }
+    }
+}
+
+impl SpamProtection {
+    /// Enables the honeypot and a [`DEFAULT_MIN_FILL_SECS`] time trap.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether to render and check the honeypot.
+    #[must_use]
+    pub fn honeypot(mut self, enabled: bool) -> Self {
+        self.honeypot = enabled;
+        self
+    }
+
+    /// Sets the fewest seconds between rendering and submitting; `0` turns
+    /// the time trap off.
+    #[must_use]
+    pub fn min_fill_secs(mut self, secs: u32) -> Self {
+        self.min_fill_secs = secs;
+        self
+    }
+
+    /// Checks a submission, removing the spam-protection inputs from `data`
+    /// either way. `require_stamp` rejects submissions without a render
+    /// stamp, for forms known to be rendered with one.
+    pub fn check(
+        &self,
+        secret: &SpamSecret,
+        data: &mut HashMap<String, FieldValue>,
+        require_stamp: bool,
+    ) -> Result<(), FormError> {
+        self.check_at(secret, data, require_stamp, Utc::now())
+    }
+
+    /// Like [`Self::check`], as of `now`.
+    pub fn check_at(
+        &self,
+        secret: &SpamSecret,
+        data: &mut HashMap<String, FieldValue>,
+        require_stamp: bool,
+        now: DateTime<Utc>,
+    ) -> Result<(), FormError> {
+        let (honeypot, stamp) = strip(data);
+
+        if self.honeypot && honeypot.is_some_and(|v| !v.is_empty()) {
+            return Err(rejected("honeypot filled in"));
+        }
+
+        if self.min_fill_secs == 0 {
+            return Ok(());
+        }
+        let Some(stamp) = stamp.as_ref().and_then(FieldValue::as_str) else {
+            return if require_stamp {
+                Err(rejected("render stamp missing"))
+            } else {
+                Ok(())
+            };
+        };
+        let rendered_at = secret
+            .verify(stamp)
+            .ok_or_else(|| rejected("render stamp invalid"))?;
+        if (now - rendered_at).num_seconds() < i64::from(self.min_fill_secs) {
+            return Err(rejected("submitted too quickly"));
+        }
+        Ok(())
+    }
+}
+
+/// Removes the spam-protection inputs from submitted data, returning the
+/// honeypot and render stamp values.
+pub fn strip(data: &mut HashMap<String, FieldValue>) -> (Option<FieldValue>, Option<FieldValue>) {
+    (data.remove(HONEYPOT_FIELD), data.remove(RENDERED_AT_FIELD))
+}
+
+fn rejected(reason: &str) -> FormError {
+    tracing::debug!(reason, "submission rejected as spam");
+    FormError::SpamRejected
+}
+
+/// Secret key that signs render stamps.
+#[derive(Clone)]
+pub struct SpamSecret(Arc<[u8]>);
+
+impl SpamSecret {
+    /// Creates a signing key from secret bytes.
+    #[must_use]
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self(Arc::from(secret.as_ref()))
+    }
+
+    /// Creates a random signing key, valid for the life of the process.
+    #[must_use]
+    pub fn random() -> Self {
+        let mut key = [0u8; 32];
+        key[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+        key[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+        Self::new(key)
+    }
+
+    /// Signs a render stamp for `rendered_at`.
+    #[must_use]
+    pub fn stamp(&self, rendered_at: DateTime<Utc>) -> String {
+        self.sign(&rendered_at.timestamp().to_be_bytes())
+    }
+
+    /// Returns the render time of a stamp this key signed.
+    #[must_use]
+    pub fn verify(&self, stamp: &str) -> Option<DateTime<Utc>> {
+        let payload = self.open(stamp)?;
+        DateTime::from_timestamp(i64::from_be_bytes(payload.try_into().ok()?), 0)
+    }
+
+    /// Signs `payload` into a token. Tokens of different kinds tell apart by
+    /// the length of their payload.
+    pub(crate) fn sign(&self, payload: &[u8]) -> String {
+        let signature = self.mac(payload).finalize().into_bytes();
+        format!("{}.{}", BASE64.encode(payload), BASE64.encode(signature))
+    }
+
+    /// Returns the payload of a token this key signed.
+    pub(crate) fn open(&self, token: &str) -> Option<Vec<u8>> {
+        let (payload, signature) = token.split_once('.')?;
+        let payload = BASE64.decode(payload).ok()?;
+        let signature = BASE64.decode(signature).ok()?;
+        self.mac(&payload).verify_slice(&signature).ok()?;
+        Some(payload)
+    }
+
+    fn mac(&self, payload: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("HMAC accepts keys of any length");
+        mac.update(payload);
+        mac
+    }
+}
+
+impl Default for SpamSecret {
+    fn default() -> Self {
+        Self::random()
+    }
+}
+
+impl std::fmt::Debug for SpamSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SpamSecret(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn submission(honeypot: &str, stamp: Option<String>) -> HashMap<String, FieldValue> {
+        let mut data = HashMap::from([
+            ("name".to_string(), FieldValue::from("Ada")),
+            (HONEYPOT_FIELD.to_string(), FieldValue::from(honeypot)),
+        ]);
+        if let Some(stamp) = stamp {
+            data.insert(RENDERED_AT_FIELD.to_string(), FieldValue::from(stamp.as_str()));
+        }
+        data
+    }
+
+    #[test]
+    fn test_stamps_verify_with_their_key_only() {
+        let secret = SpamSecret::new("s3cret");
+        let rendered_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let stamp = secret.stamp(rendered_at);
+
+        assert_eq!(secret.verify(&stamp), Some(rendered_at));
+        assert_eq!(SpamSecret::new("other").verify(&stamp), None);
+        assert_eq!(secret.verify("garbage"), None);
+    }
+
+    #[test]
+    fn test_checks_honeypot_and_fill_time() {
+        let secret = SpamSecret::random();
+        let protection = SpamProtection::new();
+        let now = Utc::now();
+        let early = Some(secret.stamp(now - Duration::seconds(1)));
+        let late = Some(secret.stamp(now - Duration::seconds(10)));
+
+        let mut data = submission("", late.clone());
+        assert!(protection.check_at(&secret, &mut data, true, now).is_ok());
+        assert_eq!(data.len(), 1, "spam inputs are stripped");
+
+        let mut data = submission("http://spam.example", late);
+        assert!(protection.check_at(&secret, &mut data, true, now).is_err());
+        assert!(protection.check_at(&secret, &mut submission("", early), true, now).is_err());
+        assert!(protection.check_at(&secret, &mut submission("", None), true, now).is_err());
+        assert!(protection.check_at(&secret, &mut submission("", None), false, now).is_ok());
+
+        let lenient = protection.honeypot(false).min_fill_secs(0);
+        let mut data = submission("filled", None);
+        assert!(lenient.check_at(&secret, &mut data, true, now).is_ok());
+        assert_eq!(data.len(), 1);
+    }
+}
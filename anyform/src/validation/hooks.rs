@@ -0,0 +1,198 @@
+//! Async validation hooks for fields with server-side checks.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::entities::field::Model as Field;
+use crate::entities::form::Model as Form;
+use crate::error::ValidationErrors;
+use crate::schema::FieldValue;
+
+/// Checks field values that need the server, such as uniqueness or coupon
+/// validity.
+///
+/// Register one with
+/// [`AnyFormRouterBuilder::field_validator`](crate::AnyFormRouterBuilder::field_validator).
+/// It is consulted for fields with
+/// [`ValidationRules::server_validate`](crate::ValidationRules::server_validate)
+/// set, both by the single-field validation endpoint and on submit, and only
+/// for non-empty values that passed the field's built-in rules.
+///
+/// ```rust,ignore
+/// use anyform::validation::FieldValidator;
+///
+/// struct UniqueUsername(DatabaseConnection);
+///
+/// #[async_trait::async_trait]
+/// impl FieldValidator for UniqueUsername {
+///     async fn validate(&self, _form: &Form, field: &Field, value: &FieldValue, _values: &HashMap<String, FieldValue>) -> Vec<String> {
+///         match (field.name.as_str(), value.as_str()) {
+///             ("username", Some(name)) if username_taken(&self.0, name).await => {
+///                 vec!["That username is taken".into()]
+///             }
+///             _ => vec![],
+///         }
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait FieldValidator: Send + Sync + 'static {
+    /// Returns error messages for `value`, or none when it is valid.
+    ///
+    /// `values` holds the rest of the form's values, when known.
+    async fn validate(
+        &self,
+        form: &Form,
+        field: &Field,
+        value: &FieldValue,
+        values: &HashMap<String, FieldValue>,
+    ) -> Vec<String>;
+}
+
+impl std::fmt::Debug for dyn FieldValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FieldValidator")
+    }
+}
+
+/// Runs `validator` over a submission's `server_validate` fields, adding to
+/// `errors`.
+///
+/// Fields that already have errors, and empty values, are skipped.
+pub async fn run_field_validator(
+    validator: &dyn FieldValidator,
+    form: &Form,
+    fields: &[Field],
+    data: &HashMap<String, FieldValue>,
+    errors: &mut ValidationErrors,
+) {
+    for field in fields {
+        if !field.validation().server_validate || errors.get(&field.name).is_some() {
+            continue;
+        }
+
+        let value = data.get(&field.id.to_string()).or_else(|| data.get(&field.name));
+        let Some(value) = value.filter(|v| !v.is_empty()) else {
+            continue;
+        };
+
+        for message in validator.validate(form, field, value, data).await {
+            errors.add(&field.name, message);
+        }
+    }
+}
+
+/// Async validators registered by name, run on the fields that list them in
+/// [`ValidationRules::validators`](crate::ValidationRules::validators).
+///
+/// Register them with
+/// [`AnyFormRouterBuilder::validator`](crate::AnyFormRouterBuilder::validator)
+/// or [`AnyFormRouterBuilder::validators`](crate::AnyFormRouterBuilder::validators).
+/// Like the field validator, they run on submit and from the single-field
+/// validation endpoint, for non-empty values that passed the field's
+/// built-in rules, in the order the field lists them.
+///
+/// ```rust,ignore
+/// use anyform::validation::ValidatorRegistry;
+///
+/// let validators = ValidatorRegistry::new()
+///     .register("unique_email", UniqueEmail(db.clone()))
+///     .register("valid_coupon", CouponCheck::new());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ValidatorRegistry {
+    validators: HashMap<String, Arc<dyn FieldValidator>>,
+}
+
+impl ValidatorRegistry {
+    /// Creates an empty registry.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `validator` under `name`, replacing any validator
+    /// registered under it before.
+    #[must_use]
+    pub fn register(mut self, name: impl Into<String>, validator: impl FieldValidator) -> Self {
+        self.validators.insert(name.into(), Arc::new(validator));
+        self
+    }
+
+    /// Returns true if a validator is registered under `name`.
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.validators.contains_key(name)
+    }
+
+    /// Returns the registered names, sorted.
+    #[must_use]
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.validators.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Returns true if no validators are registered.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    /// Runs the validators `field` lists on `value`, returning their error
+    /// messages.
+    ///
+    /// Names that aren't registered are skipped with a warning.
+    pub async fn validate(
+        &self,
+        form: &Form,
+        field: &Field,
+        value: &FieldValue,
+        values: &HashMap<String, FieldValue>,
+    ) -> Vec<String> {
+        let mut errors = Vec::new();
+        for name in &field.validation().validators {
+            match self.validators.get(name) {
+                Some(validator) => {
+                    errors.extend(validator.validate(form, field, value, values).await);
+                }
+                None => tracing::warn!(
+                    form = %form.slug,
+                    field = %field.name,
+                    validator = %name,
+                    "field lists an unregistered validator"
+                ),
+            }
+        }
+        errors
+    }
+}
+
+/// Runs the named validators of a submission's fields from `registry`,
+/// adding to `errors`.
+///
+/// Fields that already have errors, and empty values, are skipped.
+pub async fn run_validators(
+    registry: &ValidatorRegistry,
+    form: &Form,
+    fields: &[Field],
+    data: &HashMap<String, FieldValue>,
+    errors: &mut ValidationErrors,
+) {
+    for field in fields {
+        if field.validation().validators.is_empty() || errors.get(&field.name).is_some() {
+            continue;
+        }
+
+        let value = data.get(&field.id.to_string()).or_else(|| data.get(&field.name));
+        let Some(value) = value.filter(|v| !v.is_empty()) else {
+            continue;
+        };
+
+        for message in registry.validate(form, field, value, data).await {
+            errors.add(&field.name, message);
+        }
+    }
+}
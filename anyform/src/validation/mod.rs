@@ -1,34 +1,50 @@
 //! Form validation engine.
 
+mod hooks;
+
 use regex::Regex;
 use std::collections::HashMap;
 
+use crate::branching::route_steps;
 use crate::entities::field::Model as Field;
 use crate::entities::step::Model as Step;
 use crate::error::{StepValidationErrors, ValidationErrors};
-use crate::schema::{FieldValue, ValidationRules, ValueType};
+use crate::i18n::{interpolate, Messages};
+use crate::piping::{self, Answers};
+use crate::schema::{FieldValue, GeoLocation, ValidationRules, ValueType};
+
+pub use hooks::{run_field_validator, run_validators, FieldValidator, ValidatorRegistry};
 
 /// Validates a submission against a form's fields.
 ///
+/// Sub-fields of repeatable groups in `fields` are checked against each of
+/// their group's rows; see [`validate_rows`].
+///
 /// Returns a `ValidationErrors` containing any validation failures.
 pub fn validate_submission(
     fields: &[Field],
     data: &HashMap<String, FieldValue>,
+) -> ValidationErrors {
+    validate_submission_with(fields, data, &Messages::default())
+}
+
+/// Validates a submission like [`validate_submission`], with error messages
+/// from `messages`.
+pub fn validate_submission_with(
+    fields: &[Field],
+    data: &HashMap<String, FieldValue>,
+    messages: &Messages,
 ) -> ValidationErrors {
     let mut errors = ValidationErrors::new();
 
     for field in fields {
-        // Skip display-only fields
-        if field.is_display_only() {
+        // Skip display-only fields and sub-fields, checked with their group
+        if field.is_display_only() || field.parent_id.is_some() {
             continue;
         }
 
         let value = data.get(&field.id.to_string()).or_else(|| data.get(&field.name));
-        let field_errors = validate_field(field, value);
-
-        for error in field_errors {
-            errors.add(&field.name, error);
-        }
+        errors.merge(validate_with_rows(field, fields, value, messages));
     }
 
     errors
@@ -38,6 +54,7 @@ pub fn validate_submission(
 ///
 /// This function:
 /// - Evaluates step conditions and skips hidden steps
+/// - Follows jump rules and skips steps jumped over
 /// - Evaluates field conditions and skips hidden fields
 /// - Groups validation errors by step ID
 ///
@@ -52,6 +69,16 @@ pub fn validate_submission(
 pub fn validate_multi_step_submission(
     steps: &[(Step, Vec<Field>)],
     data: &HashMap<String, FieldValue>,
+) -> StepValidationErrors {
+    validate_multi_step_submission_with(steps, data, &Messages::default())
+}
+
+/// Validates a multi-step submission like
+/// [`validate_multi_step_submission`], with error messages from `messages`.
+pub fn validate_multi_step_submission_with(
+    steps: &[(Step, Vec<Field>)],
+    data: &HashMap<String, FieldValue>,
+    messages: &Messages,
 ) -> StepValidationErrors {
     let mut errors = StepValidationErrors::new();
 
@@ -61,20 +88,21 @@ pub fn validate_multi_step_submission(
         .map(|(k, v)| (k.clone(), v.into()))
         .collect();
 
+    // Steps hidden by their condition or jumped over are off the route
+    let step_models: Vec<Step> = steps.iter().map(|(step, _)| step.clone()).collect();
+    let field_models: Vec<Field> = steps.iter().flat_map(|(_, f)| f.iter().cloned()).collect();
+    let route = route_steps(&step_models, &field_models, data);
+
     for (step, fields) in steps {
-        // Check step condition - skip if step is hidden
-        if let Some(condition) = step.condition_rule() {
-            if !condition.evaluate(&json_data) {
-                // Step is hidden, skip validation
-                continue;
-            }
+        if !route.contains(&step.id) {
+            continue;
         }
 
         let step_id = step.id.to_string();
 
         for field in fields {
-            // Skip display-only fields
-            if field.is_display_only() {
+            // Skip display-only fields and sub-fields, checked with their group
+            if field.is_display_only() || field.parent_id.is_some() {
                 continue;
             }
 
@@ -87,10 +115,12 @@ pub fn validate_multi_step_submission(
             }
 
             let value = data.get(&field.id.to_string()).or_else(|| data.get(&field.name));
-            let field_errors = validate_field(field, value);
+            let field_errors = validate_with_rows(field, fields, value, messages);
 
-            for error in field_errors {
-                errors.add(&step_id, &field.name, error);
+            for (name, messages) in field_errors.errors {
+                for error in messages {
+                    errors.add(&step_id, &name, error);
+                }
             }
         }
     }
@@ -113,6 +143,17 @@ pub fn validate_step(
     step: &Step,
     fields: &[Field],
     data: &HashMap<String, FieldValue>,
+) -> ValidationErrors {
+    validate_step_with(step, fields, data, &Messages::default())
+}
+
+/// Validates a step like [`validate_step`], with error messages from
+/// `messages`.
+pub fn validate_step_with(
+    step: &Step,
+    fields: &[Field],
+    data: &HashMap<String, FieldValue>,
+    messages: &Messages,
 ) -> ValidationErrors {
     let mut errors = ValidationErrors::new();
 
@@ -130,8 +171,8 @@ pub fn validate_step(
     }
 
     for field in fields {
-        // Skip display-only fields
-        if field.is_display_only() {
+        // Skip display-only fields and sub-fields, checked with their group
+        if field.is_display_only() || field.parent_id.is_some() {
             continue;
         }
 
@@ -143,11 +184,7 @@ pub fn validate_step(
         }
 
         let value = data.get(&field.id.to_string()).or_else(|| data.get(&field.name));
-        let field_errors = validate_field(field, value);
-
-        for error in field_errors {
-            errors.add(&field.name, error);
-        }
+        errors.merge(validate_with_rows(field, fields, value, messages));
     }
 
     errors
@@ -189,9 +226,23 @@ pub fn is_step_visible(step: &Step, data: &HashMap<String, FieldValue>) -> bool
 
 /// Validates a single field value.
 pub fn validate_field(field: &Field, value: Option<&FieldValue>) -> Vec<String> {
+    validate_field_with(field, value, &Messages::default())
+}
+
+/// Validates a single field value like [`validate_field`], with error
+/// messages from `messages`.
+///
+/// Messages name the field by its label, with answers it pipes in read as
+/// their fallbacks.
+pub fn validate_field_with(
+    field: &Field,
+    value: Option<&FieldValue>,
+    messages: &Messages,
+) -> Vec<String> {
     let mut errors = Vec::new();
     let rules = field.validation();
     let value_type = field.value_type();
+    let label = &piping::resolve(&field.label, &Answers::default());
 
     // Check required
     if field.required {
@@ -201,7 +252,7 @@ pub fn validate_field(field: &Field, value: Option<&FieldValue>) -> Vec<String>
         };
 
         if is_empty {
-            errors.push(format!("{} is required", field.label));
+            errors.push(rule_error(&rules, messages, "required", label, &[]));
             return errors; // No point checking other rules if empty and required
         }
     }
@@ -217,64 +268,137 @@ pub fn validate_field(field: &Field, value: Option<&FieldValue>) -> Vec<String>
 
     // Type-specific validation
     if let Some(vt) = value_type {
-        errors.extend(validate_by_type(vt, value, &field.label));
+        errors.extend(validate_by_type(vt, value, label, messages));
     }
 
     // Rule-based validation
-    errors.extend(validate_by_rules(&rules, value, &field.label));
+    errors.extend(validate_by_rules(&rules, value, label, messages));
+
+    errors
+}
+
+/// Validates the rows of a repeatable group against its sub-fields.
+///
+/// Each row is checked like a submission of the sub-fields, with errors
+/// keyed by the sub-field's input name, e.g. `members[0][email]`. The
+/// group's own rules, such as `min_rows`, are left to [`validate_field`].
+pub fn validate_rows(
+    group: &Field,
+    sub_fields: &[Field],
+    value: Option<&FieldValue>,
+) -> ValidationErrors {
+    validate_rows_with(group, sub_fields, value, &Messages::default())
+}
+
+/// Validates a repeatable group's rows like [`validate_rows`], with error
+/// messages from `messages`.
+pub fn validate_rows_with(
+    group: &Field,
+    sub_fields: &[Field],
+    value: Option<&FieldValue>,
+    messages: &Messages,
+) -> ValidationErrors {
+    let mut errors = ValidationErrors::new();
+    let Some(rows) = value.and_then(FieldValue::as_rows) else {
+        return errors;
+    };
+
+    for (index, row) in rows.iter().enumerate() {
+        for field in sub_fields.iter().filter(|f| !f.is_display_only()) {
+            for error in validate_field_with(field, row.get(&field.name), messages) {
+                errors.add(format!("{}[{index}][{}]", group.name, field.name), error);
+            }
+        }
+    }
+
+    errors
+}
+
+/// Validates a field's value and, for a repeatable group, its rows against
+/// the group's sub-fields among `fields`.
+fn validate_with_rows(
+    field: &Field,
+    fields: &[Field],
+    value: Option<&FieldValue>,
+    messages: &Messages,
+) -> ValidationErrors {
+    let mut errors = ValidationErrors::new();
+    for error in validate_field_with(field, value, messages) {
+        errors.add(&field.name, error);
+    }
+
+    if field.is_repeater() {
+        let sub_fields: Vec<Field> = fields
+            .iter()
+            .filter(|f| f.parent_id == Some(field.id))
+            .cloned()
+            .collect();
+        errors.merge(validate_rows_with(field, &sub_fields, value, messages));
+    }
 
     errors
 }
 
 /// Validates a value based on its type.
-fn validate_by_type(value_type: ValueType, value: &FieldValue, label: &str) -> Vec<String> {
+fn validate_by_type(
+    value_type: ValueType,
+    value: &FieldValue,
+    label: &str,
+    messages: &Messages,
+) -> Vec<String> {
     let mut errors = Vec::new();
 
     match value_type {
         ValueType::Email => {
             if let Some(s) = value.as_str() {
                 if !is_valid_email(s) {
-                    errors.push(format!("{label} must be a valid email address"));
+                    errors.push(messages.format("email", label, &[]));
                 }
             }
         }
         ValueType::Url => {
             if let Some(s) = value.as_str() {
                 if !is_valid_url(s) {
-                    errors.push(format!("{label} must be a valid URL"));
+                    errors.push(messages.format("url", label, &[]));
                 }
             }
         }
-        ValueType::Number | ValueType::Rating | ValueType::Scale | ValueType::Nps => {
-            if value.as_number().is_none() {
-                errors.push(format!("{label} must be a number"));
-            }
+        ValueType::Number | ValueType::Rating | ValueType::Scale | ValueType::Nps
+            if value.as_number().is_none() =>
+        {
+            errors.push(messages.format("number", label, &[]));
+        }
+        ValueType::Repeater if value.as_rows().is_none() => {
+            errors.push(messages.format("rows", label, &[]));
+        }
+        ValueType::Location if !value.as_location().is_some_and(GeoLocation::is_valid) => {
+            errors.push(messages.format("location", label, &[]));
         }
         ValueType::Tel => {
             if let Some(s) = value.as_str() {
                 if !is_valid_phone(s) {
-                    errors.push(format!("{label} must be a valid phone number"));
+                    errors.push(messages.format("phone", label, &[]));
                 }
             }
         }
         ValueType::Date => {
             if let Some(s) = value.as_str() {
                 if !is_valid_date(s) {
-                    errors.push(format!("{label} must be a valid date (YYYY-MM-DD)"));
+                    errors.push(messages.format("date", label, &[]));
                 }
             }
         }
         ValueType::DateTime => {
             if let Some(s) = value.as_str() {
                 if !is_valid_datetime(s) {
-                    errors.push(format!("{label} must be a valid date and time"));
+                    errors.push(messages.format("datetime", label, &[]));
                 }
             }
         }
         ValueType::Time => {
             if let Some(s) = value.as_str() {
                 if !is_valid_time(s) {
-                    errors.push(format!("{label} must be a valid time (HH:MM)"));
+                    errors.push(messages.format("time", label, &[]));
                 }
             }
         }
@@ -285,19 +409,24 @@ fn validate_by_type(value_type: ValueType, value: &FieldValue, label: &str) -> V
 }
 
 /// Validates a value based on validation rules.
-fn validate_by_rules(rules: &ValidationRules, value: &FieldValue, label: &str) -> Vec<String> {
+fn validate_by_rules(
+    rules: &ValidationRules,
+    value: &FieldValue,
+    label: &str,
+    messages: &Messages,
+) -> Vec<String> {
     let mut errors = Vec::new();
 
     // String length validation
     if let Some(s) = value.as_str() {
         if let Some(min) = rules.min_length {
             if s.len() < min {
-                errors.push(format!("{label} must be at least {min} characters"));
+                errors.push(rule_error(rules, messages, "min_length", label, &[("min", &min)]));
             }
         }
         if let Some(max) = rules.max_length {
             if s.len() > max {
-                errors.push(format!("{label} must be at most {max} characters"));
+                errors.push(rule_error(rules, messages, "max_length", label, &[("max", &max)]));
             }
         }
 
@@ -305,11 +434,11 @@ fn validate_by_rules(rules: &ValidationRules, value: &FieldValue, label: &str) -
         if let Some(pattern) = &rules.pattern {
             if let Ok(re) = Regex::new(pattern) {
                 if !re.is_match(s) {
-                    let message = rules
-                        .pattern_message
-                        .as_deref()
-                        .unwrap_or("Invalid format");
-                    errors.push(format!("{label}: {message}"));
+                    let message = rules.pattern_message.as_deref().map_or_else(
+                        || messages.template("invalid_format").to_string(),
+                        |message| interpolate(message, label, &[]),
+                    );
+                    errors.push(messages.format("pattern", label, &[("message", &message)]));
                 }
             }
         }
@@ -319,12 +448,12 @@ fn validate_by_rules(rules: &ValidationRules, value: &FieldValue, label: &str) -
     if let Some(n) = value.as_number() {
         if let Some(min) = rules.min {
             if n < min {
-                errors.push(format!("{label} must be at least {min}"));
+                errors.push(rule_error(rules, messages, "min", label, &[("min", &min)]));
             }
         }
         if let Some(max) = rules.max {
             if n > max {
-                errors.push(format!("{label} must be at most {max}"));
+                errors.push(rule_error(rules, messages, "max", label, &[("max", &max)]));
             }
         }
     }
@@ -333,12 +462,26 @@ fn validate_by_rules(rules: &ValidationRules, value: &FieldValue, label: &str) -
     if let Some(arr) = value.as_array() {
         if let Some(min) = rules.min_selections {
             if arr.len() < min {
-                errors.push(format!("{label} requires at least {min} selections"));
+                errors.push(rule_error(rules, messages, "min_selections", label, &[("min", &min)]));
             }
         }
         if let Some(max) = rules.max_selections {
             if arr.len() > max {
-                errors.push(format!("{label} allows at most {max} selections"));
+                errors.push(rule_error(rules, messages, "max_selections", label, &[("max", &max)]));
+            }
+        }
+    }
+
+    // Repeatable group validation
+    if let Some(rows) = value.as_rows() {
+        if let Some(min) = rules.min_rows {
+            if rows.len() < min {
+                errors.push(rule_error(rules, messages, "min_rows", label, &[("min", &min)]));
+            }
+        }
+        if let Some(max) = rules.max_rows {
+            if rows.len() > max {
+                errors.push(rule_error(rules, messages, "max_rows", label, &[("max", &max)]));
             }
         }
     }
@@ -346,6 +489,20 @@ fn validate_by_rules(rules: &ValidationRules, value: &FieldValue, label: &str) -
     errors
 }
 
+/// Formats the error for `rule`, from the field's own template if it has one.
+fn rule_error(
+    rules: &ValidationRules,
+    messages: &Messages,
+    rule: &str,
+    label: &str,
+    args: &[(&str, &dyn std::fmt::Display)],
+) -> String {
+    match rules.message_for(rule) {
+        Some(template) => interpolate(template, label, args),
+        None => messages.format(rule, label, args),
+    }
+}
+
 /// Checks if a string is a valid email address.
 fn is_valid_email(s: &str) -> bool {
     // Basic email validation - contains @ and has text on both sides
@@ -388,12 +545,14 @@ fn is_valid_time(s: &str) -> bool {
 mod tests {
     use super::*;
     use chrono::Utc;
+    use std::collections::BTreeMap;
     use uuid::Uuid;
 
     fn make_field(name: &str, field_type: &str, required: bool) -> Field {
         Field {
             id: Uuid::new_v4(),
             step_id: Uuid::new_v4(),
+            parent_id: None,
             name: name.to_string(),
             label: name.to_string(),
             field_type: field_type.to_string(),
@@ -407,6 +566,8 @@ mod tests {
             correct_answer: None,
             points: None,
             weight: None,
+            category: None,
+            classification: "standard".to_string(),
             created_at: Utc::now().into(),
         }
     }
@@ -419,7 +580,11 @@ mod tests {
             description: None,
             order: 0,
             condition,
+            next: None,
+            include_form: None,
+            include_as: None,
             created_at: Utc::now().into(),
+            updated_at: Utc::now().into(),
         }
     }
 
@@ -541,6 +706,33 @@ mod tests {
         assert!(errors.is_empty());
     }
 
+    #[test]
+    fn test_multi_step_skips_steps_jumped_over() {
+        // Personal accounts skip the company step
+        let mut step1 = make_step("Account Type", None);
+        step1.next = Some(serde_json::json!([
+            { "when": { "field": "role", "op": "eq", "value": "personal" }, "to": "Contact" }
+        ]));
+        let step2 = make_step("Company", None);
+        let step3 = make_step("Contact", None);
+
+        let steps = vec![
+            (step1, vec![make_field("role", "select", true)]),
+            (step2, vec![make_field("company_size", "number", true)]),
+            (step3, vec![make_field("email", "email", true)]),
+        ];
+
+        let mut data = HashMap::new();
+        data.insert("role".to_string(), FieldValue::Text("personal".to_string()));
+        let errors = validate_multi_step_submission(&steps, &data);
+        assert_eq!(errors.error_count(), 1);
+        assert!(errors.steps.contains_key(&steps[2].0.id.to_string()));
+
+        data.insert("role".to_string(), FieldValue::Text("business".to_string()));
+        let errors = validate_multi_step_submission(&steps, &data);
+        assert_eq!(errors.error_count(), 2);
+    }
+
     #[test]
     fn test_step_validation_errors_grouped_by_step() {
         let step1 = make_step("Step 1", None);
@@ -675,4 +867,43 @@ mod tests {
         assert!(is_valid_time("14:30:00"));
         assert!(!is_valid_time("2:30 PM"));
     }
+
+    #[test]
+    fn test_rows_are_validated_against_sub_fields() {
+        let group = make_field("members", "repeater", true);
+        let mut name = make_field("name", "text", true);
+        name.parent_id = Some(group.id);
+        let mut email = make_field("email", "email", false);
+        email.parent_id = Some(group.id);
+
+        let row = |name: &str, email: &str| {
+            BTreeMap::from([
+                ("name".to_string(), FieldValue::from(name)),
+                ("email".to_string(), FieldValue::from(email)),
+            ])
+        };
+        let value = FieldValue::Rows(vec![row("Ada", "ada@example.com"), row("", "nope")]);
+
+        let mut data = HashMap::new();
+        data.insert("members".to_string(), value);
+        let errors = validate_submission(&[group, name, email], &data);
+        assert!(errors.get("members").is_none());
+        assert!(errors.get("members[0][name]").is_none());
+        assert!(errors.get("members[1][name]").is_some());
+        assert!(errors.get("members[1][email]").is_some());
+        assert!(errors.get("name").is_none());
+    }
+
+    #[test]
+    fn test_location_validation() {
+        let field = make_field("spot", "location", true);
+        let errors = |value: FieldValue| validate_field(&field, Some(&value));
+
+        assert!(errors(GeoLocation::new(51.5, -0.12).accuracy(20.0).into()).is_empty());
+        assert!(errors(GeoLocation::new(-90.0, 180.0).into()).is_empty());
+        assert_eq!(errors(GeoLocation::new(91.0, 0.0).into()).len(), 1);
+        assert_eq!(errors(GeoLocation::new(0.0, -180.5).into()).len(), 1);
+        assert_eq!(errors(GeoLocation::new(0.0, 0.0).accuracy(-1.0).into()).len(), 1);
+        assert_eq!(errors(FieldValue::from("51.5, -0.12")).len(), 1);
+    }
 }
@@ -0,0 +1,247 @@
+//! API versioning for the JSON contract.
+//!
+//! Routes are mounted twice by [`AnyFormRouter`](crate::AnyFormRouter):
+//!
+//! - `/api/v1/...` — pinned to [`ApiVersion::V1`]
+//! - `/api/...` — the unversioned (legacy) surface, which negotiates a
+//!   version from request headers, defaults to the oldest supported version,
+//!   and carries `Deprecation`/`Link` headers pointing at the versioned path
+//!
+//! Clients negotiate with either `AnyForm-Version: 1` or
+//! `Accept: application/vnd.anyform.v1+json`. Every response reports the
+//! version it was served with in the `AnyForm-Version` header.
+//!
+//! When the output shape changes, a new variant is added and
+//! [`ApiVersion::form_json`] adapts the current [`FormJson`] back to each older
+//! wire format, so deployed SPAs keep working.
+
+use std::str::FromStr;
+
+use axum::extract::FromRequestParts;
+#[cfg(feature = "router")]
+use axum::{
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+#[cfg(feature = "router")]
+use http::HeaderValue;
+use http::{header, request::Parts, HeaderMap, HeaderName};
+use serde::Serialize;
+
+use crate::error::FormError;
+use crate::render::{FormJson, StepJson};
+
+/// Request/response header carrying the API version.
+pub const VERSION_HEADER: HeaderName = HeaderName::from_static("anyform-version");
+
+/// Media type prefix for `Accept`-based negotiation (`...v1+json`).
+const VENDOR_MEDIA_TYPE: &str = "application/vnd.anyform.";
+
+/// A version of the public JSON contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize)]
+pub enum ApiVersion {
+    /// The original contract.
+    #[default]
+    V1,
+}
+
+impl ApiVersion {
+    /// The newest supported version.
+    pub const LATEST: Self = Self::V1;
+
+    /// All supported versions, oldest first.
+    pub const ALL: &'static [Self] = &[Self::V1];
+
+    /// Returns the version number as used in headers (`"1"`).
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V1 => "1",
+        }
+    }
+
+    /// Returns the route prefix for this version (`/api/v1`).
+    #[must_use]
+    pub fn path_prefix(self) -> &'static str {
+        match self {
+            Self::V1 => "/api/v1",
+        }
+    }
+
+    /// Converts a rendered form into this version's wire format.
+    #[must_use]
+    pub fn form_json(self, form: FormJson) -> serde_json::Value {
+        match self {
+            Self::V1 => serde_json::to_value(form).unwrap_or_default(),
+        }
+    }
+
+    /// Converts a rendered step into this version's wire format.
+    #[must_use]
+    pub fn step_json(self, step: StepJson) -> serde_json::Value {
+        match self {
+            Self::V1 => serde_json::to_value(step).unwrap_or_default(),
+        }
+    }
+
+    /// Negotiates a version from request headers.
+    ///
+    /// Returns `Ok(None)` when the client expressed no preference.
+    pub fn negotiate(headers: &HeaderMap) -> Result<Option<Self>, FormError> {
+        if let Some(value) = headers.get(&VERSION_HEADER) {
+            let value = value.to_str().unwrap_or_default();
+            return value.parse().map(Some);
+        }
+
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        for media_type in accept.split(',') {
+            let media_type = media_type.split(';').next().unwrap_or_default().trim();
+            if let Some(rest) = media_type.strip_prefix(VENDOR_MEDIA_TYPE) {
+                let version = rest.split('+').next().unwrap_or_default();
+                return version.parse().map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl FromStr for ApiVersion {
+    type Err = FormError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().trim_start_matches(['v', 'V']) {
+            "1" => Ok(Self::V1),
+            other => Err(FormError::UnsupportedApiVersion(other.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}", self.as_str())
+    }
+}
+
+/// Extracts the version the request is being served with.
+///
+/// Set by the router's version middleware; falls back to header negotiation
+/// (and then [`ApiVersion::V1`]) when handlers are mounted manually.
+impl<S> FromRequestParts<S> for ApiVersion
+where
+    S: Send + Sync,
+{
+    type Rejection = FormError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(version) = parts.extensions.get::<Self>() {
+            return Ok(*version);
+        }
+        Ok(Self::negotiate(&parts.headers)?.unwrap_or_default())
+    }
+}
+
+/// Middleware pinning requests to `version` (used under `/api/v1`).
+#[cfg(feature = "router")]
+pub(crate) async fn pin_version(version: ApiVersion, mut req: Request, next: Next) -> Response {
+    req.extensions_mut().insert(version);
+    let mut response = next.run(req).await;
+    response
+        .headers_mut()
+        .insert(VERSION_HEADER, HeaderValue::from_static(version.as_str()));
+    response
+}
+
+/// Configuration for the deprecation headers on unversioned routes.
+#[cfg(feature = "router")]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Deprecation {
+    /// Optional `Sunset` header value (an HTTP-date).
+    pub sunset: Option<String>,
+}
+
+/// Middleware for the unversioned routes: negotiates the version and marks
+/// responses as deprecated with a link to the versioned successor.
+#[cfg(feature = "router")]
+pub(crate) async fn negotiate_unversioned(
+    deprecation: Deprecation,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let version = match ApiVersion::negotiate(req.headers()) {
+        Ok(version) => version.unwrap_or_default(),
+        Err(err) => return err.into_response(),
+    };
+    let successor = format!(
+        "<{}{}>; rel=\"successor-version\"",
+        version.path_prefix(),
+        req.uri().path()
+    );
+
+    req.extensions_mut().insert(version);
+    let mut response = next.run(req).await;
+
+    let headers = response.headers_mut();
+    headers.insert(VERSION_HEADER, HeaderValue::from_static(version.as_str()));
+    headers.insert(
+        HeaderName::from_static("deprecation"),
+        HeaderValue::from_static("true"),
+    );
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.append(header::LINK, link);
+    }
+    if let Some(sunset) = deprecation.sunset.as_deref() {
+        if let Ok(value) = HeaderValue::from_str(sunset) {
+            headers.insert(HeaderName::from_static("sunset"), value);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_versions() {
+        assert_eq!("1".parse::<ApiVersion>().unwrap(), ApiVersion::V1);
+        assert_eq!("v1".parse::<ApiVersion>().unwrap(), ApiVersion::V1);
+        assert!("2".parse::<ApiVersion>().is_err());
+    }
+
+    #[test]
+    fn test_negotiate_from_version_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(VERSION_HEADER, HeaderValue::from_static("1"));
+        assert_eq!(ApiVersion::negotiate(&headers).unwrap(), Some(ApiVersion::V1));
+    }
+
+    #[test]
+    fn test_negotiate_from_accept_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("text/html, application/vnd.anyform.v1+json;q=0.9"),
+        );
+        assert_eq!(ApiVersion::negotiate(&headers).unwrap(), Some(ApiVersion::V1));
+    }
+
+    #[test]
+    fn test_negotiate_without_preference() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_static("application/json"));
+        assert_eq!(ApiVersion::negotiate(&headers).unwrap(), None);
+    }
+
+    #[test]
+    fn test_negotiate_unsupported_version() {
+        let mut headers = HeaderMap::new();
+        headers.insert(VERSION_HEADER, HeaderValue::from_static("7"));
+        let err = ApiVersion::negotiate(&headers).unwrap_err();
+        assert_eq!(err.error_code(), "UNSUPPORTED_API_VERSION");
+    }
+}
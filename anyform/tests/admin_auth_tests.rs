@@ -0,0 +1,141 @@
+//! Tests for admin route authentication.
+
+mod common;
+
+use anyform::admin_auth::{ApiKeyAuth, BearerTokenAuth};
+use anyform::{AnyFormRouterBuilder, FormBuilder};
+use axum::body::Body;
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::{Request, StatusCode};
+
+const TOKEN: &str = "admin-test-token";
+const VIEWER_TOKEN: &str = "viewer-test-token";
+
+const AS_ADMIN: &[(&str, &str)] = &[("Authorization", "Bearer admin-test-token")];
+const AS_VIEWER: &[(&str, &str)] = &[("Authorization", "Bearer viewer-test-token")];
+
+async fn app_with(builder: AnyFormRouterBuilder) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = builder.database(test_db.db.clone()).enable_admin(true).build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), contact_form()).await;
+    app
+}
+
+async fn bearer_app() -> TestApp {
+    let auth = BearerTokenAuth::new(TOKEN).read_only_token(VIEWER_TOKEN);
+    app_with(anyform::AnyFormRouter::builder().admin_auth(auth)).await
+}
+
+async fn send(
+    app: &TestApp,
+    method: &str,
+    uri: &str,
+    headers: &[(&str, &str)],
+) -> common::app::TestResponse {
+    let mut request = Request::builder().method(method).uri(uri);
+    for &(name, value) in headers {
+        request = request.header(name, value);
+    }
+    app.send_raw(request.body(Body::empty()).unwrap()).await
+}
+
+// ============================================================================
+// Bearer tokens
+// ============================================================================
+
+#[tokio::test]
+async fn test_missing_or_wrong_token_is_unauthorized() {
+    let app = bearer_app().await;
+
+    for headers in [&[][..], &[("Authorization", "Bearer wrong-token")]] {
+        let response = send(&app, "GET", "/api/v1/admin/forms", headers).await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+        response.assert_api_error("UNAUTHORIZED");
+        assert_eq!(response.headers["www-authenticate"], "Bearer realm=\"anyform\"");
+    }
+
+    let response = send(&app, "GET", "/api/admin/forms", &[]).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_valid_token_is_let_through() {
+    let app = bearer_app().await;
+
+    let response = send(&app, "GET", "/api/v1/admin/forms", AS_ADMIN).await;
+    response.assert_status(StatusCode::OK);
+    response.assert_body_contains("test-contact");
+}
+
+#[tokio::test]
+async fn test_read_only_token_cannot_write() {
+    let app = bearer_app().await;
+    let form_id = FormBuilder::find_by_slug(app.db(), "test-contact")
+        .await
+        .unwrap()
+        .unwrap()
+        .id;
+
+    let uri = format!("/api/v1/admin/forms/{form_id}");
+    send(&app, "GET", &uri, AS_VIEWER)
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = send(&app, "DELETE", &uri, AS_VIEWER).await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    response.assert_api_error("FORBIDDEN");
+    assert!(!response.headers.contains_key("www-authenticate"));
+
+    send(&app, "DELETE", &uri, AS_ADMIN)
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_public_routes_stay_open() {
+    let app = bearer_app().await;
+
+    app.get("/api/v1/forms/test-contact/json").await.assert_status(StatusCode::OK);
+    app.get("/health").await.assert_status(StatusCode::OK);
+}
+
+// ============================================================================
+// API keys
+// ============================================================================
+
+#[tokio::test]
+async fn test_api_key_header() {
+    let auth = ApiKeyAuth::new("key-1").key("key-2");
+    let app = app_with(anyform::AnyFormRouter::builder().admin_auth(auth)).await;
+
+    for key in ["key-1", "key-2"] {
+        send(&app, "GET", "/api/v1/admin/forms", &[("X-Api-Key", key)])
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    let headers = [("Authorization", "Bearer key-1")];
+    let response = send(&app, "GET", "/api/v1/admin/forms", &headers).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(!response.headers.contains_key("www-authenticate"));
+}
+
+// ============================================================================
+// Preflight
+// ============================================================================
+
+#[tokio::test]
+async fn test_unprotected_admin_routes_are_a_warning() {
+    let db = TestDb::new().await;
+    let builder = anyform::AnyFormRouter::builder()
+        .database(db.conn().clone())
+        .enable_admin(true);
+
+    let report = builder.preflight().await.unwrap();
+    assert!(report.warnings().any(|i| i.code == "ADMIN_UNPROTECTED"));
+    assert!(report.is_ok());
+
+    let report = builder.admin_auth(BearerTokenAuth::new(TOKEN)).preflight().await.unwrap();
+    assert!(!report.warnings().any(|i| i.code == "ADMIN_UNPROTECTED"));
+}
@@ -0,0 +1,149 @@
+//! Tests for tagging free-text answers with sentiment and themes.
+
+mod common;
+
+use anyform::entities::submission::SubmissionMetadata;
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, FieldClassification, FormSettings,
+    KeywordAnalyzer, Sentiment, SubmissionEntity,
+};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+const URI: &str = "/api/forms/feedback";
+
+fn feedback_form(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Feedback", "feedback")
+        .settings(settings)
+        .step(CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("name", "Name", "text"),
+            CreateFieldInput::new("comment", "Comment", "textarea"),
+            CreateFieldInput::new("notes", "Notes", "textarea")
+                .classification(FieldClassification::NoAnalytics),
+        ]))
+}
+
+async fn app_with_analyzer() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .text_analyzer(
+            KeywordAnalyzer::new()
+                .tag("venue", ["venue", "room*"])
+                .tag("pricing", ["price*", "expensive"]),
+        )
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+async fn give_feedback(app: &TestApp, answers: Value) -> Uuid {
+    let response = app.post_json(URI, &answers).await;
+    response.assert_status(StatusCode::CREATED);
+    let id = response.json::<Value>()["data"]["submission_id"].as_str().unwrap().to_string();
+    id.parse().unwrap()
+}
+
+async fn stored_metadata(app: &TestApp, id: Uuid) -> Option<Value> {
+    SubmissionEntity::find_by_id(id)
+        .one(app.db())
+        .await
+        .unwrap()
+        .unwrap()
+        .metadata
+}
+
+#[tokio::test]
+async fn test_free_text_answers_are_analyzed() {
+    let app = app_with_analyzer().await;
+    create_test_form(app.db(), feedback_form(FormSettings::new())).await;
+
+    let id = give_feedback(
+        &app,
+        json!({ "name": "Ada", "comment": "Lovely venue but far too expensive", "notes": "vip" }),
+    )
+    .await;
+    let stored = stored_metadata(&app, id).await.unwrap();
+    let metadata: SubmissionMetadata = serde_json::from_value(stored).unwrap();
+    assert_eq!(metadata.text_analysis.len(), 1);
+    let comment = &metadata.text_analysis["comment"];
+    assert_eq!(comment.tags, vec!["venue", "pricing"]);
+    assert_eq!(comment.sentiment_label(), Some(Sentiment::Neutral));
+
+    // Blank answers aren't analyzed
+    let id = give_feedback(&app, json!({ "name": "Grace", "comment": " " })).await;
+    let metadata = stored_metadata(&app, id).await.unwrap_or_default();
+    assert!(metadata.get("text_analysis").is_none());
+}
+
+#[tokio::test]
+async fn test_analytics_sum_up_sentiment_and_tags() {
+    let app = app_with_analyzer().await;
+    let form = create_test_form(app.db(), feedback_form(FormSettings::new())).await;
+    give_feedback(&app, json!({ "comment": "Great talks, lovely rooms" })).await;
+    give_feedback(&app, json!({ "comment": "The venue was cold and the prices awful" })).await;
+    give_feedback(&app, json!({ "comment": "It was on a Tuesday", "notes": "expensive" })).await;
+    give_feedback(&app, json!({ "name": "Edsger" })).await;
+
+    let response = app.get(&format!("/api/admin/forms/{}/analytics", form.id)).await;
+    response.assert_status(StatusCode::OK);
+    let json: Value = response.json();
+    let fields = json["data"]["fields"].as_array().unwrap();
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0]["name"], "comment");
+    assert_eq!(fields[0]["responses"], 3);
+    assert_eq!(
+        fields[0]["text"],
+        json!({
+            "positive": 1,
+            "neutral": 1,
+            "negative": 1,
+            "average_sentiment": 0.0,
+            "tags": [{ "tag": "venue", "count": 2 }, { "tag": "pricing", "count": 1 }],
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_anonymous_forms_keep_only_the_analysis() {
+    let app = app_with_analyzer().await;
+    create_test_form(app.db(), feedback_form(FormSettings::new().anonymous(true))).await;
+
+    let id = give_feedback(&app, json!({ "comment": "Loved the venue" })).await;
+    let metadata = stored_metadata(&app, id).await.unwrap();
+    assert_eq!(
+        metadata,
+        json!({ "text_analysis": { "comment": { "sentiment": 1.0, "tags": ["venue"] } } })
+    );
+
+    let id = give_feedback(&app, json!({ "name": "Ada" })).await;
+    assert!(stored_metadata(&app, id).await.is_none());
+}
+
+#[tokio::test]
+async fn test_completed_submissions_are_analyzed() {
+    let app = app_with_analyzer().await;
+    create_test_form(app.db(), feedback_form(FormSettings::new())).await;
+
+    let response = app.post_json(&format!("{URI}/submissions/start"), &json!({})).await;
+    response.assert_status(StatusCode::CREATED);
+    let id = response.json::<Value>()["data"]["submission_id"].as_str().unwrap().to_string();
+    app.patch_json(
+        &format!("{URI}/submissions/{id}/step"),
+        &json!({ "data": { "comment": "Terrible room" } }),
+    )
+    .await
+    .assert_status(StatusCode::OK);
+    app.post_json(&format!("{URI}/submissions/{id}/complete"), &json!({}))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let metadata = stored_metadata(&app, id.parse().unwrap()).await.unwrap();
+    assert_eq!(
+        metadata["text_analysis"]["comment"],
+        json!({ "sentiment": -1.0, "tags": ["venue"] })
+    );
+}
@@ -0,0 +1,153 @@
+//! Tests for submission analytics.
+
+mod common;
+
+use anyform::services::Analytics;
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FieldClassification,
+    FormSettings, ResultsPrivacy,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use uuid::Uuid;
+
+const URI: &str = "/api/forms/survey";
+
+fn survey_form() -> CreateFormInput {
+    CreateFormInput::new("Survey", "survey").step(CreateStepInput::new("Main").fields(vec![
+        CreateFieldInput::new("plan", "Plan", "select").options(vec![
+            CreateOptionInput::new("Free", "free"),
+            CreateOptionInput::new("Pro", "pro"),
+            CreateOptionInput::new("Team", "team"),
+        ]),
+        CreateFieldInput::new("seats", "Seats", "number"),
+        CreateFieldInput::new("recommend", "Recommend", "nps"),
+        CreateFieldInput::new("salary", "Salary", "number")
+            .classification(FieldClassification::NoAnalytics),
+        CreateFieldInput::new("comment", "Comment", "textarea"),
+    ]))
+}
+
+async fn answer(app: &TestApp, answers: serde_json::Value) {
+    app.post_json(URI, &answers).await.assert_status(StatusCode::CREATED);
+}
+
+fn field<'a>(json: &'a serde_json::Value, name: &str) -> &'a serde_json::Value {
+    json["data"]["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["name"] == name)
+        .unwrap()
+}
+
+// ============================================================================
+// Aggregation
+// ============================================================================
+
+#[tokio::test]
+async fn test_per_field_aggregates() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), survey_form()).await;
+    answer(&app, serde_json::json!({ "plan": "pro", "seats": 5, "recommend": 10 })).await;
+    answer(&app, serde_json::json!({ "plan": "pro", "seats": 15, "recommend": 8 })).await;
+    answer(&app, serde_json::json!({ "plan": "legacy", "recommend": 3, "salary": 1 })).await;
+    // HTML forms submit numbers as text
+    app.post_form(URI, &[("plan", "free"), ("seats", "10"), ("recommend", "9")])
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = app.get(&format!("/api/admin/forms/{}/analytics", form.id)).await;
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["respondents"], 4);
+
+    let plan = field(&json, "plan");
+    assert_eq!(plan["responses"], 4);
+    let options: Vec<(&str, u64)> = plan["options"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| (o["value"].as_str().unwrap(), o["count"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(options, vec![("free", 1), ("pro", 2), ("team", 0), ("legacy", 1)]);
+    assert_eq!(plan["options"][1]["label"], "Pro");
+
+    let seats = field(&json, "seats");
+    assert_eq!(seats["responses"], 3);
+    assert_eq!(seats["numbers"], serde_json::json!({ "min": 5.0, "avg": 10.0, "max": 15.0 }));
+    assert!(seats.get("nps").is_none());
+
+    let nps = &field(&json, "recommend")["nps"];
+    assert_eq!(nps["detractors"], 1);
+    assert_eq!(nps["passives"], 1);
+    assert_eq!(nps["promoters"], 2);
+    assert_eq!(nps["score"], 25.0);
+
+    // Fields excluded from analytics, and unanalyzed free text, aren't aggregated
+    let names: Vec<&str> = json["data"]["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["plan", "seats", "recommend"]);
+}
+
+#[tokio::test]
+async fn test_counts_and_completion_rate() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), common::quiz_form()).await;
+    let empty = Analytics::form(app.db(), &form).await.unwrap();
+    assert_eq!(empty.submissions, 0);
+    assert_eq!(empty.completion_rate, None);
+    assert_eq!(empty.average_score, None);
+    assert!(empty.fields.iter().all(|f| f.responses == 0));
+
+    let uri = format!("/api/forms/{}", form.slug);
+    for answers in [
+        serde_json::json!({ "q1": "4", "q2": "paris" }),
+        serde_json::json!({ "q1": "3", "q2": "paris" }),
+    ] {
+        app.post_json(&uri, &answers).await.assert_status(StatusCode::CREATED);
+    }
+
+    let analytics = Analytics::form(app.db(), &form).await.unwrap();
+    assert_eq!(analytics.submissions, 2);
+    assert_eq!(analytics.completed, 2);
+    assert_eq!(analytics.completion_rate, Some(1.0));
+    assert_eq!(analytics.average_score, Some(15.0));
+}
+
+// ============================================================================
+// Privacy
+// ============================================================================
+
+#[tokio::test]
+async fn test_too_few_respondents_are_suppressed() {
+    let app = TestApp::with_admin().await;
+    let privacy = ResultsPrivacy::new().min_respondents(3);
+    let form = create_test_form(
+        app.db(),
+        survey_form().settings(FormSettings::new().results_privacy(privacy)),
+    )
+    .await;
+    answer(&app, serde_json::json!({ "plan": "pro", "recommend": 10 })).await;
+
+    let analytics = Analytics::form(app.db(), &form).await.unwrap();
+    assert!(analytics.suppressed);
+    assert_eq!(analytics.submissions, 1);
+    assert!(analytics.fields.is_empty());
+}
+
+// ============================================================================
+// Route
+// ============================================================================
+
+#[tokio::test]
+async fn test_analytics_of_unknown_form() {
+    let app = TestApp::with_admin().await;
+    app.get(&format!("/api/admin/forms/{}/analytics", Uuid::new_v4()))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,234 @@
+//! Tests for anonymous and identified submission modes.
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, FormError, FormSettings,
+    Respondent, RespondentResolver, SubmissionEntity,
+};
+use axum::body::Body;
+use common::{create_test_form, TestApp, TestDb};
+use http::{HeaderMap, Request, StatusCode};
+use sea_orm::EntityTrait;
+use uuid::Uuid;
+
+fn survey_form(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Survey", "survey")
+        .settings(settings)
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("answer", "Answer", "text")))
+}
+
+/// Resolves `X-User` headers and counts how often it was asked.
+#[derive(Clone, Default)]
+struct HeaderResolver(Arc<AtomicUsize>);
+
+#[async_trait::async_trait]
+impl RespondentResolver for HeaderResolver {
+    async fn resolve(&self, headers: &HeaderMap) -> Option<Respondent> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        let user = headers.get("x-user")?.to_str().ok()?;
+        Some(Respondent::new(user).email(format!("{user}@example.com")))
+    }
+}
+
+async fn app_with_resolver(resolver: HeaderResolver) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .respondent_resolver(resolver)
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+async fn submit(app: &TestApp, user: Option<&str>) -> common::app::TestResponse {
+    let mut request = Request::builder()
+        .uri("/api/forms/survey")
+        .method("POST")
+        .header("Content-Type", "application/json")
+        .header("X-Forwarded-For", "203.0.113.7")
+        .header("User-Agent", "test-agent");
+    if let Some(user) = user {
+        request = request.header("X-User", user);
+    }
+    app.send_raw(request.body(Body::from(r#"{"answer":"ok"}"#)).unwrap())
+        .await
+}
+
+async fn send_as(app: &TestApp, method: &str, uri: &str, user: &str) -> common::app::TestResponse {
+    let request = Request::builder()
+        .uri(uri)
+        .method(method)
+        .header("Content-Type", "application/json")
+        .header("X-User", user)
+        .body(Body::from("{}"))
+        .unwrap();
+    app.send_raw(request).await
+}
+
+async fn stored_metadata(app: &TestApp, response: &common::app::TestResponse) -> Option<serde_json::Value> {
+    let json: serde_json::Value = response.json();
+    let id = Uuid::parse_str(json["data"]["submission_id"].as_str().unwrap()).unwrap();
+    SubmissionEntity::find_by_id(id)
+        .one(app.db())
+        .await
+        .unwrap()
+        .unwrap()
+        .metadata
+}
+
+#[tokio::test]
+async fn test_default_mode_stores_request_metadata() {
+    let app = app_with_resolver(HeaderResolver::default()).await;
+    create_test_form(app.db(), survey_form(FormSettings::new())).await;
+
+    let response = submit(&app, Some("ada")).await;
+    response.assert_status(StatusCode::CREATED);
+
+    let metadata = stored_metadata(&app, &response).await.unwrap();
+    assert_eq!(metadata["ip"], "203.0.113.7");
+    assert_eq!(metadata["user_agent"], "test-agent");
+    assert_eq!(metadata["user_id"], "ada");
+    assert_eq!(metadata["email"], "ada@example.com");
+}
+
+#[tokio::test]
+async fn test_anonymous_mode_stores_no_metadata() {
+    let resolver = HeaderResolver::default();
+    let app = app_with_resolver(resolver.clone()).await;
+    create_test_form(app.db(), survey_form(FormSettings::new().anonymous(true))).await;
+
+    let response = submit(&app, Some("ada")).await;
+    response.assert_status(StatusCode::CREATED);
+
+    assert!(stored_metadata(&app, &response).await.is_none());
+    assert_eq!(resolver.0.load(Ordering::SeqCst), 0, "resolver must not be consulted");
+}
+
+#[tokio::test]
+async fn test_anonymous_form_shows_notice() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        survey_form(FormSettings::new().anonymous(true).anonymous_notice("Nobody will know.")),
+    )
+    .await;
+
+    app.get("/api/forms/survey")
+        .await
+        .assert_body_contains("<p class=\"af-anonymous-notice\" data-af-anonymous>Nobody will know.</p>");
+
+    let json: serde_json::Value = app.get("/api/forms/survey/json").await.json();
+    assert_eq!(json["settings"]["anonymous"], true);
+}
+
+#[tokio::test]
+async fn test_identified_mode_requires_respondent() {
+    let app = app_with_resolver(HeaderResolver::default()).await;
+    create_test_form(app.db(), survey_form(FormSettings::new().identified(true))).await;
+
+    let response = submit(&app, None).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    response.assert_api_error("RESPONDENT_REQUIRED");
+
+    let response = submit(&app, Some("grace")).await;
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(stored_metadata(&app, &response).await.unwrap()["user_id"], "grace");
+}
+
+#[tokio::test]
+async fn test_identified_mode_without_resolver_rejects() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), survey_form(FormSettings::new().identified(true))).await;
+
+    submit(&app, Some("grace"))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_form_cannot_be_anonymous_and_identified() {
+    let db = TestDb::new().await;
+    let result = FormBuilder::create(
+        &db.db,
+        survey_form(FormSettings::new().anonymous(true).identified(true)),
+    )
+    .await;
+
+    assert!(matches!(result, Err(FormError::InvalidData(_))));
+
+    let result = FormBuilder::create(
+        &db.db,
+        survey_form(FormSettings::new().anonymous(true).one_per_respondent(true)),
+    )
+    .await;
+    assert!(matches!(result, Err(FormError::InvalidData(_))));
+}
+
+// ============================================================================
+// Respondent IDs
+// ============================================================================
+
+#[tokio::test]
+async fn test_admin_filters_submissions_by_respondent() {
+    let app = app_with_resolver(HeaderResolver::default()).await;
+    let form = create_test_form(app.db(), survey_form(FormSettings::new())).await;
+    for user in [Some("ada"), Some("grace"), Some("ada"), None] {
+        submit(&app, user).await.assert_status(StatusCode::CREATED);
+    }
+
+    let url = format!("/api/v1/admin/forms/{}/submissions", form.id);
+    let json: serde_json::Value = app.get(&format!("{url}?respondent=ada")).await.json();
+    let submissions = json["data"]["submissions"].as_array().unwrap();
+    assert_eq!(submissions.len(), 2);
+    assert!(submissions.iter().all(|s| s["respondent_id"] == "ada"));
+
+    let json: serde_json::Value = app.get(&url).await.json();
+    assert_eq!(json["data"]["count"], 4);
+    let json: serde_json::Value = app.get(&format!("{url}?respondent=lin")).await.json();
+    assert_eq!(json["data"]["count"], 0);
+}
+
+#[tokio::test]
+async fn test_one_submission_per_respondent() {
+    let app = app_with_resolver(HeaderResolver::default()).await;
+    let form = create_test_form(
+        app.db(),
+        survey_form(FormSettings::new().one_per_respondent(true)),
+    )
+    .await;
+
+    submit(&app, None).await.assert_api_error("RESPONDENT_REQUIRED");
+    let response = submit(&app, Some("ada")).await;
+    response.assert_status(StatusCode::CREATED);
+    let first: serde_json::Value = response.json();
+
+    let response = submit(&app, Some("ada")).await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("ALREADY_RESPONDED");
+    submit(&app, Some("grace")).await.assert_status(StatusCode::CREATED);
+
+    // The form page tells a respondent who has answered instead of showing the form
+    send_as(&app, "GET", "/api/forms/survey", "ada")
+        .await
+        .assert_body_contains("data-af-responded");
+    let html = send_as(&app, "GET", "/api/forms/survey", "lin").await.text();
+    assert!(html.contains("<form"));
+
+    // Nor can they start answering step by step
+    send_as(&app, "POST", "/api/forms/survey/submissions/start", "ada")
+        .await
+        .assert_api_error("ALREADY_RESPONDED");
+
+    // Deleting the response lets them answer again
+    let url = format!(
+        "/api/v1/admin/forms/{}/submissions/{}",
+        form.id,
+        first["data"]["submission_id"].as_str().unwrap()
+    );
+    app.delete(&url).await.assert_success();
+    submit(&app, Some("ada")).await.assert_status(StatusCode::CREATED);
+}
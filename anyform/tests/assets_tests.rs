@@ -0,0 +1,152 @@
+//! Integration tests for the WASM client asset routes.
+
+mod common;
+
+use anyform::assets::ClientAssets;
+use common::{create_test_form, multi_step_form, TestApp};
+use http::StatusCode;
+
+// ============================================================================
+// Embedded Bundle
+// ============================================================================
+
+#[tokio::test]
+async fn test_bootstrap_imports_versioned_files() {
+    let app = TestApp::new().await;
+    let version = ClientAssets::embedded().version();
+
+    let response = app.get("/forms/assets/af-client.js").await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("text/javascript");
+    response.assert_body_contains(&format!("./anyform_client.{version}.js"));
+    response.assert_body_contains(&format!("./anyform_client_bg.{version}.wasm"));
+    response.assert_body_contains("hydrate_all()");
+    assert_eq!(response.headers["cache-control"], "no-cache");
+}
+
+#[tokio::test]
+async fn test_versioned_wasm_is_immutable() {
+    let app = TestApp::new().await;
+    let version = ClientAssets::embedded().version();
+
+    let response = app
+        .get(&format!("/forms/assets/anyform_client_bg.{version}.wasm"))
+        .await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("application/wasm");
+    assert!(response.body.starts_with(b"\0asm"));
+    assert!(response.headers["cache-control"]
+        .to_str()
+        .unwrap()
+        .contains("immutable"));
+}
+
+#[tokio::test]
+async fn test_versioned_glue_served() {
+    let app = TestApp::new().await;
+    let version = ClientAssets::embedded().version();
+
+    let response = app
+        .get(&format!("/forms/assets/anyform_client.{version}.js"))
+        .await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_body_contains("export function hydrate_all");
+}
+
+#[tokio::test]
+async fn test_stale_version_returns_404() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/forms/assets/anyform_client_bg.0.0.1.wasm").await;
+
+    response.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_rendered_html_loads_served_client() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), multi_step_form()).await;
+
+    let response = app.get(&format!("/api/forms/{}", form.slug)).await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_body_contains("src=\"/forms/assets/af-client.js?v=");
+}
+
+// ============================================================================
+// Directory Source
+// ============================================================================
+
+#[tokio::test]
+async fn test_directory_assets_use_package_version() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("package.json"), r#"{"version": "9.9.9"}"#).unwrap();
+    std::fs::write(dir.path().join("anyform_client.js"), "// glue").unwrap();
+    std::fs::write(dir.path().join("anyform_client_bg.wasm"), b"\0asm").unwrap();
+
+    let test_db = common::TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .assets(ClientAssets::directory(dir.path()))
+        .build();
+    let app = TestApp::from_router(test_db, router);
+
+    app.get("/forms/assets/anyform_client.9.9.9.js")
+        .await
+        .assert_status(StatusCode::OK)
+        .assert_body_contains("// glue");
+    app.get("/forms/assets/anyform_client_bg.9.9.9.wasm")
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+// ============================================================================
+// Integrity & Version Pinning
+// ============================================================================
+
+#[tokio::test]
+async fn test_loader_tag_pins_version_and_integrity() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), multi_step_form()).await;
+    let assets = ClientAssets::embedded();
+
+    let response = app.get(&format!("/api/forms/{}", form.slug)).await;
+
+    response.assert_body_contains(&format!(
+        "src=\"/forms/assets/af-client.js?v={}\" integrity=\"{}\" crossorigin=\"anonymous\"",
+        assets.version(),
+        assets.integrity()
+    ));
+}
+
+#[tokio::test]
+async fn test_integrity_matches_served_bootstrap() {
+    use base64::Engine;
+    use sha2::Digest;
+
+    let app = TestApp::new().await;
+    let response = app.get("/forms/assets/af-client.js").await;
+
+    let digest = sha2::Sha384::digest(&response.body);
+    let expected = format!(
+        "sha384-{}",
+        base64::engine::general_purpose::STANDARD.encode(digest)
+    );
+    assert_eq!(ClientAssets::embedded().integrity(), expected);
+}
+
+#[test]
+fn test_client_version_compatibility() {
+    assert!(anyform::render::is_compatible_client_version(&format!(
+        "{}.3",
+        anyform::render::SCHEMA_VERSION
+    )));
+    assert!(!anyform::render::is_compatible_client_version("0.0.1"));
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("package.json"), r#"{"version": "0.0.1"}"#).unwrap();
+    assert!(!ClientAssets::directory(dir.path()).check_compatibility());
+}
@@ -0,0 +1,289 @@
+//! Tests for the size and shape limits on submission bodies, with a corpus
+//! of pathological payloads and seeded mutations of a valid one.
+
+mod common;
+
+use anyform::BodyLimits;
+use axum::body::Body;
+use common::{contact_form, create_test_form, sample_submission_data, TestApp, TestDb};
+use http::{Request, StatusCode};
+
+const URI: &str = "/api/v1/forms/test-contact";
+
+async fn app_with(limits: BodyLimits) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .body_limits(limits)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), contact_form()).await;
+    app
+}
+
+async fn post(
+    app: &TestApp,
+    content_type: &str,
+    body: impl Into<Body>,
+) -> common::app::TestResponse {
+    let request = Request::builder()
+        .method("POST")
+        .uri(URI)
+        .header("Content-Type", content_type)
+        .body(body.into())
+        .unwrap();
+    app.send_raw(request).await
+}
+
+async fn post_json(app: &TestApp, body: impl Into<Body>) -> common::app::TestResponse {
+    post(app, "application/json", body).await
+}
+
+/// A valid submission with a raw JSON value under an extra `team` key.
+fn with_team(value: &str) -> String {
+    format!(
+        r#"{{"name": "Ada", "email": "ada@example.com", "message": "Hello there", "team": {value}}}"#
+    )
+}
+
+// ============================================================================
+// Limits
+// ============================================================================
+
+#[tokio::test]
+async fn test_default_limits_accept_ordinary_submissions() {
+    let app = app_with(BodyLimits::new()).await;
+
+    post_json(&app, sample_submission_data().to_string())
+        .await
+        .assert_status(StatusCode::CREATED);
+    post(
+        &app,
+        "application/x-www-form-urlencoded",
+        "name=Ada&email=ada%40example.com&message=Hi+there",
+    )
+    .await
+    .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_oversized_body_is_413() {
+    let app = app_with(BodyLimits::new().max_bytes(128)).await;
+    let body = with_team(&format!("\"{}\"", "a".repeat(200)));
+
+    let response = post_json(&app, body.clone()).await;
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    response.assert_api_error("PAYLOAD_TOO_LARGE");
+
+    // A declared length over the limit is turned away unread
+    let request = Request::builder()
+        .method("POST")
+        .uri(URI)
+        .header("Content-Type", "application/json")
+        .header("Content-Length", "1000000")
+        .body(Body::from(body))
+        .unwrap();
+    app.send_raw(request)
+        .await
+        .assert_api_error("PAYLOAD_TOO_LARGE");
+
+    let response = post(
+        &app,
+        "application/x-www-form-urlencoded",
+        format!("name={}", "a".repeat(200)),
+    )
+    .await;
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_long_string_is_413() {
+    let app = app_with(BodyLimits::new().max_string_len(16)).await;
+
+    let response = post_json(&app, with_team(&format!("\"{}\"", "a".repeat(17)))).await;
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    response.assert_body_contains("a string exceeds 16 bytes");
+
+    let response = post(
+        &app,
+        "application/x-www-form-urlencoded",
+        "message=a+rather+long+message",
+    )
+    .await;
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_deep_nesting_is_422() {
+    let app = app_with(BodyLimits::new().max_depth(4)).await;
+
+    // A repeatable group row holding an array is 4 deep
+    post_json(&app, with_team(r#"[{"tags": ["a"]}]"#))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = post_json(&app, with_team(r#"[{"tags": [["a"]]}]"#)).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_api_error("PAYLOAD_TOO_COMPLEX");
+    response.assert_body_contains("nested deeper than 4");
+}
+
+#[tokio::test]
+async fn test_long_array_is_422() {
+    let app = app_with(BodyLimits::new().max_array_len(3)).await;
+
+    let response = post_json(&app, with_team(r#"["a", "b", "c", "d"]"#)).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_body_contains("more than 3 entries");
+}
+
+#[tokio::test]
+async fn test_too_many_keys_is_422() {
+    let app = app_with(BodyLimits::new().max_keys(4)).await;
+
+    let response = post_json(
+        &app,
+        r#"{"name": "Ada", "email": "ada@example.com", "message": "Hi there", "a": 1, "b": 2}"#,
+    )
+    .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_body_contains("more than 4 keys");
+
+    let response = post(
+        &app,
+        "application/x-www-form-urlencoded",
+        "a=1&b=2&c=3&d=4&e=5",
+    )
+    .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+// ============================================================================
+// Corpus
+// ============================================================================
+
+/// Payloads that are malformed, or built to be expensive to parse.
+fn corpus() -> Vec<String> {
+    let mut corpus: Vec<String> = [
+        "",
+        "{",
+        "}",
+        "[]",
+        "null",
+        "\"",
+        "{\"",
+        "{\"name\": \"\\",
+        "{\"name\": \"\\\"}",
+        "{\"name\": \"\\u00\"}",
+        "{\"name\": \"\u{0}\"}",
+        "{\"name\": [}",
+        "{\"name\": ]]]]}",
+        "{\"name\": 1e999999}",
+        "{\"name\": -}",
+        "{::::}",
+        "{,,,,}",
+        "{\"a\": tru}",
+        "\u{feff}{}",
+        "{\"name\": \"\u{1F600}\"}",
+    ]
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+
+    corpus.push(format!("{}{}", "[".repeat(100_000), "]".repeat(100_000)));
+    corpus.push(format!("{{\"a\": {}", "[".repeat(100_000)));
+    corpus.push(format!("{{{}}}", "\"a\": {".repeat(50_000)));
+    corpus.push(with_team(&format!("[{}1]", "1,".repeat(200_000))));
+    corpus.push(with_team(&format!("[{}]", "[],".repeat(100_000) + "[]")));
+    corpus.push(format!(
+        "{{{}\"z\": 1}}",
+        (0..50_000)
+            .map(|i| format!("\"k{i}\": 1,"))
+            .collect::<String>()
+    ));
+    corpus.push(with_team(&format!("\"{}\"", "\\\"".repeat(200_000))));
+    corpus.push(with_team(&format!("\"{}", "a".repeat(500_000))));
+    corpus.push(format!("{{\"a\": \"{}\"}}", ":".repeat(100_000)));
+    corpus
+}
+
+#[tokio::test]
+async fn test_corpus_is_rejected_cleanly() {
+    let app = app_with(BodyLimits::new()).await;
+
+    for payload in corpus() {
+        let shown: String = payload.chars().take(40).collect();
+        let response = post_json(&app, payload).await;
+        assert!(
+            matches!(
+                response.status,
+                StatusCode::BAD_REQUEST
+                    | StatusCode::PAYLOAD_TOO_LARGE
+                    | StatusCode::UNPROCESSABLE_ENTITY
+            ),
+            "{shown}: {} {}",
+            response.status,
+            response.text()
+        );
+    }
+}
+
+/// A small xorshift generator, so failures replay.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[tokio::test]
+async fn test_mutated_payloads_never_fail_the_server() {
+    let app = app_with(BodyLimits::new().max_depth(4).max_array_len(8).max_keys(8)).await;
+    let seed = with_team(r#"["Ada", {"x": [1, 2]}, "Lovelace"]"#).into_bytes();
+    const TOKENS: &[&[u8]] = &[
+        b"{",
+        b"}",
+        b"[",
+        b"]",
+        b",",
+        b":",
+        b"\"",
+        b"\\",
+        b"[[[[[",
+        b"1,1,1,1,1,1,1,1,1",
+    ];
+    let mut rng = Rng(0x5eed_f00d);
+
+    for round in 0..300 {
+        let mut payload = seed.clone();
+        for _ in 0..=rng.below(4) {
+            let at = rng.below(payload.len() + 1);
+            match rng.below(3) {
+                0 if at < payload.len() => {
+                    payload.remove(at);
+                }
+                1 if at < payload.len() => payload[at] = rng.next() as u8,
+                _ => {
+                    let token = TOKENS[rng.below(TOKENS.len())];
+                    payload.splice(at..at, token.iter().copied());
+                }
+            }
+        }
+
+        let response = post_json(&app, payload.clone()).await;
+        assert!(
+            response.status.is_success() || response.status.is_client_error(),
+            "round {round}: {} for {}",
+            response.status,
+            String::from_utf8_lossy(&payload)
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Tests for jump rules between the steps of multi-step forms.
+
+mod common;
+
+use anyform::{
+    ConditionRule, CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, FormError,
+    JumpRule, StepEntity, UiOptions,
+};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use serde_json::{json, Value};
+
+const SLUG: &str = "test-branching";
+
+/// Plan, then Personal or Company depending on the plan, then Contact.
+/// Answering "none" ends the form after the first step.
+fn branching_form() -> CreateFormInput {
+    let business = ConditionRule::eq("plan", "business");
+    CreateFormInput::new("Branching", SLUG)
+        .step(
+            CreateStepInput::new("Plan")
+                .field(
+                    CreateFieldInput::new("plan", "Plan", "text")
+                        .required()
+                        .ui(UiOptions::new().jump(JumpRule::to_step("Company").when(business))),
+                )
+                .jump(JumpRule::end_form().when(ConditionRule::eq("plan", "none"))),
+        )
+        .step(
+            CreateStepInput::new("Personal")
+                .field(CreateFieldInput::new("nickname", "Nickname", "text").required())
+                .jump(JumpRule::to_step("Contact")),
+        )
+        .step(
+            CreateStepInput::new("Company")
+                .field(CreateFieldInput::new("company", "Company", "text").required()),
+        )
+        .step(
+            CreateStepInput::new("Contact")
+                .field(CreateFieldInput::new("email", "Email", "email").required()),
+        )
+}
+
+async fn branching_app() -> (TestApp, Vec<String>) {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), branching_form()).await;
+    let steps = StepEntity::find_by_form(app.db(), form.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.id.to_string())
+        .collect();
+    (app, steps)
+}
+
+// ============================================================================
+// Form JSON
+// ============================================================================
+
+#[tokio::test]
+async fn test_form_json_exposes_jump_rules_with_step_ids() {
+    let (app, steps) = branching_app().await;
+
+    let response = app.get(&format!("/api/forms/{SLUG}/json")).await;
+    response.assert_status(StatusCode::OK);
+    let form: Value = response.json();
+
+    let plan = &form["steps"][0];
+    assert_eq!(
+        plan["fields"][0]["next"],
+        json!([{ "when": { "field": "plan", "op": "eq", "value": "business" }, "to": steps[2] }])
+    );
+    assert!(plan["fields"][0].get("ui_options").is_none());
+    assert_eq!(
+        plan["next"],
+        json!([{ "when": { "field": "plan", "op": "eq", "value": "none" } }])
+    );
+    assert_eq!(form["steps"][1]["next"], json!([{ "to": steps[3] }]));
+    assert!(form["steps"][2].get("next").is_none());
+}
+
+#[tokio::test]
+async fn test_skeleton_keeps_field_jump_rules_on_their_step() {
+    let (app, steps) = branching_app().await;
+
+    let response = app.get(&format!("/api/forms/{SLUG}/json?steps=ids")).await;
+    response.assert_status(StatusCode::OK);
+    let form: Value = response.json();
+
+    let plan = &form["steps"][0];
+    assert_eq!(plan["fields"], json!([]));
+    assert_eq!(
+        plan["next"],
+        json!([
+            { "when": { "field": "plan", "op": "eq", "value": "business" }, "to": steps[2] },
+            { "when": { "field": "plan", "op": "eq", "value": "none" } }
+        ])
+    );
+    assert_eq!(form["steps"][1]["next"], json!([{ "to": steps[3] }]));
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+#[tokio::test]
+async fn test_steps_jumped_over_are_not_validated() {
+    let (app, _) = branching_app().await;
+    let uri = format!("/api/forms/{SLUG}");
+
+    let business = json!({ "plan": "business", "company": "Acme", "email": "a@acme.com" });
+    app.post_json(&uri, &business)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let personal = json!({ "plan": "personal", "nickname": "Ada", "email": "ada@example.com" });
+    app.post_json(&uri, &personal)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    // Ending the form skips every later step
+    app.post_json(&uri, &json!({ "plan": "none" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_steps_on_the_route_are_still_validated() {
+    let (app, _) = branching_app().await;
+
+    let response = app
+        .post_json(&format!("/api/forms/{SLUG}"), &json!({ "plan": "personal" }))
+        .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_api_error("VALIDATION_FAILED");
+    let body = response.text();
+    assert!(body.contains("nickname"), "{body}");
+    assert!(body.contains("email"), "{body}");
+    assert!(!body.contains("company"), "{body}");
+}
+
+// ============================================================================
+// Step by step
+// ============================================================================
+
+#[tokio::test]
+async fn test_in_progress_submissions_follow_jump_rules() {
+    let (app, steps) = branching_app().await;
+
+    let response = app
+        .post_json(&format!("/api/forms/{SLUG}/submissions/start"), &json!({}))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let id = response.json::<Value>()["data"]["submission_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let uri = format!("/api/forms/{SLUG}/submissions/{id}/step");
+
+    let response = app.patch_json(&uri, &json!({ "data": { "plan": "business" } })).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["data"]["current_step_id"], steps[2].as_str());
+
+    let response = app.patch_json(&uri, &json!({ "data": { "company": "Acme" } })).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["data"]["current_step_id"], steps[3].as_str());
+}
+
+// ============================================================================
+// Saving forms
+// ============================================================================
+
+#[tokio::test]
+async fn test_jumps_must_go_to_a_later_step() {
+    let db = TestDb::new().await;
+
+    let backwards = branching_form().step(
+        CreateStepInput::new("Thanks").jump(JumpRule::to_step("Plan")),
+    );
+    let err = FormBuilder::create(db.conn(), backwards).await.unwrap_err();
+    assert!(matches!(err, FormError::InvalidData(ref m) if m.contains("'Plan'")), "{err}");
+
+    let unknown = branching_form().step(
+        CreateStepInput::new("Thanks").field(
+            CreateFieldInput::new("rating", "Rating", "text")
+                .ui(UiOptions::new().jump(JumpRule::to_step("Nowhere"))),
+        ),
+    );
+    let err = FormBuilder::create(db.conn(), unknown).await.unwrap_err();
+    assert!(matches!(err, FormError::InvalidData(ref m) if m.contains("'rating'")), "{err}");
+
+    FormBuilder::create(db.conn(), branching_form()).await.unwrap();
+}
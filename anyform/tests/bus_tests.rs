@@ -0,0 +1,304 @@
+//! Tests for publishing events to a message bus.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use anyform::bus::NatsBus;
+use anyform::schema::FormSettings;
+use anyform::{BusEvent, BusEventType, EventPublisher, FormBuilder, MessageBus};
+use async_trait::async_trait;
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufStream};
+use tokio::net::TcpListener;
+
+const URI: &str = "/api/v1/forms/test-contact";
+
+/// Records the events it is asked to publish, failing every one while
+/// `fail` is set.
+#[derive(Clone, Default)]
+struct Recorder {
+    published: Arc<Mutex<Vec<(String, BusEvent)>>>,
+    fail: bool,
+}
+
+#[async_trait]
+impl MessageBus for Recorder {
+    async fn publish(&self, topic: &str, event: &BusEvent) -> Result<(), String> {
+        if self.fail {
+            return Err("broker unavailable".into());
+        }
+        self.published.lock().unwrap().push((topic.to_string(), event.clone()));
+        Ok(())
+    }
+}
+
+impl Recorder {
+    fn types(&self) -> Vec<String> {
+        let published = self.published.lock().unwrap();
+        published.iter().map(|(_, e)| e.event_type.clone()).collect()
+    }
+}
+
+async fn bus_app(bus: Recorder) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .events(EventPublisher::new(bus, "anyform.events").source("https://forms.example.com"))
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+async fn submit(app: &TestApp) -> StatusCode {
+    let body = serde_json::json!({
+        "name": "Ada",
+        "email": "ada@example.com",
+        "message": "Hello there"
+    });
+    app.post_json(URI, &body).await.status
+}
+
+// ============================================================================
+// Publishing
+// ============================================================================
+
+#[tokio::test]
+async fn test_submission_events_are_published() {
+    let bus = Recorder::default();
+    let app = bus_app(bus.clone()).await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    assert_eq!(submit(&app).await, StatusCode::CREATED);
+
+    let (topic, created) = bus.published.lock().unwrap()[0].clone();
+    assert_eq!(topic, "anyform.events");
+    assert_eq!(created.specversion, "1.0");
+    assert_eq!(created.event_type, "anyform.submission.created.v1");
+    assert_eq!(created.source, "https://forms.example.com");
+    assert_eq!(created.data["form_slug"], "test-contact");
+    assert_eq!(created.data["status"], "submitted");
+    assert_eq!(created.data["data"]["name"], "Ada");
+    assert_eq!(created.subject, created.data["submission_id"].as_str().unwrap());
+
+    let uri = format!("/api/admin/forms/{}/submissions/{}", form.id, created.subject);
+    app.delete(&uri).await.assert_status(StatusCode::OK);
+
+    let (_, deleted) = bus.published.lock().unwrap()[1].clone();
+    assert_eq!(deleted.event_type, BusEventType::SubmissionDeleted.cloud_event_type());
+    assert_eq!(deleted.subject, created.subject);
+    assert!(deleted.data.get("data").is_none());
+    assert_ne!(deleted.id, created.id);
+}
+
+#[tokio::test]
+async fn test_form_published_once_when_it_goes_live() {
+    let bus = Recorder::default();
+    let app = bus_app(bus.clone()).await;
+    let form =
+        create_test_form(app.db(), contact_form().settings(FormSettings::new().draft(true))).await;
+
+    let uri = format!("/api/admin/forms/{}/publish", form.id);
+    for _ in 0..2 {
+        app.post_json(&uri, &serde_json::json!({}))
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    assert_eq!(
+        bus.types(),
+        vec!["anyform.form.published.v1", "anyform.form.opened.v1"]
+    );
+    let (_, event) = bus.published.lock().unwrap()[0].clone();
+    assert_eq!(event.subject, form.id.to_string());
+    assert_eq!(event.data["form_slug"], "test-contact");
+
+    // Publishing outside the admin API doesn't go through the hooks
+    FormBuilder::unpublish(app.db(), form.id).await.unwrap();
+    FormBuilder::publish(app.db(), form.id).await.unwrap();
+    assert_eq!(bus.types().len(), 2);
+}
+
+#[tokio::test]
+async fn test_form_archived_and_closed_are_published() {
+    let bus = Recorder::default();
+    let app = bus_app(bus.clone()).await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    app.post_json(&format!("/api/admin/forms/{}/archive", form.id), &serde_json::json!({}))
+        .await
+        .assert_status(StatusCode::OK);
+
+    assert_eq!(
+        bus.types(),
+        vec!["anyform.form.archived.v1", "anyform.form.closed.v1"]
+    );
+    let (_, closed) = bus.published.lock().unwrap()[1].clone();
+    assert_eq!(closed.subject, form.id.to_string());
+    assert_eq!(closed.data["form_slug"], "test-contact");
+    assert_eq!(closed.data["reason"], "archived");
+}
+
+#[tokio::test]
+async fn test_form_created_and_updated_are_published() {
+    let bus = Recorder::default();
+    let app = bus_app(bus.clone()).await;
+
+    let response = app.post_json("/api/admin/forms", &contact_form()).await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    let form_id = json["data"]["id"].as_str().unwrap().to_string();
+    app.put_json(&format!("/api/admin/forms/{form_id}"), &contact_form())
+        .await
+        .assert_status(StatusCode::OK);
+
+    let sync = vec![contact_form(), anyform::CreateFormInput::new("Survey", "survey")];
+    app.post_json("/api/admin/forms/sync", &sync)
+        .await
+        .assert_status(StatusCode::OK);
+
+    assert_eq!(
+        bus.types(),
+        vec![
+            "anyform.form.created.v1",
+            "anyform.form.updated.v1",
+            "anyform.form.updated.v1",
+            "anyform.form.created.v1",
+        ]
+    );
+    let published = bus.published.lock().unwrap();
+    assert_eq!(published[1].1.subject, form_id);
+    assert_eq!(published[3].1.data["form_slug"], "survey");
+}
+
+#[tokio::test]
+async fn test_bus_failures_do_not_fail_submissions() {
+    let bus = Recorder {
+        fail: true,
+        ..Recorder::default()
+    };
+    let app = bus_app(bus.clone()).await;
+    create_test_form(app.db(), contact_form()).await;
+
+    assert_eq!(submit(&app).await, StatusCode::CREATED);
+    assert!(bus.types().is_empty());
+}
+
+// ============================================================================
+// NATS
+// ============================================================================
+
+/// Runs a NATS server stand-in for one connection, returning the
+/// address and the `(subject, payload)` of each message published.
+///
+/// Messages published with a reply subject, as JetStream publishes are,
+/// are answered with a stream acknowledgement, or with a JetStream error
+/// when `reject` is set.
+async fn fake_nats(reject: bool) -> (String, Arc<Mutex<Vec<(String, String)>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let received = messages.clone();
+
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut stream = BufStream::new(socket);
+        let info = r#"INFO {"server_id":"test","max_payload":1048576,"headers":true}"#;
+        stream.write_all(format!("{info}\r\n").as_bytes()).await.unwrap();
+        stream.flush().await.unwrap();
+        let mut subscriptions: Vec<(String, String)> = Vec::new();
+        let mut line = String::new();
+        while stream.read_line(&mut line).await.unwrap_or(0) > 0 {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["PING"] => stream.write_all(b"PONG\r\n").await.unwrap(),
+                ["SUB", subject, sid] => subscriptions.push((subject.to_string(), sid.to_string())),
+                ["PUB", subject, rest @ ..] => {
+                    let len: usize = rest.last().unwrap().parse().unwrap();
+                    let mut payload = vec![0; len + 2];
+                    stream.read_exact(&mut payload).await.unwrap();
+                    payload.truncate(len);
+                    let payload = String::from_utf8(payload).unwrap();
+                    received.lock().unwrap().push((subject.to_string(), payload));
+
+                    if let [reply, _] = rest {
+                        let ack = if reject {
+                            r#"{"error":{"code":503,"err_code":10039,"description":"jetstream not enabled"}}"#
+                        } else {
+                            r#"{"stream":"EVENTS","seq":1}"#
+                        };
+                        let (_, sid) = subscriptions
+                            .iter()
+                            .find(|(s, _)| s.strip_suffix('*').is_some_and(|p| reply.starts_with(p)))
+                            .unwrap();
+                        let msg = format!("MSG {reply} {sid} {}\r\n{ack}\r\n", ack.len());
+                        stream.write_all(msg.as_bytes()).await.unwrap();
+                    }
+                }
+                _ => {}
+            }
+            stream.flush().await.unwrap();
+            line.clear();
+        }
+    });
+
+    (address, messages)
+}
+
+fn sample_event() -> BusEvent {
+    serde_json::from_value(serde_json::json!({
+        "specversion": "1.0",
+        "id": "1",
+        "source": "anyform",
+        "type": "anyform.submission.created.v1",
+        "subject": "sub-1",
+        "time": "2025-01-01T00:00:00+00:00",
+        "datacontenttype": "application/json",
+        "data": { "answer": 42 }
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_nats_bus_publishes_cloud_events() {
+    let (address, messages) = fake_nats(false).await;
+    let bus = NatsBus::connect(address).await.unwrap();
+
+    for _ in 0..2 {
+        bus.publish("anyform.events", &sample_event()).await.unwrap();
+    }
+
+    // Core NATS publishes aren't acknowledged; give the server a moment
+    for _ in 0..50 {
+        if messages.lock().unwrap().len() == 2 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let messages = messages.lock().unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].0, "anyform.events");
+    let event: BusEvent = serde_json::from_str(&messages[0].1).unwrap();
+    assert_eq!(event, sample_event());
+}
+
+#[tokio::test]
+async fn test_nats_bus_waits_for_jetstream_acks() {
+    let (address, messages) = fake_nats(false).await;
+    let bus = NatsBus::connect(address).await.unwrap().jetstream();
+
+    bus.publish("anyform.events", &sample_event()).await.unwrap();
+    assert_eq!(messages.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_nats_bus_reports_jetstream_errors() {
+    let (address, _) = fake_nats(true).await;
+    let bus = NatsBus::connect(address).await.unwrap().jetstream();
+
+    let err = bus.publish("anyform.events", &sample_event()).await.unwrap_err();
+    assert!(err.contains("jetstream not enabled"), "{err}");
+
+    let err = bus.publish("bad subject", &sample_event()).await.unwrap_err();
+    assert!(err.contains("invalid NATS subject"), "{err}");
+}
@@ -0,0 +1,121 @@
+//! Tests for capacity-limited options.
+
+mod common;
+
+use anyform::{CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FormBuilder};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+
+fn workshop_form() -> CreateFormInput {
+    CreateFormInput::new("Workshops", "workshops").step(
+        CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("name", "Name", "text").required(),
+            CreateFieldInput::new("workshop", "Workshop", "select")
+                .required()
+                .options(vec![
+                    CreateOptionInput::new("Workshop A", "a").capacity(1),
+                    CreateOptionInput::new("Workshop B", "b"),
+                ]),
+        ]),
+    )
+}
+
+async fn register(app: &TestApp, workshop: &str) -> common::app::TestResponse {
+    app.post_json(
+        "/api/forms/workshops",
+        &serde_json::json!({ "name": "Ada", "workshop": workshop }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_json_exposes_remaining_seats() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), workshop_form()).await;
+
+    let json: serde_json::Value = app.get("/api/forms/workshops/json").await.json();
+    let options = &json["steps"][0]["fields"][1]["options"];
+    assert_eq!(options[0]["capacity"], 1);
+    assert_eq!(options[0]["remaining"], 1);
+    assert!(options[1].get("capacity").is_none());
+
+    register(&app, "a").await.assert_status(StatusCode::CREATED);
+
+    let json: serde_json::Value = app.get("/api/forms/workshops/json").await.json();
+    assert_eq!(json["steps"][0]["fields"][1]["options"][0]["remaining"], 0);
+}
+
+#[tokio::test]
+async fn test_submit_rejects_full_option() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), workshop_form()).await;
+
+    register(&app, "a").await.assert_status(StatusCode::CREATED);
+
+    let response = register(&app, "a").await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("OPTION_FULL");
+
+    // Unlimited options keep accepting
+    register(&app, "b").await.assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_html_disables_sold_out_option() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), workshop_form()).await;
+    register(&app, "a").await.assert_status(StatusCode::CREATED);
+
+    let response = app.get("/api/forms/workshops").await;
+    response.assert_body_contains(
+        "<option value=\"a\" disabled data-af-sold-out>Workshop A (sold out)</option>",
+    );
+    response.assert_body_contains("<option value=\"b\">Workshop B</option>");
+}
+
+#[tokio::test]
+async fn test_redirect_submit_rerenders_when_full() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), workshop_form()).await;
+    register(&app, "a").await.assert_status(StatusCode::CREATED);
+
+    let response = app
+        .post_form(
+            "/api/forms/workshops/submit",
+            &[("name", "Grace"), ("workshop", "a")],
+        )
+        .await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_body_contains("Workshop A is full");
+}
+
+#[tokio::test]
+async fn test_form_update_keeps_reserved_seats() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), workshop_form()).await;
+    register(&app, "a").await.assert_status(StatusCode::CREATED);
+
+    FormBuilder::update(app.db(), form.id, workshop_form())
+        .await
+        .unwrap();
+
+    register(&app, "a").await.assert_status(StatusCode::CONFLICT);
+}
+
+#[cfg(feature = "admin")]
+#[tokio::test]
+async fn test_deleting_submission_releases_seat() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), workshop_form()).await;
+
+    let json: serde_json::Value = register(&app, "a").await.json();
+    let sub_id = json["data"]["submission_id"].as_str().unwrap().to_string();
+    register(&app, "a").await.assert_status(StatusCode::CONFLICT);
+
+    app.delete(&format!("/api/admin/forms/{}/submissions/{}", form.id, sub_id))
+        .await
+        .assert_status(StatusCode::OK);
+
+    register(&app, "a").await.assert_status(StatusCode::CREATED);
+}
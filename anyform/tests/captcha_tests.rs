@@ -0,0 +1,141 @@
+//! Tests for CAPTCHA verification.
+
+mod common;
+
+use anyform::schema::FormSettings;
+use anyform::{Captcha, CaptchaProvider, CaptchaVerifier, SubmissionEntity};
+use async_trait::async_trait;
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::{header, StatusCode};
+
+const SITE_KEY: &str = "site-key-123";
+const URI: &str = "/api/v1/forms/test-contact";
+
+/// Accepts the token `"human"` only.
+struct FakeVerifier;
+
+#[async_trait]
+impl CaptchaVerifier for FakeVerifier {
+    async fn verify(&self, captcha: &Captcha, token: &str, _remote_ip: Option<&str>) -> bool {
+        captcha.site_key == SITE_KEY && token == "human"
+    }
+}
+
+async fn captcha_app(verifier: Option<FakeVerifier>) -> TestApp {
+    let test_db = TestDb::new().await;
+    let mut builder = anyform::AnyFormRouter::builder().database(test_db.db.clone());
+    if let Some(verifier) = verifier {
+        builder = builder.captcha_verifier(verifier);
+    }
+    let app = TestApp::from_router(test_db, builder.build());
+    let captcha = Captcha::new(CaptchaProvider::Turnstile, SITE_KEY);
+    create_test_form(app.db(), contact_form().settings(FormSettings::new().captcha(captcha))).await;
+    app
+}
+
+fn answers(token: Option<&str>) -> serde_json::Value {
+    let mut body = serde_json::json!({
+        "name": "Ada",
+        "email": "ada@example.com",
+        "message": "Hello there"
+    });
+    if let Some(token) = token {
+        body["cf-turnstile-response"] = token.into();
+    }
+    body
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+#[tokio::test]
+async fn test_captcha_forms_render_widget_and_allow_it_in_csp() {
+    let app = captcha_app(Some(FakeVerifier)).await;
+
+    let response = app.get(URI).await;
+    response.assert_status(StatusCode::OK);
+    let html = response.text();
+    assert!(html.contains(&format!(
+        "<div class=\"af-captcha cf-turnstile\" data-sitekey=\"{SITE_KEY}\"></div>"
+    )));
+    assert!(html.contains(CaptchaProvider::Turnstile.script_url()));
+
+    let policy = response.headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+    assert!(policy.contains("frame-src https://challenges.cloudflare.com"));
+}
+
+#[tokio::test]
+async fn test_forms_without_captcha_render_no_widget() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), contact_form()).await;
+
+    let response = app.get(URI).await;
+    assert!(!response.text().contains("af-captcha"));
+    let policy = response.headers[header::CONTENT_SECURITY_POLICY].to_str().unwrap();
+    assert!(!policy.contains("frame-src"));
+}
+
+// ============================================================================
+// Submissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_submissions_need_a_verified_token() {
+    let app = captcha_app(Some(FakeVerifier)).await;
+
+    for token in [None, Some(""), Some("robot")] {
+        let response = app.post_json(URI, &answers(token)).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        response.assert_api_error("CAPTCHA_FAILED");
+    }
+
+    app.post_json(URI, &answers(Some("human")))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let form = anyform::FormBuilder::find_by_slug(app.db(), "test-contact")
+        .await
+        .unwrap()
+        .unwrap();
+    let subs = SubmissionEntity::find_by_form(app.db(), form.id).await.unwrap();
+    assert_eq!(subs.len(), 1);
+    assert!(subs[0].data.get("cf-turnstile-response").is_none());
+}
+
+#[tokio::test]
+async fn test_ssr_submissions_are_verified() {
+    let app = captcha_app(Some(FakeVerifier)).await;
+    let uri = "/api/v1/forms/test-contact/submit";
+    let fields = [
+        ("name", "Ada"),
+        ("email", "ada@example.com"),
+        ("message", "Hello there"),
+    ];
+
+    app.post_form(uri, &fields)
+        .await
+        .assert_api_error("CAPTCHA_FAILED");
+
+    let mut verified = fields.to_vec();
+    verified.push(("cf-turnstile-response", "human"));
+    app.post_form(uri, &verified)
+        .await
+        .assert_status(StatusCode::SEE_OTHER);
+}
+
+#[tokio::test]
+async fn test_submissions_fail_without_a_verifier() {
+    let app = captcha_app(None).await;
+
+    app.post_json(URI, &answers(Some("human")))
+        .await
+        .assert_api_error("CAPTCHA_FAILED");
+
+    let report = anyform::AnyFormRouter::builder()
+        .database(app.db().clone())
+        .preflight()
+        .await
+        .unwrap();
+    assert!(report.issues.iter().any(|i| i.code == "CAPTCHA_VERIFIER_MISSING"));
+}
@@ -0,0 +1,114 @@
+//! Tests for chaos mode's simulated failures.
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use anyform::{CreateFieldInput, CreateFormInput, CreateStepInput, DeadLetterEntity, SubmissionEntity};
+use axum::body::Body;
+use common::{create_test_form, TestApp, TestDb};
+use http::{Request, StatusCode};
+use sea_orm::EntityTrait;
+
+const SECRET: &str = "chaos-test-secret";
+
+async fn chaos_app() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .chaos_secret(SECRET)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(
+        app.db(),
+        CreateFormInput::new("Contact", "contact")
+            .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text"))),
+    )
+    .await;
+    app
+}
+
+fn request(method: &str, uri: &str, secret: &str, faults: &str) -> Request<Body> {
+    let body = if method == "POST" {
+        Body::from(r#"{"name":"Ada"}"#)
+    } else {
+        Body::empty()
+    };
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("Content-Type", "application/json")
+        .header("X-Anyform-Chaos-Secret", secret)
+        .header("X-Anyform-Chaos", faults)
+        .body(body)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_db_timeout() {
+    let app = chaos_app().await;
+
+    let response = app
+        .send_raw(request("GET", "/api/v1/forms/contact/json", SECRET, "db-timeout=20"))
+        .await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    response.assert_api_error("DATABASE_ERROR");
+}
+
+#[tokio::test]
+async fn test_slow_render() {
+    let app = chaos_app().await;
+
+    let started = Instant::now();
+    app.send_raw(request("GET", "/api/v1/forms/contact", SECRET, "slow-render=50"))
+        .await
+        .assert_status(StatusCode::OK);
+    assert!(started.elapsed() >= Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn test_webhook_failure_dead_letters_the_submission() {
+    let app = chaos_app().await;
+
+    let response = app
+        .send_raw(request("POST", "/api/v1/forms/contact", SECRET, "webhook-failure"))
+        .await;
+    response.assert_status(StatusCode::BAD_GATEWAY);
+    response.assert_api_error("WEBHOOK_FAILED");
+
+    assert!(SubmissionEntity::find().all(app.db()).await.unwrap().is_empty());
+    assert_eq!(DeadLetterEntity::count_pending(app.db()).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_faults_require_the_secret() {
+    let app = chaos_app().await;
+
+    app.send_raw(request("POST", "/api/v1/forms/contact", "wrong", "db-timeout"))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    // Without a configured secret, chaos headers are ignored
+    let plain = TestApp::new().await;
+    create_test_form(
+        plain.db(),
+        CreateFormInput::new("Contact", "contact")
+            .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text"))),
+    )
+    .await;
+    plain
+        .send_raw(request("POST", "/api/v1/forms/contact", SECRET, "db-timeout"))
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_unknown_fault_is_rejected() {
+    let app = chaos_app().await;
+
+    let response = app
+        .send_raw(request("GET", "/api/v1/forms/contact/json", SECRET, "meteor-strike"))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    response.assert_api_error("INVALID_DATA");
+}
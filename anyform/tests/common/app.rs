@@ -49,6 +49,13 @@ impl TestApp {
         Self { test_db, router }
     }
 
+    /// Creates a test app from a custom-built router.
+    ///
+    /// The router must have been built against `test_db`'s connection.
+    pub fn from_router(test_db: TestDb, router: Router) -> Self {
+        Self { test_db, router }
+    }
+
     /// Returns a reference to the database connection.
     pub fn db(&self) -> &DatabaseConnection {
         &self.test_db.db
@@ -102,6 +109,18 @@ impl TestApp {
         self.send(request).await
     }
 
+    /// Sends a PATCH request with JSON body.
+    pub async fn patch_json<T: serde::Serialize>(&self, uri: &str, body: &T) -> TestResponse {
+        let json = serde_json::to_vec(body).expect("Failed to serialize JSON body");
+        let request = Request::builder()
+            .uri(uri)
+            .method("PATCH")
+            .header("Content-Type", "application/json")
+            .body(Body::from(json))
+            .expect("Failed to build PATCH request");
+        self.send(request).await
+    }
+
     /// Sends a DELETE request.
     pub async fn delete(&self, uri: &str) -> TestResponse {
         let request = Request::builder()
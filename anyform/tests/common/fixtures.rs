@@ -2,7 +2,7 @@
 
 use anyform::{
     services::{CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput},
-    schema::{FormSettings, UiOptions, ValidationRules},
+    schema::{Direction, FormSettings, UiOptions, ValidationRules},
 };
 use sea_orm::DatabaseConnection;
 
@@ -82,6 +82,32 @@ pub fn multi_step_form() -> CreateFormInput {
         )
 }
 
+/// Creates an Arabic multi-step form (right-to-left via its locale).
+pub fn arabic_form() -> CreateFormInput {
+    CreateFormInput::new("نموذج الاتصال", "test-arabic")
+        .settings(FormSettings::new().locale("ar").show_progress(true))
+        .step(
+            CreateStepInput::new("المعلومات الشخصية")
+                .order(0)
+                .fields(vec![CreateFieldInput::new("name", "الاسم", "text").required()]),
+        )
+        .step(
+            CreateStepInput::new("التواصل")
+                .order(1)
+                .fields(vec![CreateFieldInput::new("email", "البريد الإلكتروني", "email")]),
+        )
+}
+
+/// Creates a Hebrew single-step form with an explicit right-to-left direction.
+pub fn hebrew_form() -> CreateFormInput {
+    CreateFormInput::new("טופס יצירת קשר", "test-hebrew")
+        .settings(FormSettings::new().direction(Direction::Rtl).submit_label("שלח"))
+        .step(
+            CreateStepInput::new("ראשי")
+                .fields(vec![CreateFieldInput::new("name", "שם", "text").required()]),
+        )
+}
+
 /// Creates a quiz form with scoring.
 pub fn quiz_form() -> CreateFormInput {
     CreateFormInput::new("Test Quiz", "test-quiz")
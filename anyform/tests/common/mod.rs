@@ -1,5 +1,8 @@
 //! Shared test utilities for anyform.
 
+// Each test binary uses a different subset of these helpers.
+#![allow(dead_code, unused_imports)]
+
 pub mod app;
 pub mod cli;
 pub mod db;
@@ -0,0 +1,202 @@
+//! Tests for soft-delete cascades and the database consistency check.
+
+mod common;
+
+use std::collections::HashMap;
+
+use anyform::services::IssueKind;
+use anyform::{
+    ConsistencyService, FormBuilder, FormEntity, Migrator, MigratorTrait, ResultActiveModel,
+    ResultEntity, SubmissionEntity, SubmissionService,
+};
+use common::db::TestDb;
+use sea_orm::prelude::Expr;
+use sea_orm::{ActiveModelTrait, ActiveValue, ConnectionTrait, EntityTrait};
+use uuid::Uuid;
+
+/// Seeds the contact form and gives it two submissions and a result.
+async fn contact_with_children(db: &TestDb) -> (anyform::Form, Vec<Uuid>, Uuid) {
+    anyform::seed_contact_form(db.conn()).await.unwrap();
+    let form = FormBuilder::find_by_slug(db.conn(), "contact")
+        .await
+        .unwrap()
+        .unwrap();
+
+    let mut submissions = Vec::new();
+    for _ in 0..2 {
+        let submission = SubmissionService::create(db.conn(), &form, &[], &HashMap::new(), None, None)
+            .await
+            .unwrap();
+        submissions.push(submission.id);
+    }
+
+    let result = ResultActiveModel {
+        id: ActiveValue::Set(Uuid::new_v4()),
+        form_id: ActiveValue::Set(form.id),
+        key: ActiveValue::Set("thanks".to_string()),
+        title: ActiveValue::Set("Thanks".to_string()),
+        description: ActiveValue::Set(None),
+        min_score: ActiveValue::Set(None),
+        max_score: ActiveValue::Set(None),
+        category: ActiveValue::Set(None),
+        condition: ActiveValue::Set(None),
+        order: ActiveValue::Set(0),
+        deleted_at: ActiveValue::Set(None),
+    }
+    .insert(db.conn())
+    .await
+    .unwrap();
+
+    (form, submissions, result.id)
+}
+
+async fn is_deleted(db: &TestDb, submission_id: Uuid) -> bool {
+    SubmissionEntity::find_by_id(submission_id)
+        .one(db.conn())
+        .await
+        .unwrap()
+        .unwrap()
+        .is_deleted()
+}
+
+// ============================================================================
+// Soft-delete cascade
+// ============================================================================
+
+#[tokio::test]
+async fn test_soft_delete_form_cascades_to_children() {
+    let db = TestDb::new().await;
+    let (form, submissions, result_id) = contact_with_children(&db).await;
+
+    FormBuilder::soft_delete(db.conn(), form.id).await.unwrap();
+
+    let deleted_at = FormEntity::find_by_id(form.id)
+        .one(db.conn())
+        .await
+        .unwrap()
+        .unwrap()
+        .deleted_at;
+    for id in &submissions {
+        let submission = SubmissionEntity::find_by_id(*id).one(db.conn()).await.unwrap().unwrap();
+        assert_eq!(submission.deleted_at, deleted_at);
+    }
+    let result = ResultEntity::find_by_id(result_id).one(db.conn()).await.unwrap().unwrap();
+    assert_eq!(result.deleted_at, deleted_at);
+    assert!(ResultEntity::find_by_form(db.conn(), form.id).await.unwrap().is_empty());
+    assert!(ConsistencyService::check(db.conn()).await.unwrap().is_clean());
+}
+
+#[tokio::test]
+async fn test_restore_form_keeps_separately_deleted_submissions_deleted() {
+    let db = TestDb::new().await;
+    let (form, submissions, _) = contact_with_children(&db).await;
+
+    SubmissionEntity::soft_delete(db.conn(), submissions[0]).await.unwrap();
+    FormBuilder::soft_delete(db.conn(), form.id).await.unwrap();
+    FormBuilder::restore(db.conn(), form.id).await.unwrap();
+
+    assert!(is_deleted(&db, submissions[0]).await);
+    assert!(!is_deleted(&db, submissions[1]).await);
+    assert_eq!(ResultEntity::find_by_form(db.conn(), form.id).await.unwrap().len(), 1);
+    assert!(ConsistencyService::check(db.conn()).await.unwrap().is_clean());
+}
+
+#[tokio::test]
+async fn test_migration_backfills_children_of_deleted_forms() {
+    let db = TestDb::new().await;
+    let (form, submissions, _) = contact_with_children(&db).await;
+
+    // A form soft-deleted before deletes cascaded (m20250101_000015)
+    let since = Migrator::migrations()
+        .iter()
+        .rev()
+        .position(|m| m.name().starts_with("m20250101_000015"))
+        .unwrap();
+    Migrator::down(db.conn(), Some(since as u32 + 1)).await.unwrap();
+    FormEntity::update_many()
+        .col_expr(
+            anyform::entities::form::Column::DeletedAt,
+            Expr::value(chrono::Utc::now().fixed_offset()),
+        )
+        .exec(db.conn())
+        .await
+        .unwrap();
+    Migrator::up(db.conn(), None).await.unwrap();
+
+    assert!(is_deleted(&db, submissions[0]).await);
+    assert!(ResultEntity::find_by_form(db.conn(), form.id).await.unwrap().is_empty());
+    assert!(ConsistencyService::check(db.conn()).await.unwrap().is_clean());
+}
+
+// ============================================================================
+// Consistency check
+// ============================================================================
+
+#[tokio::test]
+async fn test_check_seeded_database_is_clean() {
+    let db = TestDb::new().await;
+    anyform::seed_all(db.conn()).await.unwrap();
+
+    let report = ConsistencyService::check(db.conn()).await.unwrap();
+    assert!(report.is_clean(), "{:?}", report.issues);
+}
+
+#[tokio::test]
+async fn test_check_reports_orphans() {
+    let db = TestDb::new().await;
+    let (form, submissions, result_id) = contact_with_children(&db).await;
+
+    // Deleting the form without foreign keys leaves its children behind
+    db.conn().execute_unprepared("PRAGMA foreign_keys = OFF").await.unwrap();
+    FormEntity::delete_by_id(form.id).exec(db.conn()).await.unwrap();
+
+    let report = ConsistencyService::check(db.conn()).await.unwrap();
+    assert!(!report.is_clean());
+    assert!(report.issues.iter().all(|issue| issue.kind == IssueKind::Orphan));
+    assert!(report.issues.iter().all(|issue| issue.parent_table == "af_forms"));
+    assert!(report.issues.iter().all(|issue| issue.parent_id == form.id));
+
+    let orphaned = |table: &str| -> Vec<Uuid> {
+        report
+            .issues
+            .iter()
+            .filter(|issue| issue.table == table)
+            .map(|issue| issue.id)
+            .collect()
+    };
+    assert_eq!(orphaned("af_steps").len(), 1);
+    assert_eq!(orphaned("af_form_revisions").len(), 1);
+    assert_eq!(orphaned("af_results"), [result_id]);
+    let mut orphaned_submissions = orphaned("af_submissions");
+    orphaned_submissions.sort();
+    let mut submissions = submissions;
+    submissions.sort();
+    assert_eq!(orphaned_submissions, submissions);
+}
+
+#[tokio::test]
+async fn test_check_reports_active_children_of_deleted_forms() {
+    let db = TestDb::new().await;
+    let (form, submissions, result_id) = contact_with_children(&db).await;
+
+    // Marking the form deleted directly skips the cascade
+    FormEntity::update_many()
+        .col_expr(
+            anyform::entities::form::Column::DeletedAt,
+            Expr::value(chrono::Utc::now().fixed_offset()),
+        )
+        .exec(db.conn())
+        .await
+        .unwrap();
+
+    let report = ConsistencyService::check(db.conn()).await.unwrap();
+    assert_eq!(report.issues.len(), 3, "{:?}", report.issues);
+    for issue in &report.issues {
+        assert_eq!(issue.kind, IssueKind::ActiveUnderDeletedForm);
+        assert_eq!(issue.parent_id, form.id);
+        assert!(submissions.contains(&issue.id) || issue.id == result_id);
+    }
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["issues"][0]["kind"], "active_under_deleted_form");
+}
@@ -0,0 +1,186 @@
+//! Tests for CRM push integrations.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use anyform::crm::{CrmClient, CrmMapping, CrmProvider, CrmRecord};
+use anyform::schema::FormSettings;
+use anyform::services::{CrmService, MAX_CRM_ATTEMPTS};
+use anyform::{CrmPushEntity, CrmPushStatus, SubmissionEntity};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use common::{contact_form, create_test_form, TestApp};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+
+const URI: &str = "/api/v1/forms/test-contact";
+
+/// Keeps one record per email, failing every upsert while `fail` is set.
+#[derive(Default)]
+struct FakeCrm {
+    records: Mutex<HashMap<String, String>>,
+    upserts: Mutex<Vec<CrmRecord>>,
+    fail: bool,
+}
+
+#[async_trait]
+impl CrmClient for FakeCrm {
+    fn provider(&self) -> CrmProvider {
+        CrmProvider::HubSpot
+    }
+
+    async fn upsert(&self, record: &CrmRecord) -> Result<String, String> {
+        if self.fail {
+            return Err("503 Service Unavailable".into());
+        }
+        self.upserts.lock().unwrap().push(record.clone());
+        let mut records = self.records.lock().unwrap();
+        let next = format!("crm-{}", records.len() + 1);
+        Ok(records.entry(record.email.clone()).or_insert(next).clone())
+    }
+}
+
+fn mapping() -> CrmMapping {
+    CrmMapping::new(CrmProvider::HubSpot, "email")
+        .property("email", "email")
+        .property("name", "firstname")
+        .property("message", "message")
+        .constant("lifecyclestage", "lead")
+}
+
+async fn crm_app(settings: FormSettings) -> TestApp {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), contact_form().settings(settings)).await;
+    app
+}
+
+async fn submit(app: &TestApp, name: &str, email: &str) -> uuid::Uuid {
+    let body = serde_json::json!({ "name": name, "email": email, "message": "Hello there" });
+    let response = app.post_json(URI, &body).await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    json["data"]["submission_id"].as_str().unwrap().parse().unwrap()
+}
+
+// ============================================================================
+// Pushing
+// ============================================================================
+
+#[tokio::test]
+async fn test_submissions_are_pushed_with_mapped_properties() {
+    let app = crm_app(FormSettings::new().crm(mapping())).await;
+    let id = submit(&app, "Ada", "ada@example.com").await;
+
+    let queued = CrmPushEntity::find_by_submission(app.db(), id).await.unwrap();
+    assert_eq!(queued.len(), 1);
+    assert_eq!(queued[0].status, CrmPushStatus::Pending.as_str());
+
+    let crm = FakeCrm::default();
+    let pushed = CrmService::push_due(app.db(), &crm, Utc::now().fixed_offset()).await.unwrap();
+    assert_eq!(pushed.len(), 1);
+    assert!(pushed[0].is_pushed());
+    assert_eq!(pushed[0].record_id.as_deref(), Some("crm-1"));
+
+    let record = crm.upserts.lock().unwrap()[0].clone();
+    assert_eq!(record.object, "contacts");
+    assert_eq!(
+        serde_json::Value::Object(record.properties.clone()),
+        serde_json::json!({
+            "email": "ada@example.com",
+            "firstname": "Ada",
+            "message": "Hello there",
+            "lifecyclestage": "lead"
+        })
+    );
+
+    let sub = SubmissionEntity::find_by_id(id).one(app.db()).await.unwrap().unwrap();
+    let metadata = sub.metadata.unwrap();
+    assert_eq!(metadata["crm"]["provider"], "hubspot");
+    assert_eq!(metadata["crm"]["record_id"], "crm-1");
+
+    // Nothing is left to push
+    let again = CrmService::push_due(app.db(), &crm, Utc::now().fixed_offset()).await.unwrap();
+    assert!(again.is_empty());
+}
+
+#[tokio::test]
+async fn test_pushes_are_deduplicated_on_email() {
+    let app = crm_app(FormSettings::new().crm(mapping())).await;
+    submit(&app, "Ada", "ada@example.com").await;
+    submit(&app, "Ada L", "ADA@Example.com").await;
+    submit(&app, "Bob", "bob@example.com").await;
+
+    let crm = FakeCrm::default();
+    let pushed = CrmService::push_due(app.db(), &crm, Utc::now().fixed_offset()).await.unwrap();
+    let ids: Vec<_> = pushed.iter().map(|p| p.record_id.as_deref().unwrap()).collect();
+    assert_eq!(ids, vec!["crm-1", "crm-1", "crm-2"]);
+
+    let upserts = crm.upserts.lock().unwrap();
+    assert_eq!(upserts[0].existing_id, None);
+    assert_eq!(upserts[1].email, "ada@example.com");
+    assert_eq!(upserts[1].existing_id.as_deref(), Some("crm-1"));
+    assert_eq!(upserts[2].existing_id, None);
+}
+
+#[tokio::test]
+async fn test_failed_pushes_are_retried_with_backoff() {
+    let app = crm_app(FormSettings::new().crm(mapping())).await;
+    let id = submit(&app, "Ada", "ada@example.com").await;
+    let now = Utc::now().fixed_offset();
+
+    let failing = FakeCrm {
+        fail: true,
+        ..FakeCrm::default()
+    };
+    let attempted = CrmService::push_due(app.db(), &failing, now).await.unwrap();
+    assert_eq!(attempted[0].status, CrmPushStatus::Pending.as_str());
+    assert_eq!(attempted[0].attempts, 1);
+    assert!(attempted[0].next_attempt_at > now);
+
+    // Not due again yet
+    let crm = FakeCrm::default();
+    assert!(CrmService::push_due(app.db(), &crm, now).await.unwrap().is_empty());
+
+    let later = now + Duration::hours(1);
+    let pushed = CrmService::push_due(app.db(), &crm, later).await.unwrap();
+    assert!(pushed[0].is_pushed());
+    assert_eq!(pushed[0].last_error, None);
+
+    let pushes = CrmPushEntity::find_by_submission(app.db(), id).await.unwrap();
+    assert_eq!(pushes.len(), 1);
+}
+
+#[tokio::test]
+async fn test_pushes_are_given_up_after_too_many_failures() {
+    let app = crm_app(FormSettings::new().crm(mapping())).await;
+    let id = submit(&app, "Ada", "ada@example.com").await;
+
+    let failing = FakeCrm {
+        fail: true,
+        ..FakeCrm::default()
+    };
+    let now = Utc::now().fixed_offset();
+    for day in 0..i64::from(MAX_CRM_ATTEMPTS) + 2 {
+        CrmService::push_due(app.db(), &failing, now + Duration::days(day)).await.unwrap();
+    }
+
+    let push = &CrmPushEntity::find_by_submission(app.db(), id).await.unwrap()[0];
+    assert_eq!(push.status, CrmPushStatus::Failed.as_str());
+    assert_eq!(push.attempts, MAX_CRM_ATTEMPTS);
+    assert_eq!(push.last_error.as_deref(), Some("503 Service Unavailable"));
+}
+
+// ============================================================================
+// Queueing
+// ============================================================================
+
+#[tokio::test]
+async fn test_forms_without_crm_or_anonymous_forms_queue_nothing() {
+    for settings in [FormSettings::new(), FormSettings::new().crm(mapping()).anonymous(true)] {
+        let app = crm_app(settings).await;
+        let id = submit(&app, "Ada", "ada@example.com").await;
+        assert!(CrmPushEntity::find_by_submission(app.db(), id).await.unwrap().is_empty());
+    }
+}
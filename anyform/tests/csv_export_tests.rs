@@ -0,0 +1,126 @@
+//! Tests for CSV exports and export mappings.
+
+mod common;
+
+use anyform::schema::{ExportColumnMapping, ExportMapping, SubmissionAttribute};
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FieldClassification,
+    FormBuilder, FormError, FormSettings,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use uuid::Uuid;
+
+fn order_form(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Orders", "orders").settings(settings).step(
+        CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("name", "Name", "text"),
+            CreateFieldInput::new("plan", "Plan", "select").options(vec![
+                CreateOptionInput::new("Free plan", "free"),
+                CreateOptionInput::new("Pro plan", "pro"),
+            ]),
+            CreateFieldInput::new("start", "Start date", "date"),
+            CreateFieldInput::new("ssn", "SSN", "text")
+                .classification(FieldClassification::Sensitive),
+        ]),
+    )
+}
+
+async fn export(app: &TestApp, form_id: Uuid, query: &str) -> Vec<String> {
+    let response = app.get(&format!("/api/admin/forms/{form_id}/submissions/export{query}")).await;
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("text/csv");
+    response.text().lines().map(str::to_string).collect()
+}
+
+async fn order(app: &TestApp) {
+    let body = serde_json::json!({
+        "name": "Smith, Ada",
+        "plan": "pro",
+        "start": "2025-03-01",
+        "ssn": "123-45-6789"
+    });
+    app.post_json("/api/forms/orders", &body).await.assert_status(StatusCode::CREATED);
+}
+
+// ============================================================================
+// Layout
+// ============================================================================
+
+#[tokio::test]
+async fn test_default_layout() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), order_form(FormSettings::new())).await;
+    order(&app).await;
+
+    let lines = export(&app, form.id, "").await;
+    assert_eq!(
+        lines[0],
+        "id,created_at,completed_at,status,instance_id,labels,Name,Plan,Start date"
+    );
+    assert!(lines[1].ends_with(",submitted,,,\"Smith, Ada\",pro,2025-03-01"), "{}", lines[1]);
+
+    let lines = export(&app, form.id, "?include_sensitive=true").await;
+    assert!(lines[0].ends_with(",SSN"));
+    assert!(lines[1].ends_with(",123-45-6789"));
+}
+
+#[tokio::test]
+async fn test_mapping_orders_renames_and_formats_columns() {
+    let app = TestApp::with_admin().await;
+    let mapping = ExportMapping::new()
+        .column(ExportColumnMapping::field("plan").header("Subscription"))
+        .column(ExportColumnMapping::field("name"))
+        .column(ExportColumnMapping::field("start"))
+        .column(ExportColumnMapping::field("referrer").header("Referrer"))
+        .column(ExportColumnMapping::attribute(SubmissionAttribute::Status).header("State"))
+        .column(ExportColumnMapping::field("ssn"))
+        .date_format("%d/%m/%Y")
+        .option_labels(true);
+    let form = create_test_form(app.db(), order_form(FormSettings::new().export(mapping))).await;
+    order(&app).await;
+
+    let lines = export(&app, form.id, "").await;
+    assert_eq!(lines[0], "Subscription,Name,Start date,Referrer,State");
+    assert_eq!(lines[1], "Pro plan,\"Smith, Ada\",01/03/2025,,submitted");
+    assert_eq!(lines.len(), 2);
+
+    // Sensitive fields still need asking for
+    let lines = export(&app, form.id, "?include_sensitive=true").await;
+    assert_eq!(lines[0], "Subscription,Name,Start date,Referrer,State,SSN");
+}
+
+#[tokio::test]
+async fn test_excluded_fields_are_left_out() {
+    let app = TestApp::with_admin().await;
+    let mapping = ExportMapping::new().exclude("name").date_format("%Y");
+    let form = create_test_form(app.db(), order_form(FormSettings::new().export(mapping))).await;
+    order(&app).await;
+
+    let lines = export(&app, form.id, "").await;
+    assert_eq!(
+        lines[0],
+        "id,created_at,completed_at,status,instance_id,labels,Plan,Start date"
+    );
+    let created = lines[1].split(',').nth(1).unwrap();
+    assert_eq!(created.len(), 4);
+    assert!(lines[1].ends_with(",pro,2025"));
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+#[tokio::test]
+async fn test_invalid_date_format_is_rejected() {
+    let app = TestApp::with_admin().await;
+    let mapping = ExportMapping::new().date_format("%Q");
+    let err = FormBuilder::create(app.db(), order_form(FormSettings::new().export(mapping)))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, FormError::InvalidData(_)), "{err:?}");
+
+    app.get(&format!("/api/admin/forms/{}/submissions/export", Uuid::new_v4()))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
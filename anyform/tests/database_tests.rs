@@ -0,0 +1,190 @@
+//! Tests for SQLite connection settings and retries on a busy database.
+
+mod common;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyform::database::{RetryPolicy, SqliteConfig};
+use anyform::loadtest::{self, LoadTestConfig, RouterTarget, SubmissionGenerator};
+use anyform::{AnyFormRouter, FormBuilder, FormError, MigratorTrait};
+use common::TestApp;
+use http::StatusCode;
+use sea_orm::{ConnectOptions, ConnectionTrait, DatabaseConnection, DbErr, Statement};
+use tempfile::TempDir;
+
+/// Creates a migrated SQLite file database with the seeded forms.
+async fn file_db(dir: &TempDir, config: SqliteConfig) -> (String, DatabaseConnection) {
+    let url = format!("sqlite:{}?mode=rwc", dir.path().join("anyform.db").display());
+    let db = config.connect(&url).await.unwrap();
+    anyform::Migrator::up(&db, None).await.unwrap();
+    anyform::seed_all(&db).await.unwrap();
+    (url, db)
+}
+
+/// Opens a second connection and takes the database's write lock.
+async fn lock_writes(url: &str) -> DatabaseConnection {
+    let mut options = ConnectOptions::new(url);
+    options.max_connections(1);
+    let locker = sea_orm::Database::connect(options).await.unwrap();
+    locker.execute_unprepared("BEGIN IMMEDIATE").await.unwrap();
+    locker
+}
+
+async fn pragma(db: &DatabaseConnection, name: &str) -> String {
+    let row = db
+        .query_one(Statement::from_string(db.get_database_backend(), format!("PRAGMA {name}")))
+        .await
+        .unwrap()
+        .unwrap();
+    row.try_get_by_index::<String>(0)
+        .or_else(|_| row.try_get_by_index::<i64>(0).map(|v| v.to_string()))
+        .unwrap()
+}
+
+fn contact_submission() -> serde_json::Value {
+    serde_json::json!({
+        "name": "Jane Doe",
+        "email": "jane@example.com",
+        "message": "Hello from a busy database"
+    })
+}
+
+// ============================================================================
+// Connection settings
+// ============================================================================
+
+#[tokio::test]
+async fn test_sqlite_config_applies_at_connect() {
+    let dir = TempDir::new().unwrap();
+    let config = SqliteConfig::new().busy_timeout(Duration::from_millis(2500));
+    let (_, db) = file_db(&dir, config).await;
+
+    assert_eq!(pragma(&db, "journal_mode").await, "wal");
+    assert_eq!(pragma(&db, "busy_timeout").await, "2500");
+}
+
+#[tokio::test]
+async fn test_sqlite_config_without_wal() {
+    let dir = TempDir::new().unwrap();
+    let (_, db) = file_db(&dir, SqliteConfig::new().wal(false)).await;
+
+    assert_eq!(pragma(&db, "journal_mode").await, "delete");
+}
+
+// ============================================================================
+// Busy errors
+// ============================================================================
+
+#[test]
+fn test_locked_database_errors_are_busy() {
+    let err = FormError::from(DbErr::Custom(
+        "error returned from database: (code: 5) database is locked".to_string(),
+    ));
+
+    assert!(matches!(err, FormError::DatabaseBusy(_)));
+    assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(err.error_code(), "DATABASE_BUSY");
+}
+
+#[tokio::test]
+async fn test_submit_on_locked_database_fails_busy() {
+    let dir = TempDir::new().unwrap();
+    let config = SqliteConfig::new().busy_timeout(Duration::ZERO);
+    let (url, db) = file_db(&dir, config).await;
+    let router = AnyFormRouter::builder()
+        .database(db.clone())
+        .retry_policy(RetryPolicy::disabled())
+        .build();
+    let app = TestApp::from_router(common::TestDb { db }, router);
+
+    let locker = lock_writes(&url).await;
+    let response = app.post_json("/api/v1/forms/contact", &contact_submission()).await;
+    locker.execute_unprepared("ROLLBACK").await.unwrap();
+
+    response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    response.assert_api_error("DATABASE_BUSY");
+}
+
+#[tokio::test]
+async fn test_submit_retries_until_database_is_free() {
+    let dir = TempDir::new().unwrap();
+    let config = SqliteConfig::new().busy_timeout(Duration::ZERO);
+    let (url, db) = file_db(&dir, config).await;
+    let router = AnyFormRouter::builder()
+        .database(db.clone())
+        .retry_policy(
+            RetryPolicy::new()
+                .max_retries(20)
+                .initial_backoff(Duration::from_millis(10))
+                .max_backoff(Duration::from_millis(50)),
+        )
+        .build();
+    let app = TestApp::from_router(common::TestDb { db }, router);
+
+    let locker = lock_writes(&url).await;
+    let release = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        locker.execute_unprepared("COMMIT").await.unwrap();
+    });
+
+    let response = app.post_json("/api/v1/forms/contact", &contact_submission()).await;
+    release.await.unwrap();
+
+    response.assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_retry_policy_gives_up_after_max_retries() {
+    let policy = RetryPolicy::new()
+        .max_retries(3)
+        .initial_backoff(Duration::from_millis(1));
+    let mut attempts = 0;
+
+    let result: Result<(), FormError> = policy
+        .run(|| {
+            attempts += 1;
+            async { Err(FormError::DatabaseBusy("database is locked".to_string())) }
+        })
+        .await;
+
+    assert!(matches!(result, Err(FormError::DatabaseBusy(_))));
+    assert_eq!(attempts, 4);
+}
+
+#[tokio::test]
+async fn test_retry_policy_does_not_retry_other_errors() {
+    let mut attempts = 0;
+
+    let result: Result<(), FormError> = RetryPolicy::new()
+        .run(|| {
+            attempts += 1;
+            async { Err(FormError::FormClosed) }
+        })
+        .await;
+
+    assert!(matches!(result, Err(FormError::FormClosed)));
+    assert_eq!(attempts, 1);
+}
+
+// ============================================================================
+// Concurrent submits
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_submits_on_file_database() {
+    let dir = TempDir::new().unwrap();
+    let (_, db) = file_db(&dir, SqliteConfig::new()).await;
+    let form = FormBuilder::find_by_slug(&db, "feedback").await.unwrap().unwrap();
+    let generator = Arc::new(SubmissionGenerator::for_form(&db, &form).await.unwrap());
+    let target = Arc::new(RouterTarget::new(AnyFormRouter::new(db.clone())));
+
+    let config = LoadTestConfig {
+        concurrency: 16,
+        duration: Duration::from_millis(500),
+    };
+    let report = loadtest::run(target, "feedback", generator, &config).await;
+
+    assert!(report.requests > 0);
+    assert_eq!(report.errors, 0, "{:?}", report.statuses);
+}
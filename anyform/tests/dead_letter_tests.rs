@@ -0,0 +1,105 @@
+//! Tests for dead-lettered submissions.
+
+mod common;
+
+use anyform::{CreateFieldInput, CreateFormInput, CreateStepInput, DeadLetterEntity, SubmissionEntity};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use sea_orm::{ConnectionTrait, EntityTrait};
+
+fn contact_form() -> CreateFormInput {
+    CreateFormInput::new("Contact", "contact")
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text").required()))
+}
+
+/// Makes submission inserts fail until [`restore_submissions`] is called.
+async fn break_submissions(app: &TestApp) {
+    app.db()
+        .execute_unprepared("ALTER TABLE af_submissions RENAME TO af_submissions_broken")
+        .await
+        .unwrap();
+}
+
+async fn restore_submissions(app: &TestApp) {
+    app.db()
+        .execute_unprepared("ALTER TABLE af_submissions_broken RENAME TO af_submissions")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_failed_submission_is_captured() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    break_submissions(&app).await;
+
+    let response = app
+        .post_json("/api/forms/contact", &serde_json::json!({ "name": "Ada" }))
+        .await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+    let letters = DeadLetterEntity::find_pending(app.db(), Some(form.id)).await.unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].data["name"], "Ada");
+    assert!(!letters[0].error.is_empty());
+}
+
+#[tokio::test]
+async fn test_invalid_and_rejected_submissions_are_not_captured() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), contact_form()).await;
+
+    app.post_json("/api/forms/contact", &serde_json::json!({}))
+        .await
+        .assert_api_error("VALIDATION_FAILED");
+
+    assert_eq!(DeadLetterEntity::count_pending(app.db()).await.unwrap(), 0);
+}
+
+#[cfg(feature = "admin")]
+#[tokio::test]
+async fn test_list_and_replay_dead_letters() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    break_submissions(&app).await;
+    app.post_form("/api/forms/contact/submit", &[("name", "Ada")])
+        .await
+        .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+
+    let json: serde_json::Value = app.get("/api/admin/dead-letters").await.json();
+    assert_eq!(json["data"]["count"], 1);
+    assert_eq!(json["data"]["pending"], 1);
+    let id = json["data"]["dead_letters"][0]["id"].as_str().unwrap().to_string();
+    let replay = format!("/api/admin/dead-letters/{id}/replay");
+
+    // Still broken: the replay fails and is recorded
+    app.post_json(&replay, &serde_json::json!({}))
+        .await
+        .assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    let json: serde_json::Value = app.get("/api/admin/dead-letters").await.json();
+    assert_eq!(json["data"]["dead_letters"][0]["attempts"], 1);
+
+    restore_submissions(&app).await;
+    let response = app.post_json(&replay, &serde_json::json!({})).await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["status"], "submitted");
+    let submission_id = json["data"]["submission_id"].clone();
+
+    let submissions = SubmissionEntity::find_by_form(app.db(), form.id).await.unwrap();
+    assert_eq!(submissions.len(), 1);
+    assert_eq!(submissions[0].data["name"], "Ada");
+
+    // Replaying again returns the same submission
+    let json: serde_json::Value = app.post_json(&replay, &serde_json::json!({})).await.json();
+    assert_eq!(json["data"]["submission_id"], submission_id);
+
+    let json: serde_json::Value = app.get("/api/admin/dead-letters").await.json();
+    assert_eq!(json["data"]["pending"], 0);
+    assert!(DeadLetterEntity::find_by_id(id.parse::<uuid::Uuid>().unwrap())
+        .one(app.db())
+        .await
+        .unwrap()
+        .unwrap()
+        .is_replayed());
+}
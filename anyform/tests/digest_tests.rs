@@ -0,0 +1,179 @@
+//! Tests for notification digests.
+
+mod common;
+
+use std::sync::Mutex;
+
+use anyform::schema::{FieldClassification, NotificationDigest};
+use anyform::services::{CreateFieldInput, Digest, DigestSender, DigestService};
+use anyform::{DigestDeliveryEntity, FormSettings};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use common::{create_test_form, options_form, TestApp};
+use http::StatusCode;
+
+const URI: &str = "/api/v1/forms/test-options";
+
+/// Records the digests it is asked to send, failing while `fail` is set.
+#[derive(Default)]
+struct Outbox {
+    sent: Mutex<Vec<Digest>>,
+    fail: bool,
+}
+
+#[async_trait]
+impl DigestSender for Outbox {
+    async fn send(&self, digest: &Digest) -> Result<(), String> {
+        if self.fail {
+            return Err("smtp unavailable".into());
+        }
+        self.sent.lock().unwrap().push(digest.clone());
+        Ok(())
+    }
+}
+
+async fn digest_app() -> (TestApp, anyform::Form) {
+    let app = TestApp::with_admin().await;
+    let digest = NotificationDigest::daily()
+        .recipient("owner@example.com")
+        .recipient("team@example.com");
+    let mut input = options_form().settings(FormSettings::new().digest(digest));
+    input.steps[0].fields.push(
+        CreateFieldInput::new("phone", "Phone", "text")
+            .classification(FieldClassification::Sensitive),
+    );
+    let form = create_test_form(app.db(), input).await;
+
+    let answers = [
+        ("us", vec!["news"]),
+        ("ca", vec!["news", "offers"]),
+        ("us", vec![]),
+    ];
+    for (country, interests) in answers {
+        app.post_json(
+            URI,
+            &serde_json::json!({ "country": country, "interests": interests, "phone": "555-0100" }),
+        )
+        .await
+        .assert_status(StatusCode::CREATED);
+    }
+    (app, form)
+}
+
+/// A time whose previous day is today, so today's submissions are due.
+fn tomorrow() -> DateTime<FixedOffset> {
+    (Utc::now() + Duration::days(1)).fixed_offset()
+}
+
+// ============================================================================
+// Sending
+// ============================================================================
+
+#[tokio::test]
+async fn test_due_digests_are_sent_once() {
+    let (app, form) = digest_app().await;
+    let outbox = Outbox::default();
+
+    let sent = DigestService::send_due(app.db(), &outbox, tomorrow()).await.unwrap();
+    assert_eq!(sent.len(), 1);
+    let digest = &sent[0];
+    assert_eq!(digest.submissions, 3);
+    assert_eq!(digest.recipients, vec!["owner@example.com", "team@example.com"]);
+
+    let countries = &digest.top_options["country"];
+    assert_eq!((countries[0].label.as_str(), countries[0].count), ("United States", 2));
+    assert_eq!((countries[1].label.as_str(), countries[1].count), ("Canada", 1));
+    assert_eq!(digest.top_options["interests"][0].value, "news");
+    assert_eq!(digest.top_options["interests"][0].count, 2);
+
+    assert_eq!(digest.highlights.len(), 3);
+    assert!(digest.highlights.iter().all(|h| !h.answers.contains_key("phone")));
+
+    // Running again in the same period sends nothing
+    let again = DigestService::send_due(app.db(), &outbox, tomorrow()).await.unwrap();
+    assert!(again.is_empty());
+    assert_eq!(outbox.sent.lock().unwrap().len(), 1);
+
+    let deliveries = DigestDeliveryEntity::find_by_form(app.db(), form.id).await.unwrap();
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0].submissions, 3);
+}
+
+#[tokio::test]
+async fn test_failed_digests_are_retried() {
+    let (app, _) = digest_app().await;
+
+    let failing = Outbox {
+        fail: true,
+        ..Outbox::default()
+    };
+    let sent = DigestService::send_due(app.db(), &failing, tomorrow()).await.unwrap();
+    assert!(sent.is_empty());
+
+    let outbox = Outbox::default();
+    let sent = DigestService::send_due(app.db(), &outbox, tomorrow()).await.unwrap();
+    assert_eq!(sent.len(), 1);
+}
+
+#[tokio::test]
+async fn test_empty_periods_are_skipped() {
+    let (app, _) = digest_app().await;
+    let outbox = Outbox::default();
+
+    // Yesterday had no submissions
+    let sent = DigestService::send_due(app.db(), &outbox, Utc::now().fixed_offset()).await.unwrap();
+    assert!(sent.is_empty());
+}
+
+// ============================================================================
+// Admin routes
+// ============================================================================
+
+#[tokio::test]
+async fn test_unsubscribed_recipients_get_no_digest() {
+    let (app, form) = digest_app().await;
+    let base = format!("/api/admin/forms/{}/digest", form.id);
+
+    for email in ["owner@example.com", "team@example.com"] {
+        let response = app
+            .put_json(
+                &format!("{base}/recipients/{email}"),
+                &serde_json::json!({ "subscribed": false }),
+            )
+            .await;
+        response.assert_status(StatusCode::OK);
+    }
+    let json: serde_json::Value = app.get(&base).await.json();
+    assert_eq!(json["data"]["recipients"], serde_json::json!([]));
+
+    let outbox = Outbox::default();
+    let sent = DigestService::send_due(app.db(), &outbox, tomorrow()).await.unwrap();
+    assert!(sent.is_empty());
+
+    let response = app
+        .put_json(
+            &format!("{base}/recipients/owner@example.com"),
+            &serde_json::json!({ "subscribed": true }),
+        )
+        .await;
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["recipients"][0]["subscribed"], true);
+    assert_eq!(json["data"]["recipients"][1]["subscribed"], false);
+
+    let sent = DigestService::send_due(app.db(), &outbox, tomorrow()).await.unwrap();
+    assert_eq!(sent[0].recipients, vec!["owner@example.com"]);
+}
+
+#[tokio::test]
+async fn test_unknown_recipient_is_not_found() {
+    let (app, form) = digest_app().await;
+
+    let response = app
+        .put_json(
+            &format!("/api/admin/forms/{}/digest/recipients/nobody@example.com", form.id),
+            &serde_json::json!({ "subscribed": false }),
+        )
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    response.assert_api_error("DIGEST_RECIPIENT_NOT_FOUND");
+}
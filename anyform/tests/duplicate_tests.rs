@@ -0,0 +1,187 @@
+//! Tests for one-response-per-browser duplicate prevention.
+
+mod common;
+
+use anyform::duplicates::{DuplicateGuard, BROWSER_COOKIE, RESPONDED_HEADER};
+use anyform::schema::FormSettings;
+use anyform::SubmissionEntity;
+use axum::body::Body;
+use common::app::TestResponse;
+use common::{contact_form, create_test_form, TestApp};
+use http::{header, Request, StatusCode};
+
+const URI: &str = "/api/forms/test-contact";
+
+async fn guarded_app(guard: DuplicateGuard) -> (TestApp, anyform::Form) {
+    let app = TestApp::with_admin().await;
+    let settings = FormSettings::new().duplicate_guard(guard);
+    let form = create_test_form(app.db(), contact_form().settings(settings)).await;
+    (app, form)
+}
+
+fn answers() -> serde_json::Value {
+    serde_json::json!({
+        "name": "Ada",
+        "email": "ada@example.com",
+        "message": "Hello there"
+    })
+}
+
+/// Returns the `Cookie` header a browser would send back after `response`.
+fn cookies(response: &TestResponse) -> String {
+    response
+        .headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|c| c.to_str().unwrap().split(';').next().unwrap().to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+async fn submit(app: &TestApp, extra: Option<(&str, &str)>) -> TestResponse {
+    let mut request = Request::builder()
+        .method("POST")
+        .uri(URI)
+        .header(header::CONTENT_TYPE, "application/json");
+    if let Some((name, value)) = extra {
+        request = request.header(name, value);
+    }
+    app.send_raw(request.body(Body::from(answers().to_string())).unwrap())
+        .await
+}
+
+// ============================================================================
+// Submissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_second_response_from_a_browser_is_rejected() {
+    let (app, form) = guarded_app(DuplicateGuard::one_per_browser()).await;
+
+    let first = submit(&app, None).await;
+    first.assert_status(StatusCode::CREATED);
+    let cookie = cookies(&first);
+    assert!(cookie.contains(&format!("{BROWSER_COOKIE}=")));
+    assert!(cookie.contains(&format!("af_responded_{}=", form.id.simple())));
+
+    let again = submit(&app, Some(("cookie", &cookie))).await;
+    again.assert_status(StatusCode::CONFLICT);
+    again.assert_api_error("ALREADY_RESPONDED");
+
+    // Without cookies the guard can't tell
+    submit(&app, None).await.assert_status(StatusCode::CREATED);
+
+    let subs = SubmissionEntity::find_by_form(app.db(), form.id).await.unwrap();
+    assert_eq!(subs.len(), 2);
+    assert!(subs.iter().all(|s| s.metadata.as_ref().unwrap()["browser_id"].is_string()));
+}
+
+#[tokio::test]
+async fn test_duplicate_token_is_accepted_from_a_header() {
+    let (app, _) = guarded_app(DuplicateGuard::one_per_browser()).await;
+
+    let first = submit(&app, None).await;
+    let body: serde_json::Value = first.json();
+    let token = body["data"]["duplicate_token"].as_str().unwrap().to_string();
+
+    submit(&app, Some((RESPONDED_HEADER, &token)))
+        .await
+        .assert_api_error("ALREADY_RESPONDED");
+    submit(&app, Some((RESPONDED_HEADER, "forged.token")))
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_forms_without_a_guard_set_no_cookies() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), contact_form()).await;
+
+    let response = submit(&app, None).await;
+    response.assert_status(StatusCode::CREATED);
+    assert!(response.headers.get(header::SET_COOKIE).is_none());
+    let body: serde_json::Value = response.json();
+    assert!(body["data"].get("duplicate_token").is_none());
+}
+
+// ============================================================================
+// Pages
+// ============================================================================
+
+#[tokio::test]
+async fn test_form_page_tells_browsers_they_already_responded() {
+    let guard = DuplicateGuard::one_per_browser().message("Thanks, your vote is in!");
+    let (app, _) = guarded_app(guard).await;
+
+    let page = app.get(URI).await;
+    page.assert_status(StatusCode::OK);
+    assert!(page.text().contains("<form"));
+    assert!(cookies(&page).contains(&format!("{BROWSER_COOKIE}=")));
+
+    let cookie = cookies(&submit(&app, None).await);
+    let request = Request::get(URI)
+        .header(header::COOKIE, &cookie)
+        .body(Body::empty())
+        .unwrap();
+    let page = app.send_raw(request).await;
+    page.assert_status(StatusCode::OK);
+    assert!(page.text().contains("data-af-responded"));
+    assert!(page.text().contains("Thanks, your vote is in!"));
+    assert!(!page.text().contains("<form"));
+
+    let request = Request::post(format!("{URI}/submit"))
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(header::COOKIE, &cookie)
+        .body(Body::from("name=Ada&email=ada%40example.com&message=Hello+there"))
+        .unwrap();
+    let page = app.send_raw(request).await;
+    assert!(page.text().contains("data-af-responded"));
+}
+
+// ============================================================================
+// Admin override list
+// ============================================================================
+
+#[tokio::test]
+async fn test_allowed_browsers_may_respond_again() {
+    let (app, form) = guarded_app(DuplicateGuard::one_per_browser()).await;
+
+    let cookie = cookies(&submit(&app, None).await);
+    let subs = SubmissionEntity::find_by_form(app.db(), form.id).await.unwrap();
+    let browser_id = subs[0].metadata.as_ref().unwrap()["browser_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let uri = format!("/api/admin/forms/{}/duplicate-guard/browsers/{browser_id}", form.id);
+    let response = app.put_json(&uri, &serde_json::json!({ "allowed": true })).await;
+    response.assert_status(StatusCode::OK);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["data"]["allowed_browsers"][0], browser_id.as_str());
+
+    submit(&app, Some(("cookie", &cookie)))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    app.put_json(&uri, &serde_json::json!({ "allowed": false }))
+        .await
+        .assert_status(StatusCode::OK);
+    submit(&app, Some(("cookie", &cookie)))
+        .await
+        .assert_api_error("ALREADY_RESPONDED");
+}
+
+#[tokio::test]
+async fn test_override_list_needs_a_guarded_form() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let uri = format!(
+        "/api/admin/forms/{}/duplicate-guard/browsers/{}",
+        form.id,
+        uuid::Uuid::new_v4()
+    );
+    app.put_json(&uri, &serde_json::json!({ "allowed": true }))
+        .await
+        .assert_api_error("INVALID_DATA");
+}
@@ -0,0 +1,163 @@
+//! Tests for encrypting answers to sensitive fields at rest.
+
+mod common;
+
+use anyform::encryption::is_encrypted;
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, EncryptionKey,
+    FieldClassification, FormSettings, SubmissionEntity,
+};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+
+const URI: &str = "/api/forms/patients";
+
+fn patient_form() -> CreateFormInput {
+    CreateFormInput::new("Patients", "patients").step(CreateStepInput::new("Main").fields(vec![
+        CreateFieldInput::new("name", "Name", "text"),
+        CreateFieldInput::new("ssn", "Social security number", "text")
+            .required()
+            .classification(FieldClassification::Sensitive),
+        CreateFieldInput::new("conditions", "Conditions", "checkbox_group")
+            .classification(FieldClassification::Sensitive)
+            .option(CreateOptionInput::new("Asthma", "asthma"))
+            .option(CreateOptionInput::new("Diabetes", "diabetes")),
+    ]))
+}
+
+async fn app_with_key() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .encryption_key(EncryptionKey::new([7; 32]))
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+async fn stored_data(app: &TestApp) -> Vec<Value> {
+    SubmissionEntity::find()
+        .all(app.db())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.data)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_sensitive_answers_are_stored_encrypted() {
+    let app = app_with_key().await;
+    create_test_form(app.db(), patient_form()).await;
+
+    let answers = json!({ "name": "Ada", "ssn": "078-05-1120", "conditions": ["asthma"] });
+    app.post_json(URI, &answers).await.assert_status(StatusCode::CREATED);
+
+    let stored = stored_data(&app).await;
+    assert_eq!(stored[0]["name"], "Ada");
+    for field in ["ssn", "conditions"] {
+        let value = stored[0][field].as_str().unwrap();
+        assert!(is_encrypted(value), "{field}: {value}");
+        assert!(!value.contains("078-05-1120") && !value.contains("asthma"));
+    }
+}
+
+#[tokio::test]
+async fn test_admin_reads_and_exports_decrypted() {
+    let app = app_with_key().await;
+    let form = create_test_form(app.db(), patient_form()).await;
+    let answers =
+        json!({ "name": "Ada", "ssn": "078-05-1120", "conditions": ["asthma", "diabetes"] });
+    let response = app.post_json(URI, &answers).await;
+    response.assert_status(StatusCode::CREATED);
+    let id = response.json::<Value>()["data"]["submission_id"].as_str().unwrap().to_string();
+
+    let base = format!("/api/admin/forms/{}/submissions", form.id);
+    let sub: Value = app.get(&format!("{base}/{id}")).await.json();
+    assert_eq!(sub["data"]["data"], answers);
+    let list: Value = app.get(&base).await.json();
+    assert_eq!(list["data"]["submissions"][0]["data"], answers);
+
+    let csv = app.get(&format!("{base}/export?include_sensitive=true")).await.text();
+    assert!(csv.contains("078-05-1120"));
+    assert!(!csv.contains("enc:v1:"));
+    // Left out by default, as before
+    let csv = app.get(&format!("{base}/export")).await.text();
+    assert!(!csv.contains("078-05-1120"));
+}
+
+#[tokio::test]
+async fn test_steps_are_saved_encrypted_and_resumed_decrypted() {
+    let app = app_with_key().await;
+    create_test_form(app.db(), patient_form()).await;
+
+    let response = app.post_json(&format!("{URI}/submissions/start"), &json!({})).await;
+    response.assert_status(StatusCode::CREATED);
+    let id = response.json::<Value>()["data"]["submission_id"].as_str().unwrap().to_string();
+    let response = app
+        .patch_json(
+            &format!("{URI}/submissions/{id}/step"),
+            &json!({ "data": { "name": "Ada", "ssn": "078-05-1120" } }),
+        )
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["data"]["data"]["ssn"], "078-05-1120");
+    assert!(is_encrypted(stored_data(&app).await[0]["ssn"].as_str().unwrap()));
+
+    let resumed: Value = app.get(&format!("{URI}/submissions/{id}")).await.json();
+    assert_eq!(resumed["data"]["data"]["ssn"], "078-05-1120");
+
+    // The saved answers validate decrypted, and stay encrypted once complete
+    app.post_json(&format!("{URI}/submissions/{id}/complete"), &json!({}))
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert!(is_encrypted(stored_data(&app).await[0]["ssn"].as_str().unwrap()));
+}
+
+#[tokio::test]
+async fn test_sensitive_answers_are_scored_as_submitted() {
+    let app = app_with_key().await;
+    let quiz = CreateFormInput::new("Checkup", "checkup")
+        .settings(FormSettings::new().is_quiz(true))
+        .step(CreateStepInput::new("Main").field(
+            CreateFieldInput::new("pulse", "Resting pulse", "text")
+                .classification(FieldClassification::Sensitive)
+                .correct_answer("60")
+                .points(5),
+        ));
+    create_test_form(app.db(), quiz).await;
+
+    app.post_json("/api/forms/checkup", &json!({ "pulse": "60" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+    let sub = SubmissionEntity::find().one(app.db()).await.unwrap().unwrap();
+    assert_eq!(sub.score, Some(5));
+    assert!(is_encrypted(sub.data["pulse"].as_str().unwrap()));
+}
+
+#[tokio::test]
+async fn test_answers_that_look_encrypted_are_encrypted_too() {
+    let app = app_with_key().await;
+    create_test_form(app.db(), patient_form()).await;
+
+    let answers = json!({ "ssn": "enc:v1:078-05-1120" });
+    app.post_json(URI, &answers).await.assert_status(StatusCode::CREATED);
+
+    let stored = stored_data(&app).await[0]["ssn"].as_str().unwrap().to_string();
+    assert_ne!(stored, "enc:v1:078-05-1120");
+    let key = EncryptionKey::new([7; 32]);
+    assert_eq!(key.decrypt("ssn", &stored), Some(json!("enc:v1:078-05-1120")));
+}
+
+#[tokio::test]
+async fn test_without_a_key_answers_are_stored_as_given() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), patient_form()).await;
+
+    app.post_json(URI, &json!({ "ssn": "078-05-1120" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert_eq!(stored_data(&app).await[0]["ssn"], "078-05-1120");
+}
@@ -0,0 +1,265 @@
+//! Tests for single-field validation and server-side field validators.
+
+mod common;
+
+use std::collections::HashMap;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, Field, FieldValidator, FieldValue, Form,
+    ValidationRules, ValidatorRegistry,
+};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+
+/// Rejects usernames that are already taken.
+struct TakenUsernames;
+
+#[async_trait::async_trait]
+impl FieldValidator for TakenUsernames {
+    async fn validate(
+        &self,
+        _form: &Form,
+        field: &Field,
+        value: &FieldValue,
+        _values: &HashMap<String, FieldValue>,
+    ) -> Vec<String> {
+        match (field.name.as_str(), value.as_str()) {
+            ("username", Some("admin")) => vec!["That username is taken".to_string()],
+            _ => vec![],
+        }
+    }
+}
+
+/// Rejects emails already on the list.
+struct KnownEmails(Vec<&'static str>);
+
+#[async_trait::async_trait]
+impl FieldValidator for KnownEmails {
+    async fn validate(
+        &self,
+        _form: &Form,
+        _field: &Field,
+        value: &FieldValue,
+        _values: &HashMap<String, FieldValue>,
+    ) -> Vec<String> {
+        match value.as_str() {
+            Some(email) if self.0.contains(&email) => vec!["Already registered".to_string()],
+            _ => vec![],
+        }
+    }
+}
+
+/// Rejects addresses at example.com.
+struct NoExamples;
+
+#[async_trait::async_trait]
+impl FieldValidator for NoExamples {
+    async fn validate(
+        &self,
+        _form: &Form,
+        _field: &Field,
+        value: &FieldValue,
+        _values: &HashMap<String, FieldValue>,
+    ) -> Vec<String> {
+        match value.as_str() {
+            Some(email) if email.ends_with("@example.com") => vec!["Use a real address".into()],
+            _ => vec![],
+        }
+    }
+}
+
+fn signup_form() -> CreateFormInput {
+    CreateFormInput::new("Signup", "signup").step(
+        CreateStepInput::new("Main")
+            .field(
+                CreateFieldInput::new("username", "Username", "text")
+                    .required()
+                    .validation(ValidationRules::new().min_length(3).server_validate(true)),
+            )
+            .field(CreateFieldInput::new("email", "Email", "email")),
+    )
+}
+
+async fn signup_app() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .field_validator(TakenUsernames)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), signup_form()).await;
+    app
+}
+
+async fn validate(app: &TestApp, field: &str, body: serde_json::Value) -> serde_json::Value {
+    let response = app
+        .post_json(&format!("/api/v1/forms/signup/fields/{field}/validate"), &body)
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.json()
+}
+
+#[tokio::test]
+async fn test_validates_builtin_rules() {
+    let app = signup_app().await;
+
+    let json = validate(&app, "username", serde_json::json!({ "value": "ab" })).await;
+    assert_eq!(json["data"]["field"], "username");
+    assert_eq!(json["data"]["valid"], false);
+    assert_eq!(json["data"]["errors"].as_array().unwrap().len(), 1);
+
+    let json = validate(&app, "email", serde_json::json!({ "value": "not-an-email" })).await;
+    assert_eq!(json["data"]["valid"], false);
+
+    let json = validate(&app, "email", serde_json::json!({})).await;
+    assert_eq!(json["data"]["valid"], true);
+}
+
+#[tokio::test]
+async fn test_runs_field_validator() {
+    let app = signup_app().await;
+
+    let json = validate(&app, "username", serde_json::json!({ "value": "admin", "seq": 7 })).await;
+    assert_eq!(json["data"]["valid"], false);
+    assert_eq!(json["data"]["errors"][0], "That username is taken");
+    assert_eq!(json["data"]["seq"], 7);
+
+    let json = validate(&app, "username", serde_json::json!({ "value": "ada" })).await;
+    assert_eq!(json["data"]["valid"], true);
+    assert!(json["data"].get("seq").is_none());
+}
+
+#[tokio::test]
+async fn test_field_validator_runs_on_submit() {
+    let app = signup_app().await;
+
+    let response = app
+        .post_json("/api/forms/signup", &serde_json::json!({ "username": "admin" }))
+        .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_api_error("VALIDATION_FAILED");
+    response.assert_body_contains("That username is taken");
+
+    app.post_form("/api/forms/signup/submit", &[("username", "admin")])
+        .await
+        .assert_status(StatusCode::OK)
+        .assert_body_contains("That username is taken");
+
+    app.post_json("/api/forms/signup", &serde_json::json!({ "username": "ada" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_unknown_field_is_not_found() {
+    let app = signup_app().await;
+
+    let response = app
+        .post_json(
+            "/api/v1/forms/signup/fields/nope/validate",
+            &serde_json::json!({ "value": "x" }),
+        )
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    response.assert_api_error("FIELD_NOT_FOUND");
+}
+
+// ============================================================================
+// Named validators
+// ============================================================================
+
+fn newsletter_form() -> CreateFormInput {
+    CreateFormInput::new("Newsletter", "newsletter").step(
+        CreateStepInput::new("Main")
+            .field(
+                CreateFieldInput::new("email", "Email", "email").required().validation(
+                    ValidationRules::new().validator("unique_email").validator("no_examples"),
+                ),
+            )
+            .field(
+                CreateFieldInput::new("backup", "Backup email", "email")
+                    .validation(ValidationRules::new().validator("missing")),
+            ),
+    )
+}
+
+async fn newsletter_app() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .validators(ValidatorRegistry::new().register("no_examples", NoExamples))
+        .validator("unique_email", KnownEmails(vec!["ada@example.com", "grace@navy.mil"]))
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), newsletter_form()).await;
+    app
+}
+
+#[tokio::test]
+async fn test_named_validators_run_in_order() {
+    let app = newsletter_app().await;
+    let uri = "/api/v1/forms/newsletter/fields/email/validate";
+
+    let json: serde_json::Value =
+        app.post_json(uri, &serde_json::json!({ "value": "ada@example.com" })).await.json();
+    assert_eq!(
+        json["data"]["errors"],
+        serde_json::json!(["Already registered", "Use a real address"])
+    );
+
+    let json: serde_json::Value =
+        app.post_json(uri, &serde_json::json!({ "value": "bob@example.com" })).await.json();
+    assert_eq!(json["data"]["errors"], serde_json::json!(["Use a real address"]));
+
+    // Built-in rules fail first, without calling the validators
+    let json: serde_json::Value =
+        app.post_json(uri, &serde_json::json!({ "value": "nope" })).await.json();
+    assert_eq!(json["data"]["valid"], false);
+    assert!(!json.to_string().contains("Already registered"));
+}
+
+#[tokio::test]
+async fn test_named_validators_run_on_submit() {
+    let app = newsletter_app().await;
+
+    let response = app
+        .post_json("/api/forms/newsletter", &serde_json::json!({ "email": "grace@navy.mil" }))
+        .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_body_contains("Already registered");
+
+    // Unregistered validators are skipped
+    app.post_json(
+        "/api/forms/newsletter",
+        &serde_json::json!({ "email": "alan@bletchley.uk", "backup": "alan@example.org" }),
+    )
+    .await
+    .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_named_validators_mark_fields_for_server_checks() {
+    let app = newsletter_app().await;
+
+    let html = app.get("/api/forms/newsletter").await.text();
+    assert!(html.contains("serverValidate"), "{html}");
+
+    let json: serde_json::Value = app.get("/api/forms/newsletter/json").await.json();
+    assert_eq!(
+        json["steps"][0]["fields"][0]["validation"]["validators"],
+        serde_json::json!(["unique_email", "no_examples"])
+    );
+}
+
+#[test]
+fn test_registry_lists_names() {
+    let registry = ValidatorRegistry::new()
+        .register("no_examples", NoExamples)
+        .register("unique_email", KnownEmails(vec![]));
+
+    assert_eq!(registry.names(), ["no_examples", "unique_email"]);
+    assert!(registry.contains("unique_email"));
+    assert!(!registry.contains("missing"));
+    assert!(!registry.is_empty());
+    assert!(ValidatorRegistry::new().is_empty());
+}
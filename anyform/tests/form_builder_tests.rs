@@ -2,18 +2,29 @@
 
 mod common;
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyform::database::SqliteConfig;
 use anyform::{
     entities::{
         field::Entity as FieldEntity,
         field_option::Entity as FieldOptionEntity,
         form::Entity as FormEntity,
-        step::Entity as StepEntity,
+        form_revision::Entity as FormRevisionEntity,
+        step::{Column as StepColumn, Entity as StepEntity},
     },
     services::{CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FormBuilder},
     schema::{FormSettings, ValidationRules},
+    FormError, MigratorTrait,
 };
 use common::db::TestDb;
-use sea_orm::EntityTrait;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, DatabaseConnection, EntityTrait, QuerySelect, SqlErr,
+};
+use tempfile::TempDir;
+use uuid::Uuid;
 
 async fn setup() -> TestDb {
     TestDb::new().await
@@ -209,7 +220,122 @@ async fn test_duplicate_slug_rejected() {
     let input2 = CreateFormInput::new("Form 2", "duplicate-slug");
     let result = FormBuilder::create(db.conn(), input2).await;
 
-    assert!(result.is_err());
+    assert!(matches!(result, Err(FormError::DuplicateSlug(slug)) if slug == "duplicate-slug"));
+}
+
+#[tokio::test]
+async fn test_update_to_taken_slug_rejected() {
+    let db = setup().await;
+
+    FormBuilder::create(db.conn(), CreateFormInput::new("Form 1", "taken")).await.unwrap();
+    let form = FormBuilder::create(db.conn(), CreateFormInput::new("Form 2", "free"))
+        .await
+        .unwrap();
+
+    let result = FormBuilder::update(db.conn(), form.id, CreateFormInput::new("Form 2", "taken")).await;
+    assert!(matches!(result, Err(FormError::DuplicateSlug(_))));
+}
+
+#[tokio::test]
+async fn test_slug_of_deleted_form_can_be_reused() {
+    let db = setup().await;
+
+    let old = FormBuilder::create(db.conn(), CreateFormInput::new("Old", "reused")).await.unwrap();
+    FormBuilder::soft_delete(db.conn(), old.id).await.unwrap();
+
+    let new = FormBuilder::create(db.conn(), CreateFormInput::new("New", "reused")).await.unwrap();
+    let found = FormBuilder::find_by_slug(db.conn(), "reused").await.unwrap().unwrap();
+    assert_eq!(found.id, new.id);
+
+    // The old form can't come back while the new one holds the slug
+    let result = FormBuilder::restore(db.conn(), old.id).await;
+    assert!(matches!(result, Err(FormError::DuplicateSlug(_))));
+    assert!(FormEntity::find_by_id(old.id).one(db.conn()).await.unwrap().unwrap().is_deleted());
+}
+
+#[tokio::test]
+async fn test_index_rejects_active_duplicate_slugs() {
+    let db = setup().await;
+    let form = FormBuilder::create(db.conn(), CreateFormInput::new("Form", "indexed")).await.unwrap();
+
+    // Bypassing FormBuilder's check
+    let now = chrono::Utc::now().fixed_offset();
+    let copy = anyform::FormActiveModel {
+        id: ActiveValue::Set(Uuid::new_v4()),
+        name: ActiveValue::Set("Copy".to_string()),
+        slug: ActiveValue::Set(form.slug),
+        description: ActiveValue::Set(None),
+        settings: ActiveValue::Set(None),
+        response_count: ActiveValue::Set(0),
+        status: ActiveValue::Set("published".to_string()),
+        lifecycle_state: ActiveValue::Set(None),
+        created_at: ActiveValue::Set(now),
+        updated_at: ActiveValue::Set(now),
+        deleted_at: ActiveValue::Set(None),
+    };
+    let err = copy.insert(db.conn()).await.unwrap_err();
+    assert!(matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))), "{err}");
+}
+
+/// Counts steps by ID alone, so it works at any schema version.
+async fn step_count(db: &DatabaseConnection) -> usize {
+    StepEntity::find()
+        .select_only()
+        .column(StepColumn::Id)
+        .into_tuple::<Uuid>()
+        .all(db)
+        .await
+        .unwrap()
+        .len()
+}
+
+#[tokio::test]
+async fn test_slug_migration_keeps_child_rows() {
+    let db = setup().await;
+    anyform::seed_all(db.conn()).await.unwrap();
+    let steps = step_count(db.conn()).await;
+
+    // Rebuilding af_forms (m20250101_000016) must not cascade to its children
+    let since = anyform::Migrator::migrations()
+        .iter()
+        .rev()
+        .position(|m| m.name().starts_with("m20250101_000016"))
+        .unwrap();
+    anyform::Migrator::down(db.conn(), Some(since as u32 + 1)).await.unwrap();
+    assert_eq!(step_count(db.conn()).await, steps);
+    anyform::Migrator::up(db.conn(), None).await.unwrap();
+    assert_eq!(step_count(db.conn()).await, steps);
+
+    let report = anyform::ConsistencyService::check(db.conn()).await.unwrap();
+    assert!(report.is_clean(), "{:?}", report.issues);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_creates_with_same_slug() {
+    let dir = TempDir::new().unwrap();
+    let url = format!("sqlite:{}?mode=rwc", dir.path().join("anyform.db").display());
+    let db = SqliteConfig::new().connect(&url).await.unwrap();
+    anyform::Migrator::up(&db, None).await.unwrap();
+
+    let creates: Vec<_> = (0..8)
+        .map(|i| {
+            let db = db.clone();
+            tokio::spawn(async move {
+                FormBuilder::create(&db, CreateFormInput::new(format!("Form {i}"), "race")).await
+            })
+        })
+        .collect();
+
+    let mut created = 0;
+    for create in creates {
+        match create.await.unwrap() {
+            Ok(_) => created += 1,
+            Err(FormError::DuplicateSlug(slug)) => assert_eq!(slug, "race"),
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+    assert_eq!(created, 1);
+    assert_eq!(FormEntity::find_active(&db).await.unwrap().len(), 1);
 }
 
 // ============================================================================
@@ -255,6 +381,34 @@ async fn test_update_form() {
     assert_eq!(fields[1].name, "new_field2");
 }
 
+#[tokio::test]
+async fn test_create_and_update_record_revisions() {
+    let db = setup().await;
+
+    let input = CreateFormInput::new("Revisioned", "revision-test")
+        .step(CreateStepInput::new("Step 1").field(
+            CreateFieldInput::new("field1", "Field 1", "text"),
+        ));
+    let form = FormBuilder::create(db.conn(), input).await.unwrap();
+
+    let update_input = CreateFormInput::new("Revisioned", "revision-test")
+        .step(CreateStepInput::new("Step 1").field(
+            CreateFieldInput::new("field1", "Renamed Field", "text"),
+        ));
+    FormBuilder::update(db.conn(), form.id, update_input)
+        .await
+        .unwrap();
+
+    let revisions = FormRevisionEntity::find_by_form(db.conn(), form.id)
+        .await
+        .unwrap();
+    assert_eq!(revisions.len(), 2);
+    assert_eq!(revisions[0].revision, 1);
+    assert_eq!(revisions[1].revision, 2);
+    assert_eq!(revisions[0].fields()[0].label, "Field 1");
+    assert_eq!(revisions[1].fields()[0].label, "Renamed Field");
+}
+
 // ============================================================================
 // Form Deletion
 // ============================================================================
@@ -429,3 +583,84 @@ async fn test_validation_rules_stored() {
     assert_eq!(rules.max_length, Some(20));
     assert_eq!(rules.pattern, Some("^[a-z0-9_]+$".to_string()));
 }
+
+// ============================================================================
+// Batch Inserts
+// ============================================================================
+
+/// Builds a form with `steps` steps of `fields` select fields, each with
+/// three options.
+fn large_form(slug: &str, steps: usize, fields: usize) -> CreateFormInput {
+    let mut input = CreateFormInput::new("Large Form", slug);
+    for s in 0..steps {
+        let mut step = CreateStepInput::new(format!("Step {s}"));
+        for f in 0..fields {
+            let mut field = CreateFieldInput::new(format!("field_{s}_{f}"), "Pick one", "select");
+            for o in ["a", "b", "c"] {
+                field = field.option(CreateOptionInput::new(o.to_uppercase(), o));
+            }
+            step = step.field(field);
+        }
+        input = input.step(step);
+    }
+    input
+}
+
+/// Returns a fresh database and a counter of the statements it runs.
+async fn counted_db() -> (DatabaseConnection, Arc<AtomicUsize>) {
+    let mut db = setup().await.db;
+    let statements = Arc::new(AtomicUsize::new(0));
+    let counter = statements.clone();
+    db.set_metric_callback(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    (db, statements)
+}
+
+#[tokio::test]
+async fn test_create_batches_inserts() {
+    let (db, statements) = counted_db().await;
+
+    FormBuilder::create(&db, large_form("small", 1, 50)).await.unwrap();
+    let small = statements.swap(0, Ordering::SeqCst);
+
+    let started = Instant::now();
+    let form = FormBuilder::create(&db, large_form("large", 10, 50)).await.unwrap();
+    let elapsed = started.elapsed();
+    let large = statements.swap(0, Ordering::SeqCst);
+
+    // One insert per table, however many rows
+    assert_eq!(large, small);
+    assert!(large < 10, "{large} statements");
+    assert!(elapsed < Duration::from_secs(5), "took {elapsed:?}");
+
+    let steps = StepEntity::find_by_form(&db, form.id).await.unwrap();
+    assert_eq!(steps.len(), 10);
+    assert_eq!(steps[3].order, 3);
+    let fields = FieldEntity::find_by_step(&db, steps[3].id).await.unwrap();
+    assert_eq!(fields.len(), 50);
+    assert_eq!(fields[49].name, "field_3_49");
+    assert_eq!(fields[49].order, 49);
+    let options = FieldOptionEntity::find_by_field(&db, fields[49].id).await.unwrap();
+    let values: Vec<_> = options.iter().map(|o| o.value.as_str()).collect();
+    assert_eq!(values, ["a", "b", "c"]);
+    assert_eq!(FieldOptionEntity::find().all(&db).await.unwrap().len(), 1650);
+}
+
+#[tokio::test]
+async fn test_update_batches_inserts() {
+    let (db, statements) = counted_db().await;
+    // Created as first updated, so both updates keep the first step as is
+    let form = FormBuilder::create(&db, large_form("large", 1, 50)).await.unwrap();
+
+    statements.store(0, Ordering::SeqCst);
+    FormBuilder::update(&db, form.id, large_form("large", 1, 50)).await.unwrap();
+    let small = statements.swap(0, Ordering::SeqCst);
+
+    FormBuilder::update(&db, form.id, large_form("large", 10, 50)).await.unwrap();
+    let large = statements.swap(0, Ordering::SeqCst);
+
+    assert_eq!(large, small);
+    let fields = FieldEntity::find().all(&db).await.unwrap();
+    assert_eq!(fields.len(), 500);
+}
@@ -0,0 +1,145 @@
+//! Tests for duplicating forms.
+
+mod common;
+
+use anyform::{
+    ConditionRule, CreateFieldInput, CreateFormInput, CreateOptionInput, CreateResultInput,
+    CreateStepInput, FormBuilder, FormStatus, FormsRepository, JumpRule, ResultEntity,
+    SeaOrmRepository, UiOptions,
+};
+use common::{create_test_form, quiz_form, TestApp, TestDb};
+use http::StatusCode;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn survey() -> CreateFormInput {
+    let mut input = quiz_form()
+        .step(
+            CreateStepInput::new("Follow-up")
+                .condition(r#"{"field": "q1", "op": "eq", "value": "4"}"#)
+                .jump(JumpRule::end_form().when(ConditionRule::eq("again", "no")))
+                .fields(vec![
+                    CreateFieldInput::new("again", "Again?", "radio")
+                        .option(CreateOptionInput::new("Yes", "yes"))
+                        .option(CreateOptionInput::new("No", "no")),
+                    CreateFieldInput::new("why", "Why?", "textarea")
+                        .ui(UiOptions::new().condition(ConditionRule::eq("again", "no"))),
+                    CreateFieldInput::new("guests", "Guests", "repeater").fields(vec![
+                        CreateFieldInput::new("name", "Name", "text").required(),
+                        CreateFieldInput::new("meal", "Meal", "select")
+                            .option(CreateOptionInput::new("Fish", "fish").capacity(10)),
+                    ]),
+                ]),
+        )
+        .result(CreateResultInput::new("ace", "Ace").min_score(20));
+    input.slug = "survey".to_string();
+    input
+}
+
+/// Returns what a form's steps hold, without their IDs.
+async fn outline(db: &TestDb, form_id: Uuid) -> Value {
+    let steps = SeaOrmRepository::new(db.conn().clone())
+        .load_steps_with_fields(form_id)
+        .await
+        .unwrap();
+    let field = |f: &anyform::repository::FieldWithOptions| {
+        json!({
+            "name": f.field.name,
+            "label": f.field.label,
+            "type": f.field.field_type,
+            "required": f.field.required,
+            "ui": f.field.ui_options,
+            "correct_answer": f.field.correct_answer,
+            "points": f.field.points,
+            "options": f.options.iter().map(|o| {
+                json!([o.label, o.value, o.is_correct, o.points, o.capacity])
+            }).collect::<Vec<_>>(),
+        })
+    };
+    steps
+        .iter()
+        .map(|s| {
+            json!({
+                "name": s.step.name,
+                "order": s.step.order,
+                "condition": s.step.condition,
+                "next": s.step.next,
+                "fields": s.fields.iter().map(|f| {
+                    let mut json = field(f);
+                    json["fields"] = f.fields.iter().map(field).collect();
+                    json
+                }).collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_duplicate_copies_the_whole_form() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), survey()).await;
+
+    let copy = FormBuilder::duplicate(db.conn(), form.id).await.unwrap();
+    assert_ne!(copy.id, form.id);
+    assert_eq!(copy.slug, "survey-copy");
+    assert_eq!(copy.name, "Test Quiz (copy)");
+    assert_eq!(copy.status(), FormStatus::Draft);
+    assert_eq!(copy.settings, form.settings);
+    assert!(copy.settings().is_quiz);
+
+    let original = outline(&db, form.id).await;
+    assert_eq!(outline(&db, copy.id).await, original);
+    assert_eq!(original.as_array().unwrap().len(), 2);
+    assert_eq!(original[1]["fields"][2]["fields"][1]["options"][0][4], 10);
+
+    let results = ResultEntity::find_by_form(db.conn(), copy.id).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!((results[0].key.as_str(), results[0].min_score), ("ace", Some(20)));
+}
+
+#[tokio::test]
+async fn test_duplicate_picks_a_free_slug() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), survey()).await;
+
+    let slugs = [
+        FormBuilder::duplicate(db.conn(), form.id).await.unwrap().slug,
+        FormBuilder::duplicate(db.conn(), form.id).await.unwrap().slug,
+        FormBuilder::duplicate(db.conn(), form.id).await.unwrap().slug,
+    ];
+    assert_eq!(slugs, ["survey-copy", "survey-copy-2", "survey-copy-3"]);
+
+    // Slugs of deleted forms are free again
+    let second = FormBuilder::find_by_slug(db.conn(), "survey-copy-2").await.unwrap().unwrap();
+    FormBuilder::soft_delete(db.conn(), second.id).await.unwrap();
+    let again = FormBuilder::duplicate(db.conn(), form.id).await.unwrap();
+    assert_eq!(again.slug, "survey-copy-2");
+}
+
+#[tokio::test]
+async fn test_duplicate_endpoint() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), survey()).await;
+    app.post_json("/api/forms/survey", &json!({ "q1": "4", "q2": "paris" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = app
+        .post_json(&format!("/api/admin/forms/{}/duplicate", form.id), &json!({}))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let json: Value = response.json();
+    assert_eq!(json["data"]["slug"], "survey-copy");
+    let id: Uuid = json["data"]["id"].as_str().unwrap().parse().unwrap();
+
+    // A draft without the original's submissions
+    app.get("/api/forms/survey-copy").await.assert_status(StatusCode::NOT_FOUND);
+    let copy = FormBuilder::find_by_id(app.db(), id).await.unwrap().unwrap();
+    assert_eq!(copy.response_count, 0);
+    let submissions: Value = app.get(&format!("/api/admin/forms/{id}/submissions")).await.json();
+    assert_eq!(submissions["data"]["submissions"], json!([]));
+
+    app.post_json(&format!("/api/admin/forms/{}/duplicate", Uuid::new_v4()), &json!({}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,158 @@
+//! Tests for resolving public routes' forms with a custom `FormResolver`.
+
+mod common;
+
+use std::collections::HashMap;
+
+use anyform::entities::form;
+use anyform::repository::FormsRepository;
+use anyform::{FormError, FormResolver};
+use async_trait::async_trait;
+use axum::body::Body;
+use common::app::TestResponse;
+use common::{contact_form, create_test_form, sample_submission_data, TestApp, TestDb};
+use http::{header, HeaderMap, Request, StatusCode};
+
+/// Resolves campaign codes within the tenant the `X-Tenant` header names.
+struct Campaigns(HashMap<(&'static str, &'static str), &'static str>);
+
+#[async_trait]
+impl FormResolver for Campaigns {
+    async fn resolve(
+        &self,
+        key: &str,
+        headers: &HeaderMap,
+        repository: &dyn FormsRepository,
+    ) -> Result<Option<form::Model>, FormError> {
+        let Some(tenant) = headers.get("x-tenant").and_then(|h| h.to_str().ok()) else {
+            return Ok(None);
+        };
+        match self.0.get(&(tenant, key)) {
+            Some(slug) => repository.find_form_by_slug(slug).await,
+            None => Ok(None),
+        }
+    }
+}
+
+async fn app() -> TestApp {
+    let test_db = TestDb::new().await;
+    let campaigns = Campaigns(HashMap::from([(("acme", "spring"), "test-contact")]));
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .form_resolver(campaigns)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), contact_form()).await;
+    app
+}
+
+async fn send(
+    app: &TestApp,
+    method: &str,
+    uri: &str,
+    tenant: Option<&str>,
+    content_type: &str,
+    body: impl Into<Body>,
+) -> TestResponse {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, content_type);
+    if let Some(tenant) = tenant {
+        request = request.header("X-Tenant", tenant);
+    }
+    app.send_raw(request.body(body.into()).unwrap()).await
+}
+
+async fn get(app: &TestApp, uri: &str, tenant: Option<&str>) -> TestResponse {
+    send(app, "GET", uri, tenant, "text/plain", Body::empty()).await
+}
+
+#[tokio::test]
+async fn test_custom_key_renders_form_posting_back_to_it() {
+    let app = app().await;
+
+    let response = get(&app, "/api/forms/spring", Some("acme")).await;
+    response.assert_status(StatusCode::OK);
+    let html = response.text();
+    assert!(html.contains(r#"data-af-form="test-contact""#));
+    assert!(html.contains(r#"action="/api/v1/forms/spring/submit""#));
+
+    let response = get(&app, "/api/v1/forms/spring/json", Some("acme")).await;
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["slug"], "test-contact");
+
+    get(&app, "/api/forms/spring/success", Some("acme"))
+        .await
+        .assert_body_contains("Thank you!");
+}
+
+#[tokio::test]
+async fn test_custom_key_accepts_submissions() {
+    let app = app().await;
+
+    send(
+        &app,
+        "POST",
+        "/api/forms/spring",
+        Some("acme"),
+        "application/json",
+        sample_submission_data().to_string(),
+    )
+    .await
+    .assert_status(StatusCode::CREATED);
+
+    let response = send(
+        &app,
+        "POST",
+        "/api/forms/spring/submit",
+        Some("acme"),
+        "application/x-www-form-urlencoded",
+        "name=Ada&email=ada%40example.com&message=Hi+there",
+    )
+    .await;
+    assert!(response.status.is_redirection(), "{}", response.status);
+    assert_eq!(
+        response.headers.get(header::LOCATION).unwrap(),
+        "/api/v1/forms/spring/success"
+    );
+}
+
+#[tokio::test]
+async fn test_unresolved_key_is_404() {
+    let app = app().await;
+
+    // Another tenant's code, no tenant, and the bare slug all miss
+    get(&app, "/api/forms/spring", Some("globex"))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    get(&app, "/api/forms/spring/json", None)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    get(&app, "/api/forms/test-contact", Some("acme"))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    send(
+        &app,
+        "POST",
+        "/api/forms/summer",
+        Some("acme"),
+        "application/json",
+        sample_submission_data().to_string(),
+    )
+    .await
+    .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_default_resolver_uses_slugs() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), contact_form()).await;
+
+    app.get("/api/forms/test-contact")
+        .await
+        .assert_status(StatusCode::OK)
+        .assert_body_contains(r#"action="/api/v1/forms/test-contact/submit""#);
+    app.get("/api/forms/spring").await.assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,112 @@
+//! Tests for form publication status.
+
+mod common;
+
+use anyform::{FormBuilder, FormSettings, FormStatus};
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::StatusCode;
+
+const URI: &str = "/api/forms/test-contact";
+
+// ============================================================================
+// FormBuilder
+// ============================================================================
+
+#[tokio::test]
+async fn test_forms_are_published_unless_created_as_drafts() {
+    let db = TestDb::new().await;
+    let live = create_test_form(db.conn(), contact_form()).await;
+    assert_eq!(live.status(), FormStatus::Published);
+
+    let mut input = contact_form().settings(FormSettings::new().draft(true));
+    input.slug = "draft".to_string();
+    let draft = create_test_form(db.conn(), input).await;
+    assert_eq!(draft.status(), FormStatus::Draft);
+    // The flag only picks the initial status
+    assert!(!draft.settings().draft);
+}
+
+#[tokio::test]
+async fn test_status_transitions() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), contact_form()).await;
+
+    let form = FormBuilder::unpublish(db.conn(), form.id).await.unwrap();
+    assert!(form.is_draft());
+    let form = FormBuilder::publish(db.conn(), form.id).await.unwrap();
+    assert!(form.is_published());
+    let form = FormBuilder::archive(db.conn(), form.id).await.unwrap();
+    assert!(form.is_archived());
+
+    // Updating keeps the status unless the draft flag is set
+    let form = FormBuilder::update(db.conn(), form.id, contact_form()).await.unwrap();
+    assert!(form.is_archived());
+    let input = contact_form().settings(FormSettings::new().draft(true));
+    let form = FormBuilder::update(db.conn(), form.id, input).await.unwrap();
+    assert!(form.is_draft());
+}
+
+#[tokio::test]
+async fn test_status_changes_need_an_active_form() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), contact_form()).await;
+    FormBuilder::soft_delete(db.conn(), form.id).await.unwrap();
+
+    let err = FormBuilder::publish(db.conn(), form.id).await.unwrap_err();
+    assert!(matches!(err, anyform::FormError::NotFound(_)), "{err}");
+}
+
+// ============================================================================
+// Routes
+// ============================================================================
+
+#[tokio::test]
+async fn test_unpublished_forms_are_hidden_from_public_routes() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let body = serde_json::json!({ "name": "Ada", "email": "ada@example.com", "message": "Hi" });
+
+    for action in ["unpublish", "archive"] {
+        app.post_json(&format!("/api/admin/forms/{}/{action}", form.id), &serde_json::json!({}))
+            .await
+            .assert_status(StatusCode::OK);
+
+        app.get(URI).await.assert_status(StatusCode::NOT_FOUND);
+        app.get(&format!("{URI}/json")).await.assert_status(StatusCode::NOT_FOUND);
+        app.post_json(URI, &body).await.assert_status(StatusCode::NOT_FOUND);
+
+        // Still visible to admins
+        app.get(&format!("/api/admin/forms/{}", form.id))
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    let response = app
+        .post_json(&format!("/api/admin/forms/{}/publish", form.id), &serde_json::json!({}))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["status"], "published");
+
+    app.get(URI).await.assert_status(StatusCode::OK);
+    app.post_json(URI, &body).await.assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_admin_form_list_includes_status() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    FormBuilder::archive(app.db(), form.id).await.unwrap();
+
+    let json: serde_json::Value = app.get("/api/admin/forms").await.json();
+    assert_eq!(json["data"]["forms"][0]["status"], "archived");
+}
+
+#[tokio::test]
+async fn test_status_routes_answer_404_for_unknown_forms() {
+    let app = TestApp::with_admin().await;
+    let uri = format!("/api/admin/forms/{}/publish", uuid::Uuid::new_v4());
+    app.post_json(&uri, &serde_json::json!({}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,150 @@
+//! Tests for steps including the fields of other forms.
+
+mod common;
+
+use anyform::entities::submission::Entity as SubmissionEntity;
+use anyform::{
+    ConditionRule, CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, FormSettings,
+    UiOptions,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+
+fn address_block() -> CreateFormInput {
+    CreateFormInput::new("Address", "address-block")
+        .settings(FormSettings::new().draft(true))
+        .step(CreateStepInput::new("Address").fields(vec![
+            CreateFieldInput::new("street", "Street", "text").required(),
+            CreateFieldInput::new("country", "Country", "text").required(),
+            CreateFieldInput::new("state", "State", "text")
+                .required()
+                .ui(UiOptions::new().condition(ConditionRule::eq("country", "US"))),
+        ]))
+}
+
+fn order_form() -> CreateFormInput {
+    CreateFormInput::new("Order", "order")
+        .step(
+            CreateStepInput::new("Shipping")
+                .field(CreateFieldInput::new("name", "Name", "text").required())
+                .include_form("address-block"),
+        )
+        .step(
+            CreateStepInput::new("Billing")
+                .include_form("address-block")
+                .include_as("billing"),
+        )
+}
+
+/// Returns the names of the fields of each step of a form, as rendered.
+async fn field_names(app: &TestApp, slug: &str) -> Vec<Vec<String>> {
+    let response = app.get(&format!("/api/forms/{slug}/json")).await;
+    response.assert_status(StatusCode::OK);
+    let json: Value = response.json();
+    json["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|step| {
+            step["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|f| f["name"].as_str().unwrap().to_string())
+                .collect()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_included_fields_are_namespaced() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), address_block()).await;
+    create_test_form(app.db(), order_form()).await;
+
+    assert_eq!(
+        field_names(&app, "order").await,
+        vec![
+            vec!["name", "address_block.street", "address_block.country", "address_block.state"],
+            vec!["billing.street", "billing.country", "billing.state"],
+        ]
+    );
+    let html = app.get("/api/forms/order").await;
+    html.assert_status(StatusCode::OK);
+    html.assert_body_contains("name=\"billing.street\"");
+}
+
+#[tokio::test]
+async fn test_included_fields_are_validated() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), address_block()).await;
+    create_test_form(app.db(), order_form()).await;
+
+    let mut answers = json!({
+        "name": "Ada",
+        "address_block.street": "1 Main St",
+        "address_block.country": "US",
+        "billing.street": "2 High St",
+        "billing.country": "UK",
+    });
+    // The fragment's condition reads the namespaced country
+    let response = app.post_json("/api/forms/order", &answers).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_body_contains("address_block.state");
+    assert!(!response.text().contains("billing.state"));
+
+    answers["address_block.state"] = json!("NY");
+    let response = app.post_json("/api/forms/order", &answers).await;
+    response.assert_status(StatusCode::CREATED);
+    let submission = SubmissionEntity::find().one(app.db()).await.unwrap().unwrap();
+    assert_eq!(submission.data["billing.street"], "2 High St");
+}
+
+#[tokio::test]
+async fn test_fragment_changes_reach_including_forms() {
+    let app = TestApp::new().await;
+    let fragment = create_test_form(app.db(), address_block()).await;
+    create_test_form(app.db(), order_form()).await;
+
+    let mut input = address_block();
+    input.steps[0].fields.push(CreateFieldInput::new("postcode", "Postcode", "text"));
+    FormBuilder::update(app.db(), fragment.id, input).await.unwrap();
+
+    let names = field_names(&app, "order").await;
+    assert_eq!(names[1].last().unwrap(), "billing.postcode");
+}
+
+#[tokio::test]
+async fn test_invalid_includes_are_rejected() {
+    let app = TestApp::new().await;
+
+    let own = CreateFormInput::new("Loop", "loop")
+        .step(CreateStepInput::new("Main").include_form("loop"));
+    let error = FormBuilder::create(app.db(), own).await.unwrap_err();
+    assert!(error.to_string().contains("includes its own form"));
+
+    let twice = CreateFormInput::new("Twice", "twice")
+        .step(CreateStepInput::new("A").include_form("address-block"))
+        .step(CreateStepInput::new("B").include_form("address-block"));
+    let error = FormBuilder::create(app.db(), twice).await.unwrap_err();
+    assert!(error.to_string().contains("included twice"));
+
+    // Fragments are looked up when the form is loaded
+    create_test_form(app.db(), order_form()).await;
+    let response = app.get("/api/forms/order/json").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    response.assert_body_contains("which does not exist");
+
+    // Fragments including each other
+    let a = CreateFormInput::new("A", "frag-a")
+        .step(CreateStepInput::new("Main").include_form("frag-b"));
+    let b = CreateFormInput::new("B", "frag-b")
+        .step(CreateStepInput::new("Main").include_form("frag-a"));
+    create_test_form(app.db(), a).await;
+    create_test_form(app.db(), b).await;
+    let response = app.get("/api/forms/frag-a/json").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    response.assert_body_contains("includes itself");
+}
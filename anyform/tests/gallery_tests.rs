@@ -0,0 +1,86 @@
+//! Tests for the dev-mode example gallery.
+
+mod common;
+
+use anyform::seed::Seeder;
+use common::{TestApp, TestDb};
+use http::StatusCode;
+use serde_json::json;
+
+async fn dev_app() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .dev_mode(true)
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+// ============================================================================
+// Gallery
+// ============================================================================
+
+#[tokio::test]
+async fn test_gallery_links_every_example() {
+    let app = dev_app().await;
+    Seeder::examples().run(app.db()).await.unwrap();
+
+    let response = app.get("/forms/examples").await;
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("text/html");
+    response.assert_body_contains("Development mode.");
+    for slug in ["contact", "feedback", "quiz", "onboarding", "showcase"] {
+        response
+            .assert_body_contains(&format!("<a href=\"/api/v1/forms/{slug}\">HTML</a>"))
+            .assert_body_contains(&format!("<a href=\"/api/v1/forms/{slug}/json\">JSON</a>"));
+        app.get(&format!("/api/v1/forms/{slug}")).await.assert_status(StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_gallery_points_to_seeding_when_empty() {
+    let app = dev_app().await;
+
+    let response = app.get("/forms/examples").await;
+    response.assert_status(StatusCode::OK);
+    response.assert_body_contains("Run <code>anyform seed</code>");
+    assert!(!response.text().contains("/api/v1/forms/contact/json"));
+}
+
+#[tokio::test]
+async fn test_gallery_is_off_by_default() {
+    let app = TestApp::new().await;
+    app.get("/forms/examples").await.assert_status(StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Showcase form
+// ============================================================================
+
+#[tokio::test]
+async fn test_showcase_form_accepts_a_submission() {
+    let app = dev_app().await;
+    Seeder::examples().run(app.db()).await.unwrap();
+
+    let answers = json!({
+        "username": "ada_l",
+        "email": "ada@example.com",
+        "age": 36,
+        "plan": "pro",
+        "interests": ["surveys", "quizzes"],
+        "source": "other",
+        "source_other": "A meetup",
+        "terms": true,
+        "start_date": "2026-01-05",
+        "stars": 5,
+        "recommend": 9,
+    });
+    app.post_json("/api/v1/forms/showcase", &answers)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let mut invalid = answers;
+    invalid["username"] = json!("Ada Lovelace");
+    let response = app.post_json("/api/v1/forms/showcase", &invalid).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,363 @@
+//! Tests for the GraphQL API.
+
+mod common;
+
+use anyform::admin_auth::BearerTokenAuth;
+use anyform::{AnyFormRouterBuilder, FormBuilder, SubmissionEntity};
+use axum::body::Body;
+use common::{contact_form, create_test_form, options_form, quiz_form, TestApp, TestDb};
+use http::{Request, StatusCode};
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+
+async fn app_with(builder: AnyFormRouterBuilder) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = builder
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .enable_graphql(true)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), contact_form()).await;
+    create_test_form(app.db(), options_form()).await;
+    app
+}
+
+async fn graphql_app() -> TestApp {
+    app_with(anyform::AnyFormRouter::builder()).await
+}
+
+/// Runs a query, returning the whole GraphQL response.
+async fn run(app: &TestApp, uri: &str, query: &str, variables: Value) -> Value {
+    let response = app
+        .post_json(uri, &json!({ "query": query, "variables": variables }))
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.json()
+}
+
+/// Returns the `extensions.code` of every error in a response.
+fn codes(response: &Value) -> Vec<&str> {
+    response["errors"]
+        .as_array()
+        .map(|errors| errors.iter().filter_map(|e| e["extensions"]["code"].as_str()).collect())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+#[tokio::test]
+async fn test_query_form_with_steps_fields_and_options() {
+    let app = graphql_app().await;
+    let query = r#"
+        query Options($slug: String!) {
+          form(slug: $slug) {
+            __typename
+            name
+            steps {
+              name
+              fields { ...FieldParts options { label value } }
+            }
+          }
+        }
+        fragment FieldParts on Field { name fieldType required }
+    "#;
+
+    let response = run(&app, "/api/graphql", query, json!({ "slug": "test-options" })).await;
+    assert!(response.get("errors").is_none(), "{response}");
+    let form = &response["data"]["form"];
+    assert_eq!(form["__typename"], "Form");
+    assert_eq!(form["name"], "Test Options Form");
+    let country = &form["steps"][0]["fields"][0];
+    assert_eq!(
+        country,
+        &json!({
+            "name": "country",
+            "fieldType": "select",
+            "required": true,
+            "options": [
+                { "label": "United States", "value": "us" },
+                { "label": "Canada", "value": "ca" },
+                { "label": "Mexico", "value": "mx" },
+            ],
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_aliases_and_directives() {
+    let app = graphql_app().await;
+    let query = r#"
+        query ($withFields: Boolean!) {
+          contact: form(slug: "test-contact") {
+            slug
+            description @skip(if: true)
+            steps @include(if: $withFields) { fields { name } }
+          }
+          options: form(slug: "test-options") { ... on Form { slug } }
+          missing: form(slug: "nope") { slug }
+        }
+    "#;
+
+    let response = run(&app, "/api/graphql", query, json!({ "withFields": false })).await;
+    assert_eq!(
+        response["data"],
+        json!({
+            "contact": { "slug": "test-contact" },
+            "options": { "slug": "test-options" },
+            "missing": null,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_public_schema_hides_unpublished_forms_and_admin_fields() {
+    let app = graphql_app().await;
+    let form = FormBuilder::find_by_slug(app.db(), "test-contact").await.unwrap().unwrap();
+    FormBuilder::unpublish(app.db(), form.id).await.unwrap();
+
+    let query = r#"{ form(slug: "test-contact") { id } }"#;
+    let response = run(&app, "/api/graphql", query, json!({})).await;
+    assert_eq!(response["data"]["form"], Value::Null);
+
+    let query = r#"{ form(slug: "test-options") { status submissions { total } } }"#;
+    let response = run(&app, "/api/graphql", query, json!({})).await;
+    assert_eq!(response["data"], Value::Null);
+    assert_eq!(
+        response["errors"][0]["message"],
+        "Unknown field \"status\" on type \"Form\"."
+    );
+    assert_eq!(response["errors"][0]["locations"], json!([{ "line": 1, "column": 32 }]));
+    assert_eq!(codes(&response), ["GRAPHQL_VALIDATION_FAILED"; 2]);
+}
+
+#[tokio::test]
+async fn test_get_requests_query_but_do_not_mutate() {
+    let app = graphql_app().await;
+    let response = app
+        .get("/api/v1/graphql?query=%7Bform(slug%3A%22test-contact%22)%7Bname%7D%7D")
+        .await;
+    response.assert_status(StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["data"]["form"]["name"], "Test Contact Form");
+
+    let response = app
+        .get("/api/graphql?query=mutation%7Bsubmit(slug%3A%22x%22%2Cdata%3A%7B%7D)%7Bstatus%7D%7D")
+        .await;
+    assert_eq!(codes(&response.json()), ["METHOD_NOT_ALLOWED"]);
+}
+
+// ============================================================================
+// Submitting
+// ============================================================================
+
+#[tokio::test]
+async fn test_submit_mutation_stores_submission() {
+    let app = graphql_app().await;
+    let query = r#"
+        mutation Submit($data: JSON!) {
+          submit(slug: "test-contact", data: $data) { submissionId status message }
+        }
+    "#;
+    let data = json!({ "name": "Ada", "email": "ada@example.com", "message": "Hello" });
+
+    let response = run(&app, "/api/graphql", query, json!({ "data": data })).await;
+    assert!(response.get("errors").is_none(), "{response}");
+    let receipt = &response["data"]["submit"];
+    assert_eq!(receipt["status"], "submitted");
+    assert_eq!(receipt["message"], "Thank you!");
+
+    let id: uuid::Uuid = receipt["submissionId"].as_str().unwrap().parse().unwrap();
+    let stored = SubmissionEntity::find_by_id(id).one(app.db()).await.unwrap().unwrap();
+    assert_eq!(stored.data["name"], "Ada");
+}
+
+#[tokio::test]
+async fn test_submit_mutation_reports_validation_errors() {
+    let app = graphql_app().await;
+    let query = r#"mutation { submit(slug: "test-contact", data: {name: "A"}) { status } }"#;
+
+    let response = run(&app, "/api/graphql", query, json!({})).await;
+    assert_eq!(response["data"], Value::Null);
+    let error = &response["errors"][0];
+    assert_eq!(error["path"], json!(["submit"]));
+    assert_eq!(error["extensions"]["code"], "VALIDATION_FAILED");
+    assert!(error["extensions"]["details"]["email"].is_array(), "{error}");
+}
+
+#[tokio::test]
+async fn test_submit_mutation_scores_quizzes() {
+    let app = graphql_app().await;
+    create_test_form(app.db(), quiz_form()).await;
+    let query = r#"
+        mutation { submit(slug: "test-quiz", data: {q1: "4", q2: "london"}) { score maxScore } }
+    "#;
+
+    let response = run(&app, "/api/graphql", query, json!({})).await;
+    assert_eq!(response["data"]["submit"], json!({ "score": 10, "maxScore": 20 }));
+}
+
+// ============================================================================
+// Admin
+// ============================================================================
+
+#[tokio::test]
+async fn test_admin_queries_forms_and_submissions() {
+    let app = graphql_app().await;
+    let form = FormBuilder::find_by_slug(app.db(), "test-options").await.unwrap().unwrap();
+    FormBuilder::unpublish(app.db(), form.id).await.unwrap();
+    for name in ["Ada", "Grace"] {
+        let data = json!({ "name": name, "email": "a@example.com", "message": "Hi there" });
+        app.post_json("/api/forms/test-contact", &data)
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    let query = r#"
+        {
+          drafts: forms(status: DRAFT) { slug status }
+          form(slug: "test-contact") {
+            responseCount
+            submissions(perPage: 1) { total page perPage submissions { status data } }
+          }
+        }
+    "#;
+    let response = run(&app, "/api/admin/graphql", query, json!({})).await;
+    assert!(response.get("errors").is_none(), "{response}");
+    let data = &response["data"];
+    assert_eq!(data["drafts"], json!([{ "slug": "test-options", "status": "DRAFT" }]));
+    let page = &data["form"]["submissions"];
+    assert_eq!(page["total"], 2);
+    assert_eq!(page["perPage"], 1);
+    assert_eq!(page["submissions"][0]["status"], "SUBMITTED");
+    assert_eq!(page["submissions"][0]["data"]["name"], "Grace");
+}
+
+#[tokio::test]
+async fn test_admin_creates_and_updates_forms() {
+    let app = graphql_app().await;
+    let create = r#"
+        mutation ($input: JSON!) { createForm(input: $input) { id slug steps { fields { name } } } }
+    "#;
+    let input = json!({
+        "name": "Signup",
+        "slug": "signup",
+        "steps": [{
+            "name": "Main",
+            "fields": [{ "name": "email", "label": "Email", "field_type": "email" }],
+        }],
+    });
+
+    let response = run(&app, "/api/admin/graphql", create, json!({ "input": input })).await;
+    assert!(response.get("errors").is_none(), "{response}");
+    let created = &response["data"]["createForm"];
+    assert_eq!(created["steps"][0]["fields"][0]["name"], "email");
+
+    let update = r#"
+        mutation ($id: ID!, $input: JSON!) { updateForm(id: $id, input: $input) { name } }
+    "#;
+    let mut input = input;
+    input["name"] = json!("Newsletter signup");
+    let variables = json!({ "id": created["id"], "input": input });
+    let response = run(&app, "/api/admin/graphql", update, variables).await;
+    assert_eq!(response["data"]["updateForm"]["name"], "Newsletter signup");
+
+    // Duplicate slugs answer the REST API's error code
+    let response = run(&app, "/api/admin/graphql", create, json!({ "input": input })).await;
+    assert_eq!(response["data"], Value::Null);
+    assert_eq!(codes(&response), ["DUPLICATE_SLUG"]);
+}
+
+#[tokio::test]
+async fn test_admin_graphql_requires_admin_auth() {
+    let auth = BearerTokenAuth::new("admin-token").read_only_token("viewer-token");
+    let app = app_with(anyform::AnyFormRouter::builder().admin_auth(auth)).await;
+    let body = json!({ "query": "{ forms { slug } }" }).to_string();
+    let send = |token: Option<&'static str>, method: &'static str| {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(if method == "GET" {
+                "/api/admin/graphql?query=%7Bforms%7Bslug%7D%7D"
+            } else {
+                "/api/admin/graphql"
+            })
+            .header("Content-Type", "application/json");
+        if let Some(token) = token {
+            request = request.header("Authorization", format!("Bearer {token}"));
+        }
+        let body = if method == "GET" { Body::empty() } else { Body::from(body.clone()) };
+        request.body(body).unwrap()
+    };
+
+    app.send_raw(send(None, "POST")).await.assert_status(StatusCode::UNAUTHORIZED);
+    app.send_raw(send(Some("admin-token"), "POST")).await.assert_status(StatusCode::OK);
+    // Read-only credentials query with GET
+    app.send_raw(send(Some("viewer-token"), "POST")).await.assert_status(StatusCode::FORBIDDEN);
+    app.send_raw(send(Some("viewer-token"), "GET")).await.assert_status(StatusCode::OK);
+
+    // The public endpoint stays open
+    let query = json!({ "query": r#"{ form(slug: "test-contact") { name } }"# });
+    app.post_json("/api/graphql", &query).await.assert_status(StatusCode::OK);
+}
+
+// ============================================================================
+// Schema and errors
+// ============================================================================
+
+#[tokio::test]
+async fn test_schema_is_served_as_sdl() {
+    let app = graphql_app().await;
+    let response = app.get("/api/graphql/schema.graphql").await;
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("text/plain");
+    let sdl = response.text();
+    assert!(sdl.contains("type Query {"));
+    assert!(!sdl.contains("createForm"));
+
+    let sdl = app.get("/api/admin/graphql/schema.graphql").await.text();
+    assert!(sdl.contains("createForm(input: JSON!): Form!"));
+}
+
+#[tokio::test]
+async fn test_request_errors() {
+    let app = graphql_app().await;
+
+    let response = run(&app, "/api/graphql", "{ form(slug: ) { id } }", json!({})).await;
+    assert!(response["errors"][0]["message"].as_str().unwrap().contains("expected value"));
+    assert_eq!(codes(&response), ["GRAPHQL_PARSE_FAILED"]);
+
+    let query = "query ($slug: String!) { form(slug: $slug) { id } }";
+    let response = run(&app, "/api/graphql", query, json!({})).await;
+    assert_eq!(response["errors"][0]["message"], "Variable slug is not defined.");
+
+    let response = run(&app, "/api/graphql", "{ __schema { types { name } } }", json!({})).await;
+    let types = response["data"]["__schema"]["types"].as_array().unwrap();
+    assert!(types.iter().any(|ty| ty["name"] == "Form"));
+
+    let response = app.post_json("/api/graphql", &json!({ "document": "{}" })).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(codes(&response.json()), ["INVALID_DATA"]);
+}
+
+#[tokio::test]
+async fn test_read_only_router_refuses_mutations() {
+    let app = app_with(anyform::AnyFormRouter::builder().read_only(true)).await;
+
+    let query = r#"{ form(slug: "test-contact") { name } }"#;
+    let response = run(&app, "/api/graphql", query, json!({})).await;
+    assert_eq!(response["data"]["form"]["name"], "Test Contact Form");
+
+    let query = r#"mutation { submit(slug: "test-contact", data: {}) { status } }"#;
+    let response = run(&app, "/api/graphql", query, json!({})).await;
+    assert_eq!(codes(&response), ["READ_ONLY"]);
+}
+
+#[tokio::test]
+async fn test_graphql_is_off_by_default() {
+    let app = TestApp::new().await;
+    app.get("/api/graphql?query=%7B__typename%7D")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
@@ -10,6 +10,7 @@
 //! - GET /api/admin/forms/{id}/submissions - List submissions
 //! - GET /api/admin/forms/{form_id}/submissions/{sub_id} - Get submission
 //! - DELETE /api/admin/forms/{form_id}/submissions/{sub_id} - Delete submission
+//! - GET/POST /api/admin/forms/{form_id}/submissions/{sub_id}/notes - Notes and labels
 
 #![cfg(feature = "admin")]
 
@@ -488,6 +489,115 @@ async fn test_list_submissions_form_not_found() {
     response.assert_status(StatusCode::OK);
 }
 
+/// Submits `count` contact forms, returning their IDs oldest first.
+async fn submit_many(app: &TestApp, slug: &str, count: usize) -> Vec<String> {
+    let mut ids = Vec::new();
+    for i in 0..count {
+        ids.push(submit_contact(app, slug, &format!("User {i}")).await);
+    }
+    ids
+}
+
+/// Sets a submission's creation time.
+async fn backdate(app: &TestApp, id: &str, created_at: &str) {
+    use anyform::entities::submission::Column;
+    use sea_orm::{sea_query::Expr, ColumnTrait, EntityTrait, QueryFilter};
+
+    let at = chrono::DateTime::parse_from_rfc3339(created_at).unwrap();
+    anyform::SubmissionEntity::update_many()
+        .col_expr(Column::CreatedAt, Expr::value(at))
+        .filter(Column::Id.eq(Uuid::parse_str(id).unwrap()))
+        .exec(app.db())
+        .await
+        .unwrap();
+}
+
+fn listed_ids(json: &serde_json::Value) -> Vec<&str> {
+    json["data"]["submissions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["id"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_list_submissions_paginates() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let ids = submit_many(&app, &form.slug, 5).await;
+    for (i, id) in ids.iter().enumerate() {
+        backdate(&app, id, &format!("2025-03-0{}T09:00:00Z", i + 1)).await;
+    }
+
+    let url = format!("/api/admin/forms/{}/submissions?per_page=2", form.id);
+    let json: serde_json::Value = app.get(&url).await.json();
+    assert_eq!(listed_ids(&json), [ids[4].as_str(), ids[3].as_str()]);
+    assert_eq!(json["pagination"]["current_page"], 1);
+    assert_eq!(json["pagination"]["total_items"], 5);
+    assert_eq!(json["pagination"]["total_pages"], 3);
+
+    let json: serde_json::Value = app.get(&format!("{url}&page=3")).await.json();
+    assert_eq!(json["data"]["count"], 1);
+    assert_eq!(listed_ids(&json), [ids[0].as_str()]);
+
+    let json: serde_json::Value = app
+        .get(&format!("{url}&page=2&sort=created_at"))
+        .await
+        .json();
+    assert_eq!(listed_ids(&json), [ids[2].as_str(), ids[3].as_str()]);
+}
+
+#[tokio::test]
+async fn test_list_submissions_filters_by_date_and_completion() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let ids = submit_many(&app, &form.slug, 3).await;
+    backdate(&app, &ids[0], "2025-02-28T23:00:00Z").await;
+    backdate(&app, &ids[1], "2025-03-01T10:00:00Z").await;
+    backdate(&app, &ids[2], "2025-03-02T10:00:00Z").await;
+
+    let base = format!("/api/admin/forms/{}/submissions", form.id);
+    let json: serde_json::Value = app
+        .get(&format!("{base}?since=2025-03-01&until=2025-03-01"))
+        .await
+        .json();
+    assert_eq!(listed_ids(&json), [ids[1].as_str()]);
+
+    let json: serde_json::Value = app
+        .get(&format!("{base}?since=2025-03-01T12:00:00Z"))
+        .await
+        .json();
+    assert_eq!(listed_ids(&json), [ids[2].as_str()]);
+
+    let started = app
+        .post_json(
+            &format!("/api/v1/forms/{}/submissions/start", form.slug),
+            &serde_json::json!({}),
+        )
+        .await;
+    started.assert_status(StatusCode::CREATED);
+
+    let json: serde_json::Value = app.get(&format!("{base}?completed=false")).await.json();
+    assert_eq!(json["data"]["count"], 1);
+    assert_eq!(json["data"]["submissions"][0]["status"], "in_progress");
+    let json: serde_json::Value = app.get(&format!("{base}?completed=true")).await.json();
+    assert_eq!(json["pagination"]["total_items"], 3);
+}
+
+#[tokio::test]
+async fn test_list_submissions_rejects_bad_parameters() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let base = format!("/api/admin/forms/{}/submissions", form.id);
+
+    for query in ["sort=name", "since=last-week"] {
+        let response = app.get(&format!("{base}?{query}")).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        response.assert_api_error("INVALID_DATA");
+    }
+}
+
 // ============================================================================
 // GET /api/admin/forms/{form_id}/submissions/{sub_id} - Get Submission
 // ============================================================================
@@ -612,3 +722,122 @@ async fn test_delete_submission_not_found() {
 
     response.assert_status(StatusCode::NOT_FOUND);
 }
+
+// ============================================================================
+// /api/admin/forms/{form_id}/submissions/{sub_id}/notes - Notes and Labels
+// ============================================================================
+
+async fn submit_contact(app: &TestApp, slug: &str, name: &str) -> String {
+    let data = serde_json::json!({
+        "name": name,
+        "email": "notes@example.com",
+        "message": "Hello"
+    });
+    let response = app.post_json(&format!("/api/forms/{}", slug), &data).await;
+    let json: serde_json::Value = response.json();
+    json["data"]["submission_id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_add_submission_note() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let sub_id = submit_contact(&app, &form.slug, "Noted").await;
+
+    let response = app
+        .post_json(
+            &format!("/api/admin/forms/{}/submissions/{}/notes", form.id, sub_id),
+            &serde_json::json!({
+                "body": "Called back, waiting on reply",
+                "labels": ["Urgent", " follow-up ", "urgent"],
+                "author": "admin"
+            }),
+        )
+        .await;
+
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["body"], "Called back, waiting on reply");
+    assert_eq!(json["data"]["labels"], serde_json::json!(["urgent", "follow-up"]));
+
+    let response = app
+        .get(&format!("/api/admin/forms/{}/submissions/{}", form.id, sub_id))
+        .await;
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["labels"], serde_json::json!(["urgent", "follow-up"]));
+    assert_eq!(json["data"]["notes"][0]["author"], "admin");
+
+    let response = app
+        .get(&format!("/api/admin/forms/{}/submissions/{}/notes", form.id, sub_id))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["count"], 1);
+}
+
+#[tokio::test]
+async fn test_add_submission_note_requires_content() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let sub_id = submit_contact(&app, &form.slug, "Empty").await;
+
+    let response = app
+        .post_json(
+            &format!("/api/admin/forms/{}/submissions/{}/notes", form.id, sub_id),
+            &serde_json::json!({ "body": "  ", "labels": [] }),
+        )
+        .await;
+
+    response.assert_status(StatusCode::BAD_REQUEST);
+    response.assert_api_error("INVALID_DATA");
+}
+
+#[tokio::test]
+async fn test_add_submission_note_not_found() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let response = app
+        .post_json(
+            &format!(
+                "/api/admin/forms/{}/submissions/{}/notes",
+                form.id,
+                Uuid::new_v4()
+            ),
+            &serde_json::json!({ "labels": ["urgent"] }),
+        )
+        .await;
+
+    response.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_list_submissions_filter_by_label() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let urgent = submit_contact(&app, &form.slug, "Urgent").await;
+    let _other = submit_contact(&app, &form.slug, "Other").await;
+
+    app.post_json(
+        &format!("/api/admin/forms/{}/submissions/{}/notes", form.id, urgent),
+        &serde_json::json!({ "labels": ["urgent"] }),
+    )
+    .await
+    .assert_status(StatusCode::CREATED);
+
+    let response = app
+        .get(&format!("/api/admin/forms/{}/submissions?label=URGENT", form.id))
+        .await;
+
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["count"], 1);
+    assert_eq!(json["data"]["submissions"][0]["id"], urgent);
+    assert_eq!(json["data"]["submissions"][0]["labels"], serde_json::json!(["urgent"]));
+
+    let response = app
+        .get(&format!("/api/admin/forms/{}/submissions", form.id))
+        .await;
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["count"], 2);
+}
@@ -3,14 +3,19 @@
 //! Tests cover:
 //! - GET /api/forms/{slug} - HTML rendering
 //! - GET /api/forms/{slug}/json - JSON schema
+//! - GET /api/forms/{slug}/config.js - Embeddable config script
 //! - POST /api/forms/{slug} - Form submission (JSON response)
 //! - POST /api/forms/{slug}/submit - Form submission (redirect)
 //! - GET /api/forms/{slug}/success - Success page
+//! - Raw responses, without the envelope
 
 mod common;
 
-use common::{contact_form, create_test_form, TestApp};
-use http::StatusCode;
+use axum::body::Body;
+use anyform::schema::{Branding, FormSettings};
+use anyform::{FormBuilder, ResponseFormat};
+use common::{contact_form, create_test_form, multi_step_form, TestApp, TestDb};
+use http::{header, Request, StatusCode};
 
 // ============================================================================
 // GET /api/forms/{slug}/json - JSON Schema
@@ -97,6 +102,97 @@ async fn test_get_form_json_includes_field_details() {
     assert!(field.get("field_type").is_some());
 }
 
+#[tokio::test]
+async fn test_get_form_json_skeleton_leaves_out_fields() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), multi_step_form()).await;
+
+    let full: serde_json::Value =
+        app.get(&format!("/api/forms/{}/json", form.slug)).await.json();
+    let response = app.get(&format!("/api/forms/{}/json?steps=ids", form.slug)).await;
+
+    response.assert_status(StatusCode::OK);
+    let skeleton: serde_json::Value = response.json();
+    assert_eq!(skeleton["slug"], form.slug);
+    let steps = skeleton["steps"].as_array().unwrap();
+    assert_eq!(steps.len(), 3);
+    for (step, full_step) in steps.iter().zip(full["steps"].as_array().unwrap()) {
+        assert_eq!(step["id"], full_step["id"]);
+        assert_eq!(step["name"], full_step["name"]);
+        assert_eq!(step["lazy"], true);
+        assert_eq!(step["fields"], serde_json::json!([]));
+        assert!(full_step.get("lazy").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_get_form_json_single_step() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), multi_step_form()).await;
+    let full: serde_json::Value =
+        app.get(&format!("/api/forms/{}/json", form.slug)).await.json();
+    let second = &full["steps"][1];
+
+    let response = app
+        .get(&format!("/api/v1/forms/{}/json?step={}", form.slug, second["id"].as_str().unwrap()))
+        .await;
+
+    response.assert_status(StatusCode::OK);
+    let step: serde_json::Value = response.json();
+    assert_eq!(&step, second);
+    assert_eq!(step["fields"][0]["name"], "email");
+
+    let response = app
+        .get(&format!("/api/forms/{}/json?step={}", form.slug, uuid::Uuid::new_v4()))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    response.assert_api_error("STEP_NOT_FOUND");
+
+    let response = app.get(&format!("/api/forms/{}/json?step=first", form.slug)).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_get_form_json_changed_since_returns_changed_steps() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), multi_step_form()).await;
+    let before: serde_json::Value =
+        app.get(&format!("/api/forms/{}/json", form.slug)).await.json();
+    let since = before["updated_at"].as_str().unwrap();
+
+    // Change the second step and drop the third
+    let mut input = multi_step_form();
+    input.steps[1].fields[1].required = true;
+    input.steps.truncate(2);
+    FormBuilder::update(app.db(), form.id, input).await.unwrap();
+
+    let after: serde_json::Value =
+        app.get(&format!("/api/forms/{}/json", form.slug)).await.json();
+    assert_ne!(after["updated_at"], before["updated_at"]);
+    for key in ["id", "etag", "updated_at"] {
+        assert_eq!(after["steps"][0][key], before["steps"][0][key], "{key}");
+    }
+    assert_eq!(after["steps"][1]["id"], before["steps"][1]["id"]);
+    assert_ne!(after["steps"][1]["etag"], before["steps"][1]["etag"]);
+
+    let response = app
+        .get(&format!("/api/forms/{}/json?if_changed_since={since}", form.slug))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let changes: serde_json::Value = response.json();
+    assert_eq!(changes["updated_at"], after["updated_at"]);
+    assert_eq!(changes["steps"], serde_json::json!([after["steps"][1]]));
+    assert_eq!(changes["unchanged"], serde_json::json!([before["steps"][0]["id"]]));
+
+    let latest = after["updated_at"].as_str().unwrap();
+    let changes: serde_json::Value = app
+        .get(&format!("/api/forms/{}/json?if_changed_since={latest}", form.slug))
+        .await
+        .json();
+    assert_eq!(changes["steps"], serde_json::json!([]));
+    assert_eq!(changes["unchanged"].as_array().unwrap().len(), 2);
+}
+
 // ============================================================================
 // GET /api/forms/{slug} - HTML Rendering
 // ============================================================================
@@ -152,6 +248,134 @@ async fn test_get_form_html_deleted_form_returns_404() {
     response.assert_status(StatusCode::NOT_FOUND);
 }
 
+#[tokio::test]
+async fn test_get_form_html_sets_csp_header_matching_nonce() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), multi_step_form()).await;
+
+    let response = app.get(&format!("/api/forms/{}", form.slug)).await;
+
+    response.assert_status(StatusCode::OK);
+    let policy = response
+        .headers
+        .get("content-security-policy")
+        .and_then(|v| v.to_str().ok())
+        .expect("Missing Content-Security-Policy header");
+
+    let html = response.text();
+    let start = html.find("<style nonce=\"").expect("Style tag should carry a nonce") + 14;
+    let nonce = &html[start..start + html[start..].find('"').unwrap()];
+    assert!(policy.contains(&format!("'nonce-{nonce}'")));
+    assert!(!policy.contains("'unsafe-inline'"));
+}
+
+#[tokio::test]
+async fn test_get_form_html_uses_fresh_nonce_per_request() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let first = app.get(&format!("/api/forms/{}", form.slug)).await;
+    let second = app.get(&format!("/api/forms/{}", form.slug)).await;
+
+    assert_ne!(
+        first.headers.get("content-security-policy"),
+        second.headers.get("content-security-policy")
+    );
+}
+
+#[tokio::test]
+async fn test_get_form_html_posts_back_to_the_router() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let response = app.get(&format!("/api/forms/{}", form.slug)).await;
+    response.assert_body_contains("action=\"/api/v1/forms/test-contact/submit\"");
+}
+
+#[tokio::test]
+async fn test_stylesheet_asset_served_as_css() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/forms/assets/anyform.css").await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("text/css");
+    response.assert_body_contains(".af-step");
+}
+
+// ============================================================================
+// GET /api/forms/{slug}/config.js - Embeddable Config Script
+// ============================================================================
+
+#[tokio::test]
+async fn test_config_js_is_es_module() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let response = app.get(&format!("/api/v1/forms/{}/config.js", form.slug)).await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("text/javascript");
+    response.assert_body_contains(&format!("\"slug\":\"{}\"", form.slug));
+    response.assert_body_contains("new URL(\"/api/v1\", import.meta.url)");
+    response.assert_body_contains(&format!("apiBase + \"/forms/{}\"", form.slug));
+    response.assert_body_contains("export default form;");
+}
+
+#[tokio::test]
+async fn test_config_js_jsonp_callback() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let response = app
+        .get(&format!("/api/forms/{}/config.js?callback=anyform.load", form.slug))
+        .await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_body_contains("anyform.load({ form: {");
+    assert!(!response.text().contains("export "));
+
+    app.get(&format!("/api/forms/{}/config.js?callback=alert(1)", form.slug))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_config_js_revalidates_with_etag() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let uri = format!("/api/forms/{}/config.js", form.slug);
+
+    let response = app.get(&uri).await;
+    let etag = response.headers.get("etag").expect("Missing ETag header").clone();
+    assert!(response.headers.contains_key("last-modified"));
+    assert!(response.headers["cache-control"]
+        .to_str()
+        .unwrap()
+        .starts_with("public"));
+
+    let response = app
+        .send_raw(
+            Request::builder()
+                .uri(&uri)
+                .header("If-None-Match", etag)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+    response.assert_status(StatusCode::NOT_MODIFIED);
+    assert!(response.body.is_empty());
+}
+
+#[tokio::test]
+async fn test_config_js_not_found() {
+    let app = TestApp::new().await;
+
+    app.get("/api/forms/nonexistent-form/config.js")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
 // ============================================================================
 // POST /api/forms/{slug} - Form Submission (JSON Response)
 // ============================================================================
@@ -221,6 +445,31 @@ async fn test_submit_form_creates_submission_record() {
     assert_eq!(submissions[0].data["name"], "Jane Doe");
 }
 
+#[tokio::test]
+async fn test_submit_form_stamps_revision() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let response = app
+        .post_json(
+            &format!("/api/forms/{}", form.slug),
+            &common::sample_submission_data(),
+        )
+        .await;
+    response.assert_status(StatusCode::CREATED);
+
+    use anyform::{FormRevisionEntity, SubmissionEntity};
+
+    let revision = FormRevisionEntity::find_latest(app.db(), form.id)
+        .await
+        .unwrap()
+        .expect("form should have a revision");
+    let submissions = SubmissionEntity::find_by_form(app.db(), form.id)
+        .await
+        .unwrap();
+    assert_eq!(submissions[0].revision_id, Some(revision.id));
+}
+
 #[tokio::test]
 async fn test_submit_form_validation_error_missing_required() {
     let app = TestApp::new().await;
@@ -244,6 +493,31 @@ async fn test_submit_form_validation_error_missing_required() {
     response.assert_api_error("VALIDATION_FAILED");
 }
 
+#[tokio::test]
+async fn test_submit_form_skips_fields_hidden_by_conditions() {
+    let app = TestApp::new().await;
+    anyform::seed_onboarding_form(app.db()).await.unwrap();
+
+    // "Your Stack" (with required `languages`) is for developers only
+    let designer = serde_json::json!({
+        "name": "Ada",
+        "role": "designer",
+        "email": "ada@example.com"
+    });
+    app.post_json("/api/forms/onboarding", &designer)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let developer = serde_json::json!({
+        "name": "Ada",
+        "role": "developer",
+        "email": "ada@example.com"
+    });
+    let response = app.post_json("/api/forms/onboarding", &developer).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_body_contains("languages");
+}
+
 #[tokio::test]
 async fn test_submit_form_validation_error_invalid_email() {
     let app = TestApp::new().await;
@@ -309,6 +583,7 @@ async fn test_submit_form_redirect_success() {
 
     // Should redirect to success page
     response.assert_status(StatusCode::SEE_OTHER);
+    assert_eq!(response.headers["location"], "/api/v1/forms/test-contact/success");
 }
 
 #[tokio::test]
@@ -332,6 +607,7 @@ async fn test_submit_form_redirect_validation_error_rerenders() {
     response.assert_status(StatusCode::OK);
     response.assert_content_type("text/html");
     response.assert_body_contains("<form");
+    assert!(response.headers.contains_key("content-security-policy"));
 }
 
 // ============================================================================
@@ -373,3 +649,153 @@ async fn test_success_page_includes_default_message() {
     response.assert_status(StatusCode::OK);
     response.assert_body_contains("Thank you");
 }
+
+#[tokio::test]
+async fn test_success_page_is_branded() {
+    let app = TestApp::new().await;
+    let input = contact_form().settings(
+        FormSettings::new().success_message("Thanks <3").branding(
+            Branding::new()
+                .logo_url("/acme.svg")
+                .primary_color("#0a7")
+                .footer_text("Acme Inc."),
+        ),
+    );
+    let form = create_test_form(app.db(), input).await;
+
+    let response = app
+        .get(&format!("/api/forms/{}/success", form.slug))
+        .await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_body_contains(".af-form { --af-primary: #0a7; }");
+    response.assert_body_contains("<img src=\"/acme.svg\"");
+    response.assert_body_contains("<p>Acme Inc.</p>");
+    response.assert_body_contains("Thanks &lt;3");
+}
+
+// ============================================================================
+// Raw responses
+// ============================================================================
+
+const RAW: &str = "application/json; profile=\"raw\"";
+
+async fn app_with_format(format: ResponseFormat) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .response_format(format)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), contact_form()).await;
+    app
+}
+
+async fn post_with_accept(
+    app: &TestApp,
+    uri: &str,
+    accept: &str,
+    body: serde_json::Value,
+) -> common::app::TestResponse {
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::ACCEPT, accept)
+        .header("x-request-id", "req-123")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    app.send_raw(request).await
+}
+
+fn valid_contact() -> serde_json::Value {
+    serde_json::json!({ "name": "Ada", "email": "ada@example.com", "message": "Hello!" })
+}
+
+#[tokio::test]
+async fn test_responses_are_enveloped_by_default() {
+    let app = app_with_format(ResponseFormat::default()).await;
+
+    let response =
+        post_with_accept(&app, "/api/forms/test-contact", "application/json", valid_contact())
+            .await;
+    response.assert_status(StatusCode::CREATED);
+    response.assert_api_success();
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["request_id"], "req-123");
+    assert!(json["data"]["submission_id"].is_string());
+    assert_eq!(response.headers[header::VARY], "accept");
+}
+
+#[tokio::test]
+async fn test_raw_mode_returns_the_data_alone() {
+    let app = app_with_format(ResponseFormat::Raw).await;
+
+    let response =
+        post_with_accept(&app, "/api/v1/forms/test-contact", "*/*", valid_contact()).await;
+    response.assert_status(StatusCode::CREATED);
+    response.assert_content_type("application/json");
+    let json: serde_json::Value = response.json();
+    assert!(json["submission_id"].is_string());
+    assert!(json.get("success").is_none());
+    assert_eq!(response.headers["x-request-id"], "req-123");
+}
+
+#[tokio::test]
+async fn test_raw_mode_returns_errors_as_problems() {
+    let app = app_with_format(ResponseFormat::Raw).await;
+
+    let response = app.get("/api/v1/forms/missing/instance").await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    response.assert_content_type("application/problem+json");
+    let problem: serde_json::Value = response.json();
+    assert_eq!(problem["type"], "about:blank");
+    assert_eq!(problem["title"], "Not Found");
+    assert_eq!(problem["status"], 404);
+    assert_eq!(problem["code"], "FORM_NOT_FOUND");
+    assert!(problem["detail"].as_str().unwrap().contains("missing"));
+
+    let response = post_with_accept(
+        &app,
+        "/api/forms/test-contact",
+        "application/json",
+        serde_json::json!({ "name": "Ada" }),
+    )
+    .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_content_type("application/problem+json");
+    let problem: serde_json::Value = response.json();
+    assert_eq!(problem["code"], "VALIDATION_FAILED");
+    assert!(problem["details"]["email"].is_array());
+    assert_eq!(problem["request_id"], "req-123");
+}
+
+#[tokio::test]
+async fn test_accept_profile_picks_the_format() {
+    let app = app_with_format(ResponseFormat::Envelope).await;
+    let response =
+        post_with_accept(&app, "/api/forms/test-contact", RAW, valid_contact()).await;
+    response.assert_status(StatusCode::CREATED);
+    assert!(response.json::<serde_json::Value>()["submission_id"].is_string());
+
+    let app = app_with_format(ResponseFormat::Raw).await;
+    let envelope = "application/json; profile=\"envelope\"";
+    let response =
+        post_with_accept(&app, "/api/forms/test-contact", envelope, valid_contact()).await;
+    response.assert_status(StatusCode::CREATED);
+    response.assert_api_success();
+}
+
+#[tokio::test]
+async fn test_raw_mode_leaves_other_responses_alone() {
+    let app = app_with_format(ResponseFormat::Raw).await;
+
+    let response = app.get("/api/forms/test-contact/json").await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<serde_json::Value>()["slug"], "test-contact");
+    assert!(response.headers.get(header::VARY).is_none());
+
+    let response = app.get("/api/forms/test-contact").await;
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("text/html");
+}
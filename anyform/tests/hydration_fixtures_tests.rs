@@ -0,0 +1,51 @@
+//! Keeps the HTML fixtures of the browser hydration tests in sync with the
+//! renderer.
+//!
+//! The seeded example forms are rendered into `anyform-client/tests/fixtures`,
+//! which `anyform-client/tests/hydrate_e2e.rs` loads into a headless browser.
+//! Run with `UPDATE_FIXTURES=1` to regenerate them after a renderer change.
+
+mod common;
+
+use std::path::PathBuf;
+
+use anyform::render::{HtmlOptions, HtmlRenderer};
+use anyform::FormBuilder;
+use common::TestDb;
+
+const SLUGS: [&str; 4] = ["contact", "feedback", "quiz", "onboarding"];
+
+fn fixture_path(slug: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../anyform-client/tests/fixtures")
+        .join(format!("{slug}.html"))
+}
+
+#[tokio::test]
+async fn test_hydration_fixtures_match_renderer() {
+    let db = TestDb::new().await;
+    anyform::seed_all(db.conn()).await.unwrap();
+    let update = std::env::var_os("UPDATE_FIXTURES").is_some();
+
+    for slug in SLUGS {
+        let form = FormBuilder::find_by_slug(db.conn(), slug)
+            .await
+            .unwrap()
+            .unwrap();
+        let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new())
+            .await
+            .unwrap();
+
+        let path = fixture_path(slug);
+        if update {
+            std::fs::write(&path, &html).unwrap();
+            continue;
+        }
+        let fixture = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            fixture == html,
+            "{} is out of date; rerun with UPDATE_FIXTURES=1",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,266 @@
+//! Tests for translated form content and localized validation messages.
+
+mod common;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FieldTranslation,
+    FormSettings, FormTranslation, JumpRule, MessageCatalog, Messages, StepTranslation,
+    ValidationRules,
+};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use serde_json::{json, Value};
+
+const SLUG: &str = "test-i18n";
+
+/// A two-step form with a French translation, and a jump to its last step.
+fn translated_form() -> CreateFormInput {
+    let french = FormTranslation::new()
+        .name("Inscription")
+        .description("Inscrivez-vous")
+        .submit_label("Envoyer")
+        .success_message("Merci !")
+        .step("About you", StepTranslation::new().name("Vous"))
+        .step("Contact", StepTranslation::new().name("Coordonnées"))
+        .field("name", FieldTranslation::new().label("Nom").placeholder("Votre nom"))
+        .field(
+            "code",
+            FieldTranslation::new().label("Code").pattern_message("trois chiffres"),
+        )
+        .field(
+            "plan",
+            FieldTranslation::new().label("Formule").option("basic", "Essentiel"),
+        )
+        .field(
+            "city",
+            FieldTranslation::new()
+                .label("Ville")
+                .message("min_length", "{label} : au moins {min} lettres"),
+        );
+
+    CreateFormInput::new("Signup", SLUG)
+        .description("Sign up")
+        .settings(
+            FormSettings::new()
+                .submit_label("Send")
+                .success_message("Thanks!")
+                .translation("fr", french),
+        )
+        .step(
+            CreateStepInput::new("About you")
+                .order(0)
+                .field(
+                    CreateFieldInput::new("name", "Name", "text")
+                        .placeholder("Your name")
+                        .required()
+                        .validation(ValidationRules::new().min_length(2)),
+                )
+                .field(
+                    CreateFieldInput::new("code", "Code", "text").validation(
+                        ValidationRules::new()
+                            .pattern("^[0-9]{3}$")
+                            .pattern_message("three digits"),
+                    ),
+                )
+                .field(CreateFieldInput::new("plan", "Plan", "radio").options(vec![
+                    CreateOptionInput::new("Basic", "basic"),
+                    CreateOptionInput::new("Pro", "pro"),
+                ]))
+                .jump(JumpRule::to_step("Contact")),
+        )
+        .step(
+            CreateStepInput::new("Contact")
+                .order(1)
+                .field(CreateFieldInput::new("email", "Email", "email"))
+                .field(
+                    CreateFieldInput::new("city", "City", "text").validation(
+                        ValidationRules::new()
+                            .min_length(2)
+                            .message("min_length", "{label} takes {min} letters or more"),
+                    ),
+                ),
+        )
+}
+
+async fn app() -> TestApp {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), translated_form()).await;
+    app
+}
+
+async fn app_with(catalog: MessageCatalog) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .messages(catalog)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), translated_form()).await;
+    app
+}
+
+fn french() -> MessageCatalog {
+    MessageCatalog::new().locale(
+        "fr",
+        Messages::new()
+            .set("required", "{label} est obligatoire")
+            .set("min_length", "{label} doit contenir au moins {min} caractères"),
+    )
+}
+
+// ============================================================================
+// Content
+// ============================================================================
+
+#[tokio::test]
+async fn test_html_in_requested_locale() {
+    let app = app().await;
+
+    let response = app.get(&format!("/api/forms/{SLUG}?locale=fr")).await;
+    response.assert_status(StatusCode::OK);
+    let html = response.text();
+    assert!(html.contains("Vous"));
+    assert!(html.contains("Nom"));
+    assert!(html.contains("Votre nom"));
+    assert!(html.contains("Essentiel"));
+    assert!(html.contains("Pro"));
+    assert!(html.contains("Envoyer"));
+    assert!(html.contains(r#"lang="fr""#));
+    assert!(html.contains("/submit?locale=fr"));
+    assert!(!html.contains("Your name"));
+}
+
+#[tokio::test]
+async fn test_html_without_translation_is_as_written() {
+    let app = app().await;
+
+    for uri in [
+        format!("/api/forms/{SLUG}"),
+        format!("/api/forms/{SLUG}?locale=de"),
+    ] {
+        let html = app.get(&uri).await.text();
+        assert!(html.contains("Your name"), "{uri}");
+        assert!(html.contains("Send"), "{uri}");
+        assert!(!html.contains("Envoyer"), "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn test_regional_locale_falls_back_to_language() {
+    let app = app().await;
+
+    let html = app.get(&format!("/api/forms/{SLUG}?locale=fr_CA")).await.text();
+    assert!(html.contains("Votre nom"));
+    assert!(html.contains(r#"lang="fr""#));
+}
+
+#[tokio::test]
+async fn test_json_in_requested_locale_keeps_jumps() {
+    let app = app().await;
+
+    let form: Value = app.get(&format!("/api/forms/{SLUG}/json?locale=fr")).await.json();
+    assert_eq!(form["name"], "Inscription");
+    let steps = form["steps"].as_array().unwrap();
+    assert_eq!(steps[0]["name"], "Vous");
+    assert_eq!(steps[1]["name"], "Coordonnées");
+    assert_eq!(steps[0]["next"], json!([{ "to": steps[1]["id"] }]));
+    assert_eq!(steps[0]["fields"][0]["label"], "Nom");
+    assert_eq!(steps[0]["fields"][2]["options"][0]["label"], "Essentiel");
+    assert_eq!(steps[0]["fields"][2]["options"][0]["value"], "basic");
+}
+
+#[tokio::test]
+async fn test_success_page_in_requested_locale() {
+    let app = app().await;
+
+    let response = app.get(&format!("/api/forms/{SLUG}/success?locale=fr")).await;
+    response.assert_body_contains("Merci !");
+}
+
+// ============================================================================
+// Validation messages
+// ============================================================================
+
+#[tokio::test]
+async fn test_validation_errors_in_requested_locale() {
+    let app = app_with(french()).await;
+
+    let response = app
+        .post_json(&format!("/api/forms/{SLUG}?locale=fr"), &json!({ "code": "12" }))
+        .await;
+    response.assert_api_error("VALIDATION_FAILED");
+    response.assert_body_contains("Nom est obligatoire");
+    response.assert_body_contains("Code: trois chiffres");
+
+    let response = app
+        .post_json(&format!("/api/forms/{SLUG}?locale=fr"), &json!({ "name": "A" }))
+        .await;
+    response.assert_body_contains("Nom doit contenir au moins 2 caractères");
+}
+
+#[tokio::test]
+async fn test_validation_errors_default_to_english() {
+    let app = app_with(french()).await;
+
+    let response = app.post_json(&format!("/api/forms/{SLUG}"), &json!({ "code": "12" })).await;
+    response.assert_body_contains("Name is required");
+    response.assert_body_contains("Code: three digits");
+
+    // A regional locale falls back to its language's messages; one the
+    // catalog lacks keeps the built-in messages
+    let response = app
+        .post_json(&format!("/api/forms/{SLUG}?locale=fr-BE"), &json!({}))
+        .await;
+    response.assert_body_contains("Nom est obligatoire");
+    let response = app
+        .post_json(&format!("/api/forms/{SLUG}?locale=de"), &json!({}))
+        .await;
+    response.assert_body_contains("Name is required");
+}
+
+#[tokio::test]
+async fn test_field_validation_in_requested_locale() {
+    let app = app_with(french()).await;
+
+    let response = app
+        .post_json(
+            &format!("/api/forms/{SLUG}/fields/name/validate?locale=fr"),
+            &json!({ "value": "A" }),
+        )
+        .await;
+    let json: Value = response.json();
+    assert_eq!(
+        json["data"]["errors"],
+        json!(["Nom doit contenir au moins 2 caractères"])
+    );
+}
+
+#[tokio::test]
+async fn test_rerendered_form_stays_in_locale() {
+    let app = app_with(french()).await;
+
+    let response = app
+        .post_form(&format!("/api/forms/{SLUG}/submit?locale=fr"), &[("name", "A")])
+        .await;
+    let html = response.text();
+    assert!(html.contains("Nom doit contenir au moins 2 caractères"));
+    assert!(html.contains("Envoyer"));
+}
+
+#[tokio::test]
+async fn test_field_messages_in_requested_locale() {
+    let app = app_with(french()).await;
+
+    let response = app
+        .post_json(&format!("/api/forms/{SLUG}?locale=fr"), &json!({ "name": "Ann", "city": "X" }))
+        .await;
+    response.assert_body_contains("Ville : au moins 2 lettres");
+    let response = app
+        .post_json(&format!("/api/forms/{SLUG}"), &json!({ "name": "Ann", "city": "X" }))
+        .await;
+    response.assert_body_contains("City takes 2 letters or more");
+
+    // The browser gets the same template to show as the user types
+    let html = app.get(&format!("/api/forms/{SLUG}?locale=fr")).await.text();
+    assert!(html.contains(r#""minLengthMessage":"{label} : au moins {min} lettres""#));
+}
@@ -0,0 +1,179 @@
+//! Tests for recurring form instances.
+
+mod common;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, FieldClassification, FormSettings,
+    Frequency, InstanceService, Recurrence, ResultsPrivacy,
+};
+use chrono::{DateTime, FixedOffset};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+
+fn at(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn pulse_form(recurrence: Recurrence) -> CreateFormInput {
+    CreateFormInput::new("Pulse", "pulse")
+        .settings(FormSettings::new().recurrence(recurrence))
+        .step(CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("mood", "Mood", "rating").required(),
+            CreateFieldInput::new("comment", "Comment", "textarea"),
+            CreateFieldInput::new("salary", "Salary", "number")
+                .classification(FieldClassification::NoAnalytics),
+        ]))
+}
+
+fn daily() -> Recurrence {
+    Recurrence::new(Frequency::Daily, at("2020-01-01T00:00:00Z"))
+}
+
+async fn answer(app: &TestApp, mood: u8) -> common::app::TestResponse {
+    app.post_json("/api/forms/pulse", &serde_json::json!({ "mood": mood, "salary": 50000 }))
+        .await
+}
+
+#[tokio::test]
+async fn test_current_instance_by_slug() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), pulse_form(daily())).await;
+
+    let response = app.get("/api/forms/pulse/instance").await;
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+
+    let opens_at = at(json["data"]["opens_at"].as_str().unwrap());
+    let closes_at = at(json["data"]["closes_at"].as_str().unwrap());
+    assert_eq!(closes_at - opens_at, chrono::Duration::days(1));
+    assert!(json["data"]["sequence"].as_i64().unwrap() > 0);
+
+    // Repeated lookups resolve to the same instance
+    let again: serde_json::Value = app.get("/api/forms/pulse/instance").await.json();
+    assert_eq!(again["data"]["id"], json["data"]["id"]);
+}
+
+#[tokio::test]
+async fn test_closed_recurring_form_rejects_submissions() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), pulse_form(daily().count(1))).await;
+
+    let response = app.get("/api/forms/pulse/instance").await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("INSTANCE_CLOSED");
+
+    let response = answer(&app, 4).await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("INSTANCE_CLOSED");
+}
+
+#[tokio::test]
+async fn test_non_recurring_form_has_no_instance() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        CreateFormInput::new("Once", "once")
+            .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text"))),
+    )
+    .await;
+
+    app.get("/api/forms/once/instance")
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_max_submissions_applies_per_instance() {
+    let app = TestApp::new().await;
+    let form = create_test_form(
+        app.db(),
+        pulse_form(daily()).settings(FormSettings::new().recurrence(daily()).max_submissions(1)),
+    )
+    .await;
+
+    // A full earlier instance does not count against the current one
+    let previous = InstanceService::current_at(app.db(), &form, at("2020-01-01T12:00:00Z"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(previous.sequence, 0);
+
+    answer(&app, 4).await.assert_status(StatusCode::CREATED);
+    answer(&app, 5)
+        .await
+        .assert_api_error("SUBMISSION_LIMIT_REACHED");
+}
+
+#[cfg(feature = "admin")]
+#[tokio::test]
+async fn test_admin_compares_instances() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), pulse_form(daily())).await;
+
+    let previous = InstanceService::current_at(app.db(), &form, at("2020-01-01T12:00:00Z"))
+        .await
+        .unwrap()
+        .unwrap();
+
+    answer(&app, 4).await.assert_status(StatusCode::CREATED);
+    answer(&app, 2).await.assert_status(StatusCode::CREATED);
+
+    let json: serde_json::Value = app
+        .get(&format!("/api/admin/forms/{}/instances", form.id))
+        .await
+        .json();
+    assert_eq!(json["data"]["count"], 2);
+
+    let instances = json["data"]["instances"].as_array().unwrap();
+    assert_eq!(instances[0]["id"], previous.id.to_string());
+    assert_eq!(instances[0]["submissions"], 0);
+    assert!(instances[0]["field_averages"].as_object().unwrap().is_empty());
+    assert_eq!(instances[1]["submissions"], 2);
+    assert_eq!(instances[1]["field_averages"]["mood"], 3.0);
+    assert!(instances[1]["field_averages"].get("salary").is_none());
+
+    let current = instances[1]["id"].as_str().unwrap();
+    let json: serde_json::Value = app
+        .get(&format!(
+            "/api/admin/forms/{}/submissions?instance={}",
+            form.id, current
+        ))
+        .await
+        .json();
+    assert_eq!(json["data"]["count"], 2);
+    assert_eq!(json["data"]["submissions"][0]["instance_id"], current);
+
+    let json: serde_json::Value = app
+        .get(&format!(
+            "/api/admin/forms/{}/submissions?instance={}",
+            form.id, previous.id
+        ))
+        .await
+        .json();
+    assert_eq!(json["data"]["count"], 0);
+}
+
+#[tokio::test]
+async fn test_small_instances_are_suppressed() {
+    let app = TestApp::new().await;
+    let privacy = ResultsPrivacy::new().min_respondents(3).round_counts_to(5);
+    let settings = FormSettings::new().recurrence(daily()).results_privacy(privacy);
+    let form = create_test_form(app.db(), pulse_form(daily()).settings(settings)).await;
+
+    answer(&app, 4).await.assert_status(StatusCode::CREATED);
+    answer(&app, 2).await.assert_status(StatusCode::CREATED);
+
+    let stats = InstanceService::stats(app.db(), &form).await.unwrap();
+    let current = stats.last().unwrap();
+    assert!(current.suppressed);
+    assert_eq!(current.submissions, 0);
+    assert!(current.field_averages.is_empty());
+
+    answer(&app, 3).await.assert_status(StatusCode::CREATED);
+
+    let stats = InstanceService::stats(app.db(), &form).await.unwrap();
+    let current = stats.last().unwrap();
+    assert!(!current.suppressed);
+    assert_eq!(current.submissions, 5);
+    assert_eq!(current.field_averages["mood"], 3.0);
+}
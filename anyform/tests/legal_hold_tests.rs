@@ -0,0 +1,125 @@
+//! Tests for legal holds on submissions.
+
+mod common;
+
+use anyform::{FormBuilder, LegalHold, PreviewSecret, PreviewToken, SubmissionEntity};
+use chrono::Duration;
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+use serde_json::json;
+use uuid::Uuid;
+
+const SECRET: &str = "legal-hold-test-secret";
+
+async fn admin_app() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .preview_secret(SECRET)
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+fn answers() -> serde_json::Value {
+    json!({ "name": "Ada", "email": "ada@example.com", "message": "Please keep this" })
+}
+
+async fn submit(app: &TestApp, url: &str) -> Uuid {
+    let response = app.post_json(url, &answers()).await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    json["data"]["submission_id"].as_str().unwrap().parse().unwrap()
+}
+
+// ============================================================================
+// Placing and lifting
+// ============================================================================
+
+#[tokio::test]
+async fn test_held_submission_cannot_be_deleted_until_lifted() {
+    let app = admin_app().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let sub_id = submit(&app, "/api/v1/forms/test-contact").await;
+    let url = format!("/api/v1/admin/forms/{}/submissions/{sub_id}", form.id);
+
+    let response = app.post_json(&format!("{url}/legal-hold"), &json!({})).await;
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["legal_hold"], true);
+
+    let json: serde_json::Value = app.get(&url).await.json();
+    assert_eq!(json["data"]["legal_hold"], true);
+
+    let response = app.delete(&url).await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("LEGAL_HOLD");
+    app.get(&url).await.assert_status(StatusCode::OK);
+
+    let json: serde_json::Value = app.delete(&format!("{url}/legal-hold")).await.json();
+    assert_eq!(json["data"]["legal_hold"], false);
+    app.delete(&url).await.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_hold_requires_a_submission_of_the_form() {
+    let app = admin_app().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let url = format!(
+        "/api/v1/admin/forms/{}/submissions/{}/legal-hold",
+        form.id,
+        Uuid::new_v4()
+    );
+    app.post_json(&url, &json!({}))
+        .await
+        .assert_api_error("SUBMISSION_NOT_FOUND");
+}
+
+// ============================================================================
+// Bulk and form deletes
+// ============================================================================
+
+#[tokio::test]
+async fn test_form_with_held_submissions_cannot_be_hard_deleted() {
+    let app = TestApp::new().await;
+    let db = app.db();
+    let form = create_test_form(db, contact_form()).await;
+    let sub_id = submit(&app, "/api/v1/forms/test-contact").await;
+
+    let sub = SubmissionEntity::find_by_id(sub_id).one(db).await.unwrap().unwrap();
+    let sub = LegalHold::set(db, sub, true).await.unwrap();
+
+    let err = FormBuilder::hard_delete(db, form.id).await.unwrap_err();
+    assert_eq!(err.error_code(), "LEGAL_HOLD");
+    assert!(FormBuilder::find_by_id(db, form.id).await.unwrap().is_some());
+
+    LegalHold::set(db, sub, false).await.unwrap();
+    FormBuilder::hard_delete(db, form.id).await.unwrap();
+    assert!(SubmissionEntity::find_by_id(sub_id).one(db).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_discarding_previews_skips_held_ones() {
+    let app = admin_app().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let token = PreviewSecret::new(SECRET).sign(&PreviewToken::new(form.id, Duration::hours(1)));
+    let preview_url = format!("/api/v1/forms/test-contact/preview?token={token}");
+
+    let held = submit(&app, &preview_url).await;
+    submit(&app, &preview_url).await;
+    let url = format!("/api/v1/admin/forms/{}/submissions/{held}/legal-hold", form.id);
+    app.post_json(&url, &json!({})).await.assert_status(StatusCode::OK);
+
+    let json: serde_json::Value = app
+        .delete(&format!("/api/v1/admin/forms/{}/preview", form.id))
+        .await
+        .json();
+    assert_eq!(json["data"]["discarded"], 1);
+    assert_eq!(json["data"]["held"], json!([held.to_string()]));
+
+    let previews = SubmissionEntity::find_previews(app.db(), form.id).await.unwrap();
+    assert_eq!(previews.len(), 1);
+    assert_eq!(previews[0].id, held);
+}
@@ -0,0 +1,174 @@
+//! Tests for announcing forms opening and closing.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use anyform::entities::form;
+use anyform::services::ClosedReason;
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, FormEntity, FormEvents, FormSettings,
+    FormTransition, LifecycleService,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use sea_orm::{ActiveModelTrait, ActiveValue, EntityTrait, IntoActiveModel};
+
+/// Records the lifecycle events it receives, e.g. `closed:quota_reached`.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+#[async_trait]
+impl FormEvents for Recorder {
+    async fn on_form_published(&self, form: &form::Model) {
+        self.record(form, "published");
+    }
+
+    async fn on_form_archived(&self, form: &form::Model) {
+        self.record(form, "archived");
+    }
+
+    async fn on_form_opened(&self, form: &form::Model) {
+        self.record(form, "opened");
+    }
+
+    async fn on_form_closed(&self, form: &form::Model, reason: ClosedReason) {
+        let reason = serde_json::to_value(reason).unwrap();
+        self.record(form, &format!("closed:{}", reason.as_str().unwrap()));
+    }
+}
+
+impl Recorder {
+    fn record(&self, form: &form::Model, event: &str) {
+        assert_eq!(form.slug, "poll");
+        self.0.lock().unwrap().push(event.to_string());
+    }
+
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+fn poll_form(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Poll", "poll")
+        .settings(settings)
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("answer", "Answer", "text")))
+}
+
+async fn app(events: Recorder) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .events(events)
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+async fn answer(app: &TestApp) -> common::app::TestResponse {
+    app.post_json("/api/forms/poll", &serde_json::json!({ "answer": "yes" }))
+        .await
+}
+
+fn in_minutes(minutes: i64) -> DateTime<FixedOffset> {
+    (Utc::now() + Duration::minutes(minutes)).fixed_offset()
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+#[tokio::test]
+async fn test_quota_closes_and_deleting_reopens() {
+    let events = Recorder::default();
+    let app = app(events.clone()).await;
+    let form = create_test_form(app.db(), poll_form(FormSettings::new().close_after(1))).await;
+
+    let json: serde_json::Value = answer(&app).await.json();
+    assert_eq!(events.take(), vec!["closed:quota_reached"]);
+    answer(&app).await.assert_status(StatusCode::CONFLICT);
+    assert!(events.take().is_empty());
+
+    let sub_id = json["data"]["submission_id"].as_str().unwrap();
+    app.delete(&format!("/api/admin/forms/{}/submissions/{sub_id}", form.id))
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(events.take(), vec!["opened"]);
+}
+
+#[tokio::test]
+async fn test_status_changes_open_and_close() {
+    let events = Recorder::default();
+    let app = app(events.clone()).await;
+    let form = create_test_form(app.db(), poll_form(FormSettings::new())).await;
+    let admin = |action: &str| format!("/api/admin/forms/{}/{action}", form.id);
+    let body = serde_json::json!({});
+
+    app.post_json(&admin("unpublish"), &body).await.assert_status(StatusCode::OK);
+    assert_eq!(events.take(), vec!["closed:draft"]);
+
+    app.post_json(&admin("archive"), &body).await.assert_status(StatusCode::OK);
+    app.post_json(&admin("archive"), &body).await.assert_status(StatusCode::OK);
+    // Already closed, so only archiving is announced
+    assert_eq!(events.take(), vec!["archived"]);
+
+    app.post_json(&admin("publish"), &body).await.assert_status(StatusCode::OK);
+    assert_eq!(events.take(), vec!["published", "opened"]);
+}
+
+#[tokio::test]
+async fn test_updating_settings_closes() {
+    let events = Recorder::default();
+    let app = app(events.clone()).await;
+    let form = create_test_form(app.db(), poll_form(FormSettings::new())).await;
+
+    let input = poll_form(FormSettings::new().closes_at(in_minutes(-1)));
+    app.put_json(&format!("/api/admin/forms/{}", form.id), &input)
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(events.take(), vec!["closed:past_close_date"]);
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+#[tokio::test]
+async fn test_announce_due_follows_the_window() {
+    let db = TestDb::new().await;
+    let events = Recorder::default();
+    let settings = FormSettings::new().opens_at(in_minutes(60)).closes_at(in_minutes(120));
+    let form = create_test_form(db.conn(), poll_form(settings)).await;
+
+    // Created closed, which isn't announced
+    let announced = LifecycleService::announce_due(db.conn(), &events, in_minutes(0)).await.unwrap();
+    assert!(announced.is_empty());
+
+    let announced = LifecycleService::announce_due(db.conn(), &events, in_minutes(90)).await.unwrap();
+    assert_eq!(announced.len(), 1);
+    assert_eq!(announced[0].0.id, form.id);
+    assert_eq!(announced[0].1, FormTransition::Opened);
+
+    for _ in 0..2 {
+        LifecycleService::announce_due(db.conn(), &events, in_minutes(150)).await.unwrap();
+    }
+    assert_eq!(events.take(), vec!["opened", "closed:past_close_date"]);
+}
+
+#[tokio::test]
+async fn test_unannounced_form_is_recorded_silently() {
+    let db = TestDb::new().await;
+    let events = Recorder::default();
+    let form = create_test_form(db.conn(), poll_form(FormSettings::new())).await;
+    let mut model = form.into_active_model();
+    model.lifecycle_state = ActiveValue::Set(None);
+    let form = model.update(db.conn()).await.unwrap();
+
+    let at = in_minutes(0);
+    assert_eq!(LifecycleService::announce(db.conn(), &events, form.id, at).await.unwrap(), None);
+    let form = FormEntity::find_by_id(form.id).one(db.conn()).await.unwrap().unwrap();
+    assert_eq!(form.lifecycle_state.as_deref(), Some("open"));
+    assert!(events.take().is_empty());
+}
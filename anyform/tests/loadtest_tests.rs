@@ -0,0 +1,187 @@
+//! Tests for submit-path load testing.
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyform::loadtest::{
+    self, Latency, LoadReport, LoadTestConfig, RouterTarget, SubmissionGenerator, Thresholds,
+};
+use anyform::{AnyFormRouter, FormBuilder};
+use common::TestDb;
+
+async fn seeded_db() -> TestDb {
+    let db = TestDb::new().await;
+    anyform::seed_all(db.conn()).await.unwrap();
+    db
+}
+
+async fn generator(db: &TestDb, slug: &str) -> SubmissionGenerator {
+    let form = FormBuilder::find_by_slug(db.conn(), slug)
+        .await
+        .unwrap()
+        .unwrap();
+    SubmissionGenerator::for_form(db.conn(), &form).await.unwrap()
+}
+
+fn report(p50: f64, p95: f64, p99: f64, error_rate: f64, throughput: f64) -> LoadReport {
+    LoadReport {
+        form: "contact".to_string(),
+        concurrency: 10,
+        duration_secs: 10.0,
+        requests: 1000,
+        errors: (error_rate * 1000.0) as u64,
+        error_rate,
+        throughput,
+        latency_ms: Latency {
+            mean: p50,
+            p50,
+            p95,
+            p99,
+            max: p99,
+        },
+        statuses: BTreeMap::from([("201".to_string(), 1000)]),
+    }
+}
+
+// ============================================================================
+// Generation
+// ============================================================================
+
+#[tokio::test]
+async fn test_generated_submissions_are_accepted() {
+    let db = seeded_db().await;
+    let target = Arc::new(RouterTarget::new(AnyFormRouter::new(db.conn().clone())));
+    let config = LoadTestConfig {
+        concurrency: 4,
+        duration: Duration::from_millis(200),
+    };
+
+    for slug in ["contact", "feedback", "quiz", "onboarding"] {
+        let generator = Arc::new(generator(&db, slug).await);
+        let report = loadtest::run(target.clone(), slug, generator, &config).await;
+
+        assert!(report.requests > 0, "{slug}: no requests completed");
+        assert_eq!(report.errors, 0, "{slug}: {:?}", report.statuses);
+        assert_eq!(report.statuses.get("201"), Some(&report.requests));
+    }
+}
+
+#[tokio::test]
+async fn test_generation_is_deterministic() {
+    let db = seeded_db().await;
+    let generator = generator(&db, "feedback").await;
+
+    assert_eq!(generator.generate(7), generator.generate(7));
+    assert_ne!(generator.generate(7), generator.generate(8));
+}
+
+#[tokio::test]
+async fn test_generation_drops_fields_hidden_by_conditions() {
+    let db = seeded_db().await;
+    let generator = generator(&db, "onboarding").await;
+
+    let mut roles = Vec::new();
+    for n in 0..100 {
+        let values = generator.generate(n);
+        let role = values["role"].as_str().unwrap().to_string();
+
+        // "Your Stack" is shown to developers only
+        assert_eq!(values.contains_key("languages"), role == "developer", "{values:?}");
+        if role != "other" {
+            assert!(!values.contains_key("role_details"), "{values:?}");
+        }
+        roles.push(role);
+    }
+    for role in ["developer", "designer", "other"] {
+        assert!(roles.iter().any(|r| r == role), "{role} never generated");
+    }
+}
+
+// ============================================================================
+// Thresholds
+// ============================================================================
+
+#[test]
+fn test_thresholds_pass() {
+    let thresholds = Thresholds {
+        max_p50_ms: Some(10.0),
+        max_p95_ms: Some(50.0),
+        max_p99_ms: Some(100.0),
+        max_error_rate: Some(0.01),
+        min_throughput: Some(500.0),
+    };
+    assert!(thresholds.check(&report(5.0, 40.0, 90.0, 0.0, 800.0)).is_empty());
+    assert!(Thresholds::default().check(&report(5.0, 40.0, 90.0, 0.5, 1.0)).is_empty());
+}
+
+#[test]
+fn test_thresholds_fail() {
+    let thresholds = Thresholds {
+        max_p95_ms: Some(50.0),
+        max_error_rate: Some(0.01),
+        min_throughput: Some(500.0),
+        ..Thresholds::default()
+    };
+    let failures = thresholds.check(&report(5.0, 60.0, 90.0, 0.02, 400.0));
+
+    assert_eq!(failures.len(), 3, "{failures:?}");
+    assert!(failures[0].starts_with("p95 latency"));
+    assert!(failures[1].starts_with("error rate"));
+    assert!(failures[2].starts_with("throughput"));
+}
+
+#[test]
+fn test_thresholds_fail_without_requests() {
+    let mut empty = report(0.0, 0.0, 0.0, 0.0, 0.0);
+    empty.requests = 0;
+    assert_eq!(Thresholds::default().check(&empty), vec!["no requests completed"]);
+}
+
+#[test]
+fn test_baseline_regressions() {
+    let baseline = report(5.0, 40.0, 90.0, 0.0, 800.0);
+
+    // Within 20%
+    let current = report(5.5, 47.0, 100.0, 0.0, 700.0);
+    assert!(current.regressions(&baseline, 0.2).is_empty());
+
+    let current = report(5.5, 60.0, 100.0, 0.0, 500.0);
+    let regressions = current.regressions(&baseline, 0.2);
+    assert_eq!(regressions.len(), 2, "{regressions:?}");
+    assert!(regressions[0].starts_with("p95 latency"));
+    assert!(regressions[1].starts_with("throughput"));
+}
+
+#[test]
+fn test_report_round_trips_as_json() {
+    let report = report(5.0, 40.0, 90.0, 0.0, 800.0);
+    let json = serde_json::to_value(&report).unwrap();
+
+    assert_eq!(json["latency_ms"]["p95"], 40.0);
+    assert_eq!(json["statuses"]["201"], 1000);
+    assert_eq!(serde_json::from_value::<LoadReport>(json).unwrap(), report);
+}
+
+// ============================================================================
+// Render benchmarks
+// ============================================================================
+
+#[tokio::test]
+async fn test_bench_render_times_a_synthetic_form() {
+    let db = TestDb::new().await;
+    let form = FormBuilder::create(db.conn(), loadtest::bench_form("bench-render-20", 20))
+        .await
+        .unwrap();
+
+    let report = loadtest::bench_render(db.conn(), &form, 5).await.unwrap();
+
+    assert_eq!(report.form, "bench-render-20");
+    assert_eq!(report.steps, 20);
+    assert_eq!(report.fields, 100);
+    assert_eq!(report.iterations, 5);
+    assert!(report.html_ms.p50 > 0.0 && report.html_ms.p50 <= report.html_ms.max);
+    assert!(report.json_ms.p50 > 0.0 && report.json_ms.p50 <= report.json_ms.max);
+}
@@ -0,0 +1,212 @@
+//! Tests for location fields.
+
+mod common;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, FormError, ReverseGeocoder,
+    SubmissionEntity,
+};
+use async_trait::async_trait;
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+const URI: &str = "/api/forms/meetup";
+
+fn meetup_form() -> CreateFormInput {
+    CreateFormInput::new("Meetup", "meetup").step(CreateStepInput::new("Main").fields(vec![
+        CreateFieldInput::new("name", "Name", "text"),
+        CreateFieldInput::new("spot", "Meeting point", "location").required(),
+    ]))
+}
+
+async fn stored_spots(app: &TestApp) -> Vec<Value> {
+    SubmissionEntity::find()
+        .all(app.db())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.data["spot"].clone())
+        .collect()
+}
+
+/// Names every place after its hemisphere.
+struct Hemispheres;
+
+#[async_trait]
+impl ReverseGeocoder for Hemispheres {
+    async fn reverse_geocode(&self, lat: f64, _lng: f64) -> Option<String> {
+        (lat != 0.0).then(|| if lat > 0.0 { "North" } else { "South" }.to_string())
+    }
+}
+
+/// Counts its lookups, naming nothing.
+#[derive(Clone, Default)]
+struct Counting(Arc<AtomicUsize>);
+
+#[async_trait]
+impl ReverseGeocoder for Counting {
+    async fn reverse_geocode(&self, _lat: f64, _lng: f64) -> Option<String> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        None
+    }
+}
+
+#[tokio::test]
+async fn test_location_renders_with_locate_button() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), meetup_form()).await;
+
+    let html = app.get(URI).await.text();
+    assert!(html.contains("<div class=\"af-location\" data-af-location=\"spot\">"));
+    // Shown by the WASM client where the browser can locate the device
+    assert!(html.contains(
+        "<button type=\"button\" class=\"af-location-button\" data-af-locate=\"spot\" hidden>Use my location</button>"
+    ));
+    assert!(html.contains(
+        "name=\"spot[lat]\" id=\"spot\" value=\"\" step=\"any\" min=\"-90\" max=\"90\""
+    ));
+    assert!(html.contains(
+        "name=\"spot[lng]\" id=\"spot-lng\" value=\"\" step=\"any\" min=\"-180\" max=\"180\""
+    ));
+    assert!(html.contains("<input type=\"hidden\" name=\"spot[accuracy]\""));
+    assert!(html.contains("name=\"spot[label]\" id=\"spot-label\""));
+
+    let json: Value = app.get(&format!("{URI}/json")).await.json();
+    assert_eq!(json["steps"][0]["fields"][1]["field_type"], "location");
+}
+
+#[tokio::test]
+async fn test_location_is_stored_as_object() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), meetup_form()).await;
+
+    let spot = json!({ "lat": 51.5, "lng": -0.12, "accuracy": 25.0, "label": "King's Cross" });
+    app.post_json(URI, &json!({ "name": "Ada", "spot": spot }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    app.post_form(
+        &format!("{URI}/submit"),
+        &[
+            ("name", "Grace"),
+            ("spot[lat]", "48.85"),
+            ("spot[lng]", "2.35"),
+            ("spot[accuracy]", ""),
+            ("spot[label]", ""),
+        ],
+    )
+    .await
+    .assert_status(StatusCode::SEE_OTHER);
+
+    let spots = stored_spots(&app).await;
+    assert!(spots.contains(&spot));
+    assert!(spots.contains(&json!({ "lat": 48.85, "lng": 2.35 })));
+}
+
+#[tokio::test]
+async fn test_location_coordinates_are_range_checked() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), meetup_form()).await;
+
+    for spot in [
+        json!({ "lat": 90.5, "lng": 0.0 }),
+        json!({ "lat": 0.0, "lng": -180.5 }),
+        json!({ "lat": 0.0, "lng": 0.0, "accuracy": -5.0 }),
+        json!("51.5, -0.12"),
+        Value::Null,
+    ] {
+        let response = app.post_json(URI, &json!({ "spot": spot })).await;
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(response.text().contains("Meeting point"), "{spot}");
+    }
+
+    // Coordinates that aren't numbers re-render with the error, as typed
+    let response = app
+        .post_form(&format!("{URI}/submit"), &[("spot[lat]", "north"), ("spot[lng]", "2.35")])
+        .await;
+    response.assert_status(StatusCode::OK);
+    let html = response.text();
+    assert!(html.contains("Meeting point must be a location"));
+    assert!(html.contains("name=\"spot[lat]\" id=\"spot\" value=\"north\""));
+    assert!(stored_spots(&app).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_reverse_geocoder_labels_unnamed_locations() {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .reverse_geocoder(Hemispheres)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), meetup_form()).await;
+
+    for spot in [
+        json!({ "lat": 51.5, "lng": -0.12 }),
+        json!({ "lat": -33.9, "lng": 151.2, "label": "Sydney" }),
+        json!({ "lat": 0.0, "lng": 0.0 }),
+    ] {
+        app.post_json(URI, &json!({ "spot": spot }))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    let mut labels: Vec<Value> =
+        stored_spots(&app).await.iter().map(|s| s["label"].clone()).collect();
+    labels.sort_by_key(Value::to_string);
+    // The respondent's name is kept; unknown places stay unnamed
+    assert_eq!(labels, vec![json!("North"), json!("Sydney"), Value::Null]);
+}
+
+#[tokio::test]
+async fn test_reverse_geocoder_skips_rejected_submissions() {
+    let test_db = TestDb::new().await;
+    let geocoder = Counting::default();
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .reverse_geocoder(geocoder.clone())
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), meetup_form()).await;
+
+    app.post_json(URI, &json!({ "spot": { "lat": 999.0, "lng": 0.0 } }))
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(geocoder.0.load(Ordering::SeqCst), 0);
+
+    app.post_json(URI, &json!({ "spot": { "lat": 51.5, "lng": -0.12 } }))
+        .await
+        .assert_status(StatusCode::CREATED);
+    assert_eq!(geocoder.0.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_location_exports_as_lat_and_lng_columns() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), meetup_form()).await;
+    app.post_json(URI, &json!({ "name": "Ada", "spot": { "lat": 51.5, "lng": -0.12 } }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = app.get(&format!("/api/admin/forms/{}/submissions/export", form.id)).await;
+    response.assert_status(StatusCode::OK);
+    let text = response.text();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines[0].ends_with(",Name,Meeting point (lat),Meeting point (lng)"));
+    assert!(lines[1].ends_with(",Ada,51.5,-0.12"));
+}
+
+#[tokio::test]
+async fn test_location_is_not_allowed_in_groups() {
+    let db = TestDb::new().await;
+    let input = CreateFormInput::new("Trip", "trip").step(CreateStepInput::new("Main").field(
+        CreateFieldInput::new("stops", "Stops", "repeater")
+            .field(CreateFieldInput::new("where", "Where", "location")),
+    ));
+    let err = FormBuilder::create(&db.db, input).await.unwrap_err();
+    assert!(matches!(err, FormError::InvalidData(message) if message.contains("'where'")));
+}
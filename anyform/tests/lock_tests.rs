@@ -0,0 +1,145 @@
+//! Tests for database-backed job locks.
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyform::database::SqliteConfig;
+use anyform::entities::lock::{Column as LockColumn, Entity as LockEntity};
+use anyform::{FormError, LockService, MigratorTrait};
+use common::TestDb;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use tempfile::TempDir;
+
+const TTL: Duration = Duration::from_secs(60);
+
+/// Backdates the lock's expiry, as if its holder had crashed long ago.
+async fn expire(db: &sea_orm::DatabaseConnection, name: &str) {
+    let past = chrono::Utc::now().fixed_offset() - chrono::Duration::hours(1);
+    LockEntity::update_many()
+        .col_expr(LockColumn::ExpiresAt, Expr::value(past))
+        .filter(LockColumn::Name.eq(name))
+        .exec(db)
+        .await
+        .unwrap();
+}
+
+// ============================================================================
+// Acquire and release
+// ============================================================================
+
+#[tokio::test]
+async fn test_held_lock_blocks_others_until_released() {
+    let test_db = TestDb::new().await;
+    let db = test_db.conn();
+
+    let lock = LockService::try_acquire(db, "retention", TTL).await.unwrap();
+    let lock = lock.expect("lock should be free");
+    assert_eq!(lock.name(), "retention");
+
+    assert!(LockService::try_acquire(db, "retention", TTL).await.unwrap().is_none());
+    // Other names are independent
+    let other = LockService::try_acquire(db, "archival", TTL).await.unwrap();
+    other.expect("other lock should be free").release().await.unwrap();
+
+    lock.release().await.unwrap();
+    let lock = LockService::try_acquire(db, "retention", TTL).await.unwrap();
+    lock.expect("released lock should be free").release().await.unwrap();
+    assert_eq!(LockEntity::find().all(db).await.unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_expired_lock_is_taken_over() {
+    let test_db = TestDb::new().await;
+    let db = test_db.conn();
+
+    let stale = LockService::try_acquire(db, "retention", TTL).await.unwrap().unwrap();
+    expire(db, "retention").await;
+
+    let lock = LockService::try_acquire(db, "retention", TTL).await.unwrap();
+    let lock = lock.expect("expired lock should be taken over");
+
+    // The stale holder releasing late leaves the new holder's lock alone
+    stale.release().await.unwrap();
+    assert!(LockService::try_acquire(db, "retention", TTL).await.unwrap().is_none());
+
+    lock.release().await.unwrap();
+}
+
+// ============================================================================
+// Exclusive jobs
+// ============================================================================
+
+#[tokio::test]
+async fn test_run_exclusive_skips_job_when_lock_is_held() {
+    let test_db = TestDb::new().await;
+    let db = test_db.conn();
+
+    let outer = LockService::run_exclusive(db, "retention", TTL, || async {
+        let inner = LockService::run_exclusive(db, "retention", TTL, || async {
+            Ok::<_, FormError>("inner")
+        })
+        .await?;
+        assert_eq!(inner, None);
+        Ok("outer")
+    })
+    .await
+    .unwrap();
+    assert_eq!(outer, Some("outer"));
+}
+
+#[tokio::test]
+async fn test_run_exclusive_releases_lock_when_job_fails() {
+    let test_db = TestDb::new().await;
+    let db = test_db.conn();
+
+    let result = LockService::run_exclusive(db, "retention", TTL, || async {
+        Err::<(), _>(FormError::InvalidData("job failed".to_string()))
+    })
+    .await;
+    assert!(result.is_err());
+
+    let ran = LockService::run_exclusive(db, "retention", TTL, || async { Ok(()) })
+        .await
+        .unwrap();
+    assert_eq!(ran, Some(()));
+}
+
+#[tokio::test]
+async fn test_concurrent_jobs_never_overlap() {
+    let dir = TempDir::new().unwrap();
+    let url = format!("sqlite:{}?mode=rwc", dir.path().join("anyform.db").display());
+    let db = SqliteConfig::new().connect(&url).await.unwrap();
+    anyform::Migrator::up(&db, None).await.unwrap();
+
+    let running = Arc::new(AtomicUsize::new(0));
+    let most = Arc::new(AtomicUsize::new(0));
+    let runs: Vec<_> = (0..8)
+        .map(|_| {
+            let (db, running, most) = (db.clone(), running.clone(), most.clone());
+            tokio::spawn(async move {
+                LockService::run_exclusive(&db, "retention", TTL, || async {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    most.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(())
+                })
+                .await
+                .unwrap()
+            })
+        })
+        .collect();
+
+    let mut ran = 0;
+    for run in runs {
+        if run.await.unwrap().is_some() {
+            ran += 1;
+        }
+    }
+    assert!(ran >= 1);
+    assert_eq!(most.load(Ordering::SeqCst), 1);
+}
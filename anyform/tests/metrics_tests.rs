@@ -0,0 +1,82 @@
+//! Tests for the Prometheus metrics endpoint.
+
+mod common;
+
+use anyform::metrics::Metrics;
+use anyform::{CreateFieldInput, CreateFormInput, CreateStepInput};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use serde_json::json;
+
+async fn metrics_app(metrics: Metrics) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .metrics(metrics)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(
+        app.db(),
+        CreateFormInput::new("Contact", "contact").step(
+            CreateStepInput::new("Main")
+                .field(CreateFieldInput::new("email", "Email", "email").required()),
+        ),
+    )
+    .await;
+    app
+}
+
+/// Returns the value of the series `series` in the exposition `text`.
+fn sample(text: &str, series: &str) -> Option<f64> {
+    text.lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+        .map(|value| value.parse().unwrap())
+}
+
+#[tokio::test]
+async fn test_metrics_count_submissions_and_renders() {
+    let metrics = Metrics::new();
+    let app = metrics_app(metrics.clone()).await;
+
+    app.get("/api/forms/contact").await.assert_status(StatusCode::OK);
+    app.get("/api/forms/contact/json").await.assert_status(StatusCode::OK);
+    app.post_json("/api/forms/contact", &json!({ "email": "ada@example.com" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+    app.post_json("/api/forms/contact", &json!({ "email": "" }))
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    app.post_form("/api/forms/contact/submit", &[("email", "not an email")])
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app.get("/metrics").await;
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("text/plain; version=0.0.4");
+    let text = response.text();
+    assert_eq!(sample(&text, "anyform_submissions_total{form=\"contact\"}"), Some(1.0));
+    assert_eq!(
+        sample(&text, "anyform_validation_failures_total{form=\"contact\"}"),
+        Some(2.0)
+    );
+    // The page and the re-render with errors
+    assert_eq!(
+        sample(&text, "anyform_render_duration_seconds_count{format=\"html\"}"),
+        Some(2.0)
+    );
+    assert_eq!(
+        sample(&text, "anyform_render_duration_seconds_count{format=\"json\"}"),
+        Some(1.0)
+    );
+    let queries = sample(&text, "anyform_db_query_duration_seconds_count{outcome=\"ok\"}");
+    assert!(queries.unwrap() > 0.0);
+
+    // The handle sees what the endpoint serves
+    assert!(metrics.render().contains("anyform_submissions_total{form=\"contact\"} 1"));
+}
+
+#[tokio::test]
+async fn test_metrics_are_off_by_default() {
+    let app = TestApp::new().await;
+    app.get("/metrics").await.assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,147 @@
+//! Tests for importing a field's options from CSV and JSON.
+
+#![cfg(feature = "admin")]
+
+mod common;
+
+use anyform::entities::field_option::Entity as FieldOptionEntity;
+use anyform::{CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FormBuilder};
+use axum::body::Body;
+use common::app::TestResponse;
+use common::{create_test_form, TestApp};
+use http::{Request, StatusCode};
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+fn shipping_form() -> CreateFormInput {
+    CreateFormInput::new("Shipping", "shipping").step(CreateStepInput::new("Main").fields(vec![
+        CreateFieldInput::new("name", "Name", "text"),
+        CreateFieldInput::new("country", "Country", "select").options(vec![
+            CreateOptionInput::new("France", "fr").capacity(10),
+            CreateOptionInput::new("Germany", "de"),
+            CreateOptionInput::new("Atlantis", "at"),
+        ]),
+    ]))
+}
+
+async fn import_csv(app: &TestApp, form_id: Uuid, query: &str, csv: &str) -> TestResponse {
+    let request = Request::builder()
+        .uri(format!("/api/admin/forms/{form_id}/fields/country/options/import{query}"))
+        .method("POST")
+        .header("Content-Type", "text/csv")
+        .body(Body::from(csv.to_string()))
+        .unwrap();
+    app.send_raw(request).await
+}
+
+/// Returns the field's options as (value, label, group), in order.
+async fn options(app: &TestApp) -> Vec<(String, String, Option<String>)> {
+    let mut options = FieldOptionEntity::find().all(app.db()).await.unwrap();
+    options.sort_by_key(|o| o.order);
+    options.into_iter().map(|o| (o.value, o.label, o.group)).collect()
+}
+
+#[tokio::test]
+async fn test_csv_import_replaces_options() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), shipping_form()).await;
+
+    let csv = "value,label,group\nde,Deutschland,Europe\nfr,France,Europe\nus,United States,Americas\n";
+    let response = import_csv(&app, form.id, "", csv).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.json::<Value>()["data"],
+        json!({
+            "added": ["us"],
+            "updated": ["de", "fr"],
+            "removed": ["at"],
+            "unchanged": 0,
+            "applied": true,
+        })
+    );
+
+    let group = |g: &str| Some(g.to_string());
+    assert_eq!(
+        options(&app).await,
+        vec![
+            ("de".into(), "Deutschland".into(), group("Europe")),
+            ("fr".into(), "France".into(), group("Europe")),
+            ("us".into(), "United States".into(), group("Americas")),
+        ]
+    );
+    // Re-imported options keep their capacity
+    let france = FieldOptionEntity::find().all(app.db()).await.unwrap();
+    assert_eq!(france.iter().find(|o| o.value == "fr").unwrap().capacity, Some(10));
+
+    let html = app.get("/api/forms/shipping").await;
+    html.assert_body_contains("<optgroup label=\"Europe\">");
+    let json = app.get("/api/forms/shipping/json").await.json::<Value>();
+    assert_eq!(json["steps"][0]["fields"][1]["options"][2]["group"], "Americas");
+}
+
+#[tokio::test]
+async fn test_json_import_merges_options() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), shipping_form()).await;
+
+    let response = app
+        .post_json(
+            &format!("/api/admin/forms/{}/fields/country/options/import?mode=merge", form.id),
+            &json!([
+                { "label": "Spain", "value": "es", "points": 2 },
+                { "label": "Atlantis (lost)", "value": "at" },
+            ]),
+        )
+        .await;
+    response.assert_status(StatusCode::OK);
+    let diff = &response.json::<Value>()["data"];
+    assert_eq!(diff["added"], json!(["es"]));
+    assert_eq!(diff["updated"], json!(["at"]));
+    assert_eq!(diff["removed"], json!([]));
+    assert_eq!(diff["unchanged"], 2);
+
+    let values: Vec<String> = options(&app).await.into_iter().map(|o| o.0).collect();
+    assert_eq!(values, vec!["fr", "de", "at", "es"]);
+}
+
+#[tokio::test]
+async fn test_dry_run_changes_nothing() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), shipping_form()).await;
+    let before = options(&app).await;
+
+    let response = import_csv(&app, form.id, "?dry_run=true", "label\nFrance\nItaly\n").await;
+    response.assert_status(StatusCode::OK);
+    let diff = &response.json::<Value>()["data"];
+    assert_eq!(diff["applied"], false);
+    assert_eq!(diff["added"], json!(["France", "Italy"]));
+    assert_eq!(diff["removed"].as_array().unwrap().len(), 3);
+    assert_eq!(options(&app).await, before);
+}
+
+#[tokio::test]
+async fn test_invalid_imports_are_rejected() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), shipping_form()).await;
+
+    let response = import_csv(&app, form.id, "", "label,value\nFrance,fr\nFrankreich,fr\n").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    response.assert_body_contains("value 'fr'");
+
+    let response = import_csv(&app, form.id, "", "name,value\nFrance,fr\n").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    let request = Request::builder()
+        .uri(format!("/api/admin/forms/{}/fields/name/options/import", form.id))
+        .method("POST")
+        .body(Body::from("label\nA\n"))
+        .unwrap();
+    app.send_raw(request).await.assert_status(StatusCode::BAD_REQUEST);
+
+    let response = import_csv(&app, Uuid::new_v4(), "", "label\nA\n").await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    FormBuilder::soft_delete(app.db(), form.id).await.unwrap();
+    let response = import_csv(&app, form.id, "", "label\nA\n").await;
+    response.assert_status(StatusCode::NOT_FOUND);
+}
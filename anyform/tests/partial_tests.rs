@@ -0,0 +1,143 @@
+//! Tests for saving each step of multi-step forms as partial responses.
+
+mod common;
+
+use anyform::{FormSettings, SubmissionEntity};
+use common::{create_test_form, multi_step_form, TestApp};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+
+const SLUG: &str = "test-multi-step";
+
+/// Creates the multi-step fixture form saving each step, and returns the
+/// form's admin URI.
+async fn saving_app() -> (TestApp, String) {
+    let app = TestApp::with_admin().await;
+    let input = multi_step_form().settings(FormSettings::new().save_each_step(true));
+    let form = create_test_form(app.db(), input).await;
+    (app, format!("/api/admin/forms/{}", form.id))
+}
+
+/// Starts a submission, saves the first step and returns its ID.
+async fn save_first_step(app: &TestApp) -> String {
+    let response = app
+        .post_json(&format!("/api/forms/{SLUG}/submissions/start"), &json!({}))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let id = response.json::<Value>()["data"]["submission_id"].as_str().unwrap().to_string();
+    app.patch_json(
+        &format!("/api/forms/{SLUG}/submissions/{id}/step"),
+        &json!({ "data": { "first_name": "Ada", "last_name": "Lovelace" } }),
+    )
+    .await
+    .assert_status(StatusCode::OK);
+    id
+}
+
+fn answers() -> Value {
+    json!({
+        "first_name": "Ada",
+        "last_name": "Lovelace",
+        "email": "ada@example.com",
+        "agree_terms": true
+    })
+}
+
+#[tokio::test]
+async fn test_html_marks_forms_saving_each_step() {
+    let (app, _) = saving_app().await;
+    let html = app.get(&format!("/api/forms/{SLUG}")).await.text();
+    assert!(html.contains("data-af-save-steps"));
+    assert!(html.contains("data-af-step-id=\""));
+
+    let app = TestApp::new().await;
+    create_test_form(app.db(), multi_step_form()).await;
+    let html = app.get(&format!("/api/forms/{SLUG}")).await.text();
+    assert!(!html.contains("data-af-save-steps"));
+    assert!(!html.contains("data-af-step-id"));
+}
+
+#[tokio::test]
+async fn test_analytics_count_partial_responses() {
+    let (app, admin) = saving_app().await;
+    save_first_step(&app).await;
+    // Started but nothing saved yet
+    app.post_json(&format!("/api/forms/{SLUG}/submissions/start"), &json!({}))
+        .await
+        .assert_status(StatusCode::CREATED);
+    app.post_json(&format!("/api/forms/{SLUG}"), &answers())
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let json: Value = app.get(&format!("{admin}/analytics")).await.json();
+    assert_eq!(json["data"]["completed"], 1);
+    assert_eq!(json["data"]["in_progress"], 2);
+    assert_eq!(json["data"]["partial"], 1);
+}
+
+#[tokio::test]
+async fn test_exports_include_partials_on_request() {
+    let (app, admin) = saving_app().await;
+    save_first_step(&app).await;
+    app.post_json(&format!("/api/forms/{SLUG}/submissions/start"), &json!({}))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let csv = app.get(&format!("{admin}/submissions/export")).await.text();
+    assert!(!csv.contains("Lovelace"));
+
+    let csv = app
+        .get(&format!("{admin}/submissions/export?include_partials=true"))
+        .await
+        .text();
+    assert!(csv.contains("Lovelace"));
+    assert!(csv.contains("in_progress"));
+    // The empty start isn't a response
+    assert_eq!(csv.lines().count(), 2, "{csv}");
+
+    app.get(&format!("{admin}/submissions/export?format=xlsx&include_partials=true"))
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_submitting_discards_the_saved_steps() {
+    let (app, _) = saving_app().await;
+    let id = save_first_step(&app).await;
+
+    let mut data = answers();
+    data["_af_submission"] = json!(id);
+    app.post_json(&format!("/api/forms/{SLUG}"), &data)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let submissions = SubmissionEntity::find().all(app.db()).await.unwrap();
+    assert_eq!(submissions.len(), 1);
+    assert_ne!(submissions[0].id.to_string(), id);
+    assert!(submissions[0].data.get("_af_submission").is_none());
+}
+
+#[tokio::test]
+async fn test_html_submit_discards_the_saved_steps() {
+    let (app, _) = saving_app().await;
+    let id = save_first_step(&app).await;
+
+    let response = app
+        .post_form(
+            &format!("/api/forms/{SLUG}/submit"),
+            &[
+                ("first_name", "Ada"),
+                ("last_name", "Lovelace"),
+                ("email", "ada@example.com"),
+                ("agree_terms", "true"),
+                ("_af_submission", &id),
+            ],
+        )
+        .await;
+    response.assert_status(StatusCode::SEE_OTHER);
+
+    let submissions = SubmissionEntity::find().all(app.db()).await.unwrap();
+    assert_eq!(submissions.len(), 1);
+    assert!(submissions[0].completed_at.is_some());
+}
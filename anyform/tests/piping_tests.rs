@@ -0,0 +1,113 @@
+//! Tests for piping earlier answers into labels, descriptions and help text.
+
+mod common;
+
+use std::collections::HashMap;
+
+use anyform::error::ValidationErrors;
+use anyform::schema::FieldValue;
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, TeraRenderer,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+
+const URI: &str = "/api/forms/checkin";
+
+fn checkin_form() -> CreateFormInput {
+    CreateFormInput::new("Check-in", "checkin")
+        .step(CreateStepInput::new("You").fields(vec![
+            CreateFieldInput::new("first_name", "First name", "text"),
+            CreateFieldInput::new("plan", "Plan", "radio")
+                .option(CreateOptionInput::new("Pro & more", "pro"))
+                .option(CreateOptionInput::new("Free", "free")),
+        ]))
+        .step(
+            CreateStepInput::new("Feedback")
+                .description("Thanks for choosing {{field:plan|us}}.")
+                .field(
+                    CreateFieldInput::new(
+                        "rating",
+                        "Hi {{field:first_name|there}}, how satisfied are you?",
+                        "number",
+                    )
+                    .help_text("Rate {{ field:plan }} from 1 to 5")
+                    .required(),
+                ),
+        )
+}
+
+#[tokio::test]
+async fn test_placeholders_show_fallbacks_until_answered() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), checkin_form()).await;
+
+    let html = app.get(URI).await.text();
+    assert!(html.contains(
+        "<label for=\"rating\">Hi <span class=\"af-pipe\" data-af-pipe=\"first_name\" data-af-fallback=\"there\">there</span>, how satisfied are you?"
+    ));
+    assert!(html.contains(
+        "<p>Thanks for choosing <span class=\"af-pipe\" data-af-pipe=\"plan\" data-af-fallback=\"us\">us</span>.</p>"
+    ));
+    // No fallback: nothing until the WASM client fills it in
+    assert!(html.contains("Rate <span class=\"af-pipe\" data-af-pipe=\"plan\"></span> from 1 to 5"));
+
+    // JSON clients get the placeholders, for `FormState::resolve_text`
+    let json: serde_json::Value = app.get(&format!("{URI}/json")).await.json();
+    assert_eq!(
+        json["steps"][1]["fields"][0]["label"],
+        "Hi {{field:first_name|there}}, how satisfied are you?"
+    );
+}
+
+#[tokio::test]
+async fn test_rerender_quotes_submitted_answers_escaped() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), checkin_form()).await;
+
+    let response = app
+        .post_form(
+            &format!("{URI}/submit"),
+            &[("first_name", "<b>Ada</b>"), ("plan", "pro")],
+        )
+        .await;
+    response.assert_status(StatusCode::OK);
+    let html = response.text();
+    assert!(html.contains(
+        "Hi <span class=\"af-pipe\" data-af-pipe=\"first_name\" data-af-fallback=\"there\">&lt;b&gt;Ada&lt;/b&gt;</span>, how satisfied are you?"
+    ));
+    // Choices are quoted by their labels
+    assert!(html.contains("Thanks for choosing <span class=\"af-pipe\" data-af-pipe=\"plan\" data-af-fallback=\"us\">Pro &amp; more</span>."));
+    assert!(html.contains("Rate <span class=\"af-pipe\" data-af-pipe=\"plan\">Pro &amp; more</span> from 1 to 5"));
+    assert!(!html.contains("<b>Ada</b>"));
+    // Messages read placeholders as their fallbacks
+    assert!(html.contains("Hi there, how satisfied are you? is required"));
+}
+
+#[tokio::test]
+async fn test_print_shows_fallbacks() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), checkin_form()).await;
+
+    let html = app.get(&format!("{URI}?print=true")).await.text();
+    assert!(html.contains("Hi <span class=\"af-pipe\" data-af-pipe=\"first_name\" data-af-fallback=\"there\">there</span>, how satisfied are you?"));
+    assert!(!html.contains("{{field:"));
+}
+
+#[tokio::test]
+async fn test_tera_context_resolves_placeholders() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), checkin_form()).await;
+
+    let values = HashMap::from([
+        ("first_name".to_string(), FieldValue::from("Ada")),
+        ("plan".to_string(), FieldValue::from("free")),
+    ]);
+    let ctx = TeraRenderer::context_with_values(app.db(), &form, &values, &ValidationErrors::new())
+        .await
+        .unwrap();
+    let step = &ctx.get("form").unwrap()["steps"][1];
+    assert_eq!(step["description"], "Thanks for choosing Free.");
+    assert_eq!(step["fields"][0]["label"], "Hi Ada, how satisfied are you?");
+    assert_eq!(step["fields"][0]["help_text"], "Rate Free from 1 to 5");
+}
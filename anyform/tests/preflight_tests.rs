@@ -0,0 +1,224 @@
+//! Tests for the startup self-check.
+
+mod common;
+
+use std::collections::HashMap;
+
+use anyform::preflight::{FormCounts, Severity};
+use anyform::{
+    AnyFormRouter, CreateFieldInput, CreateFormInput, CreateStepInput, Field, FieldValidator,
+    FieldValue, Form, FormBuilder, FormSettings, MigratorTrait, Respondent, RespondentResolver,
+    ValidationRules,
+};
+use common::{contact_form, create_test_form, TestDb};
+use http::HeaderMap;
+use sea_orm::{ConnectionTrait, Database};
+
+struct AnyoneResolver;
+
+#[async_trait::async_trait]
+impl RespondentResolver for AnyoneResolver {
+    async fn resolve(&self, _headers: &HeaderMap) -> Option<Respondent> {
+        Some(Respondent::new("anyone"))
+    }
+}
+
+struct NoOpValidator;
+
+#[async_trait::async_trait]
+impl FieldValidator for NoOpValidator {
+    async fn validate(
+        &self,
+        _form: &Form,
+        _field: &Field,
+        _value: &FieldValue,
+        _values: &HashMap<String, FieldValue>,
+    ) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+fn form_with_settings(slug: &str, settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Form", slug)
+        .settings(settings)
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text")))
+}
+
+fn codes(report: &anyform::preflight::PreflightReport) -> Vec<&'static str> {
+    report.issues.iter().map(|i| i.code).collect()
+}
+
+// ============================================================================
+// Migrations
+// ============================================================================
+
+#[tokio::test]
+async fn test_migrated_database_passes() {
+    let db = TestDb::new().await;
+    create_test_form(db.conn(), contact_form()).await;
+
+    let report = AnyFormRouter::preflight(db.conn()).await.unwrap();
+    assert!(report.is_ok());
+    assert!(report.issues.is_empty(), "{report}");
+    assert!(report.migrations.pending.is_empty());
+    assert_eq!(report.migrations.applied, anyform::Migrator::migrations().len());
+    assert_eq!(report.forms.as_ref().unwrap().published, 1);
+    assert!(report.ensure_ok().is_ok());
+    assert!(report.to_string().contains("No issues found."));
+}
+
+#[tokio::test]
+async fn test_unmigrated_database_fails() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+
+    let report = AnyFormRouter::preflight(&db).await.unwrap();
+    assert!(!report.is_ok());
+    assert_eq!(codes(&report), ["MIGRATIONS_PENDING"]);
+    assert_eq!(report.migrations.pending.len(), anyform::Migrator::migrations().len());
+    assert_eq!(report.forms, None);
+
+    let err = report.ensure_ok().unwrap_err();
+    assert_eq!(err.error_code(), "PREFLIGHT_FAILED");
+    assert!(err.to_string().contains("anyform migrate --up"));
+
+    // The check only reads: no migrations table was created
+    let tables = db
+        .query_all(sea_orm::Statement::from_string(
+            db.get_database_backend(),
+            "SELECT name FROM sqlite_master WHERE type = 'table'",
+        ))
+        .await
+        .unwrap();
+    assert!(tables.is_empty());
+}
+
+#[tokio::test]
+async fn test_pending_and_unknown_migrations_are_listed() {
+    let db = TestDb::new().await;
+    anyform::Migrator::down(db.conn(), Some(1)).await.unwrap();
+    db.conn()
+        .execute_unprepared(
+            "INSERT INTO seaql_migrations (version, applied_at) \
+            VALUES ('m20990101_000001_from_the_future', 0)",
+        )
+        .await
+        .unwrap();
+
+    let report = AnyFormRouter::preflight(db.conn()).await.unwrap();
+    let last = anyform::Migrator::migrations().pop().unwrap();
+    assert_eq!(report.migrations.pending, [last.name()]);
+    assert_eq!(report.migrations.unknown, ["m20990101_000001_from_the_future"]);
+    assert_eq!(codes(&report), ["MIGRATIONS_PENDING", "UNKNOWN_MIGRATIONS"]);
+    assert_eq!(report.issues[1].severity, Severity::Warning);
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+#[tokio::test]
+async fn test_identified_forms_need_a_resolver() {
+    let db = TestDb::new().await;
+    let settings = FormSettings::new().identified(true);
+    create_test_form(db.conn(), form_with_settings("members", settings)).await;
+
+    let report = AnyFormRouter::preflight(db.conn()).await.unwrap();
+    assert_eq!(codes(&report), ["RESPONDENT_RESOLVER_MISSING"]);
+    assert!(report.issues[0].message.contains("members"));
+    assert!(!report.is_ok());
+
+    let report = AnyFormRouter::builder()
+        .database(db.conn().clone())
+        .respondent_resolver(AnyoneResolver)
+        .preflight()
+        .await
+        .unwrap();
+    assert!(report.is_ok(), "{report}");
+    assert!(!codes(&report).contains(&"RESPONDENT_RESOLVER_MISSING"));
+}
+
+#[tokio::test]
+async fn test_unserved_hooks_are_warnings() {
+    let db = TestDb::new().await;
+    let settings = FormSettings::new().notify_email("team@example.com");
+    create_test_form(db.conn(), form_with_settings("notify", settings)).await;
+    create_test_form(
+        db.conn(),
+        CreateFormInput::new("Signup", "signup").step(
+            CreateStepInput::new("Main").field(
+                CreateFieldInput::new("username", "Username", "text")
+                    .validation(ValidationRules::new().server_validate(true)),
+            ),
+        ),
+    )
+    .await;
+
+    let report = AnyFormRouter::preflight(db.conn()).await.unwrap();
+    assert_eq!(codes(&report), ["EVENTS_HOOK_MISSING", "FIELD_VALIDATOR_MISSING"]);
+    assert!(report.warnings().all(|i| i.severity == Severity::Warning));
+    assert!(report.is_ok());
+    assert!(report.to_string().contains("warning EVENTS_HOOK_MISSING"));
+}
+
+#[tokio::test]
+async fn test_unregistered_validators_are_warnings() {
+    let db = TestDb::new().await;
+    let rules = ValidationRules::new().validator("unique_email").validator("valid_coupon");
+    create_test_form(
+        db.conn(),
+        CreateFormInput::new("Signup", "signup").step(
+            CreateStepInput::new("Main")
+                .field(CreateFieldInput::new("email", "Email", "email").validation(rules)),
+        ),
+    )
+    .await;
+
+    let report = AnyFormRouter::preflight(db.conn()).await.unwrap();
+    assert_eq!(codes(&report), ["VALIDATOR_NOT_REGISTERED"]);
+    assert!(report.to_string().contains("unique_email, valid_coupon"));
+
+    let report = AnyFormRouter::builder()
+        .database(db.conn().clone())
+        .validator("unique_email", NoOpValidator)
+        .preflight()
+        .await
+        .unwrap();
+    let issue = report.issues.iter().find(|i| i.code == "VALIDATOR_NOT_REGISTERED").unwrap();
+    assert!(issue.message.contains("valid_coupon") && !issue.message.contains("unique_email"));
+
+    let report = AnyFormRouter::builder()
+        .database(db.conn().clone())
+        .validator("unique_email", NoOpValidator)
+        .validator("valid_coupon", NoOpValidator)
+        .preflight()
+        .await
+        .unwrap();
+    assert!(!codes(&report).contains(&"VALIDATOR_NOT_REGISTERED"));
+}
+
+#[tokio::test]
+async fn test_forms_are_counted_by_state() {
+    let db = TestDb::new().await;
+    create_test_form(db.conn(), form_with_settings("live", FormSettings::new())).await;
+    create_test_form(db.conn(), form_with_settings("draft", FormSettings::new().draft(true)))
+        .await;
+    create_test_form(db.conn(), form_with_settings("full", FormSettings::new().close_after(0)))
+        .await;
+    let old = create_test_form(db.conn(), form_with_settings("old", FormSettings::new())).await;
+    FormBuilder::archive(db.conn(), old.id).await.unwrap();
+    let gone = create_test_form(db.conn(), form_with_settings("gone", FormSettings::new())).await;
+    FormBuilder::soft_delete(db.conn(), gone.id).await.unwrap();
+
+    let report = AnyFormRouter::preflight(db.conn()).await.unwrap();
+    let expected = FormCounts {
+        published: 1,
+        drafts: 1,
+        archived: 1,
+        closed: 1,
+        deleted: 1,
+    };
+    assert_eq!(report.forms, Some(expected));
+    assert!(report
+        .to_string()
+        .contains("Forms: 1 published, 1 drafts, 1 archived, 1 closed, 1 deleted"));
+}
@@ -0,0 +1,129 @@
+//! Tests for field presets forms reference with `$ref`.
+
+#![cfg(feature = "admin")]
+
+mod common;
+
+use anyform::{
+    CreateFieldInput, CreateOptionInput, FieldEntity, FieldLibrary, FormBuilder, StepEntity,
+};
+use common::{TestApp, TestDb};
+use http::StatusCode;
+use serde_json::{json, Value};
+
+fn library() -> FieldLibrary {
+    FieldLibrary::new()
+        .preset(
+            "email",
+            vec![CreateFieldInput::new("email", "Email address", "email").required()],
+        )
+        .preset(
+            "eu_consent",
+            vec![
+                CreateFieldInput::new("consent", "I agree to the privacy policy", "checkbox")
+                    .required()
+                    .help_text("We keep your answers for one year."),
+                CreateFieldInput::new("contact_by", "Contact me by", "radio").options(vec![
+                    CreateOptionInput::new("Email", "email"),
+                    CreateOptionInput::new("Phone", "phone"),
+                ]),
+            ],
+        )
+}
+
+async fn app_with_library() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .field_library(library())
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+fn signup(slug: &str) -> Value {
+    json!({
+        "name": "Signup",
+        "slug": slug,
+        "steps": [{ "name": "Main", "fields": [
+            { "name": "name", "label": "Name", "field_type": "text" },
+            { "$ref": "email" },
+            { "$ref": "eu_consent" },
+        ] }],
+    })
+}
+
+/// Returns the names, labels and help texts of a form's fields.
+async fn fields(app: &TestApp, slug: &str) -> Vec<(String, String, Option<String>)> {
+    let form = FormBuilder::find_by_slug(app.db(), slug).await.unwrap().unwrap();
+    let steps = StepEntity::find_by_form(app.db(), form.id).await.unwrap();
+    let mut fields = Vec::new();
+    for step in steps {
+        for field in FieldEntity::find_by_step(app.db(), step.id).await.unwrap() {
+            fields.push((field.name, field.label, field.help_text));
+        }
+    }
+    fields
+}
+
+#[tokio::test]
+async fn test_references_are_expanded_on_create() {
+    let app = app_with_library().await;
+    app.post_json("/api/admin/forms", &signup("signup"))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let fields = fields(&app, "signup").await;
+    let names: Vec<&str> = fields.iter().map(|(n, _, _)| n.as_str()).collect();
+    assert_eq!(names, vec!["name", "email", "consent", "contact_by"]);
+    assert_eq!(fields[1].1, "Email address");
+    assert_eq!(fields[2].2.as_deref(), Some("We keep your answers for one year."));
+
+    // The presets' rules apply: email and consent are required
+    let response = app.post_json("/api/forms/signup", &json!({ "name": "Ada" })).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn test_references_are_expanded_on_update_and_sync() {
+    let app = app_with_library().await;
+    let response = app
+        .post_json("/api/admin/forms/sync", &json!([signup("a"), signup("b")]))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["data"]["created"], 2);
+    assert_eq!(fields(&app, "b").await.len(), 4);
+
+    let form = FormBuilder::find_by_slug(app.db(), "a").await.unwrap().unwrap();
+    let mut update = signup("a");
+    update["steps"][0]["fields"] = json!([{ "$ref": "email" }]);
+    app.put_json(&format!("/api/admin/forms/{}", form.id), &update)
+        .await
+        .assert_status(StatusCode::OK);
+    let fields = fields(&app, "a").await;
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields[0].0, "email");
+}
+
+#[tokio::test]
+async fn test_unknown_or_unexpanded_references_are_rejected() {
+    let app = app_with_library().await;
+    let mut input = signup("signup");
+    input["steps"][0]["fields"][1] = json!({ "$ref": "phone" });
+    let response = app.post_json("/api/admin/forms", &input).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let json: Value = response.json();
+    assert!(json["error"]["message"].as_str().unwrap().contains("Unknown field preset 'phone'"));
+
+    let response = app
+        .post_json("/api/admin/forms/sync", &json!([input]))
+        .await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["data"]["errors"].as_array().unwrap().len(), 1);
+
+    // Without a library, references are refused rather than stored empty
+    let app = TestApp::with_admin().await;
+    let response = app.post_json("/api/admin/forms", &signup("signup")).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(FormBuilder::find_by_slug(app.db(), "signup").await.unwrap().is_none());
+}
@@ -0,0 +1,219 @@
+//! Tests for draft forms and signed preview links.
+
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, Form, FormBuilder, FormEntity,
+    FormEvents, FormSettings, PreviewSecret, PreviewToken, Submission, SubmissionEntity,
+};
+use chrono::Duration;
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+
+const SECRET: &str = "preview-test-secret";
+
+/// Counts submission events.
+#[derive(Clone, Default)]
+struct CountingEvents(Arc<AtomicUsize>);
+
+#[async_trait::async_trait]
+impl FormEvents for CountingEvents {
+    async fn on_submission_created(&self, _form: &Form, _submission: &Submission) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn rsvp_form(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("RSVP", "rsvp")
+        .settings(settings)
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text").required()))
+}
+
+async fn preview_app(events: CountingEvents) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .preview_secret(SECRET)
+        .events(events)
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+fn token_for(form: &Form) -> String {
+    PreviewSecret::new(SECRET).sign(&PreviewToken::new(form.id, Duration::hours(1)))
+}
+
+#[tokio::test]
+async fn test_draft_is_hidden_from_public_routes() {
+    let app = preview_app(CountingEvents::default()).await;
+    create_test_form(app.db(), rsvp_form(FormSettings::new().draft(true))).await;
+
+    app.get("/api/forms/rsvp").await.assert_status(StatusCode::NOT_FOUND);
+    app.get("/api/forms/rsvp/json").await.assert_status(StatusCode::NOT_FOUND);
+    app.post_json("/api/forms/rsvp", &serde_json::json!({ "name": "Ada" }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_preview_renders_draft() {
+    let app = preview_app(CountingEvents::default()).await;
+    let form = create_test_form(app.db(), rsvp_form(FormSettings::new().draft(true))).await;
+    let token = token_for(&form);
+
+    let response = app.get(&format!("/api/v1/forms/rsvp/preview?token={token}")).await;
+    response.assert_status(StatusCode::OK);
+    response.assert_body_contains("data-af-preview");
+    response.assert_body_contains(&format!("action=\"/api/v1/forms/rsvp/preview/submit?token={token}\""));
+}
+
+#[tokio::test]
+async fn test_preview_renders_archived_form() {
+    let app = preview_app(CountingEvents::default()).await;
+    let form = create_test_form(app.db(), rsvp_form(FormSettings::new())).await;
+    let form = FormBuilder::archive(app.db(), form.id).await.unwrap();
+
+    app.get("/api/forms/rsvp").await.assert_status(StatusCode::NOT_FOUND);
+    let token = token_for(&form);
+    app.get(&format!("/api/v1/forms/rsvp/preview?token={token}"))
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_preview_rejects_bad_tokens() {
+    let app = preview_app(CountingEvents::default()).await;
+    create_test_form(app.db(), rsvp_form(FormSettings::new().draft(true))).await;
+    let other = create_test_form(
+        app.db(),
+        CreateFormInput::new("Other", "other")
+            .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text"))),
+    )
+    .await;
+
+    let response = app.get("/api/forms/rsvp/preview?token=forged").await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    response.assert_api_error("INVALID_PREVIEW_TOKEN");
+
+    // A token for another form does not open this one
+    app.get(&format!("/api/forms/rsvp/preview?token={}", token_for(&other)))
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    let expired = PreviewSecret::new(SECRET).sign(&PreviewToken::new(other.id, Duration::hours(-1)));
+    app.get(&format!("/api/forms/other/preview?token={expired}"))
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_preview_submissions_are_not_counted() {
+    let events = CountingEvents::default();
+    let app = preview_app(events.clone()).await;
+    let form = create_test_form(app.db(), rsvp_form(FormSettings::new().close_after(1))).await;
+    let token = token_for(&form);
+
+    let response = app
+        .post_json(
+            &format!("/api/forms/rsvp/preview?token={token}"),
+            &serde_json::json!({ "name": "Ada" }),
+        )
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["status"], "preview");
+
+    app.post_json(
+        &format!("/api/forms/rsvp/preview?token={token}"),
+        &serde_json::json!({}),
+    )
+    .await
+    .assert_api_error("VALIDATION_FAILED");
+
+    let form = FormEntity::find_by_id(form.id).one(app.db()).await.unwrap().unwrap();
+    assert_eq!(form.response_count, 0);
+    assert_eq!(events.0.load(Ordering::SeqCst), 0);
+    assert!(SubmissionEntity::find_by_form(app.db(), form.id).await.unwrap().is_empty());
+    assert_eq!(SubmissionEntity::find_previews(app.db(), form.id).await.unwrap().len(), 1);
+
+    // The quota is still open for a real response
+    app.post_json("/api/forms/rsvp", &serde_json::json!({ "name": "Grace" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_ssr_preview_submit() {
+    let app = preview_app(CountingEvents::default()).await;
+    let form = create_test_form(
+        app.db(),
+        rsvp_form(FormSettings::new().draft(true).success_message("See you there!")),
+    )
+    .await;
+    let uri = format!("/api/forms/rsvp/preview/submit?token={}", token_for(&form));
+
+    app.post_form(&uri, &[("name", "")])
+        .await
+        .assert_status(StatusCode::OK)
+        .assert_body_contains("data-af-preview")
+        .assert_body_contains("af-error");
+
+    app.post_form(&uri, &[("name", "Ada")])
+        .await
+        .assert_status(StatusCode::OK)
+        .assert_body_contains("See you there!")
+        .assert_body_contains("Preview: submissions are not recorded.");
+}
+
+#[tokio::test]
+async fn test_preview_routes_require_secret() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), rsvp_form(FormSettings::new())).await;
+
+    app.get(&format!("/api/forms/rsvp/preview?token={}", token_for(&form)))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[cfg(feature = "admin")]
+#[tokio::test]
+async fn test_admin_preview_links_and_discard() {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .preview_secret(SECRET)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    let form = create_test_form(app.db(), rsvp_form(FormSettings::new().draft(true))).await;
+
+    let response = app
+        .post_json(&format!("/api/admin/forms/{}/preview", form.id), &serde_json::json!({}))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    let url = json["data"]["url"].as_str().unwrap().to_string();
+    assert!(url.starts_with("/api/v1/forms/rsvp/preview?token="));
+
+    app.get(&url).await.assert_status(StatusCode::OK);
+    app.post_json(&url, &serde_json::json!({ "name": "Ada" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let submissions = format!("/api/admin/forms/{}/submissions", form.id);
+    let json: serde_json::Value = app.get(&submissions).await.json();
+    assert_eq!(json["data"]["count"], 0);
+    let json: serde_json::Value = app.get(&format!("{submissions}?status=preview")).await.json();
+    assert_eq!(json["data"]["count"], 1);
+
+    let json: serde_json::Value = app
+        .delete(&format!("/api/admin/forms/{}/preview", form.id))
+        .await
+        .json();
+    assert_eq!(json["data"]["discarded"], 1);
+    assert!(SubmissionEntity::find_previews(app.db(), form.id).await.unwrap().is_empty());
+}
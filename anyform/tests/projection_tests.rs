@@ -0,0 +1,209 @@
+//! Tests for rebuilding projections from submissions.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyform::entities::{field_option, form, submission};
+use anyform::services::{
+    OptionSeats, Projection, RebuildProgress, ResponseCount, BUILTIN_PROJECTIONS,
+    PROJECTIONS_LOCK,
+};
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FormEntity, FormError,
+    FormSettings, LockService, ProjectionService,
+};
+use async_trait::async_trait;
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+fn workshop_form() -> CreateFormInput {
+    CreateFormInput::new("Workshops", "workshops")
+        .settings(FormSettings::new().close_after(10))
+        .step(CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("name", "Name", "text"),
+            CreateFieldInput::new("workshop", "Workshop", "select").options(vec![
+                CreateOptionInput::new("Workshop A", "a").capacity(5),
+                CreateOptionInput::new("Workshop B", "b").capacity(5),
+            ]),
+        ]))
+}
+
+async fn register(app: &TestApp, workshop: &str) -> String {
+    let response = app
+        .post_json(
+            "/api/forms/workshops",
+            &serde_json::json!({ "name": "Ada", "workshop": workshop }),
+        )
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    json["data"]["submission_id"].as_str().unwrap().to_string()
+}
+
+/// Returns the form's `response_count` and the seats taken per option.
+async fn counts(app: &TestApp, form_id: Uuid) -> (i32, Vec<(String, i32)>) {
+    let form = FormEntity::find_by_id(form_id).one(app.db()).await.unwrap().unwrap();
+    let mut seats: Vec<_> = field_option::Entity::find()
+        .filter(field_option::Column::Capacity.is_not_null())
+        .all(app.db())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|o| (o.value, o.reserved))
+        .collect();
+    seats.sort();
+    (form.response_count, seats)
+}
+
+/// Throws the form's derived counts off, as a bug might.
+async fn corrupt(app: &TestApp, form_id: Uuid) {
+    FormEntity::update_many()
+        .col_expr(form::Column::ResponseCount, Expr::value(42))
+        .filter(form::Column::Id.eq(form_id))
+        .exec(app.db())
+        .await
+        .unwrap();
+    field_option::Entity::update_many()
+        .col_expr(field_option::Column::Reserved, Expr::value(4))
+        .exec(app.db())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_rebuild_restores_counts_in_chunks() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), workshop_form()).await;
+    for workshop in ["a", "a", "b", "a", "b"] {
+        register(&app, workshop).await;
+    }
+    let deleted = register(&app, "a").await;
+    app.delete(&format!("/api/admin/forms/{}/submissions/{deleted}", form.id))
+        .await
+        .assert_status(StatusCode::OK);
+    let expected = counts(&app, form.id).await;
+    assert_eq!(expected, (5, vec![("a".into(), 3), ("b".into(), 2)]));
+
+    corrupt(&app, form.id).await;
+    let mut progress = Vec::new();
+    let report = ProjectionService::rebuild(app.db(), &form, BUILTIN_PROJECTIONS, 2, |p| {
+        progress.push(p);
+    })
+    .await
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(counts(&app, form.id).await, expected);
+    assert_eq!(report.projections, vec!["response_count", "option_seats", "search_index"]);
+    assert_eq!(report.replayed, 5);
+    assert_eq!(report.chunks, 3);
+    assert_eq!(
+        progress,
+        vec![
+            RebuildProgress { replayed: 2, total: 5 },
+            RebuildProgress { replayed: 4, total: 5 },
+            RebuildProgress { replayed: 5, total: 5 },
+        ]
+    );
+
+    // Replays are deterministic
+    ProjectionService::rebuild(app.db(), &form, BUILTIN_PROJECTIONS, 100, |_| {})
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(counts(&app, form.id).await, expected);
+}
+
+#[tokio::test]
+async fn test_rebuild_only_selected_projections() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), workshop_form()).await;
+    register(&app, "b").await;
+
+    corrupt(&app, form.id).await;
+    ProjectionService::rebuild(app.db(), &form, &[&ResponseCount], 10, |_| {})
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(counts(&app, form.id).await, (1, vec![("a".into(), 4), ("b".into(), 4)]));
+
+    ProjectionService::rebuild(app.db(), &form, &[&OptionSeats], 10, |_| {})
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(counts(&app, form.id).await, (1, vec![("a".into(), 0), ("b".into(), 1)]));
+}
+
+#[tokio::test]
+async fn test_rebuild_skips_when_already_running() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), workshop_form()).await;
+
+    let lock = format!("{PROJECTIONS_LOCK}.{}", form.id);
+    let guard = LockService::try_acquire(app.db(), &lock, Duration::from_secs(60))
+        .await
+        .unwrap()
+        .unwrap();
+    let report = ProjectionService::rebuild(app.db(), &form, BUILTIN_PROJECTIONS, 10, |_| {})
+        .await
+        .unwrap();
+    assert!(report.is_none());
+    guard.release().await.unwrap();
+}
+
+/// Records the names of the submissions it's given, chunk by chunk.
+#[derive(Default)]
+struct Names(Arc<Mutex<Vec<Vec<String>>>>);
+
+#[async_trait]
+impl Projection for Names {
+    fn name(&self) -> &str {
+        "names"
+    }
+
+    async fn reset(&self, _db: &DatabaseTransaction, _form: &form::Model) -> Result<(), FormError> {
+        self.0.lock().unwrap().clear();
+        Ok(())
+    }
+
+    async fn apply(
+        &self,
+        _db: &DatabaseTransaction,
+        _form: &form::Model,
+        submissions: &[submission::Model],
+    ) -> Result<(), FormError> {
+        let names = submissions.iter().map(|s| s.data["name"].to_string()).collect();
+        self.0.lock().unwrap().push(names);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_custom_projection_replays_oldest_first() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), workshop_form()).await;
+    for name in ["Ada", "Grace", "Edsger"] {
+        app.post_json("/api/forms/workshops", &serde_json::json!({ "name": name }))
+            .await
+            .assert_status(StatusCode::CREATED);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    let names = Names::default();
+    ProjectionService::rebuild(app.db(), &form, &[&names], 2, |_| {})
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        *names.0.lock().unwrap(),
+        vec![
+            vec![r#""Ada""#.to_string(), r#""Grace""#.to_string()],
+            vec![r#""Edsger""#.to_string()],
+        ]
+    );
+}
@@ -0,0 +1,269 @@
+//! Tests for response quotas, opening times and sampling.
+
+mod common;
+
+use std::collections::HashMap;
+
+use anyform::database::SqliteConfig;
+use anyform::schema::FieldValue;
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, FormEntity, FormError,
+    FormSettings, MigratorTrait, SubmissionEntity, SubmissionService,
+};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+use tempfile::TempDir;
+
+fn poll_form(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Poll", "poll")
+        .settings(settings)
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("answer", "Answer", "text")))
+}
+
+async fn answer(app: &TestApp) -> common::app::TestResponse {
+    app.post_json("/api/forms/poll", &serde_json::json!({ "answer": "yes" }))
+        .await
+}
+
+#[tokio::test]
+async fn test_close_after_quota() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        poll_form(FormSettings::new().close_after(2).closed_message("Poll closed.")),
+    )
+    .await;
+
+    answer(&app).await.assert_status(StatusCode::CREATED);
+    answer(&app).await.assert_status(StatusCode::CREATED);
+
+    let response = answer(&app).await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("FORM_CLOSED");
+
+    // Public form state reflects the closed quota
+    app.get("/api/forms/poll")
+        .await
+        .assert_status(StatusCode::OK)
+        .assert_body_contains("<p>Poll closed.</p>");
+    let json: serde_json::Value = app.get("/api/forms/poll/json").await.json();
+    assert_eq!(json["closed"], true);
+}
+
+#[tokio::test]
+async fn test_response_count_is_cached_on_form() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), poll_form(FormSettings::new())).await;
+
+    answer(&app).await.assert_status(StatusCode::CREATED);
+    answer(&app).await.assert_status(StatusCode::CREATED);
+
+    let form = FormEntity::find_by_id(form.id)
+        .one(app.db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(form.response_count, 2);
+    assert!(!form.is_closed());
+
+    // Updating the form keeps the count
+    let form = FormBuilder::update(app.db(), form.id, poll_form(FormSettings::new().close_after(2)))
+        .await
+        .unwrap();
+    assert_eq!(form.response_count, 2);
+    assert!(form.is_closed());
+}
+
+#[tokio::test]
+async fn test_redirect_submit_shows_closed_message() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), poll_form(FormSettings::new().close_after(1))).await;
+    answer(&app).await.assert_status(StatusCode::CREATED);
+
+    app.post_form("/api/forms/poll/submit", &[("answer", "no")])
+        .await
+        .assert_status(StatusCode::OK)
+        .assert_body_contains("This form is no longer accepting responses.");
+}
+
+#[tokio::test]
+async fn test_unsampled_submissions_are_thanked_but_not_recorded() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        poll_form(FormSettings::new().sample_rate(0.0).success_message("Thanks!")),
+    )
+    .await;
+
+    let response = answer(&app).await;
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["status"], "not_recorded");
+    assert_eq!(json["data"]["message"], "Thanks!");
+    assert!(json["data"].get("submission_id").is_none());
+
+    let response = app.post_form("/api/forms/poll/submit", &[("answer", "no")]).await;
+    response.assert_status(StatusCode::SEE_OTHER);
+
+    assert!(SubmissionEntity::find().all(app.db()).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_fully_sampled_submissions_are_recorded() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), poll_form(FormSettings::new().sample_rate(1.0))).await;
+
+    answer(&app).await.assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_sample_rate_must_be_a_fraction() {
+    let db = TestDb::new().await;
+    let result = FormBuilder::create(&db.db, poll_form(FormSettings::new().sample_rate(10.0))).await;
+
+    assert!(matches!(result, Err(FormError::InvalidData(_))));
+}
+
+#[cfg(feature = "admin")]
+#[tokio::test]
+async fn test_deleting_submission_reopens_quota() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), poll_form(FormSettings::new().close_after(1))).await;
+
+    let json: serde_json::Value = answer(&app).await.json();
+    let sub_id = json["data"]["submission_id"].as_str().unwrap().to_string();
+    answer(&app).await.assert_status(StatusCode::CONFLICT);
+
+    app.delete(&format!("/api/admin/forms/{}/submissions/{}", form.id, sub_id))
+        .await
+        .assert_status(StatusCode::OK);
+
+    answer(&app).await.assert_status(StatusCode::CREATED);
+}
+
+// ============================================================================
+// Opening and closing times
+// ============================================================================
+
+fn hours_from_now(hours: i64) -> chrono::DateTime<chrono::FixedOffset> {
+    (chrono::Utc::now() + chrono::Duration::hours(hours)).fixed_offset()
+}
+
+#[tokio::test]
+async fn test_form_before_opens_at() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        poll_form(
+            FormSettings::new()
+                .opens_at(hours_from_now(1))
+                .not_open_message("Voting opens at noon."),
+        ),
+    )
+    .await;
+
+    let response = answer(&app).await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("FORM_NOT_OPEN");
+
+    let html = app.get("/api/forms/poll").await.text();
+    assert!(html.contains("data-af-not-open"));
+    assert!(html.contains("<p>Voting opens at noon.</p>"));
+    assert!(!html.contains("<form"));
+    let json: serde_json::Value = app.get("/api/forms/poll/json").await.json();
+    assert_eq!(json["closed"], true);
+
+    app.post_form("/api/forms/poll/submit", &[("answer", "no")])
+        .await
+        .assert_status(StatusCode::OK)
+        .assert_body_contains("Voting opens at noon.");
+}
+
+#[tokio::test]
+async fn test_form_after_closes_at() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        poll_form(
+            FormSettings::new()
+                .opens_at(hours_from_now(-2))
+                .closes_at(hours_from_now(-1))
+                .closed_message("Voting has ended."),
+        ),
+    )
+    .await;
+
+    let response = answer(&app).await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("FORM_CLOSED");
+
+    app.get("/api/forms/poll")
+        .await
+        .assert_body_contains("<p>Voting has ended.</p>");
+    app.post_form("/api/forms/poll/submit", &[("answer", "no")])
+        .await
+        .assert_body_contains("Voting has ended.");
+}
+
+#[tokio::test]
+async fn test_form_within_window_accepts() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        poll_form(
+            FormSettings::new()
+                .opens_at(hours_from_now(-1))
+                .closes_at(hours_from_now(1)),
+        ),
+    )
+    .await;
+
+    answer(&app).await.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = app.get("/api/forms/poll/json").await.json();
+    assert!(json.get("closed").is_none());
+}
+
+#[tokio::test]
+async fn test_window_must_open_before_closing() {
+    let db = TestDb::new().await;
+    let at = hours_from_now(1);
+    let result = FormBuilder::create(
+        &db.db,
+        poll_form(FormSettings::new().opens_at(at).closes_at(at)),
+    )
+    .await;
+
+    assert!(matches!(result, Err(FormError::InvalidData(_))));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_submissions_respect_max_submissions() {
+    let dir = TempDir::new().unwrap();
+    let url = format!("sqlite:{}?mode=rwc", dir.path().join("anyform.db").display());
+    let db = SqliteConfig::new().connect(&url).await.unwrap();
+    anyform::Migrator::up(&db, None).await.unwrap();
+    let form = create_test_form(&db, poll_form(FormSettings::new().max_submissions(3))).await;
+
+    let submissions: Vec<_> = (0..8)
+        .map(|_| {
+            let db = db.clone();
+            let form = form.clone();
+            tokio::spawn(async move {
+                let data = HashMap::from([("answer".to_string(), FieldValue::from("yes"))]);
+                SubmissionService::create(&db, &form, &[], &data, None, None).await
+            })
+        })
+        .collect();
+
+    let mut accepted = 0;
+    for submission in submissions {
+        match submission.await.unwrap() {
+            Ok(_) => accepted += 1,
+            Err(FormError::SubmissionLimitReached) => {}
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+    assert_eq!(accepted, 3);
+    assert_eq!(SubmissionEntity::find().all(&db).await.unwrap().len(), 3);
+}
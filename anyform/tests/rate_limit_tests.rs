@@ -0,0 +1,122 @@
+//! Tests for submission rate limiting.
+
+mod common;
+
+use anyform::schema::FormSettings;
+use anyform::{RateLimit, RateLimits};
+use axum::body::Body;
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::{Request, StatusCode};
+
+const URI: &str = "/api/v1/forms/test-contact";
+
+async fn app_with(limits: RateLimits) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .rate_limits(limits)
+        .build();
+    TestApp::from_router(test_db, router)
+}
+
+async fn submit_from(app: &TestApp, uri: &str, ip: &str) -> common::app::TestResponse {
+    let body = serde_json::json!({
+        "name": "Ada",
+        "email": "ada@example.com",
+        "message": "Hello there"
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri(uri)
+        .header("Content-Type", "application/json")
+        .header("X-Forwarded-For", ip)
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    app.send_raw(request).await
+}
+
+// ============================================================================
+// Router limits
+// ============================================================================
+
+#[tokio::test]
+async fn test_per_ip_limit_returns_429_with_retry_after() {
+    let app = app_with(RateLimits::new().per_ip(RateLimit::per_minute(2))).await;
+    create_test_form(app.db(), contact_form()).await;
+
+    for _ in 0..2 {
+        submit_from(&app, URI, "203.0.113.7")
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+
+    let response = submit_from(&app, URI, "203.0.113.7").await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    response.assert_api_error("RATE_LIMITED");
+    assert_eq!(response.headers["retry-after"], "30");
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"]["retry_after"], 30);
+
+    // Another client still gets through
+    submit_from(&app, URI, "203.0.113.8")
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_ssr_submissions_are_limited_too() {
+    let app = app_with(RateLimits::new().per_form(RateLimit::per_hour(1))).await;
+    create_test_form(app.db(), contact_form()).await;
+
+    submit_from(&app, URI, "203.0.113.7")
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = submit_from(&app, &format!("{URI}/submit"), "203.0.113.8").await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers["retry-after"], "3600");
+}
+
+#[tokio::test]
+async fn test_no_limits_by_default() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), contact_form()).await;
+
+    for _ in 0..5 {
+        submit_from(&app, URI, "203.0.113.7")
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+}
+
+// ============================================================================
+// Form overrides
+// ============================================================================
+
+#[tokio::test]
+async fn test_form_settings_replace_router_limits() {
+    let app = app_with(RateLimits::new().per_ip(RateLimit::per_minute(1))).await;
+    let unlimited = FormSettings::new().rate_limits(RateLimits::new());
+    create_test_form(app.db(), contact_form().settings(unlimited)).await;
+
+    for _ in 0..3 {
+        submit_from(&app, URI, "203.0.113.7")
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+}
+
+#[tokio::test]
+async fn test_form_settings_limit_an_unlimited_router() {
+    let app = TestApp::new().await;
+    let strict = RateLimits::new().per_ip(RateLimit::per_minute(1));
+    create_test_form(app.db(), contact_form().settings(FormSettings::new().rate_limits(strict)))
+        .await;
+
+    submit_from(&app, URI, "203.0.113.7")
+        .await
+        .assert_status(StatusCode::CREATED);
+    submit_from(&app, URI, "203.0.113.7")
+        .await
+        .assert_api_error("RATE_LIMITED");
+}
@@ -0,0 +1,134 @@
+//! Tests for read-only mode.
+
+mod common;
+
+use std::time::Duration;
+
+use anyform::SubmissionEntity;
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use sea_orm::{EntityTrait, PaginatorTrait};
+use serde_json::json;
+
+fn read_only_router() -> anyform::AnyFormRouterBuilder {
+    anyform::AnyFormRouter::builder()
+        .read_only(true)
+        .read_only_retry_after(Duration::from_secs(120))
+}
+
+async fn read_only_app() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = read_only_router().database(test_db.db.clone()).build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), contact_form()).await;
+    app
+}
+
+fn answers() -> serde_json::Value {
+    json!({ "name": "Ada", "email": "ada@example.com", "message": "Hello while read-only" })
+}
+
+// ============================================================================
+// Reads
+// ============================================================================
+
+#[tokio::test]
+async fn test_forms_are_still_served() {
+    let app = read_only_app().await;
+
+    app.get("/api/forms/test-contact/json").await.assert_status(StatusCode::OK);
+    app.get("/api/forms/test-contact/success").await.assert_status(StatusCode::OK);
+
+    let response = app
+        .post_json(
+            "/api/forms/test-contact/fields/email/validate",
+            &json!({ "value": "ada@example.com" }),
+        )
+        .await;
+    response.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_form_page_says_submissions_are_unavailable() {
+    let app = read_only_app().await;
+
+    let response = app.get("/api/forms/test-contact").await;
+    response.assert_status(StatusCode::OK);
+    response
+        .assert_body_contains("data-af-unavailable")
+        .assert_body_contains("temporarily unavailable")
+        .assert_body_contains("<button type=\"submit\" disabled>");
+}
+
+// ============================================================================
+// Writes
+// ============================================================================
+
+#[tokio::test]
+async fn test_writes_are_rejected_with_retry_after() {
+    let app = read_only_app().await;
+
+    let responses = [
+        app.post_json("/api/forms/test-contact", &answers()).await,
+        app.post_json("/api/v1/forms/test-contact/submissions/start", &json!({})).await,
+    ];
+    for response in responses {
+        response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        response.assert_api_error("READ_ONLY");
+        assert_eq!(response.headers["retry-after"], "120");
+    }
+
+    let stored = SubmissionEntity::find().count(app.db()).await.unwrap();
+    assert_eq!(stored, 0);
+}
+
+#[cfg(feature = "admin")]
+#[tokio::test]
+async fn test_admin_writes_are_rejected() {
+    let test_db = TestDb::new().await;
+    let router = read_only_router()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    app.get("/api/admin/forms").await.assert_status(StatusCode::OK);
+    let response = app.delete(&format!("/api/admin/forms/{}", form.id)).await;
+    response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    response.assert_api_error("READ_ONLY");
+    assert!(anyform::FormBuilder::find_by_slug(app.db(), "test-contact")
+        .await
+        .unwrap()
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_ssr_post_renders_unavailable_notice() {
+    let app = read_only_app().await;
+
+    let response = app
+        .post_form(
+            "/api/forms/test-contact/submit",
+            &[("name", "Ada"), ("email", "ada@example.com"), ("message", "Hello there!")],
+        )
+        .await;
+    response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    response.assert_content_type("text/html");
+    response
+        .assert_body_contains("data-af-error=\"READ_ONLY\"")
+        .assert_body_contains("temporarily unavailable");
+    assert_eq!(response.headers["retry-after"], "120");
+}
+
+#[tokio::test]
+async fn test_writable_router_accepts_submissions() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), contact_form()).await;
+
+    let response = app.get("/api/forms/test-contact").await;
+    assert!(!response.text().contains("data-af-unavailable"));
+    app.post_json("/api/forms/test-contact", &answers())
+        .await
+        .assert_status(StatusCode::CREATED);
+}
@@ -0,0 +1,639 @@
+//! Tests for the HTML renderer.
+
+mod common;
+
+use std::collections::HashMap;
+
+use anyform::error::ValidationErrors;
+use anyform::progress::ProgressMode;
+use anyform::render::{csp, HtmlOptions, HtmlRenderer};
+use anyform::schema::{Branding, FieldValue, FormSettings, HelpMedia, UiOptions, ValidationRules};
+use anyform::services::{CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput};
+use anyform::FormBuilder;
+use common::{
+    arabic_form, contact_form, create_test_form, hebrew_form, multi_step_form, TestDb,
+};
+
+// ============================================================================
+// Content Security Policy
+// ============================================================================
+
+#[tokio::test]
+async fn test_render_without_nonce_emits_plain_tags() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), multi_step_form()).await;
+
+    let options = HtmlOptions::new().wasm_base_url("/assets");
+    let html = HtmlRenderer::render(db.conn(), &form, &options).await.unwrap();
+
+    assert!(html.contains("<style>"));
+    assert!(html.contains("<script type=\"module\" src=\"/assets/af-client.js\"></script>"));
+    assert!(!html.contains("nonce="));
+}
+
+#[tokio::test]
+async fn test_render_applies_nonce_to_inline_tags() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), multi_step_form()).await;
+
+    let options = HtmlOptions::new()
+        .wasm_base_url("/assets")
+        .csp_nonce("r4nd0m");
+    let html = HtmlRenderer::render(db.conn(), &form, &options).await.unwrap();
+
+    assert!(html.contains("<style nonce=\"r4nd0m\">"));
+    assert!(html.contains("src=\"/assets/af-client.js\" nonce=\"r4nd0m\""));
+}
+
+#[tokio::test]
+async fn test_render_links_stylesheet_when_href_set() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), multi_step_form()).await;
+
+    let options = HtmlOptions::new().styles_href("/forms/assets/anyform.css");
+    let html = HtmlRenderer::render(db.conn(), &form, &options).await.unwrap();
+
+    assert!(html.contains("<link rel=\"stylesheet\" href=\"/forms/assets/anyform.css\">"));
+    assert!(!html.contains("<style"));
+}
+
+#[tokio::test]
+async fn test_inline_style_attributes_are_covered_by_policy() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), multi_step_form()).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new())
+        .await
+        .unwrap();
+
+    // Every inline style attribute the renderer emits must be hash-allowed.
+    for attr in html.split("style=\"").skip(1) {
+        let value = &attr[..attr.find('"').unwrap()];
+        assert!(
+            csp::INLINE_STYLE_ATTRIBUTES.contains(&value),
+            "Unlisted inline style attribute: {value}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_single_step_form_has_no_inline_styles() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), contact_form()).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new().csp_nonce("n"))
+        .await
+        .unwrap();
+
+    assert!(!html.contains("<style"));
+}
+
+// ============================================================================
+// Custom snippets
+// ============================================================================
+
+#[tokio::test]
+async fn test_render_injects_custom_snippets() {
+    let db = TestDb::new().await;
+    let input = contact_form().settings(
+        FormSettings::new()
+            .custom_css(".af-form { --af-primary: #0a7; }")
+            .custom_js("document.body.classList.add('branded');"),
+    );
+    let form = create_test_form(db.conn(), input).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new().csp_nonce("n"))
+        .await
+        .unwrap();
+
+    assert!(html.contains(
+        "<style nonce=\"n\" data-af-custom>\n.af-form { --af-primary: #0a7; }\n</style>"
+    ));
+    assert!(html.contains("<script nonce=\"n\" data-af-custom>"));
+    assert!(html.contains("document.body.classList.add('branded');"));
+    assert!(html.find("data-af-custom>\n(function").unwrap() > html.find("</form>").unwrap());
+}
+
+#[tokio::test]
+async fn test_unsafe_snippets_are_rejected_on_save() {
+    let db = TestDb::new().await;
+
+    let css = contact_form()
+        .settings(FormSettings::new().custom_css("</style><script>alert(1)</script>"));
+    assert!(FormBuilder::create(db.conn(), css).await.is_err());
+
+    let js = contact_form()
+        .settings(FormSettings::new().custom_js("fetch('/steal?c=' + document.cookie)"));
+    assert!(FormBuilder::create(db.conn(), js).await.is_err());
+}
+
+// ============================================================================
+// Branding
+// ============================================================================
+
+#[tokio::test]
+async fn test_render_applies_branding() {
+    let db = TestDb::new().await;
+    let input = contact_form().settings(
+        FormSettings::new().branding(
+            Branding::new()
+                .logo_url("https://cdn.example.com/acme.svg")
+                .logo_alt("Acme")
+                .primary_color("#0a7")
+                .secondary_color("rgb(0, 0, 0)")
+                .footer_text("Acme & Co.")
+                .footer_link("Privacy", "/privacy"),
+        ),
+    );
+    let form = create_test_form(db.conn(), input).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new().csp_nonce("n"))
+        .await
+        .unwrap();
+
+    assert!(html.contains(
+        "<style nonce=\"n\" data-af-branding>.af-form { --af-primary: #0a7; --af-secondary: rgb(0, 0, 0); }</style>"
+    ));
+    assert!(html.contains("<img src=\"https://cdn.example.com/acme.svg\" alt=\"Acme\">"));
+    assert!(html.contains("<p>Acme &amp; Co.</p>"));
+    assert!(html.contains("<a href=\"/privacy\">Privacy</a>"));
+    assert!(html.find("af-footer").unwrap() < html.find("</form>").unwrap());
+}
+
+#[tokio::test]
+async fn test_unsafe_branding_is_rejected_on_save() {
+    let db = TestDb::new().await;
+
+    let input = contact_form().settings(
+        FormSettings::new().branding(Branding::new().primary_color("red; } body { display: none")),
+    );
+    assert!(FormBuilder::create(db.conn(), input).await.is_err());
+
+    let input = contact_form()
+        .settings(FormSettings::new().branding(Branding::new().logo_url("javascript:alert(1)")));
+    assert!(FormBuilder::create(db.conn(), input).await.is_err());
+}
+
+// ============================================================================
+// Right-to-left
+// ============================================================================
+
+#[tokio::test]
+async fn test_render_rtl_from_locale() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), arabic_form()).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new())
+        .await
+        .unwrap();
+
+    assert!(html.contains("data-af-form=\"test-arabic\" dir=\"rtl\" lang=\"ar\">"));
+    assert!(html.contains("<h2>المعلومات الشخصية</h2>"));
+
+    // Progress comes before the steps; buttons keep reading order for CSS to mirror
+    assert!(html.contains("<progress value=\"1\" max=\"2\"></progress>"));
+    assert!(html.find("data-af-progress").unwrap() < html.find("data-af-step=").unwrap());
+    assert!(html.find("class=\"af-prev").unwrap() < html.find("class=\"af-next").unwrap());
+    assert!(html.contains(".af-navigation .af-prev { margin-inline-end: auto; }"));
+}
+
+#[tokio::test]
+async fn test_render_explicit_rtl_direction() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), hebrew_form()).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new())
+        .await
+        .unwrap();
+
+    assert!(html.contains("data-af-form=\"test-hebrew\" dir=\"rtl\">"));
+    assert!(html.contains("<button type=\"submit\">שלח</button>"));
+    assert!(!html.contains("af-progress"));
+}
+
+#[tokio::test]
+async fn test_render_ltr_by_default() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), multi_step_form()).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new())
+        .await
+        .unwrap();
+
+    assert!(!html.contains(" dir="));
+}
+
+// ============================================================================
+// Progress
+// ============================================================================
+
+fn branching_form(mode: ProgressMode) -> CreateFormInput {
+    CreateFormInput::new("Branching", "test-branching")
+        .settings(FormSettings::new().show_progress(true).progress_mode(mode))
+        .step(
+            CreateStepInput::new("Plan")
+                .order(0)
+                .field(CreateFieldInput::new("plan", "Plan", "text")),
+        )
+        .step(
+            CreateStepInput::new("Business")
+                .order(1)
+                .condition(r#"{"field": "plan", "op": "eq", "value": "business"}"#)
+                .field(CreateFieldInput::new("company", "Company", "text")),
+        )
+        .step(
+            CreateStepInput::new("Done")
+                .order(2)
+                .field(CreateFieldInput::new("notes", "Notes", "text")),
+        )
+}
+
+#[tokio::test]
+async fn test_progress_counts_visible_steps() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), branching_form(ProgressMode::Visible)).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new())
+        .await
+        .unwrap();
+
+    assert!(html.contains("<span class=\"af-progress-text\">Step 1 of 2</span>"));
+}
+
+#[tokio::test]
+async fn test_estimated_progress_counts_possible_steps() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), branching_form(ProgressMode::Estimated)).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new())
+        .await
+        .unwrap();
+    assert!(html.contains("<progress value=\"1\" max=\"3\"></progress>"));
+
+    // Once the branch is decided, the estimate shrinks
+    let values = HashMap::from([("plan".to_string(), FieldValue::from("personal"))]);
+    let html = HtmlRenderer::render_with_values(
+        db.conn(),
+        &form,
+        &HtmlOptions::new(),
+        &values,
+        &ValidationErrors::new(),
+    )
+    .await
+    .unwrap();
+    assert!(html.contains("<progress value=\"1\" max=\"2\"></progress>"));
+}
+
+// ============================================================================
+// Hydration markup
+// ============================================================================
+
+#[tokio::test]
+async fn test_step_condition_renders_as_object() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), branching_form(ProgressMode::Visible)).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new())
+        .await
+        .unwrap();
+
+    assert!(html.contains(
+        r#"data-af-step="1" data-af-visible="false" data-af-condition='{"field":"plan","op":"eq","value":"business"}'"#
+    ));
+}
+
+#[tokio::test]
+async fn test_multi_select_renders_checkbox_group() {
+    let db = TestDb::new().await;
+    let input = CreateFormInput::new("Toppings", "test-toppings").step(
+        CreateStepInput::new("Main").field(
+            CreateFieldInput::new("toppings", "Toppings", "multi_select").options(vec![
+                CreateOptionInput::new("Cheese", "cheese"),
+                CreateOptionInput::new("Olives", "olives"),
+            ]),
+        ),
+    );
+    let form = create_test_form(db.conn(), input).await;
+
+    let values = HashMap::from([(
+        "toppings".to_string(),
+        FieldValue::Array(vec!["olives".to_string()]),
+    )]);
+    let html = HtmlRenderer::render_with_values(
+        db.conn(),
+        &form,
+        &HtmlOptions::new(),
+        &values,
+        &ValidationErrors::new(),
+    )
+    .await
+    .unwrap();
+
+    assert!(html.contains(
+        r#"<label><input type="checkbox" name="toppings[]" id="toppings_cheese" value="cheese"> Cheese</label>"#
+    ));
+    assert!(html.contains(
+        r#"<label><input type="checkbox" name="toppings[]" id="toppings_olives" value="olives" checked> Olives</label>"#
+    ));
+}
+
+// ============================================================================
+// Constraint attributes
+// ============================================================================
+
+/// Renders one field with `rules` and returns the markup of its control,
+/// with the `data-af-validation` payload of its wrapper.
+async fn render_control(field_type: &str, rules: ValidationRules) -> (String, String) {
+    let db = TestDb::new().await;
+    let input = CreateFormInput::new("Constraints", "test-constraints").step(
+        CreateStepInput::new("Main")
+            .field(CreateFieldInput::new("answer", "Answer", field_type).validation(rules)),
+    );
+    let form = create_test_form(db.conn(), input).await;
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new())
+        .await
+        .unwrap();
+
+    let start = html.find("data-af-validation='").unwrap() + 20;
+    let payload = html[start..start + html[start..].find('\'').unwrap()].to_string();
+    let control = html
+        .lines()
+        .find(|line| line.contains("name=\"answer\""))
+        .unwrap()
+        .to_string();
+    (control, payload)
+}
+
+#[tokio::test]
+async fn test_min_length_renders_minlength() {
+    let (control, payload) = render_control("text", ValidationRules::new().min_length(3)).await;
+    assert!(control.contains(r#" minlength="3""#), "{control}");
+    assert!(payload.contains(r#""minLength":3"#));
+
+    let (control, _) = render_control("textarea", ValidationRules::new().min_length(3)).await;
+    assert!(control.starts_with("      <textarea") && control.contains(r#" minlength="3""#));
+}
+
+#[tokio::test]
+async fn test_max_length_renders_maxlength() {
+    let (control, payload) = render_control("email", ValidationRules::new().max_length(80)).await;
+    assert!(control.contains(r#" maxlength="80""#), "{control}");
+    assert!(payload.contains(r#""maxLength":80"#));
+}
+
+#[tokio::test]
+async fn test_min_and_max_render_on_numbers() {
+    let rules = ValidationRules::new().min(18.0).max(120.5);
+    let (control, payload) = render_control("number", rules.clone()).await;
+    assert!(control.contains(r#" min="18" max="120.5""#), "{control}");
+    assert!(payload.contains(r#""min":18.0"#) && payload.contains(r#""max":120.5"#));
+
+    // Lengths and bounds only go where browsers apply them
+    let (control, _) = render_control("text", rules).await;
+    assert!(!control.contains(" min="));
+    let (control, _) = render_control("number", ValidationRules::new().min_length(2)).await;
+    assert!(!control.contains("minlength"));
+}
+
+#[tokio::test]
+async fn test_step_renders_step() {
+    let (control, payload) = render_control("number", ValidationRules::new().step(0.25)).await;
+    assert!(control.contains(r#" step="0.25""#), "{control}");
+    assert!(payload.contains(r#""step":0.25"#));
+}
+
+#[tokio::test]
+async fn test_pattern_renders_escaped_pattern() {
+    let (control, payload) =
+        render_control("text", ValidationRules::new().pattern(r#"^[A-Z]{3}-\d{4}$"#)).await;
+    assert!(control.contains(r#" pattern="^[A-Z]{3}-\d{4}$""#), "{control}");
+    assert!(payload.contains(r#""pattern":"^[A-Z]{3}-\\d{4}$""#));
+
+    // Quotes and markup are escaped, and a pattern found anywhere in the
+    // value is matched anywhere by the browser too
+    let (control, _) = render_control("text", ValidationRules::new().pattern(r#"a"<'&"#)).await;
+    assert!(
+        control.contains(r#" pattern=".*(?:a&quot;&lt;&#39;&amp;).*""#),
+        "{control}"
+    );
+
+    // Rust-only syntax is left to server validation
+    let (control, payload) =
+        render_control("text", ValidationRules::new().pattern("(?i)^abc$")).await;
+    assert!(!control.contains("pattern="));
+    assert!(payload.contains(r#""pattern":"(?i)^abc$""#));
+
+    let (control, _) = render_control("textarea", ValidationRules::new().pattern("^a$")).await;
+    assert!(!control.contains("pattern="));
+}
+
+// ============================================================================
+// Print mode
+// ============================================================================
+
+fn paper_form() -> CreateFormInput {
+    CreateFormInput::new("Site survey", "site-survey")
+        .settings(FormSettings::new().branding(Branding::new().footer_text("Acme Inc.")))
+        .step(CreateStepInput::new("Visitor").fields(vec![
+            CreateFieldInput::new("name", "Name", "text").required(),
+            CreateFieldInput::new("source", "How did you hear of us?", "radio").options(vec![
+                CreateOptionInput::new("A friend", "friend"),
+                CreateOptionInput::new("Online", "online"),
+            ]),
+            CreateFieldInput::new("token", "Token", "hidden"),
+        ]))
+        .step(CreateStepInput::new("Visit").fields(vec![
+            CreateFieldInput::new("areas", "Areas visited", "multi_select").options(vec![
+                CreateOptionInput::new("Lobby", "lobby"),
+                CreateOptionInput::new("Lab", "lab"),
+            ]),
+            CreateFieldInput::new("notes", "Notes", "textarea"),
+            CreateFieldInput::new("consent", "I agree to the terms", "checkbox"),
+        ]))
+}
+
+#[tokio::test]
+async fn test_print_mode_renders_blank_paper_form() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), paper_form()).await;
+
+    let options = HtmlOptions::new()
+        .wasm_base_url("/assets")
+        .print_mode(true);
+    let html = HtmlRenderer::render(db.conn(), &form, &options).await.unwrap();
+
+    assert!(html
+        .contains(r#"<div class="af-form af-print" data-af-form="site-survey" data-af-print>"#));
+    assert!(html.contains("<h1>Site survey</h1>"));
+    // Every step is shown
+    assert!(html.contains("<h2>Visitor</h2>") && html.contains("<h2>Visit</h2>"));
+    assert_eq!(html.matches("data-af-visible=\"false\"").count(), 0);
+
+    // Lines to write on, boxes to tick
+    assert!(html.contains(r#"<p class="af-label">Name <span class="required">*</span></p>"#));
+    assert_eq!(html.matches(r#"<span class="af-line"></span>"#).count(), 1 + 4);
+    assert!(html.contains(
+        r#"<div class="af-choice"><span class="af-box af-box--round"></span> A friend</div>"#
+    ));
+    assert!(html.contains(r#"<div class="af-choice"><span class="af-box"></span> Lab</div>"#));
+    assert!(html.contains(
+        r#"<div class="af-choice af-label"><span class="af-box"></span> I agree to the terms</div>"#
+    ));
+    assert!(!html.contains("Token"));
+    assert!(html.contains("Acme Inc."));
+
+    // Nothing to fill in on screen
+    for tag in ["<form", "<input", "<select", "<textarea", "<button", "<script"] {
+        assert!(!html.contains(tag), "{tag}");
+    }
+    assert!(html.contains(".af-print .af-box"));
+}
+
+#[tokio::test]
+async fn test_print_mode_route() {
+    let app = common::TestApp::new().await;
+    create_test_form(app.db(), paper_form()).await;
+
+    let response = app.get("/api/forms/site-survey?print=true").await;
+    response.assert_status(http::StatusCode::OK);
+    let html = response.text();
+    assert!(html.contains("data-af-print"));
+    assert!(!html.contains("<form"));
+
+    let html = app.get("/api/forms/site-survey").await.text();
+    assert!(html.contains("<form"));
+}
+
+// ============================================================================
+// Help media
+// ============================================================================
+
+fn card_form(help: HelpMedia) -> CreateFormInput {
+    CreateFormInput::new("Payment", "payment").step(CreateStepInput::new("Card").field(
+        CreateFieldInput::new("cvc", "CVC", "text")
+            .help_text("Three digits")
+            .ui(UiOptions::new().help(help)),
+    ))
+}
+
+#[tokio::test]
+async fn test_help_media_describes_input() {
+    let db = TestDb::new().await;
+    let help = HelpMedia::new()
+        .image("/img/cvc.png", "The back of a card")
+        .caption("Look next to the signature")
+        .tooltip("Printed on the back")
+        .link("What is a CVC?", "https://example.com/cvc");
+    let form = create_test_form(db.conn(), card_form(help)).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new()).await.unwrap();
+    assert!(html.contains(
+        r#"<input type="text" name="cvc" id="cvc" value="" aria-describedby="cvc-help cvc-tooltip cvc-media">"#
+    ));
+    assert!(html.contains(r#"<small class="help" id="cvc-help">Three digits</small>"#));
+    assert!(html.contains(r#"aria-controls="cvc-tooltip" aria-expanded="false" aria-label="Help: CVC" title="Printed on the back">?</button>"#));
+    assert!(html.contains(
+        r#"<span class="af-tooltip" id="cvc-tooltip" role="tooltip" hidden>Printed on the back</span>"#
+    ));
+    assert!(html.contains(r#"<div class="af-help-media" id="cvc-media">"#));
+    assert!(html.contains(r#"<img src="/img/cvc.png" alt="The back of a card" loading="lazy">"#));
+    assert!(html.contains("<figcaption>Look next to the signature</figcaption>"));
+    assert!(html.contains(
+        r#"<a href="https://example.com/cvc" target="_blank" rel="noopener noreferrer">What is a CVC?</a>"#
+    ));
+}
+
+#[tokio::test]
+async fn test_help_media_in_json() {
+    let app = common::TestApp::new().await;
+    let help = HelpMedia::new().tooltip("Printed on the back");
+    create_test_form(app.db(), card_form(help)).await;
+
+    let json: serde_json::Value = app.get("/api/forms/payment/json").await.json();
+    let field = &json["steps"][0]["fields"][0];
+    assert_eq!(field["ui_options"]["help"], serde_json::json!({ "tooltip": "Printed on the back" }));
+
+    // A tooltip alone adds no media block
+    let html = app.get("/api/forms/payment").await.text();
+    assert!(html.contains(r#"aria-describedby="cvc-help cvc-tooltip""#));
+    assert!(!html.contains("af-help-media"));
+}
+
+#[tokio::test]
+async fn test_unsafe_help_media_is_rejected() {
+    let db = TestDb::new().await;
+
+    let help = HelpMedia::new().link("Help", "javascript:alert(1)");
+    let error = FormBuilder::create(db.conn(), card_form(help)).await.unwrap_err();
+    assert!(error.to_string().contains("http(s), mailto or relative URLs"));
+
+    let help = HelpMedia::new().image("/img/cvc.png", " ");
+    let error = FormBuilder::create(db.conn(), card_form(help)).await.unwrap_err();
+    assert!(error.to_string().contains("needs alt text"));
+}
+
+// ============================================================================
+// Image choice
+// ============================================================================
+
+fn poster_form(options: Vec<CreateOptionInput>) -> CreateFormInput {
+    CreateFormInput::new("Poster vote", "poster-vote").step(CreateStepInput::new("Vote").field(
+        CreateFieldInput::new("poster", "Favourite poster", "image_choice")
+            .required()
+            .options(options),
+    ))
+}
+
+fn posters() -> Vec<CreateOptionInput> {
+    vec![
+        CreateOptionInput::new("Sunrise", "sunrise").image("/img/sunrise.jpg"),
+        CreateOptionInput::new("Harbour", "harbour").image("https://cdn.example.com/harbour.jpg"),
+    ]
+}
+
+#[tokio::test]
+async fn test_image_choice_renders_card_grid() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), poster_form(posters())).await;
+
+    let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new()).await.unwrap();
+    assert!(html.contains(r#"<div class="af-image-choices">"#));
+    assert_eq!(html.matches(r#"<label class="af-image-card">"#).count(), 2);
+    assert!(html.contains(
+        r#"<input type="radio" name="poster" id="poster_sunrise" value="sunrise" required>"#
+    ));
+    assert!(html.contains(r#"<img src="/img/sunrise.jpg" alt="" loading="lazy">"#));
+    assert!(html.contains(r#"<span class="af-image-caption">Harbour</span>"#));
+    assert!(html.contains(".af-image-card:has(input:checked)"));
+}
+
+#[tokio::test]
+async fn test_image_choice_json_and_submission() {
+    let app = common::TestApp::new().await;
+    create_test_form(app.db(), poster_form(posters())).await;
+
+    let json: serde_json::Value = app.get("/api/forms/poster-vote/json").await.json();
+    let field = &json["steps"][0]["fields"][0];
+    assert_eq!(field["field_type"], "image_choice");
+    assert_eq!(field["options"][0]["image_url"], "/img/sunrise.jpg");
+
+    let response = app.post_json("/api/forms/poster-vote", &serde_json::json!({})).await;
+    response.assert_status(http::StatusCode::UNPROCESSABLE_ENTITY);
+    let response = app
+        .post_json("/api/forms/poster-vote", &serde_json::json!({ "poster": "harbour" }))
+        .await;
+    response.assert_status(http::StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn test_image_choice_options_need_images() {
+    let db = TestDb::new().await;
+
+    let options = vec![CreateOptionInput::new("Sunrise", "sunrise")];
+    let error = FormBuilder::create(db.conn(), poster_form(options)).await.unwrap_err();
+    assert!(error.to_string().contains("needs an image_url"));
+
+    let options = vec![CreateOptionInput::new("Sunrise", "sunrise").image("javascript:alert(1)")];
+    let error = FormBuilder::create(db.conn(), poster_form(options)).await.unwrap_err();
+    assert!(error.to_string().contains("image_url"));
+}
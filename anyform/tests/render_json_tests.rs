@@ -219,9 +219,11 @@ async fn test_contact_form_json_snapshot() {
     // Convert to JSON Value for snapshot, replacing dynamic IDs
     let mut json = serde_json::to_value(&result).unwrap();
     json["id"] = serde_json::Value::String("[ID]".to_string());
+    json["updated_at"] = serde_json::Value::String("[UPDATED_AT]".to_string());
     if let Some(steps) = json["steps"].as_array_mut() {
         for step in steps {
             step["id"] = serde_json::Value::String("[STEP_ID]".to_string());
+            step["updated_at"] = serde_json::Value::String("[UPDATED_AT]".to_string());
             if let Some(fields) = step["fields"].as_array_mut() {
                 for field in fields {
                     field["id"] = serde_json::Value::String("[FIELD_ID]".to_string());
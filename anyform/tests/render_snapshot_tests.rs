@@ -0,0 +1,191 @@
+//! Golden-file snapshots of the HTML and JSON renderers.
+//!
+//! The seeded example forms are rendered with several option and theme
+//! combinations, so markup changes show up as snapshot diffs. Review them
+//! with `cargo insta review` (or rerun with `INSTA_UPDATE=always`).
+
+mod common;
+
+use std::collections::HashMap;
+
+use anyform::entities::form;
+use anyform::error::ValidationErrors;
+use anyform::render::{HtmlOptions, HtmlRenderer, JsonRenderer};
+use anyform::schema::{Branding, FieldValue};
+use anyform::FormBuilder;
+use common::TestDb;
+
+const SLUGS: [&str; 4] = ["contact", "feedback", "quiz", "onboarding"];
+
+/// Option combinations every seeded form is rendered with.
+fn variants() -> Vec<(&'static str, HtmlOptions)> {
+    vec![
+        ("default", HtmlOptions::new()),
+        // Bare markup, e.g. for sites with their own stylesheet
+        ("bare", HtmlOptions::default()),
+        // Utility-class frameworks
+        (
+            "classes",
+            HtmlOptions {
+                label_class: Some("block text-sm".to_string()),
+                input_class: Some("input".to_string()),
+                error_class: Some("text-red-600".to_string()),
+                help_class: Some("text-gray-500".to_string()),
+                button_class: Some("btn".to_string()),
+                ..HtmlOptions::new().form_class("space-y-4").field_class("mb-4")
+            },
+        ),
+        // Strict CSP with the hydration loader
+        (
+            "csp",
+            HtmlOptions::new()
+                .csp_nonce("snapshot-nonce")
+                .styles_href("/forms/assets/anyform.css")
+                .wasm_base_url("/forms/assets/")
+                .wasm_version("0.5.1")
+                .wasm_integrity("sha384-snapshot")
+                .action("/custom/submit"),
+        ),
+        ("multi_step", HtmlOptions::new().multi_step(true).preview(true)),
+    ]
+}
+
+async fn seeded(db: &TestDb, slug: &str) -> form::Model {
+    FormBuilder::find_by_slug(db.conn(), slug)
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+async fn seeded_db() -> TestDb {
+    let db = TestDb::new().await;
+    anyform::seed_all(db.conn()).await.unwrap();
+    db
+}
+
+/// Applies white-label branding and a right-to-left locale to a form.
+fn themed(mut form: form::Model) -> form::Model {
+    let settings = form.settings().locale("ar").branding(
+        Branding::new()
+            .logo_url("https://cdn.example.com/logo.svg")
+            .logo_alt("Example")
+            .primary_color("#0a7")
+            .secondary_color("#333")
+            .footer_text("Example & Co.")
+            .footer_link("Privacy", "/privacy"),
+    );
+    form.settings = Some(serde_json::to_value(settings).unwrap());
+    form
+}
+
+/// Replaces generated IDs and change times in a JSON rendering.
+fn redact_ids(json: &mut serde_json::Value) {
+    json["id"] = "[ID]".into();
+    json["updated_at"] = "[UPDATED_AT]".into();
+    for step in json["steps"].as_array_mut().into_iter().flatten() {
+        step["id"] = "[STEP_ID]".into();
+        step["updated_at"] = "[UPDATED_AT]".into();
+        for field in step["fields"].as_array_mut().into_iter().flatten() {
+            field["id"] = "[FIELD_ID]".into();
+            for option in field["options"].as_array_mut().into_iter().flatten() {
+                option["id"] = "[OPTION_ID]".into();
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_seeded_forms_html() {
+    let db = seeded_db().await;
+
+    for slug in SLUGS {
+        let form = seeded(&db, slug).await;
+        for (variant, options) in variants() {
+            let html = HtmlRenderer::render(db.conn(), &form, &options)
+                .await
+                .unwrap();
+            insta::assert_snapshot!(format!("{slug}_{variant}"), html);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_seeded_forms_themed_html() {
+    let db = seeded_db().await;
+
+    for slug in SLUGS {
+        let form = themed(seeded(&db, slug).await);
+        let html = HtmlRenderer::render(db.conn(), &form, &HtmlOptions::new().csp_nonce("n"))
+            .await
+            .unwrap();
+        insta::assert_snapshot!(format!("{slug}_themed"), html);
+    }
+}
+
+#[tokio::test]
+async fn test_submitted_values_and_errors_html() {
+    let db = seeded_db().await;
+    let form = seeded(&db, "contact").await;
+
+    // Values that need escaping, and errors from a failed submit
+    let values = HashMap::from([
+        (
+            "name".to_string(),
+            FieldValue::from("O'Brien <script>alert(\"x\")</script>"),
+        ),
+        ("email".to_string(), FieldValue::from("not-an-email")),
+        ("message".to_string(), FieldValue::from("Tom & Jerry")),
+        ("preferred_contact".to_string(), FieldValue::from("phone")),
+    ]);
+    let mut errors = ValidationErrors::new();
+    errors.add("email", "Email Address must be a valid email address");
+    errors.add("message", "Message must be at least 10 characters");
+
+    let html =
+        HtmlRenderer::render_with_values(db.conn(), &form, &HtmlOptions::new(), &values, &errors)
+            .await
+            .unwrap();
+    insta::assert_snapshot!("contact_with_errors", html);
+
+    let values = HashMap::from([(
+        "features_used".to_string(),
+        FieldValue::Array(vec!["templates".to_string(), "api".to_string()]),
+    )]);
+    let form = seeded(&db, "feedback").await;
+    let html = HtmlRenderer::render_with_values(
+        db.conn(),
+        &form,
+        &HtmlOptions::new(),
+        &values,
+        &ValidationErrors::new(),
+    )
+    .await
+    .unwrap();
+    insta::assert_snapshot!("feedback_with_values", html);
+}
+
+#[tokio::test]
+async fn test_seeded_forms_pages_html() {
+    let db = seeded_db().await;
+    let form = themed(seeded(&db, "contact").await);
+
+    insta::assert_snapshot!(
+        "contact_success",
+        HtmlRenderer::render_success(&form, "Thanks, <we'll> be in touch & soon!")
+    );
+    insta::assert_snapshot!("contact_closed", HtmlRenderer::render_closed(&form));
+}
+
+#[tokio::test]
+async fn test_seeded_forms_json() {
+    let db = seeded_db().await;
+
+    for slug in SLUGS {
+        let form = seeded(&db, slug).await;
+        let rendered = JsonRenderer::render(db.conn(), &form).await.unwrap();
+
+        let mut json = serde_json::to_value(&rendered).unwrap();
+        redact_ids(&mut json);
+        insta::assert_json_snapshot!(format!("{slug}_json"), json);
+    }
+}
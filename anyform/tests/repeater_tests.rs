@@ -0,0 +1,212 @@
+//! Tests for repeatable field groups.
+
+mod common;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, FormError, SubmissionEntity,
+    UiOptions, ValidationRules,
+};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use serde_json::{json, Value};
+
+const SLUG: &str = "test-repeater";
+
+/// A team name and two or three members, each with a name and an email.
+fn team_form() -> CreateFormInput {
+    CreateFormInput::new("Team", SLUG).step(
+        CreateStepInput::new("Team")
+            .field(CreateFieldInput::new("team", "Team", "text").required())
+            .field(
+                CreateFieldInput::new("members", "Members", "repeater")
+                    .required()
+                    .validation(ValidationRules::new().min_rows(2).max_rows(3))
+                    .ui(UiOptions::new().row_labels("Add member", "Remove member"))
+                    .field(CreateFieldInput::new("name", "Name", "text").required())
+                    .field(CreateFieldInput::new("email", "Email", "email")),
+            ),
+    )
+}
+
+async fn team_app() -> TestApp {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), team_form()).await;
+    app
+}
+
+async fn stored_data(app: &TestApp) -> Value {
+    let form = FormBuilder::find_by_slug(app.db(), SLUG).await.unwrap().unwrap();
+    let mut submissions = SubmissionEntity::find_by_form(app.db(), form.id).await.unwrap();
+    submissions.pop().expect("a submission").data
+}
+
+// ============================================================================
+// Saving forms
+// ============================================================================
+
+#[tokio::test]
+async fn test_groups_must_hold_plain_fields() {
+    let db = TestDb::new().await;
+    let form = |field| {
+        CreateFormInput::new("Team", SLUG).step(CreateStepInput::new("Team").field(field))
+    };
+
+    let empty = CreateFieldInput::new("members", "Members", "repeater");
+    let err = FormBuilder::create(db.conn(), form(empty)).await.unwrap_err();
+    assert!(matches!(err, FormError::InvalidData(ref m) if m.contains("no sub-fields")), "{err}");
+
+    let not_a_group = CreateFieldInput::new("team", "Team", "text")
+        .field(CreateFieldInput::new("name", "Name", "text"));
+    let err = FormBuilder::create(db.conn(), form(not_a_group)).await.unwrap_err();
+    assert!(matches!(err, FormError::InvalidData(ref m) if m.contains("'team'")), "{err}");
+
+    let nested = CreateFieldInput::new("members", "Members", "repeater").field(
+        CreateFieldInput::new("phones", "Phones", "repeater")
+            .field(CreateFieldInput::new("phone", "Phone", "tel")),
+    );
+    let err = FormBuilder::create(db.conn(), form(nested)).await.unwrap_err();
+    assert!(matches!(err, FormError::InvalidData(ref m) if m.contains("another group")), "{err}");
+
+    FormBuilder::create(db.conn(), team_form()).await.unwrap();
+}
+
+// ============================================================================
+// Form JSON
+// ============================================================================
+
+#[tokio::test]
+async fn test_form_json_nests_sub_fields_in_their_group() {
+    let app = team_app().await;
+
+    let response = app.get(&format!("/api/forms/{SLUG}/json")).await;
+    response.assert_status(StatusCode::OK);
+    let form: Value = response.json();
+
+    let fields = form["steps"][0]["fields"].as_array().unwrap();
+    assert_eq!(fields.len(), 2);
+    let members = &fields[1];
+    assert_eq!(members["field_type"], "repeater");
+    assert_eq!(members["validation"]["min_rows"], 2);
+    assert_eq!(members["validation"]["max_rows"], 3);
+    let names: Vec<&str> = members["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["name", "email"]);
+    assert!(fields[0].get("fields").is_none());
+}
+
+// ============================================================================
+// HTML
+// ============================================================================
+
+#[tokio::test]
+async fn test_html_renders_entries_and_a_template() {
+    let app = team_app().await;
+
+    let html = app.get(&format!("/api/forms/{SLUG}")).await.text();
+    assert!(html.contains("data-af-repeater"), "{html}");
+    assert!(html.contains("data-af-row=\"1\""), "{html}");
+    assert!(html.contains("name=\"members[0][name]\""), "{html}");
+    assert!(html.contains("<template class=\"af-row-template\">"), "{html}");
+    assert!(html.contains("name=\"members[__index__][email]\""), "{html}");
+    assert!(html.contains("data-af-subfield=\"email\""), "{html}");
+    assert!(html.contains(">Add member</button>"), "{html}");
+    assert!(html.contains(">Remove member</button>"), "{html}");
+    assert!(!html.contains("data-af-row=\"2\""), "{html}");
+}
+
+// ============================================================================
+// Submissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_json_submission_stores_entries() {
+    let app = team_app().await;
+
+    let data = json!({
+        "team": "Core",
+        "members": [
+            { "name": "Ada", "email": "ada@example.com" },
+            { "name": "Grace" }
+        ]
+    });
+    app.post_json(&format!("/api/forms/{SLUG}"), &data)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let stored = stored_data(&app).await;
+    assert_eq!(stored["members"], data["members"]);
+}
+
+#[tokio::test]
+async fn test_urlencoded_submission_groups_inputs_into_entries() {
+    let app = team_app().await;
+
+    app.post_form(
+        &format!("/api/forms/{SLUG}"),
+        &[
+            ("team", "Core"),
+            ("members[0][name]", "Ada"),
+            ("members[0][email]", "ada@example.com"),
+            ("members[1][name]", "Grace"),
+            ("members[1][email]", ""),
+            // A blank entry left on the page
+            ("members[2][name]", ""),
+            ("members[2][email]", ""),
+        ],
+    )
+    .await
+    .assert_success();
+
+    let stored = stored_data(&app).await;
+    assert_eq!(
+        stored["members"],
+        json!([
+            { "name": "Ada", "email": "ada@example.com" },
+            { "name": "Grace", "email": null }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_entries_are_validated_against_sub_fields() {
+    let app = team_app().await;
+
+    let data = json!({
+        "team": "Core",
+        "members": [{ "name": "Ada", "email": "nope" }, { "email": "grace@example.com" }]
+    });
+    let response = app.post_json(&format!("/api/forms/{SLUG}"), &data).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_api_error("VALIDATION_FAILED");
+    let body = response.text();
+    assert!(body.contains("members[0][email]"), "{body}");
+    assert!(body.contains("members[1][name]"), "{body}");
+    assert!(!body.contains("members[0][name]"), "{body}");
+}
+
+#[tokio::test]
+async fn test_entry_count_is_validated() {
+    let app = team_app().await;
+    let uri = format!("/api/forms/{SLUG}");
+
+    let none = json!({ "team": "Core", "members": [] });
+    let body = app.post_json(&uri, &none).await.text();
+    assert!(body.contains("Members is required"), "{body}");
+
+    let member = json!({ "name": "Ada" });
+    let one = json!({ "team": "Core", "members": [member] });
+    let body = app.post_json(&uri, &one).await.text();
+    assert!(body.contains("Members requires at least 2 entries"), "{body}");
+
+    let four = json!({ "team": "Core", "members": [member, member, member, member] });
+    let body = app.post_json(&uri, &four).await.text();
+    assert!(body.contains("Members allows at most 3 entries"), "{body}");
+
+    let text = json!({ "team": "Core", "members": "Ada" });
+    let body = app.post_json(&uri, &text).await.text();
+    assert!(body.contains("Members must be a list of entries"), "{body}");
+}
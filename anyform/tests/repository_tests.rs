@@ -0,0 +1,594 @@
+//! Tests for pluggable form storage.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyform::entities::submission_note::Model as SubmissionNote;
+use anyform::repository::{FieldWithOptions, StepWithFields};
+use anyform::schema::{FieldValue, NotificationDigest};
+use anyform::services::{
+    ChangeBatch, CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, Digest,
+    FormSchedule, ImportMode, InstallationStats, OptionDiff, OptionRow, Score, SubmissionChanges,
+};
+use anyform::{
+    DeadLetter, DuplicateGuard, EncryptionKey, Field, Form, FormAnalytics, FormError, FormEvents,
+    FormInstance, FormResult, FormStatus, FormTransition, FormsRepository, FormsTransaction,
+    HtmlOptions, HtmlRenderer, InstanceStats, SeaOrmRepository, Submission, SubmissionEntity,
+    SubmissionMetadata, SubmissionPage, SubmissionQuery, SubmissionSchema,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, FixedOffset};
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use sea_orm::DatabaseConnection;
+use http::StatusCode;
+use uuid::Uuid;
+
+/// Caches step definitions and counts the calls reaching the database.
+struct Cached {
+    inner: SeaOrmRepository,
+    steps: Mutex<HashMap<Uuid, Vec<StepWithFields>>>,
+    loads: Arc<AtomicUsize>,
+    inserts: Arc<AtomicUsize>,
+    hidden: Option<&'static str>,
+}
+
+/// Counts the submissions inserted in a transaction of [`Cached`].
+struct Counted {
+    inner: Box<dyn FormsTransaction>,
+    inserts: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl FormsRepository for Cached {
+    async fn find_form_by_slug(&self, slug: &str) -> Result<Option<Form>, FormError> {
+        if self.hidden == Some(slug) {
+            return Ok(None);
+        }
+        self.inner.find_form_by_slug(slug).await
+    }
+
+    async fn find_form(&self, id: Uuid) -> Result<Option<Form>, FormError> {
+        self.inner.find_form(id).await
+    }
+
+    async fn load_steps_with_fields(
+        &self,
+        form_id: Uuid,
+    ) -> Result<Vec<StepWithFields>, FormError> {
+        if let Some(steps) = self.steps.lock().unwrap().get(&form_id) {
+            return Ok(steps.clone());
+        }
+        self.loads.fetch_add(1, Ordering::SeqCst);
+        let steps = self.inner.load_steps_with_fields(form_id).await?;
+        self.steps.lock().unwrap().insert(form_id, steps.clone());
+        Ok(steps)
+    }
+
+    async fn begin(&self) -> Result<Box<dyn FormsTransaction>, FormError> {
+        Ok(Box::new(Counted {
+            inner: self.inner.begin().await?,
+            inserts: self.inserts.clone(),
+        }))
+    }
+
+    async fn list_forms(&self) -> Result<Vec<Form>, FormError> {
+        self.inner.list_forms().await
+    }
+
+    async fn create_form(&self, input: CreateFormInput) -> Result<Form, FormError> {
+        self.inner.create_form(input).await
+    }
+
+    async fn update_form(&self, id: Uuid, input: CreateFormInput) -> Result<Form, FormError> {
+        self.inner.update_form(id, input).await
+    }
+
+    async fn duplicate_form(&self, id: Uuid) -> Result<Form, FormError> {
+        self.inner.duplicate_form(id).await
+    }
+
+    async fn copy_slug(&self, slug: &str) -> Result<String, FormError> {
+        self.inner.copy_slug(slug).await
+    }
+
+    async fn set_form_status(&self, id: Uuid, status: FormStatus) -> Result<Form, FormError> {
+        self.inner.set_form_status(id, status).await
+    }
+
+    async fn delete_form(&self, id: Uuid) -> Result<(), FormError> {
+        self.inner.delete_form(id).await
+    }
+
+    async fn set_browser_allowed(
+        &self,
+        form_id: Uuid,
+        browser_id: Uuid,
+        allowed: bool,
+    ) -> Result<DuplicateGuard, FormError> {
+        self.inner.set_browser_allowed(form_id, browser_id, allowed).await
+    }
+
+    async fn set_digest_subscription(
+        &self,
+        form: Form,
+        email: &str,
+        subscribed: bool,
+    ) -> Result<NotificationDigest, FormError> {
+        self.inner.set_digest_subscription(form, email, subscribed).await
+    }
+
+    async fn import_options(
+        &self,
+        form: &Form,
+        field_name: &str,
+        rows: Vec<OptionRow>,
+        mode: ImportMode,
+        dry_run: bool,
+    ) -> Result<OptionDiff, FormError> {
+        self.inner.import_options(form, field_name, rows, mode, dry_run).await
+    }
+
+    async fn find_result(&self, form_id: Uuid, key: &str) -> Result<Option<FormResult>, FormError> {
+        self.inner.find_result(form_id, key).await
+    }
+
+    async fn current_instance(&self, form: &Form) -> Result<Option<FormInstance>, FormError> {
+        self.inner.current_instance(form).await
+    }
+
+    async fn announce_lifecycle(
+        &self,
+        events: &dyn FormEvents,
+        form_id: Uuid,
+        at: DateTime<FixedOffset>,
+    ) -> Result<Option<FormTransition>, FormError> {
+        self.inner.announce_lifecycle(events, form_id, at).await
+    }
+
+    async fn find_submission(&self, id: Uuid) -> Result<Option<Submission>, FormError> {
+        self.inner.find_submission(id).await
+    }
+
+    async fn has_responded(&self, form_id: Uuid, respondent_id: &str) -> Result<bool, FormError> {
+        self.inner.has_responded(form_id, respondent_id).await
+    }
+
+    async fn start_submission(
+        &self,
+        form: &Form,
+        metadata: Option<SubmissionMetadata>,
+    ) -> Result<Submission, FormError> {
+        self.inner.start_submission(form, metadata).await
+    }
+
+    async fn discard_draft(&self, form_id: Uuid, id: Uuid) -> Result<bool, FormError> {
+        self.inner.discard_draft(form_id, id).await
+    }
+
+    async fn promote_submission(
+        &self,
+        form: &Form,
+        sub: Submission,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        self.inner.promote_submission(form, sub, key).await
+    }
+
+    async fn delete_submission(
+        &self,
+        sub: &Submission,
+        key: Option<&EncryptionKey>,
+    ) -> Result<(), FormError> {
+        self.inner.delete_submission(sub, key).await
+    }
+
+    async fn set_legal_hold(&self, sub: Submission, hold: bool) -> Result<Submission, FormError> {
+        self.inner.set_legal_hold(sub, hold).await
+    }
+
+    async fn list_submissions(&self, query: &SubmissionQuery) -> Result<SubmissionPage, FormError> {
+        self.inner.list_submissions(query).await
+    }
+
+    async fn submission_changes(
+        &self,
+        changes: &SubmissionChanges,
+        now: DateTime<FixedOffset>,
+    ) -> Result<ChangeBatch, FormError> {
+        self.inner.submission_changes(changes, now).await
+    }
+
+    async fn exported_submissions(
+        &self,
+        form_id: Uuid,
+        include_partials: bool,
+    ) -> Result<Vec<Submission>, FormError> {
+        self.inner.exported_submissions(form_id, include_partials).await
+    }
+
+    async fn submission_schema(
+        &self,
+        form_id: Uuid,
+        subs: &[Submission],
+    ) -> Result<SubmissionSchema, FormError> {
+        self.inner.submission_schema(form_id, subs).await
+    }
+
+    async fn choice_fields(&self, form_id: Uuid) -> Result<Vec<FieldWithOptions>, FormError> {
+        self.inner.choice_fields(form_id).await
+    }
+
+    async fn preview_submissions(&self, form_id: Uuid) -> Result<Vec<Submission>, FormError> {
+        self.inner.preview_submissions(form_id).await
+    }
+
+    async fn delete_previews(&self, form_id: Uuid) -> Result<u64, FormError> {
+        self.inner.delete_previews(form_id).await
+    }
+
+    async fn submission_labels(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<String>>, FormError> {
+        self.inner.submission_labels(ids).await
+    }
+
+    async fn submission_notes(&self, id: Uuid) -> Result<Vec<SubmissionNote>, FormError> {
+        self.inner.submission_notes(id).await
+    }
+
+    async fn add_submission_note(
+        &self,
+        id: Uuid,
+        body: Option<String>,
+        labels: &[String],
+        author: Option<String>,
+    ) -> Result<SubmissionNote, FormError> {
+        self.inner.add_submission_note(id, body, labels, author).await
+    }
+
+    async fn record_dead_letter(
+        &self,
+        form_id: Uuid,
+        data: &HashMap<String, FieldValue>,
+        metadata: Option<&SubmissionMetadata>,
+        error: &str,
+    ) -> Result<DeadLetter, FormError> {
+        self.inner.record_dead_letter(form_id, data, metadata, error).await
+    }
+
+    async fn find_dead_letter(&self, id: Uuid) -> Result<Option<DeadLetter>, FormError> {
+        self.inner.find_dead_letter(id).await
+    }
+
+    async fn pending_dead_letters(
+        &self,
+        form_id: Option<Uuid>,
+    ) -> Result<Vec<DeadLetter>, FormError> {
+        self.inner.pending_dead_letters(form_id).await
+    }
+
+    async fn count_pending_dead_letters(&self) -> Result<u64, FormError> {
+        self.inner.count_pending_dead_letters().await
+    }
+
+    async fn mark_dead_letter_failed(
+        &self,
+        letter: DeadLetter,
+        error: &str,
+    ) -> Result<DeadLetter, FormError> {
+        self.inner.mark_dead_letter_failed(letter, error).await
+    }
+
+    async fn mark_dead_letter_replayed(
+        &self,
+        letter: DeadLetter,
+        submission_id: Uuid,
+    ) -> Result<DeadLetter, FormError> {
+        self.inner.mark_dead_letter_replayed(letter, submission_id).await
+    }
+
+    async fn form_analytics(&self, form: &Form) -> Result<FormAnalytics, FormError> {
+        self.inner.form_analytics(form).await
+    }
+
+    async fn instance_stats(&self, form: &Form) -> Result<Vec<InstanceStats>, FormError> {
+        self.inner.instance_stats(form).await
+    }
+
+    async fn installation_stats(&self) -> Result<InstallationStats, FormError> {
+        self.inner.installation_stats().await
+    }
+
+    async fn form_schedule(
+        &self,
+        form: &Form,
+        at: DateTime<FixedOffset>,
+        upcoming: usize,
+    ) -> Result<FormSchedule, FormError> {
+        self.inner.form_schedule(form, at, upcoming).await
+    }
+
+    async fn latest_digest(
+        &self,
+        form: &Form,
+        now: DateTime<FixedOffset>,
+    ) -> Result<Digest, FormError> {
+        self.inner.latest_digest(form, now).await
+    }
+
+    async fn score(
+        &self,
+        form: &Form,
+        fields: &[Field],
+        data: &HashMap<String, FieldValue>,
+    ) -> Result<Option<Score>, FormError> {
+        self.inner.score(form, fields, data).await
+    }
+
+    async fn resolve_result(
+        &self,
+        form_id: Uuid,
+        score: Option<&Score>,
+        data: &HashMap<String, FieldValue>,
+    ) -> Result<Option<FormResult>, FormError> {
+        self.inner.resolve_result(form_id, score, data).await
+    }
+}
+
+#[async_trait]
+impl FormsTransaction for Counted {
+    async fn find_form(&self, id: Uuid) -> Result<Option<Form>, FormError> {
+        self.inner.find_form(id).await
+    }
+
+    async fn load_steps_with_fields(
+        &self,
+        form_id: Uuid,
+    ) -> Result<Vec<StepWithFields>, FormError> {
+        self.inner.load_steps_with_fields(form_id).await
+    }
+
+    async fn find_submission(&self, id: Uuid) -> Result<Option<Submission>, FormError> {
+        self.inner.find_submission(id).await
+    }
+
+    async fn insert_submission(
+        &self,
+        form: &Form,
+        fields: &[Field],
+        data: &HashMap<String, FieldValue>,
+        metadata: Option<SubmissionMetadata>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        self.inserts.fetch_add(1, Ordering::SeqCst);
+        self.inner.insert_submission(form, fields, data, metadata, key).await
+    }
+
+    async fn insert_preview(
+        &self,
+        form: &Form,
+        data: &HashMap<String, FieldValue>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        self.inner.insert_preview(form, data, key).await
+    }
+
+    async fn save_step(
+        &self,
+        sub: Submission,
+        step_id: Uuid,
+        data: &HashMap<String, FieldValue>,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        self.inner.save_step(sub, step_id, data, key).await
+    }
+
+    async fn complete_submission(
+        &self,
+        form: &Form,
+        fields: &[Field],
+        sub: Submission,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Submission, FormError> {
+        self.inner.complete_submission(form, fields, sub, key).await
+    }
+
+    async fn discard_submission(&self, id: Uuid) -> Result<(), FormError> {
+        self.inner.discard_submission(id).await
+    }
+
+    async fn commit(self: Box<Self>) -> Result<(), FormError> {
+        self.inner.commit().await
+    }
+}
+
+struct Counters {
+    loads: Arc<AtomicUsize>,
+    inserts: Arc<AtomicUsize>,
+}
+
+async fn cached_app(hidden: Option<&'static str>) -> (TestApp, Counters) {
+    let test_db = TestDb::new().await;
+    let counters = Counters {
+        loads: Arc::new(AtomicUsize::new(0)),
+        inserts: Arc::new(AtomicUsize::new(0)),
+    };
+    let repository = Cached {
+        inner: SeaOrmRepository::new(test_db.db.clone()),
+        steps: Mutex::new(HashMap::new()),
+        loads: counters.loads.clone(),
+        inserts: counters.inserts.clone(),
+        hidden,
+    };
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .repository(repository)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), contact_form()).await;
+    (app, counters)
+}
+
+fn answers() -> serde_json::Value {
+    serde_json::json!({
+        "name": "Ada",
+        "email": "ada@example.com",
+        "message": "Hello there"
+    })
+}
+
+// ============================================================================
+// Reading forms
+// ============================================================================
+
+#[tokio::test]
+async fn test_form_json_is_read_through_the_repository() {
+    let (app, counters) = cached_app(None).await;
+
+    for _ in 0..3 {
+        let response = app.get("/api/forms/test-contact/json").await;
+        response.assert_status(StatusCode::OK);
+        let json: serde_json::Value = response.json();
+        assert_eq!(json["steps"][0]["fields"][0]["name"], "name");
+    }
+    app.get("/api/forms/test-contact/config.js")
+        .await
+        .assert_status(StatusCode::OK);
+
+    assert_eq!(counters.loads.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_forms_the_repository_doesnt_find_are_not_served() {
+    let (app, _) = cached_app(Some("test-contact")).await;
+
+    app.get("/api/forms/test-contact/json")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.get("/api/forms/test-contact")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.post_json("/api/forms/test-contact", &answers())
+        .await
+        .assert_api_error("FORM_NOT_FOUND");
+}
+
+// ============================================================================
+// Storing submissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_submissions_are_stored_through_the_repository() {
+    let (app, counters) = cached_app(None).await;
+
+    app.post_json("/api/forms/test-contact", &answers())
+        .await
+        .assert_status(StatusCode::CREATED);
+    app.post_json("/api/forms/test-contact", &serde_json::json!({ "name": "Ada" }))
+        .await
+        .assert_api_error("VALIDATION_FAILED");
+
+    assert_eq!(counters.inserts.load(Ordering::SeqCst), 1);
+    let form = anyform::FormBuilder::find_by_slug(app.db(), "test-contact")
+        .await
+        .unwrap()
+        .unwrap();
+    let subs = SubmissionEntity::find_by_form(app.db(), form.id).await.unwrap();
+    assert_eq!(subs.len(), 1);
+}
+
+// ============================================================================
+// Batch loading
+// ============================================================================
+
+/// A form with several steps, fields and options, numbered by position.
+fn large_form() -> CreateFormInput {
+    let mut input = CreateFormInput::new("Large", "large");
+    for s in 0..4 {
+        let fields = (0..5)
+            .map(|f| {
+                let options = (0..3)
+                    .map(|o| CreateOptionInput::new(format!("O{o}"), format!("o{o}")))
+                    .collect();
+                CreateFieldInput::new(format!("q{s}_{f}"), format!("Q{s}.{f}"), "radio")
+                    .options(options)
+            })
+            .collect();
+        input = input.step(CreateStepInput::new(format!("Step {s}")).order(s).fields(fields));
+    }
+    input
+}
+
+/// Returns a connection to `db` that counts the statements it runs.
+fn counting(db: &DatabaseConnection) -> (DatabaseConnection, Arc<AtomicUsize>) {
+    let queries = Arc::new(AtomicUsize::new(0));
+    let counter = queries.clone();
+    let mut db = db.clone();
+    db.set_metric_callback(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    (db, queries)
+}
+
+#[tokio::test]
+async fn test_steps_fields_and_options_load_in_three_queries() {
+    let test_db = TestDb::new().await;
+    let form = create_test_form(&test_db.db, large_form()).await;
+    let (db, queries) = counting(&test_db.db);
+
+    let steps = SeaOrmRepository::new(db).load_steps_with_fields(form.id).await.unwrap();
+
+    assert_eq!(queries.load(Ordering::SeqCst), 3);
+    assert_eq!(steps.len(), 4);
+    for (s, step) in steps.iter().enumerate() {
+        assert_eq!(step.step.name, format!("Step {s}"));
+        let names: Vec<_> = step.fields.iter().map(|f| f.field.name.clone()).collect();
+        assert_eq!(names, (0..5).map(|f| format!("q{s}_{f}")).collect::<Vec<_>>());
+        for field in &step.fields {
+            let values: Vec<_> = field.options.iter().map(|o| o.value.as_str()).collect();
+            assert_eq!(values, ["o0", "o1", "o2"]);
+            assert!(field.options.iter().all(|o| o.field_id == field.field.id));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_options_of_many_fields_load_in_batches() {
+    let test_db = TestDb::new().await;
+    // 200 steps with 3 choice fields each
+    let form = create_test_form(&test_db.db, anyform::loadtest::bench_form("wide", 200)).await;
+    let (db, queries) = counting(&test_db.db);
+
+    let steps = SeaOrmRepository::new(db).load_steps_with_fields(form.id).await.unwrap();
+
+    // Steps, fields, then two batches of options
+    assert_eq!(queries.load(Ordering::SeqCst), 4);
+    assert_eq!(steps.len(), 200);
+    for step in &steps {
+        let counts: Vec<_> = step.fields.iter().map(|f| f.options.len()).collect();
+        assert_eq!(counts, [0, 0, 4, 4, 6]);
+        for field in &step.fields {
+            let values: Vec<_> = field.options.iter().map(|o| o.value.clone()).collect();
+            let expected: Vec<_> = (1..=values.len()).map(|i| format!("option_{i}")).collect();
+            assert_eq!(values, expected);
+            assert!(field.options.iter().all(|o| o.field_id == field.field.id));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_rendering_html_does_not_query_per_field() {
+    let test_db = TestDb::new().await;
+    let form = create_test_form(&test_db.db, large_form()).await;
+    let (db, queries) = counting(&test_db.db);
+
+    let html = HtmlRenderer::render(&db, &form, &HtmlOptions::new()).await.unwrap();
+
+    assert_eq!(queries.load(Ordering::SeqCst), 3);
+    assert!(html.contains("name=\"q3_4\""));
+    assert_eq!(html.matches("value=\"o2\"").count(), 20);
+}
@@ -0,0 +1,232 @@
+//! Tests for mapping submissions to results.
+
+mod common;
+
+use anyform::{
+    ConditionRule, CreateFieldInput, CreateFormInput, CreateOptionInput, CreateResultInput,
+    CreateStepInput, FormBuilder, FormError, FormSettings, ResultEntity, SubmissionEntity,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+use serde_json::json;
+use uuid::Uuid;
+
+fn style_survey() -> CreateFormInput {
+    CreateFormInput::new("Work style", "style")
+        .step(CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("energy", "Where do you get your energy?", "radio").options(vec![
+                CreateOptionInput::new("People", "people"),
+                CreateOptionInput::new("Quiet time", "quiet"),
+            ]),
+            CreateFieldInput::new("plans", "Do you plan ahead?", "radio").options(vec![
+                CreateOptionInput::new("Yes", "yes"),
+                CreateOptionInput::new("No", "no"),
+            ]),
+        ]))
+        .result(
+            CreateResultInput::new("organizer", "The Organizer")
+                .description("Outgoing and on schedule")
+                .condition(ConditionRule::and(vec![
+                    ConditionRule::eq("energy", "people"),
+                    ConditionRule::eq("plans", "yes"),
+                ])),
+        )
+        .result(
+            CreateResultInput::new("thinker", "The Thinker")
+                .condition(ConditionRule::eq("energy", "quiet")),
+        )
+        .result(CreateResultInput::new("explorer", "The Explorer"))
+}
+
+fn graded_quiz() -> CreateFormInput {
+    CreateFormInput::new("Quiz", "graded")
+        .settings(FormSettings::new().is_quiz(true))
+        .step(CreateStepInput::new("Questions").fields(vec![
+            CreateFieldInput::new("q1", "2 + 2?", "text").correct_answer("4").points(10),
+            CreateFieldInput::new("q2", "Capital of France?", "text")
+                .correct_answer("paris")
+                .points(10),
+        ]))
+        .result(CreateResultInput::new("novice", "Novice").score_range(0, 9))
+        .result(
+            CreateResultInput::new("expert", "Expert")
+                .description("Full marks")
+                .min_score(20)
+                .condition(ConditionRule::eq("q2", "paris")),
+        )
+        .result(CreateResultInput::new("intermediate", "Intermediate").max_score(19))
+}
+
+/// A personality quiz whose answers lean towards a type, the second
+/// question counting double.
+fn personality_quiz() -> CreateFormInput {
+    let question = |name: &str, category: &str| {
+        CreateFieldInput::new(name, name, "radio")
+            .category(category)
+            .options(vec![
+                CreateOptionInput::new("Agree", "agree").points(2),
+                CreateOptionInput::new("Neutral", "neutral").points(1),
+                CreateOptionInput::new("Disagree", "disagree").points(0),
+            ])
+    };
+    CreateFormInput::new("Personality", "personality")
+        .settings(FormSettings::new().is_quiz(true))
+        .step(CreateStepInput::new("Questions").fields(vec![
+            question("parties", "extrovert"),
+            question("books", "introvert").weight(2.0),
+        ]))
+        .result(CreateResultInput::new("introvert", "The Introvert").category("introvert"))
+        .result(CreateResultInput::new("extrovert", "The Extrovert").category("extrovert"))
+}
+
+/// Submits `data` and returns the response's `data` object.
+async fn submit(app: &TestApp, slug: &str, data: serde_json::Value) -> serde_json::Value {
+    let response = app.post_json(&format!("/api/forms/{slug}"), &data).await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<serde_json::Value>()["data"].clone()
+}
+
+// ============================================================================
+// Conditions
+// ============================================================================
+
+#[tokio::test]
+async fn test_survey_result_by_condition() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), style_survey()).await;
+
+    let data = submit(&app, "style", json!({ "energy": "people", "plans": "yes" })).await;
+    assert_eq!(data["result_key"], "organizer");
+    assert_eq!(data["result"]["title"], "The Organizer");
+    assert_eq!(data["result"]["description"], "Outgoing and on schedule");
+    assert!(data.get("score").is_none());
+
+    let id: Uuid = data["submission_id"].as_str().unwrap().parse().unwrap();
+    let submission = SubmissionEntity::find_by_id(id).one(app.db()).await.unwrap().unwrap();
+    assert_eq!(submission.result_key.as_deref(), Some("organizer"));
+
+    let data = submit(&app, "style", json!({ "energy": "quiet", "plans": "yes" })).await;
+    assert_eq!(data["result_key"], "thinker");
+
+    // The result without a score range or condition catches the rest
+    let data = submit(&app, "style", json!({ "energy": "people", "plans": "no" })).await;
+    assert_eq!(data["result"]["key"], "explorer");
+}
+
+#[tokio::test]
+async fn test_no_matching_result() {
+    let app = TestApp::new().await;
+    let survey = style_survey().results(vec![CreateResultInput::new("thinker", "The Thinker")
+        .condition(ConditionRule::eq("energy", "quiet"))]);
+    create_test_form(app.db(), survey).await;
+
+    let data = submit(&app, "style", json!({ "energy": "people" })).await;
+    assert!(data.get("result_key").is_none());
+    assert!(data.get("result").is_none());
+}
+
+// ============================================================================
+// Scores
+// ============================================================================
+
+#[tokio::test]
+async fn test_quiz_result_by_score_range_and_condition() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), graded_quiz()).await;
+
+    let data = submit(&app, "graded", json!({ "q1": "4", "q2": "paris" })).await;
+    assert_eq!(data["score"], 20);
+    assert_eq!(data["result"]["title"], "Expert");
+
+    let data = submit(&app, "graded", json!({ "q1": "4", "q2": "rome" })).await;
+    assert_eq!(data["result_key"], "intermediate");
+
+    let data = submit(&app, "graded", json!({ "q1": "5", "q2": "rome" })).await;
+    assert_eq!(data["result_key"], "novice");
+    assert!(data["result"]["description"].is_null());
+}
+
+// ============================================================================
+// Categories
+// ============================================================================
+
+#[tokio::test]
+async fn test_result_by_highest_category() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), personality_quiz()).await;
+
+    let data = submit(&app, "personality", json!({ "parties": "agree", "books": "neutral" })).await;
+    // 2 points for introvert (1 weighted by 2) and 2 for extrovert: a tie
+    // goes to the first result
+    assert_eq!(data["score"], 4);
+    assert_eq!(data["result_key"], "introvert");
+
+    let answers = json!({ "parties": "agree", "books": "disagree" });
+    let data = submit(&app, "personality", answers).await;
+    assert_eq!(data["result"]["title"], "The Extrovert");
+
+    let id: Uuid = data["submission_id"].as_str().unwrap().parse().unwrap();
+    let submission = SubmissionEntity::find_by_id(id).one(app.db()).await.unwrap().unwrap();
+    let metadata = submission.metadata.unwrap();
+    assert_eq!(metadata["category_scores"], json!({ "extrovert": 2, "introvert": 0 }));
+}
+
+#[tokio::test]
+async fn test_category_scores_stored_for_anonymous_forms() {
+    let app = TestApp::new().await;
+    let quiz = personality_quiz().settings(FormSettings::new().is_quiz(true).anonymous(true));
+    create_test_form(app.db(), quiz).await;
+
+    let data = submit(&app, "personality", json!({ "parties": "neutral", "books": "agree" })).await;
+    let id: Uuid = data["submission_id"].as_str().unwrap().parse().unwrap();
+    let submission = SubmissionEntity::find_by_id(id).one(app.db()).await.unwrap().unwrap();
+    let metadata = submission.metadata.unwrap();
+    assert_eq!(metadata, json!({ "category_scores": { "extrovert": 1, "introvert": 4 } }));
+}
+
+// ============================================================================
+// Form builder
+// ============================================================================
+
+#[tokio::test]
+async fn test_update_replaces_results() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), style_survey()).await;
+    let results = ResultEntity::find_by_form(app.db(), form.id).await.unwrap();
+    let keys: Vec<&str> = results.iter().map(|r| r.key.as_str()).collect();
+    assert_eq!(keys, vec!["organizer", "thinker", "explorer"]);
+
+    // Without results, the form keeps its own
+    let mut input = style_survey();
+    input.results = None;
+    FormBuilder::update(app.db(), form.id, input).await.unwrap();
+    assert_eq!(ResultEntity::find_by_form(app.db(), form.id).await.unwrap().len(), 3);
+
+    let input = style_survey().results(vec![CreateResultInput::new("everyone", "Everyone")]);
+    FormBuilder::update(app.db(), form.id, input).await.unwrap();
+    let results = ResultEntity::find_by_form(app.db(), form.id).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].key, "everyone");
+
+    let data = submit(&app, "style", json!({ "energy": "quiet" })).await;
+    assert_eq!(data["result_key"], "everyone");
+}
+
+#[tokio::test]
+async fn test_invalid_results_are_rejected() {
+    let app = TestApp::new().await;
+    for result in [
+        CreateResultInput::new("", "Nameless"),
+        CreateResultInput::new("backwards", "Backwards").score_range(10, 5),
+    ] {
+        let err = FormBuilder::create(app.db(), style_survey().result(result))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, FormError::InvalidData(_)), "{err:?}");
+    }
+
+    let duplicate = style_survey().result(CreateResultInput::new("thinker", "Again"));
+    let err = FormBuilder::create(app.db(), duplicate).await.unwrap_err();
+    assert!(matches!(err, FormError::InvalidData(_)), "{err:?}");
+}
@@ -0,0 +1,254 @@
+//! Tests for filling in multi-step forms over several requests.
+
+mod common;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FormSettings,
+    StepEntity, SubmissionEntity,
+};
+use common::{create_test_form, multi_step_form, TestApp};
+use http::StatusCode;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// Creates the multi-step fixture form and returns its step IDs in order.
+async fn multi_step_app() -> (TestApp, Vec<String>) {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), multi_step_form()).await;
+    let steps = StepEntity::find_by_form(app.db(), form.id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.id.to_string())
+        .collect();
+    (app, steps)
+}
+
+/// Starts a submission and returns its ID.
+async fn start(app: &TestApp, slug: &str) -> String {
+    let response = app
+        .post_json(&format!("/api/forms/{slug}/submissions/start"), &json!({}))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["data"]["submission_id"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn save_step(app: &TestApp, slug: &str, id: &str, body: Value) -> common::app::TestResponse {
+    app.patch_json(&format!("/api/forms/{slug}/submissions/{id}/step"), &body)
+        .await
+}
+
+async fn complete(app: &TestApp, slug: &str, id: &str) -> common::app::TestResponse {
+    app.post_json(&format!("/api/forms/{slug}/submissions/{id}/complete"), &json!({}))
+        .await
+}
+
+// ============================================================================
+// Save and resume
+// ============================================================================
+
+#[tokio::test]
+async fn test_fill_in_steps_and_complete() {
+    let (app, steps) = multi_step_app().await;
+    let slug = "test-multi-step";
+
+    let response = app
+        .post_json(&format!("/api/forms/{slug}/submissions/start"), &json!({}))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let data = response.json::<Value>()["data"].clone();
+    assert_eq!(data["status"], "in_progress");
+    assert_eq!(data["current_step_id"], steps[0].as_str());
+    let id = data["submission_id"].as_str().unwrap().to_string();
+
+    let body = json!({ "data": { "first_name": "Ada", "last_name": "Lovelace" } });
+    let response = save_step(&app, slug, &id, body).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["data"]["current_step_id"], steps[1].as_str());
+
+    // Resuming picks up where the respondent left off
+    let response = app.get(&format!("/api/forms/{slug}/submissions/{id}")).await;
+    response.assert_status(StatusCode::OK);
+    let data = response.json::<Value>()["data"].clone();
+    assert_eq!(data["current_step_id"], steps[1].as_str());
+    assert_eq!(data["data"]["first_name"], "Ada");
+
+    let body = json!({ "data": { "email": "ada@example.com" } });
+    save_step(&app, slug, &id, body).await.assert_status(StatusCode::OK);
+    let body = json!({ "step_id": steps[2], "data": { "agree_terms": "on" } });
+    let response = save_step(&app, slug, &id, body).await;
+    assert_eq!(response.json::<Value>()["data"]["current_step_id"], steps[2].as_str());
+
+    let response = complete(&app, slug, &id).await;
+    response.assert_status(StatusCode::CREATED);
+    let data = response.json::<Value>()["data"].clone();
+    assert_eq!(data["submission_id"], id.as_str());
+    assert_eq!(data["status"], "submitted");
+
+    let submission = SubmissionEntity::find_by_id(id.parse::<Uuid>().unwrap())
+        .one(app.db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(submission.is_complete());
+    assert_eq!(submission.current_step_id, None);
+    assert_eq!(submission.data["last_name"], "Lovelace");
+    assert_eq!(submission.data["email"], "ada@example.com");
+}
+
+#[tokio::test]
+async fn test_step_errors_are_grouped_by_step() {
+    let (app, steps) = multi_step_app().await;
+    let slug = "test-multi-step";
+    let id = start(&app, slug).await;
+
+    let response = save_step(&app, slug, &id, json!({ "data": { "first_name": "Ada" } })).await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    response.assert_api_error("VALIDATION_FAILED");
+    let details = response.json::<Value>()["error"]["details"].clone();
+    assert!(details["steps"][&steps[0]]["last_name"].is_array(), "{details}");
+
+    // Nothing was saved
+    let response = app.get(&format!("/api/forms/{slug}/submissions/{id}")).await;
+    let data = response.json::<Value>()["data"].clone();
+    assert_eq!(data["current_step_id"], steps[0].as_str());
+    assert_eq!(data["data"], json!({}));
+}
+
+#[tokio::test]
+async fn test_save_step_ignores_answers_outside_the_step() {
+    let (app, steps) = multi_step_app().await;
+    let slug = "test-multi-step";
+    let id = start(&app, slug).await;
+
+    let body = json!({ "data": {
+        "first_name": "Ada",
+        "last_name": "Lovelace",
+        "email": "not an email",
+        "unknown": "value"
+    } });
+    let response = save_step(&app, slug, &id, body).await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.json::<Value>()["data"]["current_step_id"], steps[1].as_str());
+
+    let submission = SubmissionEntity::find_by_id(id.parse::<Uuid>().unwrap())
+        .one(app.db())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(submission.data["first_name"], "Ada");
+    assert!(submission.data.get("email").is_none());
+    assert!(submission.data.get("unknown").is_none());
+}
+
+#[tokio::test]
+async fn test_complete_validates_all_answers() {
+    let (app, _) = multi_step_app().await;
+    let slug = "test-multi-step";
+    let id = start(&app, slug).await;
+
+    let body = json!({ "data": { "first_name": "Ada", "last_name": "Lovelace" } });
+    save_step(&app, slug, &id, body).await.assert_status(StatusCode::OK);
+
+    let response = complete(&app, slug, &id).await;
+    response.assert_api_error("VALIDATION_FAILED");
+    let response = app.get(&format!("/api/forms/{slug}/submissions/{id}")).await;
+    assert_eq!(response.json::<Value>()["data"]["status"], "in_progress");
+}
+
+#[tokio::test]
+async fn test_save_step_skips_hidden_steps() {
+    let app = TestApp::new().await;
+    let input = CreateFormInput::new("Branching", "branching")
+        .step(
+            CreateStepInput::new("Track").field(
+                CreateFieldInput::new("track", "Track", "radio")
+                    .required()
+                    .options(vec![
+                        CreateOptionInput::new("Basic", "basic"),
+                        CreateOptionInput::new("Advanced", "advanced"),
+                    ]),
+            ),
+        )
+        .step(
+            CreateStepInput::new("Advanced")
+                .condition(r#"{"field": "track", "op": "eq", "value": "advanced"}"#)
+                .field(CreateFieldInput::new("detail", "Detail", "text").required()),
+        )
+        .step(CreateStepInput::new("Done").field(CreateFieldInput::new("notes", "Notes", "text")));
+    let form = create_test_form(app.db(), input).await;
+    let steps = StepEntity::find_by_form(app.db(), form.id).await.unwrap();
+    let id = start(&app, "branching").await;
+
+    let response = save_step(&app, "branching", &id, json!({ "data": { "track": "basic" } })).await;
+    let current = response.json::<Value>()["data"]["current_step_id"].clone();
+    assert_eq!(current, steps[2].id.to_string().as_str());
+
+    complete(&app, "branching", &id).await.assert_status(StatusCode::CREATED);
+}
+
+// ============================================================================
+// Places and lifecycle
+// ============================================================================
+
+#[tokio::test]
+async fn test_in_progress_submission_takes_no_place() {
+    let app = TestApp::new().await;
+    let input = common::contact_form().settings(FormSettings::new().max_submissions(1));
+    create_test_form(app.db(), input).await;
+    let slug = "test-contact";
+    let answers = json!({
+        "name": "Ada",
+        "email": "ada@example.com",
+        "message": "Hello from a saved draft"
+    });
+
+    let id = start(&app, slug).await;
+    save_step(&app, slug, &id, json!({ "data": answers })).await.assert_status(StatusCode::OK);
+
+    // The draft doesn't count towards the limit
+    app.post_json(&format!("/api/forms/{slug}"), &answers)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let response = complete(&app, slug, &id).await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("SUBMISSION_LIMIT_REACHED");
+}
+
+#[tokio::test]
+async fn test_completed_submission_cannot_be_resumed() {
+    let app = TestApp::new().await;
+    anyform::seed_contact_form(app.db()).await.unwrap();
+    let id = start(&app, "contact").await;
+    let answers = json!({
+        "name": "Ada",
+        "email": "ada@example.com",
+        "message": "Hello from a saved draft"
+    });
+    save_step(&app, "contact", &id, json!({ "data": answers })).await.assert_status(StatusCode::OK);
+    complete(&app, "contact", &id).await.assert_status(StatusCode::CREATED);
+
+    let response = app.get(&format!("/api/forms/contact/submissions/{id}")).await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    response.assert_api_error("SUBMISSION_NOT_FOUND");
+    complete(&app, "contact", &id).await.assert_api_error("SUBMISSION_NOT_FOUND");
+    save_step(&app, "contact", &id, json!({ "data": answers }))
+        .await
+        .assert_api_error("SUBMISSION_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_submission_of_another_form_is_not_found() {
+    let app = TestApp::new().await;
+    anyform::seed_all(app.db()).await.unwrap();
+    let id = start(&app, "contact").await;
+
+    let response = app.get(&format!("/api/forms/feedback/submissions/{id}")).await;
+    response.assert_status(StatusCode::NOT_FOUND);
+    response.assert_api_error("SUBMISSION_NOT_FOUND");
+}
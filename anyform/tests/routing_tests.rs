@@ -0,0 +1,229 @@
+//! Tests for trailing slashes, HEAD and OPTIONS on the API routes.
+
+mod common;
+
+use anyform::{ApiKeyAuth, TrailingSlash};
+use axum::body::Body;
+use common::app::TestResponse;
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::{header, Method, Request, StatusCode};
+
+async fn app_with(mode: TrailingSlash) -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .admin_auth(ApiKeyAuth::new("secret"))
+        .trailing_slash(mode)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), contact_form()).await;
+    app
+}
+
+async fn send(app: &TestApp, method: Method, uri: &str) -> TestResponse {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    app.send_raw(request).await
+}
+
+fn header(response: &TestResponse, name: header::HeaderName) -> &str {
+    response
+        .headers
+        .get(name)
+        .map(|v| v.to_str().unwrap())
+        .unwrap_or_default()
+}
+
+fn methods(response: &TestResponse, name: header::HeaderName) -> Vec<String> {
+    let mut methods: Vec<String> = header(response, name)
+        .split(',')
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+    methods.sort();
+    methods
+}
+
+/// Public GET routes, under both prefixes.
+const GET_ROUTES: &[&str] = &[
+    "/api/forms/test-contact",
+    "/api/forms/test-contact/json",
+    "/api/forms/test-contact/config.js",
+    "/api/forms/test-contact/success",
+    "/api/v1/forms/test-contact",
+    "/api/v1/forms/test-contact/json",
+    "/api/v1/forms/test-contact/config.js",
+    "/api/v1/forms/test-contact/success",
+];
+
+// ============================================================================
+// HEAD
+// ============================================================================
+
+#[tokio::test]
+async fn test_get_routes_answer_head_without_a_body() {
+    let app = app_with(TrailingSlash::default()).await;
+
+    for uri in GET_ROUTES {
+        let get = send(&app, Method::GET, uri).await;
+        let head = send(&app, Method::HEAD, uri).await;
+
+        assert_eq!(get.status, StatusCode::OK, "GET {uri}");
+        assert_eq!(head.status, StatusCode::OK, "HEAD {uri}");
+        assert!(head.body.is_empty(), "HEAD {uri} has a body");
+        assert_eq!(
+            header(&head, header::CONTENT_TYPE),
+            header(&get, header::CONTENT_TYPE),
+            "HEAD {uri}"
+        );
+    }
+
+    send(&app, Method::HEAD, "/api/forms/missing")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// OPTIONS
+// ============================================================================
+
+#[tokio::test]
+async fn test_options_lists_each_routes_methods() {
+    let app = app_with(TrailingSlash::default()).await;
+    let id = uuid::Uuid::new_v4();
+
+    let cases: Vec<(String, &[&str])> = vec![
+        ("/api/forms/test-contact".into(), &["GET", "HEAD", "OPTIONS", "POST"]),
+        ("/api/forms/test-contact/json".into(), &["GET", "HEAD", "OPTIONS"]),
+        ("/api/forms/test-contact/submit".into(), &["OPTIONS", "POST"]),
+        (
+            format!("/api/forms/test-contact/submissions/{id}/step"),
+            &["OPTIONS", "PATCH"],
+        ),
+        ("/api/v1/forms/test-contact".into(), &["GET", "HEAD", "OPTIONS", "POST"]),
+        (
+            format!("/api/v1/admin/forms/{id}"),
+            &["DELETE", "GET", "HEAD", "OPTIONS", "PUT"],
+        ),
+    ];
+
+    for (uri, expected) in cases {
+        let response = send(&app, Method::OPTIONS, &uri).await;
+        assert_eq!(response.status, StatusCode::NO_CONTENT, "OPTIONS {uri}");
+        assert!(response.body.is_empty());
+        assert_eq!(methods(&response, header::ALLOW), expected, "OPTIONS {uri}");
+        assert_eq!(
+            methods(&response, header::ACCESS_CONTROL_ALLOW_METHODS),
+            expected,
+            "OPTIONS {uri}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_options_lists_the_headers_the_api_reads() {
+    let app = app_with(TrailingSlash::default()).await;
+
+    let request = Request::builder()
+        .method(Method::OPTIONS)
+        .uri("/api/forms/test-contact")
+        .header(header::ORIGIN, "https://example.com")
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send_raw(request).await;
+
+    response.assert_status(StatusCode::NO_CONTENT);
+    let headers = header(&response, header::ACCESS_CONTROL_ALLOW_HEADERS);
+    for name in ["content-type", "authorization", "anyform-version", "x-anyform-responded"] {
+        assert!(headers.contains(name), "{name} not in {headers}");
+    }
+    assert_eq!(header(&response, header::ACCESS_CONTROL_MAX_AGE), "86400");
+    assert_eq!(header(&response, anyform::versioning::VERSION_HEADER), "1");
+}
+
+#[tokio::test]
+async fn test_options_on_unknown_paths_is_not_found() {
+    let app = app_with(TrailingSlash::default()).await;
+
+    send(&app, Method::OPTIONS, "/api/nothing/here")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Trailing slashes
+// ============================================================================
+
+#[tokio::test]
+async fn test_trailing_slashes_are_ignored_by_default() {
+    let app = app_with(TrailingSlash::default()).await;
+
+    for uri in GET_ROUTES {
+        for method in [Method::GET, Method::HEAD] {
+            let response = send(&app, method.clone(), &format!("{uri}/")).await;
+            assert_eq!(response.status, StatusCode::OK, "{method} {uri}/");
+        }
+        let response = send(&app, Method::OPTIONS, &format!("{uri}/")).await;
+        assert_eq!(response.status, StatusCode::NO_CONTENT, "OPTIONS {uri}/");
+    }
+
+    let response = app
+        .post_json(
+            "/api/forms/test-contact/",
+            &serde_json::json!({
+                "name": "Ada",
+                "email": "ada@example.com",
+                "message": "Hello there"
+            }),
+        )
+        .await;
+    response.assert_status(StatusCode::CREATED);
+
+    let response = app.get("/api/forms/test-contact/config.js/?callback=init").await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.text().contains("init({ form: "));
+
+    send(&app, Method::GET, "/api/forms/missing/")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    send(&app, Method::GET, "/api/nothing/here/")
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_trailing_slashes_can_redirect() {
+    let app = app_with(TrailingSlash::Redirect).await;
+
+    for method in [Method::GET, Method::HEAD, Method::POST, Method::OPTIONS] {
+        let response = send(&app, method.clone(), "/api/v1/forms/test-contact/?lang=fr").await;
+        assert_eq!(response.status, StatusCode::PERMANENT_REDIRECT, "{method}");
+        assert_eq!(
+            header(&response, header::LOCATION),
+            "/api/v1/forms/test-contact?lang=fr",
+            "{method}"
+        );
+    }
+
+    send(&app, Method::GET, "/api/forms/test-contact")
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_trailing_slashes_can_be_strict() {
+    let app = app_with(TrailingSlash::Strict).await;
+
+    for uri in GET_ROUTES {
+        for method in [Method::GET, Method::HEAD, Method::OPTIONS] {
+            let response = send(&app, method.clone(), &format!("{uri}/")).await;
+            assert_eq!(response.status, StatusCode::NOT_FOUND, "{method} {uri}/");
+        }
+    }
+}
@@ -0,0 +1,212 @@
+//! Tests for form schedule previews.
+
+mod common;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, FormSettings, Frequency,
+    Recurrence, ScheduleService,
+};
+use chrono::{DateTime, FixedOffset};
+use common::{create_test_form, TestApp, TestDb};
+use http::StatusCode;
+use serde_json::{json, Value};
+
+fn at(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+fn form_with(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Signup", "signup")
+        .settings(settings)
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text")))
+}
+
+async fn sign_up(app: &TestApp) {
+    app.post_json("/api/forms/signup", &json!({ "name": "Ada" }))
+        .await
+        .assert_status(StatusCode::CREATED);
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+#[tokio::test]
+async fn test_plain_form_is_open() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), form_with(FormSettings::new())).await;
+
+    let schedule = ScheduleService::preview(db.conn(), &form, at("2025-01-01T00:00:00Z"), 5)
+        .await
+        .unwrap();
+    let json = serde_json::to_value(&schedule).unwrap();
+    assert_eq!(json["state"], "open");
+    assert!(json.get("reason").is_none());
+    assert_eq!(json["quotas"], json!([]));
+    assert_eq!(json["upcoming"], json!([]));
+}
+
+#[tokio::test]
+async fn test_status_closes_the_form() {
+    let db = TestDb::new().await;
+    let form = create_test_form(db.conn(), form_with(FormSettings::new())).await;
+    let now = at("2025-01-01T00:00:00Z");
+
+    let form = FormBuilder::unpublish(db.conn(), form.id).await.unwrap();
+    let schedule = ScheduleService::preview(db.conn(), &form, now, 5).await.unwrap();
+    let json = serde_json::to_value(&schedule).unwrap();
+    assert_eq!(json["state"], "closed");
+    assert_eq!(json["reason"], "draft");
+
+    let form = FormBuilder::archive(db.conn(), form.id).await.unwrap();
+    let schedule = ScheduleService::preview(db.conn(), &form, now, 5).await.unwrap();
+    assert_eq!(serde_json::to_value(&schedule).unwrap()["reason"], "archived");
+}
+
+#[tokio::test]
+async fn test_opening_times_close_the_form_outside_them() {
+    let db = TestDb::new().await;
+    let settings = FormSettings::new()
+        .opens_at(at("2025-03-01T09:00:00Z"))
+        .closes_at(at("2025-03-31T17:00:00Z"));
+    let form = create_test_form(db.conn(), form_with(settings)).await;
+
+    for (now, state, reason) in [
+        ("2025-02-28T12:00:00Z", "closed", json!("not_yet_open")),
+        ("2025-03-01T09:00:00Z", "open", Value::Null),
+        ("2025-03-31T17:00:00Z", "closed", json!("past_close_date")),
+    ] {
+        let schedule = ScheduleService::preview(db.conn(), &form, at(now), 5).await.unwrap();
+        let json = serde_json::to_value(&schedule).unwrap();
+        assert_eq!(json["state"], state, "{now}");
+        assert_eq!(json["reason"], reason, "{now}");
+    }
+}
+
+#[tokio::test]
+async fn test_recurring_form_lists_upcoming_instances() {
+    let db = TestDb::new().await;
+    let weekly = Recurrence::new(Frequency::Weekly, at("2025-01-06T09:00:00Z"))
+        .open_for_hours(48)
+        .count(3);
+    let form = create_test_form(db.conn(), form_with(FormSettings::new().recurrence(weekly))).await;
+
+    // Before the first instance
+    let schedule = ScheduleService::preview(db.conn(), &form, at("2025-01-01T00:00:00Z"), 2)
+        .await
+        .unwrap();
+    let json = serde_json::to_value(&schedule).unwrap();
+    assert_eq!(json["state"], "closed");
+    assert_eq!(json["reason"], "between_instances");
+    assert!(json.get("instance").is_none());
+    let upcoming = json["upcoming"].as_array().unwrap();
+    assert_eq!(upcoming.len(), 2);
+    assert_eq!(at(upcoming[0]["opens_at"].as_str().unwrap()), at("2025-01-06T09:00:00Z"));
+    assert_eq!(at(upcoming[0]["closes_at"].as_str().unwrap()), at("2025-01-08T09:00:00Z"));
+
+    // During the second
+    let schedule = ScheduleService::preview(db.conn(), &form, at("2025-01-14T00:00:00Z"), 5)
+        .await
+        .unwrap();
+    let json = serde_json::to_value(&schedule).unwrap();
+    assert_eq!(json["state"], "open");
+    assert_eq!(json["instance"]["sequence"], 1);
+    assert_eq!(json["upcoming"].as_array().unwrap().len(), 1);
+
+    // After the last
+    let schedule = ScheduleService::preview(db.conn(), &form, at("2025-02-01T00:00:00Z"), 5)
+        .await
+        .unwrap();
+    assert_eq!(serde_json::to_value(&schedule).unwrap()["reason"], "ended");
+}
+
+// ============================================================================
+// Quotas
+// ============================================================================
+
+#[tokio::test]
+async fn test_quotas_count_down_to_closing() {
+    let app = TestApp::new().await;
+    let settings = FormSettings::new().close_after(2).max_submissions(5);
+    let form = create_test_form(app.db(), form_with(settings)).await;
+    let now = chrono::Utc::now().fixed_offset();
+
+    sign_up(&app).await;
+    let form = FormBuilder::find_by_slug(app.db(), &form.slug).await.unwrap().unwrap();
+    let schedule = ScheduleService::preview(app.db(), &form, now, 5).await.unwrap();
+    let json = serde_json::to_value(&schedule).unwrap();
+    assert_eq!(json["state"], "open");
+    assert_eq!(
+        json["quotas"],
+        json!([
+            {
+                "kind": "max_submissions", "limit": 5, "accepted": 1, "remaining": 4,
+                "then": "closed"
+            },
+            {
+                "kind": "close_after", "limit": 2, "accepted": 1, "remaining": 1,
+                "then": "closed"
+            }
+        ])
+    );
+
+    sign_up(&app).await;
+    let form = FormBuilder::find_by_slug(app.db(), &form.slug).await.unwrap().unwrap();
+    let schedule = ScheduleService::preview(app.db(), &form, now, 5).await.unwrap();
+    let json = serde_json::to_value(&schedule).unwrap();
+    assert_eq!(json["state"], "closed");
+    assert_eq!(json["reason"], "quota_reached");
+}
+
+#[tokio::test]
+async fn test_full_form_with_waitlist_is_waitlisting() {
+    let app = TestApp::new().await;
+    let settings = FormSettings::new().max_submissions(1).waitlist(true);
+    let form = create_test_form(app.db(), form_with(settings)).await;
+
+    sign_up(&app).await;
+    let schedule =
+        ScheduleService::preview(app.db(), &form, chrono::Utc::now().fixed_offset(), 5)
+            .await
+            .unwrap();
+    let json = serde_json::to_value(&schedule).unwrap();
+    assert_eq!(json["state"], "waitlisting");
+    assert!(json.get("reason").is_none());
+    assert_eq!(json["quotas"][0]["remaining"], 0);
+    assert_eq!(json["quotas"][0]["then"], "waitlisting");
+}
+
+// ============================================================================
+// Admin route
+// ============================================================================
+
+#[cfg(feature = "admin")]
+#[tokio::test]
+async fn test_admin_schedule_route() {
+    let app = TestApp::with_admin().await;
+    let weekly = Recurrence::new(Frequency::Weekly, at("2025-01-06T09:00:00Z"));
+    let form = create_test_form(app.db(), form_with(FormSettings::new().recurrence(weekly))).await;
+
+    let response = app
+        .get(&format!(
+            "/api/admin/forms/{}/schedule?at=2025-01-01T00:00:00Z&upcoming=3",
+            form.id
+        ))
+        .await;
+    response.assert_status(StatusCode::OK);
+    let json: Value = response.json();
+    assert_eq!(json["data"]["reason"], "between_instances");
+    assert_eq!(json["data"]["upcoming"].as_array().unwrap().len(), 3);
+
+    // Without `at`, the schedule is for now
+    let json: Value = app
+        .get(&format!("/api/admin/forms/{}/schedule", form.id))
+        .await
+        .json();
+    assert_eq!(json["data"]["state"], "open");
+    assert_eq!(json["data"]["upcoming"].as_array().unwrap().len(), 5);
+
+    app.get(&format!("/api/admin/forms/{}/schedule", uuid::Uuid::new_v4()))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,100 @@
+//! Tests for serving only some forms.
+
+mod common;
+
+use common::{contact_form, create_test_form, options_form, TestApp, TestDb};
+use http::StatusCode;
+use serde_json::{json, Value};
+
+/// Serves `test-contact` of a database also holding `test-options`, and
+/// returns the IDs of both.
+async fn scoped_app() -> (TestApp, String, String) {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .enable_graphql(true)
+        .only_forms(["test-contact"])
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    let contact = create_test_form(app.db(), contact_form()).await;
+    let options = create_test_form(app.db(), options_form()).await;
+    (app, contact.id.to_string(), options.id.to_string())
+}
+
+#[tokio::test]
+async fn test_public_routes_serve_only_listed_forms() {
+    let (app, _, _) = scoped_app().await;
+
+    app.get("/api/forms/test-contact").await.assert_status(StatusCode::OK);
+    app.get("/api/forms/test-contact/json").await.assert_status(StatusCode::OK);
+    app.get("/api/forms/test-options").await.assert_status(StatusCode::NOT_FOUND);
+    app.get("/api/forms/test-options/json").await.assert_status(StatusCode::NOT_FOUND);
+    app.post_json("/api/forms/test-options", &json!({ "color": "red" }))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_admin_routes_hide_other_forms() {
+    let (app, contact, options) = scoped_app().await;
+
+    app.get(&format!("/api/admin/forms/{contact}")).await.assert_status(StatusCode::OK);
+    app.get(&format!("/api/admin/forms/{options}")).await.assert_status(StatusCode::NOT_FOUND);
+    app.get(&format!("/api/admin/forms/{options}/submissions"))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.delete(&format!("/api/admin/forms/{options}"))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.post_json(&format!("/api/admin/forms/{options}/duplicate"), &json!({}))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let json: Value = app.get("/api/admin/forms").await.json();
+    assert_eq!(json["data"]["count"], 1);
+    assert_eq!(json["data"]["forms"][0]["slug"], "test-contact");
+
+    // Totals across every form aren't served
+    app.get("/api/admin/stats").await.assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_writes_stay_within_listed_forms() {
+    let (app, contact, _) = scoped_app().await;
+
+    let mut input = serde_json::to_value(contact_form()).unwrap();
+    input["slug"] = json!("elsewhere");
+    app.post_json("/api/admin/forms", &input)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    app.put_json(&format!("/api/admin/forms/{contact}"), &input)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    // The copy's slug isn't listed either
+    app.post_json(&format!("/api/admin/forms/{contact}/duplicate"), &json!({}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let json: Value = app.post_json("/api/admin/forms/sync", &json!([input])).await.json();
+    assert_eq!(json["data"]["created"], 0);
+    assert_eq!(json["data"]["errors"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_graphql_lists_only_listed_forms() {
+    let (app, _, options) = scoped_app().await;
+
+    let query = json!({ "query": "{ forms { slug } }" });
+    let json: Value = app.post_json("/api/admin/graphql", &query).await.json();
+    assert_eq!(json["data"]["forms"], json!([{ "slug": "test-contact" }]));
+
+    let query = json!({
+        "query": "query($id: ID!) { form(id: $id) { slug } }",
+        "variables": { "id": options },
+    });
+    let json: Value = app.post_json("/api/admin/graphql", &query).await.json();
+    assert!(json.get("errors").is_none(), "{json}");
+    assert_eq!(json["data"]["form"], Value::Null);
+}
@@ -0,0 +1,225 @@
+//! Tests for quiz scoring.
+
+mod common;
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FormSettings,
+    ResultActiveModel, SubmissionEntity,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, EntityTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+async fn quiz_app() -> TestApp {
+    let app = TestApp::new().await;
+    anyform::seed_quiz_form(app.db()).await.unwrap();
+    app
+}
+
+fn quiz_form(slug: &str, fields: Vec<CreateFieldInput>) -> CreateFormInput {
+    CreateFormInput::new("Quiz", slug)
+        .settings(FormSettings::new().is_quiz(true))
+        .step(CreateStepInput::new("Questions").fields(fields))
+}
+
+async fn add_result(db: &DatabaseConnection, form_id: Uuid, key: &str, min: i32, max: i32) {
+    ResultActiveModel {
+        id: ActiveValue::Set(Uuid::new_v4()),
+        form_id: ActiveValue::Set(form_id),
+        key: ActiveValue::Set(key.to_string()),
+        title: ActiveValue::Set(key.to_string()),
+        description: ActiveValue::Set(None),
+        min_score: ActiveValue::Set(Some(min)),
+        max_score: ActiveValue::Set(Some(max)),
+        category: ActiveValue::Set(None),
+        condition: ActiveValue::Set(None),
+        order: ActiveValue::Set(0),
+        deleted_at: ActiveValue::Set(None),
+    }
+    .insert(db)
+    .await
+    .unwrap();
+}
+
+/// Submits `data` and returns the response's `data` object.
+async fn submit(app: &TestApp, slug: &str, data: serde_json::Value) -> serde_json::Value {
+    let response = app.post_json(&format!("/api/forms/{slug}"), &data).await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<serde_json::Value>()["data"].clone()
+}
+
+// ============================================================================
+// Seeded quiz
+// ============================================================================
+
+#[tokio::test]
+async fn test_quiz_submission_is_scored() {
+    let app = quiz_app().await;
+
+    let data = submit(&app, "quiz", json!({ "q1": "paris", "q2": "mars", "q3": "4" })).await;
+    assert_eq!(data["score"], 30);
+    assert_eq!(data["max_score"], 30);
+
+    let data = submit(&app, "quiz", json!({ "q1": "paris", "q2": "venus", "q3": "5" })).await;
+    assert_eq!(data["score"], 10);
+    assert_eq!(data["max_score"], 30);
+
+    let id: Uuid = data["submission_id"].as_str().unwrap().parse().unwrap();
+    let submission = SubmissionEntity::find_by_id(id).one(app.db()).await.unwrap().unwrap();
+    assert_eq!(submission.score, Some(10));
+    assert_eq!(submission.max_score, Some(30));
+    assert_eq!(submission.result_key, None);
+}
+
+#[tokio::test]
+async fn test_quiz_submission_resolves_result() {
+    let app = quiz_app().await;
+    let form = anyform::FormBuilder::find_by_slug(app.db(), "quiz").await.unwrap().unwrap();
+    add_result(app.db(), form.id, "keep-practicing", 0, 19).await;
+    add_result(app.db(), form.id, "expert", 20, 30).await;
+
+    let data = submit(&app, "quiz", json!({ "q1": "paris", "q2": "mars", "q3": "5" })).await;
+    assert_eq!(data["result_key"], "expert");
+
+    let data = submit(&app, "quiz", json!({ "q1": "london", "q2": "mars", "q3": "5" })).await;
+    assert_eq!(data["result_key"], "keep-practicing");
+
+    let id: Uuid = data["submission_id"].as_str().unwrap().parse().unwrap();
+    let submission = SubmissionEntity::find_by_id(id).one(app.db()).await.unwrap().unwrap();
+    assert_eq!(submission.result_key.as_deref(), Some("keep-practicing"));
+}
+
+#[tokio::test]
+async fn test_non_quiz_submission_is_not_scored() {
+    let app = TestApp::new().await;
+    anyform::seed_contact_form(app.db()).await.unwrap();
+
+    let data = submit(
+        &app,
+        "contact",
+        json!({ "name": "Ada", "email": "ada@example.com", "message": "Hello there!" }),
+    )
+    .await;
+    assert!(data.get("score").is_none());
+    assert!(data.get("max_score").is_none());
+}
+
+// ============================================================================
+// Scoring rules
+// ============================================================================
+
+#[tokio::test]
+async fn test_correct_answer_is_case_insensitive() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        quiz_form(
+            "capitals",
+            vec![
+                CreateFieldInput::new("capital", "Capital of Italy?", "text")
+                    .correct_answer("Rome")
+                    .points(5),
+                CreateFieldInput::new("sea", "Largest ocean?", "text").correct_answer("Pacific"),
+            ],
+        ),
+    )
+    .await;
+
+    let data = submit(&app, "capitals", json!({ "capital": "  rome ", "sea": "Atlantic" })).await;
+    assert_eq!(data["score"], 5);
+    assert_eq!(data["max_score"], 6);
+}
+
+#[tokio::test]
+async fn test_correct_options_must_all_be_chosen() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        quiz_form(
+            "primes",
+            vec![CreateFieldInput::new("primes", "Which are prime?", "multi_select")
+                .points(4)
+                .options(vec![
+                    CreateOptionInput::new("2", "2").correct(),
+                    CreateOptionInput::new("3", "3").correct(),
+                    CreateOptionInput::new("4", "4"),
+                ])],
+        ),
+    )
+    .await;
+
+    let data = submit(&app, "primes", json!({ "primes": ["2", "3"] })).await;
+    assert_eq!(data["score"], 4);
+
+    let data = submit(&app, "primes", json!({ "primes": ["2"] })).await;
+    assert_eq!(data["score"], 0);
+
+    let data = submit(&app, "primes", json!({ "primes": ["2", "3", "4"] })).await;
+    assert_eq!(data["score"], 0);
+    assert_eq!(data["max_score"], 4);
+}
+
+#[tokio::test]
+async fn test_option_points_add_up_with_weight() {
+    let app = TestApp::new().await;
+    create_test_form(
+        app.db(),
+        quiz_form(
+            "habits",
+            vec![
+                CreateFieldInput::new("habits", "Habits", "multi_select")
+                    .weight(2.0)
+                    .options(vec![
+                        CreateOptionInput::new("Exercise", "exercise").points(3),
+                        CreateOptionInput::new("Sleep", "sleep").points(2),
+                        CreateOptionInput::new("Smoking", "smoking").points(-4),
+                    ]),
+                CreateFieldInput::new("diet", "Diet", "radio").options(vec![
+                    CreateOptionInput::new("Balanced", "balanced").points(5),
+                    CreateOptionInput::new("Fast food", "fast_food").points(1),
+                ]),
+            ],
+        ),
+    )
+    .await;
+
+    let data = submit(
+        &app,
+        "habits",
+        json!({ "habits": ["exercise", "smoking"], "diet": "fast_food" }),
+    )
+    .await;
+    // (3 - 4) * 2 + 1
+    assert_eq!(data["score"], -1);
+    // (3 + 2) * 2 + 5
+    assert_eq!(data["max_score"], 15);
+}
+
+#[tokio::test]
+async fn test_fields_on_hidden_steps_are_not_scored() {
+    let app = TestApp::new().await;
+    let input = quiz_form(
+        "branching",
+        vec![CreateFieldInput::new("track", "Track", "radio")
+            .required()
+            .options(vec![
+                CreateOptionInput::new("Basic", "basic"),
+                CreateOptionInput::new("Advanced", "advanced"),
+            ])],
+    )
+    .step(
+        CreateStepInput::new("Advanced")
+            .condition(r#"{"field": "track", "op": "eq", "value": "advanced"}"#)
+            .field(CreateFieldInput::new("hard", "Hard question", "text").correct_answer("42")),
+    );
+    create_test_form(app.db(), input).await;
+
+    let data = submit(&app, "branching", json!({ "track": "basic" })).await;
+    assert!(data.get("score").is_none(), "{data}");
+
+    let data = submit(&app, "branching", json!({ "track": "advanced", "hard": "42" })).await;
+    assert_eq!(data["score"], 1);
+    assert_eq!(data["max_score"], 1);
+}
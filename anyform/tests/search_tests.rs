@@ -0,0 +1,137 @@
+//! Tests for full-text search over submissions.
+
+#![cfg(feature = "search")]
+
+mod common;
+
+use anyform::services::SearchIndex;
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateStepInput, FieldClassification, ProjectionService,
+    SearchService,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use uuid::Uuid;
+
+fn feedback_form() -> CreateFormInput {
+    CreateFormInput::new("Feedback", "feedback").step(CreateStepInput::new("Main").fields(vec![
+        CreateFieldInput::new("email", "Email", "text")
+            .classification(FieldClassification::Sensitive),
+        CreateFieldInput::new("comment", "Comment", "textarea"),
+        CreateFieldInput::new("rating", "Rating", "number"),
+    ]))
+}
+
+async fn give_feedback(app: &TestApp, email: &str, comment: &str) -> String {
+    let response = app
+        .post_json(
+            "/api/forms/feedback",
+            &serde_json::json!({ "email": email, "comment": comment, "rating": 3 }),
+        )
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    json["data"]["submission_id"].as_str().unwrap().to_string()
+}
+
+async fn search(app: &TestApp, form_id: Uuid, params: &str) -> serde_json::Value {
+    let response = app
+        .get(&format!("/api/admin/forms/{form_id}/submissions?{params}"))
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.json()
+}
+
+fn ids(json: &serde_json::Value) -> Vec<String> {
+    json["data"]["submissions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_search_ranks_matches_with_snippets() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), feedback_form()).await;
+    let mild = give_feedback(
+        &app,
+        "ada@example.com",
+        "The colours were lovely, the fonts readable, but the checkout felt slow at times",
+    )
+    .await;
+    let strong = give_feedback(&app, "grace@example.com", "Slow checkout. So slow <3").await;
+    give_feedback(&app, "edsger@example.com", "Checkout was quick").await;
+
+    let json = search(&app, form.id, "q=SLOW+checkout").await;
+    assert_eq!(ids(&json), vec![strong, mild]);
+    assert_eq!(json["pagination"]["total_items"], 2);
+    assert_eq!(
+        json["data"]["submissions"][0]["snippet"],
+        "<mark>Slow</mark> <mark>checkout</mark>. So <mark>slow</mark> &lt;3"
+    );
+
+    // A blank search lists everything, without snippets
+    let json = search(&app, form.id, "q=+").await;
+    assert_eq!(ids(&json).len(), 3);
+    assert!(json["data"]["submissions"][0].get("snippet").is_none());
+}
+
+#[tokio::test]
+async fn test_search_skips_sensitive_answers() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), feedback_form()).await;
+    give_feedback(&app, "ada@example.com", "Great workshop").await;
+
+    assert!(ids(&search(&app, form.id, "q=ada").await).is_empty());
+    assert_eq!(ids(&search(&app, form.id, "q=workshop").await).len(), 1);
+}
+
+#[tokio::test]
+async fn test_search_applies_filters_and_pages() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), feedback_form()).await;
+    let first = give_feedback(&app, "a@example.com", "Slow").await;
+    let second = give_feedback(&app, "b@example.com", "Slow").await;
+    let deleted = give_feedback(&app, "c@example.com", "Slow").await;
+    app.delete(&format!("/api/admin/forms/{}/submissions/{deleted}", form.id))
+        .await
+        .assert_status(StatusCode::OK);
+
+    let page1 = search(&app, form.id, "q=slow&per_page=1").await;
+    let page2 = search(&app, form.id, "q=slow&per_page=1&page=2").await;
+    assert_eq!(page1["pagination"]["total_items"], 2);
+    let mut found = [ids(&page1), ids(&page2)].concat();
+    found.sort();
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(found, expected);
+
+    let json = search(&app, form.id, "q=slow&status=waitlisted").await;
+    assert!(ids(&json).is_empty());
+}
+
+#[tokio::test]
+async fn test_rebuild_reindexes_submissions() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), feedback_form()).await;
+    let id = give_feedback(&app, "ada@example.com", "Loved the venue").await;
+
+    SearchService::clear(app.db(), form.id).await.unwrap();
+    assert!(ids(&search(&app, form.id, "q=venue").await).is_empty());
+
+    let report = ProjectionService::rebuild(app.db(), &form, &[&SearchIndex], 10, |_| {})
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(report.replayed, 1);
+    assert_eq!(ids(&search(&app, form.id, "q=venue").await), vec![id]);
+
+    // Rebuilding again doesn't index submissions twice
+    ProjectionService::rebuild(app.db(), &form, &[&SearchIndex], 10, |_| {})
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(ids(&search(&app, form.id, "q=venue").await).len(), 1);
+}
@@ -0,0 +1,135 @@
+//! Tests for the seed registry.
+
+mod common;
+
+use anyform::seed::{EmbeddedJson, JsonDir, SeedProvider, Seeder};
+use anyform::{CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, SeedReport};
+use common::TestDb;
+use tempfile::TempDir;
+
+fn signup_form(label: &str) -> CreateFormInput {
+    CreateFormInput::new("Signup", "signup")
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("email", label, "email")))
+}
+
+fn slugs(list: &[String]) -> Vec<&str> {
+    list.iter().map(String::as_str).collect()
+}
+
+// ============================================================================
+// Idempotency
+// ============================================================================
+
+#[tokio::test]
+async fn test_examples_are_seeded_once() {
+    let db = TestDb::new().await;
+
+    let report = Seeder::examples().run(db.conn()).await.unwrap();
+    assert_eq!(
+        slugs(&report.created),
+        ["contact", "feedback", "quiz", "onboarding", "showcase"]
+    );
+
+    let report = Seeder::examples().run(db.conn()).await.unwrap();
+    assert!(report.created.is_empty());
+    assert_eq!(report.unchanged.len(), 5);
+    assert_eq!(FormBuilder::list(db.conn()).await.unwrap().len(), 5);
+}
+
+#[tokio::test]
+async fn test_changed_definition_updates_form() {
+    let db = TestDb::new().await;
+    Seeder::new().form(signup_form("Email")).run(db.conn()).await.unwrap();
+
+    let report = Seeder::new()
+        .form(signup_form("Work email"))
+        .run(db.conn())
+        .await
+        .unwrap();
+    assert_eq!(slugs(&report.updated), ["signup"]);
+
+    let form = FormBuilder::find_by_slug(db.conn(), "signup").await.unwrap().unwrap();
+    let json = anyform::JsonRenderer::render(db.conn(), &form).await.unwrap();
+    assert_eq!(json.steps[0].fields[0].label, "Work email");
+}
+
+#[tokio::test]
+async fn test_forms_created_elsewhere_are_left_alone() {
+    let db = TestDb::new().await;
+    FormBuilder::create(db.conn(), signup_form("Email")).await.unwrap();
+
+    let report = Seeder::new()
+        .form(signup_form("Work email"))
+        .run(db.conn())
+        .await
+        .unwrap();
+    assert_eq!(
+        report,
+        SeedReport {
+            skipped: vec!["signup".to_string()],
+            ..SeedReport::default()
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_clear_forgets_seeded_forms() {
+    let db = TestDb::new().await;
+    Seeder::examples().run(db.conn()).await.unwrap();
+
+    assert_eq!(Seeder::examples().clear(db.conn()).await.unwrap(), 5);
+    assert!(FormBuilder::list(db.conn()).await.unwrap().is_empty());
+
+    let report = Seeder::examples().run(db.conn()).await.unwrap();
+    assert_eq!(report.created.len(), 5);
+}
+
+// ============================================================================
+// Providers
+// ============================================================================
+
+#[tokio::test]
+async fn test_json_directory_is_seeded_in_name_order() {
+    let dir = TempDir::new().unwrap();
+    let form = |slug: &str| format!(r#"{{"name": "{slug}", "slug": "{slug}"}}"#);
+    std::fs::write(dir.path().join("b.json"), form("beta")).unwrap();
+    std::fs::write(
+        dir.path().join("a.json"),
+        format!("[{}, {}]", form("alpha"), form("gamma")),
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "not a form").unwrap();
+
+    let forms = JsonDir::new(dir.path()).forms().unwrap();
+    let order: Vec<&str> = forms.iter().map(|f| f.slug.as_str()).collect();
+    assert_eq!(order, ["alpha", "gamma", "beta"]);
+
+    let db = TestDb::new().await;
+    let report = Seeder::new().dir(dir.path()).run(db.conn()).await.unwrap();
+    assert_eq!(report.created.len(), 3);
+}
+
+#[tokio::test]
+async fn test_embedded_json_and_code_providers_combine() {
+    static FILES: &[(&str, &str)] = &[("poll.json", r#"{"name": "Poll", "slug": "poll"}"#)];
+
+    let db = TestDb::new().await;
+    let report = Seeder::new()
+        .provider(EmbeddedJson::new(FILES))
+        .form(signup_form("Email"))
+        .run(db.conn())
+        .await
+        .unwrap();
+    assert_eq!(slugs(&report.created), ["poll", "signup"]);
+    assert_eq!(report.to_string(), "Created: poll, signup");
+}
+
+#[test]
+fn test_duplicate_slugs_are_rejected() {
+    let seeder = Seeder::new()
+        .form(signup_form("Email"))
+        .form(signup_form("Work email"));
+    let err = seeder.forms().unwrap_err();
+    assert_eq!(err.error_code(), "INVALID_DATA");
+    assert!(err.to_string().contains("signup"));
+}
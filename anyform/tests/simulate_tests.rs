@@ -0,0 +1,151 @@
+//! Tests for simulating submissions (admin).
+
+#![cfg(feature = "admin")]
+
+mod common;
+
+use anyform::{
+    ConditionRule, CreateFieldInput, CreateFormInput, CreateResultInput, CreateStepInput,
+    FormSettings, JumpRule, SubmissionEntity, UiOptions,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+/// Plan, then Personal or Company depending on the plan, then Contact.
+fn branching_form() -> CreateFormInput {
+    let business = ConditionRule::eq("plan", "business");
+    CreateFormInput::new("Branching", "branching")
+        .settings(FormSettings::new().draft(true))
+        .step(
+            CreateStepInput::new("Plan").field(
+                CreateFieldInput::new("plan", "Plan", "text")
+                    .required()
+                    .ui(UiOptions::new().jump(JumpRule::to_step("Company").when(business))),
+            ),
+        )
+        .step(
+            CreateStepInput::new("Personal")
+                .field(CreateFieldInput::new("nickname", "Nickname", "text").required())
+                .jump(JumpRule::to_step("Contact")),
+        )
+        .step(
+            CreateStepInput::new("Company")
+                .field(CreateFieldInput::new("company", "Company", "text").required()),
+        )
+        .step(
+            CreateStepInput::new("Contact")
+                .field(CreateFieldInput::new("email", "Email", "email").required()),
+        )
+        .result(
+            CreateResultInput::new("business", "Business")
+                .condition(ConditionRule::eq("plan", "business")),
+        )
+}
+
+fn graded_quiz() -> CreateFormInput {
+    CreateFormInput::new("Quiz", "graded")
+        .settings(FormSettings::new().is_quiz(true))
+        .step(CreateStepInput::new("Questions").fields(vec![
+            CreateFieldInput::new("q1", "2 + 2?", "text").correct_answer("4").points(10),
+            CreateFieldInput::new("q2", "Capital of France?", "text")
+                .correct_answer("paris")
+                .points(10),
+        ]))
+        .result(CreateResultInput::new("novice", "Novice").score_range(0, 9))
+        .result(CreateResultInput::new("expert", "Expert").description("Full marks").min_score(20))
+        .result(CreateResultInput::new("intermediate", "Intermediate").max_score(19))
+}
+
+async fn simulate(app: &TestApp, form_id: Uuid, answers: Value) -> Value {
+    let response = app
+        .post_json(&format!("/api/admin/forms/{form_id}/simulate"), &answers)
+        .await;
+    response.assert_status(StatusCode::OK);
+    response.json::<Value>()["data"].clone()
+}
+
+fn visible_steps(simulation: &Value) -> Vec<&str> {
+    simulation["steps"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|s| s["visible"] == true)
+        .map(|s| s["name"].as_str().unwrap())
+        .collect()
+}
+
+// ============================================================================
+// Branching and validation
+// ============================================================================
+
+#[tokio::test]
+async fn test_simulation_follows_branching() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), branching_form()).await;
+
+    let personal = simulate(&app, form.id, json!({ "plan": "personal", "nickname": "Ada" })).await;
+    assert_eq!(visible_steps(&personal), ["Plan", "Personal", "Contact"]);
+    assert_eq!(personal["route"].as_array().unwrap().len(), 3);
+    assert_eq!(personal["valid"], false);
+    let errors = personal["errors"].as_object().unwrap();
+    assert_eq!(errors.keys().collect::<Vec<_>>(), ["email"]);
+    assert!(personal.get("result").is_none());
+
+    let business = simulate(
+        &app,
+        form.id,
+        json!({ "plan": "business", "company": "Acme", "email": "ada@example.com" }),
+    )
+    .await;
+    assert_eq!(visible_steps(&business), ["Plan", "Company", "Contact"]);
+    assert_eq!(business["valid"], true);
+    assert_eq!(business["errors"], json!({}));
+    assert_eq!(business["result"]["key"], "business");
+    let company = &business["steps"][2]["fields"][0];
+    assert_eq!(company["name"], "company");
+    assert_eq!(company["visible"], true);
+    assert_eq!(business["steps"][1]["fields"][0]["visible"], false);
+}
+
+#[tokio::test]
+async fn test_simulation_stores_nothing() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), graded_quiz()).await;
+
+    simulate(&app, form.id, json!({ "q1": "4", "q2": "paris" })).await;
+
+    let submissions = SubmissionEntity::find_by_form(app.db(), form.id).await.unwrap();
+    assert!(submissions.is_empty());
+}
+
+// ============================================================================
+// Scores and results
+// ============================================================================
+
+#[tokio::test]
+async fn test_simulation_scores_quizzes() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), graded_quiz()).await;
+
+    let full = simulate(&app, form.id, json!({ "q1": "4", "q2": "paris" })).await;
+    assert_eq!(full["score"], 20);
+    assert_eq!(full["max_score"], 20);
+    assert_eq!(full["result"]["key"], "expert");
+    assert_eq!(full["result"]["description"], "Full marks");
+
+    let half = simulate(&app, form.id, json!({ "q1": "4", "q2": "rome" })).await;
+    assert_eq!(half["score"], 10);
+    assert_eq!(half["result"]["key"], "intermediate");
+}
+
+#[tokio::test]
+async fn test_simulating_unknown_form_is_not_found() {
+    let app = TestApp::with_admin().await;
+
+    let response = app
+        .post_json(&format!("/api/admin/forms/{}/simulate", Uuid::new_v4()), &json!({}))
+        .await;
+    response.assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,127 @@
+//! Tests for SMS notifications.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use anyform::schema::FormSettings;
+use anyform::{ConditionRule, RateLimit, SmsMessage, SmsNotification, SmsNotifier, SmsTransport};
+use async_trait::async_trait;
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::StatusCode;
+
+const URI: &str = "/api/v1/forms/test-contact";
+
+/// Records the texts it is asked to send, failing for the number `+1000`.
+#[derive(Clone, Default)]
+struct Outbox(Arc<Mutex<Vec<SmsMessage>>>);
+
+#[async_trait]
+impl SmsTransport for Outbox {
+    async fn send(&self, message: &SmsMessage) -> Result<(), String> {
+        if message.to == "+1000" {
+            return Err("unreachable".into());
+        }
+        self.0.lock().unwrap().push(message.clone());
+        Ok(())
+    }
+}
+
+impl Outbox {
+    fn bodies(&self) -> Vec<String> {
+        self.0.lock().unwrap().iter().map(|m| m.body.clone()).collect()
+    }
+}
+
+async fn sms_app(sms: SmsNotification) -> (TestApp, Outbox) {
+    let outbox = Outbox::default();
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .events(SmsNotifier::new(outbox.clone()))
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), contact_form().settings(FormSettings::new().sms(sms))).await;
+    (app, outbox)
+}
+
+async fn submit(app: &TestApp, name: &str, message: &str) {
+    let body = serde_json::json!({
+        "name": name,
+        "email": "ada@example.com",
+        "message": message
+    });
+    app.post_json(URI, &body).await.assert_status(StatusCode::CREATED);
+}
+
+// ============================================================================
+// Sending
+// ============================================================================
+
+#[tokio::test]
+async fn test_submissions_are_texted_to_each_recipient() {
+    let sms = SmsNotification::new("+15005550006", "New demo request from {{name}}")
+        .recipient("+15551230001")
+        .recipient("+1000")
+        .recipient("+15551230002");
+    let (app, outbox) = sms_app(sms).await;
+
+    submit(&app, "Ada", "Hello there").await;
+
+    let sent = outbox.0.lock().unwrap().clone();
+    let to: Vec<_> = sent.iter().map(|m| m.to.as_str()).collect();
+    assert_eq!(to, vec!["+15551230001", "+15551230002"], "failed sends are skipped");
+    assert!(sent.iter().all(|m| m.from == "+15005550006"));
+    assert_eq!(sent[0].body, "New demo request from Ada");
+}
+
+#[tokio::test]
+async fn test_only_matching_submissions_are_texted() {
+    let sms = SmsNotification::new("+15005550006", "Urgent: {{message}}")
+        .recipient("+15551230001")
+        .condition(ConditionRule::Simple {
+            field: "message".into(),
+            op: anyform::ConditionOp::Contains,
+            value: Some("urgent".into()),
+        });
+    let (app, outbox) = sms_app(sms).await;
+
+    submit(&app, "Ada", "Just saying hi").await;
+    submit(&app, "Bob", "This is urgent").await;
+
+    assert_eq!(outbox.bodies(), vec!["Urgent: This is urgent"]);
+}
+
+#[tokio::test]
+async fn test_texts_stop_at_the_rate_limit() {
+    let sms = SmsNotification::new("+15005550006", "From {{name}}")
+        .recipient("+15551230001")
+        .recipient("+15551230002")
+        .rate_limit(RateLimit::per_hour(2));
+    let (app, outbox) = sms_app(sms).await;
+
+    for name in ["Ada", "Bob", "Cy"] {
+        submit(&app, name, "Hello there").await;
+    }
+
+    // Each submission counts once, however many recipients it is sent to
+    assert_eq!(outbox.bodies(), vec!["From Ada", "From Ada", "From Bob", "From Bob"]);
+}
+
+// ============================================================================
+// Preflight
+// ============================================================================
+
+#[tokio::test]
+async fn test_preflight_warns_without_an_events_hook() {
+    let app = TestApp::new().await;
+    let sms = SmsNotification::new("+15005550006", "hi").recipient("+15551230001");
+    create_test_form(app.db(), contact_form().settings(FormSettings::new().sms(sms))).await;
+
+    let report = anyform::AnyFormRouter::builder()
+        .database(app.db().clone())
+        .preflight()
+        .await
+        .unwrap();
+    assert!(report.issues.iter().any(|i| i.code == "EVENTS_HOOK_MISSING"));
+}
@@ -0,0 +1,146 @@
+//! Tests for honeypot and time-trap spam protection.
+
+mod common;
+
+use anyform::schema::FormSettings;
+use anyform::spam::{HONEYPOT_FIELD, RENDERED_AT_FIELD};
+use anyform::{SpamProtection, SpamSecret, SubmissionEntity};
+use chrono::{Duration, Utc};
+use common::{contact_form, create_test_form, TestApp, TestDb};
+use http::StatusCode;
+
+const SECRET: &str = "spam-test-secret";
+const SSR_URI: &str = "/api/v1/forms/test-contact/submit";
+
+async fn protected_app() -> TestApp {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .spam_secret(SECRET)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    let settings = FormSettings::new().spam_protection(SpamProtection::new());
+    create_test_form(app.db(), contact_form().settings(settings)).await;
+    app
+}
+
+fn stamp(seconds_ago: i64) -> String {
+    SpamSecret::new(SECRET).stamp(Utc::now() - Duration::seconds(seconds_ago))
+}
+
+fn answers<'a>(extra: &[(&'a str, &'a str)]) -> Vec<(&'a str, &'a str)> {
+    let mut fields = vec![
+        ("name", "Ada"),
+        ("email", "ada@example.com"),
+        ("message", "Hello there"),
+    ];
+    fields.extend_from_slice(extra);
+    fields
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+#[tokio::test]
+async fn test_protected_forms_render_honeypot_and_stamp() {
+    let app = protected_app().await;
+
+    let response = app.get("/api/v1/forms/test-contact").await;
+    response.assert_status(StatusCode::OK);
+    let html = response.text();
+    assert!(html.contains(&format!("name=\"{HONEYPOT_FIELD}\"")));
+
+    let value = html
+        .split(&format!("name=\"{RENDERED_AT_FIELD}\" value=\""))
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("render stamp");
+    let rendered_at = SpamSecret::new(SECRET).verify(value).unwrap();
+    assert!((Utc::now() - rendered_at).num_seconds() < 5);
+}
+
+#[tokio::test]
+async fn test_unprotected_forms_render_no_traps() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), contact_form()).await;
+
+    let html = app.get("/api/v1/forms/test-contact").await.text();
+    assert!(!html.contains(HONEYPOT_FIELD));
+    assert!(!html.contains(RENDERED_AT_FIELD));
+}
+
+// ============================================================================
+// Server-rendered submissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_ssr_submissions_need_a_stamp_old_enough() {
+    let app = protected_app().await;
+    let (early, late) = (stamp(0), stamp(10));
+
+    for extra in [vec![], vec![(RENDERED_AT_FIELD, early.as_str())]] {
+        let response = app.post_form(SSR_URI, &answers(&extra)).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        response.assert_api_error("SPAM_REJECTED");
+    }
+
+    let extra = [(RENDERED_AT_FIELD, late.as_str()), (HONEYPOT_FIELD, "")];
+    app.post_form(SSR_URI, &answers(&extra))
+        .await
+        .assert_status(StatusCode::SEE_OTHER);
+}
+
+#[tokio::test]
+async fn test_filled_honeypot_is_rejected() {
+    let app = protected_app().await;
+    let late = stamp(10);
+
+    let extra = [
+        (RENDERED_AT_FIELD, late.as_str()),
+        (HONEYPOT_FIELD, "https://spam.example"),
+    ];
+    app.post_form(SSR_URI, &answers(&extra))
+        .await
+        .assert_api_error("SPAM_REJECTED");
+}
+
+// ============================================================================
+// JSON submissions
+// ============================================================================
+
+#[tokio::test]
+async fn test_json_submissions_are_checked_when_stamped() {
+    let app = protected_app().await;
+    let uri = "/api/v1/forms/test-contact";
+
+    let body = serde_json::json!({
+        "name": "Ada",
+        "email": "ada@example.com",
+        "message": "Hello there"
+    });
+    let response = app.post_json(uri, &body).await;
+    response.assert_status(StatusCode::CREATED);
+
+    let mut stamped = body.clone();
+    stamped[RENDERED_AT_FIELD] = stamp(0).into();
+    app.post_json(uri, &stamped)
+        .await
+        .assert_api_error("SPAM_REJECTED");
+
+    let mut stamped = body;
+    stamped[RENDERED_AT_FIELD] = stamp(10).into();
+    stamped[HONEYPOT_FIELD] = "".into();
+    app.post_json(uri, &stamped)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let form = anyform::FormBuilder::find_by_slug(app.db(), "test-contact")
+        .await
+        .unwrap()
+        .unwrap();
+    for sub in SubmissionEntity::find_by_form(app.db(), form.id).await.unwrap() {
+        assert!(sub.data.get(HONEYPOT_FIELD).is_none());
+        assert!(sub.data.get(RENDERED_AT_FIELD).is_none());
+    }
+}
@@ -0,0 +1,85 @@
+//! Tests for installation-wide statistics.
+
+#![cfg(feature = "admin")]
+
+mod common;
+
+use anyform::services::STATS_DAYS;
+use anyform::{CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, FormSettings};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use serde_json::{json, Value};
+
+fn form(name: &str, slug: &str) -> CreateFormInput {
+    CreateFormInput::new(name, slug)
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text")))
+}
+
+async fn submit(app: &TestApp, slug: &str, times: usize) {
+    for _ in 0..times {
+        app.post_json(&format!("/api/forms/{slug}"), &json!({ "name": "Ada" }))
+            .await
+            .assert_status(StatusCode::CREATED);
+    }
+}
+
+async fn stats(app: &TestApp) -> Value {
+    let response = app.get("/api/admin/stats").await;
+    response.assert_status(StatusCode::OK);
+    response.json::<Value>()["data"].clone()
+}
+
+#[tokio::test]
+async fn test_stats_total_the_installation() {
+    let app = TestApp::with_admin().await;
+    create_test_form(app.db(), form("Signup", "signup")).await;
+    create_test_form(app.db(), form("Feedback", "feedback")).await;
+    create_test_form(
+        app.db(),
+        form("Draft", "draft").settings(FormSettings::new().draft(true)),
+    )
+    .await;
+    let deleted = create_test_form(app.db(), form("Old", "old")).await;
+    submit(&app, "signup", 3).await;
+    submit(&app, "feedback", 1).await;
+    submit(&app, "old", 5).await;
+    FormBuilder::soft_delete(app.db(), deleted.id).await.unwrap();
+    // Started but not completed
+    app.post_json("/api/forms/feedback/submissions/start", &json!({}))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let stats = stats(&app).await;
+    assert_eq!(stats["forms"], 3);
+    assert_eq!(stats["published_forms"], 2);
+    assert_eq!(stats["submissions"], 5);
+    // Signup completes all of its submissions, feedback half
+    assert_eq!(stats["average_completion_rate"], 0.75);
+
+    let top: Vec<(&str, u64)> = stats["top_forms"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| (f["slug"].as_str().unwrap(), f["submissions"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(top, vec![("signup", 3), ("feedback", 2)]);
+    assert_eq!(stats["top_forms"][1]["completion_rate"], 0.5);
+
+    let days = stats["submissions_per_day"].as_array().unwrap();
+    assert_eq!(days.len() as u64, STATS_DAYS);
+    let today = chrono::Utc::now().date_naive().to_string();
+    assert_eq!(days.last().unwrap(), &json!({ "date": today, "submissions": 5 }));
+    assert!(days[..days.len() - 1].iter().all(|d| d["submissions"] == 0));
+}
+
+#[tokio::test]
+async fn test_stats_of_empty_installation() {
+    let app = TestApp::with_admin().await;
+
+    let stats = stats(&app).await;
+    assert_eq!(stats["forms"], 0);
+    assert_eq!(stats["submissions"], 0);
+    assert!(stats["average_completion_rate"].is_null());
+    assert!(stats["top_forms"].as_array().unwrap().is_empty());
+    assert_eq!(stats["submissions_per_day"].as_array().unwrap().len() as u64, STATS_DAYS);
+}
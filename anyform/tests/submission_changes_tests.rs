@@ -0,0 +1,173 @@
+//! Tests for submission change feeds.
+
+mod common;
+
+use anyform::services::{ChangeOp, SubmissionChanges};
+use anyform::{FormBuilder, LegalHold, SubmissionEntity};
+use chrono::{DateTime, Duration, FixedOffset, Utc};
+use common::{contact_form, create_test_form, TestApp};
+use http::StatusCode;
+use sea_orm::prelude::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+const URI: &str = "/api/v1/forms/test-contact";
+
+async fn submit(app: &TestApp, name: &str) -> Uuid {
+    let body = serde_json::json!({ "name": name, "email": "ada@example.com", "message": "Hi" });
+    let response = app.post_json(URI, &body).await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    json["data"]["submission_id"].as_str().unwrap().parse().unwrap()
+}
+
+/// A time after every change so far has settled.
+fn later() -> DateTime<FixedOffset> {
+    Utc::now().fixed_offset() + Duration::minutes(1)
+}
+
+// ============================================================================
+// Change feed
+// ============================================================================
+
+#[tokio::test]
+async fn test_changes_page_through_in_order() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let mut ids = Vec::new();
+    for name in ["Ada", "Bob", "Cy"] {
+        ids.push(submit(&app, name).await);
+    }
+
+    let first = SubmissionChanges::new(form.id).limit(2).fetch(app.db(), later()).await.unwrap();
+    assert!(first.has_more);
+    let cursor = first.next_cursor.unwrap();
+    let second = SubmissionChanges::new(form.id)
+        .after(cursor)
+        .limit(2)
+        .fetch(app.db(), later())
+        .await
+        .unwrap();
+    assert!(!second.has_more);
+
+    let seen: Vec<Uuid> =
+        first.changes.iter().chain(&second.changes).map(|c| c.submission.id).collect();
+    assert_eq!(seen, ids);
+    assert!(first.changes.iter().all(|c| c.op == ChangeOp::Insert));
+
+    // Caught up: nothing new, and the cursor stays put
+    let cursor = second.next_cursor.unwrap();
+    let third =
+        SubmissionChanges::new(form.id).after(cursor).fetch(app.db(), later()).await.unwrap();
+    assert!(third.changes.is_empty());
+    assert_eq!(third.next_cursor, Some(cursor));
+}
+
+#[tokio::test]
+async fn test_updates_and_deletes_since_the_cursor() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    let held = submit(&app, "Ada").await;
+    let deleted = submit(&app, "Bob").await;
+    let synced = SubmissionChanges::new(form.id).fetch(app.db(), later()).await.unwrap();
+    let cursor = synced.next_cursor.unwrap();
+
+    let sub = SubmissionEntity::find_by_id(held).one(app.db()).await.unwrap().unwrap();
+    LegalHold::set(app.db(), sub, true).await.unwrap();
+    app.delete(&format!("/api/admin/forms/{}/submissions/{deleted}", form.id))
+        .await
+        .assert_status(StatusCode::OK);
+    let added = submit(&app, "Cy").await;
+
+    let batch =
+        SubmissionChanges::new(form.id).after(cursor).fetch(app.db(), later()).await.unwrap();
+    let ops: Vec<(Uuid, ChangeOp)> =
+        batch.changes.iter().map(|c| (c.submission.id, c.op)).collect();
+    assert_eq!(
+        ops,
+        vec![(held, ChangeOp::Update), (deleted, ChangeOp::Delete), (added, ChangeOp::Insert)]
+    );
+}
+
+#[tokio::test]
+async fn test_deleting_a_form_reports_its_submissions_deleted() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    submit(&app, "Ada").await;
+    let synced = SubmissionChanges::new(form.id).fetch(app.db(), later()).await.unwrap();
+
+    FormBuilder::soft_delete(app.db(), form.id).await.unwrap();
+
+    let batch = SubmissionChanges::new(form.id)
+        .after(synced.next_cursor.unwrap())
+        .fetch(app.db(), later())
+        .await
+        .unwrap();
+    assert_eq!(batch.changes.len(), 1);
+    assert_eq!(batch.changes[0].op, ChangeOp::Delete);
+}
+
+#[tokio::test]
+async fn test_recent_changes_are_held_back() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    submit(&app, "Ada").await;
+
+    let now = Utc::now().fixed_offset();
+    let batch = SubmissionChanges::new(form.id).fetch(app.db(), now).await.unwrap();
+    assert!(batch.changes.is_empty());
+    assert_eq!(batch.next_cursor, None);
+}
+
+// ============================================================================
+// Route
+// ============================================================================
+
+#[tokio::test]
+async fn test_changes_route() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+    submit(&app, "Ada").await;
+    submit(&app, "Bob").await;
+
+    // Let the changes settle
+    let past = Utc::now().fixed_offset() - Duration::hours(1);
+    SubmissionEntity::update_many()
+        .col_expr(anyform::entities::submission::Column::UpdatedAt, Expr::value(past))
+        .filter(anyform::entities::submission::Column::FormId.eq(form.id))
+        .exec(app.db())
+        .await
+        .unwrap();
+
+    let uri = format!("/api/admin/forms/{}/submissions/changes", form.id);
+    let response = app.get(&format!("{uri}?limit=1")).await;
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    let data = &json["data"];
+    assert_eq!(data["has_more"], true);
+    assert_eq!(data["changes"].as_array().unwrap().len(), 1);
+    assert_eq!(data["changes"][0]["op"], "insert");
+    let fields: Vec<&str> = data["schema"][0]["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, vec!["name", "email", "message"]);
+    assert_eq!(data["schema"][0]["revision"], 1);
+
+    let first = data["changes"][0]["id"].clone();
+    let cursor = data["next_cursor"].as_str().unwrap();
+    let json: serde_json::Value = app.get(&format!("{uri}?cursor={cursor}")).await.json();
+    assert_eq!(json["data"]["has_more"], false);
+    assert_eq!(json["data"]["changes"].as_array().unwrap().len(), 1);
+    // Ties on updated_at are broken by ID
+    assert_ne!(json["data"]["changes"][0]["id"], first);
+
+    app.get(&format!("{uri}?cursor=nope"))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    app.get(&format!("/api/admin/forms/{}/submissions/changes", Uuid::new_v4()))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
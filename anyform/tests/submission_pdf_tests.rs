@@ -0,0 +1,221 @@
+//! Tests for submission PDFs.
+
+mod common;
+
+use std::collections::HashMap;
+
+use anyform::schema::{Branding, FormSettings};
+use anyform::services::SubmissionPdf;
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FieldClassification,
+    SubmissionEntity,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+use lopdf::content::Content;
+use sea_orm::EntityTrait;
+use uuid::Uuid;
+
+fn order_form(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Orders", "orders").settings(settings).step(
+        CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("name", "Name", "text"),
+            CreateFieldInput::new("plan", "Plan", "select").options(vec![
+                CreateOptionInput::new("Free plan", "free"),
+                CreateOptionInput::new("Pro plan", "pro"),
+            ]),
+            CreateFieldInput::new("notes", "Notes", "textarea"),
+            CreateFieldInput::new("ssn", "SSN", "text")
+                .classification(FieldClassification::Sensitive),
+        ]),
+    )
+}
+
+async fn order(app: &TestApp, body: serde_json::Value) -> Uuid {
+    let response = app.post_json("/api/forms/orders", &body).await;
+    response.assert_status(StatusCode::CREATED);
+    let data = &response.json::<serde_json::Value>()["data"];
+    data["submission_id"].as_str().unwrap().parse().unwrap()
+}
+
+/// A PDF's text, a line per text operation on each page, and the colors
+/// rectangles are filled with.
+struct Pdf {
+    pages: Vec<Vec<String>>,
+    fills: Vec<[f32; 3]>,
+}
+
+impl Pdf {
+    fn parse(bytes: &[u8]) -> Self {
+        assert!(bytes.starts_with(b"%PDF-"));
+        let doc = lopdf::Document::load_mem(bytes).unwrap();
+        let mut pages = Vec::new();
+        let mut fills = Vec::new();
+        for page in doc.page_iter() {
+            let resources = dict(&doc, doc.get_dictionary(page).unwrap().get(b"Resources").unwrap());
+            let glyphs: HashMap<&[u8], HashMap<u16, char>> = dict(&doc, resources.get(b"Font").unwrap())
+                .iter()
+                .map(|(name, font)| (name.as_slice(), to_unicode(&doc, dict(&doc, font))))
+                .collect();
+            let content = Content::decode(&doc.get_page_content(page).unwrap()).unwrap();
+            let (mut lines, mut font, mut color) = (Vec::new(), None, [0.0; 3]);
+            for op in content.operations {
+                match op.operator.as_str() {
+                    "Tf" => font = Some(op.operands[0].as_name().unwrap().to_vec()),
+                    "Tj" => {
+                        let glyphs = &glyphs[font.as_deref().unwrap()];
+                        let line = op.operands[0]
+                            .as_str()
+                            .unwrap()
+                            .chunks(2)
+                            .map(|gid| glyphs[&u16::from_be_bytes([gid[0], gid[1]])])
+                            .collect();
+                        lines.push(line);
+                    }
+                    "rg" => {
+                        for (c, operand) in color.iter_mut().zip(&op.operands) {
+                            *c = operand.as_float().unwrap();
+                        }
+                    }
+                    "re" => fills.push(color),
+                    _ => {}
+                }
+            }
+            pages.push(lines);
+        }
+        Self { pages, fills }
+    }
+
+    fn has_line(&self, line: &str) -> bool {
+        self.pages.iter().flatten().any(|l| l == line)
+    }
+
+    fn contains(&self, text: &str) -> bool {
+        self.pages.iter().flatten().any(|l| l.contains(text))
+    }
+}
+
+/// Returns the dictionary `object` is or refers to.
+fn dict<'a>(doc: &'a lopdf::Document, object: &'a lopdf::Object) -> &'a lopdf::Dictionary {
+    doc.dereference(object).unwrap().1.as_dict().unwrap()
+}
+
+/// Reads a font's map of glyphs to the characters they show.
+fn to_unicode(doc: &lopdf::Document, font: &lopdf::Dictionary) -> HashMap<u16, char> {
+    let id = font.get(b"ToUnicode").unwrap().as_reference().unwrap();
+    let stream = doc.get_object(id).unwrap().as_stream().unwrap();
+    let cmap = stream.decompressed_content().unwrap_or_else(|_| stream.content.clone());
+    String::from_utf8(cmap)
+        .unwrap()
+        .lines()
+        .filter_map(|line| {
+            let (gid, c) = line.strip_prefix('<')?.strip_suffix('>')?.split_once("> <")?;
+            let c = char::from_u32(u32::from_str_radix(c, 16).ok()?)?;
+            Some((u16::from_str_radix(gid, 16).ok()?, c))
+        })
+        .collect()
+}
+
+/// Fetches a submission's PDF.
+async fn pdf(app: &TestApp, form_id: Uuid, sub_id: Uuid, query: &str) -> Pdf {
+    let uri = format!("/api/admin/forms/{form_id}/submissions/{sub_id}/pdf{query}");
+    let response = app.get(&uri).await;
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("application/pdf");
+    Pdf::parse(&response.body)
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+#[tokio::test]
+async fn test_pdf_shows_labelled_answers() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), order_form(FormSettings::new())).await;
+    let id = order(
+        &app,
+        serde_json::json!({ "name": "Smith (and Sons)", "plan": "pro", "ssn": "123-45-6789" }),
+    )
+    .await;
+
+    let pdf = pdf(&app, form.id, id, "").await;
+    assert!(pdf.has_line("Orders"));
+    assert!(pdf.has_line(&format!("Submission {id}")));
+    assert!(pdf.has_line("Name"));
+    assert!(pdf.has_line("Smith (and Sons)"));
+    // Chosen options by their labels
+    assert!(pdf.has_line("Pro plan"));
+    // Unanswered questions are left out
+    assert!(!pdf.has_line("Notes"));
+    assert!(pdf.has_line("Page 1 of 1"));
+
+    // Sensitive answers only on request
+    assert!(!pdf.contains("123-45-6789"));
+    assert!(pdf.has_line("1 sensitive answer(s) left out."));
+    let pdf = self::pdf(&app, form.id, id, "?include_sensitive=true").await;
+    assert!(pdf.has_line("123-45-6789"));
+}
+
+#[tokio::test]
+async fn test_pdf_prints_unicode_answers() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), order_form(FormSettings::new())).await;
+    let id = order(&app, serde_json::json!({ "name": "Zoë Ωμέγα Привет 東京" })).await;
+
+    let pdf = pdf(&app, form.id, id, "").await;
+    // Characters without a glyph are marked rather than dropped
+    assert!(pdf.has_line("Zoë Ωμέγα Привет \u{fffd}\u{fffd}"), "{:?}", pdf.pages);
+}
+
+#[tokio::test]
+async fn test_pdf_uses_branding() {
+    let app = TestApp::with_admin().await;
+    let branding = Branding::new()
+        .primary_color("#0a7")
+        .footer_text("Acme Inc.")
+        .footer_link("Privacy", "https://acme.example/privacy");
+    let form = create_test_form(app.db(), order_form(FormSettings::new().branding(branding))).await;
+    let id = order(&app, serde_json::json!({ "name": "Ada" })).await;
+
+    let pdf = pdf(&app, form.id, id, "").await;
+    // Header band in the primary color
+    let [r, g, b] = pdf.fills[0];
+    assert!(r.abs() < 0.01 && (g - 0.67).abs() < 0.01 && (b - 0.47).abs() < 0.01, "{:?}", pdf.fills);
+    assert!(pdf.contains("Acme Inc."));
+    assert!(pdf.contains("Privacy: https://acme.example/privacy"));
+}
+
+#[tokio::test]
+async fn test_long_answers_span_pages() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), order_form(FormSettings::new())).await;
+    let notes = "All work and no play makes a dull order. ".repeat(400);
+    let id = order(&app, serde_json::json!({ "name": "Ada", "notes": notes })).await;
+
+    let sub = SubmissionEntity::find_by_id(id).one(app.db()).await.unwrap().unwrap();
+    let bytes = SubmissionPdf::render(app.db(), &form, &sub, false).await.unwrap();
+    let pdf = Pdf::parse(&bytes);
+    let pages = pdf.pages.len();
+    assert!(pages > 1, "{pages} pages");
+    assert!(pdf.pages[pages - 1].contains(&format!("Page {pages} of {pages}")));
+    // Every page repeats the header
+    assert!(pdf.pages.iter().all(|lines| lines[0] == "Orders"), "{:?}", pdf.pages);
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[tokio::test]
+async fn test_pdf_of_another_forms_submission_is_not_found() {
+    let app = TestApp::with_admin().await;
+    create_test_form(app.db(), order_form(FormSettings::new())).await;
+    let other = CreateFormInput::new("Other", "other")
+        .step(CreateStepInput::new("Main").field(CreateFieldInput::new("name", "Name", "text")));
+    let other = create_test_form(app.db(), other).await;
+    let id = order(&app, serde_json::json!({ "name": "Ada" })).await;
+
+    let uri = format!("/api/admin/forms/{}/submissions/{id}/pdf", other.id);
+    app.get(&uri).await.assert_status(StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,199 @@
+//! Tests for export schema resolution across form revisions.
+
+mod common;
+
+use anyform::{
+    entities::submission,
+    services::{CreateFieldInput, CreateFormInput, CreateStepInput, FormBuilder, SubmissionSchema},
+    FieldClassification, FormRevisionEntity,
+};
+use common::db::TestDb;
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, DatabaseConnection};
+use uuid::Uuid;
+
+fn original_form() -> CreateFormInput {
+    CreateFormInput::new("Signup", "signup").step(CreateStepInput::new("Main").fields(vec![
+        CreateFieldInput::new("name", "Name", "text"),
+        CreateFieldInput::new("email", "Email", "email"),
+    ]))
+}
+
+fn revised_form() -> CreateFormInput {
+    CreateFormInput::new("Signup", "signup").step(CreateStepInput::new("Main").fields(vec![
+        CreateFieldInput::new("email", "Work email", "email"),
+        CreateFieldInput::new("phone", "Phone", "tel"),
+    ]))
+}
+
+async fn insert_submission(
+    db: &DatabaseConnection,
+    form_id: Uuid,
+    revision_id: Option<Uuid>,
+    data: serde_json::Value,
+) -> submission::Model {
+    let now = chrono::Utc::now().fixed_offset();
+    submission::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        form_id: Set(form_id),
+        revision_id: Set(revision_id),
+        instance_id: Set(None),
+        respondent_id: Set(None),
+        data: Set(data),
+        metadata: Set(None),
+        current_step_id: Set(None),
+        completed_at: Set(Some(now)),
+        score: Set(None),
+        max_score: Set(None),
+        result_key: Set(None),
+        status: Set("submitted".into()),
+        created_at: Set(now),
+        updated_at: Set(now),
+        legal_hold: Set(false),
+        deleted_at: Set(None),
+    }
+    .insert(db)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_columns_are_superset_across_revisions() {
+    let db = TestDb::new().await;
+    let form = FormBuilder::create(db.conn(), original_form()).await.unwrap();
+    let rev1 = FormRevisionEntity::find_latest(db.conn(), form.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let old = insert_submission(
+        db.conn(),
+        form.id,
+        Some(rev1.id),
+        serde_json::json!({"name": "Ada", "email": "ada@example.com"}),
+    )
+    .await;
+
+    FormBuilder::update(db.conn(), form.id, revised_form())
+        .await
+        .unwrap();
+    let rev2 = FormRevisionEntity::find_latest(db.conn(), form.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let new = insert_submission(
+        db.conn(),
+        form.id,
+        Some(rev2.id),
+        serde_json::json!({"email": "grace@example.com", "phone": "555-0100"}),
+    )
+    .await;
+
+    let schema = SubmissionSchema::load(db.conn(), form.id, &[old, new])
+        .await
+        .unwrap();
+
+    let names: Vec<&str> = schema.columns().iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, vec!["name", "email", "phone"]);
+
+    // Removed fields keep their last known label; current labels win otherwise
+    let labels: Vec<&str> = schema.columns().iter().map(|c| c.label.as_str()).collect();
+    assert_eq!(labels, vec!["Name", "Work email", "Phone"]);
+}
+
+#[tokio::test]
+async fn test_label_for_resolves_per_revision() {
+    let db = TestDb::new().await;
+    let form = FormBuilder::create(db.conn(), original_form()).await.unwrap();
+    let rev1 = FormRevisionEntity::find_latest(db.conn(), form.id)
+        .await
+        .unwrap()
+        .unwrap();
+    FormBuilder::update(db.conn(), form.id, revised_form())
+        .await
+        .unwrap();
+    let rev2 = FormRevisionEntity::find_latest(db.conn(), form.id)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let schema = SubmissionSchema::load(db.conn(), form.id, &[])
+        .await
+        .unwrap();
+
+    assert_eq!(schema.label_for(Some(rev1.id), "email"), Some("Email"));
+    assert_eq!(schema.label_for(Some(rev2.id), "email"), Some("Work email"));
+    assert_eq!(schema.label_for(None, "email"), Some("Work email"));
+    assert_eq!(schema.label_for(Some(rev2.id), "name"), Some("Name"));
+    assert_eq!(schema.label_for(None, "unknown"), None);
+}
+
+#[tokio::test]
+async fn test_unknown_keys_are_appended() {
+    let db = TestDb::new().await;
+    let form = FormBuilder::create(db.conn(), original_form()).await.unwrap();
+    let legacy = insert_submission(
+        db.conn(),
+        form.id,
+        None,
+        serde_json::json!({"name": "Ada", "referrer": "newsletter"}),
+    )
+    .await;
+
+    let schema = SubmissionSchema::load(db.conn(), form.id, &[legacy])
+        .await
+        .unwrap();
+
+    let last = schema.columns().last().unwrap();
+    assert_eq!(last.name, "referrer");
+    assert_eq!(last.label, "referrer");
+    assert!(last.field_type.is_none());
+}
+
+#[tokio::test]
+async fn test_sensitive_fields_are_left_out_of_exports() {
+    let db = TestDb::new().await;
+    let sensitive = CreateFormInput::new("Signup", "signup").step(
+        CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("name", "Name", "text")
+                .classification(FieldClassification::Sensitive),
+            CreateFieldInput::new("email", "Email", "email"),
+        ]),
+    );
+    let form = FormBuilder::create(db.conn(), sensitive).await.unwrap();
+    let rev1 = FormRevisionEntity::find_latest(db.conn(), form.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let old = insert_submission(
+        db.conn(),
+        form.id,
+        Some(rev1.id),
+        serde_json::json!({"name": "Ada", "email": "ada@example.com"}),
+    )
+    .await;
+
+    // Answers collected as sensitive stay sensitive after reclassification
+    FormBuilder::update(db.conn(), form.id, original_form())
+        .await
+        .unwrap();
+
+    let schema = SubmissionSchema::load(db.conn(), form.id, std::slice::from_ref(&old))
+        .await
+        .unwrap();
+    assert_eq!(schema.sensitive_count(), 1);
+
+    let names = |include| -> Vec<String> {
+        schema
+            .export_columns(include)
+            .iter()
+            .map(|c| c.name.clone())
+            .collect()
+    };
+    assert_eq!(names(false), ["email"]);
+    assert_eq!(names(true), ["name", "email"]);
+
+    assert_eq!(
+        schema.export_data(&old.data, false),
+        serde_json::json!({"email": "ada@example.com"})
+    );
+    assert_eq!(schema.export_data(&old.data, true), old.data);
+}
@@ -0,0 +1,123 @@
+//! Tests for the Tera context and template functions.
+//!
+//! The example templates in `tests/fixtures/tera` are rendered with the
+//! seeded forms and compared with the `.html` next to them. Run with
+//! `UPDATE_FIXTURES=1` to regenerate them after a change.
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyform::error::ValidationErrors;
+use anyform::i18n::{MessageCatalog, Messages};
+use anyform::schema::FieldValue;
+use anyform::{FormBuilder, TeraRenderer};
+use common::TestDb;
+use tera::{Context, Tera};
+
+fn fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tera")
+}
+
+fn tera() -> Tera {
+    let mut tera = Tera::new(fixture_dir().join("*.tera").to_str().unwrap()).unwrap();
+    TeraRenderer::register_functions(
+        &mut tera,
+        MessageCatalog::new().locale(
+            "fr",
+            Messages::new()
+                .set("required", "{label} est obligatoire")
+                .set("min_length", "{label} : au moins {min} caractères")
+                .set("finish", "Terminer"),
+        ),
+    );
+    tera
+}
+
+async fn onboarding_context(values: &[(&str, &str)], locale: Option<&str>) -> Context {
+    let db = TestDb::new().await;
+    anyform::seed_all(db.conn()).await.unwrap();
+    let form = FormBuilder::find_by_slug(db.conn(), "onboarding")
+        .await
+        .unwrap()
+        .unwrap();
+    let values: HashMap<String, FieldValue> = values
+        .iter()
+        .map(|(k, v)| (k.to_string(), FieldValue::from(*v)))
+        .collect();
+    TeraRenderer::context_with_locale(db.conn(), &form, &values, &ValidationErrors::new(), locale)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_example_templates_match_fixtures() {
+    let tera = tera();
+    let context = onboarding_context(&[("role", "other")], Some("fr")).await;
+    let update = std::env::var_os("UPDATE_FIXTURES").is_some();
+
+    let html = tera.render("onboarding.html.tera", &context).unwrap();
+    let path = fixture_dir().join("onboarding.html");
+    if update {
+        std::fs::write(&path, &html).unwrap();
+        return;
+    }
+    let fixture = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        fixture == html,
+        "{} is out of date; rerun with UPDATE_FIXTURES=1",
+        path.display()
+    );
+}
+
+#[tokio::test]
+async fn test_is_visible_follows_values() {
+    let mut tera = tera();
+    tera.add_raw_template(
+        "visible",
+        r#"{{ is_visible(field=field(form=form, name="role_details"), values=values) }} {% for s in form.steps %}{{ is_visible(step=s, values=values) }} {% endfor %}"#,
+    )
+    .unwrap();
+
+    let context = onboarding_context(&[("role", "developer")], None).await;
+    assert_eq!(tera.render("visible", &context).unwrap(), "false true true true ");
+    let context = onboarding_context(&[("role", "other")], None).await;
+    assert_eq!(tera.render("visible", &context).unwrap(), "true true false true ");
+}
+
+#[tokio::test]
+async fn test_t_falls_back_to_english_then_key() {
+    let mut tera = tera();
+    tera.add_raw_template(
+        "t",
+        r#"{{ t(key="required", label="Email") }}|{{ t(key="finish", locale="fr-CA") }}|{{ t(key="finish") }}"#,
+    )
+    .unwrap();
+
+    let context = onboarding_context(&[], None).await;
+    assert_eq!(
+        tera.render("t", &context).unwrap(),
+        "Email is required|Terminer|finish"
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_field_is_a_template_error() {
+    let mut tera = tera();
+    tera.add_raw_template("missing", r#"{{ options_of(form=form, name="country") }}"#)
+        .unwrap();
+
+    let context = onboarding_context(&[], None).await;
+    let error = tera.render("missing", &context).unwrap_err();
+    let mut messages = vec![error.to_string()];
+    let mut source = std::error::Error::source(&error);
+    while let Some(error) = source {
+        messages.push(error.to_string());
+        source = error.source();
+    }
+    assert!(
+        messages.iter().any(|m| m.contains("no field named `country`")),
+        "{messages:?}"
+    );
+}
@@ -454,3 +454,58 @@ async fn test_multiple_validation_errors() {
     let errors = validate_submission(&fields, &data);
     assert_eq!(errors.len(), 3); // name required, email invalid, age too low
 }
+
+// ============================================================================
+// Custom Messages
+// ============================================================================
+
+#[tokio::test]
+async fn test_custom_messages_fill_in_placeholders() {
+    let db = setup().await;
+
+    let form = CreateFormInput::new("Test", "test-custom-messages")
+        .step(
+            CreateStepInput::new("Main").fields(vec![
+                CreateFieldInput::new("name", "Name", "text")
+                    .required()
+                    .validation(
+                        ValidationRules::new()
+                            .min_length(3)
+                            .message("required", "Tell us your {label}, please")
+                            .message("min_length", "{label} needs {min} letters or more"),
+                    ),
+                CreateFieldInput::new("age", "Age", "number").validation(
+                    ValidationRules::new()
+                        .min(18.0)
+                        .max(120.0)
+                        .message("max", "{label} over {max}? {unknown}"),
+                ),
+                CreateFieldInput::new("code", "Code", "text").validation(
+                    ValidationRules::new()
+                        .pattern("^[0-9]+$")
+                        .pattern_message("{label} takes digits only"),
+                ),
+            ]),
+        );
+
+    let form = FormBuilder::create(db.conn(), form).await.unwrap();
+    let steps = anyform::entities::step::Entity::find_by_form(db.conn(), form.id)
+        .await
+        .unwrap();
+    let fields = FieldEntity::find_by_step(db.conn(), steps[0].id)
+        .await
+        .unwrap();
+
+    let errors = validate_submission(&fields, &make_data(vec![("code", "x")]));
+    assert_eq!(errors.get("name").unwrap(), &["Tell us your Name, please"]);
+    assert_eq!(errors.get("code").unwrap(), &["Code: Code takes digits only"]);
+
+    let data = make_data(vec![("name", "Al"), ("age", "130")]);
+    let errors = validate_submission(&fields, &data);
+    assert_eq!(errors.get("name").unwrap(), &["Name needs 3 letters or more"]);
+    assert_eq!(errors.get("age").unwrap(), &["Age over 120? {unknown}"]);
+
+    // Rules without a template of their own keep the built-in message
+    let errors = validate_submission(&fields, &make_data(vec![("name", "Ann"), ("age", "9")]));
+    assert_eq!(errors.get("age").unwrap(), &["Age must be at least 18"]);
+}
@@ -0,0 +1,146 @@
+//! Integration tests for API versioning and deprecation headers.
+
+mod common;
+
+use axum::body::Body;
+use common::{contact_form, create_test_form, sample_submission_data, TestApp, TestDb};
+use http::{Request, StatusCode};
+
+// ============================================================================
+// Versioned Routes
+// ============================================================================
+
+#[tokio::test]
+async fn test_v1_form_json_available() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let response = app.get(&format!("/api/v1/forms/{}/json", form.slug)).await;
+
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.headers["anyform-version"], "1");
+    assert!(!response.headers.contains_key("deprecation"));
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["slug"], form.slug);
+}
+
+#[tokio::test]
+async fn test_v1_submit_available() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let response = app
+        .post_json(&format!("/api/v1/forms/{}", form.slug), &sample_submission_data())
+        .await;
+
+    response.assert_status(StatusCode::CREATED);
+    response.assert_api_success();
+}
+
+#[tokio::test]
+async fn test_v1_admin_routes_available() {
+    let app = TestApp::with_admin().await;
+    create_test_form(app.db(), contact_form()).await;
+
+    let response = app.get("/api/v1/admin/forms").await;
+
+    response.assert_status(StatusCode::OK);
+    response.assert_api_success();
+}
+
+#[tokio::test]
+async fn test_versioned_and_unversioned_json_are_identical() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let v1 = app.get(&format!("/api/v1/forms/{}/json", form.slug)).await;
+    let legacy = app.get(&format!("/api/forms/{}/json", form.slug)).await;
+
+    assert_eq!(v1.body, legacy.body);
+}
+
+// ============================================================================
+// Unversioned Routes
+// ============================================================================
+
+#[tokio::test]
+async fn test_unversioned_routes_carry_deprecation_headers() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let response = app.get(&format!("/api/forms/{}/json", form.slug)).await;
+
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.headers["deprecation"], "true");
+    assert_eq!(response.headers["anyform-version"], "1");
+    assert_eq!(
+        response.headers["link"],
+        format!("</api/v1/forms/{}/json>; rel=\"successor-version\"", form.slug)
+    );
+    assert!(!response.headers.contains_key("sunset"));
+}
+
+#[tokio::test]
+async fn test_unversioned_routes_negotiate_accept_header() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let request = Request::builder()
+        .uri(format!("/api/forms/{}/json", form.slug))
+        .header("Accept", "application/vnd.anyform.v1+json")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send_raw(request).await;
+
+    response.assert_status(StatusCode::OK);
+    assert_eq!(response.headers["anyform-version"], "1");
+}
+
+#[tokio::test]
+async fn test_unsupported_version_rejected() {
+    let app = TestApp::new().await;
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let request = Request::builder()
+        .uri(format!("/api/forms/{}/json", form.slug))
+        .header("AnyForm-Version", "99")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.send_raw(request).await;
+
+    response.assert_status(StatusCode::NOT_ACCEPTABLE);
+    response.assert_api_error("UNSUPPORTED_API_VERSION");
+}
+
+#[tokio::test]
+async fn test_sunset_header_when_configured() {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .unversioned_sunset("Wed, 01 Jul 2026 00:00:00 GMT")
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    let response = app.get(&format!("/api/forms/{}/json", form.slug)).await;
+
+    assert_eq!(response.headers["sunset"], "Wed, 01 Jul 2026 00:00:00 GMT");
+}
+
+#[tokio::test]
+async fn test_unversioned_routes_can_be_disabled() {
+    let test_db = TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .unversioned_routes(false)
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    let form = create_test_form(app.db(), contact_form()).await;
+
+    app.get(&format!("/api/forms/{}/json", form.slug))
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    app.get(&format!("/api/v1/forms/{}/json", form.slug))
+        .await
+        .assert_status(StatusCode::OK);
+}
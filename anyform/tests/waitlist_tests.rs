@@ -0,0 +1,168 @@
+//! Tests for submission limits and the waitlist.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, Form, FormEvents,
+    FormSettings, Submission,
+};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+
+fn signup_form(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Signup", "signup")
+        .settings(settings)
+        .step(CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("name", "Name", "text").required(),
+            CreateFieldInput::new("session", "Session", "select")
+                .required()
+                .options(vec![
+                    CreateOptionInput::new("Morning", "am").capacity(1),
+                    CreateOptionInput::new("Afternoon", "pm"),
+                ]),
+        ]))
+}
+
+async fn sign_up(app: &TestApp, session: &str) -> common::app::TestResponse {
+    app.post_json(
+        "/api/forms/signup",
+        &serde_json::json!({ "name": "Ada", "session": session }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_max_submissions_rejects_without_waitlist() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), signup_form(FormSettings::new().max_submissions(1))).await;
+
+    sign_up(&app, "pm").await.assert_status(StatusCode::CREATED);
+
+    let response = sign_up(&app, "pm").await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("SUBMISSION_LIMIT_REACHED");
+}
+
+#[tokio::test]
+async fn test_full_form_accepts_onto_waitlist() {
+    let app = TestApp::new().await;
+    let settings = FormSettings::new()
+        .max_submissions(1)
+        .waitlist(true)
+        .waitlist_message("You're on the list.");
+    create_test_form(app.db(), signup_form(settings)).await;
+
+    let json: serde_json::Value = sign_up(&app, "pm").await.json();
+    assert_eq!(json["data"]["status"], "submitted");
+
+    let response = sign_up(&app, "pm").await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["status"], "waitlisted");
+    assert_eq!(json["data"]["message"], "You're on the list.");
+}
+
+#[tokio::test]
+async fn test_full_option_accepts_onto_waitlist_without_taking_seat() {
+    let app = TestApp::new().await;
+    create_test_form(app.db(), signup_form(FormSettings::new().waitlist(true))).await;
+
+    sign_up(&app, "am").await.assert_status(StatusCode::CREATED);
+
+    let json: serde_json::Value = sign_up(&app, "am").await.json();
+    assert_eq!(json["data"]["status"], "waitlisted");
+
+    let json: serde_json::Value = app.get("/api/forms/signup/json").await.json();
+    assert_eq!(json["steps"][0]["fields"][1]["options"][0]["remaining"], 0);
+}
+
+#[derive(Clone, Default)]
+struct RecordingEvents(Arc<Mutex<Vec<String>>>);
+
+#[async_trait::async_trait]
+impl FormEvents for RecordingEvents {
+    async fn on_submission_created(&self, _form: &Form, _submission: &Submission) {
+        self.0.lock().unwrap().push("created".into());
+    }
+
+    async fn on_submission_waitlisted(&self, _form: &Form, _submission: &Submission) {
+        self.0.lock().unwrap().push("waitlisted".into());
+    }
+
+    async fn on_submission_promoted(&self, _form: &Form, _submission: &Submission) {
+        self.0.lock().unwrap().push("promoted".into());
+    }
+}
+
+#[tokio::test]
+async fn test_events_fire_for_created_and_waitlisted() {
+    let events = RecordingEvents::default();
+    let test_db = common::TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .events(events.clone())
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    create_test_form(app.db(), signup_form(FormSettings::new().waitlist(true))).await;
+
+    sign_up(&app, "am").await.assert_status(StatusCode::CREATED);
+    sign_up(&app, "am").await.assert_status(StatusCode::CREATED);
+
+    assert_eq!(*events.0.lock().unwrap(), vec!["created", "waitlisted"]);
+}
+
+#[cfg(feature = "admin")]
+#[tokio::test]
+async fn test_promote_waitlisted_submission() {
+    let events = RecordingEvents::default();
+    let test_db = common::TestDb::new().await;
+    let router = anyform::AnyFormRouter::builder()
+        .database(test_db.db.clone())
+        .enable_admin(true)
+        .events(events.clone())
+        .build();
+    let app = TestApp::from_router(test_db, router);
+    let form = create_test_form(app.db(), signup_form(FormSettings::new().waitlist(true))).await;
+
+    let json: serde_json::Value = sign_up(&app, "am").await.json();
+    let first = json["data"]["submission_id"].as_str().unwrap().to_string();
+    let json: serde_json::Value = sign_up(&app, "am").await.json();
+    let waitlisted = json["data"]["submission_id"].as_str().unwrap().to_string();
+
+    let json: serde_json::Value = app
+        .get(&format!("/api/admin/forms/{}/submissions?status=waitlisted", form.id))
+        .await
+        .json();
+    assert_eq!(json["data"]["count"], 1);
+    assert_eq!(json["data"]["submissions"][0]["id"], waitlisted.as_str());
+
+    // Still full
+    let promote = format!(
+        "/api/admin/forms/{}/submissions/{}/promote",
+        form.id, waitlisted
+    );
+    let response = app.post_json(&promote, &serde_json::json!({})).await;
+    response.assert_status(StatusCode::CONFLICT);
+    response.assert_api_error("OPTION_FULL");
+
+    app.delete(&format!("/api/admin/forms/{}/submissions/{}", form.id, first))
+        .await
+        .assert_status(StatusCode::OK);
+
+    let response = app.post_json(&promote, &serde_json::json!({})).await;
+    response.assert_status(StatusCode::OK);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["data"]["status"], "submitted");
+
+    // Already promoted
+    app.post_json(&promote, &serde_json::json!({}))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    assert_eq!(
+        *events.0.lock().unwrap(),
+        vec!["created", "waitlisted", "promoted"]
+    );
+}
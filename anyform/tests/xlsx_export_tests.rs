@@ -0,0 +1,169 @@
+//! Tests for Excel exports.
+
+mod common;
+
+use std::io::Cursor;
+
+use anyform::schema::{ExportColumnMapping, ExportMapping, SubmissionAttribute};
+use anyform::services::XlsxExport;
+use anyform::{
+    CreateFieldInput, CreateFormInput, CreateOptionInput, CreateStepInput, FieldClassification,
+    FormSettings,
+};
+use calamine::{open_workbook_from_rs, Data, Reader, Xlsx};
+use common::{create_test_form, TestApp};
+use http::StatusCode;
+
+fn order_form(settings: FormSettings) -> CreateFormInput {
+    CreateFormInput::new("Orders", "orders").settings(settings).step(
+        CreateStepInput::new("Main").fields(vec![
+            CreateFieldInput::new("name", "Name", "text"),
+            CreateFieldInput::new("plan", "Plan", "select").options(vec![
+                CreateOptionInput::new("Free plan", "free"),
+                CreateOptionInput::new("Pro plan", "pro"),
+            ]),
+            CreateFieldInput::new("seats", "Seats", "number"),
+            CreateFieldInput::new("start", "Start date", "date"),
+            CreateFieldInput::new("terms", "Terms", "checkbox"),
+            CreateFieldInput::new("ssn", "SSN", "text")
+                .classification(FieldClassification::Sensitive),
+        ]),
+    )
+}
+
+async fn order(app: &TestApp, name: &str, plan: &str) {
+    let body = serde_json::json!({
+        "name": name,
+        "plan": plan,
+        "seats": 5,
+        "start": "2025-03-01",
+        "terms": true,
+        "ssn": "123-45-6789"
+    });
+    app.post_json("/api/forms/orders", &body).await.assert_status(StatusCode::CREATED);
+}
+
+/// Reads a workbook's sheets, in order, as rows of cells.
+fn read(bytes: &[u8]) -> Vec<(String, Vec<Vec<Data>>)> {
+    let mut book: Xlsx<_> = open_workbook_from_rs(Cursor::new(bytes.to_vec())).unwrap();
+    book.worksheets()
+        .into_iter()
+        .map(|(name, range)| (name, range.rows().map(<[Data]>::to_vec).collect()))
+        .collect()
+}
+
+/// Returns the rows of the sheet `name`.
+fn sheet<'a>(book: &'a [(String, Vec<Vec<Data>>)], name: &str) -> &'a [Vec<Data>] {
+    &book.iter().find(|(n, _)| n == name).unwrap().1
+}
+
+fn text(s: &str) -> Data {
+    Data::String(s.to_string())
+}
+
+async fn export(app: &TestApp, form_id: uuid::Uuid, query: &str) -> Vec<(String, Vec<Vec<Data>>)> {
+    let uri = format!("/api/admin/forms/{form_id}/submissions/export?format=xlsx{query}");
+    let response = app.get(&uri).await;
+    response.assert_status(StatusCode::OK);
+    response.assert_content_type("application/vnd.openxmlformats-officedocument.spreadsheetml");
+    assert!(response.headers[http::header::CONTENT_DISPOSITION]
+        .to_str()
+        .unwrap()
+        .ends_with("orders-submissions.xlsx\""));
+    read(&response.body)
+}
+
+// ============================================================================
+// Workbook
+// ============================================================================
+
+#[tokio::test]
+async fn test_workbook_sheets() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), order_form(FormSettings::new())).await;
+    order(&app, "Ada", "pro").await;
+
+    let book = export(&app, form.id, "").await;
+    let names: Vec<&str> = book.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["Submissions", "Form"]);
+}
+
+#[tokio::test]
+async fn test_typed_cells() {
+    let app = TestApp::with_admin().await;
+    let mapping = ExportMapping::new()
+        .column(ExportColumnMapping::field("name"))
+        .column(ExportColumnMapping::field("plan"))
+        .column(ExportColumnMapping::field("seats"))
+        .column(ExportColumnMapping::field("start"))
+        .column(ExportColumnMapping::field("terms"))
+        .column(ExportColumnMapping::attribute(SubmissionAttribute::CreatedAt).header("When"))
+        .column(ExportColumnMapping::field("ssn"))
+        .option_labels(true);
+    let form = create_test_form(app.db(), order_form(FormSettings::new().export(mapping))).await;
+    order(&app, "Smith & Sons", "pro").await;
+
+    let book = export(&app, form.id, "").await;
+    let rows = sheet(&book, "Submissions");
+    // Header row of labels
+    assert_eq!(
+        rows[0],
+        ["Name", "Plan", "Seats", "Start date", "Terms", "When"].map(text)
+    );
+
+    let row = &rows[1];
+    assert_eq!(row[0], text("Smith & Sons"));
+    assert_eq!(row[1], text("Pro plan"));
+    assert_eq!(row[2], Data::Float(5.0));
+    let Data::DateTime(start) = &row[3] else {
+        panic!("start is not a date: {:?}", row[3]);
+    };
+    assert_eq!(start.as_datetime().unwrap().to_string(), "2025-03-01 00:00:00");
+    assert_eq!(row[4], Data::Bool(true));
+    assert!(matches!(row[5], Data::DateTime(_)), "{:?}", row[5]);
+
+    let book = export(&app, form.id, "&include_sensitive=true").await;
+    assert_eq!(sheet(&book, "Submissions")[1][6], text("123-45-6789"));
+}
+
+#[tokio::test]
+async fn test_form_sheet_counts_options() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), order_form(FormSettings::new())).await;
+    order(&app, "Ada", "pro").await;
+    order(&app, "Grace", "pro").await;
+    order(&app, "Alan", "legacy").await;
+
+    let book = export(&app, form.id, "").await;
+    let rows = sheet(&book, "Form");
+    assert_eq!(rows[0][..2], [text("Form"), text("Orders")]);
+    assert_eq!(rows[4][..2], [text("Submissions"), Data::Float(3.0)]);
+
+    for (option, n) in [("Free plan", 0.0), ("Pro plan", 2.0), ("legacy", 1.0)] {
+        let row = rows
+            .iter()
+            .find(|r| r[1] == text(option))
+            .unwrap_or_else(|| panic!("no row for {option}"));
+        assert_eq!(row[3], Data::Float(n), "{row:?}");
+    }
+}
+
+// ============================================================================
+// Library
+// ============================================================================
+
+#[tokio::test]
+async fn test_write_form_to_writer() {
+    let app = TestApp::with_admin().await;
+    let form = create_test_form(app.db(), order_form(FormSettings::new())).await;
+    for i in 0..3 {
+        order(&app, &format!("Customer {i}"), "free").await;
+    }
+
+    let bytes = XlsxExport::write_form(app.db(), &form, false, Vec::new()).await.unwrap();
+    let book = read(&bytes);
+    let rows = sheet(&book, "Submissions");
+    assert_eq!(rows.len(), 4);
+    let cells: Vec<&Data> = rows.iter().flatten().collect();
+    assert!(cells.contains(&&text("Customer 0")) && cells.contains(&&text("Customer 2")));
+}